@@ -1,10 +1,84 @@
 use clap::{Args, Parser, Subcommand};
+use x402_domain::{DurationField, SolanaAddress, SolanaNetwork};
 
 /// Parse log level from string for CLI argument
 fn parse_log_level(s: &str) -> Result<crate::config::LogLevel, String> {
     s.parse()
 }
 
+/// Parse a pricing amount, rejecting locale formats like "0,01" with the
+/// corrected amount as a suggestion
+pub(crate) fn parse_amount(s: &str) -> Result<f64, String> {
+    x402_domain::Amount::parse_user_input(s)
+        .map(|amount| amount.to_f64())
+        .map_err(|e| e.to_string())
+}
+
+/// Parse a TCP port, refusing privileged ports with an unprivileged
+/// suggestion (80 → 8080)
+pub(crate) fn parse_port(s: &str) -> Result<u16, String> {
+    s.parse::<x402_domain::Port>()
+        .map(|port| port.get())
+        .map_err(|e| e.to_string())
+}
+
+/// Parse a duration given as seconds or with a unit ("500ms", "30s", "1h")
+pub(crate) fn parse_duration(s: &str) -> Result<std::time::Duration, String> {
+    s.parse::<DurationField>()
+        .map(|duration| duration.as_duration())
+        .map_err(|e| e.to_string())
+}
+
+/// Parse an audit sample rate between 0.0 and 1.0
+pub(crate) fn parse_sample_rate(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+        _ => Err(format!(
+            "'{}' is not a rate from 0.0 (log only denials) to 1.0 (log every decision)",
+            s
+        )),
+    }
+}
+
+/// Parse a positive `--budget` amount
+fn parse_budget(s: &str) -> Result<x402_domain::Decimal, String> {
+    let amount = x402_domain::Amount::parse_user_input(s).map_err(|e| e.to_string())?;
+    if amount.is_zero() {
+        return Err("budget must be positive".to_string());
+    }
+    Ok(amount.as_decimal())
+}
+
+/// Parse a `NAME=VALUE` suite variable
+fn parse_variable(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.is_empty() => Ok((name.to_string(), value.to_string())),
+        _ => Err(format!("expected NAME=VALUE, got '{}'", s)),
+    }
+}
+
+/// Report format of the test and check commands
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    /// Human output plus GitHub Actions annotations
+    Github,
+}
+
+impl OutputFormat {
+    /// `explicit` if given, else `github` when running in GitHub Actions
+    pub fn resolve(explicit: Option<Self>) -> Self {
+        explicit.unwrap_or_else(|| {
+            if std::env::var("GITHUB_ACTIONS").is_ok_and(|v| v == "true") {
+                OutputFormat::Github
+            } else {
+                OutputFormat::Text
+            }
+        })
+    }
+}
+
 #[derive(Parser)]
 #[command(name = "x402-dev", about = "x402 Protocol Standard Toolkit", version)]
 pub struct Cli {
@@ -18,6 +92,23 @@ pub struct Cli {
     /// Enable debug output with stack traces
     #[arg(global = true, short, long)]
     pub debug: bool,
+
+    /// Language for error messages: en, es, ja (default: $X402_DEV_LANG, then en)
+    #[arg(global = true, long, value_name = "LANG")]
+    pub lang: Option<String>,
+
+    /// Only print results and errors, not progress lines
+    #[arg(global = true, short, long)]
+    pub quiet: bool,
+
+    /// Never color output (also $NO_COLOR; colors are off when stdout is not
+    /// a terminal)
+    #[arg(global = true, long)]
+    pub no_color: bool,
+
+    /// Print ASCII markers ([ok], [x], [!]) instead of emoji and symbols
+    #[arg(global = true, long)]
+    pub ascii: bool,
 }
 
 #[derive(Subcommand)]
@@ -28,7 +119,13 @@ pub enum Commands {
     /// Run automated test suites (Epic 3)
     Test(TestArgs),
 
-    /// Verify x402 protocol compliance (Epic 3)
+    /// Project the spend of a test suite or traffic profile before running it
+    Estimate(EstimateArgs),
+
+    /// Run config, policy and test suite bundles (x402-scenario.yaml)
+    Scenario(ScenarioArgs),
+
+    /// Verify a payment proof with the simulated or RPC verifier
     Verify(VerifyArgs),
 
     /// Check configuration and system health (Epic 4)
@@ -43,6 +140,9 @@ pub enum Commands {
     /// Show example implementations and usage (Epic 6)
     Examples(ExamplesArgs),
 
+    /// Generate client SDK code from config and pricing
+    Generate(GenerateArgs),
+
     /// Diagnose issues and validate setup (Epic 4)
     Doctor(DoctorArgs),
 
@@ -54,6 +154,24 @@ pub enum Commands {
 
     /// Manage configuration settings (Story 1.4)
     Config(ConfigArgs),
+
+    /// Store secrets referenced as ${secret:name} in config and test suites
+    Secret(SecretArgs),
+
+    /// Inspect payment receipts (X-Payment-Receipt)
+    Receipt(ReceiptArgs),
+
+    /// Request a path and pay its invoice: the whole 402 flow in one command
+    Curl(CurlArgs),
+
+    /// Explain an error code: causes, fix steps and related config
+    Explain(ExplainArgs),
+
+    /// Translate a config, policy, suite or scenario file between YAML and JSON
+    Convert(ConvertArgs),
+
+    /// Check the content hash of a report written with --report
+    VerifyReport(VerifyReportArgs),
 }
 
 // Placeholder argument structs for each command
@@ -64,24 +182,137 @@ pub enum Commands {
 EXAMPLES:
   x402-dev mock --port 3402          Start server
   x402-dev mock --pricing 0.02       Start with custom default pricing
+  x402-dev mock --tls-self-signed    Serve https:// with a generated certificate
+  x402-dev mock --http2              Also accept cleartext HTTP/2 (h2c)
+  x402-dev mock --bind 0.0.0.0       Reachable from the LAN (admin stays local)
+  x402-dev mock --sign key.json      Sign invoices (Solana keypair file)
+  x402-dev mock --sign               Sign invoices with an ephemeral key
+  x402-dev mock --policy policy.yaml Deny requests that fail policy checks (403)
+  x402-dev mock --policy policy.yaml --shared-state .x402dev/shared.db --port 3403
+                                     Share policy counters with other instances
+  x402-dev mock --match-stats        Count hits per pricing rule and policy
+  x402-dev mock --allow-time-travel  Let tests move the clock (POST /__admin/clock/advance)
+  x402-dev mock --policy policy.yaml --audit-log audit.jsonl --audit-sample-rate 0.1
+                                     Log every denial and 10% of allows
+  x402-dev mock --port-fallback auto --env-file mock.env
+                                     Use the next free port if 3402 is taken
   x402-dev mock stop                 Stop server
   x402-dev mock status               Check status
   x402-dev mock restart              Restart server
+  x402-dev mock stats                Per-path request statistics
+  x402-dev mock list --shared-state .x402dev/shared.db
+                                     Instances sharing a policy state file
+  x402-dev mock dash                 Live dashboard of the running server
+  x402-dev mock audit query --agent agent-1 --decision deny --since -1h
+                                     Denials of one agent in the last hour
+  x402-dev mock snapshot export bug-123.tar.gz
+                                     Save the server state for a bug report
+  x402-dev mock snapshot import bug-123.tar.gz
+                                     Load it into a fresh server
 
 SEE ALSO:
   x402-dev test      Run test suites against mock server
-  x402-dev verify    Verify protocol compliance
+  x402-dev verify    Verify a payment proof
   x402-dev doctor    Diagnose setup issues
 ")]
 pub struct MockArgs {
-    /// Port for the mock server (default: 3402)
-    #[arg(long, short, default_value = "3402")]
+    /// Port for the mock server, 1024-65535 (default: 3402)
+    #[arg(long, short, default_value = "3402", value_parser = parse_port)]
     pub port: u16,
 
-    /// Override default pricing amount in SOL/USDC (overrides config file)
-    #[arg(long, value_name = "AMOUNT")]
+    /// Interface to listen on: 127.0.0.1, a LAN address, 0.0.0.0 or ::
+    /// (overrides bind_address; default 127.0.0.1)
+    #[arg(long, value_name = "IP")]
+    pub bind: Option<std::net::IpAddr>,
+
+    /// When the port is taken: `fail` (exit 2) or `auto` (try the next ports)
+    #[arg(long, value_name = "MODE", default_value = "fail")]
+    pub port_fallback: x402_server::PortFallback,
+
+    /// Ports to try after --port with --port-fallback auto
+    #[arg(long, value_name = "N", default_value_t = x402_server::DEFAULT_PORT_FALLBACK_ATTEMPTS)]
+    pub port_fallback_attempts: u16,
+
+    /// Log level of this instance: error, warn, info, debug, trace
+    /// (overrides log_level and X402_DEV_LOG_LEVEL)
+    #[arg(long, value_name = "LEVEL", value_parser = parse_log_level)]
+    pub log_level: Option<crate::config::LogLevel>,
+
+    /// Request log lines as `pretty` text or `json` (overrides log_format)
+    #[arg(long, value_name = "FORMAT")]
+    pub log_format: Option<x402_server::LogFormat>,
+
+    /// Append X402_DEV_ACTUAL_PORT=<port> to FILE once the server is listening
+    #[arg(long, value_name = "FILE")]
+    pub env_file: Option<std::path::PathBuf>,
+
+    /// Override default pricing amount in SOL/USDC (overrides config file);
+    /// '.' is the decimal separator, e.g. 0.01 or 1,000.50
+    #[arg(long, value_name = "AMOUNT", value_parser = parse_amount)]
     pub pricing: Option<f64>,
 
+    /// Base58 Solana address invoices name as recipient (overrides
+    /// recipient)
+    #[arg(long, value_name = "ADDRESS")]
+    pub recipient: Option<SolanaAddress>,
+
+    /// Serve https:// using an ephemeral self-signed certificate
+    #[arg(long)]
+    pub tls_self_signed: bool,
+
+    /// Accept cleartext HTTP/2 (h2c prior knowledge) as well as HTTP/1.1
+    #[arg(long)]
+    pub http2: bool,
+
+    /// Count hits per pricing rule and policy (shown by `mock stats`)
+    #[arg(long)]
+    pub match_stats: bool,
+
+    /// Run invoices and policies on a virtual clock that
+    /// POST /__admin/clock/advance moves forward (for tests)
+    #[arg(long)]
+    pub allow_time_travel: bool,
+
+    /// Sign invoices with Ed25519, using KEYPAIR or an ephemeral key
+    /// (requires the `signing` feature)
+    #[arg(long, value_name = "KEYPAIR", num_args = 0..=1)]
+    pub sign: Option<Option<std::path::PathBuf>>,
+
+    /// Enforce a policy file (simple or runtime schema) before invoicing
+    #[arg(long, value_name = "FILE")]
+    pub policy: Option<std::path::PathBuf>,
+
+    /// Keep policy counters in a SQLite file shared with other instances,
+    /// so limits hold across all of them (overrides state_backend and
+    /// state_path)
+    #[arg(long, value_name = "PATH", requires = "policy")]
+    pub shared_state: Option<std::path::PathBuf>,
+
+    /// Write policy decisions to a JSONL file (overrides audit_log)
+    #[arg(long, value_name = "PATH", requires = "policy")]
+    pub audit_log: Option<std::path::PathBuf>,
+
+    /// Share of allowed decisions written to the audit log, 0.0 to 1.0;
+    /// denials are always written (overrides audit_sample_rate)
+    #[arg(long, value_name = "RATE", value_parser = parse_sample_rate)]
+    pub audit_sample_rate: Option<f64>,
+
+    /// Shut down after DURATION (seconds, or "10m") without a request
+    /// other than /health and /__admin/* (0 disables; overrides
+    /// idle_timeout_secs)
+    #[arg(long, value_name = "DURATION")]
+    pub idle_timeout_secs: Option<DurationField>,
+
+    /// Shut down DURATION (seconds, or "1h") after start regardless of
+    /// activity (0 disables; overrides max_lifetime_secs)
+    #[arg(long, value_name = "DURATION")]
+    pub max_lifetime_secs: Option<DurationField>,
+
+    /// Give up when /health/ready has not answered 200 after DURATION
+    /// (seconds, or "1m"; overrides ready_timeout_secs, default 30)
+    #[arg(long, value_name = "DURATION")]
+    pub ready_timeout_secs: Option<DurationField>,
+
     #[command(subcommand)]
     pub command: Option<MockSubcommand>,
 }
@@ -94,6 +325,120 @@ pub enum MockSubcommand {
     Status,
     /// Restart the mock server
     Restart,
+    /// Show per-path request statistics from the running server
+    Stats {
+        /// Port of the running mock server
+        #[arg(long, short, default_value = "3402")]
+        port: u16,
+
+        /// Output the raw snapshot as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the instances registered in a shared policy state file
+    List {
+        /// State file the instances share (default: the configured
+        /// state_path)
+        #[arg(long, value_name = "PATH")]
+        shared_state: Option<std::path::PathBuf>,
+
+        /// Output the instances as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Live terminal dashboard of the running server (q quits, p pauses
+    /// events, m switches the simulation mode)
+    Dash {
+        /// Port of the running mock server
+        #[arg(long, short, default_value = "3402")]
+        port: u16,
+
+        /// Milliseconds between polls of the server (100-60000)
+        #[arg(long, value_name = "MS", default_value = "1000", value_parser = clap::value_parser!(u64).range(100..=60_000))]
+        refresh_ms: u64,
+    },
+    /// Read the policy decision audit log
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+    /// Save or load the running server's state as a `.tar.gz` archive
+    Snapshot {
+        #[command(subcommand)]
+        command: SnapshotCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SnapshotCommand {
+    /// Write the server's config, stats, policy counters, invoices and
+    /// events to an archive
+    Export {
+        /// Archive to write
+        #[arg(value_name = "FILE")]
+        file: std::path::PathBuf,
+
+        /// Port of the running mock server
+        #[arg(long, short, default_value = "3402")]
+        port: u16,
+
+        /// Keep only the newest N invoices
+        #[arg(long, value_name = "N", default_value = "10000")]
+        max_invoices: usize,
+    },
+    /// Add an archive's invoices, stats, policy counters and events to the
+    /// running server
+    Import {
+        /// Archive written by `mock snapshot export`
+        #[arg(value_name = "FILE")]
+        file: std::path::PathBuf,
+
+        /// Port of the running mock server
+        #[arg(long, short, default_value = "3402")]
+        port: u16,
+    },
+    /// Print what an archive holds, without a server
+    Show {
+        /// Archive written by `mock snapshot export`
+        #[arg(value_name = "FILE")]
+        file: std::path::PathBuf,
+
+        /// Output the whole snapshot as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AuditCommand {
+    /// Print audit entries matching every filter, oldest first
+    Query {
+        /// Audit log to read, with its rotated files (default: the
+        /// configured audit_log)
+        #[arg(long, value_name = "PATH")]
+        file: Option<std::path::PathBuf>,
+
+        /// Only entries of this agent
+        #[arg(long, value_name = "ID")]
+        agent: Option<String>,
+
+        /// Only `allow` or `deny` entries
+        #[arg(long, value_name = "DECISION")]
+        decision: Option<x402_core::policy::AuditDecision>,
+
+        /// Only entries decided by this policy
+        #[arg(long, value_name = "ID")]
+        policy: Option<String>,
+
+        /// Only entries at or after TIME: relative (`-1h`, `-30m`) or
+        /// RFC3339 (`2026-03-01T12:00:00Z`)
+        #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+        since: Option<String>,
+
+        /// Output the matching entries as JSON lines
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Args)]
@@ -101,24 +446,40 @@ pub enum MockSubcommand {
 EXAMPLES:
   x402-dev test tests/suite.yaml
   x402-dev test tests/suite.yaml --json
+  x402-dev test tests/suite.yaml --format github
   x402-dev test tests/suite.yaml --quiet
   x402-dev test tests/suite.yaml --junit report.xml
+  x402-dev test tests/suite.yaml --report results.json
+  x402-dev test tests/suite.yaml --tag fast --skip-tag e2e
+  x402-dev test tests/suite.yaml --only invoice
+  x402-dev test tests/suite.yaml --var base_url=http://localhost:3000
+  x402-dev test tests/suite.yaml --resolve api.internal:8402:127.0.0.1
+  x402-dev test tests/devnet.yaml --budget 0.50
+  x402-dev test tests/suite.yaml --preflight-health /health
+  x402-dev test tests/suite.yaml --history-dir .x402-history
+  x402-dev test trends --last 10
 
 SEE ALSO:
   x402-dev mock      Start mock server for testing
-  x402-dev verify    Verify compliance after tests
+  x402-dev verify    Verify a payment proof
 ")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct TestArgs {
+    #[command(subcommand)]
+    pub command: Option<TestCommands>,
+
     /// Path to YAML test suite file
-    pub suite: std::path::PathBuf,
+    #[arg(required = true)]
+    pub suite: Option<std::path::PathBuf>,
 
     /// Output results in JSON format (for CI/CD integration)
-    #[arg(long)]
+    #[arg(long, conflicts_with = "format")]
     pub json: bool,
 
-    /// Suppress verbose output, only show summary
-    #[arg(long, short)]
-    pub quiet: bool,
+    /// Output format: text, json or github (default: github in GitHub
+    /// Actions, else text)
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
 
     /// Generate JUnit XML report (for CI/CD integration)
     #[arg(long, value_name = "FILE")]
@@ -127,21 +488,178 @@ pub struct TestArgs {
     /// Generate HTML report (optional)
     #[arg(long, value_name = "FILE")]
     pub html: Option<std::path::PathBuf>,
+
+    /// Write the results as canonical JSON with a content hash to FILE
+    /// (see `x402-dev verify-report`)
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<std::path::PathBuf>,
+
+    /// Only run tests carrying this tag (repeatable, all must match)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Skip tests carrying this tag (repeatable)
+    #[arg(long = "skip-tag", value_name = "TAG")]
+    pub skip_tags: Vec<String>,
+
+    /// Only run tests whose name contains this substring
+    #[arg(long, value_name = "SUBSTRING")]
+    pub only: Option<String>,
+
+    /// Set a suite variable used as ${NAME} (repeatable, overrides the suite)
+    #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable)]
+    pub vars: Vec<(String, String)>,
+
+    /// Connect to ADDR for HOST instead of resolving it, like curl
+    /// (repeatable; IPv6 as [ADDR])
+    #[arg(long = "resolve", value_name = "HOST:PORT:ADDR")]
+    pub resolve: Vec<x402_core::testing::ResolveOverride>,
+
+    /// Skip the remaining tests once invoices total AMOUNT (overrides the
+    /// suite's budget.max_total)
+    #[arg(long, value_name = "AMOUNT", value_parser = parse_budget)]
+    pub budget: Option<x402_domain::Decimal>,
+
+    /// Check variables, DNS and that every target accepts connections
+    /// before running any test (as with `preflight:` in the suite)
+    #[arg(long)]
+    pub preflight: bool,
+
+    /// Also require PATH (e.g. /health) to answer 2xx on every target;
+    /// implies --preflight
+    #[arg(long, value_name = "PATH")]
+    pub preflight_health: Option<String>,
+
+    /// Append a summary of this run to DIR/history.jsonl (see `test trends`)
+    #[arg(long, value_name = "DIR")]
+    pub history_dir: Option<std::path::PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum TestCommands {
+    /// Show per-test pass rate and duration trends from --history-dir runs
+    Trends {
+        /// Directory the runs were recorded in
+        #[arg(long, value_name = "DIR", default_value = ".x402-history")]
+        history_dir: std::path::PathBuf,
+
+        /// Number of most recent runs to show
+        #[arg(long, value_name = "N", default_value_t = x402_core::testing::DEFAULT_TREND_RUNS)]
+        last: usize,
+    },
 }
 
 #[derive(Args)]
 #[command(after_help = "\
 EXAMPLES:
-  x402-dev verify
-  x402-dev verify --strict
-  x402-dev verify --output json
+  x402-dev scenario run x402-scenario.yaml
+  x402-dev scenario run x402-scenario.yaml --json
+  x402-dev scenario run x402-scenario.yaml --tag smoke
+  x402-dev scenario validate x402-scenario.yaml
+
+FILE FORMAT:
+  name, description, config (mock settings), policy (either policy schema)
+  and suite (test suite). config, policy and suite are written inline or as
+  a path relative to the scenario file. Test URLs start with ${base_url},
+  the in-process server the scenario starts on a free loopback port.
 
 SEE ALSO:
-  x402-dev test      Run automated test suites
-  x402-dev check     Check system health
+  x402-dev test      Run a test suite against a running server
+  x402-dev policy    Validate policy files
+")]
+pub struct ScenarioArgs {
+    #[command(subcommand)]
+    pub command: ScenarioCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ScenarioCommands {
+    /// Serve the scenario's config and policies, run its suite, then stop
+    Run {
+        /// Path to the scenario file
+        file: std::path::PathBuf,
+
+        /// Output the combined report (suite results + server stats) as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Only run tests carrying this tag (repeatable, all must match)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Skip tests carrying this tag (repeatable)
+        #[arg(long = "skip-tag", value_name = "TAG")]
+        skip_tags: Vec<String>,
+
+        /// Only run tests whose name contains this substring
+        #[arg(long, value_name = "SUBSTRING")]
+        only: Option<String>,
+    },
+
+    /// Check the config, policy and suite sections without running anything
+    Validate {
+        /// Path to the scenario file
+        file: std::path::PathBuf,
+    },
+}
+
+#[derive(Args)]
+#[command(after_help = "\
+EXAMPLES:
+  x402-dev verify req-1b2c --amount 0.05      Simulated payment of the invoice req-1b2c
+  x402-dev verify req-1b2c --amount 0.05 --paid 0.049
+                                              Check a declared amount against amount_tolerance
+  x402-dev verify 5VERv8N... --amount 0.05 --verifier rpc --memo req-1b2c
+                                              Look the transaction up on solana_rpc
+  x402-dev verify short-req-1b2c --amount 0.05 --verifier rpc --rpc-url http://127.0.0.1:3402/rpc
+
+OUTCOMES:
+  verified, rejected, pending, underpayment or overpayment; anything but
+  verified exits with status 1. Both verifiers classify the same way.
+
+SEE ALSO:
+  x402-dev curl      Run the whole payment flow against a server
+  x402-dev receipt   Check the receipt of a verified payment
 ")]
 pub struct VerifyArgs {
-    // Epic 3: Protocol verification arguments
+    /// Payment proof: the invoice memo (simulated) or a transaction
+    /// signature (rpc)
+    pub proof: String,
+
+    /// Invoiced amount in USDC, e.g. 0.05
+    #[arg(long, value_name = "AMOUNT", value_parser = x402_domain::Amount::parse_user_input)]
+    pub amount: x402_domain::Amount,
+
+    /// Memo the payment must carry (default: any; simulated payments take
+    /// the proof as the memo)
+    #[arg(long, value_name = "MEMO")]
+    pub memo: Option<String>,
+
+    /// Address the payment must go to (rpc only; default: any)
+    #[arg(long, value_name = "ADDRESS")]
+    pub recipient: Option<SolanaAddress>,
+
+    /// Amount the payer declared, checked against amount_tolerance
+    /// (simulated only; default: the invoiced amount)
+    #[arg(long, value_name = "AMOUNT", value_parser = x402_domain::Amount::parse_user_input)]
+    pub paid: Option<x402_domain::Amount>,
+
+    /// Verifier to use: simulated or rpc (default: the configured verifier)
+    #[arg(long, value_name = "VERIFIER")]
+    pub verifier: Option<x402_core::verifier::VerifierKind>,
+
+    /// Simulation mode of the simulated verifier (default: the configured
+    /// simulation_mode)
+    #[arg(long, value_name = "MODE")]
+    pub mode: Option<crate::config::SimulationMode>,
+
+    /// JSON-RPC endpoint of the rpc verifier (default: solana_rpc)
+    #[arg(long, value_name = "URL")]
+    pub rpc_url: Option<String>,
+
+    /// Print the outcome as JSON
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(Args)]
@@ -149,19 +667,140 @@ pub struct VerifyArgs {
 EXAMPLES:
   x402-dev check http://localhost:3402/api/data
   x402-dev check http://localhost:3402/api/data --format json
+  x402-dev check http://localhost:3402/api/data --format github
+  x402-dev check https://localhost:3402/api/data --insecure
+  x402-dev check http://localhost:3402/api/data --save-baseline baseline.json
+  x402-dev check http://localhost:3402/api/data --baseline baseline.json --strict-baseline
+  x402-dev check http://localhost:3402/api/data --min-amount 0.001
+  x402-dev check http://localhost:3402/api/data --strict
+  x402-dev check http://localhost:3402/api/data --expected-memo-prefix staging-
+  x402-dev check http://localhost:3402/api/data --verify-signature
+  x402-dev check http://localhost:3402/api/data --verify-signature <PUBKEY>
+  x402-dev check http://api.internal/api/data --resolve api.internal:3402:127.0.0.1
+  x402-dev check http://localhost:3402/api/data --quota-probes 3
+  x402-dev check http://localhost:3402/api/data --badge badge.svg --summary COMPLIANCE.md
+  x402-dev check http://localhost:3402/api/data --report compliance.json
+  x402-dev check --self-test
+  x402-dev check --battery http://localhost:8080
 
 SEE ALSO:
   x402-dev doctor    Diagnose and fix issues
   x402-dev config    View configuration
-  x402-dev verify    Verify protocol compliance
+  x402-dev verify    Verify a payment proof
 ")]
 pub struct CheckArgs {
     /// URL to check for x402 compliance
-    pub url: String,
+    #[arg(required_unless_present_any = ["self_test", "battery"])]
+    pub url: Option<String>,
+
+    /// Check the conformance fixtures served by an in-process server and
+    /// report any verdict that differs from the expected one
+    #[arg(long, conflicts_with_all = ["url", "battery"])]
+    pub self_test: bool,
+
+    /// Check the conformance fixtures served at BASE_URL/battery/<name>
+    #[arg(long, value_name = "BASE_URL", conflicts_with = "url")]
+    pub battery: Option<String>,
+
+    /// Output format: text, json or github (default: github in GitHub
+    /// Actions, else text)
+    #[arg(long, value_enum)]
+    pub format: Option<OutputFormat>,
+
+    /// Accept invalid TLS certificates (e.g. the mock's self-signed cert)
+    #[arg(long, short = 'k')]
+    pub insecure: bool,
+
+    /// Save the response shape to FILE for later --baseline runs
+    #[arg(long, value_name = "FILE", conflicts_with = "baseline")]
+    pub save_baseline: Option<std::path::PathBuf>,
+
+    /// Compare the response shape against a saved baseline
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<std::path::PathBuf>,
+
+    /// Fail instead of warn when the response differs from the baseline
+    #[arg(long, requires = "baseline")]
+    pub strict_baseline: bool,
+
+    /// Extra invoice field or header to exclude from baselines (repeatable)
+    #[arg(long = "baseline-ignore", value_name = "FIELD")]
+    pub baseline_ignore: Vec<String>,
+
+    /// Fail on unknown/duplicate fields, non-canonical order, unquoted
+    /// reserved characters, amounts with more than 6 decimals and memos
+    /// other than `<prefix><id>`; also pays with the memo as proof and
+    /// checks the payment receipt when the server accepts it
+    #[arg(long)]
+    pub strict: bool,
 
-    /// Output format (text or json)
-    #[arg(long, default_value = "text")]
-    pub format: String,
+    /// Verify the invoice signature, optionally requiring signer PUBKEY
+    /// (Base58; requires the `signing` feature)
+    #[arg(long, value_name = "PUBKEY", num_args = 0..=1)]
+    pub verify_signature: Option<Option<SolanaAddress>>,
+
+    /// Warn when the invoice amount is below this minimum (dust payments);
+    /// '.' is the decimal separator, e.g. 0.001
+    #[arg(long, value_name = "AMOUNT", value_parser = parse_amount)]
+    pub min_amount: Option<f64>,
+
+    /// Fail when the invoice amount is above this maximum, e.g. 1.50
+    #[arg(long, value_name = "AMOUNT", value_parser = x402_domain::Amount::parse_user_input)]
+    pub max_amount: Option<x402_domain::Amount>,
+
+    /// Fail unless the invoice names this network: devnet, testnet or
+    /// mainnet-beta
+    #[arg(long, value_name = "NETWORK")]
+    pub network: Option<SolanaNetwork>,
+
+    /// Memo prefix the invoice must use (default: the configured
+    /// memo_prefix, else req-)
+    #[arg(long, value_name = "PREFIX")]
+    pub expected_memo_prefix: Option<x402_domain::MemoPrefix>,
+
+    /// With --strict, fail unless the memo ID has this format: uuidv4,
+    /// uuidv7 or ulid (default: any of them)
+    #[arg(long, value_name = "FORMAT")]
+    pub id_format: Option<x402_domain::IdFormat>,
+
+    /// Protocol identifier the invoice header may use; repeat to accept
+    /// several, the first is the one --strict expects (default: the
+    /// configured protocol_id, else x402-solana)
+    #[arg(long = "protocol", value_name = "ID")]
+    pub protocols: Vec<x402_domain::ProtocolId>,
+
+    /// Judge expiry by the server's `ts` issue time when the local clock
+    /// is skewed beyond --skew-tolerance
+    #[arg(long)]
+    pub tolerate_skew: bool,
+
+    /// Seconds the local clock may differ from the server's `ts` before
+    /// skew is reported
+    #[arg(long, value_name = "SECONDS", default_value_t = x402_core::testing::DEFAULT_SKEW_TOLERANCE_SECS)]
+    pub skew_tolerance: u64,
+
+    /// Connect to ADDR for HOST instead of resolving it, like curl
+    /// (repeatable; IPv6 as [ADDR])
+    #[arg(long = "resolve", value_name = "HOST:PORT:ADDR")]
+    pub resolve: Vec<x402_core::testing::ResolveOverride>,
+
+    /// Send N more requests as one agent and fail unless the remaining
+    /// quota headers (configured `quota_headers`) decrease on each (2-100)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(2..=100))]
+    pub quota_probes: Option<u32>,
+
+    /// Write an SVG badge of the compliance grade to FILE
+    #[arg(long, value_name = "FILE")]
+    pub badge: Option<std::path::PathBuf>,
+
+    /// Write a markdown table of every check and its evidence to FILE
+    #[arg(long, value_name = "FILE")]
+    pub summary: Option<std::path::PathBuf>,
+
+    /// Write the checks as canonical JSON with a content hash to FILE
+    /// (see `x402-dev verify-report`)
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<std::path::PathBuf>,
 }
 
 #[derive(Args)]
@@ -181,6 +820,9 @@ pub struct MonitorArgs {
 // PolicyArgs is now defined in commands/policy.rs
 pub use crate::commands::policy::PolicyArgs;
 
+// GenerateArgs is defined in commands/generate.rs
+pub use crate::commands::generate::GenerateArgs;
+
 #[derive(Args)]
 #[command(after_help = "\
 EXAMPLES:
@@ -218,15 +860,27 @@ pub struct DoctorArgs {
 #[command(after_help = "\
 EXAMPLES:
   x402-dev init
-  x402-dev init --defaults
-  x402-dev init --template minimal
+  x402-dev init --output-dir services/payments
+  x402-dev init --config-name x402dev.staging.yaml
+  x402-dev init --format json        Write .x402dev.json
 
 SEE ALSO:
   x402-dev config    Manage configuration
+  x402-dev convert   Translate config files between YAML and JSON
   x402-dev examples  View example code
 ")]
 pub struct InitArgs {
-    // Epic 6: Project initialization arguments
+    /// Directory to write the config file into (created if missing)
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub output_dir: std::path::PathBuf,
+
+    /// File name of the config file (default: .x402dev.yaml, or .x402dev.json with --format json)
+    #[arg(long, value_name = "NAME")]
+    pub config_name: Option<String>,
+
+    /// Config file format: yaml or json (default: from --config-name, else yaml)
+    #[arg(long, value_name = "FORMAT")]
+    pub format: Option<x402_core::format::FileFormat>,
 }
 
 #[derive(Args)]
@@ -234,6 +888,10 @@ pub struct InitArgs {
 EXAMPLES:
   x402-dev version
   x402-dev version --no-update-check
+  x402-dev version --check-update
+
+ENVIRONMENT:
+  X402_DEV_NO_UPDATE_CHECK=1   Disable all update checks
 
 SEE ALSO:
   x402-dev doctor    Diagnose issues
@@ -243,6 +901,10 @@ pub struct VersionArgs {
     /// Skip checking for updates
     #[arg(long)]
     pub no_update_check: bool,
+
+    /// Query the latest release and report whether an update is available
+    #[arg(long, conflicts_with = "no_update_check")]
+    pub check_update: bool,
 }
 
 #[derive(Args)]
@@ -250,19 +912,27 @@ pub struct VersionArgs {
 EXAMPLES:
   x402-dev config show
   x402-dev config show --port 8888
+  x402-dev config show --reveal     Print secrets in plain text (asks first)
   X402_DEV_PORT=9999 x402-dev config show
+  x402-dev config diff --right ci/.x402dev.yaml
+  x402-dev config diff --clean-env --right-env X402_DEV_PORT=8402 --json
+  x402-dev config explain-price /api/premium/report
+  x402-dev config explain-price /api/items --method POST --json
 
 PRIORITY ORDER:
   CLI flags > Environment variables > Project config > Global config > Defaults
 
 CONFIG FILES:
   Global: ~/.x402dev/config.yaml
-  Project: ./.x402dev.yaml
+  Project: nearest .x402dev.yaml from the current directory up to the git root
 
 ENVIRONMENT VARIABLES:
+  X402_DEV_CONFIG       Explicit project config file (skips discovery)
   X402_DEV_PORT         Override port (e.g., 8402)
   X402_DEV_SOLANA_RPC   Override Solana RPC URL
   X402_DEV_LOG_LEVEL    Override log level (error|warn|info|debug|trace)
+  X402_DEV_STATE_DIR    Directory for PID files, caches and secrets
+                        (default ~/.x402dev, or the temp dir if read-only)
 
 SEE ALSO:
   x402-dev init      Initialize project configuration
@@ -272,7 +942,7 @@ pub struct ConfigArgs {
     #[command(subcommand)]
     pub command: ConfigCommands,
 
-    /// Override port setting
+    /// Override port setting (1024-65535)
     #[arg(long, global = true)]
     pub port: Option<u16>,
 
@@ -288,5 +958,385 @@ pub struct ConfigArgs {
 #[derive(Subcommand)]
 pub enum ConfigCommands {
     /// Display current configuration with sources
-    Show,
+    Show {
+        /// Print secrets in plain text after a confirmation prompt
+        #[arg(long)]
+        reveal: bool,
+    },
+
+    /// Compare the effective configuration of two scenarios (exit 3 if they differ)
+    Diff {
+        /// Project config file for the left side (default: the one in effect)
+        #[arg(long, value_name = "FILE")]
+        left: Option<std::path::PathBuf>,
+
+        /// Project config file for the right side (default: the one in effect)
+        #[arg(long, value_name = "FILE")]
+        right: Option<std::path::PathBuf>,
+
+        /// Environment variable for the left side (repeatable)
+        #[arg(long = "left-env", value_name = "NAME=VALUE")]
+        left_env: Vec<String>,
+
+        /// Environment variable for the right side (repeatable)
+        #[arg(long = "right-env", value_name = "NAME=VALUE")]
+        right_env: Vec<String>,
+
+        /// Ignore the current X402_DEV_* variables on both sides
+        #[arg(long)]
+        clean_env: bool,
+
+        /// Print the differences as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the pricing rules that apply to a path, in priority order
+    ExplainPrice {
+        /// Request path, e.g. /api/premium/report
+        path: String,
+
+        /// Also consider method-qualified rules (e.g. "POST /api/*")
+        #[arg(long, value_name = "METHOD")]
+        method: Option<String>,
+
+        /// Print the explanation as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Args)]
+#[command(after_help = "\
+EXAMPLES:
+  x402-dev secret set facilitator_key          Prompt for the value
+  echo \"$KEY\" | x402-dev secret set facilitator_key --file
+  x402-dev secret list
+  x402-dev secret rm facilitator_key
+
+REFERENCES:
+  Config files and test suites use ${secret:facilitator_key}; the value is
+  resolved when the file is used and masked in test reports.
+
+STORAGE:
+  OS keyring (builds with the keyring feature), else files in
+  ~/.x402dev/secrets encrypted with a passphrase
+
+ENVIRONMENT VARIABLES:
+  X402_DEV_SECRETS_PASSPHRASE   Passphrase of encrypted secret files
+                                (prompted for in a terminal when unset)
+")]
+pub struct SecretArgs {
+    #[command(subcommand)]
+    pub command: SecretCommands,
+}
+
+#[derive(Subcommand)]
+pub enum SecretCommands {
+    /// Store a secret, read from a hidden prompt or stdin
+    Set {
+        /// Secret name (letters, digits, '_', '-' or '.')
+        name: String,
+
+        /// Use an encrypted file even when the OS keyring is available
+        #[arg(long)]
+        file: bool,
+    },
+
+    /// List secret names (never values)
+    List,
+
+    /// Delete a secret
+    Rm {
+        /// Secret name
+        name: String,
+    },
+}
+
+#[derive(Args)]
+#[command(after_help = "\
+EXAMPLES:
+  x402-dev receipt verify eyJtZW1vIjoi...      Check structure and signature
+  x402-dev receipt verify \"$RECEIPT\" --signer <PUBKEY>
+  curl -si -H 'X-Payment-Proof: req-...' http://localhost:3402/api/data \\
+    | sed -n 's/^x-payment-receipt: //ip' | x402-dev receipt verify -
+
+SIGNATURES:
+  Receipts from a mock started with --sign carry signer and signature
+  fields; checking them needs a build with the signing feature.
+")]
+pub struct ReceiptArgs {
+    #[command(subcommand)]
+    pub command: ReceiptCommands,
+}
+
+#[derive(Args)]
+#[command(after_help = "\
+EXAMPLES:
+  x402-dev curl /api/data                     Pay on the configured mock server
+  x402-dev curl /api/data --no-pay            Only fetch and show the invoice
+  x402-dev curl /api/data --mode failure      Expect the payment to be refused
+  x402-dev curl /api/data --url http://localhost:8402 --json
+
+FLOW:
+  1. GET the path and expect 402 with a WWW-Authenticate invoice
+  2. Retry with X-Payment-Proof set to the invoice memo
+  3. Expect the outcome of --mode: 2xx (success), 402 (failure) or
+     408 (timeout)
+  Any other response exits with status 1.
+")]
+pub struct CurlArgs {
+    /// Path to request, e.g. /api/data
+    pub path: String,
+
+    /// Server base URL (default: http://127.0.0.1:<port> from the config)
+    #[arg(long, value_name = "URL")]
+    pub url: Option<String>,
+
+    /// Stop after printing the invoice
+    #[arg(long)]
+    pub no_pay: bool,
+
+    /// Send X-Simulation-Mode with the payment and expect that outcome
+    /// (default: no header, expect success)
+    #[arg(long, value_name = "MODE")]
+    pub mode: Option<crate::config::SimulationMode>,
+
+    /// Print the flow as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Connect to ADDR for HOST instead of resolving it, like curl
+    /// (repeatable; IPv6 as [ADDR])
+    #[arg(long = "resolve", value_name = "HOST:PORT:ADDR")]
+    pub resolve: Vec<x402_core::testing::ResolveOverride>,
+}
+
+#[derive(Args)]
+#[command(after_help = "\
+EXAMPLES:
+  x402-dev estimate tests/suite.yaml              Price the suite with the pricing config
+  x402-dev estimate tests/suite.yaml --probe      Price from the target's own 402 invoices
+  x402-dev estimate --profile traffic.yaml --policy policy.yaml --json
+  x402-dev estimate tests/devnet.yaml --budget 0.50
+
+TRAFFIC PROFILE:
+  target: http://localhost:3402   # base URL (default: the configured mock)
+  agent: agent-a                  # X-Agent-Id (default: anonymous)
+  requests:
+    - path: /api/data
+      count: 100
+    - path: /api/premium/report
+      method: POST
+      count: 5
+
+LIMITS:
+  The suite budget (or --budget) and the spending caps of the policy files
+  (--policy, else policy_files) are replayed over the requests in order.
+  A cap is charged by the policy that would decide each request, per
+  agent, as if the whole run fell within one cap window.
+")]
+pub struct EstimateArgs {
+    /// Test suite whose requests are estimated (repeats and `then:` steps included)
+    #[arg(conflicts_with = "profile", required_unless_present = "profile")]
+    pub suite: Option<std::path::PathBuf>,
+
+    /// Traffic profile listing requests per path with counts
+    #[arg(long, value_name = "FILE")]
+    pub profile: Option<std::path::PathBuf>,
+
+    /// Price each distinct path from the 402 invoice the target answers
+    /// (default: the local pricing config)
+    #[arg(long)]
+    pub probe: bool,
+
+    /// Base URL of the profile's paths (overrides the profile's target)
+    #[arg(long, value_name = "URL", requires = "profile")]
+    pub target: Option<String>,
+
+    /// Policy file whose spending caps are checked (repeatable; default:
+    /// policy_files from the config)
+    #[arg(long, value_name = "FILE")]
+    pub policy: Vec<std::path::PathBuf>,
+
+    /// Budget to compare against (overrides the suite's budget.max_total)
+    #[arg(long, value_name = "AMOUNT", value_parser = parse_budget)]
+    pub budget: Option<x402_domain::Decimal>,
+
+    /// X-Agent-Id of requests that set none (default: anonymous)
+    #[arg(long, value_name = "ID")]
+    pub agent: Option<String>,
+
+    /// Print the estimate as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+#[command(after_help = "\
+EXAMPLES:
+  x402-dev explain config.invalid_port    Explain a config error
+  x402-dev explain X402-P010              Explain a policy rule code
+  x402-dev explain underpayment --json    Mock server error envelope code
+  x402-dev explain                        List every code
+
+CODES:
+  config.*     Config validation errors
+  X402-P*      Policy validation rules (x402-dev policy rules)
+  snake_case   `code` of mock server error responses
+")]
+pub struct ExplainArgs {
+    /// Error code, e.g. X402-P010 (case-insensitive); omit to list all codes
+    pub code: Option<String>,
+
+    /// Print the explanation as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+#[command(after_help = "\
+EXAMPLES:
+  x402-dev convert .x402dev.yaml .x402dev.json
+  x402-dev convert policy.json policy.yaml
+  x402-dev convert suite.txt suite.json --from yaml
+
+Formats come from the file extensions (.yaml, .yml, .json); an input with
+another extension is JSON if it starts with { or [. Every value and the key
+order are kept. Comments are dropped, YAML anchors are expanded, and YAML
+tags or non-string keys are rejected when writing JSON.
+")]
+pub struct ConvertArgs {
+    /// File to read
+    pub input: std::path::PathBuf,
+
+    /// File to write (overwritten if it exists)
+    pub output: std::path::PathBuf,
+
+    /// Input format: yaml or json (default: detected)
+    #[arg(long, value_name = "FORMAT")]
+    pub from: Option<x402_core::format::FileFormat>,
+
+    /// Output format: yaml or json (default: from the output extension)
+    #[arg(long, value_name = "FORMAT")]
+    pub to: Option<x402_core::format::FileFormat>,
+}
+
+#[derive(Args)]
+#[command(after_help = "\
+EXAMPLES:
+  x402-dev test tests/suite.yaml --report results.json
+  x402-dev verify-report results.json
+  x402-dev verify-report compliance.json --json
+
+Reports written with --report (test, check) are canonical JSON: sorted keys,
+decimal strings for fractional numbers and millisecond UTC timestamps, with
+a content_hash over the rest. Exits 1 if the recomputed hash differs.
+")]
+pub struct VerifyReportArgs {
+    /// Report file written with --report
+    pub file: std::path::PathBuf,
+
+    /// Output the recorded and computed hashes as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Subcommand)]
+pub enum ReceiptCommands {
+    /// Decode a receipt and validate its fields and signature offline
+    Verify {
+        /// X-Payment-Receipt header value, or - to read it from stdin
+        receipt: String,
+
+        /// Require a signature by this base58 public key
+        #[arg(long, value_name = "PUBKEY")]
+        signer: Option<String>,
+
+        /// Fail when the receipt is not signed
+        #[arg(long)]
+        require_signature: bool,
+
+        /// Print the decoded receipt as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Error text clap prints for `args`
+    fn parse_error(args: &[&str]) -> String {
+        match Cli::try_parse_from([&["x402-dev"], args].concat()) {
+            Ok(_) => panic!("{:?} was accepted", args),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn mock(args: &[&str]) -> MockArgs {
+        match Cli::try_parse_from([&["x402-dev", "mock"], args].concat()).unwrap() {
+            Cli {
+                command: Commands::Mock(args),
+                ..
+            } => args,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_valid_domain_values_are_accepted() {
+        let args = mock(&[
+            "--port",
+            "8402",
+            "--pricing",
+            "1,000.50",
+            "--recipient",
+            "7EqQdEULxWcraVx3mXKFjc84LhCkMGZCkRuDpvcMwJeK",
+            "--idle-timeout-secs",
+            "10m",
+        ]);
+        assert_eq!(args.port, 8402);
+        assert_eq!(args.pricing, Some(1000.5));
+        assert_eq!(
+            args.recipient.unwrap().as_str(),
+            "7EqQdEULxWcraVx3mXKFjc84LhCkMGZCkRuDpvcMwJeK"
+        );
+        assert_eq!(args.idle_timeout_secs.unwrap().as_secs(), 600);
+
+        let Commands::Check(check) = Cli::try_parse_from([
+            "x402-dev",
+            "check",
+            "http://localhost:3402/api/data",
+            "--network",
+            "mainnet",
+            "--min-amount",
+            "0.001",
+        ])
+        .unwrap()
+        .command
+        else {
+            unreachable!()
+        };
+        assert_eq!(check.network, Some(SolanaNetwork::MainnetBeta));
+        assert_eq!(check.min_amount, Some(0.001));
+    }
+
+    #[test]
+    fn test_invalid_values_fail_with_suggestions() {
+        assert!(parse_error(&["mock", "--pricing", "0,01"]).contains(
+            "Invalid amount '0,01': ',' is not a valid decimal separator. Use '.' as the decimal separator without grouping (did you mean 0.01?)"
+        ));
+        assert!(parse_error(&["mock", "--port", "80"]).contains(
+            "Invalid port 80: ports below 1024 are privileged and need root. Use 1024-65535 (did you mean 8080?)"
+        ));
+        assert!(parse_error(&["mock", "--recipient", "bad!address"])
+            .contains("Invalid Solana address: length must be between 32 and 44 characters"));
+        assert!(parse_error(&["mock", "--max-lifetime-secs", "soon"])
+            .contains("Use a number of seconds (e.g. 3600) or a duration with a unit"));
+        assert!(parse_error(&["check", "http://x/", "--network", "devnett"]).contains(
+            "Unknown Solana network 'devnett' (did you mean devnet?). Use devnet, testnet or mainnet-beta"
+        ));
+    }
 }
@@ -1,42 +1,45 @@
-use crate::cli::CheckArgs;
+use crate::cli::{CheckArgs, OutputFormat};
+use crate::commands::check_battery;
+use crate::commands::receipt::verify_receipt;
+use crate::config::{load_merged_config, resolve_overrides};
+use crate::errors::CliError;
+use crate::output::Output;
 use anyhow::{anyhow, Result};
+use chrono::Utc;
 use colored::Colorize;
 use reqwest;
 use std::collections::HashMap;
+use std::str::FromStr;
+use x402_core::canonical::to_canonical_string;
+use x402_core::policy::QuotaHeaders;
+use x402_core::receipt::{PaymentReceipt, RECEIPT_HEADER};
+use x402_core::testing::{
+    apply_resolve_overrides, check_cache_headers, check_head_parity, decode_body, is_dns_error,
+    override_for_url, Annotation, BaselineDiff, ChangeKind, CheckBaseline, ComplianceReport,
+    HeadParityCheck, InvoiceHeader, ProbeResponse, DEFAULT_VOLATILE_FIELDS, PAYMENT_PROOF_HEADER,
+    PROTOCOL_SECTION, REQUIRED_FIELDS, STRICT_SECTION,
+};
+use x402_domain::{
+    Amount, Decimal, IdFormat, InvoiceMemo, MemoPrefix, SolanaAddress, SolanaNetwork,
+};
 
-/// Parse WWW-Authenticate header into key-value pairs
-///
-/// Format: "x402-solana recipient=<addr> amount=<val> currency=USDC memo=<id> network=devnet"
-fn parse_www_authenticate(header: &str) -> Result<HashMap<String, String>> {
-    let mut fields = HashMap::new();
-
-    // Split by whitespace
-    let parts: Vec<&str> = header.split_whitespace().collect();
-
-    // First part should be protocol identifier
-    if parts.is_empty() || parts[0] != "x402-solana" {
-        return Err(anyhow!(
-            "Invalid protocol identifier, expected 'x402-solana'"
-        ));
-    }
+/// Agent the `--quota-probes` requests are counted against
+const QUOTA_PROBE_AGENT: &str = "x402-dev-check";
 
-    // Parse key=value pairs
-    for part in &parts[1..] {
-        if let Some((key, value)) = part.split_once('=') {
-            fields.insert(key.to_string(), value.to_string());
-        }
-    }
-
-    Ok(fields)
-}
+/// Report section of the invoice field checks
+const INVOICE_SECTION: &str = "Invoice Structure";
 
 /// Validate invoice structure
-fn validate_invoice(fields: &HashMap<String, String>) -> Vec<(String, bool, String)> {
+fn validate_invoice(
+    fields: &HashMap<String, String>,
+    max_amount: Option<Amount>,
+    memo_prefix: &MemoPrefix,
+    expected_network: Option<SolanaNetwork>,
+) -> Vec<(String, bool, String)> {
     let mut results = Vec::new();
 
     // Check required fields
-    let required_fields = vec!["recipient", "amount", "currency", "memo", "network"];
-    for field in required_fields {
+    for field in REQUIRED_FIELDS {
         let exists = fields.contains_key(field);
         let status = if exists { "present" } else { "missing" };
         results.push((format!("Field '{}'", field), exists, status.to_string()));
@@ -57,13 +60,21 @@ fn validate_invoice(fields: &HashMap<String, String>) -> Vec<(String, bool, Stri
         results.push(("Recipient address".to_string(), valid, status));
     }
 
-    // Validate amount (parseable as f64, positive)
+    // Validate amount (plain positive decimal, USDC precision, --max-amount)
     if let Some(amount_str) = fields.get("amount") {
-        let valid = amount_str.parse::<f64>().map(|a| a > 0.0).unwrap_or(false);
-        let status = if valid {
-            format!("{} USDC", amount_str)
-        } else {
-            "invalid amount".to_string()
+        let (valid, status) = match Amount::parse_invoice_amount(amount_str, max_amount) {
+            Ok(amount) => {
+                let canonical = amount.to_canonical_string();
+                if canonical == *amount_str {
+                    (true, format!("{} USDC", canonical))
+                } else {
+                    (
+                        true,
+                        format!("{} USDC (normalized from {})", canonical, amount_str),
+                    )
+                }
+            }
+            Err(e) => (false, e.to_string()),
         };
         results.push(("Amount".to_string(), valid, status));
     }
@@ -75,28 +86,33 @@ fn validate_invoice(fields: &HashMap<String, String>) -> Vec<(String, bool, Stri
         results.push(("Currency".to_string(), valid, status.to_string()));
     }
 
-    // Validate memo (should start with req-)
+    // Validate memo (should start with the expected prefix)
     if let Some(memo) = fields.get("memo") {
-        let valid = memo.starts_with("req-") && memo.len() > 4;
-        let status = if valid {
-            memo.clone()
-        } else {
-            "invalid format".to_string()
+        let (valid, status) = match memo.strip_prefix(memo_prefix.as_str()) {
+            Some(rest) if !rest.is_empty() => (true, memo.clone()),
+            Some(_) => (false, "invalid format".to_string()),
+            None => (
+                false,
+                format!("{} does not start with '{}'", memo, memo_prefix),
+            ),
         };
         results.push(("Memo".to_string(), valid, status));
     }
 
-    // Validate network (devnet, testnet, or mainnet-beta)
+    // Validate network (devnet, testnet, or mainnet-beta; --network if given)
     if let Some(network) = fields.get("network") {
-        let valid_networks = ["devnet", "testnet", "mainnet-beta", "mainnet"];
-        let valid = valid_networks.contains(&network.as_str());
-        let status = if valid {
-            network.clone()
-        } else {
-            format!(
-                "invalid (expected devnet/testnet/mainnet-beta, got {})",
-                network
-            )
+        let (valid, status) = match (network.parse::<SolanaNetwork>(), expected_network) {
+            (Ok(actual), Some(expected)) if actual != expected => {
+                (false, format!("expected {}, got {}", expected, network))
+            }
+            (Ok(_), _) => (true, network.clone()),
+            (Err(_), _) => (
+                false,
+                format!(
+                    "invalid (expected devnet/testnet/mainnet-beta, got {})",
+                    network
+                ),
+            ),
         };
         results.push(("Network".to_string(), valid, status));
     }
@@ -104,71 +120,707 @@ fn validate_invoice(fields: &HashMap<String, String>) -> Vec<(String, bool, Stri
     results
 }
 
+/// Expiry check (optional; RFC 3339 and still in the future) and the
+/// clock-skew warning when the server's `ts` is beyond `skew_tolerance`
+///
+/// With `tolerate_skew`, a skewed invoice's remaining time is measured
+/// from `ts` rather than the local clock.
+fn check_expiry(
+    header: &InvoiceHeader,
+    tolerate_skew: bool,
+    skew_tolerance: u64,
+) -> (Option<(String, bool, String)>, Option<String>) {
+    let Some(expires) = header.get("expires") else {
+        return (None, None);
+    };
+    let status = match header.expiry_status(Utc::now(), tolerate_skew.then_some(skew_tolerance)) {
+        Ok(Some(status)) => status,
+        Ok(None) => return (None, None),
+        Err(_) if header.expires().is_err() => {
+            let detail = format!("invalid RFC 3339 timestamp: {}", expires);
+            return (Some(("Expiry".to_string(), false, detail)), None);
+        }
+        Err(e) => return (Some(("Expiry".to_string(), false, e.to_string())), None),
+    };
+
+    let clock = if status.server_time {
+        ", server time"
+    } else {
+        ""
+    };
+    let check = if status.remaining > 0 {
+        (
+            true,
+            format!("{} (in {}s{})", expires, status.remaining, clock),
+        )
+    } else {
+        (
+            false,
+            format!("{} (expired {}s ago{})", expires, -status.remaining, clock),
+        )
+    };
+
+    let warning = status
+        .skew
+        .filter(|skew| skew.unsigned_abs() > skew_tolerance)
+        .map(|skew| {
+            format!(
+                "Local clock is {}s {} the server's ts (tolerance {}s)",
+                skew.unsigned_abs(),
+                if skew > 0 { "ahead of" } else { "behind" },
+                skew_tolerance
+            )
+        });
+    (Some(("Expiry".to_string(), check.0, check.1)), warning)
+}
+
+/// Strict memo check: exactly `<prefix><id>` with the expected prefix and,
+/// when pinned, the expected ID format
+fn check_memo_format(
+    memo: Option<&String>,
+    memo_prefix: &MemoPrefix,
+    id_format: Option<IdFormat>,
+) -> (bool, String) {
+    let Some(memo) = memo else {
+        return (false, "missing".to_string());
+    };
+    let expected = match id_format {
+        Some(format) => format!("{}<{}>", memo_prefix, format),
+        None => format!("{}<id>", memo_prefix),
+    };
+    match InvoiceMemo::new(memo.as_str()) {
+        Ok(parsed)
+            if parsed.prefix() == Some(memo_prefix.as_str())
+                && id_format.is_none_or(|format| parsed.format() == Some(format)) =>
+        {
+            (true, expected)
+        }
+        Ok(_) => (false, format!("expected {}, got {}", expected, memo)),
+        Err(e) => (false, e.to_string()),
+    }
+}
+
+/// Annotation message: the detail, then the suggested fix if any
+fn with_fix(detail: &str, suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(fix) => format!("{}\nFix: {}", detail, fix),
+        None => detail.to_string(),
+    }
+}
+
+/// Print the collected annotations when reporting for GitHub Actions
+fn print_annotations(format: OutputFormat, annotations: &[Annotation], out: &Output) {
+    if format == OutputFormat::Github && !annotations.is_empty() {
+        out.line("");
+        for annotation in annotations {
+            out.line(annotation);
+        }
+    }
+}
+
+/// An "Amount" detail (`0.05 USDC ...`) with the amount in the display
+/// format; the report keeps the canonical string
+fn display_amount(detail: &str, out: &Output) -> String {
+    match detail.split_once(" USDC") {
+        Some((amount, rest)) => format!("{}{}", out.amount(amount, "USDC"), rest),
+        None => detail.to_string(),
+    }
+}
+
+/// Compare the JSON body's invoice amount with the header's
+///
+/// Reads `invoice.amount` (the mock server's body) or a top-level `amount`.
+/// Both sides go through the invoice amount parser, so `0.010` and `0.01`
+/// agree while a body amount the header rounded away does not. Returns
+/// `None` when the body carries no amount.
+fn check_body_amount(header_amount: Option<&String>, body: &str) -> Option<(String, bool, String)> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    let body_amount = match json
+        .pointer("/invoice/amount")
+        .or_else(|| json.get("amount"))?
+    {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+
+    let (passed, status) = match (
+        Amount::parse_invoice_amount(&body_amount, None),
+        header_amount.map(|h| Amount::parse_invoice_amount(h, None)),
+    ) {
+        (Err(e), _) => (false, format!("body amount {}: {}", body_amount, e)),
+        (Ok(_), None) => (
+            false,
+            "body has an amount but the header has none".to_string(),
+        ),
+        (Ok(_), Some(Err(_))) => (false, "header amount is invalid".to_string()),
+        (Ok(body), Some(Ok(header))) if body == header => (
+            true,
+            format!("matches header ({})", header.to_canonical_string()),
+        ),
+        (Ok(body), Some(Ok(header))) => (
+            false,
+            format!(
+                "{} does not match header {}",
+                body.to_canonical_string(),
+                header.to_canonical_string()
+            ),
+        ),
+    };
+    Some(("Body amount".to_string(), passed, status))
+}
+
+/// Warning for a 503 with `Retry-After`: the endpoint is temporarily down
+/// for maintenance, so its compliance cannot be judged now
+fn maintenance_warning(
+    status: reqwest::StatusCode,
+    headers: &reqwest::header::HeaderMap,
+    body: &str,
+) -> Option<String> {
+    if status != reqwest::StatusCode::SERVICE_UNAVAILABLE {
+        return None;
+    }
+    let retry_after = headers.get("retry-after")?.to_str().unwrap_or("?");
+    let message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|body| body["message"].as_str().map(str::to_string));
+    Some(format!(
+        "Endpoint is under maintenance (503, Retry-After: {}){}; compliance not checked",
+        retry_after,
+        message.map_or_else(String::new, |m| format!(": {}", m))
+    ))
+}
+
+/// Send HEAD to the URL and compare the answer with the GET response
+async fn probe_head(
+    client: &reqwest::Client,
+    url: &str,
+    get: ProbeResponse<'_>,
+) -> HeadParityCheck {
+    let response = match client.head(url).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            return HeadParityCheck {
+                passed: false,
+                detail: format!("HEAD request failed: {}", e),
+                suggestion: None,
+            }
+        }
+    };
+    let status = response.status().as_u16();
+    let invoice = response
+        .headers()
+        .get("www-authenticate")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let body_len = response.bytes().await.map_or(0, |b| b.len());
+
+    check_head_parity(
+        get,
+        ProbeResponse {
+            status,
+            invoice: invoice.as_deref(),
+            body_len,
+        },
+    )
+}
+
+/// Undo the body's Content-Encoding
+///
+/// Returns the text, its sizes before and after decoding (`body` in JSON
+/// output), and a warning if it could not be decoded.
+fn decode_response_body(
+    headers: &reqwest::header::HeaderMap,
+    raw: Vec<u8>,
+) -> (String, serde_json::Value, Option<String>) {
+    let encoded_size = raw.len();
+    let content_encoding = headers
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok());
+    match decode_body(content_encoding, raw) {
+        Ok(body) => {
+            let report = serde_json::json!({
+                "encoding": body.encoding,
+                "encoded_size": body.encoded_size,
+                "size": body.bytes.len(),
+            });
+            (body.text(), report, None)
+        }
+        Err(e) => {
+            let report = serde_json::json!({
+                "encoding": content_encoding,
+                "encoded_size": encoded_size,
+            });
+            let reason = e.to_string();
+            let reason = reason.lines().next().unwrap_or_default();
+            let warning = format!("Could not decode the response body: {}", reason);
+            (String::new(), report, Some(warning))
+        }
+    }
+}
+
+/// One-line description of a baseline difference
+fn describe_diff(diff: &BaselineDiff) -> String {
+    let none = String::new();
+    let before = diff.baseline.as_ref().unwrap_or(&none);
+    let after = diff.current.as_ref().unwrap_or(&none);
+    match diff.change {
+        ChangeKind::Added => format!("{} added: {}", diff.field, after),
+        ChangeKind::Removed => format!("{} removed (was: {})", diff.field, before),
+        ChangeKind::Changed => format!("{} changed: {} → {}", diff.field, before, after),
+    }
+}
+
+/// Error for --verify-signature in builds without the `signing` feature
+const SIGNING_UNAVAILABLE: &str = "Signature verification is not available in this build\nFix: Rebuild x402-dev with `--features signing`";
+
+/// Verify the invoice signature, returning the signer
+#[cfg(feature = "signing")]
+fn verify_signature(header: &InvoiceHeader, expected_signer: Option<&str>) -> Result<String> {
+    x402_core::signing::verify_invoice_signature(header, expected_signer)
+}
+
+#[cfg(not(feature = "signing"))]
+fn verify_signature(_header: &InvoiceHeader, _expected_signer: Option<&str>) -> Result<String> {
+    Err(anyhow!(SIGNING_UNAVAILABLE))
+}
+
+/// Pay a second time with the same memo and expect a refusal (`--strict`)
+///
+/// Only meaningful after [`check_receipt`] saw the first payment accepted.
+async fn check_replay(
+    client: &reqwest::Client,
+    url: &str,
+    invoice: &HashMap<String, String>,
+) -> (bool, String) {
+    let Some(memo) = invoice.get("memo") else {
+        return (false, "invoice has no memo".to_string());
+    };
+    let response = match client
+        .get(url)
+        .header(PAYMENT_PROOF_HEADER, memo)
+        .header("X-Simulation-Mode", "success")
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return (false, format!("replay request failed: {}", e)),
+    };
+    let status = response.status();
+    if status.is_success() {
+        return (
+            false,
+            format!("{} was accepted twice ({})", memo, status.as_u16()),
+        );
+    }
+    let code = response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|body| body["code"].as_str().map(str::to_string));
+    (
+        true,
+        match code {
+            Some(code) => format!("second payment refused ({} {})", status.as_u16(), code),
+            None => format!("second payment refused ({})", status.as_u16()),
+        },
+    )
+}
+
+/// Request `url` `probes` times as one agent and expect the remaining quota
+/// to go down on each response (`--quota-probes`)
+///
+/// Requests left must decrease and spending left must not grow. A policy
+/// denial (403) ends the probes early: the quota ran out.
+async fn check_quota(
+    client: &reqwest::Client,
+    url: &str,
+    probes: u32,
+    names: &QuotaHeaders,
+) -> (bool, String) {
+    let mut seen: Vec<(Option<u64>, Option<Decimal>)> = Vec::new();
+    for _ in 0..probes {
+        let response = match client
+            .get(url)
+            .header("X-Agent-Id", QUOTA_PROBE_AGENT)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => return (false, format!("probe request failed: {}", e)),
+        };
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            break;
+        }
+        let value = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let requests = value(&names.rate_limit_remaining).and_then(|v| v.parse().ok());
+        let spend = value(&names.spend_remaining).and_then(|v| Decimal::from_str(&v).ok());
+        if requests.is_none() && spend.is_none() {
+            return (
+                false,
+                format!(
+                    "response {} has no {} or {} header",
+                    seen.len() + 1,
+                    names.rate_limit_remaining,
+                    names.spend_remaining
+                ),
+            );
+        }
+        seen.push((requests, spend));
+    }
+
+    for pair in seen.windows(2) {
+        let ((requests, spend), (next_requests, next_spend)) = (pair[0], pair[1]);
+        if let (Some(before), Some(after)) = (requests, next_requests) {
+            if after >= before {
+                return (
+                    false,
+                    format!(
+                        "{} went from {} to {}",
+                        names.rate_limit_remaining, before, after
+                    ),
+                );
+            }
+        }
+        if let (Some(before), Some(after)) = (spend, next_spend) {
+            if after > before {
+                return (
+                    false,
+                    format!(
+                        "{} went from {} to {}",
+                        names.spend_remaining, before, after
+                    ),
+                );
+            }
+        }
+    }
+
+    let (Some(first), Some(last)) = (seen.first(), seen.last()) else {
+        return (true, "quota exhausted before the first probe".to_string());
+    };
+    let mut changes = Vec::new();
+    if let (Some(before), Some(after)) = (first.0, last.0) {
+        changes.push(format!(
+            "{} {} → {}",
+            names.rate_limit_remaining, before, after
+        ));
+    }
+    if let (Some(before), Some(after)) = (first.1, last.1) {
+        changes.push(format!("{} {} → {}", names.spend_remaining, before, after));
+    }
+    let mut detail = format!("{} over {} requests", changes.join(", "), seen.len());
+    if seen.len() < probes as usize {
+        detail.push_str(" (then denied: quota exhausted)");
+    }
+    (true, detail)
+}
+
+/// Pay with the invoice memo as proof and check the receipt (`--strict`)
+///
+/// `None` when the server does not accept the payment, as real servers will
+/// not for a made-up proof; then there is no receipt to check.
+async fn check_receipt(
+    client: &reqwest::Client,
+    url: &str,
+    invoice: &HashMap<String, String>,
+) -> Option<(bool, String)> {
+    let memo = invoice.get("memo")?;
+    let response = client
+        .get(url)
+        .header(PAYMENT_PROOF_HEADER, memo)
+        .header("X-Simulation-Mode", "success")
+        .send()
+        .await
+        .ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let Some(value) = response
+        .headers()
+        .get(RECEIPT_HEADER)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return Some((
+            false,
+            format!("paid response has no {} header", RECEIPT_HEADER),
+        ));
+    };
+    let receipt = match PaymentReceipt::decode(value) {
+        Ok(receipt) => receipt,
+        Err(e) => return Some((false, e.to_string())),
+    };
+
+    let amounts_match = match (
+        invoice.get("amount"),
+        Amount::parse_invoice_amount(&receipt.amount, None),
+    ) {
+        (Some(amount), Ok(paid)) => {
+            Amount::parse_invoice_amount(amount, None).is_ok_and(|amount| amount == paid)
+        }
+        _ => false,
+    };
+    if receipt.memo != *memo {
+        return Some((
+            false,
+            format!("receipt memo {} is not {}", receipt.memo, memo),
+        ));
+    }
+    if !amounts_match {
+        return Some((
+            false,
+            format!(
+                "receipt amount {} does not match the invoice",
+                receipt.amount
+            ),
+        ));
+    }
+    if let (Some(paid), Some(recipient)) = (&receipt.recipient, invoice.get("recipient")) {
+        if paid != recipient {
+            return Some((
+                false,
+                format!("receipt recipient {} is not {}", paid, recipient),
+            ));
+        }
+    }
+    if !receipt.is_signed() {
+        return Some((true, format!("{} (unsigned)", receipt.memo)));
+    }
+    if cfg!(not(feature = "signing")) {
+        return Some((
+            true,
+            format!("{} (signature not checked in this build)", receipt.memo),
+        ));
+    }
+    // A signed invoice's receipt must come from the same key
+    let expected_signer = invoice.get("signer").map(String::as_str);
+    Some(match verify_receipt(&receipt, expected_signer, false) {
+        Ok(signer) => (
+            true,
+            format!(
+                "{} (signed by {})",
+                receipt.memo,
+                signer.unwrap_or_default()
+            ),
+        ),
+        Err(e) => (false, e.to_string()),
+    })
+}
+
+/// Write the `--badge` and `--summary` files of the report
+fn write_artifacts(args: &CheckArgs, report: &ComplianceReport, out: &Output) -> Result<()> {
+    let artifacts = [
+        (&args.badge, report.render_badge()),
+        (&args.summary, report.render_summary()),
+        (&args.report, report.render_json()),
+    ];
+    for (path, contents) in artifacts {
+        let Some(path) = path else { continue };
+        std::fs::write(path, contents).map_err(|e| {
+            anyhow!(
+                "Failed to write {}: {}\nFix: Check that the directory exists and is writable",
+                path.display(),
+                e
+            )
+        })?;
+        out.status(format!("{} {}", "Wrote:".bold(), path.display()));
+    }
+    Ok(())
+}
+
 /// Run the check command
-pub async fn run(args: &CheckArgs) -> Result<()> {
-    println!("{}", "x402 API Compliance Check".bold().cyan());
-    println!("{}", "=========================".cyan());
-    println!();
-    println!("Checking: {}", args.url.yellow());
-    println!();
+pub async fn run(args: &CheckArgs, out: &Output) -> Result<()> {
+    if args.self_test || args.battery.is_some() {
+        return check_battery::run(args, out).await;
+    }
+    if args.verify_signature.is_some() && cfg!(not(feature = "signing")) {
+        return Err(anyhow!(SIGNING_UNAVAILABLE));
+    }
+    let url = args.url.as_deref().unwrap_or_default();
+
+    out.status("x402 API Compliance Check".bold().cyan());
+    out.status("=========================".cyan());
+    out.status("");
+    out.status(format!("Checking: {}", url.yellow()));
+    let overrides = resolve_overrides(&args.resolve)?;
+    if let Some(entry) = override_for_url(url, &overrides) {
+        out.status(format!(
+            "Resolving: {} → {} (--resolve {})",
+            entry.host,
+            entry.socket_addr(),
+            entry
+        ));
+    }
+    out.status("");
 
     // Make HTTP request with 10 second timeout
-    let client = reqwest::Client::builder()
+    let builder = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
-        .build()?;
+        .danger_accept_invalid_certs(args.insecure);
+    let client = apply_resolve_overrides(builder, &overrides).build()?;
 
     let response = client
-        .get(&args.url)
+        .get(url)
         .send()
         .await
-        .map_err(|e| anyhow!("Failed to connect to URL (timeout: 10s): {}", e))?;
+        .map_err(|e| {
+            if is_dns_error(&e) {
+                CliError::network_with_suggestion(
+                    format!("Could not resolve host of {}: {}", url, e),
+                    "Check the host name, or map it to an address with --resolve HOST:PORT:ADDR",
+                    "dns-resolution",
+                )
+                .into()
+            } else if url.starts_with("https://") && !args.insecure {
+                anyhow!(
+                    "Failed to connect to URL (timeout: 10s): {}\nFix: For self-signed certificates, re-run with --insecure",
+                    e
+                )
+            } else {
+                anyhow!("Failed to connect to URL (timeout: 10s): {}", e)
+            }
+        })?;
 
     // Track validation results
-    let mut checks_passed = 0;
-    let mut checks_total = 0;
+    let format = OutputFormat::resolve(args.format);
+    let mut annotations = Vec::new();
+    let mut report = ComplianceReport::new(url);
 
     // Protocol Validation Section
-    println!("{}", "Protocol Validation:".bold());
+    out.line("Protocol Validation:".bold());
 
     // Check 1: HTTP 402 status code
-    checks_total += 1;
     let status = response.status();
-    let status_check = status.as_u16() == 402;
+    let headers = response.headers().clone();
+    let raw_body = response.bytes().await.map(Vec::from).unwrap_or_default();
+    let (body, body_report, body_warning) = decode_response_body(&headers, raw_body);
+    if let Some(warning) = maintenance_warning(status, &headers, &body) {
+        // Not a verdict on the protocol: the server says to come back later
+        out.line(format!("  {} {}", "⚠️ ".yellow(), warning));
+        annotations.push(Annotation::warning("Maintenance", &warning));
+        print_annotations(format, &annotations, out);
+        out.line("");
+        out.line(format!(
+            "{} {}",
+            "Overall:".bold(),
+            "⚠️  SKIPPED (endpoint under maintenance)".yellow().bold()
+        ));
+        if format == OutputFormat::Json {
+            out.line("");
+            println!(
+                "{}",
+                to_canonical_string(&serde_json::json!({
+                    "status": "maintenance",
+                    "url": url,
+                    "warnings": [warning],
+                }))?
+            );
+        }
+        return Ok(());
+    }
+    let status_check = report.record(
+        PROTOCOL_SECTION,
+        "HTTP 402 status code",
+        status.as_u16() == 402,
+        status.as_u16().to_string(),
+    );
     if status_check {
-        checks_passed += 1;
-        println!(
+        out.line(format!(
             "  {} HTTP 402 status code: {}",
             "✅".green(),
             "PASS".green()
-        );
+        ));
     } else {
-        println!(
+        out.line(format!(
             "  {} HTTP 402 status code: {} (got {})",
             "❌".red(),
             "FAIL".red(),
             status.as_u16()
-        );
+        ));
+        annotations.push(Annotation::error(
+            "HTTP 402 status code",
+            format!("{} returned {}", url, status.as_u16()),
+        ));
+    }
+
+    if let Some(encoding) = body_report["encoding"].as_str() {
+        out.line(format!(
+            "  {} Body: {} bytes {}-encoded, {} decoded",
+            "ℹ️ ".cyan(),
+            body_report["encoded_size"],
+            encoding,
+            body_report["size"]
+        ));
+    }
+
+    // Check 2: 402 responses must not be cacheable by shared caches
+    let cache_check = check_cache_headers(&headers);
+    if report.record(
+        PROTOCOL_SECTION,
+        "Not cacheable",
+        cache_check.passed,
+        &cache_check.detail,
+    ) {
+        out.line(format!(
+            "  {} Not cacheable: {} ({})",
+            "✅".green(),
+            "PASS".green(),
+            cache_check.detail
+        ));
+    } else {
+        out.line(format!(
+            "  {} Not cacheable: {} ({})",
+            "❌".red(),
+            "FAIL".red(),
+            cache_check.detail
+        ));
+        out.line("     Stale 402 responses can hand clients expired invoice memos");
+        if let Some(suggestion) = &cache_check.suggestion {
+            out.line(format!("     {} {}", "Fix:".yellow(), suggestion));
+        }
+        annotations.push(Annotation::error(
+            "Not cacheable",
+            with_fix(&cache_check.detail, cache_check.suggestion.as_deref()),
+        ));
     }
 
-    // Check 2: WWW-Authenticate header
-    checks_total += 1;
-    let www_auth = response.headers().get("www-authenticate");
+    // Check 3: WWW-Authenticate header
+    let www_auth = headers.get("www-authenticate");
     let header_check = www_auth.is_some();
-    if header_check {
-        checks_passed += 1;
-        println!(
+    let evidence = if header_check { "present" } else { "missing" };
+    if report.record(
+        PROTOCOL_SECTION,
+        "WWW-Authenticate header",
+        header_check,
+        evidence,
+    ) {
+        out.line(format!(
             "  {} WWW-Authenticate header: {}",
             "✅".green(),
             "PASS".green()
-        );
+        ));
     } else {
-        println!(
+        out.line(format!(
             "  {} WWW-Authenticate header: {}",
             "❌".red(),
             "FAIL (missing)".red()
-        );
-        println!();
-        println!("{} {}", "Overall:".bold(), "❌ CHECKS FAILED".red().bold());
+        ));
+        annotations.push(Annotation::error(
+            "WWW-Authenticate header",
+            format!("{} sent no WWW-Authenticate invoice", url),
+        ));
+        print_annotations(format, &annotations, out);
+        write_artifacts(args, &report, out)?;
+        out.line("");
+        out.line(format!(
+            "{} {}",
+            "Overall:".bold(),
+            "❌ CHECKS FAILED".red().bold()
+        ));
         std::process::exit(1);
     }
 
@@ -178,57 +830,358 @@ pub async fn run(args: &CheckArgs) -> Result<()> {
         .to_str()
         .map_err(|e| anyhow!("Invalid header encoding: {}", e))?;
 
-    println!();
-    println!("{}", "Invoice Structure:".bold());
+    // Check 4: HEAD answers like GET without a body
+    let get_probe = ProbeResponse {
+        status: status.as_u16(),
+        invoice: Some(header_value),
+        body_len: body.len(),
+    };
+    let head_check = probe_head(&client, url, get_probe).await;
+    if report.record(
+        PROTOCOL_SECTION,
+        "HEAD parity",
+        head_check.passed,
+        &head_check.detail,
+    ) {
+        out.line(format!(
+            "  {} HEAD parity: {} ({})",
+            "✅".green(),
+            "PASS".green(),
+            head_check.detail
+        ));
+    } else {
+        out.line(format!(
+            "  {} HEAD parity: {} ({})",
+            "❌".red(),
+            "FAIL".red(),
+            head_check.detail
+        ));
+        if let Some(suggestion) = &head_check.suggestion {
+            out.line(format!("     {} {}", "Fix:".yellow(), suggestion));
+        }
+        annotations.push(Annotation::error(
+            "HEAD parity",
+            with_fix(&head_check.detail, head_check.suggestion.as_deref()),
+        ));
+    }
+
+    out.line("");
+    out.line("Invoice Structure:".bold());
 
-    let fields = match parse_www_authenticate(header_value) {
-        Ok(f) => f,
+    // --protocol, else the configured protocol_id, else x402-solana
+    let protocols = if args.protocols.is_empty() {
+        vec![load_merged_config(None)
+            .ok()
+            .and_then(|config| config.protocol_id)
+            .unwrap_or_default()]
+    } else {
+        args.protocols.clone()
+    };
+
+    let invoice_header = match InvoiceHeader::parse_with_protocols(header_value, &protocols) {
+        Ok(h) => h,
         Err(e) => {
-            println!("  {} Failed to parse header: {}", "❌".red(), e);
-            println!();
-            println!("{} {}", "Overall:".bold(), "❌ CHECKS FAILED".red().bold());
+            out.line(format!("  {} Failed to parse header: {}", "❌".red(), e));
+            annotations.push(Annotation::error("Invoice header", e.to_string()));
+            report.record(INVOICE_SECTION, "Invoice header", false, e.to_string());
+            print_annotations(format, &annotations, out);
+            write_artifacts(args, &report, out)?;
+            out.line("");
+            out.line(format!(
+                "{} {}",
+                "Overall:".bold(),
+                "❌ CHECKS FAILED".red().bold()
+            ));
             std::process::exit(1);
         }
     };
 
-    let validation_results = validate_invoice(&fields);
+    // --expected-memo-prefix, else the configured prefix, else req-
+    let memo_prefix = args
+        .expected_memo_prefix
+        .clone()
+        .or_else(|| load_merged_config(None).ok()?.memo_prefix)
+        .unwrap_or_default();
+
+    let fields = invoice_header.to_map();
+    let mut validation_results =
+        validate_invoice(&fields, args.max_amount, &memo_prefix, args.network);
+    let (expiry_check, skew_warning) =
+        check_expiry(&invoice_header, args.tolerate_skew, args.skew_tolerance);
+    validation_results.extend(expiry_check);
+    validation_results.extend(check_body_amount(fields.get("amount"), &body));
 
     for (name, passed, value) in validation_results {
-        checks_total += 1;
-        if passed {
-            checks_passed += 1;
-            println!("  {} {}: {}", "✅".green(), name, value);
+        if report.record(INVOICE_SECTION, &name, passed, &value) {
+            let value = if name == "Amount" {
+                display_amount(&value, out)
+            } else {
+                value
+            };
+            out.line(format!("  {} {}: {}", "✅".green(), name, value));
         } else {
-            println!("  {} {}: {}", "❌".red(), name, value.red());
+            out.line(format!("  {} {}: {}", "❌".red(), name, value.red()));
+            annotations.push(Annotation::error(name, value));
+        }
+    }
+
+    // Strict protocol mode: every deviation from the canonical header is a failed check
+    if args.strict {
+        out.line("");
+        out.line("Strict Protocol:".bold());
+        let (memo_passed, memo_detail) =
+            check_memo_format(fields.get("memo"), &memo_prefix, args.id_format);
+        let strict_results = invoice_header
+            .strict_checks_for(protocols[0].as_str())
+            .into_iter()
+            .map(|check| (check.name, check.passed, check.detail))
+            .chain([("Memo format", memo_passed, memo_detail)]);
+        for (name, passed, detail) in strict_results {
+            if report.record(STRICT_SECTION, name, passed, &detail) {
+                out.line(format!("  {} {}: {}", "✅".green(), name, detail));
+            } else {
+                out.line(format!("  {} {}: {}", "❌".red(), name, detail.red()));
+                annotations.push(Annotation::error(format!("Strict: {}", name), detail));
+            }
+        }
+
+        let paid = match check_receipt(&client, url, &fields).await {
+            Some((true, detail)) => {
+                report.record(STRICT_SECTION, "Payment receipt", true, &detail);
+                out.line(format!("  {} Payment receipt: {}", "✅".green(), detail));
+                true
+            }
+            Some((false, detail)) => {
+                report.record(STRICT_SECTION, "Payment receipt", false, &detail);
+                out.line(format!(
+                    "  {} Payment receipt: {}",
+                    "❌".red(),
+                    detail.red()
+                ));
+                annotations.push(Annotation::error("Strict: Payment receipt", detail));
+                true
+            }
+            None => {
+                out.line(format!(
+                    "  {} Payment receipt: skipped (the memo was not accepted as payment proof)",
+                    "ℹ️ ".cyan()
+                ));
+                false
+            }
+        };
+
+        // A correct server never accepts the same proof twice
+        if paid {
+            let (passed, detail) = check_replay(&client, url, &fields).await;
+            report.record(STRICT_SECTION, "Replay protection", passed, &detail);
+            match (passed, detail) {
+                (true, detail) => {
+                    out.line(format!("  {} Replay protection: {}", "✅".green(), detail));
+                }
+                (false, detail) => {
+                    out.line(format!(
+                        "  {} Replay protection: {}",
+                        "❌".red(),
+                        detail.red()
+                    ));
+                    annotations.push(Annotation::error("Strict: Replay protection", detail));
+                }
+            }
+        }
+    }
+
+    // Invoice signature (signer/signature fields over the canonical header)
+    if let Some(expected_signer) = &args.verify_signature {
+        out.line("");
+        out.line("Signature:".bold());
+        match verify_signature(
+            &invoice_header,
+            expected_signer.as_ref().map(SolanaAddress::as_str),
+        ) {
+            Ok(signer) => {
+                report.record("Signature", "Signature valid", true, &signer);
+                out.line(format!("  {} Signature valid: {}", "✅".green(), signer));
+            }
+            Err(e) => {
+                report.record("Signature", "Signature valid", false, e.to_string());
+                out.line(format!(
+                    "  {} Signature invalid: {}",
+                    "❌".red(),
+                    e.to_string().red()
+                ));
+                annotations.push(Annotation::error("Signature", e.to_string()));
+            }
         }
     }
 
+    // Remaining quota headers of an enforcing server
+    if let Some(probes) = args.quota_probes {
+        out.line("");
+        out.line("Policy Quota:".bold());
+        let names = load_merged_config(None)
+            .map(|config| config.quota_headers)
+            .unwrap_or_default();
+        let (passed, detail) = check_quota(&client, url, probes, &names).await;
+        report.record("Policy Quota", "Remaining quota decreases", passed, &detail);
+        match (passed, detail) {
+            (true, detail) => {
+                out.line(format!(
+                    "  {} Remaining quota decreases: {}",
+                    "✅".green(),
+                    detail
+                ));
+            }
+            (false, detail) => {
+                out.line(format!(
+                    "  {} Remaining quota decreases: {}",
+                    "❌".red(),
+                    detail.red()
+                ));
+                annotations.push(Annotation::error("Policy quota", detail));
+            }
+        }
+    }
+
+    if let Some(warning) = body_warning {
+        out.line(format!("  {} {}", "⚠️ ".yellow(), warning));
+        annotations.push(Annotation::warning("Content-Encoding", &warning));
+        report.warnings.push(warning);
+    }
+
+    // Clock skew: a warning only; --tolerate-skew decides how expiry is judged
+    if let Some(warning) = skew_warning {
+        out.line(format!("  {} {}", "⚠️ ".yellow(), warning));
+        if !args.tolerate_skew {
+            out.line("     Re-run with --tolerate-skew to judge expiry by the server's clock");
+        }
+        annotations.push(Annotation::warning("Clock skew", &warning));
+        report.warnings.push(warning);
+    }
+
+    // Zero amount: the Amount check fails; point at the usual cause
+    if let Some(amount) = fields.get("amount") {
+        if Amount::from_decimal_str(amount).is_ok_and(|a| a.is_zero()) {
+            let warning = format!("Invoice amount {} is zero", amount);
+            out.line(format!("  {} {}", "⚠️ ".yellow(), warning));
+            out.line("     Free resources should answer 200 without an invoice (zero_price_behavior: serve)");
+            annotations.push(Annotation::warning("Zero amount", &warning));
+            report.warnings.push(warning);
+        }
+    }
+
+    // Minimum invoice amount: a warning only, since dust is valid protocol-wise
+    if let (Some(min), Some(amount)) = (args.min_amount, fields.get("amount")) {
+        if Amount::parse_invoice_amount(amount, None).is_ok_and(|a| a.to_f64() < min) {
+            let warning = format!("Invoice amount {} is below --min-amount {}", amount, min);
+            out.line(format!("  {} {}", "⚠️ ".yellow(), warning));
+            out.line("     Facilitators typically refuse dust payments");
+            annotations.push(Annotation::warning("Minimum amount", &warning));
+            report.warnings.push(warning);
+        }
+    }
+
+    // Baseline snapshot / comparison (response shape drift)
+    let mut volatile_fields: Vec<String> = DEFAULT_VOLATILE_FIELDS
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+    volatile_fields.extend(args.baseline_ignore.iter().cloned());
+    let snapshot =
+        CheckBaseline::capture(url, status.as_u16(), &headers, &fields, &volatile_fields);
+
+    let mut baseline_report = None;
+    if let Some(path) = &args.save_baseline {
+        snapshot.save(path)?;
+        out.line("");
+        out.line(format!("{} {}", "Baseline saved:".bold(), path.display()));
+        baseline_report = Some(serde_json::json!({ "saved": path }));
+    }
+    if let Some(path) = &args.baseline {
+        let baseline = CheckBaseline::load(path)?;
+        let diffs = baseline.diff(&snapshot);
+
+        out.line("");
+        out.line("Baseline Comparison:".bold());
+        if args.strict_baseline {
+            let evidence = match diffs.as_slice() {
+                [] => format!("matches {}", path.display()),
+                [diff] => describe_diff(diff),
+                [diff, rest @ ..] => format!("{} (+{} more)", describe_diff(diff), rest.len()),
+            };
+            report.record(
+                "Baseline Comparison",
+                "Matches baseline",
+                diffs.is_empty(),
+                evidence,
+            );
+        }
+        if diffs.is_empty() {
+            out.line(format!(
+                "  {} Matches baseline {}",
+                "✅".green(),
+                path.display()
+            ));
+        }
+        for diff in &diffs {
+            let icon = if args.strict_baseline {
+                "❌".red()
+            } else {
+                "⚠️ ".yellow()
+            };
+            out.line(format!("  {} {}", icon, describe_diff(diff)));
+            annotations.push(if args.strict_baseline {
+                Annotation::error("Baseline", describe_diff(diff))
+            } else {
+                Annotation::warning("Baseline", describe_diff(diff))
+            });
+        }
+
+        baseline_report = Some(serde_json::json!({
+            "path": path,
+            "strict": args.strict_baseline,
+            "diff": diffs,
+        }));
+    }
+
     // Summary
-    println!();
-    if checks_passed == checks_total {
-        println!(
+    print_annotations(format, &annotations, out);
+    write_artifacts(args, &report, out)?;
+    let (checks_passed, checks_total) = (report.passed(), report.total());
+    let warnings = &report.warnings;
+    out.line("");
+    if report.all_passed() {
+        out.line(format!(
             "{} {}",
             "Overall:".bold(),
             format!("✅ ALL CHECKS PASSED ({}/{})", checks_passed, checks_total)
                 .green()
                 .bold()
-        );
+        ));
 
         // JSON output if requested
-        if args.format == "json" {
-            let json_output = serde_json::json!({
+        if format == OutputFormat::Json {
+            let mut json_output = serde_json::json!({
                 "status": "pass",
                 "checks_passed": checks_passed,
                 "checks_total": checks_total,
-                "url": args.url,
+                "url": url,
+                "grade": report.grade(),
+                "checks": report.checks,
             });
-            println!();
-            println!("{}", serde_json::to_string_pretty(&json_output)?);
+            if let Some(report) = &baseline_report {
+                json_output["baseline"] = report.clone();
+            }
+            json_output["body"] = body_report.clone();
+            if !warnings.is_empty() {
+                json_output["warnings"] = serde_json::json!(warnings);
+            }
+            out.line("");
+            println!("{}", to_canonical_string(&json_output)?);
         }
 
         Ok(())
     } else {
-        println!(
+        out.line(format!(
             "{} {}",
             "Overall:".bold(),
             format!(
@@ -237,18 +1190,27 @@ pub async fn run(args: &CheckArgs) -> Result<()> {
             )
             .red()
             .bold()
-        );
+        ));
 
         // JSON output if requested
-        if args.format == "json" {
-            let json_output = serde_json::json!({
+        if format == OutputFormat::Json {
+            let mut json_output = serde_json::json!({
                 "status": "fail",
                 "checks_passed": checks_passed,
                 "checks_total": checks_total,
-                "url": args.url,
+                "url": url,
+                "grade": report.grade(),
+                "checks": report.checks,
             });
-            println!();
-            println!("{}", serde_json::to_string_pretty(&json_output)?);
+            if let Some(report) = &baseline_report {
+                json_output["baseline"] = report.clone();
+            }
+            json_output["body"] = body_report.clone();
+            if !warnings.is_empty() {
+                json_output["warnings"] = serde_json::json!(warnings);
+            }
+            out.line("");
+            println!("{}", to_canonical_string(&json_output)?);
         }
 
         std::process::exit(1);
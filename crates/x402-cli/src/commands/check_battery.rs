@@ -0,0 +1,239 @@
+// `check --self-test` and `check --battery <BASE_URL>`
+//
+// Runs the full check against every fixture of the conformance battery
+// (x402_core::testing::battery) and compares the verdicts with the expected
+// ones. Each fixture is checked by a child `x402-dev check` writing
+// `--report`, so the battery exercises exactly the code users run, exits
+// included. --self-test serves the fixtures from an in-process server: a
+// mismatch there is a bug in x402-dev. --battery checks fixtures another
+// server replays at BASE_URL/battery/<name>.
+
+use crate::cli::CheckArgs;
+use crate::output::Output;
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+use x402_core::testing::battery::{
+    self, BatteryFixture, Mismatch, BATTERY_MEMO_PREFIX, BATTERY_PROTOCOL,
+};
+use x402_core::testing::ComplianceReport;
+use x402_server::TestServer;
+
+/// Verdict of one fixture
+struct Outcome {
+    url: String,
+    /// Checks recorded by the child run
+    checks: usize,
+    mismatches: Vec<Mismatch>,
+}
+
+/// Run the battery against the in-process server or `--battery`
+pub async fn run(args: &CheckArgs, out: &Output) -> Result<()> {
+    let server = if args.self_test {
+        Some(TestServer::start_battery().await?)
+    } else {
+        None
+    };
+    let base_url = match (&server, &args.battery) {
+        (Some(server), _) => server.base_url().to_string(),
+        (None, Some(base_url)) => base_url.trim_end_matches('/').to_string(),
+        (None, None) => unreachable!("run is only called with --self-test or --battery"),
+    };
+
+    let title = if args.self_test {
+        "x402 Check Self-Test"
+    } else {
+        "x402 Conformance Battery"
+    };
+    out.status(title.bold().cyan());
+    out.status("=".repeat(title.len()).cyan());
+    out.status("");
+    out.status(format!(
+        "Fixtures: {}{}<name>",
+        base_url.yellow(),
+        battery::BATTERY_PATH
+    ));
+    out.status("");
+
+    let report_dir = std::env::temp_dir().join(format!("x402dev-battery-{}", std::process::id()));
+    std::fs::create_dir_all(&report_dir)
+        .with_context(|| format!("Failed to create {}", report_dir.display()))?;
+    let fixtures = battery::fixtures();
+    let width = fixtures.iter().map(|f| f.name.len()).max().unwrap_or(0);
+    let mut outcomes = Vec::new();
+    for fixture in &fixtures {
+        let outcome = check_fixture(args, &base_url, fixture, &report_dir).await;
+        match &outcome {
+            Ok(outcome) if outcome.mismatches.is_empty() => out.line(format!(
+                "  {} {:<width$}  {}",
+                "✅".green(),
+                fixture.name,
+                expected_verdict(fixture, outcome.checks).dimmed(),
+                width = width
+            )),
+            Ok(outcome) => {
+                out.line(format!(
+                    "  {} {:<width$}  {}",
+                    "❌".red(),
+                    fixture.name,
+                    "MISMATCH".red(),
+                    width = width
+                ));
+                out.line(format!("     {}", fixture.description));
+                for mismatch in &outcome.mismatches {
+                    out.line(format!("     - {}", mismatch));
+                }
+            }
+            Err(e) => {
+                out.line(format!(
+                    "  {} {:<width$}  {}",
+                    "❌".red(),
+                    fixture.name,
+                    "ERROR".red(),
+                    width = width
+                ));
+                out.line(format!("     {:#}", e));
+            }
+        }
+        outcomes.push((fixture, outcome));
+    }
+    let _ = std::fs::remove_dir_all(&report_dir);
+    if let Some(server) = server {
+        server.stop().await?;
+    }
+
+    let failed: Vec<_> = outcomes
+        .iter()
+        .filter(|(_, outcome)| outcome.as_ref().map_or(true, |o| !o.mismatches.is_empty()))
+        .collect();
+    out.line("");
+    if failed.is_empty() {
+        out.line(format!(
+            "{} {}",
+            "Overall:".bold(),
+            format!("✅ ALL {} FIXTURES MATCH", outcomes.len())
+                .green()
+                .bold()
+        ));
+        return Ok(());
+    }
+
+    out.line(format!(
+        "{} {}",
+        "Overall:".bold(),
+        format!("❌ {} OF {} FIXTURES DIFFER", failed.len(), outcomes.len())
+            .red()
+            .bold()
+    ));
+    out.line("");
+    if args.self_test {
+        out.line("These fixtures are served by x402-dev itself, so each mismatch is a bug in");
+        out.line("x402-dev's check, not in your server. Please report it with this output and");
+        out.line("`x402-dev version`. Reproduce one with:");
+    } else {
+        out.line("Either the server does not serve the fixture responses as published in");
+        out.line("x402_core::testing::battery, or check judged them wrongly (compare with");
+        out.line("`x402-dev check --self-test`). Reproduce one with:");
+    }
+    for (_, outcome) in failed {
+        if let Ok(outcome) = outcome {
+            out.line(format!(
+                "  x402-dev check {}",
+                replay_args(args, &outcome.url).join(" ")
+            ));
+        }
+    }
+    std::process::exit(1);
+}
+
+/// Run `x402-dev check` on one fixture and compare its report
+async fn check_fixture(
+    args: &CheckArgs,
+    base_url: &str,
+    fixture: &BatteryFixture,
+    report_dir: &Path,
+) -> Result<Outcome> {
+    let url = format!("{}{}", base_url, fixture.path());
+    let report_path = report_dir.join(format!("{}.json", fixture.name));
+    let _ = std::fs::remove_file(&report_path);
+
+    let exe = std::env::current_exe().context("Failed to locate the x402-dev executable")?;
+    let output = Command::new(exe)
+        .arg("check")
+        .args(replay_args(args, &url))
+        .arg("--report")
+        .arg(&report_path)
+        .env("X402_DEV_NO_UPDATE_CHECK", "1")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .context("Failed to run x402-dev check")?;
+    let code = output.status.code();
+    if !matches!(code, Some(0) | Some(1)) {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!(
+            "check exited with {}: {}",
+            code.map_or("a signal".to_string(), |c| format!("code {}", c)),
+            stderr.trim()
+        ));
+    }
+
+    let report = read_report(&report_path)?;
+    let mut mismatches = fixture.compare(&report);
+    // The exit code must agree with the report
+    if (code == Some(0)) != report.all_passed() {
+        mismatches.push(Mismatch::UnexpectedFailure {
+            check: "exit code".to_string(),
+            evidence: format!(
+                "{:?} with {} failed checks",
+                code,
+                report.total() - report.passed()
+            ),
+        });
+    }
+    Ok(Outcome {
+        url,
+        checks: report.total(),
+        mismatches,
+    })
+}
+
+fn read_report(path: &Path) -> Result<ComplianceReport> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("check wrote no report to {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("Invalid report in {}", path.display()))
+}
+
+/// Arguments of the child check: the URL plus flags that pin the verdict
+/// to the fixtures rather than the local config
+fn replay_args(args: &CheckArgs, url: &str) -> Vec<String> {
+    let mut replay = vec![
+        url.to_string(),
+        "--protocol".to_string(),
+        BATTERY_PROTOCOL.to_string(),
+        "--expected-memo-prefix".to_string(),
+        BATTERY_MEMO_PREFIX.to_string(),
+    ];
+    if args.insecure {
+        replay.push("--insecure".to_string());
+    }
+    for entry in &args.resolve {
+        replay.push("--resolve".to_string());
+        replay.push(entry.to_string());
+    }
+    replay
+}
+
+/// `6/6 checks passed` or `failed as expected: Currency (6 checks)`
+fn expected_verdict(fixture: &BatteryFixture, checks: usize) -> String {
+    match fixture.expected_failures.as_slice() {
+        [] => format!("{}/{} checks passed", checks, checks),
+        failures => format!(
+            "failed as expected: {} ({} checks)",
+            failures.join(", "),
+            checks
+        ),
+    }
+}
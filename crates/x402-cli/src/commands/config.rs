@@ -1,55 +1,487 @@
-use crate::cli::ConfigArgs;
-use crate::config::{load_merged_config_with_sources, CliOverrides};
-use anyhow::Result;
+use crate::cli::{ConfigArgs, ConfigCommands};
+use crate::config::{
+    config_field_sources, display_config_path, load_merged_config, load_merged_config_from,
+    load_merged_config_with_sources, CliOverrides, Config, ConfigInputs, CONFIG_PATH_ENV,
+    PROJECT_CONFIG_FILE,
+};
+use crate::output::Output;
+use anyhow::{bail, Result};
+use serde_json::json;
+use serde_yaml::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::env;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use x402_core::redact::{is_sensitive_field, redact_url, HeaderRedactor, MASK_SET};
+use x402_server::{MatchExplanation, PricingMatcher};
 
 /// Run the config command
-pub async fn run(args: &ConfigArgs) -> Result<()> {
+pub async fn run(args: &ConfigArgs, out: &Output) -> Result<()> {
     match &args.command {
-        crate::cli::ConfigCommands::Show => show_config(args).await,
+        ConfigCommands::Show { reveal } => show_config(args, *reveal, out).await,
+        ConfigCommands::Diff {
+            left,
+            right,
+            left_env,
+            right_env,
+            clean_env,
+            json,
+        } => {
+            let left = Scenario::new(left.as_ref(), left_env, *clean_env, "--left-env")?;
+            let right = Scenario::new(right.as_ref(), right_env, *clean_env, "--right-env")?;
+            diff_config(args, &left, &right, *json, out)
+        }
+        ConfigCommands::ExplainPrice { path, method, json } => {
+            explain_price(args, path, method.as_deref(), *json, out)
+        }
     }
 }
 
-/// Display the merged configuration with sources
-async fn show_config(args: &ConfigArgs) -> Result<()> {
-    // Build CLI overrides from global flags
-    let cli_overrides = CliOverrides {
+/// Exit code of `config diff` when the two sides differ
+const EXIT_DIFFERENT: i32 = 3;
+
+/// CLI overrides from the global config flags
+fn cli_overrides(args: &ConfigArgs) -> CliOverrides {
+    CliOverrides {
         port: args.port,
         solana_rpc: args.solana_rpc.clone(),
         log_level: args.log_level,
         pricing: None, // Story 2.2: Configurable pricing (not implemented yet)
+        recipient: None,
+    }
+}
+
+/// Ask on stderr before printing secrets; any answer but y/yes declines
+fn confirm_reveal() -> Result<bool> {
+    eprint!("Print secrets from the configuration in plain text? [y/N] ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// `path: value` lines for a serialized config section
+///
+/// Sensitive fields print as "•••• (set)" and URLs lose their password and
+/// query values unless `reveal` is set.
+fn flatten(path: &str, value: &Value, reveal: bool, lines: &mut Vec<(String, String)>) {
+    let scalar = |value: &Value| match value {
+        Value::String(s) if !reveal && s.contains("://") => Some(redact_url(s)),
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
     };
 
+    match value {
+        Value::Mapping(map) => {
+            for (key, value) in map {
+                let key = key.as_str().unwrap_or_default();
+                let path = if path.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                if !reveal && is_sensitive_field(key) && !value.is_null() {
+                    lines.push((path, MASK_SET.to_string()));
+                } else {
+                    flatten(&path, value, reveal, lines);
+                }
+            }
+        }
+        Value::Sequence(items) => match items.iter().map(scalar).collect::<Option<Vec<_>>>() {
+            Some(items) => lines.push((path.to_string(), items.join(", "))),
+            None => {
+                for (i, item) in items.iter().enumerate() {
+                    flatten(&format!("{}[{}]", path, i), item, reveal, lines);
+                }
+            }
+        },
+        value => {
+            if let Some(text) = scalar(value) {
+                lines.push((path.to_string(), text));
+            }
+        }
+    }
+}
+
+/// TLS, signing and webhook settings, which hold the config's secrets
+fn integration_lines(config: &Config, reveal: bool) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    flatten(
+        "tls",
+        &serde_yaml::to_value(&config.tls)?,
+        reveal,
+        &mut lines,
+    );
+    flatten(
+        "signing",
+        &serde_yaml::to_value(&config.signing)?,
+        reveal,
+        &mut lines,
+    );
+    for (i, webhook) in config.webhooks.iter().enumerate() {
+        flatten(
+            &format!("webhooks[{}]", i),
+            &serde_yaml::to_value(webhook)?,
+            reveal,
+            &mut lines,
+        );
+    }
+    Ok(lines
+        .into_iter()
+        .map(|(path, value)| format!("{}: {}", path, value))
+        .collect())
+}
+
+/// Display the merged configuration with sources
+async fn show_config(args: &ConfigArgs, reveal: bool, out: &Output) -> Result<()> {
+    if reveal && !confirm_reveal()? {
+        bail!("Secrets not revealed\nFix: Answer y at the prompt, or run config show without --reveal");
+    }
+
+    // Build CLI overrides from global flags
+    let cli_overrides = cli_overrides(args);
+
     // Load merged config with source tracking
     let config_with_sources = load_merged_config_with_sources(Some(&cli_overrides))?;
+    // Every setting, including those not tracked by source
+    let config = load_merged_config(Some(&cli_overrides))?;
 
-    println!("x402-dev Configuration");
-    println!("=====================");
-    println!();
-    println!("Configuration Priority:");
-    println!("  CLI flags > Environment variables > Project config > Global config > Defaults");
-    println!();
-    println!("Current Configuration:");
-    println!(
+    out.status("x402-dev Configuration");
+    out.status("=====================");
+    out.status("");
+    out.line("Configuration Priority:");
+    out.line("  CLI flags > Environment variables > Project config > Global config > Defaults");
+    out.line("");
+    out.line("Current Configuration:");
+    out.line(format!(
         "  port: {} (source: {})",
         config_with_sources.config.port, config_with_sources.port_source
-    );
-    println!(
+    ));
+    let solana_rpc = &config_with_sources.config.solana_rpc;
+    out.line(format!(
         "  solana_rpc: {} (source: {})",
-        config_with_sources.config.solana_rpc, config_with_sources.solana_rpc_source
-    );
-    println!(
+        if reveal {
+            solana_rpc.clone()
+        } else {
+            redact_url(solana_rpc)
+        },
+        config_with_sources.solana_rpc_source
+    ));
+    out.line(format!(
         "  log_level: {} (source: {})",
         config_with_sources.config.log_level, config_with_sources.log_level_source
-    );
-    println!();
-    println!("Config File Locations:");
-    println!("  Global: ~/.x402dev/config.yaml");
-    println!("  Project: ./.x402dev.yaml");
-    println!();
-    println!("Environment Variables:");
-    println!("  X402_DEV_PORT");
-    println!("  X402_DEV_SOLANA_RPC");
-    println!("  X402_DEV_LOG_LEVEL");
+    ));
+    let pricing = &config_with_sources.config.pricing;
+    out.line(format!(
+        "  pricing.default: {} (source: {})",
+        pricing.default, config_with_sources.pricing_source
+    ));
+    out.line(format!(
+        "  pricing.min_amount: {}",
+        pricing
+            .min_amount
+            .map_or_else(|| "none".to_string(), |a| a.to_string())
+    ));
+    out.line(format!(
+        "  pricing.max_amount: {}",
+        pricing
+            .max_amount
+            .map_or_else(|| "none".to_string(), |a| a.to_string())
+    ));
+    out.line(format!(
+        "  pricing.out_of_bounds: {}",
+        pricing.out_of_bounds
+    ));
+    let redactor = HeaderRedactor::new(&config.log_redaction);
+    out.line(format!(
+        "  log_redaction: {}",
+        redactor.names().collect::<Vec<_>>().join(", ")
+    ));
+    let integrations = integration_lines(&config, reveal)?;
+    if !integrations.is_empty() {
+        out.line("");
+        out.line("Integrations:");
+        for line in integrations {
+            out.line(format!("  {}", line));
+        }
+    }
+    out.line("");
+    out.line("Config File Locations:");
+    out.line("  Global: ~/.x402dev/config.yaml");
+    match &config_with_sources.project_file {
+        Some(path) if env::var_os(CONFIG_PATH_ENV).is_some_and(|p| !p.is_empty()) => {
+            out.line(format!(
+                "  Project: {} (from {})",
+                display_config_path(path),
+                CONFIG_PATH_ENV
+            ))
+        }
+        Some(path) => out.line(format!(
+            "  Project: {} (discovered)",
+            display_config_path(path)
+        )),
+        None => out.line(format!(
+            "  Project: none ({} not found up to the git root)",
+            PROJECT_CONFIG_FILE
+        )),
+    }
+    out.line("");
+    out.line("Environment Variables:");
+    out.line(format!("  {}", CONFIG_PATH_ENV));
+    out.line("  X402_DEV_PORT");
+    out.line("  X402_DEV_SOLANA_RPC");
+    out.line("  X402_DEV_LOG_LEVEL");
+
+    Ok(())
+}
+
+/// One side of `config diff`: a project file and environment variables
+struct Scenario {
+    project_file: Option<PathBuf>,
+    env: HashMap<String, String>,
+}
+
+impl Scenario {
+    /// Scenario from `--left`/`--right` and `NAME=VALUE` entries added to
+    /// the current environment (without its `X402_DEV_*` variables with
+    /// `clean_env`)
+    fn new(
+        project_file: Option<&PathBuf>,
+        env_entries: &[String],
+        clean_env: bool,
+        flag: &str,
+    ) -> Result<Self> {
+        let mut env = ConfigInputs::from_process()?.env;
+        if clean_env {
+            env.retain(|name, _| !name.starts_with("X402_DEV_"));
+        }
+        for entry in env_entries {
+            match entry.split_once('=') {
+                Some((name, value)) if !name.is_empty() => {
+                    env.insert(name.to_string(), value.to_string());
+                }
+                _ => bail!(
+                    "Invalid {} value: '{}'\nFix: Use NAME=VALUE, e.g., {} X402_DEV_PORT=8402",
+                    flag,
+                    entry,
+                    flag
+                ),
+            }
+        }
+        if let Some(path) = project_file.filter(|path| !path.is_file()) {
+            bail!(
+                "Config file not found: {}\nFix: Check the path, or leave the flag out to use {}",
+                path.display(),
+                PROJECT_CONFIG_FILE
+            );
+        }
+        Ok(Self {
+            project_file: project_file.cloned(),
+            env,
+        })
+    }
 
+    fn inputs(&self) -> Result<ConfigInputs> {
+        let mut inputs = ConfigInputs::with_env(self.env.clone())?;
+        if let Some(path) = &self.project_file {
+            inputs.project_file = Some(path.clone());
+        }
+        Ok(inputs)
+    }
+}
+
+/// Flattened settings of one side with the source of each
+struct Effective {
+    inputs: ConfigInputs,
+    /// Plain values, compared
+    values: BTreeMap<String, String>,
+    /// Values with secrets masked, printed
+    shown: BTreeMap<String, String>,
+    sources: HashMap<String, String>,
+}
+
+impl Effective {
+    fn load(scenario: &Scenario, cli: &CliOverrides) -> Result<Self> {
+        let inputs = scenario.inputs()?;
+        let config = serde_yaml::to_value(load_merged_config_from(&inputs, Some(cli))?)?;
+        let sources = config_field_sources(&inputs, Some(cli))?;
+        let mut values = Vec::new();
+        let mut shown = Vec::new();
+        flatten("", &config, true, &mut values);
+        flatten("", &config, false, &mut shown);
+        Ok(Self {
+            inputs,
+            values: values.into_iter().collect(),
+            shown: shown.into_iter().collect(),
+            sources,
+        })
+    }
+
+    /// Source of a flattened setting: its own entry, else its top-level field's
+    fn source(&self, path: &str) -> &str {
+        let field = path.split(['.', '[']).next().unwrap_or(path);
+        self.sources
+            .get(path)
+            .or_else(|| self.sources.get(field))
+            .map_or("default", String::as_str)
+    }
+
+    fn entry(&self, path: &str) -> serde_json::Value {
+        json!({
+            "value": self.shown.get(path),
+            "source": self.source(path),
+        })
+    }
+
+    fn describe(&self) -> serde_json::Value {
+        json!({
+            "global_file": self.inputs.global_file.as_deref().map(display_config_path),
+            "project_file": self.inputs.project_file.as_deref().map(display_config_path),
+            "env": self
+                .inputs
+                .env
+                .keys()
+                .filter(|name| name.starts_with("X402_DEV_"))
+                .collect::<BTreeSet<_>>(),
+        })
+    }
+}
+
+/// Pricing rules matching `path` under the merged configuration
+fn explain_price(
+    args: &ConfigArgs,
+    path: &str,
+    method: Option<&str>,
+    json: bool,
+    out: &Output,
+) -> Result<()> {
+    if !path.starts_with('/') {
+        bail!(
+            "Path '{}' must start with '/'\nFix: Pass the request path, e.g. /api/data",
+            path
+        );
+    }
+    let config = load_merged_config(Some(&cli_overrides(args)))?;
+    let matcher = PricingMatcher::new((&config.pricing).into());
+    let explanation = match method {
+        Some(method) => matcher.explain_request(&method.to_uppercase(), path),
+        None => matcher.explain(path),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&explanation)?);
+    } else {
+        print!("{}", out.text(&render_price_explanation(&explanation)));
+    }
+    Ok(())
+}
+
+/// Candidates with the winner marked `→`, then the invoice amount
+fn render_price_explanation(explanation: &MatchExplanation) -> String {
+    let request = match &explanation.method {
+        Some(method) => format!("{} {}", method, explanation.path),
+        None => explanation.path.clone(),
+    };
+    let width = explanation
+        .candidates
+        .iter()
+        .map(|c| c.pattern.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut out = format!("Pricing rules for {} (highest priority first)\n\n", request);
+    for candidate in &explanation.candidates {
+        out.push_str(&format!(
+            "{} {:<width$}  {:<7}  {}\n",
+            if candidate.winner { "→" } else { " " },
+            candidate.pattern,
+            candidate.kind.to_string(),
+            candidate.price,
+            width = width
+        ));
+    }
+    out.push('\n');
+    match explanation.invoice_amount {
+        Some(amount) if amount == explanation.price => {
+            out.push_str(&format!("Invoice amount: {}\n", amount))
+        }
+        Some(amount) => out.push_str(&format!(
+            "Invoice amount: {} (price {} clamped to min_amount/max_amount)\n",
+            amount, explanation.price
+        )),
+        None => out.push_str(&format!(
+            "Invoice amount: none (price {} is outside min_amount/max_amount, out_of_bounds: error)\n",
+            explanation.price
+        )),
+    }
+    out
+}
+
+/// Compare the merged configuration of two scenarios field by field
+fn diff_config(
+    args: &ConfigArgs,
+    left: &Scenario,
+    right: &Scenario,
+    json: bool,
+    out: &Output,
+) -> Result<()> {
+    let cli = cli_overrides(args);
+    let left = Effective::load(left, &cli)?;
+    let right = Effective::load(right, &cli)?;
+
+    let paths: BTreeSet<&String> = left.values.keys().chain(right.values.keys()).collect();
+    let differences: Vec<&String> = paths
+        .into_iter()
+        .filter(|path| left.values.get(*path) != right.values.get(*path))
+        .collect();
+
+    if json {
+        let entries: Vec<serde_json::Value> = differences
+            .iter()
+            .map(|path| {
+                json!({
+                    "field": path,
+                    "left": left.entry(path),
+                    "right": right.entry(path),
+                })
+            })
+            .collect();
+        let report = json!({
+            "identical": differences.is_empty(),
+            "left": left.describe(),
+            "right": right.describe(),
+            "differences": entries,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else if differences.is_empty() {
+        out.line("Configurations are identical");
+    } else {
+        for path in &differences {
+            out.line(format!("{}:", path));
+            for (side, effective) in [("left", &left), ("right", &right)] {
+                out.line(format!(
+                    "  {:<6} {} (source: {})",
+                    format!("{}:", side),
+                    effective
+                        .shown
+                        .get(*path)
+                        .map_or("(not set)", String::as_str),
+                    effective.source(path)
+                ));
+            }
+        }
+        out.line("");
+        out.line(format!(
+            "{} setting{} differ",
+            differences.len(),
+            if differences.len() == 1 { "" } else { "s" }
+        ));
+    }
+
+    if !differences.is_empty() {
+        std::process::exit(EXIT_DIFFERENT);
+    }
     Ok(())
 }
@@ -0,0 +1,52 @@
+// x402-dev convert command - Translate files between YAML and JSON
+//
+// Works on any of the user-facing files (project config, policy files, test
+// suites, scenario bundles) since they share one schema in both formats.
+// The document is translated as a whole, so the command needs no knowledge
+// of which kind of file it is; run the file's own validate command after.
+
+use crate::cli::ConvertArgs;
+use anyhow::{anyhow, bail, Context, Result};
+use colored::Colorize;
+use x402_core::format::{convert, DetectedFormat, FileFormat};
+
+/// Run the convert command
+pub fn run(args: &ConvertArgs) -> Result<()> {
+    let content = std::fs::read_to_string(&args.input)
+        .with_context(|| format!("Failed to read {}", args.input.display()))?;
+    let from = match args.from {
+        Some(format) => DetectedFormat::given(format),
+        None => DetectedFormat::detect(Some(&args.input), &content),
+    };
+    let Some(to) = args.to.or_else(|| FileFormat::from_path(&args.output)) else {
+        bail!(
+            "Cannot tell the output format of {}\nFix: Use a .yaml, .yml or .json extension, or pass --to yaml|json",
+            args.output.display()
+        );
+    };
+
+    let text =
+        convert(&content, from, to).map_err(|e| anyhow!("{}: {:#}", args.input.display(), e))?;
+    crate::safe_write::write(&args.output, &text)
+        .with_context(|| format!("Failed to write {}", args.output.display()))?;
+
+    println!(
+        "{} Converted {} ({}) -> {} ({})",
+        "✓".green().bold(),
+        args.input.display(),
+        from.format,
+        args.output.display(),
+        to
+    );
+    if from.format == FileFormat::Yaml && has_comments(&content) {
+        println!("  Comments in the input are not carried over");
+    }
+    Ok(())
+}
+
+/// True if a YAML document has a line that is only a comment
+///
+/// Trailing comments are missed; this only decides whether to print a note.
+fn has_comments(yaml: &str) -> bool {
+    yaml.lines().any(|line| line.trim_start().starts_with('#'))
+}
@@ -0,0 +1,200 @@
+// x402-dev curl command - The two-phase payment flow in one command
+//
+// GETs a path, shows the parsed invoice, retries with the invoice memo as
+// X-Payment-Proof and shows the final response. Each step is checked against
+// what the flow expects; a deviation is printed and then exits non-zero.
+
+use crate::cli::CurlArgs;
+use crate::config::{load_merged_config, resolve_overrides, SimulationMode};
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use x402_core::testing::{HttpOptions, InvoiceHeader, PAYMENT_PROOF_HEADER, REQUIRED_FIELDS};
+
+/// One request of the flow and the response to it
+#[derive(Debug, Serialize)]
+struct Exchange {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payment_proof: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    simulation_mode: Option<SimulationMode>,
+    status: u16,
+    /// JSON bodies as JSON, anything else as a string
+    body: serde_json::Value,
+}
+
+/// `--json` report of the whole flow
+#[derive(Debug, Default, Serialize)]
+struct Flow {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    invoice: Option<BTreeMap<String, String>>,
+    steps: Vec<Exchange>,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Run the curl command
+pub async fn run(args: &CurlArgs) -> Result<()> {
+    let url = request_url(args)?;
+    let options = HttpOptions {
+        resolve: resolve_overrides(&args.resolve)?,
+        ..HttpOptions::default()
+    };
+    let client = options.build_client()?;
+
+    let mut flow = Flow::default();
+    let result = drive(&client, &url, args, &mut flow).await;
+    if args.json {
+        flow.ok = result.is_ok();
+        flow.error = result.as_ref().err().map(|e| e.to_string());
+        println!("{}", serde_json::to_string_pretty(&flow)?);
+    }
+    result
+}
+
+/// `path` on `--url`, or on the configured mock server
+fn request_url(args: &CurlArgs) -> Result<String> {
+    let base = match &args.url {
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None => format!("http://127.0.0.1:{}", load_merged_config(None)?.port),
+    };
+    if args.path.starts_with('/') {
+        Ok(format!("{}{}", base, args.path))
+    } else {
+        Ok(format!("{}/{}", base, args.path))
+    }
+}
+
+/// Run the steps, recording each in `flow` and printing it unless `--json`
+async fn drive(client: &Client, url: &str, args: &CurlArgs, flow: &mut Flow) -> Result<()> {
+    let text = !args.json;
+
+    // Step 1: the unpaid request must be answered with an invoice
+    if text {
+        println!("{} GET {}", "→".cyan(), url);
+    }
+    let response = client.get(url).send().await.with_context(|| {
+        format!(
+            "Failed to connect to {}\nFix: Start the server with `x402-dev mock` or pass --url",
+            url
+        )
+    })?;
+    let status = response.status();
+    let invoice = response
+        .headers()
+        .get("www-authenticate")
+        .map(|v| v.to_str().map(str::to_string));
+    let body = body_value(&response.bytes().await.unwrap_or_default());
+    if text {
+        println!("{} {}", "←".cyan(), status);
+    }
+    flow.steps.push(Exchange {
+        url: url.to_string(),
+        payment_proof: None,
+        simulation_mode: None,
+        status: status.as_u16(),
+        body,
+    });
+
+    if status != StatusCode::PAYMENT_REQUIRED {
+        bail!(
+            "Expected 402 Payment Required from {}, got {}\nFix: Check that the path is priced by the server",
+            url,
+            status
+        );
+    }
+    let Some(Ok(invoice)) = invoice else {
+        bail!("The 402 response has no readable WWW-Authenticate invoice");
+    };
+    let invoice = InvoiceHeader::parse(&invoice).context("Failed to parse the invoice")?;
+    flow.invoice = Some(invoice.to_map().into_iter().collect());
+    if text {
+        println!();
+        println!("{}", "Invoice:".bold());
+        for field in &invoice.fields {
+            println!("   {:<10} {}", field.name, field.value);
+        }
+    }
+    let missing: Vec<&str> = REQUIRED_FIELDS
+        .into_iter()
+        .filter(|field| invoice.get(field).is_none())
+        .collect();
+    if !missing.is_empty() {
+        bail!("The invoice is missing {}", missing.join(", "));
+    }
+    if args.no_pay {
+        return Ok(());
+    }
+
+    // Step 2: retry with the memo as proof and expect the --mode outcome
+    let proof = invoice.payment_proof().unwrap_or_default();
+    if text {
+        println!();
+        println!(
+            "{} GET {} ({}: {})",
+            "→".cyan(),
+            url,
+            PAYMENT_PROOF_HEADER,
+            proof
+        );
+    }
+    let mut request = client.get(url).header(PAYMENT_PROOF_HEADER, proof);
+    if let Some(mode) = args.mode {
+        request = request.header("X-Simulation-Mode", mode.to_string());
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Payment request to {} failed", url))?;
+    let status = response.status();
+    let body = body_value(&response.bytes().await.unwrap_or_default());
+    if text {
+        println!("{} {}", "←".cyan(), status);
+        match &body {
+            serde_json::Value::String(body) => println!("{}", body),
+            body => println!("{}", serde_json::to_string_pretty(body)?),
+        }
+    }
+    flow.steps.push(Exchange {
+        url: url.to_string(),
+        payment_proof: Some(proof.to_string()),
+        simulation_mode: args.mode,
+        status: status.as_u16(),
+        body,
+    });
+
+    let expected = args.mode.unwrap_or_default();
+    let (matches, outcome) = match expected {
+        SimulationMode::Success => (status.is_success(), "2xx"),
+        SimulationMode::Failure => (status == StatusCode::PAYMENT_REQUIRED, "402"),
+        SimulationMode::Timeout => (status == StatusCode::REQUEST_TIMEOUT, "408"),
+    };
+    if !matches {
+        bail!(
+            "Expected the payment to end in {} ({}), got {}\nFix: Pass --mode to expect another outcome",
+            expected,
+            outcome,
+            status
+        );
+    }
+    if text {
+        println!();
+        println!(
+            "{} Payment flow completed ({}: {})",
+            "✅".green(),
+            expected,
+            status.as_u16()
+        );
+    }
+    Ok(())
+}
+
+/// Parse a body as JSON, keeping other bodies as text
+fn body_value(bytes: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(bytes)
+        .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(bytes).into_owned()))
+}
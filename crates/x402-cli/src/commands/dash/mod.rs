@@ -0,0 +1,206 @@
+// x402-dev mock dash - Live terminal dashboard for a running mock server
+//
+// Reads the server's capabilities from /health once, then polls
+// /__admin/stats, /__admin/events and /__admin/simulation-mode every
+// --refresh-ms and redraws. Panels an older server cannot feed are shown
+// as unavailable, and poll errors are reported in the footer rather than
+// ending the session, so the dashboard survives server restarts.
+
+mod model;
+mod ui;
+
+use anyhow::{bail, Context, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::DefaultTerminal;
+use std::io::{self, IsTerminal};
+use std::time::{Duration, Instant};
+use x402_domain::AmountFormatter;
+use x402_server::events::ADMIN_EVENTS_PATH;
+use x402_server::server::{ADMIN_SIMULATION_MODE_PATH, HEALTH_PATH};
+use x402_server::stats::ADMIN_STATS_PATH;
+use x402_server::{EventsPage, StatsSnapshot};
+
+use model::{Capabilities, Dashboard};
+
+/// Per-request timeout for admin calls
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Run the dashboard until the user quits
+pub async fn run(port: u16, refresh_ms: u64, amounts: AmountFormatter) -> Result<()> {
+    if !io::stdout().is_terminal() || !io::stdin().is_terminal() {
+        bail!("x402-dev mock dash needs an interactive terminal\nFix: Run it in a terminal, or use `x402-dev mock stats --json` for scripted output");
+    }
+
+    let client = AdminClient::new(port)?;
+    let health = client.health().await.with_context(|| {
+        format!(
+            "No mock server answering on port {}\nFix: Start the server with `x402-dev mock` or pass --port",
+            port
+        )
+    })?;
+    let mut dash = Dashboard::new(Capabilities::from_health(&health));
+    dash.amounts = amounts;
+
+    let mut terminal = ratatui::try_init().context("Failed to set up the terminal")?;
+    let result = event_loop(&mut terminal, &client, &mut dash, port, refresh_ms).await;
+    ratatui::restore();
+    result
+}
+
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    client: &AdminClient,
+    dash: &mut Dashboard,
+    port: u16,
+    refresh_ms: u64,
+) -> Result<()> {
+    let refresh = Duration::from_millis(refresh_ms);
+    let mut next_poll = Instant::now();
+    loop {
+        if Instant::now() >= next_poll {
+            poll(client, dash).await;
+            next_poll = Instant::now() + refresh;
+        }
+        terminal.draw(|frame| ui::draw(frame, dash, port, refresh_ms))?;
+
+        // Wait for a key until the next poll is due; a resize just redraws
+        let timeout = next_poll.saturating_duration_since(Instant::now());
+        let input = tokio::task::spawn_blocking(move || -> io::Result<Option<Event>> {
+            if event::poll(timeout)? {
+                event::read().map(Some)
+            } else {
+                Ok(None)
+            }
+        })
+        .await??;
+
+        let Some(Event::Key(key)) = input else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Char('p') | KeyCode::Char(' ') => dash.toggle_pause(),
+            KeyCode::Char('m') => switch_mode(client, dash).await,
+            _ => {}
+        }
+    }
+}
+
+/// Refresh everything the server supports, keeping the last error
+async fn poll(client: &AdminClient, dash: &mut Dashboard) {
+    let mut errors = Vec::new();
+    if dash.capabilities.stats {
+        match client.stats().await {
+            Ok(snapshot) => dash.record_stats(&snapshot),
+            Err(e) => errors.push(e),
+        }
+    }
+    if dash.capabilities.events {
+        match client.events(dash.cursor).await {
+            Ok(page) => dash.ingest(page),
+            Err(e) => errors.push(e),
+        }
+    }
+    if dash.capabilities.simulation_mode {
+        match client.mode().await {
+            Ok(mode) => dash.mode = Some(mode),
+            Err(e) => errors.push(e),
+        }
+    }
+    // Key feedback stays until the next error-free poll replaces it
+    if let Some(e) = errors.first() {
+        dash.message = Some(format!("Server unreachable: {:#}", e));
+    } else if dash
+        .message
+        .as_deref()
+        .is_some_and(|m| m.starts_with("Server unreachable"))
+    {
+        dash.message = None;
+    }
+}
+
+async fn switch_mode(client: &AdminClient, dash: &mut Dashboard) {
+    if !dash.capabilities.simulation_mode {
+        dash.message =
+            Some("This server cannot switch simulation modes (upgrade x402-dev)".to_string());
+        return;
+    }
+    let next = dash.next_mode();
+    dash.message = Some(match client.set_mode(next).await {
+        Ok(mode) => {
+            dash.mode = Some(mode.clone());
+            format!("Simulation mode: {}", mode)
+        }
+        Err(e) => format!("Failed to switch simulation mode: {:#}", e),
+    });
+}
+
+/// HTTP client for the admin endpoints of a server on 127.0.0.1
+struct AdminClient {
+    http: reqwest::Client,
+    base: String,
+}
+
+impl AdminClient {
+    fn new(port: u16) -> Result<Self> {
+        Ok(Self {
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()?,
+            base: format!("http://127.0.0.1:{}", port),
+        })
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
+        Ok(self
+            .http
+            .get(format!("{}{}", self.base, path))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    async fn health(&self) -> Result<serde_json::Value> {
+        self.get(HEALTH_PATH).await
+    }
+
+    async fn stats(&self) -> Result<StatsSnapshot> {
+        self.get(ADMIN_STATS_PATH).await
+    }
+
+    async fn events(&self, after: u64) -> Result<EventsPage> {
+        self.get(&format!("{}?after={}", ADMIN_EVENTS_PATH, after))
+            .await
+    }
+
+    async fn mode(&self) -> Result<String> {
+        let body: serde_json::Value = self.get(ADMIN_SIMULATION_MODE_PATH).await?;
+        mode_of(&body)
+    }
+
+    async fn set_mode(&self, mode: &str) -> Result<String> {
+        let body: serde_json::Value = self
+            .http
+            .post(format!("{}{}", self.base, ADMIN_SIMULATION_MODE_PATH))
+            .json(&serde_json::json!({ "mode": mode }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        mode_of(&body)
+    }
+}
+
+fn mode_of(body: &serde_json::Value) -> Result<String> {
+    body["mode"]
+        .as_str()
+        .map(str::to_string)
+        .context("Simulation mode response has no `mode`")
+}
@@ -0,0 +1,521 @@
+// Dashboard data model
+//
+// Polled stats and events are folded into fixed-size ring buffers that the
+// widgets draw from, so panels stay bounded however long the dashboard
+// runs. Nothing here touches the terminal.
+
+use std::collections::VecDeque;
+use x402_domain::AmountFormatter;
+use x402_server::{EventType, EventsPage, LoggedEvent, PathStatsSnapshot, StatsSnapshot};
+
+/// Request rate samples kept for the sparkline (one per poll)
+pub const RATE_SAMPLES: usize = 120;
+
+/// Rows of the recent invoices table
+pub const INVOICE_ROWS: usize = 50;
+
+/// Entries of the policy denial feed
+pub const DENIAL_ROWS: usize = 50;
+
+/// Events held while the stream is paused; older ones are dropped
+pub const HELD_EVENTS: usize = 1000;
+
+/// Simulation modes in the order the `m` key cycles through them
+pub const SIMULATION_MODES: [&str; 3] = ["success", "failure", "timeout"];
+
+/// Fixed-capacity FIFO: pushing onto a full ring drops the oldest item
+#[derive(Debug, Clone)]
+pub struct Ring<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> Ring<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Append an item, returning the one dropped to make room
+    pub fn push(&mut self, item: T) -> Option<T> {
+        let dropped = if self.items.len() == self.capacity {
+            self.items.pop_front()
+        } else {
+            None
+        };
+        self.items.push_back(item);
+        dropped
+    }
+
+    /// Oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// Newest item matching `predicate`
+    fn find_last_mut(&mut self, predicate: impl Fn(&T) -> bool) -> Option<&mut T> {
+        self.items.iter_mut().rev().find(|item| predicate(item))
+    }
+}
+
+/// Admin endpoints the server advertises in `/health`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub stats: bool,
+    pub events: bool,
+    pub simulation_mode: bool,
+}
+
+impl Capabilities {
+    /// Read the `capabilities` list of a `/health` body
+    ///
+    /// Servers from before the list existed only serve `/__admin/stats`.
+    pub fn from_health(health: &serde_json::Value) -> Self {
+        let Some(list) = health.get("capabilities").and_then(|c| c.as_array()) else {
+            return Self {
+                stats: true,
+                events: false,
+                simulation_mode: false,
+            };
+        };
+        let has = |name: &str| list.iter().any(|c| c.as_str() == Some(name));
+        Self {
+            stats: has("stats"),
+            events: has("events"),
+            simulation_mode: has("simulation_mode"),
+        }
+    }
+}
+
+/// Where an invoice stands, from the events seen so far
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvoiceStatus {
+    Issued,
+    Paid,
+    Failed,
+    TimedOut,
+    /// A payment proof that was already used, refused by the server
+    Replayed,
+}
+
+impl InvoiceStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            InvoiceStatus::Issued => "issued",
+            InvoiceStatus::Paid => "paid",
+            InvoiceStatus::Failed => "failed",
+            InvoiceStatus::TimedOut => "timeout",
+            InvoiceStatus::Replayed => "replay",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvoiceRow {
+    pub memo: String,
+    pub path: String,
+    pub amount: f64,
+    pub status: InvoiceStatus,
+    /// Event time as sent by the server (RFC 3339)
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DenialRow {
+    pub path: String,
+    pub reason: String,
+    /// A 503 from a path in maintenance rather than a policy denial
+    pub maintenance: bool,
+    pub timestamp: String,
+}
+
+/// Everything the dashboard shows
+#[derive(Debug)]
+pub struct Dashboard {
+    pub capabilities: Capabilities,
+    /// Requests handled between consecutive stats polls
+    pub rates: Ring<u64>,
+    pub invoices: Ring<InvoiceRow>,
+    pub denials: Ring<DenialRow>,
+    /// Latest totals row of `/__admin/stats`
+    pub totals: Option<PathStatsSnapshot>,
+    /// Server simulation mode, when the server reports it
+    pub mode: Option<String>,
+    pub paused: bool,
+    /// Events the server or the pause buffer dropped before they were shown
+    pub missed: u64,
+    /// Sequence number to poll events after
+    pub cursor: u64,
+    /// Last poll error or key feedback for the footer
+    pub message: Option<String>,
+    /// How invoice amounts are shown
+    pub amounts: AmountFormatter,
+    held: VecDeque<LoggedEvent>,
+    last_requests: Option<u64>,
+    polled_events: bool,
+}
+
+impl Dashboard {
+    pub fn new(capabilities: Capabilities) -> Self {
+        Self {
+            capabilities,
+            rates: Ring::new(RATE_SAMPLES),
+            invoices: Ring::new(INVOICE_ROWS),
+            denials: Ring::new(DENIAL_ROWS),
+            totals: None,
+            mode: None,
+            paused: false,
+            missed: 0,
+            cursor: 0,
+            message: None,
+            amounts: AmountFormatter::default(),
+            held: VecDeque::new(),
+            last_requests: None,
+            polled_events: false,
+        }
+    }
+
+    /// Add a rate sample: requests since the previous snapshot
+    ///
+    /// The first snapshot only sets the baseline. A total lower than the
+    /// last one means the stats were reset, so it counts from zero.
+    pub fn record_stats(&mut self, snapshot: &StatsSnapshot) {
+        let requests = snapshot.totals.requests;
+        if let Some(last) = self.last_requests {
+            let delta = if requests >= last {
+                requests - last
+            } else {
+                requests
+            };
+            self.rates.push(delta);
+        }
+        self.last_requests = Some(requests);
+        self.totals = Some(snapshot.totals.clone());
+    }
+
+    /// Take a page of `/__admin/events`, holding it while paused
+    ///
+    /// Events the server dropped before the first poll are history, not
+    /// misses, so they are not counted.
+    pub fn ingest(&mut self, page: EventsPage) {
+        if self.polled_events {
+            self.missed += page.missed;
+        }
+        self.polled_events = true;
+        // A sequence that went backwards means the server restarted: start over
+        self.cursor = if page.last_seq < self.cursor {
+            0
+        } else {
+            page.last_seq
+        };
+
+        if self.paused {
+            for event in page.events {
+                if self.held.len() == HELD_EVENTS {
+                    self.held.pop_front();
+                    self.missed += 1;
+                }
+                self.held.push_back(event);
+            }
+        } else {
+            for event in page.events {
+                self.apply(event);
+            }
+        }
+    }
+
+    /// Events waiting for resume
+    pub fn held(&self) -> usize {
+        self.held.len()
+    }
+
+    /// Pause or resume the event stream; resuming applies held events
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused {
+            while let Some(event) = self.held.pop_front() {
+                self.apply(event);
+            }
+        }
+    }
+
+    /// Mode after the current one in [`SIMULATION_MODES`]
+    pub fn next_mode(&self) -> &'static str {
+        let current = self
+            .mode
+            .as_deref()
+            .and_then(|mode| SIMULATION_MODES.iter().position(|m| *m == mode));
+        match current {
+            Some(i) => SIMULATION_MODES[(i + 1) % SIMULATION_MODES.len()],
+            None => SIMULATION_MODES[0],
+        }
+    }
+
+    fn apply(&mut self, logged: LoggedEvent) {
+        let event = logged.event;
+        match event.event_type {
+            EventType::InvoiceIssued => {
+                self.invoices.push(InvoiceRow {
+                    memo: event.memo.unwrap_or_default(),
+                    path: event.path,
+                    amount: event.amount,
+                    status: InvoiceStatus::Issued,
+                    timestamp: event.timestamp,
+                });
+            }
+            EventType::PaymentVerified | EventType::PaymentFailed => {
+                let status = match (event.event_type, event.outcome.as_str()) {
+                    (EventType::PaymentVerified, _) => InvoiceStatus::Paid,
+                    (_, "timeout") => InvoiceStatus::TimedOut,
+                    (_, "replayed") => InvoiceStatus::Replayed,
+                    _ => InvoiceStatus::Failed,
+                };
+                let memo = event.memo.unwrap_or_default();
+                match self.invoices.find_last_mut(|row| row.memo == memo) {
+                    Some(row) => row.status = status,
+                    // Invoiced before the dashboard started, or a made-up proof
+                    None => {
+                        self.invoices.push(InvoiceRow {
+                            memo,
+                            path: event.path,
+                            amount: event.amount,
+                            status,
+                            timestamp: event.timestamp,
+                        });
+                    }
+                }
+            }
+            EventType::PolicyDenied => {
+                self.denials.push(DenialRow {
+                    path: event.path,
+                    reason: event
+                        .reason
+                        .unwrap_or_else(|| "denied by policy".to_string()),
+                    maintenance: false,
+                    timestamp: event.timestamp,
+                });
+            }
+            EventType::Maintenance => {
+                self.denials.push(DenialRow {
+                    path: event.path,
+                    reason: event
+                        .reason
+                        .unwrap_or_else(|| "under maintenance".to_string()),
+                    maintenance: true,
+                    timestamp: event.timestamp,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x402_server::WebhookEvent;
+
+    const ALL: Capabilities = Capabilities {
+        stats: true,
+        events: true,
+        simulation_mode: true,
+    };
+
+    fn logged(seq: u64, event: WebhookEvent) -> LoggedEvent {
+        LoggedEvent { seq, event }
+    }
+
+    fn issued(seq: u64, memo: &str) -> LoggedEvent {
+        let event = WebhookEvent::new(
+            EventType::InvoiceIssued,
+            "req",
+            Some(memo.to_string()),
+            "/api/data",
+            0.01,
+            "payment_required",
+        );
+        logged(seq, event)
+    }
+
+    fn paid(seq: u64, memo: &str, event_type: EventType, outcome: &str) -> LoggedEvent {
+        let event = WebhookEvent::new(
+            event_type,
+            "req",
+            Some(memo.to_string()),
+            "/api/data",
+            0.01,
+            outcome,
+        );
+        logged(seq, event)
+    }
+
+    fn page(events: Vec<LoggedEvent>, missed: u64) -> EventsPage {
+        let last_seq = events.last().map_or(0, |e| e.seq);
+        EventsPage {
+            events,
+            last_seq,
+            missed,
+        }
+    }
+
+    fn snapshot(requests: u64) -> StatsSnapshot {
+        let mut snapshot = x402_server::StatsRegistry::new().snapshot();
+        snapshot.totals.requests = requests;
+        snapshot
+    }
+
+    #[test]
+    fn test_ring_drops_oldest() {
+        let mut ring = Ring::new(3);
+        assert_eq!(ring.push(1), None);
+        ring.push(2);
+        ring.push(3);
+        assert_eq!(ring.push(4), Some(1));
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(ring.last(), Some(&4));
+    }
+
+    #[test]
+    fn test_events_update_invoice_rows_and_denials() {
+        let mut dash = Dashboard::new(ALL);
+        let denied = WebhookEvent::new(
+            EventType::PolicyDenied,
+            "req",
+            None,
+            "/api/admin",
+            0.05,
+            "denied",
+        )
+        .with_reason("Blocked agent");
+        let maintenance = WebhookEvent::new(
+            EventType::Maintenance,
+            "req",
+            None,
+            "/api/data",
+            0.01,
+            "maintenance",
+        );
+        dash.ingest(page(
+            vec![
+                issued(1, "memo-a"),
+                issued(2, "memo-b"),
+                paid(3, "memo-a", EventType::PaymentVerified, "success"),
+                paid(4, "memo-b", EventType::PaymentFailed, "timeout"),
+                paid(5, "memo-x", EventType::PaymentFailed, "failure"),
+                paid(6, "memo-a", EventType::PaymentFailed, "replayed"),
+                logged(7, denied),
+                logged(8, maintenance),
+            ],
+            0,
+        ));
+
+        let rows: Vec<(&str, InvoiceStatus)> = dash
+            .invoices
+            .iter()
+            .map(|row| (row.memo.as_str(), row.status))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("memo-a", InvoiceStatus::Replayed),
+                ("memo-b", InvoiceStatus::TimedOut),
+                ("memo-x", InvoiceStatus::Failed),
+            ]
+        );
+        let denials: Vec<(&str, &str, bool)> = dash
+            .denials
+            .iter()
+            .map(|row| (row.path.as_str(), row.reason.as_str(), row.maintenance))
+            .collect();
+        assert_eq!(
+            denials,
+            [
+                ("/api/admin", "Blocked agent", false),
+                ("/api/data", "under maintenance", true),
+            ]
+        );
+        assert_eq!(dash.cursor, 8);
+    }
+
+    #[test]
+    fn test_invoice_table_is_bounded() {
+        let mut dash = Dashboard::new(ALL);
+        let events = (1..=INVOICE_ROWS as u64 + 5)
+            .map(|seq| issued(seq, &format!("memo-{}", seq)))
+            .collect();
+        dash.ingest(page(events, 0));
+        assert_eq!(dash.invoices.len(), INVOICE_ROWS);
+        assert_eq!(dash.invoices.iter().next().unwrap().memo, "memo-6");
+    }
+
+    #[test]
+    fn test_pause_holds_events_until_resume() {
+        let mut dash = Dashboard::new(ALL);
+        dash.toggle_pause();
+        dash.ingest(page(vec![issued(1, "memo-a"), issued(2, "memo-b")], 0));
+        assert!(dash.invoices.is_empty());
+        assert_eq!(dash.held(), 2);
+        assert_eq!(dash.cursor, 2);
+
+        dash.toggle_pause();
+        assert_eq!(dash.held(), 0);
+        assert_eq!(dash.invoices.len(), 2);
+    }
+
+    #[test]
+    fn test_missed_events_count_after_the_first_poll() {
+        let mut dash = Dashboard::new(ALL);
+        dash.ingest(page(vec![issued(40, "memo-a")], 39));
+        assert_eq!(dash.missed, 0);
+        dash.ingest(page(vec![issued(50, "memo-b")], 9));
+        assert_eq!(dash.missed, 9);
+        assert_eq!(dash.cursor, 50);
+
+        // Restarted server
+        dash.ingest(page(vec![], 0));
+        assert_eq!(dash.cursor, 0);
+    }
+
+    #[test]
+    fn test_rate_samples_follow_request_totals() {
+        let mut dash = Dashboard::new(ALL);
+        dash.record_stats(&snapshot(10));
+        assert!(dash.rates.is_empty());
+        dash.record_stats(&snapshot(14));
+        dash.record_stats(&snapshot(14));
+        // Stats reset between polls
+        dash.record_stats(&snapshot(3));
+        assert_eq!(dash.rates.iter().copied().collect::<Vec<_>>(), [4, 0, 3]);
+        assert_eq!(dash.totals.as_ref().unwrap().requests, 3);
+    }
+
+    #[test]
+    fn test_capabilities_and_mode_cycle() {
+        let old = Capabilities::from_health(&serde_json::json!({"status": "ok"}));
+        assert!(old.stats && !old.events && !old.simulation_mode);
+        let new = Capabilities::from_health(&serde_json::json!({
+            "capabilities": ["stats", "events", "simulation_mode"]
+        }));
+        assert_eq!(new, ALL);
+
+        let mut dash = Dashboard::new(new);
+        assert_eq!(dash.next_mode(), "success");
+        dash.mode = Some("failure".to_string());
+        assert_eq!(dash.next_mode(), "timeout");
+        dash.mode = Some("timeout".to_string());
+        assert_eq!(dash.next_mode(), "success");
+    }
+}
@@ -0,0 +1,203 @@
+// Dashboard drawing
+//
+// Lays the panels out for the current terminal size on every frame, so
+// resizes only need a redraw. Panels the server cannot feed say so.
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Row, Sparkline, Table};
+use ratatui::Frame;
+
+use x402_domain::Decimal;
+
+use super::model::{Dashboard, InvoiceStatus};
+
+pub fn draw(frame: &mut Frame, dash: &Dashboard, port: u16, refresh_ms: u64) {
+    let [header, rate, panels, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(7),
+        Constraint::Min(6),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [invoices, denials] =
+        Layout::horizontal([Constraint::Percentage(62), Constraint::Percentage(38)]).areas(panels);
+
+    frame.render_widget(header_line(dash, port), header);
+    draw_rate(frame, dash, rate, refresh_ms);
+    draw_invoices(frame, dash, invoices);
+    draw_denials(frame, dash, denials);
+    frame.render_widget(footer_line(dash), footer);
+}
+
+fn header_line(dash: &Dashboard, port: u16) -> Paragraph<'static> {
+    let mut spans = vec![
+        Span::from(format!(" x402-dev mock dash  127.0.0.1:{}  ", port)).bold(),
+        Span::from("mode: "),
+    ];
+    spans.push(match &dash.mode {
+        Some(mode) => Span::styled(mode.to_uppercase(), mode_style(mode)),
+        None => Span::from("unknown").dark_gray(),
+    });
+    if let Some(totals) = &dash.totals {
+        spans.push(Span::from(format!(
+            "  requests: {}  verified: {}  denied: {}",
+            totals.requests, totals.verified, totals.policy_denied
+        )));
+        if totals.maintenance > 0 {
+            spans.push(Span::from(format!("  maintenance: {}", totals.maintenance)).yellow());
+        }
+    }
+    if dash.paused {
+        spans.push(
+            Span::from(format!("  PAUSED ({} held)", dash.held()))
+                .yellow()
+                .bold(),
+        );
+    }
+    if dash.missed > 0 {
+        spans.push(Span::from(format!("  missed: {}", dash.missed)).yellow());
+    }
+    Paragraph::new(Line::from(spans))
+}
+
+fn mode_style(mode: &str) -> Style {
+    let color = match mode {
+        "success" => Color::Green,
+        "failure" => Color::Red,
+        _ => Color::Yellow,
+    };
+    Style::default().fg(color).add_modifier(Modifier::BOLD)
+}
+
+fn draw_rate(frame: &mut Frame, dash: &Dashboard, area: Rect, refresh_ms: u64) {
+    let per_second = |count: u64| count as f64 * 1000.0 / refresh_ms as f64;
+    let title = match dash.rates.last() {
+        Some(last) => format!(" Requests/s: {:.1} ", per_second(*last)),
+        None => " Requests/s ".to_string(),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    if !dash.capabilities.stats {
+        frame.render_widget(unsupported("stats").block(block), area);
+        return;
+    }
+
+    // Newest samples on the right, as many as fit
+    let width = area.width.saturating_sub(2) as usize;
+    let skip = dash.rates.len().saturating_sub(width);
+    let data: Vec<u64> = dash.rates.iter().skip(skip).copied().collect();
+    frame.render_widget(
+        Sparkline::default()
+            .block(block)
+            .data(&data)
+            .style(Style::default().fg(Color::Cyan)),
+        area,
+    );
+}
+
+fn draw_invoices(frame: &mut Frame, dash: &Dashboard, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Recent invoices ");
+    if !dash.capabilities.events {
+        frame.render_widget(unsupported("events").block(block), area);
+        return;
+    }
+    if dash.invoices.is_empty() {
+        let hint = "No invoices yet: request a priced path without X-Payment-Proof";
+        frame.render_widget(Paragraph::new(hint).dark_gray().block(block), area);
+        return;
+    }
+
+    let rows = dash.invoices.iter().rev().map(|row| {
+        let status = match row.status {
+            InvoiceStatus::Issued => Span::from(row.status.label()).yellow(),
+            InvoiceStatus::Paid => Span::from(row.status.label()).green(),
+            InvoiceStatus::Failed | InvoiceStatus::TimedOut | InvoiceStatus::Replayed => {
+                Span::from(row.status.label()).red()
+            }
+        };
+        Row::new(vec![
+            Line::from(clock(&row.timestamp)),
+            Line::from(row.memo.clone()),
+            Line::from(row.path.clone()),
+            Line::from(
+                Decimal::try_from(row.amount)
+                    .map_or_else(|_| row.amount.to_string(), |v| dash.amounts.format(v)),
+            ),
+            Line::from(status),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Fill(3),
+            Constraint::Fill(2),
+            Constraint::Length(12),
+            Constraint::Length(7),
+        ],
+    )
+    .header(Row::new(["TIME", "MEMO", "PATH", "AMOUNT", "STATUS"]).bold())
+    .block(block);
+    frame.render_widget(table, area);
+}
+
+fn draw_denials(frame: &mut Frame, dash: &Dashboard, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Denied requests ");
+    if !dash.capabilities.events {
+        frame.render_widget(unsupported("events").block(block), area);
+        return;
+    }
+
+    let items: Vec<ListItem> = dash
+        .denials
+        .iter()
+        .rev()
+        .map(|denial| {
+            let mut spans = vec![Span::from(format!("{} ", clock(&denial.timestamp))).dark_gray()];
+            if denial.maintenance {
+                spans.push(Span::from("[503] ").yellow().bold());
+                spans.push(Span::from(format!("{} ", denial.path)).yellow());
+            } else {
+                spans.push(Span::from(format!("{} ", denial.path)).red());
+            }
+            spans.push(Span::from(denial.reason.clone()));
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(block), area);
+}
+
+fn footer_line(dash: &Dashboard) -> Paragraph<'static> {
+    let mut spans = vec![Span::from(" q quit  p pause/resume events").dark_gray()];
+    if dash.capabilities.simulation_mode {
+        spans.push(Span::from("  m switch mode").dark_gray());
+    }
+    if let Some(message) = &dash.message {
+        spans.push(Span::from(format!("  {}", message)).yellow());
+    }
+    Paragraph::new(Line::from(spans))
+}
+
+fn unsupported(capability: &str) -> Paragraph<'static> {
+    Paragraph::new(format!(
+        "Not available: this server has no `{}` endpoint (upgrade x402-dev)",
+        capability
+    ))
+    .dark_gray()
+}
+
+/// `HH:MM:SS` of an RFC 3339 timestamp, in local time
+fn clock(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
@@ -2,11 +2,15 @@ use anyhow::Result;
 use colored::Colorize;
 use std::fs;
 use std::net::TcpListener;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use crate::cli::DoctorArgs;
-use crate::config::load_merged_config;
+use crate::config::{
+    display_config_path, get_config_dir, load_merged_config, project_config_path, secrets_dir,
+};
+use crate::output::Output;
+use x402_server::state_dir::{self, StateDirSource, STATE_DIR_ENV};
 
 /// Status indicator for checks
 enum CheckStatus {
@@ -67,59 +71,63 @@ impl DiagnosticResults {
 }
 
 /// Run the doctor command for system diagnostics
-pub async fn run(_args: &DoctorArgs) -> Result<()> {
-    println!("{}", "x402-dev System Diagnostics".bold());
-    println!("{}", "===========================".bold());
-    println!();
+pub async fn run(_args: &DoctorArgs, out: &Output) -> Result<()> {
+    out.status("x402-dev System Diagnostics".bold());
+    out.status("===========================".bold());
+    out.status("");
 
     let mut results = DiagnosticResults::new();
 
     // Check environment
-    check_environment(&mut results).await?;
-    println!();
+    check_environment(&mut results, out).await?;
+    out.line("");
 
     // Check configuration
-    check_configuration(&mut results).await?;
-    println!();
+    check_configuration(&mut results, out).await?;
+    out.line("");
+
+    // Check that state, config and secrets directories are writable
+    check_directories(&mut results, out);
+    out.line("");
 
     // Check x402 ecosystem packages
-    check_ecosystem(&mut results).await?;
-    println!();
+    check_ecosystem(&mut results, out).await?;
+    out.line("");
 
     // Print summary
-    print_summary(&results);
+    print_summary(&results, out);
 
     // Always exit with success (diagnostics don't fail)
     Ok(())
 }
 
 /// Check environment (Rust toolchain optional, npm availability)
-async fn check_environment(results: &mut DiagnosticResults) -> Result<()> {
-    println!("{}", "Environment:".bold());
+async fn check_environment(results: &mut DiagnosticResults, out: &Output) -> Result<()> {
+    out.line("Environment:".bold());
 
     // Check x402-dev version
     let version = env!("CARGO_PKG_VERSION");
-    println!(
+    out.line(format!(
         "  {} x402-dev binary: {}",
         CheckStatus::Pass.symbol(),
         format!("v{}", version).cyan()
-    );
+    ));
 
     // Check Rust toolchain (optional)
     match check_rust_version() {
         Some(version) => {
-            println!(
+            out.line(format!(
                 "  {} Rust toolchain: {}",
                 CheckStatus::Pass.symbol(),
                 version.cyan()
-            );
+            ));
         }
         None => {
-            println!(
+            out.line(format!(
                 "  {} Rust toolchain: {}",
                 CheckStatus::Warning.symbol(),
                 CheckStatus::Warning.color_text("Not detected (optional for binary users)")
-            );
+            ));
             results.add_warning("Rust toolchain not detected".to_string());
         }
     }
@@ -127,14 +135,18 @@ async fn check_environment(results: &mut DiagnosticResults) -> Result<()> {
     // Check npm availability
     match check_npm_version() {
         Some(version) => {
-            println!("  {} npm: {}", CheckStatus::Pass.symbol(), version.cyan());
+            out.line(format!(
+                "  {} npm: {}",
+                CheckStatus::Pass.symbol(),
+                version.cyan()
+            ));
         }
         None => {
-            println!(
+            out.line(format!(
                 "  {} npm: {}",
                 CheckStatus::Warning.symbol(),
                 CheckStatus::Warning.color_text("Not detected (optional)")
-            );
+            ));
             results.add_warning("npm not detected".to_string());
             results.add_suggestion(
                 "Install Node.js/npm for x402 ecosystem packages: https://nodejs.org/".to_string(),
@@ -146,78 +158,183 @@ async fn check_environment(results: &mut DiagnosticResults) -> Result<()> {
 }
 
 /// Check configuration files and port availability
-async fn check_configuration(results: &mut DiagnosticResults) -> Result<()> {
-    println!("{}", "Configuration:".bold());
-
-    // Check for .x402dev.yaml
-    let config_path = PathBuf::from(".x402dev.yaml");
-    let config_exists = config_path.exists();
+async fn check_configuration(results: &mut DiagnosticResults, out: &Output) -> Result<()> {
+    out.line("Configuration:".bold());
+
+    // Check for a project config (X402_DEV_CONFIG or discovered .x402dev.yaml)
+    let config_path = match project_config_path() {
+        Ok(path) => path,
+        Err(e) => {
+            out.line(format!(
+                "  {} Config file: {}",
+                CheckStatus::Fail.symbol(),
+                CheckStatus::Fail.color_text(&e.to_string())
+            ));
+            results.add_failure(format!("Project config not found: {}", e));
+            return Ok(());
+        }
+    };
 
-    if config_exists {
-        println!(
+    if let Some(config_path) = &config_path {
+        out.line(format!(
             "  {} Config file: {}",
             CheckStatus::Pass.symbol(),
-            ".x402dev.yaml".cyan()
-        );
+            display_config_path(config_path).cyan()
+        ));
 
         // Try to load and validate config
         match load_merged_config(None) {
             Ok(config) => {
-                println!(
+                out.line(format!(
                     "  {} Config syntax: {}",
                     CheckStatus::Pass.symbol(),
                     "Valid".green()
-                );
+                ));
 
                 // Check port availability
-                check_port_availability(config.port, results);
+                check_port_availability(config.port, results, out);
             }
             Err(e) => {
-                println!(
+                out.line(format!(
                     "  {} Config syntax: {}",
                     CheckStatus::Fail.symbol(),
                     CheckStatus::Fail.color_text(&format!("Invalid - {}", e))
-                );
+                ));
                 results.add_failure(format!("Configuration validation failed: {}", e));
-                results.add_suggestion("Fix configuration errors in .x402dev.yaml".to_string());
+                results.add_suggestion(format!(
+                    "Fix configuration errors in {}",
+                    config_path.display()
+                ));
             }
         }
     } else {
-        println!(
+        out.line(format!(
             "  {} Config file: {}",
             CheckStatus::Warning.symbol(),
             CheckStatus::Warning.color_text("Not found (.x402dev.yaml)")
-        );
+        ));
         results.add_warning("No project configuration file".to_string());
         results.add_suggestion(
             "Create .x402dev.yaml configuration file with: x402-dev init".to_string(),
         );
 
         // Check default port (3402 is the x402-dev mock server default)
-        check_port_availability(3402, results);
+        check_port_availability(3402, results, out);
     }
 
     Ok(())
 }
 
+/// Check writability of the directories x402-dev writes to
+///
+/// A read-only ~/.x402dev is only a warning: global config is still read
+/// and state moves to the temp dir.
+fn check_directories(results: &mut DiagnosticResults, out: &Output) {
+    out.line("Directories:".bold());
+
+    match state_dir::resolve() {
+        Ok(state) if state.source == StateDirSource::Temp => {
+            out.line(format!(
+                "  {} State dir: {}",
+                CheckStatus::Warning.symbol(),
+                CheckStatus::Warning.color_text(&format!(
+                    "{} (temp fallback, lost on reboot)",
+                    state.path.display()
+                ))
+            ));
+            results.add_warning("State dir fell back to the temp dir".to_string());
+            results.add_suggestion(format!(
+                "Set {} to a persistent writable directory",
+                STATE_DIR_ENV
+            ));
+        }
+        Ok(state) => {
+            let source = match state.source {
+                StateDirSource::Override => format!(" (from {})", STATE_DIR_ENV),
+                _ => String::new(),
+            };
+            out.line(format!(
+                "  {} State dir: {}{}",
+                CheckStatus::Pass.symbol(),
+                state.path.display().to_string().cyan(),
+                source
+            ));
+        }
+        Err(e) => {
+            out.line(format!(
+                "  {} State dir: {}",
+                CheckStatus::Fail.symbol(),
+                CheckStatus::Fail.color_text(&e.to_string())
+            ));
+            results.add_failure("No writable state dir; mock cannot start".to_string());
+            results.add_suggestion(format!("Set {} to a writable directory", STATE_DIR_ENV));
+        }
+    }
+
+    if let Ok(dir) = get_config_dir() {
+        check_writable_dir(
+            "Config dir",
+            &dir,
+            "global config is still read",
+            results,
+            out,
+        );
+    }
+    if let Ok(dir) = secrets_dir() {
+        check_writable_dir("Secrets dir", &dir, "`secret set` will fail", results, out);
+    }
+}
+
+/// Report whether `dir` is writable, with `impact` when it is not
+fn check_writable_dir(
+    label: &str,
+    dir: &Path,
+    impact: &str,
+    results: &mut DiagnosticResults,
+    out: &Output,
+) {
+    match state_dir::probe_writable(dir) {
+        Ok(()) => out.line(format!(
+            "  {} {}: {}",
+            CheckStatus::Pass.symbol(),
+            label,
+            format!("{} (writable)", dir.display()).cyan()
+        )),
+        Err(e) => {
+            out.line(format!(
+                "  {} {}: {}",
+                CheckStatus::Warning.symbol(),
+                label,
+                CheckStatus::Warning.color_text(&format!(
+                    "{} not writable ({}); {}",
+                    dir.display(),
+                    e,
+                    impact
+                ))
+            ));
+            results.add_warning(format!("{} {} is not writable", label, dir.display()));
+        }
+    }
+}
+
 /// Check port availability
-fn check_port_availability(port: u16, results: &mut DiagnosticResults) {
+fn check_port_availability(port: u16, results: &mut DiagnosticResults, out: &Output) {
     match TcpListener::bind(("127.0.0.1", port)) {
         Ok(_) => {
-            println!(
+            out.line(format!(
                 "  {} Port {}: {}",
                 CheckStatus::Pass.symbol(),
                 port,
                 "Available".green()
-            );
+            ));
         }
         Err(_) => {
-            println!(
+            out.line(format!(
                 "  {} Port {}: {}",
                 CheckStatus::Warning.symbol(),
                 port,
                 CheckStatus::Warning.color_text("In use")
-            );
+            ));
             results.add_warning(format!("Port {} is already in use", port));
             results.add_suggestion(format!(
                 "Stop the process using port {} or use a different port: x402-dev mock --port <PORT>",
@@ -228,27 +345,27 @@ fn check_port_availability(port: u16, results: &mut DiagnosticResults) {
 }
 
 /// Check for x402 ecosystem packages
-async fn check_ecosystem(results: &mut DiagnosticResults) -> Result<()> {
-    println!("{}", "x402 Ecosystem:".bold());
+async fn check_ecosystem(results: &mut DiagnosticResults, out: &Output) -> Result<()> {
+    out.line("x402 Ecosystem:".bold());
 
     let package_json_path = PathBuf::from("package.json");
 
     if !package_json_path.exists() {
-        println!(
+        out.line(format!(
             "  {} Corbits SDK: {}",
             CheckStatus::Fail.symbol(),
             CheckStatus::Fail.color_text("Not detected (package.json not found)")
-        );
-        println!(
+        ));
+        out.line(format!(
             "  {} PayAI packages: {}",
             CheckStatus::Fail.symbol(),
             CheckStatus::Fail.color_text("Not detected (package.json not found)")
-        );
-        println!(
+        ));
+        out.line(format!(
             "  {} CDP SDK: {}",
             CheckStatus::Fail.symbol(),
             CheckStatus::Fail.color_text("Not detected (package.json not found)")
-        );
+        ));
 
         results.add_failure("No package.json found".to_string());
         results.add_suggestion("Initialize Node.js project: npm init -y (if needed)".to_string());
@@ -277,6 +394,7 @@ async fn check_ecosystem(results: &mut DiagnosticResults) -> Result<()> {
         dependencies,
         dev_dependencies,
         results,
+        out,
     );
 
     // Check PayAI packages
@@ -286,6 +404,7 @@ async fn check_ecosystem(results: &mut DiagnosticResults) -> Result<()> {
         dependencies,
         dev_dependencies,
         results,
+        out,
     );
 
     // Check CDP SDK
@@ -295,6 +414,7 @@ async fn check_ecosystem(results: &mut DiagnosticResults) -> Result<()> {
         dependencies,
         dev_dependencies,
         results,
+        out,
     );
 
     Ok(())
@@ -307,6 +427,7 @@ fn check_package(
     dependencies: Option<&serde_json::Map<String, serde_json::Value>>,
     dev_dependencies: Option<&serde_json::Map<String, serde_json::Value>>,
     results: &mut DiagnosticResults,
+    out: &Output,
 ) {
     let found = package_names.iter().any(|pkg| {
         dependencies.is_some_and(|deps| deps.contains_key(*pkg))
@@ -314,19 +435,19 @@ fn check_package(
     });
 
     if found {
-        println!(
+        out.line(format!(
             "  {} {}: {}",
             CheckStatus::Pass.symbol(),
             name,
             "Detected".green()
-        );
+        ));
     } else {
-        println!(
+        out.line(format!(
             "  {} {}: {}",
             CheckStatus::Fail.symbol(),
             name,
             CheckStatus::Fail.color_text("Not detected")
-        );
+        ));
         results.add_failure(format!("{} not installed", name));
 
         // Add specific installation suggestion
@@ -346,38 +467,38 @@ fn check_package(
 }
 
 /// Print summary and suggestions
-fn print_summary(results: &DiagnosticResults) {
+fn print_summary(results: &DiagnosticResults, out: &Output) {
     if !results.suggestions.is_empty() {
-        println!("{}", "💡 Suggestions:".bold().cyan());
+        out.line("💡 Suggestions:".bold().cyan());
         for suggestion in &results.suggestions {
-            println!("  - {}", suggestion);
+            out.line(format!("  - {}", suggestion));
         }
-        println!();
+        out.line("");
     }
 
     if !results.has_issues() {
-        println!(
+        out.line(format!(
             "{} {}",
             "Overall:".bold(),
             "✅ ALL CHECKS PASSED".green().bold()
-        );
+        ));
     } else if results.failures.is_empty() {
-        println!(
+        out.line(format!(
             "{} {}",
             "Overall:".bold(),
             "⚠️  WARNINGS DETECTED (not blocking)".yellow().bold()
-        );
+        ));
     } else {
-        println!(
+        out.line(format!(
             "{} {}",
             "Overall:".bold(),
             "❌ ISSUES DETECTED".red().bold()
-        );
-        println!();
-        println!("{}", "For more help:".bold());
-        println!("  - Documentation: https://docs.x402-dev.com/setup");
-        println!("  - Run: x402-dev config show");
-        println!("  - Run: x402-dev version");
+        ));
+        out.line("");
+        out.line("For more help:".bold());
+        out.line("  - Documentation: https://docs.x402-dev.com/setup");
+        out.line("  - Run: x402-dev config show");
+        out.line("  - Run: x402-dev version");
     }
 }
 
@@ -0,0 +1,699 @@
+// x402-dev estimate command - Projected spend of a test suite or traffic profile
+//
+// Counts the requests a suite would send (repeats and `then:` steps
+// included) or a traffic profile lists, prices each distinct path from the
+// local pricing config or, with --probe, from the target's own 402
+// invoices, and totals the spend with decimal math. The suite budget and
+// the spending caps of the policy files are replayed over the requests in
+// order to show which would be exceeded, and at which request.
+
+use crate::cli::EstimateArgs;
+use crate::config::{load_merged_config, Config};
+use crate::output::Output;
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use reqwest::{Client, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use x402_core::format::DetectedFormat;
+use x402_core::policy::rules::load_policy_file;
+use x402_core::policy::{PolicyEngine, Request, RuntimePolicy};
+use x402_core::testing::{Budget, HttpOptions, InvoiceHeader, Step, TestSuite};
+use x402_domain::{Amount, Decimal};
+use x402_server::PricingMatcher;
+
+/// Agent charged when a request sets no `X-Agent-Id`, as in the mock
+const DEFAULT_AGENT: &str = "anonymous";
+
+/// Currency of mock invoices, used for prices from the local config
+const MOCK_CURRENCY: &str = "USDC";
+
+/// Traffic profile file (`x402-dev estimate --profile`)
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TrafficProfile {
+    /// Base URL of the paths, probed with --probe (default: the configured mock)
+    #[serde(default)]
+    target: Option<String>,
+    /// `X-Agent-Id` of every request (default `anonymous`)
+    #[serde(default)]
+    agent: Option<String>,
+    requests: Vec<ProfileEntry>,
+}
+
+/// Requests to one path, sent `count` times in a row
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileEntry {
+    path: String,
+    #[serde(default = "default_method")]
+    method: String,
+    count: u64,
+    /// Overrides the profile's `agent`
+    #[serde(default)]
+    agent: Option<String>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// `count` identical requests sent in a row
+#[derive(Debug, Clone, PartialEq)]
+struct Batch {
+    method: String,
+    url: String,
+    path: String,
+    agent: String,
+    count: u64,
+}
+
+/// Price of one request to a path
+#[derive(Debug, Clone)]
+struct Price {
+    amount: Amount,
+    currency: String,
+    /// Why the path is not invoiced, e.g. a price out of bounds
+    note: Option<String>,
+}
+
+/// Spend on one method and path
+#[derive(Debug, Serialize)]
+struct PathCost {
+    method: String,
+    path: String,
+    requests: u64,
+    unit_price: String,
+    currency: String,
+    subtotal: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+}
+
+/// A suite budget or spending cap replayed over the requests
+#[derive(Debug, Serialize)]
+struct LimitCheck {
+    /// `budget` or `spending_cap`
+    kind: &'static str,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    agent: Option<String>,
+    max_amount: String,
+    currency: String,
+    /// What the requests it covers would cost without the limit
+    projected: String,
+    exceeded: bool,
+    /// 1-based request in the run that would go over the limit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exceeded_at_request: Option<u64>,
+    /// Requests a spending cap would deny
+    #[serde(skip_serializing_if = "Option::is_none")]
+    denied_requests: Option<u64>,
+}
+
+/// `--json` form of the estimate
+#[derive(Debug, Serialize)]
+struct Estimate {
+    source: String,
+    /// `config` or `probe`
+    pricing: &'static str,
+    requests: u64,
+    paths: Vec<PathCost>,
+    /// Total per currency
+    totals: BTreeMap<String, String>,
+    limits: Vec<LimitCheck>,
+}
+
+/// Run the estimate command
+pub async fn run(args: &EstimateArgs, out: &Output) -> Result<()> {
+    let config = load_merged_config(None)?;
+    let (source, batches, budget) = match (&args.suite, &args.profile) {
+        (Some(path), _) => {
+            let (batches, budget) = suite_batches(path, args)?;
+            (path, batches, budget)
+        }
+        (None, Some(path)) => (path, profile_batches(path, args, &config)?, None),
+        (None, None) => bail!(
+            "No requests to estimate\nFix: Pass a test suite (`x402-dev estimate tests.yaml`) or --profile traffic.yaml"
+        ),
+    };
+    let budget = match args.budget {
+        Some(max_total) => Some(Budget {
+            max_total,
+            currency: budget.map_or_else(|| MOCK_CURRENCY.to_string(), |b| b.currency),
+        }),
+        None => budget,
+    };
+
+    let prices = if args.probe {
+        probe_prices(&batches).await?
+    } else {
+        config_prices(&batches, &config)
+    };
+    let policy_files = if args.policy.is_empty() {
+        &config.policy_files
+    } else {
+        &args.policy
+    };
+    let mut policies = Vec::new();
+    for path in policy_files {
+        policies.extend(load_policy_file(path)?.into_runtime()?.policies);
+    }
+
+    let estimate = Estimate {
+        source: source.display().to_string(),
+        pricing: if args.probe { "probe" } else { "config" },
+        requests: batches.iter().map(|b| b.count).sum(),
+        paths: path_costs(&batches, &prices)?,
+        totals: totals(&batches, &prices)?
+            .into_iter()
+            .map(|(currency, total)| (currency, total.to_canonical_string()))
+            .collect(),
+        limits: budget
+            .iter()
+            .map(|budget| budget_check(budget, &batches, &prices))
+            .chain(cap_checks(&policies, &batches, &prices))
+            .collect::<Result<_>>()?,
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&estimate)?);
+    } else {
+        print_estimate(&estimate, out);
+    }
+    Ok(())
+}
+
+/// Requests of every test in order, and the suite's budget
+fn suite_batches(path: &Path, args: &EstimateArgs) -> Result<(Vec<Batch>, Option<Budget>)> {
+    let mut suite = TestSuite::from_file(path)?;
+    if !suite.secret_names()?.is_empty() {
+        let store = crate::config::open_secret_store()?;
+        suite.load_secrets(|name| store.get(name))?;
+    }
+    let mut batches = Vec::new();
+    for test in &suite.tests {
+        let url = suite.interpolate(&test.url)?;
+        let agent = agent_header(&suite, &test.headers)?.unwrap_or_else(|| default_agent(args));
+        batches.push(batch(
+            &test.method,
+            &url,
+            &agent,
+            test.repeat.unwrap_or(1).into(),
+        )?);
+        // Extracted values are only known at run time: `{name}` stands in
+        let mut extracted: BTreeMap<String, String> = BTreeMap::new();
+        let mut extract = &test.extract;
+        let mut previous = url;
+        let mut step = test.then.as_deref();
+        while let Some(Step {
+            url,
+            method,
+            headers,
+            extract: step_extract,
+            then,
+            ..
+        }) = step
+        {
+            extracted.extend(
+                extract
+                    .keys()
+                    .map(|name| (name.clone(), format!("{{{}}}", name))),
+            );
+            extract = step_extract;
+            let url = match url {
+                Some(url) => suite.interpolate_with(url, &extracted)?,
+                None => previous,
+            };
+            let agent = match headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("X-Agent-Id"))
+            {
+                Some((_, value)) => suite.interpolate_with(value, &extracted)?,
+                None => agent.clone(),
+            };
+            batches.push(batch(method, &url, &agent, 1)?);
+            previous = url;
+            step = then.as_deref();
+        }
+    }
+    Ok((batches, suite.budget))
+}
+
+/// Interpolated `X-Agent-Id` among `headers`, matched case-insensitively
+fn agent_header(suite: &TestSuite, headers: &BTreeMap<String, String>) -> Result<Option<String>> {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("X-Agent-Id"))
+        .map(|(_, value)| suite.interpolate(value))
+        .transpose()
+}
+
+fn default_agent(args: &EstimateArgs) -> String {
+    args.agent
+        .clone()
+        .unwrap_or_else(|| DEFAULT_AGENT.to_string())
+}
+
+/// Entries of a traffic profile, on `--target`, the profile's target or the
+/// configured mock
+fn profile_batches(path: &Path, args: &EstimateArgs, config: &Config) -> Result<Vec<Batch>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read traffic profile {}", path.display()))?;
+    let profile: TrafficProfile = DetectedFormat::detect(Some(path), &content)
+        .parse(&content)
+        .with_context(|| {
+            format!(
+                "Invalid traffic profile {}\nFix: List `requests:` entries with `path` and `count` (optional `method`, `agent`)",
+                path.display()
+            )
+        })?;
+    let base = args
+        .target
+        .clone()
+        .or(profile.target)
+        .unwrap_or_else(|| format!("http://127.0.0.1:{}", config.port));
+    let base = base.trim_end_matches('/');
+    let profile_agent = profile.agent.or_else(|| args.agent.clone());
+    profile
+        .requests
+        .iter()
+        .map(|entry| {
+            if !entry.path.starts_with('/') {
+                bail!(
+                    "Invalid path in traffic profile: {}\nFix: Start paths with /, e.g., /api/data",
+                    entry.path
+                );
+            }
+            let agent = entry
+                .agent
+                .clone()
+                .or_else(|| profile_agent.clone())
+                .unwrap_or_else(|| DEFAULT_AGENT.to_string());
+            batch(
+                &entry.method,
+                &format!("{}{}", base, entry.path),
+                &agent,
+                entry.count,
+            )
+        })
+        .collect()
+}
+
+fn batch(method: &str, url: &str, agent: &str, count: u64) -> Result<Batch> {
+    let parsed = reqwest::Url::parse(url).with_context(|| {
+        format!(
+            "Invalid request URL: {}\nFix: Use a full URL such as http://localhost:3402/api/data",
+            url
+        )
+    })?;
+    Ok(Batch {
+        method: method.to_ascii_uppercase(),
+        url: url.to_string(),
+        path: parsed.path().to_string(),
+        agent: agent.to_string(),
+        count,
+    })
+}
+
+/// Key of the price of a batch: one probe per method and URL
+fn price_key(batch: &Batch) -> (String, String) {
+    (batch.method.clone(), batch.url.clone())
+}
+
+/// Prices the mock would invoice with the merged config
+fn config_prices(batches: &[Batch], config: &Config) -> HashMap<(String, String), Price> {
+    let matcher = PricingMatcher::new((&config.pricing).into());
+    batches
+        .iter()
+        .map(|batch| {
+            let price = match matcher
+                .invoice_amount_for_request(&batch.method, &batch.path)
+                .map(Amount::from_f64_rounded)
+            {
+                Ok(Ok(amount)) => Price {
+                    amount,
+                    currency: MOCK_CURRENCY.to_string(),
+                    note: None,
+                },
+                Ok(Err(e)) => unpriced(e.to_string()),
+                Err(e) => unpriced(format!("not invoiced: {}", e)),
+            };
+            (price_key(batch), price)
+        })
+        .collect()
+}
+
+fn unpriced(note: String) -> Price {
+    Price {
+        amount: Amount::zero(),
+        currency: MOCK_CURRENCY.to_string(),
+        note: Some(note),
+    }
+}
+
+/// Amounts of the target's 402 invoices, one unpaid request per method and URL
+async fn probe_prices(batches: &[Batch]) -> Result<HashMap<(String, String), Price>> {
+    let client = HttpOptions::default().build_client()?;
+    let mut prices = HashMap::new();
+    for batch in batches {
+        let key = price_key(batch);
+        if prices.contains_key(&key) {
+            continue;
+        }
+        let method = Method::from_bytes(batch.method.as_bytes())
+            .with_context(|| format!("Invalid HTTP method: {}", batch.method))?;
+        prices.insert(key, probe(&client, method, &batch.url).await?);
+    }
+    Ok(prices)
+}
+
+async fn probe(client: &Client, method: Method, url: &str) -> Result<Price> {
+    let response = client
+        .request(method, url)
+        .header("X-Agent-Id", "x402-dev-estimate")
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to probe {}\nFix: Start the target, or pass --target with its base URL",
+                url
+            )
+        })?;
+    if response.status() != StatusCode::PAYMENT_REQUIRED {
+        return Ok(unpriced(format!(
+            "answered {}, not 402",
+            response.status().as_u16()
+        )));
+    }
+    let header = response
+        .headers()
+        .get("www-authenticate")
+        .and_then(|v| v.to_str().ok())
+        .with_context(|| {
+            format!(
+                "The 402 from {} has no readable WWW-Authenticate invoice",
+                url
+            )
+        })?;
+    let invoice = InvoiceHeader::parse(header)
+        .with_context(|| format!("Failed to parse the invoice from {}", url))?;
+    let amount = invoice
+        .get("amount")
+        .with_context(|| format!("The invoice from {} has no amount", url))?;
+    Ok(Price {
+        amount: Amount::parse_invoice_amount(amount, None)
+            .with_context(|| format!("Invalid invoice amount from {}: {}", url, amount))?,
+        currency: invoice.get("currency").unwrap_or(MOCK_CURRENCY).to_string(),
+        note: None,
+    })
+}
+
+fn price<'a>(prices: &'a HashMap<(String, String), Price>, batch: &Batch) -> &'a Price {
+    &prices[&price_key(batch)]
+}
+
+/// `price` times `count`
+fn cost(price: &Price, count: u64) -> Result<Amount> {
+    Ok(price.amount.checked_mul(Decimal::from(count))?)
+}
+
+/// Spend per method and path, in order of first request
+fn path_costs(
+    batches: &[Batch],
+    prices: &HashMap<(String, String), Price>,
+) -> Result<Vec<PathCost>> {
+    let mut rows: Vec<(String, String, u64, &Price)> = Vec::new();
+    for batch in batches {
+        let price = price(prices, batch);
+        match rows
+            .iter_mut()
+            .find(|(method, path, _, _)| *method == batch.method && *path == batch.path)
+        {
+            Some(row) => row.2 += batch.count,
+            None => rows.push((batch.method.clone(), batch.path.clone(), batch.count, price)),
+        }
+    }
+    rows.into_iter()
+        .map(|(method, path, requests, price)| {
+            Ok(PathCost {
+                method,
+                path,
+                requests,
+                unit_price: price.amount.to_canonical_string(),
+                currency: price.currency.clone(),
+                subtotal: cost(price, requests)?.to_canonical_string(),
+                note: price.note.clone(),
+            })
+        })
+        .collect()
+}
+
+fn totals(
+    batches: &[Batch],
+    prices: &HashMap<(String, String), Price>,
+) -> Result<BTreeMap<String, Amount>> {
+    let mut totals: BTreeMap<String, Amount> = BTreeMap::new();
+    for batch in batches {
+        let price = price(prices, batch);
+        let total = totals
+            .entry(price.currency.clone())
+            .or_insert(Amount::zero());
+        *total = total.checked_add(&cost(price, batch.count)?)?;
+    }
+    Ok(totals)
+}
+
+/// The suite budget: tests stop once the invoiced total reaches it, so the
+/// request that goes over it is reported
+fn budget_check(
+    budget: &Budget,
+    batches: &[Batch],
+    prices: &HashMap<(String, String), Price>,
+) -> Result<LimitCheck> {
+    let mut spent = Decimal::ZERO;
+    let mut offset = 0;
+    let mut exceeded_at = None;
+    for batch in batches {
+        let price = price(prices, batch);
+        if price.currency.eq_ignore_ascii_case(&budget.currency) && !price.amount.is_zero() {
+            let unit = price.amount.as_decimal();
+            if exceeded_at.is_none() && spent + unit * Decimal::from(batch.count) > budget.max_total
+            {
+                // Requests of the batch that stay within the budget
+                let within = ((budget.max_total - spent) / unit).floor();
+                exceeded_at = Some(offset + u64::try_from(within).unwrap_or(0) + 1);
+            }
+            spent += unit * Decimal::from(batch.count);
+        }
+        offset += batch.count;
+    }
+    Ok(LimitCheck {
+        kind: "budget",
+        name: "suite budget".to_string(),
+        agent: None,
+        max_amount: budget.max_total.normalize().to_string(),
+        currency: budget.currency.clone(),
+        projected: spent.normalize().to_string(),
+        exceeded: exceeded_at.is_some(),
+        exceeded_at_request: exceeded_at,
+        denied_requests: None,
+    })
+}
+
+/// Spend charged to one spending cap for one agent
+struct CapState {
+    policy: usize,
+    agent: String,
+    max: Decimal,
+    spent: Decimal,
+    projected: Decimal,
+    exceeded_at: Option<u64>,
+    denied: u64,
+}
+
+/// Spending caps of the policies that would decide each request
+///
+/// As in the policy engine, a request is charged to the first policy whose
+/// patterns match it, per agent, and a request that does not fit under
+/// the cap is denied without being counted. The whole run is assumed to
+/// fall within one cap window.
+fn cap_checks(
+    policies: &[RuntimePolicy],
+    batches: &[Batch],
+    prices: &HashMap<(String, String), Price>,
+) -> Vec<Result<LimitCheck>> {
+    let engine = PolicyEngine::new(policies.to_vec());
+    let ordered = engine.policies();
+    let mut states: Vec<CapState> = Vec::new();
+    let mut offset = 0;
+    for batch in batches {
+        let price = price(prices, batch);
+        let request = Request {
+            agent_id: batch.agent.clone(),
+            wallet_address: None,
+            ip_address: None,
+            endpoint: batch.path.clone(),
+            amount: price.amount.to_f64(),
+            timestamp: std::time::SystemTime::now(),
+            memo_id: None,
+            request_id: None,
+        };
+        let winner = engine.explain(&request).winner;
+        let Some((index, cap)) = ordered.iter().enumerate().find_map(|(i, p)| {
+            (p.id == winner)
+                .then_some(p.spending_cap.as_ref())
+                .flatten()
+                .map(|cap| (i, cap))
+        }) else {
+            offset += batch.count;
+            continue;
+        };
+        if price.amount.is_zero() || !cap.counts_currency(&price.currency) {
+            offset += batch.count;
+            continue;
+        }
+        let state = match states
+            .iter()
+            .position(|s| s.policy == index && s.agent == batch.agent)
+        {
+            Some(i) => &mut states[i],
+            None => {
+                states.push(CapState {
+                    policy: index,
+                    agent: batch.agent.clone(),
+                    max: Amount::from_f64_rounded(cap.max_amount)
+                        .map_or(Decimal::ZERO, |a| a.as_decimal()),
+                    spent: Decimal::ZERO,
+                    projected: Decimal::ZERO,
+                    exceeded_at: None,
+                    denied: 0,
+                });
+                states.last_mut().expect("just pushed")
+            }
+        };
+        let unit = price.amount.as_decimal();
+        let fit = u64::try_from(((state.max - state.spent) / unit).floor())
+            .unwrap_or(0)
+            .min(batch.count);
+        if fit < batch.count {
+            state.exceeded_at.get_or_insert(offset + fit + 1);
+            state.denied += batch.count - fit;
+        }
+        state.spent += unit * Decimal::from(fit);
+        state.projected += unit * Decimal::from(batch.count);
+        offset += batch.count;
+    }
+
+    // Caps no request reached are listed too
+    for (index, policy) in ordered.iter().enumerate() {
+        if policy.spending_cap.is_some() && !states.iter().any(|s| s.policy == index) {
+            states.push(CapState {
+                policy: index,
+                agent: String::new(),
+                max: Decimal::ZERO,
+                spent: Decimal::ZERO,
+                projected: Decimal::ZERO,
+                exceeded_at: None,
+                denied: 0,
+            });
+        }
+    }
+    states
+        .into_iter()
+        .map(|state| {
+            let policy = &ordered[state.policy];
+            let cap = policy.spending_cap.as_ref().expect("cap policies only");
+            Ok(LimitCheck {
+                kind: "spending_cap",
+                name: format!("policy '{}'", policy.id),
+                agent: (!state.agent.is_empty()).then_some(state.agent),
+                max_amount: Amount::from_f64_rounded(cap.max_amount)?.to_canonical_string(),
+                currency: cap.currency.clone(),
+                projected: state.projected.normalize().to_string(),
+                exceeded: state.exceeded_at.is_some(),
+                exceeded_at_request: state.exceeded_at,
+                denied_requests: Some(state.denied),
+            })
+        })
+        .collect()
+}
+
+fn print_estimate(estimate: &Estimate, out: &Output) {
+    let number = |amount: &str| {
+        amount
+            .parse::<Decimal>()
+            .map_or_else(|_| amount.to_string(), |value| out.amounts.format(value))
+    };
+    println!(
+        "{} {} ({} requests, prices from {})",
+        "Cost estimate:".bold(),
+        estimate.source,
+        estimate.requests,
+        if estimate.pricing == "probe" {
+            "probing the target"
+        } else {
+            "the pricing config"
+        }
+    );
+    println!();
+    println!(
+        "  {:<7} {:<32} {:>9} {:>12} {:>14}",
+        "METHOD", "PATH", "REQUESTS", "UNIT PRICE", "SUBTOTAL"
+    );
+    for row in &estimate.paths {
+        println!(
+            "  {:<7} {:<32} {:>9} {:>12} {:>14}{}",
+            row.method,
+            row.path,
+            row.requests,
+            number(&row.unit_price),
+            out.amount(&row.subtotal, &row.currency),
+            row.note
+                .as_deref()
+                .map_or_else(String::new, |note| format!("  ({})", note)
+                    .dimmed()
+                    .to_string())
+        );
+    }
+    println!();
+    if estimate.totals.is_empty() {
+        println!("{} 0", "Total:".bold());
+    }
+    for (currency, total) in &estimate.totals {
+        println!("{} {}", "Total:".bold(), out.amount(total, currency));
+    }
+
+    if estimate.limits.is_empty() {
+        return;
+    }
+    println!();
+    println!("{}", "Limits:".bold());
+    for limit in &estimate.limits {
+        let agent = limit
+            .agent
+            .as_deref()
+            .map_or_else(String::new, |agent| format!(" (agent {})", agent));
+        let line = format!(
+            "{}{}: {} of {}",
+            limit.name,
+            agent,
+            number(&limit.projected),
+            out.amount(&limit.max_amount, &limit.currency)
+        );
+        match limit.exceeded_at_request {
+            Some(at) => println!(
+                "  {} {}, exceeded at request {} of {}{}",
+                "✗".red(),
+                line,
+                at,
+                estimate.requests,
+                limit
+                    .denied_requests
+                    .map_or_else(String::new, |denied| format!(", {} denied", denied))
+            ),
+            None => println!("  {} {}", "✓".green(), line),
+        }
+    }
+}
@@ -0,0 +1,104 @@
+// x402-dev explain command - What an error code means and how to fix it
+//
+// Looks the code up in x402-core's explanation catalog (config errors,
+// policy rule codes, mock server error envelopes); without a code, lists
+// every known one.
+
+use crate::cli::ExplainArgs;
+use anyhow::{bail, Result};
+use colored::Colorize;
+use serde::Serialize;
+use x402_core::explain::{self, Explanation};
+
+/// `--json` form: the explanation plus its documentation link
+#[derive(Serialize)]
+struct Report<'a> {
+    #[serde(flatten)]
+    explanation: &'a Explanation,
+    link: String,
+}
+
+impl<'a> Report<'a> {
+    fn new(explanation: &'a Explanation) -> Self {
+        Self {
+            explanation,
+            link: explanation.link(),
+        }
+    }
+}
+
+/// Run the explain command
+pub fn run(args: &ExplainArgs) -> Result<()> {
+    let Some(code) = &args.code else {
+        return list(args.json);
+    };
+    let Some(explanation) = explain::lookup(code) else {
+        bail!(
+            "Unknown error code '{}'\nFix: Run `x402-dev explain` to list the known codes",
+            code
+        );
+    };
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&Report::new(explanation))?
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{} {}",
+        format!("{}:", explanation.code).bold(),
+        explanation.title.bold()
+    );
+    println!();
+    println!("{}", explanation.description);
+
+    println!();
+    println!("{}", "Common causes:".yellow().bold());
+    for cause in explanation.causes {
+        println!("  • {}", cause);
+    }
+
+    println!();
+    println!("{}", "How to fix:".green().bold());
+    for (step, fix) in explanation.fixes.iter().enumerate() {
+        println!("  {}. {}", step + 1, fix);
+    }
+
+    if !explanation.config_keys.is_empty() {
+        println!();
+        println!("{}", "Related config:".cyan().bold());
+        for key in explanation.config_keys {
+            println!("  {}", key);
+        }
+    }
+
+    println!();
+    println!("{} {}", "Docs:".dimmed(), explanation.link());
+    Ok(())
+}
+
+/// Every code with its title
+fn list(json: bool) -> Result<()> {
+    let all = explain::all();
+    if json {
+        let reports: Vec<Report> = all.iter().map(Report::new).collect();
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    let width = all.iter().map(|e| e.code.len()).max().unwrap_or(0);
+    for explanation in all {
+        println!(
+            "  {:<width$}  {}",
+            explanation.code.bold(),
+            explanation.title,
+            width = width
+        );
+    }
+    println!();
+    println!("Run `x402-dev explain <code>` for causes and fix steps");
+    Ok(())
+}
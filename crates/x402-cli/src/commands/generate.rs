@@ -0,0 +1,81 @@
+// Client SDK generation from the project's pricing config
+
+use crate::config::load_merged_config;
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use std::path::PathBuf;
+use x402_core::codegen::{generate_typescript_client, ClientSpec};
+
+#[derive(Args)]
+#[command(after_help = "\
+EXAMPLES:
+  # Generate a TypeScript client from the current config
+  x402-dev generate client --language ts --output x402-client.ts
+
+  # Print to stdout (for piping)
+  x402-dev generate client --language ts
+
+SEE ALSO:
+  x402-dev config    View pricing configuration
+  x402-dev mock      Start the mock server the client talks to
+")]
+pub struct GenerateArgs {
+    #[command(subcommand)]
+    pub command: GenerateCommand,
+}
+
+#[derive(Subcommand)]
+pub enum GenerateCommand {
+    /// Generate a typed client that handles 402 responses and payment retries
+    Client {
+        /// Target language
+        #[arg(long, short, value_name = "LANGUAGE")]
+        language: ClientLanguage,
+
+        /// Output file path (prints to stdout if omitted)
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+pub enum ClientLanguage {
+    /// TypeScript fetch wrapper
+    #[value(name = "ts", alias = "typescript")]
+    TypeScript,
+}
+
+pub fn handle_generate_command(args: GenerateArgs) -> Result<()> {
+    match args.command {
+        GenerateCommand::Client { language, output } => client_command(language, output),
+    }
+}
+
+fn client_command(language: ClientLanguage, output: Option<PathBuf>) -> Result<()> {
+    let config = load_merged_config(None)?;
+    let spec = ClientSpec {
+        default_price: config.pricing.default,
+        prices: config.pricing.per_resource.into_iter().collect(),
+    };
+
+    let code = match language {
+        ClientLanguage::TypeScript => generate_typescript_client(&spec),
+    };
+
+    if let Some(output_path) = output {
+        std::fs::write(&output_path, &code)
+            .with_context(|| format!("Failed to write output file: {}", output_path.display()))?;
+        println!(
+            "{} Generated client: {}",
+            "✓".green().bold(),
+            output_path.display()
+        );
+        println!("  Priced paths: {}", spec.prices.len());
+        println!("  Lines: {}", code.lines().count());
+    } else {
+        print!("{}", code);
+    }
+
+    Ok(())
+}
@@ -1,13 +1,17 @@
 use anyhow::{Context, Result};
-use dialoguer::{Confirm, Input, Select};
 use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::cli::InitArgs;
-use crate::config::{Config, LogLevel, PricingConfig, SimulationMode};
+use crate::config::{
+    Config, LogLevel, PricingConfig, SimulationMode, CONFIG_PATH_ENV, PROJECT_CONFIG_FILES,
+};
+use crate::prompt::{Prompter, TerminalPrompter};
+use x402_core::format::FileFormat;
+use x402_domain::{DurationField, Port, SolanaNetwork};
 
-/// Configuration for YAML serialization (includes all fields)
+/// Configuration for serialization (includes all fields)
 #[derive(Serialize)]
 struct ProjectConfig {
     port: u16,
@@ -25,20 +29,59 @@ impl From<Config> for ProjectConfig {
     }
 }
 
+/// Write `text` to `output_dir/config_name`, creating the directory if missing
+fn write_project_config(output_dir: &Path, config_name: &str, text: &str) -> Result<PathBuf> {
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create directory: {:?}", output_dir))?;
+
+    let config_path = output_dir.join(config_name);
+    crate::safe_write::write(&config_path, text)
+        .with_context(|| format!("Failed to write configuration file: {:?}", config_path))?;
+
+    Ok(config_path)
+}
+
+/// Format and file name of the config file to write
+///
+/// An explicit `--format` wins, then the `--config-name` extension; the
+/// default name follows the format.
+fn resolve_output(args: &InitArgs) -> (FileFormat, String) {
+    let format = args
+        .format
+        .or_else(|| {
+            args.config_name
+                .as_deref()
+                .and_then(|name| FileFormat::from_path(Path::new(name)))
+        })
+        .unwrap_or_default();
+    let name = args
+        .config_name
+        .clone()
+        .unwrap_or_else(|| format!(".x402dev.{}", format.extension()));
+    (format, name)
+}
+
 /// Run the init command
-pub async fn run(_args: &InitArgs) -> Result<()> {
+pub async fn run(args: &InitArgs) -> Result<()> {
+    run_with(args, &mut TerminalPrompter)
+}
+
+/// Run the init wizard, asking through `prompter`
+fn run_with(args: &InitArgs, prompter: &mut dyn Prompter) -> Result<()> {
     println!("x402-dev Project Initialization");
     println!("================================\n");
 
-    // Check if .x402dev.yaml already exists
-    let config_path = PathBuf::from(".x402dev.yaml");
+    let (format, config_name) = resolve_output(args);
+
+    // Check if the config file already exists
+    let config_path = args.output_dir.join(&config_name);
     if config_path.exists() {
-        println!("⚠️  Configuration file already exists: .x402dev.yaml\n");
+        println!(
+            "⚠️  Configuration file already exists: {}\n",
+            config_path.display()
+        );
 
-        let overwrite = Confirm::new()
-            .with_prompt("Do you want to overwrite it?")
-            .default(false)
-            .interact()?;
+        let overwrite = prompter.confirm("Do you want to overwrite it?", false)?;
 
         if !overwrite {
             println!("\nℹ️  Initialization cancelled. Existing configuration preserved.");
@@ -51,33 +94,20 @@ pub async fn run(_args: &InitArgs) -> Result<()> {
     println!("Please provide the following configuration:\n");
 
     // Port configuration
-    let port: u16 = Input::new()
-        .with_prompt("Mock server port")
-        .default(8402)
-        .validate_with(|input: &u16| -> Result<(), &str> {
-            if *input >= 1024 {
-                Ok(())
-            } else {
-                Err("Port must be 1024 or higher")
-            }
-        })
-        .interact_text()?;
+    let port = loop {
+        match prompter
+            .input("Mock server port", Some("8402"))?
+            .parse::<Port>()
+        {
+            Ok(port) => break port.get(),
+            Err(e) => prompter.say(&e.to_string()),
+        }
+    };
 
     // Solana network selection
-    let networks = vec!["devnet", "testnet", "mainnet-beta"];
-    let network_idx = Select::new()
-        .with_prompt("Solana network")
-        .items(&networks)
-        .default(0)
-        .interact()?;
-
-    let solana_rpc = match networks[network_idx] {
-        "devnet" => "https://api.devnet.solana.com",
-        "testnet" => "https://api.testnet.solana.com",
-        "mainnet-beta" => "https://api.mainnet-beta.solana.com",
-        _ => "https://api.devnet.solana.com",
-    }
-    .to_string();
+    let networks: Vec<&str> = SolanaNetwork::ALL.iter().map(|n| n.as_str()).collect();
+    let network_idx = prompter.select("Solana network", &networks, 0)?;
+    let solana_rpc = SolanaNetwork::ALL[network_idx].rpc_url().to_string();
 
     // Log level selection
     let log_level_options = [
@@ -88,11 +118,7 @@ pub async fn run(_args: &InitArgs) -> Result<()> {
         LogLevel::Trace,
     ];
     let log_level_display = vec!["error", "warn", "info", "debug", "trace"];
-    let log_level_idx = Select::new()
-        .with_prompt("Log level")
-        .items(&log_level_display)
-        .default(2) // "info" is at index 2
-        .interact()?;
+    let log_level_idx = prompter.select("Log level", &log_level_display, 2)?; // "info" is at index 2
 
     let log_level = log_level_options[log_level_idx];
 
@@ -103,7 +129,48 @@ pub async fn run(_args: &InitArgs) -> Result<()> {
         log_level,
         pricing: PricingConfig::default(), // Story 2.2: Use default pricing
         simulation_mode: SimulationMode::default(), // Story 2.3: Default to success mode
-        timeout_delay_ms: 5000,            // Story 2.3: Default 5 second timeout
+        timeout_delay_ms: DurationField::from_millis(5000), // Story 2.3: Default 5 second timeout
+        http2: false,
+        tls: None,
+        webhooks: vec![],
+        signing: None,
+        policies_dir: None,
+        policy_files: vec![],
+        invoice_ttl_seconds: DurationField::from_secs(x402_server::DEFAULT_INVOICE_TTL_SECONDS),
+        invoice_ttl_per_resource: Default::default(),
+        methods: Default::default(),
+        recipient: None,
+        recipient_routing: Default::default(),
+        recipient_routing_seed: None,
+        maintenance: vec![],
+        responses: Default::default(),
+        memo_prefix: None,
+        id_format: Default::default(),
+        id_seed: None,
+        protocol_id: None,
+        resolve: vec![],
+        state_backend: Default::default(),
+        state_path: None,
+        audit_log: None,
+        audit_sample_rate: None,
+        log_rotation: None,
+        log_redaction: vec![],
+        allow_replay: false,
+        max_invoices: None,
+        idle_timeout_secs: None,
+        max_lifetime_secs: None,
+        startup_hooks: vec![],
+        ready_timeout_secs: None,
+        bind_address: None,
+        admin_loopback_only: None,
+        log_format: None,
+        zero_price_behavior: None,
+        rpc: None,
+        verifier: Default::default(),
+        amount_tolerance: Default::default(),
+        quota_headers: Default::default(),
+        lint: Default::default(),
+        display: Default::default(),
     };
 
     // Validate configuration
@@ -114,20 +181,29 @@ pub async fn run(_args: &InitArgs) -> Result<()> {
     // Convert to ProjectConfig for serialization
     let project_config: ProjectConfig = config.into();
 
-    // Serialize to YAML
-    let yaml = serde_yaml::to_string(&project_config)
-        .context("Failed to serialize configuration to YAML")?;
+    // Serialize in the chosen format
+    let text = format
+        .render(&project_config)
+        .with_context(|| format!("Failed to serialize configuration to {}", format))?;
 
     // Write to file
-    fs::write(&config_path, yaml)
-        .with_context(|| format!("Failed to write configuration file: {:?}", config_path))?;
+    let config_path = write_project_config(&args.output_dir, &config_name, &text)?;
 
     println!("\n✅ Configuration file created successfully!");
-    println!("   File: .x402dev.yaml");
+    println!("   File: {}", config_path.display());
     println!("\n📝 Configuration:");
     println!("   Port: {}", project_config.port);
     println!("   Solana RPC: {}", project_config.solana_rpc);
     println!("   Log Level: {}", project_config.log_level);
+    if !PROJECT_CONFIG_FILES.contains(&config_name.as_str()) {
+        // Discovery only looks for the default names
+        println!(
+            "\nℹ️  Other commands look for {}; set {}={} to use this file",
+            PROJECT_CONFIG_FILES.join(", "),
+            CONFIG_PATH_ENV,
+            config_path.display()
+        );
+    }
     println!("\n💡 Next steps:");
     println!("   1. Run 'x402-dev config show' to verify configuration");
     println!("   2. Run 'x402-dev doctor' to check system health");
@@ -135,3 +211,71 @@ pub async fn run(_args: &InitArgs) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PROJECT_CONFIG_FILE;
+    use crate::prompt::Script;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_project_config_creates_nested_directory() {
+        let root = TempDir::new().unwrap();
+        let output_dir = root.path().join("services/payments");
+
+        let path =
+            write_project_config(&output_dir, "x402dev.staging.yaml", "port: 8402\n").unwrap();
+
+        assert_eq!(path, output_dir.join("x402dev.staging.yaml"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "port: 8402\n");
+    }
+
+    #[test]
+    fn test_scripted_session_writes_config() {
+        let dir = TempDir::new().unwrap();
+        let args = InitArgs {
+            output_dir: dir.path().to_path_buf(),
+            config_name: None,
+            format: None,
+        };
+        let mut script = Script::new(&["80", "9000", "testnet", ""]);
+
+        run_with(&args, &mut script).unwrap();
+
+        assert!(script.output().contains(
+            "ports below 1024 are privileged and need root. Use 1024-65535 (did you mean 8080?)"
+        ));
+        let text = fs::read_to_string(dir.path().join(PROJECT_CONFIG_FILE)).unwrap();
+        assert!(text.contains("port: 9000"), "{}", text);
+        assert!(text.contains("api.testnet.solana.com"), "{}", text);
+        assert!(text.contains("log_level: info"), "{}", text);
+    }
+
+    #[test]
+    fn test_resolve_output_format_and_name() {
+        let args = |config_name: Option<&str>, format: Option<FileFormat>| InitArgs {
+            output_dir: PathBuf::from("."),
+            config_name: config_name.map(str::to_string),
+            format,
+        };
+
+        assert_eq!(
+            resolve_output(&args(None, None)),
+            (FileFormat::Yaml, PROJECT_CONFIG_FILE.to_string())
+        );
+        assert_eq!(
+            resolve_output(&args(None, Some(FileFormat::Json))),
+            (FileFormat::Json, ".x402dev.json".to_string())
+        );
+        assert_eq!(
+            resolve_output(&args(Some("staging.json"), None)),
+            (FileFormat::Json, "staging.json".to_string())
+        );
+        // An explicit format is not second-guessed by the name
+        assert_eq!(
+            resolve_output(&args(Some("staging.conf"), Some(FileFormat::Json))),
+            (FileFormat::Json, "staging.conf".to_string())
+        );
+    }
+}
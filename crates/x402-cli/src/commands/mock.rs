@@ -1,12 +1,23 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::path::Path;
+use x402_core::policy::{
+    load_policy_file, read_audit_entries, AuditDecision, AuditEntry, AuditQuery, PolicyStateStore,
+    SqliteStateStore, StateBackend, StateStoreConfig, StoreInstance,
+};
+use x402_server::process::is_server_running;
+use x402_server::snapshot::ADMIN_SNAPSHOT_PATH;
 use x402_server::{
     restart_server as server_restart, server_status as server_status_check,
-    start_server as server_start, stop_server as server_stop, Config, InvoiceGenerator,
-    MockServerConfig, PricingConfig, PricingMatcher,
+    start_server as server_start, stop_server as server_stop, Config, InvoiceStatus,
+    MockServerConfig, PathStatsSnapshot, RestoreSummary, RuleHitsSnapshot, ServerSnapshot,
+    SigningConfig, StatsSnapshot, TlsConfig,
 };
 
-use crate::cli::{MockArgs, MockSubcommand};
+use crate::cli::{AuditCommand, MockArgs, MockSubcommand, SnapshotCommand};
+use crate::commands::dash;
 use crate::config::{load_merged_config, CliOverrides};
+use crate::output::Output;
 
 // ============================================================================
 // CLI Command Handlers
@@ -25,9 +36,451 @@ pub async fn handle_status() -> Result<()> {
 /// Handle restart command
 pub async fn handle_restart(args: &MockArgs) -> Result<()> {
     let server_config = build_server_config(args)?;
+    x402_server::logging::init(&server_config.config)?;
     server_restart(server_config).await
 }
 
+/// Handle stats command
+pub async fn handle_stats(port: u16, json: bool) -> Result<()> {
+    let url = format!(
+        "http://127.0.0.1:{}{}",
+        port,
+        x402_server::stats::ADMIN_STATS_PATH
+    );
+    let response = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(5))
+        .build()?
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to fetch stats from {}\nFix: Start the server with `x402-dev mock` or pass --port",
+                url
+            )
+        })?
+        .error_for_status()
+        .with_context(|| format!("Stats request to {} failed", url))?;
+    let snapshot: StatsSnapshot = response
+        .json()
+        .await
+        .context("Stats response is not a stats snapshot")?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+    } else {
+        print!("{}", render_stats_table(&snapshot));
+    }
+    Ok(())
+}
+
+/// Handle list command
+pub async fn handle_list(shared_state: Option<&Path>, json: bool) -> Result<()> {
+    let path = match shared_state {
+        Some(path) => path.to_path_buf(),
+        None => load_merged_config(None)?.policy_state().sqlite_path(),
+    };
+    if !path.exists() {
+        bail!(
+            "No shared policy state at {}\nFix: Start instances with `x402-dev mock --policy <FILE> --shared-state {}`",
+            path.display(),
+            path.display()
+        );
+    }
+    let instances: Vec<(StoreInstance, bool)> = SqliteStateStore::open(&path)?
+        .instances()?
+        .into_iter()
+        .map(|instance| {
+            let running = is_running(&instance);
+            (instance, running)
+        })
+        .collect();
+
+    if json {
+        let rows: Vec<_> = instances
+            .iter()
+            .map(|(instance, running)| {
+                let mut row = serde_json::to_value(instance)?;
+                row["running"] = (*running).into();
+                Ok(row)
+            })
+            .collect::<Result<_>>()?;
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "shared_state": path,
+                "instances": rows,
+            }))?
+        );
+    } else {
+        println!("Instances sharing {}\n", path.display());
+        print!("{}", render_instances_table(&instances));
+    }
+    Ok(())
+}
+
+/// Handle audit query command
+pub fn handle_audit_query(file: Option<&Path>, query: &AuditQuery, json: bool) -> Result<()> {
+    let path = match file {
+        Some(path) => path.to_path_buf(),
+        None => load_merged_config(None)?.audit_log.ok_or_else(|| {
+            anyhow!("No audit log configured\nFix: Pass --file or set audit_log in .x402dev.yaml")
+        })?,
+    };
+    let entries: Vec<AuditEntry> = read_audit_entries(&path)?
+        .into_iter()
+        .filter(|entry| query.matches(entry))
+        .collect();
+
+    if json {
+        for entry in &entries {
+            println!("{}", serde_json::to_string(entry)?);
+        }
+    } else {
+        print!("{}", render_audit_table(&entries));
+    }
+    Ok(())
+}
+
+/// Handle snapshot export command
+pub async fn handle_snapshot_export(file: &Path, port: u16, max_invoices: usize) -> Result<()> {
+    let url = format!(
+        "http://127.0.0.1:{}{}?max_invoices={}",
+        port, ADMIN_SNAPSHOT_PATH, max_invoices
+    );
+    let snapshot: ServerSnapshot = snapshot_client()?
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to fetch a snapshot from {}\nFix: Start the server with `x402-dev mock` or pass --port",
+                url
+            )
+        })?
+        .error_for_status()
+        .with_context(|| format!("Snapshot request to {} failed", url))?
+        .json()
+        .await
+        .context("Snapshot response is not a server snapshot")?;
+
+    let out = std::fs::File::create(file)
+        .with_context(|| format!("Failed to create {}", file.display()))?;
+    snapshot.write_archive(out)?;
+    println!("Snapshot written to {}\n", file.display());
+    print!("{}", render_snapshot_summary(&snapshot));
+    Ok(())
+}
+
+/// Handle snapshot import command
+pub async fn handle_snapshot_import(file: &Path, port: u16) -> Result<()> {
+    let snapshot = read_snapshot(file)?;
+    let url = format!("http://127.0.0.1:{}{}", port, ADMIN_SNAPSHOT_PATH);
+    let response = snapshot_client()?
+        .post(&url)
+        .json(&snapshot)
+        .send()
+        .await
+        .with_context(|| {
+            format!(
+                "Failed to send the snapshot to {}\nFix: Start the server with `x402-dev mock` or pass --port",
+                url
+            )
+        })?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        bail!(
+            "Snapshot import to {} failed ({}): {}\nFix: Import into a server of version {} or later",
+            url,
+            status,
+            body,
+            snapshot.manifest.version
+        );
+    }
+    let summary: RestoreSummary = response
+        .json()
+        .await
+        .context("Restore response is not a restore summary")?;
+
+    println!(
+        "Restored {} invoices ({} already known), {} events, stats of {} patterns{}",
+        summary.invoices,
+        summary.invoices_skipped,
+        summary.events,
+        summary.stats_paths,
+        if summary.policy_state {
+            " and policy counters"
+        } else {
+            ""
+        }
+    );
+    for warning in &summary.warnings {
+        println!("  ⚠ {}", warning);
+    }
+    Ok(())
+}
+
+/// Handle snapshot show command
+pub fn handle_snapshot_show(file: &Path, json: bool) -> Result<()> {
+    let snapshot = read_snapshot(file)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&snapshot)?);
+    } else {
+        print!("{}", render_snapshot_summary(&snapshot));
+    }
+    Ok(())
+}
+
+fn read_snapshot(file: &Path) -> Result<ServerSnapshot> {
+    let input = std::fs::File::open(file).with_context(|| {
+        format!(
+            "Failed to open {}\nFix: Pass an archive written by `x402-dev mock snapshot export`",
+            file.display()
+        )
+    })?;
+    // Flattened so a gzip or tar error keeps the file name in the message
+    ServerSnapshot::read_archive(std::io::BufReader::new(input))
+        .map_err(|err| anyhow!("Failed to read snapshot {}: {:#}", file.display(), err))
+}
+
+/// Snapshots can be large; allow more time than the stats request
+fn snapshot_client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()?)
+}
+
+/// `--since` as a time: `-1h` (ago) or RFC3339
+fn parse_since(since: &str) -> Result<DateTime<Utc>> {
+    if let Some(ago) = since.strip_prefix('-') {
+        let ago = humantime::parse_duration(ago)
+            .ok()
+            .and_then(|ago| chrono::Duration::from_std(ago).ok());
+        if let Some(ago) = ago {
+            return Ok(Utc::now() - ago);
+        }
+    } else if let Ok(time) = DateTime::parse_from_rfc3339(since) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    bail!(
+        "Invalid --since: '{}'\nFix: Use a time relative to now such as -1h or -30m, or RFC3339 such as 2026-03-01T12:00:00Z",
+        since
+    )
+}
+
+fn render_audit_table(entries: &[AuditEntry]) -> String {
+    let mut out = format!(
+        "{:<24} {:<8} {:<16} {:<20} {:>10} {:<16} {}\n",
+        "TIME", "DECISION", "AGENT", "ENDPOINT", "AMOUNT", "POLICY", "REASON"
+    );
+    for entry in entries {
+        let decision = match entry.decision {
+            AuditDecision::Allow => "allow",
+            AuditDecision::Deny => "deny",
+        };
+        out.push_str(&format!(
+            "{:<24} {:<8} {:<16} {:<20} {:>10} {:<16} {}\n",
+            entry.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            decision,
+            entry.agent,
+            entry.endpoint,
+            entry.amount,
+            entry.policy_id,
+            entry.reason.as_deref().unwrap_or("-")
+        ));
+    }
+    out.push_str(&format!("\n{} entries\n", entries.len()));
+    out
+}
+
+/// Manifest, counts and stats table of a snapshot
+fn render_snapshot_summary(snapshot: &ServerSnapshot) -> String {
+    let manifest = &snapshot.manifest;
+    let mut out = format!(
+        "Snapshot of x402-dev {} taken {}\n",
+        manifest.version,
+        manifest
+            .created_at
+            .to_rfc3339_opts(SecondsFormat::Millis, true)
+    );
+    if manifest.server_time != manifest.created_at {
+        out.push_str(&format!(
+            "Server clock  {}\n",
+            manifest
+                .server_time
+                .to_rfc3339_opts(SecondsFormat::Millis, true)
+        ));
+    }
+    let verified = snapshot
+        .invoices
+        .iter()
+        .filter(|record| record.status == InvoiceStatus::Verified)
+        .count();
+    out.push_str(&format!(
+        "Invoices      {} of {} ({} verified)\n",
+        manifest.invoices_included, manifest.invoices_total, verified
+    ));
+    out.push_str(&format!("Events        {}\n", snapshot.events.len()));
+    match (&snapshot.policy_state, manifest.policy_backend) {
+        (Some(state), _) => out.push_str(&format!(
+            "Policy state  {} rate limit, {} spending and {} calendar counters\n",
+            state.requests.len(),
+            state.spends.len(),
+            state.buckets.len()
+        )),
+        (None, Some(backend)) => out.push_str(&format!(
+            "Policy state  not included ({}): {}\n",
+            backend,
+            manifest.policy_state_note.as_deref().unwrap_or("-")
+        )),
+        (None, None) => out.push_str("Policy state  no policies\n"),
+    }
+    out.push('\n');
+    out.push_str(&render_stats_table(&snapshot.stats));
+    out
+}
+
+/// True if the instance's process is still the one that registered
+fn is_running(instance: &StoreInstance) -> bool {
+    is_server_running(&x402_server::PidMetadata {
+        pid: instance.pid,
+        port: instance.port,
+        bind: None,
+        started: instance.started,
+    })
+}
+
+/// Aligned table of registered instances and whether each still runs
+pub(crate) fn render_instances_table(instances: &[(StoreInstance, bool)]) -> String {
+    if instances.is_empty() {
+        return "No instances registered\n".to_string();
+    }
+    let rows: Vec<[String; 4]> = instances
+        .iter()
+        .map(|(instance, running)| {
+            [
+                instance.id.clone(),
+                instance.pid.to_string(),
+                instance
+                    .port
+                    .map_or_else(|| "-".to_string(), |p| p.to_string()),
+                if *running { "running" } else { "exited" }.to_string(),
+            ]
+        })
+        .collect();
+    let headers = ["INSTANCE", "PID", "PORT", "STATUS"].map(str::to_string);
+    let mut widths = headers.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    std::iter::once(&headers)
+        .chain(&rows)
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            format!("{}\n", cells.join("  ").trim_end())
+        })
+        .collect()
+}
+
+/// Aligned table of per-path counters with a totals row
+pub(crate) fn render_stats_table(snapshot: &StatsSnapshot) -> String {
+    const HEADERS: [&str; 11] = [
+        "PATH", "REQUESTS", "402", "VERIFIED", "FREE", "FAILED", "TIMEOUT", "DENIED", "ERRORS",
+        "MEAN ms", "P95 ms",
+    ];
+    let cells = |row: &PathStatsSnapshot| -> [String; 11] {
+        [
+            row.pattern.clone(),
+            row.requests.to_string(),
+            row.invoices_issued.to_string(),
+            row.verified.to_string(),
+            row.free.to_string(),
+            row.verification_failed.to_string(),
+            row.verification_timeout.to_string(),
+            row.policy_denied.to_string(),
+            row.errors.to_string(),
+            format!("{:.2}", row.latency_mean_ms),
+            format!("{:.2}", row.latency_p95_ms),
+        ]
+    };
+
+    let rows: Vec<[String; 11]> = snapshot.paths.iter().map(cells).collect();
+    let totals = cells(&snapshot.totals);
+    let mut widths = HEADERS.map(str::len);
+    for row in rows.iter().chain(std::iter::once(&totals)) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    // Path column left-aligned, counters right-aligned
+    let line = |row: &[String]| -> String {
+        let mut out = format!("{:<width$}", row[0], width = widths[0]);
+        for (cell, width) in row[1..].iter().zip(&widths[1..]) {
+            out.push_str(&format!("  {:>width$}", cell, width = width));
+        }
+        out.push('\n');
+        out
+    };
+
+    let mut out = format!("Stats since {}\n\n", snapshot.since.to_rfc3339());
+    out.push_str(&line(&HEADERS.map(str::to_string)));
+    for row in &rows {
+        out.push_str(&line(row));
+    }
+    let rule_width = widths.iter().sum::<usize>() + 2 * (widths.len() - 1);
+    out.push_str(&"-".repeat(rule_width));
+    out.push('\n');
+    out.push_str(&line(&totals));
+    if let Some(rule_hits) = &snapshot.rule_hits {
+        out.push_str(&render_rule_hits(rule_hits));
+    }
+    out
+}
+
+/// Hits and last match per pricing rule and policy (`mock --match-stats`)
+fn render_rule_hits(rule_hits: &RuleHitsSnapshot) -> String {
+    let sections = [
+        ("Pricing rule", &rule_hits.pricing),
+        ("Policy", &rule_hits.policies),
+    ];
+    let width = sections
+        .iter()
+        .flat_map(|(title, rules)| rules.iter().map(|r| r.rule.len()).chain([title.len()]))
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for (title, rules) in sections.iter().filter(|(_, rules)| !rules.is_empty()) {
+        out.push_str(&format!(
+            "\n{:<width$}  {:>8}  LAST MATCHED\n",
+            title.to_uppercase(),
+            "HITS",
+            width = width
+        ));
+        for rule in rules.iter() {
+            let last = rule
+                .last_matched
+                .map_or_else(|| "-".to_string(), |t| t.to_rfc3339());
+            out.push_str(&format!(
+                "{:<width$}  {:>8}  {}\n",
+                rule.rule,
+                rule.hits,
+                last,
+                width = width
+            ));
+        }
+    }
+    out
+}
+
 /// Build server configuration from CLI arguments
 fn build_server_config(args: &MockArgs) -> Result<MockServerConfig> {
     let port = args.port;
@@ -36,49 +489,185 @@ fn build_server_config(args: &MockArgs) -> Result<MockServerConfig> {
     let cli_overrides = CliOverrides {
         port: None, // Port is handled separately
         solana_rpc: None,
-        log_level: None,
+        log_level: args.log_level,
         pricing: args.pricing,
+        recipient: args.recipient.clone(),
     };
 
     let config = load_merged_config(Some(&cli_overrides))?;
 
     // Convert CLI config to server config
-    let server_config = Config {
-        port,
-        solana_rpc: config.solana_rpc.clone(),
-        log_level: config.log_level.to_string(),
-        pricing: PricingConfig {
-            default: config.pricing.default,
-            per_resource: config.pricing.per_resource.clone(),
-        },
-        simulation_mode: match config.simulation_mode {
-            crate::config::SimulationMode::Success => x402_server::SimulationMode::Success,
-            crate::config::SimulationMode::Failure => x402_server::SimulationMode::Failure,
-            crate::config::SimulationMode::Timeout => x402_server::SimulationMode::Timeout,
-        },
-        timeout_delay_ms: config.timeout_delay_ms,
-    };
+    let mut builder = Config::builder()
+        .port(port)
+        .bind_address(
+            args.bind
+                .or(config.bind_address)
+                .unwrap_or(x402_server::DEFAULT_BIND_ADDRESS),
+        )
+        .admin_loopback_only(config.admin_loopback_only.unwrap_or(true))
+        .port_fallback(args.port_fallback)
+        .port_fallback_attempts(args.port_fallback_attempts)
+        .solana_rpc(config.solana_rpc.clone())
+        .log_level(config.log_level.to_string())
+        .log_format(args.log_format.or(config.log_format).unwrap_or_default())
+        .log_redaction(config.log_redaction.clone())
+        .pricing((&config.pricing).into())
+        .zero_price_behavior(config.zero_price_behavior.unwrap_or_default())
+        .simulation_mode(config.simulation_mode.into())
+        .timeout_delay_ms(config.timeout_delay_ms.as_millis())
+        .allow_replay(config.allow_replay)
+        .max_invoices(
+            config
+                .max_invoices
+                .unwrap_or(x402_server::DEFAULT_MAX_INVOICES),
+        )
+        .amount_tolerance(config.amount_tolerance)
+        .verifier(config.verifier)
+        .quota_headers(config.quota_headers.clone())
+        .idle_timeout_secs(
+            args.idle_timeout_secs
+                .or(config.idle_timeout_secs)
+                .map_or(0, |timeout| timeout.as_secs()),
+        )
+        .max_lifetime_secs(
+            args.max_lifetime_secs
+                .or(config.max_lifetime_secs)
+                .map_or(0, |lifetime| lifetime.as_secs()),
+        )
+        .ready_timeout_secs(
+            args.ready_timeout_secs
+                .or(config.ready_timeout_secs)
+                .map_or(
+                    x402_server::readiness::DEFAULT_READY_TIMEOUT_SECS,
+                    |timeout| timeout.as_secs(),
+                ),
+        )
+        .http2(args.http2 || config.http2)
+        .match_stats(args.match_stats)
+        .allow_time_travel(args.allow_time_travel)
+        .webhooks(config.webhooks.clone())
+        .invoice_ttl_seconds(config.invoice_ttl_seconds.as_secs());
+    for (path, ttl) in &config.invoice_ttl_per_resource {
+        builder = builder.invoice_ttl(path.clone(), ttl.as_secs());
+    }
+    for (path, methods) in &config.methods {
+        builder = builder.allowed_methods(path.clone(), methods.clone());
+    }
+    if let Some(recipient) = &config.recipient {
+        builder = builder.recipient(recipient.clone());
+    }
+    for (path, route) in &config.recipient_routing {
+        builder = builder.recipient_route(path.clone(), route.clone());
+    }
+    if let Some(seed) = config.recipient_routing_seed {
+        builder = builder.recipient_routing_seed(seed);
+    }
+    for rule in &config.maintenance {
+        builder = builder.maintenance(rule.clone());
+    }
+    for hook in &config.startup_hooks {
+        builder = builder.startup_hook(hook.clone());
+    }
+    for (path, spec) in &config.responses {
+        builder = builder.response(path.clone(), spec.clone());
+    }
+    if let Some(prefix) = &config.memo_prefix {
+        builder = builder.memo_prefix(prefix.clone());
+    }
+    builder = builder.id_format(config.id_format);
+    if let Some(seed) = config.id_seed {
+        builder = builder.id_seed(seed);
+    }
+    if let Some(protocol) = &config.protocol_id {
+        builder = builder.protocol_id(protocol.clone());
+    }
+    if let Some(rpc) = &config.rpc {
+        builder = builder.rpc(rpc.clone());
+    }
+    if args.tls_self_signed {
+        builder = builder.tls(TlsConfig::self_signed());
+    } else if let Some(tls) = &config.tls {
+        builder = builder.tls(tls.clone());
+    }
+    match &args.sign {
+        Some(Some(keypair)) => builder = builder.signing(SigningConfig::keypair(keypair)),
+        Some(None) => builder = builder.signing(SigningConfig::ephemeral()),
+        None => {
+            if let Some(signing) = &config.signing {
+                builder = builder.signing(signing.clone());
+            }
+        }
+    }
 
-    // Create pricing matcher
-    let pricing_matcher = PricingMatcher::new(server_config.pricing.clone());
+    if let Some(path) = &args.policy {
+        let runtime = load_policy_file(path)?.into_runtime()?;
+        let state = match &args.shared_state {
+            Some(path) => StateStoreConfig {
+                backend: StateBackend::Sqlite,
+                path: Some(path.clone()),
+            },
+            None => config.policy_state(),
+        };
+        builder = builder.policies(runtime.policies).policy_state(state);
+        if let Some(audit) =
+            config.audit_log_config(args.audit_log.as_deref(), args.audit_sample_rate)
+        {
+            builder = builder.audit_log(audit);
+        }
+    }
 
-    Ok(MockServerConfig {
-        port,
-        pricing_matcher,
-        invoice_generator: InvoiceGenerator::new(),
-        config: server_config,
-    })
+    let mut server_config = MockServerConfig::builder().config(builder.build()?);
+    if let Some(path) = &args.env_file {
+        server_config = server_config.env_file(path);
+    }
+    server_config.build()
 }
 
 /// Main entry point for mock command
-pub async fn run(args: &MockArgs) -> Result<()> {
+pub async fn run(args: &MockArgs, out: &Output) -> Result<()> {
     // Handle subcommands
     match &args.command {
         Some(MockSubcommand::Stop) => handle_stop().await,
         Some(MockSubcommand::Status) => handle_status().await,
         Some(MockSubcommand::Restart) => handle_restart(args).await,
+        Some(MockSubcommand::Stats { port, json }) => handle_stats(*port, *json).await,
+        Some(MockSubcommand::List { shared_state, json }) => {
+            handle_list(shared_state.as_deref(), *json).await
+        }
+        Some(MockSubcommand::Dash { port, refresh_ms }) => {
+            dash::run(*port, *refresh_ms, out.amounts).await
+        }
+        Some(MockSubcommand::Audit {
+            command:
+                AuditCommand::Query {
+                    file,
+                    agent,
+                    decision,
+                    policy,
+                    since,
+                    json,
+                },
+        }) => {
+            let query = AuditQuery {
+                agent: agent.clone(),
+                decision: *decision,
+                policy_id: policy.clone(),
+                since: since.as_deref().map(parse_since).transpose()?,
+            };
+            handle_audit_query(file.as_deref(), &query, *json)
+        }
+        Some(MockSubcommand::Snapshot { command }) => match command {
+            SnapshotCommand::Export {
+                file,
+                port,
+                max_invoices,
+            } => handle_snapshot_export(file, *port, *max_invoices).await,
+            SnapshotCommand::Import { file, port } => handle_snapshot_import(file, *port).await,
+            SnapshotCommand::Show { file, json } => handle_snapshot_show(file, *json),
+        },
         None => {
             let server_config = build_server_config(args)?;
+            x402_server::logging::init(&server_config.config)?;
             server_start(server_config).await
         }
     }
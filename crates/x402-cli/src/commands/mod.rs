@@ -1,10 +1,23 @@
 pub mod check;
+pub mod check_battery;
 pub mod config;
+pub mod convert;
+pub mod curl;
+pub mod dash;
 pub mod doctor;
+pub mod estimate;
 pub mod examples;
+pub mod explain;
+pub mod generate;
 pub mod init;
 pub mod invoice;
 pub mod mock;
 pub mod policy;
+pub mod policy_init;
+pub mod receipt;
+pub mod scenario;
+pub mod secret;
 pub mod test;
+pub mod verify;
+pub mod verify_report;
 pub mod version;
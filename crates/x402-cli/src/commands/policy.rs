@@ -1,23 +1,60 @@
 // Epic 5: Policy Management Command
 // Implements FR-5.6 (validate), FR-6.1 (Express), FR-6.2 (Fastify)
 
+use crate::cli::{parse_amount, parse_duration, OutputFormat};
+use crate::commands::policy_init::{run_wizard, write_policy, PolicyAnswers, PolicyTemplate};
+use crate::config::load_merged_config;
+use crate::output::Output;
+use crate::prompt::{Prompter, TerminalPrompter};
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use clap::{Args, Subcommand};
 use colored::Colorize;
-use std::path::PathBuf;
-use x402_core::policy::rules::PolicyFile;
-use x402_core::policy::types::PolicyConfig;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use x402_core::clock::VirtualClock;
+use x402_core::format::{DetectedFormat, FileFormat};
+use x402_core::policy::rules::{load_policy_file, LoadedPolicyFile, PolicyFile};
 use x402_core::policy::{
-    codegen::{generate_express_middleware, generate_fastify_plugin},
-    validate_policies, IssueType, ValidationReport,
+    codegen::{generate_express_middleware, generate_fastify_plugin, generate_test_suite},
+    discover_policy_files, fix_unsorted_values, lenient_requested, lint_policies,
+    parse_policy_file_lenient, simulate, validate_policies, IssueType, LintConfig, PolicyAction,
+    PolicyDecision, PolicyEngine, PolicyMatchExplanation, PopulationSpec, Request, RuleCode,
+    SimulationReport, SkippedRule, Suppressions, ValidationReport, DEFAULT_POLICIES_DIR,
 };
+use x402_core::testing::Annotation;
+use x402_server::policy_server::load_policies;
+use x402_server::{start_policy_server, DEFAULT_POLICY_SERVER_PORT};
 
 #[derive(Args)]
 #[command(after_help = "\
 EXAMPLES:
+  # Write a starter policy, or build one with guided questions
+  x402-dev policy init policy.yaml --from-template strict
+  x402-dev policy init policy.yaml --interactive
+
   # Validate policy file
   x402-dev policy validate policy.yaml
 
+  # Validate every workspace policy file
+  x402-dev policy validate
+
+  # Fail CI on warnings, except acknowledged multiple rate limits
+  x402-dev policy validate --fail-on warnings --allow X402-P010
+
+  # List validation rule codes
+  x402-dev policy rules
+
+  # Check style (sorted lists, round windows, ...) and sort lists in place
+  x402-dev policy lint policy.yaml --fix
+
+  # Validate and lint in one pass
+  x402-dev policy validate --with-lint
+
+  # List workspace policy files
+  x402-dev policy list
+
   # Generate Express middleware
   x402-dev policy generate policy.yaml --framework express --output middleware.js
 
@@ -27,6 +64,50 @@ EXAMPLES:
   # Print to stdout (for piping)
   x402-dev policy generate policy.yaml --framework express
 
+  # Generate one file per workspace policy into a directory
+  x402-dev policy generate --framework express --output generated/
+
+  # Also write a test suite that exercises the middleware
+  x402-dev policy generate policy.yaml --framework express --output middleware.js --with-tests policy-tests.yaml
+  x402-dev test policy-tests.yaml --var base_url=http://localhost:3000/api/data
+
+  # Convert a simple policy file to the runtime schema
+  x402-dev policy export-runtime policy.yaml runtime-policy.yaml
+
+  # Serve decisions over HTTP for non-Node services
+  x402-dev policy serve --policy policy.yaml --port 9402
+  x402-dev policy serve --policy policy.yaml --audit-log audit.jsonl --audit-sample-rate 0.1
+
+  # Show which policy decides a request and why the others did not
+  x402-dev policy explain-match policy.yaml --agent agent-1 --endpoint /api/data --amount 0.05
+
+  # Replay a synthetic agent population and report outcomes per cohort
+  x402-dev policy simulate --policy policy.yaml --population population.yaml --duration 10m
+
+  # Evaluate just before a daily spending cap resets, then a day later
+  x402-dev policy explain-match --agent agent-1 --endpoint /api/data --at 2025-03-14T23:59:00Z
+  x402-dev policy explain-match --agent agent-1 --endpoint /api/data --at 2025-03-14T23:59:00Z --advance 24h
+
+WORKSPACE POLICIES:
+  Without a file argument, commands use policy_files plus every YAML or JSON
+  file under policies_dir (default: ./x402-policies), minus .x402ignore matches.
+
+LINT:
+  Style rules (X402-L*) are configured in the `lint:` section of
+  .x402dev.yaml: `production: true` also flags agent allowlists of '*', and
+  `rules:` sets each code to error, warning, info or off. --fix rewrites
+  the file with sorted lists, dropping comments.
+
+SUPPRESSING WARNINGS:
+  Add `suppress: [X402-P010]` at the top of a policy file, or to one rule
+  to acknowledge only issues involving it. Suppressed warnings are still
+  reported, marked suppressed, and never fail validation. Errors cannot be
+  suppressed.
+
+GITHUB ACTIONS:
+  Under GitHub Actions, validate also prints errors and warnings as
+  annotations on the policy file.
+
 SEE ALSO:
   x402-dev monitor   Monitor policy enforcement
   x402-dev init      Initialize project with policies
@@ -40,23 +121,303 @@ pub struct PolicyArgs {
 pub enum PolicyCommand {
     /// Validate policy file syntax and detect conflicts (FR-5.6)
     Validate {
-        /// Path to policy YAML file
-        file: PathBuf,
+        /// Path to policy file (default: all workspace policy files)
+        file: Option<PathBuf>,
+
+        /// Lowest severity that fails validation
+        #[arg(long, value_enum, default_value = "errors")]
+        fail_on: FailOn,
+
+        /// Suppress warnings with this rule code (repeatable)
+        #[arg(long, value_name = "CODE", value_parser = parse_allowed_code)]
+        allow: Vec<RuleCode>,
+
+        /// Also check the style rules of `policy lint`
+        #[arg(long)]
+        with_lint: bool,
+
+        /// Skip rules that fail to parse, reporting them, and validate the
+        /// rest (as does `lenient: true` in the file)
+        #[arg(long)]
+        lenient: bool,
+
+        /// Output format: text, json or github
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Check policy style: sorted lists, round windows, explicit currencies
+    Lint {
+        /// Path to policy file (default: all workspace policy files)
+        file: Option<PathBuf>,
+
+        /// Sort allowlist and denylist values in place first
+        #[arg(long)]
+        fix: bool,
+
+        /// Lowest severity that fails lint
+        #[arg(long, value_enum, default_value = "errors")]
+        fail_on: FailOn,
+
+        /// Suppress warnings with this rule code (repeatable)
+        #[arg(long, value_name = "CODE", value_parser = parse_allowed_code)]
+        allow: Vec<RuleCode>,
+
+        /// Output format: text, json or github
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
     },
 
+    /// List validation and lint rule codes and their severities
+    Rules,
+
     /// Generate middleware code from policy file (FR-6.1, FR-6.2)
     Generate {
-        /// Path to policy YAML file
-        file: PathBuf,
+        /// Path to policy file (default: all workspace policy files)
+        file: Option<PathBuf>,
 
         /// Target framework (express or fastify)
         #[arg(long, short, value_name = "FRAMEWORK")]
         framework: Framework,
 
-        /// Output file path (prints to stdout if omitted)
+        /// Output file path, or directory for workspace policies (prints to stdout if omitted)
         #[arg(long, short, value_name = "FILE")]
         output: Option<PathBuf>,
+
+        /// Also write a test suite for the middleware (directory for workspace policies)
+        #[arg(long, value_name = "FILE")]
+        with_tests: Option<PathBuf>,
+
+        /// Test suite format: yaml or json (default: from the --with-tests extension)
+        #[arg(long, value_name = "FORMAT", requires = "with_tests")]
+        format: Option<FileFormat>,
+    },
+
+    /// List workspace policy files with rule counts and modification times
+    List,
+
+    /// Convert a policy file to the runtime schema with explicit priorities
+    ExportRuntime {
+        /// Path to policy file (simple or runtime schema)
+        input: PathBuf,
+
+        /// Path to write the runtime policy file
+        output: PathBuf,
+
+        /// Output format: yaml or json (default: from the output extension)
+        #[arg(long, value_name = "FORMAT")]
+        format: Option<FileFormat>,
     },
+
+    /// Run the policy engine as an HTTP sidecar (/evaluate, /policies, /reload)
+    Serve {
+        /// Path to policy file (simple or runtime schema)
+        #[arg(long, value_name = "FILE")]
+        policy: PathBuf,
+
+        /// Port to listen on (127.0.0.1)
+        #[arg(long, short, default_value_t = DEFAULT_POLICY_SERVER_PORT)]
+        port: u16,
+
+        /// Write decisions to a JSONL file (overrides audit_log)
+        #[arg(long, value_name = "PATH")]
+        audit_log: Option<PathBuf>,
+
+        /// Share of allowed decisions written to the audit log, 0.0 to 1.0;
+        /// denials are always written (overrides audit_sample_rate)
+        #[arg(long, value_name = "RATE", value_parser = crate::cli::parse_sample_rate)]
+        audit_sample_rate: Option<f64>,
+    },
+
+    /// Show which policy decides a request, with every policy considered
+    ExplainMatch {
+        /// Path to policy file (default: the only workspace policy file)
+        file: Option<PathBuf>,
+
+        /// Agent ID of the request
+        #[arg(long)]
+        agent: String,
+
+        /// Endpoint of the request, e.g. /api/data
+        #[arg(long)]
+        endpoint: String,
+
+        /// Amount of the request (checked against spending caps), e.g. 0.05
+        #[arg(long, default_value_t = 0.0, value_parser = parse_amount)]
+        amount: f64,
+
+        #[command(flatten)]
+        clock: ClockArgs,
+
+        /// Print the explanation as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Write a starter policy file, or build one with guided questions
+    Init {
+        /// Policy file to write (format from the extension)
+        #[arg(default_value = "policy.yaml")]
+        file: PathBuf,
+
+        /// Ask about agents, limits and endpoints, validating each answer
+        #[arg(long, short)]
+        interactive: bool,
+
+        /// Starting point: strict, permissive or demo
+        #[arg(long, value_enum, value_name = "TEMPLATE", default_value = "demo")]
+        from_template: PolicyTemplate,
+
+        /// Overwrite FILE if it exists
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Replay a synthetic agent population against a policy file
+    Simulate {
+        /// Path to policy file (simple or runtime schema)
+        #[arg(long, value_name = "FILE")]
+        policy: PathBuf,
+
+        /// Population file describing the agent cohorts
+        #[arg(long, value_name = "FILE")]
+        population: PathBuf,
+
+        /// Simulated time span, e.g. 60s or 10m
+        #[arg(long, default_value = "60s", value_parser = parse_duration)]
+        duration: Duration,
+
+        /// Override the population's seed
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Start of the simulated traffic
+        #[command(flatten)]
+        clock: ClockArgs,
+
+        /// Print the cohort report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Time of policy evaluation: `--at` moved on by `--advance`
+#[derive(Args, Debug, Clone, Default)]
+pub struct ClockArgs {
+    /// Evaluate at this RFC 3339 time instead of now, e.g. 2025-03-14T23:59:00Z
+    #[arg(long, value_name = "TIME", value_parser = parse_rfc3339)]
+    pub at: Option<DateTime<Utc>>,
+
+    /// Move the clock forward from --at (or now), e.g. 25h or 90 (seconds)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub advance: Option<Duration>,
+}
+
+impl ClockArgs {
+    /// Virtual clock reading `--at` (default: now) plus `--advance`
+    fn clock(&self) -> Arc<VirtualClock> {
+        let clock = VirtualClock::starting_at(self.at.map_or_else(SystemTime::now, Into::into));
+        if let Some(advance) = self.advance {
+            clock.advance(advance);
+        }
+        Arc::new(clock)
+    }
+
+    /// True when the evaluation time is not simply now
+    fn is_set(&self) -> bool {
+        self.at.is_some() || self.advance.is_some()
+    }
+}
+
+fn parse_rfc3339(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|at| at.with_timezone(&Utc))
+        .map_err(|e| format!("{} (expected RFC 3339, e.g. 2025-03-14T23:59:00Z)", e))
+}
+
+/// Severity at which `policy validate` fails
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailOn {
+    Errors,
+    Warnings,
+}
+
+impl FailOn {
+    /// True if `report` has unsuppressed issues at or above this severity;
+    /// skipped rules count as warnings
+    fn fails(self, report: &ValidationReport) -> bool {
+        report.has_errors
+            || (self == FailOn::Warnings && (report.has_warnings || !report.skipped.is_empty()))
+    }
+}
+
+/// Parse an `--allow` rule code, rejecting codes of error rules
+fn parse_allowed_code(s: &str) -> Result<RuleCode, String> {
+    let code: RuleCode = s.parse()?;
+    code.check_suppressible()
+        .map_err(|e| e.to_string().lines().next().unwrap_or_default().to_string())?;
+    Ok(code)
+}
+
+/// Options of `policy validate` and `policy lint`
+struct ValidateOptions {
+    fail_on: FailOn,
+    allow: Vec<RuleCode>,
+    format: OutputFormat,
+    passes: Passes,
+    parsing: RuleParsing,
+}
+
+/// What happens to policy rules that fail to parse
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RuleParsing {
+    /// The file is rejected, unless it sets `lenient: true`
+    Strict,
+    /// They are skipped and reported (`--lenient`)
+    Lenient,
+    /// The file is rejected, and so is a file setting `lenient: true`
+    /// (code generation)
+    Enforcing,
+}
+
+/// Checks run on each policy file
+enum Passes {
+    Validate,
+    ValidateAndLint(LintConfig),
+    Lint(LintConfig),
+}
+
+/// Words of the text report for the checks that ran
+struct Wording {
+    title: &'static str,
+    name: &'static str,
+    file_passed: &'static str,
+    files_passed: &'static str,
+    passed: &'static str,
+    failed: &'static str,
+}
+
+impl Passes {
+    fn wording(&self) -> Wording {
+        match self {
+            Passes::Validate | Passes::ValidateAndLint(_) => Wording {
+                title: "Policy Validation",
+                name: "validation",
+                file_passed: "is valid",
+                files_passed: "are valid",
+                passed: "Valid",
+                failed: "Invalid",
+            },
+            Passes::Lint(_) => Wording {
+                title: "Policy Lint",
+                name: "lint",
+                file_passed: "passes lint",
+                files_passed: "pass lint",
+                passed: "Passes lint",
+                failed: "Fails lint",
+            },
+        }
+    }
 }
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -65,79 +426,620 @@ pub enum Framework {
     Fastify,
 }
 
-pub fn handle_policy_command(args: PolicyArgs) -> Result<()> {
+pub async fn handle_policy_command(args: PolicyArgs, out: &Output) -> Result<()> {
     match args.command {
-        PolicyCommand::Validate { file } => validate_command(file),
-        PolicyCommand::Generate {
+        PolicyCommand::Validate {
+            file,
+            fail_on,
+            allow,
+            with_lint,
+            lenient,
+            format,
+        } => {
+            let passes = if with_lint {
+                Passes::ValidateAndLint(load_merged_config(None)?.lint)
+            } else {
+                Passes::Validate
+            };
+            let options = ValidateOptions {
+                fail_on,
+                allow,
+                format: OutputFormat::resolve(format),
+                passes,
+                parsing: if lenient {
+                    RuleParsing::Lenient
+                } else {
+                    RuleParsing::Strict
+                },
+            };
+            check_command(file, &options, out)
+        }
+        PolicyCommand::Lint {
             file,
+            fix,
+            fail_on,
+            allow,
+            format,
+        } => {
+            let options = ValidateOptions {
+                fail_on,
+                allow,
+                format: OutputFormat::resolve(format),
+                passes: Passes::Lint(load_merged_config(None)?.lint),
+                parsing: RuleParsing::Strict,
+            };
+            if fix {
+                let files = match &file {
+                    Some(file) => vec![file.clone()],
+                    None => workspace_policy_files()?.1,
+                };
+                for file in &files {
+                    fix_policy_file(file, options.format, out)?;
+                }
+            }
+            check_command(file, &options, out)
+        }
+        PolicyCommand::Rules => rules_command(out),
+        PolicyCommand::Generate {
+            file: Some(file),
             framework,
             output,
-        } => generate_command(file, framework, output),
+            with_tests,
+            format,
+        } => generate_command(file, framework, output, with_tests, format, out),
+        PolicyCommand::Generate {
+            file: None,
+            framework,
+            output,
+            with_tests,
+            format,
+        } => generate_workspace_command(framework, output, with_tests, format, out),
+        PolicyCommand::List => list_command(out),
+        PolicyCommand::ExportRuntime {
+            input,
+            output,
+            format,
+        } => export_runtime_command(input, output, format, out),
+        PolicyCommand::Serve {
+            policy,
+            port,
+            audit_log,
+            audit_sample_rate,
+        } => {
+            let config = load_merged_config(None)?;
+            let audit = config.audit_log_config(audit_log.as_deref(), audit_sample_rate);
+            start_policy_server(policy, port, config.policy_state(), audit).await
+        }
+        PolicyCommand::ExplainMatch {
+            file,
+            agent,
+            endpoint,
+            amount,
+            clock,
+            json,
+        } => explain_match_command(file, agent, endpoint, amount, &clock, json, out),
+        PolicyCommand::Simulate {
+            policy,
+            population,
+            duration,
+            seed,
+            clock,
+            json,
+        } => simulate_command(&policy, &population, duration, seed, &clock, json, out),
+        PolicyCommand::Init {
+            file,
+            interactive,
+            from_template,
+            force,
+        } => init_command(
+            &mut TerminalPrompter,
+            &file,
+            interactive,
+            from_template,
+            force,
+            out,
+        ),
     }
 }
 
-/// FR-5.6: Policy validation with conflict detection
-fn validate_command(file: PathBuf) -> Result<()> {
-    println!("{}", "Policy Validation".bold().cyan());
-    println!("File: {}\n", file.display());
+/// Write a policy file from a template, through the wizard if `interactive`
+fn init_command(
+    prompter: &mut dyn Prompter,
+    file: &Path,
+    interactive: bool,
+    template: PolicyTemplate,
+    mut force: bool,
+    out: &Output,
+) -> Result<()> {
+    let start = PolicyAnswers::template(template);
+    let answers = if interactive {
+        out.status("Policy Wizard".bold().cyan());
+        if file.exists() && !force {
+            let prompt = format!("{} already exists. Overwrite it?", file.display());
+            if !prompter.confirm(&prompt, false)? {
+                out.line(format!("Cancelled; {} was not changed", file.display()));
+                return Ok(());
+            }
+            force = true;
+        }
+        let format = FileFormat::from_path(file).unwrap_or_default();
+        match run_wizard(prompter, start, format)? {
+            Some(answers) => answers,
+            None => {
+                out.line("Cancelled; nothing was written");
+                return Ok(());
+            }
+        }
+    } else {
+        start
+    };
+
+    let config = answers.to_config();
+    write_policy(file, &config, force)?;
+    out.line(format!(
+        "{} Wrote {} ({} rules)",
+        "✓".green().bold(),
+        file.display(),
+        config.policies.len()
+    ));
+
+    if interactive {
+        let frameworks = ["No", "Express middleware", "Fastify plugin"];
+        let framework = match prompter.select("Generate middleware now?", &frameworks, 0)? {
+            1 => Framework::Express,
+            2 => Framework::Fastify,
+            _ => return Ok(()),
+        };
+        let (_, code) = generate_code(file, &framework, out)?;
+        let stem = file
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("policy");
+        let suffix = match framework {
+            Framework::Express => "express",
+            Framework::Fastify => "fastify",
+        };
+        let output = file.with_file_name(format!("{}-{}.js", stem, suffix));
+        std::fs::write(&output, code)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+        out.line(format!(
+            "{} Generated {}",
+            "✓".green().bold(),
+            output.display()
+        ));
+    } else {
+        out.status("\nNext: x402-dev policy init --interactive to adjust it step by step,");
+        out.status(format!("      x402-dev policy validate {}", file.display()));
+    }
+    Ok(())
+}
+
+/// Run the checks of `options` on one file, or every workspace policy file
+fn check_command(file: Option<PathBuf>, options: &ValidateOptions, out: &Output) -> Result<()> {
+    match file {
+        Some(file) if options.format == OutputFormat::Json => {
+            validate_json_command(vec![(file.display().to_string(), file)], options)
+        }
+        Some(file) => validate_command(file, options, out),
+        None => validate_workspace_command(options, out),
+    }
+}
 
-    // Load policy file
-    let policy_content = std::fs::read_to_string(&file)
+/// `policy lint --fix`: sort allowlist and denylist values in place
+///
+/// Files whose lists are already sorted are left untouched.
+fn fix_policy_file(file: &Path, format: OutputFormat, out: &Output) -> Result<()> {
+    let content = std::fs::read_to_string(file)
         .with_context(|| format!("Failed to read policy file: {}", file.display()))?;
+    let detected = DetectedFormat::detect(Some(file), &content);
+    let fixed = fix_unsorted_values(&content, detected)
+        .map_err(|e| anyhow::anyhow!("Invalid policy file: {}: {:#}", file.display(), e))?;
+    let Some(fixed) = fixed else {
+        return Ok(());
+    };
+    crate::safe_write::write(file, fixed)
+        .with_context(|| format!("Failed to write policy file: {}", file.display()))?;
+
+    // Keep stdout a single JSON document
+    let note = format!(
+        "{} Sorted list values: {}",
+        "✓".green().bold(),
+        file.display()
+    );
+    if format == OutputFormat::Json {
+        out.error(note);
+    } else {
+        out.line(note);
+    }
+    Ok(())
+}
+
+/// Policy files of the current workspace, as configured in .x402dev.yaml
+///
+/// Returns the workspace root (for display) and the discovered files.
+fn workspace_policy_files() -> Result<(PathBuf, Vec<PathBuf>)> {
+    let config = load_merged_config(None)?;
+    let root = std::env::current_dir().context("Could not determine current directory")?;
+    let files = discover_policy_files(&root, config.policies_dir.as_deref(), &config.policy_files)?;
 
-    let policy_file: PolicyFile =
-        serde_yaml::from_str(&policy_content).context("Failed to parse YAML policy file")?;
+    if files.is_empty() {
+        anyhow::bail!(
+            "No policy files found in {}\n\
+             Fix: Add YAML or JSON policy files to {}/ or set policies_dir / policy_files in .x402dev.yaml",
+            root.display(),
+            config
+                .policies_dir
+                .as_deref()
+                .unwrap_or(Path::new(DEFAULT_POLICIES_DIR))
+                .display()
+        );
+    }
+    Ok((root, files))
+}
+
+/// Path relative to the workspace root, for report headings
+fn display_path(root: &Path, file: &Path) -> String {
+    file.strip_prefix(root)
+        .unwrap_or(file)
+        .display()
+        .to_string()
+}
+
+/// Load a policy file and run conflict detection and/or lint on it
+///
+/// Issues covered by the file's `suppress:` lists or by `allow` are marked
+/// suppressed. Rules skipped by lenient parsing are listed in the report's
+/// `skipped`, apart from the issues.
+fn validate_file(
+    file: &Path,
+    allow: &[RuleCode],
+    passes: &Passes,
+    parsing: RuleParsing,
+) -> Result<(PolicyFile, ValidationReport)> {
+    let policy_content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read policy file: {}", file.display()))?;
+
+    let format = DetectedFormat::detect(Some(file), &policy_content);
+    let invalid =
+        |e: anyhow::Error| anyhow::anyhow!("Invalid policy file: {}: {:#}", file.display(), e);
+    let doc: serde_yaml::Value = format.parse(&policy_content).map_err(invalid)?;
+    let lenient = match parsing {
+        RuleParsing::Lenient => true,
+        RuleParsing::Strict => lenient_requested(&doc),
+        RuleParsing::Enforcing if lenient_requested(&doc) => anyhow::bail!(
+            "Policy file {} is marked `lenient: true`; code is only generated from files whose every rule parses\nFix: Fix the rules `x402-dev policy validate {}` skips and remove `lenient: true`",
+            file.display(),
+            file.display()
+        ),
+        RuleParsing::Enforcing => false,
+    };
+    let (policy_file, indices, skipped) = if lenient {
+        let parsed = parse_policy_file_lenient(&policy_content, format).map_err(invalid)?;
+        (parsed.file, Some(parsed.indices), parsed.skipped)
+    } else {
+        let policy_file: PolicyFile = format.parse(&policy_content).map_err(|e| match parsing {
+            RuleParsing::Strict => anyhow::anyhow!(
+                "{:#}\nFix: Correct the rule, or pass --lenient to skip rules that fail to parse and validate the rest",
+                invalid(e)
+            ),
+            _ => invalid(e),
+        })?;
+        (policy_file, None, Vec::new())
+    };
+    let mut suppressions = Suppressions::parse(&policy_content, format)?;
+    suppressions.allow(allow);
 
     // Convert PolicyFile to PolicyConfig for validation
-    let policy_config = PolicyConfig {
-        policies: policy_file.policies.clone(),
+    let config = policy_file.to_config();
+    let mut report = match passes {
+        Passes::Validate => validate_policies(&config),
+        Passes::ValidateAndLint(lint) => {
+            let mut report = validate_policies(&config);
+            report.extend(lint_policies(&config, lint));
+            report
+        }
+        Passes::Lint(lint) => lint_policies(&config, lint),
     };
+    // Number issues by position in the file, as suppressions do
+    if let Some(indices) = indices {
+        report.renumber_policies(&indices);
+    }
+    report.skipped = skipped;
+    report.apply_suppressions(&suppressions);
+    Ok((policy_file, report))
+}
 
-    // Validate policies
-    let report = validate_policies(&policy_config);
+/// Fix hint when warnings fail validation under `--fail-on warnings`
+const WARNINGS_FIX: &str = "Fix: Resolve the warnings, or acknowledge them with `suppress: [CODE]` in the policy file or --allow CODE";
+
+/// FR-5.6: Policy validation with conflict detection
+fn validate_command(file: PathBuf, options: &ValidateOptions, out: &Output) -> Result<()> {
+    let wording = options.passes.wording();
+    out.status(wording.title.bold().cyan());
+    out.status(format!("File: {}\n", file.display()));
+
+    let (_, report) = validate_file(&file, &options.allow, &options.passes, options.parsing)
+        .inspect_err(|e| {
+            annotate_policy_file(options.format, &file.display().to_string(), Err(e), out);
+        })?;
 
     // Display validation results
-    display_validation_report(&report);
+    display_validation_report(&report, out);
+    annotate_policy_file(
+        options.format,
+        &file.display().to_string(),
+        Ok(&report),
+        out,
+    );
 
     if report.has_errors {
-        anyhow::bail!("Policy validation failed with errors");
+        anyhow::bail!("Policy {} failed with errors", wording.name);
     }
+    if options.fail_on.fails(&report) {
+        anyhow::bail!(
+            "Policy {} failed with warnings (--fail-on warnings)\n{}",
+            wording.name,
+            WARNINGS_FIX
+        );
+    }
+
+    if !report.has_warnings && report.skipped.is_empty() {
+        out.line(format!(
+            "\n{} Policy file {}!{}",
+            "".green().bold(),
+            wording.file_passed,
+            report_note(&report)
+        ));
+    } else {
+        out.line(format!(
+            "\n{} Policy file {} (with warnings){}",
+            "�".yellow().bold(),
+            wording.file_passed,
+            report_note(&report)
+        ));
+    }
+
+    Ok(())
+}
 
-    if !report.has_warnings {
-        println!("\n{} Policy file is valid!", "".green().bold());
+/// " (N rules skipped, M suppressed)" for the parts the report has
+fn report_note(report: &ValidationReport) -> String {
+    let mut parts = Vec::new();
+    match report.skipped.len() {
+        0 => {}
+        1 => parts.push("1 rule skipped".to_string()),
+        n => parts.push(format!("{} rules skipped", n)),
+    }
+    if report.suppressed() > 0 {
+        parts.push(format!("{} suppressed", report.suppressed()));
+    }
+    if parts.is_empty() {
+        String::new()
     } else {
-        println!(
-            "\n{} Policy file is valid (with warnings)",
-            "�".yellow().bold()
+        format!(" ({})", parts.join(", "))
+    }
+}
+
+/// Validate every workspace policy file, one report section per file
+fn validate_workspace_command(options: &ValidateOptions, out: &Output) -> Result<()> {
+    let (root, files) = workspace_policy_files()?;
+    if options.format == OutputFormat::Json {
+        let files = files
+            .into_iter()
+            .map(|file| (display_path(&root, &file), file))
+            .collect();
+        return validate_json_command(files, options);
+    }
+
+    let wording = options.passes.wording();
+    out.status(wording.title.bold().cyan());
+    out.status(format!(
+        "Workspace: {} ({} files)\n",
+        root.display(),
+        files.len()
+    ));
+
+    let mut failed = 0;
+    for file in &files {
+        let shown = display_path(&root, file);
+        out.line(format!("── {} ──", shown).bold());
+        match validate_file(file, &options.allow, &options.passes, options.parsing) {
+            Ok((_, report)) => {
+                display_validation_report(&report, out);
+                annotate_policy_file(options.format, &shown, Ok(&report), out);
+                if report.has_errors {
+                    failed += 1;
+                    out.line(format!("{} {}", "✗".red().bold(), wording.failed));
+                } else if options.fail_on.fails(&report) {
+                    failed += 1;
+                    out.line(format!(
+                        "{} {} (warnings, --fail-on warnings)",
+                        "✗".red().bold(),
+                        wording.failed
+                    ));
+                } else if report.has_warnings || !report.skipped.is_empty() {
+                    out.line(format!(
+                        "{} {} (with warnings)",
+                        "⚠".yellow().bold(),
+                        wording.passed
+                    ));
+                } else {
+                    out.line(format!("{} {}", "✓".green().bold(), wording.passed));
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                out.line(format!("{} {:#}", "✗".red().bold(), e));
+                annotate_policy_file(options.format, &shown, Err(&e), out);
+            }
+        }
+        out.line("");
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "Policy {} failed for {} of {} files",
+            wording.name,
+            failed,
+            files.len()
         );
     }
 
+    out.line(format!(
+        "{} All {} policy files {}",
+        "✓".green().bold(),
+        files.len(),
+        wording.files_passed
+    ));
+    Ok(())
+}
+
+/// `--format json`: one JSON report for `files` (display name, path)
+fn validate_json_command(files: Vec<(String, PathBuf)>, options: &ValidateOptions) -> Result<()> {
+    let mut passed = true;
+    let reports: Vec<serde_json::Value> = files
+        .iter()
+        .map(|(shown, file)| match validate_file(file, &options.allow, &options.passes, options.parsing) {
+            Ok((_, report)) => {
+                let file_passed = !options.fail_on.fails(&report);
+                passed &= file_passed;
+                let (errors, warnings, _) = report.counts();
+                serde_json::json!({
+                    "file": shown,
+                    "passed": file_passed,
+                    "errors": errors,
+                    "warnings": warnings,
+                    "suppressed": report.suppressed(),
+                    "issues": report.issues.iter().map(|issue| serde_json::json!({
+                        "code": issue.code.as_str(),
+                        "severity": severity_name(&issue.issue_type),
+                        "suppressed": issue.suppressed,
+                        "message": issue.message,
+                        "details": issue.details,
+                        "policies": issue.policy_indices,
+                    })).collect::<Vec<_>>(),
+                    "skipped": report.skipped.iter().map(|rule| serde_json::json!({
+                        "policy": rule.index,
+                        "line": rule.line,
+                        "error": rule.error,
+                    })).collect::<Vec<_>>(),
+                })
+            }
+            Err(e) => {
+                passed = false;
+                serde_json::json!({ "file": shown, "passed": false, "error": format!("{:#}", e) })
+            }
+        })
+        .collect();
+
+    let output = serde_json::json!({
+        "passed": passed,
+        "fail_on": match options.fail_on {
+            FailOn::Errors => "errors",
+            FailOn::Warnings => "warnings",
+        },
+        "files": reports,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    if !passed {
+        anyhow::bail!("Policy {} failed", options.passes.wording().name);
+    }
+    Ok(())
+}
+
+fn severity_name(issue_type: &IssueType) -> &'static str {
+    match issue_type {
+        IssueType::Error => "error",
+        IssueType::Warning => "warning",
+        IssueType::Info => "info",
+        _ => "unknown",
+    }
+}
+
+/// Print the validation rule table
+fn rules_command(out: &Output) -> Result<()> {
+    out.status("Policy Validation Rules".bold().cyan());
+    out.status("");
+    for code in RuleCode::ALL {
+        out.line(format!(
+            "  {}  {:<8} {}",
+            code.as_str().bold(),
+            severity_name(&code.severity()),
+            code.summary()
+        ));
+    }
+    out.line("");
+    out.line("X402-L* rules run with `policy lint` or `policy validate --with-lint`; set their");
+    out.line("severity under lint.rules in .x402dev.yaml.");
+    out.line("Suppress a warning with `suppress: [CODE]` in the policy file or on one rule,");
+    out.line("or with `x402-dev policy validate --allow CODE`. Errors cannot be suppressed.");
     Ok(())
 }
 
 /// FR-6.1, FR-6.2: Generate middleware code
-fn generate_command(file: PathBuf, framework: Framework, output: Option<PathBuf>) -> Result<()> {
-    println!("{}", "Code Generation".bold().cyan());
-    println!("Policy file: {}", file.display());
-    println!("Framework: {:?}\n", framework);
+fn generate_command(
+    file: PathBuf,
+    framework: Framework,
+    output: Option<PathBuf>,
+    with_tests: Option<PathBuf>,
+    format: Option<FileFormat>,
+    out: &Output,
+) -> Result<()> {
+    out.status("Code Generation".bold().cyan());
+    out.status(format!("Policy file: {}", file.display()));
+    out.status(format!("Framework: {:?}\n", framework));
 
-    // Load policy file
-    let policy_content = std::fs::read_to_string(&file)
-        .with_context(|| format!("Failed to read policy file: {}", file.display()))?;
+    let (policy_file, generated_code) = generate_code(&file, &framework, out)?;
 
-    let policy_file: PolicyFile =
-        serde_yaml::from_str(&policy_content).context("Failed to parse YAML policy file")?;
+    // Output code
+    if let Some(output_path) = output {
+        std::fs::write(&output_path, &generated_code)
+            .with_context(|| format!("Failed to write output file: {}", output_path.display()))?;
+        out.line(format!(
+            "{} Generated middleware: {}",
+            "".green().bold(),
+            output_path.display()
+        ));
+        out.status(format!("  Lines: {}", generated_code.lines().count()));
+        out.status(format!("  Size: {} bytes", generated_code.len()));
+    } else {
+        // Print to stdout, untouched by --ascii
+        println!("{}", generated_code);
+    }
 
-    // Convert PolicyFile to PolicyConfig for validation
-    let policy_config = PolicyConfig {
-        policies: policy_file.policies.clone(),
-    };
+    if let Some(tests_path) = with_tests {
+        let format = format
+            .or_else(|| FileFormat::from_path(&tests_path))
+            .unwrap_or_default();
+        let count = write_test_suite(&policy_file, &tests_path, format)?;
+        out.line(format!(
+            "{} Generated test suite: {} ({} tests)",
+            "✓".green().bold(),
+            tests_path.display(),
+            count
+        ));
+    }
+
+    Ok(())
+}
 
+/// Write the test suite derived from a policy file, returning its test count
+fn write_test_suite(policy_file: &PolicyFile, path: &Path, format: FileFormat) -> Result<usize> {
+    let suite = generate_test_suite(policy_file);
+    crate::safe_write::write(path, suite.render(format)?)
+        .with_context(|| format!("Failed to write test suite: {}", path.display()))?;
+    Ok(suite.tests.len())
+}
+
+/// Validate a policy file and generate middleware for it
+fn generate_code(file: &Path, framework: &Framework, out: &Output) -> Result<(PolicyFile, String)> {
     // Validate before generation
-    let report = validate_policies(&policy_config);
+    let (policy_file, report) =
+        validate_file(file, &[], &Passes::Validate, RuleParsing::Enforcing)?;
     if report.has_errors {
-        eprintln!("{}", " Policy validation failed:".red().bold());
-        display_validation_report(&report);
+        out.error(" Policy validation failed:".red().bold());
+        display_validation_report(&report, out);
         anyhow::bail!("Cannot generate code from invalid policy file");
     }
 
@@ -147,50 +1049,552 @@ fn generate_command(file: PathBuf, framework: Framework, output: Option<PathBuf>
         .and_then(|n| n.to_str())
         .unwrap_or("policy.yaml");
 
-    let generated_code = match framework {
-        Framework::Express => generate_express_middleware(&policy_file, policy_filename),
-        Framework::Fastify => generate_fastify_plugin(&policy_file.policies, Some(policy_filename)),
+    // Quota header names follow the mock's `quota_headers` for parity
+    let quota_headers = load_merged_config(None)?.quota_headers;
+    let code = match framework {
+        Framework::Express => {
+            generate_express_middleware(&policy_file, policy_filename, &quota_headers)
+        }
+        Framework::Fastify => {
+            generate_fastify_plugin(&policy_file.policies, Some(policy_filename), &quota_headers)
+        }
     };
+    Ok((policy_file, code))
+}
 
-    // Output code
-    if let Some(output_path) = output {
-        std::fs::write(&output_path, &generated_code)
-            .with_context(|| format!("Failed to write output file: {}", output_path.display()))?;
-        println!(
-            "{} Generated middleware: {}",
-            "".green().bold(),
-            output_path.display()
+/// Generate middleware for every workspace policy file
+///
+/// With `--output`, writes `<output>/<policy name>.js` per file; otherwise
+/// prints each file's code after a `// Source:` comment.
+fn generate_workspace_command(
+    framework: Framework,
+    output: Option<PathBuf>,
+    with_tests: Option<PathBuf>,
+    format: Option<FileFormat>,
+    out: &Output,
+) -> Result<()> {
+    let (root, files) = workspace_policy_files()?;
+    let format = format.unwrap_or_default();
+
+    for dir in output.iter().chain(&with_tests) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create output directory: {}", dir.display()))?;
+    }
+
+    let mut failed = 0;
+    for file in &files {
+        let name = display_path(&root, file);
+        let (policy_file, code) = match generate_code(file, &framework, out) {
+            Ok(generated) => generated,
+            Err(e) => {
+                failed += 1;
+                out.error(format!("{} {}: {:#}", "✗".red().bold(), name, e));
+                continue;
+            }
+        };
+
+        let stem = file
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("policy");
+        match &output {
+            Some(dir) => {
+                let path = dir.join(format!("{}.js", stem));
+                std::fs::write(&path, &code)
+                    .with_context(|| format!("Failed to write output file: {}", path.display()))?;
+                out.line(format!(
+                    "{} {} -> {}",
+                    "✓".green().bold(),
+                    name,
+                    path.display()
+                ));
+            }
+            None => println!("// Source: {}\n{}", name, code),
+        }
+
+        if let Some(dir) = &with_tests {
+            let path = dir.join(format!("{}.test.{}", stem, format.extension()));
+            let count = write_test_suite(&policy_file, &path, format)?;
+            out.line(format!(
+                "{} {} -> {} ({} tests)",
+                "✓".green().bold(),
+                name,
+                path.display(),
+                count
+            ));
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "Code generation failed for {} of {} files",
+            failed,
+            files.len()
         );
-        println!("  Lines: {}", generated_code.lines().count());
-        println!("  Size: {} bytes", generated_code.len());
+    }
+    Ok(())
+}
+
+/// Print discovered workspace policy files
+fn list_command(out: &Output) -> Result<()> {
+    let (root, files) = workspace_policy_files()?;
+
+    out.status("Workspace Policies".bold().cyan());
+    out.status(format!("Workspace: {}\n", root.display()));
+
+    for file in &files {
+        let rules = match load_policy_file(file) {
+            Ok(LoadedPolicyFile::Simple(policy_file)) => {
+                format!("{} rules", policy_file.policies.len())
+            }
+            Ok(LoadedPolicyFile::Runtime(runtime)) => {
+                format!("{} rules", runtime.policies.len())
+            }
+            Err(_) => "invalid".to_string(),
+        };
+        let modified = std::fs::metadata(file)
+            .and_then(|m| m.modified())
+            .map(|t| {
+                chrono::DateTime::<chrono::Local>::from(t)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|_| "-".to_string());
+
+        out.line(format!(
+            "  {:<40} {:>10}  {}",
+            display_path(&root, file),
+            rules,
+            modified.dimmed()
+        ));
+    }
+
+    out.line(format!("\n{} policy files", files.len()));
+    Ok(())
+}
+
+/// Write the runtime-schema equivalent of a policy file
+/// Explain a request against a policy file with fresh (empty) state
+///
+/// Lists the candidates from [`PolicyEngine::explain`] and, since limits
+/// are not part of matching, also evaluates the request once so a spending
+/// cap the amount exceeds shows up in the decision. The request is made at
+/// the time of `clock`.
+fn explain_match_command(
+    file: Option<PathBuf>,
+    agent: String,
+    endpoint: String,
+    amount: f64,
+    clock: &ClockArgs,
+    json: bool,
+    out: &Output,
+) -> Result<()> {
+    if !amount.is_finite() || amount < 0.0 {
+        anyhow::bail!(
+            "--amount must be a non-negative number, got {}\nFix: Pass the request amount, e.g. --amount 0.05",
+            amount
+        );
+    }
+    let file = match file {
+        Some(file) => file,
+        None => {
+            let (root, mut files) = workspace_policy_files()?;
+            if files.len() > 1 {
+                anyhow::bail!(
+                    "{} workspace policy files found in {}\nFix: Pass the policy file to explain against",
+                    files.len(),
+                    root.display()
+                );
+            }
+            files.remove(0)
+        }
+    };
+
+    let engine = PolicyEngine::new(load_policies(&file)?).with_clock(clock.clock());
+    let request = Request {
+        agent_id: agent,
+        wallet_address: None,
+        ip_address: None,
+        endpoint,
+        amount,
+        timestamp: engine.now(),
+        memo_id: None,
+        request_id: None,
+    };
+    let at: DateTime<Utc> = request.timestamp.into();
+    let explanation = engine.explain(&request);
+    let (decision, policy_id, reason) = match engine.evaluate(&request)? {
+        PolicyDecision::Allow { policy_id, .. } => ("allow", policy_id, None),
+        PolicyDecision::Deny { reason, policy_id } => ("deny", policy_id, Some(reason)),
+        other => anyhow::bail!("Unsupported policy decision: {:?}", other),
+    };
+
+    if json {
+        let report = serde_json::json!({
+            "file": file.display().to_string(),
+            "amount": amount,
+            "at": at.to_rfc3339(),
+            "explanation": explanation,
+            "decision": {
+                "decision": decision,
+                "policy_id": policy_id,
+                "reason": reason,
+            },
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    out.status("Policy Match".bold().cyan());
+    out.status(format!("File: {}", file.display()));
+    if clock.is_set() {
+        out.status(format!("At: {}", at.to_rfc3339()));
+    }
+    out.line(format!(
+        "Request: agent {} → {} (amount {})\n",
+        explanation.agent_id, explanation.endpoint, amount
+    ));
+    print!("{}", out.text(&render_match_explanation(&explanation)));
+    out.line("");
+    match reason {
+        None => out.line(format!("{} Allowed by {}", "✓".green().bold(), policy_id)),
+        Some(reason) => out.line(format!(
+            "{} Denied by {}: {}",
+            "✗".red().bold(),
+            policy_id,
+            reason
+        )),
+    }
+    Ok(())
+}
+
+/// Policies in evaluation order; the winner is marked `→`
+fn render_match_explanation(explanation: &PolicyMatchExplanation) -> String {
+    let width = explanation
+        .candidates
+        .iter()
+        .map(|c| c.policy_id.len())
+        .chain(["POLICY".len()])
+        .max()
+        .unwrap_or(0);
+    let mark = |matched: bool| if matched { "yes" } else { "no" };
+
+    let mut out = format!(
+        "  {:<width$}  {:>8}  {:<6}  {:<5}  {}\n",
+        "POLICY",
+        "PRIORITY",
+        "ACTION",
+        "AGENT",
+        "ENDPOINT",
+        width = width
+    );
+    for candidate in &explanation.candidates {
+        let action = match &candidate.action {
+            PolicyAction::Allow => "allow",
+            _ => "deny",
+        };
+        out.push_str(&format!(
+            "{} {:<width$}  {:>8}  {:<6}  {:<5}  {}\n",
+            if candidate.winner { "→" } else { " " },
+            candidate.policy_id,
+            candidate.priority,
+            action,
+            mark(candidate.agent_matched),
+            mark(candidate.endpoint_matched),
+            width = width
+        ));
+    }
+    if explanation.candidates.iter().all(|c| !c.winner) {
+        out.push_str("→ default (no policy matched: deny)\n");
+    }
+    out
+}
+
+/// Evaluate a generated population with fresh in-memory state
+///
+/// Traffic starts at the time of `clock`. Fails (exit 1) after the report
+/// when a cohort's deny rate is off its `expected_deny_rate`.
+fn simulate_command(
+    policy: &Path,
+    population: &Path,
+    duration: Duration,
+    seed: Option<u64>,
+    clock: &ClockArgs,
+    json: bool,
+    out: &Output,
+) -> Result<()> {
+    if duration.is_zero() {
+        anyhow::bail!(
+            "--duration must be longer than 0\nFix: Pass a time span, e.g. --duration 60s"
+        );
+    }
+    let mut spec = PopulationSpec::from_file(population)?;
+    if let Some(seed) = seed {
+        spec.seed = seed;
+    }
+    let engine = PolicyEngine::new(load_policies(policy)?).with_clock(clock.clock());
+    let start = engine.now();
+    let report = simulate(&engine, &spec, start, duration)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
     } else {
-        // Print to stdout
-        println!("{}", generated_code);
+        out.status("Policy Simulation".bold().cyan());
+        out.status(format!("Policy: {}", policy.display()));
+        if clock.is_set() {
+            out.status(format!(
+                "Start: {}",
+                DateTime::<Utc>::from(start).to_rfc3339()
+            ));
+        }
+        out.status(format!(
+            "Population: {} (seed {}, {}, {} requests)\n",
+            population.display(),
+            report.seed,
+            humantime::format_duration(duration),
+            report.requests
+        ));
+        print!("{}", out.text(&render_simulation_report(&report)));
+    }
+
+    let unexpected: Vec<String> = report
+        .unexpected()
+        .map(|outcome| {
+            if outcome.malicious {
+                format!("{} (malicious)", outcome.cohort)
+            } else {
+                outcome.cohort.clone()
+            }
+        })
+        .collect();
+    if !unexpected.is_empty() {
+        anyhow::bail!(
+            "Cohorts missed their expected deny rate (tolerance {}): {}\nFix: Adjust the policy limits, or expected_deny_rate in {}",
+            spec.tolerance,
+            unexpected.join(", "),
+            population.display()
+        );
+    }
+    Ok(())
+}
+
+/// One row per cohort (malicious agents separately) with outcome counts
+fn render_simulation_report(report: &SimulationReport) -> String {
+    const HEADERS: [&str; 9] = [
+        "COHORT",
+        "AGENTS",
+        "REQUESTS",
+        "ALLOWED",
+        "DENIED",
+        "RATE-LIMITED",
+        "CAP-HIT",
+        "DENY %",
+        "EXPECTED",
+    ];
+    let rows: Vec<[String; 9]> = report
+        .cohorts
+        .iter()
+        .map(|row| {
+            let name = if row.malicious {
+                format!("{} (malicious)", row.cohort)
+            } else {
+                row.cohort.clone()
+            };
+            let expected = match (row.expected_deny_rate, row.as_expected) {
+                (Some(rate), Some(true)) => format!("{:.1}% ✓", rate * 100.0),
+                (Some(rate), _) => format!("{:.1}% ✗", rate * 100.0),
+                (None, _) => "-".to_string(),
+            };
+            [
+                name,
+                row.agents.to_string(),
+                row.requests.to_string(),
+                row.allowed.to_string(),
+                row.denied.to_string(),
+                row.rate_limited.to_string(),
+                row.cap_hit.to_string(),
+                format!("{:.1}%", row.deny_rate * 100.0),
+                expected,
+            ]
+        })
+        .collect();
+
+    let mut widths = HEADERS.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    // Cohort left-aligned, counts right-aligned
+    let line = |row: &[String]| -> String {
+        let mut out = format!("{:<width$}", row[0], width = widths[0]);
+        for (cell, width) in row[1..].iter().zip(&widths[1..]) {
+            out.push_str(&format!("  {:>width$}", cell, width = width));
+        }
+        out.push('\n');
+        out
+    };
+
+    let mut out = line(&HEADERS.map(str::to_string));
+    for row in &rows {
+        out.push_str(&line(row));
+    }
+    out
+}
+
+fn export_runtime_command(
+    input: PathBuf,
+    output: PathBuf,
+    format: Option<FileFormat>,
+    out: &Output,
+) -> Result<()> {
+    out.status("Runtime Policy Export".bold().cyan());
+    out.status(format!("Input: {}\n", input.display()));
+
+    let runtime = match load_policy_file(&input)? {
+        LoadedPolicyFile::Simple(policy_file) => {
+            let report = validate_policies(&policy_file.to_config());
+            if report.has_errors {
+                display_validation_report(&report, out);
+                anyhow::bail!("Cannot export an invalid policy file");
+            }
+            policy_file.to_runtime()?
+        }
+        LoadedPolicyFile::Runtime(runtime) => {
+            out.status("Input already uses the runtime schema; normalizing");
+            runtime
+        }
+    };
+
+    let format = format
+        .or_else(|| FileFormat::from_path(&output))
+        .unwrap_or_default();
+    let text = format
+        .render(&runtime)
+        .context("Failed to serialize runtime policies")?;
+    crate::safe_write::write(&output, &text)
+        .with_context(|| format!("Failed to write output file: {}", output.display()))?;
+
+    out.line(format!(
+        "{} Exported {} runtime policies: {}",
+        "✓".green().bold(),
+        runtime.policies.len(),
+        output.display()
+    ));
+    for policy in &runtime.policies {
+        out.line(format!("  {:>4}  {}", policy.priority, policy.id));
     }
 
     Ok(())
 }
 
+/// Under GitHub Actions, print a file annotation per unsuppressed error and
+/// warning (or for a file that failed to load)
+fn annotate_policy_file(
+    format: OutputFormat,
+    file: &str,
+    outcome: Result<&ValidationReport, &anyhow::Error>,
+    out: &Output,
+) {
+    if format != OutputFormat::Github {
+        return;
+    }
+    let report = match outcome {
+        Ok(report) => report,
+        Err(e) => {
+            let annotation = Annotation::error("Policy validation", format!("{:#}", e));
+            out.line(annotation.in_file(file));
+            return;
+        }
+    };
+    for rule in &report.skipped {
+        let annotation = Annotation::warning(
+            "Policy rule skipped",
+            format!("{}: {}", skipped_location(rule), rule.error),
+        );
+        out.line(annotation.in_file(file));
+    }
+    for issue in report.issues.iter().filter(|i| !i.suppressed) {
+        let message = match &issue.details {
+            Some(details) => format!("{}\n{}", issue.message, details),
+            None => issue.message.clone(),
+        };
+        let pass = if issue.code.is_lint() {
+            "lint"
+        } else {
+            "validation"
+        };
+        let title = format!("Policy {} {}", pass, issue.code);
+        let annotation = match issue.issue_type {
+            IssueType::Error => Annotation::error(title, message),
+            IssueType::Warning => Annotation::warning(title, message),
+            _ => continue,
+        };
+        out.line(annotation.in_file(file));
+    }
+}
+
+/// "Policy #1 (line 6)" for a skipped rule
+fn skipped_location(rule: &SkippedRule) -> String {
+    match rule.line {
+        Some(line) => format!("Policy #{} (line {})", rule.index, line),
+        None => format!("Policy #{}", rule.index),
+    }
+}
+
 /// Display validation report with colored output
-fn display_validation_report(report: &ValidationReport) {
+fn display_validation_report(report: &ValidationReport, out: &Output) {
+    if !report.skipped.is_empty() {
+        out.line("Rules skipped due to parse errors:".bold());
+        out.line("");
+        for rule in &report.skipped {
+            out.line(format!(
+                "{}  {}: {}",
+                "⚠".yellow().bold(),
+                skipped_location(rule),
+                rule.error
+            ));
+        }
+        out.line("");
+    }
     if report.issues.is_empty() {
         return;
     }
 
-    println!("{}", "Validation Issues:".bold());
-    println!();
+    out.line("Validation Issues:".bold());
+    out.line("");
 
     for (i, issue) in report.issues.iter().enumerate() {
         let prefix = match issue.issue_type {
             IssueType::Error => format!("{}  ERROR", "".red().bold()),
             IssueType::Warning => format!("{}  WARNING", "�".yellow().bold()),
-            IssueType::Info => format!("{}  INFO", "9".cyan()),
+            _ => format!("{}  INFO", "9".cyan()),
         };
 
-        println!("{} {}", prefix, issue.message);
+        if issue.suppressed {
+            out.line(
+                format!(
+                    "   SUPPRESSED {} {} [{}]",
+                    severity_name(&issue.issue_type).to_uppercase(),
+                    issue.message,
+                    issue.code
+                )
+                .dimmed(),
+            );
+            if i < report.issues.len() - 1 {
+                out.line("");
+            }
+            continue;
+        }
+        out.line(format!(
+            "{} {} {}",
+            prefix,
+            issue.message,
+            format!("[{}]", issue.code).dimmed()
+        ));
 
         if let Some(details) = &issue.details {
-            println!("   {}", details.dimmed());
+            out.line(format!("   {}", details.dimmed()));
         }
 
         if !issue.policy_indices.is_empty() {
@@ -199,18 +1603,19 @@ fn display_validation_report(report: &ValidationReport) {
                 .iter()
                 .map(|i| format!("#{}", i))
                 .collect();
-            println!("   Policies: {}", indices.join(", ").dimmed());
+            out.line(format!("   Policies: {}", indices.join(", ").dimmed()));
         }
 
         if !issue.suggestions.is_empty() {
             for suggestion in &issue.suggestions {
-                println!("   {} {}", "=�".cyan(), suggestion.description);
-                println!("      � {}", suggestion.action.italic());
+                let (description, action) = suggestion.localized();
+                out.line(format!("   {} {}", "=�".cyan(), description));
+                out.line(format!("      � {}", action.italic()));
             }
         }
 
         if i < report.issues.len() - 1 {
-            println!();
+            out.line("");
         }
     }
 }
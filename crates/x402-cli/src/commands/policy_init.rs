@@ -0,0 +1,511 @@
+// x402-dev policy init - Write a starter policy file, optionally guided
+//
+// `--from-template` picks a starting point (strict, permissive, demo).
+// `--interactive` then walks through the questions a new user can answer
+// without knowing the schema: which agents, denied agents, a per-agent rate
+// limit, a daily spending cap and which endpoints. The policy is rebuilt
+// and validated after every answer, so an allow/deny conflict is raised
+// (and resolved) at the question that caused it. `<` goes back a step and
+// `-` clears an answer.
+
+use crate::prompt::{Prompter, BACK};
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use x402_core::format::FileFormat;
+use x402_core::policy::types::PolicyConfig;
+use x402_core::policy::{validate_policies, IssueType, PolicyRule, RuleCode, WindowType};
+use x402_domain::{normalize_currency, DurationField};
+
+/// Answer that clears a list or turns a limit off
+const CLEAR: &str = "-";
+
+/// Default currency of a spending cap answered without one
+const DEFAULT_CURRENCY: &str = "USDC";
+
+/// Starting point of `policy init`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PolicyTemplate {
+    /// Named agents only, tight rate limit and daily cap
+    Strict,
+    /// Any agent, generous rate limit, no cap
+    Permissive,
+    /// Demo agents, a denied agent, rate limit and daily cap
+    #[default]
+    Demo,
+}
+
+/// Answers to the wizard's questions; each becomes at most one rule
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PolicyAnswers {
+    pub allowed_agents: Vec<String>,
+    pub denied_agents: Vec<String>,
+    pub rate_limit: Option<(u32, DurationField)>,
+    /// Daily cap per agent: amount and currency
+    pub daily_cap: Option<(f64, String)>,
+    pub endpoints: Vec<String>,
+}
+
+impl PolicyAnswers {
+    pub fn template(template: PolicyTemplate) -> Self {
+        let list = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        match template {
+            PolicyTemplate::Strict => Self {
+                allowed_agents: list(&["agent-prod-001"]),
+                rate_limit: Some((60, DurationField::from_secs(60))),
+                daily_cap: Some((5.0, DEFAULT_CURRENCY.to_string())),
+                ..Self::default()
+            },
+            PolicyTemplate::Permissive => Self {
+                rate_limit: Some((1000, DurationField::from_secs(3600))),
+                ..Self::default()
+            },
+            PolicyTemplate::Demo => Self {
+                allowed_agents: list(&["agent-demo-001", "agent-demo-002"]),
+                denied_agents: list(&["agent-bad-actor"]),
+                rate_limit: Some((100, DurationField::from_secs(3600))),
+                daily_cap: Some((10.0, DEFAULT_CURRENCY.to_string())),
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Policy rules in the simple schema
+    pub fn to_config(&self) -> PolicyConfig {
+        let mut policies = Vec::new();
+        if !self.allowed_agents.is_empty() {
+            policies.push(PolicyRule::Allowlist {
+                field: "agent_id".to_string(),
+                values: self.allowed_agents.clone(),
+            });
+        }
+        if !self.denied_agents.is_empty() {
+            policies.push(PolicyRule::Denylist {
+                field: "agent_id".to_string(),
+                values: self.denied_agents.clone(),
+            });
+        }
+        if !self.endpoints.is_empty() {
+            policies.push(PolicyRule::Allowlist {
+                field: "endpoint".to_string(),
+                values: self.endpoints.clone(),
+            });
+        }
+        if let Some((max_requests, window)) = self.rate_limit {
+            policies.push(PolicyRule::RateLimit {
+                max_requests,
+                window_seconds: window,
+            });
+        }
+        if let Some((max_amount, currency)) = &self.daily_cap {
+            policies.push(PolicyRule::SpendingCap {
+                max_amount: *max_amount,
+                currency: currency.clone(),
+                window_seconds: None,
+                window_type: WindowType::CalendarDay,
+                timezone: None,
+            });
+        }
+        PolicyConfig {
+            policies,
+            allow_custom_currencies: false,
+        }
+    }
+
+    /// Agents in both the allowlist and the denylist
+    fn conflicting_agents(&self) -> Vec<String> {
+        self.allowed_agents
+            .iter()
+            .filter(|agent| self.denied_agents.contains(agent))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Render `config` as a policy file
+pub fn render_policy(config: &PolicyConfig, format: FileFormat) -> Result<String> {
+    format
+        .render(config)
+        .with_context(|| format!("Failed to serialize policy to {}", format))
+}
+
+/// Write `config` to `path`, refusing to overwrite unless `force`
+pub fn write_policy(path: &Path, config: &PolicyConfig, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        bail!(
+            "{} already exists\nFix: Pass --force to overwrite it, or choose another file name",
+            path.display()
+        );
+    }
+    let format = FileFormat::from_path(path).unwrap_or_default();
+    crate::safe_write::write(path, render_policy(config, format)?)
+        .with_context(|| format!("Failed to write policy file: {}", path.display()))
+}
+
+/// Questions of the wizard, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Agents,
+    DeniedAgents,
+    RateLimit,
+    SpendingCap,
+    Endpoints,
+    Preview,
+}
+
+impl Step {
+    const ALL: [Step; 6] = [
+        Step::Agents,
+        Step::DeniedAgents,
+        Step::RateLimit,
+        Step::SpendingCap,
+        Step::Endpoints,
+        Step::Preview,
+    ];
+
+    fn index(self) -> usize {
+        Step::ALL.iter().position(|step| *step == self).unwrap_or(0)
+    }
+
+    fn next(self) -> Step {
+        Step::ALL[(self.index() + 1).min(Step::ALL.len() - 1)]
+    }
+
+    fn previous(self) -> Step {
+        Step::ALL[self.index().saturating_sub(1)]
+    }
+}
+
+/// What to do after a question
+enum Outcome {
+    Next,
+    Back,
+    /// Ask the same question again
+    Again,
+}
+
+/// Walk through the questions starting from `answers`
+///
+/// Returns the answers to write, or None if the user cancelled at the
+/// preview.
+pub fn run_wizard(
+    prompter: &mut dyn Prompter,
+    mut answers: PolicyAnswers,
+    format: FileFormat,
+) -> Result<Option<PolicyAnswers>> {
+    prompter.say("Answer each question, enter - to clear an answer or < to go back a step.\n");
+    let mut step = Step::Agents;
+    loop {
+        let outcome = match step {
+            Step::Agents => ask_list(
+                prompter,
+                "Agents allowed to pay (IDs or patterns like agent-*, comma-separated; - for any)",
+                &mut answers.allowed_agents,
+            )?,
+            Step::DeniedAgents => ask_list(
+                prompter,
+                "Agents to always deny (comma-separated; - for none)",
+                &mut answers.denied_agents,
+            )?,
+            Step::RateLimit => ask_rate_limit(prompter, &mut answers)?,
+            Step::SpendingCap => ask_spending_cap(prompter, &mut answers)?,
+            Step::Endpoints => ask_list(
+                prompter,
+                "Endpoints agents may call (e.g. /api/*, comma-separated; - for all)",
+                &mut answers.endpoints,
+            )?,
+            Step::Preview => {
+                let text = render_policy(&answers.to_config(), format)?;
+                prompter.say(&format!("\nPolicy preview:\n\n{}", text));
+                match prompter.select(
+                    "Write this policy?",
+                    &["Write it", "Go back", "Cancel"],
+                    0,
+                )? {
+                    0 => return Ok(Some(answers)),
+                    1 => Outcome::Back,
+                    _ => return Ok(None),
+                }
+            }
+        };
+
+        step = match outcome {
+            Outcome::Back if step == Step::Agents => {
+                prompter.say("This is the first question.");
+                step
+            }
+            Outcome::Back => step.previous(),
+            Outcome::Again => step,
+            Outcome::Next if step == Step::Preview => step,
+            Outcome::Next => match check_answers(prompter, &mut answers)? {
+                true => step.next(),
+                false => step,
+            },
+        };
+    }
+}
+
+/// Ask for a comma-separated list, defaulting to the current values
+fn ask_list(
+    prompter: &mut dyn Prompter,
+    prompt: &str,
+    values: &mut Vec<String>,
+) -> Result<Outcome> {
+    let current = values.join(", ");
+    let answer = prompter.input(prompt, Some(&current))?;
+    if answer.trim() == BACK {
+        return Ok(Outcome::Back);
+    }
+    *values = answer
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty() && *value != CLEAR)
+        .map(str::to_string)
+        .collect();
+    Ok(Outcome::Next)
+}
+
+fn ask_rate_limit(prompter: &mut dyn Prompter, answers: &mut PolicyAnswers) -> Result<Outcome> {
+    let current = answers
+        .rate_limit
+        .map(|(max, window)| format!("{}/{}", max, window))
+        .unwrap_or_default();
+    let answer = prompter.input(
+        "Rate limit per agent as REQUESTS/WINDOW, e.g. 100/1h (- for none)",
+        Some(&current),
+    )?;
+    let answer = answer.trim();
+    if answer == BACK {
+        return Ok(Outcome::Back);
+    }
+    if answer.is_empty() || answer == CLEAR {
+        answers.rate_limit = None;
+        return Ok(Outcome::Next);
+    }
+    let parsed = answer
+        .split_once('/')
+        .and_then(|(max, window)| Some((max.trim().parse().ok()?, window.trim().parse().ok()?)));
+    match parsed {
+        Some(limit) => {
+            answers.rate_limit = Some(limit);
+            Ok(Outcome::Next)
+        }
+        None => {
+            prompter.say(&format!(
+                "'{}' is not a rate limit; enter e.g. 100/1h or 10/30s",
+                answer
+            ));
+            Ok(Outcome::Again)
+        }
+    }
+}
+
+fn ask_spending_cap(prompter: &mut dyn Prompter, answers: &mut PolicyAnswers) -> Result<Outcome> {
+    let current = answers
+        .daily_cap
+        .as_ref()
+        .map(|(amount, currency)| format!("{} {}", amount, currency))
+        .unwrap_or_default();
+    let answer = prompter.input(
+        "Daily spending cap per agent, e.g. 10.00 USDC (- for none)",
+        Some(&current),
+    )?;
+    let answer = answer.trim();
+    if answer == BACK {
+        return Ok(Outcome::Back);
+    }
+    if answer.is_empty() || answer == CLEAR {
+        answers.daily_cap = None;
+        return Ok(Outcome::Next);
+    }
+    let mut parts = answer.split_whitespace();
+    let amount = parts.next().and_then(|amount| amount.parse::<f64>().ok());
+    let currency = parts.next().unwrap_or(DEFAULT_CURRENCY);
+    match amount {
+        Some(amount) if parts.next().is_none() => {
+            answers.daily_cap = Some((amount, normalize_currency(currency)));
+            Ok(Outcome::Next)
+        }
+        _ => {
+            prompter.say(&format!(
+                "'{}' is not a spending cap; enter an amount and optionally a currency, e.g. 10.00 USDC",
+                answer
+            ));
+            Ok(Outcome::Again)
+        }
+    }
+}
+
+/// Validate the policy built so far; true if the wizard may move on
+///
+/// Errors and warnings are shown as soon as an answer causes them. An
+/// allow/deny conflict is resolved on the spot by choosing which list keeps
+/// the agents.
+fn check_answers(prompter: &mut dyn Prompter, answers: &mut PolicyAnswers) -> Result<bool> {
+    let report = validate_policies(&answers.to_config());
+    for issue in &report.issues {
+        let label = match issue.issue_type {
+            IssueType::Error => "✗ Error",
+            IssueType::Warning => "⚠ Warning",
+            _ => continue,
+        };
+        prompter.say(&format!("{} [{}]: {}", label, issue.code, issue.message));
+        if let Some(details) = &issue.details {
+            prompter.say(&format!("  {}", details.replace('\n', "\n  ")));
+        }
+    }
+    if !report.has_errors {
+        return Ok(true);
+    }
+
+    let conflict = report
+        .issues
+        .iter()
+        .any(|issue| issue.code == RuleCode::AllowDenyConflict);
+    let agents = answers.conflicting_agents();
+    if !conflict || agents.is_empty() {
+        return Ok(false);
+    }
+    let agents_text = agents.join(", ");
+    let choice = prompter.select(
+        &format!("{} cannot be both allowed and denied", agents_text),
+        &[
+            &format!("Allow {} (remove from the denied agents)", agents_text),
+            &format!("Deny {} (remove from the allowed agents)", agents_text),
+            "Answer the question again",
+        ],
+        0,
+    )?;
+    match choice {
+        0 => answers
+            .denied_agents
+            .retain(|agent| !agents.contains(agent)),
+        1 => answers
+            .allowed_agents
+            .retain(|agent| !agents.contains(agent)),
+        _ => return Ok(false),
+    }
+    prompter.say("Conflict resolved.");
+    check_answers(prompter, answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt::Script;
+    use x402_core::policy::rules::PolicyFile;
+
+    fn run(answers: &[&str], start: PolicyAnswers) -> (Option<PolicyAnswers>, Script) {
+        let mut script = Script::new(answers);
+        let result = run_wizard(&mut script, start, FileFormat::Yaml).unwrap();
+        (result, script)
+    }
+
+    #[test]
+    fn test_full_session_writes_valid_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let (answers, script) = run(
+            &[
+                "agent-a, agent-b",
+                "",
+                "20/1m",
+                "25 USDC",
+                "/api/*",
+                "Write it",
+            ],
+            PolicyAnswers::default(),
+        );
+        let answers = answers.unwrap();
+        assert!(script.output().contains("Policy preview:"));
+
+        let path = dir.path().join("policy.yaml");
+        write_policy(&path, &answers.to_config(), false).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let policy_file: PolicyFile = serde_yaml::from_str(&text).unwrap();
+        let config = policy_file.to_config();
+        let report = validate_policies(&config);
+        assert!(!report.has_errors && !report.has_warnings, "{:?}", report);
+        assert_eq!(config.policies.len(), 4);
+        assert!(matches!(
+            &config.policies[3],
+            PolicyRule::SpendingCap { max_amount, window_type: WindowType::CalendarDay, .. } if *max_amount == 25.0
+        ));
+    }
+
+    #[test]
+    fn test_conflict_is_raised_and_resolved() {
+        let (answers, script) = run(
+            &[
+                "agent-a, agent-b",
+                // Denying an allowed agent raises the conflict at once
+                "agent-b, agent-x",
+                "Deny",
+                "",
+                "",
+                "",
+                "Write it",
+            ],
+            PolicyAnswers::default(),
+        );
+        let answers = answers.unwrap();
+        let output = script.output();
+        assert!(output.contains("[X402-P001]"), "{}", output);
+        assert!(
+            output.contains("agent-b cannot be both allowed and denied"),
+            "{}",
+            output
+        );
+        assert!(output.contains("Conflict resolved."), "{}", output);
+        assert_eq!(answers.allowed_agents, ["agent-a"]);
+        assert_eq!(answers.denied_agents, ["agent-b", "agent-x"]);
+        assert!(!validate_policies(&answers.to_config()).has_errors);
+    }
+
+    #[test]
+    fn test_back_and_invalid_answers() {
+        let (answers, script) = run(
+            &[
+                "<",
+                "",
+                "",
+                "lots",
+                "<",
+                "agent-x",
+                "",
+                "",
+                "",
+                "Go back",
+                "/api/data",
+                "Write it",
+            ],
+            PolicyAnswers::template(PolicyTemplate::Demo),
+        );
+        let answers = answers.unwrap();
+        let output = script.output();
+        assert!(output.contains("This is the first question."));
+        assert!(output.contains("'lots' is not a rate limit"));
+        // Going back from the rate limit re-asked the denied agents
+        assert_eq!(answers.denied_agents, ["agent-x"]);
+        assert_eq!(answers.endpoints, ["/api/data"]);
+        assert_eq!(answers.rate_limit.unwrap().0, 100);
+    }
+
+    #[test]
+    fn test_templates_are_valid() {
+        for template in [
+            PolicyTemplate::Strict,
+            PolicyTemplate::Permissive,
+            PolicyTemplate::Demo,
+        ] {
+            let report = validate_policies(&PolicyAnswers::template(template).to_config());
+            assert!(!report.has_errors && !report.has_warnings, "{:?}", template);
+        }
+    }
+
+    #[test]
+    fn test_cancel_at_preview() {
+        let start = PolicyAnswers::template(PolicyTemplate::Strict);
+        let (answers, script) = run(&["", "", "", "", "", "Cancel"], start);
+        assert!(answers.is_none());
+        assert!(script.output().contains("agent-prod-001"));
+    }
+}
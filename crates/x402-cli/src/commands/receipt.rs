@@ -0,0 +1,89 @@
+// x402-dev receipt command - Offline checks of X-Payment-Receipt values
+//
+// Decodes the base64 JSON receipt, validates its fields and, when it is
+// signed, its Ed25519 signature. Verifying signatures needs the `signing`
+// feature; a signed receipt is refused rather than passed unchecked.
+
+use crate::cli::{ReceiptArgs, ReceiptCommands};
+use crate::output::Output;
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::io::{self, Read};
+use x402_core::receipt::PaymentReceipt;
+
+/// Run the receipt command
+pub fn run(args: &ReceiptArgs, out: &Output) -> Result<()> {
+    match &args.command {
+        ReceiptCommands::Verify {
+            receipt,
+            signer,
+            require_signature,
+            json,
+        } => {
+            let value = if receipt == "-" {
+                let mut value = String::new();
+                io::stdin()
+                    .read_to_string(&mut value)
+                    .context("Failed to read the receipt from stdin")?;
+                value
+            } else {
+                receipt.clone()
+            };
+
+            let receipt = PaymentReceipt::decode(&value)?;
+            let verified_signer = verify_receipt(&receipt, signer.as_deref(), *require_signature)?;
+
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&receipt)?);
+                return Ok(());
+            }
+            println!("{} Receipt valid", "✅".green());
+            println!("   Memo:        {}", receipt.memo);
+            println!("   Amount:      {}", out.amount(&receipt.amount, "USDC"));
+            if let Some(delta) = &receipt.amount_delta {
+                println!(
+                    "   Delta:       {} (within tolerance)",
+                    out.amount(delta, "USDC")
+                );
+            }
+            if let Some(recipient) = &receipt.recipient {
+                println!("   Recipient:   {}", recipient);
+            }
+            println!("   Resource:    {}", receipt.resource);
+            println!("   Verified at: {}", receipt.verified_at);
+            println!("   Mode:        {}", receipt.simulation_mode);
+            match verified_signer {
+                Some(signer) => println!("   Signature:   valid ({})", signer),
+                None => println!("   Signature:   {}", "unsigned".yellow()),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check a decoded receipt's signature, returning the signer if signed
+///
+/// With `expected_signer` or `require_signature`, unsigned receipts fail.
+pub fn verify_receipt(
+    receipt: &PaymentReceipt,
+    expected_signer: Option<&str>,
+    require_signature: bool,
+) -> Result<Option<String>> {
+    if !receipt.is_signed() {
+        if expected_signer.is_some() || require_signature {
+            bail!("Receipt is not signed\nFix: Start the mock server with --sign");
+        }
+        return Ok(None);
+    }
+    verify_signature(receipt, expected_signer).map(Some)
+}
+
+#[cfg(feature = "signing")]
+fn verify_signature(receipt: &PaymentReceipt, expected_signer: Option<&str>) -> Result<String> {
+    receipt.verify_signature(expected_signer)
+}
+
+#[cfg(not(feature = "signing"))]
+fn verify_signature(_receipt: &PaymentReceipt, _expected_signer: Option<&str>) -> Result<String> {
+    bail!("Receipt signature verification is not available in this build\nFix: Rebuild x402-dev with `--features signing`")
+}
@@ -0,0 +1,131 @@
+// x402-dev scenario command - config, policy and suite bundles
+//
+// Runs an x402-scenario.yaml against an in-process mock server and reports
+// suite results together with the server's stats.
+
+use crate::cli::{ScenarioArgs, ScenarioCommands};
+use crate::commands::mock::render_stats_table;
+use crate::config::open_secret_store;
+use crate::output::Output;
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+use x402_core::canonical::to_canonical_string;
+use x402_core::policy::IssueType;
+use x402_core::testing::{format_summary, TestFilter};
+use x402_server::{validate_scenario, ScenarioIssue, ScenarioValidation};
+
+/// Main entry point for the scenario command
+pub async fn run(args: &ScenarioArgs, out: &Output) -> Result<()> {
+    match &args.command {
+        ScenarioCommands::Run {
+            file,
+            json,
+            tags,
+            skip_tags,
+            only,
+        } => {
+            let filter = TestFilter {
+                tags: tags.clone(),
+                skip_tags: skip_tags.clone(),
+                only: only.clone(),
+            };
+            run_command(file, *json, &filter, out).await
+        }
+        ScenarioCommands::Validate { file } => validate_command(file, out),
+    }
+}
+
+/// Exit codes: 0 when every test passed, 1 otherwise
+async fn run_command(file: &Path, json: bool, filter: &TestFilter, out: &Output) -> Result<()> {
+    if !json {
+        out.status(format!("{} {}", "Loading scenario:".cyan(), file.display()));
+    }
+    let validation = validate_scenario(file)?;
+    if validation.has_errors() || !json {
+        print_issues(&validation, out);
+    }
+    let Some(mut scenario) = validation.scenario else {
+        anyhow::bail!(
+            "Scenario {} has errors\nFix: Correct the sections listed above",
+            file.display()
+        );
+    };
+
+    if !scenario.suite.secret_names()?.is_empty() {
+        let store = open_secret_store()?;
+        scenario.suite.load_secrets(|name| store.get(name))?;
+    }
+    if !json {
+        out.status(format!(
+            "{} {} ({} tests, {} policies)\n",
+            "Running scenario:".cyan(),
+            scenario.name.bold(),
+            scenario.suite.tests.len(),
+            scenario.config.policies.len()
+        ));
+    }
+
+    let report = scenario.run(filter).await?;
+    if json {
+        println!("{}", to_canonical_string(&report.to_json())?);
+    } else {
+        out.line(format_summary(&report.suite, out.quiet, &out.amounts));
+        out.line(format!("\n{}", "Server stats:".bold()));
+        print!("{}", render_stats_table(&report.stats));
+    }
+
+    std::process::exit(report.exit_code());
+}
+
+fn validate_command(file: &Path, out: &Output) -> Result<()> {
+    out.line("Scenario Validation".bold().cyan());
+    out.line(format!("File: {}\n", file.display()));
+
+    let validation = validate_scenario(file)?;
+    print_issues(&validation, out);
+
+    let errors = validation
+        .issues
+        .iter()
+        .filter(|issue| issue.issue_type == IssueType::Error)
+        .count();
+    if errors > 0 {
+        anyhow::bail!("Scenario validation failed with {} error(s)", errors);
+    }
+
+    let Some(scenario) = &validation.scenario else {
+        anyhow::bail!("Scenario validation failed");
+    };
+    let note = match warnings(&validation).count() {
+        0 => String::new(),
+        n => format!(" ({} warning(s))", n),
+    };
+    out.line(format!(
+        "{} Scenario '{}' is valid{}: {} tests, {} policies",
+        "✓".green().bold(),
+        scenario.name,
+        note,
+        scenario.suite.tests.len(),
+        scenario.config.policies.len()
+    ));
+    Ok(())
+}
+
+fn warnings(validation: &ScenarioValidation) -> impl Iterator<Item = &ScenarioIssue> {
+    validation
+        .issues
+        .iter()
+        .filter(|issue| issue.issue_type == IssueType::Warning)
+}
+
+/// One line per error, then the warnings
+fn print_issues(validation: &ScenarioValidation, out: &Output) {
+    for issue in &validation.issues {
+        match issue.issue_type {
+            IssueType::Error => out.error(format!("{} {}", "❌".red(), issue)),
+            IssueType::Warning => out.error(format!("{} {}", "⚠️".yellow(), issue)),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,74 @@
+// x402-dev secret command - Secrets for ${secret:name} references
+//
+// Values go to the OS keyring when the build has the `keyring` feature,
+// otherwise (or with --file) to passphrase-encrypted files. Values are read
+// from a hidden prompt or stdin and are never printed.
+
+use crate::cli::{SecretArgs, SecretCommands};
+use crate::config::open_secret_store;
+use anyhow::{bail, Result};
+use colored::Colorize;
+use std::io::{self, IsTerminal, Read};
+use x402_core::secrets::{validate_secret_name, SecretBackend};
+use x402_server::state_dir;
+
+/// Run the secret command
+pub fn run(args: &SecretArgs) -> Result<()> {
+    let store = open_secret_store()?;
+
+    match &args.command {
+        SecretCommands::Set { name, file } => {
+            validate_secret_name(name)?;
+            let value = read_value(name)?;
+            let backend = if *file {
+                SecretBackend::File
+            } else {
+                SecretBackend::preferred()
+            };
+            state_dir::ensure_writable(store.dir())?;
+            store.set(name, &value, backend)?;
+            println!("{} Stored secret '{}' ({})", "✅".green(), name, backend);
+            println!("   Reference it as ${{secret:{}}}", name);
+        }
+        SecretCommands::List => {
+            let secrets = store.list()?;
+            if secrets.is_empty() {
+                println!("No secrets stored in {}", store.dir().display());
+            }
+            for (name, backend) in secrets {
+                println!("{}  ({})", name, backend);
+            }
+        }
+        SecretCommands::Rm { name } => {
+            if !store.remove(name)? {
+                bail!(
+                    "Secret '{}' not found\nFix: Run x402-dev secret list to see stored names",
+                    name
+                );
+            }
+            println!("{} Removed secret '{}'", "✅".green(), name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Secret value from a hidden prompt, or all of stdin without the final newline
+fn read_value(name: &str) -> Result<String> {
+    let value = if io::stdin().is_terminal() {
+        dialoguer::Password::new()
+            .with_prompt(format!("Value for {}", name))
+            .interact()?
+    } else {
+        let mut value = String::new();
+        io::stdin().read_to_string(&mut value)?;
+        value.trim_end_matches(['\n', '\r']).to_string()
+    };
+    if value.is_empty() {
+        bail!(
+            "No value given for secret '{}'\nFix: Type the value at the prompt or pipe it on stdin",
+            name
+        );
+    }
+    Ok(value)
+}
@@ -2,10 +2,17 @@
 //
 // Implements YAML-based test suites for x402 payment flows
 
-use crate::cli::TestArgs;
-use anyhow::Result;
+use crate::cli::{OutputFormat, TestArgs, TestCommands};
+use crate::config::{open_secret_store, resolve_overrides};
+use crate::output::Output;
+use anyhow::{Context, Result};
 use colored::Colorize;
-use x402_core::testing::{format_json, format_summary, generate_junit_xml, SuiteResult, TestSuite};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use x402_core::testing::{
+    append_run, compute_trends, format_github, format_json, format_summary, generate_html_report,
+    generate_junit_xml, History, RunRecord, SuiteResult, TestFilter, TestSuite,
+};
 
 /// Execute test command and return result (library-friendly, no process::exit)
 ///
@@ -13,58 +20,257 @@ use x402_core::testing::{format_json, format_summary, generate_junit_xml, SuiteR
 /// It executes the test suite and returns the SuiteResult without exiting the process.
 ///
 /// For CLI usage, see `execute()` which wraps this and handles process exit.
-pub async fn execute_with_result(args: &TestArgs) -> Result<SuiteResult> {
+pub async fn execute_with_result(args: &TestArgs, out: &Output) -> Result<SuiteResult> {
     // Load test suite from YAML file
-    let suite_path = &args.suite;
+    let Some(suite_path) = &args.suite else {
+        anyhow::bail!("No test suite given\nFix: Run `x402-dev test <SUITE>`");
+    };
 
     if !suite_path.exists() {
         anyhow::bail!("Test suite file not found: {}", suite_path.display());
     }
 
-    if !args.quiet {
-        println!("{} {}", "Loading test suite:".cyan(), suite_path.display());
-    }
+    out.status(format!(
+        "{} {}",
+        "Loading test suite:".cyan(),
+        suite_path.display()
+    ));
 
     // Parse test suite (FR-2.1)
-    let suite = TestSuite::from_file(suite_path)?;
+    let mut suite = TestSuite::from_file(suite_path)?;
+    suite.variables.extend(args.vars.iter().cloned());
+    if !suite.secret_names()?.is_empty() {
+        let store = open_secret_store()?;
+        suite.load_secrets(|name| store.get(name))?;
+    }
+    suite.http.resolve.extend(resolve_overrides(&args.resolve)?);
+    if let Some(max_total) = args.budget {
+        suite.override_budget(max_total);
+    }
+    if args.preflight || args.preflight_health.is_some() {
+        let preflight = suite.preflight.get_or_insert_with(Default::default);
+        if let Some(path) = &args.preflight_health {
+            preflight.health = Some(path.clone());
+        }
+    }
 
-    if !args.quiet {
-        println!(
-            "{} {} tests\n",
-            "Found".cyan(),
-            suite.tests.len().to_string().bold()
-        );
+    for entry in &suite.http.resolve {
+        out.status(format!(
+            "{} {} → {}",
+            "Resolving:".cyan(),
+            entry.host,
+            entry.socket_addr()
+        ));
+    }
+    out.status(format!(
+        "{} {} tests\n",
+        "Found".cyan(),
+        suite.tests.len().to_string().bold()
+    ));
+
+    let filter = TestFilter {
+        tags: args.tags.clone(),
+        skip_tags: args.skip_tags.clone(),
+        only: args.only.clone(),
+    };
+
+    if !filter.is_empty() {
+        let selected = filter.selected_count(&suite.tests);
+        if selected == 0 {
+            out.error(format!(
+                "{} No tests matched the given filters (--tag/--skip-tag/--only)",
+                "⚠️".yellow()
+            ));
+        } else {
+            out.status(format!(
+                "{} {} of {} tests ({} filtered)\n",
+                "Selected".cyan(),
+                selected.to_string().bold(),
+                suite.tests.len(),
+                suite.tests.len() - selected
+            ));
+        }
     }
 
     // Execute test suite (FR-2.3)
-    let result = x402_core::testing::execute_test_suite(&suite).await?;
+    let result = x402_core::testing::execute_test_suite_filtered(&suite, &filter).await?;
 
     // Output results based on flags
-    if args.json {
-        // FR-2.4: JSON output for CI/CD
-        println!("{}", format_json(&result));
+    let format = if args.json {
+        OutputFormat::Json
     } else {
+        OutputFormat::resolve(args.format)
+    };
+    match format {
+        // FR-2.4: JSON output for CI/CD
+        OutputFormat::Json => println!("{}", format_json(&result)),
+        OutputFormat::Github => println!(
+            "{}",
+            format_github(&result, &suite_path.display().to_string())
+        ),
         // FR-2.5: Human-readable summary
-        println!("{}", format_summary(&result, args.quiet));
+        OutputFormat::Text => out.line(format_summary(&result, out.quiet, &out.amounts)),
     }
 
     // Generate JUnit XML if requested (FR-2.5)
     if let Some(junit_path) = &args.junit {
         let xml = generate_junit_xml(&result);
         std::fs::write(junit_path, xml)?;
-        if !args.quiet {
-            println!(
-                "\n{} {}",
-                "JUnit XML report written to:".cyan(),
-                junit_path.display()
-            );
-        }
+        out.status(format!(
+            "\n{} {}",
+            "JUnit XML report written to:".cyan(),
+            junit_path.display()
+        ));
+    }
+
+    // Generate HTML report if requested
+    if let Some(html_path) = &args.html {
+        std::fs::write(html_path, generate_html_report(&result))?;
+        out.status(format!(
+            "{} {}",
+            "HTML report written to:".cyan(),
+            html_path.display()
+        ));
+    }
+
+    if let Some(report_path) = &args.report {
+        std::fs::write(report_path, format_json(&result) + "\n")?;
+        out.status(format!(
+            "{} {}",
+            "JSON report written to:".cyan(),
+            report_path.display()
+        ));
+    }
+
+    if let Some(history_dir) = &args.history_dir {
+        let run = RunRecord::from_result(
+            &result,
+            &suite_path.display().to_string(),
+            chrono::Utc::now(),
+            git_sha(),
+        );
+        let path = append_run(history_dir, &run)?;
+        out.status(format!("{} {}", "Run appended to:".cyan(), path.display()));
     }
 
     // Return result for library integration
     Ok(result)
 }
 
+/// Commit being tested: $GITHUB_SHA in GitHub Actions, else `git rev-parse`
+fn git_sha() -> Option<String> {
+    if let Some(sha) = std::env::var("GITHUB_SHA").ok().filter(|s| !s.is_empty()) {
+        return Some(sha);
+    }
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let sha = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !sha.is_empty()).then_some(sha)
+}
+
+/// Print pass rate and duration trends of the last `last` recorded runs
+fn trends(history_dir: &Path, last: usize, out: &Output) -> Result<()> {
+    if last == 0 {
+        anyhow::bail!(
+            "--last must be at least 1\nFix: Pass the number of runs to show, e.g. --last 20"
+        );
+    }
+    let history = History::load(history_dir)
+        .with_context(|| format!("Failed to load history from {}", history_dir.display()))?;
+    for corrupt in &history.corrupt {
+        out.error(format!(
+            "{} Skipping corrupt line {} of {}: {}",
+            "⚠️".yellow(),
+            corrupt.line,
+            history_dir.join(x402_core::testing::HISTORY_FILE).display(),
+            corrupt.error
+        ));
+    }
+    if history.runs.is_empty() {
+        out.line(format!(
+            "No runs recorded in {} (run `x402-dev test <SUITE> --history-dir {}`)",
+            history_dir.display(),
+            history_dir.display()
+        ));
+        return Ok(());
+    }
+
+    let trends = compute_trends(&history.runs, last);
+    let window = &history.runs[history.runs.len() - trends.runs..];
+    let first = window.first().expect("window is not empty");
+    let latest = window.last().expect("window is not empty");
+    out.line(format!(
+        "{} last {} runs, {} to {}{}\n",
+        "Trends:".cyan().bold(),
+        trends.runs,
+        first.timestamp.format("%Y-%m-%d %H:%M"),
+        latest.timestamp.format("%Y-%m-%d %H:%M"),
+        latest
+            .git_sha
+            .as_deref()
+            .map(|sha| format!(" ({})", sha.chars().take(8).collect::<String>()))
+            .unwrap_or_default()
+    ));
+
+    let width = trends.tests.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    out.line(format!(
+        "  {:<width$}  {:>5}  {:<runs$}  {:<runs$}  {:>9}",
+        "TEST",
+        "PASS",
+        "STATUS",
+        "DURATION",
+        "LAST",
+        width = width,
+        runs = trends.runs.max(8)
+    ));
+    for test in &trends.tests {
+        let rate = if test.executed == 0 {
+            "-".to_string()
+        } else {
+            format!("{:.0}%", test.pass_rate * 100.0)
+        };
+        let last_ms = test
+            .durations_ms
+            .iter()
+            .rev()
+            .flatten()
+            .next()
+            .map_or("-".to_string(), |ms| format!("{}ms", ms));
+        let rate = match test.pass_rate {
+            _ if test.executed == 0 => rate.dimmed(),
+            r if r >= 1.0 => rate.green(),
+            r if r >= 0.8 => rate.yellow(),
+            _ => rate.red(),
+        };
+        out.line(format!(
+            "  {:<width$}  {:>5}  {:<runs$}  {:<runs$}  {:>9}",
+            test.name,
+            rate,
+            test.status_line(),
+            test.duration_sparkline(),
+            last_ms,
+            width = width,
+            runs = trends.runs.max(8)
+        ));
+    }
+
+    if trends.newly_flaky.is_empty() {
+        out.line(format!("\n{} No newly flaky tests", "✓".green()));
+    } else {
+        out.line(format!(
+            "\n{} Newly flaky (reliable before, retried or failing now and then recently):",
+            "⚠️".yellow()
+        ));
+        for name in &trends.newly_flaky {
+            out.line(format!("  - {}", name));
+        }
+    }
+    Ok(())
+}
+
 /// Execute test command (CLI entry point)
 ///
 /// This function is the CLI entry point. It calls `execute_with_result()`
@@ -73,8 +279,12 @@ pub async fn execute_with_result(args: &TestArgs) -> Result<SuiteResult> {
 /// Exit codes (FR-2.4):
 /// - 0: All tests passed
 /// - 1: One or more tests failed
-pub async fn execute(args: &TestArgs) -> Result<()> {
-    let result = execute_with_result(args).await?;
+/// - 3: A pre-flight check failed, no test was run
+pub async fn execute(args: &TestArgs, out: &Output) -> Result<()> {
+    if let Some(TestCommands::Trends { history_dir, last }) = &args.command {
+        return trends(history_dir, *last, out);
+    }
+    let result = execute_with_result(args, out).await?;
 
     // FR-2.4: Exit with appropriate code
     std::process::exit(result.exit_code());
@@ -0,0 +1,139 @@
+// x402-dev verify command - Verify one payment proof outside the mock
+//
+// Runs the same verifier the mock server would (`verifier:` in the config,
+// or --verifier) on a proof and invoice terms given on the command line.
+// Without the mock's invoice store there are no deadlines or replays to
+// check: the outcome is verified, rejected, pending or an amount mismatch.
+
+use crate::cli::VerifyArgs;
+use crate::config::load_merged_config;
+use crate::output::Output;
+use anyhow::{bail, Result};
+use chrono::Utc;
+use colored::Colorize;
+use std::time::Duration;
+use x402_core::redact::redact_url;
+use x402_core::solana_rpc::SolanaRpcClient;
+use x402_core::verifier::{
+    FailureReason, InvoiceTerms, PaymentProof, PaymentVerifier, VerificationOutcome, VerifierKind,
+};
+use x402_server::{RpcVerifier, Settlement, SimulatedVerifier};
+
+/// Run the verify command
+pub async fn run(args: &VerifyArgs, out: &Output) -> Result<()> {
+    let config = load_merged_config(None)?;
+    let kind = args.verifier.unwrap_or(config.verifier);
+
+    let proof = PaymentProof {
+        proof: args.proof.clone(),
+        declared_amount: args.paid,
+        received_at: Utc::now(),
+    };
+    let terms = InvoiceTerms {
+        memo: args.memo.clone(),
+        recipient: args.recipient.as_ref().map(|r| r.to_string()),
+        amount: args.amount,
+        // Only the invoice store records resources
+        resource: String::new(),
+    };
+    let settlement = Settlement::new(config.amount_tolerance);
+    let rpc_url = args.rpc_url.as_deref().unwrap_or(&config.solana_rpc);
+
+    let outcome = match kind {
+        VerifierKind::Simulated => {
+            let mode = args.mode.unwrap_or(config.simulation_mode);
+            let timeout = Duration::from_millis(config.timeout_delay_ms.as_millis());
+            SimulatedVerifier::new(mode.into(), timeout, settlement)
+                .verify(&proof, &terms)
+                .await?
+        }
+        VerifierKind::Rpc => {
+            let client = SolanaRpcClient::new(rpc_url)?;
+            RpcVerifier::new(&client, settlement)
+                .verify(&proof, &terms)
+                .await?
+        }
+    };
+
+    if args.json {
+        let mut report = serde_json::json!({
+            "verifier": kind,
+            "proof": args.proof,
+            "outcome": outcome.class(),
+            "verified": outcome.is_verified(),
+            "amount": args.amount.to_canonical_string(),
+        });
+        match &outcome {
+            VerificationOutcome::Verified {
+                memo, amount_delta, ..
+            } => {
+                report["memo"] = serde_json::json!(memo);
+                report["amount_delta"] = serde_json::json!(amount_delta.normalize().to_string());
+            }
+            VerificationOutcome::Failed(_) => {
+                report["message"] = serde_json::json!(outcome.to_string());
+            }
+        }
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_outcome(&outcome, kind, rpc_url, out);
+    }
+
+    if !outcome.is_verified() {
+        bail!(
+            "Payment proof {} was not verified ({})\nFix: {}",
+            args.proof,
+            outcome.class(),
+            fix_hint(&outcome, kind)
+        );
+    }
+    Ok(())
+}
+
+fn print_outcome(outcome: &VerificationOutcome, kind: VerifierKind, rpc_url: &str, out: &Output) {
+    let via = match kind {
+        VerifierKind::Simulated => "simulated".to_string(),
+        VerifierKind::Rpc => format!("rpc, {}", redact_url(rpc_url)),
+    };
+    match outcome {
+        VerificationOutcome::Verified {
+            memo, amount_delta, ..
+        } => {
+            println!("{} Payment verified ({})", "✅".green(), via);
+            println!("   Memo:    {}", memo);
+            if !amount_delta.is_zero() {
+                println!(
+                    "   Delta:   {} (within tolerance)",
+                    out.amount(&amount_delta.to_string(), "USDC")
+                );
+            }
+        }
+        VerificationOutcome::Failed(_) => {
+            println!(
+                "{} Payment not verified: {} ({})",
+                "❌".red(),
+                outcome.class(),
+                via
+            );
+            println!("   {}", outcome);
+        }
+    }
+}
+
+/// What to try next for an outcome that did not verify
+fn fix_hint(outcome: &VerificationOutcome, kind: VerifierKind) -> &'static str {
+    match (outcome, kind) {
+        (VerificationOutcome::Failed(FailureReason::AmountMismatch(_)), _) => {
+            "Pay the invoiced amount, or widen amount_tolerance in the config"
+        }
+        (VerificationOutcome::Failed(FailureReason::Pending(_)), VerifierKind::Rpc) => {
+            "Wait for the transaction to be confirmed and verify again"
+        }
+        (_, VerifierKind::Simulated) => {
+            "Pass --mode success, or set simulation_mode: success in the config"
+        }
+        (_, VerifierKind::Rpc) => {
+            "Check the signature, --memo and --recipient against the transaction on --rpc-url"
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// x402-dev verify-report command - Check the content hash of a report
+//
+// `test --report` and `check --report` write canonical JSON with a
+// `content_hash` over the rest of the report. Recomputing it tells whether
+// the file was edited after it was written; reformatting the JSON (key
+// order, whitespace) does not change the hash.
+
+use crate::cli::VerifyReportArgs;
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use x402_core::canonical::verify_report;
+
+/// Run the verify-report command; exits 1 if the hash does not match
+pub fn run(args: &VerifyReportArgs) -> Result<()> {
+    let text = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("Failed to read {}", args.file.display()))?;
+    let verification =
+        verify_report(&text).map_err(|e| anyhow!("{}: {:#}", args.file.display(), e))?;
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "file": args.file,
+                "valid": verification.matches(),
+                "recorded": verification.recorded,
+                "computed": verification.computed,
+            }))?
+        );
+    } else if verification.matches() {
+        println!(
+            "{} {}: content hash matches ({})",
+            "✓".green().bold(),
+            args.file.display(),
+            verification.computed
+        );
+    } else {
+        println!(
+            "{} {}: content hash mismatch, the report was modified after it was written",
+            "✗".red().bold(),
+            args.file.display()
+        );
+        println!("  Recorded: {}", verification.recorded);
+        println!("  Computed: {}", verification.computed);
+    }
+
+    if !verification.matches() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
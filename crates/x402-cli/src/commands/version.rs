@@ -1,28 +1,50 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-const CRATES_IO_API: &str = "https://crates.io/api/v1/crates/x402-dev";
+const RELEASES_API: &str = "https://api.github.com/repos/valentynkt/x402_CLI/releases";
+const RELEASES_PAGE: &str = "https://github.com/valentynkt/x402_CLI/releases";
 const CHECK_INTERVAL_SECS: u64 = 604800; // 7 days in seconds
+const EXPLICIT_CHECK_INTERVAL_SECS: u64 = 86400; // 24 hours for --check-update
+const REQUEST_TIMEOUT_SECS: u64 = 3;
+
+/// Set to any non-empty value other than "0" to disable update checks
+pub const NO_UPDATE_CHECK_ENV: &str = "X402_DEV_NO_UPDATE_CHECK";
 
 #[derive(Debug, Serialize, Deserialize)]
 struct UpdateCache {
     last_check: u64,
     latest_version: String,
+    #[serde(default = "default_changelog_url")]
+    changelog_url: String,
 }
 
-#[derive(Debug, Deserialize)]
-struct CratesIoResponse {
-    #[serde(rename = "crate")]
-    crate_info: CrateInfo,
+fn default_changelog_url() -> String {
+    RELEASES_PAGE.to_string()
 }
 
 #[derive(Debug, Deserialize)]
-struct CrateInfo {
-    max_version: String,
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    prerelease: bool,
+    #[serde(default)]
+    draft: bool,
+}
+
+/// Result of comparing the running version against the latest release
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateStatus {
+    pub current: String,
+    pub latest: String,
+    pub changelog_url: String,
+    pub update_available: bool,
+    /// True when answered from ~/.x402dev/update-check.json without a request
+    pub from_cache: bool,
 }
 
 pub async fn run(args: &crate::cli::VersionArgs) -> Result<()> {
@@ -39,109 +61,172 @@ pub async fn run(args: &crate::cli::VersionArgs) -> Result<()> {
     // For now, we'll skip the Rust version display since we can't reliably get it
     // without external crates. We can add rustc_version crate later if needed.
 
-    // Check for updates (if not disabled)
-    if !args.no_update_check {
+    if update_checks_disabled() {
+        if args.check_update {
+            println!("\nUpdate check disabled ({} is set)", NO_UPDATE_CHECK_ENV);
+        }
+        return Ok(());
+    }
+
+    if args.check_update {
+        // Explicit check: report the outcome, but a network failure is only a warning
+        match UpdateChecker::new(EXPLICIT_CHECK_INTERVAL_SECS) {
+            Ok(checker) => match checker.check(env!("CARGO_PKG_VERSION")).await {
+                Ok(status) => print_status(&status),
+                Err(e) => eprintln!("\n⚠️  Could not check for updates: {:#}", e),
+            },
+            Err(e) => eprintln!("\n⚠️  Could not check for updates: {:#}", e),
+        }
+    } else if !args.no_update_check {
         // Silently ignore any errors from update check
         // Update check is optional and should never fail the version command
-        let _ = check_for_updates().await;
+        if let Ok(checker) = UpdateChecker::new(CHECK_INTERVAL_SECS) {
+            if let Ok(status) = checker.check(env!("CARGO_PKG_VERSION")).await {
+                if status.update_available {
+                    print_update_notice(&status);
+                }
+            }
+        }
     }
 
     Ok(())
 }
 
-async fn check_for_updates() -> Result<()> {
-    let cache_path = get_cache_path()?;
-    let current_time = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .context("Failed to get current time")?
-        .as_secs();
-
-    // Check if we should perform update check
-    if let Ok(cache_data) = fs::read_to_string(&cache_path) {
-        if let Ok(cache) = serde_json::from_str::<UpdateCache>(&cache_data) {
-            // If last check was within the interval, use cached result
-            if current_time - cache.last_check < CHECK_INTERVAL_SECS {
-                let current_version = env!("CARGO_PKG_VERSION");
-                if is_newer_version(&cache.latest_version, current_version) {
-                    println!(
-                        "\n✨ Update available: {} → {}",
-                        current_version, cache.latest_version
-                    );
-                    println!("Run: cargo install x402-dev");
-                }
-                return Ok(());
-            }
-        }
+fn update_checks_disabled() -> bool {
+    std::env::var(NO_UPDATE_CHECK_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
+fn print_status(status: &UpdateStatus) {
+    println!("\nLatest release: v{}", status.latest);
+    println!("Changelog: {}", status.changelog_url);
+    if status.update_available {
+        print_update_notice(status);
+    } else {
+        println!("✅ You are running the latest version");
     }
+}
 
-    // Perform update check
-    let latest_version = fetch_latest_version().await?;
-    let current_version = env!("CARGO_PKG_VERSION");
+fn print_update_notice(status: &UpdateStatus) {
+    println!(
+        "\n✨ Update available: {} → {}",
+        status.current, status.latest
+    );
+    println!("Run: cargo install x402-dev");
+}
 
-    // Save cache
-    let cache = UpdateCache {
-        last_check: current_time,
-        latest_version: latest_version.clone(),
-    };
+/// Fetches the latest stable release, caching the answer on disk
+struct UpdateChecker {
+    api_url: String,
+    cache_path: PathBuf,
+    max_age_secs: u64,
+}
 
-    // Ensure cache directory exists
-    if let Some(parent) = cache_path.parent() {
-        fs::create_dir_all(parent).context("Failed to create cache directory")?;
+impl UpdateChecker {
+    fn new(max_age_secs: u64) -> Result<Self> {
+        Ok(Self {
+            api_url: RELEASES_API.to_string(),
+            cache_path: get_cache_path()?,
+            max_age_secs,
+        })
     }
 
-    fs::write(
-        &cache_path,
-        serde_json::to_string_pretty(&cache).context("Failed to serialize cache")?,
-    )
-    .context("Failed to write cache file")?;
+    async fn check(&self, current_version: &str) -> Result<UpdateStatus> {
+        let current_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("Failed to get current time")?
+            .as_secs();
 
-    // Display update notification if newer version available
-    if is_newer_version(&latest_version, current_version) {
-        println!(
-            "\n✨ Update available: {} → {}",
-            current_version, latest_version
-        );
-        println!("Run: cargo install x402-dev");
+        // If last check was within the interval, use cached result
+        if let Some(cache) = self.read_cache() {
+            if current_time.saturating_sub(cache.last_check) < self.max_age_secs {
+                return Ok(status(current_version, cache, true));
+            }
+        }
+
+        let (latest_version, changelog_url) = self.fetch_latest_release().await?;
+        let cache = UpdateCache {
+            last_check: current_time,
+            latest_version,
+            changelog_url,
+        };
+
+        // A cache write failure only costs an extra request next time
+        let _ = self.write_cache(&cache);
+
+        Ok(status(current_version, cache, false))
     }
 
-    Ok(())
-}
+    fn read_cache(&self) -> Option<UpdateCache> {
+        let data = fs::read_to_string(&self.cache_path).ok()?;
+        serde_json::from_str(&data).ok()
+    }
 
-async fn fetch_latest_version() -> Result<String> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(5))
-        .build()
-        .context("Failed to create HTTP client")?;
+    fn write_cache(&self, cache: &UpdateCache) -> Result<()> {
+        // Ensure cache directory exists
+        if let Some(parent) = self.cache_path.parent() {
+            fs::create_dir_all(parent).context("Failed to create cache directory")?;
+        }
 
-    let response = client
-        .get(CRATES_IO_API)
-        .send()
-        .await
-        .context("Failed to connect to crates.io")?;
+        fs::write(
+            &self.cache_path,
+            serde_json::to_string_pretty(cache).context("Failed to serialize cache")?,
+        )
+        .context("Failed to write cache file")
+    }
 
-    let crate_info: CratesIoResponse = response
-        .json()
-        .await
-        .context("Failed to parse crates.io response")?;
+    /// Highest stable release; drafts and pre-releases are skipped
+    async fn fetch_latest_release(&self) -> Result<(String, String)> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .user_agent(concat!("x402-dev/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("Failed to create HTTP client")?;
 
-    Ok(crate_info.crate_info.max_version)
-}
+        let response = client
+            .get(&self.api_url)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .context("Failed to connect to GitHub releases API")?
+            .error_for_status()
+            .context("GitHub releases API returned an error")?;
 
-fn get_cache_path() -> Result<PathBuf> {
-    // Use home directory + .x402dev for cross-platform compatibility
-    let home_dir = dirs::home_dir().context("Failed to determine home directory")?;
+        let releases: Vec<GithubRelease> = response
+            .json()
+            .await
+            .context("Failed to parse GitHub releases response")?;
 
-    Ok(home_dir.join(".x402dev").join("update-check.json"))
+        releases
+            .into_iter()
+            .filter(|r| !r.draft && !r.prerelease)
+            .filter_map(|r| {
+                let version = Version::parse(r.tag_name.trim_start_matches('v')).ok()?;
+                version.pre.is_empty().then_some((version, r.html_url))
+            })
+            .max_by(|a, b| a.0.cmp(&b.0))
+            .map(|(version, url)| (version.to_string(), url))
+            .ok_or_else(|| anyhow!("No stable release found at {}", self.api_url))
+    }
 }
 
-mod dirs {
-    use std::path::PathBuf;
-
-    pub fn home_dir() -> Option<PathBuf> {
-        directories::UserDirs::new().map(|ud| ud.home_dir().to_path_buf())
+fn status(current_version: &str, cache: UpdateCache, from_cache: bool) -> UpdateStatus {
+    UpdateStatus {
+        current: current_version.to_string(),
+        update_available: is_newer_version(&cache.latest_version, current_version),
+        latest: cache.latest_version,
+        changelog_url: cache.changelog_url,
+        from_cache,
     }
 }
 
+/// update-check.json in the state directory (~/.x402dev unless overridden
+/// or read-only)
+fn get_cache_path() -> Result<PathBuf> {
+    Ok(x402_server::state_dir::resolve()?
+        .path
+        .join("update-check.json"))
+}
+
 fn is_newer_version(latest: &str, current: &str) -> bool {
     // Proper semantic versioning comparison using semver crate
     // Returns true if latest > current, false otherwise or if parsing fails
@@ -154,3 +239,149 @@ fn is_newer_version(latest: &str, current: &str) -> bool {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn release(tag: &str, prerelease: bool) -> serde_json::Value {
+        json!({
+            "tag_name": tag,
+            "html_url": format!("{}/tag/{}", RELEASES_PAGE, tag),
+            "prerelease": prerelease,
+            "draft": false
+        })
+    }
+
+    async fn releases_server(releases: serde_json::Value, expected_calls: u64) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/releases"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(releases))
+            .expect(expected_calls)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn checker(server: &MockServer, dir: &tempfile::TempDir) -> UpdateChecker {
+        UpdateChecker {
+            api_url: format!("{}/releases", server.uri()),
+            cache_path: dir.path().join("update-check.json"),
+            max_age_secs: EXPLICIT_CHECK_INTERVAL_SECS,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_newer_release_available() {
+        let server = releases_server(
+            json!([release("v0.2.0", false), release("v0.1.5", false)]),
+            1,
+        )
+        .await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let status = checker(&server, &dir).check("0.1.0").await.unwrap();
+
+        assert!(status.update_available);
+        assert_eq!(status.latest, "0.2.0");
+        assert_eq!(
+            status.changelog_url,
+            format!("{}/tag/v0.2.0", RELEASES_PAGE)
+        );
+        assert!(!status.from_cache);
+        assert!(dir.path().join("update-check.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_up_to_date() {
+        let server = releases_server(json!([release("v0.1.0", false)]), 1).await;
+        let dir = tempfile::tempdir().unwrap();
+
+        let status = checker(&server, &dir).check("0.1.0").await.unwrap();
+
+        assert!(!status.update_available);
+        assert_eq!(status.latest, "0.1.0");
+    }
+
+    #[tokio::test]
+    async fn test_pre_releases_are_not_offered() {
+        // Flagged pre-release, unflagged semver pre-release, and the stable one
+        let releases = json!([
+            release("v0.3.0-rc.1", true),
+            release("v0.2.0-beta.2", false),
+            release("v0.1.0", false)
+        ]);
+        let server = releases_server(releases, 2).await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let status = checker(&server, &dir).check("0.1.0").await.unwrap();
+        assert_eq!(status.latest, "0.1.0");
+        assert!(!status.update_available);
+
+        // Running a pre-release of the latest stable still prompts an upgrade
+        let dir = tempfile::tempdir().unwrap();
+        let status = checker(&server, &dir).check("0.1.0-beta.1").await.unwrap();
+        assert!(status.update_available);
+    }
+
+    #[tokio::test]
+    async fn test_fresh_cache_skips_request() {
+        let server = releases_server(json!([release("v9.9.9", false)]), 0).await;
+        let dir = tempfile::tempdir().unwrap();
+        let checker = checker(&server, &dir);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        checker
+            .write_cache(&UpdateCache {
+                last_check: now - 60,
+                latest_version: "0.2.0".to_string(),
+                changelog_url: default_changelog_url(),
+            })
+            .unwrap();
+
+        let status = checker.check("0.1.0").await.unwrap();
+
+        assert!(status.from_cache);
+        assert_eq!(status.latest, "0.2.0");
+        assert!(status.update_available);
+    }
+
+    #[tokio::test]
+    async fn test_stale_cache_refetches() {
+        let server = releases_server(json!([release("v0.3.0", false)]), 1).await;
+        let dir = tempfile::tempdir().unwrap();
+        let checker = checker(&server, &dir);
+
+        checker
+            .write_cache(&UpdateCache {
+                last_check: 0,
+                latest_version: "0.2.0".to_string(),
+                changelog_url: default_changelog_url(),
+            })
+            .unwrap();
+
+        let status = checker.check("0.1.0").await.unwrap();
+
+        assert!(!status.from_cache);
+        assert_eq!(status.latest, "0.3.0");
+    }
+
+    #[tokio::test]
+    async fn test_network_error_is_an_error_not_a_panic() {
+        let dir = tempfile::tempdir().unwrap();
+        let checker = UpdateChecker {
+            api_url: "http://127.0.0.1:1/releases".to_string(),
+            cache_path: dir.path().join("update-check.json"),
+            max_age_secs: EXPLICIT_CHECK_INTERVAL_SECS,
+        };
+
+        assert!(checker.check("0.1.0").await.is_err());
+    }
+}
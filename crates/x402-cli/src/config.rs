@@ -1,11 +1,36 @@
+use crate::env_expand::{expand_env_vars, Expanded};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, IsTerminal};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use x402_core::format::DetectedFormat;
+use x402_core::i18n::{CatalogError, Message};
+use x402_core::policy::{
+    AuditLogConfig, LintConfig, LogRotation, QuotaHeaders, StateBackend, StateStoreConfig,
+};
+use x402_core::redact::redact_url;
+use x402_core::secrets::{interpolate_secrets, secret_references, SecretStore, PASSPHRASE_ENV};
+use x402_core::testing::ResolveOverride;
+use x402_core::verifier::VerifierKind;
+use x402_domain::amount::user_input;
+use x402_domain::{
+    AmountFormatter, DurationField, IdFormat, MemoPrefix, Millis, ProtocolId, SolanaAddress,
+};
+use x402_server::maintenance::{validate_maintenance, MaintenanceRule};
+use x402_server::readiness::{validate_startup_hooks, StartupHook};
+use x402_server::responses::validate_responses;
+use x402_server::routing::validate_recipient_routing;
+use x402_server::state_dir;
+use x402_server::{
+    AmountTolerance, LogFormat, OutOfBounds, RecipientRoute, ResponseSpec, RpcConfig,
+    SigningConfig, TlsConfig, WebhookConfig, ZeroPriceBehavior, SUPPORTED_METHODS,
+};
 
 /// Log level for application logging
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -21,27 +46,23 @@ pub enum LogLevel {
 }
 
 impl LogLevel {
+    /// The matching `tracing` level, used for log filtering
+    pub fn to_tracing_level(self) -> tracing::Level {
+        match self {
+            LogLevel::Error => tracing::Level::ERROR,
+            LogLevel::Warn => tracing::Level::WARN,
+            LogLevel::Info => tracing::Level::INFO,
+            LogLevel::Debug => tracing::Level::DEBUG,
+            LogLevel::Trace => tracing::Level::TRACE,
+        }
+    }
+
     /// Returns true if this level is at least as verbose as `other`
     ///
     /// Library API for future log filtering feature
     #[allow(dead_code)]
     pub fn is_at_least(&self, other: LogLevel) -> bool {
-        use LogLevel::*;
-        let self_level = match self {
-            Error => 0,
-            Warn => 1,
-            Info => 2,
-            Debug => 3,
-            Trace => 4,
-        };
-        let other_level = match other {
-            Error => 0,
-            Warn => 1,
-            Info => 2,
-            Debug => 3,
-            Trace => 4,
-        };
-        self_level >= other_level
+        self.to_tracing_level() >= other.to_tracing_level()
     }
 }
 
@@ -124,14 +145,206 @@ pub struct Config {
     #[serde(default)]
     pub log_level: LogLevel,
 
+    /// Request log lines as `pretty` text or `json` (default pretty)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_format: Option<LogFormat>,
+
     #[serde(default)]
     pub pricing: PricingConfig,
 
     #[serde(default)]
     pub simulation_mode: SimulationMode,
 
+    /// Milliseconds (`5000`) or a duration with a unit (`"5s"`)
     #[serde(default = "default_timeout_ms")]
-    pub timeout_delay_ms: u64,
+    pub timeout_delay_ms: DurationField<Millis>,
+
+    /// Serve the mock over https:// (cert_path/key_path or self_signed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+
+    /// Accept cleartext HTTP/2 (h2c) on the mock in addition to HTTP/1.1
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub http2: bool,
+
+    /// Event webhooks (invoice.issued, payment.verified, payment.failed, policy.denied)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+
+    /// Sign invoices with Ed25519 (keypair_path or ephemeral; `signing` feature)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
+
+    /// Directory scanned by `policy` commands run without a file (default: x402-policies)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policies_dir: Option<PathBuf>,
+
+    /// Policy files always included by `policy` commands run without a file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy_files: Vec<PathBuf>,
+
+    /// How long a mock invoice stays payable (`expires` header field), in
+    /// seconds or with a unit (`"5m"`)
+    #[serde(default = "default_invoice_ttl_seconds")]
+    pub invoice_ttl_seconds: DurationField,
+
+    /// Invoice TTL overrides for exact paths or `/prefix/*` patterns
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub invoice_ttl_per_resource: HashMap<String, DurationField>,
+
+    /// Methods the mock accepts per exact path or `/prefix/*` pattern (others get 405)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub methods: HashMap<String, Vec<String>>,
+
+    /// Address every mock invoice pays to (Base58, checked when the file loads)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<SolanaAddress>,
+
+    /// Recipients per exact path or `/prefix/*` pattern, one address or a
+    /// weighted split summing to 100
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub recipient_routing: HashMap<String, RecipientRoute>,
+
+    /// Seed that makes weighted recipient picks repeatable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient_routing_seed: Option<u64>,
+
+    /// Paths the mock answers with 503 and `Retry-After` instead of an
+    /// invoice, per exact path or `/prefix/*` pattern
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub maintenance: Vec<MaintenanceRule>,
+
+    /// Bodies the mock serves after a verified payment, per exact path or
+    /// `/prefix/*` pattern (inline or file, optionally gzip/deflate encoded)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub responses: HashMap<String, ResponseSpec>,
+
+    /// Prefix before the UUID in mock invoice memos (default `req-`),
+    /// checked when the file loads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo_prefix: Option<MemoPrefix>,
+
+    /// Format of mock memos and request IDs: `uuidv4` (default), `uuidv7`
+    /// or `ulid`; the last two sort by creation time
+    #[serde(default, skip_serializing_if = "IdFormat::is_default")]
+    pub id_format: IdFormat,
+
+    /// Seed that makes generated IDs repeatable (uuidv4 and ulid only);
+    /// for tests, never production
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id_seed: Option<u64>,
+
+    /// Auth scheme of mock invoice headers and the identifier `check`
+    /// accepts (default `x402-solana`), checked when the file loads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_id: Option<ProtocolId>,
+
+    /// `HOST:PORT:ADDR` host overrides for `check` and `test` (like curl's
+    /// --resolve), checked when the file loads
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolve: Vec<ResolveOverride>,
+
+    /// Where policy counters are kept by `mock --policy` and `policy serve`
+    #[serde(default, skip_serializing_if = "StateBackend::is_memory")]
+    pub state_backend: StateBackend,
+
+    /// SQLite file of the sqlite state backend (default .x402dev/policy-state.db)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_path: Option<PathBuf>,
+
+    /// JSONL file `mock --policy` and `policy serve` write policy decisions
+    /// to (disabled when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Share of allowed decisions written to audit_log, 0.0 to 1.0
+    /// (denials are always written; unset: 1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_sample_rate: Option<f64>,
+
+    /// Size (`"10MB"`) at which log files such as audit_log are rotated and
+    /// how many rotated files are kept (unset: 10MB, 5 files)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_rotation: Option<LogRotation>,
+
+    /// Request headers masked in logs besides Authorization, X-Api-Key and
+    /// cookies, checked when the file loads
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log_redaction: Vec<String>,
+
+    /// Let the mock accept a payment proof more than once (replays get
+    /// 409 `replayed_payment` by default)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_replay: bool,
+
+    /// Invoices and verified proofs the mock remembers (default 100000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_invoices: Option<usize>,
+
+    /// Stop the mock after this long without a non-admin request, in
+    /// seconds or with a unit (`"10m"`; 0 or unset: never)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_secs: Option<DurationField>,
+
+    /// Stop the mock this long after start, active or not, in seconds or
+    /// with a unit (`"1h"`; 0 or unset: never)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_lifetime_secs: Option<DurationField>,
+
+    /// Run in order once the mock listens (`warm_pricing`, `self_check`);
+    /// /health/ready answers 503 until all of them succeeded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub startup_hooks: Vec<StartupHook>,
+
+    /// How long `mock` waits for /health/ready, in seconds or with a unit
+    /// (`"1m"`; unset: 30s)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ready_timeout_secs: Option<DurationField>,
+
+    /// Interface the mock listens on: 127.0.0.1 (unset), a LAN address, or
+    /// 0.0.0.0 / :: for all interfaces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bind_address: Option<IpAddr>,
+
+    /// Answer /__admin/* for loopback clients only, whatever bind_address is
+    /// (unset: true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_loopback_only: Option<bool>,
+
+    /// Serve a fake Solana JSON-RPC on the mock's /rpc, with behaviors keyed
+    /// by signature prefix (disabled when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc: Option<RpcConfig>,
+
+    /// How the mock and `verify` check payment proofs: `simulated`
+    /// (simulation_mode decides) or `rpc` (proofs are transaction
+    /// signatures looked up on solana_rpc)
+    #[serde(default, skip_serializing_if = "VerifierKind::is_simulated")]
+    pub verifier: VerifierKind,
+
+    /// Accepted difference between a payment's X-Payment-Amount and the
+    /// invoice (`absolute` USDC and/or `percent`; exact by default)
+    #[serde(default, skip_serializing_if = "AmountTolerance::is_exact")]
+    pub amount_tolerance: AmountTolerance,
+
+    /// Response header names for the remaining policy quota, sent by the
+    /// mock and the generated middleware on allowed requests
+    #[serde(default, skip_serializing_if = "QuotaHeaders::is_default")]
+    pub quota_headers: QuotaHeaders,
+
+    /// `policy lint` settings: `production` and severities by rule code
+    #[serde(default, skip_serializing_if = "LintConfig::is_default")]
+    pub lint: LintConfig,
+
+    /// Paths priced at 0: `serve` them with 200 and no invoice, or
+    /// `invoice` them for amount=0 (unset: serve)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zero_price_behavior: Option<ZeroPriceBehavior>,
+
+    /// How human output shows amounts: `grouping`, `min_fraction_digits`
+    /// and `max_fraction_digits` (JSON keeps canonical strings)
+    #[serde(default, skip_serializing_if = "AmountFormatter::is_default")]
+    pub display: AmountFormatter,
 }
 
 // Default value functions for serde
@@ -143,8 +356,12 @@ fn default_solana_rpc() -> String {
     "https://api.devnet.solana.com".to_string()
 }
 
-fn default_timeout_ms() -> u64 {
-    5000
+fn default_timeout_ms() -> DurationField<Millis> {
+    DurationField::from_millis(5000)
+}
+
+fn default_invoice_ttl_seconds() -> DurationField {
+    DurationField::from_secs(x402_server::DEFAULT_INVOICE_TTL_SECONDS)
 }
 
 impl Default for Config {
@@ -156,20 +373,84 @@ impl Default for Config {
             pricing: PricingConfig::default(),
             simulation_mode: SimulationMode::default(),
             timeout_delay_ms: default_timeout_ms(),
+            tls: None,
+            http2: false,
+            webhooks: vec![],
+            signing: None,
+            policies_dir: None,
+            policy_files: vec![],
+            invoice_ttl_seconds: default_invoice_ttl_seconds(),
+            invoice_ttl_per_resource: HashMap::new(),
+            methods: HashMap::new(),
+            recipient: None,
+            recipient_routing: HashMap::new(),
+            recipient_routing_seed: None,
+            maintenance: vec![],
+            responses: HashMap::new(),
+            memo_prefix: None,
+            id_format: IdFormat::default(),
+            id_seed: None,
+            protocol_id: None,
+            resolve: vec![],
+            state_backend: StateBackend::default(),
+            state_path: None,
+            audit_log: None,
+            audit_sample_rate: None,
+            log_rotation: None,
+            log_redaction: vec![],
+            allow_replay: false,
+            max_invoices: None,
+            idle_timeout_secs: None,
+            max_lifetime_secs: None,
+            startup_hooks: vec![],
+            ready_timeout_secs: None,
+            bind_address: None,
+            admin_loopback_only: None,
+            log_format: None,
+            zero_price_behavior: None,
+            rpc: None,
+            verifier: VerifierKind::default(),
+            amount_tolerance: AmountTolerance::default(),
+            quota_headers: QuotaHeaders::default(),
+            lint: LintConfig::default(),
+            display: AmountFormatter::default(),
         }
     }
 }
 
 /// Pricing configuration for mock server endpoints
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PricingConfig {
     /// Default pricing for all endpoints (in SOL/USDC)
-    #[serde(default = "default_pricing_amount")]
+    #[serde(
+        default = "default_pricing_amount",
+        deserialize_with = "user_input::deserialize_f64"
+    )]
     pub default: f64,
 
     /// Per-resource pricing rules (supports exact match and wildcard patterns)
-    #[serde(default)]
+    #[serde(default, deserialize_with = "user_input::deserialize_f64_map")]
     pub per_resource: HashMap<String, f64>,
+
+    /// Smallest amount an invoice may carry (facilitators refuse dust)
+    #[serde(
+        default,
+        deserialize_with = "user_input::deserialize_opt_f64",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub min_amount: Option<f64>,
+
+    /// Largest amount a single invoice may carry
+    #[serde(
+        default,
+        deserialize_with = "user_input::deserialize_opt_f64",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_amount: Option<f64>,
+
+    /// Clamp or reject per-resource prices outside the bounds (clamp | error)
+    #[serde(default)]
+    pub out_of_bounds: OutOfBounds,
 }
 
 fn default_pricing_amount() -> f64 {
@@ -181,6 +462,9 @@ impl Default for PricingConfig {
         PricingConfig {
             default: default_pricing_amount(),
             per_resource: HashMap::new(),
+            min_amount: None,
+            max_amount: None,
+            out_of_bounds: OutOfBounds::default(),
         }
     }
 }
@@ -190,40 +474,97 @@ impl PricingConfig {
     pub fn validate(&self) -> Result<()> {
         // Validate default pricing
         if self.default < 0.0 {
-            anyhow::bail!(
-                "Default pricing must be non-negative. Got: {}\nFix: Set default pricing to a non-negative value, e.g., 0.01",
-                self.default
-            );
+            anyhow::bail!(CatalogError::new(
+                Message::new("config.default_price_negative").arg("amount", self.default)
+            ));
         }
         if self.default > 100.0 {
-            anyhow::bail!(
-                "Default pricing must be <= 100 SOL. Got: {}\nFix: Set default pricing to a reasonable value, e.g., 0.01",
-                self.default
-            );
+            anyhow::bail!(CatalogError::new(
+                Message::new("config.default_price_too_high").arg("amount", self.default)
+            ));
         }
 
         // Validate per-resource pricing
         for (path, amount) in &self.per_resource {
             if *amount < 0.0 {
-                anyhow::bail!(
-                    "Pricing for {} must be non-negative. Got: {}\nFix: Set pricing to a non-negative value",
-                    path,
-                    amount
-                );
+                anyhow::bail!(CatalogError::new(
+                    Message::new("config.resource_price_negative")
+                        .arg("path", path)
+                        .arg("amount", amount)
+                ));
             }
             if *amount > 100.0 {
-                anyhow::bail!(
-                    "Pricing for {} must be <= 100 SOL. Got: {}\nFix: Set pricing to a reasonable value",
-                    path,
-                    amount
-                );
+                anyhow::bail!(CatalogError::new(
+                    Message::new("config.resource_price_too_high")
+                        .arg("path", path)
+                        .arg("amount", amount)
+                ));
             }
         }
 
+        // Validate invoice bounds (min <= default <= max)
+        for (name, bound) in [
+            ("min_amount", self.min_amount),
+            ("max_amount", self.max_amount),
+        ] {
+            if let Some(amount) = bound.filter(|b| *b < 0.0) {
+                anyhow::bail!(CatalogError::new(
+                    Message::new("config.price_bound_negative")
+                        .arg("name", name)
+                        .arg("amount", amount)
+                ));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_amount, self.max_amount) {
+            if min > max {
+                anyhow::bail!(CatalogError::new(
+                    Message::new("config.price_bounds_inverted")
+                        .arg("min", min)
+                        .arg("max", max)
+                ));
+            }
+        }
+        if let Some(min) = self.min_amount.filter(|min| self.default < *min) {
+            anyhow::bail!(CatalogError::new(
+                Message::new("config.default_price_below_min")
+                    .arg("amount", self.default)
+                    .arg("min", min)
+            ));
+        }
+        if let Some(max) = self.max_amount.filter(|max| self.default > *max) {
+            anyhow::bail!(CatalogError::new(
+                Message::new("config.default_price_above_max")
+                    .arg("amount", self.default)
+                    .arg("max", max)
+            ));
+        }
+
         Ok(())
     }
 }
 
+impl From<SimulationMode> for x402_server::SimulationMode {
+    fn from(mode: SimulationMode) -> Self {
+        match mode {
+            SimulationMode::Success => x402_server::SimulationMode::Success,
+            SimulationMode::Failure => x402_server::SimulationMode::Failure,
+            SimulationMode::Timeout => x402_server::SimulationMode::Timeout,
+        }
+    }
+}
+
+impl From<&PricingConfig> for x402_server::PricingConfig {
+    fn from(pricing: &PricingConfig) -> Self {
+        Self {
+            default: pricing.default,
+            per_resource: pricing.per_resource.clone(),
+            min_amount: pricing.min_amount,
+            max_amount: pricing.max_amount,
+            out_of_bounds: pricing.out_of_bounds,
+        }
+    }
+}
+
 /// Pricing matcher for route-based pricing
 ///
 /// Library API for future route-based pricing feature
@@ -287,24 +628,86 @@ impl Config {
         self.pricing = other.pricing.clone();
         self.simulation_mode = other.simulation_mode;
         self.timeout_delay_ms = other.timeout_delay_ms;
+        self.tls = other.tls.clone();
+        self.http2 = other.http2;
+        self.webhooks = other.webhooks.clone();
+        self.signing = other.signing.clone();
+        self.policies_dir = other.policies_dir.clone();
+        self.policy_files = other.policy_files.clone();
+        self.invoice_ttl_seconds = other.invoice_ttl_seconds;
+        self.invoice_ttl_per_resource = other.invoice_ttl_per_resource.clone();
+        self.methods = other.methods.clone();
+        self.recipient = other.recipient.clone();
+        self.recipient_routing = other.recipient_routing.clone();
+        self.recipient_routing_seed = other.recipient_routing_seed;
+        self.maintenance = other.maintenance.clone();
+        self.responses = other.responses.clone();
+        self.memo_prefix = other.memo_prefix.clone();
+        self.id_format = other.id_format;
+        self.id_seed = other.id_seed;
+        self.protocol_id = other.protocol_id.clone();
+        self.resolve = other.resolve.clone();
+        self.state_backend = other.state_backend;
+        self.state_path = other.state_path.clone();
+        self.audit_log = other.audit_log.clone();
+        self.audit_sample_rate = other.audit_sample_rate;
+        self.log_rotation = other.log_rotation;
+        self.log_redaction = other.log_redaction.clone();
+        self.allow_replay = other.allow_replay;
+        self.max_invoices = other.max_invoices;
+        self.idle_timeout_secs = other.idle_timeout_secs;
+        self.max_lifetime_secs = other.max_lifetime_secs;
+        self.startup_hooks = other.startup_hooks.clone();
+        self.ready_timeout_secs = other.ready_timeout_secs;
+        self.bind_address = other.bind_address;
+        self.admin_loopback_only = other.admin_loopback_only;
+        self.log_format = other.log_format;
+        self.zero_price_behavior = other.zero_price_behavior;
+        self.rpc = other.rpc.clone();
+        self.verifier = other.verifier;
+        self.amount_tolerance = other.amount_tolerance;
+        self.quota_headers = other.quota_headers.clone();
+        self.lint = other.lint.clone();
+        self.display = other.display;
+    }
+
+    /// Policy state store selected by `state_backend` and `state_path`
+    pub fn policy_state(&self) -> StateStoreConfig {
+        StateStoreConfig {
+            backend: self.state_backend,
+            path: self.state_path.clone(),
+        }
+    }
+
+    /// Decision audit log at `path` (default: audit_log), allows sampled at
+    /// `sample_rate` (default: audit_sample_rate); `None` when no file is set
+    pub fn audit_log_config(
+        &self,
+        path: Option<&Path>,
+        sample_rate: Option<f64>,
+    ) -> Option<AuditLogConfig> {
+        let path = path.map(Path::to_path_buf).or(self.audit_log.clone())?;
+        Some(
+            AuditLogConfig::new(path)
+                .sample_rate(sample_rate.or(self.audit_sample_rate).unwrap_or(1.0))
+                .rotation(self.log_rotation.unwrap_or_default()),
+        )
     }
 
     /// Validate configuration values
     pub fn validate(&self) -> Result<()> {
         // Validate port range
         if !(1024..=65535).contains(&self.port) {
-            anyhow::bail!(
-                "Invalid port: {}. Port must be between 1024 and 65535.\nFix: Set port to a value in the valid range, e.g., 8402",
-                self.port
-            );
+            anyhow::bail!(CatalogError::new(
+                Message::new("config.invalid_port").arg("port", self.port)
+            ));
         }
 
         // Validate Solana RPC URL format
         if !self.solana_rpc.starts_with("http://") && !self.solana_rpc.starts_with("https://") {
-            anyhow::bail!(
-                "Invalid Solana RPC URL: {}. URL must start with http:// or https://.\nFix: Use a valid URL, e.g., https://api.devnet.solana.com",
-                self.solana_rpc
-            );
+            anyhow::bail!(CatalogError::new(
+                Message::new("config.invalid_rpc_url").arg("url", redact_url(&self.solana_rpc))
+            ));
         }
 
         // Log level validation is now compile-time enforced by the LogLevel enum
@@ -314,66 +717,333 @@ impl Config {
         self.pricing.validate()?;
 
         // Validate timeout delay (100ms to 60s)
-        if self.timeout_delay_ms < 100 || self.timeout_delay_ms > 60000 {
+        if !(100..=60_000).contains(&self.timeout_delay_ms.as_millis()) {
+            anyhow::bail!(CatalogError::new(
+                Message::new("config.invalid_timeout").arg("ms", self.timeout_delay_ms.as_millis())
+            ));
+        }
+
+        // Validate invoice TTLs (1s to 1 day)
+        let overrides = self
+            .invoice_ttl_per_resource
+            .iter()
+            .map(|(path, ttl)| (path.as_str(), ttl.as_secs()));
+        for (name, ttl) in
+            std::iter::once(("invoice_ttl_seconds", self.invoice_ttl_seconds.as_secs()))
+                .chain(overrides)
+        {
+            if !(1..=86_400).contains(&ttl) {
+                anyhow::bail!(CatalogError::new(
+                    Message::new("config.invalid_invoice_ttl")
+                        .arg("name", name)
+                        .arg("seconds", ttl)
+                ));
+            }
+        }
+
+        // Validate allowed methods (non-empty, uppercase, known to the mock)
+        for (path, methods) in &self.methods {
+            if methods.is_empty()
+                || methods
+                    .iter()
+                    .any(|m| !SUPPORTED_METHODS.contains(&m.as_str()))
+            {
+                anyhow::bail!(CatalogError::new(
+                    Message::new("config.invalid_methods")
+                        .arg("path", path)
+                        .arg("methods", methods.join(", "))
+                        .arg("supported", SUPPORTED_METHODS.join(", "))
+                ));
+            }
+        }
+
+        validate_recipient_routing(&self.recipient_routing)?;
+        validate_maintenance(&self.maintenance)?;
+        validate_startup_hooks(&self.startup_hooks)?;
+        validate_responses(&self.responses)?;
+        if self.id_seed.is_some() && self.id_format == IdFormat::Uuidv7 {
+            anyhow::bail!(
+                "id_seed cannot be used with id_format: uuidv7\nFix: Remove id_seed, or use id_format: uuidv4 or ulid for reproducible test IDs"
+            );
+        }
+        self.amount_tolerance.validate()?;
+        self.quota_headers.validate()?;
+        self.lint.validate()?;
+        if let Err(e) = self.display.validate() {
             anyhow::bail!(
-                "Invalid timeout delay: {} ms. Must be between 100ms and 60000ms (1 minute).\n\
-                Fix: Set timeout_delay_ms to a reasonable value between 100 and 60000",
-                self.timeout_delay_ms
+                "{}\nFix: Keep display.min_fraction_digits at most display.max_fraction_digits, and that at most 28",
+                e
             );
         }
+        if let Some(rpc) = &self.rpc {
+            rpc.validate()?;
+        }
+
+        // Validate redacted header names
+        if let Some(name) = self
+            .log_redaction
+            .iter()
+            .find(|name| reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err())
+        {
+            anyhow::bail!(CatalogError::new(
+                Message::new("config.invalid_log_redaction").arg("name", name)
+            ));
+        }
 
         Ok(())
     }
 }
 
 /// Get the global config directory (~/.x402dev/)
-fn get_config_dir() -> Result<PathBuf> {
-    directories::BaseDirs::new()
-        .map(|dirs| dirs.home_dir().join(".x402dev"))
-        .context("Could not determine home directory")
+pub fn get_config_dir() -> Result<PathBuf> {
+    state_dir::home_dir().context("Could not determine home directory")
+}
+
+/// ~/.x402dev/secrets, or `secrets` in `X402_DEV_STATE_DIR` when set
+///
+/// Secrets never fall back to the temp dir like other state: other users
+/// could read them there, and they would not survive a reboot.
+pub fn secrets_dir() -> Result<PathBuf> {
+    match env::var_os(state_dir::STATE_DIR_ENV).filter(|value| !value.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir).join("secrets")),
+        None => Ok(get_config_dir()?.join("secrets")),
+    }
+}
+
+/// Secrets in [`secrets_dir`]; the passphrase comes from
+/// `X402_DEV_SECRETS_PASSPHRASE`, else a prompt when stdin is a terminal
+pub fn open_secret_store() -> Result<SecretStore> {
+    Ok(SecretStore::new(secrets_dir()?, || -> Result<String> {
+        if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+            return Ok(passphrase);
+        }
+        if !io::stdin().is_terminal() {
+            anyhow::bail!(
+                "A passphrase is needed to use encrypted secrets\nFix: Set {}",
+                PASSPHRASE_ENV
+            );
+        }
+        Ok(dialoguer::Password::new()
+            .with_prompt("Secrets passphrase")
+            .interact()?)
+    }))
+}
+
+/// True if `path` exists and can be opened; other files are skipped with a
+/// debug log
+fn is_readable_file(path: &Path) -> bool {
+    match fs::File::open(path) {
+        Ok(_) => true,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => false,
+        Err(e) => {
+            tracing::debug!("Skipping global config {}: {}", path.display(), e);
+            false
+        }
+    }
 }
 
-/// Load global config from ~/.x402dev/config.yaml
-fn load_global_config() -> Result<Option<Config>> {
-    let config_dir = get_config_dir()?;
-    let config_path = config_dir.join("config.yaml");
+/// Read one config file and expand its `${VAR}` references from `env`
+fn read_expanded(path: &Path, kind: &str, env: &HashMap<String, String>) -> Result<Expanded> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {} config file: {:?}", kind, path))?;
+    expand_env_vars(&content, &display_config_path(path), |name| {
+        env.get(name).cloned()
+    })
+}
 
-    if !config_path.exists() {
-        return Ok(None);
+/// Read and parse one config file; `kind` is "global" or "project"
+///
+/// `${VAR}` references are expanded from `env`, then `${secret:name}`
+/// references are resolved, before parsing. Also returns the variables each
+/// top-level field expanded.
+fn read_config_file(
+    path: &Path,
+    kind: &str,
+    parse_error: &'static str,
+    env: &HashMap<String, String>,
+) -> Result<(Config, BTreeMap<String, Vec<String>>)> {
+    let Expanded {
+        text: mut content,
+        fields,
+    } = read_expanded(path, kind, env)?;
+    if !secret_references(&content).is_empty() {
+        let store = open_secret_store()?;
+        content = interpolate_secrets(&content, |name| store.get(name))?;
     }
 
-    let content = fs::read_to_string(&config_path)
-        .with_context(|| format!("Failed to read global config file: {:?}", config_path))?;
+    let config = DetectedFormat::detect(Some(path), &content)
+        .parse(&content)
+        .map_err(|e| {
+            CatalogError::new(
+                Message::new(parse_error)
+                    .arg("path", format!("{:?}", path))
+                    .arg("error", format!("{:#}", e)),
+            )
+        })?;
+    Ok((config, fields))
+}
 
-    let config: Config = serde_yaml::from_str(&content).with_context(|| {
-        format!(
-            "Failed to parse global config file: {:?}\nFix: Ensure the YAML syntax is valid",
-            config_path
-        )
-    })?;
+/// Source label of a field a config file set, naming the variables its
+/// value expanded: "project (.x402dev.yaml, expanded $STAGING_RPC)"
+fn file_field_source(
+    source: &str,
+    field: &str,
+    expanded: &BTreeMap<String, Vec<String>>,
+) -> String {
+    match (expanded.get(field), source.strip_suffix(')')) {
+        (Some(vars), Some(label)) => {
+            let vars: Vec<String> = vars.iter().map(|var| format!("${}", var)).collect();
+            format!("{}, expanded {})", label, vars.join(", "))
+        }
+        _ => source.to_string(),
+    }
+}
+
+/// Top-level keys written in a config file
+fn config_file_keys(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {:?}", path))?;
+    let value: serde_yaml::Value = DetectedFormat::detect(Some(path), &content)
+        .parse(&content)
+        .unwrap_or_default();
+    Ok(value
+        .as_mapping()
+        .map(|map| {
+            map.keys()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Project config file name searched for by discovery
+pub const PROJECT_CONFIG_FILE: &str = ".x402dev.yaml";
+
+/// Every project config file name discovery accepts, preferred first when a
+/// directory holds more than one
+pub const PROJECT_CONFIG_FILES: [&str; 3] = [PROJECT_CONFIG_FILE, ".x402dev.yml", ".x402dev.json"];
+
+/// Global config file names in ~/.x402dev/, preferred first
+const GLOBAL_CONFIG_FILES: [&str; 3] = ["config.yaml", "config.yml", "config.json"];
+
+/// Environment variable naming an explicit project config file
+pub const CONFIG_PATH_ENV: &str = "X402_DEV_CONFIG";
+
+/// Environment variables overriding config fields, with the field each sets
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("X402_DEV_PORT", "port"),
+    ("X402_DEV_SOLANA_RPC", "solana_rpc"),
+    ("X402_DEV_LOG_LEVEL", "log_level"),
+];
 
-    Ok(Some(config))
+/// Source label of the global config file
+const GLOBAL_SOURCE: &str = "global (~/.x402dev/config.yaml)";
+
+/// Find one of `file_names` in `start` or its ancestors, up to the enclosing
+/// git root
+///
+/// The nearest directory wins, then the earliest name in `file_names`.
+/// Outside a git repository only `start` is searched, so stray files higher
+/// up are never picked up.
+pub fn discover_project_config(start: &Path, file_names: &[&str]) -> Option<PathBuf> {
+    let in_repo = start.ancestors().any(|dir| dir.join(".git").exists());
+    for dir in start.ancestors() {
+        let found = file_names
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| candidate.is_file());
+        if found.is_some() {
+            return found;
+        }
+        if !in_repo || dir.join(".git").exists() {
+            break;
+        }
+    }
+    None
 }
 
-/// Load project config from ./.x402dev.yaml
-fn load_project_config() -> Result<Option<Config>> {
-    let config_path = PathBuf::from(".x402dev.yaml");
+/// Project config file in effect: `X402_DEV_CONFIG` if set, else the
+/// nearest `.x402dev.yaml` (or `.yml`, `.json`) found from the current directory
+pub fn project_config_path() -> Result<Option<PathBuf>> {
+    project_config_path_from(env::var_os(CONFIG_PATH_ENV).map(PathBuf::from))
+}
 
-    if !config_path.exists() {
-        return Ok(None);
+/// Project config file in effect given the value of `X402_DEV_CONFIG`
+fn project_config_path_from(explicit: Option<PathBuf>) -> Result<Option<PathBuf>> {
+    if let Some(path) = explicit.filter(|path| !path.as_os_str().is_empty()) {
+        if !path.is_file() {
+            anyhow::bail!(CatalogError::new(
+                Message::new("config.env_path_missing")
+                    .arg("var", CONFIG_PATH_ENV)
+                    .arg("path", path.display())
+            ));
+        }
+        return Ok(Some(path));
     }
 
-    let content = fs::read_to_string(&config_path)
-        .with_context(|| format!("Failed to read project config file: {:?}", config_path))?;
+    let cwd = env::current_dir().context("Could not determine current directory")?;
+    Ok(discover_project_config(&cwd, &PROJECT_CONFIG_FILES))
+}
 
-    let config: Config = serde_yaml::from_str(&content).with_context(|| {
-        format!(
-            "Failed to parse project config file: {:?}\nFix: Ensure the YAML syntax is valid",
-            config_path
-        )
-    })?;
+/// Path for messages: relative to the current directory when below it
+pub(crate) fn display_config_path(path: &Path) -> String {
+    env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf())
+        .display()
+        .to_string()
+}
+
+/// Files and environment a merged configuration is built from
+///
+/// Loading reads these instead of the process environment, so `config diff`
+/// can build two scenarios side by side.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigInputs {
+    /// Global config file, when it exists
+    pub global_file: Option<PathBuf>,
+    /// Project config file, when one is in effect
+    pub project_file: Option<PathBuf>,
+    /// Environment variables: `X402_DEV_*` overrides, and the values of
+    /// `${VAR}` references in the config files
+    pub env: HashMap<String, String>,
+}
+
+impl ConfigInputs {
+    /// What this process uses: ~/.x402dev/config.yaml, the project file in
+    /// effect and the process environment
+    pub fn from_process() -> Result<Self> {
+        let env = env::vars_os()
+            .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
+            .collect();
+        Self::with_env(env)
+    }
+
+    /// The usual global file and project discovery, with `env` in place of
+    /// the process environment (including `X402_DEV_CONFIG`)
+    pub fn with_env(env: HashMap<String, String>) -> Result<Self> {
+        // A missing home or unreadable global file only loses the global layer
+        let global_file = get_config_dir().ok().and_then(|dir| {
+            GLOBAL_CONFIG_FILES
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| is_readable_file(path))
+        });
+        let project_file = project_config_path_from(env.get(CONFIG_PATH_ENV).map(PathBuf::from))?;
+        Ok(Self {
+            global_file,
+            project_file,
+            env,
+        })
+    }
 
-    Ok(Some(config))
+    /// Source label of the project file
+    fn project_source(&self) -> Option<String> {
+        self.project_file
+            .as_deref()
+            .map(|path| format!("project ({})", display_config_path(path)))
+    }
 }
 
 /// CLI override options
@@ -383,22 +1053,23 @@ pub struct CliOverrides {
     pub solana_rpc: Option<String>,
     pub log_level: Option<LogLevel>,
     pub pricing: Option<f64>,
+    pub recipient: Option<SolanaAddress>,
 }
 
 impl Config {
-    /// Merge environment variables into config
-    pub fn merge_env(&mut self) -> Result<()> {
-        if let Ok(port_str) = env::var("X402_DEV_PORT") {
+    /// Merge `X402_DEV_*` environment variables into config
+    pub fn merge_env(&mut self, env: &HashMap<String, String>) -> Result<()> {
+        if let Some(port_str) = env.get("X402_DEV_PORT") {
             self.port = port_str
                 .parse()
                 .context("Invalid X402_DEV_PORT value. Must be a valid port number.")?;
         }
 
-        if let Ok(rpc) = env::var("X402_DEV_SOLANA_RPC") {
-            self.solana_rpc = rpc;
+        if let Some(rpc) = env.get("X402_DEV_SOLANA_RPC") {
+            self.solana_rpc = rpc.clone();
         }
 
-        if let Ok(level) = env::var("X402_DEV_LOG_LEVEL") {
+        if let Some(level) = env.get("X402_DEV_LOG_LEVEL") {
             self.log_level = level.parse().map_err(|e: String| anyhow::anyhow!(e))?;
         }
 
@@ -419,26 +1090,37 @@ impl Config {
         if let Some(pricing) = cli.pricing {
             self.pricing.default = pricing;
         }
+        if let Some(ref recipient) = cli.recipient {
+            self.recipient = Some(recipient.clone());
+        }
     }
 }
 
 /// Load merged configuration with priority: CLI > ENV > project > global > defaults
 pub fn load_merged_config(cli_overrides: Option<&CliOverrides>) -> Result<Config> {
+    load_merged_config_from(&ConfigInputs::from_process()?, cli_overrides)
+}
+
+/// [`load_merged_config`] over explicit files and environment
+pub fn load_merged_config_from(
+    inputs: &ConfigInputs,
+    cli_overrides: Option<&CliOverrides>,
+) -> Result<Config> {
     // Step 1: Start with defaults
     let mut config = Config::default();
 
     // Step 2: Apply global config (~/.x402dev/config.yaml)
-    if let Some(global) = load_global_config()? {
-        config.merge(global);
+    if let Some(path) = &inputs.global_file {
+        config.merge(read_config_file(path, "global", "config.parse_global", &inputs.env)?.0);
     }
 
-    // Step 3: Apply project config (./.x402dev.yaml)
-    if let Some(project) = load_project_config()? {
-        config.merge(project);
+    // Step 3: Apply project config (X402_DEV_CONFIG or nearest .x402dev.yaml)
+    if let Some(path) = &inputs.project_file {
+        config.merge(read_config_file(path, "project", "config.parse_project", &inputs.env)?.0);
     }
 
     // Step 4: Apply environment variables
-    config.merge_env()?;
+    config.merge_env(&inputs.env)?;
 
     // Step 5: Apply CLI flags (highest priority)
     if let Some(cli) = cli_overrides {
@@ -451,6 +1133,64 @@ pub fn load_merged_config(cli_overrides: Option<&CliOverrides>) -> Result<Config
     Ok(config)
 }
 
+/// Where each setting of [`load_merged_config_from`] comes from
+///
+/// Keys are top-level fields, or a dotted path when a flag sets part of one
+/// (`pricing.default`). Fields not listed keep their default. A project file
+/// replaces the whole global file, so only one file is ever listed.
+pub fn config_field_sources(
+    inputs: &ConfigInputs,
+    cli_overrides: Option<&CliOverrides>,
+) -> Result<HashMap<String, String>> {
+    let mut sources = HashMap::new();
+
+    let file = match (&inputs.project_file, inputs.project_source()) {
+        (Some(path), Some(source)) => Some((path, "project", source)),
+        _ => inputs
+            .global_file
+            .as_ref()
+            .map(|path| (path, "global", GLOBAL_SOURCE.to_string())),
+    };
+    if let Some((path, kind, source)) = file {
+        let expanded = read_expanded(path, kind, &inputs.env)?.fields;
+        for key in config_file_keys(path)? {
+            let source = file_field_source(&source, &key, &expanded);
+            sources.insert(key, source);
+        }
+    }
+
+    for (var, field) in ENV_OVERRIDES {
+        if inputs.env.contains_key(*var) {
+            sources.insert(field.to_string(), format!("environment ({})", var));
+        }
+    }
+
+    if let Some(cli) = cli_overrides {
+        let flags = [
+            ("port", "--port", cli.port.is_some()),
+            ("solana_rpc", "--solana-rpc", cli.solana_rpc.is_some()),
+            ("log_level", "--log-level", cli.log_level.is_some()),
+            ("pricing.default", "--pricing", cli.pricing.is_some()),
+            ("recipient", "--recipient", cli.recipient.is_some()),
+        ];
+        for (field, flag, set) in flags {
+            if set {
+                sources.insert(field.to_string(), format!("CLI flag ({})", flag));
+            }
+        }
+    }
+
+    Ok(sources)
+}
+
+/// Host overrides for `check` and `test`: the configured `resolve` entries,
+/// then `flags` (the last entry for a host wins)
+pub fn resolve_overrides(flags: &[ResolveOverride]) -> Result<Vec<ResolveOverride>> {
+    let mut overrides = load_merged_config(None)?.resolve;
+    overrides.extend(flags.iter().cloned());
+    Ok(overrides)
+}
+
 /// Configuration source tracking for display purposes
 #[derive(Debug, Clone)]
 pub struct ConfigWithSources {
@@ -458,15 +1198,22 @@ pub struct ConfigWithSources {
     pub port_source: String,
     pub solana_rpc_source: String,
     pub log_level_source: String,
-    /// Reserved for Epic 2: Will be displayed in config show command
-    /// when per-resource pricing tracking is implemented
-    #[allow(dead_code)]
     pub pricing_source: String,
+    /// Project config file that was applied, if any
+    pub project_file: Option<PathBuf>,
 }
 
 /// Load merged configuration with source tracking
 pub fn load_merged_config_with_sources(
     cli_overrides: Option<&CliOverrides>,
+) -> Result<ConfigWithSources> {
+    load_merged_config_with_sources_from(&ConfigInputs::from_process()?, cli_overrides)
+}
+
+/// [`load_merged_config_with_sources`] over explicit files and environment
+pub fn load_merged_config_with_sources_from(
+    inputs: &ConfigInputs,
+    cli_overrides: Option<&CliOverrides>,
 ) -> Result<ConfigWithSources> {
     let defaults = Config::default();
     let mut config = defaults.clone();
@@ -477,49 +1224,63 @@ pub fn load_merged_config_with_sources(
     let mut pricing_source = "default".to_string();
 
     // Global config
-    if let Some(global) = load_global_config()? {
+    if let Some(path) = &inputs.global_file {
+        let (global, expanded) =
+            read_config_file(path, "global", "config.parse_global", &inputs.env)?;
+        let source = |field| file_field_source(GLOBAL_SOURCE, field, &expanded);
         if global.port != defaults.port {
             config.port = global.port;
-            port_source = "global (~/.x402dev/config.yaml)".to_string();
+            port_source = source("port");
         }
         if global.solana_rpc != defaults.solana_rpc {
             config.solana_rpc = global.solana_rpc.clone();
-            solana_rpc_source = "global (~/.x402dev/config.yaml)".to_string();
+            solana_rpc_source = source("solana_rpc");
         }
         if global.log_level != defaults.log_level {
             config.log_level = global.log_level;
-            log_level_source = "global (~/.x402dev/config.yaml)".to_string();
+            log_level_source = source("log_level");
+        }
+        if global.pricing != defaults.pricing {
+            config.pricing = global.pricing.clone();
+            pricing_source = source("pricing");
         }
     }
 
     // Project config
-    if let Some(project) = load_project_config()? {
+    if let (Some(path), Some(source)) = (&inputs.project_file, inputs.project_source()) {
+        let (project, expanded) =
+            read_config_file(path, "project", "config.parse_project", &inputs.env)?;
+        let source = |field| file_field_source(&source, field, &expanded);
         if project.port != defaults.port {
             config.port = project.port;
-            port_source = "project (.x402dev.yaml)".to_string();
+            port_source = source("port");
         }
         if project.solana_rpc != defaults.solana_rpc {
             config.solana_rpc = project.solana_rpc.clone();
-            solana_rpc_source = "project (.x402dev.yaml)".to_string();
+            solana_rpc_source = source("solana_rpc");
         }
         if project.log_level != defaults.log_level {
             config.log_level = project.log_level;
-            log_level_source = "project (.x402dev.yaml)".to_string();
+            log_level_source = source("log_level");
+        }
+        if project.pricing != defaults.pricing {
+            config.pricing = project.pricing.clone();
+            pricing_source = source("pricing");
         }
     }
 
     // Environment variables
-    if let Ok(port_str) = env::var("X402_DEV_PORT") {
+    if let Some(port_str) = inputs.env.get("X402_DEV_PORT") {
         config.port = port_str
             .parse()
             .context("Invalid X402_DEV_PORT value. Must be a valid port number.")?;
         port_source = "environment (X402_DEV_PORT)".to_string();
     }
-    if let Ok(rpc) = env::var("X402_DEV_SOLANA_RPC") {
-        config.solana_rpc = rpc;
+    if let Some(rpc) = inputs.env.get("X402_DEV_SOLANA_RPC") {
+        config.solana_rpc = rpc.clone();
         solana_rpc_source = "environment (X402_DEV_SOLANA_RPC)".to_string();
     }
-    if let Ok(level) = env::var("X402_DEV_LOG_LEVEL") {
+    if let Some(level) = inputs.env.get("X402_DEV_LOG_LEVEL") {
         config.log_level = level.parse().map_err(|e: String| anyhow::anyhow!(e))?;
         log_level_source = "environment (X402_DEV_LOG_LEVEL)".to_string();
     }
@@ -542,6 +1303,9 @@ pub fn load_merged_config_with_sources(
             config.pricing.default = pricing;
             pricing_source = "CLI flag (--pricing)".to_string();
         }
+        if let Some(ref recipient) = cli.recipient {
+            config.recipient = Some(recipient.clone());
+        }
     }
 
     // Validate
@@ -553,6 +1317,7 @@ pub fn load_merged_config_with_sources(
         solana_rpc_source,
         log_level_source,
         pricing_source,
+        project_file: inputs.project_file.clone(),
     })
 }
 
@@ -566,6 +1331,7 @@ mod tests {
         let config = PricingConfig {
             default: 0.01,
             per_resource: HashMap::new(),
+            ..PricingConfig::default()
         };
         assert!(config.validate().is_ok());
 
@@ -573,6 +1339,7 @@ mod tests {
         let config = PricingConfig {
             default: -0.01,
             per_resource: HashMap::new(),
+            ..PricingConfig::default()
         };
         assert!(config.validate().is_err());
 
@@ -580,6 +1347,7 @@ mod tests {
         let config = PricingConfig {
             default: 101.0,
             per_resource: HashMap::new(),
+            ..PricingConfig::default()
         };
         assert!(config.validate().is_err());
 
@@ -589,10 +1357,101 @@ mod tests {
         let config = PricingConfig {
             default: 0.01,
             per_resource,
+            ..PricingConfig::default()
         };
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_pricing_bounds_validation() {
+        let bounded = |default, min, max| PricingConfig {
+            default,
+            min_amount: min,
+            max_amount: max,
+            ..PricingConfig::default()
+        };
+
+        assert!(bounded(0.01, Some(0.001), Some(1.0)).validate().is_ok());
+        assert!(bounded(0.01, Some(0.01), None).validate().is_ok());
+
+        // Inverted bounds
+        let err = bounded(0.01, Some(1.0), Some(0.001))
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CatalogError>().unwrap().code(),
+            "config.price_bounds_inverted"
+        );
+        assert!(err.to_string().contains("min_amount 1 > max_amount 0.001"));
+
+        // Default outside the bounds
+        let err = bounded(0.0001, Some(0.001), None).validate().unwrap_err();
+        assert!(err.to_string().contains("below min_amount 0.001"));
+        let err = bounded(5.0, None, Some(1.0)).validate().unwrap_err();
+        assert!(err.to_string().contains("above max_amount 1"));
+
+        // Negative bound
+        assert!(bounded(0.01, Some(-0.5), None).validate().is_err());
+    }
+
+    #[test]
+    fn test_methods_validation() {
+        let config: Config =
+            serde_yaml::from_str("methods:\n  /api/data: [GET, POST]\n  /api/admin/*: [DELETE]\n")
+                .unwrap();
+        assert_eq!(config.methods["/api/data"], vec!["GET", "POST"]);
+        assert!(config.validate().is_ok());
+
+        for methods in [vec![], vec!["get".to_string()], vec!["FETCH".to_string()]] {
+            let config = Config {
+                methods: HashMap::from([("/api/data".to_string(), methods)]),
+                ..Config::default()
+            };
+            let err = config.validate().unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<CatalogError>().unwrap().code(),
+                "config.invalid_methods"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pricing_bounds_yaml() {
+        let yaml = r#"
+pricing:
+  default: 0.01
+  min_amount: "0.001"
+  max_amount: 2
+  out_of_bounds: error
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.pricing.min_amount, Some(0.001));
+        assert_eq!(config.pricing.max_amount, Some(2.0));
+        assert_eq!(config.pricing.out_of_bounds, OutOfBounds::Error);
+
+        // Unset bounds are omitted and default to clamp
+        let config: Config = serde_yaml::from_str("port: 8402\n").unwrap();
+        assert_eq!(config.pricing.out_of_bounds, OutOfBounds::Clamp);
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert!(!yaml.contains("min_amount"));
+    }
+
+    #[test]
+    fn test_policy_state_yaml() {
+        let config: Config =
+            serde_yaml::from_str("state_backend: sqlite\nstate_path: /tmp/state.db\n").unwrap();
+        let state = config.policy_state();
+        assert_eq!(state.backend, StateBackend::Sqlite);
+        assert_eq!(state.sqlite_path(), PathBuf::from("/tmp/state.db"));
+        assert_eq!(state.to_string(), "sqlite (/tmp/state.db)");
+
+        // Memory is the default and is left out when saving
+        let config: Config = serde_yaml::from_str("port: 8402\n").unwrap();
+        assert_eq!(config.policy_state(), StateStoreConfig::default());
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        assert!(!yaml.contains("state_backend"));
+    }
+
     #[test]
     fn test_pricing_matcher_exact_match() {
         let mut per_resource = HashMap::new();
@@ -602,6 +1461,7 @@ mod tests {
         let config = PricingConfig {
             default: 0.01,
             per_resource,
+            ..PricingConfig::default()
         };
         let matcher = PricingMatcher::new(config);
 
@@ -622,6 +1482,7 @@ mod tests {
         let config = PricingConfig {
             default: 0.01,
             per_resource,
+            ..PricingConfig::default()
         };
         let matcher = PricingMatcher::new(config);
 
@@ -644,6 +1505,7 @@ mod tests {
         let config = PricingConfig {
             default: 0.01,
             per_resource,
+            ..PricingConfig::default()
         };
         let matcher = PricingMatcher::new(config);
 
@@ -678,6 +1540,7 @@ mod tests {
         let config = PricingConfig {
             default: 0.01,
             per_resource,
+            ..PricingConfig::default()
         };
         let matcher = PricingMatcher::new(config);
 
@@ -841,7 +1704,48 @@ mod tests {
             log_level: LogLevel::Debug,
             pricing: PricingConfig::default(),
             simulation_mode: SimulationMode::Success,
-            timeout_delay_ms: 5000,
+            timeout_delay_ms: DurationField::from_millis(5000),
+            tls: None,
+            http2: false,
+            webhooks: vec![],
+            signing: None,
+            policies_dir: None,
+            policy_files: vec![],
+            invoice_ttl_seconds: default_invoice_ttl_seconds(),
+            invoice_ttl_per_resource: HashMap::new(),
+            methods: HashMap::new(),
+            recipient: None,
+            recipient_routing: HashMap::new(),
+            recipient_routing_seed: None,
+            maintenance: vec![],
+            responses: HashMap::new(),
+            memo_prefix: None,
+            id_format: IdFormat::default(),
+            id_seed: None,
+            protocol_id: None,
+            resolve: vec![],
+            state_backend: StateBackend::default(),
+            state_path: None,
+            audit_log: None,
+            audit_sample_rate: None,
+            log_rotation: None,
+            log_redaction: vec![],
+            allow_replay: false,
+            max_invoices: None,
+            idle_timeout_secs: None,
+            max_lifetime_secs: None,
+            startup_hooks: vec![],
+            ready_timeout_secs: None,
+            bind_address: None,
+            admin_loopback_only: None,
+            log_format: None,
+            zero_price_behavior: None,
+            rpc: None,
+            verifier: VerifierKind::default(),
+            amount_tolerance: AmountTolerance::default(),
+            quota_headers: QuotaHeaders::default(),
+            lint: LintConfig::default(),
+            display: AmountFormatter::default(),
         };
 
         assert_eq!(config.log_level, LogLevel::Debug);
@@ -856,7 +1760,48 @@ mod tests {
             log_level: LogLevel::Info,
             pricing: PricingConfig::default(),
             simulation_mode: SimulationMode::Success,
-            timeout_delay_ms: 5000,
+            timeout_delay_ms: DurationField::from_millis(5000),
+            tls: None,
+            http2: false,
+            webhooks: vec![],
+            signing: None,
+            policies_dir: None,
+            policy_files: vec![],
+            invoice_ttl_seconds: default_invoice_ttl_seconds(),
+            invoice_ttl_per_resource: HashMap::new(),
+            methods: HashMap::new(),
+            recipient: None,
+            recipient_routing: HashMap::new(),
+            recipient_routing_seed: None,
+            maintenance: vec![],
+            responses: HashMap::new(),
+            memo_prefix: None,
+            id_format: IdFormat::default(),
+            id_seed: None,
+            protocol_id: None,
+            resolve: vec![],
+            state_backend: StateBackend::default(),
+            state_path: None,
+            audit_log: None,
+            audit_sample_rate: None,
+            log_rotation: None,
+            log_redaction: vec![],
+            allow_replay: false,
+            max_invoices: None,
+            idle_timeout_secs: None,
+            max_lifetime_secs: None,
+            startup_hooks: vec![],
+            ready_timeout_secs: None,
+            bind_address: None,
+            admin_loopback_only: None,
+            log_format: None,
+            zero_price_behavior: None,
+            rpc: None,
+            verifier: VerifierKind::default(),
+            amount_tolerance: AmountTolerance::default(),
+            quota_headers: QuotaHeaders::default(),
+            lint: LintConfig::default(),
+            display: AmountFormatter::default(),
         };
 
         let yaml = serde_yaml::to_string(&config).unwrap();
@@ -877,4 +1822,363 @@ timeout_delay_ms: 5000
         assert_eq!(config.log_level, LogLevel::Debug);
         assert_eq!(config.simulation_mode, SimulationMode::Failure);
     }
+
+    #[test]
+    fn test_durations_with_units() {
+        let yaml = r#"
+timeout_delay_ms: 2s
+invoice_ttl_seconds: 5m
+invoice_ttl_per_resource:
+  /api/slow/*: 90
+idle_timeout_secs: 10m
+max_lifetime_secs: 3600
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.timeout_delay_ms.as_millis(), 2000);
+        assert_eq!(config.invoice_ttl_seconds.as_secs(), 300);
+        assert_eq!(config.invoice_ttl_per_resource["/api/slow/*"].as_secs(), 90);
+        assert_eq!(config.idle_timeout_secs.unwrap().as_secs(), 600);
+        assert_eq!(config.max_lifetime_secs.unwrap().as_secs(), 3600);
+        config.validate().unwrap();
+
+        // Written back in the friendliest unit
+        let yaml = serde_yaml::to_string(&config).unwrap();
+        for line in [
+            "timeout_delay_ms: 2s",
+            "invoice_ttl_seconds: 5m",
+            "/api/slow/*: 90s",
+            "idle_timeout_secs: 10m",
+            "max_lifetime_secs: 1h",
+        ] {
+            assert!(yaml.contains(line), "{}: {}", line, yaml);
+        }
+
+        let err = serde_yaml::from_str::<Config>("timeout_delay_ms: soon\n").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("number of milliseconds (e.g. 5000)"),
+            "{}",
+            err
+        );
+
+        let config: Config = serde_yaml::from_str("timeout_delay_ms: 2m\n").unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("120000 ms"), "{}", err);
+        assert!(err.contains("5000 or \"5s\""), "{}", err);
+    }
+
+    #[test]
+    fn test_audit_log_config() {
+        let yaml = r#"
+audit_log: .x402dev/audit.jsonl
+audit_sample_rate: 0.1
+log_rotation:
+  max_size: 1MiB
+  max_files: 3
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let audit = config.audit_log_config(None, None).unwrap();
+        assert_eq!(audit.path, PathBuf::from(".x402dev/audit.jsonl"));
+        assert_eq!(audit.sample_rate, 0.1);
+        assert_eq!(audit.rotation.max_size.bytes(), 1024 * 1024);
+        assert_eq!(audit.rotation.max_files, 3);
+
+        // Flags win over the file
+        let audit = config
+            .audit_log_config(Some(Path::new("other.jsonl")), Some(0.0))
+            .unwrap();
+        assert_eq!(audit.path, PathBuf::from("other.jsonl"));
+        assert_eq!(audit.sample_rate, 0.0);
+
+        assert!(Config::default().audit_log_config(None, None).is_none());
+    }
+
+    #[test]
+    fn test_pricing_yaml_string_amounts() {
+        let yaml = r#"
+pricing:
+  default: "1_000"
+  per_resource:
+    /api/data: 0.05
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.pricing.default, 1000.0);
+        assert_eq!(config.pricing.per_resource["/api/data"], 0.05);
+
+        let yaml = "pricing:\n  default: \"0,01\"\n";
+        let err = serde_yaml::from_str::<Config>(yaml).unwrap_err();
+        assert!(err.to_string().contains("did you mean 0.01?"));
+    }
+
+    #[test]
+    fn test_memo_prefix_checked_at_load() {
+        let config: Config = serde_yaml::from_str("memo_prefix: staging-\n").unwrap();
+        assert_eq!(config.memo_prefix.unwrap().as_str(), "staging-");
+
+        for (prefix, expected) in [
+            ("staging", "must end with '-'"),
+            ("stg_eu-", "letters, digits and '-'"),
+            ("-", "between 2 and 16 characters"),
+        ] {
+            let yaml = format!("memo_prefix: \"{}\"\n", prefix);
+            let err = serde_yaml::from_str::<Config>(&yaml).unwrap_err();
+            assert!(err.to_string().contains(expected), "{}: {}", prefix, err);
+        }
+    }
+
+    #[test]
+    fn test_id_format_checked_at_load() {
+        let config: Config = serde_yaml::from_str("id_format: ulid\nid_seed: 42\n").unwrap();
+        assert_eq!(config.id_format, IdFormat::Ulid);
+        assert!(config.validate().is_ok());
+
+        let err = serde_yaml::from_str::<Config>("id_format: uuid\n").unwrap_err();
+        assert!(err.to_string().contains("not one of"), "{}", err);
+
+        let config: Config = serde_yaml::from_str("id_format: uuidv7\nid_seed: 42\n").unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("id_seed"), "{}", err);
+    }
+
+    #[test]
+    fn test_protocol_id_checked_at_load() {
+        let config: Config = serde_yaml::from_str("protocol_id: x402-svm\n").unwrap();
+        assert_eq!(config.protocol_id.unwrap().as_str(), "x402-svm");
+
+        let err = serde_yaml::from_str::<Config>("protocol_id: \"x402 svm\"\n").unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid protocol identifier"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_rpc_behaviors_checked_at_load() {
+        let yaml = "rpc:\n  behaviors:\n    notfound-: not_found\n    short-: wrong_amount\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let rpc = config.rpc.unwrap();
+        assert_eq!(
+            rpc.behaviors["short-"],
+            x402_server::RpcBehavior::WrongAmount
+        );
+
+        let err = serde_yaml::from_str::<Config>("rpc:\n  behaviors:\n    x-: slow\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant"), "{}", err);
+    }
+
+    #[test]
+    fn test_bind_address_checked_at_load() {
+        let config: Config = serde_yaml::from_str("bind_address: \"::\"\n").unwrap();
+        assert_eq!(config.bind_address, Some("::".parse().unwrap()));
+
+        let err = serde_yaml::from_str::<Config>("bind_address: localhost\n").unwrap_err();
+        assert!(err.to_string().contains("invalid IP address"), "{}", err);
+    }
+
+    #[test]
+    fn test_log_format_checked_at_load() {
+        let config: Config = serde_yaml::from_str("log_format: json\n").unwrap();
+        assert_eq!(config.log_format, Some(LogFormat::Json));
+
+        let err = serde_yaml::from_str::<Config>("log_format: logfmt\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant"), "{}", err);
+        assert_eq!(LogLevel::Debug.to_tracing_level(), tracing::Level::DEBUG);
+    }
+
+    #[test]
+    fn test_zero_price_behavior_checked_at_load() {
+        let config: Config = serde_yaml::from_str("zero_price_behavior: invoice\n").unwrap();
+        assert_eq!(config.zero_price_behavior, Some(ZeroPriceBehavior::Invoice));
+        assert_eq!(Config::default().zero_price_behavior, None);
+
+        let err = serde_yaml::from_str::<Config>("zero_price_behavior: free\n").unwrap_err();
+        assert!(err.to_string().contains("unknown variant"), "{}", err);
+    }
+
+    #[test]
+    fn test_resolve_entries_checked_at_load() {
+        let yaml = "resolve:\n  - api.internal:8402:127.0.0.1\n  - api.v6:443:[::1]\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            config.resolve[0].socket_addr().to_string(),
+            "127.0.0.1:8402"
+        );
+        assert_eq!(config.resolve[1].to_string(), "api.v6:443:[::1]");
+
+        let err = serde_yaml::from_str::<Config>("resolve: [\"api.internal:8402\"]\n").unwrap_err();
+        assert!(
+            err.to_string().contains("expected HOST:PORT:ADDR"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_discover_project_config_nearest_wins() {
+        // Given: Config files at the repo root and in services/payments
+        let repo = tempfile::TempDir::new().unwrap();
+        let root = repo.path();
+        let payments = root.join("services/payments");
+        let start = payments.join("src/handlers");
+        fs::create_dir_all(&start).unwrap();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join(PROJECT_CONFIG_FILE), "port: 8402\n").unwrap();
+        fs::write(payments.join(PROJECT_CONFIG_FILE), "port: 9402\n").unwrap();
+
+        // Then: The file closest to the start directory is used
+        assert_eq!(
+            discover_project_config(&start, &PROJECT_CONFIG_FILES),
+            Some(payments.join(PROJECT_CONFIG_FILE))
+        );
+        assert_eq!(
+            discover_project_config(&root.join("services"), &PROJECT_CONFIG_FILES),
+            Some(root.join(PROJECT_CONFIG_FILE))
+        );
+        assert_eq!(
+            discover_project_config(&start, &["x402dev.staging.yaml"]),
+            None
+        );
+
+        // And: A nearer JSON config wins; YAML is preferred within one directory
+        fs::write(start.join(".x402dev.json"), "{\"port\": 7402}\n").unwrap();
+        assert_eq!(
+            discover_project_config(&start, &PROJECT_CONFIG_FILES),
+            Some(start.join(".x402dev.json"))
+        );
+        fs::write(start.join(PROJECT_CONFIG_FILE), "port: 6402\n").unwrap();
+        assert_eq!(
+            discover_project_config(&start, &PROJECT_CONFIG_FILES),
+            Some(start.join(PROJECT_CONFIG_FILE))
+        );
+    }
+
+    #[test]
+    fn test_discover_project_config_stops_at_git_root() {
+        // Given: A config file above the repository root
+        let outer = tempfile::TempDir::new().unwrap();
+        let repo = outer.path().join("repo");
+        let nested = repo.join("services");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(outer.path().join(PROJECT_CONFIG_FILE), "port: 8402\n").unwrap();
+
+        // Then: Outside a repository only the start directory is searched
+        assert_eq!(
+            discover_project_config(&nested, &PROJECT_CONFIG_FILES),
+            None
+        );
+
+        // And: Inside one the search ends at the git root
+        fs::create_dir(repo.join(".git")).unwrap();
+        assert_eq!(
+            discover_project_config(&nested, &PROJECT_CONFIG_FILES),
+            None
+        );
+        assert_eq!(
+            discover_project_config(outer.path(), &PROJECT_CONFIG_FILES),
+            Some(outer.path().join(PROJECT_CONFIG_FILE))
+        );
+    }
+
+    #[test]
+    fn test_injected_env_and_field_sources() {
+        // Given: A global and a project file, and two environments
+        let dir = tempfile::TempDir::new().unwrap();
+        let global = dir.path().join("global.yaml");
+        let project = dir.path().join("project.yaml");
+        fs::write(&global, "port: 4402\nsimulation_mode: failure\n").unwrap();
+        fs::write(&project, "port: 8402\ntimeout_delay_ms: 100\n").unwrap();
+        let inputs = |env: &[(&str, &str)]| ConfigInputs {
+            global_file: Some(global.clone()),
+            project_file: Some(project.clone()),
+            env: env
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        };
+        let local = inputs(&[]);
+        let ci = inputs(&[("X402_DEV_PORT", "9402"), ("X402_DEV_LOG_LEVEL", "debug")]);
+
+        // Then: Only the injected environment changes the result
+        let config = load_merged_config_from(&local, None).unwrap();
+        assert_eq!((config.port, config.log_level), (8402, LogLevel::Info));
+        let config = load_merged_config_from(&ci, None).unwrap();
+        assert_eq!((config.port, config.log_level), (9402, LogLevel::Debug));
+
+        // And: Each field is attributed to the layer that set it; the project
+        // file replaces the global one
+        let sources = config_field_sources(&local, None).unwrap();
+        let project_source = format!("project ({})", display_config_path(&project));
+        assert_eq!(sources["port"], project_source);
+        assert_eq!(sources["timeout_delay_ms"], project_source);
+        assert!(!sources.contains_key("simulation_mode"));
+
+        let cli = CliOverrides {
+            pricing: Some(0.05),
+            ..CliOverrides::default()
+        };
+        let sources = config_field_sources(&ci, Some(&cli)).unwrap();
+        assert_eq!(sources["port"], "environment (X402_DEV_PORT)");
+        assert_eq!(sources["log_level"], "environment (X402_DEV_LOG_LEVEL)");
+        assert_eq!(sources["pricing.default"], "CLI flag (--pricing)");
+
+        // And: Without a project file the global file applies
+        let global_only = ConfigInputs {
+            project_file: None,
+            ..inputs(&[])
+        };
+        let config = load_merged_config_from(&global_only, None).unwrap();
+        assert_eq!(config.simulation_mode, SimulationMode::Failure);
+        let sources = config_field_sources(&global_only, None).unwrap();
+        assert_eq!(sources["simulation_mode"], GLOBAL_SOURCE);
+    }
+
+    #[test]
+    fn test_env_expansion_and_sources() {
+        // Given: A project file reading its RPC URL and port from the environment
+        let dir = tempfile::TempDir::new().unwrap();
+        let project = dir.path().join("project.yaml");
+        fs::write(
+            &project,
+            "port: ${MOCK_PORT:-8402}\nsolana_rpc: ${STAGING_RPC}\n# log_level: ${UNSET}\ntimeout_delay_ms: 100\n",
+        )
+        .unwrap();
+        let inputs = |env: &[(&str, &str)]| ConfigInputs {
+            global_file: None,
+            project_file: Some(project.clone()),
+            env: env
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        };
+        let ci = inputs(&[("STAGING_RPC", "https://staging.example.com")]);
+
+        // Then: Values are expanded, with defaults for unset variables
+        let config = load_merged_config_from(&ci, None).unwrap();
+        assert_eq!(config.port, 8402);
+        assert_eq!(config.solana_rpc, "https://staging.example.com");
+
+        // And: Sources name the variables each field expanded
+        let project_source = display_config_path(&project);
+        let with_sources = load_merged_config_with_sources_from(&ci, None).unwrap();
+        assert_eq!(
+            with_sources.solana_rpc_source,
+            format!("project ({}, expanded $STAGING_RPC)", project_source)
+        );
+        let sources = config_field_sources(&ci, None).unwrap();
+        assert_eq!(
+            sources["port"],
+            format!("project ({}, expanded $MOCK_PORT)", project_source)
+        );
+        assert_eq!(
+            sources["timeout_delay_ms"],
+            format!("project ({})", project_source)
+        );
+
+        // And: An undefined variable without a default names the file
+        let err = load_merged_config_from(&inputs(&[]), None).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Environment variable STAGING_RPC is not set (solana_rpc in"));
+    }
 }
@@ -0,0 +1,221 @@
+// Environment variable expansion in config files
+//
+// `${VAR}` and `${VAR:-default}` are replaced in the YAML text before it is
+// parsed, so any value (ports included) can come from the environment.
+// Expansion is strict: an unset variable without a default is an error
+// rather than an empty value. Single-quoted scalars and comments are copied
+// as written, so `'$HOME'`-style literals keep their dollar signs, and
+// `${secret:name}` references are left for secret interpolation.
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use x402_core::i18n::{CatalogError, Message};
+use x402_core::secrets::SECRET_PREFIX;
+
+/// Config text after expansion, with the variables each top-level field used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expanded {
+    pub text: String,
+    /// Variable names by top-level field, in order of appearance
+    pub fields: BTreeMap<String, Vec<String>>,
+}
+
+/// Expand `${VAR}` and `${VAR:-default}` in the YAML `text` of `file`
+///
+/// `lookup` returns a variable's value; set but empty variables take the
+/// default, like the shell's `:-`.
+pub fn expand_env_vars(
+    text: &str,
+    file: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Expanded> {
+    let mut out = String::with_capacity(text.len());
+    let mut fields: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut field = String::new();
+    let mut quote: Option<char> = None;
+
+    for line in text.split_inclusive('\n') {
+        if quote.is_none() {
+            if let Some(key) = top_level_key(line) {
+                field = key;
+            }
+        }
+
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            let c = rest.chars().next().unwrap_or_default();
+            match quote {
+                Some('\'') => {
+                    if rest.starts_with("''") {
+                        out.push_str("''");
+                        i += 2;
+                        continue;
+                    }
+                    if c == '\'' {
+                        quote = None;
+                    }
+                }
+                Some(_) if c == '\\' => {
+                    // Keep escapes (including `\"`) together
+                    let len = rest.chars().take(2).map(char::len_utf8).sum();
+                    out.push_str(&rest[..len]);
+                    i += len;
+                    continue;
+                }
+                Some(_) if c == '"' => quote = None,
+                Some(_) => {}
+                None if c == '#' && (i == 0 || line[..i].ends_with(char::is_whitespace)) => {
+                    out.push_str(rest);
+                    break;
+                }
+                None if matches!(c, '\'' | '"') && starts_scalar(&line[..i]) => quote = Some(c),
+                None => {}
+            }
+
+            if quote != Some('\'') && rest.starts_with("${") {
+                let Some(len) = rest.find('}') else {
+                    return Err(invalid_reference(rest.trim_end(), file));
+                };
+                let reference = &rest[..=len];
+                let inner = &rest[2..len];
+                if inner.starts_with(SECRET_PREFIX) {
+                    out.push_str(reference);
+                } else {
+                    let (name, default) = match inner.split_once(":-") {
+                        Some((name, default)) => (name, Some(default)),
+                        None => (inner, None),
+                    };
+                    if !is_var_name(name) {
+                        return Err(invalid_reference(reference, file));
+                    }
+                    let value = match (lookup(name), default) {
+                        (Some(value), Some(default)) if value.is_empty() => default.to_string(),
+                        (Some(value), _) => value,
+                        (None, Some(default)) => default.to_string(),
+                        (None, None) => {
+                            return Err(CatalogError::new(
+                                Message::new("config.env_undefined")
+                                    .arg("var", name)
+                                    .arg("field", &field)
+                                    .arg("path", file)
+                                    .arg("example", format!("${{{}:-value}}", name)),
+                            )
+                            .into())
+                        }
+                    };
+                    out.push_str(&value);
+                    let vars = fields.entry(field.clone()).or_default();
+                    if !vars.iter().any(|var| var == name) {
+                        vars.push(name.to_string());
+                    }
+                }
+                i += reference.len();
+                continue;
+            }
+
+            out.push(c);
+            i += c.len_utf8();
+        }
+    }
+
+    Ok(Expanded { text: out, fields })
+}
+
+/// Key of a `key: value` line at the top level of the document
+fn top_level_key(line: &str) -> Option<String> {
+    if line.starts_with(|c: char| c.is_whitespace() || matches!(c, '#' | '-')) {
+        return None;
+    }
+    let (key, _) = line.split_once(':')?;
+    Some(key.trim().trim_matches(['\'', '"']).to_string())
+}
+
+/// True if a quote after `before` opens a quoted scalar rather than being
+/// part of a plain one (e.g. `key: 'x'` but not `key: it's`)
+fn starts_scalar(before: &str) -> bool {
+    before.is_empty()
+        || before.ends_with(|c: char| c.is_whitespace() || matches!(c, '[' | '{' | ','))
+}
+
+fn is_var_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn invalid_reference(reference: &str, file: &str) -> anyhow::Error {
+    CatalogError::new(
+        Message::new("config.env_invalid_reference")
+            .arg("reference", reference)
+            .arg("path", file),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(text: &str) -> Result<Expanded> {
+        expand_env_vars(text, ".x402dev.yaml", |name| match name {
+            "STAGING_RPC" => Some("https://staging.example.com".to_string()),
+            "PORT" => Some("9402".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_expands_values_and_defaults() {
+        let expanded = expand(
+            "port: ${PORT}\nsolana_rpc: \"${STAGING_RPC}/rpc\"\nlog_level: ${LEVEL:-debug}\nsimulation_mode: ${EMPTY:-success}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            expanded.text,
+            "port: 9402\nsolana_rpc: \"https://staging.example.com/rpc\"\nlog_level: debug\nsimulation_mode: success\n"
+        );
+        assert_eq!(expanded.fields["port"], ["PORT"]);
+        assert_eq!(expanded.fields["solana_rpc"], ["STAGING_RPC"]);
+        assert_eq!(expanded.fields["log_level"], ["LEVEL"]);
+
+        // Nested values are attributed to their top-level field
+        let expanded = expand("pricing:\n  default: ${PRICE:-0.02}\n").unwrap();
+        assert_eq!(expanded.text, "pricing:\n  default: 0.02\n");
+        assert_eq!(expanded.fields["pricing"], ["PRICE"]);
+    }
+
+    #[test]
+    fn test_undefined_variable_is_an_error() {
+        let err = expand("port: 8402\nsolana_rpc: ${MISSING_RPC}\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Environment variable MISSING_RPC is not set (solana_rpc in .x402dev.yaml)\nFix: Export MISSING_RPC, or give it a default: ${MISSING_RPC:-value}"
+        );
+
+        let err = expand("port: ${1PORT}\n").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Invalid environment variable reference ${1PORT}"));
+        assert!(expand("port: ${PORT\n").is_err());
+    }
+
+    #[test]
+    fn test_single_quotes_comments_and_secrets_are_literal() {
+        let text = "memo_prefix: '${PORT}'\n# port: ${MISSING}\nsolana_rpc: \"${secret:rpc}\"\nnote: it's ${PORT}\nlist: ['a''${PORT}', \"${PORT}\"]\n";
+        let expanded = expand(text).unwrap();
+        assert_eq!(
+            expanded.text,
+            "memo_prefix: '${PORT}'\n# port: ${MISSING}\nsolana_rpc: \"${secret:rpc}\"\nnote: it's 9402\nlist: ['a''${PORT}', \"9402\"]\n"
+        );
+        assert!(!expanded.fields.contains_key("memo_prefix"));
+        assert!(!expanded.fields.contains_key("solana_rpc"));
+
+        // A multi-line single-quoted value stays literal to its closing quote
+        let expanded = expand("memo_prefix: 'a\n  ${MISSING}'\nport: ${PORT}\n").unwrap();
+        assert_eq!(
+            expanded.text,
+            "memo_prefix: 'a\n  ${MISSING}'\nport: 9402\n"
+        );
+    }
+}
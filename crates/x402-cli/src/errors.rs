@@ -1,5 +1,8 @@
+use crate::output::Output;
 use colored::Colorize;
 use std::fmt;
+use x402_core::explain::{self, DOCS_BASE_URL};
+use x402_core::i18n::{CatalogError, Message};
 
 /// Exit codes following POSIX conventions
 /// Reserved for future use when explicit success exit codes are needed
@@ -20,9 +23,7 @@ pub enum CliError {
         suggestion: Option<String>,
         code: &'static str,
     },
-    /// Network-related errors (exit code 3)
-    /// Reserved for Epic 2: Solana RPC connection errors
-    #[allow(dead_code)]
+    /// Network-related errors (exit code 3), e.g. a host that does not resolve
     Network {
         message: String,
         suggestion: Option<String>,
@@ -55,18 +56,21 @@ impl CliError {
         }
     }
 
-    /// Get documentation link if available
-    pub fn docs_link(&self) -> Option<String> {
+    /// Stable error code, if this error has one
+    pub fn code(&self) -> Option<&'static str> {
         match self {
             CliError::Config { code, .. }
             | CliError::Network { code, .. }
-            | CliError::Validation { code, .. } => {
-                Some(format!("https://docs.x402-dev.com/errors/{}", code))
-            }
+            | CliError::Validation { code, .. } => Some(code),
             _ => None,
         }
     }
 
+    /// Get documentation link if available
+    pub fn docs_link(&self) -> Option<String> {
+        self.code().map(|code| format!("{}{}", DOCS_BASE_URL, code))
+    }
+
     /// Helper: Create a config error
     /// Reserved for Epic 2: Server startup config validation
     #[allow(dead_code)]
@@ -105,8 +109,6 @@ impl CliError {
     }
 
     /// Helper: Create a network error with suggestion
-    /// Reserved for Epic 2: Solana RPC connection error handling with hints
-    #[allow(dead_code)]
     pub fn network_with_suggestion(
         message: impl Into<String>,
         suggestion: impl Into<String>,
@@ -217,52 +219,109 @@ impl std::error::Error for CliError {
 /// Convert anyhow::Error to CliError
 /// This allows backward compatibility with commands that return anyhow::Result
 pub fn convert_anyhow_to_cli_error(error: anyhow::Error) -> CliError {
+    // Commands that already classified the failure return a CliError
+    let error = match error.downcast::<CliError>() {
+        Ok(cli_error) => return cli_error,
+        Err(error) => error,
+    };
+
     // Try to downcast to known error types
     if let Some(io_err) = error.downcast_ref::<std::io::Error>() {
         return CliError::io("I/O operation failed", io_err.kind().into());
     }
 
+    // Catalog errors are rendered in the selected locale; the code stays the key
+    if let Some(err) = error.chain().find_map(|e| e.downcast_ref::<CatalogError>()) {
+        let message = err.message.localized();
+        let suggestion = err.fix.as_ref().map(Message::localized);
+        let code = err.code();
+        return if code.starts_with("config.") {
+            CliError::Config {
+                message,
+                suggestion,
+                code,
+            }
+        } else {
+            CliError::Validation {
+                message,
+                suggestion,
+                code,
+            }
+        };
+    }
+
     // Default to Other variant with error message
     CliError::other(error.to_string())
 }
 
 /// Print error with appropriate formatting and verbosity
-pub fn print_error(error: &CliError, verbose: bool, debug: bool) {
+pub fn print_error(error: &CliError, out: &Output, debug: bool) {
     // Print formatted error (uses Display trait with colors)
-    eprintln!("{}", error);
+    out.error(error);
 
     // Print docs link if available
     if let Some(link) = error.docs_link() {
-        eprintln!(
+        out.error(format!(
             "\n{} {}",
             "📖".cyan(),
-            format!("Documentation: {}", link).cyan()
-        );
+            Message::new("cli.documentation")
+                .arg("link", link)
+                .localized()
+                .cyan()
+        ));
+    }
+
+    // Point at `x402-dev explain` for codes it knows
+    if let Some(code) = error.code().filter(|code| explain::lookup(code).is_some()) {
+        out.error(format!(
+            "{} {}",
+            "💡".yellow(),
+            Message::new("cli.explain_hint")
+                .arg("code", code)
+                .localized()
+        ));
     }
 
     // Print verbose/debug info
     if debug {
-        eprintln!("\n{}", "Debug trace:".dimmed());
-        eprintln!("{:?}", error);
+        out.error(format!(
+            "\n{}",
+            Message::new("cli.debug_trace").localized().dimmed()
+        ));
+        out.error(format!("{:?}", error));
 
         // Print source error chain if available
         if let Some(source) = std::error::Error::source(error) {
-            eprintln!("\n{}", "Caused by:".dimmed());
-            eprintln!("  {}", source);
+            out.error(format!(
+                "\n{}",
+                Message::new("cli.caused_by").localized().dimmed()
+            ));
+            out.error(format!("  {}", source));
         }
-    } else if verbose {
-        eprintln!("\n{}", "Additional context:".dimmed());
+    } else if out.verbose {
+        out.error(format!(
+            "\n{}",
+            Message::new("cli.additional_context").localized().dimmed()
+        ));
 
         // Show error type and exit code in verbose mode
         let error_type = match error {
-            CliError::Config { .. } => "Configuration Error",
-            CliError::Network { .. } => "Network Error",
-            CliError::Validation { .. } => "Validation Error",
-            CliError::Io { .. } => "I/O Error",
-            CliError::Other { .. } => "General Error",
+            CliError::Config { .. } => "cli.error_type.config",
+            CliError::Network { .. } => "cli.error_type.network",
+            CliError::Validation { .. } => "cli.error_type.validation",
+            CliError::Io { .. } => "cli.error_type.io",
+            CliError::Other { .. } => "cli.error_type.other",
         };
 
-        eprintln!("  Error type: {}", error_type.dimmed());
-        eprintln!("  Exit code: {}", error.exit_code().to_string().dimmed());
+        out.error(format!(
+            "  {} {}",
+            Message::new("cli.error_type").localized(),
+            Message::new(error_type).localized().dimmed()
+        ));
+        out.error(format!(
+            "  {} {}",
+            Message::new("cli.exit_code").localized(),
+            error.exit_code().to_string().dimmed()
+        ));
     }
 }
@@ -1,42 +1,70 @@
 mod cli;
 mod commands;
 mod config;
+mod env_expand;
 mod errors;
+mod output;
+mod prompt;
+mod safe_write;
 
 use clap::Parser;
 use cli::{Cli, Commands};
-use commands::{check, config as config_cmd, doctor, examples, init, mock, policy, test, version};
+use commands::{
+    check, config as config_cmd, convert, curl, doctor, estimate, examples, explain, generate,
+    init, mock, policy, receipt, scenario, secret, test, verify, verify_report, version,
+};
 use errors::{convert_anyhow_to_cli_error, print_error};
+use output::Output;
+use x402_core::i18n;
 
 // ADR-002: Use multi-thread runtime (no V8 constraints in pure Rust)
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
 
+    let env_lang = std::env::var(i18n::LANG_ENV).ok();
+    i18n::set_locale(i18n::Locale::resolve(
+        cli.lang.as_deref(),
+        env_lang.as_deref(),
+    ));
+    i18n::set_debug(cli.debug);
+    // A config that fails to load is reported by the commands reading it
+    let amounts = config::load_merged_config(None)
+        .map(|config| config.display)
+        .unwrap_or_default();
+    let out = Output::new(cli.quiet, cli.verbose, cli.no_color, cli.ascii).with_amounts(amounts);
+    out.install();
+
     let result = match cli.command {
-        Commands::Mock(args) => mock::run(&args).await,
-        Commands::Test(args) => test::execute(&args).await,
-        Commands::Verify(_) => {
-            println!("Command 'verify' not yet implemented - coming in Epic 3");
-            Ok(())
-        }
-        Commands::Check(args) => check::run(&args).await,
+        Commands::Mock(args) => mock::run(&args, &out).await,
+        Commands::Test(args) => test::execute(&args, &out).await,
+        Commands::Estimate(args) => estimate::run(&args, &out).await,
+        Commands::Scenario(args) => scenario::run(&args, &out).await,
+        Commands::Verify(args) => verify::run(&args, &out).await,
+        Commands::Check(args) => check::run(&args, &out).await,
         Commands::Monitor(_) => {
             println!("Command 'monitor' not yet implemented - coming in Epic 5");
             Ok(())
         }
-        Commands::Policy(args) => policy::handle_policy_command(args),
+        Commands::Policy(args) => policy::handle_policy_command(args, &out).await,
         Commands::Examples(args) => examples::run(&args).await,
-        Commands::Doctor(args) => doctor::run(&args).await,
+        Commands::Generate(args) => generate::handle_generate_command(args),
+        Commands::Doctor(args) => doctor::run(&args, &out).await,
         Commands::Init(args) => init::run(&args).await,
         Commands::Version(args) => version::run(&args).await,
-        Commands::Config(args) => config_cmd::run(&args).await,
+        Commands::Config(args) => config_cmd::run(&args, &out).await,
+        Commands::Secret(args) => secret::run(&args),
+        Commands::Receipt(args) => receipt::run(&args, &out),
+        Commands::Curl(args) => curl::run(&args).await,
+        Commands::Explain(args) => explain::run(&args),
+        Commands::Convert(args) => convert::run(&args),
+        Commands::VerifyReport(args) => verify_report::run(&args),
     };
 
     // Handle errors with proper formatting and exit codes
     if let Err(e) = result {
         let cli_error = convert_anyhow_to_cli_error(e);
-        print_error(&cli_error, cli.verbose, cli.debug);
+        print_error(&cli_error, &out, cli.debug);
         std::process::exit(cli_error.exit_code());
     }
 }
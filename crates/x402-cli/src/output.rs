@@ -0,0 +1,170 @@
+// Terminal output shared by the commands (--quiet, --verbose, --no-color, --ascii)
+//
+// `Output` is built once in main from the global flags, `NO_COLOR` and
+// whether stdout is a terminal, and handed to the commands' rendering code.
+// Colors are switched for the whole process through `colored`, so
+// `.green()` and friends need no checks of their own. `status` lines are
+// progress chatter dropped under --quiet; `line` is the command's result
+// and `error` goes to stderr, neither of which --quiet touches. With
+// --ascii every symbol below is swapped for its ASCII marker. JSON output
+// is printed with plain `println!` and never goes through here, and neither
+// do its amounts: `amounts` (the config's `display:` section) only shapes
+// amounts shown to people.
+
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::str::FromStr;
+use x402_domain::{AmountFormatter, Decimal};
+
+/// Disables colors when set to a non-empty value (https://no-color.org)
+pub const NO_COLOR_ENV: &str = "NO_COLOR";
+
+/// Forces colors on a non-terminal stdout when set to anything but `0`
+pub const CLICOLOR_FORCE_ENV: &str = "CLICOLOR_FORCE";
+
+/// Symbols printed by the commands and their --ascii markers; emoji with a
+/// variation selector come before their bare form
+const ASCII_MARKERS: &[(&str, &str)] = &[
+    ("⚠️", "[!]"),
+    ("⚠", "[!]"),
+    ("✅", "[ok]"),
+    ("✓", "[ok]"),
+    ("❌", "[x]"),
+    ("✗", "[x]"),
+    ("💡", "[tip]"),
+    ("📖", "[docs]"),
+    ("🔍", "[?]"),
+    ("→", "->"),
+    ("─", "-"),
+    ("═", "="),
+];
+
+/// How the commands print
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Output {
+    /// Drop `status` lines
+    pub quiet: bool,
+    /// Explain errors further (type and exit code)
+    pub verbose: bool,
+    pub color: bool,
+    /// Print ASCII markers instead of emoji and symbols
+    pub ascii: bool,
+    /// How amounts are shown
+    pub amounts: AmountFormatter,
+}
+
+impl Output {
+    /// Output for the global flags, the environment and the real stdout
+    pub fn new(quiet: bool, verbose: bool, no_color: bool, ascii: bool) -> Self {
+        let env_set = |name| std::env::var(name).is_ok_and(|v| !v.is_empty() && v != "0");
+        Self::resolve(
+            quiet,
+            verbose,
+            no_color || std::env::var(NO_COLOR_ENV).is_ok_and(|v| !v.is_empty()),
+            ascii,
+            std::io::stdout().is_terminal() || env_set(CLICOLOR_FORCE_ENV),
+        )
+    }
+
+    /// Colors unless turned off, and only on a terminal (or when forced)
+    pub fn resolve(quiet: bool, verbose: bool, no_color: bool, ascii: bool, tty: bool) -> Self {
+        Output {
+            quiet,
+            verbose,
+            color: tty && !no_color,
+            ascii,
+            amounts: AmountFormatter::default(),
+        }
+    }
+
+    /// Show amounts with `amounts`
+    pub fn with_amounts(self, amounts: AmountFormatter) -> Self {
+        Output { amounts, ..self }
+    }
+
+    /// Apply the color choice to every `colored` string of the process
+    pub fn install(&self) {
+        colored::control::set_override(self.color);
+    }
+
+    /// `text` with --ascii markers in place of symbols
+    pub fn text<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !self.ascii || text.is_ascii() {
+            return Cow::Borrowed(text);
+        }
+        let mut text = text.to_string();
+        for (symbol, marker) in ASCII_MARKERS {
+            if text.contains(symbol) {
+                text = text.replace(symbol, marker);
+            }
+        }
+        Cow::Owned(text)
+    }
+
+    /// A decimal string with its currency in the display format; text that
+    /// is not a number is shown as is
+    pub fn amount(&self, amount: &str, currency: &str) -> String {
+        match Decimal::from_str(amount) {
+            Ok(value) => self.amounts.format_currency(value, currency),
+            Err(_) => format!("{} {}", amount, currency),
+        }
+    }
+
+    /// Result line, always printed
+    pub fn line(&self, text: impl Display) {
+        println!("{}", self.text(&text.to_string()));
+    }
+
+    /// Progress line, dropped under --quiet
+    pub fn status(&self, text: impl Display) {
+        if !self.quiet {
+            self.line(text);
+        }
+    }
+
+    /// Warning or error on stderr, always printed
+    pub fn error(&self, text: impl Display) {
+        eprintln!("{}", self.text(&text.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_needs_a_terminal() {
+        assert!(Output::resolve(false, false, false, false, true).color);
+        assert!(!Output::resolve(false, false, false, false, false).color);
+        // --no-color and NO_COLOR win over a terminal
+        assert!(!Output::resolve(false, false, true, false, true).color);
+    }
+
+    #[test]
+    fn test_ascii_markers_replace_symbols() {
+        let out = Output {
+            ascii: true,
+            ..Output::default()
+        };
+        assert_eq!(
+            out.text("  ⚠️  stale → ✅ fixed ✗"),
+            "  [!]  stale -> [ok] fixed [x]"
+        );
+        assert!(out.text("💡 try x402-dev explain").is_ascii());
+
+        let plain = Output::default();
+        assert_eq!(plain.text("✅ ok"), "✅ ok");
+    }
+
+    #[test]
+    fn test_amounts_use_display_format() {
+        let out = Output::default().with_amounts(AmountFormatter {
+            grouping: false,
+            ..AmountFormatter::default()
+        });
+        assert_eq!(out.amount("1234567.8900", "USDC"), "1234567.89 USDC");
+        assert_eq!(out.amount("-0.000001", "USDC"), "-0.000001 USDC");
+        assert_eq!(out.amount("n/a", "USDC"), "n/a USDC");
+    }
+}
@@ -0,0 +1,125 @@
+// Prompts of the interactive wizards (`init`, `policy init --interactive`)
+//
+// Wizards ask through the `Prompter` trait rather than calling dialoguer
+// directly, so tests can drive a whole session with a `Script` of answers
+// and read back everything the wizard said. Typing `<` at a text prompt is
+// the wizards' way of going back a step; `Prompter` itself has no notion
+// of steps.
+
+use anyhow::Result;
+use dialoguer::{Confirm, Input, Select};
+
+/// Answer to a text prompt that means "go back one step"
+pub const BACK: &str = "<";
+
+/// Source of answers for a wizard
+pub trait Prompter {
+    /// Free text; an empty answer takes `default` when one is given
+    fn input(&mut self, prompt: &str, default: Option<&str>) -> Result<String>;
+
+    /// Index of the chosen item
+    fn select(&mut self, prompt: &str, items: &[&str], default: usize) -> Result<usize>;
+
+    fn confirm(&mut self, prompt: &str, default: bool) -> Result<bool>;
+
+    /// Show `text` to the user
+    fn say(&mut self, text: &str);
+}
+
+/// Prompts on the terminal
+pub struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn input(&mut self, prompt: &str, default: Option<&str>) -> Result<String> {
+        let mut input = Input::<String>::new().with_prompt(prompt).allow_empty(true);
+        if let Some(default) = default.filter(|d| !d.is_empty()) {
+            input = input.default(default.to_string());
+        }
+        Ok(input.interact_text()?)
+    }
+
+    fn select(&mut self, prompt: &str, items: &[&str], default: usize) -> Result<usize> {
+        Ok(Select::new()
+            .with_prompt(prompt)
+            .items(items)
+            .default(default)
+            .interact()?)
+    }
+
+    fn confirm(&mut self, prompt: &str, default: bool) -> Result<bool> {
+        Ok(Confirm::new()
+            .with_prompt(prompt)
+            .default(default)
+            .interact()?)
+    }
+
+    fn say(&mut self, text: &str) {
+        println!("{}", text);
+    }
+}
+
+/// Prompter answering from a fixed list, for tests
+///
+/// Select answers name the item (or a prefix of it); confirm answers are
+/// `y` or `n`; an empty answer takes the default. Prompts and everything
+/// said are kept in `transcript`.
+#[cfg(test)]
+pub struct Script {
+    answers: std::collections::VecDeque<String>,
+    pub transcript: Vec<String>,
+}
+
+#[cfg(test)]
+impl Script {
+    pub fn new(answers: &[&str]) -> Self {
+        Self {
+            answers: answers.iter().map(|a| a.to_string()).collect(),
+            transcript: Vec::new(),
+        }
+    }
+
+    fn next(&mut self, prompt: &str) -> Result<String> {
+        self.transcript.push(format!("? {}", prompt));
+        self.answers
+            .pop_front()
+            .ok_or_else(|| anyhow::anyhow!("Script has no answer for '{}'", prompt))
+    }
+
+    /// Everything said and asked, one entry per line
+    pub fn output(&self) -> String {
+        self.transcript.join("\n")
+    }
+}
+
+#[cfg(test)]
+impl Prompter for Script {
+    fn input(&mut self, prompt: &str, default: Option<&str>) -> Result<String> {
+        let answer = self.next(prompt)?;
+        Ok(match (answer.is_empty(), default) {
+            (true, Some(default)) => default.to_string(),
+            _ => answer,
+        })
+    }
+
+    fn select(&mut self, prompt: &str, items: &[&str], default: usize) -> Result<usize> {
+        let answer = self.next(prompt)?;
+        if answer.is_empty() {
+            return Ok(default);
+        }
+        items
+            .iter()
+            .position(|item| item.starts_with(&answer))
+            .ok_or_else(|| anyhow::anyhow!("'{}' is not one of {:?}", answer, items))
+    }
+
+    fn confirm(&mut self, prompt: &str, default: bool) -> Result<bool> {
+        Ok(match self.next(prompt)?.as_str() {
+            "" => default,
+            answer => answer == "y",
+        })
+    }
+
+    fn say(&mut self, text: &str) {
+        self.transcript.extend(text.lines().map(str::to_string));
+    }
+}
@@ -0,0 +1,276 @@
+// Safe writes of user files (config, policy, suite and converted files)
+//
+// `write` never leaves a half-written file behind: the new contents go to a
+// temporary file in the same directory, are synced, and are renamed over
+// the original, whose previous version is kept as `<name>.bak`. An advisory
+// lock is held throughout, so of two x402-dev processes writing the same
+// file one fails fast instead of interleaving. The lock is taken on a
+// separate file, because the file itself is replaced by rename, kept in
+// `locks/` of the state directory rather than next to user files. A
+// symlink is written through: its target is replaced and the link stays.
+
+use anyhow::{bail, Context, Result};
+use fs2::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::NamedTempFile;
+use x402_core::testing::sha256_hex;
+use x402_server::state_dir;
+
+/// Longest chain of symlinks followed to the file written
+const MAX_SYMLINKS: usize = 40;
+
+/// Replace `path` with `contents`, keeping the previous version as
+/// `<name>.bak`
+pub fn write(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let target = resolve_symlinks(path)?;
+    let _lock = lock(&target)?;
+    let temp = write_temp(&target, contents.as_ref())?;
+    replace(&target, temp)
+}
+
+/// `path` with every symlink followed; the final target may not exist yet
+fn resolve_symlinks(path: &Path) -> Result<PathBuf> {
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_SYMLINKS {
+        match fs::symlink_metadata(&path) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                let link = fs::read_link(&path)
+                    .with_context(|| format!("Failed to read symlink: {}", path.display()))?;
+                // A relative link is relative to the directory holding it
+                path = match path.parent() {
+                    Some(parent) => parent.join(link),
+                    None => link,
+                };
+            }
+            _ => return Ok(path),
+        }
+    }
+    bail!(
+        "Too many levels of symlinks: {}\nFix: Point the link at a regular file",
+        path.display()
+    )
+}
+
+/// `<name>.<extension>` next to `path`
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.{}", name, extension))
+}
+
+/// Directory of `path`, `.` for a bare file name
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+/// `<name>-<hash>.lock` in `locks/` of the state directory, the hash being
+/// of the absolute path of `target`
+fn lock_path(target: &Path) -> Result<PathBuf> {
+    let dir = state_dir::resolve()?.path.join("locks");
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create lock directory: {}", dir.display()))?;
+    let name = target.file_name().unwrap_or_default();
+    let absolute = fs::canonicalize(parent_dir(target))
+        .with_context(|| format!("Failed to resolve {}", target.display()))?
+        .join(name);
+    let hash = sha256_hex(absolute.to_string_lossy().as_bytes());
+    Ok(dir.join(format!("{}-{}.lock", name.to_string_lossy(), &hash[..16])))
+}
+
+/// Take the write lock of `target`, failing if another process holds it
+///
+/// The OS releases the lock when the returned file is dropped or its
+/// holder exits, so a lock file left behind never blocks a later write.
+fn lock(target: &Path) -> Result<File> {
+    let lock_path = lock_path(target)?;
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open lock file: {}", lock_path.display()))?;
+    if let Err(e) = lock.try_lock_exclusive() {
+        if e.kind() == fs2::lock_contended_error().kind() {
+            bail!(
+                "{} is being written by another x402-dev process\nFix: Wait for it to finish, then run the command again",
+                target.display()
+            );
+        }
+        return Err(e).with_context(|| format!("Failed to lock {}", lock_path.display()));
+    }
+    Ok(lock)
+}
+
+/// `contents` in a synced temporary file next to `target`, with the mode
+/// of the file it replaces; dropping it removes it
+fn write_temp(target: &Path, contents: &[u8]) -> Result<NamedTempFile> {
+    let name = target.file_name().unwrap_or_default().to_string_lossy();
+    let mut temp = tempfile::Builder::new()
+        .prefix(&format!(".{}.", name))
+        .suffix(".tmp")
+        .tempfile_in(parent_dir(target))
+        .with_context(|| {
+            format!(
+                "Failed to create a temporary file next to {}",
+                target.display()
+            )
+        })?;
+    temp.write_all(contents)
+        .with_context(|| format!("Failed to write {}", temp.path().display()))?;
+    match fs::metadata(target) {
+        Ok(meta) => fs::set_permissions(temp.path(), meta.permissions())?,
+        Err(_) => set_new_file_permissions(temp.path())?,
+    }
+    temp.as_file()
+        .sync_all()
+        .with_context(|| format!("Failed to sync {}", temp.path().display()))?;
+    Ok(temp)
+}
+
+/// Temporary files are private; a new file gets the usual 0644
+#[cfg(unix)]
+fn set_new_file_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(fs::set_permissions(
+        path,
+        fs::Permissions::from_mode(0o644),
+    )?)
+}
+
+#[cfg(not(unix))]
+fn set_new_file_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Back up `target` and rename `temp` over it
+fn replace(target: &Path, temp: NamedTempFile) -> Result<()> {
+    if target.is_file() {
+        let backup = sibling(target, "bak");
+        fs::copy(target, &backup).with_context(|| {
+            format!(
+                "Failed to back up {} to {}",
+                target.display(),
+                backup.display()
+            )
+        })?;
+    }
+    temp.persist(target)
+        .map_err(|e| e.error)
+        .with_context(|| format!("Failed to replace {}", target.display()))?;
+    // Make the rename itself durable; not every platform can sync a directory
+    if let Ok(dir) = File::open(parent_dir(target)) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_keeps_one_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.yaml");
+
+        write(&path, "port: 1\n").unwrap();
+        assert!(!sibling(&path, "bak").exists());
+        assert!(!sibling(&path, "lock").exists());
+
+        write(&path, "port: 2\n").unwrap();
+        write(&path, "port: 3\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "port: 3\n");
+        assert_eq!(
+            fs::read_to_string(sibling(&path, "bak")).unwrap(),
+            "port: 2\n"
+        );
+    }
+
+    #[test]
+    fn test_interrupted_write_leaves_original_intact() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("policy.yaml");
+        write(&path, "policies: []\n").unwrap();
+
+        // The process dies after writing the temporary file, before the rename
+        let lock = lock(&path).unwrap();
+        let temp = write_temp(&path, b"policies: [trunc").unwrap();
+        drop(temp);
+        drop(lock);
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "policies: []\n");
+        let leftovers: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "{:?}", leftovers);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_through_symlink_keeps_link() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let real = dir.path().join("shared").join("config.yaml");
+        fs::create_dir(real.parent().unwrap()).unwrap();
+        fs::write(&real, "port: 1\n").unwrap();
+        fs::set_permissions(&real, fs::Permissions::from_mode(0o600)).unwrap();
+        let link = dir.path().join(".x402dev.yaml");
+        std::os::unix::fs::symlink("shared/config.yaml", &link).unwrap();
+
+        write(&link, "port: 2\n").unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "port: 2\n");
+        assert_eq!(
+            fs::read_to_string(sibling(&real, "bak")).unwrap(),
+            "port: 1\n"
+        );
+        let mode = fs::metadata(&real).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_concurrent_writer_fails_fast() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.yaml");
+        write(&path, "port: 1\n").unwrap();
+
+        // One thread holds the lock mid-write until told to finish
+        let (locked_tx, locked_rx) = mpsc::channel();
+        let (finish_tx, finish_rx) = mpsc::channel::<()>();
+        let holder = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let _lock = lock(&path).unwrap();
+                locked_tx.send(()).unwrap();
+                finish_rx.recv().unwrap();
+            })
+        };
+        locked_rx.recv().unwrap();
+
+        let writer = {
+            let path = path.clone();
+            std::thread::spawn(move || write(&path, "port: 2\n"))
+        };
+        let err = writer.join().unwrap().unwrap_err().to_string();
+        assert!(err.contains("another x402-dev process"), "{}", err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "port: 1\n");
+
+        finish_tx.send(()).unwrap();
+        holder.join().unwrap();
+        write(&path, "port: 2\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "port: 2\n");
+    }
+}
@@ -0,0 +1,138 @@
+// `mock audit query` tests
+//
+// Writes a fixture audit log (as `mock --audit-log` does) with decisions of
+// two agents spread over the last few hours, and filters it through the CLI.
+
+use chrono::{Duration as TimeDelta, SecondsFormat, Utc};
+use std::time::Duration;
+
+fn x402_dev(dir: &std::path::Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir)
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+/// One JSONL audit entry decided `minutes_ago`
+fn entry(minutes_ago: i64, agent: &str, decision: &str, policy: &str) -> String {
+    let timestamp =
+        (Utc::now() - TimeDelta::minutes(minutes_ago)).to_rfc3339_opts(SecondsFormat::Millis, true);
+    let reason = if decision == "deny" {
+        r#","reason":"Rate limit exceeded""#
+    } else {
+        ""
+    };
+    format!(
+        r#"{{"timestamp":"{}","agent":"{}","endpoint":"/api/data","amount":0.01,"decision":"{}","policy_id":"{}"{},"quota":{{}}}}"#,
+        timestamp, agent, decision, policy, reason
+    )
+}
+
+fn fixture(dir: &std::path::Path) {
+    let lines = [
+        entry(180, "agent-1", "deny", "rate"),
+        entry(50, "agent-1", "allow", "rate"),
+        entry(40, "agent-1", "deny", "rate"),
+        entry(30, "agent-2", "deny", "rate"),
+        entry(20, "agent-1", "deny", "default"),
+    ];
+    std::fs::write(dir.join("audit.jsonl"), lines.join("\n") + "\n").unwrap();
+}
+
+fn query(dir: &std::path::Path, filters: &[&str]) -> Vec<serde_json::Value> {
+    let mut args = vec!["mock", "audit", "query", "--file", "audit.jsonl", "--json"];
+    args.extend_from_slice(filters);
+    let assert = x402_dev(dir, &args).success();
+    String::from_utf8(assert.get_output().stdout.clone())
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_query_filters_agent_decision_and_since() {
+    // Given: Decisions of two agents over the last three hours
+    let dir = tempfile::tempdir().unwrap();
+    fixture(dir.path());
+
+    // When: Asking for agent-1's denials in the last hour
+    let entries = query(
+        dir.path(),
+        &["--agent", "agent-1", "--decision", "deny", "--since", "-1h"],
+    );
+
+    // Then: Only the two recent denials of agent-1 are printed, oldest first
+    let policies: Vec<&str> = entries
+        .iter()
+        .map(|e| e["policy_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(policies, ["rate", "default"]);
+    assert!(entries
+        .iter()
+        .all(|e| e["agent"] == "agent-1" && e["decision"] == "deny"));
+}
+
+#[test]
+fn test_query_without_filters_and_by_policy() {
+    // Given: The same fixture
+    let dir = tempfile::tempdir().unwrap();
+    fixture(dir.path());
+
+    // When/Then: No filters print every entry
+    assert_eq!(query(dir.path(), &[]).len(), 5);
+
+    // When/Then: A policy filter keeps that policy's entries only
+    let entries = query(dir.path(), &["--policy", "default"]);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["agent"], "agent-1");
+}
+
+#[test]
+fn test_query_table_output() {
+    // Given: The same fixture
+    let dir = tempfile::tempdir().unwrap();
+    fixture(dir.path());
+
+    // When: Querying agent-2 without --json
+    let assert = x402_dev(
+        dir.path(),
+        &[
+            "mock",
+            "audit",
+            "query",
+            "--file",
+            "audit.jsonl",
+            "--agent",
+            "agent-2",
+        ],
+    );
+
+    // Then: A table with the one entry and its reason is printed
+    let output = String::from_utf8(assert.success().get_output().stdout.clone()).unwrap();
+    assert!(output.starts_with("TIME"), "{}", output);
+    assert!(output.contains("Rate limit exceeded"), "{}", output);
+    assert!(output.ends_with("1 entries\n"), "{}", output);
+}
+
+#[test]
+fn test_query_rejects_invalid_since() {
+    let dir = tempfile::tempdir().unwrap();
+    fixture(dir.path());
+
+    x402_dev(
+        dir.path(),
+        &[
+            "mock",
+            "audit",
+            "query",
+            "--file",
+            "audit.jsonl",
+            "--since",
+            "yesterday",
+        ],
+    )
+    .failure()
+    .stderr(predicates::str::contains("Invalid --since"));
+}
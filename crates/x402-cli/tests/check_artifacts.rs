@@ -0,0 +1,124 @@
+// `check --badge` / `--summary` tests
+//
+// Runs the mock server in-process and writes the compliance artifacts of a
+// check against it.
+
+mod common;
+
+use common::{free_port, serve};
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_server::Config;
+
+/// Start a server for `config` and return the URL of a priced path
+async fn start(config: Config) -> String {
+    format!("{}/api/data", serve(config).await)
+}
+
+fn x402_dev(dir: &std::path::Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir)
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+#[actix_web::test]
+async fn test_badge_and_summary_of_passing_check() {
+    // Given: A compliant mock server
+    let url = start(Config::builder().port(free_port()).build().unwrap()).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    // When: Checking it with --badge and --summary
+    let args = [
+        "check",
+        &url,
+        "--badge",
+        "badge.svg",
+        "--summary",
+        "COMPLIANCE.md",
+    ];
+    x402_dev(dir.path(), &args)
+        .success()
+        .stdout(predicate::str::contains("Wrote: badge.svg"))
+        .stdout(predicate::str::contains("Wrote: COMPLIANCE.md"));
+
+    // Then: The badge shows the grade
+    let badge = std::fs::read_to_string(dir.path().join("badge.svg")).unwrap();
+    assert!(
+        badge.contains("<title>x402: compliant</title>"),
+        "{}",
+        badge
+    );
+
+    // And: The summary has a row per check
+    let summary = std::fs::read_to_string(dir.path().join("COMPLIANCE.md")).unwrap();
+    assert!(summary.contains("| Protocol Validation | HTTP 402 status code | ✅ pass | `402` |"));
+    assert!(summary.contains("| Invoice Structure | Currency | ✅ pass | `USDC` |"));
+    assert!(!summary.contains("❌"), "{}", summary);
+
+    // And: Rerunning gives the same badge
+    x402_dev(dir.path(), &args).success();
+    let rerun = std::fs::read_to_string(dir.path().join("badge.svg")).unwrap();
+    assert_eq!(badge, rerun);
+}
+
+#[actix_web::test]
+async fn test_summary_written_when_check_fails() {
+    // Given: A server whose memos do not have the expected prefix
+    let url = start(Config::builder().port(free_port()).build().unwrap()).await;
+    let dir = tempfile::tempdir().unwrap();
+
+    // When: The check fails
+    x402_dev(
+        dir.path(),
+        &[
+            "check",
+            &url,
+            "--expected-memo-prefix",
+            "perf-",
+            "--badge",
+            "badge.svg",
+            "--summary",
+            "COMPLIANCE.md",
+        ],
+    )
+    .failure();
+
+    // Then: Both files are still written with the failure
+    let badge = std::fs::read_to_string(dir.path().join("badge.svg")).unwrap();
+    assert!(badge.contains("fill=\"#dfb317\""), "{}", badge);
+    let summary = std::fs::read_to_string(dir.path().join("COMPLIANCE.md")).unwrap();
+    assert!(summary.contains("- **Grade:** partial"), "{}", summary);
+    assert!(summary.contains("| Invoice Structure | Memo | ❌ fail |"));
+}
+
+#[actix_web::test]
+async fn test_strict_check_pins_id_format() {
+    // Given: A server issuing ULID memos
+    let url = start(
+        Config::builder()
+            .port(free_port())
+            .id_format(x402_domain::IdFormat::Ulid)
+            .build()
+            .unwrap(),
+    )
+    .await;
+    let dir = tempfile::tempdir().unwrap();
+
+    // When/Then: A strict check pinned to ulid passes
+    x402_dev(
+        dir.path(),
+        &["check", &url, "--strict", "--id-format", "ulid"],
+    )
+    .success()
+    .stdout(predicate::str::contains("Memo format: req-<ulid>"));
+
+    // And: One pinned to uuidv7 fails on the memo
+    x402_dev(
+        dir.path(),
+        &["check", &url, "--strict", "--id-format", "uuidv7"],
+    )
+    .failure()
+    .stdout(predicate::str::contains("expected req-<uuidv7>, got req-"));
+}
@@ -0,0 +1,74 @@
+// `check --self-test` and `check --battery` tests
+//
+// The self-test serves the conformance fixtures in-process and must match
+// every expected verdict on the current implementation. `--battery` runs
+// the same fixtures against a server given by URL: the battery server
+// matches, the mock (which answers every path with a valid invoice) does
+// not.
+
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_core::testing::battery;
+use x402_server::{Config, MockServerConfig, TestServer};
+
+fn x402_dev(args: &[&str]) -> assert_cmd::assert::Assert {
+    let dir = tempfile::tempdir().unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .env("X402_DEV_NO_UPDATE_CHECK", "1")
+        .args(args)
+        .timeout(Duration::from_secs(120))
+        .assert()
+}
+
+#[test]
+fn test_self_test_passes() {
+    let all_match = format!("ALL {} FIXTURES MATCH", battery::fixtures().len());
+    x402_dev(&["check", "--self-test"])
+        .success()
+        .stdout(predicate::str::contains(all_match))
+        .stdout(predicate::str::contains("cacheable"))
+        .stdout(predicate::str::contains(
+            "failed as expected: Not cacheable",
+        ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_battery_against_a_replaying_server() {
+    // Given: A server replaying the fixtures
+    let server = TestServer::start_battery().await.unwrap();
+
+    // When/Then: Every verdict matches
+    x402_dev(&["check", "--battery", &format!("{}/", server.base_url())])
+        .success()
+        .stdout(predicate::str::contains("x402 Conformance Battery"));
+    server.stop().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_battery_reports_mismatches() {
+    // Given: The mock server, which sends a valid invoice on every path
+    let server = TestServer::start(MockServerConfig::new(Config::default()).unwrap())
+        .await
+        .unwrap();
+
+    // When/Then: Fixtures that should fail pass, and each is listed with a replay command
+    x402_dev(&["check", "--battery", server.base_url()])
+        .code(1)
+        .stdout(predicate::str::contains("FIXTURES DIFFER"))
+        .stdout(predicate::str::contains(
+            "'WWW-Authenticate header' passed (present) but should fail",
+        ))
+        .stdout(predicate::str::contains(format!(
+            "x402-dev check {}/battery/wrong_currency --protocol x402-solana",
+            server.base_url()
+        )));
+    server.stop().await.unwrap();
+}
+
+#[test]
+fn test_url_is_required_without_battery() {
+    x402_dev(&["check"])
+        .code(2)
+        .stderr(predicate::str::contains("<URL>"));
+}
@@ -0,0 +1,56 @@
+// `check` tests for endpoints under maintenance
+//
+// A 503 with Retry-After means "come back later", not a broken endpoint:
+// check warns, skips the compliance checks and exits 0. A 503 without
+// Retry-After is still a failure.
+
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_server::{Config, MaintenanceRule, MockServerConfig, TestServer};
+
+fn check(args: &[&str]) -> assert_cmd::assert::Assert {
+    let dir = tempfile::tempdir().unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .env("X402_DEV_NO_UPDATE_CHECK", "1")
+        .arg("check")
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_maintenance_is_a_warning() {
+    // Given: A server with /api/data in maintenance
+    let config = Config::builder()
+        .maintenance(
+            MaintenanceRule::new("/api/data")
+                .retry_after_secs(90)
+                .message("Reloading fixtures"),
+        )
+        .build()
+        .unwrap();
+    let server = TestServer::start(MockServerConfig::new(config).unwrap())
+        .await
+        .unwrap();
+    let url = format!("{}/api/data", server.base_url());
+
+    // When/Then: check warns and passes
+    check(&[&url])
+        .success()
+        .stdout(predicate::str::contains(
+            "Endpoint is under maintenance (503, Retry-After: 90): Reloading fixtures",
+        ))
+        .stdout(predicate::str::contains(
+            "SKIPPED (endpoint under maintenance)",
+        ));
+    check(&[&url, "--format", "json"])
+        .success()
+        .stdout(predicate::str::contains(r#""status": "maintenance""#));
+
+    // And: Other paths are checked as usual
+    check(&[&format!("{}/api/other", server.base_url())])
+        .success()
+        .stdout(predicate::str::contains("ALL CHECKS PASSED"));
+    server.stop().await.unwrap();
+}
@@ -0,0 +1,73 @@
+// `check --protocol` tests
+//
+// Runs a mock server issuing a forked auth scheme (x402-svm) in-process and
+// checks it through the CLI with and without the variant allowed.
+
+mod common;
+
+use common::{free_port, serve};
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_domain::ProtocolId;
+use x402_server::Config;
+
+/// Start an x402-svm server and return the URL of a priced path
+async fn start_variant() -> String {
+    let config = Config::builder()
+        .port(free_port())
+        .protocol_id(ProtocolId::new("x402-svm").unwrap())
+        .build()
+        .unwrap();
+    format!("{}/api/data", serve(config).await)
+}
+
+fn x402_dev(args: &[&str]) -> assert_cmd::assert::Assert {
+    let dir = tempfile::tempdir().unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+#[actix_web::test]
+async fn test_variant_rejected_by_default() {
+    let url = start_variant().await;
+
+    x402_dev(&["check", &url])
+        .failure()
+        .stdout(predicate::str::contains(
+            "Invalid protocol identifier 'x402-svm', expected 'x402-solana'",
+        ));
+}
+
+#[actix_web::test]
+async fn test_variant_accepted_when_allowed() {
+    let url = start_variant().await;
+
+    x402_dev(&["check", &url, "--protocol", "x402-svm"])
+        .success()
+        .stdout(predicate::str::contains("ALL CHECKS PASSED"));
+
+    // Either identifier passes leniently; strict mode expects the first
+    x402_dev(&[
+        "check",
+        &url,
+        "--protocol",
+        "x402-solana",
+        "--protocol",
+        "x402-svm",
+        "--strict",
+    ])
+    .failure()
+    .stdout(predicate::str::contains(
+        "Protocol identifier: got x402-svm, expected x402-solana",
+    ));
+}
+
+#[test]
+fn test_invalid_protocol_flag_is_rejected() {
+    x402_dev(&["check", "http://127.0.0.1:1/api", "--protocol", "x402 svm"])
+        .failure()
+        .stderr(predicate::str::contains("Invalid protocol identifier"));
+}
@@ -0,0 +1,79 @@
+// `check --quota-probes` tests
+//
+// Runs an enforcing mock server in-process and probes its remaining quota
+// headers through the CLI.
+
+mod common;
+
+use common::{free_port, serve};
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_core::policy::{PolicyAction, RateLimitConfig, RuntimePolicy, SpendingCapConfig};
+use x402_server::Config;
+
+/// Start a server for `config` and return the URL of a priced path
+async fn start(config: Config) -> String {
+    format!("{}/api/data", serve(config).await)
+}
+
+/// 5 requests a minute and 0.1 USDC an hour for every agent
+fn metered() -> RuntimePolicy {
+    RuntimePolicy {
+        id: "metered".to_string(),
+        description: String::new(),
+        priority: 10,
+        agent_patterns: vec![],
+        endpoint_patterns: vec![],
+        action: PolicyAction::Allow,
+        rate_limit: Some(RateLimitConfig {
+            max_requests: 5,
+            window: Duration::from_secs(60),
+        }),
+        spending_cap: Some(SpendingCapConfig {
+            max_amount: 0.1,
+            currency: "USDC".to_string(),
+            window: Some(Duration::from_secs(3600)),
+            window_type: Default::default(),
+            timezone: None,
+        }),
+        concurrency_limit: None,
+    }
+}
+
+fn x402_dev(args: &[&str]) -> assert_cmd::assert::Assert {
+    let dir = tempfile::tempdir().unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+#[actix_web::test]
+async fn test_quota_probes_pass_against_enforcing_server() {
+    let config = Config::builder()
+        .port(free_port())
+        .policies(vec![metered()])
+        .build()
+        .unwrap();
+    let url = start(config).await;
+
+    // Each probe is invoiced 0.01 and counted as one request
+    x402_dev(&["check", &url, "--quota-probes", "3"])
+        .success()
+        .stdout(predicate::str::contains("Policy Quota:"))
+        .stdout(predicate::str::contains(
+            "X-RateLimit-Remaining 4 → 2, X-Spend-Remaining 0.09 → 0.07 over 3 requests",
+        ));
+}
+
+#[actix_web::test]
+async fn test_quota_probes_fail_without_quota_headers() {
+    let url = start(Config::builder().port(free_port()).build().unwrap()).await;
+
+    x402_dev(&["check", &url, "--quota-probes", "2"])
+        .failure()
+        .stdout(predicate::str::contains(
+            "response 1 has no X-RateLimit-Remaining or X-Spend-Remaining header",
+        ));
+}
@@ -0,0 +1,38 @@
+// `check` tests for zero-amount invoices
+//
+// Runs a mock server that invoices a price-0 path (zero_price_behavior:
+// invoice) in-process and checks that the CLI flags the amount=0 invoice.
+
+mod common;
+
+use common::{free_port, serve};
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_server::{Config, ZeroPriceBehavior};
+
+/// Start a server invoicing `/api/free` for 0 and return its URL
+async fn start_zero_invoicing() -> String {
+    let config = Config::builder()
+        .port(free_port())
+        .resource_price("/api/free", 0.0)
+        .zero_price_behavior(ZeroPriceBehavior::Invoice)
+        .build()
+        .unwrap();
+    format!("{}/api/free", serve(config).await)
+}
+
+#[actix_web::test]
+async fn test_zero_amount_invoice_warned() {
+    let url = start_zero_invoicing().await;
+    let dir = tempfile::tempdir().unwrap();
+
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .args(["check", &url])
+        .timeout(Duration::from_secs(20))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("must be greater than zero"))
+        .stdout(predicate::str::contains("Invoice amount 0 is zero"))
+        .stdout(predicate::str::contains("zero_price_behavior: serve"));
+}
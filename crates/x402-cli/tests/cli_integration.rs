@@ -10,7 +10,7 @@ use tempfile::TempDir;
 
 /// Helper to create a test command
 fn cli() -> Command {
-    Command::cargo_bin("x402-dev").unwrap()
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
 }
 
 /// Test: x402-dev --version shows version
@@ -41,7 +41,7 @@ fn test_help_command() {
 #[test]
 fn test_mock_help() {
     cli()
-        .args(&["mock", "--help"])
+        .args(["mock", "--help"])
         .assert()
         .success()
         .stdout(predicate::str::contains("mock server"))
@@ -52,7 +52,7 @@ fn test_mock_help() {
 #[test]
 fn test_config_show() {
     cli()
-        .args(&["config", "show"])
+        .args(["config", "show"])
         .assert()
         .success()
         .stdout(predicate::str::contains("Configuration"))
@@ -64,12 +64,114 @@ fn test_config_show() {
 #[test]
 fn test_config_show_with_overrides() {
     cli()
-        .args(&["config", "show", "--port", "9999"])
+        .args(["config", "show", "--port", "9999"])
         .assert()
         .success()
         .stdout(predicate::str::contains("9999"));
 }
 
+/// Test: config show uses the nearest .x402dev.yaml up to the git root
+#[test]
+fn test_config_show_discovers_nearest_project_config() {
+    // Given: A monorepo with configs at the root and in services/payments
+    let repo = TempDir::new().unwrap();
+    let payments = repo.path().join("services/payments");
+    fs::create_dir_all(payments.join("src")).unwrap();
+    fs::create_dir(repo.path().join(".git")).unwrap();
+    fs::write(repo.path().join(".x402dev.yaml"), "port: 8500\n").unwrap();
+    fs::write(payments.join(".x402dev.yaml"), "port: 9500\n").unwrap();
+
+    // When/Then: Run from a subdirectory, the payments config wins
+    cli()
+        .current_dir(payments.join("src"))
+        .env("HOME", repo.path())
+        .env_remove("X402_DEV_CONFIG")
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("port: 9500 (source: project ("))
+        .stdout(predicate::str::contains(
+            "services/payments/.x402dev.yaml (discovered)",
+        ));
+}
+
+/// Test: X402_DEV_CONFIG wins over discovery and must name a file
+#[test]
+fn test_config_env_var_overrides_discovery() {
+    // Given: A discovered config and an explicit one elsewhere
+    let repo = TempDir::new().unwrap();
+    fs::create_dir(repo.path().join(".git")).unwrap();
+    fs::write(repo.path().join(".x402dev.yaml"), "port: 8500\n").unwrap();
+    let explicit = repo.path().join("x402dev.staging.yaml");
+    fs::write(&explicit, "port: 9600\n").unwrap();
+
+    // When/Then: The env var's file is used
+    cli()
+        .current_dir(repo.path())
+        .env("HOME", repo.path())
+        .env("X402_DEV_CONFIG", &explicit)
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("port: 9600"))
+        .stdout(predicate::str::contains("(from X402_DEV_CONFIG)"));
+
+    // And: A path that is not a file is an error rather than a fallback
+    cli()
+        .current_dir(repo.path())
+        .env("HOME", repo.path())
+        .env("X402_DEV_CONFIG", repo.path().join("missing.yaml"))
+        .args(["config", "show"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "missing.yaml, which is not a file",
+        ));
+}
+
+/// Test: ${VAR} references in the project config expand from the environment
+#[test]
+fn test_config_show_expands_env_vars() {
+    // Given: A project config reading its RPC URL from the environment
+    let repo = TempDir::new().unwrap();
+    fs::create_dir(repo.path().join(".git")).unwrap();
+    fs::write(
+        repo.path().join(".x402dev.yaml"),
+        "port: ${MOCK_PORT:-9700}\nsolana_rpc: ${STAGING_RPC}\n",
+    )
+    .unwrap();
+
+    // When/Then: The values and the variables they came from are shown
+    cli()
+        .current_dir(repo.path())
+        .env("HOME", repo.path())
+        .env("STAGING_RPC", "https://staging.example.com")
+        .env_remove("MOCK_PORT")
+        .env_remove("X402_DEV_CONFIG")
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "port: 9700 (source: project (.x402dev.yaml, expanded $MOCK_PORT))",
+        ))
+        .stdout(predicate::str::contains(
+            "solana_rpc: https://staging.example.com (source: project (.x402dev.yaml, expanded $STAGING_RPC))",
+        ));
+
+    // And: An unset variable without a default is an error
+    cli()
+        .current_dir(repo.path())
+        .env("HOME", repo.path())
+        .env_remove("STAGING_RPC")
+        .env_remove("X402_DEV_CONFIG")
+        .args(["config", "show"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Environment variable STAGING_RPC is not set (solana_rpc in .x402dev.yaml)",
+        ));
+}
+
 /// Test: x402-dev policy validate with valid YAML
 #[test]
 fn test_policy_validate_success() {
@@ -92,7 +194,7 @@ policies:
     // When: Running validate command
     // Then: Should succeed
     cli()
-        .args(&["policy", "validate", policy_path.to_str().unwrap()])
+        .args(["policy", "validate", policy_path.to_str().unwrap()])
         .assert()
         .success()
         .stdout(predicate::str::contains("valid"));
@@ -118,7 +220,7 @@ policies:
     // When: Running validate command
     // Then: Should fail with error
     cli()
-        .args(&["policy", "validate", policy_path.to_str().unwrap()])
+        .args(["policy", "validate", policy_path.to_str().unwrap()])
         .assert()
         .failure();
 }
@@ -149,7 +251,7 @@ policies:
     // When: Running validate command
     // Then: Should detect conflict
     cli()
-        .args(&["policy", "validate", policy_path.to_str().unwrap()])
+        .args(["policy", "validate", policy_path.to_str().unwrap()])
         .assert()
         .failure()
         .stdout(predicate::str::contains("CONFLICT").or(predicate::str::contains("ERROR")))
@@ -178,7 +280,7 @@ policies:
 
     // When: Generating Express middleware
     cli()
-        .args(&[
+        .args([
             "policy",
             "generate",
             policy_path.to_str().unwrap(),
@@ -219,7 +321,7 @@ policies:
 
     // When: Generating Fastify plugin
     cli()
-        .args(&[
+        .args([
             "policy",
             "generate",
             policy_path.to_str().unwrap(),
@@ -260,7 +362,7 @@ policies:
     // When: Generating without --output
     // Then: Should print to stdout
     cli()
-        .args(&[
+        .args([
             "policy",
             "generate",
             policy_path.to_str().unwrap(),
@@ -275,7 +377,7 @@ policies:
 /// Test: Invalid command shows helpful error
 #[test]
 fn test_invalid_command() {
-    cli().args(&["invalid-command"]).assert().failure().stderr(
+    cli().args(["invalid-command"]).assert().failure().stderr(
         predicate::str::contains("unrecognized subcommand").or(predicate::str::contains("error")),
     );
 }
@@ -284,7 +386,7 @@ fn test_invalid_command() {
 #[test]
 fn test_missing_required_args() {
     cli()
-        .args(&["policy", "validate"])
+        .args(["policy", "export-runtime"])
         .assert()
         .failure()
         .stderr(predicate::str::contains("required"));
@@ -304,7 +406,7 @@ fn test_version_subcommand() {
 #[test]
 fn test_policy_file_not_found() {
     cli()
-        .args(&["policy", "validate", "/nonexistent/policy.yaml"])
+        .args(["policy", "validate", "/nonexistent/policy.yaml"])
         .assert()
         .failure()
         .stderr(
@@ -332,7 +434,7 @@ policies:
     .unwrap();
 
     cli()
-        .args(&[
+        .args([
             "policy",
             "generate",
             policy_path.to_str().unwrap(),
@@ -373,13 +475,13 @@ policies:
 
     // When: First validating
     cli()
-        .args(&["policy", "validate", policy_path.to_str().unwrap()])
+        .args(["policy", "validate", policy_path.to_str().unwrap()])
         .assert()
         .success();
 
     // Then: Generating should also succeed
     cli()
-        .args(&[
+        .args([
             "policy",
             "generate",
             policy_path.to_str().unwrap(),
@@ -406,7 +508,7 @@ fn test_exit_codes() {
 
     // Failure case: non-zero exit code
     cli()
-        .args(&["policy", "validate", "/nonexistent.yaml"])
+        .args(["policy", "validate", "/nonexistent.yaml"])
         .assert()
         .code(predicate::ne(0));
 }
@@ -415,7 +517,7 @@ fn test_exit_codes() {
 #[test]
 fn test_verbose_flag() {
     cli()
-        .args(&["--verbose", "config", "show"])
+        .args(["--verbose", "config", "show"])
         .assert()
         .success();
     // Note: Actual verbose output would depend on implementation
@@ -424,9 +526,263 @@ fn test_verbose_flag() {
 /// Test: Debug flag provides debug information
 #[test]
 fn test_debug_flag() {
+    cli().args(["--debug", "config", "show"]).assert().success();
+    // Note: Debug output would depend on implementation
+}
+
+/// Test: --lang / X402_DEV_LANG localize config validation errors
+#[test]
+fn test_localized_port_range_error() {
     cli()
-        .args(&["--debug", "config", "show"])
+        .args(["config", "show", "--port", "80", "--lang", "es"])
         .assert()
-        .success();
-    // Note: Debug output would depend on implementation
+        .code(2)
+        .stderr(predicate::str::contains(
+            "Puerto inválido: 80. El puerto debe estar entre 1024 y 65535.",
+        ))
+        .stderr(predicate::str::contains("config.invalid_port"));
+
+    cli()
+        .env("X402_DEV_LANG", "es-MX")
+        .args(["config", "show", "--port", "80"])
+        .assert()
+        .stderr(predicate::str::contains("Puerto inválido: 80"));
+
+    // --lang wins over the environment; an unsupported one falls through to it
+    cli()
+        .env("X402_DEV_LANG", "es")
+        .args(["config", "show", "--port", "80", "--lang", "ja"])
+        .assert()
+        .stderr(predicate::str::contains("無効なポート: 80"));
+    cli()
+        .env("X402_DEV_LANG", "es")
+        .args(["config", "show", "--port", "80", "--lang", "fr"])
+        .assert()
+        .stderr(predicate::str::contains("Puerto inválido: 80"));
+    cli()
+        .args(["config", "show", "--port", "80", "--lang", "fr"])
+        .assert()
+        .stderr(predicate::str::contains("Invalid port: 80"));
+}
+
+const ALLOWLIST_POLICY: &str = r#"
+policies:
+  - type: allowlist
+    field: agent_id
+    values:
+      - "agent-test"
+"#;
+
+/// Workspace with two valid policies, one conflicting policy and noise
+/// that discovery must skip (non-YAML, .x402ignore'd drafts)
+fn policy_workspace() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    let policies = dir.path().join("x402-policies");
+    fs::create_dir_all(policies.join("drafts")).unwrap();
+    fs::write(policies.join("agents.yaml"), ALLOWLIST_POLICY).unwrap();
+    fs::write(
+        policies.join("limits.yml"),
+        "policies:\n  - type: rate_limit\n    max_requests: 100\n    window_seconds: 60\n",
+    )
+    .unwrap();
+    fs::write(
+        policies.join("conflict.yaml"),
+        r#"
+policies:
+  - type: allowlist
+    field: agent_id
+    values: ["agent-test"]
+  - type: denylist
+    field: agent_id
+    values: ["agent-test"]
+"#,
+    )
+    .unwrap();
+    fs::write(policies.join("README.md"), "# not a policy").unwrap();
+    fs::write(policies.join("drafts/wip.yaml"), "policies: [").unwrap();
+    fs::write(dir.path().join(".x402ignore"), "drafts/\n").unwrap();
+    dir
+}
+
+/// Test: x402-dev policy validate without a file validates the workspace
+#[test]
+fn test_policy_validate_workspace() {
+    // Given: Two valid and one invalid policy under ./x402-policies
+    let workspace = policy_workspace();
+
+    // When: Running validate with no path
+    let output = cli()
+        .current_dir(workspace.path())
+        .env("HOME", workspace.path())
+        .args(["policy", "validate"])
+        .output()
+        .unwrap();
+
+    // Then: One section per discovered file, and exit code 1
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let sections: Vec<&str> = stdout.lines().filter(|l| l.starts_with("── ")).collect();
+    assert_eq!(
+        sections,
+        vec![
+            "── x402-policies/agents.yaml ──",
+            "── x402-policies/conflict.yaml ──",
+            "── x402-policies/limits.yml ──",
+        ]
+    );
+    assert!(stdout.contains("Invalid"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Policy validation failed for 1 of 3 files"));
+}
+
+/// Test: policies_dir / policy_files in .x402dev.yaml drive discovery
+#[test]
+fn test_policy_list_configured_files() {
+    // Given: A project config pointing at a custom directory and one extra file
+    let workspace = TempDir::new().unwrap();
+    fs::create_dir(workspace.path().join("rules")).unwrap();
+    fs::write(workspace.path().join("rules/agents.yaml"), ALLOWLIST_POLICY).unwrap();
+    fs::write(workspace.path().join("extra.yaml"), ALLOWLIST_POLICY).unwrap();
+    fs::write(
+        workspace.path().join(".x402dev.yaml"),
+        "policies_dir: rules\npolicy_files:\n  - extra.yaml\n",
+    )
+    .unwrap();
+
+    // When/Then: list shows both files with their rule counts
+    cli()
+        .current_dir(workspace.path())
+        .env("HOME", workspace.path())
+        .args(["policy", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("extra.yaml"))
+        .stdout(predicate::str::contains("rules/agents.yaml"))
+        .stdout(predicate::str::contains("1 rules"))
+        .stdout(predicate::str::contains("2 policy files"));
+
+    // And: validate passes for the configured set
+    cli()
+        .current_dir(workspace.path())
+        .env("HOME", workspace.path())
+        .args(["policy", "validate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All 2 policy files are valid"));
+}
+
+/// Test: generate client embeds the configured pricing in a TypeScript client
+#[test]
+fn test_generate_client_typescript() {
+    // Given: A project config with a default and per-resource prices
+    let workspace = TempDir::new().unwrap();
+    fs::write(
+        workspace.path().join(".x402dev.yaml"),
+        "pricing:\n  default: 0.01\n  per_resource:\n    /api/premium: 0.05\n    /api/admin/*: 0.02\n",
+    )
+    .unwrap();
+
+    // When: The client is generated to a file
+    cli()
+        .current_dir(workspace.path())
+        .env("HOME", workspace.path())
+        .args([
+            "generate",
+            "client",
+            "--language",
+            "ts",
+            "--output",
+            "x402-client.ts",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Generated client: x402-client.ts"));
+
+    // Then: It has the typed invoice, the retry loop and the price map
+    let code = fs::read_to_string(workspace.path().join("x402-client.ts")).unwrap();
+    assert!(code.contains("export interface Invoice {"));
+    assert!(code.contains("response.status === 402 && attempt < maxRetries"));
+    assert!(code.contains(r#"export const DEFAULT_PRICE = "0.01";"#));
+    assert!(code.contains(r#""/api/premium": "0.05","#));
+    assert!(code.contains(r#""/api/admin/*": "0.02","#));
+
+    // And: Only TypeScript is supported
+    cli()
+        .current_dir(workspace.path())
+        .args(["generate", "client", "--language", "python"])
+        .assert()
+        .failure();
+}
+
+/// Test: policy generate --with-tests writes a runnable test suite
+#[test]
+fn test_policy_generate_with_tests() {
+    // Given: The comprehensive fixture policy
+    let temp_dir = TempDir::new().unwrap();
+    let policy = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tests/fixtures/policies/test_comprehensive.yaml"
+    );
+    let middleware = temp_dir.path().join("middleware.js");
+    let suite = temp_dir.path().join("policy-tests.yaml");
+
+    // When: Generating middleware together with its tests
+    cli()
+        .args([
+            "policy",
+            "generate",
+            policy,
+            "--framework",
+            "express",
+            "--output",
+            middleware.to_str().unwrap(),
+            "--with-tests",
+            suite.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Generated test suite"))
+        .stdout(predicate::str::contains("(4 tests)"));
+
+    // Then: The suite uses the testing schema with a base URL variable
+    let yaml = fs::read_to_string(&suite).unwrap();
+    assert!(yaml.contains("base_url: http://localhost:3000/"));
+    assert!(yaml.contains("url: ${base_url}"));
+    assert!(yaml.contains("X-Agent-Id: agent-gpt4"));
+    assert!(yaml.contains("repeat: 101"));
+
+    // And: x402-dev test accepts it (filtered so nothing is sent)
+    cli()
+        .args([
+            "test",
+            suite.to_str().unwrap(),
+            "--var",
+            "base_url=http://127.0.0.1:1",
+            "--skip-tag",
+            "policy",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found"));
+}
+
+/// Test: x402-dev policy serve fails fast on an invalid policy file
+#[test]
+fn test_policy_serve_rejects_invalid_policy_file() {
+    // Given: A policy file that doesn't parse
+    let temp = TempDir::new().unwrap();
+    fs::write(temp.path().join("policy.yaml"), "policies: [unterminated\n").unwrap();
+
+    // When: Serving it
+    cli()
+        .current_dir(temp.path())
+        .env("HOME", temp.path())
+        .args(["policy", "serve", "--policy", "policy.yaml", "--port", "0"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Starting").not())
+        .stderr(predicate::str::contains("Invalid policy file: policy.yaml"));
+
+    // Then: Nothing was started, so no PID file was claimed
+    assert!(!temp.path().join(".x402dev").exists());
 }
@@ -0,0 +1,67 @@
+// Helpers shared by the integration tests
+//
+// Each test file is its own crate and compiles this module with `mod
+// common;`, so helpers a file does not call are dead code there.
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::time::Duration;
+use tempfile::TempDir;
+use x402_server::server::start_http_server;
+use x402_server::{Config, MockServerConfig};
+
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// Start a mock server for `config` in-process and return its base URL once
+/// it accepts connections
+pub async fn serve(config: Config) -> String {
+    let port = config.port.get();
+    actix_web::rt::spawn(start_http_server(MockServerConfig::new(config).unwrap()));
+    for _ in 0..50 {
+        if tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_ok()
+        {
+            return format!("http://127.0.0.1:{}", port);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("server on port {} did not start", port);
+}
+
+/// A git repo that HOME points inside
+pub fn repo() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir(dir.path().join(".git")).unwrap();
+    dir
+}
+
+/// [`repo`] with `contents` written to `name`
+pub fn repo_with(name: &str, contents: &str) -> TempDir {
+    let dir = repo();
+    fs::write(dir.path().join(name), contents).unwrap();
+    dir
+}
+
+/// A git repo with a project config; HOME points inside it
+pub fn project(config: &str) -> TempDir {
+    repo_with(".x402dev.yaml", config)
+}
+
+/// `x402-dev` run in `dir` with HOME there, ignoring the caller's
+/// `X402_DEV_CONFIG` and CI environment
+pub fn cli(dir: &TempDir) -> assert_cmd::Command {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("x402-dev");
+    cmd.current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env_remove("X402_DEV_CONFIG")
+        .env_remove("GITHUB_ACTIONS");
+    cmd
+}
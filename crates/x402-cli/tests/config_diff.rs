@@ -0,0 +1,120 @@
+// Config diff tests
+//
+// `config diff` builds the merged configuration twice, from injected
+// environments and project files, and reports each differing field with
+// the source of both values.
+
+mod common;
+
+use common::{cli, project};
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+fn config_diff(dir: &TempDir) -> assert_cmd::Command {
+    let mut cmd = cli(dir);
+    cmd.env("X402_DEV_PORT", "7402")
+        .args(["config", "diff", "--clean-env"]);
+    cmd
+}
+
+/// Test: two injected env sets give attributed JSON entries and exit 3
+#[test]
+fn test_diff_injected_envs_json() {
+    let dir = project("port: 8402\nsolana_rpc: https://rpc.example.com/?api-key=3f9a1c22-7d41\n");
+
+    let output = config_diff(&dir)
+        .args([
+            "--left-env",
+            "X402_DEV_LOG_LEVEL=debug",
+            "--right-env",
+            "X402_DEV_PORT=9402",
+            "--right-env",
+            "X402_DEV_SOLANA_RPC=https://rpc.example.com/?api-key=77aa0b11-9e",
+            "--json",
+        ])
+        .assert()
+        .code(3)
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+    // Then: Each differing field names both values and their sources
+    assert_eq!(report["identical"], false);
+    assert_eq!(
+        report["differences"],
+        serde_json::json!([
+            {
+                "field": "log_level",
+                "left": {"value": "debug", "source": "environment (X402_DEV_LOG_LEVEL)"},
+                "right": {"value": "info", "source": "default"},
+            },
+            {
+                "field": "port",
+                "left": {"value": "8402", "source": "project (.x402dev.yaml)"},
+                "right": {"value": "9402", "source": "environment (X402_DEV_PORT)"},
+            },
+            {
+                "field": "solana_rpc",
+                "left": {"value": "https://rpc.example.com/?api-key=3f9a••••", "source": "project (.x402dev.yaml)"},
+                "right": {"value": "https://rpc.example.com/?api-key=77aa••••", "source": "environment (X402_DEV_SOLANA_RPC)"},
+            },
+        ])
+    );
+
+    // And: --clean-env dropped the process's X402_DEV_PORT
+    assert_eq!(
+        report["left"]["env"],
+        serde_json::json!(["X402_DEV_LOG_LEVEL"])
+    );
+    assert_eq!(report["right"]["project_file"], ".x402dev.yaml");
+}
+
+/// Test: a project file on one side is compared field by field
+#[test]
+fn test_diff_against_file() {
+    let dir = project("port: 8402\n");
+    fs::write(
+        dir.path().join("ci.yaml"),
+        "port: 8402\nwebhooks:\n  - url: https://hooks.example.com/x402\n    events: [invoice.issued]\n    secret: whsec_4f1b9c0e77a2\n",
+    )
+    .unwrap();
+
+    config_diff(&dir)
+        .args(["--right", "ci.yaml"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("port").not())
+        .stdout(predicate::str::contains(
+            "webhooks[0].secret:\n  left:  (not set) (source: default)\n  right: •••• (set) (source: project (ci.yaml))",
+        ))
+        .stdout(predicate::str::contains("whsec_").not())
+        .stdout(predicate::str::contains("settings differ"));
+}
+
+/// Test: identical scenarios exit 0; bad arguments fail
+#[test]
+fn test_diff_identical_and_invalid() {
+    let dir = project("port: 8402\n");
+
+    config_diff(&dir)
+        .args(["--left-env", "X402_DEV_PORT=8402"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Configurations are identical"));
+
+    config_diff(&dir)
+        .args(["--right-env", "X402_DEV_PORT"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --right-env value"));
+
+    config_diff(&dir)
+        .args(["--left", "missing.yaml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Config file not found: missing.yaml",
+        ));
+}
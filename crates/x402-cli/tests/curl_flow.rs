@@ -0,0 +1,128 @@
+// `curl` tests
+//
+// Runs the mock server in-process and drives the two-phase flow against it
+// in each simulation mode; outcomes other than the expected one exit 1.
+
+mod common;
+
+use common::{free_port, serve};
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_server::{Config, SimulationMode};
+
+fn x402_dev(args: &[&str]) -> assert_cmd::assert::Assert {
+    let dir = tempfile::tempdir().unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+#[actix_web::test]
+async fn test_curl_success_flow() {
+    let url = serve(Config::builder().port(free_port()).build().unwrap()).await;
+
+    x402_dev(&["curl", "/api/data", "--url", &url])
+        .success()
+        .stdout(predicate::str::contains("402 Payment Required"))
+        .stdout(predicate::str::contains("Invoice:"))
+        .stdout(predicate::str::contains("X-Payment-Proof: req-"))
+        .stdout(predicate::str::contains("200 OK"))
+        .stdout(predicate::str::contains(
+            "Payment flow completed (success: 200)",
+        ));
+
+    // --json reports both steps and the parsed invoice
+    let output = x402_dev(&["curl", "/api/data", "--url", &url, "--json"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let flow: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(flow["ok"], true);
+    assert_eq!(flow["steps"][0]["status"], 402);
+    assert_eq!(flow["steps"][1]["status"], 200);
+    assert_eq!(flow["steps"][1]["payment_proof"], flow["invoice"]["memo"]);
+    assert_eq!(flow["invoice"]["currency"], "USDC");
+}
+
+#[actix_web::test]
+async fn test_curl_no_pay_stops_after_invoice() {
+    let url = serve(Config::builder().port(free_port()).build().unwrap()).await;
+
+    x402_dev(&["curl", "api/data", "--url", &url, "--no-pay"])
+        .success()
+        .stdout(predicate::str::contains("Invoice:"))
+        .stdout(predicate::str::contains("X-Payment-Proof").not());
+}
+
+#[actix_web::test]
+async fn test_curl_failure_mode() {
+    let config = Config::builder()
+        .port(free_port())
+        .simulation_mode(SimulationMode::Failure)
+        .build()
+        .unwrap();
+    let url = serve(config).await;
+
+    // A refused payment deviates from the default success expectation
+    x402_dev(&["curl", "/api/data", "--url", &url])
+        .failure()
+        .stdout(predicate::str::contains("402 Payment Required"))
+        .stderr(predicate::str::contains(
+            "Expected the payment to end in success (2xx), got 402 Payment Required",
+        ));
+
+    x402_dev(&["curl", "/api/data", "--url", &url, "--mode", "failure"])
+        .success()
+        .stdout(predicate::str::contains(
+            "Payment flow completed (failure: 402)",
+        ));
+}
+
+#[actix_web::test]
+async fn test_curl_timeout_mode() {
+    let config = Config::builder()
+        .port(free_port())
+        .timeout_delay_ms(100)
+        .build()
+        .unwrap();
+    let url = serve(config).await;
+
+    x402_dev(&["curl", "/api/data", "--url", &url, "--mode", "timeout"])
+        .success()
+        .stdout(predicate::str::contains("408 Request Timeout"))
+        .stdout(predicate::str::contains(
+            "Payment flow completed (timeout: 408)",
+        ));
+
+    let output = x402_dev(&[
+        "curl",
+        "/api/data",
+        "--url",
+        &url,
+        "--mode",
+        "success",
+        "--json",
+    ])
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let flow: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(flow["steps"][1]["simulation_mode"], "success");
+}
+
+#[test]
+fn test_curl_fails_without_a_server() {
+    let port = free_port().to_string();
+    x402_dev(&[
+        "curl",
+        "/api/data",
+        "--url",
+        &format!("http://127.0.0.1:{}", port),
+    ])
+    .failure()
+    .stderr(predicate::str::contains("Failed to connect to"));
+}
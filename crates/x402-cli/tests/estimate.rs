@@ -0,0 +1,217 @@
+// `estimate` tests
+//
+// A fixture suite priced from a fixture pricing config, with exact totals
+// and the request at which the budget and a spending cap are exceeded, and
+// probe mode against wiremock invoices of differing amounts. The table
+// shows amounts in the `display:` format while --json keeps canonical
+// strings.
+
+mod common;
+
+use common::{cli, project};
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+use wiremock::matchers::path;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const PRICING: &str =
+    "pricing:\n  default: 0.01\n  per_resource:\n    /api/premium/*: 0.05\n    /health: 0\n";
+
+const SUITE: &str = r#"budget:
+  max_total: 0.2
+tests:
+  - name: data
+    url: http://127.0.0.1:1/api/data
+    repeat: 10
+    expect:
+      status: 402
+  - name: premium
+    url: http://127.0.0.1:1/api/premium/report
+    repeat: 3
+    expect:
+      status: 402
+    extract:
+      report_id:
+        header: X-Report-Id
+    then:
+      url: http://127.0.0.1:1/api/premium/reports/${report_id}
+      method: POST
+      expect:
+        status: 402
+"#;
+
+const CAP: &str = r#"policies:
+  - type: spending_cap
+    max_amount: 0.12
+    currency: USDC
+    window_seconds: 3600
+"#;
+
+/// The pricing project with the suite and cap fixtures next to its config
+fn fixtures() -> TempDir {
+    let dir = project(PRICING);
+    fs::write(dir.path().join("suite.yaml"), SUITE).unwrap();
+    fs::write(dir.path().join("cap.yaml"), CAP).unwrap();
+    dir
+}
+
+fn estimate(dir: &TempDir, args: &[&str]) -> Value {
+    let output = cli(dir)
+        .arg("estimate")
+        .args(args)
+        .arg("--json")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn invoice(amount: &str) -> ResponseTemplate {
+    ResponseTemplate::new(402).insert_header(
+        "WWW-Authenticate",
+        format!(
+            "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount={} currency=USDC memo=req-550e8400-e29b-41d4-a716-446655440000 network=devnet",
+            amount
+        ),
+    )
+}
+
+/// Test: exact totals per path, budget and cap exceeded at the right request
+#[test]
+fn test_estimate_suite_from_pricing_config() {
+    // Given: 10 requests at 0.01, 3 at 0.05 and a POST step at 0.05
+    let dir = fixtures();
+
+    // When: Estimating with the spending cap policy
+    let estimate = estimate(&dir, &["suite.yaml", "--policy", "cap.yaml"]);
+
+    // Then: Decimal totals are exact
+    assert_eq!(estimate["pricing"], "config");
+    assert_eq!(estimate["requests"], 14);
+    assert_eq!(estimate["totals"]["USDC"], "0.3");
+    let paths = estimate["paths"].as_array().unwrap();
+    assert_eq!(paths.len(), 3);
+    assert_eq!(paths[0]["path"], "/api/data");
+    assert_eq!(paths[0]["subtotal"], "0.1");
+    assert_eq!(paths[1]["requests"], 3);
+    assert_eq!(paths[1]["subtotal"], "0.15");
+    assert_eq!(paths[2]["method"], "POST");
+    assert_eq!(paths[2]["path"], "/api/premium/reports/%7Breport_id%7D");
+    assert_eq!(paths[2]["unit_price"], "0.05");
+
+    // And: The budget of 0.2 goes over on the third premium request
+    let limits = estimate["limits"].as_array().unwrap();
+    assert_eq!(limits[0]["kind"], "budget");
+    assert_eq!(limits[0]["projected"], "0.3");
+    assert_eq!(limits[0]["exceeded_at_request"], 13);
+
+    // And: The cap of 0.12 lets the data requests through and denies the rest
+    assert_eq!(limits[1]["kind"], "spending_cap");
+    assert_eq!(limits[1]["agent"], "anonymous");
+    assert_eq!(limits[1]["exceeded"], true);
+    assert_eq!(limits[1]["exceeded_at_request"], 11);
+    assert_eq!(limits[1]["denied_requests"], 4);
+}
+
+/// Test: --budget overrides the suite budget
+#[test]
+fn test_estimate_budget_flag() {
+    let dir = fixtures();
+
+    let estimate = estimate(&dir, &["suite.yaml", "--budget", "0.3"]);
+
+    let limits = estimate["limits"].as_array().unwrap();
+    assert_eq!(limits.len(), 1);
+    assert_eq!(limits[0]["max_amount"], "0.3");
+    assert_eq!(limits[0]["exceeded"], false);
+}
+
+/// Test: --probe prices each path from the target's own invoices
+#[tokio::test]
+async fn test_estimate_probe_uses_live_amounts() {
+    // Given: A target invoicing 0.002 and 0.25, and a free health path
+    let server = MockServer::start().await;
+    Mock::given(path("/api/cheap"))
+        .respond_with(invoice("0.002"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/api/dear"))
+        .respond_with(invoice("0.25"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(path("/health"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let dir = fixtures();
+    fs::write(
+        dir.path().join("traffic.yaml"),
+        "agent: agent-a\nrequests:\n  - path: /api/cheap\n    count: 500\n  - path: /api/dear\n    count: 4\n  - path: /health\n    count: 20\n",
+    )
+    .unwrap();
+
+    // When: Probing instead of reading the pricing config
+    let estimate = estimate(
+        &dir,
+        &[
+            "--profile",
+            "traffic.yaml",
+            "--probe",
+            "--target",
+            &server.uri(),
+        ],
+    );
+
+    // Then: Each distinct path was probed once, and the amounts differ
+    // from the config's
+    assert_eq!(estimate["pricing"], "probe");
+    assert_eq!(estimate["requests"], 524);
+    let paths = estimate["paths"].as_array().unwrap();
+    assert_eq!(paths[0]["unit_price"], "0.002");
+    assert_eq!(paths[0]["subtotal"], "1");
+    assert_eq!(paths[1]["subtotal"], "1");
+    assert_eq!(paths[2]["subtotal"], "0");
+    assert_eq!(paths[2]["note"], "answered 200, not 402");
+    assert_eq!(estimate["totals"]["USDC"], "2");
+}
+
+/// Test: the table groups and pads amounts, --json stays canonical
+#[test]
+fn test_estimate_display_format() {
+    // Given: 200000 requests at 0.05, and a display section keeping 3 digits
+    let dir = fixtures();
+    fs::write(
+        dir.path().join(".x402dev.yaml"),
+        format!("{}display:\n  min_fraction_digits: 3\n", PRICING),
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("traffic.yaml"),
+        "requests:\n  - path: /api/premium/report\n    count: 200000\n",
+    )
+    .unwrap();
+
+    // When: Printing the table
+    let output = cli(&dir)
+        .args(["estimate", "--profile", "traffic.yaml"])
+        .output()
+        .unwrap();
+
+    // Then: Amounts are grouped, with at least 3 fraction digits
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("10,000.000 USDC"), "{}", text);
+    assert!(text.contains("0.050"), "{}", text);
+
+    // And: JSON keeps the stored values
+    let estimate = estimate(&dir, &["--profile", "traffic.yaml"]);
+    assert_eq!(estimate["totals"]["USDC"], "10000");
+    assert_eq!(estimate["paths"][0]["unit_price"], "0.05");
+}
@@ -0,0 +1,80 @@
+// `explain` tests
+//
+// Every catalog code renders with its fix steps, and errors with a code
+// point at `x402-dev explain`.
+
+use predicates::prelude::*;
+
+fn x402_dev(args: &[&str]) -> assert_cmd::assert::Assert {
+    let dir = tempfile::tempdir().unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .env_remove("X402_DEV_CONFIG")
+        .args(args)
+        .assert()
+}
+
+#[test]
+fn test_every_code_has_a_fix_section() {
+    for explanation in x402_core::explain::all() {
+        x402_dev(&["explain", explanation.code])
+            .success()
+            .stdout(predicate::str::contains(explanation.title))
+            .stdout(predicate::str::contains("Common causes:"))
+            .stdout(predicate::str::contains("How to fix:"))
+            .stdout(predicate::str::contains(format!(
+                "1. {}",
+                explanation.fixes[0]
+            )));
+    }
+}
+
+#[test]
+fn test_codes_the_cli_emits_are_explained() {
+    // CliError codes set in the CLI itself rather than the message catalog
+    assert!(x402_core::explain::lookup("dns-resolution").is_some());
+}
+
+#[test]
+fn test_explain_json_and_case_insensitive_lookup() {
+    let output = x402_dev(&["explain", "x402-p011", "--json"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["code"], "X402-P011");
+    assert_eq!(report["link"], "https://docs.x402-dev.com/errors/X402-P011");
+    assert!(!report["fixes"].as_array().unwrap().is_empty());
+
+    // Without a code, every entry is listed
+    let output = x402_dev(&["explain", "--json"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let all: Vec<serde_json::Value> = serde_json::from_slice(&output).unwrap();
+    assert_eq!(all.len(), x402_core::explain::all().len());
+}
+
+#[test]
+fn test_unknown_code_fails() {
+    x402_dev(&["explain", "E0000"])
+        .failure()
+        .stderr(predicate::str::contains("Unknown error code 'E0000'"));
+}
+
+#[test]
+fn test_errors_point_at_explain() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join(".x402dev.yaml"), "port: 80\n").unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .env_remove("X402_DEV_CONFIG")
+        .args(["config", "show"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "Run `x402-dev explain config.invalid_port` for details",
+        ));
+}
@@ -0,0 +1,152 @@
+// YAML and JSON file format tests
+//
+// JSON copies of the policy fixtures validate like the YAML originals, a
+// project `.x402dev.json` is discovered, parse errors name the format and
+// position, and `convert` round-trips without changing the parsed document.
+
+mod common;
+
+use common::{cli, repo};
+use predicates::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use x402_core::policy::load_policy_file;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../tests/fixtures/policies")
+        .join(name)
+}
+
+/// Test: JSON fixtures load to the same policies and pass validation
+#[test]
+fn test_json_policy_fixtures_match_yaml() {
+    for stem in ["test_allowlist", "test_comprehensive"] {
+        // Given: A policy fixture in both formats
+        let yaml = fixture(&format!("{}.yaml", stem));
+        let json = fixture(&format!("{}.json", stem));
+
+        // Then: Both parse to the same policies
+        assert_eq!(
+            load_policy_file(&yaml).unwrap(),
+            load_policy_file(&json).unwrap(),
+            "{}",
+            stem
+        );
+
+        // And: Validation gives the same result
+        let dir = repo();
+        let run = |path: &Path| {
+            cli(&dir)
+                .args(["policy", "validate"])
+                .arg(path)
+                .args(["--format", "json"])
+                .output()
+                .unwrap()
+        };
+        let (from_yaml, from_json) = (run(&yaml), run(&json));
+        assert!(from_json.status.success(), "{:?}", from_json);
+        assert_eq!(from_yaml.status.code(), from_json.status.code());
+        let report = |stdout: &[u8]| -> serde_json::Value {
+            let mut report: serde_json::Value = serde_json::from_slice(stdout).unwrap();
+            report["files"][0].as_object_mut().unwrap().remove("file");
+            report
+        };
+        assert_eq!(report(&from_yaml.stdout), report(&from_json.stdout));
+    }
+}
+
+/// Test: config show discovers a project .x402dev.json
+#[test]
+fn test_project_json_config_is_discovered() {
+    let dir = repo();
+    fs::write(
+        dir.path().join(".x402dev.json"),
+        "{\n  \"port\": 9700,\n  \"pricing\": {\"default\": 0.02}\n}\n",
+    )
+    .unwrap();
+
+    cli(&dir)
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("port: 9700 (source: project ("))
+        .stdout(predicate::str::contains(".x402dev.json"));
+}
+
+/// Test: a JSON syntax error names the format, how it was detected and where
+#[test]
+fn test_json_parse_error_names_format_and_line() {
+    let dir = repo();
+    fs::write(
+        dir.path().join("policy.json"),
+        "{\n  \"policies\": [\n    {\"type\": \"allowlist\",}\n  ]\n}\n",
+    )
+    .unwrap();
+
+    cli(&dir)
+        .args(["policy", "validate", "policy.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "JSON parse error (from the file extension)",
+        ))
+        .stderr(predicate::str::contains("line 3"));
+}
+
+/// Test: YAML -> JSON -> YAML leaves the parsed policy unchanged
+#[test]
+fn test_convert_round_trip() {
+    // Given: The comprehensive YAML fixture
+    let dir = repo();
+    let original = fixture("test_comprehensive.yaml");
+
+    // When: Converting to JSON and back
+    cli(&dir)
+        .arg("convert")
+        .arg(&original)
+        .arg("policy.json")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(YAML) -> policy.json (JSON)"));
+    cli(&dir)
+        .args(["convert", "policy.json", "back.yaml"])
+        .assert()
+        .success();
+
+    // Then: Every step parses to the same policies
+    let expected = load_policy_file(&original).unwrap();
+    assert_eq!(
+        load_policy_file(&dir.path().join("policy.json")).unwrap(),
+        expected
+    );
+    assert_eq!(
+        load_policy_file(&dir.path().join("back.yaml")).unwrap(),
+        expected
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("policy.json")).unwrap()).unwrap();
+    assert_eq!(json["policies"][1]["max_requests"], 100);
+}
+
+/// Test: convert needs a target format it can work out
+#[test]
+fn test_convert_requires_known_target_format() {
+    let dir = repo();
+    fs::write(dir.path().join("in.yaml"), "a: 1\n").unwrap();
+
+    cli(&dir)
+        .args(["convert", "in.yaml", "out.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--to"));
+
+    cli(&dir)
+        .args(["convert", "in.yaml", "out.txt", "--to", "json"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("out.txt")).unwrap(),
+        "{\n  \"a\": 1\n}\n"
+    );
+}
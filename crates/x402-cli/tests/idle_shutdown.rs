@@ -0,0 +1,133 @@
+// Automatic mock shutdown tests
+//
+// `mock --idle-timeout-secs` and `--max-lifetime-secs` run the real binary
+// with HOME in a temp dir, so the PID file it writes and removes is
+// observable.
+
+use std::io::Read;
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Start `x402-dev mock` on a free port with `args`, waiting until it listens
+fn spawn_mock(home: &Path, args: &[&str]) -> (Child, u16) {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let child = Command::new(env!("CARGO_BIN_EXE_x402-dev"))
+        .current_dir(home)
+        .env("HOME", home)
+        .env_remove("X402_DEV_CONFIG")
+        .args(["mock", "--port", &port.to_string()])
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let listening = (0..100).any(|_| {
+        std::thread::sleep(Duration::from_millis(50));
+        TcpStream::connect(("127.0.0.1", port)).is_ok()
+    });
+    assert!(listening, "mock on port {} did not start", port);
+    (child, port)
+}
+
+/// Wait up to `timeout` for `child` to exit on its own, returning its stdout
+fn wait_for_exit(mut child: Child, timeout: Duration) -> Option<String> {
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if child.try_wait().unwrap().is_some() {
+            let mut stdout = String::new();
+            child
+                .stdout
+                .take()
+                .unwrap()
+                .read_to_string(&mut stdout)
+                .unwrap();
+            return Some(stdout);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    child.kill().unwrap();
+    child.wait().unwrap();
+    None
+}
+
+fn pid_file(home: &Path) -> std::path::PathBuf {
+    home.join(".x402dev").join("mock-server.pid")
+}
+
+/// Test: health pings do not keep an idle server alive
+#[tokio::test]
+async fn test_idle_timeout_exits_and_removes_pid_file() {
+    // Given: A mock that shuts down after 2 idle seconds
+    let home = TempDir::new().unwrap();
+    let (child, port) = spawn_mock(home.path(), &["--idle-timeout-secs", "2"]);
+    assert!(pid_file(home.path()).exists());
+
+    // When: Only /health is polled
+    let client = reqwest::Client::new();
+    let health = format!("http://127.0.0.1:{}/health", port);
+    let mut countdown = Vec::new();
+    for _ in 0..4 {
+        if let Ok(resp) = client.get(&health).send().await {
+            let body: serde_json::Value = resp.json().await.unwrap();
+            countdown.push(body["idle_shutdown_in_secs"].as_u64().unwrap());
+            assert!(body["max_lifetime_in_secs"].is_null());
+        }
+        tokio::time::sleep(Duration::from_millis(400)).await;
+    }
+
+    // Then: The countdown never resets and the server exits on its own
+    assert!(!countdown.is_empty());
+    assert!(
+        countdown.windows(2).all(|w| w[1] <= w[0]),
+        "{:?}",
+        countdown
+    );
+    let stdout = wait_for_exit(child, Duration::from_secs(10)).expect("mock did not exit");
+    assert!(stdout.contains("Shutting down: idle for 2s"), "{}", stdout);
+    assert!(!pid_file(home.path()).exists());
+}
+
+/// Test: requests keep the idle clock fresh, but not past the max lifetime
+#[tokio::test]
+async fn test_max_lifetime_exits_despite_activity() {
+    // Given: A mock with a long idle timeout and a 2-second lifetime
+    let home = TempDir::new().unwrap();
+    let (child, port) = spawn_mock(
+        home.path(),
+        &["--idle-timeout-secs", "60", "--max-lifetime-secs", "2"],
+    );
+
+    // When: A paid path is requested, resetting the idle clock
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("http://127.0.0.1:{}/api/data", port))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 402);
+    let body: serde_json::Value = client
+        .get(format!("http://127.0.0.1:{}/health", port))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["idle_shutdown_in_secs"].as_u64().unwrap() >= 59);
+
+    // Then: The server still exits at its maximum lifetime
+    let stdout = wait_for_exit(child, Duration::from_secs(10)).expect("mock did not exit");
+    assert!(
+        stdout.contains("Shutting down: maximum lifetime of 2s reached"),
+        "{}",
+        stdout
+    );
+    assert!(!pid_file(home.path()).exists());
+}
@@ -0,0 +1,103 @@
+// Signed invoice tests for `check --verify-signature`
+//
+// With the `signing` feature: valid, tampered and wrong-signer invoices
+// served by wiremock. Without it: the flag fails with a rebuild hint.
+
+use predicates::prelude::*;
+use wiremock::matchers::any;
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const UNSIGNED: &str = "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo=req-550e8400-e29b-41d4-a716-446655440000 network=devnet";
+
+async fn serve(header: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(
+            ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
+                .insert_header("WWW-Authenticate", header),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+fn check(url: &str, args: &[&str]) -> assert_cmd::assert::Assert {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .arg("check")
+        .arg(url)
+        .args(args)
+        .assert()
+}
+
+#[cfg(feature = "signing")]
+mod signing {
+    use super::*;
+    use x402_core::signing::InvoiceSigner;
+    use x402_core::testing::InvoiceHeader;
+
+    fn sign(signer: &InvoiceSigner, header: &str) -> String {
+        let canonical = InvoiceHeader::parse(header).unwrap().canonical_string();
+        format!(
+            "{} signer={} signature={}",
+            header,
+            signer.public_key(),
+            signer.sign(&canonical)
+        )
+    }
+
+    #[tokio::test]
+    async fn test_verify_signature_valid() {
+        let signer = InvoiceSigner::generate();
+        let server = serve(&sign(&signer, UNSIGNED)).await;
+
+        check(&server.uri(), &["--verify-signature"])
+            .success()
+            .stdout(predicate::str::contains("Signature valid"));
+        check(&server.uri(), &["--verify-signature", &signer.public_key()]).success();
+
+        // Extra fields do not disturb the lenient checks, and strict mode
+        // knows signer/signature
+        check(&server.uri(), &["--strict"]).success();
+    }
+
+    #[tokio::test]
+    async fn test_verify_signature_tampered_amount() {
+        let signer = InvoiceSigner::generate();
+        let tampered = sign(&signer, UNSIGNED).replace("amount=0.01", "amount=0.001");
+        let server = serve(&tampered).await;
+
+        check(&server.uri(), &["--verify-signature"])
+            .failure()
+            .stdout(predicate::str::contains(
+                "Signature does not match the invoice fields",
+            ));
+
+        // Without the flag the invoice is still structurally valid
+        check(&server.uri(), &[]).success();
+    }
+
+    #[tokio::test]
+    async fn test_verify_signature_wrong_signer_or_unsigned() {
+        let other = InvoiceSigner::generate().public_key();
+        let signed = serve(&sign(&InvoiceSigner::generate(), UNSIGNED)).await;
+        check(&signed.uri(), &["--verify-signature", &other])
+            .failure()
+            .stdout(predicate::str::contains("expected"));
+
+        let unsigned = serve(UNSIGNED).await;
+        check(&unsigned.uri(), &["--verify-signature"])
+            .failure()
+            .stdout(predicate::str::contains("Invoice has no signer field"));
+    }
+}
+
+#[cfg(not(feature = "signing"))]
+#[tokio::test]
+async fn test_verify_signature_needs_feature() {
+    let server = serve(UNSIGNED).await;
+
+    check(&server.uri(), &["--verify-signature"])
+        .failure()
+        .stderr(predicate::str::contains("--features signing"));
+}
@@ -0,0 +1,160 @@
+// `config explain-price` and `policy explain-match` tests
+//
+// Both commands list every rule considered in priority order and mark the
+// one that decides the request.
+
+mod common;
+
+use common::{cli, project};
+use predicates::prelude::*;
+use std::fs;
+
+/// Test: explain-price orders exact, longest prefix, shorter prefix, default
+#[test]
+fn test_explain_price_json_order() {
+    // Given: Overlapping pricing rules
+    let dir = project(
+        "pricing:\n  default: 0.01\n  per_resource:\n    /api/*: 0.02\n    /api/premium/*: 0.05\n    /api/premium/report: 0.1\n",
+    );
+
+    // When: Explaining the most specific path
+    let output = cli(&dir)
+        .args(["config", "explain-price", "/api/premium/report", "--json"])
+        .output()
+        .unwrap();
+
+    // Then: Candidates come in priority order with the exact rule winning
+    assert!(output.status.success(), "{:?}", output);
+    let explanation: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let patterns: Vec<&str> = explanation["candidates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["pattern"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        patterns,
+        ["/api/premium/report", "/api/premium/*", "/api/*", "*"]
+    );
+    assert_eq!(explanation["candidates"][0]["winner"], true);
+    assert_eq!(explanation["price"], 0.1);
+}
+
+/// Test: method-qualified rules win in text output
+#[test]
+fn test_explain_price_method_text() {
+    let dir = project("pricing:\n  per_resource:\n    /api/*: 0.02\n    \"POST /api/*\": 0.09\n");
+
+    cli(&dir)
+        .args(["config", "explain-price", "/api/items", "--method", "post"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("→ POST /api/*"))
+        .stdout(predicate::str::contains("Invoice amount: 0.09"));
+}
+
+/// Test: explain-match shows the candidates and the default deny
+#[test]
+fn test_explain_match_default_deny() {
+    // Given: An allowlist policy file
+    let dir = project("");
+    fs::write(
+        dir.path().join("policy.yaml"),
+        "policies:\n  - type: allowlist\n    field: agent_id\n    values: [agent-trusted]\n",
+    )
+    .unwrap();
+
+    // When/Then: An unknown agent falls through to the default deny
+    cli(&dir)
+        .args([
+            "policy",
+            "explain-match",
+            "policy.yaml",
+            "--agent",
+            "agent-x",
+            "--endpoint",
+            "/api/data",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("→ default"))
+        .stdout(predicate::str::contains("Denied by default"));
+
+    // And: A trusted agent is matched in JSON output
+    let output = cli(&dir)
+        .args([
+            "policy",
+            "explain-match",
+            "policy.yaml",
+            "--agent",
+            "agent-trusted",
+            "--endpoint",
+            "/api/data",
+            "--amount",
+            "0.05",
+            "--json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["decision"]["decision"], "allow");
+    assert_eq!(
+        report["explanation"]["winner"],
+        report["decision"]["policy_id"]
+    );
+}
+
+/// Test: --at and --advance set the time the request is evaluated at
+#[test]
+fn test_explain_match_at_and_advance() {
+    let dir = project("");
+    fs::write(
+        dir.path().join("policy.yaml"),
+        "policies:\n  - type: allowlist\n    field: agent_id\n    values: [agent-1]\n",
+    )
+    .unwrap();
+    let explain = |extra: &[&str]| {
+        let mut cmd = cli(&dir);
+        cmd.args([
+            "policy",
+            "explain-match",
+            "policy.yaml",
+            "--agent",
+            "agent-1",
+        ])
+        .args(["--endpoint", "/api/data"])
+        .args(extra);
+        cmd
+    };
+
+    // Given: A fixed time, moved on by a day
+    let output = explain(&[
+        "--at",
+        "2025-03-14T23:59:00+01:00",
+        "--advance",
+        "24h",
+        "--json",
+    ])
+    .output()
+    .unwrap();
+
+    // Then: The request is made at that time
+    assert!(output.status.success(), "{:?}", output);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let at: chrono::DateTime<chrono::Utc> = report["at"].as_str().unwrap().parse().unwrap();
+    let expected: chrono::DateTime<chrono::Utc> = "2025-03-15T22:59:00Z".parse().unwrap();
+    assert!((at - expected).num_seconds().abs() <= 1, "{}", at);
+
+    // And: Text output names the time
+    explain(&["--at", "2025-03-14T23:59:00Z"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("At: 2025-03-14T23:59:0"));
+
+    // And: A time that is not RFC 3339 is refused
+    explain(&["--at", "yesterday"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected RFC 3339"));
+}
@@ -0,0 +1,27 @@
+// `mock dash` tests
+//
+// The dashboard itself needs a terminal; these cover what happens without
+// one and the option checks. The panels' data model is tested in-crate.
+
+use predicates::prelude::*;
+
+#[test]
+fn test_mock_dash_refuses_without_a_terminal() {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .args(["mock", "dash", "--port", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "x402-dev mock dash needs an interactive terminal",
+        ))
+        .stderr(predicate::str::contains("x402-dev mock stats --json"));
+}
+
+#[test]
+fn test_mock_dash_rejects_out_of_range_refresh() {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .args(["mock", "dash", "--refresh-ms", "10"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--refresh-ms"));
+}
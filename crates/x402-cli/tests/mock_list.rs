@@ -0,0 +1,105 @@
+// `mock list` tests
+//
+// Registers instances in a shared policy state file, as `mock
+// --shared-state` does, and lists them through the CLI.
+
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_core::policy::{PolicyStateStore, SqliteStateStore, StoreInstance};
+use x402_server::PidMetadata;
+
+fn x402_dev(dir: &std::path::Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir)
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+/// Register this test process on `port` and a long gone one on 3499
+fn register(path: &std::path::Path, port: u16) {
+    let pid = std::process::id();
+    let live = StoreInstance {
+        id: format!("{}-{}", pid, port),
+        pid,
+        port: Some(port),
+        started: PidMetadata::for_process(pid).started,
+    };
+    let gone = StoreInstance {
+        id: "999999-3499".to_string(),
+        pid: 999_999,
+        port: Some(3499),
+        started: Some(1),
+    };
+    let store = SqliteStateStore::open(path).unwrap();
+    store.register_instance(&live).unwrap();
+    store.register_instance(&gone).unwrap();
+}
+
+#[test]
+fn test_list_shows_instances_sharing_a_store() {
+    // Given: Two instances registered in a shared file, one still running
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("shared.db");
+    register(&path, 3402);
+    let live = format!("{}-3402", std::process::id());
+
+    // When: Listing the file's instances
+    let assert = x402_dev(dir.path(), &["mock", "list", "--shared-state", "shared.db"]);
+
+    // Then: Both are shown with their status, oldest first
+    let output = String::from_utf8(assert.success().get_output().stdout.clone()).unwrap();
+    assert!(
+        output.starts_with("Instances sharing shared.db\n"),
+        "{}",
+        output
+    );
+    let lines: Vec<&str> = output.lines().skip(2).collect();
+    assert!(lines[0].starts_with("INSTANCE"), "{}", output);
+    assert!(
+        lines[1].starts_with(&live) && lines[1].ends_with("running"),
+        "{}",
+        output
+    );
+    assert!(lines[2].starts_with("999999-3499") && lines[2].ends_with("exited"));
+}
+
+#[test]
+fn test_list_json() {
+    let dir = tempfile::tempdir().unwrap();
+    register(&dir.path().join("shared.db"), 3403);
+
+    let assert = x402_dev(
+        dir.path(),
+        &["mock", "list", "--shared-state", "shared.db", "--json"],
+    );
+
+    let output = assert.success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let instances = json["instances"].as_array().unwrap();
+    assert_eq!(instances.len(), 2);
+    assert_eq!(instances[0]["port"], 3403);
+    assert_eq!(instances[0]["running"], true);
+    assert_eq!(instances[1]["running"], false);
+}
+
+#[test]
+fn test_list_without_shared_file_explains_fix() {
+    let dir = tempfile::tempdir().unwrap();
+
+    x402_dev(dir.path(), &["mock", "list", "--shared-state", "none.db"])
+        .failure()
+        .stderr(predicate::str::contains(
+            "No shared policy state at none.db",
+        ))
+        .stderr(predicate::str::contains("--shared-state none.db"));
+}
+
+#[test]
+fn test_shared_state_requires_policy() {
+    let dir = tempfile::tempdir().unwrap();
+
+    x402_dev(dir.path(), &["mock", "--shared-state", "shared.db"])
+        .failure()
+        .stderr(predicate::str::contains("--policy"));
+}
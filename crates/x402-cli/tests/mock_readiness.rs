@@ -0,0 +1,49 @@
+// Mock readiness tests
+//
+// `mock` waits for /health/ready after binding. A startup hook that fails
+// keeps the server not ready, so the command gives up after
+// `--ready-timeout-secs` with the hook's reason and removes its PID file.
+
+mod common;
+
+use common::free_port;
+use predicates::prelude::*;
+use std::fs;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Test: a failing startup hook makes `mock` time out with the reason
+#[test]
+fn test_mock_times_out_with_failing_hook_reason() {
+    // Given: A self-check expecting 200 from a path that answers 402
+    let home = TempDir::new().unwrap();
+    fs::write(
+        home.path().join(".x402dev.yaml"),
+        "startup_hooks:\n  - type: self_check\n    path: /api/data\n    expect_status: 200\n",
+    )
+    .unwrap();
+
+    // When: The mock starts with a 2-second readiness timeout
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("x402-dev");
+    cmd.current_dir(home.path())
+        .env("HOME", home.path())
+        .env_remove("X402_DEV_CONFIG")
+        .args(["mock", "--port", &free_port().to_string()])
+        .args(["--ready-timeout-secs", "2"])
+        .timeout(Duration::from_secs(30));
+
+    // Then: It fails naming the hook and cleans up its PID file
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Server did not become ready within 2s",
+        ))
+        .stderr(predicate::str::contains(
+            "self_check GET /api/data: answered 402, expected 200",
+        ));
+    assert!(!home
+        .path()
+        .join(".x402dev")
+        .join("mock-server.pid")
+        .exists());
+}
@@ -0,0 +1,98 @@
+// `mock snapshot` tests
+//
+// Exports the state of an in-process mock server to an archive, prints it
+// with `show`, and imports it into a second server.
+
+use predicates::prelude::*;
+use x402_server::{Config, InvoiceStatus, MockServerConfig, TestServer};
+
+async fn start() -> TestServer {
+    TestServer::start(MockServerConfig::new(Config::default()).unwrap())
+        .await
+        .unwrap()
+}
+
+fn snapshot(args: &[&str]) -> assert_cmd::assert::Assert {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .args(["mock", "snapshot"])
+        .args(args)
+        .assert()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_export_show_import() {
+    // Given: A server that issued two invoices, one of them paid
+    let original = start().await;
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/data", original.base_url());
+    let mut memos = Vec::new();
+    for _ in 0..2 {
+        let body: serde_json::Value = client.get(&url).send().await.unwrap().json().await.unwrap();
+        memos.push(body["invoice"]["memo"].as_str().unwrap().to_string());
+    }
+    client
+        .get(&url)
+        .header("X-Payment-Proof", &memos[0])
+        .send()
+        .await
+        .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let archive = dir.path().join("bug.tar.gz");
+    let archive_arg = archive.to_str().unwrap();
+
+    // When: Exporting it
+    let exported = original.port().to_string();
+    snapshot(&["export", archive_arg, "--port", &exported])
+        .success()
+        .stdout(predicate::str::contains("Snapshot written to"))
+        .stdout(predicate::str::contains(
+            "Invoices      2 of 2 (1 verified)",
+        ));
+    original.stop().await.unwrap();
+
+    // Then: `show` reads the archive without a server
+    snapshot(&["show", archive_arg])
+        .success()
+        .stdout(predicate::str::contains("Policy state  no policies"))
+        .stdout(predicate::str::contains("Stats since"));
+    snapshot(&["show", archive_arg, "--json"])
+        .success()
+        .stdout(predicate::str::contains(&memos[1]));
+
+    // When: Importing it into a fresh server
+    let restored = start().await;
+    snapshot(&[
+        "import",
+        archive_arg,
+        "--port",
+        &restored.port().to_string(),
+    ])
+    .success()
+    .stdout(predicate::str::contains(
+        "Restored 2 invoices (0 already known)",
+    ));
+
+    // Then: The invoices are known there
+    let state = restored.snapshot().await.unwrap();
+    let statuses: Vec<_> = state
+        .invoices
+        .iter()
+        .map(|record| (record.memo.clone(), record.status))
+        .collect();
+    assert!(statuses.contains(&(memos[0].clone(), InvoiceStatus::Verified)));
+    assert!(statuses.contains(&(memos[1].clone(), InvoiceStatus::Issued)));
+
+    restored.stop().await.unwrap();
+}
+
+#[test]
+fn test_show_rejects_other_files() {
+    // Given: A file that is not a snapshot archive
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), "not an archive").unwrap();
+
+    // When/Then: `show` fails naming the file
+    snapshot(&["show", file.path().to_str().unwrap()])
+        .failure()
+        .stderr(predicate::str::contains("Failed to read snapshot"));
+}
@@ -0,0 +1,107 @@
+// `mock stats` rendering tests
+//
+// wiremock stands in for a running mock server's /__admin/stats endpoint.
+
+use predicates::prelude::*;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn row(pattern: &str, requests: u64, invoices: u64, mean: f64, p95: f64) -> serde_json::Value {
+    serde_json::json!({
+        "pattern": pattern,
+        "requests": requests,
+        "invoices_issued": invoices,
+        "verified": 1,
+        "verification_failed": 0,
+        "verification_timeout": 0,
+        "policy_denied": requests - invoices - 1,
+        "errors": 0,
+        "latency_mean_ms": mean,
+        "latency_p95_ms": p95,
+    })
+}
+
+async fn serve_stats() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/__admin/stats"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "since": "2026-01-01T00:00:00Z",
+            "paths": [
+                row("*", 3, 1, 0.5, 1.0),
+                row("/api/premium/*", 12, 10, 1.25, 2.5),
+            ],
+            "totals": {
+                "pattern": "total",
+                "requests": 15,
+                "invoices_issued": 11,
+                "verified": 2,
+                "verification_failed": 0,
+                "verification_timeout": 0,
+                "policy_denied": 2,
+                "errors": 0,
+                "latency_mean_ms": 1.1,
+                "latency_p95_ms": 2.5,
+            },
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn stats(server: &MockServer, args: &[&str]) -> assert_cmd::assert::Assert {
+    let port = server.address().port().to_string();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .args(["mock", "stats", "--port", &port])
+        .args(args)
+        .assert()
+}
+
+#[tokio::test]
+async fn test_mock_stats_table() {
+    let server = serve_stats().await;
+
+    let output = stats(&server, &[]).success().get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(lines[0], "Stats since 2026-01-01T00:00:00+00:00");
+    assert_eq!(
+        lines[2],
+        "PATH            REQUESTS  402  VERIFIED  FREE  FAILED  TIMEOUT  DENIED  ERRORS  MEAN ms  P95 ms"
+    );
+    assert_eq!(
+        lines[3],
+        "*                      3    1         1     0       0        0       1       0     0.50    1.00"
+    );
+    assert_eq!(
+        lines[4],
+        "/api/premium/*        12   10         1     0       0        0       1       0     1.25    2.50"
+    );
+    assert!(lines[5].chars().all(|c| c == '-'));
+    assert_eq!(lines[5].len(), lines[2].len());
+    assert_eq!(
+        lines[6],
+        "total                 15   11         2     0       0        0       2       0     1.10    2.50"
+    );
+}
+
+#[tokio::test]
+async fn test_mock_stats_json() {
+    let server = serve_stats().await;
+
+    stats(&server, &["--json"])
+        .success()
+        .stdout(predicate::str::contains(r#""pattern": "/api/premium/*""#))
+        .stdout(predicate::str::contains(r#""requests": 15"#));
+}
+
+#[test]
+fn test_mock_stats_server_not_running() {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .args(["mock", "stats", "--port", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to fetch stats"))
+        .stderr(predicate::str::contains("Fix: Start the server"));
+}
@@ -0,0 +1,123 @@
+// Global output flags: --quiet, --no-color, --ascii and terminal detection
+//
+// Tests run with stdout piped, so a terminal is simulated with
+// CLICOLOR_FORCE. `policy explain-match` prints a progress title, symbols
+// (→, ✓) and colored text, which covers every switch.
+
+mod common;
+
+use common::{cli, repo_with};
+use std::fs;
+use std::process::Output;
+use tempfile::TempDir;
+
+const ANSI: &str = "\x1b[";
+
+const POLICY: &str =
+    "policies:\n  - type: allowlist\n    field: agent_id\n    values: [agent-trusted]\n";
+
+fn explain(dir: &TempDir, env: &[(&str, &str)], extra: &[&str]) -> Output {
+    let mut cmd = cli(dir);
+    cmd.env_remove("NO_COLOR")
+        .env_remove("CLICOLOR_FORCE")
+        .envs(env.iter().copied())
+        .args([
+            "policy",
+            "explain-match",
+            "policy.yaml",
+            "--agent",
+            "agent-trusted",
+            "--endpoint",
+            "/api/data",
+        ])
+        .args(extra);
+    cmd.output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// Test: no colors when stdout is not a terminal, symbols kept
+#[test]
+fn test_piped_output_is_plain() {
+    let dir = repo_with("policy.yaml", POLICY);
+
+    let text = stdout(&explain(&dir, &[], &[]));
+
+    assert!(!text.contains(ANSI), "{}", text);
+    assert!(text.contains("Policy Match"));
+    assert!(text.contains("✓ Allowed by"));
+}
+
+/// Test: a (simulated) terminal gets colors unless --no-color or NO_COLOR
+#[test]
+fn test_terminal_colors_and_opt_outs() {
+    let dir = repo_with("policy.yaml", POLICY);
+    let tty = ("CLICOLOR_FORCE", "1");
+
+    assert!(stdout(&explain(&dir, &[tty], &[])).contains(ANSI));
+    assert!(!stdout(&explain(&dir, &[tty], &["--no-color"])).contains(ANSI));
+    assert!(!stdout(&explain(&dir, &[tty, ("NO_COLOR", "1")], &[])).contains(ANSI));
+}
+
+/// Test: --ascii swaps emoji and symbols for markers
+#[test]
+fn test_ascii_markers() {
+    let dir = repo_with("policy.yaml", POLICY);
+
+    let text = stdout(&explain(&dir, &[], &["--ascii"]));
+
+    assert!(text.is_ascii(), "{}", text);
+    assert!(text.contains("[ok] Allowed by"));
+    assert!(text.contains("agent-trusted -> /api/data"));
+}
+
+/// Test: --quiet drops progress lines but keeps the result and errors
+#[test]
+fn test_quiet_keeps_results_and_errors() {
+    let dir = repo_with("policy.yaml", POLICY);
+
+    // Given/When: A successful run with -q
+    let text = stdout(&explain(&dir, &[], &["-q"]));
+
+    // Then: The title is gone, the verdict stays
+    assert!(!text.contains("Policy Match"), "{}", text);
+    assert!(text.contains("Allowed by"));
+
+    // When: A failing run with --quiet --ascii
+    fs::remove_file(dir.path().join("policy.yaml")).unwrap();
+    let output = explain(&dir, &[], &["--quiet", "--ascii"]);
+
+    // Then: The error is still printed, in ASCII
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("[x] "), "{}", stderr);
+}
+
+/// Test: JSON output is not touched by the output flags
+#[test]
+fn test_json_bypasses_output_flags() {
+    let dir = repo_with("policy.yaml", POLICY);
+
+    let plain = stdout(&explain(&dir, &[], &["--json"]));
+    let flagged = stdout(&explain(
+        &dir,
+        &[("CLICOLOR_FORCE", "1")],
+        &["--json", "--ascii", "--quiet"],
+    ));
+
+    // Identical apart from the evaluation time
+    let parse = |text: &str| {
+        let mut json: serde_json::Value = serde_json::from_str(text).unwrap();
+        json.as_object_mut().unwrap().remove("at");
+        json
+    };
+    assert!(!flagged.contains(ANSI));
+    assert_eq!(parse(&plain), parse(&flagged));
+}
@@ -0,0 +1,136 @@
+// Payment receipt tests for `receipt verify` and `check --strict`
+//
+// Runs the mock server in-process, captures the X-Payment-Receipt of a
+// paid request and verifies it offline; tampered receipts are rejected.
+// `check --strict` also pays twice and expects the replay to be refused.
+
+mod common;
+
+use common::{free_port, serve};
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_core::receipt::{PaymentReceipt, RECEIPT_HEADER};
+use x402_server::Config;
+
+async fn start(config: Config) -> String {
+    format!("{}/api/data", serve(config).await)
+}
+
+/// Pay for `url` with its invoice memo and return the receipt header value
+async fn capture_receipt(url: &str) -> String {
+    let client = reqwest::Client::new();
+    let invoice: serde_json::Value = client.get(url).send().await.unwrap().json().await.unwrap();
+    let memo = invoice["invoice"]["memo"].as_str().unwrap();
+    let paid = client
+        .get(url)
+        .header("X-Payment-Proof", memo)
+        .send()
+        .await
+        .unwrap();
+    assert!(paid.status().is_success());
+    paid.headers()[RECEIPT_HEADER].to_str().unwrap().to_string()
+}
+
+fn x402_dev(args: &[&str]) -> assert_cmd::assert::Assert {
+    let dir = tempfile::tempdir().unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+#[actix_web::test]
+async fn test_capture_and_verify_receipt() {
+    let url = start(Config::builder().port(free_port()).build().unwrap()).await;
+    let receipt = capture_receipt(&url).await;
+
+    x402_dev(&["receipt", "verify", &receipt])
+        .success()
+        .stdout(predicate::str::contains("Receipt valid"))
+        .stdout(predicate::str::contains("Resource:    /api/data"))
+        .stdout(predicate::str::contains("unsigned"));
+    x402_dev(&["receipt", "verify", &receipt, "--json"])
+        .success()
+        .stdout(predicate::str::contains(r#""simulation_mode": "success""#));
+
+    // Unsigned receipts fail when a signature is required
+    x402_dev(&["receipt", "verify", &receipt, "--require-signature"])
+        .failure()
+        .stderr(predicate::str::contains("Receipt is not signed"));
+
+    // The strict compliance check pays and validates the receipt
+    x402_dev(&["check", &url, "--strict"])
+        .success()
+        .stdout(predicate::str::contains("✅ Payment receipt: req-"))
+        .stdout(predicate::str::contains(
+            "✅ Replay protection: second payment refused (409 replayed_payment)",
+        ));
+}
+
+#[actix_web::test]
+async fn test_strict_check_fails_when_replays_are_accepted() {
+    let url = start(
+        Config::builder()
+            .port(free_port())
+            .allow_replay(true)
+            .build()
+            .unwrap(),
+    )
+    .await;
+
+    x402_dev(&["check", &url, "--strict"])
+        .failure()
+        .stdout(predicate::str::contains("❌ Replay protection: req-"))
+        .stdout(predicate::str::contains("was accepted twice (200)"));
+}
+
+#[actix_web::test]
+async fn test_tampered_receipt_is_rejected() {
+    let url = start(Config::builder().port(free_port()).build().unwrap()).await;
+    let mut receipt = PaymentReceipt::decode(&capture_receipt(&url).await).unwrap();
+
+    receipt.amount = "0.0000001".to_string();
+    x402_dev(&["receipt", "verify", &receipt.encode()])
+        .failure()
+        .stderr(predicate::str::contains("not a positive USDC amount"));
+    x402_dev(&["receipt", "verify", "bm90IGpzb24="])
+        .failure()
+        .stderr(predicate::str::contains("not a valid receipt JSON object"));
+}
+
+#[cfg(feature = "signing")]
+mod signing {
+    use super::*;
+    use x402_server::SigningConfig;
+
+    #[actix_web::test]
+    async fn test_signed_receipt_round_trip_and_tampering() {
+        let url = start(
+            Config::builder()
+                .port(free_port())
+                .signing(SigningConfig::ephemeral())
+                .build()
+                .unwrap(),
+        )
+        .await;
+        let value = capture_receipt(&url).await;
+        let receipt = PaymentReceipt::decode(&value).unwrap();
+        let signer = receipt.signer.clone().unwrap();
+
+        x402_dev(&["receipt", "verify", &value, "--signer", &signer])
+            .success()
+            .stdout(predicate::str::contains(format!("valid ({})", signer)));
+        x402_dev(&["check", &url, "--strict"])
+            .success()
+            .stdout(predicate::str::contains(format!("(signed by {})", signer)));
+
+        let mut tampered = receipt;
+        tampered.amount = "100".to_string();
+        x402_dev(&["receipt", "verify", &tampered.encode()])
+            .failure()
+            .stderr(predicate::str::contains(
+                "Signature does not match the receipt fields",
+            ));
+    }
+}
@@ -0,0 +1,129 @@
+// Lenient policy validation tests
+//
+// `policy validate --lenient` (or `lenient: true` in the file) skips rules
+// that fail to parse, lists them apart from the semantic issues, and
+// validates the rest. Strict validation rejects the whole file, and code
+// generation and the policy engine refuse files marked lenient.
+
+mod common;
+
+use common::cli;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+/// Two valid rules around a rule with a misspelled type
+const ONE_BROKEN: &str = r#"policies:
+  - type: allowlist
+    field: agent_id
+    values: ["agent-a"]
+  - type: rate_limt
+    max_requests: 10
+  - type: rate_limit
+    max_requests: 100
+    window_seconds: 1h
+"#;
+
+fn x402_dev(dir: &TempDir, yaml: &str) -> assert_cmd::Command {
+    fs::write(dir.path().join("policy.yaml"), yaml).unwrap();
+    cli(dir)
+}
+
+/// Test: the valid rules validate in lenient mode, the file fails in strict mode
+#[test]
+fn test_lenient_validates_the_rules_that_parse() {
+    let dir = TempDir::new().unwrap();
+
+    // Strict: the whole file is rejected
+    x402_dev(&dir, ONE_BROKEN)
+        .args(["policy", "validate", "policy.yaml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("rate_limt"))
+        .stderr(predicate::str::contains("pass --lenient"));
+
+    // Lenient: the broken rule is listed with its line, the others pass
+    x402_dev(&dir, ONE_BROKEN)
+        .args(["policy", "validate", "policy.yaml", "--lenient"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Rules skipped due to parse errors:",
+        ))
+        .stdout(predicate::str::contains(
+            "Policy #1 (line 5): unknown variant `rate_limt`",
+        ))
+        .stdout(predicate::str::contains("(with warnings) (1 rule skipped)"));
+
+    // JSON keeps skipped rules apart from issues
+    let output = x402_dev(&dir, ONE_BROKEN)
+        .args([
+            "policy",
+            "validate",
+            "policy.yaml",
+            "--lenient",
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let file = &json["files"][0];
+    assert_eq!(file["passed"], true);
+    assert_eq!(file["errors"], 0);
+    assert_eq!(file["skipped"][0]["policy"], 1);
+    assert_eq!(file["skipped"][0]["line"], 5);
+
+    // Skipped rules fail the warnings gate
+    x402_dev(&dir, ONE_BROKEN)
+        .args([
+            "policy",
+            "validate",
+            "policy.yaml",
+            "--lenient",
+            "--fail-on",
+            "warnings",
+        ])
+        .assert()
+        .failure();
+}
+
+/// Test: `lenient: true` validates leniently but cannot be enforced or generated
+#[test]
+fn test_lenient_files_are_refused_for_enforcement() {
+    let dir = TempDir::new().unwrap();
+    let yaml = format!("lenient: true\n{}", ONE_BROKEN);
+
+    x402_dev(&dir, &yaml)
+        .args(["policy", "validate", "policy.yaml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Policy #1 (line 6)"));
+
+    x402_dev(&dir, &yaml)
+        .args([
+            "policy",
+            "generate",
+            "policy.yaml",
+            "--framework",
+            "express",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is marked `lenient: true`"));
+
+    x402_dev(&dir, &yaml)
+        .args([
+            "policy",
+            "explain-match",
+            "policy.yaml",
+            "--agent",
+            "agent-a",
+            "--endpoint",
+            "/api/data",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("marked `lenient: true`"));
+}
@@ -0,0 +1,201 @@
+// Policy lint tests
+//
+// `policy lint` style rules with their X402-L codes, severities set in the
+// project's `lint:` section, the --fix sort rewriting the file, and
+// `policy validate --with-lint`.
+
+mod common;
+
+use common::{cli, repo_with};
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+/// Valid policies breaking every lint rule but X402-L004
+const UNTIDY: &str = r#"
+policies:
+  - type: allowlist
+    field: agent_id
+    values: ["agent-b", "*", "agent-a"]
+  - type: rate_limit
+    max_requests: 100
+    window_seconds: 90
+  - type: spending_cap
+    max_amount: 10.0
+    currency: usdc
+    window_seconds: 86400
+"#;
+
+/// Codes and severities of the JSON report of `args`
+fn issues(dir: &TempDir, args: &[&str]) -> Vec<(String, String)> {
+    let output = cli(dir)
+        .args(args)
+        .args(["--format", "json"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    json["files"][0]["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|issue| {
+            (
+                issue["code"].as_str().unwrap().to_string(),
+                issue["severity"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+fn pair(code: &str, severity: &str) -> (String, String) {
+    (code.to_string(), severity.to_string())
+}
+
+/// Test: each rule fires with its code and default severity
+#[test]
+fn test_each_rule_fires() {
+    // Given: A valid but untidy policy file
+    let dir = repo_with("policy.yaml", UNTIDY);
+
+    // When: Linting it
+    let found = issues(&dir, &["policy", "lint", "policy.yaml"]);
+
+    // Then: Every style rule is reported, and no validation rule
+    assert_eq!(
+        found,
+        [
+            pair("X402-L001", "warning"),
+            pair("X402-L002", "info"),
+            pair("X402-L003", "warning"),
+        ]
+    );
+
+    // And: Warnings pass by default, like validation
+    cli(&dir)
+        .args(["policy", "lint", "policy.yaml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Policy Lint"))
+        .stdout(predicate::str::contains(
+            "Allowlist values of policy #0 are not sorted [X402-L001]",
+        ))
+        .stdout(predicate::str::contains("Set window_seconds to 60 or 120"))
+        .stdout(predicate::str::contains(
+            "Policy file passes lint (with warnings)",
+        ));
+
+    // And: A production project also flags the bare '*'
+    fs::write(
+        dir.path().join(".x402dev.yaml"),
+        "lint:\n  production: true\n",
+    )
+    .unwrap();
+    let found = issues(&dir, &["policy", "lint", "policy.yaml"]);
+    assert!(found.contains(&pair("X402-L004", "warning")), "{:?}", found);
+}
+
+/// Test: severities come from the lint section of .x402dev.yaml
+#[test]
+fn test_severity_overrides_from_config() {
+    // Given: A project raising X402-L001 and turning X402-L002 off
+    let dir = repo_with("policy.yaml", UNTIDY);
+    fs::write(
+        dir.path().join(".x402dev.yaml"),
+        "lint:\n  rules:\n    X402-L001: error\n    X402-L002: off\n",
+    )
+    .unwrap();
+
+    // Then: The overrides apply
+    assert_eq!(
+        issues(&dir, &["policy", "lint", "policy.yaml"]),
+        [pair("X402-L001", "error"), pair("X402-L003", "warning")]
+    );
+    cli(&dir)
+        .args(["policy", "lint", "policy.yaml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Policy lint failed with errors"));
+
+    // And: Warnings can be suppressed like validation warnings
+    cli(&dir)
+        .args(["policy", "lint", "policy.yaml", "--fail-on", "warnings"])
+        .args(["--allow", "X402-L003"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("SUPPRESSED WARNING"));
+
+    // And: Validation rules cannot be configured there
+    fs::write(
+        dir.path().join(".x402dev.yaml"),
+        "lint:\n  rules:\n    X402-P010: off\n",
+    )
+    .unwrap();
+    cli(&dir)
+        .args(["policy", "lint", "policy.yaml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "X402-P010 is a validation rule, not a lint rule",
+        ));
+}
+
+/// Test: --fix sorts lists in place, the same way every time
+#[test]
+fn test_fix_sorts_lists_deterministically() {
+    // Given: Unsorted allowlist and denylist values
+    let dir = repo_with("policy.yaml", 
+        "policies:\n  - type: allowlist\n    field: agent_id\n    values: [\"zeta\", \"alpha\", \"mid\"]\n  - type: denylist\n    field: endpoint\n    values: [\"/b\", \"/a\"]\n",
+    );
+
+    // When: Fixing
+    cli(&dir)
+        .args(["policy", "lint", "policy.yaml", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Sorted list values: policy.yaml"))
+        .stdout(predicate::str::contains("X402-L001").not());
+
+    // Then: The file is rewritten with sorted values
+    let fixed = fs::read_to_string(dir.path().join("policy.yaml")).unwrap();
+    assert_eq!(
+        fixed,
+        "policies:\n- type: allowlist\n  field: agent_id\n  values:\n  - alpha\n  - mid\n  - zeta\n- type: denylist\n  field: endpoint\n  values:\n  - /a\n  - /b\n"
+    );
+
+    // And: Fixing again leaves it untouched
+    cli(&dir)
+        .args(["policy", "lint", "policy.yaml", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Sorted list values").not());
+    assert_eq!(
+        fs::read_to_string(dir.path().join("policy.yaml")).unwrap(),
+        fixed
+    );
+}
+
+/// Test: validate runs lint only with --with-lint
+#[test]
+fn test_validate_with_lint() {
+    let dir = repo_with(
+        "policy.yaml",
+        &format!(
+            "{}  - type: rate_limit\n    max_requests: 5\n    window_seconds: 60\n",
+            UNTIDY
+        ),
+    );
+
+    assert_eq!(
+        issues(&dir, &["policy", "validate", "policy.yaml"]),
+        [pair("X402-P010", "warning")]
+    );
+    assert_eq!(
+        issues(&dir, &["policy", "validate", "policy.yaml", "--with-lint"]),
+        [
+            pair("X402-P010", "warning"),
+            pair("X402-L001", "warning"),
+            pair("X402-L002", "info"),
+            pair("X402-L003", "warning"),
+        ]
+    );
+}
@@ -0,0 +1,112 @@
+// `policy simulate` tests
+//
+// A small population with a fixed seed and even arrivals, so the cohort
+// counts are exact and the same on every run.
+
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+
+const POLICY: &str = r#"policies:
+  - type: denylist
+    field: agent_id
+    values: ["bot-*"]
+  - type: rate_limit
+    max_requests: 5
+    window_seconds: 60
+"#;
+
+const POPULATION: &str = r#"seed: 7
+cohorts:
+  - name: agents
+    agents: 4
+    agent_id: agent-{n}
+    requests_per_minute: 3
+    amount: 0.01
+    arrivals: even
+    expected_deny_rate: 0.0
+    malicious:
+      fraction: 0.25
+      rate_multiplier: 4
+      expected_deny_rate: 0.6
+  - name: bots
+    agents: 2
+    agent_id: bot-{n}
+    requests_per_minute: 2
+    arrivals: even
+    expected_deny_rate: 1.0
+"#;
+
+fn workspace(population: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("policy.yaml"), POLICY).unwrap();
+    fs::write(dir.path().join("population.yaml"), population).unwrap();
+    dir
+}
+
+fn simulate(dir: &TempDir) -> assert_cmd::Command {
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("x402-dev");
+    cmd.current_dir(dir.path()).args([
+        "policy",
+        "simulate",
+        "--policy",
+        "policy.yaml",
+        "--population",
+        "population.yaml",
+        "--duration",
+        "60s",
+    ]);
+    cmd
+}
+
+/// Test: exact cohort counts in the JSON report
+#[test]
+fn test_simulate_json_counts() {
+    // Given: Well-behaved agents, one flooding agent and blocked bots
+    let dir = workspace(POPULATION);
+
+    // When: Simulating one minute
+    let output = simulate(&dir).arg("--json").output().unwrap();
+
+    // Then: Each cohort gets exactly the expected outcome
+    assert!(output.status.success(), "{:?}", output);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let counts: Vec<(String, u64, u64, u64, u64)> = report["cohorts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| {
+            (
+                c["cohort"].as_str().unwrap().to_string(),
+                c["requests"].as_u64().unwrap(),
+                c["allowed"].as_u64().unwrap(),
+                c["denied"].as_u64().unwrap(),
+                c["rate_limited"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            ("agents".to_string(), 9, 9, 0, 0),
+            ("agents".to_string(), 12, 5, 0, 7),
+            ("bots".to_string(), 4, 0, 4, 0),
+        ]
+    );
+    assert_eq!(report["cohorts"][1]["malicious"], true);
+    assert_eq!(report["seed"], 7);
+}
+
+/// Test: a missed expectation is reported and exits 1
+#[test]
+fn test_simulate_unexpected_deny_rate_fails() {
+    // Given: Bots expected to get through
+    let dir = workspace(&POPULATION.replace("expected_deny_rate: 1.0", "expected_deny_rate: 0.0"));
+
+    // When/Then: The table is printed and the command fails naming the cohort
+    simulate(&dir)
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("RATE-LIMITED"))
+        .stderr(predicate::str::contains("tolerance 0.05): bots"));
+}
@@ -0,0 +1,196 @@
+// Policy validation gate tests
+//
+// `policy validate --fail-on warnings`, suppression by `suppress:` lists and
+// --allow, rule codes in JSON output, unknown spending cap currencies, and
+// the `policy rules` listing.
+
+mod common;
+
+use common::cli;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Two rate limits: valid, but with an X402-P010 warning
+const TWO_RATE_LIMITS: &str = r#"
+policies:
+  - type: rate_limit
+    max_requests: 100
+    window_seconds: 3600
+  - type: rate_limit
+    max_requests: 50
+    window_seconds: 3600
+"#;
+
+fn policy(dir: &TempDir, yaml: &str) -> PathBuf {
+    let path = dir.path().join("policy.yaml");
+    fs::write(&path, yaml).unwrap();
+    path
+}
+
+fn validate(dir: &TempDir) -> assert_cmd::Command {
+    let mut cmd = cli(dir);
+    cmd.args(["policy", "validate", "policy.yaml"]);
+    cmd
+}
+
+/// Test: warnings fail only under --fail-on warnings
+#[test]
+fn test_fail_on_warnings() {
+    let dir = TempDir::new().unwrap();
+    policy(&dir, TWO_RATE_LIMITS);
+
+    validate(&dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Multiple rate limits defined [X402-P010]",
+        ));
+
+    validate(&dir)
+        .args(["--fail-on", "warnings"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Policy validation failed with warnings (--fail-on warnings)",
+        ));
+}
+
+/// Test: --allow and inline suppress lists pass the gate, still reported
+#[test]
+fn test_suppressed_warning_passes() {
+    let dir = TempDir::new().unwrap();
+    policy(&dir, TWO_RATE_LIMITS);
+
+    validate(&dir)
+        .args(["--fail-on", "warnings", "--allow", "X402-P010"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "SUPPRESSED WARNING Multiple rate limits defined [X402-P010]",
+        ))
+        .stdout(predicate::str::contains(
+            "Policy file is valid! (1 suppressed)",
+        ));
+
+    // File-wide and per-rule suppress lists
+    for yaml in [
+        format!("suppress: [X402-P010]\n{}", TWO_RATE_LIMITS),
+        format!("{}    suppress: [X402-P010]\n", TWO_RATE_LIMITS),
+    ] {
+        policy(&dir, &yaml);
+        validate(&dir)
+            .args(["--fail-on", "warnings"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("SUPPRESSED WARNING"));
+    }
+}
+
+/// Test: errors and unknown codes cannot be suppressed
+#[test]
+fn test_error_codes_not_suppressible() {
+    let dir = TempDir::new().unwrap();
+    policy(&dir, TWO_RATE_LIMITS);
+
+    validate(&dir)
+        .args(["--allow", "X402-P001"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "X402-P001 is an error and cannot be suppressed",
+        ));
+    validate(&dir)
+        .args(["--allow", "X402-P999"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown rule code 'X402-P999'"));
+
+    policy(&dir, &format!("suppress: [X402-P002]\n{}", TWO_RATE_LIMITS));
+    validate(&dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("X402-P002 is an error"));
+}
+
+/// Test: JSON output carries the same codes on every run
+#[test]
+fn test_json_codes_are_stable() {
+    let dir = TempDir::new().unwrap();
+    policy(
+        &dir,
+        &format!(
+            "{}  - type: allowlist\n    field: agent_id\n    values: [a]\n  - type: denylist\n    field: agent_id\n    values: [a]\n",
+            TWO_RATE_LIMITS
+        ),
+    );
+
+    let run = || {
+        let output = validate(&dir).args(["--format", "json"]).output().unwrap();
+        assert!(!output.status.success());
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["files"][0]["issues"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|issue| {
+                (
+                    issue["code"].as_str().unwrap().to_string(),
+                    issue["severity"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let codes = run();
+    assert_eq!(
+        codes,
+        [
+            ("X402-P001".to_string(), "error".to_string()),
+            ("X402-P010".to_string(), "warning".to_string()),
+        ]
+    );
+    assert_eq!(run(), codes);
+}
+
+/// Test: an unknown currency is an error suggesting the closest known one,
+/// unless custom currencies are allowed
+#[test]
+fn test_unknown_currency() {
+    // Given: A spending cap in USD, a fiat code rather than a token
+    let dir = TempDir::new().unwrap();
+    let cap = "policies:\n  - type: spending_cap\n    max_amount: 10.0\n    currency: {}\n    window_seconds: 1h\n";
+    policy(&dir, &cap.replace("{}", "USD"));
+
+    // When/Then: Validation fails with X402-P003 and suggests USDC
+    validate(&dir)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("X402-P003"))
+        .stdout(predicate::str::contains("did you mean USDC?"));
+
+    // When/Then: A custom currency passes with the escape hatch
+    policy(
+        &dir,
+        &format!(
+            "allow_custom_currencies: true\n{}",
+            cap.replace("{}", "CREDITS")
+        ),
+    );
+    validate(&dir).assert().success();
+}
+
+/// Test: policy rules lists every code with its severity
+#[test]
+fn test_policy_rules_listing() {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .args(["policy", "rules"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("X402-P001  error"))
+        .stdout(predicate::str::contains(
+            "X402-P010  warning  More than one rate limit is defined",
+        ))
+        .stdout(predicate::str::contains("X402-P101  info"));
+}
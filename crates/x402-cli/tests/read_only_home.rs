@@ -0,0 +1,183 @@
+// Read-only home directory tests
+//
+// Locked-down CI containers mount HOME read-only. Commands that only read
+// config keep working, `mock` moves its PID file to the temp dir or to
+// X402_DEV_STATE_DIR, and an unwritable override fails with
+// `config.state_dir_unwritable`.
+
+#![cfg(unix)]
+
+use predicates::prelude::*;
+use std::fs;
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use tempfile::TempDir;
+use x402_server::{Config, MockServerConfig, TestServer};
+
+/// A home directory x402-dev cannot write, with its own temp and work dirs
+struct ReadOnlyHome {
+    root: TempDir,
+}
+
+impl ReadOnlyHome {
+    fn new() -> Self {
+        let root = TempDir::new().unwrap();
+        for dir in ["home", "tmp", "work"] {
+            fs::create_dir(root.path().join(dir)).unwrap();
+        }
+        let home = Self { root };
+        fs::set_permissions(home.home(), fs::Permissions::from_mode(0o555)).unwrap();
+        // chmod does not stop root; a file in place of ~/.x402dev does
+        if fs::create_dir(home.home().join(".x402dev")).is_ok() {
+            fs::remove_dir(home.home().join(".x402dev")).unwrap();
+            fs::write(home.home().join(".x402dev"), "").unwrap();
+        }
+        home
+    }
+
+    fn home(&self) -> PathBuf {
+        self.root.path().join("home")
+    }
+
+    fn work(&self) -> PathBuf {
+        self.root.path().join("work")
+    }
+
+    /// Where state goes when ~/.x402dev is not writable (USER is "ci")
+    fn temp_state(&self) -> PathBuf {
+        self.root.path().join("tmp").join("x402dev-ci")
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_x402-dev"));
+        command
+            .current_dir(self.work())
+            .env("HOME", self.home())
+            .env("TMPDIR", self.root.path().join("tmp"))
+            .env("USER", "ci")
+            .env("X402_DEV_NO_UPDATE_CHECK", "1")
+            .env_remove("X402_DEV_CONFIG")
+            .env_remove("X402_DEV_STATE_DIR");
+        command
+    }
+
+    fn x402_dev(&self, args: &[&str]) -> assert_cmd::assert::Assert {
+        let mut command = assert_cmd::Command::from_std(self.command());
+        command.args(args).timeout(Duration::from_secs(20)).assert()
+    }
+}
+
+impl Drop for ReadOnlyHome {
+    fn drop(&mut self) {
+        // Let TempDir remove the tree
+        let _ = fs::set_permissions(self.home(), fs::Permissions::from_mode(0o755));
+    }
+}
+
+/// Start `mock` on a free port, waiting until it listens
+fn spawn_mock(mut command: Command) -> (Child, u16) {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let child = command
+        .args(["mock", "--port", &port.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let listening = (0..100).any(|_| {
+        std::thread::sleep(Duration::from_millis(50));
+        TcpStream::connect(("127.0.0.1", port)).is_ok()
+    });
+    assert!(listening, "mock on port {} did not start", port);
+    (child, port)
+}
+
+fn stop(mut child: Child) {
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+/// Test: commands that only read config work without a writable home
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_only_commands_work() {
+    // Given: A read-only home and a running server
+    let home = ReadOnlyHome::new();
+    let server = TestServer::start(MockServerConfig::new(Config::default()).unwrap())
+        .await
+        .unwrap();
+    let url = format!("{}/api/data", server.base_url());
+    fs::write(
+        home.work().join("suite.yaml"),
+        format!(
+            "tests:\n  - name: invoiced\n    url: {}\n    expect:\n      status: 402\n",
+            url
+        ),
+    )
+    .unwrap();
+
+    // When/Then: config show, check and test succeed
+    home.x402_dev(&["config", "show"])
+        .success()
+        .stdout(predicate::str::contains("port"));
+    home.x402_dev(&["check", &url]).success();
+    home.x402_dev(&["test", "suite.yaml", "--quiet"]).success();
+
+    // And: doctor reports the temp fallback instead of failing
+    home.x402_dev(&["doctor"])
+        .success()
+        .stdout(predicate::str::contains("State dir"))
+        .stdout(predicate::str::contains("temp fallback"));
+}
+
+/// Test: mock falls back to the temp dir for its PID file
+#[test]
+fn test_mock_falls_back_to_temp_state_dir() {
+    let home = ReadOnlyHome::new();
+    let (child, _) = spawn_mock(home.command());
+    assert!(home.temp_state().join("mock-server.pid").exists());
+    stop(child);
+}
+
+/// Test: X402_DEV_STATE_DIR takes the PID file
+#[test]
+fn test_mock_uses_state_dir_override() {
+    let home = ReadOnlyHome::new();
+    let state = home.work().join("state");
+    let mut command = home.command();
+    command.env("X402_DEV_STATE_DIR", &state);
+    let (child, _) = spawn_mock(command);
+    assert!(state.join("mock-server.pid").exists());
+    assert!(!home.temp_state().exists());
+    stop(child);
+}
+
+/// Test: an unwritable override fails naming the path and the variable
+#[test]
+fn test_unwritable_override_fails_with_guidance() {
+    // Given: X402_DEV_STATE_DIR points below a regular file
+    let home = ReadOnlyHome::new();
+    let blocked: &Path = &home.work().join("not-a-dir");
+    fs::write(blocked, "").unwrap();
+    let state = blocked.join("state");
+
+    // When: mock starts
+    let mut command = assert_cmd::Command::from_std(home.command());
+    command
+        .env("X402_DEV_STATE_DIR", &state)
+        .args(["mock", "--port", "3999"])
+        .timeout(Duration::from_secs(20));
+
+    // Then: It exits with the config code and the override in the fix
+    command
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(state.display().to_string()))
+        .stderr(predicate::str::contains("X402_DEV_STATE_DIR"))
+        .stderr(predicate::str::contains("config.state_dir_unwritable"));
+}
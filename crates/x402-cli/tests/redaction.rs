@@ -0,0 +1,164 @@
+// Secret redaction tests
+//
+// `config show` masks secrets unless --reveal is confirmed, and a mock
+// server logging at debug level masks credential headers.
+
+mod common;
+
+use common::{cli, project};
+use predicates::prelude::*;
+use std::io::Read;
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tempfile::TempDir;
+
+const SECRET_CONFIG: &str = r#"
+solana_rpc: https://mainnet.helius-rpc.com/?api-key=3f9a1c22-7d41-4c1e
+tls:
+  cert_path: certs/mock.pem
+  key_path: certs/mock-key.pem
+webhooks:
+  - url: https://hooks.example.com/x402
+    events: [invoice.issued]
+    secret: whsec_4f1b9c0e77a2
+log_redaction: [X-Session-Token]
+"#;
+
+fn config_show(dir: &TempDir) -> assert_cmd::Command {
+    let mut cmd = cli(dir);
+    cmd.env_remove("X402_DEV_SOLANA_RPC")
+        .args(["config", "show"]);
+    cmd
+}
+
+/// Test: config show masks secrets and redacts URL credentials
+#[test]
+fn test_config_show_masks_secrets() {
+    let dir = project(SECRET_CONFIG);
+
+    config_show(&dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "solana_rpc: https://mainnet.helius-rpc.com/?api-key=3f9a•••• (source: project",
+        ))
+        .stdout(predicate::str::contains("tls.cert_path: certs/mock.pem"))
+        .stdout(predicate::str::contains("tls.key_path: •••• (set)"))
+        .stdout(predicate::str::contains(
+            "webhooks[0].url: https://hooks.example.com/x402",
+        ))
+        .stdout(predicate::str::contains("webhooks[0].secret: •••• (set)"))
+        .stdout(predicate::str::contains(
+            "log_redaction: authorization, cookie, proxy-authorization, set-cookie, x-api-key, x-session-token",
+        ))
+        .stdout(predicate::str::contains("whsec_").not())
+        .stdout(predicate::str::contains("mock-key.pem").not())
+        .stdout(predicate::str::contains("7d41").not());
+}
+
+/// Test: --reveal prints secrets only after the prompt is confirmed
+#[test]
+fn test_config_show_reveal_requires_confirmation() {
+    let dir = project(SECRET_CONFIG);
+
+    // When: The prompt is confirmed
+    config_show(&dir)
+        .arg("--reveal")
+        .write_stdin("y\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("[y/N]"))
+        .stdout(predicate::str::contains(
+            "api-key=3f9a1c22-7d41-4c1e (source: project",
+        ))
+        .stdout(predicate::str::contains("tls.key_path: certs/mock-key.pem"))
+        .stdout(predicate::str::contains(
+            "webhooks[0].secret: whsec_4f1b9c0e77a2",
+        ));
+
+    // When: The prompt is declined (or stdin is empty)
+    for answer in ["n\n", ""] {
+        config_show(&dir)
+            .arg("--reveal")
+            .write_stdin(answer)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("whsec_").not())
+            .stderr(predicate::str::contains("Secrets not revealed"));
+    }
+}
+
+/// Test: an invalid log_redaction entry fails config loading
+#[test]
+fn test_invalid_log_redaction_rejected() {
+    let dir = project("log_redaction: [\"X Session\"]\n");
+
+    config_show(&dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid header name in log_redaction: 'X Session'",
+        ));
+}
+
+/// Test: debug request logs mask Authorization and configured headers
+#[tokio::test]
+async fn test_mock_request_log_masks_headers() {
+    // Given: A mock logging at debug level with one extra redacted header
+    let dir = project("log_level: debug\nlog_redaction: [X-Session-Token]\n");
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut mock = Command::new(env!("CARGO_BIN_EXE_x402-dev"))
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env_remove("X402_DEV_CONFIG")
+        .env_remove("X402_DEV_LOG_LEVEL")
+        .args(["mock", "--port", &port.to_string()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let listening = (0..100).any(|_| {
+        std::thread::sleep(Duration::from_millis(100));
+        TcpStream::connect(("127.0.0.1", port)).is_ok()
+    });
+
+    // When: A request carries credentials
+    let status = if listening {
+        reqwest::Client::new()
+            .get(format!("http://127.0.0.1:{}/api/data", port))
+            .header("Authorization", "Bearer sk_live_51HxY2aBcD")
+            .header("X-Session-Token", "sess_9d8c7b6a")
+            .header("X-Agent-Id", "agent-1")
+            .send()
+            .await
+            .map(|resp| resp.status().as_u16())
+            .ok()
+    } else {
+        None
+    };
+    mock.kill().unwrap();
+    let mut stdout = String::new();
+    mock.stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut stdout)
+        .unwrap();
+    mock.wait().unwrap();
+
+    // Then: The logged request shows the header names with masked values
+    assert_eq!(status, Some(402), "mock output:\n{}", stdout);
+    let line = stdout
+        .lines()
+        .find(|line| line.contains("GET /api/data headers:"))
+        .unwrap_or_else(|| panic!("no header log line in:\n{}", stdout));
+    assert!(line.contains("authorization: ••••"), "{}", line);
+    assert!(line.contains("x-session-token: ••••"), "{}", line);
+    assert!(line.contains("x-agent-id: agent-1"), "{}", line);
+    assert!(!stdout.contains("sk_live"), "{}", stdout);
+    assert!(!stdout.contains("sess_9d8c"), "{}", stdout);
+}
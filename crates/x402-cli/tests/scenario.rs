@@ -0,0 +1,96 @@
+// `scenario` command tests
+//
+// Runs the bundled x402-scenario.yaml fixture through the CLI. The scenario
+// starts its own in-process server, so no mock has to be running.
+
+use predicates::prelude::*;
+
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../tests/fixtures/scenarios/x402-scenario.yaml"
+);
+
+fn cli() -> assert_cmd::Command {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+}
+
+/// Test: scenario run --json prints one combined report
+#[test]
+fn test_scenario_run_json_report() {
+    // Given/When: Running the fixture with JSON output
+    let output = cli()
+        .args(["scenario", "run", FIXTURE, "--json"])
+        .output()
+        .unwrap();
+
+    // Then: Exit 0 and stdout is only the report (no request lines)
+    assert!(output.status.success(), "{:?}", output);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["scenario"], "premium-allowlist");
+    assert_eq!(report["passed"], true);
+    assert_eq!(report["suite"]["passed"], 3);
+    assert_eq!(report["stats"]["totals"]["policy_denied"], 1);
+}
+
+/// Test: scenario run prints suite results and the stats table
+#[test]
+fn test_scenario_run_text_report() {
+    cli()
+        .args(["scenario", "run", FIXTURE])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✓ PASS unknown agent is denied"))
+        .stdout(predicate::str::contains("Server stats:"))
+        .stdout(predicate::str::contains("/api/premium/*"));
+}
+
+/// Test: failing tests exit 1
+#[test]
+fn test_scenario_run_failure_exit_code() {
+    // Given: A scenario expecting a free 200 from a priced path
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("x402-scenario.yaml");
+    std::fs::write(
+        &path,
+        "suite:\n  tests:\n    - name: priced\n      url: ${base_url}/api/data\n      expect: { status: 200 }\n",
+    )
+    .unwrap();
+
+    // When/Then: The run reports the failure through the exit code
+    cli()
+        .args(["scenario", "run", path.to_str().unwrap(), "--quiet"])
+        .assert()
+        .code(1);
+}
+
+/// Test: scenario validate names the section of each error
+#[test]
+fn test_scenario_validate_section_locations() {
+    // Given: Errors in the config and suite sections
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("x402-scenario.yaml");
+    std::fs::write(
+        &path,
+        "config:\n  invoice_ttl_seconds: 0\nsuite:\n  tests: []\n",
+    )
+    .unwrap();
+
+    // When/Then: Both are reported with their section and the run fails
+    cli()
+        .args(["scenario", "validate", path.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("x402-scenario.yaml#config:"))
+        .stderr(predicate::str::contains(
+            "x402-scenario.yaml#suite: Test suite must contain at least one test",
+        ));
+
+    // And: The fixture is valid
+    cli()
+        .args(["scenario", "validate", FIXTURE])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Scenario 'premium-allowlist' is valid",
+        ));
+}
@@ -0,0 +1,147 @@
+// Secret command tests
+//
+// `secret set/list/rm` on the encrypted-file backend with the passphrase
+// from X402_DEV_SECRETS_PASSPHRASE, and `${secret:name}` resolved in test
+// suites and config files.
+
+mod common;
+
+use common::{cli, repo};
+use predicates::prelude::*;
+use std::fs;
+use tempfile::TempDir;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const API_KEY: &str = "fk_live_7Qm2xR9pL4vT";
+
+/// [`cli`] with the secrets passphrase; secrets land in the repo's ~/.x402dev
+fn x402_dev(dir: &TempDir) -> assert_cmd::Command {
+    let mut cmd = cli(dir);
+    cmd.env("X402_DEV_SECRETS_PASSPHRASE", "correct horse");
+    cmd
+}
+
+/// Test: set, list and rm never print the value
+#[test]
+fn test_secret_set_list_rm() {
+    let dir = repo();
+
+    x402_dev(&dir)
+        .args(["secret", "set", "facilitator_key", "--file"])
+        .write_stdin(format!("{}\n", API_KEY))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Stored secret 'facilitator_key' (file)",
+        ))
+        .stdout(predicate::str::contains("${secret:facilitator_key}"));
+    let file = fs::read_to_string(dir.path().join(".x402dev/secrets/facilitator_key.enc")).unwrap();
+    assert!(!file.contains(API_KEY));
+
+    x402_dev(&dir)
+        .args(["secret", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("facilitator_key  (file)"))
+        .stdout(predicate::str::contains(API_KEY).not());
+
+    x402_dev(&dir)
+        .args(["secret", "rm", "facilitator_key"])
+        .assert()
+        .success();
+    x402_dev(&dir)
+        .args(["secret", "rm", "facilitator_key"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Secret 'facilitator_key' not found",
+        ));
+}
+
+/// Test: without the variable and a terminal, set asks for the passphrase variable
+#[test]
+fn test_secret_set_needs_passphrase() {
+    let dir = repo();
+
+    x402_dev(&dir)
+        .env_remove("X402_DEV_SECRETS_PASSPHRASE")
+        .args(["secret", "set", "facilitator_key", "--file"])
+        .write_stdin(API_KEY)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("X402_DEV_SECRETS_PASSPHRASE"));
+}
+
+/// Test: a suite sends the secret in a header and the report masks it
+#[tokio::test(flavor = "multi_thread")]
+async fn test_suite_header_from_secret() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/data"))
+        .and(header("x-api-key", API_KEY))
+        .respond_with(ResponseTemplate::new(402))
+        .mount(&server)
+        .await;
+
+    let dir = repo();
+    fs::write(
+        dir.path().join("suite.yaml"),
+        format!(
+            "tests:\n  - name: keyed\n    url: \"{}/api/data?echo=${{secret:facilitator_key}}\"\n    headers:\n      X-Api-Key: \"${{secret:facilitator_key}}\"\n    expect:\n      status: 402\n",
+            server.uri()
+        ),
+    )
+    .unwrap();
+
+    // When: The secret is missing, the run fails naming it
+    x402_dev(&dir)
+        .args(["test", "suite.yaml", "--json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Secret 'facilitator_key' not found",
+        ));
+
+    // Then: Once stored, the request carries it and the report does not
+    x402_dev(&dir)
+        .args(["secret", "set", "facilitator_key", "--file"])
+        .write_stdin(API_KEY)
+        .assert()
+        .success();
+    x402_dev(&dir)
+        .args(["test", "suite.yaml", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"passed\": 1"))
+        .stdout(predicate::str::contains(API_KEY).not());
+}
+
+/// Test: config files resolve references when loaded
+#[test]
+fn test_config_reference() {
+    let dir = repo();
+    fs::write(
+        dir.path().join(".x402dev.yaml"),
+        "solana_rpc: \"https://rpc.example.com/?api-key=${secret:rpc_key}\"\n",
+    )
+    .unwrap();
+
+    x402_dev(&dir)
+        .args(["config", "show"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Secret 'rpc_key' not found"));
+
+    x402_dev(&dir)
+        .args(["secret", "set", "rpc_key", "--file"])
+        .write_stdin("3f9a1c22-7d41-4c1e")
+        .assert()
+        .success();
+    x402_dev(&dir)
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("api-key=3f9a••••"))
+        .stdout(predicate::str::contains("7d41").not());
+}
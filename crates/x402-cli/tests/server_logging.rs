@@ -0,0 +1,124 @@
+// Mock server request log tests
+//
+// Runs `x402-dev mock` with `log_format: json` and checks the request log
+// lines: RFC3339 UTC timestamps, debug lines hidden at the configured info
+// level, and `--log-level debug` raising the level for one instance only.
+
+mod common;
+
+use common::project;
+use serde_json::Value;
+use std::fs;
+use std::io::Read;
+use std::net::{TcpListener, TcpStream};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Run the mock with `extra_args`, send one request and return the server's
+/// JSON log lines (actix startup lines are logged too and left out here)
+async fn json_log_lines(dir: &TempDir, extra_args: &[&str]) -> Vec<Value> {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut mock = Command::new(env!("CARGO_BIN_EXE_x402-dev"))
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env_remove("X402_DEV_CONFIG")
+        .env_remove("X402_DEV_LOG_LEVEL")
+        .args(["mock", "--port", &port.to_string()])
+        .args(extra_args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let listening = (0..100).any(|_| {
+        std::thread::sleep(Duration::from_millis(100));
+        TcpStream::connect(("127.0.0.1", port)).is_ok()
+    });
+
+    let status = if listening {
+        reqwest::get(format!("http://127.0.0.1:{}/api/data", port))
+            .await
+            .map(|resp| resp.status().as_u16())
+            .ok()
+    } else {
+        None
+    };
+    // Give the log line time to be written before stopping the server
+    std::thread::sleep(Duration::from_millis(200));
+    mock.kill().unwrap();
+    let mut stdout = String::new();
+    mock.stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut stdout)
+        .unwrap();
+    mock.wait().unwrap();
+
+    assert_eq!(status, Some(402), "mock output:\n{}", stdout);
+    stdout
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| {
+            serde_json::from_str::<Value>(line).unwrap_or_else(|e| panic!("{}: {}", e, line))
+        })
+        .filter(|line| line["target"] == "x402_server::handlers")
+        .collect()
+}
+
+fn levels(lines: &[Value]) -> Vec<&str> {
+    lines
+        .iter()
+        .map(|line| line["level"].as_str().unwrap())
+        .collect()
+}
+
+/// Test: JSON request lines at the configured level, with RFC3339 UTC timestamps
+#[tokio::test]
+async fn test_json_log_at_configured_level() {
+    let dir = project("log_level: info\nlog_format: json\n");
+
+    let lines = json_log_lines(&dir, &[]).await;
+
+    // Then: One info line for the request, no debug header line
+    assert_eq!(levels(&lines), ["INFO"], "{:?}", lines);
+    let request = &lines[0];
+    assert_eq!(request["message"], "request");
+    assert_eq!(request["method"], "GET");
+    assert_eq!(request["path"], "/api/data");
+    assert_eq!(request["status"], 402);
+    let stamp = request["timestamp"].as_str().unwrap();
+    let parsed = chrono::DateTime::parse_from_rfc3339(stamp).unwrap();
+    assert_eq!(parsed.offset().local_minus_utc(), 0, "{}", stamp);
+    assert!(stamp.ends_with('Z'), "{}", stamp);
+}
+
+/// Test: --log-level debug overrides the config for that instance only
+#[tokio::test]
+async fn test_log_level_flag_overrides_config() {
+    let dir = project("log_level: info\nlog_format: json\n");
+
+    // When: Started with --log-level debug
+    let lines = json_log_lines(&dir, &["--log-level", "debug"]).await;
+
+    // Then: The request headers are logged at debug
+    assert_eq!(levels(&lines), ["DEBUG", "INFO"], "{:?}", lines);
+    assert!(lines[0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("GET /api/data headers:"));
+    for line in &lines {
+        assert!(chrono::DateTime::parse_from_rfc3339(line["timestamp"].as_str().unwrap()).is_ok());
+    }
+
+    // And: The config file still says info, so the next start is quiet again
+    assert_eq!(
+        fs::read_to_string(dir.path().join(".x402dev.yaml")).unwrap(),
+        "log_level: info\nlog_format: json\n"
+    );
+    let lines = json_log_lines(&dir, &[]).await;
+    assert_eq!(levels(&lines), ["INFO"], "{:?}", lines);
+}
@@ -0,0 +1,111 @@
+// `test --history-dir` and `test trends` tests
+//
+// Each run with `--history-dir` appends one line to history.jsonl;
+// `test trends` reads the file back, skipping corrupt lines with a warning,
+// and lists pass rates, duration sparklines and newly flaky tests.
+
+use predicates::prelude::*;
+use std::time::Duration;
+
+fn x402_dev(dir: &std::path::Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir)
+        .env("GITHUB_SHA", "0123456789abcdef")
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+/// History of ten runs: `flaky` fails in runs 7 and 9, and run 4 was cut short
+fn history_fixture() -> String {
+    let mut lines: Vec<String> = (0..10)
+        .map(|i| {
+            let flaky = if i == 7 || i == 9 { "failed" } else { "passed" };
+            serde_json::json!({
+                "timestamp": format!("2026-03-01T10:{:02}:00Z", i),
+                "suite": "suite.yaml",
+                "tests": [
+                    { "name": "stable", "status": "passed", "duration_ms": 100 + 10 * i, "attempts": 1 },
+                    { "name": "flaky", "status": flaky, "duration_ms": 50, "attempts": 1 },
+                ],
+            })
+            .to_string()
+        })
+        .collect();
+    lines.insert(4, r#"{"timestamp":"2026-03-01T10:04:00Z","sui"#.to_string());
+    lines.join("\n") + "\n"
+}
+
+#[test]
+fn test_runs_are_appended_to_history() {
+    // Given: A suite with a test against a closed port
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("suite.yaml"),
+        "tests:\n  - name: unreachable\n    url: http://127.0.0.1:1/api\n    expect:\n      status: 402\n",
+    )
+    .unwrap();
+
+    // When: It runs twice with --history-dir
+    for _ in 0..2 {
+        x402_dev(
+            dir.path(),
+            &[
+                "test",
+                "suite.yaml",
+                "--quiet",
+                "--history-dir",
+                ".x402-history",
+            ],
+        )
+        .code(1);
+    }
+
+    // Then: Each run is one line with the test's status and the commit
+    let history = std::fs::read_to_string(dir.path().join(".x402-history/history.jsonl")).unwrap();
+    let runs: Vec<serde_json::Value> = history
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(runs.len(), 2);
+    for run in &runs {
+        assert_eq!(run["git_sha"], "0123456789abcdef");
+        assert_eq!(run["suite"], "suite.yaml");
+        assert_eq!(run["tests"][0]["name"], "unreachable");
+        assert_eq!(run["tests"][0]["status"], "failed");
+        assert_eq!(run["tests"][0]["attempts"], 1);
+    }
+
+    // And: trends reads them back
+    x402_dev(dir.path(), &["test", "trends"])
+        .success()
+        .stdout(predicate::str::contains("last 2 runs"))
+        .stdout(predicate::str::contains("unreachable"));
+}
+
+#[test]
+fn test_trends_skip_corrupt_lines_and_flag_flaky_tests() {
+    // Given: A history with a truncated line
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("history")).unwrap();
+    std::fs::write(dir.path().join("history/history.jsonl"), history_fixture()).unwrap();
+
+    // When/Then: The line is skipped with a warning and the rest is shown
+    x402_dev(dir.path(), &["test", "trends", "--history-dir", "history"])
+        .success()
+        .stderr(predicate::str::contains("Skipping corrupt line 5"))
+        .stdout(predicate::str::contains("last 10 runs"))
+        .stdout(predicate::str::contains("✓✓✓✓✓✓✓✗✓✗"))
+        .stdout(predicate::str::contains("▁▂▃▃▄▅▆▆▇█"))
+        .stdout(predicate::str::contains("Newly flaky").and(predicate::str::contains("  - flaky")));
+
+    // When/Then: --last narrows the window; too short to call anything new
+    x402_dev(
+        dir.path(),
+        &["test", "trends", "--history-dir", "history", "--last", "3"],
+    )
+    .success()
+    .stdout(predicate::str::contains("last 3 runs"))
+    .stdout(predicate::str::contains("✗✓✗"))
+    .stdout(predicate::str::contains("No newly flaky tests"));
+}
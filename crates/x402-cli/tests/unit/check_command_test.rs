@@ -199,7 +199,7 @@ async fn test_handles_missing_www_authenticate() {
 /// Ensures robust error handling for various malformed header formats.
 #[tokio::test]
 async fn test_handles_malformed_www_authenticate() {
-    let malformed_headers = vec![
+    let malformed_headers = [
         "invalid-protocol recipient=7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU amount=0.01",
         "x402-solana",                // Protocol only, no fields
         "x402-solana recipient",      // Missing value
@@ -212,7 +212,7 @@ fn test_rejects_invalid_ports() {
     for port in invalid_ports {
         // Port numbers are u16, so negative and > 65535 are caught at compile time
         // We test the logic for 0 and boundary cases
-        if port >= 0 && port <= 65535 {
+        if (0..=65535).contains(&port) {
             let port_u16 = port as u16;
             if port_u16 == 0 {
                 assert_eq!(port_u16, 0, "Port 0 should be caught");
@@ -477,9 +477,10 @@ fn test_failure_messages_are_red() {
 #[test]
 fn test_includes_suggestions() {
     // Verify that diagnostic results can store suggestions
-    let mut suggestions: Vec<String> = Vec::new();
-    suggestions.push("Install Node.js/npm: https://nodejs.org/".to_string());
-    suggestions.push("Create config file: x402-dev init".to_string());
+    let suggestions: Vec<String> = vec![
+        "Install Node.js/npm: https://nodejs.org/".to_string(),
+        "Create config file: x402-dev init".to_string(),
+    ];
 
     assert_eq!(suggestions.len(), 2, "Should have 2 suggestions");
     assert!(
@@ -0,0 +1,118 @@
+// `verify` tests
+//
+// Verifies single proofs with the simulated verifier (no server needed) and
+// with the RPC verifier against an in-process mock's fake /rpc; outcomes
+// other than verified exit 1.
+
+mod common;
+
+use common::{free_port, serve};
+use predicates::prelude::*;
+use std::time::Duration;
+use x402_server::{Config, RpcBehavior, RpcConfig};
+
+fn x402_dev(args: &[&str]) -> assert_cmd::assert::Assert {
+    let dir = tempfile::tempdir().unwrap();
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir.path())
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+#[test]
+fn test_verify_simulated_proof() {
+    // Given/When: a proof paying the invoice exactly
+    // Then: it verifies with the proof as the memo
+    x402_dev(&["verify", "req-1", "--amount", "0.05"])
+        .success()
+        .stdout(predicate::str::contains("Payment verified (simulated)"))
+        .stdout(predicate::str::contains("Memo:    req-1"));
+
+    // An underpayment is classified and exits 1
+    x402_dev(&["verify", "req-1", "--amount", "0.05", "--paid", "0.04"])
+        .code(1)
+        .stdout(predicate::str::contains(
+            "Payment not verified: underpayment",
+        ))
+        .stderr(predicate::str::contains("amount_tolerance"));
+
+    // --mode overrides the configured simulation mode
+    let output = x402_dev(&[
+        "verify", "req-1", "--amount", "0.05", "--mode", "failure", "--json",
+    ])
+    .code(1)
+    .get_output()
+    .stdout
+    .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["verifier"], "simulated");
+    assert_eq!(report["outcome"], "rejected");
+    assert_eq!(report["verified"], false);
+}
+
+#[actix_web::test]
+async fn test_verify_rpc_proof() {
+    // Given: a mock with a fake RPC and an invoice issued by it
+    let rpc = RpcConfig {
+        behaviors: [("short-".to_string(), RpcBehavior::WrongAmount)]
+            .into_iter()
+            .collect(),
+    };
+    let config = Config::builder()
+        .port(free_port())
+        .default_price(0.01)
+        .rpc(rpc)
+        .build()
+        .unwrap();
+    let base = serve(config).await;
+    let invoice: serde_json::Value = reqwest::get(format!("{}/api/data", base))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let memo = invoice["invoice"]["memo"].as_str().unwrap().to_string();
+    let rpc_url = format!("{}/rpc", base);
+
+    // When: its signature is verified over RPC
+    // Then: the settled transfer pays the invoice
+    let output = x402_dev(&[
+        "verify",
+        &memo,
+        "--amount",
+        "0.01",
+        "--memo",
+        &memo,
+        "--verifier",
+        "rpc",
+        "--rpc-url",
+        &rpc_url,
+        "--json",
+    ])
+    .success()
+    .get_output()
+    .stdout
+    .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["verifier"], "rpc");
+    assert_eq!(report["outcome"], "verified");
+    assert_eq!(report["memo"], memo.as_str());
+
+    // A transfer of half the amount is an underpayment
+    let short = format!("short-{}", memo);
+    x402_dev(&[
+        "verify",
+        &short,
+        "--amount",
+        "0.01",
+        "--verifier",
+        "rpc",
+        "--rpc-url",
+        &rpc_url,
+    ])
+    .code(1)
+    .stdout(predicate::str::contains(
+        "Payment not verified: underpayment (rpc",
+    ));
+}
@@ -0,0 +1,80 @@
+// `--report` and `verify-report` tests
+//
+// `test --report` writes canonical JSON with a content hash. The hash
+// survives reformatting the file but not editing a value, which
+// `verify-report` reports with exit code 1.
+
+use std::time::Duration;
+
+fn x402_dev(dir: &std::path::Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    assert_cmd::cargo::cargo_bin_cmd!("x402-dev")
+        .current_dir(dir)
+        .args(args)
+        .timeout(Duration::from_secs(20))
+        .assert()
+}
+
+/// Run a one-test suite against a closed port with `--report results.json`
+fn write_report(dir: &std::path::Path) -> String {
+    std::fs::write(
+        dir.join("suite.yaml"),
+        "tests:\n  - name: unreachable\n    url: http://127.0.0.1:1/api\n    expect:\n      status: 402\n",
+    )
+    .unwrap();
+    x402_dev(
+        dir,
+        &["test", "suite.yaml", "--quiet", "--report", "results.json"],
+    )
+    .code(1);
+    std::fs::read_to_string(dir.join("results.json")).unwrap()
+}
+
+#[test]
+fn test_report_verifies_after_reformatting() {
+    // Given: A report written by `test --report`
+    let dir = tempfile::tempdir().unwrap();
+    let report = write_report(dir.path());
+    assert!(
+        report.starts_with("{\n  \"content_hash\": \"sha256:"),
+        "{}",
+        report
+    );
+
+    // When/Then: The report as written verifies
+    x402_dev(dir.path(), &["verify-report", "results.json"])
+        .success()
+        .stdout(predicates::str::contains("content hash matches"));
+
+    // When/Then: Compact JSON of the same content verifies too
+    let value: serde_json::Value = serde_json::from_str(&report).unwrap();
+    std::fs::write(dir.path().join("compact.json"), value.to_string()).unwrap();
+    x402_dev(dir.path(), &["verify-report", "compact.json"]).success();
+}
+
+#[test]
+fn test_edited_report_fails_verification() {
+    // Given: A report whose failed count was edited by hand
+    let dir = tempfile::tempdir().unwrap();
+    let report = write_report(dir.path());
+    let edited = report.replace("\"failed\": 1", "\"failed\": 0");
+    assert_ne!(edited, report);
+    std::fs::write(dir.path().join("results.json"), edited).unwrap();
+
+    // When: Verifying it
+    let assert = x402_dev(dir.path(), &["verify-report", "results.json", "--json"]).code(1);
+
+    // Then: The recorded hash no longer matches the content
+    let output: serde_json::Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    assert_eq!(output["valid"], false);
+    assert_ne!(output["recorded"], output["computed"]);
+}
+
+#[test]
+fn test_report_without_hash_is_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("plain.json"), "{\"passed\": 1}").unwrap();
+
+    x402_dev(dir.path(), &["verify-report", "plain.json"])
+        .failure()
+        .stderr(predicates::str::contains("no content_hash"));
+}
@@ -1,11 +1,104 @@
-// DISABLED: Pure Rust implementation (ADR-001)
-// TypeScript runtime integration removed - see docs/epics.md Story 1.8
+// Build script for x402-core
 //
-// This build.rs previously bundled TypeScript code for deno_core integration.
-// Kept for reference but disabled per KISS refactoring (2025-11-10).
-// Can be re-enabled in ~2 hours if requirements change post-hackathon.
+// Compiles src/explain/catalog.yaml into the explanation table behind
+// `x402-dev explain`, and fails the build when a stable error code has no
+// explanation: every catalog error in src/i18n/en.yaml (a key with a
+// `.fix` entry) and every policy rule code in src/policy/codes.rs.
+//
+// The TypeScript bundling this script once did was removed with the pure
+// Rust implementation (ADR-001).
+
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::path::Path;
+
+const CATALOG: &str = "src/explain/catalog.yaml";
+const MESSAGES: &str = "src/i18n/en.yaml";
+const RULE_CODES: &str = "src/policy/codes.rs";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Entry {
+    title: String,
+    description: String,
+    causes: Vec<String>,
+    fixes: Vec<String>,
+    #[serde(default)]
+    config: Vec<String>,
+}
 
 fn main() {
-    // No build steps needed for pure Rust implementation
-    println!("cargo:warning=Pure Rust build (no TypeScript bundling)");
+    for path in [CATALOG, MESSAGES, RULE_CODES] {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+
+    let catalog: serde_yaml::Mapping = read_yaml(CATALOG);
+    let mut entries = Vec::new();
+    for (code, entry) in catalog {
+        let code = code
+            .as_str()
+            .expect("catalog codes are strings")
+            .to_string();
+        let entry: Entry = serde_yaml::from_value(entry)
+            .unwrap_or_else(|e| panic!("{}: invalid entry for {}: {}", CATALOG, code, e));
+        if entry.causes.is_empty() || entry.fixes.is_empty() {
+            panic!("{}: {} needs at least one cause and one fix", CATALOG, code);
+        }
+        entries.push((code, entry));
+    }
+
+    let mut missing: Vec<String> = required_codes()
+        .into_iter()
+        .filter(|code| !entries.iter().any(|(known, _)| known == code))
+        .collect();
+    missing.sort();
+    if !missing.is_empty() {
+        panic!(
+            "error codes without an explanation in {}: {}",
+            CATALOG,
+            missing.join(", ")
+        );
+    }
+
+    let mut out = String::from("/// Explanations compiled from explain/catalog.yaml\n");
+    out.push_str("pub(crate) const CATALOG: &[Explanation] = &[\n");
+    for (code, entry) in &entries {
+        writeln!(
+            out,
+            "    Explanation {{ code: {:?}, title: {:?}, description: {:?}, causes: &{:?}, fixes: &{:?}, config_keys: &{:?} }},",
+            code, entry.title, entry.description, entry.causes, entry.fixes, entry.config
+        )
+        .unwrap();
+    }
+    out.push_str("];\n");
+
+    let out_dir = std::env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    std::fs::write(Path::new(&out_dir).join("explain_catalog.rs"), out)
+        .expect("write explain_catalog.rs");
+}
+
+fn read_yaml<T: serde::de::DeserializeOwned>(path: &str) -> T {
+    let text = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+    serde_yaml::from_str(&text).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+/// Codes that must be explained
+fn required_codes() -> Vec<String> {
+    // Catalog errors: message keys that come with a fix suggestion
+    let messages: serde_yaml::Mapping = read_yaml(MESSAGES);
+    let keys: Vec<&str> = messages.keys().filter_map(|k| k.as_str()).collect();
+    let mut codes: Vec<String> = keys
+        .iter()
+        .filter(|key| keys.contains(&format!("{}.fix", key).as_str()))
+        .map(|key| key.to_string())
+        .collect();
+
+    // Policy validation and lint codes: the `RuleCode::as_str` arms
+    // (`=> "X402-P001",`, `=> "X402-L001",`)
+    let rules = std::fs::read_to_string(RULE_CODES).expect("read policy rule codes");
+    codes.extend(rules.lines().filter_map(|line| {
+        let code = line.trim().split_once("=> \"")?.1.strip_suffix("\",")?;
+        (code.starts_with("X402-P") || code.starts_with("X402-L")).then(|| code.to_string())
+    }));
+    codes
 }
@@ -0,0 +1,181 @@
+// Canonical JSON for report files
+//
+// Test, compliance and scenario reports are diffed between CI runs and
+// cached by content, so the same logical report must serialize to the same
+// bytes. Canonical JSON sorts object keys, writes non-integer numbers as
+// decimal strings and RFC3339 timestamps in UTC with millisecond precision.
+// The SHA-256 of the compact canonical bytes is embedded as `content_hash`,
+// the first key of the written report, and `x402-dev verify-report`
+// recomputes it to detect edits.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+/// Key of the content hash in a canonical report
+pub const CONTENT_HASH_FIELD: &str = "content_hash";
+
+/// Prefix of the content hash value, naming the algorithm
+const HASH_PREFIX: &str = "sha256:";
+
+/// Normalize `value`: sorted keys, decimal strings for non-integer numbers
+/// and millisecond UTC timestamps
+pub fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, canonicalize(value)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        Value::Number(n) if !n.is_i64() && !n.is_u64() => match n.as_f64() {
+            Some(f) => Value::String(f.to_string()),
+            None => Value::Number(n),
+        },
+        Value::String(s) => match DateTime::parse_from_rfc3339(&s) {
+            Ok(time) => Value::String(
+                time.with_timezone(&Utc)
+                    .to_rfc3339_opts(SecondsFormat::Millis, true),
+            ),
+            Err(_) => Value::String(s),
+        },
+        other => other,
+    }
+}
+
+/// `sha256:<hex>` of the compact canonical form of `report`, ignoring any
+/// `content_hash` it already has
+pub fn content_hash(report: &Value) -> String {
+    let mut report = report.clone();
+    if let Value::Object(map) = &mut report {
+        map.remove(CONTENT_HASH_FIELD);
+    }
+    let bytes = serde_json::to_vec(&canonicalize(report)).unwrap_or_default();
+    let hex: String = Sha256::digest(&bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{}{}", HASH_PREFIX, hex)
+}
+
+/// Pretty canonical JSON of `report` with its `content_hash` as first key
+///
+/// The report must serialize to a JSON object.
+pub fn to_canonical_string<T: Serialize>(report: &T) -> Result<String> {
+    let mut value = serde_json::to_value(report).context("Failed to serialize report")?;
+    let Value::Object(map) = &mut value else {
+        bail!("A canonical report must be a JSON object");
+    };
+    map.remove(CONTENT_HASH_FIELD);
+    let hash = content_hash(&value);
+    let body = serde_json::to_string_pretty(&canonicalize(value))?;
+    let hash_line = format!("  \"{}\": \"{}\"", CONTENT_HASH_FIELD, hash);
+    Ok(match body.strip_prefix("{\n") {
+        Some(rest) => format!("{{\n{},\n{}", hash_line, rest),
+        None => format!("{{\n{}\n}}", hash_line),
+    })
+}
+
+/// Recorded and recomputed content hash of a report
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HashVerification {
+    pub recorded: String,
+    pub computed: String,
+}
+
+impl HashVerification {
+    /// True if the report was not changed since it was written
+    pub fn matches(&self) -> bool {
+        self.recorded == self.computed
+    }
+}
+
+/// Recompute the content hash of the report in `text`
+///
+/// Fails if `text` is not a JSON object or has no `content_hash`.
+pub fn verify_report(text: &str) -> Result<HashVerification> {
+    let value: Value = serde_json::from_str(text).context("Report is not valid JSON")?;
+    let recorded = match value.get(CONTENT_HASH_FIELD) {
+        Some(Value::String(hash)) => hash.clone(),
+        _ => bail!(
+            "Report has no {} field\nFix: Write it with a report option such as `x402-dev test --report` or `x402-dev check --report`",
+            CONTENT_HASH_FIELD
+        ),
+    };
+    Ok(HashVerification {
+        recorded,
+        computed: content_hash(&value),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_identical_content_gives_identical_bytes() {
+        // HashMaps built in different orders iterate differently
+        let first: HashMap<&str, u32> = (0..50)
+            .map(|i| (["a", "b", "c", "d"][i % 4], i as u32))
+            .collect();
+        let mut second: HashMap<&str, u32> = HashMap::new();
+        for key in ["d", "c", "b", "a"] {
+            second.insert(key, first[key]);
+        }
+        let report = |counts| json!({ "url": "http://localhost:3402", "counts": counts });
+
+        assert_eq!(
+            to_canonical_string(&report(&first)).unwrap(),
+            to_canonical_string(&report(&second)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_numbers_and_timestamps_are_normalized() {
+        let value = canonicalize(json!({
+            "amount": 0.01,
+            "count": 3,
+            "at": "2026-03-01T13:00:00.123456+01:00",
+            "memo": "req-1",
+        }));
+        assert_eq!(value["amount"], "0.01");
+        assert_eq!(value["count"], 3);
+        assert_eq!(value["at"], "2026-03-01T12:00:00.123Z");
+        assert_eq!(value["memo"], "req-1");
+    }
+
+    #[test]
+    fn test_hash_is_first_and_verifies() {
+        let text = to_canonical_string(&json!({ "passed": 2, "failed": 0 })).unwrap();
+        assert!(
+            text.starts_with("{\n  \"content_hash\": \"sha256:"),
+            "{}",
+            text
+        );
+        assert!(verify_report(&text).unwrap().matches());
+
+        let edited = text.replace("\"passed\": 2", "\"passed\": 3");
+        let verification = verify_report(&edited).unwrap();
+        assert!(!verification.matches());
+        assert_eq!(
+            verification.recorded,
+            verify_report(&text).unwrap().recorded
+        );
+    }
+
+    #[test]
+    fn test_missing_hash_is_an_error() {
+        let err = verify_report("{\"passed\": 2}").unwrap_err();
+        assert!(err.to_string().contains("no content_hash"));
+        assert!(to_canonical_string(&json!([1, 2])).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+// Clocks for policy windows and invoice deadlines
+//
+// The policy engine and the mock server read the time through a `Clock`.
+// `SystemClock` is the real time and the default. `VirtualClock` runs at
+// real speed from an offset that can be moved forward, so tests can jump
+// past a rate limit window or an invoice expiry without sleeping
+// (`x402-dev mock --allow-time-travel`, `POST /__admin/clock/advance`).
+
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// Source of the current time
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> SystemTime;
+
+    /// [`now`](Self::now) as a UTC date-time
+    fn now_utc(&self) -> DateTime<Utc> {
+        self.now().into()
+    }
+}
+
+/// The real time
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Real time shifted by an offset that [`advance`](Self::advance) moves
+#[derive(Debug, Default)]
+pub struct VirtualClock {
+    /// Milliseconds added to the real time (negative for a past start)
+    offset_ms: AtomicI64,
+}
+
+impl VirtualClock {
+    /// Clock starting at the real time
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clock reading `at` now and running on from there
+    pub fn starting_at(at: SystemTime) -> Self {
+        let offset_ms = match at.duration_since(SystemTime::now()) {
+            Ok(ahead) => millis(ahead),
+            Err(behind) => -millis(behind.duration()),
+        };
+        Self {
+            offset_ms: AtomicI64::new(offset_ms),
+        }
+    }
+
+    /// Jump `by` into the future; returns the new current time
+    pub fn advance(&self, by: Duration) -> SystemTime {
+        self.offset_ms.fetch_add(millis(by), Ordering::SeqCst);
+        self.now()
+    }
+
+    /// Total shift from the real time, in milliseconds
+    pub fn offset_ms(&self) -> i64 {
+        self.offset_ms.load(Ordering::SeqCst)
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> SystemTime {
+        let offset = self.offset_ms();
+        let shift = Duration::from_millis(offset.unsigned_abs());
+        if offset >= 0 {
+            SystemTime::now() + shift
+        } else {
+            SystemTime::now() - shift
+        }
+    }
+}
+
+fn millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs_between(a: SystemTime, b: SystemTime) -> i64 {
+        match b.duration_since(a) {
+            Ok(d) => d.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        }
+    }
+
+    #[test]
+    fn test_virtual_clock_advances() {
+        let clock = VirtualClock::new();
+        assert!(secs_between(SystemTime::now(), clock.now()).abs() <= 1);
+
+        clock.advance(Duration::from_secs(3600));
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(clock.offset_ms(), 3_600_500);
+        let ahead = secs_between(SystemTime::now(), clock.now());
+        assert!((3599..=3601).contains(&ahead), "{}s", ahead);
+    }
+
+    #[test]
+    fn test_virtual_clock_starting_in_the_past() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let clock = VirtualClock::starting_at(start);
+        assert!(secs_between(start, clock.now()).abs() <= 1);
+        assert!(clock.offset_ms() < 0);
+
+        clock.advance(Duration::from_secs(60));
+        assert!((59..=61).contains(&secs_between(start, clock.now())));
+        assert!((clock.now_utc().timestamp() - 1_700_000_060).abs() <= 1);
+    }
+}
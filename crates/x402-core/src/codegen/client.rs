@@ -0,0 +1,324 @@
+// TypeScript client SDK generator
+//
+// Emits a fetch wrapper that handles the 402 flow: parse the
+// WWW-Authenticate invoice, call the app's pay(invoice) callback, retry with
+// the X-Payment-Proof header, and map error responses to typed errors.
+// Invoice fields come from the shared header definitions so the generated
+// parser stays in step with the server and the check command.
+
+pub use crate::testing::PAYMENT_PROOF_HEADER;
+use crate::testing::{CANONICAL_FIELDS, REQUIRED_FIELDS, SIGNATURE_FIELDS};
+use chrono::Utc;
+use std::collections::BTreeMap;
+
+/// Pricing embedded in a generated client
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientSpec {
+    /// Price of paths without a per-resource entry
+    pub default_price: f64,
+    /// Per-resource prices, exact paths or `prefix/*` wildcards
+    pub prices: BTreeMap<String, f64>,
+}
+
+/// Generate the TypeScript client for `spec`
+pub fn generate_typescript_client(spec: &ClientSpec) -> String {
+    let mut code = String::new();
+
+    code.push_str(&generate_header());
+    code.push('\n');
+    code.push_str(&generate_invoice_types());
+    code.push('\n');
+    code.push_str(ERROR_TYPES);
+    code.push('\n');
+    code.push_str(&generate_price_map(spec));
+    code.push('\n');
+    code.push_str(INVOICE_PARSER);
+    code.push('\n');
+    code.push_str(FETCH_WRAPPER);
+
+    code
+}
+
+fn generate_header() -> String {
+    format!(
+        r#"// Generated by: x402-dev generate client --language ts
+// Generated: {}
+// DO NOT EDIT THIS FILE MANUALLY - Regenerate after changing pricing
+
+export const X402_SCHEME = "x402-solana";
+export const PAYMENT_PROOF_HEADER = "{}";
+"#,
+        Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+        PAYMENT_PROOF_HEADER
+    )
+}
+
+fn generate_invoice_types() -> String {
+    let mut code = String::from(
+        "/** Invoice from a `WWW-Authenticate: x402-solana ...` header */\nexport interface Invoice {\n",
+    );
+    let optional: Vec<&str> = CANONICAL_FIELDS
+        .iter()
+        .filter(|field| !REQUIRED_FIELDS.contains(field))
+        .chain(&SIGNATURE_FIELDS)
+        .copied()
+        .collect();
+    for field in REQUIRED_FIELDS {
+        code.push_str(&format!("  {}: string;\n", field));
+    }
+    for field in &optional {
+        code.push_str(&format!("  {}?: string;\n", field));
+    }
+    code.push_str("}\n\n");
+
+    code.push_str(&format!(
+        "/** Fields every invoice must carry */\nexport const INVOICE_FIELDS = [{}] as const;\n",
+        quoted_list(&REQUIRED_FIELDS)
+    ));
+    code.push_str(&format!(
+        "const OPTIONAL_INVOICE_FIELDS = [{}] as const;\n",
+        quoted_list(&optional)
+    ));
+    code
+}
+
+fn generate_price_map(spec: &ClientSpec) -> String {
+    let mut code = format!(
+        "/** Price of paths without a per-resource entry */\nexport const DEFAULT_PRICE = \"{}\";\n\n",
+        spec.default_price
+    );
+
+    code.push_str(
+        "/** Expected invoice amount per configured path (`*` suffix = prefix match) */\n",
+    );
+    code.push_str("export const PRICES = {\n");
+    for (path, price) in &spec.prices {
+        code.push_str(&format!("  {:?}: \"{}\",\n", path, price));
+    }
+    code.push_str("} as const;\n\n");
+
+    code.push_str(
+        r#"export type PricedPath = keyof typeof PRICES;
+
+/** Expected price for a request path: exact match, longest wildcard, then default */
+export function expectedPrice(path: PricedPath | string): string {
+  const prices: Record<string, string> = PRICES;
+  if (path in prices) {
+    return prices[path];
+  }
+  let best: string | undefined;
+  for (const pattern of Object.keys(prices)) {
+    if (pattern.endsWith("*") && path.startsWith(pattern.slice(0, -1))) {
+      if (best === undefined || pattern.length > best.length) {
+        best = pattern;
+      }
+    }
+  }
+  return best === undefined ? DEFAULT_PRICE : prices[best];
+}
+"#,
+    );
+    code
+}
+
+fn quoted_list(fields: &[&str]) -> String {
+    fields
+        .iter()
+        .map(|f| format!("\"{}\"", f))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+const ERROR_TYPES: &str = r#"/** Error envelope returned by the x402 server (`{ error, message, ... }`) */
+export interface X402ErrorBody {
+  error?: string;
+  message?: string;
+  reason?: string;
+  policy_id?: string;
+  [key: string]: unknown;
+}
+
+export type X402ErrorKind =
+  | "invalid_invoice"
+  | "payment_rejected"
+  | "policy_denied"
+  | "bad_request"
+  | "timeout"
+  | "http";
+
+/** Typed failure of an x402 request */
+export class X402Error extends Error {
+  constructor(
+    readonly kind: X402ErrorKind,
+    readonly status: number,
+    message: string,
+    readonly body?: X402ErrorBody,
+    readonly invoice?: Invoice,
+  ) {
+    super(message);
+    this.name = "X402Error";
+  }
+}
+
+async function errorFromResponse(response: Response, invoice?: Invoice): Promise<X402Error> {
+  let body: X402ErrorBody | undefined;
+  try {
+    body = (await response.json()) as X402ErrorBody;
+  } catch {
+    body = undefined;
+  }
+  const kind: X402ErrorKind =
+    response.status === 402
+      ? "payment_rejected"
+      : response.status === 403
+        ? "policy_denied"
+        : response.status === 400
+          ? "bad_request"
+          : response.status === 408
+            ? "timeout"
+            : "http";
+  const message = body?.message ?? body?.reason ?? body?.error ?? response.statusText;
+  return new X402Error(kind, response.status, message, body, invoice);
+}
+"#;
+
+const INVOICE_PARSER: &str = r#"/** Parse a WWW-Authenticate header value into an Invoice */
+export function parseInvoice(header: string): Invoice {
+  const trimmed = header.trim();
+  if (!trimmed.startsWith(X402_SCHEME + " ")) {
+    throw new X402Error("invalid_invoice", 402, `Expected a ${X402_SCHEME} invoice, got: ${trimmed}`);
+  }
+
+  const fields: Record<string, string> = {};
+  const param = /([^\s=]+)=(?:"((?:[^"\\]|\\.)*)"|(\S*))/g;
+  for (const match of trimmed.slice(X402_SCHEME.length).matchAll(param)) {
+    const [, name, quoted, bare] = match;
+    if (!(name in fields)) {
+      fields[name] = quoted !== undefined ? quoted.replace(/\\(.)/g, "$1") : bare;
+    }
+  }
+
+  const missing = INVOICE_FIELDS.filter((field) => fields[field] === undefined);
+  if (missing.length > 0) {
+    throw new X402Error("invalid_invoice", 402, `Invoice is missing: ${missing.join(", ")}`);
+  }
+
+  const invoice = {} as Invoice;
+  for (const field of INVOICE_FIELDS) {
+    invoice[field] = fields[field];
+  }
+  for (const field of OPTIONAL_INVOICE_FIELDS) {
+    if (fields[field] !== undefined) {
+      invoice[field] = fields[field];
+    }
+  }
+  return invoice;
+}
+"#;
+
+const FETCH_WRAPPER: &str = r#"/** Pays an invoice and resolves to the payment proof sent on retry */
+export type PayCallback = (invoice: Invoice) => Promise<string>;
+
+export interface X402ClientOptions {
+  /** Called for each 402 invoice; resolves to the payment proof */
+  pay: PayCallback;
+  /** Paid retries before giving up (default 1) */
+  maxRetries?: number;
+  /** fetch implementation (default: global fetch) */
+  fetch?: typeof fetch;
+}
+
+/**
+ * fetch with the x402 payment flow
+ *
+ * On 402 the invoice is parsed, passed to `pay`, and the request is retried
+ * with the proof in the X-Payment-Proof header. Non-2xx responses that
+ * remain are thrown as X402Error.
+ */
+export async function x402Fetch(
+  input: string | URL,
+  init: RequestInit,
+  options: X402ClientOptions,
+): Promise<Response> {
+  const doFetch = options.fetch ?? fetch;
+  const maxRetries = options.maxRetries ?? 1;
+
+  let response = await doFetch(input, init);
+  let invoice: Invoice | undefined;
+  for (let attempt = 0; response.status === 402 && attempt < maxRetries; attempt++) {
+    const header = response.headers.get("WWW-Authenticate");
+    if (header === null) {
+      throw await errorFromResponse(response);
+    }
+    invoice = parseInvoice(header);
+    const proof = await options.pay(invoice);
+
+    const headers = new Headers(init.headers);
+    headers.set(PAYMENT_PROOF_HEADER, proof);
+    response = await doFetch(input, { ...init, headers });
+  }
+
+  if (!response.ok) {
+    throw await errorFromResponse(response, invoice);
+  }
+  return response;
+}
+
+/** Client bound to one pay callback */
+export function createX402Client(options: X402ClientOptions) {
+  return {
+    fetch: (input: string | URL, init: RequestInit = {}) => x402Fetch(input, init, options),
+    expectedPrice,
+  };
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> ClientSpec {
+        ClientSpec {
+            default_price: 0.01,
+            prices: BTreeMap::from([
+                ("/api/premium".to_string(), 0.05),
+                ("/api/admin/*".to_string(), 0.02),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_emits_retry_loop() {
+        let code = generate_typescript_client(&fixture());
+        assert!(code.contains("response.status === 402 && attempt < maxRetries"));
+        assert!(code.contains("const proof = await options.pay(invoice);"));
+        assert!(code.contains("headers.set(PAYMENT_PROOF_HEADER, proof);"));
+        assert!(code.contains(r#"export const PAYMENT_PROOF_HEADER = "X-Payment-Proof";"#));
+        assert!(code.contains("throw await errorFromResponse(response, invoice);"));
+    }
+
+    #[test]
+    fn test_emits_typed_invoice() {
+        let code = generate_typescript_client(&fixture());
+        assert!(code.contains("export interface Invoice {"));
+        for field in REQUIRED_FIELDS {
+            assert!(code.contains(&format!("  {}: string;\n", field)));
+        }
+        for field in ["expires", "signer", "signature"] {
+            assert!(code.contains(&format!("  {}?: string;\n", field)));
+        }
+        assert!(code.contains(
+            r#"export const INVOICE_FIELDS = ["recipient", "amount", "currency", "memo", "network"] as const;"#
+        ));
+        assert!(code.contains("export class X402Error extends Error"));
+    }
+
+    #[test]
+    fn test_emits_price_map() {
+        let code = generate_typescript_client(&fixture());
+        assert!(code.contains(r#"export const DEFAULT_PRICE = "0.01";"#));
+        assert!(code.contains(
+            "export const PRICES = {\n  \"/api/admin/*\": \"0.02\",\n  \"/api/premium\": \"0.05\",\n} as const;"
+        ));
+    }
+}
@@ -0,0 +1,5 @@
+// Code generation for x402 clients
+
+pub mod client;
+
+pub use client::{generate_typescript_client, ClientSpec};
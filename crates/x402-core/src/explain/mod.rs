@@ -0,0 +1,79 @@
+// Explanations of stable error codes, shown by `x402-dev explain <code>`
+//
+// The table is compiled from catalog.yaml by build.rs, which fails the build
+// when a catalog error (i18n) or policy rule code has no explanation. Codes
+// are looked up case-insensitively.
+
+use serde::Serialize;
+
+/// Documentation page of a code is this URL followed by the code
+pub const DOCS_BASE_URL: &str = "https://docs.x402-dev.com/errors/";
+
+/// What an error code means and how to fix it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    /// Common causes
+    pub causes: &'static [&'static str],
+    /// Fix steps, in order
+    pub fixes: &'static [&'static str],
+    /// Related config keys, e.g. `pricing.default`
+    pub config_keys: &'static [&'static str],
+}
+
+include!(concat!(env!("OUT_DIR"), "/explain_catalog.rs"));
+
+impl Explanation {
+    /// Documentation page of the code
+    pub fn link(&self) -> String {
+        format!("{}{}", DOCS_BASE_URL, self.code)
+    }
+}
+
+/// Every explanation, in catalog order
+pub fn all() -> &'static [Explanation] {
+    CATALOG
+}
+
+/// Explanation of `code`, ignoring case and surrounding whitespace
+pub fn lookup(code: &str) -> Option<&'static Explanation> {
+    let code = code.trim();
+    CATALOG.iter().find(|e| e.code.eq_ignore_ascii_case(code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::RuleCode;
+
+    #[test]
+    fn test_every_rule_code_is_explained() {
+        for code in RuleCode::ALL {
+            assert!(lookup(code.as_str()).is_some(), "{} is not explained", code);
+        }
+    }
+
+    #[test]
+    fn test_lookup_ignores_case() {
+        let explanation = lookup(" x402-p010 ").unwrap();
+        assert_eq!(explanation.code, "X402-P010");
+        assert_eq!(
+            explanation.link(),
+            "https://docs.x402-dev.com/errors/X402-P010"
+        );
+        assert!(lookup("E0000").is_none());
+    }
+
+    #[test]
+    fn test_codes_are_unique_and_complete() {
+        let mut codes: Vec<&str> = all().iter().map(|e| e.code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), all().len());
+        assert!(all()
+            .iter()
+            .all(|e| !e.description.is_empty() && !e.fixes.is_empty()));
+    }
+}
@@ -0,0 +1,298 @@
+// Document formats
+//
+// Every user-facing file (project config, policy files, test suites,
+// scenario bundles, populations) can be written as YAML or JSON with the
+// same schema. The format comes from the file extension (.yaml, .yml,
+// .json); without one of those, a document whose first character is `{` or
+// `[` is JSON and anything else YAML. JSON is parsed with serde_json rather
+// than as YAML so errors point at JSON positions.
+//
+// Converting between the two keeps every value and the key order, but not
+// comments, anchors or YAML tags: anchors are expanded on load and JSON has
+// no comments.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Serialization format of a document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl FileFormat {
+    /// Format named by the extension of `path` (`.yaml`, `.yml` or `.json`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Some(FileFormat::Yaml),
+            "json" => Some(FileFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the content: JSON if it starts with `{` or `[`
+    pub fn sniff(content: &str) -> Self {
+        match content
+            .trim_start_matches('\u{feff}')
+            .trim_start()
+            .chars()
+            .next()
+        {
+            Some('{' | '[') => FileFormat::Json,
+            _ => FileFormat::Yaml,
+        }
+    }
+
+    /// Conventional file extension, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            FileFormat::Yaml => "yaml",
+            FileFormat::Json => "json",
+        }
+    }
+
+    /// Parse `content` in this format
+    pub fn parse<T: DeserializeOwned>(self, content: &str) -> Result<T> {
+        match self {
+            FileFormat::Yaml => Ok(serde_yaml::from_str(content)?),
+            FileFormat::Json => Ok(serde_json::from_str(content)?),
+        }
+    }
+
+    /// Serialize `value` in this format; JSON is pretty-printed
+    pub fn render<T: Serialize + ?Sized>(self, value: &T) -> Result<String> {
+        match self {
+            FileFormat::Yaml => Ok(serde_yaml::to_string(value)?),
+            FileFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(value)?)),
+        }
+    }
+}
+
+impl fmt::Display for FileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FileFormat::Yaml => "YAML",
+            FileFormat::Json => "JSON",
+        })
+    }
+}
+
+impl FromStr for FileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Ok(FileFormat::Yaml),
+            "json" => Ok(FileFormat::Json),
+            _ => Err(format!("unknown format '{}' (expected yaml or json)", s)),
+        }
+    }
+}
+
+/// A document's format and how it was chosen, for error messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectedFormat {
+    pub format: FileFormat,
+    pub source: FormatSource,
+}
+
+/// What decided a document's format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatSource {
+    /// The file extension
+    Extension,
+    /// The first character of the content
+    Content,
+    /// The caller (e.g. an API that only takes YAML)
+    Given,
+}
+
+impl DetectedFormat {
+    /// Format of `content` read from `path`: the extension, else the content
+    pub fn detect(path: Option<&Path>, content: &str) -> Self {
+        match path.and_then(FileFormat::from_path) {
+            Some(format) => Self {
+                format,
+                source: FormatSource::Extension,
+            },
+            None => Self {
+                format: FileFormat::sniff(content),
+                source: FormatSource::Content,
+            },
+        }
+    }
+
+    /// A format the caller already knows
+    pub fn given(format: FileFormat) -> Self {
+        Self {
+            format,
+            source: FormatSource::Given,
+        }
+    }
+
+    /// Parse `content`; errors name the format, how it was detected and the
+    /// line and column where parsing failed
+    pub fn parse<T: DeserializeOwned>(self, content: &str) -> Result<T> {
+        self.format
+            .parse(content)
+            .map_err(|e| anyhow!("{}: {}", self, e))
+    }
+}
+
+impl fmt::Display for DetectedFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source {
+            FormatSource::Extension => {
+                write!(f, "{} parse error (from the file extension)", self.format)
+            }
+            FormatSource::Content => {
+                write!(f, "{} parse error (detected from content)", self.format)
+            }
+            FormatSource::Given => write!(f, "{} parse error", self.format),
+        }
+    }
+}
+
+/// Read and parse the document at `path` in whichever format it is written
+///
+/// Parse errors start with the path, then name the format and position.
+pub fn read_document<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    DetectedFormat::detect(Some(path), &content)
+        .parse(&content)
+        .map_err(|e| anyhow!("{}: {}", path.display(), e))
+}
+
+/// Translate a document between formats, keeping values and key order
+///
+/// Comments are dropped and YAML anchors are expanded.
+pub fn convert(content: &str, from: DetectedFormat, to: FileFormat) -> Result<String> {
+    let value: serde_yaml::Value = from.parse(content)?;
+    if to == FileFormat::Json {
+        check_json_compatible(&value, "")?;
+    }
+    to.render(&value)
+}
+
+/// Reject what JSON cannot represent: non-string keys and tagged values
+fn check_json_compatible(value: &serde_yaml::Value, at: &str) -> Result<()> {
+    use serde_yaml::Value;
+    match value {
+        Value::Mapping(map) => {
+            for (key, item) in map {
+                let Some(key) = key.as_str() else {
+                    bail!(
+                        "{}: key {:?} is not a string and cannot be written as JSON\nFix: Quote the key",
+                        display_location(at),
+                        key
+                    );
+                };
+                check_json_compatible(item, &format!("{}.{}", at, key))?;
+            }
+        }
+        Value::Sequence(items) => {
+            for (i, item) in items.iter().enumerate() {
+                check_json_compatible(item, &format!("{}[{}]", at, i))?;
+            }
+        }
+        Value::Tagged(tagged) => bail!(
+            "{}: YAML tag {} cannot be written as JSON\nFix: Remove the tag",
+            display_location(at),
+            tagged.tag
+        ),
+        Value::Number(n) if n.as_f64().is_some_and(|f| !f.is_finite()) => bail!(
+            "{}: {} cannot be written as JSON\nFix: Use a finite number",
+            display_location(at),
+            n
+        ),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn display_location(at: &str) -> &str {
+    match at.strip_prefix('.') {
+        Some(path) => path,
+        None if at.is_empty() => "document root",
+        None => at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_detect_by_extension_then_content() {
+        let json = DetectedFormat::detect(Some(Path::new("p.JSON")), "a: 1");
+        assert_eq!(json.format, FileFormat::Json);
+        assert_eq!(json.source, FormatSource::Extension);
+
+        let yml = DetectedFormat::detect(Some(Path::new("p.yml")), "{}");
+        assert_eq!(yml.format, FileFormat::Yaml);
+
+        for (content, format) in [
+            ("\u{feff}  {\"a\": 1}", FileFormat::Json),
+            ("\n[1, 2]", FileFormat::Json),
+            ("# comment\na: 1", FileFormat::Yaml),
+            ("", FileFormat::Yaml),
+        ] {
+            let detected = DetectedFormat::detect(Some(Path::new(".x402dev")), content);
+            assert_eq!(detected.format, format, "{:?}", content);
+            assert_eq!(detected.source, FormatSource::Content);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors_name_format_and_position() {
+        let detected = DetectedFormat::detect(Some(Path::new("p.json")), "");
+        let err = detected
+            .parse::<BTreeMap<String, u32>>("{\n  \"a\": 1,\n  \"b\": x\n}")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("JSON parse error (from the file extension)"));
+        assert!(err.contains("line 3 column 8"), "{}", err);
+
+        let detected = DetectedFormat::detect(None, "a: [1");
+        let err = detected
+            .parse::<BTreeMap<String, u32>>("a: [1")
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("YAML parse error (detected from content)"));
+        assert!(err.contains("line 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_convert_round_trip_keeps_order() {
+        let yaml =
+            "# comment\nzeta: 1\nalpha:\n  - 0.5\n  - \"text\"\nnested: {b: true, a: null}\n";
+        let yaml_in = DetectedFormat::detect(Some(Path::new("a.yaml")), yaml);
+        let json = convert(yaml, yaml_in, FileFormat::Json).unwrap();
+        assert!(json.find("zeta").unwrap() < json.find("alpha").unwrap());
+
+        let json_in = DetectedFormat::detect(Some(Path::new("a.json")), &json);
+        let back = convert(&json, json_in, FileFormat::Yaml).unwrap();
+        let original: serde_yaml::Value = serde_yaml::from_str(yaml).unwrap();
+        let round_tripped: serde_yaml::Value = serde_yaml::from_str(&back).unwrap();
+        assert_eq!(original, round_tripped);
+    }
+
+    #[test]
+    fn test_convert_rejects_what_json_cannot_hold() {
+        let from = DetectedFormat::detect(Some(Path::new("a.yaml")), "");
+        let err = convert("a:\n  1: x\n", from, FileFormat::Json).unwrap_err();
+        assert!(err.to_string().starts_with("a: key"), "{}", err);
+
+        let err = convert("a: !secret x\n", from, FileFormat::Json).unwrap_err();
+        assert!(err.to_string().contains("!secret"), "{}", err);
+    }
+}
@@ -0,0 +1,340 @@
+// Message catalog for user-facing error and suggestion strings
+//
+// Strings are referenced by stable keys and rendered from per-locale YAML
+// catalogs compiled into the binary. English is the source of truth:
+// Display impls and machine-readable output always use it, and any key
+// missing from another locale falls back to it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+/// Environment variable selecting the display locale (e.g. `es`, `ja-JP`)
+pub const LANG_ENV: &str = "X402_DEV_LANG";
+
+const EN: &str = include_str!("en.yaml");
+const ES: &str = include_str!("es.yaml");
+const JA: &str = include_str!("ja.yaml");
+
+static CURRENT_LOCALE: AtomicU8 = AtomicU8::new(Locale::En as u8);
+static DEBUG: AtomicBool = AtomicBool::new(false);
+
+/// Supported display locales
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    #[default]
+    En = 0,
+    Es = 1,
+    Ja = 2,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 3] = [Locale::En, Locale::Es, Locale::Ja];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+            Locale::Ja => "ja",
+        }
+    }
+
+    /// Pick the best supported locale from an Accept-Language style list
+    ///
+    /// Accepts `es`, `es-MX`, `ja_JP.UTF-8` and weighted lists such as
+    /// `fr;q=1.0, ja;q=0.8, en;q=0.5`. Returns `None` if nothing matches.
+    pub fn negotiate(header: &str) -> Option<Locale> {
+        let mut candidates: Vec<(f32, Locale)> = header
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';');
+                let locale = parts.next()?.trim().parse().ok()?;
+                let quality = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.trim().parse().ok())
+                    .unwrap_or(1.0);
+                (quality > 0.0).then_some((quality, locale))
+            })
+            .collect();
+
+        // Stable sort keeps list order among equal weights
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+        candidates.first().map(|(_, locale)| *locale)
+    }
+
+    /// Resolve the display locale: `--lang` first, then `X402_DEV_LANG`, then English
+    pub fn resolve(cli_lang: Option<&str>, env_lang: Option<&str>) -> Locale {
+        cli_lang
+            .and_then(Locale::negotiate)
+            .or_else(|| env_lang.and_then(Locale::negotiate))
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Primary subtag of `es-MX`, `ja_JP.UTF-8`, ...
+        let primary = s
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Ok(Locale::En),
+            "es" => Ok(Locale::Es),
+            "ja" => Ok(Locale::Ja),
+            _ => Err(format!(
+                "Unsupported language: {}. Supported languages: en, es, ja",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// Set the process-wide display locale
+pub fn set_locale(locale: Locale) {
+    CURRENT_LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+/// Current process-wide display locale
+pub fn locale() -> Locale {
+    match CURRENT_LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::Es,
+        2 => Locale::Ja,
+        _ => Locale::En,
+    }
+}
+
+/// Log missing translations to stderr (enabled by the CLI's --debug)
+pub fn set_debug(enabled: bool) {
+    DEBUG.store(enabled, Ordering::Relaxed);
+}
+
+fn catalog(locale: Locale) -> &'static HashMap<String, String> {
+    static CATALOGS: OnceLock<HashMap<Locale, HashMap<String, String>>> = OnceLock::new();
+    let catalogs = CATALOGS.get_or_init(|| {
+        Locale::ALL
+            .iter()
+            .map(|&locale| {
+                let source = match locale {
+                    Locale::En => EN,
+                    Locale::Es => ES,
+                    Locale::Ja => JA,
+                };
+                let entries = serde_yaml::from_str(source).unwrap_or_else(|e| {
+                    panic!("Embedded {} message catalog is invalid: {}", locale, e)
+                });
+                (locale, entries)
+            })
+            .collect()
+    });
+    &catalogs[&locale]
+}
+
+/// Template for `key` in `locale`, falling back to English
+fn template(locale: Locale, key: &str) -> Option<&'static str> {
+    lookup(locale, key, catalog(locale), catalog(Locale::En))
+}
+
+fn lookup<'a>(
+    locale: Locale,
+    key: &str,
+    localized: &'a HashMap<String, String>,
+    english: &'a HashMap<String, String>,
+) -> Option<&'a str> {
+    if let Some(text) = localized.get(key) {
+        return Some(text);
+    }
+    if locale != Locale::En && DEBUG.load(Ordering::Relaxed) {
+        eprintln!(
+            "debug: no '{}' translation for message '{}', using English",
+            locale, key
+        );
+    }
+    english.get(key).map(String::as_str)
+}
+
+/// A catalog message: a stable key plus named `{placeholder}` arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    key: &'static str,
+    args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            args: Vec::new(),
+        }
+    }
+
+    /// Fill the `{name}` placeholder
+    pub fn arg(mut self, name: &'static str, value: impl fmt::Display) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    /// Render in `locale`; unknown keys render as the key itself
+    pub fn render(&self, locale: Locale) -> String {
+        let Some(template) = template(locale, self.key) else {
+            return self.key.to_string();
+        };
+        self.args
+            .iter()
+            .fold(template.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+    }
+
+    /// Render in the process-wide locale
+    pub fn localized(&self) -> String {
+        self.render(locale())
+    }
+}
+
+/// English rendering, for logs and machine-readable output
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(Locale::En))
+    }
+}
+
+/// An error whose message and fix suggestion come from the catalog
+///
+/// The fix is the `<key>.fix` entry when the catalog has one. Display is
+/// always English ("message\nFix: fix") so existing callers and JSON stay
+/// stable; the CLI error printer renders it in the selected locale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogError {
+    pub message: Message,
+    pub fix: Option<Message>,
+}
+
+impl CatalogError {
+    pub fn new(message: Message) -> Self {
+        let fix_key = format!("{}.fix", message.key);
+        let fix = catalog(Locale::En)
+            .get_key_value(&fix_key)
+            .map(|(key, _)| Message {
+                key: key.as_str(),
+                args: message.args.clone(),
+            });
+        Self { message, fix }
+    }
+
+    /// Stable error code (the message key)
+    pub fn code(&self) -> &'static str {
+        self.message.key
+    }
+}
+
+impl fmt::Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(fix) = &self.fix {
+            write!(f, "\nFix: {}", fix)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for CatalogError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spanish_port_range_message() {
+        let err = CatalogError::new(Message::new("config.invalid_port").arg("port", 80));
+
+        assert_eq!(
+            err.message.render(Locale::Es),
+            "Puerto inválido: 80. El puerto debe estar entre 1024 y 65535."
+        );
+        assert!(err
+            .fix
+            .as_ref()
+            .unwrap()
+            .render(Locale::Es)
+            .contains("8402"));
+
+        // Display stays English regardless of locale
+        assert!(err
+            .to_string()
+            .starts_with("Invalid port: 80. Port must be between 1024 and 65535.\nFix: "));
+    }
+
+    #[test]
+    fn test_missing_translation_falls_back_to_english() {
+        let english = HashMap::from([("greeting".to_string(), "Hello".to_string())]);
+        let partial = HashMap::new();
+
+        assert_eq!(
+            lookup(Locale::Ja, "greeting", &partial, &english),
+            Some("Hello")
+        );
+        assert_eq!(lookup(Locale::Ja, "missing", &partial, &english), None);
+        assert_eq!(
+            Message::new("no.such.key").render(Locale::Es),
+            "no.such.key"
+        );
+    }
+
+    #[test]
+    fn test_negotiate_accept_language() {
+        assert_eq!(Locale::negotiate("es"), Some(Locale::Es));
+        assert_eq!(Locale::negotiate("ja_JP.UTF-8"), Some(Locale::Ja));
+        assert_eq!(
+            Locale::negotiate("fr-FR, en;q=0.5, ja;q=0.8"),
+            Some(Locale::Ja)
+        );
+        assert_eq!(Locale::negotiate("fr, de"), None);
+        assert_eq!(Locale::negotiate("es;q=0, en"), Some(Locale::En));
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        assert_eq!(Locale::resolve(Some("ja"), Some("es")), Locale::Ja);
+        assert_eq!(Locale::resolve(None, Some("es-MX")), Locale::Es);
+        assert_eq!(Locale::resolve(Some("xx"), Some("es")), Locale::Es);
+        assert_eq!(Locale::resolve(None, None), Locale::En);
+    }
+
+    #[test]
+    fn test_translations_use_known_keys_and_placeholders() {
+        let english = catalog(Locale::En);
+        for locale in [Locale::Es, Locale::Ja] {
+            for (key, text) in catalog(locale) {
+                let source = english
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{} key '{}' is not in en.yaml", locale, key));
+                for placeholder in source.split('{').skip(1).filter_map(|s| s.split_once('}')) {
+                    let placeholder = format!("{{{}}}", placeholder.0);
+                    assert!(
+                        text.contains(&placeholder),
+                        "{} '{}' lacks {}",
+                        locale,
+                        key,
+                        placeholder
+                    );
+                }
+            }
+        }
+    }
+}
@@ -5,10 +5,37 @@
 // - Protocol handlers
 // - Configuration management
 // - Policy enforcement
+// - Clocks for policy windows and invoice expiry (real or virtual)
 // - Testing framework
+// - Invoice signatures (`signing` feature)
+// - Payment receipts (signed with the `signing` feature)
+// - Secret redaction for logs and config output
+// - Secret storage for `${secret:name}` references (keyring with the `keyring` feature)
+// - Client SDK generation
+// - Explanations of stable error codes (`x402-dev explain`)
+// - Solana JSON-RPC client for payment verification
+// - YAML/JSON detection and conversion for user-facing files
+// - Canonical JSON with a content hash for report files
+//
+// `prelude` re-exports the common types. The public API is checked against
+// `public-api.txt` by `cargo xtask public-api` (see CONTRIBUTING.md).
 
+pub mod canonical;
+pub mod clock;
+pub mod codegen;
+pub mod explain;
+pub mod format;
+pub mod i18n;
 pub mod policy;
+pub mod prelude;
+pub mod receipt;
+pub mod redact;
+pub mod secrets;
+#[cfg(feature = "signing")]
+pub mod signing;
+pub mod solana_rpc;
 pub mod testing;
+pub mod verifier;
 
 pub use policy::{
     validate_policies,
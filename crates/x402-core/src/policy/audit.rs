@@ -0,0 +1,524 @@
+// Policy decision audit log
+//
+// Every decision of a `PolicyEngine` can be handed to `DecisionHook`s. The
+// `AuditLog` hook writes one JSON object per decision to a file from a
+// background thread, so evaluation never waits on disk I/O: entries go
+// through a bounded channel and are dropped (and counted) when it is full.
+// Denials are always written; allows are sampled at `sample_rate`.
+
+use super::runtime_types::QuotaStatus;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use x402_domain::ByteSizeField;
+
+/// Receives every decision a [`PolicyEngine`](super::PolicyEngine) makes
+///
+/// Called on the evaluating thread; implementations should return quickly.
+pub trait DecisionHook: Send + Sync {
+    fn on_decision(&self, entry: &AuditEntry);
+}
+
+/// Outcome recorded in an audit entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditDecision {
+    Allow,
+    Deny,
+}
+
+impl std::str::FromStr for AuditDecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "allow" => Ok(AuditDecision::Allow),
+            "deny" => Ok(AuditDecision::Deny),
+            _ => Err(format!(
+                "Invalid decision: '{}'. Valid values: allow, deny",
+                s
+            )),
+        }
+    }
+}
+
+/// One policy decision, written as a line of JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// ID of the HTTP request, when the caller has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    pub agent: String,
+    pub endpoint: String,
+    pub amount: f64,
+    pub decision: AuditDecision,
+    /// Deciding policy, or `default` when no policy matched
+    pub policy_id: String,
+    /// Why the request was denied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Allowance left under `policy_id` after the decision
+    #[serde(default)]
+    pub quota: QuotaStatus,
+}
+
+/// When and how far log files are rotated
+///
+/// A file reaching `max_size` is renamed to `<file>.1` (older files shift to
+/// `.2`, `.3`, ...) and only `max_files` rotated files are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogRotation {
+    pub max_size: ByteSizeField,
+    pub max_files: usize,
+}
+
+impl Default for LogRotation {
+    fn default() -> Self {
+        Self {
+            max_size: ByteSizeField::new(10 * 1000 * 1000),
+            max_files: 5,
+        }
+    }
+}
+
+/// Default number of entries waiting to be written before new ones are dropped
+pub const DEFAULT_AUDIT_BUFFER: usize = 1024;
+
+/// Where and how much an [`AuditLog`] writes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuditLogConfig {
+    pub path: PathBuf,
+    /// Share of allows written, from 0.0 (none) to 1.0 (all); denials are
+    /// always written
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f64,
+    /// Entries queued for the writer thread before new ones are dropped
+    #[serde(default = "default_buffer")]
+    pub buffer: usize,
+    #[serde(default)]
+    pub rotation: LogRotation,
+}
+
+fn default_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_buffer() -> usize {
+    DEFAULT_AUDIT_BUFFER
+}
+
+impl AuditLogConfig {
+    /// Log every decision to `path` with the default buffer and rotation
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            sample_rate: 1.0,
+            buffer: DEFAULT_AUDIT_BUFFER,
+            rotation: LogRotation::default(),
+        }
+    }
+
+    /// Write only this share of allows
+    pub fn sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = rate;
+        self
+    }
+
+    /// Rotate files with these settings
+    pub fn rotation(mut self, rotation: LogRotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Check the sample rate and buffer size
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.sample_rate) {
+            bail!(
+                "Invalid audit sample rate: {}\nFix: Use a number from 0.0 (log only denials) to 1.0 (log every decision)",
+                self.sample_rate
+            );
+        }
+        if self.buffer == 0 {
+            bail!("Invalid audit buffer: 0\nFix: Use a buffer of at least 1 entry");
+        }
+        Ok(())
+    }
+}
+
+enum Message {
+    Entry(Box<AuditEntry>),
+    Flush(SyncSender<()>),
+}
+
+/// [`DecisionHook`] appending sampled decisions to a JSONL file
+pub struct AuditLog {
+    path: PathBuf,
+    sample_rate: f64,
+    sender: Mutex<Option<SyncSender<Message>>>,
+    writer: Mutex<Option<JoinHandle<()>>>,
+    allows_seen: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl AuditLog {
+    /// Open (or create) the log file and start the writer thread
+    pub fn open(config: &AuditLogConfig) -> Result<Self> {
+        config.validate()?;
+        if let Some(parent) = config.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create audit log directory {}", parent.display())
+            })?;
+        }
+        let file = RotatingFile::open(&config.path, config.rotation)?;
+        let (sender, receiver) = mpsc::sync_channel(config.buffer);
+        let writer = std::thread::Builder::new()
+            .name("x402-audit".to_string())
+            .spawn(move || write_entries(receiver, file))
+            .context("Failed to start audit log writer")?;
+
+        Ok(Self {
+            path: config.path.clone(),
+            sample_rate: config.sample_rate,
+            sender: Mutex::new(Some(sender)),
+            writer: Mutex::new(Some(writer)),
+            allows_seen: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// File entries are written to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Entries dropped because the writer fell behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Wait until every entry queued so far is on disk
+    pub fn flush(&self) {
+        let (ack, done) = mpsc::sync_channel(1);
+        let sent = self
+            .sender
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|sender| sender.send(Message::Flush(ack)).is_ok());
+        if sent {
+            let _ = done.recv();
+        }
+    }
+
+    /// Whether the next allow is written
+    ///
+    /// Spreads the sampled allows evenly: with a rate of 0.25, every fourth
+    /// allow is written.
+    fn sample_allow(&self) -> bool {
+        let n = self.allows_seen.fetch_add(1, Ordering::Relaxed) as f64;
+        ((n + 1.0) * self.sample_rate).floor() > (n * self.sample_rate).floor()
+    }
+}
+
+impl DecisionHook for AuditLog {
+    fn on_decision(&self, entry: &AuditEntry) {
+        if entry.decision == AuditDecision::Allow && !self.sample_allow() {
+            return;
+        }
+        let sender = self.sender.lock().unwrap();
+        let Some(sender) = sender.as_ref() else {
+            return;
+        };
+        match sender.try_send(Message::Entry(Box::new(entry.clone()))) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+impl Drop for AuditLog {
+    /// Write what is queued before the log goes away
+    fn drop(&mut self) {
+        self.sender.lock().unwrap().take();
+        if let Some(writer) = self.writer.lock().unwrap().take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+fn write_entries(receiver: Receiver<Message>, mut file: RotatingFile) {
+    for message in receiver {
+        match message {
+            Message::Entry(entry) => {
+                if let Err(e) = file.write_entry(&entry) {
+                    eprintln!("⚠️  Audit log {}: {:#}", file.path.display(), e);
+                }
+            }
+            Message::Flush(ack) => {
+                let _ = file.flush();
+                let _ = ack.send(());
+            }
+        }
+    }
+    let _ = file.flush();
+}
+
+/// Log file renamed aside once it reaches the rotation size
+struct RotatingFile {
+    path: PathBuf,
+    rotation: LogRotation,
+    out: BufWriter<File>,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, rotation: LogRotation) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open audit log {}", path.display()))?;
+        let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path: path.to_path_buf(),
+            rotation,
+            out: BufWriter::new(file),
+            size,
+        })
+    }
+
+    fn write_entry(&mut self, entry: &AuditEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let max_size = self.rotation.max_size.bytes();
+        if self.size > 0 && max_size > 0 && self.size + line.len() as u64 > max_size {
+            self.rotate()?;
+        }
+        self.out.write_all(&line)?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        self.out.flush()?;
+        let max_files = self.rotation.max_files;
+        if max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated_path(&self.path, max_files));
+            for n in (1..max_files).rev() {
+                let from = rotated_path(&self.path, n);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        *self = Self::open(&self.path, self.rotation)?;
+        Ok(())
+    }
+}
+
+/// `<path>.<n>`, the n-th most recent rotated file
+pub fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+// ============================================================================
+// Querying
+// ============================================================================
+
+/// Filters of `x402-dev mock audit query`; unset filters match everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditQuery {
+    pub agent: Option<String>,
+    pub decision: Option<AuditDecision>,
+    pub policy_id: Option<String>,
+    /// Entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl AuditQuery {
+    /// Whether `entry` passes every filter
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.agent
+            .as_ref()
+            .is_none_or(|agent| &entry.agent == agent)
+            && self
+                .decision
+                .is_none_or(|decision| entry.decision == decision)
+            && self
+                .policy_id
+                .as_ref()
+                .is_none_or(|id| &entry.policy_id == id)
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+    }
+}
+
+/// Entries of an audit log and its rotated files, oldest first
+pub fn read_audit_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    if !path.exists() {
+        bail!(
+            "Audit log not found: {}\nFix: Start the server with --audit-log {} or pass the file with --file",
+            path.display(),
+            path.display()
+        );
+    }
+
+    let mut files: Vec<PathBuf> = (1..)
+        .map(|n| rotated_path(path, n))
+        .take_while(|p| p.exists())
+        .collect();
+    files.reverse();
+    files.push(path.to_path_buf());
+
+    let mut entries = Vec::new();
+    for file in files {
+        let reader = BufReader::new(
+            File::open(&file)
+                .with_context(|| format!("Failed to open audit log {}", file.display()))?,
+        );
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line).with_context(|| {
+                format!(
+                    "Invalid audit entry at {}:{}\nFix: Each line must be one JSON entry written by --audit-log",
+                    file.display(),
+                    index + 1
+                )
+            })?;
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(decision: AuditDecision) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now(),
+            request_id: None,
+            agent: "agent-1".to_string(),
+            endpoint: "/api/data".to_string(),
+            amount: 0.01,
+            decision,
+            policy_id: "p1".to_string(),
+            reason: (decision == AuditDecision::Deny).then(|| "blocked".to_string()),
+            quota: QuotaStatus::default(),
+        }
+    }
+
+    fn log_mixed(rate: f64) -> Vec<AuditEntry> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = AuditLog::open(&AuditLogConfig::new(&path).sample_rate(rate)).unwrap();
+        for _ in 0..10 {
+            log.on_decision(&entry(AuditDecision::Allow));
+            log.on_decision(&entry(AuditDecision::Deny));
+        }
+        log.flush();
+        assert_eq!(log.dropped(), 0);
+        read_audit_entries(&path).unwrap()
+    }
+
+    #[test]
+    fn test_sample_rate_zero_logs_only_denials() {
+        let entries = log_mixed(0.0);
+        assert_eq!(entries.len(), 10);
+        assert!(entries.iter().all(|e| e.decision == AuditDecision::Deny));
+    }
+
+    #[test]
+    fn test_sample_rate_one_logs_everything() {
+        let entries = log_mixed(1.0);
+        assert_eq!(entries.len(), 20);
+        let allows = entries
+            .iter()
+            .filter(|e| e.decision == AuditDecision::Allow)
+            .count();
+        assert_eq!(allows, 10);
+    }
+
+    #[test]
+    fn test_partial_sample_rate_spreads_allows() {
+        let entries = log_mixed(0.3);
+        let allows = entries
+            .iter()
+            .filter(|e| e.decision == AuditDecision::Allow)
+            .count();
+        assert_eq!(allows, 3);
+    }
+
+    #[test]
+    fn test_invalid_sample_rate_rejected() {
+        let err = AuditLogConfig::new("audit.jsonl")
+            .sample_rate(1.5)
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid audit sample rate"));
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let rotation = LogRotation {
+            max_size: ByteSizeField::new(400),
+            max_files: 2,
+        };
+        let log = AuditLog::open(&AuditLogConfig::new(&path).rotation(rotation)).unwrap();
+        for _ in 0..20 {
+            log.on_decision(&entry(AuditDecision::Deny));
+        }
+        drop(log);
+
+        assert!(rotated_path(&path, 1).exists());
+        assert!(rotated_path(&path, 2).exists());
+        assert!(!rotated_path(&path, 3).exists());
+        for file in [path.clone(), rotated_path(&path, 1)] {
+            assert!(fs::metadata(file).unwrap().len() <= 400);
+        }
+        // Oldest rotated file first, current file last
+        let entries = read_audit_entries(&path).unwrap();
+        assert!(!entries.is_empty() && entries.len() < 20);
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let mut old = entry(AuditDecision::Deny);
+        old.timestamp = Utc::now() - chrono::Duration::hours(2);
+        let query = AuditQuery {
+            agent: Some("agent-1".to_string()),
+            decision: Some(AuditDecision::Deny),
+            since: Some(Utc::now() - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(query.matches(&entry(AuditDecision::Deny)));
+        assert!(!query.matches(&entry(AuditDecision::Allow)));
+        assert!(!query.matches(&old));
+    }
+}
@@ -1,13 +1,23 @@
 // Express.js middleware code generator
 
+use super::{
+    js_array, js_currency, js_string, quota_header_statements, rate_limit_remaining_helper,
+    spending_window_literal, CALENDAR_BUCKET_HELPER, SPENDING_REMAINING_HELPER,
+};
 use crate::policy::rules::PolicyFile;
+use crate::policy::runtime_types::QuotaHeaders;
 use crate::policy::types::PolicyRule;
-use chrono::Utc;
+
+/// Amount each request adds to an agent's spending total
+pub(crate) const REQUEST_AMOUNT: f64 = 0.01;
 
 /// Generate Express.js middleware from policy configuration
+///
+/// Allowed requests get the remaining quota in `quota_headers`.
 pub fn generate_express_middleware(
     policy_file_content: &PolicyFile,
     policy_file_name: &str,
+    quota_headers: &QuotaHeaders,
 ) -> String {
     let mut code = String::new();
 
@@ -20,7 +30,10 @@ pub fn generate_express_middleware(
     code.push('\n');
 
     // Main middleware function
-    code.push_str(&generate_middleware_function(policy_file_content));
+    code.push_str(&generate_middleware_function(
+        policy_file_content,
+        quota_headers,
+    ));
 
     code
 }
@@ -28,12 +41,10 @@ pub fn generate_express_middleware(
 fn generate_header(policy_file: &str) -> String {
     format!(
         r#"// Generated by: x402-dev policy generate {}
-// Generated: {}
 // DO NOT EDIT THIS FILE MANUALLY - Regenerate from policy file
 
 "#,
-        policy_file,
-        Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+        policy_file
     )
 }
 
@@ -48,6 +59,8 @@ fn generate_helper_functions(config: &PolicyFile) -> String {
     {
         code.push_str(&generate_rate_limit_helper());
         code.push('\n');
+        code.push_str(&rate_limit_remaining_helper("ratelimit"));
+        code.push('\n');
     }
 
     // Spending cap checker
@@ -58,6 +71,18 @@ fn generate_helper_functions(config: &PolicyFile) -> String {
     {
         code.push_str(&generate_spending_cap_helper());
         code.push('\n');
+        code.push_str(SPENDING_REMAINING_HELPER);
+        code.push('\n');
+    }
+
+    // In-flight request tracker
+    if config
+        .policies
+        .iter()
+        .any(|p| matches!(p, PolicyRule::ConcurrencyLimit { .. }))
+    {
+        code.push_str(&generate_concurrency_helper());
+        code.push('\n');
     }
 
     // Invoice generator
@@ -95,7 +120,7 @@ function rateLimitExceeded(agentId, maxRequests, windowSeconds) {
   const windowStart = now - (windowSeconds * 1000);
 
   // Remove expired timestamps
-  const validTimestamps = timestamps.filter(ts => ts > windowStart);
+  const validTimestamps = timestamps.filter(ts => ts >= windowStart);
   rateLimitStore.set(key, validTimestamps);
 
   // Check if limit exceeded
@@ -114,7 +139,7 @@ function rateLimitExceeded(agentId, maxRequests, windowSeconds) {
 }
 
 fn generate_spending_cap_helper() -> String {
-    r#"// Spending tracking (in-memory, use database for production)
+    let mut code = r#"// Spending tracking (in-memory, use database for production)
 const spendingStore = new Map();
 
 /**
@@ -122,61 +147,88 @@ const spendingStore = new Map();
  * @param {string} agentId - The agent identifier
  * @param {number} amount - Current request amount
  * @param {number} maxAmount - Maximum spending allowed
- * @param {string} period - Time period ('daily', 'weekly', 'monthly')
+ * @param {object} window - { type: 'rolling', seconds } or
+ *   { type: 'calendar_day' | 'calendar_month', timeZone }
  * @returns {boolean} true if spending cap exceeded
  */
-function spendingCapExceeded(agentId, amount, maxAmount, period) {
+function spendingCapExceeded(agentId, amount, maxAmount, window) {
   const now = Date.now();
-  const key = `spending:${agentId}:${period}`;
+  const key = `spending:${agentId}`;
+
+  if (window.type === 'rolling') {
+    // Keep only payments inside the sliding window
+    const payments = (spendingStore.get(key) || [])
+      .filter(entry => now - entry.timestamp <= window.seconds * 1000);
+    spendingStore.set(key, payments);
 
-  if (!spendingStore.has(key)) {
-    spendingStore.set(key, { total: 0, resetAt: getNextResetTime(period) });
+    const total = payments.reduce((sum, entry) => sum + entry.amount, 0);
+    if (total + amount > maxAmount) {
+      return true;
+    }
+    payments.push({ timestamp: now, amount });
+    return false;
   }
 
+  // Calendar windows keep one running total, reset when the bucket changes
+  const bucket = calendarBucket(window, now);
   const spending = spendingStore.get(key);
-
-  // Reset if period elapsed
-  if (now >= spending.resetAt) {
-    spending.total = 0;
-    spending.resetAt = getNextResetTime(period);
-  }
+  const total = spending && spending.bucket === bucket ? spending.total : 0;
 
   // Check if adding this amount would exceed cap
-  if (spending.total + amount > maxAmount) {
+  if (total + amount > maxAmount) {
     return true;
   }
 
   // Add to spending
-  spending.total += amount;
-  spendingStore.set(key, spending);
+  spendingStore.set(key, { bucket, total: total + amount });
 
   return false;
 }
 
+"#
+    .to_string();
+    code.push_str(CALENDAR_BUCKET_HELPER);
+    code
+}
+
+fn generate_concurrency_helper() -> String {
+    r#"// In-flight request tracking (per process)
+const inFlightStore = new Map();
+
 /**
- * Calculate next reset time for spending period
- * @param {string} period - 'daily', 'weekly', or 'monthly'
- * @returns {number} timestamp in milliseconds
+ * Take an in-flight slot for an agent
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxInFlight - Maximum simultaneous requests
+ * @returns {boolean} true if a slot was taken
  */
-function getNextResetTime(period) {
-  const now = new Date();
-
-  switch (period) {
-    case 'daily':
-      now.setHours(24, 0, 0, 0);
-      return now.getTime();
-    case 'weekly':
-      const daysUntilMonday = (8 - now.getDay()) % 7;
-      now.setDate(now.getDate() + daysUntilMonday);
-      now.setHours(0, 0, 0, 0);
-      return now.getTime();
-    case 'monthly':
-      now.setMonth(now.getMonth() + 1, 1);
-      now.setHours(0, 0, 0, 0);
-      return now.getTime();
-    default:
-      return now.getTime() + (24 * 60 * 60 * 1000); // Default to 24h
+function acquireSlot(agentId, maxInFlight) {
+  const count = inFlightStore.get(agentId) || 0;
+  if (count >= maxInFlight) {
+    return false;
   }
+  inFlightStore.set(agentId, count + 1);
+  return true;
+}
+
+/**
+ * Release the agent's slot once the response finishes or the client disconnects
+ * @param {object} res - Node.js response object
+ * @param {string} agentId - The agent identifier
+ */
+function releaseSlotOnFinish(res, agentId) {
+  let released = false;
+  const release = () => {
+    if (released) return;
+    released = true;
+    const count = inFlightStore.get(agentId) || 0;
+    if (count <= 1) {
+      inFlightStore.delete(agentId);
+    } else {
+      inFlightStore.set(agentId, count - 1);
+    }
+  };
+  res.on('finish', release);
+  res.on('close', release);
 }
 "#
     .to_string()
@@ -192,7 +244,7 @@ fn generate_invoice_helper(config: &PolicyFile) -> String {
  */
 function generateInvoice(agentId, resource) {{
   const amount = {};
-  const currency = '{}';
+  const currency = {};
   const memoPrefix = {};
   const memo = memoPrefix ? `${{memoPrefix}}_req_${{Date.now()}}` : `req_${{Date.now()}}`;
 
@@ -203,12 +255,12 @@ function generateInvoice(agentId, resource) {{
 }}
 "#,
         config.pricing.amount,
-        config.pricing.currency,
+        js_currency(&config.pricing.currency),
         config
             .pricing
             .memo_prefix
-            .as_ref()
-            .map(|s| format!("'{}'", s))
+            .as_deref()
+            .map(js_string)
             .unwrap_or_else(|| "null".to_string())
     )
 }
@@ -235,8 +287,8 @@ function logPaymentAttempt(agentId, resource, action, result) {{
     result
   }};
 
-  const format = '{}';
-  const destination = '{}';
+  const format = {};
+  const destination = {};
 
   let logLine;
   if (format === 'csv') {{
@@ -257,11 +309,12 @@ function logPaymentAttempt(agentId, resource, action, result) {{
   }}
 }}
 "#,
-        format, destination
+        js_string(format),
+        js_string(destination)
     )
 }
 
-fn generate_middleware_function(config: &PolicyFile) -> String {
+fn generate_middleware_function(config: &PolicyFile, quota_headers: &QuotaHeaders) -> String {
     let mut code = String::new();
 
     code.push_str(
@@ -279,44 +332,59 @@ const x402Middleware = (req, res, next) => {
 "#,
     );
 
-    // Generate policy checks
-    for policy in &config.policies {
+    // Every spending cap charges the same amount, declared once
+    if config
+        .policies
+        .iter()
+        .any(|p| matches!(p, PolicyRule::SpendingCap { .. }))
+    {
+        code.push_str(&format!(
+            "  const requestAmount = {}; // Amount for this request\n\n",
+            REQUEST_AMOUNT
+        ));
+    }
+
+    // Generate policy checks; lists are numbered so repeated rules don't
+    // redeclare them
+    for (idx, policy) in config.policies.iter().enumerate() {
         match policy {
             PolicyRule::Allowlist { field: _, values } => {
                 code.push_str("  // Allowlist policy check\n");
                 code.push_str(&format!(
-                    "  const allowedAgents = {};\n",
-                    serde_json::to_string(values)
-                        .expect("Vec<String> should always serialize to JSON")
+                    "  const allowedAgents_{} = {};\n",
+                    idx,
+                    js_array(values)
                 ));
-                code.push_str(
-                    r#"  if (!allowedAgents.includes(agentId)) {
-    if (logPaymentAttempt) {
+                code.push_str(&format!(
+                    r#"  if (!allowedAgents_{}.includes(agentId)) {{
+    if (typeof logPaymentAttempt === 'function') {{
       logPaymentAttempt(agentId, resource, 'allowlist_check', 'denied');
-    }
-    return res.status(403).json({ error: 'Agent not allowed' });
-  }
+    }}
+    return res.status(403).json({{ error: 'Agent not allowed' }});
+  }}
 
 "#,
-                );
+                    idx
+                ));
             }
             PolicyRule::Denylist { field: _, values } => {
                 code.push_str("  // Denylist policy check\n");
                 code.push_str(&format!(
-                    "  const deniedAgents = {};\n",
-                    serde_json::to_string(values)
-                        .expect("Vec<String> should always serialize to JSON")
+                    "  const deniedAgents_{} = {};\n",
+                    idx,
+                    js_array(values)
                 ));
-                code.push_str(
-                    r#"  if (deniedAgents.includes(agentId)) {
-    if (logPaymentAttempt) {
+                code.push_str(&format!(
+                    r#"  if (deniedAgents_{}.includes(agentId)) {{
+    if (typeof logPaymentAttempt === 'function') {{
       logPaymentAttempt(agentId, resource, 'denylist_check', 'denied');
-    }
-    return res.status(403).json({ error: 'Agent denied' });
-  }
+    }}
+    return res.status(403).json({{ error: 'Agent denied' }});
+  }}
 
 "#,
-                );
+                    idx
+                ));
             }
             PolicyRule::RateLimit {
                 max_requests,
@@ -325,43 +393,71 @@ const x402Middleware = (req, res, next) => {
                 code.push_str("  // Rate limit policy check\n");
                 code.push_str(&format!(
                     r#"  if (rateLimitExceeded(agentId, {}, {})) {{
-    if (logPaymentAttempt) {{
+    if (typeof logPaymentAttempt === 'function') {{
       logPaymentAttempt(agentId, resource, 'rate_limit_check', 'exceeded');
     }}
     return res.status(429).json({{ error: 'Rate limit exceeded', retry_after: {} }});
   }}
 
 "#,
-                    max_requests, window_seconds, window_seconds
+                    max_requests,
+                    window_seconds.as_secs(),
+                    window_seconds.as_secs()
                 ));
             }
             PolicyRule::SpendingCap {
                 max_amount,
                 currency,
-                window_seconds,
+                ..
             } => {
+                let window = spending_window_literal(policy);
                 code.push_str("  // Spending cap policy check\n");
                 code.push_str(&format!(
-                    r#"  const requestAmount = {}; // Amount for this request
-  if (spendingCapExceeded(agentId, requestAmount, {}, '{}')) {{
-    if (logPaymentAttempt) {{
+                    r#"  if (spendingCapExceeded(agentId, requestAmount, {}, {})) {{
+    if (typeof logPaymentAttempt === 'function') {{
       logPaymentAttempt(agentId, resource, 'spending_cap_check', 'exceeded');
     }}
     return res.status(402).json({{
       error: 'Spending cap exceeded',
       max_amount: {},
-      period: '{}',
-      currency: '{}'
+      window: {},
+      currency: {}
     }});
   }}
 
 "#,
-                    0.01, max_amount, window_seconds, max_amount, window_seconds, currency
+                    max_amount,
+                    window,
+                    max_amount,
+                    window,
+                    js_currency(currency)
+                ));
+            }
+            PolicyRule::ConcurrencyLimit { max_in_flight } => {
+                code.push_str("  // Concurrency limit policy check\n");
+                code.push_str(&format!(
+                    r#"  if (!acquireSlot(agentId, {})) {{
+    if (typeof logPaymentAttempt === 'function') {{
+      logPaymentAttempt(agentId, resource, 'concurrency_limit_check', 'exceeded');
+    }}
+    return res.status(429).json({{ error: 'Too many concurrent requests', max_in_flight: {} }});
+  }}
+  releaseSlotOnFinish(res, agentId);
+
+"#,
+                    max_in_flight, max_in_flight
                 ));
             }
         }
     }
 
+    code.push_str(&quota_header_statements(
+        &config.policies,
+        quota_headers,
+        "res.set",
+        "  ",
+    ));
+
     // Generate 402 response with invoice
     code.push_str(
         r#"  // Generate 402 Payment Required response with invoice
@@ -389,10 +485,10 @@ const x402Middleware = (req, res, next) => {
        amount: "#,
     );
     code.push_str(&format!("{}", config.pricing.amount));
-    code.push_str(",\n       currency: '");
-    code.push_str(&config.pricing.currency);
+    code.push_str(",\n       currency: ");
+    code.push_str(&js_currency(&config.pricing.currency));
     code.push_str(
-        r#"'
+        r#"
      });
 };
 
@@ -407,6 +503,8 @@ module.exports = x402Middleware;
 mod tests {
     use super::*;
     use crate::policy::rules::{AuditConfig, PolicyFile, PricingConfig};
+    use crate::policy::window::WindowType;
+    use x402_domain::DurationField;
 
     #[test]
     fn test_generate_header() {
@@ -427,7 +525,7 @@ mod tests {
         let helper = generate_spending_cap_helper();
         assert!(helper.contains("function spendingCapExceeded"));
         assert!(helper.contains("spendingStore"));
-        assert!(helper.contains("getNextResetTime"));
+        assert!(helper.contains("function calendarBucket"));
     }
 
     #[test]
@@ -440,6 +538,7 @@ mod tests {
                 memo_prefix: Some("test".to_string()),
             },
             audit: AuditConfig::default(),
+            allow_custom_currencies: false,
         };
 
         let helper = generate_invoice_helper(&config);
@@ -459,6 +558,7 @@ mod tests {
                 format: "csv".to_string(),
                 destination: Some("/var/log/audit.csv".to_string()),
             },
+            allow_custom_currencies: false,
         };
 
         let logger = generate_audit_logger(&config);
@@ -476,9 +576,10 @@ mod tests {
             }],
             pricing: PricingConfig::default(),
             audit: AuditConfig::default(),
+            allow_custom_currencies: false,
         };
 
-        let code = generate_express_middleware(&config, "test.yaml");
+        let code = generate_express_middleware(&config, "test.yaml", &QuotaHeaders::default());
         assert!(code.contains("x402Middleware"));
         assert!(code.contains("allowedAgents"));
         assert!(code.contains("agent-1"));
@@ -491,19 +592,59 @@ mod tests {
         let config = PolicyFile {
             policies: vec![PolicyRule::RateLimit {
                 max_requests: 100,
-                window_seconds: 3600,
+                window_seconds: DurationField::from_secs(3600),
             }],
             pricing: PricingConfig::default(),
             audit: AuditConfig::default(),
+            allow_custom_currencies: false,
         };
 
-        let code = generate_express_middleware(&config, "test.yaml");
+        let code = generate_express_middleware(&config, "test.yaml", &QuotaHeaders::default());
         assert!(code.contains("rateLimitExceeded"));
         assert!(code.contains("100"));
         assert!(code.contains("3600"));
         assert!(code.contains("status(429)"));
     }
 
+    #[test]
+    fn test_generate_middleware_with_calendar_spending_cap() {
+        let config = PolicyFile {
+            policies: vec![PolicyRule::SpendingCap {
+                max_amount: 25.0,
+                currency: "USDC".to_string(),
+                window_seconds: None,
+                window_type: WindowType::CalendarMonth,
+                timezone: None,
+            }],
+            pricing: PricingConfig::default(),
+            audit: AuditConfig::default(),
+            allow_custom_currencies: false,
+        };
+
+        let code = generate_express_middleware(&config, "test.yaml", &QuotaHeaders::default());
+        assert!(code.contains(
+            "spendingCapExceeded(agentId, requestAmount, 25, { type: 'calendar_month', timeZone: 'UTC' })"
+        ));
+        assert!(code.contains("day.slice(0, 7)"));
+    }
+
+    #[test]
+    fn test_generate_middleware_with_concurrency_limit() {
+        let config = PolicyFile {
+            policies: vec![PolicyRule::ConcurrencyLimit { max_in_flight: 5 }],
+            pricing: PricingConfig::default(),
+            audit: AuditConfig::default(),
+            allow_custom_currencies: false,
+        };
+
+        let code = generate_express_middleware(&config, "test.yaml", &QuotaHeaders::default());
+        assert!(code.contains("function acquireSlot"));
+        assert!(code.contains("acquireSlot(agentId, 5)"));
+        assert!(code.contains("res.on('finish', release)"));
+        assert!(code.contains("res.on('close', release)"));
+        assert!(code.contains("status(429)"));
+    }
+
     #[test]
     fn test_generate_complete_middleware() {
         let config = PolicyFile {
@@ -514,7 +655,7 @@ mod tests {
                 },
                 PolicyRule::RateLimit {
                     max_requests: 50,
-                    window_seconds: 60,
+                    window_seconds: DurationField::from_secs(60),
                 },
             ],
             pricing: PricingConfig {
@@ -527,9 +668,10 @@ mod tests {
                 format: "json".to_string(),
                 destination: Some("stdout".to_string()),
             },
+            allow_custom_currencies: false,
         };
 
-        let code = generate_express_middleware(&config, "policy.yaml");
+        let code = generate_express_middleware(&config, "policy.yaml", &QuotaHeaders::default());
 
         // Check header
         assert!(code.contains("Generated by: x402-dev"));
@@ -549,4 +691,55 @@ mod tests {
         // Check export
         assert!(code.contains("module.exports"));
     }
+
+    #[test]
+    fn test_generate_middleware_quota_headers() {
+        let config = PolicyFile {
+            policies: vec![
+                PolicyRule::RateLimit {
+                    max_requests: 10,
+                    window_seconds: DurationField::from_secs(60),
+                },
+                PolicyRule::SpendingCap {
+                    max_amount: 1.5,
+                    currency: "USDC".to_string(),
+                    window_seconds: Some(DurationField::from_secs(3600)),
+                    window_type: WindowType::Rolling,
+                    timezone: None,
+                },
+            ],
+            pricing: PricingConfig::default(),
+            audit: AuditConfig::default(),
+            allow_custom_currencies: false,
+        };
+
+        let code = generate_express_middleware(&config, "test.yaml", &QuotaHeaders::default());
+        assert!(code.contains("function rateLimitRemaining"));
+        assert!(code.contains("rateLimitStore.get(`ratelimit:${agentId}`)"));
+        assert!(code.contains("res.set('X-RateLimit-Limit', '10');"));
+        assert!(code.contains(
+            "res.set('X-RateLimit-Remaining', String(rateLimitRemaining(agentId, 10, 60)));"
+        ));
+        assert!(code.contains("res.set('X-Spend-Cap', formatAmount(1.5));"));
+
+        // Names follow the configured gateway
+        let headers = QuotaHeaders {
+            spend_remaining: "X-Budget-Left".to_string(),
+            ..QuotaHeaders::default()
+        };
+        let code = generate_express_middleware(&config, "test.yaml", &headers);
+        assert!(code.contains(
+            "res.set('X-Budget-Left', formatAmount(spendingRemaining(agentId, 1.5, { type: 'rolling', seconds: 3600 })));"
+        ));
+
+        // No quota policies, no headers
+        let config = PolicyFile {
+            policies: vec![PolicyRule::ConcurrencyLimit { max_in_flight: 5 }],
+            pricing: PricingConfig::default(),
+            audit: AuditConfig::default(),
+            allow_custom_currencies: false,
+        };
+        let code = generate_express_middleware(&config, "test.yaml", &QuotaHeaders::default());
+        assert!(!code.contains("X-RateLimit"));
+    }
 }
@@ -1,7 +1,11 @@
 // Fastify plugin code generation from policy rules
 
+use super::{
+    js_array, js_currency, js_string, quota_header_statements, rate_limit_remaining_helper,
+    spending_window_literal, CALENDAR_BUCKET_HELPER, SPENDING_REMAINING_HELPER,
+};
+use crate::policy::runtime_types::QuotaHeaders;
 use crate::policy::types::PolicyRule;
-use chrono::Utc;
 
 /// Generate Fastify plugin code from policy rules
 ///
@@ -10,22 +14,26 @@ use chrono::Utc;
 /// - JSON Schema validation
 /// - preHandler hook for policy enforcement
 /// - 402 Payment Required responses with invoices
+/// - Remaining quota headers on allowed requests
 /// - Comprehensive audit logging
 ///
 /// # Arguments
 /// * `policies` - Vector of policy rules to enforce
 /// * `policy_filename` - Optional source policy file name for documentation
+/// * `quota_headers` - Header names for the remaining quota
 ///
 /// # Returns
 /// Complete JavaScript code as a String
-pub fn generate_fastify_plugin(policies: &[PolicyRule], policy_filename: Option<&str>) -> String {
-    let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S UTC");
+pub fn generate_fastify_plugin(
+    policies: &[PolicyRule],
+    policy_filename: Option<&str>,
+    quota_headers: &QuotaHeaders,
+) -> String {
     let filename = policy_filename.unwrap_or("policy.yaml");
 
     let mut code = format!(
         r#"// Generated by: x402-dev policy generate
 // Policy file: {}
-// Generated: {}
 // Framework: Fastify.js
 //
 // This is a production-ready Fastify plugin implementing x402 protocol
@@ -46,7 +54,7 @@ pub fn generate_fastify_plugin(policies: &[PolicyRule], policy_filename: Option<
 const fp = require('fastify-plugin');
 
 "#,
-        filename, timestamp
+        filename
     );
 
     // Generate schema definitions
@@ -56,7 +64,7 @@ const fp = require('fastify-plugin');
     code.push_str(&generate_helpers(policies));
 
     // Generate main plugin function
-    code.push_str(&generate_plugin_function(policies));
+    code.push_str(&generate_plugin_function(policies, quota_headers));
 
     // Export with metadata
     code.push_str(
@@ -149,7 +157,7 @@ function checkRateLimit(agentId, maxRequests, windowSeconds) {
   const requests = rateLimitStore.get(key);
 
   // Remove expired entries
-  const validRequests = requests.filter(timestamp => now - timestamp < windowMs);
+  const validRequests = requests.filter(timestamp => now - timestamp <= windowMs);
 
   if (validRequests.length >= maxRequests) {
     return false; // Rate limit exceeded
@@ -164,6 +172,8 @@ function checkRateLimit(agentId, maxRequests, windowSeconds) {
 
 "#,
         );
+        code.push_str(&rate_limit_remaining_helper("rate"));
+        code.push('\n');
     }
 
     // Check if we need spending tracking
@@ -175,11 +185,28 @@ function checkRateLimit(agentId, maxRequests, windowSeconds) {
             r#"// Spending tracking state (in-memory, use database in production)
 const spendingStore = new Map();
 
-function checkSpendingCap(agentId, amount, maxAmount, windowSeconds) {
+// window: { type: 'rolling', seconds } or
+//         { type: 'calendar_day' | 'calendar_month', timeZone }
+function checkSpendingCap(agentId, amount, maxAmount, window) {
   const now = Date.now();
-  const windowMs = windowSeconds * 1000;
   const key = `spending:${agentId}`;
 
+  if (window.type !== 'rolling') {
+    // Calendar windows keep one running total, reset when the bucket changes
+    const bucket = calendarBucket(window, now);
+    const spending = spendingStore.get(key);
+    const total = spending && spending.bucket === bucket ? spending.total : 0;
+
+    if (total + amount > maxAmount) {
+      return false; // Spending cap exceeded
+    }
+
+    spendingStore.set(key, { bucket, total: total + amount });
+    return true; // Within spending cap
+  }
+
+  const windowMs = window.seconds * 1000;
+
   if (!spendingStore.has(key)) {
     spendingStore.set(key, []);
   }
@@ -187,7 +214,7 @@ function checkSpendingCap(agentId, amount, maxAmount, windowSeconds) {
   const payments = spendingStore.get(key);
 
   // Remove expired entries
-  const validPayments = payments.filter(entry => now - entry.timestamp < windowMs);
+  const validPayments = payments.filter(entry => now - entry.timestamp <= windowMs);
 
   // Calculate total spending
   const totalSpent = validPayments.reduce((sum, entry) => sum + entry.amount, 0);
@@ -203,6 +230,49 @@ function checkSpendingCap(agentId, amount, maxAmount, windowSeconds) {
   return true; // Within spending cap
 }
 
+"#,
+        );
+        code.push_str(CALENDAR_BUCKET_HELPER);
+        code.push('\n');
+        code.push_str(SPENDING_REMAINING_HELPER);
+        code.push('\n');
+    }
+
+    // Check if we need in-flight tracking
+    let has_concurrency_limit = policies
+        .iter()
+        .any(|p| matches!(p, PolicyRule::ConcurrencyLimit { .. }));
+    if has_concurrency_limit {
+        code.push_str(
+            r#"// In-flight request tracking (per process)
+const inFlightStore = new Map();
+
+function acquireSlot(agentId, maxInFlight) {
+  const count = inFlightStore.get(agentId) || 0;
+  if (count >= maxInFlight) {
+    return false; // Too many concurrent requests
+  }
+  inFlightStore.set(agentId, count + 1);
+  return true;
+}
+
+// Release once the raw response finishes or the client disconnects
+function releaseSlotOnFinish(raw, agentId) {
+  let released = false;
+  const release = () => {
+    if (released) return;
+    released = true;
+    const count = inFlightStore.get(agentId) || 0;
+    if (count <= 1) {
+      inFlightStore.delete(agentId);
+    } else {
+      inFlightStore.set(agentId, count - 1);
+    }
+  };
+  raw.on('finish', release);
+  raw.on('close', release);
+}
+
 "#,
         );
     }
@@ -234,7 +304,7 @@ function logAuditEvent(event) {
 }
 
 /// Generate main plugin function with policy enforcement
-fn generate_plugin_function(policies: &[PolicyRule]) -> String {
+fn generate_plugin_function(policies: &[PolicyRule], quota_headers: &QuotaHeaders) -> String {
     let mut code = String::from(
         r#"// Main Fastify plugin function
 async function x402PolicyPlugin(fastify, options) {
@@ -269,12 +339,12 @@ async function x402PolicyPlugin(fastify, options) {
                 };
 
                 code.push_str(&format!(
-                    r#"    const allowedValues_{} = [{}];
+                    r#"    const allowedValues_{} = {};
     if (!allowedValues_{}.includes({})) {{
       logAuditEvent({{
         event: 'policy_denied',
         policy: 'allowlist',
-        field: '{}',
+        field: {},
         value: {},
         reason: 'not in allowlist'
       }});
@@ -283,14 +353,10 @@ async function x402PolicyPlugin(fastify, options) {
 
 "#,
                     idx,
-                    values
-                        .iter()
-                        .map(|v| format!("'{}'", v))
-                        .collect::<Vec<_>>()
-                        .join(", "),
+                    js_array(values),
                     idx,
                     field_var,
-                    field,
+                    js_string(field),
                     field_var
                 ));
             }
@@ -303,12 +369,12 @@ async function x402PolicyPlugin(fastify, options) {
                 };
 
                 code.push_str(&format!(
-                    r#"    const deniedValues_{} = [{}];
+                    r#"    const deniedValues_{} = {};
     if (deniedValues_{}.includes({})) {{
       logAuditEvent({{
         event: 'policy_denied',
         policy: 'denylist',
-        field: '{}',
+        field: {},
         value: {},
         reason: 'in denylist'
       }});
@@ -317,14 +383,10 @@ async function x402PolicyPlugin(fastify, options) {
 
 "#,
                     idx,
-                    values
-                        .iter()
-                        .map(|v| format!("'{}'", v))
-                        .collect::<Vec<_>>()
-                        .join(", "),
+                    js_array(values),
                     idx,
                     field_var,
-                    field,
+                    js_string(field),
                     field_var
                 ));
             }
@@ -345,15 +407,17 @@ async function x402PolicyPlugin(fastify, options) {
     }}
 
 "#,
-                    max_requests, window_seconds
+                    max_requests,
+                    window_seconds.as_secs()
                 ));
             }
 
             PolicyRule::SpendingCap {
                 max_amount,
                 currency,
-                window_seconds,
+                ..
             } => {
+                let window = spending_window_literal(policy);
                 code.push_str(&format!(
                     r#"    const paymentAmount_{} = parseFloat(request.headers['x-payment-amount'] || '0.01');
     if (!checkSpendingCap(agentId, paymentAmount_{}, {}, {})) {{
@@ -363,19 +427,51 @@ async function x402PolicyPlugin(fastify, options) {
         agent_id: agentId,
         amount: paymentAmount_{},
         max_amount: {},
-        currency: '{}',
+        currency: {},
         reason: 'spending cap exceeded'
       }});
       return reply.code(403).send({{ error: 'Spending cap exceeded' }});
     }}
 
 "#,
-                    idx, idx, max_amount, window_seconds, idx, max_amount, currency
+                    idx,
+                    idx,
+                    max_amount,
+                    window,
+                    idx,
+                    max_amount,
+                    js_currency(currency)
+                ));
+            }
+
+            PolicyRule::ConcurrencyLimit { max_in_flight } => {
+                code.push_str(&format!(
+                    r#"    if (!acquireSlot(agentId, {})) {{
+      logAuditEvent({{
+        event: 'policy_denied',
+        policy: 'concurrency_limit',
+        agent_id: agentId,
+        max_in_flight: {},
+        reason: 'too many concurrent requests'
+      }});
+      return reply.code(429).send({{ error: 'Too many concurrent requests' }});
+    }}
+    releaseSlotOnFinish(reply.raw, agentId);
+
+"#,
+                    max_in_flight, max_in_flight
                 ));
             }
         }
     }
 
+    code.push_str(&quota_header_statements(
+        policies,
+        quota_headers,
+        "reply.header",
+        "    ",
+    ));
+
     // Generate 402 response with invoice
     code.push_str(
         r#"    // All policies passed, generate 402 Payment Required response
@@ -417,6 +513,8 @@ async function x402PolicyPlugin(fastify, options) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::policy::window::WindowType;
+    use x402_domain::DurationField;
 
     #[test]
     fn test_generate_basic_fastify_plugin() {
@@ -425,7 +523,7 @@ mod tests {
             values: vec!["agent-123".to_string(), "agent-456".to_string()],
         }];
 
-        let code = generate_fastify_plugin(&policies, Some("test.yaml"));
+        let code = generate_fastify_plugin(&policies, Some("test.yaml"), &QuotaHeaders::default());
 
         // Check key components are present
         assert!(code.contains("fastify-plugin"));
@@ -441,10 +539,10 @@ mod tests {
     fn test_generate_rate_limit_plugin() {
         let policies = vec![PolicyRule::RateLimit {
             max_requests: 100,
-            window_seconds: 3600,
+            window_seconds: DurationField::from_secs(3600),
         }];
 
-        let code = generate_fastify_plugin(&policies, None);
+        let code = generate_fastify_plugin(&policies, None, &QuotaHeaders::default());
 
         assert!(code.contains("checkRateLimit"));
         assert!(code.contains("rateLimitStore"));
@@ -458,16 +556,48 @@ mod tests {
         let policies = vec![PolicyRule::SpendingCap {
             max_amount: 10.0,
             currency: "USDC".to_string(),
-            window_seconds: 86400,
+            window_seconds: Some(DurationField::from_secs(86400)),
+            window_type: WindowType::Rolling,
+            timezone: None,
         }];
 
-        let code = generate_fastify_plugin(&policies, None);
+        let code = generate_fastify_plugin(&policies, None, &QuotaHeaders::default());
 
         assert!(code.contains("checkSpendingCap"));
         assert!(code.contains("spendingStore"));
         assert!(code.contains("10"));
         assert!(code.contains("USDC"));
-        assert!(code.contains("86400"));
+        assert!(code.contains("{ type: 'rolling', seconds: 86400 }"));
+    }
+
+    #[test]
+    fn test_generate_calendar_spending_cap_plugin() {
+        let policies = vec![PolicyRule::SpendingCap {
+            max_amount: 10.0,
+            currency: "USDC".to_string(),
+            window_seconds: None,
+            window_type: WindowType::CalendarDay,
+            timezone: Some(chrono_tz::America::New_York),
+        }];
+
+        let code = generate_fastify_plugin(&policies, None, &QuotaHeaders::default());
+
+        assert!(code.contains(
+            "checkSpendingCap(agentId, paymentAmount_0, 10, { type: 'calendar_day', timeZone: 'America/New_York' })"
+        ));
+        assert!(code.contains("function calendarBucket(window, timestamp)"));
+    }
+
+    #[test]
+    fn test_generate_concurrency_limit_plugin() {
+        let policies = vec![PolicyRule::ConcurrencyLimit { max_in_flight: 3 }];
+
+        let code = generate_fastify_plugin(&policies, None, &QuotaHeaders::default());
+
+        assert!(code.contains("inFlightStore"));
+        assert!(code.contains("acquireSlot(agentId, 3)"));
+        assert!(code.contains("releaseSlotOnFinish(reply.raw, agentId)"));
+        assert!(code.contains("raw.on('close', release)"));
     }
 
     #[test]
@@ -479,16 +609,19 @@ mod tests {
             },
             PolicyRule::RateLimit {
                 max_requests: 100,
-                window_seconds: 3600,
+                window_seconds: DurationField::from_secs(3600),
             },
             PolicyRule::SpendingCap {
                 max_amount: 10.0,
                 currency: "USDC".to_string(),
-                window_seconds: 86400,
+                window_seconds: Some(DurationField::from_secs(86400)),
+                window_type: WindowType::Rolling,
+                timezone: None,
             },
         ];
 
-        let code = generate_fastify_plugin(&policies, Some("complex.yaml"));
+        let code =
+            generate_fastify_plugin(&policies, Some("complex.yaml"), &QuotaHeaders::default());
 
         // All policy types should be present
         assert!(code.contains("allowlist"));
@@ -500,10 +633,42 @@ mod tests {
     #[test]
     fn test_audit_logging_included() {
         let policies = vec![];
-        let code = generate_fastify_plugin(&policies, None);
+        let code = generate_fastify_plugin(&policies, None, &QuotaHeaders::default());
 
         assert!(code.contains("logAuditEvent"));
         assert!(code.contains("payment_request"));
         assert!(code.contains("payment_required"));
     }
+
+    #[test]
+    fn test_quota_headers_on_allowed_requests() {
+        let policies = vec![
+            PolicyRule::RateLimit {
+                max_requests: 100,
+                window_seconds: DurationField::from_secs(3600),
+            },
+            PolicyRule::SpendingCap {
+                max_amount: 10.0,
+                currency: "USDC".to_string(),
+                window_seconds: None,
+                window_type: WindowType::CalendarDay,
+                timezone: None,
+            },
+        ];
+
+        let headers = QuotaHeaders {
+            rate_limit_remaining: "RateLimit-Remaining".to_string(),
+            ..QuotaHeaders::default()
+        };
+        let code = generate_fastify_plugin(&policies, None, &headers);
+        assert!(code.contains("rateLimitStore.get(`rate:${agentId}`)"));
+        assert!(code.contains(
+            "    reply.header('RateLimit-Remaining', String(rateLimitRemaining(agentId, 100, 3600)));"
+        ));
+        assert!(code.contains("reply.header('X-RateLimit-Reset', '3600');"));
+        assert!(code.contains(
+            "reply.header('X-Spend-Remaining', formatAmount(spendingRemaining(agentId, 10, { type: 'calendar_day', timeZone: 'UTC' })));"
+        ));
+        assert!(code.contains("function formatAmount"));
+    }
 }
@@ -1,7 +1,216 @@
-// Code generation module for Express and Fastify middleware
+// Code generation module for Express and Fastify middleware and their test suites
 
 pub mod express;
 pub mod fastify;
+pub mod test_suite;
 
 pub use express::generate_express_middleware;
 pub use fastify::generate_fastify_plugin;
+pub use test_suite::generate_test_suite;
+
+use super::runtime_types::QuotaHeaders;
+use super::types::PolicyRule;
+use super::window::SpendingWindow;
+use x402_domain::normalize_currency;
+
+/// JS string literal of a currency in canonical case (`usdc` becomes
+/// `'USDC'`); custom currencies are emitted as written
+pub(crate) fn js_currency(currency: &str) -> String {
+    js_string(&normalize_currency(currency))
+}
+
+/// Single-quoted JS string literal for a value from the policy file
+///
+/// Escapes quotes, backslashes and line terminators (including U+2028 and
+/// U+2029), so agent ids and other strings cannot end the literal early.
+pub(crate) fn js_string(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => literal.push_str("\\'"),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() || c == '\u{2028}' || c == '\u{2029}' => {
+                literal.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+/// JS array literal of [`js_string`]s, e.g. `['agent-1', 'agent-2']`
+pub(crate) fn js_array(values: &[String]) -> String {
+    let items: Vec<String> = values.iter().map(|v| js_string(v)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// JS object literal for a spending cap's window, e.g.
+/// `{ type: 'rolling', seconds: 86400 }` or
+/// `{ type: 'calendar_day', timeZone: 'Europe/Berlin' }`
+pub(crate) fn spending_window_literal(rule: &PolicyRule) -> String {
+    match rule.spending_window() {
+        Some(window @ (SpendingWindow::CalendarDay(tz) | SpendingWindow::CalendarMonth(tz))) => {
+            format!(
+                "{{ type: '{}', timeZone: '{}' }}",
+                window.window_type(),
+                tz.name()
+            )
+        }
+        Some(SpendingWindow::Rolling(window)) => {
+            format!("{{ type: 'rolling', seconds: {} }}", window.as_secs())
+        }
+        None => "{ type: 'rolling', seconds: 0 }".to_string(),
+    }
+}
+
+/// `calendarBucket()` for the generated spending cap helpers
+///
+/// Buckets are the local date (`2025-03-14`) or month (`2025-03`) in the
+/// window's timezone, so a cap resets at local midnight across DST changes.
+pub(crate) const CALENDAR_BUCKET_HELPER: &str = r#"/**
+ * Calendar day or month a timestamp falls in, in the window's timezone
+ * @param {object} window - { type: 'calendar_day' | 'calendar_month', timeZone }
+ * @param {number} timestamp - Milliseconds since the epoch
+ * @returns {string} '2025-03-14' for days, '2025-03' for months
+ */
+function calendarBucket(window, timestamp) {
+  const day = new Intl.DateTimeFormat('en-CA', {
+    timeZone: window.timeZone,
+    year: 'numeric',
+    month: '2-digit',
+    day: '2-digit'
+  }).format(timestamp);
+  return window.type === 'calendar_month' ? day.slice(0, 7) : day;
+}
+"#;
+
+/// `rateLimitRemaining()` over the rate limit store, keyed `<prefix>:<agentId>`
+pub(crate) fn rate_limit_remaining_helper(key_prefix: &str) -> String {
+    format!(
+        r#"/**
+ * Requests left in the agent's current rate limit window
+ * @param {{string}} agentId - The agent identifier
+ * @param {{number}} maxRequests - Maximum requests allowed
+ * @param {{number}} windowSeconds - Time window in seconds
+ * @returns {{number}} Requests remaining
+ */
+function rateLimitRemaining(agentId, maxRequests, windowSeconds) {{
+  const windowStart = Date.now() - (windowSeconds * 1000);
+  const timestamps = rateLimitStore.get(`{}:${{agentId}}`) || [];
+  return Math.max(0, maxRequests - timestamps.filter(ts => ts >= windowStart).length);
+}}
+"#,
+        key_prefix
+    )
+}
+
+/// `spendingRemaining()` and `formatAmount()` over the spending store
+pub(crate) const SPENDING_REMAINING_HELPER: &str = r#"/**
+ * Amount left under the agent's spending cap in the current window
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxAmount - Maximum spending allowed
+ * @param {object} window - Same window object as the spending cap check
+ * @returns {number} Amount remaining
+ */
+function spendingRemaining(agentId, maxAmount, window) {
+  const now = Date.now();
+  const spending = spendingStore.get(`spending:${agentId}`);
+  let total = 0;
+  if (window.type === 'rolling') {
+    total = (spending || [])
+      .filter(entry => now - entry.timestamp <= window.seconds * 1000)
+      .reduce((sum, entry) => sum + entry.amount, 0);
+  } else if (spending && spending.bucket === calendarBucket(window, now)) {
+    total = spending.total;
+  }
+  return Math.max(0, maxAmount - total);
+}
+
+/**
+ * Decimal string with at most 6 places, e.g. 0.09
+ * @param {number} amount - Amount to format
+ * @returns {string}
+ */
+function formatAmount(amount) {
+  return String(Number(amount.toFixed(6)));
+}
+"#;
+
+/// Statements setting the quota headers with `set` (`res.set` or
+/// `reply.header`), like the x402-dev mock server does on allowed responses
+///
+/// The first rate limit and spending cap rule are reported; nothing is
+/// emitted without them.
+pub(crate) fn quota_header_statements(
+    policies: &[PolicyRule],
+    headers: &QuotaHeaders,
+    set: &str,
+    indent: &str,
+) -> String {
+    let mut lines = Vec::new();
+    if let Some(PolicyRule::RateLimit {
+        max_requests,
+        window_seconds,
+    }) = policies
+        .iter()
+        .find(|p| matches!(p, PolicyRule::RateLimit { .. }))
+    {
+        lines.push(format!(
+            "{}({}, '{}');",
+            set,
+            js_string(&headers.rate_limit_limit),
+            max_requests
+        ));
+        lines.push(format!(
+            "{}({}, String(rateLimitRemaining(agentId, {}, {})));",
+            set,
+            js_string(&headers.rate_limit_remaining),
+            max_requests,
+            window_seconds.as_secs()
+        ));
+        lines.push(format!(
+            "{}({}, '{}');",
+            set,
+            js_string(&headers.rate_limit_reset),
+            window_seconds.as_secs()
+        ));
+    }
+    if let Some(rule @ PolicyRule::SpendingCap { max_amount, .. }) = policies
+        .iter()
+        .find(|p| matches!(p, PolicyRule::SpendingCap { .. }))
+    {
+        lines.push(format!(
+            "{}({}, formatAmount({}));",
+            set,
+            js_string(&headers.spend_cap),
+            max_amount
+        ));
+        lines.push(format!(
+            "{}({}, formatAmount(spendingRemaining(agentId, {}, {})));",
+            set,
+            js_string(&headers.spend_remaining),
+            max_amount,
+            spending_window_literal(rule)
+        ));
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    let mut code = format!(
+        "{}// Remaining quota, as the x402-dev mock server reports it\n",
+        indent
+    );
+    for line in lines {
+        code.push_str(indent);
+        code.push_str(&line);
+        code.push('\n');
+    }
+    code.push('\n');
+    code
+}
@@ -0,0 +1,383 @@
+// Test suite generation from policy rules
+//
+// Derives scenarios that exercise generated middleware: listed agents pass,
+// denied agents get 403, the request after a rate limit window fills gets
+// 429, and spending past a cap gets no invoice (402 from Express, 403 from
+// Fastify). Each scenario uses its own sample agent where the policy allows
+// it, so rate-limit and spending counters do not leak between scenarios.
+
+use crate::policy::rules::PolicyFile;
+use crate::policy::types::PolicyRule;
+use crate::testing::{Expectations, HeaderAssertion, Test, TestSuite};
+use std::collections::BTreeMap;
+use x402_domain::normalize_currency;
+
+use super::express::REQUEST_AMOUNT;
+
+/// Variable holding the URL of a route the middleware protects
+pub const BASE_URL_VARIABLE: &str = "base_url";
+
+/// Default for [`BASE_URL_VARIABLE`] (Express dev server)
+pub const DEFAULT_BASE_URL: &str = "http://localhost:3000/";
+
+/// Repeated requests above this count tag a scenario `slow`
+const SLOW_REPEAT: u32 = 50;
+
+/// Derive a test suite that exercises the middleware generated for `policy`
+///
+/// Tests target `${base_url}`; override it with `x402-dev test --var`.
+pub fn generate_test_suite(policy: &PolicyFile) -> TestSuite {
+    let mut agents = SampleAgents::new(&policy.policies);
+    let mut tests = Vec::new();
+
+    for rule in &policy.policies {
+        match rule {
+            PolicyRule::Allowlist { field, .. } => {
+                let allowed = agents.next_allowed();
+                tests.push(scenario(
+                    format!("allowlist: listed agent '{}' passes", allowed),
+                    "allowlist",
+                    agent_header(field, &allowed),
+                    None,
+                    passes(),
+                ));
+                let unlisted = agents.synthesize("unlisted");
+                tests.push(scenario(
+                    format!("allowlist: unlisted agent '{}' gets 403", unlisted),
+                    "allowlist",
+                    agent_header(field, &unlisted),
+                    None,
+                    status(403),
+                ));
+            }
+            PolicyRule::Denylist { field, values } => {
+                let denied = &values[0];
+                tests.push(scenario(
+                    format!("denylist: denied agent '{}' gets 403", denied),
+                    "denylist",
+                    agent_header(field, denied),
+                    None,
+                    status(403),
+                ));
+                let allowed = agents.next_allowed();
+                tests.push(scenario(
+                    format!("denylist: other agent '{}' passes", allowed),
+                    "denylist",
+                    agent_header(field, &allowed),
+                    None,
+                    passes(),
+                ));
+            }
+            PolicyRule::RateLimit {
+                max_requests,
+                window_seconds,
+            } => {
+                let agent = agents.next_allowed();
+                let over = max_requests.saturating_add(1);
+                tests.push(scenario(
+                    format!(
+                        "rate_limit: request {} within {}s gets 429",
+                        over,
+                        window_seconds.as_secs()
+                    ),
+                    "rate_limit",
+                    agent_header("agent_id", &agent),
+                    Some(over),
+                    status(429),
+                ));
+            }
+            PolicyRule::SpendingCap {
+                max_amount,
+                currency,
+                ..
+            } => {
+                let agent = agents.next_allowed();
+                // First request whose charge would push the total past the cap
+                let repeat = ((max_amount / REQUEST_AMOUNT).floor() as u32).saturating_add(1);
+                tests.push(scenario(
+                    format!(
+                        "spending_cap: spending over {} {} gets no invoice",
+                        max_amount,
+                        normalize_currency(currency)
+                    ),
+                    "spending_cap",
+                    agent_header("agent_id", &agent),
+                    Some(repeat),
+                    Expectations {
+                        status_in: Some(vec![402, 403]),
+                        headers: Some(vec![header_exists(false)]),
+                        ..Expectations::default()
+                    },
+                ));
+            }
+            PolicyRule::ConcurrencyLimit { max_in_flight } => {
+                let agent = agents.next_allowed();
+                tests.push(scenario(
+                    format!(
+                        "concurrency_limit: single request under {} in flight passes",
+                        max_in_flight
+                    ),
+                    "concurrency_limit",
+                    agent_header("agent_id", &agent),
+                    None,
+                    passes(),
+                ));
+            }
+        }
+    }
+
+    TestSuite {
+        http: Default::default(),
+        variables: BTreeMap::from([(BASE_URL_VARIABLE.to_string(), DEFAULT_BASE_URL.to_string())]),
+        secrets: BTreeMap::new(),
+        timeout_ms: None,
+        total_timeout_ms: None,
+        budget: None,
+        preflight: None,
+        latency: None,
+        tests,
+    }
+}
+
+fn scenario(
+    name: String,
+    rule_type: &str,
+    headers: BTreeMap<String, String>,
+    repeat: Option<u32>,
+    expect: Expectations,
+) -> Test {
+    let mut tags = vec!["policy".to_string(), rule_type.to_string()];
+    if repeat.is_some_and(|r| r > SLOW_REPEAT) {
+        tags.push("slow".to_string());
+    }
+    Test {
+        name,
+        url: format!("${{{}}}", BASE_URL_VARIABLE),
+        method: "GET".to_string(),
+        tags,
+        headers,
+        body: None,
+        repeat,
+        timeout_ms: None,
+        expect,
+        extract: BTreeMap::new(),
+        then: None,
+        extends: None,
+    }
+}
+
+/// Request header the middleware reads the policy field from
+fn agent_header(field: &str, value: &str) -> BTreeMap<String, String> {
+    let header = match field {
+        "wallet_address" => "X-Wallet-Address",
+        _ => "X-Agent-Id",
+    };
+    BTreeMap::from([(header.to_string(), value.to_string())])
+}
+
+/// Passing every policy ends in the 402 invoice
+fn passes() -> Expectations {
+    Expectations {
+        status: Some(402),
+        headers: Some(vec![header_exists(true)]),
+        ..Expectations::default()
+    }
+}
+
+fn status(code: u16) -> Expectations {
+    Expectations {
+        status: Some(code),
+        ..Expectations::default()
+    }
+}
+
+fn header_exists(exists: bool) -> HeaderAssertion {
+    HeaderAssertion {
+        name: "WWW-Authenticate".to_string(),
+        exists: Some(exists),
+        value: None,
+        contains: None,
+        regex: None,
+    }
+}
+
+/// Agent IDs that satisfy the policy's allow/deny lists
+struct SampleAgents {
+    /// Allowlisted agents not also denied (empty without an allowlist)
+    allowed: Vec<String>,
+    /// Every listed value, so synthesized IDs never collide
+    listed: Vec<String>,
+    /// Shared prefix of listed IDs, e.g. `agent-`
+    prefix: String,
+    next: usize,
+}
+
+impl SampleAgents {
+    fn new(rules: &[PolicyRule]) -> Self {
+        let mut allowlist = Vec::new();
+        let mut listed = Vec::new();
+        let mut denied = Vec::new();
+        for rule in rules {
+            match rule {
+                PolicyRule::Allowlist { values, .. } => {
+                    allowlist.extend(values.iter().cloned());
+                    listed.extend(values.iter().cloned());
+                }
+                PolicyRule::Denylist { values, .. } => {
+                    denied.extend(values.iter().cloned());
+                    listed.extend(values.iter().cloned());
+                }
+                _ => {}
+            }
+        }
+        allowlist.retain(|a| !denied.contains(a));
+
+        Self {
+            prefix: common_prefix(&listed),
+            allowed: allowlist,
+            listed,
+            next: 0,
+        }
+    }
+
+    /// A fresh allowed agent per call, cycling through the allowlist
+    fn next_allowed(&mut self) -> String {
+        let index = self.next;
+        self.next += 1;
+        if self.allowed.is_empty() {
+            self.synthesize(&format!("test-{}", index + 1))
+        } else {
+            self.allowed[index % self.allowed.len()].clone()
+        }
+    }
+
+    /// An ID shaped like the listed ones that no list contains
+    fn synthesize(&self, label: &str) -> String {
+        let base = format!("{}x402-{}", self.prefix, label);
+        let mut candidate = base.clone();
+        let mut n = 2;
+        while self.listed.contains(&candidate) {
+            candidate = format!("{}-{}", base, n);
+            n += 1;
+        }
+        candidate
+    }
+}
+
+/// Longest common prefix ending at a separator (`agent-gpt4`, `agent-gemini` → `agent-`)
+fn common_prefix(values: &[String]) -> String {
+    let Some(first) = values.first() else {
+        return "agent-".to_string();
+    };
+    let mut len = first.len();
+    for value in &values[1..] {
+        len = first
+            .bytes()
+            .zip(value.bytes())
+            .take(len)
+            .take_while(|(a, b)| a == b)
+            .count();
+    }
+    let prefix = &first[..len];
+    match prefix.rfind(|c: char| !c.is_ascii_alphanumeric()) {
+        Some(end) => prefix[..=end].to_string(),
+        None if values.len() == 1 => "agent-".to_string(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const COMPREHENSIVE: &str =
+        include_str!("../../../../../tests/fixtures/policies/test_comprehensive.yaml");
+
+    fn comprehensive_suite() -> TestSuite {
+        let policy: PolicyFile = serde_yaml::from_str(COMPREHENSIVE).unwrap();
+        generate_test_suite(&policy)
+    }
+
+    #[test]
+    fn test_generated_suite_round_trips() {
+        let yaml = comprehensive_suite().to_yaml().unwrap();
+        let suite = <TestSuite as FromStr>::from_str(&yaml).unwrap();
+
+        assert_eq!(suite.variables[BASE_URL_VARIABLE], DEFAULT_BASE_URL);
+        assert!(suite.tests.iter().all(|t| t.url == "${base_url}"));
+        assert_eq!(
+            suite.interpolate(&suite.tests[0].url).unwrap(),
+            DEFAULT_BASE_URL
+        );
+    }
+
+    #[test]
+    fn test_one_scenario_per_rule_type() {
+        let suite = comprehensive_suite();
+        let names: Vec<&str> = suite.tests.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "allowlist: listed agent 'agent-gpt4' passes",
+                "allowlist: unlisted agent 'agent-x402-unlisted' gets 403",
+                "rate_limit: request 101 within 3600s gets 429",
+                "spending_cap: spending over 10 USDC gets no invoice",
+            ]
+        );
+
+        // Each scenario uses its own allowlisted agent
+        let agents: Vec<&str> = suite
+            .tests
+            .iter()
+            .map(|t| t.headers["X-Agent-Id"].as_str())
+            .collect();
+        assert_eq!(
+            agents,
+            vec![
+                "agent-gpt4",
+                "agent-x402-unlisted",
+                "agent-claude",
+                "agent-gemini"
+            ]
+        );
+
+        let rate_limit = &suite.tests[2];
+        assert_eq!(rate_limit.repeat, Some(101));
+        assert_eq!(rate_limit.expect.status, Some(429));
+        assert!(rate_limit.tags.contains(&"slow".to_string()));
+
+        let spending = &suite.tests[3];
+        assert_eq!(spending.repeat, Some(1001));
+        assert_eq!(spending.expect.status_in, Some(vec![402, 403]));
+    }
+
+    #[test]
+    fn test_denylist_and_synthesized_agents() {
+        let policy: PolicyFile = serde_yaml::from_str(
+            r#"
+policies:
+  - type: denylist
+    field: agent_id
+    values: ["bot-evil", "bot-spam"]
+  - type: concurrency_limit
+    max_in_flight: 2
+"#,
+        )
+        .unwrap();
+        let suite = generate_test_suite(&policy);
+
+        let agents: Vec<&str> = suite
+            .tests
+            .iter()
+            .map(|t| t.headers["X-Agent-Id"].as_str())
+            .collect();
+        assert_eq!(
+            agents,
+            vec!["bot-evil", "bot-x402-test-1", "bot-x402-test-2"]
+        );
+        assert_eq!(suite.tests[0].expect.status, Some(403));
+        assert_eq!(suite.tests[1].expect.status, Some(402));
+        assert_eq!(suite.tests[2].tags, vec!["policy", "concurrency_limit"]);
+    }
+}
@@ -0,0 +1,281 @@
+// Stable codes of policy validation rules and their suppression
+//
+// Every issue `validate_policies` and `lint_policies` report carries one of
+// these codes, so CI output and `suppress:` lists keep working when messages
+// are reworded. Validation rules are `X402-P*`, style lint rules `X402-L*`.
+// Codes are never renumbered; retired rules keep their number unused.
+
+use super::validator::IssueType;
+use crate::format::{DetectedFormat, FileFormat};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Code of a policy validation or lint rule, e.g. `X402-P010`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum RuleCode {
+    /// A value in both an allowlist and a denylist for the same field
+    AllowDenyConflict,
+    /// A rule with missing or invalid settings
+    InvalidPolicy,
+    /// A spending cap in a currency missing from the currency registry
+    UnknownCurrency,
+    /// More than one rate limit
+    MultipleRateLimits,
+    /// More than one spending cap
+    MultipleSpendingCaps,
+    /// A file without rules
+    NoPolicies,
+    /// Every rule is valid
+    AllValid,
+    /// Lint: allowlist or denylist values out of order
+    UnsortedValues,
+    /// Lint: a window that is not a round duration
+    IrregularWindow,
+    /// Lint: a spending cap currency that is not a token symbol
+    ImplicitCurrency,
+    /// Lint: an allowlist matching every agent in a production project
+    WildcardAgent,
+}
+
+impl RuleCode {
+    /// Every rule, in code order (the `policy rules` listing)
+    pub const ALL: [RuleCode; 11] = [
+        RuleCode::AllowDenyConflict,
+        RuleCode::InvalidPolicy,
+        RuleCode::UnknownCurrency,
+        RuleCode::MultipleRateLimits,
+        RuleCode::MultipleSpendingCaps,
+        RuleCode::NoPolicies,
+        RuleCode::AllValid,
+        RuleCode::UnsortedValues,
+        RuleCode::IrregularWindow,
+        RuleCode::ImplicitCurrency,
+        RuleCode::WildcardAgent,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleCode::AllowDenyConflict => "X402-P001",
+            RuleCode::InvalidPolicy => "X402-P002",
+            RuleCode::UnknownCurrency => "X402-P003",
+            RuleCode::MultipleRateLimits => "X402-P010",
+            RuleCode::MultipleSpendingCaps => "X402-P011",
+            RuleCode::NoPolicies => "X402-P100",
+            RuleCode::AllValid => "X402-P101",
+            RuleCode::UnsortedValues => "X402-L001",
+            RuleCode::IrregularWindow => "X402-L002",
+            RuleCode::ImplicitCurrency => "X402-L003",
+            RuleCode::WildcardAgent => "X402-L004",
+        }
+    }
+
+    /// True for style rules checked by `lint_policies` rather than validation
+    pub fn is_lint(&self) -> bool {
+        matches!(
+            self,
+            RuleCode::UnsortedValues
+                | RuleCode::IrregularWindow
+                | RuleCode::ImplicitCurrency
+                | RuleCode::WildcardAgent
+        )
+    }
+
+    /// Severity of the issues this rule reports (lint rules: the default,
+    /// which a project's `lint.rules` can override)
+    pub fn severity(&self) -> IssueType {
+        match self {
+            RuleCode::AllowDenyConflict | RuleCode::InvalidPolicy | RuleCode::UnknownCurrency => {
+                IssueType::Error
+            }
+            RuleCode::MultipleRateLimits | RuleCode::MultipleSpendingCaps => IssueType::Warning,
+            RuleCode::NoPolicies | RuleCode::AllValid => IssueType::Info,
+            RuleCode::UnsortedValues | RuleCode::ImplicitCurrency | RuleCode::WildcardAgent => {
+                IssueType::Warning
+            }
+            RuleCode::IrregularWindow => IssueType::Info,
+        }
+    }
+
+    /// One-line description for the `policy rules` listing
+    pub fn summary(&self) -> &'static str {
+        match self {
+            RuleCode::AllowDenyConflict => {
+                "A value is in both an allowlist and a denylist for the same field"
+            }
+            RuleCode::InvalidPolicy => "A policy rule has missing or invalid settings",
+            RuleCode::UnknownCurrency => "A spending cap currency is not a known currency",
+            RuleCode::MultipleRateLimits => "More than one rate limit is defined",
+            RuleCode::MultipleSpendingCaps => "More than one spending cap is defined",
+            RuleCode::NoPolicies => "The file defines no policy rules",
+            RuleCode::AllValid => "Every policy rule is valid",
+            RuleCode::UnsortedValues => "Allowlist or denylist values are not sorted",
+            RuleCode::IrregularWindow => {
+                "A rate limit or spending cap window is not a round duration"
+            }
+            RuleCode::ImplicitCurrency => {
+                "A spending cap currency is not an explicit token symbol, e.g. USDC"
+            }
+            RuleCode::WildcardAgent => "An allowlist admits every agent ('*') in production",
+        }
+    }
+
+    /// Error unless issues of this rule may be suppressed (errors may not)
+    pub fn check_suppressible(&self) -> Result<()> {
+        if self.severity() == IssueType::Error {
+            bail!(
+                "{} is an error and cannot be suppressed\nFix: Fix the policy file; only warnings can be suppressed",
+                self
+            );
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RuleCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for RuleCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl FromStr for RuleCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RuleCode::ALL
+            .into_iter()
+            .find(|code| code.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "unknown rule code '{}' (run `x402-dev policy rules` for the list)",
+                    s
+                )
+            })
+    }
+}
+
+impl TryFrom<String> for RuleCode {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Rule codes a policy file acknowledges with `suppress:` lists
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Suppressions {
+    /// Top-level `suppress:`, covering the whole file
+    pub file: Vec<RuleCode>,
+    /// `suppress:` of each policy rule, by index
+    pub rules: Vec<Vec<RuleCode>>,
+}
+
+#[derive(Deserialize)]
+struct SuppressDoc {
+    #[serde(default)]
+    suppress: Vec<RuleCode>,
+    #[serde(default)]
+    policies: Vec<SuppressEntry>,
+}
+
+#[derive(Deserialize)]
+struct SuppressEntry {
+    #[serde(default)]
+    suppress: Vec<RuleCode>,
+}
+
+impl Suppressions {
+    /// Read the `suppress:` lists of a policy YAML document
+    ///
+    /// Unknown codes and codes of error rules are rejected.
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        Self::parse(yaml, DetectedFormat::given(FileFormat::Yaml))
+    }
+
+    /// Read the `suppress:` lists of a policy document in YAML or JSON
+    pub fn parse(content: &str, format: DetectedFormat) -> Result<Self> {
+        let doc: SuppressDoc = format
+            .parse(content)
+            .context("Invalid suppress list in policy file")?;
+        let suppressions = Self {
+            file: doc.suppress,
+            rules: doc.policies.into_iter().map(|p| p.suppress).collect(),
+        };
+        for code in suppressions
+            .file
+            .iter()
+            .chain(suppressions.rules.iter().flatten())
+        {
+            code.check_suppressible()?;
+        }
+        Ok(suppressions)
+    }
+
+    /// Also suppress `codes` everywhere (e.g. from `--allow`)
+    pub fn allow(&mut self, codes: &[RuleCode]) {
+        self.file.extend_from_slice(codes);
+    }
+
+    /// True if an issue of `code` about `policy_indices` is suppressed:
+    /// by the file, or by any rule it involves
+    pub fn covers(&self, code: RuleCode, policy_indices: &[usize]) -> bool {
+        self.file.contains(&code)
+            || policy_indices
+                .iter()
+                .any(|&i| self.rules.get(i).is_some_and(|codes| codes.contains(&code)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_are_unique_and_parse() {
+        for code in RuleCode::ALL {
+            assert_eq!(code.as_str().parse::<RuleCode>(), Ok(code));
+            assert_eq!(
+                RuleCode::ALL
+                    .iter()
+                    .filter(|c| c.as_str() == code.as_str())
+                    .count(),
+                1
+            );
+        }
+        assert_eq!("x402-p010".parse(), Ok(RuleCode::MultipleRateLimits));
+        assert!("X402-P999".parse::<RuleCode>().is_err());
+    }
+
+    #[test]
+    fn test_suppressions_from_yaml() {
+        let yaml = r#"
+suppress: [X402-P011]
+policies:
+  - type: rate_limit
+    max_requests: 10
+    window_seconds: 60
+  - type: rate_limit
+    max_requests: 20
+    window_seconds: 60
+    suppress: [X402-P010]
+"#;
+        let suppressions = Suppressions::from_yaml(yaml).unwrap();
+        assert!(suppressions.covers(RuleCode::MultipleSpendingCaps, &[]));
+        assert!(suppressions.covers(RuleCode::MultipleRateLimits, &[0, 1]));
+        assert!(!suppressions.covers(RuleCode::MultipleRateLimits, &[0]));
+
+        let err = Suppressions::from_yaml("suppress: [X402-P001]\npolicies: []\n").unwrap_err();
+        assert!(err.to_string().contains("X402-P001 is an error"));
+        let err = Suppressions::from_yaml("suppress: [nope]\npolicies: []\n").unwrap_err();
+        assert!(format!("{:#}", err).contains("unknown rule code 'nope'"));
+    }
+}
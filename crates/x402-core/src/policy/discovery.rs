@@ -0,0 +1,163 @@
+// Workspace policy discovery
+//
+// Finds the policy files a project uses when no path is given: the files
+// listed in config plus every YAML or JSON file under the policies directory,
+// minus anything matched by .x402ignore (gitignore syntax).
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::{Path, PathBuf};
+
+use crate::format::FileFormat;
+
+/// Directory scanned when the config names none
+pub const DEFAULT_POLICIES_DIR: &str = "x402-policies";
+
+/// Ignore file read from the workspace root
+pub const IGNORE_FILE: &str = ".x402ignore";
+
+/// Discover policy files below `root`
+///
+/// `policy_files` are always considered; `policies_dir` (default
+/// `x402-policies/`) is scanned recursively for `.yaml`/`.yml`/`.json` files.
+/// Relative paths resolve against `root`. The result is sorted, without
+/// duplicates, and excludes paths matched by `root/.x402ignore`.
+pub fn discover_policy_files(
+    root: &Path,
+    policies_dir: Option<&Path>,
+    policy_files: &[PathBuf],
+) -> Result<Vec<PathBuf>> {
+    let ignore = load_ignore(root)?;
+    let mut found: Vec<PathBuf> = policy_files.iter().map(|f| root.join(f)).collect();
+
+    let dir = root.join(policies_dir.unwrap_or(Path::new(DEFAULT_POLICIES_DIR)));
+    if dir.is_dir() {
+        collect_policy_files(&dir, &ignore, &mut found)?;
+    }
+
+    found.retain(|path| !is_ignored(&ignore, path, false));
+    found.sort();
+    found.dedup();
+    Ok(found)
+}
+
+/// True for `.yaml` and `.yml` files
+pub fn is_yaml_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yaml" | "yml")
+    )
+}
+
+fn load_ignore(root: &Path) -> Result<Gitignore> {
+    let mut builder = GitignoreBuilder::new(root);
+    let ignore_path = root.join(IGNORE_FILE);
+    if ignore_path.is_file() {
+        if let Some(err) = builder.add(&ignore_path) {
+            return Err(err).with_context(|| format!("Invalid {}", ignore_path.display()));
+        }
+    }
+    builder
+        .build()
+        .with_context(|| format!("Invalid {}", ignore_path.display()))
+}
+
+fn is_ignored(ignore: &Gitignore, path: &Path, is_dir: bool) -> bool {
+    // Paths outside the root (explicit absolute entries) cannot be matched
+    path.starts_with(ignore.path()) && ignore.matched_path_or_any_parents(path, is_dir).is_ignore()
+}
+
+fn collect_policy_files(dir: &Path, ignore: &Gitignore, found: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read policies directory: {}", dir.display()))?;
+
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            if !is_ignored(ignore, &path, true) {
+                collect_policy_files(&path, ignore, found)?;
+            }
+        } else if FileFormat::from_path(&path).is_some() {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn names(root: &Path, files: &[PathBuf]) -> Vec<String> {
+        files
+            .iter()
+            .map(|f| {
+                f.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_discovers_documents_in_default_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let policies = dir.path().join(DEFAULT_POLICIES_DIR);
+        fs::create_dir_all(policies.join("nested")).unwrap();
+        fs::write(policies.join("b.yaml"), "").unwrap();
+        fs::write(policies.join("a.yml"), "").unwrap();
+        fs::write(policies.join("nested/c.yaml"), "").unwrap();
+        fs::write(policies.join("nested/d.json"), "").unwrap();
+        fs::write(policies.join("README.md"), "").unwrap();
+
+        let found = discover_policy_files(dir.path(), None, &[]).unwrap();
+        assert_eq!(
+            names(dir.path(), &found),
+            vec![
+                "x402-policies/a.yml",
+                "x402-policies/b.yaml",
+                "x402-policies/nested/c.yaml",
+                "x402-policies/nested/d.json"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_configured_dir_files_and_ignore() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("rules/drafts")).unwrap();
+        fs::write(dir.path().join("rules/main.yaml"), "").unwrap();
+        fs::write(dir.path().join("rules/old.yaml"), "").unwrap();
+        fs::write(dir.path().join("rules/drafts/wip.yaml"), "").unwrap();
+        fs::write(dir.path().join("extra.yaml"), "").unwrap();
+        fs::write(
+            dir.path().join(IGNORE_FILE),
+            "# comment\ndrafts/\nold.yaml\n",
+        )
+        .unwrap();
+
+        let found = discover_policy_files(
+            dir.path(),
+            Some(Path::new("rules")),
+            &[
+                PathBuf::from("extra.yaml"),
+                PathBuf::from("rules/main.yaml"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            names(dir.path(), &found),
+            vec!["extra.yaml", "rules/main.yaml"]
+        );
+    }
+
+    #[test]
+    fn test_missing_dir_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(discover_policy_files(dir.path(), None, &[])
+            .unwrap()
+            .is_empty());
+    }
+}
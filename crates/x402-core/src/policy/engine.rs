@@ -1,33 +1,114 @@
 // Policy evaluation engine
 
-use super::runtime_types::{Policy, PolicyDecision, RateLimitConfig, Request, SpendingCapConfig};
-use super::state::PolicyState as RuntimePolicyState;
+use super::audit::{AuditDecision, AuditEntry, DecisionHook};
+use super::match_stats::MatchStats;
+use super::runtime_types::{
+    Policy, PolicyCandidate, PolicyDecision, PolicyMatchExplanation, QuotaStatus, RateLimitQuota,
+    Request, SpendingCapConfig, SpendingCapQuota,
+};
+use super::state::{InFlightSlots, MemoryStateStore};
+use super::store::{Admission, PolicyStateStore, RateCounter, SpendCounter};
 use super::types::PolicyAction;
-use anyhow::Result;
+use super::window::SpendingWindow;
+use crate::clock::{Clock, SystemClock};
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
 use std::time::SystemTime;
+use x402_domain::normalize_currency;
 
 /// Policy evaluation engine with runtime state tracking
 pub struct PolicyEngine {
     /// Ordered list of policies (sorted by priority)
     policies: Vec<Policy>,
-    /// Runtime state for rate limiting and spending tracking
-    state: RuntimePolicyState,
+    /// Rate limit and spending state
+    store: Arc<dyn PolicyStateStore>,
+    /// Concurrency slots of requests in flight in this process
+    slots: InFlightSlots,
+    /// Hits per policy plus `default`, when enabled
+    match_stats: Option<MatchStats>,
+    /// Time of requests built by callers and of quota lookups
+    clock: Arc<dyn Clock>,
+    /// Notified of every decision, e.g. an audit log
+    hooks: Vec<Arc<dyn DecisionHook>>,
 }
 
+/// Rule name under which requests no policy matched are counted
+pub const DEFAULT_POLICY_ID: &str = "default";
+
+/// Start of the reason of a rate limit denial
+pub const RATE_LIMIT_EXCEEDED: &str = "Rate limit exceeded";
+
+/// Start of the reason of a spending cap denial
+pub const SPENDING_CAP_EXCEEDED: &str = "Spending cap exceeded";
+
 impl PolicyEngine {
-    /// Create a new policy engine with the given policies
+    /// Create a new policy engine with the given policies and in-memory state
     ///
     /// Policies are automatically sorted by priority (highest first)
-    pub fn new(mut policies: Vec<Policy>) -> Self {
+    pub fn new(policies: Vec<Policy>) -> Self {
+        Self::with_store(policies, Arc::new(MemoryStateStore::new()))
+    }
+
+    /// Create a policy engine keeping its state in `store`
+    pub fn with_store(mut policies: Vec<Policy>, store: Arc<dyn PolicyStateStore>) -> Self {
         // Sort by priority (higher priority evaluated first)
-        policies.sort_by(|a, b| b.priority.cmp(&a.priority));
+        policies.sort_by_key(|p| std::cmp::Reverse(p.priority));
 
         Self {
             policies,
-            state: RuntimePolicyState::new(),
+            store,
+            slots: InFlightSlots::new(),
+            match_stats: None,
+            clock: Arc::new(SystemClock),
+            hooks: Vec::new(),
         }
     }
 
+    /// Read the time from `clock` instead of the system clock
+    ///
+    /// Rate limit and spending windows follow the timestamps of evaluated
+    /// requests, so callers stamp them with [`Self::now`].
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Current time of the engine's clock
+    pub fn now(&self) -> SystemTime {
+        self.clock.now()
+    }
+
+    /// Clock the engine reads the time from
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Count which policy decides each request (see [`Self::match_stats`])
+    pub fn with_match_stats(mut self) -> Self {
+        let rules = self.policies.iter().map(|p| p.id.as_str());
+        self.match_stats = Some(MatchStats::new(rules.chain([DEFAULT_POLICY_ID])));
+        self
+    }
+
+    /// Hits per policy in evaluation order, then `default`; `None` unless
+    /// enabled with [`Self::with_match_stats`]
+    pub fn match_stats(&self) -> Option<&MatchStats> {
+        self.match_stats.as_ref()
+    }
+
+    /// Hand every decision of [`Self::evaluate`] to `hook`
+    ///
+    /// Evaluation errors are not decisions and are not reported.
+    pub fn with_decision_hook(mut self, hook: Arc<dyn DecisionHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Hooks added with [`Self::with_decision_hook`]
+    pub fn decision_hooks(&self) -> &[Arc<dyn DecisionHook>] {
+        &self.hooks
+    }
+
     /// Evaluate a request against all policies
     ///
     /// Policies are evaluated in priority order. First deny policy that matches
@@ -40,13 +121,58 @@ impl PolicyEngine {
     /// # FR-5.2: Fail-fast on Deny
     /// Returns immediately on first deny policy match.
     pub fn evaluate(&self, request: &Request) -> Result<PolicyDecision> {
+        self.evaluate_counting(request, true)
+    }
+
+    /// Evaluate the paid retry of a request [`Self::evaluate`] allowed
+    ///
+    /// Patterns, deny actions and concurrency limits apply as usual, but
+    /// rate limits and spending caps are neither checked nor counted: the
+    /// request was counted when its invoice was issued.
+    pub fn evaluate_paid(&self, request: &Request) -> Result<PolicyDecision> {
+        self.evaluate_counting(request, false)
+    }
+
+    fn evaluate_counting(&self, request: &Request, count: bool) -> Result<PolicyDecision> {
+        let decision = self.decide(request, count)?;
+        if !self.hooks.is_empty() {
+            self.notify_hooks(request, &decision);
+        }
+        Ok(decision)
+    }
+
+    /// Report `decision` with the quota left after it
+    fn notify_hooks(&self, request: &Request, decision: &PolicyDecision) {
+        let (audit_decision, policy_id, reason) = match decision {
+            PolicyDecision::Allow { policy_id, .. } => (AuditDecision::Allow, policy_id, None),
+            PolicyDecision::Deny { reason, policy_id } => {
+                (AuditDecision::Deny, policy_id, Some(reason.clone()))
+            }
+        };
+        let entry = AuditEntry {
+            timestamp: request.timestamp.into(),
+            request_id: request.request_id.clone(),
+            agent: request.agent_id.clone(),
+            endpoint: request.endpoint.clone(),
+            amount: request.amount,
+            decision: audit_decision,
+            policy_id: policy_id.clone(),
+            reason,
+            quota: self.quota(policy_id, &request.agent_id, request.timestamp),
+        };
+        for hook in &self.hooks {
+            hook.on_decision(&entry);
+        }
+    }
+
+    fn decide(&self, request: &Request, count: bool) -> Result<PolicyDecision> {
         let now = request.timestamp;
 
         // Cleanup expired state before evaluation
-        self.state.cleanup_expired(now);
+        self.store.cleanup(now)?;
 
         // Evaluate policies in order (highest priority first)
-        for policy in &self.policies {
+        for (position, policy) in self.policies.iter().enumerate() {
             // Check if policy applies to this request
             if !self.matches_patterns(&policy.agent_patterns, &request.agent_id) {
                 continue;
@@ -56,31 +182,49 @@ impl PolicyEngine {
                 continue;
             }
 
+            // Every path below decides the request with this policy
+            if let Some(stats) = &self.match_stats {
+                stats.record_at(position, now);
+            }
+
+            let rate_key = rate_key(&policy.id, &request.agent_id);
+            let rate = policy.rate_limit.as_ref().map(|config| RateCounter {
+                key: &rate_key,
+                window: config.window,
+                max_requests: config.max_requests,
+            });
+            let spend_key = spend_key(&policy.id, &request.agent_id);
+            let window = policy
+                .spending_cap
+                .as_ref()
+                .map(|config| spending_window(policy, config))
+                .transpose()?;
+            // Free requests neither count against nor are held back by a cap
+            let spend = policy
+                .spending_cap
+                .as_ref()
+                .zip(window.as_ref())
+                .filter(|_| request.amount > 0.0)
+                .map(|(config, window)| SpendCounter {
+                    key: &spend_key,
+                    window,
+                    // Convert f64 amounts to u64 cents (multiply by 100)
+                    max_amount: (config.max_amount * 100.0) as u64,
+                    amount: (request.amount * 100.0) as u64,
+                    memo_id: request.memo_id.as_deref(),
+                });
+
             // Check rate limit if configured (FR-5.4)
-            if let Some(rate_config) = &policy.rate_limit {
-                if !self.check_rate_limit(policy, request, rate_config, now)? {
-                    return Ok(PolicyDecision::Deny {
-                        reason: format!(
-                            "Rate limit exceeded: {} requests per {}s",
-                            rate_config.max_requests,
-                            rate_config.window.as_secs()
-                        ),
-                        policy_id: policy.id.clone(),
-                    });
+            if let Some(rate) = rate.as_ref().filter(|_| count) {
+                if !self.check_rate_limit(rate, now)? {
+                    return Ok(rate_limit_denial(policy, rate));
                 }
             }
 
             // Check spending cap if configured (FR-5.5)
-            if let Some(spending_config) = &policy.spending_cap {
-                if !self.check_spending_cap(policy, request, spending_config, now)? {
-                    return Ok(PolicyDecision::Deny {
-                        reason: format!(
-                            "Spending cap exceeded: {} limit per {}s",
-                            spending_config.max_amount,
-                            spending_config.window.as_secs()
-                        ),
-                        policy_id: policy.id.clone(),
-                    });
+            if let Some(spend) = spend.as_ref().filter(|_| count) {
+                if !self.check_spending_cap(spend, now)? {
+                    return Ok(spending_cap_denial(policy, spend));
                 }
             }
 
@@ -93,23 +237,105 @@ impl PolicyEngine {
                     });
                 }
                 PolicyAction::Allow => {
-                    // Update state for rate limiting and spending tracking
-                    self.update_state(policy, request, now)?;
-                    // Return immediately on allow (priority wins)
-                    return Ok(PolicyDecision::Allow {
-                        policy_id: policy.id.clone(),
-                    });
+                    // Take an in-flight slot before counting the request,
+                    // so a concurrency denial doesn't consume rate limit quota
+                    let guard = match &policy.concurrency_limit {
+                        Some(limit) => {
+                            let key = format!("conc:{}:{}", policy.id, request.agent_id);
+                            match self.slots.try_acquire_slot(&key, limit.max_in_flight) {
+                                Some(guard) => Some(guard),
+                                None => {
+                                    return Ok(PolicyDecision::Deny {
+                                        reason: format!(
+                                            "Concurrency limit exceeded: {} requests in flight",
+                                            limit.max_in_flight
+                                        ),
+                                        policy_id: policy.id.clone(),
+                                    });
+                                }
+                            }
+                        }
+                        None => None,
+                    };
+
+                    if !count {
+                        return Ok(PolicyDecision::Allow {
+                            policy_id: policy.id.clone(),
+                            guard,
+                        });
+                    }
+
+                    // Count the request; a concurrent request may have
+                    // taken the last of the quota since the checks above
+                    let admission = self.store.try_admit(rate, spend, now)?;
+                    return match (admission, &rate, &spend) {
+                        // Return immediately on allow (priority wins)
+                        (Admission::Admitted, _, _) => Ok(PolicyDecision::Allow {
+                            policy_id: policy.id.clone(),
+                            guard,
+                        }),
+                        (Admission::RateLimited, Some(rate), _) => Ok(rate_limit_denial(policy, rate)),
+                        (Admission::SpendingCapped, _, Some(spend)) => {
+                            Ok(spending_cap_denial(policy, spend))
+                        }
+                        (admission, _, _) => Err(anyhow!(
+                            "Policy '{}': state store returned {:?} for a limit the policy does not set",
+                            policy.id,
+                            admission
+                        )),
+                    };
                 }
             }
         }
 
         // No policies matched - default deny
+        if let Some(stats) = &self.match_stats {
+            stats.record_at(self.policies.len(), now);
+        }
         Ok(PolicyDecision::Deny {
             reason: "No matching allow policy".to_string(),
-            policy_id: "default".to_string(),
+            policy_id: DEFAULT_POLICY_ID.to_string(),
         })
     }
 
+    /// Policies considered for `request` in evaluation order, with the one
+    /// [`Self::evaluate`] would apply marked
+    ///
+    /// Only patterns are compared: no state is read or recorded, so a
+    /// winner with a rate limit or spending cap may still deny.
+    pub fn explain(&self, request: &Request) -> PolicyMatchExplanation {
+        let mut winner = None;
+        let candidates = self
+            .policies
+            .iter()
+            .map(|policy| {
+                let agent_matched =
+                    self.matches_patterns(&policy.agent_patterns, &request.agent_id);
+                let endpoint_matched =
+                    self.matches_patterns(&policy.endpoint_patterns, &request.endpoint);
+                let wins = winner.is_none() && agent_matched && endpoint_matched;
+                if wins {
+                    winner = Some(policy.id.clone());
+                }
+                PolicyCandidate {
+                    policy_id: policy.id.clone(),
+                    priority: policy.priority,
+                    action: policy.action.clone(),
+                    agent_matched,
+                    endpoint_matched,
+                    winner: wins,
+                }
+            })
+            .collect();
+
+        PolicyMatchExplanation {
+            agent_id: request.agent_id.clone(),
+            endpoint: request.endpoint.clone(),
+            candidates,
+            winner: winner.unwrap_or_else(|| DEFAULT_POLICY_ID.to_string()),
+        }
+    }
+
     /// Check if a value matches any pattern in the list
     ///
     /// # FR-5.3: Wildcard Pattern Matching
@@ -194,61 +420,57 @@ impl PolicyEngine {
     ///
     /// # FR-5.4: Sliding Window Rate Limiting
     /// Implements sliding window algorithm, not fixed window
-    fn check_rate_limit(
-        &self,
-        policy: &Policy,
-        request: &Request,
-        config: &RateLimitConfig,
-        now: SystemTime,
-    ) -> Result<bool> {
-        let key = format!("rate:{}:{}", policy.id, request.agent_id);
-        let state = self.state.get_rate_limit_state(&key);
-
-        Ok(state.check_limit(config.window, config.max_requests, now))
+    fn check_rate_limit(&self, rate: &RateCounter<'_>, now: SystemTime) -> Result<bool> {
+        Ok(rate.allows(self.store.rate_count(rate.key, rate.window, now)?))
     }
 
     /// Check spending cap for a policy
     ///
     /// # FR-5.5: Spending Cap Tracking
-    /// Tracks spending within configurable time windows
-    fn check_spending_cap(
-        &self,
-        policy: &Policy,
-        request: &Request,
-        config: &SpendingCapConfig,
-        now: SystemTime,
-    ) -> Result<bool> {
-        let key = format!("spend:{}:{}", policy.id, request.agent_id);
-        let state = self.state.get_spending_state(&key);
-
-        // Convert f64 amounts to u64 cents (multiply by 100)
-        let max_amount_cents = (config.max_amount * 100.0) as u64;
-        let request_amount_cents = (request.amount * 100.0) as u64;
-
-        Ok(state.check_cap(config.window, max_amount_cents, request_amount_cents, now))
-    }
-
-    /// Update state after allowing a request
-    fn update_state(&self, policy: &Policy, request: &Request, now: SystemTime) -> Result<()> {
-        // Update rate limit state
-        if policy.rate_limit.is_some() {
-            let key = format!("rate:{}:{}", policy.id, request.agent_id);
-            let mut state = self.state.get_rate_limit_state(&key);
-            state.add_request(now);
-            self.state.update_rate_limit_state(key, state);
-        }
+    /// Tracks spending within rolling or calendar-aligned windows
+    fn check_spending_cap(&self, spend: &SpendCounter<'_>, now: SystemTime) -> Result<bool> {
+        Ok(spend.allows(self.store.spent(spend.key, spend.window, now)?))
+    }
 
-        // Update spending state
-        if policy.spending_cap.is_some() {
-            let key = format!("spend:{}:{}", policy.id, request.agent_id);
-            let mut state = self.state.get_spending_state(&key);
-            // Convert f64 to u64 cents
-            let amount_cents = (request.amount * 100.0) as u64;
-            state.add_spending(now, amount_cents);
-            self.state.update_spending_state(key, state);
+    /// Remaining rate limit and spending allowance of `agent_id` under a policy
+    ///
+    /// Unknown policy IDs (including the `default` deny) report no quota.
+    pub fn quota(&self, policy_id: &str, agent_id: &str, now: SystemTime) -> QuotaStatus {
+        let Some(policy) = self.policies.iter().find(|p| p.id == policy_id) else {
+            return QuotaStatus::default();
+        };
+
+        // A part the store cannot read is reported as absent
+        let rate_limit = policy.rate_limit.as_ref().and_then(|config| {
+            let key = rate_key(&policy.id, agent_id);
+            let used = self.store.rate_count(&key, config.window, now).ok()?;
+            Some(RateLimitQuota {
+                max_requests: config.max_requests,
+                used,
+                remaining: config.max_requests.saturating_sub(used),
+                window_seconds: config.window.as_secs(),
+            })
+        });
+
+        let spending_cap = policy.spending_cap.as_ref().and_then(|config| {
+            let window = config.spending_window().ok()?;
+            let key = spend_key(&policy.id, agent_id);
+            let spent_cents = self.store.spent(&key, &window, now).ok()?;
+            let spent = spent_cents as f64 / 100.0;
+            Some(SpendingCapQuota {
+                max_amount: config.max_amount,
+                spent,
+                remaining: (config.max_amount - spent).max(0.0),
+                currency: normalize_currency(&config.currency),
+                window_seconds: window.length_at(now).as_secs(),
+                window_type: window.window_type(),
+            })
+        });
+
+        QuotaStatus {
+            rate_limit,
+            spending_cap,
         }
-
-        Ok(())
     }
 
     /// Get the current policies
@@ -256,15 +478,60 @@ impl PolicyEngine {
         &self.policies
     }
 
-    /// Get a reference to the policy state
-    pub fn state(&self) -> &RuntimePolicyState {
-        &self.state
+    /// Store holding the rate limit and spending state
+    pub fn store(&self) -> &Arc<dyn PolicyStateStore> {
+        &self.store
+    }
+
+    /// Concurrency slots held for a key (`conc:<policy_id>:<agent_id>`)
+    pub fn in_flight(&self, key: &str) -> u32 {
+        self.slots.in_flight(key)
     }
 }
 
+fn rate_key(policy_id: &str, agent_id: &str) -> String {
+    format!("rate:{}:{}", policy_id, agent_id)
+}
+
+fn spend_key(policy_id: &str, agent_id: &str) -> String {
+    format!("spend:{}:{}", policy_id, agent_id)
+}
+
+fn rate_limit_denial(policy: &Policy, rate: &RateCounter<'_>) -> PolicyDecision {
+    PolicyDecision::Deny {
+        reason: format!(
+            "{}: {} requests per {}s",
+            RATE_LIMIT_EXCEEDED,
+            rate.max_requests,
+            rate.window.as_secs()
+        ),
+        policy_id: policy.id.clone(),
+    }
+}
+
+fn spending_cap_denial(policy: &Policy, spend: &SpendCounter<'_>) -> PolicyDecision {
+    let max_amount = policy.spending_cap.as_ref().map_or(0.0, |c| c.max_amount);
+    PolicyDecision::Deny {
+        reason: format!(
+            "{}: {} limit per {}",
+            SPENDING_CAP_EXCEEDED, max_amount, spend.window
+        ),
+        policy_id: policy.id.clone(),
+    }
+}
+
+/// Window of a policy's spending cap; an invalid configuration is an error
+fn spending_window(policy: &Policy, config: &SpendingCapConfig) -> Result<SpendingWindow> {
+    config
+        .spending_window()
+        .map_err(|e| anyhow!("Policy '{}': invalid spending_cap: {}", policy.id, e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::policy::runtime_types::{ConcurrencyLimitConfig, RateLimitConfig};
+    use crate::policy::window::WindowType;
     use std::time::Duration;
 
     fn create_test_request(agent_id: &str, amount: u64, endpoint: &str) -> Request {
@@ -275,6 +542,8 @@ mod tests {
             amount: amount as f64,
             endpoint: endpoint.to_string(),
             timestamp: SystemTime::now(),
+            memo_id: None,
+            request_id: None,
         }
     }
 
@@ -288,6 +557,7 @@ mod tests {
             endpoint_patterns: vec![],
             rate_limit: None,
             spending_cap: None,
+            concurrency_limit: None,
         }
     }
 
@@ -301,6 +571,7 @@ mod tests {
             endpoint_patterns: vec![],
             rate_limit: None,
             spending_cap: None,
+            concurrency_limit: None,
         }
     }
 
@@ -391,6 +662,7 @@ mod tests {
                 endpoint_patterns: vec![],
                 rate_limit: None,
                 spending_cap: None,
+                concurrency_limit: None,
             },
             Policy {
                 id: "high-priority".to_string(),
@@ -401,6 +673,7 @@ mod tests {
                 endpoint_patterns: vec![],
                 rate_limit: None,
                 spending_cap: None,
+                concurrency_limit: None,
             },
         ];
 
@@ -447,6 +720,7 @@ mod tests {
                 window: Duration::from_secs(60),
             }),
             spending_cap: None,
+            concurrency_limit: None,
         }];
 
         let engine = PolicyEngine::new(policies);
@@ -464,6 +738,55 @@ mod tests {
         assert!(decision.is_denied());
     }
 
+    #[test]
+    fn test_quota_reports_remaining_allowance() {
+        let policies = vec![Policy {
+            id: "metered".to_string(),
+            description: "Metered policy".to_string(),
+            action: PolicyAction::Allow,
+            priority: 0,
+            agent_patterns: vec!["*".to_string()],
+            endpoint_patterns: vec![],
+            rate_limit: Some(RateLimitConfig {
+                max_requests: 3,
+                window: Duration::from_secs(60),
+            }),
+            spending_cap: Some(SpendingCapConfig {
+                max_amount: 1.0,
+                currency: "usdc".to_string(),
+                window: Some(Duration::from_secs(3600)),
+                window_type: WindowType::Rolling,
+                timezone: None,
+            }),
+            concurrency_limit: None,
+        }];
+        let engine = PolicyEngine::new(policies);
+
+        let mut request = create_test_request("agent-123", 0, "/api/test");
+        request.amount = 0.25;
+        engine.evaluate(&request).unwrap();
+        engine.evaluate(&request).unwrap();
+
+        let quota = engine.quota("metered", "agent-123", SystemTime::now());
+        let rate = quota.rate_limit.unwrap();
+        assert_eq!((rate.used, rate.remaining, rate.window_seconds), (2, 1, 60));
+        let spending = quota.spending_cap.unwrap();
+        assert_eq!((spending.spent, spending.remaining), (0.5, 0.5));
+        // Currencies are reported and compared in canonical case
+        assert_eq!(spending.currency, "USDC");
+        let cap = engine.policies()[0].spending_cap.as_ref().unwrap();
+        assert!(cap.counts_currency("USDC"));
+        assert!(!cap.counts_currency("SOL"));
+
+        // Other agents and unknown policies start fresh
+        let other = engine.quota("metered", "agent-456", SystemTime::now());
+        assert_eq!(other.rate_limit.unwrap().remaining, 3);
+        assert_eq!(
+            engine.quota("default", "agent-123", SystemTime::now()),
+            QuotaStatus::default()
+        );
+    }
+
     #[test]
     fn test_spending_cap() {
         let policies = vec![Policy {
@@ -477,8 +800,11 @@ mod tests {
             spending_cap: Some(SpendingCapConfig {
                 max_amount: 500.0,
                 currency: "USD".to_string(),
-                window: Duration::from_secs(3600),
+                window: Some(Duration::from_secs(3600)),
+                window_type: WindowType::Rolling,
+                timezone: None,
             }),
+            concurrency_limit: None,
         }];
 
         let engine = PolicyEngine::new(policies);
@@ -499,9 +825,121 @@ mod tests {
         assert!(decision.is_denied());
 
         // Smaller amount within limit should work
-        let request = create_test_request("agent-123", 50, "/api/test");
+        let request = Request {
+            memo_id: Some("550e8400-e29b-41d4-a716-446655440000".to_string()),
+            request_id: None,
+            ..create_test_request("agent-123", 50, "/api/test")
+        };
         let decision = engine.evaluate(&request).unwrap();
         assert!(decision.is_allowed());
+
+        // Spends are recorded with the invoice memo UUID when known
+        let records = engine
+            .store()
+            .spending_records("spend:spending-capped:agent-123")
+            .unwrap();
+        let memo_ids: Vec<Option<&str>> = records.iter().map(|r| r.memo_id.as_deref()).collect();
+        assert_eq!(
+            memo_ids,
+            [None, None, Some("550e8400-e29b-41d4-a716-446655440000")]
+        );
+    }
+
+    #[test]
+    fn test_spending_cap_ignores_zero_amounts() {
+        let policies = vec![Policy {
+            spending_cap: Some(SpendingCapConfig {
+                max_amount: 1.0,
+                currency: "USDC".to_string(),
+                window: Some(Duration::from_secs(3600)),
+                window_type: WindowType::Rolling,
+                timezone: None,
+            }),
+            ..create_allow_policy("capped", vec!["*".to_string()])
+        }];
+        let engine = PolicyEngine::new(policies);
+
+        // The cap is used up
+        let paid = create_test_request("agent-123", 1, "/api/test");
+        assert!(engine.evaluate(&paid).unwrap().is_allowed());
+        assert!(engine.evaluate(&paid).unwrap().is_denied());
+
+        // Free requests still pass and record no spend
+        let free = create_test_request("agent-123", 0, "/api/free");
+        assert!(engine.evaluate(&free).unwrap().is_allowed());
+        assert!(engine.evaluate(&free).unwrap().is_allowed());
+        let records = engine
+            .store()
+            .spending_records("spend:capped:agent-123")
+            .unwrap();
+        assert_eq!(records.len(), 1);
+    }
+
+    #[test]
+    fn test_calendar_day_spending_cap() {
+        let policies = vec![Policy {
+            spending_cap: Some(SpendingCapConfig {
+                max_amount: 5.0,
+                currency: "USDC".to_string(),
+                window: None,
+                window_type: WindowType::CalendarDay,
+                timezone: None,
+            }),
+            ..create_allow_policy("daily", vec!["*".to_string()])
+        }];
+        let engine = PolicyEngine::new(policies);
+        let at =
+            |s: &str| -> SystemTime { chrono::DateTime::parse_from_rfc3339(s).unwrap().into() };
+
+        // 23:59 UTC uses up the day's cap
+        let late = Request {
+            timestamp: at("2025-03-14T23:59:00Z"),
+            ..create_test_request("agent-123", 5, "/api/test")
+        };
+        assert!(engine.evaluate(&late).unwrap().is_allowed());
+        let denied = engine.evaluate(&late).unwrap();
+        assert_eq!(
+            denied,
+            PolicyDecision::Deny {
+                reason: "Spending cap exceeded: 5 limit per calendar day (UTC)".to_string(),
+                policy_id: "daily".to_string(),
+            }
+        );
+
+        // 00:01 UTC draws from the next day's cap
+        let early = Request {
+            timestamp: at("2025-03-15T00:01:00Z"),
+            ..late
+        };
+        assert!(engine.evaluate(&early).unwrap().is_allowed());
+
+        let quota = engine.quota("daily", "agent-123", early.timestamp);
+        let spending = quota.spending_cap.unwrap();
+        assert_eq!((spending.spent, spending.remaining), (5.0, 0.0));
+        assert_eq!(spending.window_type, WindowType::CalendarDay);
+        assert_eq!(spending.window_seconds, 86400);
+    }
+
+    #[test]
+    fn test_invalid_spending_window_is_an_error() {
+        let policies = vec![Policy {
+            spending_cap: Some(SpendingCapConfig {
+                max_amount: 5.0,
+                currency: "USDC".to_string(),
+                window: Some(Duration::from_secs(3600)),
+                window_type: WindowType::CalendarMonth,
+                timezone: None,
+            }),
+            ..create_allow_policy("monthly", vec!["*".to_string()])
+        }];
+        let engine = PolicyEngine::new(policies);
+
+        let err = engine
+            .evaluate(&create_test_request("agent-123", 1, "/api/test"))
+            .unwrap_err();
+        assert!(err.to_string().contains(
+            "Policy 'monthly': invalid spending_cap: window cannot be combined with window_type: calendar_month"
+        ));
     }
 
     #[test]
@@ -515,6 +953,7 @@ mod tests {
             endpoint_patterns: vec!["/api/allowed/*".to_string()],
             rate_limit: None,
             spending_cap: None,
+            concurrency_limit: None,
         }];
 
         let engine = PolicyEngine::new(policies);
@@ -558,6 +997,7 @@ mod tests {
                 window: Duration::from_secs(1),
             }),
             spending_cap: None,
+            concurrency_limit: None,
         }];
 
         let engine = PolicyEngine::new(policies);
@@ -580,4 +1020,247 @@ mod tests {
         request.timestamp = base_time + Duration::from_secs(2);
         assert!(engine.evaluate(&request).unwrap().is_allowed());
     }
+
+    fn create_concurrency_policy(max_in_flight: u32) -> Policy {
+        Policy {
+            concurrency_limit: Some(ConcurrencyLimitConfig { max_in_flight }),
+            ..create_allow_policy("limited", vec!["*".to_string()])
+        }
+    }
+
+    #[test]
+    fn test_concurrency_limit_allows_exactly_max_in_flight() {
+        let engine = PolicyEngine::new(vec![create_concurrency_policy(4)]);
+        let barrier = std::sync::Barrier::new(16);
+
+        // 16 simultaneous evaluations, each holding its decision until all are done
+        let decisions: Vec<PolicyDecision> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..16)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        let request = create_test_request("agent-1", 1, "/api/test");
+                        engine.evaluate(&request).unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        let allowed = decisions.iter().filter(|d| d.is_allowed()).count();
+        assert_eq!(allowed, 4);
+        assert_eq!(engine.in_flight("conc:limited:agent-1"), 4);
+
+        // Another agent has its own slots
+        let other = create_test_request("agent-2", 1, "/api/test");
+        assert!(engine.evaluate(&other).unwrap().is_allowed());
+
+        drop(decisions);
+        assert_eq!(engine.in_flight("conc:limited:agent-1"), 0);
+        let request = create_test_request("agent-1", 1, "/api/test");
+        assert!(engine.evaluate(&request).unwrap().is_allowed());
+    }
+
+    #[test]
+    fn test_concurrency_guard_released_when_request_errors() {
+        let engine = PolicyEngine::new(vec![create_concurrency_policy(1)]);
+        let request = create_test_request("agent-1", 1, "/api/test");
+
+        fn handle(engine: &PolicyEngine, request: &Request) -> Result<()> {
+            let decision = engine.evaluate(request)?;
+            assert!(decision.is_allowed());
+            anyhow::bail!("upstream failed while the slot was held")
+        }
+
+        assert!(handle(&engine, &request).is_err());
+        assert_eq!(engine.in_flight("conc:limited:agent-1"), 0);
+
+        // Clones share one slot, released with the last clone
+        let decision = engine.evaluate(&request).unwrap();
+        let cloned = decision.clone();
+        drop(decision);
+        assert!(engine.evaluate(&request).unwrap().is_denied());
+        drop(cloned);
+        assert!(engine.evaluate(&request).unwrap().is_allowed());
+    }
+
+    #[test]
+    fn test_concurrency_denial_does_not_consume_rate_limit() {
+        let mut policy = create_concurrency_policy(1);
+        policy.rate_limit = Some(RateLimitConfig {
+            max_requests: 2,
+            window: Duration::from_secs(60),
+        });
+        let engine = PolicyEngine::new(vec![policy]);
+        let request = create_test_request("agent-1", 1, "/api/test");
+
+        let held = engine.evaluate(&request).unwrap();
+        match engine.evaluate(&request).unwrap() {
+            PolicyDecision::Deny { reason, .. } => {
+                assert!(reason.contains("Concurrency limit exceeded"))
+            }
+            other => panic!("expected concurrency denial, got {:?}", other),
+        }
+        drop(held);
+
+        // Second rate-limited request is still available
+        assert!(engine.evaluate(&request).unwrap().is_allowed());
+    }
+
+    #[test]
+    fn test_paid_retry_holds_slot_without_counting() {
+        let mut policy = create_concurrency_policy(1);
+        policy.rate_limit = Some(RateLimitConfig {
+            max_requests: 1,
+            window: Duration::from_secs(60),
+        });
+        policy.spending_cap = Some(SpendingCapConfig {
+            max_amount: 1.0,
+            currency: "USDC".to_string(),
+            window: Some(Duration::from_secs(3600)),
+            window_type: WindowType::Rolling,
+            timezone: None,
+        });
+        let engine = PolicyEngine::new(vec![policy]);
+        let request = create_test_request("agent-1", 1, "/api/test");
+
+        // The invoice takes the whole rate limit and spending cap
+        drop(engine.evaluate(&request).unwrap());
+
+        // The paid retry is allowed and takes the in-flight slot
+        let paid = engine.evaluate_paid(&request).unwrap();
+        assert!(paid.is_allowed());
+        assert!(engine.evaluate_paid(&request).unwrap().is_denied());
+        drop(paid);
+
+        // Only the invoice was counted
+        let stats = engine.store().stats().unwrap();
+        assert_eq!((stats.requests, stats.spending_records), (1, 1));
+        assert!(engine.evaluate(&request).unwrap().is_denied());
+    }
+
+    #[test]
+    fn test_explain_lists_policies_by_priority_with_winner() {
+        let mut low = create_allow_policy("allow-all", vec![]);
+        low.priority = 1;
+        let mut high = create_deny_policy("block-bots", vec!["bot-*".to_string()]);
+        high.priority = 10;
+        let mut mid = create_allow_policy("premium", vec!["agent-*".to_string()]);
+        mid.priority = 5;
+        mid.endpoint_patterns = vec!["/api/premium/*".to_string()];
+        let engine = PolicyEngine::new(vec![low, high, mid]);
+
+        let explanation = engine.explain(&create_test_request("agent-1", 1, "/api/data"));
+        let order: Vec<(&str, bool)> = explanation
+            .candidates
+            .iter()
+            .map(|c| (c.policy_id.as_str(), c.winner))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("block-bots", false),
+                ("premium", false),
+                ("allow-all", true)
+            ]
+        );
+        assert_eq!(explanation.winner, "allow-all");
+        assert!(explanation.candidates[1].agent_matched);
+        assert!(!explanation.candidates[1].endpoint_matched);
+
+        // Agrees with evaluate
+        let decision = engine
+            .evaluate(&create_test_request("agent-1", 1, "/api/data"))
+            .unwrap();
+        assert!(
+            matches!(decision, PolicyDecision::Allow { policy_id, .. } if policy_id == "allow-all")
+        );
+    }
+
+    #[test]
+    fn test_explain_without_match_names_default() {
+        let engine = PolicyEngine::new(vec![create_allow_policy(
+            "trusted",
+            vec!["agent-trusted".to_string()],
+        )]);
+        let explanation = engine.explain(&create_test_request("agent-x", 1, "/api"));
+        assert_eq!(explanation.winner, DEFAULT_POLICY_ID);
+        assert!(!explanation.candidates[0].winner);
+    }
+
+    #[test]
+    fn test_match_stats_count_deciding_policy() {
+        let policies = vec![
+            create_deny_policy("block-bots", vec!["bot-*".to_string()]),
+            create_allow_policy("trusted", vec!["agent-*".to_string()]),
+        ];
+        assert!(PolicyEngine::new(policies.clone()).match_stats().is_none());
+        let engine = PolicyEngine::new(policies).with_match_stats();
+
+        engine
+            .evaluate(&create_test_request("agent-1", 1, "/a"))
+            .unwrap();
+        engine
+            .evaluate(&create_test_request("agent-2", 1, "/a"))
+            .unwrap();
+        engine
+            .evaluate(&create_test_request("bot-1", 1, "/a"))
+            .unwrap();
+        engine
+            .evaluate(&create_test_request("other", 1, "/a"))
+            .unwrap();
+
+        let hits: Vec<(String, u64)> = engine
+            .match_stats()
+            .unwrap()
+            .snapshot()
+            .into_iter()
+            .map(|rule| (rule.rule, rule.hits))
+            .collect();
+        let expected = [("block-bots", 1), ("trusted", 2), ("default", 1)];
+        assert_eq!(
+            hits,
+            expected
+                .map(|(rule, hits)| (rule.to_string(), hits))
+                .to_vec()
+        );
+    }
+
+    #[derive(Default)]
+    struct RecordingHook(std::sync::Mutex<Vec<AuditEntry>>);
+
+    impl DecisionHook for RecordingHook {
+        fn on_decision(&self, entry: &AuditEntry) {
+            self.0.lock().unwrap().push(entry.clone());
+        }
+    }
+
+    #[test]
+    fn test_decision_hook_sees_every_decision() {
+        let hook = Arc::new(RecordingHook::default());
+        let engine = PolicyEngine::new(vec![
+            create_deny_policy("block-bots", vec!["bot-*".to_string()]),
+            create_allow_policy("trusted", vec!["agent-*".to_string()]),
+        ])
+        .with_decision_hook(hook.clone());
+
+        let mut request = create_test_request("agent-1", 1, "/a");
+        request.request_id = Some("req-1".to_string());
+        engine.evaluate(&request).unwrap();
+        engine
+            .evaluate(&create_test_request("bot-1", 2, "/b"))
+            .unwrap();
+
+        let entries = hook.0.lock().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].decision, AuditDecision::Allow);
+        assert_eq!(entries[0].policy_id, "trusted");
+        assert_eq!(entries[0].request_id.as_deref(), Some("req-1"));
+        assert_eq!(entries[0].reason, None);
+        assert_eq!(entries[1].decision, AuditDecision::Deny);
+        assert_eq!(entries[1].agent, "bot-1");
+        assert_eq!(entries[1].endpoint, "/b");
+        assert_eq!(entries[1].amount, 2.0);
+        assert!(entries[1].reason.is_some());
+    }
 }
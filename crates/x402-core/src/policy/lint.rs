@@ -0,0 +1,489 @@
+// Policy style lint
+//
+// Validation rejects rules that conflict or cannot be enforced; lint flags
+// rules that work but are written inconsistently. It is a separate pass over
+// PolicyConfig (`policy lint`, or `policy validate --with-lint`) reporting
+// ValidationIssues with `X402-L*` codes, so output formats and `suppress:`
+// lists treat lint issues exactly like validation issues.
+//
+// A project tunes lint in the `lint:` section of .x402dev.yaml:
+//
+//   lint:
+//     production: true      # these policies guard production traffic
+//     rules:
+//       X402-L001: error    # error, warning, info or off
+//
+// Unsorted allowlist and denylist values (X402-L001) are fixed mechanically
+// by `fix_unsorted_values`.
+
+use super::codes::RuleCode;
+use super::types::{PolicyConfig, PolicyRule};
+use super::validator::{IssueType, ResolutionSuggestion, ValidationIssue, ValidationReport};
+use crate::format::DetectedFormat;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Whole fractions of a minute: the round windows shorter than a minute
+const MINUTE_DIVISORS: [u64; 12] = [1, 2, 3, 4, 5, 6, 10, 12, 15, 20, 30, 60];
+
+/// Severity a project gives a lint rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Info,
+    /// Do not check the rule
+    Off,
+}
+
+/// The `lint:` section of .x402dev.yaml
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    /// The policies guard production traffic: also check X402-L004
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub production: bool,
+
+    /// Severity of lint rules by code, overriding their defaults
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rules: BTreeMap<RuleCode, LintSeverity>,
+}
+
+impl LintConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Only lint rules can be configured; validation rules keep their severity
+    pub fn validate(&self) -> Result<()> {
+        if let Some(code) = self.rules.keys().find(|code| !code.is_lint()) {
+            bail!(
+                "Invalid lint.rules entry: {} is a validation rule, not a lint rule\nFix: Configure only X402-L codes (run `x402-dev policy rules` for the list)",
+                code
+            );
+        }
+        Ok(())
+    }
+
+    /// Severity of `code` in this project; None when the rule is off
+    pub fn severity(&self, code: RuleCode) -> Option<IssueType> {
+        match self.rules.get(&code) {
+            None => Some(code.severity()),
+            Some(LintSeverity::Error) => Some(IssueType::Error),
+            Some(LintSeverity::Warning) => Some(IssueType::Warning),
+            Some(LintSeverity::Info) => Some(IssueType::Info),
+            Some(LintSeverity::Off) => None,
+        }
+    }
+}
+
+/// Check policy rules against the style rules
+///
+/// Unlike `validate_policies`, a clean file yields an empty report.
+pub fn lint_policies(policy_config: &PolicyConfig, lint: &LintConfig) -> ValidationReport {
+    let mut linter = Linter {
+        lint,
+        report: ValidationReport::new(),
+    };
+
+    for (idx, policy) in policy_config.policies.iter().enumerate() {
+        match policy {
+            PolicyRule::Allowlist { field, values } | PolicyRule::Denylist { field, values } => {
+                let kind = match policy {
+                    PolicyRule::Allowlist { .. } => "Allowlist",
+                    _ => "Denylist",
+                };
+                if !is_sorted(values) {
+                    linter.flag(
+                        RuleCode::UnsortedValues,
+                        idx,
+                        format!("{} values of policy #{} are not sorted", kind, idx),
+                        Some(format!("Field: {}", field)),
+                        (
+                            "Sort the values so additions and removals diff cleanly".to_string(),
+                            "Run `x402-dev policy lint --fix`".to_string(),
+                        ),
+                    );
+                }
+                if lint.production
+                    && kind == "Allowlist"
+                    && field == "agent_id"
+                    && values.iter().any(|v| v == "*")
+                {
+                    linter.flag(
+                        RuleCode::WildcardAgent,
+                        idx,
+                        format!("Allowlist #{} admits every agent ('*')", idx),
+                        Some("The project sets lint.production: true".to_string()),
+                        (
+                            "List the agents or agent prefixes allowed in production".to_string(),
+                            "Replace '*' with patterns such as 'agent-prod-*'".to_string(),
+                        ),
+                    );
+                }
+            }
+            PolicyRule::RateLimit { window_seconds, .. } => {
+                linter.check_window("Rate limit", idx, window_seconds.as_secs());
+            }
+            PolicyRule::SpendingCap {
+                currency,
+                window_seconds,
+                window_type,
+                ..
+            } => {
+                if window_type.is_rolling() {
+                    if let Some(window) = window_seconds {
+                        linter.check_window("Spending cap", idx, window.as_secs());
+                    }
+                }
+                linter.check_currency(idx, currency);
+            }
+            PolicyRule::ConcurrencyLimit { .. } => {}
+        }
+    }
+
+    linter.report
+}
+
+/// Lint issues of one file, at the project's severities
+struct Linter<'a> {
+    lint: &'a LintConfig,
+    report: ValidationReport,
+}
+
+impl Linter<'_> {
+    fn flag(
+        &mut self,
+        code: RuleCode,
+        idx: usize,
+        message: String,
+        details: Option<String>,
+        (description, action): (String, String),
+    ) {
+        let Some(issue_type) = self.lint.severity(code) else {
+            return;
+        };
+        self.report.add_issue(ValidationIssue::lint(
+            code,
+            issue_type,
+            message,
+            details,
+            vec![ResolutionSuggestion {
+                description,
+                action,
+                catalog: None,
+            }],
+            vec![idx],
+        ));
+    }
+
+    /// Windows should be whole minutes, or a whole fraction of a minute
+    fn check_window(&mut self, what: &str, idx: usize, secs: u64) {
+        // 0 is a validation error, not a style issue
+        if secs == 0 || is_round(secs) {
+            return;
+        }
+        let (below, above) = if secs < 60 {
+            let above = MINUTE_DIVISORS
+                .into_iter()
+                .find(|&d| d > secs)
+                .unwrap_or(60);
+            let below = MINUTE_DIVISORS
+                .into_iter()
+                .rev()
+                .find(|&d| d < secs)
+                .unwrap_or(1);
+            (below, above)
+        } else {
+            let below = secs / 60 * 60;
+            (below, below + 60)
+        };
+        self.flag(
+            RuleCode::IrregularWindow,
+            idx,
+            format!(
+                "{} window of policy #{} is {}s, not a round duration",
+                what, idx, secs
+            ),
+            None,
+            (
+                "Use a whole number of minutes, or a whole fraction of a minute".to_string(),
+                format!("Set window_seconds to {} or {}", below, above),
+            ),
+        );
+    }
+
+    /// Currencies should be token symbols such as USDC or SOL
+    fn check_currency(&mut self, idx: usize, currency: &str) {
+        // An empty currency is a validation error
+        if currency.is_empty() || is_token_symbol(currency) {
+            return;
+        }
+        let symbol = currency.trim().to_ascii_uppercase();
+        let action = if is_token_symbol(&symbol) {
+            format!("Set currency: {}", symbol)
+        } else {
+            "Set currency to the token symbol, e.g. USDC".to_string()
+        };
+        self.flag(
+            RuleCode::ImplicitCurrency,
+            idx,
+            format!(
+                "Spending cap #{} currency '{}' is not an explicit token symbol",
+                idx, currency
+            ),
+            None,
+            ("Name the token the cap is counted in".to_string(), action),
+        );
+    }
+}
+
+fn is_sorted(values: &[String]) -> bool {
+    values.windows(2).all(|pair| pair[0] <= pair[1])
+}
+
+fn is_round(secs: u64) -> bool {
+    if secs < 60 {
+        MINUTE_DIVISORS.contains(&secs)
+    } else {
+        secs.is_multiple_of(60)
+    }
+}
+
+/// 2-10 uppercase letters and digits, starting with a letter
+fn is_token_symbol(currency: &str) -> bool {
+    (2..=10).contains(&currency.len())
+        && currency.starts_with(|c: char| c.is_ascii_uppercase())
+        && currency
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+/// Sort the values of every allowlist and denylist in a policy document
+///
+/// Returns the document rewritten in its own format, or None when every
+/// list was already sorted. As with `convert`, rewriting drops comments.
+pub fn fix_unsorted_values(content: &str, format: DetectedFormat) -> Result<Option<String>> {
+    let mut doc: serde_yaml::Value = format.parse(content)?;
+    let mut changed = false;
+    if let Some(policies) = doc
+        .get_mut("policies")
+        .and_then(serde_yaml::Value::as_sequence_mut)
+    {
+        for policy in policies {
+            if !matches!(
+                policy.get("type").and_then(serde_yaml::Value::as_str),
+                Some("allowlist" | "denylist")
+            ) {
+                continue;
+            }
+            let Some(values) = policy
+                .get_mut("values")
+                .and_then(serde_yaml::Value::as_sequence_mut)
+            else {
+                continue;
+            };
+            let Some(strings) = values
+                .iter()
+                .map(serde_yaml::Value::as_str)
+                .collect::<Option<Vec<&str>>>()
+            else {
+                continue;
+            };
+            if strings.windows(2).any(|pair| pair[0] > pair[1]) {
+                values.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+                changed = true;
+            }
+        }
+    }
+    if !changed {
+        return Ok(None);
+    }
+    Ok(Some(format.format.render(&doc)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::FileFormat;
+    use crate::policy::window::WindowType;
+    use x402_domain::DurationField;
+
+    fn config(policies: Vec<PolicyRule>) -> PolicyConfig {
+        PolicyConfig {
+            policies,
+            allow_custom_currencies: false,
+        }
+    }
+
+    fn allowlist(values: &[&str]) -> PolicyRule {
+        PolicyRule::Allowlist {
+            field: "agent_id".to_string(),
+            values: values.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    fn spending_cap(currency: &str, window_seconds: Option<u64>) -> PolicyRule {
+        PolicyRule::SpendingCap {
+            max_amount: 10.0,
+            currency: currency.to_string(),
+            window_seconds: window_seconds.map(DurationField::from_secs),
+            window_type: WindowType::Rolling,
+            timezone: None,
+        }
+    }
+
+    fn codes(report: &ValidationReport) -> Vec<RuleCode> {
+        report.issues.iter().map(|i| i.code).collect()
+    }
+
+    #[test]
+    fn test_clean_policies_have_no_issues() {
+        let report = lint_policies(
+            &config(vec![
+                allowlist(&["agent-a", "agent-b"]),
+                PolicyRule::RateLimit {
+                    max_requests: 10,
+                    window_seconds: DurationField::from_secs(3600),
+                },
+                spending_cap("USDC", Some(86400)),
+            ]),
+            &LintConfig::default(),
+        );
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn test_each_rule_fires() {
+        let policies = config(vec![
+            allowlist(&["agent-b", "agent-a", "*"]),
+            PolicyRule::RateLimit {
+                max_requests: 10,
+                window_seconds: DurationField::from_secs(90),
+            },
+            spending_cap("usdc", Some(7)),
+        ]);
+
+        let report = lint_policies(&policies, &LintConfig::default());
+        assert_eq!(
+            codes(&report),
+            [
+                RuleCode::UnsortedValues,
+                RuleCode::IrregularWindow,
+                RuleCode::IrregularWindow,
+                RuleCode::ImplicitCurrency,
+            ]
+        );
+        assert_eq!(report.issues[0].policy_indices, [0]);
+        assert_eq!(
+            report.issues[1].suggestions[0].action,
+            "Set window_seconds to 60 or 120"
+        );
+        assert_eq!(
+            report.issues[2].suggestions[0].action,
+            "Set window_seconds to 6 or 10"
+        );
+        assert_eq!(report.issues[3].suggestions[0].action, "Set currency: USDC");
+        assert!(report.has_warnings && !report.has_errors);
+
+        // The bare wildcard only matters in production projects
+        let production = LintConfig {
+            production: true,
+            ..Default::default()
+        };
+        let report = lint_policies(&policies, &production);
+        assert!(codes(&report).contains(&RuleCode::WildcardAgent));
+    }
+
+    #[test]
+    fn test_severity_overrides() {
+        let policies = config(vec![
+            allowlist(&["b", "a"]),
+            PolicyRule::RateLimit {
+                max_requests: 10,
+                window_seconds: DurationField::from_secs(90),
+            },
+        ]);
+        let lint = LintConfig {
+            production: false,
+            rules: BTreeMap::from([
+                (RuleCode::UnsortedValues, LintSeverity::Error),
+                (RuleCode::IrregularWindow, LintSeverity::Off),
+            ]),
+        };
+
+        let report = lint_policies(&policies, &lint);
+        assert_eq!(codes(&report), [RuleCode::UnsortedValues]);
+        assert_eq!(report.issues[0].issue_type, IssueType::Error);
+        assert!(report.has_errors);
+    }
+
+    #[test]
+    fn test_config_accepts_only_lint_codes() {
+        let lint: LintConfig =
+            serde_yaml::from_str("production: true\nrules:\n  x402-l002: warning\n").unwrap();
+        assert_eq!(
+            lint.rules[&RuleCode::IrregularWindow],
+            LintSeverity::Warning
+        );
+        lint.validate().unwrap();
+
+        let lint: LintConfig = serde_yaml::from_str("rules:\n  X402-P010: off\n").unwrap();
+        let err = lint.validate().unwrap_err().to_string();
+        assert!(err.contains("X402-P010 is a validation rule"), "{}", err);
+
+        assert!(serde_yaml::from_str::<LintConfig>("rules:\n  X402-L001: loud\n").is_err());
+    }
+
+    #[test]
+    fn test_fix_sorts_lists_deterministically() {
+        let yaml = "\
+policies:
+- type: allowlist
+  field: agent_id
+  values:
+  - zeta
+  - alpha
+- type: rate_limit
+  max_requests: 10
+  window_seconds: 60
+- type: denylist
+  field: agent_id
+  values: [b, a]
+";
+        let format = DetectedFormat::given(FileFormat::Yaml);
+        let fixed = fix_unsorted_values(yaml, format).unwrap().unwrap();
+        assert_eq!(
+            fixed,
+            "\
+policies:
+- type: allowlist
+  field: agent_id
+  values:
+  - alpha
+  - zeta
+- type: rate_limit
+  max_requests: 10
+  window_seconds: 60
+- type: denylist
+  field: agent_id
+  values:
+  - a
+  - b
+"
+        );
+        // Fixing again changes nothing
+        assert_eq!(fix_unsorted_values(&fixed, format).unwrap(), None);
+
+        // JSON stays JSON
+        let json =
+            r#"{"policies": [{"type": "denylist", "field": "agent_id", "values": ["b", "a"]}]}"#;
+        let fixed = fix_unsorted_values(json, DetectedFormat::given(FileFormat::Json))
+            .unwrap()
+            .unwrap();
+        let doc: serde_json::Value = serde_json::from_str(&fixed).unwrap();
+        assert_eq!(doc["policies"][0]["values"], serde_json::json!(["a", "b"]));
+    }
+}
@@ -0,0 +1,147 @@
+// Rule match statistics
+//
+// Opt-in hit counters for a fixed list of rules (runtime policies, pricing
+// patterns). The list is set when the stats are created, so recording a
+// match is an index or map lookup plus two relaxed atomic updates and never
+// allocates. Owners keep an `Option<MatchStats>`; when it is `None` the only
+// cost on the hot path is the `None` check.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Per-rule hit counters and last-matched times
+#[derive(Debug)]
+pub struct MatchStats {
+    rules: Vec<RuleCounter>,
+    index: HashMap<String, usize>,
+}
+
+#[derive(Debug)]
+struct RuleCounter {
+    rule: String,
+    hits: AtomicU64,
+    /// Milliseconds since the Unix epoch, 0 before the first match
+    last_matched_ms: AtomicU64,
+}
+
+impl MatchStats {
+    /// Counters for `rules`, reported in the given order
+    ///
+    /// A rule listed twice is counted under its first position.
+    pub fn new<I, S>(rules: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let rules: Vec<RuleCounter> = rules
+            .into_iter()
+            .map(|rule| RuleCounter {
+                rule: rule.into(),
+                hits: AtomicU64::new(0),
+                last_matched_ms: AtomicU64::new(0),
+            })
+            .collect();
+        let mut index = HashMap::with_capacity(rules.len());
+        for (i, counter) in rules.iter().enumerate().rev() {
+            index.insert(counter.rule.clone(), i);
+        }
+        Self { rules, index }
+    }
+
+    /// Count a match of the rule at `position`; out-of-range positions are ignored
+    pub fn record_at(&self, position: usize, now: SystemTime) {
+        if let Some(counter) = self.rules.get(position) {
+            counter.hits.fetch_add(1, Ordering::Relaxed);
+            let millis = now
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+            counter.last_matched_ms.fetch_max(millis, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a match of `rule`; returns false for a rule not being tracked
+    pub fn record(&self, rule: &str, now: SystemTime) -> bool {
+        match self.index.get(rule) {
+            Some(&position) => {
+                self.record_at(position, now);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Zero every counter
+    pub fn reset(&self) {
+        for counter in &self.rules {
+            counter.hits.store(0, Ordering::Relaxed);
+            counter.last_matched_ms.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Current counters in rule order
+    pub fn snapshot(&self) -> Vec<RuleHits> {
+        self.rules
+            .iter()
+            .map(|counter| RuleHits {
+                rule: counter.rule.clone(),
+                hits: counter.hits.load(Ordering::Relaxed),
+                last_matched: match counter.last_matched_ms.load(Ordering::Relaxed) {
+                    0 => None,
+                    millis => i64::try_from(millis)
+                        .ok()
+                        .and_then(DateTime::from_timestamp_millis),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Hit count of one rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleHits {
+    /// Policy ID or pricing pattern
+    pub rule: String,
+    pub hits: u64,
+    /// Most recent match (`None` if the rule never matched)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_matched: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_record_by_name_and_position() {
+        let stats = MatchStats::new(["allow-all", "default"]);
+        let t1 = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let t2 = t1 + Duration::from_secs(5);
+
+        assert!(stats.record("default", t2));
+        stats.record_at(1, t1);
+        stats.record_at(7, t1);
+        assert!(!stats.record("unknown", t1));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot[0].hits, 0);
+        assert_eq!(snapshot[0].last_matched, None);
+        assert_eq!(snapshot[1].hits, 2);
+        // Out-of-order records keep the latest time
+        assert_eq!(snapshot[1].last_matched, Some(DateTime::<Utc>::from(t2)));
+
+        stats.reset();
+        assert!(stats.snapshot().iter().all(|rule| rule.hits == 0));
+    }
+
+    #[test]
+    fn test_duplicate_rule_counts_at_first_position() {
+        let stats = MatchStats::new(["a", "b", "a"]);
+        stats.record("a", SystemTime::now());
+        let hits: Vec<u64> = stats.snapshot().iter().map(|r| r.hits).collect();
+        assert_eq!(hits, vec![1, 0, 0]);
+    }
+}
@@ -1,29 +1,71 @@
 // Policy engine module for x402-dev
 //
 // This module provides:
-// - Policy rule definitions (YAML parsing)
-// - Code generation for Express/Fastify middleware
-// - Policy validation and conflict detection (FR-5.6)
-// - Runtime policy evaluation with state tracking (Epic 5 Task 2)
+// - Policy rule definitions (YAML or JSON parsing)
+// - Code generation for Express/Fastify middleware and matching test suites
+// - Policy validation and conflict detection (FR-5.6), with stable rule codes
+// - Style lint with per-project severities and a sort auto-fix
+// - Workspace policy file discovery (.x402ignore aware)
+// - Runtime policy evaluation with state tracking (Epic 5 Task 2), kept in
+//   memory or in SQLite
+// - Optional per-rule hit counters and match explanations
+// - Synthetic agent populations for policy load testing
+// - Decision hooks and a sampled JSONL audit log of decisions
 
+pub mod audit;
 pub mod codegen;
+pub mod codes;
+pub mod discovery;
 pub mod engine;
+pub mod lint;
+pub mod match_stats;
 pub mod rules;
 pub mod runtime_types;
+pub mod simulate;
+pub mod sqlite_store;
 pub mod state;
+pub mod store;
 pub mod types;
 pub mod validator;
+pub mod window;
 
-pub use codegen::{generate_express_middleware, generate_fastify_plugin};
-pub use rules::{PolicyFile, PolicyRule as RulesPolicyRule, PolicyType as RulesPolicyType};
+pub use audit::{
+    read_audit_entries, AuditDecision, AuditEntry, AuditLog, AuditLogConfig, AuditQuery,
+    DecisionHook, LogRotation,
+};
+pub use codegen::{generate_express_middleware, generate_fastify_plugin, generate_test_suite};
+pub use codes::{RuleCode, Suppressions};
+pub use discovery::{discover_policy_files, is_yaml_file, DEFAULT_POLICIES_DIR, IGNORE_FILE};
+pub use lint::{fix_unsorted_values, lint_policies, LintConfig, LintSeverity};
+pub use rules::{
+    detect_policy_schema, lenient_requested, load_policy_file, parse_policy_document,
+    parse_policy_file_lenient, parse_policy_yaml, runtime_policies_from_yaml, LenientPolicyFile,
+    LoadedPolicyFile, PolicyFile, PolicySchema, LENIENT_KEY,
+};
+#[doc(hidden)]
+pub use rules::{PolicyRule as RulesPolicyRule, PolicyType as RulesPolicyType};
 pub use types::{PolicyAction, PolicyConfig, PolicyRule, PolicyType};
 pub use validator::{
-    validate_policies, IssueType, ResolutionSuggestion, ValidationIssue, ValidationReport,
+    validate_policies, IssueType, ResolutionSuggestion, SkippedRule, ValidationIssue,
+    ValidationReport,
 };
+pub use window::{SpendingWindow, WindowType};
 
 // Re-export runtime evaluation types (Epic 5 Task 2)
-pub use engine::PolicyEngine;
+pub use engine::{PolicyEngine, DEFAULT_POLICY_ID, RATE_LIMIT_EXCEEDED, SPENDING_CAP_EXCEEDED};
+pub use match_stats::{MatchStats, RuleHits};
 pub use runtime_types::{
-    Policy as RuntimePolicy, PolicyDecision, RateLimitConfig, Request, SpendingCapConfig,
+    ConcurrencyLimitConfig, Policy as RuntimePolicy, PolicyCandidate, PolicyDecision,
+    PolicyMatchExplanation, QuotaHeaders, QuotaStatus, RateLimitConfig, RateLimitQuota, Request,
+    RuntimePolicyFile, SpendingCapConfig, SpendingCapQuota,
+};
+pub use simulate::{
+    simulate, Arrivals, CohortOutcome, CohortSpec, Distribution, MaliciousSpec, PopulationSpec,
+    SimulatedRequest, SimulationReport,
+};
+pub use sqlite_store::SqliteStateStore;
+pub use state::{ConcurrencyGuard, MemoryStateStore, SpendingRecord};
+pub use store::{
+    Admission, PolicyStateStore, RateCounter, SpendCounter, SpendDump, StateBackend, StateDump,
+    StateStats, StateStoreConfig, StoreInstance, DEFAULT_SQLITE_STATE_PATH,
 };
-pub use state::{RateLimitState, SpendingState};
@@ -1,13 +1,24 @@
 // Policy rule definitions
 //
 // Defines the structure for policy rules as specified in FR-5.1
+use anyhow::{anyhow, bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::runtime_types::{
+    ConcurrencyLimitConfig, Policy as RuntimePolicy, RateLimitConfig, RuntimePolicyFile,
+    SpendingCapConfig,
+};
+use super::types::{PolicyAction, PolicyConfig};
+use super::validator::SkippedRule;
+use crate::format::{DetectedFormat, FileFormat};
+use x402_domain::{canonical_currency, known_currencies, suggest_currency};
 
 // Re-export types from types.rs
 pub use super::types::{PolicyRule, PolicyType};
 
 /// Pricing configuration for generated middleware
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PricingConfig {
     #[serde(default = "default_amount")]
     pub amount: f64,
@@ -35,7 +46,7 @@ impl Default for PricingConfig {
 }
 
 /// Audit configuration for generated middleware
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AuditConfig {
     #[serde(default = "default_enabled")]
     pub enabled: bool,
@@ -63,7 +74,7 @@ impl Default for AuditConfig {
 }
 
 /// Complete policy file structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PolicyFile {
     pub policies: Vec<PolicyRule>,
 
@@ -72,4 +83,814 @@ pub struct PolicyFile {
 
     #[serde(default)]
     pub audit: AuditConfig,
+
+    /// Admit currencies missing from the currency registry, kept as written
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_custom_currencies: bool,
+}
+
+/// Which of the two policy file schemas a document uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicySchema {
+    /// `type: allowlist | denylist | rate_limit | spending_cap` entries
+    Simple,
+    /// `id` / `priority` / `action` entries evaluated by the policy engine
+    Runtime,
+}
+
+/// A policy file loaded in whichever schema it was written in
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadedPolicyFile {
+    Simple(PolicyFile),
+    Runtime(RuntimePolicyFile),
+}
+
+const SCHEMA_FIX: &str = "Fix: Use either simple entries (`type: allowlist`, `type: rate_limit`, ...) or runtime entries (`id`, `priority`, `action`) for every policy in the file";
+
+/// Detect the schema of a policy YAML document from its `policies` entries
+///
+/// Entries with a `type` key are simple rules; entries with `id` or `action`
+/// are runtime policies. A file mixing both, or an entry with neither, is
+/// rejected rather than guessed.
+pub fn detect_policy_schema(yaml: &str) -> Result<PolicySchema> {
+    let doc: serde_yaml::Value =
+        serde_yaml::from_str(yaml).context("Failed to parse YAML policy file")?;
+    schema_of(&doc)
+}
+
+fn schema_of(doc: &serde_yaml::Value) -> Result<PolicySchema> {
+    let Some(entries) = doc.get("policies").and_then(|p| p.as_sequence()) else {
+        bail!("Policy file has no 'policies' list\nFix: Add a top-level 'policies:' list");
+    };
+
+    let mut first_simple = None;
+    let mut first_runtime = None;
+
+    for (i, entry) in entries.iter().enumerate() {
+        let has = |key: &str| entry.get(key).is_some();
+        let simple = has("type");
+        let runtime = has("id") || has("action");
+
+        match (simple, runtime) {
+            (true, true) => bail!(
+                "Policy #{} is ambiguous: it has both 'type' (simple schema) and 'id'/'action' (runtime schema)\n{}",
+                i,
+                SCHEMA_FIX
+            ),
+            (false, false) => bail!(
+                "Policy #{} matches neither schema: expected 'type' (simple) or 'id'/'action' (runtime)\n{}",
+                i,
+                SCHEMA_FIX
+            ),
+            (true, false) => {
+                first_simple.get_or_insert(i);
+            }
+            (false, true) => {
+                first_runtime.get_or_insert(i);
+            }
+        }
+    }
+
+    match (first_simple, first_runtime) {
+        (Some(s), Some(r)) => bail!(
+            "Policy file mixes schemas: policy #{} is a simple rule but policy #{} is a runtime policy\n{}",
+            s,
+            r,
+            SCHEMA_FIX
+        ),
+        (None, Some(_)) => Ok(PolicySchema::Runtime),
+        _ => Ok(PolicySchema::Simple),
+    }
+}
+
+/// Parse a policy YAML document in either schema
+pub fn parse_policy_yaml(yaml: &str) -> Result<LoadedPolicyFile> {
+    parse_policy_document(yaml, DetectedFormat::given(FileFormat::Yaml))
+}
+
+/// Parse a policy document in either schema, written in YAML or JSON
+///
+/// Files marked `lenient: true` are refused: they may be missing rules, so
+/// they are only fit for `policy validate`.
+pub fn parse_policy_document(content: &str, format: DetectedFormat) -> Result<LoadedPolicyFile> {
+    let doc: serde_yaml::Value = format.parse(content)?;
+    if lenient_requested(&doc) {
+        bail!(
+            "Policy file is marked `lenient: true`, so rules that fail to parse would be skipped\nFix: Run `x402-dev policy validate` on the file, fix the skipped rules and remove `lenient: true` before enforcing it or generating code"
+        );
+    }
+    match schema_of(&doc)? {
+        PolicySchema::Simple => {
+            let mut file: PolicyFile = format
+                .parse(content)
+                .context("Failed to parse simple policy file")?;
+            file.normalize_currencies()?;
+            Ok(LoadedPolicyFile::Simple(file))
+        }
+        PolicySchema::Runtime => {
+            let mut file: RuntimePolicyFile = format
+                .parse(content)
+                .context("Failed to parse runtime policy file")?;
+            let allow_custom = file.allow_custom_currencies;
+            for policy in file.policies.iter_mut() {
+                if let Some(cap) = policy.spending_cap.as_mut() {
+                    cap.currency = checked_currency(
+                        &cap.currency,
+                        allow_custom,
+                        &format!("Policy '{}': spending_cap", policy.id),
+                    )?;
+                }
+            }
+            for policy in &file.policies {
+                if let Some(Err(e)) = policy.spending_cap.as_ref().map(|c| c.spending_window()) {
+                    bail!(
+                        "Policy '{}': invalid spending_cap: {}\nFix: Set `window` for a rolling cap, or `window_type: calendar_day`/`calendar_month` (with an optional `timezone`) without `window`",
+                        policy.id,
+                        e
+                    );
+                }
+            }
+            Ok(LoadedPolicyFile::Runtime(file))
+        }
+    }
+}
+
+/// Load a policy file from disk, auto-detecting its format and schema
+///
+/// The error names the file, followed by the cause (for parse errors: the
+/// detected format and the position).
+pub fn load_policy_file(path: &Path) -> Result<LoadedPolicyFile> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read policy file: {}", path.display()))?;
+    parse_policy_document(&content, DetectedFormat::detect(Some(path), &content))
+        .map_err(|e| anyhow!("Invalid policy file: {}: {:#}", path.display(), e))
+}
+
+/// Key of a policy file asking `policy validate` to skip rules that fail to
+/// parse instead of rejecting the file
+pub const LENIENT_KEY: &str = "lenient";
+
+/// True when a policy document sets `lenient: true`
+pub fn lenient_requested(doc: &serde_yaml::Value) -> bool {
+    doc.get(LENIENT_KEY).and_then(serde_yaml::Value::as_bool) == Some(true)
+}
+
+/// Simple policy file parsed with [`parse_policy_file_lenient`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LenientPolicyFile {
+    /// The rules that parsed, in file order
+    pub file: PolicyFile,
+    /// Position in the file of each rule of `file.policies`
+    pub indices: Vec<usize>,
+    /// Entries that failed to parse
+    pub skipped: Vec<SkippedRule>,
+}
+
+/// Parse a simple policy document rule by rule, skipping rules that fail
+///
+/// Each `policies` entry is parsed on its own, so a malformed rule is
+/// reported with its position (and line, for YAML block lists) while the
+/// others still load. A document that is not YAML/JSON or has no
+/// `policies` list still fails.
+pub fn parse_policy_file_lenient(
+    content: &str,
+    format: DetectedFormat,
+) -> Result<LenientPolicyFile> {
+    let mut doc: serde_yaml::Value = format.parse(content)?;
+    let Some(entries) = doc.get_mut("policies").and_then(|p| p.as_sequence_mut()) else {
+        bail!("Policy file has no 'policies' list\nFix: Add a top-level 'policies:' list");
+    };
+
+    let lines = match format.format {
+        FileFormat::Yaml => policy_entry_lines(content),
+        FileFormat::Json => Vec::new(),
+    };
+    let lines_match = lines.len() == entries.len();
+    let mut indices = Vec::new();
+    let mut skipped = Vec::new();
+    let mut kept = Vec::new();
+    for (index, entry) in std::mem::take(entries).into_iter().enumerate() {
+        match serde_yaml::from_value::<PolicyRule>(entry.clone()) {
+            Ok(_) => {
+                indices.push(index);
+                kept.push(entry);
+            }
+            Err(e) => skipped.push(SkippedRule {
+                index,
+                line: lines.get(index).copied().filter(|_| lines_match),
+                error: e.to_string(),
+            }),
+        }
+    }
+    *entries = kept;
+
+    let file: PolicyFile =
+        serde_yaml::from_value(doc).context("Failed to parse simple policy file")?;
+    Ok(LenientPolicyFile {
+        file,
+        indices,
+        skipped,
+    })
+}
+
+/// 1-based line of each entry of a top-level `policies:` block list
+fn policy_entry_lines(content: &str) -> Vec<usize> {
+    let mut lines = content.lines().enumerate();
+    let is_policies_key =
+        |line: &str| line.split(" #").next().unwrap_or_default().trim_end() == "policies:";
+    if !lines.any(|(_, line)| is_policies_key(line)) {
+        return Vec::new();
+    }
+
+    let mut entry_indent = None;
+    let mut starts = Vec::new();
+    for (i, line) in lines {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - trimmed.len();
+        let is_entry = trimmed == "-" || trimmed.starts_with("- ");
+        match entry_indent {
+            None if is_entry => entry_indent = Some(indent),
+            // Flow list or next top-level key
+            None => break,
+            Some(_) if indent == 0 && !is_entry => break,
+            Some(_) => {}
+        }
+        if is_entry && entry_indent == Some(indent) {
+            starts.push(i + 1);
+        }
+    }
+    starts
+}
+
+/// Parse a policy YAML document in either schema into runtime policies
+///
+/// Conflicts between rules are not checked here; run `validate_policies`
+/// first when loading untrusted files.
+pub fn runtime_policies_from_yaml(yaml: &str) -> Result<RuntimePolicyFile> {
+    parse_policy_yaml(yaml)?.into_runtime()
+}
+
+impl LoadedPolicyFile {
+    /// Runtime policies of the file, converting simple rules if needed
+    pub fn into_runtime(self) -> Result<RuntimePolicyFile> {
+        match self {
+            LoadedPolicyFile::Simple(file) => file.to_runtime(),
+            LoadedPolicyFile::Runtime(file) => Ok(file),
+        }
+    }
+}
+
+impl PolicyFile {
+    /// Rules and settings checked by `validate_policies`
+    pub fn to_config(&self) -> PolicyConfig {
+        PolicyConfig {
+            policies: self.policies.clone(),
+            allow_custom_currencies: self.allow_custom_currencies,
+        }
+    }
+
+    /// Store spending cap and pricing currencies in canonical case
+    ///
+    /// Fails on a currency missing from the registry unless
+    /// `allow_custom_currencies` is set.
+    pub fn normalize_currencies(&mut self) -> Result<()> {
+        let allow_custom = self.allow_custom_currencies;
+        for (i, policy) in self.policies.iter_mut().enumerate() {
+            if let PolicyRule::SpendingCap { currency, .. } = policy {
+                *currency = checked_currency(currency, allow_custom, &format!("Policy #{}", i))?;
+            }
+        }
+        self.pricing.currency = checked_currency(&self.pricing.currency, allow_custom, "pricing")?;
+        Ok(())
+    }
+
+    /// Convert simple rules to runtime policies with explicit priorities
+    ///
+    /// Denylists are evaluated first, then allowlists. Rate limits, spending
+    /// caps and concurrency limits apply to every allowed request, so they
+    /// are attached to each allow policy. Without an allowlist, a catch-all allow policy
+    /// keeps the simple schema's "allowed unless denied" behavior.
+    pub fn to_runtime(&self) -> Result<RuntimePolicyFile> {
+        let mut denies = Vec::new();
+        let mut allows = Vec::new();
+        let mut rate_limit = None;
+        let mut spending_cap = None;
+        let mut concurrency_limit = None;
+
+        for (i, rule) in self.policies.iter().enumerate() {
+            match rule {
+                PolicyRule::Denylist { field, values } => {
+                    let mut policy = pattern_policy(i, "deny", field, values)?;
+                    policy.action = PolicyAction::Deny(format!("{} is denylisted", field));
+                    denies.push(policy);
+                }
+                PolicyRule::Allowlist { field, values } => {
+                    allows.push(pattern_policy(i, "allow", field, values)?);
+                }
+                PolicyRule::RateLimit {
+                    max_requests,
+                    window_seconds,
+                } => {
+                    if rate_limit.is_some() {
+                        bail!(
+                            "Policy #{}: multiple rate_limit rules cannot be merged into one runtime policy\nFix: Keep a single rate_limit rule",
+                            i
+                        );
+                    }
+                    rate_limit = Some(RateLimitConfig {
+                        max_requests: *max_requests,
+                        window: window_seconds.as_duration(),
+                    });
+                }
+                PolicyRule::SpendingCap {
+                    max_amount,
+                    currency,
+                    window_seconds,
+                    window_type,
+                    timezone,
+                } => {
+                    if spending_cap.is_some() {
+                        bail!(
+                            "Policy #{}: multiple spending_cap rules cannot be merged into one runtime policy\nFix: Keep a single spending_cap rule",
+                            i
+                        );
+                    }
+                    spending_cap = Some(SpendingCapConfig {
+                        max_amount: *max_amount,
+                        currency: currency.clone(),
+                        window: window_seconds.map(|window| window.as_duration()),
+                        window_type: *window_type,
+                        timezone: *timezone,
+                    });
+                }
+                PolicyRule::ConcurrencyLimit { max_in_flight } => {
+                    if concurrency_limit.is_some() {
+                        bail!(
+                            "Policy #{}: multiple concurrency_limit rules cannot be merged into one runtime policy\nFix: Keep a single concurrency_limit rule",
+                            i
+                        );
+                    }
+                    concurrency_limit = Some(ConcurrencyLimitConfig {
+                        max_in_flight: *max_in_flight,
+                    });
+                }
+            }
+        }
+
+        if allows.is_empty() {
+            allows.push(RuntimePolicy {
+                id: "allow-all".to_string(),
+                description: "Allow requests not denied above".to_string(),
+                priority: 0,
+                agent_patterns: vec![],
+                endpoint_patterns: vec![],
+                action: PolicyAction::Allow,
+                rate_limit: None,
+                spending_cap: None,
+                concurrency_limit: None,
+            });
+        }
+        for policy in allows.iter_mut() {
+            policy.rate_limit = rate_limit.clone();
+            policy.spending_cap = spending_cap.clone();
+            policy.concurrency_limit = concurrency_limit.clone();
+        }
+
+        // Explicit, strictly descending priorities in evaluation order
+        let mut policies: Vec<RuntimePolicy> = denies.into_iter().chain(allows).collect();
+        let count = policies.len() as u32;
+        for (i, policy) in policies.iter_mut().enumerate() {
+            policy.priority = (count - i as u32) * 10;
+        }
+
+        Ok(RuntimePolicyFile {
+            policies,
+            allow_custom_currencies: self.allow_custom_currencies,
+        })
+    }
+}
+
+/// Canonical symbol of the currency of `what`, or an error suggesting the
+/// closest known one
+fn checked_currency(currency: &str, allow_custom: bool, what: &str) -> Result<String> {
+    canonical_currency(currency, allow_custom).map_err(|e| {
+        let fix = match suggest_currency(currency) {
+            Some(known) => format!("Set `currency: {}`", known),
+            None => format!(
+                "Use a known currency ({})",
+                known_currencies().collect::<Vec<_>>().join(", ")
+            ),
+        };
+        anyhow!(
+            "{}: {}\nFix: {}, or set `allow_custom_currencies: true` at the top of the file to keep a custom currency",
+            what,
+            e,
+            fix
+        )
+    })
+}
+
+/// Runtime policy matching `values` on the given simple-schema field
+fn pattern_policy(
+    index: usize,
+    kind: &str,
+    field: &str,
+    values: &[String],
+) -> Result<RuntimePolicy> {
+    let mut policy = RuntimePolicy {
+        id: format!("{}-{}-{}", kind, field, index),
+        description: format!("{}list on {} (from policy #{})", kind, field, index),
+        priority: 0,
+        agent_patterns: vec![],
+        endpoint_patterns: vec![],
+        action: PolicyAction::Allow,
+        rate_limit: None,
+        spending_cap: None,
+        concurrency_limit: None,
+    };
+
+    match field {
+        "agent_id" => policy.agent_patterns = values.to_vec(),
+        "endpoint" | "path" => policy.endpoint_patterns = values.to_vec(),
+        other => bail!(
+            "Policy #{}: field '{}' has no runtime equivalent\nFix: Runtime policies match only 'agent_id' and 'endpoint'",
+            index,
+            other
+        ),
+    }
+
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::validator::validate_policies;
+    use crate::policy::window::SpendingWindow;
+    use std::time::Duration;
+
+    const SIMPLE: &str = r#"
+policies:
+  - type: denylist
+    field: agent_id
+    values: ["agent-bad-*"]
+  - type: allowlist
+    field: agent_id
+    values: ["agent-good-*"]
+  - type: rate_limit
+    max_requests: 100
+    window_seconds: 3600
+  - type: concurrency_limit
+    max_in_flight: 5
+pricing:
+  amount: 0.05
+  currency: USDC
+  memo_prefix: api
+"#;
+
+    const RUNTIME: &str = r#"
+policies:
+  - id: block-bad
+    priority: 200
+    agent_patterns: ["agent-bad-*"]
+    action:
+      deny: Blocked agent
+  - id: paid-api
+    description: Metered API access
+    priority: 100
+    endpoint_patterns: ["/api/*"]
+    action: allow
+    rate_limit:
+      max_requests: 10
+      window: 1h
+    spending_cap:
+      max_amount: 5.0
+      window: 1day
+"#;
+
+    #[test]
+    fn test_simple_schema_round_trip() {
+        let loaded = parse_policy_yaml(SIMPLE).unwrap();
+        let LoadedPolicyFile::Simple(file) = &loaded else {
+            panic!("expected simple schema");
+        };
+
+        let yaml = serde_yaml::to_string(file).unwrap();
+        assert_eq!(parse_policy_yaml(&yaml).unwrap(), loaded);
+    }
+
+    #[test]
+    fn test_runtime_schema_round_trip() {
+        let loaded = parse_policy_yaml(RUNTIME).unwrap();
+        let LoadedPolicyFile::Runtime(file) = &loaded else {
+            panic!("expected runtime schema");
+        };
+        let rate = file.policies[1].rate_limit.as_ref().unwrap();
+        assert_eq!(rate.window, Duration::from_secs(3600));
+        assert_eq!(
+            file.policies[1].spending_cap.as_ref().unwrap().currency,
+            "USDC"
+        );
+        assert_eq!(
+            file.policies[0].action,
+            PolicyAction::Deny("Blocked agent".to_string())
+        );
+
+        let yaml = serde_yaml::to_string(file).unwrap();
+        assert!(yaml.contains("window: 1h"));
+        assert_eq!(parse_policy_yaml(&yaml).unwrap(), loaded);
+    }
+
+    #[test]
+    fn test_simple_schema_windows_with_units() {
+        let yaml = r#"
+policies:
+  - type: rate_limit
+    max_requests: 10
+    window_seconds: 90
+  - type: spending_cap
+    max_amount: 20.0
+    currency: USDC
+    window_seconds: 24h
+"#;
+        let LoadedPolicyFile::Simple(file) = parse_policy_yaml(yaml).unwrap() else {
+            panic!("expected simple schema");
+        };
+        let runtime = file.to_runtime().unwrap();
+        let policy = &runtime.policies[0];
+        assert_eq!(
+            policy.rate_limit.as_ref().unwrap().window,
+            Duration::from_secs(90)
+        );
+        assert_eq!(
+            policy.spending_cap.as_ref().unwrap().window,
+            Some(Duration::from_secs(86_400))
+        );
+
+        let yaml = serde_yaml::to_string(&file).unwrap();
+        assert!(yaml.contains("window_seconds: 90s"), "{}", yaml);
+        assert!(yaml.contains("window_seconds: 1d"), "{}", yaml);
+    }
+
+    #[test]
+    fn test_calendar_spending_caps() {
+        let yaml = r#"
+policies:
+  - type: spending_cap
+    max_amount: 20.0
+    currency: USDC
+    window_type: calendar_month
+    timezone: Europe/Berlin
+"#;
+        let LoadedPolicyFile::Simple(file) = parse_policy_yaml(yaml).unwrap() else {
+            panic!("expected simple schema");
+        };
+        let runtime = file.to_runtime().unwrap();
+        let cap = runtime.policies[0].spending_cap.as_ref().unwrap();
+        assert_eq!(cap.window, None);
+        assert_eq!(
+            cap.spending_window(),
+            Ok(SpendingWindow::CalendarMonth(chrono_tz::Europe::Berlin))
+        );
+
+        // The runtime schema writes the same keys and reads them back
+        let yaml = serde_yaml::to_string(&runtime).unwrap();
+        assert!(yaml.contains("window_type: calendar_month"));
+        assert!(yaml.contains("timezone: Europe/Berlin"));
+        assert!(!yaml.contains("window:"));
+        assert_eq!(
+            parse_policy_yaml(&yaml).unwrap(),
+            LoadedPolicyFile::Runtime(runtime)
+        );
+
+        let invalid = r#"
+policies:
+  - id: monthly
+    action: allow
+    spending_cap:
+      max_amount: 20.0
+      window: 30days
+      window_type: calendar_month
+"#;
+        let err = parse_policy_yaml(invalid).unwrap_err().to_string();
+        assert!(
+            err.contains("Policy 'monthly': invalid spending_cap"),
+            "{}",
+            err
+        );
+        assert!(err.contains("Fix:"));
+    }
+
+    #[test]
+    fn test_ambiguous_entry_is_rejected() {
+        let yaml = r#"
+policies:
+  - id: confused
+    type: allowlist
+    field: agent_id
+    values: ["a"]
+    action: allow
+"#;
+        let err = detect_policy_schema(yaml).unwrap_err().to_string();
+        assert!(err.contains("Policy #0 is ambiguous"), "{}", err);
+        assert!(err.contains("Fix:"));
+    }
+
+    #[test]
+    fn test_mixed_schemas_are_rejected() {
+        let yaml = r#"
+policies:
+  - type: allowlist
+    field: agent_id
+    values: ["a"]
+  - id: other
+    action: allow
+"#;
+        let err = detect_policy_schema(yaml).unwrap_err().to_string();
+        assert!(err.contains("policy #0 is a simple rule but policy #1 is a runtime policy"));
+    }
+
+    /// Simple policy file with one spending cap in `currency`
+    fn spending_cap_yaml(currency: &str, allow_custom: bool) -> String {
+        format!(
+            "allow_custom_currencies: {}\npolicies:\n  - type: spending_cap\n    max_amount: 10.0\n    currency: {}\n    window_seconds: 1h\n",
+            allow_custom, currency
+        )
+    }
+
+    #[test]
+    fn test_known_currency_is_normalized() {
+        let LoadedPolicyFile::Simple(file) =
+            parse_policy_yaml(&spending_cap_yaml("usdc", false)).unwrap()
+        else {
+            panic!("expected simple schema");
+        };
+        assert!(matches!(
+            &file.policies[0],
+            PolicyRule::SpendingCap { currency, .. } if currency == "USDC"
+        ));
+        let runtime = file.to_runtime().unwrap();
+        assert_eq!(
+            runtime.policies[0].spending_cap.as_ref().unwrap().currency,
+            "USDC"
+        );
+
+        let runtime = runtime_policies_from_yaml(
+            "policies:\n  - id: paid\n    action: allow\n    spending_cap:\n      max_amount: 5.0\n      currency: sol\n      window: 1h\n",
+        )
+        .unwrap();
+        assert_eq!(
+            runtime.policies[0].spending_cap.as_ref().unwrap().currency,
+            "SOL"
+        );
+    }
+
+    #[test]
+    fn test_unknown_currency_suggests_known_one() {
+        let err = parse_policy_yaml(&spending_cap_yaml("USD", false))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("Policy #0: Unknown currency 'USD' (did you mean USDC?)"),
+            "{}",
+            err
+        );
+        assert!(err.contains("Fix: Set `currency: USDC`"), "{}", err);
+
+        let err = parse_policy_yaml(&spending_cap_yaml("CREDITS", false))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Use a known currency (USDC, SOL)"), "{}", err);
+    }
+
+    #[test]
+    fn test_custom_currencies_are_allowed_when_enabled() {
+        let LoadedPolicyFile::Simple(file) =
+            parse_policy_yaml(&spending_cap_yaml("CREDITS", true)).unwrap()
+        else {
+            panic!("expected simple schema");
+        };
+        assert!(matches!(
+            &file.policies[0],
+            PolicyRule::SpendingCap { currency, .. } if currency == "CREDITS"
+        ));
+        assert!(validate_policies(&file.to_config()).is_valid());
+        assert!(file.to_runtime().unwrap().allow_custom_currencies);
+    }
+
+    #[test]
+    fn test_to_runtime_assigns_explicit_priorities() {
+        let LoadedPolicyFile::Simple(file) = parse_policy_yaml(SIMPLE).unwrap() else {
+            panic!("expected simple schema");
+        };
+        let runtime = file.to_runtime().unwrap();
+
+        let ids: Vec<_> = runtime.policies.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["deny-agent_id-0", "allow-agent_id-1"]);
+        assert_eq!(runtime.policies[0].priority, 20);
+        assert_eq!(runtime.policies[1].priority, 10);
+        assert!(runtime.policies[1].rate_limit.is_some());
+        assert_eq!(
+            runtime.policies[1].concurrency_limit,
+            Some(ConcurrencyLimitConfig { max_in_flight: 5 })
+        );
+
+        // The exported file is itself a valid runtime document
+        let yaml = serde_yaml::to_string(&runtime).unwrap();
+        assert_eq!(
+            parse_policy_yaml(&yaml).unwrap(),
+            LoadedPolicyFile::Runtime(runtime)
+        );
+    }
+
+    #[test]
+    fn test_runtime_policies_from_yaml_accepts_both_schemas() {
+        let LoadedPolicyFile::Simple(file) = parse_policy_yaml(SIMPLE).unwrap() else {
+            panic!("expected simple schema");
+        };
+        let runtime = runtime_policies_from_yaml(SIMPLE).unwrap();
+        assert_eq!(runtime, file.to_runtime().unwrap());
+
+        let yaml = serde_yaml::to_string(&runtime).unwrap();
+        assert_eq!(runtime_policies_from_yaml(&yaml).unwrap(), runtime);
+    }
+
+    #[test]
+    fn test_to_runtime_adds_catch_all_without_allowlist() {
+        let file = PolicyFile {
+            policies: vec![PolicyRule::Denylist {
+                field: "endpoint".to_string(),
+                values: vec!["/admin/*".to_string()],
+            }],
+            pricing: PricingConfig::default(),
+            audit: AuditConfig::default(),
+            allow_custom_currencies: false,
+        };
+        let runtime = file.to_runtime().unwrap();
+        assert_eq!(runtime.policies.len(), 2);
+        assert_eq!(runtime.policies[0].endpoint_patterns, ["/admin/*"]);
+        assert_eq!(runtime.policies[1].id, "allow-all");
+    }
+
+    #[test]
+    fn test_to_runtime_rejects_unmapped_field() {
+        let file = PolicyFile {
+            policies: vec![PolicyRule::Allowlist {
+                field: "wallet_address".to_string(),
+                values: vec!["0xabc".to_string()],
+            }],
+            pricing: PricingConfig::default(),
+            audit: AuditConfig::default(),
+            allow_custom_currencies: false,
+        };
+        let err = file.to_runtime().unwrap_err().to_string();
+        assert!(err.contains("'wallet_address' has no runtime equivalent"));
+    }
+
+    const ONE_BROKEN: &str = r#"# Work in progress
+policies:
+  - type: allowlist
+    field: agent_id
+    values: ["agent-a"]
+  - type: rate_limt
+    max_requests: 10
+  - type: denylist
+    field: agent_id
+    values: ["agent-a"]
+"#;
+
+    #[test]
+    fn test_lenient_parse_skips_broken_rules() {
+        let parsed =
+            parse_policy_file_lenient(ONE_BROKEN, DetectedFormat::given(FileFormat::Yaml)).unwrap();
+        assert_eq!(parsed.file.policies.len(), 2);
+        assert_eq!(parsed.indices, [0, 2]);
+        assert_eq!(parsed.skipped.len(), 1);
+        assert_eq!(parsed.skipped[0].index, 1);
+        assert_eq!(parsed.skipped[0].line, Some(6));
+        assert!(parsed.skipped[0].error.contains("rate_limt"));
+
+        // Conflicts between the rules that parsed name their file positions
+        let mut report = validate_policies(&parsed.file.to_config());
+        report.renumber_policies(&parsed.indices);
+        let conflict = report.issues.iter().find(|i| i.policy_indices.len() == 2);
+        let conflict = conflict.expect("allowlist/denylist conflict");
+        assert_eq!(conflict.policy_indices, [0, 2]);
+        assert!(conflict.details.as_deref().unwrap().contains("#0, #2"));
+
+        // Strict parsing rejects the whole file
+        assert!(parse_policy_yaml(ONE_BROKEN).is_err());
+    }
+
+    #[test]
+    fn test_lenient_files_are_refused_by_strict_loading() {
+        let yaml = format!("lenient: true\n{}", SIMPLE);
+        let err = parse_policy_yaml(&yaml).unwrap_err().to_string();
+        assert!(err.contains("marked `lenient: true`"), "{}", err);
+        let doc: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert!(lenient_requested(&doc));
+    }
 }
@@ -1,7 +1,12 @@
 // Runtime types for policy engine evaluation
 // These types are used during policy enforcement, separate from YAML parsing types
 
+use super::state::ConcurrencyGuard;
+use super::window::{SpendingWindow, WindowType};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
+use x402_domain::normalize_currency;
 
 /// Runtime request being evaluated
 #[derive(Debug, Clone)]
@@ -12,13 +17,28 @@ pub struct Request {
     pub endpoint: String,
     pub amount: f64,
     pub timestamp: SystemTime,
+    /// UUID of the invoice memo being issued (prefix stripped), recorded
+    /// with spending so policy state can be correlated with invoices
+    pub memo_id: Option<String>,
+    /// ID of the HTTP request, copied into audit entries
+    pub request_id: Option<String>,
 }
 
 /// Policy evaluation decision
+///
+/// `Allow` carries the in-flight slot for policies with a concurrency limit;
+/// keep the decision (or the guard) alive until the request completes.
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum PolicyDecision {
-    Allow { policy_id: String },
-    Deny { reason: String, policy_id: String },
+    Allow {
+        policy_id: String,
+        guard: Option<ConcurrencyGuard>,
+    },
+    Deny {
+        reason: String,
+        policy_id: String,
+    },
 }
 
 impl PolicyDecision {
@@ -33,30 +53,259 @@ impl PolicyDecision {
     }
 }
 
+/// Remaining allowance of one policy for one agent
+///
+/// Reported alongside a decision; limits the policy doesn't configure are
+/// left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuotaStatus {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitQuota>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spending_cap: Option<SpendingCapQuota>,
+}
+
+/// Policies considered for a request, highest priority first
+///
+/// Built by [`PolicyEngine::explain`](super::PolicyEngine::explain) from the
+/// agent and endpoint patterns only; rate limits and spending caps of the
+/// winner are not checked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyMatchExplanation {
+    pub agent_id: String,
+    pub endpoint: String,
+    pub candidates: Vec<PolicyCandidate>,
+    /// ID of the first candidate matching both patterns, or `default` (deny)
+    pub winner: String,
+}
+
+/// One policy in a [`PolicyMatchExplanation`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PolicyCandidate {
+    pub policy_id: String,
+    pub priority: u32,
+    pub action: crate::policy::types::PolicyAction,
+    /// An agent pattern matched (no patterns match every agent)
+    pub agent_matched: bool,
+    /// An endpoint pattern matched (no patterns match every endpoint)
+    pub endpoint_matched: bool,
+    pub winner: bool,
+}
+
+/// Requests counted in the current sliding window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimitQuota {
+    pub max_requests: u32,
+    pub used: u32,
+    pub remaining: u32,
+    pub window_seconds: u64,
+}
+
+/// Amount spent in the current window
+///
+/// For calendar windows, `window_seconds` is the length of the current day
+/// or month.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendingCapQuota {
+    pub max_amount: f64,
+    pub spent: f64,
+    pub remaining: f64,
+    pub currency: String,
+    pub window_seconds: u64,
+    #[serde(default, skip_serializing_if = "WindowType::is_rolling")]
+    pub window_type: WindowType,
+}
+
+/// Response header names reporting a [`QuotaStatus`]
+///
+/// The defaults follow the common `X-RateLimit-*` convention; rename them to
+/// match what a production gateway sends.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuotaHeaders {
+    pub rate_limit_limit: String,
+    pub rate_limit_remaining: String,
+    /// Length of the rate limit window in seconds
+    pub rate_limit_reset: String,
+    pub spend_cap: String,
+    pub spend_remaining: String,
+}
+
+impl Default for QuotaHeaders {
+    fn default() -> Self {
+        Self {
+            rate_limit_limit: "X-RateLimit-Limit".to_string(),
+            rate_limit_remaining: "X-RateLimit-Remaining".to_string(),
+            rate_limit_reset: "X-RateLimit-Reset".to_string(),
+            spend_cap: "X-Spend-Cap".to_string(),
+            spend_remaining: "X-Spend-Remaining".to_string(),
+        }
+    }
+}
+
+impl QuotaHeaders {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Every name must be a valid HTTP header name
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for name in [
+            &self.rate_limit_limit,
+            &self.rate_limit_remaining,
+            &self.rate_limit_reset,
+            &self.spend_cap,
+            &self.spend_remaining,
+        ] {
+            if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+                anyhow::bail!(
+                    "Invalid quota_headers name: '{}'. Must be an HTTP header name.\nFix: Use letters, digits and '-', e.g. X-RateLimit-Remaining",
+                    name
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Header names and values for `quota`; limits it leaves out are skipped
+    ///
+    /// Spending amounts are decimal strings with at most 6 places.
+    pub fn values(&self, quota: &QuotaStatus) -> Vec<(&str, String)> {
+        let mut values = Vec::new();
+        if let Some(rate) = &quota.rate_limit {
+            values.push((
+                self.rate_limit_limit.as_str(),
+                rate.max_requests.to_string(),
+            ));
+            values.push((
+                self.rate_limit_remaining.as_str(),
+                rate.remaining.to_string(),
+            ));
+            values.push((
+                self.rate_limit_reset.as_str(),
+                rate.window_seconds.to_string(),
+            ));
+        }
+        if let Some(spend) = &quota.spending_cap {
+            values.push((self.spend_cap.as_str(), decimal_string(spend.max_amount)));
+            values.push((
+                self.spend_remaining.as_str(),
+                decimal_string(spend.remaining),
+            ));
+        }
+        values
+    }
+}
+
+/// `amount` rounded to 6 places without trailing zeros, e.g. `0.09`
+fn decimal_string(amount: f64) -> String {
+    rust_decimal::Decimal::from_f64_retain(amount)
+        .map(|d| d.round_dp(6).normalize().to_string())
+        .unwrap_or_else(|| amount.to_string())
+}
+
 /// Rate limit configuration for runtime
-#[derive(Debug, Clone)]
+///
+/// `window` is written in humantime form in YAML, e.g. `1h` or `30s`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RateLimitConfig {
     pub max_requests: u32,
+    #[serde(with = "humantime_serde")]
     pub window: std::time::Duration,
 }
 
 /// Spending cap configuration for runtime
-#[derive(Debug, Clone)]
+///
+/// Rolling caps set `window` (humantime, e.g. `24h`); calendar caps set
+/// `window_type: calendar_day` or `calendar_month` and optionally a
+/// `timezone` instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SpendingCapConfig {
     pub max_amount: f64,
+    #[serde(default = "default_currency")]
     pub currency: String,
-    pub window: std::time::Duration,
+    #[serde(
+        default,
+        with = "humantime_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub window: Option<std::time::Duration>,
+    #[serde(default, skip_serializing_if = "WindowType::is_rolling")]
+    pub window_type: WindowType,
+    /// Timezone calendar windows reset in (default UTC)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<Tz>,
+}
+
+impl SpendingCapConfig {
+    /// True if the cap counts payments made in `currency`
+    ///
+    /// Currencies compare in canonical case, so a cap in `usdc` counts
+    /// `USDC` payments.
+    pub fn counts_currency(&self, currency: &str) -> bool {
+        normalize_currency(&self.currency) == normalize_currency(currency)
+    }
+
+    /// Window the cap is enforced over, or why the configuration has none
+    pub fn spending_window(&self) -> Result<SpendingWindow, String> {
+        if let Some(calendar) = SpendingWindow::calendar(self.window_type, self.timezone) {
+            return match self.window {
+                None => Ok(calendar),
+                Some(_) => Err(format!(
+                    "window cannot be combined with window_type: {} (calendar windows reset at local midnight)",
+                    self.window_type
+                )),
+            };
+        }
+        if self.timezone.is_some() {
+            return Err(
+                "timezone only applies to calendar_day and calendar_month windows".to_string(),
+            );
+        }
+        match self.window {
+            Some(window) if !window.is_zero() => Ok(SpendingWindow::Rolling(window)),
+            _ => Err("window must be greater than 0".to_string()),
+        }
+    }
+}
+
+/// Maximum simultaneous in-flight requests per agent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConcurrencyLimitConfig {
+    pub max_in_flight: u32,
+}
+
+fn default_currency() -> String {
+    "USDC".to_string()
 }
 
 /// Runtime policy representation (converted from YAML PolicyRule)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Policy {
     pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub description: String,
+    /// Higher priorities are evaluated first
+    #[serde(default)]
     pub priority: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub agent_patterns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub endpoint_patterns: Vec<String>,
     pub action: crate::policy::types::PolicyAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub rate_limit: Option<RateLimitConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub spending_cap: Option<SpendingCapConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency_limit: Option<ConcurrencyLimitConfig>,
+}
+
+/// Policy file in the runtime schema (`id`/`priority`/`action` entries)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuntimePolicyFile {
+    pub policies: Vec<Policy>,
+    /// Admit currencies missing from the currency registry, kept as written
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_custom_currencies: bool,
 }
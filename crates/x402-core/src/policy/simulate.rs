@@ -0,0 +1,663 @@
+// Synthetic agent populations for policy load testing
+//
+// A population spec describes cohorts of agents (ID template, endpoints,
+// request rate and spend distributions, a fraction of malicious agents that
+// send more and spend more). `PopulationSpec::generate` turns it into a
+// time-ordered stream of `Request`s from a SplitMix64 sequence, so a fixed
+// seed replays the same traffic. `simulate` feeds the stream to a
+// `PolicyEngine` in simulated time and counts outcomes per cohort, comparing
+// deny rates with the expected ones.
+
+use super::engine::{PolicyEngine, RATE_LIMIT_EXCEEDED, SPENDING_CAP_EXCEEDED};
+use super::runtime_types::{PolicyDecision, Request};
+use crate::format::DetectedFormat;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Placeholder in `agent_id` templates replaced by the agent's number
+pub const AGENT_NUMBER_PLACEHOLDER: &str = "{n}";
+
+/// Allowed difference between actual and expected deny rates by default
+pub const DEFAULT_DENY_RATE_TOLERANCE: f64 = 0.05;
+
+/// Population file (`x402-dev policy simulate --population`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PopulationSpec {
+    /// Seed of the request stream; the same seed gives the same requests
+    #[serde(default)]
+    pub seed: u64,
+    /// Allowed difference between actual and expected deny rates
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    pub cohorts: Vec<CohortSpec>,
+}
+
+fn default_tolerance() -> f64 {
+    DEFAULT_DENY_RATE_TOLERANCE
+}
+
+/// Group of agents with the same behavior
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CohortSpec {
+    pub name: String,
+    /// Number of agents
+    pub agents: u32,
+    /// Agent ID template, `{n}` is replaced by 1..=agents (e.g. `agent-{n}`)
+    pub agent_id: String,
+    /// Endpoints requested, picked uniformly per request
+    #[serde(default = "default_endpoints")]
+    pub endpoints: Vec<String>,
+    /// Requests per minute of each agent, drawn once per agent
+    pub requests_per_minute: Distribution,
+    /// Amount of each request, drawn per request
+    #[serde(default)]
+    pub amount: Distribution,
+    #[serde(default)]
+    pub arrivals: Arrivals,
+    /// Expected share of denied requests of the well-behaved agents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_deny_rate: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub malicious: Option<MaliciousSpec>,
+}
+
+fn default_endpoints() -> Vec<String> {
+    vec!["/api/data".to_string()]
+}
+
+/// Agents of a cohort that exceed its normal behavior
+///
+/// The last `fraction` of the cohort's agents (rounded) multiply their
+/// request rate and amounts and are reported as a separate row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaliciousSpec {
+    /// Share of the cohort's agents, 0 to 1
+    pub fraction: f64,
+    #[serde(default = "one")]
+    pub rate_multiplier: f64,
+    #[serde(default = "one")]
+    pub amount_multiplier: f64,
+    /// Expected share of denied requests of the malicious agents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_deny_rate: Option<f64>,
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+/// A fixed value or a uniform range (`{ min: 1, max: 5 }`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Distribution {
+    Fixed(f64),
+    Uniform { min: f64, max: f64 },
+}
+
+impl Default for Distribution {
+    fn default() -> Self {
+        Distribution::Fixed(0.0)
+    }
+}
+
+impl Distribution {
+    fn sample(&self, rng: &mut SplitMix64) -> f64 {
+        match *self {
+            Distribution::Fixed(value) => value,
+            Distribution::Uniform { min, max } => min + (max - min) * rng.next_f64(),
+        }
+    }
+
+    fn validate(&self, what: &str) -> Result<()> {
+        let (min, max) = match *self {
+            Distribution::Fixed(value) => (value, value),
+            Distribution::Uniform { min, max } => (min, max),
+        };
+        if !min.is_finite() || !max.is_finite() || min < 0.0 || min > max {
+            bail!(
+                "{} must be a non-negative number or a range with min <= max\nFix: e.g. `{}: 10` or `{}: {{ min: 5, max: 20 }}`",
+                what,
+                what,
+                what
+            );
+        }
+        Ok(())
+    }
+}
+
+/// How an agent's requests are spread over time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Arrivals {
+    /// Exponential gaps around the agent's rate (bursty)
+    #[default]
+    Poisson,
+    /// Equal gaps with a random start, so the count is exactly rate × duration
+    Even,
+}
+
+/// Request of a generated stream with the cohort it came from
+#[derive(Debug, Clone)]
+pub struct SimulatedRequest {
+    /// Index into [`PopulationSpec::cohorts`]
+    pub cohort: usize,
+    pub malicious: bool,
+    /// Offset from the start of the simulation
+    pub offset: Duration,
+    pub request: Request,
+}
+
+impl PopulationSpec {
+    /// Parse and validate a population file
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let spec: Self = serde_yaml::from_str(yaml).context("Invalid population file")?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Read a population file written in YAML or JSON
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read population file {}\nFix: Check the --population path",
+                path.display()
+            )
+        })?;
+        DetectedFormat::detect(Some(path), &content)
+            .parse(&content)
+            .and_then(|spec: Self| spec.validate().map(|()| spec))
+            .map_err(|e| anyhow!("Invalid population file {}: {:#}", path.display(), e))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.cohorts.is_empty() {
+            bail!("Population has no cohorts\nFix: Add at least one entry under `cohorts:`");
+        }
+        if !self.tolerance.is_finite() || self.tolerance < 0.0 {
+            bail!(
+                "tolerance must be a non-negative number, got {}",
+                self.tolerance
+            );
+        }
+        let mut names = HashSet::new();
+        for cohort in &self.cohorts {
+            let name = &cohort.name;
+            if !names.insert(name.as_str()) {
+                bail!(
+                    "Cohort '{}' is defined more than once\nFix: Give every cohort a unique name",
+                    name
+                );
+            }
+            if cohort.agents > 1 && !cohort.agent_id.contains(AGENT_NUMBER_PLACEHOLDER) {
+                bail!(
+                    "Cohort '{}': agent_id '{}' gives all {} agents the same ID\nFix: Add {} to the template, e.g. agent-{}",
+                    name,
+                    cohort.agent_id,
+                    cohort.agents,
+                    AGENT_NUMBER_PLACEHOLDER,
+                    AGENT_NUMBER_PLACEHOLDER
+                );
+            }
+            if cohort.endpoints.is_empty() || cohort.endpoints.iter().any(|e| !e.starts_with('/')) {
+                bail!(
+                    "Cohort '{}': endpoints must be a non-empty list of paths starting with '/'",
+                    name
+                );
+            }
+            cohort
+                .requests_per_minute
+                .validate("requests_per_minute")
+                .with_context(|| format!("Cohort '{}'", name))?;
+            cohort
+                .amount
+                .validate("amount")
+                .with_context(|| format!("Cohort '{}'", name))?;
+            let rates = cohort
+                .expected_deny_rate
+                .into_iter()
+                .chain(cohort.malicious.as_ref().and_then(|m| m.expected_deny_rate));
+            for rate in rates {
+                if !(0.0..=1.0).contains(&rate) {
+                    bail!(
+                        "Cohort '{}': expected_deny_rate must be between 0 and 1, got {}",
+                        name,
+                        rate
+                    );
+                }
+            }
+            if let Some(malicious) = &cohort.malicious {
+                if !(0.0..=1.0).contains(&malicious.fraction) {
+                    bail!(
+                        "Cohort '{}': malicious.fraction must be between 0 and 1, got {}",
+                        name,
+                        malicious.fraction
+                    );
+                }
+                for (field, value) in [
+                    ("rate_multiplier", malicious.rate_multiplier),
+                    ("amount_multiplier", malicious.amount_multiplier),
+                ] {
+                    if !value.is_finite() || value < 0.0 {
+                        bail!(
+                            "Cohort '{}': malicious.{} must be a non-negative number, got {}",
+                            name,
+                            field,
+                            value
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Requests of every agent over `duration` after `start`, ordered by time
+    ///
+    /// Deterministic for a given seed: rates, arrival times, endpoints and
+    /// amounts are all drawn from one sequence, cohort by cohort and agent
+    /// by agent.
+    pub fn generate(&self, start: SystemTime, duration: Duration) -> Vec<SimulatedRequest> {
+        let mut rng = SplitMix64::new(self.seed);
+        let mut requests = Vec::new();
+        let window = duration.as_secs_f64();
+
+        for (index, cohort) in self.cohorts.iter().enumerate() {
+            let malicious_from = cohort.agents - cohort.malicious_agents();
+            for number in 1..=cohort.agents {
+                let malicious = number > malicious_from;
+                let (rate_factor, amount_factor) = match (&cohort.malicious, malicious) {
+                    (Some(spec), true) => (spec.rate_multiplier, spec.amount_multiplier),
+                    _ => (1.0, 1.0),
+                };
+                let agent_id = cohort
+                    .agent_id
+                    .replace(AGENT_NUMBER_PLACEHOLDER, &number.to_string());
+                let per_second = cohort.requests_per_minute.sample(&mut rng) * rate_factor / 60.0;
+
+                for offset in arrival_times(cohort.arrivals, per_second, window, &mut rng) {
+                    let endpoint = &cohort.endpoints
+                        [(rng.next_u64() % cohort.endpoints.len() as u64) as usize];
+                    let amount = cohort.amount.sample(&mut rng) * amount_factor;
+                    let offset = Duration::from_secs_f64(offset);
+                    requests.push(SimulatedRequest {
+                        cohort: index,
+                        malicious,
+                        offset,
+                        request: Request {
+                            agent_id: agent_id.clone(),
+                            wallet_address: None,
+                            ip_address: None,
+                            endpoint: endpoint.clone(),
+                            // Whole cents, as the engine counts spending
+                            amount: (amount * 100.0).round() / 100.0,
+                            timestamp: start + offset,
+                            memo_id: None,
+                            request_id: None,
+                        },
+                    });
+                }
+            }
+        }
+
+        // Stable: simultaneous requests keep cohort and agent order
+        requests.sort_by_key(|r| r.offset);
+        requests
+    }
+}
+
+impl CohortSpec {
+    /// Number of agents behaving maliciously (the last ones of the cohort)
+    pub fn malicious_agents(&self) -> u32 {
+        self.malicious.as_ref().map_or(0, |m| {
+            ((f64::from(self.agents) * m.fraction).round() as u32).min(self.agents)
+        })
+    }
+}
+
+/// Offsets in seconds of one agent's requests within `[0, window)`
+fn arrival_times(
+    arrivals: Arrivals,
+    per_second: f64,
+    window: f64,
+    rng: &mut SplitMix64,
+) -> Vec<f64> {
+    if per_second <= 0.0 {
+        return Vec::new();
+    }
+    let mut times = Vec::new();
+    match arrivals {
+        Arrivals::Even => {
+            let gap = 1.0 / per_second;
+            let mut t = gap * rng.next_f64();
+            while t < window {
+                times.push(t);
+                t += gap;
+            }
+        }
+        Arrivals::Poisson => {
+            let mut t = 0.0;
+            loop {
+                t += -(1.0 - rng.next_f64()).ln() / per_second;
+                if t >= window {
+                    break;
+                }
+                times.push(t);
+            }
+        }
+    }
+    times
+}
+
+/// SplitMix64 sequence (same generator as weighted recipient routing)
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+// ============================================================================
+// Simulation
+// ============================================================================
+
+/// Outcomes of one cohort's well-behaved or malicious agents
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CohortOutcome {
+    pub cohort: String,
+    pub malicious: bool,
+    pub agents: u32,
+    pub requests: u64,
+    pub allowed: u64,
+    /// Denied by a deny policy, the default deny or a concurrency limit
+    pub denied: u64,
+    pub rate_limited: u64,
+    pub cap_hit: u64,
+    /// (denied + rate_limited + cap_hit) / requests, 0 without requests
+    pub deny_rate: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_deny_rate: Option<f64>,
+    /// Whether `deny_rate` is within the tolerance of the expected rate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_expected: Option<bool>,
+}
+
+/// Result of [`simulate`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub seed: u64,
+    pub duration_secs: f64,
+    pub requests: u64,
+    /// Cohorts in file order, each followed by its malicious agents
+    pub cohorts: Vec<CohortOutcome>,
+}
+
+impl SimulationReport {
+    /// Rows whose deny rate is off the expected one
+    pub fn unexpected(&self) -> impl Iterator<Item = &CohortOutcome> {
+        self.cohorts.iter().filter(|c| c.as_expected == Some(false))
+    }
+
+    pub fn passed(&self) -> bool {
+        self.unexpected().next().is_none()
+    }
+}
+
+/// Generate the population's requests and evaluate them in order
+///
+/// Requests carry simulated timestamps, so rate limit and spending windows
+/// advance with the simulation rather than the wall clock. Use an engine
+/// with fresh state: existing counters count against the population.
+pub fn simulate(
+    engine: &PolicyEngine,
+    spec: &PopulationSpec,
+    start: SystemTime,
+    duration: Duration,
+) -> Result<SimulationReport> {
+    let requests = spec.generate(start, duration);
+    let mut cohorts: Vec<CohortOutcome> = spec
+        .cohorts
+        .iter()
+        .flat_map(|cohort| {
+            let malicious = cohort.malicious_agents();
+            let row = |is_malicious: bool, agents: u32, expected: Option<f64>| CohortOutcome {
+                cohort: cohort.name.clone(),
+                malicious: is_malicious,
+                agents,
+                requests: 0,
+                allowed: 0,
+                denied: 0,
+                rate_limited: 0,
+                cap_hit: 0,
+                deny_rate: 0.0,
+                expected_deny_rate: expected,
+                as_expected: None,
+            };
+            [
+                row(false, cohort.agents - malicious, cohort.expected_deny_rate),
+                row(
+                    true,
+                    malicious,
+                    cohort.malicious.as_ref().and_then(|m| m.expected_deny_rate),
+                ),
+            ]
+        })
+        .collect();
+
+    for simulated in &requests {
+        let row = &mut cohorts[simulated.cohort * 2 + usize::from(simulated.malicious)];
+        row.requests += 1;
+        match engine.evaluate(&simulated.request)? {
+            PolicyDecision::Allow { .. } => row.allowed += 1,
+            PolicyDecision::Deny { reason, .. } if reason.starts_with(RATE_LIMIT_EXCEEDED) => {
+                row.rate_limited += 1
+            }
+            PolicyDecision::Deny { reason, .. } if reason.starts_with(SPENDING_CAP_EXCEEDED) => {
+                row.cap_hit += 1
+            }
+            _ => row.denied += 1,
+        }
+    }
+
+    // Cohorts without malicious agents get a single row
+    cohorts.retain(|row| !row.malicious || row.agents > 0);
+    for row in &mut cohorts {
+        if row.requests > 0 {
+            row.deny_rate =
+                (row.denied + row.rate_limited + row.cap_hit) as f64 / row.requests as f64;
+        }
+        row.as_expected = row
+            .expected_deny_rate
+            .map(|expected| (row.deny_rate - expected).abs() <= spec.tolerance + f64::EPSILON);
+    }
+
+    Ok(SimulationReport {
+        seed: spec.seed,
+        duration_secs: duration.as_secs_f64(),
+        requests: requests.len() as u64,
+        cohorts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::runtime_types::{Policy, RateLimitConfig, SpendingCapConfig};
+    use crate::policy::types::PolicyAction;
+
+    const POPULATION: &str = r#"
+seed: 7
+cohorts:
+  - name: trusted
+    agents: 4
+    agent_id: agent-{n}
+    endpoints: [/api/data, /api/premium/report]
+    requests_per_minute: 6
+    amount: 0.25
+    arrivals: even
+    expected_deny_rate: 0.0
+    malicious:
+      fraction: 0.25
+      rate_multiplier: 5
+      expected_deny_rate: 0.8
+  - name: big-spenders
+    agents: 1
+    agent_id: agent-big-{n}
+    requests_per_minute: 6
+    amount: 1.0
+    arrivals: even
+    expected_deny_rate: 0.5
+  - name: bots
+    agents: 2
+    agent_id: bot-{n}
+    requests_per_minute: 3
+    arrivals: even
+    expected_deny_rate: 1.0
+"#;
+
+    fn policies() -> Vec<Policy> {
+        vec![
+            Policy {
+                id: "block-bots".to_string(),
+                description: String::new(),
+                priority: 20,
+                agent_patterns: vec!["bot-*".to_string()],
+                endpoint_patterns: vec![],
+                action: PolicyAction::Deny("Bots are not allowed".to_string()),
+                rate_limit: None,
+                spending_cap: None,
+                concurrency_limit: None,
+            },
+            Policy {
+                id: "agents".to_string(),
+                description: String::new(),
+                priority: 10,
+                agent_patterns: vec!["agent-*".to_string()],
+                endpoint_patterns: vec![],
+                action: PolicyAction::Allow,
+                rate_limit: Some(RateLimitConfig {
+                    max_requests: 6,
+                    window: Duration::from_secs(60),
+                }),
+                spending_cap: Some(SpendingCapConfig {
+                    max_amount: 2.0,
+                    currency: "USDC".to_string(),
+                    window: Some(Duration::from_secs(3600)),
+                    window_type: Default::default(),
+                    timezone: None,
+                }),
+                concurrency_limit: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_cohort_counts_with_fixed_seed() {
+        let spec = PopulationSpec::from_yaml(POPULATION).unwrap();
+        let engine = PolicyEngine::new(policies());
+        let report = simulate(
+            &engine,
+            &spec,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+
+        let counts: Vec<(&str, bool, u32, [u64; 5])> = report
+            .cohorts
+            .iter()
+            .map(|c| {
+                (
+                    c.cohort.as_str(),
+                    c.malicious,
+                    c.agents,
+                    [c.requests, c.allowed, c.denied, c.rate_limited, c.cap_hit],
+                )
+            })
+            .collect();
+        // Trusted: 6 requests of 0.25 each, within both limits. Malicious:
+        // 30 requests, the rate limit (6/min) stops 24. Big spenders: 1.0
+        // each, the cap (2.0) stops 4 of 6. Bots: 3 each, all denied.
+        assert_eq!(
+            counts,
+            vec![
+                ("trusted", false, 3, [18, 18, 0, 0, 0]),
+                ("trusted", true, 1, [30, 6, 0, 24, 0]),
+                ("big-spenders", false, 1, [6, 2, 0, 0, 4]),
+                ("bots", false, 2, [6, 0, 6, 0, 0]),
+            ]
+        );
+        assert_eq!(report.requests, 60);
+        let expected: Vec<Option<bool>> = report.cohorts.iter().map(|c| c.as_expected).collect();
+        assert_eq!(
+            expected,
+            vec![Some(true), Some(true), Some(false), Some(true)]
+        );
+        assert!(!report.passed());
+    }
+
+    #[test]
+    fn test_generate_is_deterministic_per_seed() {
+        let mut spec = PopulationSpec::from_yaml(
+            "seed: 1\ncohorts:\n  - name: a\n    agents: 3\n    agent_id: a-{n}\n    requests_per_minute: { min: 10, max: 30 }\n    amount: { min: 0.01, max: 0.1 }\n",
+        )
+        .unwrap();
+        let start = SystemTime::UNIX_EPOCH;
+        let stream = |spec: &PopulationSpec| -> Vec<(String, Duration, f64)> {
+            spec.generate(start, Duration::from_secs(60))
+                .into_iter()
+                .map(|r| (r.request.agent_id, r.offset, r.request.amount))
+                .collect()
+        };
+
+        let first = stream(&spec);
+        assert_eq!(first, stream(&spec));
+        assert!(first.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(first.iter().all(|(_, offset, amount)| {
+            *offset < Duration::from_secs(60) && (0.01..=0.1).contains(amount)
+        }));
+
+        spec.seed = 2;
+        assert_ne!(first, stream(&spec));
+    }
+
+    #[test]
+    fn test_invalid_population_rejected() {
+        let err = PopulationSpec::from_yaml(
+            "cohorts:\n  - name: a\n    agents: 2\n    agent_id: same\n    requests_per_minute: 1\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("same ID"), "{}", err);
+
+        let err = PopulationSpec::from_yaml(
+            "cohorts:\n  - name: a\n    agents: 1\n    agent_id: a\n    requests_per_minute: { min: 5, max: 1 }\n",
+        )
+        .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("requests_per_minute"),
+            "{:#}",
+            err
+        );
+    }
+}
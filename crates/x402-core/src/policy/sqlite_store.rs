@@ -0,0 +1,357 @@
+// SQLite policy state store (`state_backend: sqlite`)
+//
+// Mirrors MemoryStateStore row for row: one row per request and per spend,
+// plus running totals per calendar bucket. Admissions run in BEGIN IMMEDIATE
+// transactions, which take the database write lock before the check, so
+// processes sharing the file serialize on it; WAL mode keeps reads going
+// meanwhile. Timestamps are nanoseconds since the Unix epoch.
+//
+// Servers sharing the file register in `instances`; the requests and spends
+// they record carry their instance id, so a shared counter can be traced
+// back to the server that admitted each request.
+
+use super::state::{SpendingRecord, RETENTION};
+use super::store::{
+    Admission, PolicyStateStore, RateCounter, SpendCounter, StateBackend, StateStats, StoreInstance,
+};
+use super::window::SpendingWindow;
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a writer waits for another process's transaction
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS rate_requests (
+    key TEXT NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS rate_requests_key_timestamp ON rate_requests (key, timestamp);
+CREATE INDEX IF NOT EXISTS rate_requests_timestamp ON rate_requests (timestamp);
+
+CREATE TABLE IF NOT EXISTS spending_records (
+    key TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    amount INTEGER NOT NULL,
+    memo_id TEXT
+);
+CREATE INDEX IF NOT EXISTS spending_records_key_timestamp ON spending_records (key, timestamp);
+CREATE INDEX IF NOT EXISTS spending_records_timestamp ON spending_records (timestamp);
+
+CREATE TABLE IF NOT EXISTS spending_buckets (
+    key TEXT NOT NULL,
+    bucket_start INTEGER NOT NULL,
+    total INTEGER NOT NULL,
+    PRIMARY KEY (key, bucket_start)
+);
+
+CREATE TABLE IF NOT EXISTS instances (
+    id TEXT PRIMARY KEY,
+    pid INTEGER NOT NULL,
+    port INTEGER,
+    started INTEGER,
+    registered INTEGER NOT NULL
+);
+";
+
+/// Tables whose rows carry the recording instance (added to older files)
+const TAGGED_TABLES: [&str; 2] = ["rate_requests", "spending_records"];
+
+/// Policy state in a SQLite database file
+#[derive(Debug)]
+pub struct SqliteStateStore {
+    path: PathBuf,
+    conn: Mutex<Connection>,
+    /// Id of the registered instance, recorded with each row
+    instance: OnceLock<String>,
+}
+
+impl SqliteStateStore {
+    /// Open or create the database at `path`, creating parent directories
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let context = || {
+            format!(
+                "Failed to open policy state database {}\nFix: Check the directory is writable or set state_path in .x402dev.yaml",
+                path.display()
+            )
+        };
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).with_context(context)?;
+        }
+        let conn = Connection::open(&path).with_context(context)?;
+        conn.busy_timeout(BUSY_TIMEOUT).with_context(context)?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .with_context(context)?;
+        conn.execute_batch(SCHEMA).with_context(context)?;
+        add_instance_columns(&conn).with_context(context)?;
+
+        Ok(Self {
+            path,
+            conn: Mutex::new(conn),
+            instance: OnceLock::new(),
+        })
+    }
+
+    /// Database file of this store
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Instance whose rows this store records, once registered
+    pub fn instance_id(&self) -> Option<&str> {
+        self.instance.get().map(String::as_str)
+    }
+
+    /// Requests recorded per instance id (`None` for untagged rows)
+    pub fn requests_by_instance(&self) -> Result<Vec<(Option<String>, u64)>> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT instance, COUNT(*) FROM rate_requests GROUP BY instance ORDER BY instance",
+        )?;
+        let counts = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(counts)
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn
+            .lock()
+            .expect("CRITICAL: Policy state database lock poisoned - thread panic detected")
+    }
+}
+
+impl PolicyStateStore for SqliteStateStore {
+    fn rate_count(&self, key: &str, window: Duration, now: SystemTime) -> Result<u32> {
+        rate_count(&self.conn(), key, window, now)
+    }
+
+    fn spent(&self, key: &str, window: &SpendingWindow, now: SystemTime) -> Result<u64> {
+        spent(&self.conn(), key, window, now)
+    }
+
+    fn spending_records(&self, key: &str) -> Result<Vec<SpendingRecord>> {
+        let conn = self.conn();
+        let mut statement = conn.prepare_cached(
+            "SELECT timestamp, amount, memo_id FROM spending_records
+             WHERE key = ?1 ORDER BY timestamp, rowid",
+        )?;
+        let records = statement
+            .query_map(params![key], |row| {
+                Ok(SpendingRecord {
+                    timestamp: from_nanos(row.get(0)?),
+                    amount: row.get::<_, i64>(1)? as u64,
+                    memo_id: row.get(2)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(records)
+    }
+
+    fn try_admit(
+        &self,
+        rate: Option<RateCounter<'_>>,
+        spend: Option<SpendCounter<'_>>,
+        now: SystemTime,
+    ) -> Result<Admission> {
+        let mut conn = self.conn();
+        // Dropping the transaction without commit rolls it back
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        if let Some(rate) = &rate {
+            if !rate.allows(rate_count(&tx, rate.key, rate.window, now)?) {
+                return Ok(Admission::RateLimited);
+            }
+        }
+        if let Some(spend) = &spend {
+            if !spend.allows(spent(&tx, spend.key, spend.window, now)?) {
+                return Ok(Admission::SpendingCapped);
+            }
+        }
+
+        let timestamp = to_nanos(now);
+        if let Some(rate) = rate {
+            tx.execute(
+                "INSERT INTO rate_requests (key, timestamp, instance) VALUES (?1, ?2, ?3)",
+                params![rate.key, timestamp, self.instance_id()],
+            )?;
+        }
+        if let Some(spend) = spend {
+            tx.execute(
+                "INSERT INTO spending_records (key, timestamp, amount, memo_id, instance)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    spend.key,
+                    timestamp,
+                    spend.amount as i64,
+                    spend.memo_id,
+                    self.instance_id()
+                ],
+            )?;
+            if let Some(bucket) = spend.window.bucket(now) {
+                tx.execute(
+                    "INSERT INTO spending_buckets (key, bucket_start, total) VALUES (?1, ?2, ?3)
+                     ON CONFLICT (key, bucket_start) DO UPDATE SET total = total + excluded.total",
+                    params![spend.key, to_nanos(bucket.start), spend.amount as i64],
+                )?;
+            }
+        }
+        tx.commit()?;
+        Ok(Admission::Admitted)
+    }
+
+    fn cleanup(&self, now: SystemTime) -> Result<()> {
+        let mut conn = self.conn();
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let now_nanos = to_nanos(now);
+        let cutoff = to_nanos(now.checked_sub(RETENTION).unwrap_or(now));
+
+        // Rolling windows: the retention period, never the future
+        for table in ["rate_requests", "spending_records"] {
+            tx.execute(
+                &format!(
+                    "DELETE FROM {} WHERE timestamp < ?1 OR timestamp > ?2",
+                    table
+                ),
+                params![cutoff, now_nanos],
+            )?;
+        }
+        // Calendar windows: only the latest bucket that has started
+        tx.execute(
+            "DELETE FROM spending_buckets
+             WHERE bucket_start > ?1
+                OR bucket_start < (
+                    SELECT MAX(b.bucket_start) FROM spending_buckets b
+                    WHERE b.key = spending_buckets.key AND b.bucket_start <= ?1
+                )",
+            params![now_nanos],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn stats(&self) -> Result<StateStats> {
+        let conn = self.conn();
+        let (rate_limit_keys, requests): (i64, i64) = conn.query_row(
+            "SELECT COUNT(DISTINCT key), COUNT(*) FROM rate_requests",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let (spending_keys, spending_records): (i64, i64) = conn.query_row(
+            "SELECT COUNT(DISTINCT key), COUNT(*) FROM spending_records",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        Ok(StateStats {
+            backend: StateBackend::Sqlite,
+            rate_limit_keys: rate_limit_keys as usize,
+            requests: requests as usize,
+            spending_keys: spending_keys as usize,
+            spending_records: spending_records as usize,
+        })
+    }
+
+    fn register_instance(&self, instance: &StoreInstance) -> Result<()> {
+        self.conn().execute(
+            "INSERT OR REPLACE INTO instances (id, pid, port, started, registered)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                instance.id,
+                instance.pid,
+                instance.port,
+                instance.started.map(|s| s as i64),
+                to_nanos(SystemTime::now())
+            ],
+        )?;
+        // The first registration names this store's rows
+        let _ = self.instance.set(instance.id.clone());
+        Ok(())
+    }
+
+    fn instances(&self) -> Result<Vec<StoreInstance>> {
+        let conn = self.conn();
+        let mut statement =
+            conn.prepare("SELECT id, pid, port, started FROM instances ORDER BY registered, id")?;
+        let instances = statement
+            .query_map([], |row| {
+                Ok(StoreInstance {
+                    id: row.get(0)?,
+                    pid: row.get(1)?,
+                    port: row.get(2)?,
+                    started: row.get::<_, Option<i64>>(3)?.map(|s| s as u64),
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(instances)
+    }
+}
+
+/// Add the `instance` column to files created before instance tagging
+fn add_instance_columns(conn: &Connection) -> rusqlite::Result<()> {
+    for table in TAGGED_TABLES {
+        let has_column = conn
+            .prepare(&format!("PRAGMA table_info({})", table))?
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .iter()
+            .any(|column| column == "instance");
+        if !has_column {
+            conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN instance TEXT", table))?;
+        }
+    }
+    Ok(())
+}
+
+/// Requests in the rolling window, bounded above by `now` like the memory store
+fn rate_count(conn: &Connection, key: &str, window: Duration, now: SystemTime) -> Result<u32> {
+    let start = now.checked_sub(window).unwrap_or(now);
+    let count: i64 = conn
+        .prepare_cached(
+            "SELECT COUNT(*) FROM rate_requests
+             WHERE key = ?1 AND timestamp >= ?2 AND timestamp <= ?3",
+        )?
+        .query_row(params![key, to_nanos(start), to_nanos(now)], |row| {
+            row.get(0)
+        })?;
+    Ok(count as u32)
+}
+
+fn spent(conn: &Connection, key: &str, window: &SpendingWindow, now: SystemTime) -> Result<u64> {
+    let total: Option<i64> = match window {
+        SpendingWindow::Rolling(duration) => {
+            let start = now.checked_sub(*duration).unwrap_or(now);
+            conn.prepare_cached(
+                "SELECT SUM(amount) FROM spending_records
+                 WHERE key = ?1 AND timestamp >= ?2 AND timestamp <= ?3",
+            )?
+            .query_row(params![key, to_nanos(start), to_nanos(now)], |row| {
+                row.get(0)
+            })?
+        }
+        calendar => match calendar.bucket(now) {
+            Some(bucket) => conn
+                .prepare_cached(
+                    "SELECT total FROM spending_buckets WHERE key = ?1 AND bucket_start = ?2",
+                )?
+                .query_row(params![key, to_nanos(bucket.start)], |row| row.get(0))
+                .optional()?,
+            None => None,
+        },
+    };
+    Ok(total.unwrap_or(0) as u64)
+}
+
+fn to_nanos(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_nanos()).unwrap_or(i64::MAX))
+}
+
+fn from_nanos(nanos: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_nanos(nanos.max(0) as u64)
+}
@@ -1,17 +1,36 @@
 // State tracking for policy evaluation
-
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
-use std::time::{Duration, SystemTime};
-
-/// Thread-safe policy state management
+//
+// MemoryStateStore is the default PolicyStateStore: per-key rate limit and
+// spending state behind locks, lost when the process exits. In-flight
+// concurrency slots are tracked separately by InFlightSlots, since a slot
+// belongs to a request of this process whatever the store.
+
+use super::store::{
+    Admission, PolicyStateStore, RateCounter, SpendCounter, SpendDump, StateBackend, StateDump,
+    StateStats,
+};
+use super::window::SpendingWindow;
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long requests and spends are kept for rolling windows
+#[doc(hidden)]
+pub const RETENTION: Duration = Duration::from_secs(3600);
+
+/// Thread-safe in-memory policy state
 #[derive(Debug, Clone)]
-pub struct PolicyState {
+pub struct MemoryStateStore {
     rate_limits: Arc<RwLock<HashMap<String, RateLimitState>>>,
     spending: Arc<RwLock<HashMap<String, SpendingState>>>,
 }
 
-impl PolicyState {
+/// Former name of [`MemoryStateStore`]
+#[deprecated(note = "renamed to `MemoryStateStore`")]
+pub type PolicyState = MemoryStateStore;
+
+impl MemoryStateStore {
     /// Create a new empty policy state
     pub fn new() -> Self {
         Self {
@@ -21,6 +40,7 @@ impl PolicyState {
     }
 
     /// Get or create rate limit state for a key
+    #[doc(hidden)]
     pub fn get_rate_limit_state(&self, key: &str) -> RateLimitState {
         let state = self
             .rate_limits
@@ -30,6 +50,7 @@ impl PolicyState {
     }
 
     /// Update rate limit state for a key
+    #[doc(hidden)]
     pub fn update_rate_limit_state(&self, key: String, state: RateLimitState) {
         let mut states = self
             .rate_limits
@@ -39,6 +60,7 @@ impl PolicyState {
     }
 
     /// Get or create spending state for a key
+    #[doc(hidden)]
     pub fn get_spending_state(&self, key: &str) -> SpendingState {
         let state = self
             .spending
@@ -48,6 +70,7 @@ impl PolicyState {
     }
 
     /// Update spending state for a key
+    #[doc(hidden)]
     pub fn update_spending_state(&self, key: String, state: SpendingState) {
         let mut states = self
             .spending
@@ -82,13 +105,289 @@ impl PolicyState {
     }
 }
 
-impl Default for PolicyState {
+impl Default for MemoryStateStore {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl PolicyStateStore for MemoryStateStore {
+    fn rate_count(&self, key: &str, window: Duration, now: SystemTime) -> Result<u32> {
+        Ok(self.get_rate_limit_state(key).count_in_window(window, now) as u32)
+    }
+
+    fn spent(&self, key: &str, window: &SpendingWindow, now: SystemTime) -> Result<u64> {
+        Ok(self.get_spending_state(key).total_for_window(window, now))
+    }
+
+    fn spending_records(&self, key: &str) -> Result<Vec<SpendingRecord>> {
+        Ok(self.get_spending_state(key).records().to_vec())
+    }
+
+    fn try_admit(
+        &self,
+        rate: Option<RateCounter<'_>>,
+        spend: Option<SpendCounter<'_>>,
+        now: SystemTime,
+    ) -> Result<Admission> {
+        // Both locks are held across check and update; always rate first
+        let mut rate_limits = self
+            .rate_limits
+            .write()
+            .expect("CRITICAL: Rate limit state lock poisoned - thread panic detected");
+        let mut spending = self
+            .spending
+            .write()
+            .expect("CRITICAL: Spending state lock poisoned - thread panic detected");
+
+        if let Some(rate) = &rate {
+            let count = rate_limits
+                .get(rate.key)
+                .map_or(0, |state| state.count_in_window(rate.window, now));
+            if !rate.allows(count as u32) {
+                return Ok(Admission::RateLimited);
+            }
+        }
+        if let Some(spend) = &spend {
+            let spent = spending
+                .get(spend.key)
+                .map_or(0, |state| state.total_for_window(spend.window, now));
+            if !spend.allows(spent) {
+                return Ok(Admission::SpendingCapped);
+            }
+        }
+
+        if let Some(rate) = rate {
+            rate_limits
+                .entry(rate.key.to_string())
+                .or_default()
+                .add_request(now);
+        }
+        if let Some(spend) = spend {
+            spending
+                .entry(spend.key.to_string())
+                .or_default()
+                .record_window_spending(
+                    spend.window,
+                    now,
+                    spend.amount,
+                    spend.memo_id.map(str::to_string),
+                );
+        }
+        Ok(Admission::Admitted)
+    }
+
+    fn cleanup(&self, now: SystemTime) -> Result<()> {
+        self.cleanup_expired(now);
+        Ok(())
+    }
+
+    fn stats(&self) -> Result<StateStats> {
+        let rate_limits = self
+            .rate_limits
+            .read()
+            .expect("CRITICAL: Rate limit state lock poisoned - thread panic detected");
+        let spending = self
+            .spending
+            .read()
+            .expect("CRITICAL: Spending state lock poisoned - thread panic detected");
+
+        Ok(StateStats {
+            backend: StateBackend::Memory,
+            rate_limit_keys: rate_limits.values().filter(|s| !s.is_empty()).count(),
+            requests: rate_limits.values().map(|s| s.request_times.len()).sum(),
+            spending_keys: spending.values().filter(|s| !s.is_empty()).count(),
+            spending_records: spending.values().map(|s| s.spending_records.len()).sum(),
+        })
+    }
+
+    fn dump(&self) -> Result<StateDump> {
+        let rate_limits = self
+            .rate_limits
+            .read()
+            .expect("CRITICAL: Rate limit state lock poisoned - thread panic detected");
+        let spending = self
+            .spending
+            .read()
+            .expect("CRITICAL: Spending state lock poisoned - thread panic detected");
+
+        let mut dump = StateDump::default();
+        for (key, state) in rate_limits.iter().filter(|(_, s)| !s.is_empty()) {
+            let times = state.request_times.iter().map(|t| to_millis(*t)).collect();
+            dump.requests.insert(key.clone(), times);
+        }
+        for (key, state) in spending.iter().filter(|(_, s)| !s.is_empty()) {
+            if !state.spending_records.is_empty() {
+                let spends = state
+                    .spending_records
+                    .iter()
+                    .map(|record| SpendDump {
+                        at_ms: to_millis(record.timestamp),
+                        amount: record.amount,
+                        memo_id: record.memo_id.clone(),
+                    })
+                    .collect();
+                dump.spends.insert(key.clone(), spends);
+            }
+            if !state.buckets.is_empty() {
+                let buckets = state
+                    .buckets
+                    .iter()
+                    .map(|(start, total)| (to_millis(*start), *total))
+                    .collect();
+                dump.buckets.insert(key.clone(), buckets);
+            }
+        }
+        Ok(dump)
+    }
+
+    fn restore(&self, dump: &StateDump) -> Result<()> {
+        let mut rate_limits = self
+            .rate_limits
+            .write()
+            .expect("CRITICAL: Rate limit state lock poisoned - thread panic detected");
+        let mut spending = self
+            .spending
+            .write()
+            .expect("CRITICAL: Spending state lock poisoned - thread panic detected");
+
+        for (key, times) in &dump.requests {
+            let state = rate_limits.entry(key.clone()).or_default();
+            state
+                .request_times
+                .extend(times.iter().map(|ms| from_millis(*ms)));
+            state.request_times.sort();
+        }
+        for (key, spends) in &dump.spends {
+            let state = spending.entry(key.clone()).or_default();
+            state
+                .spending_records
+                .extend(spends.iter().map(|spend| SpendingRecord {
+                    timestamp: from_millis(spend.at_ms),
+                    amount: spend.amount,
+                    memo_id: spend.memo_id.clone(),
+                }));
+            state
+                .spending_records
+                .sort_by_key(|record| record.timestamp);
+        }
+        for (key, buckets) in &dump.buckets {
+            let state = spending.entry(key.clone()).or_default();
+            for (start, total) in buckets {
+                *state.buckets.entry(from_millis(*start)).or_insert(0) += total;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+fn from_millis(millis: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis)
+}
+
+/// Concurrency slots held by requests of this process
+///
+/// Engine internals: not part of the stable API.
+#[doc(hidden)]
+#[derive(Debug, Clone, Default)]
+pub struct InFlightSlots {
+    in_flight: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl InFlightSlots {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take an in-flight slot for a key if fewer than `max_in_flight` are held
+    ///
+    /// The slot is released when the returned guard (and all its clones) drop.
+    pub fn try_acquire_slot(&self, key: &str, max_in_flight: u32) -> Option<ConcurrencyGuard> {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .expect("CRITICAL: Concurrency state lock poisoned - thread panic detected");
+        let count = in_flight.entry(key.to_string()).or_insert(0);
+        if *count >= max_in_flight {
+            return None;
+        }
+        *count += 1;
+
+        Some(ConcurrencyGuard {
+            slot: Arc::new(Slot {
+                slots: self.clone(),
+                key: key.to_string(),
+            }),
+        })
+    }
+
+    /// Number of slots currently held for a key
+    pub fn in_flight(&self, key: &str) -> u32 {
+        let in_flight = self
+            .in_flight
+            .lock()
+            .expect("CRITICAL: Concurrency state lock poisoned - thread panic detected");
+        in_flight.get(key).copied().unwrap_or(0)
+    }
+
+    fn release_slot(&self, key: &str) {
+        // Release runs in Drop, possibly while unwinding: never panic here
+        let mut in_flight = match self.in_flight.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(count) = in_flight.get_mut(key) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                in_flight.remove(key);
+            }
+        }
+    }
+}
+
+/// An in-flight slot held for the duration of an allowed request
+///
+/// Clones share the slot; it is released once the last clone is dropped,
+/// including when the request fails or panics.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyGuard {
+    slot: Arc<Slot>,
+}
+
+#[derive(Debug)]
+struct Slot {
+    slots: InFlightSlots,
+    key: String,
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.slots.release_slot(&self.key);
+    }
+}
+
+impl ConcurrencyGuard {
+    /// State key of the held slot (`conc:<policy_id>:<agent_id>`)
+    pub fn key(&self) -> &str {
+        &self.slot.key
+    }
+}
+
+impl PartialEq for ConcurrencyGuard {
+    fn eq(&self, other: &Self) -> bool {
+        self.slot.key == other.slot.key
+    }
+}
+
 /// Rate limiting state using sliding window algorithm
+///
+/// Engine internals: not part of the stable API.
+#[doc(hidden)]
 #[derive(Debug, Clone, Default)]
 pub struct RateLimitState {
     /// Timestamps of requests within the current window
@@ -134,7 +433,7 @@ impl RateLimitState {
     pub fn cleanup_expired(&mut self, now: SystemTime) {
         // Keep only recent requests (last hour for safety margin)
         // SECURITY: Also reject future timestamps to prevent time manipulation attacks
-        let cutoff = now.checked_sub(Duration::from_secs(3600)).unwrap_or(now);
+        let cutoff = now.checked_sub(RETENTION).unwrap_or(now);
         self.request_times
             .retain(|&time| time >= cutoff && time <= now);
     }
@@ -147,13 +446,33 @@ impl RateLimitState {
             .filter(|&&time| time >= window_start && time <= now)
             .count()
     }
+
+    fn is_empty(&self) -> bool {
+        self.request_times.is_empty()
+    }
+}
+
+/// One recorded spend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpendingRecord {
+    pub timestamp: SystemTime,
+    pub amount: u64,
+    /// Invoice memo UUID the spend was allowed for, if known
+    pub memo_id: Option<String>,
 }
 
 /// Spending tracking state with time window
+///
+/// Rolling windows sum the records; calendar windows keep a running total
+/// per bucket, keyed by the bucket start. Engine internals: not part of
+/// the stable API.
+#[doc(hidden)]
 #[derive(Debug, Clone, Default)]
 pub struct SpendingState {
     /// Amounts spent with timestamps
-    spending_records: Vec<(SystemTime, u64)>,
+    spending_records: Vec<SpendingRecord>,
+    /// Calendar window totals by bucket start
+    buckets: BTreeMap<SystemTime, u64>,
 }
 
 impl SpendingState {
@@ -161,7 +480,44 @@ impl SpendingState {
     pub fn new() -> Self {
         Self {
             spending_records: Vec::new(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Check if adding an amount would exceed the cap of `window`
+    pub fn check_window_cap(
+        &self,
+        window: &SpendingWindow,
+        max_amount: u64,
+        amount: u64,
+        now: SystemTime,
+    ) -> bool {
+        self.total_for_window(window, now) + amount <= max_amount
+    }
+
+    /// Total spent in the rolling window or calendar bucket containing `now`
+    pub fn total_for_window(&self, window: &SpendingWindow, now: SystemTime) -> u64 {
+        match window {
+            SpendingWindow::Rolling(duration) => self.total_in_window(*duration, now),
+            calendar => calendar
+                .bucket(now)
+                .and_then(|bucket| self.buckets.get(&bucket.start).copied())
+                .unwrap_or(0),
+        }
+    }
+
+    /// Record a spend counted against `window`
+    pub fn record_window_spending(
+        &mut self,
+        window: &SpendingWindow,
+        timestamp: SystemTime,
+        amount: u64,
+        memo_id: Option<String>,
+    ) {
+        if let Some(bucket) = window.bucket(timestamp) {
+            *self.buckets.entry(bucket.start).or_insert(0) += amount;
         }
+        self.record_spending(timestamp, amount, memo_id);
     }
 
     /// Check if adding an amount would exceed the spending cap
@@ -178,7 +534,21 @@ impl SpendingState {
 
     /// Record a new spending transaction
     pub fn add_spending(&mut self, timestamp: SystemTime, amount: u64) {
-        self.spending_records.push((timestamp, amount));
+        self.record_spending(timestamp, amount, None);
+    }
+
+    /// Record a spending transaction for the invoice with memo UUID `memo_id`
+    pub fn record_spending(&mut self, timestamp: SystemTime, amount: u64, memo_id: Option<String>) {
+        self.spending_records.push(SpendingRecord {
+            timestamp,
+            amount,
+            memo_id,
+        });
+    }
+
+    /// Recorded spends, oldest first
+    pub fn records(&self) -> &[SpendingRecord] {
+        &self.spending_records
     }
 
     /// Calculate total spending within the time window
@@ -187,8 +557,8 @@ impl SpendingState {
 
         self.spending_records
             .iter()
-            .filter(|(time, _)| *time >= window_start && *time <= now)
-            .map(|(_, amount)| amount)
+            .filter(|r| r.timestamp >= window_start && r.timestamp <= now)
+            .map(|r| r.amount)
             .sum()
     }
 
@@ -196,9 +566,24 @@ impl SpendingState {
     pub fn cleanup_expired(&mut self, now: SystemTime) {
         // Keep only recent records (last hour for safety margin)
         // SECURITY: Also reject future timestamps to prevent time manipulation attacks
-        let cutoff = now.checked_sub(Duration::from_secs(3600)).unwrap_or(now);
+        let cutoff = now.checked_sub(RETENTION).unwrap_or(now);
         self.spending_records
-            .retain(|(time, _)| *time >= cutoff && *time <= now);
+            .retain(|r| r.timestamp >= cutoff && r.timestamp <= now);
+
+        // Only the latest bucket that has started can still be current
+        if let Some(current) = self
+            .buckets
+            .range(..=now)
+            .next_back()
+            .map(|(start, _)| *start)
+        {
+            self.buckets = self.buckets.split_off(&current);
+        }
+        self.buckets.retain(|start, _| *start <= now);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.spending_records.is_empty() && self.buckets.is_empty()
     }
 }
 
@@ -288,9 +673,67 @@ mod tests {
         assert_eq!(state.total_in_window(window, later), 500); // 200 + 300
     }
 
+    #[test]
+    fn test_calendar_day_buckets() {
+        let at =
+            |s: &str| -> SystemTime { chrono::DateTime::parse_from_rfc3339(s).unwrap().into() };
+        let window = SpendingWindow::CalendarDay(chrono_tz::UTC);
+        let mut state = SpendingState::new();
+
+        // A spend at 23:59 UTC counts against that day only
+        let late = at("2025-03-14T23:59:00Z");
+        state.record_window_spending(&window, late, 400, None);
+        assert_eq!(state.total_for_window(&window, late), 400);
+        assert!(!state.check_window_cap(&window, 500, 200, late));
+
+        // Two minutes later the next day's bucket is empty
+        let early = at("2025-03-15T00:01:00Z");
+        assert_eq!(state.total_for_window(&window, early), 0);
+        assert!(state.check_window_cap(&window, 500, 200, early));
+
+        state.record_window_spending(&window, early, 200, None);
+        assert_eq!(state.total_for_window(&window, early), 200);
+
+        // Cleanup drops the previous day's bucket
+        state.cleanup_expired(early);
+        assert_eq!(state.buckets.len(), 1);
+        assert_eq!(state.total_for_window(&window, early), 200);
+
+        // A rolling window of the same length still sees both spends
+        let rolling = SpendingWindow::Rolling(Duration::from_secs(86400));
+        assert_eq!(state.total_for_window(&rolling, early), 600);
+    }
+
+    #[test]
+    fn test_calendar_day_buckets_in_dst_timezone() {
+        let at =
+            |s: &str| -> SystemTime { chrono::DateTime::parse_from_rfc3339(s).unwrap().into() };
+        // Europe/Berlin springs forward on 2025-03-30: that day starts at
+        // 23:00 UTC the day before and lasts 23 hours
+        let window = SpendingWindow::CalendarDay(chrono_tz::Europe::Berlin);
+        let mut state = SpendingState::new();
+
+        // 22:30 UTC on the 29th is 23:30 local on the 29th
+        state.record_window_spending(&window, at("2025-03-29T22:30:00Z"), 300, None);
+        // 23:30 UTC on the 29th is already 00:30 local on the 30th
+        let next_day = at("2025-03-29T23:30:00Z");
+        assert_eq!(state.total_for_window(&window, next_day), 0);
+        state.record_window_spending(&window, next_day, 100, None);
+
+        // The 30th ends at 22:00 UTC (midnight CEST), not 23:00
+        assert_eq!(
+            state.total_for_window(&window, at("2025-03-30T21:59:00Z")),
+            100
+        );
+        assert_eq!(
+            state.total_for_window(&window, at("2025-03-30T22:00:00Z")),
+            0
+        );
+    }
+
     #[test]
     fn test_policy_state_thread_safety() {
-        let state = PolicyState::new();
+        let state = MemoryStateStore::new();
 
         // Test concurrent access
         let state_clone = state.clone();
@@ -313,7 +756,7 @@ mod tests {
 
     #[test]
     fn test_cleanup_expired() {
-        let state = PolicyState::new();
+        let state = MemoryStateStore::new();
         let old_time = UNIX_EPOCH + Duration::from_secs(1000);
         let now = SystemTime::now();
 
@@ -334,9 +777,48 @@ mod tests {
 
         // Check that recent entries remain
         let rl_state = state.get_rate_limit_state("test");
-        assert!(rl_state.request_times.len() > 0);
+        assert!(!rl_state.request_times.is_empty());
 
         let sp_state = state.get_spending_state("test");
-        assert!(sp_state.spending_records.len() > 0);
+        assert!(!sp_state.spending_records.is_empty());
+    }
+
+    #[test]
+    fn test_dump_restore_round_trip() {
+        let state = MemoryStateStore::new();
+        let now = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        let window = Duration::from_secs(60);
+        let day = SpendingWindow::CalendarDay(chrono_tz::Tz::UTC);
+        let rate = RateCounter {
+            key: "rate:p:agent-1",
+            window,
+            max_requests: 2,
+        };
+        let spend = SpendCounter {
+            key: "spend:p:agent-1",
+            window: &day,
+            max_amount: 500,
+            amount: 150,
+            memo_id: Some("memo-1"),
+        };
+        state.try_admit(Some(rate), Some(spend), now).unwrap();
+        state.try_admit(Some(rate), None, now).unwrap();
+
+        let dump = state.dump().unwrap();
+        assert_eq!(dump.requests["rate:p:agent-1"].len(), 2);
+        assert_eq!(
+            dump.spends["spend:p:agent-1"][0].memo_id.as_deref(),
+            Some("memo-1")
+        );
+
+        // A fresh store restored from the dump is just as full
+        let restored = MemoryStateStore::new();
+        restored.restore(&dump).unwrap();
+        assert_eq!(restored.dump().unwrap(), dump);
+        assert_eq!(
+            restored.try_admit(Some(rate), None, now).unwrap(),
+            Admission::RateLimited
+        );
+        assert_eq!(restored.spent("spend:p:agent-1", &day, now).unwrap(), 150);
     }
 }
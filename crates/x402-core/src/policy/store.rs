@@ -0,0 +1,246 @@
+// Policy state storage
+//
+// Rate limit and spending counters live behind PolicyStateStore, so the
+// engine can keep them in memory (the default) or in a SQLite file that
+// survives restarts and can be shared by several processes. Every backend
+// checks and increments in one atomic step: two requests racing for the
+// last unit of quota never both get it.
+
+use super::sqlite_store::SqliteStateStore;
+use super::state::{MemoryStateStore, SpendingRecord};
+use super::window::SpendingWindow;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// SQLite database used when no `state_path` is configured
+pub const DEFAULT_SQLITE_STATE_PATH: &str = ".x402dev/policy-state.db";
+
+/// Rate limit a request counts against
+#[derive(Debug, Clone, Copy)]
+pub struct RateCounter<'a> {
+    pub key: &'a str,
+    pub window: Duration,
+    pub max_requests: u32,
+}
+
+impl RateCounter<'_> {
+    /// True if one more request fits after `count` in the window
+    pub fn allows(&self, count: u32) -> bool {
+        count < self.max_requests
+    }
+}
+
+/// Spending cap a request is charged to (amounts in cents)
+#[derive(Debug, Clone, Copy)]
+pub struct SpendCounter<'a> {
+    pub key: &'a str,
+    pub window: &'a SpendingWindow,
+    pub max_amount: u64,
+    pub amount: u64,
+    /// Invoice memo UUID recorded with the spend
+    pub memo_id: Option<&'a str>,
+}
+
+impl SpendCounter<'_> {
+    /// True if the amount fits on top of `spent`
+    pub fn allows(&self, spent: u64) -> bool {
+        spent.saturating_add(self.amount) <= self.max_amount
+    }
+}
+
+/// Outcome of [`PolicyStateStore::try_admit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Admission {
+    /// Counted against both limits
+    Admitted,
+    /// The rate limit is full; nothing was recorded
+    RateLimited,
+    /// The spending cap would be exceeded; nothing was recorded
+    SpendingCapped,
+}
+
+/// Entry counts of a store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StateStats {
+    pub backend: StateBackend,
+    /// Keys with requests recorded
+    pub rate_limit_keys: usize,
+    pub requests: usize,
+    /// Keys with spends recorded
+    pub spending_keys: usize,
+    pub spending_records: usize,
+}
+
+/// Server process using a shared store (`mock --shared-state`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreInstance {
+    /// `<pid>-<port>`; tags the requests and spends this instance records
+    pub id: String,
+    pub pid: u32,
+    pub port: Option<u16>,
+    /// Process start time in seconds since the Unix epoch
+    pub started: Option<u64>,
+}
+
+/// Everything a store has recorded, for `mock snapshot`
+///
+/// Times are milliseconds since the Unix epoch on the engine's clock, so a
+/// virtual clock's windows survive as well.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateDump {
+    /// Request times per rate limit key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub requests: BTreeMap<String, Vec<u64>>,
+    /// Spends per spending cap key, oldest first
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub spends: BTreeMap<String, Vec<SpendDump>>,
+    /// Calendar window totals per spending cap key, by bucket start
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub buckets: BTreeMap<String, BTreeMap<u64, u64>>,
+}
+
+/// One spend of a [`StateDump`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendDump {
+    pub at_ms: u64,
+    pub amount: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo_id: Option<String>,
+}
+
+impl StateDump {
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty() && self.spends.is_empty() && self.buckets.is_empty()
+    }
+}
+
+/// Storage for policy rate limit and spending state
+///
+/// Keys are `rate:<policy_id>:<agent_id>` and `spend:<policy_id>:<agent_id>`.
+/// In-flight concurrency slots are not stored: they belong to the requests
+/// of one process.
+pub trait PolicyStateStore: Send + Sync + fmt::Debug {
+    /// Requests recorded for `key` in the `window` ending at `now`
+    fn rate_count(&self, key: &str, window: Duration, now: SystemTime) -> Result<u32>;
+
+    /// Amount spent for `key` in the rolling window or calendar bucket containing `now`
+    fn spent(&self, key: &str, window: &SpendingWindow, now: SystemTime) -> Result<u64>;
+
+    /// Spends recorded for `key`, oldest first
+    fn spending_records(&self, key: &str) -> Result<Vec<SpendingRecord>>;
+
+    /// Record a request against its rate limit and spending cap if both allow it
+    ///
+    /// Checking and recording are one atomic step, including across
+    /// processes sharing a store. Nothing is recorded unless admitted.
+    fn try_admit(
+        &self,
+        rate: Option<RateCounter<'_>>,
+        spend: Option<SpendCounter<'_>>,
+        now: SystemTime,
+    ) -> Result<Admission>;
+
+    /// Drop requests and spends no window can count any more
+    fn cleanup(&self, now: SystemTime) -> Result<()>;
+
+    fn stats(&self) -> Result<StateStats>;
+
+    /// Announce `instance` as a user of this store and tag what it records
+    ///
+    /// Only stores shared between processes keep the registration.
+    fn register_instance(&self, _instance: &StoreInstance) -> Result<()> {
+        Ok(())
+    }
+
+    /// Instances registered with this store, oldest first
+    fn instances(&self) -> Result<Vec<StoreInstance>> {
+        Ok(Vec::new())
+    }
+
+    /// Every request and spend recorded
+    ///
+    /// Stores kept outside the process (a shared SQLite file) persist on
+    /// their own and do not support this.
+    fn dump(&self) -> Result<StateDump> {
+        anyhow::bail!(
+            "the {} policy state store cannot be exported\nFix: Copy its state file instead",
+            self.stats()?.backend
+        )
+    }
+
+    /// Add the requests and spends of `dump` to this store
+    fn restore(&self, _dump: &StateDump) -> Result<()> {
+        anyhow::bail!(
+            "the {} policy state store cannot be restored from a snapshot\nFix: Import into a server using the memory backend",
+            self.stats()?.backend
+        )
+    }
+}
+
+/// Where policy state is kept (`state_backend` in .x402dev.yaml)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum StateBackend {
+    /// Per process, lost on exit
+    #[default]
+    Memory,
+    /// SQLite database file
+    Sqlite,
+}
+
+impl StateBackend {
+    pub fn is_memory(&self) -> bool {
+        *self == StateBackend::Memory
+    }
+}
+
+impl fmt::Display for StateBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StateBackend::Memory => "memory",
+            StateBackend::Sqlite => "sqlite",
+        })
+    }
+}
+
+/// Policy state backend and its location
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateStoreConfig {
+    #[serde(default)]
+    pub backend: StateBackend,
+    /// SQLite database file (default [`DEFAULT_SQLITE_STATE_PATH`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+impl StateStoreConfig {
+    /// Database file of the SQLite backend
+    pub fn sqlite_path(&self) -> PathBuf {
+        self.path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_SQLITE_STATE_PATH))
+    }
+
+    /// Open the configured store, creating the SQLite file if needed
+    pub fn open(&self) -> Result<Arc<dyn PolicyStateStore>> {
+        Ok(match self.backend {
+            StateBackend::Memory => Arc::new(MemoryStateStore::new()),
+            StateBackend::Sqlite => Arc::new(SqliteStateStore::open(self.sqlite_path())?),
+        })
+    }
+}
+
+impl fmt::Display for StateStoreConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.backend {
+            StateBackend::Memory => write!(f, "memory"),
+            StateBackend::Sqlite => write!(f, "sqlite ({})", self.sqlite_path().display()),
+        }
+    }
+}
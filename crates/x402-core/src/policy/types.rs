@@ -1,15 +1,25 @@
 // Policy type definitions for x402-dev
 
+use super::window::{SpendingWindow, WindowType};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
+use x402_domain::DurationField;
+
+/// Shown for a missing, zero or sub-second window
+const WINDOW_TOO_SHORT: &str =
+    "window_seconds must be greater than 0 seconds (a number of seconds such as 3600, or a duration such as \"1h\")";
 
 /// Complete policy configuration from YAML file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PolicyConfig {
     pub policies: Vec<PolicyRule>,
+    /// Admit spending cap currencies missing from the currency registry
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub allow_custom_currencies: bool,
 }
 
 /// Individual policy rule
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum PolicyRule {
     Allowlist {
@@ -22,22 +32,74 @@ pub enum PolicyRule {
     },
     RateLimit {
         max_requests: u32,
-        window_seconds: u32,
+        /// Seconds (`3600`) or a duration with a unit (`"1h"`)
+        window_seconds: DurationField,
     },
     SpendingCap {
         max_amount: f64,
         currency: String,
-        window_seconds: u32,
+        /// Length of a rolling window; unset for calendar windows
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        window_seconds: Option<DurationField>,
+        #[serde(default, skip_serializing_if = "WindowType::is_rolling")]
+        window_type: WindowType,
+        /// Timezone calendar windows reset in (default UTC)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timezone: Option<Tz>,
+    },
+    ConcurrencyLimit {
+        max_in_flight: u32,
     },
 }
 
 /// Policy action result
+///
+/// In YAML this is either `allow`, `deny`, or `{ deny: "<reason>" }`.
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum PolicyAction {
     Allow,
     Deny(String), // Deny with reason
 }
 
+impl Serialize for PolicyAction {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        match self {
+            PolicyAction::Allow => serializer.serialize_str("allow"),
+            PolicyAction::Deny(reason) if reason.is_empty() => serializer.serialize_str("deny"),
+            PolicyAction::Deny(reason) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("deny", reason)?;
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PolicyAction {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Name(String),
+            Deny { deny: String },
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Name(name) => match name.as_str() {
+                "allow" => Ok(PolicyAction::Allow),
+                "deny" => Ok(PolicyAction::Deny(String::new())),
+                other => Err(serde::de::Error::custom(format!(
+                    "unknown action '{}', expected 'allow', 'deny' or {{ deny: \"<reason>\" }}",
+                    other
+                ))),
+            },
+            Raw::Deny { deny } => Ok(PolicyAction::Deny(deny)),
+        }
+    }
+}
+
 /// Policy type enumeration for categorization
 #[derive(Debug, Clone, PartialEq)]
 pub enum PolicyType {
@@ -45,6 +107,7 @@ pub enum PolicyType {
     Denylist,
     RateLimit,
     SpendingCap,
+    ConcurrencyLimit,
 }
 
 impl PolicyRule {
@@ -55,6 +118,26 @@ impl PolicyRule {
             PolicyRule::Denylist { .. } => PolicyType::Denylist,
             PolicyRule::RateLimit { .. } => PolicyType::RateLimit,
             PolicyRule::SpendingCap { .. } => PolicyType::SpendingCap,
+            PolicyRule::ConcurrencyLimit { .. } => PolicyType::ConcurrencyLimit,
+        }
+    }
+
+    /// Window of a spending cap rule (None for other rules or a rolling cap
+    /// without `window_seconds`)
+    pub fn spending_window(&self) -> Option<SpendingWindow> {
+        match self {
+            PolicyRule::SpendingCap {
+                window_seconds,
+                window_type,
+                timezone,
+                ..
+            } => match window_type {
+                WindowType::Rolling => {
+                    window_seconds.map(|window| SpendingWindow::Rolling(window.as_duration()))
+                }
+                calendar => SpendingWindow::calendar(*calendar, *timezone),
+            },
+            _ => None,
         }
     }
 
@@ -77,8 +160,8 @@ impl PolicyRule {
                 if *max_requests == 0 {
                     return Err("max_requests must be greater than 0".to_string());
                 }
-                if *window_seconds == 0 {
-                    return Err("window_seconds must be greater than 0".to_string());
+                if window_seconds.as_secs() == 0 {
+                    return Err(WINDOW_TOO_SHORT.to_string());
                 }
                 Ok(())
             }
@@ -86,6 +169,8 @@ impl PolicyRule {
                 max_amount,
                 currency,
                 window_seconds,
+                window_type,
+                timezone,
             } => {
                 if *max_amount <= 0.0 {
                     return Err("max_amount must be positive".to_string());
@@ -93,8 +178,27 @@ impl PolicyRule {
                 if currency.is_empty() {
                     return Err("currency cannot be empty".to_string());
                 }
-                if *window_seconds == 0 {
-                    return Err("window_seconds must be greater than 0".to_string());
+                if window_type.is_rolling() {
+                    if window_seconds.map_or(0, |window| window.as_secs()) == 0 {
+                        return Err(WINDOW_TOO_SHORT.to_string());
+                    }
+                    if timezone.is_some() {
+                        return Err(
+                            "timezone only applies to calendar_day and calendar_month windows"
+                                .to_string(),
+                        );
+                    }
+                } else if window_seconds.is_some() {
+                    return Err(format!(
+                        "window_seconds cannot be combined with window_type: {} (calendar windows reset at local midnight)",
+                        window_type
+                    ));
+                }
+                Ok(())
+            }
+            PolicyRule::ConcurrencyLimit { max_in_flight } => {
+                if *max_in_flight == 0 {
+                    return Err("max_in_flight must be greater than 0".to_string());
                 }
                 Ok(())
             }
@@ -119,7 +223,7 @@ mod tests {
     fn test_rate_limit_validation() {
         let policy = PolicyRule::RateLimit {
             max_requests: 100,
-            window_seconds: 3600,
+            window_seconds: DurationField::from_secs(3600),
         };
         assert!(policy.validate().is_ok());
     }
@@ -129,16 +233,84 @@ mod tests {
         let policy = PolicyRule::SpendingCap {
             max_amount: 10.0,
             currency: "USDC".to_string(),
-            window_seconds: 86400,
+            window_seconds: Some(DurationField::from_secs(86400)),
+            window_type: WindowType::Rolling,
+            timezone: None,
         };
         assert!(policy.validate().is_ok());
     }
 
+    #[test]
+    fn test_spending_cap_window_type_validation() {
+        let cap = |window_seconds: Option<u64>, window_type, timezone| PolicyRule::SpendingCap {
+            max_amount: 10.0,
+            currency: "USDC".to_string(),
+            window_seconds: window_seconds.map(DurationField::from_secs),
+            window_type,
+            timezone,
+        };
+
+        let daily = cap(
+            None,
+            WindowType::CalendarDay,
+            Some(chrono_tz::Europe::Berlin),
+        );
+        assert!(daily.validate().is_ok());
+        assert_eq!(
+            daily.spending_window(),
+            Some(SpendingWindow::CalendarDay(chrono_tz::Europe::Berlin))
+        );
+
+        let err = cap(Some(86400), WindowType::CalendarMonth, None)
+            .validate()
+            .unwrap_err();
+        assert!(err.contains("window_seconds cannot be combined with window_type: calendar_month"));
+
+        let err = cap(None, WindowType::Rolling, None).validate().unwrap_err();
+        assert!(err.contains("window_seconds must be greater than 0"));
+
+        let err = cap(Some(3600), WindowType::Rolling, Some(chrono_tz::UTC))
+            .validate()
+            .unwrap_err();
+        assert!(err.contains("timezone only applies"));
+    }
+
+    #[test]
+    fn test_spending_cap_yaml() {
+        let config: PolicyConfig = serde_yaml::from_str(
+            r#"
+policies:
+  - type: spending_cap
+    max_amount: 50.0
+    currency: USDC
+    window_type: calendar_day
+    timezone: America/New_York
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.policies[0].spending_window(),
+            Some(SpendingWindow::CalendarDay(chrono_tz::America::New_York))
+        );
+
+        let unknown = serde_yaml::from_str::<PolicyConfig>(
+            r#"
+policies:
+  - type: spending_cap
+    max_amount: 50.0
+    currency: USDC
+    window_type: calendar_day
+    timezone: Mars/Olympus_Mons
+"#,
+        );
+        assert!(unknown.is_err());
+    }
+
     #[test]
     fn test_invalid_rate_limit() {
         let policy = PolicyRule::RateLimit {
             max_requests: 0,
-            window_seconds: 3600,
+            window_seconds: DurationField::from_secs(3600),
         };
         assert!(policy.validate().is_err());
     }
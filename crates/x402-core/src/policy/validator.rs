@@ -3,11 +3,16 @@
 // Detects and reports conflicting policy rules before code generation
 // Provides clear error messages with resolution suggestions
 
+use super::codes::{RuleCode, Suppressions};
 use super::types::{PolicyConfig, PolicyRule};
+use super::window::SpendingWindow;
+use crate::i18n::Message;
 use std::collections::{HashMap, HashSet};
+use x402_domain::{canonical_currency, known_currencies, suggest_currency};
 
 /// Type of validation issue
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum IssueType {
     /// Critical error that prevents code generation
     Error,
@@ -22,12 +27,37 @@ pub enum IssueType {
 pub struct ResolutionSuggestion {
     pub description: String,
     pub action: String,
+    /// Catalog messages behind `description` and `action`, for localized display
+    pub catalog: Option<(Message, Message)>,
+}
+
+impl ResolutionSuggestion {
+    /// Suggestion whose English text comes from the message catalog
+    fn from_catalog(description: Message, action: Message) -> Self {
+        Self {
+            description: description.to_string(),
+            action: action.to_string(),
+            catalog: Some((description, action)),
+        }
+    }
+
+    /// Description and action in the current display locale
+    pub fn localized(&self) -> (String, String) {
+        match &self.catalog {
+            Some((description, action)) => (description.localized(), action.localized()),
+            None => (self.description.clone(), self.action.clone()),
+        }
+    }
 }
 
 /// A single validation issue
 #[derive(Debug, Clone)]
 pub struct ValidationIssue {
+    /// Stable rule code, e.g. `X402-P010`
+    pub code: RuleCode,
     pub issue_type: IssueType,
+    /// Acknowledged by a `suppress:` list or `--allow`; never fails validation
+    pub suppressed: bool,
     pub message: String,
     pub details: Option<String>,
     pub suggestions: Vec<ResolutionSuggestion>,
@@ -36,13 +66,16 @@ pub struct ValidationIssue {
 
 impl ValidationIssue {
     fn error(
+        code: RuleCode,
         message: String,
         details: Option<String>,
         suggestions: Vec<ResolutionSuggestion>,
         policy_indices: Vec<usize>,
     ) -> Self {
         Self {
+            code,
             issue_type: IssueType::Error,
+            suppressed: false,
             message,
             details,
             suggestions,
@@ -51,13 +84,16 @@ impl ValidationIssue {
     }
 
     fn warning(
+        code: RuleCode,
         message: String,
         details: Option<String>,
         suggestions: Vec<ResolutionSuggestion>,
         policy_indices: Vec<usize>,
     ) -> Self {
         Self {
+            code,
             issue_type: IssueType::Warning,
+            suppressed: false,
             message,
             details,
             suggestions,
@@ -65,9 +101,31 @@ impl ValidationIssue {
         }
     }
 
-    fn info(message: String, details: Option<String>) -> Self {
+    /// Issue of a lint rule, at the severity the project configured
+    pub(super) fn lint(
+        code: RuleCode,
+        issue_type: IssueType,
+        message: String,
+        details: Option<String>,
+        suggestions: Vec<ResolutionSuggestion>,
+        policy_indices: Vec<usize>,
+    ) -> Self {
         Self {
+            code,
+            issue_type,
+            suppressed: false,
+            message,
+            details,
+            suggestions,
+            policy_indices,
+        }
+    }
+
+    fn info(code: RuleCode, message: String, details: Option<String>) -> Self {
+        Self {
+            code,
             issue_type: IssueType::Info,
+            suppressed: false,
             message,
             details,
             suggestions: Vec::new(),
@@ -76,24 +134,39 @@ impl ValidationIssue {
     }
 }
 
+/// A `policies` entry left out by lenient parsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedRule {
+    /// Position in the file's `policies` list
+    pub index: usize,
+    /// 1-based line of the entry, when the file is a YAML block list
+    pub line: Option<usize>,
+    /// Why the entry did not parse
+    pub error: String,
+}
+
 /// Complete validation report
 #[derive(Debug, Clone)]
 pub struct ValidationReport {
     pub issues: Vec<ValidationIssue>,
     pub has_errors: bool,
     pub has_warnings: bool,
+    /// Rules skipped due to parse errors (lenient mode only); the issues
+    /// cover the rules that did parse
+    pub skipped: Vec<SkippedRule>,
 }
 
 impl ValidationReport {
-    fn new() -> Self {
+    pub(super) fn new() -> Self {
         Self {
             issues: Vec::new(),
             has_errors: false,
             has_warnings: false,
+            skipped: Vec::new(),
         }
     }
 
-    fn add_issue(&mut self, issue: ValidationIssue) {
+    pub(super) fn add_issue(&mut self, issue: ValidationIssue) {
         match issue.issue_type {
             IssueType::Error => self.has_errors = true,
             IssueType::Warning => self.has_warnings = true,
@@ -102,18 +175,65 @@ impl ValidationReport {
         self.issues.push(issue);
     }
 
+    /// Append the issues of another report, e.g. lint issues after
+    /// validation ones
+    pub fn extend(&mut self, other: ValidationReport) {
+        for issue in other.issues {
+            self.add_issue(issue);
+        }
+    }
+
+    /// Mark issues covered by `suppressions`; they no longer count as
+    /// errors or warnings
+    pub fn apply_suppressions(&mut self, suppressions: &Suppressions) {
+        self.has_errors = false;
+        self.has_warnings = false;
+        for issue in &mut self.issues {
+            issue.suppressed = issue.issue_type != IssueType::Error
+                && suppressions.covers(issue.code, &issue.policy_indices);
+            if !issue.suppressed {
+                match issue.issue_type {
+                    IssueType::Error => self.has_errors = true,
+                    IssueType::Warning => self.has_warnings = true,
+                    IssueType::Info => {}
+                }
+            }
+        }
+    }
+
+    /// Renumber a report over a subset of a file's rules, where rule `i`
+    /// is policy `indices[i]` of the file, so that `policy_indices` and the
+    /// `#N` references in messages name positions in the file
+    pub fn renumber_policies(&mut self, indices: &[usize]) {
+        for issue in &mut self.issues {
+            for index in &mut issue.policy_indices {
+                *index = indices.get(*index).copied().unwrap_or(*index);
+            }
+            issue.message = renumber_references(&issue.message, indices);
+            issue.details = issue
+                .details
+                .as_deref()
+                .map(|details| renumber_references(details, indices));
+        }
+    }
+
     /// Check if validation passed (no errors)
     pub fn is_valid(&self) -> bool {
         !self.has_errors
     }
 
-    /// Get count of each issue type
+    /// Number of suppressed issues
+    pub fn suppressed(&self) -> usize {
+        self.issues.iter().filter(|i| i.suppressed).count()
+    }
+
+    /// Get count of each issue type, not counting suppressed issues
     pub fn counts(&self) -> (usize, usize, usize) {
         let mut errors = 0;
         let mut warnings = 0;
         let mut info = 0;
 
-        for issue in &self.issues {
+        for issue in self.issues.iter().filter(|i| !i.suppressed) {
             match issue.issue_type {
                 IssueType::Error => errors += 1,
                 IssueType::Warning => warnings += 1,
@@ -125,6 +245,30 @@ impl ValidationReport {
     }
 }
 
+/// `text` with every `#N` replaced by `#indices[N]`
+fn renumber_references(text: &str, indices: &[usize]) -> String {
+    let mut renumbered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(hash) = rest.find('#') {
+        renumbered.push_str(&rest[..=hash]);
+        rest = &rest[hash + 1..];
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        match rest[..digits]
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| indices.get(i))
+        {
+            Some(index) => renumbered.push_str(&index.to_string()),
+            None => renumbered.push_str(&rest[..digits]),
+        }
+        rest = &rest[digits..];
+    }
+    renumbered.push_str(rest);
+    renumbered
+}
+
 /// Validate policy rules and detect conflicts (FR-5.6)
 ///
 /// Detects:
@@ -137,6 +281,7 @@ pub fn validate_policies(policy_config: &PolicyConfig) -> ValidationReport {
 
     if policies.is_empty() {
         report.add_issue(ValidationIssue::info(
+            RuleCode::NoPolicies,
             "No policies defined".to_string(),
             Some("Policy file contains no policy rules".to_string()),
         ));
@@ -147,18 +292,21 @@ pub fn validate_policies(policy_config: &PolicyConfig) -> ValidationReport {
     for (idx, policy) in policies.iter().enumerate() {
         if let Err(e) = policy.validate() {
             report.add_issue(ValidationIssue::error(
+                RuleCode::InvalidPolicy,
                 format!("Invalid policy configuration at index #{}", idx),
                 Some(e),
-                vec![ResolutionSuggestion {
-                    description: "Fix policy configuration".to_string(),
-                    action: "Ensure all required fields are properly set with valid values"
-                        .to_string(),
-                }],
+                vec![ResolutionSuggestion::from_catalog(
+                    Message::new("policy.fix_config"),
+                    Message::new("policy.fix_config.action"),
+                )],
                 vec![idx],
             ));
         }
     }
 
+    // Check spending cap currencies against the currency registry
+    detect_unknown_currencies(policy_config, &mut report);
+
     // Check for allowlist/denylist conflicts
     detect_allowlist_denylist_conflicts(policies, &mut report);
 
@@ -170,6 +318,7 @@ pub fn validate_policies(policy_config: &PolicyConfig) -> ValidationReport {
 
     if !report.has_errors && !report.has_warnings {
         report.add_issue(ValidationIssue::info(
+            RuleCode::AllValid,
             "All policies valid".to_string(),
             Some(format!(
                 "Validated {} policy rules with no conflicts",
@@ -181,6 +330,52 @@ pub fn validate_policies(policy_config: &PolicyConfig) -> ValidationReport {
     report
 }
 
+/// Detect spending caps in currencies missing from the currency registry
+///
+/// Known currencies match case-insensitively; unknown ones are errors with
+/// the closest known symbol suggested, unless `allow_custom_currencies` is
+/// set. Empty currencies are reported as invalid rules instead.
+fn detect_unknown_currencies(policy_config: &PolicyConfig, report: &mut ValidationReport) {
+    if policy_config.allow_custom_currencies {
+        return;
+    }
+    for (idx, policy) in policy_config.policies.iter().enumerate() {
+        let PolicyRule::SpendingCap { currency, .. } = policy else {
+            continue;
+        };
+        if currency.trim().is_empty() {
+            continue;
+        }
+        let Err(e) = canonical_currency(currency, false) else {
+            continue;
+        };
+        let use_known = match suggest_currency(currency) {
+            Some(known) => Message::new("policy.currency.use_known.action")
+                .arg("currency", known)
+                .arg("index", idx),
+            None => Message::new("policy.currency.use_listed.action")
+                .arg("known", known_currencies().collect::<Vec<_>>().join(", "))
+                .arg("index", idx),
+        };
+        report.add_issue(ValidationIssue::error(
+            RuleCode::UnknownCurrency,
+            format!("Unknown currency in spending cap #{}", idx),
+            Some(e.to_string()),
+            vec![
+                ResolutionSuggestion::from_catalog(
+                    Message::new("policy.currency.use_known"),
+                    use_known,
+                ),
+                ResolutionSuggestion::from_catalog(
+                    Message::new("policy.currency.allow_custom"),
+                    Message::new("policy.currency.allow_custom.action").arg("currency", currency),
+                ),
+            ],
+            vec![idx],
+        ));
+    }
+}
+
 /// Detect allowlist and denylist conflicts (FR-5.6)
 ///
 /// Checks if the same value appears in both allowlist and denylist for the same field
@@ -225,6 +420,7 @@ fn detect_allowlist_denylist_conflicts(policies: &[PolicyRule], report: &mut Val
                             conflicts.iter().map(|s| s.to_string()).collect();
 
                         report.add_issue(ValidationIssue::error(
+                            RuleCode::AllowDenyConflict,
                             format!("CONFLICT: {} in both allowlist and denylist", field),
                             Some(format!(
                                 "Conflicting values: {}\nPolicy indices: #{}, #{}",
@@ -233,28 +429,22 @@ fn detect_allowlist_denylist_conflicts(policies: &[PolicyRule], report: &mut Val
                                 deny_idx
                             )),
                             vec![
-                                ResolutionSuggestion {
-                                    description: "Remove from denylist".to_string(),
-                                    action: format!(
-                                        "Remove {} from denylist policy (index #{})",
-                                        conflict_list.join(", "),
-                                        deny_idx
-                                    ),
-                                },
-                                ResolutionSuggestion {
-                                    description: "Remove from allowlist".to_string(),
-                                    action: format!(
-                                        "Remove {} from allowlist policy (index #{})",
-                                        conflict_list.join(", "),
-                                        allow_idx
-                                    ),
-                                },
-                                ResolutionSuggestion {
-                                    description: "Policy precedence (fail-fast)".to_string(),
-                                    action:
-                                        "Deny rules are evaluated first. If a value is in both, it will be denied."
-                                            .to_string(),
-                                },
+                                ResolutionSuggestion::from_catalog(
+                                    Message::new("policy.remove_from_denylist"),
+                                    Message::new("policy.remove_from_denylist.action")
+                                        .arg("values", conflict_list.join(", "))
+                                        .arg("index", deny_idx),
+                                ),
+                                ResolutionSuggestion::from_catalog(
+                                    Message::new("policy.remove_from_allowlist"),
+                                    Message::new("policy.remove_from_allowlist.action")
+                                        .arg("values", conflict_list.join(", "))
+                                        .arg("index", allow_idx),
+                                ),
+                                ResolutionSuggestion::from_catalog(
+                                    Message::new("policy.deny_precedence"),
+                                    Message::new("policy.deny_precedence.action"),
+                                ),
                             ],
                             vec![*allow_idx, *deny_idx],
                         ));
@@ -282,7 +472,9 @@ fn detect_rate_limit_conflicts(policies: &[PolicyRule], report: &mut ValidationR
                     window_seconds,
                 } => format!(
                     "Policy #{}: {} requests / {} seconds",
-                    idx, max_requests, window_seconds
+                    idx,
+                    max_requests,
+                    window_seconds.as_secs()
                 ),
                 _ => unreachable!(),
             })
@@ -295,12 +487,13 @@ fn detect_rate_limit_conflicts(policies: &[PolicyRule], report: &mut ValidationR
                 PolicyRule::RateLimit {
                     max_requests,
                     window_seconds,
-                } => (*max_requests as f64 / *window_seconds as f64 * 1000.0) as u64,
+                } => (*max_requests as f64 / window_seconds.as_secs() as f64 * 1000.0) as u64,
                 _ => unreachable!(),
             })
             .map(|(idx, _)| idx);
 
         report.add_issue(ValidationIssue::warning(
+            RuleCode::MultipleRateLimits,
             "Multiple rate limits defined".to_string(),
             Some(format!(
                 "Found {} rate limit policies:\n{}",
@@ -308,19 +501,18 @@ fn detect_rate_limit_conflicts(policies: &[PolicyRule], report: &mut ValidationR
                 details.join("\n")
             )),
             vec![
-                ResolutionSuggestion {
-                    description: "Use most restrictive limit".to_string(),
-                    action: if let Some(idx) = most_restrictive {
-                        format!("Keep policy #{} (most restrictive) and remove others", idx)
+                ResolutionSuggestion::from_catalog(
+                    Message::new("policy.rate_limit.most_restrictive"),
+                    if let Some(idx) = most_restrictive {
+                        Message::new("policy.keep_most_restrictive.action").arg("index", idx)
                     } else {
-                        "Remove duplicate rate limits".to_string()
+                        Message::new("policy.rate_limit.remove_duplicates.action")
                     },
-                },
-                ResolutionSuggestion {
-                    description: "Keep all if intentional".to_string(),
-                    action: "Multiple rate limits will all be enforced (most restrictive applies)"
-                        .to_string(),
-                },
+                ),
+                ResolutionSuggestion::from_catalog(
+                    Message::new("policy.keep_all"),
+                    Message::new("policy.rate_limit.keep_all.action"),
+                ),
             ],
             rate_limits.iter().map(|(idx, _)| *idx).collect(),
         ));
@@ -338,16 +530,30 @@ fn detect_spending_cap_conflicts(policies: &[PolicyRule], report: &mut Validatio
     if spending_caps.len() > 1 {
         let details: Vec<String> = spending_caps
             .iter()
-            .map(|(idx, p)| match p {
-                PolicyRule::SpendingCap {
+            .map(|(idx, p)| match (p, p.spending_window()) {
+                (
+                    PolicyRule::SpendingCap {
+                        max_amount,
+                        currency,
+                        ..
+                    },
+                    Some(SpendingWindow::Rolling(window)),
+                ) => format!(
+                    "Policy #{}: {} {} / {} seconds",
+                    idx,
                     max_amount,
                     currency,
-                    window_seconds,
-                } => format!(
-                    "Policy #{}: {} {} / {} seconds",
-                    idx, max_amount, currency, window_seconds
+                    window.as_secs()
                 ),
-                _ => unreachable!(),
+                (
+                    PolicyRule::SpendingCap {
+                        max_amount,
+                        currency,
+                        ..
+                    },
+                    Some(window),
+                ) => format!("Policy #{}: {} {} / {}", idx, max_amount, currency, window),
+                _ => format!("Policy #{}: invalid window", idx),
             })
             .collect();
 
@@ -355,29 +561,14 @@ fn detect_spending_cap_conflicts(policies: &[PolicyRule], report: &mut Validatio
         let most_restrictive = spending_caps
             .iter()
             .min_by(|(_, a), (_, b)| {
-                let a_rate = match a {
-                    PolicyRule::SpendingCap {
-                        max_amount,
-                        window_seconds,
-                        ..
-                    } => max_amount / *window_seconds as f64,
-                    _ => unreachable!(),
-                };
-                let b_rate = match b {
-                    PolicyRule::SpendingCap {
-                        max_amount,
-                        window_seconds,
-                        ..
-                    } => max_amount / *window_seconds as f64,
-                    _ => unreachable!(),
-                };
-                a_rate
-                    .partial_cmp(&b_rate)
+                spending_rate(a)
+                    .partial_cmp(&spending_rate(b))
                     .unwrap_or(std::cmp::Ordering::Equal)
             })
             .map(|(idx, _)| idx);
 
         report.add_issue(ValidationIssue::warning(
+            RuleCode::MultipleSpendingCaps,
             "Multiple spending caps defined".to_string(),
             Some(format!(
                 "Found {} spending cap policies:\n{}",
@@ -385,33 +576,55 @@ fn detect_spending_cap_conflicts(policies: &[PolicyRule], report: &mut Validatio
                 details.join("\n")
             )),
             vec![
-                ResolutionSuggestion {
-                    description: "Use most restrictive cap".to_string(),
-                    action: if let Some(idx) = most_restrictive {
-                        format!("Keep policy #{} (most restrictive) and remove others", idx)
+                ResolutionSuggestion::from_catalog(
+                    Message::new("policy.spending_cap.most_restrictive"),
+                    if let Some(idx) = most_restrictive {
+                        Message::new("policy.keep_most_restrictive.action").arg("index", idx)
                     } else {
-                        "Remove duplicate spending caps".to_string()
+                        Message::new("policy.spending_cap.remove_duplicates.action")
                     },
-                },
-                ResolutionSuggestion {
-                    description: "Keep all if intentional".to_string(),
-                    action:
-                        "Multiple spending caps will all be enforced (most restrictive applies)"
-                            .to_string(),
-                },
+                ),
+                ResolutionSuggestion::from_catalog(
+                    Message::new("policy.keep_all"),
+                    Message::new("policy.spending_cap.keep_all.action"),
+                ),
             ],
             spending_caps.iter().map(|(idx, _)| *idx).collect(),
         ));
     }
 }
 
+/// Seconds a calendar month is assumed to last when comparing caps
+const NOMINAL_MONTH_SECONDS: f64 = 30.0 * 86400.0;
+
+/// Amount a spending cap allows per second, for ranking caps
+///
+/// Calendar windows are compared at their nominal length (a day, 30 days).
+fn spending_rate(rule: &PolicyRule) -> f64 {
+    let PolicyRule::SpendingCap { max_amount, .. } = rule else {
+        return f64::INFINITY;
+    };
+    let seconds = match rule.spending_window() {
+        Some(SpendingWindow::Rolling(window)) => window.as_secs_f64(),
+        Some(SpendingWindow::CalendarDay(_)) => 86400.0,
+        Some(SpendingWindow::CalendarMonth(_)) => NOMINAL_MONTH_SECONDS,
+        None => return f64::INFINITY,
+    };
+    max_amount / seconds
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::policy::window::WindowType;
+    use x402_domain::DurationField;
 
     #[test]
     fn test_empty_policies() {
-        let policy_config = PolicyConfig { policies: vec![] };
+        let policy_config = PolicyConfig {
+            policies: vec![],
+            allow_custom_currencies: false,
+        };
         let report = validate_policies(&policy_config);
 
         assert!(report.is_valid());
@@ -432,6 +645,7 @@ mod tests {
                     values: vec!["agent-abc-123".to_string()],
                 },
             ],
+            allow_custom_currencies: false,
         };
 
         let report = validate_policies(&policy_config);
@@ -466,6 +680,7 @@ mod tests {
                     values: vec!["wallet-456".to_string()],
                 },
             ],
+            allow_custom_currencies: false,
         };
 
         let report = validate_policies(&policy_config);
@@ -480,13 +695,14 @@ mod tests {
             policies: vec![
                 PolicyRule::RateLimit {
                     max_requests: 100,
-                    window_seconds: 3600,
+                    window_seconds: DurationField::from_secs(3600),
                 },
                 PolicyRule::RateLimit {
                     max_requests: 50,
-                    window_seconds: 3600,
+                    window_seconds: DurationField::from_secs(3600),
                 },
             ],
+            allow_custom_currencies: false,
         };
 
         let report = validate_policies(&policy_config);
@@ -499,6 +715,34 @@ mod tests {
             .any(|i| i.message.contains("Multiple rate limits")));
     }
 
+    #[test]
+    fn test_suppressed_warning_does_not_count() {
+        let policy_config = PolicyConfig {
+            policies: vec![
+                PolicyRule::RateLimit {
+                    max_requests: 100,
+                    window_seconds: DurationField::from_secs(3600),
+                },
+                PolicyRule::RateLimit {
+                    max_requests: 50,
+                    window_seconds: DurationField::from_secs(3600),
+                },
+            ],
+            allow_custom_currencies: false,
+        };
+        let mut report = validate_policies(&policy_config);
+        assert_eq!(report.issues[0].code, RuleCode::MultipleRateLimits);
+
+        let mut suppressions = Suppressions::default();
+        suppressions.allow(&[RuleCode::MultipleRateLimits]);
+        report.apply_suppressions(&suppressions);
+
+        assert!(report.issues[0].suppressed);
+        assert!(!report.has_warnings);
+        assert_eq!(report.counts(), (0, 0, 0));
+        assert_eq!(report.suppressed(), 1);
+    }
+
     #[test]
     fn test_multiple_spending_caps_warning() {
         let policy_config = PolicyConfig {
@@ -506,14 +750,19 @@ mod tests {
                 PolicyRule::SpendingCap {
                     max_amount: 10.0,
                     currency: "USDC".to_string(),
-                    window_seconds: 86400,
+                    window_seconds: Some(DurationField::from_secs(86400)),
+                    window_type: WindowType::Rolling,
+                    timezone: None,
                 },
                 PolicyRule::SpendingCap {
                     max_amount: 5.0,
                     currency: "USDC".to_string(),
-                    window_seconds: 86400,
+                    window_seconds: None,
+                    window_type: WindowType::CalendarDay,
+                    timezone: Some(chrono_tz::America::New_York),
                 },
             ],
+            allow_custom_currencies: false,
         };
 
         let report = validate_policies(&policy_config);
@@ -524,6 +773,79 @@ mod tests {
             .issues
             .iter()
             .any(|i| i.message.contains("Multiple spending caps")));
+        let issue = report
+            .issues
+            .iter()
+            .find(|i| i.message.contains("Multiple spending caps"))
+            .unwrap();
+        let details = issue.details.as_deref().unwrap();
+        assert!(details.contains("Policy #0: 10 USDC / 86400 seconds"));
+        assert!(details.contains("Policy #1: 5 USDC / calendar day (America/New_York)"));
+    }
+
+    #[test]
+    fn test_calendar_month_with_window_seconds_is_an_error() {
+        let policy_config = PolicyConfig {
+            policies: vec![PolicyRule::SpendingCap {
+                max_amount: 100.0,
+                currency: "USDC".to_string(),
+                window_seconds: Some(DurationField::from_secs(2592000)),
+                window_type: WindowType::CalendarMonth,
+                timezone: None,
+            }],
+            allow_custom_currencies: false,
+        };
+
+        let report = validate_policies(&policy_config);
+
+        assert!(!report.is_valid());
+        assert!(report
+            .issues
+            .iter()
+            .any(|i| i.issue_type == IssueType::Error
+                && i.details.as_deref().is_some_and(|d| d.contains(
+                    "window_seconds cannot be combined with window_type: calendar_month"
+                ))));
+    }
+
+    #[test]
+    fn test_unknown_currency_is_an_error() {
+        let cap = |currency: &str| PolicyRule::SpendingCap {
+            max_amount: 10.0,
+            currency: currency.to_string(),
+            window_seconds: Some(DurationField::from_secs(3600)),
+            window_type: WindowType::Rolling,
+            timezone: None,
+        };
+        let mut policy_config = PolicyConfig {
+            policies: vec![cap("usdc"), cap("USD")],
+            allow_custom_currencies: false,
+        };
+
+        // Known currencies match in any case; USD is one edit from USDC
+        let report = validate_policies(&policy_config);
+        let errors: Vec<_> = report
+            .issues
+            .iter()
+            .filter(|i| i.code == RuleCode::UnknownCurrency)
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].policy_indices, [1]);
+        assert!(errors[0]
+            .details
+            .as_deref()
+            .unwrap()
+            .contains("did you mean USDC?"));
+        assert_eq!(
+            errors[0].suggestions[0].action,
+            "Set currency: USDC in policy #1"
+        );
+
+        policy_config.allow_custom_currencies = true;
+        assert!(!validate_policies(&policy_config)
+            .issues
+            .iter()
+            .any(|i| i.code == RuleCode::UnknownCurrency));
     }
 
     #[test]
@@ -531,18 +853,24 @@ mod tests {
         let mut report = ValidationReport::new();
 
         report.add_issue(ValidationIssue::error(
+            RuleCode::InvalidPolicy,
             "Error 1".to_string(),
             None,
             vec![],
             vec![],
         ));
         report.add_issue(ValidationIssue::warning(
+            RuleCode::MultipleRateLimits,
             "Warning 1".to_string(),
             None,
             vec![],
             vec![],
         ));
-        report.add_issue(ValidationIssue::info("Info 1".to_string(), None));
+        report.add_issue(ValidationIssue::info(
+            RuleCode::AllValid,
+            "Info 1".to_string(),
+            None,
+        ));
 
         let (errors, warnings, info) = report.counts();
         assert_eq!(errors, 1);
@@ -0,0 +1,201 @@
+// Spending cap windows
+//
+// A cap counts spends either over a rolling window ending at each request or
+// within the current calendar day or month. Calendar windows reset at local
+// midnight in the configured timezone (UTC by default), so a calendar day is
+// 23 or 25 hours long across a DST change.
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use std::time::{Duration, SystemTime};
+
+/// How a spending cap's window is aligned (`window_type` in YAML)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowType {
+    /// The configured duration before each request
+    #[default]
+    Rolling,
+    /// Local midnight to local midnight
+    CalendarDay,
+    /// Local midnight on the 1st to the 1st of the next month
+    CalendarMonth,
+}
+
+impl WindowType {
+    pub fn is_rolling(&self) -> bool {
+        *self == WindowType::Rolling
+    }
+}
+
+impl fmt::Display for WindowType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WindowType::Rolling => "rolling",
+            WindowType::CalendarDay => "calendar_day",
+            WindowType::CalendarMonth => "calendar_month",
+        })
+    }
+}
+
+/// Window a spending cap is enforced over
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpendingWindow {
+    Rolling(Duration),
+    CalendarDay(Tz),
+    CalendarMonth(Tz),
+}
+
+impl SpendingWindow {
+    /// Calendar window of `window_type` in `timezone` (UTC if unset)
+    ///
+    /// Returns None for rolling windows, which need a duration instead.
+    pub fn calendar(window_type: WindowType, timezone: Option<Tz>) -> Option<Self> {
+        let tz = timezone.unwrap_or(Tz::UTC);
+        match window_type {
+            WindowType::Rolling => None,
+            WindowType::CalendarDay => Some(SpendingWindow::CalendarDay(tz)),
+            WindowType::CalendarMonth => Some(SpendingWindow::CalendarMonth(tz)),
+        }
+    }
+
+    pub fn window_type(&self) -> WindowType {
+        match self {
+            SpendingWindow::Rolling(_) => WindowType::Rolling,
+            SpendingWindow::CalendarDay(_) => WindowType::CalendarDay,
+            SpendingWindow::CalendarMonth(_) => WindowType::CalendarMonth,
+        }
+    }
+
+    /// The calendar bucket containing `at`; None for rolling windows
+    pub fn bucket(&self, at: SystemTime) -> Option<Range<SystemTime>> {
+        let (tz, first, next) = match *self {
+            SpendingWindow::Rolling(_) => return None,
+            SpendingWindow::CalendarDay(tz) => {
+                let day = local_date(tz, at);
+                (tz, day, day.succ_opt()?)
+            }
+            SpendingWindow::CalendarMonth(tz) => {
+                let first = local_date(tz, at).with_day(1)?;
+                (tz, first, first.checked_add_months(Months::new(1))?)
+            }
+        };
+        Some(start_of_day(tz, first)?..start_of_day(tz, next)?)
+    }
+
+    /// Length of the window containing `at`
+    pub fn length_at(&self, at: SystemTime) -> Duration {
+        match self {
+            SpendingWindow::Rolling(window) => *window,
+            _ => self
+                .bucket(at)
+                .and_then(|bucket| bucket.end.duration_since(bucket.start).ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for SpendingWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpendingWindow::Rolling(window) => write!(f, "{}s", window.as_secs()),
+            SpendingWindow::CalendarDay(tz) => write!(f, "calendar day ({})", tz),
+            SpendingWindow::CalendarMonth(tz) => write!(f, "calendar month ({})", tz),
+        }
+    }
+}
+
+fn local_date(tz: Tz, at: SystemTime) -> NaiveDate {
+    DateTime::<Utc>::from(at).with_timezone(&tz).date_naive()
+}
+
+/// First instant of `day` in `tz`
+///
+/// Where a DST change skips midnight (e.g. America/Santiago), the day starts
+/// when the clocks jump forward.
+fn start_of_day(tz: Tz, day: NaiveDate) -> Option<SystemTime> {
+    let midnight = day.and_hms_opt(0, 0, 0)?;
+    (0..=120)
+        .step_by(15)
+        .find_map(|minutes| {
+            tz.from_local_datetime(&(midnight + chrono::Duration::minutes(minutes)))
+                .earliest()
+        })
+        .map(SystemTime::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> SystemTime {
+        DateTime::parse_from_rfc3339(s).unwrap().into()
+    }
+
+    #[test]
+    fn test_calendar_day_resets_at_utc_midnight() {
+        let window = SpendingWindow::calendar(WindowType::CalendarDay, None).unwrap();
+
+        let late = window.bucket(utc("2025-03-14T23:59:00Z")).unwrap();
+        let early = window.bucket(utc("2025-03-15T00:01:00Z")).unwrap();
+
+        assert_eq!(late.start, utc("2025-03-14T00:00:00Z"));
+        assert_eq!(late.end, early.start);
+        assert_eq!(early.start, utc("2025-03-15T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_calendar_day_across_dst() {
+        let window = SpendingWindow::CalendarDay(chrono_tz::America::New_York);
+
+        // 2024-03-10 04:30 UTC is still 23:30 EST on the 9th
+        let before = window.bucket(utc("2024-03-10T04:30:00Z")).unwrap();
+        assert_eq!(before.start, utc("2024-03-09T05:00:00Z"));
+
+        // Clocks spring forward at 02:00 local: the 10th lasts 23 hours
+        let spring = window.bucket(utc("2024-03-10T12:00:00Z")).unwrap();
+        assert_eq!(spring.start, utc("2024-03-10T05:00:00Z"));
+        assert_eq!(spring.end, utc("2024-03-11T04:00:00Z"));
+        assert_eq!(
+            window.length_at(utc("2024-03-10T12:00:00Z")),
+            Duration::from_secs(23 * 3600)
+        );
+
+        // ...and fall back on 2024-11-03: 25 hours
+        assert_eq!(
+            window.length_at(utc("2024-11-03T12:00:00Z")),
+            Duration::from_secs(25 * 3600)
+        );
+    }
+
+    #[test]
+    fn test_calendar_day_when_dst_skips_midnight() {
+        // Chile springs forward at midnight on 2024-09-08: 00:00 -04 becomes 01:00 -03
+        let window = SpendingWindow::CalendarDay(chrono_tz::America::Santiago);
+        let bucket = window.bucket(utc("2024-09-08T12:00:00Z")).unwrap();
+        assert_eq!(bucket.start, utc("2024-09-08T04:00:00Z"));
+    }
+
+    #[test]
+    fn test_calendar_month() {
+        let window =
+            SpendingWindow::calendar(WindowType::CalendarMonth, Some(chrono_tz::Europe::Berlin))
+                .unwrap();
+
+        let bucket = window.bucket(utc("2025-02-14T10:00:00Z")).unwrap();
+        assert_eq!(bucket.start, utc("2025-01-31T23:00:00Z"));
+        assert_eq!(bucket.end, utc("2025-02-28T23:00:00Z"));
+        assert_eq!(window.to_string(), "calendar month (Europe/Berlin)");
+    }
+
+    #[test]
+    fn test_rolling_window_has_no_bucket() {
+        let window = SpendingWindow::Rolling(Duration::from_secs(3600));
+        assert_eq!(window.bucket(SystemTime::now()), None);
+        assert_eq!(window.to_string(), "3600s");
+        assert!(SpendingWindow::calendar(WindowType::Rolling, None).is_none());
+    }
+}
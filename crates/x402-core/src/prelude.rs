@@ -0,0 +1,12 @@
+// Common x402-core types in one import
+//
+// `use x402_core::prelude::*;` brings in what embedders usually need: load
+// and validate policy files, evaluate requests against them, and run test
+// suites. Everything here is covered by the public API snapshot.
+
+pub use crate::policy::{
+    load_policy_file, runtime_policies_from_yaml, validate_policies, IssueType, LoadedPolicyFile,
+    PolicyDecision, PolicyEngine, Request, RuntimePolicy, RuntimePolicyFile, ValidationIssue,
+    ValidationReport,
+};
+pub use crate::testing::{execute_test_suite, SuiteResult, TestResult, TestSuite};
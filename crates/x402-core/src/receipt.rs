@@ -0,0 +1,296 @@
+// Payment receipts (`X-Payment-Receipt`)
+//
+// After a successful verification the mock server answers with a receipt:
+// base64 of a JSON object recording what was paid (memo, amount, recipient,
+// resource) and when. With the `signing` feature and a signing key, the
+// receipt also carries the Ed25519 `signer` and a `signature` over
+// canonical_string(), the same scheme as signed invoices.
+//
+// The JSON Schema in docs/schemas/payment-receipt.schema.json is generated
+// from PaymentReceipt by `cargo xtask schema`.
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Response header carrying the encoded receipt
+pub const RECEIPT_HEADER: &str = "X-Payment-Receipt";
+
+/// Simulation modes a receipt can record
+pub const SIMULATION_MODES: &[&str] = &["success", "failure", "timeout"];
+
+/// Proof of a verified payment, as issued by the mock server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct PaymentReceipt {
+    /// Memo of the paid invoice (the payment proof the client sent)
+    pub memo: String,
+    /// USDC amount as a plain decimal with at most 6 places, e.g. `0.01`
+    #[cfg_attr(
+        feature = "schema",
+        schemars(regex(pattern = r"^[0-9]+(\.[0-9]{1,6})?$"))
+    )]
+    pub amount: String,
+    /// Accepted difference between the amount paid and `amount`, e.g.
+    /// `-0.000001`; absent for exact payments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(regex(pattern = r"^-?[0-9]+(\.[0-9]{1,6})?$"))
+    )]
+    pub amount_delta: Option<String>,
+    /// Address paid; absent when the server picks a recipient per invoice
+    /// (rotating test addresses or weighted routes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+    /// Path of the paid resource
+    pub resource: String,
+    /// Verification time, RFC 3339 in whole seconds (UTC)
+    #[cfg_attr(feature = "schema", schemars(extend("format" = "date-time")))]
+    pub verified_at: String,
+    /// Simulation mode the server verified with (`success`, `failure` or `timeout`)
+    pub simulation_mode: String,
+    /// Base58 Ed25519 public key of a signed receipt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    /// Base58 signature over the canonical receipt string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+impl PaymentReceipt {
+    /// Unsigned receipt for `memo`, verified at `verified_at`
+    pub fn new(
+        memo: impl Into<String>,
+        amount: impl Into<String>,
+        resource: impl Into<String>,
+        simulation_mode: impl Into<String>,
+        verified_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            memo: memo.into(),
+            amount: amount.into(),
+            amount_delta: None,
+            recipient: None,
+            resource: resource.into(),
+            verified_at: verified_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            simulation_mode: simulation_mode.into(),
+            signer: None,
+            signature: None,
+        }
+    }
+
+    pub fn with_recipient(mut self, recipient: impl Into<String>) -> Self {
+        self.recipient = Some(recipient.into());
+        self
+    }
+
+    /// Record a payment that differed from `amount` by `delta` (paid
+    /// minus invoiced); a zero delta is not recorded
+    pub fn with_amount_delta(mut self, delta: Decimal) -> Self {
+        self.amount_delta = (!delta.is_zero()).then(|| delta.normalize().to_string());
+        self
+    }
+
+    /// String receipt signatures cover
+    ///
+    /// Fields in a fixed order; `amount_delta` and `recipient` only when
+    /// present. Values are quoted so spaces in a memo or path cannot shift
+    /// fields.
+    pub fn canonical_string(&self) -> String {
+        let mut canonical = format!("x402-receipt memo={:?} amount={}", self.memo, self.amount);
+        if let Some(delta) = &self.amount_delta {
+            canonical.push_str(&format!(" amount_delta={}", delta));
+        }
+        if let Some(recipient) = &self.recipient {
+            canonical.push_str(&format!(" recipient={}", recipient));
+        }
+        canonical.push_str(&format!(
+            " resource={:?} verified_at=\"{}\" simulation_mode={}",
+            self.resource, self.verified_at, self.simulation_mode
+        ));
+        canonical
+    }
+
+    /// Header value: base64 (standard alphabet, padded) of the JSON object
+    pub fn encode(&self) -> String {
+        BASE64.encode(serde_json::to_vec(self).expect("receipt serializes"))
+    }
+
+    /// Decode a header value and check its structure (not its signature)
+    pub fn decode(value: &str) -> Result<Self> {
+        let json = BASE64.decode(value.trim()).context(
+            "Receipt is not valid base64\nFix: Pass the X-Payment-Receipt header value as-is",
+        )?;
+        let receipt: Self = serde_json::from_slice(&json)
+            .map_err(|e| anyhow::anyhow!("Receipt is not a valid receipt JSON object: {}", e))?;
+        receipt.validate()?;
+        Ok(receipt)
+    }
+
+    /// Check field formats, and that `signer` and `signature` come together
+    pub fn validate(&self) -> Result<()> {
+        if self.memo.trim().is_empty() {
+            bail!("Receipt memo is empty");
+        }
+        match Decimal::from_str(&self.amount) {
+            Ok(amount) if amount > Decimal::ZERO && amount.scale() <= 6 => {}
+            _ => bail!(
+                "Receipt amount '{}' is not a positive USDC amount (at most 6 decimal places)",
+                self.amount
+            ),
+        }
+        if let Some(delta) = &self.amount_delta {
+            match Decimal::from_str(delta) {
+                Ok(delta) if !delta.is_zero() && delta.scale() <= 6 => {}
+                _ => bail!(
+                    "Receipt amount_delta '{}' is not a non-zero USDC amount (at most 6 decimal places)",
+                    delta
+                ),
+            }
+        }
+        if !self.resource.starts_with('/') {
+            bail!("Receipt resource '{}' is not a path", self.resource);
+        }
+        DateTime::parse_from_rfc3339(&self.verified_at).with_context(|| {
+            format!(
+                "Receipt verified_at '{}' is not an RFC 3339 time",
+                self.verified_at
+            )
+        })?;
+        if !SIMULATION_MODES.contains(&self.simulation_mode.as_str()) {
+            bail!(
+                "Receipt simulation_mode '{}' is not one of {}",
+                self.simulation_mode,
+                SIMULATION_MODES.join(", ")
+            );
+        }
+        if self.signer.is_some() != self.signature.is_some() {
+            bail!("Receipt has only one of signer and signature");
+        }
+        Ok(())
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+
+    /// Set `signer` and `signature` with `signer`'s key
+    #[cfg(feature = "signing")]
+    pub fn sign(&mut self, signer: &crate::signing::InvoiceSigner) {
+        self.signer = Some(signer.public_key());
+        self.signature = Some(signer.sign(&self.canonical_string()));
+    }
+
+    /// Verify the signature, returning the signer
+    ///
+    /// With `expected_signer`, the receipt must be signed by that key.
+    #[cfg(feature = "signing")]
+    pub fn verify_signature(&self, expected_signer: Option<&str>) -> Result<String> {
+        let (Some(signer), Some(signature)) = (&self.signer, &self.signature) else {
+            bail!("Receipt is not signed");
+        };
+        if let Some(expected) = expected_signer {
+            if signer != expected {
+                bail!("Receipt signed by {}, expected {}", signer, expected);
+            }
+        }
+        if !crate::signing::verify_message(signer, signature, &self.canonical_string())? {
+            bail!("Signature does not match the receipt fields");
+        }
+        Ok(signer.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt() -> PaymentReceipt {
+        let verified_at = DateTime::parse_from_rfc3339("2026-01-02T03:04:05.678Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        PaymentReceipt::new("req-123", "0.01", "/api/data", "success", verified_at)
+            .with_recipient("GXk8vTest1111111111111111111111111111qPz9")
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let receipt = receipt();
+        assert_eq!(receipt.verified_at, "2026-01-02T03:04:05Z");
+        assert_eq!(
+            receipt.canonical_string(),
+            "x402-receipt memo=\"req-123\" amount=0.01 recipient=GXk8vTest1111111111111111111111111111qPz9 resource=\"/api/data\" verified_at=\"2026-01-02T03:04:05Z\" simulation_mode=success"
+        );
+        assert_eq!(PaymentReceipt::decode(&receipt.encode()).unwrap(), receipt);
+    }
+
+    #[test]
+    fn test_amount_delta_is_recorded_and_signed_over() {
+        let exact = receipt().with_amount_delta(Decimal::ZERO);
+        assert_eq!(exact.amount_delta, None);
+
+        let receipt = receipt().with_amount_delta(Decimal::from_str("-0.000010").unwrap());
+        assert_eq!(receipt.amount_delta.as_deref(), Some("-0.00001"));
+        assert!(receipt.canonical_string().starts_with(
+            "x402-receipt memo=\"req-123\" amount=0.01 amount_delta=-0.00001 recipient="
+        ));
+        assert_eq!(PaymentReceipt::decode(&receipt.encode()).unwrap(), receipt);
+
+        let mut json = serde_json::to_value(&receipt).unwrap();
+        json["amount_delta"] = serde_json::json!("0");
+        let err = PaymentReceipt::decode(&BASE64.encode(json.to_string())).unwrap_err();
+        assert!(err.to_string().contains("amount_delta"));
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_receipts() {
+        let err = PaymentReceipt::decode("not base64!").unwrap_err();
+        assert!(err.to_string().contains("not valid base64"));
+
+        let encode = |json: serde_json::Value| BASE64.encode(json.to_string());
+        let mut json = serde_json::to_value(receipt()).unwrap();
+        json["amount"] = serde_json::json!("0.0000001");
+        let err = PaymentReceipt::decode(&encode(json.clone())).unwrap_err();
+        assert!(err.to_string().contains("not a positive USDC amount"));
+
+        json["amount"] = serde_json::json!("0.01");
+        json["extra"] = serde_json::json!(true);
+        assert!(PaymentReceipt::decode(&encode(json.clone())).is_err());
+
+        json.as_object_mut().unwrap().remove("extra");
+        json["signer"] = serde_json::json!("abc");
+        let err = PaymentReceipt::decode(&encode(json)).unwrap_err();
+        assert!(err.to_string().contains("only one of signer and signature"));
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = crate::signing::InvoiceSigner::generate();
+        let mut receipt = receipt();
+        receipt.sign(&signer);
+
+        let decoded = PaymentReceipt::decode(&receipt.encode()).unwrap();
+        assert_eq!(decoded.verify_signature(None).unwrap(), signer.public_key());
+        assert!(decoded
+            .verify_signature(Some(
+                &crate::signing::InvoiceSigner::generate().public_key()
+            ))
+            .unwrap_err()
+            .to_string()
+            .contains("expected"));
+
+        let mut tampered = decoded;
+        tampered.amount = "0.001".to_string();
+        let err = tampered.verify_signature(None).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("does not match the receipt fields"));
+    }
+}
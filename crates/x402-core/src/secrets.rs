@@ -0,0 +1,496 @@
+// Secrets referenced as `${secret:name}` from config files and test suites
+//
+// Values live outside the project, in the OS keyring (`keyring` feature) or
+// in files under ~/.x402dev/secrets encrypted with ChaCha20-Poly1305 under
+// an Argon2id key derived from a passphrase. The directory holds one entry
+// per secret either way (`<name>.enc`, or an empty `<name>.keyring` marker),
+// so names can be listed and removed without the passphrase.
+
+use anyhow::{bail, Context, Result};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Marks a secret reference inside `${...}`
+pub const SECRET_PREFIX: &str = "secret:";
+
+/// Environment variable holding the passphrase of encrypted secret files
+pub const PASSPHRASE_ENV: &str = "X402_DEV_SECRETS_PASSPHRASE";
+
+/// Keyring service the secrets are stored under
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "x402-dev";
+
+/// Version of the encrypted file format
+const FILE_VERSION: u32 = 1;
+
+/// Argon2id salt length in bytes
+const SALT_LEN: usize = 16;
+
+/// Longest secret name accepted
+const MAX_NAME_LEN: usize = 64;
+
+/// Where a secret's value is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum SecretBackend {
+    /// OS keyring: macOS Keychain, Windows Credential Manager, Linux keyutils
+    Keyring,
+    /// Passphrase-encrypted file
+    File,
+}
+
+impl SecretBackend {
+    /// The keyring when this build supports it, else an encrypted file
+    pub fn preferred() -> Self {
+        if cfg!(feature = "keyring") {
+            Self::Keyring
+        } else {
+            Self::File
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Keyring => "keyring",
+            Self::File => "enc",
+        }
+    }
+}
+
+impl fmt::Display for SecretBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Keyring => "keyring",
+            Self::File => "file",
+        })
+    }
+}
+
+/// Supplies the passphrase of encrypted secret files, asked only when needed
+pub trait PassphraseProvider: Send + Sync {
+    fn passphrase(&self) -> Result<String>;
+}
+
+impl<F> PassphraseProvider for F
+where
+    F: Fn() -> Result<String> + Send + Sync,
+{
+    fn passphrase(&self) -> Result<String> {
+        self()
+    }
+}
+
+/// Contents of a `<name>.enc` file
+#[derive(Serialize, Deserialize)]
+struct EncryptedSecret {
+    version: u32,
+    /// Argon2id salt (base64)
+    salt: String,
+    /// ChaCha20-Poly1305 nonce (base64)
+    nonce: String,
+    /// Encrypted value with its tag (base64)
+    ciphertext: String,
+}
+
+/// Check a secret name: 1-64 letters, digits, `_`, `-` or `.`, starting
+/// with a letter or digit
+pub fn validate_secret_name(name: &str) -> Result<()> {
+    let valid = name.len() <= MAX_NAME_LEN
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        bail!(
+            "Invalid secret name '{}'\nFix: Use 1-{} letters, digits, '_', '-' or '.', starting with a letter or digit",
+            name,
+            MAX_NAME_LEN
+        );
+    }
+    Ok(())
+}
+
+/// Names referenced as `${secret:name}` in `text`
+pub fn secret_references(text: &str) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        if let Some(name) = rest[start + 2..start + 2 + len].strip_prefix(SECRET_PREFIX) {
+            names.insert(name.to_string());
+        }
+        rest = &rest[start + 3 + len..];
+    }
+    names
+}
+
+/// Replace `${secret:name}` references in `text` with `resolve(name)`;
+/// other `${...}` references are left alone
+pub fn interpolate_secrets(
+    text: &str,
+    mut resolve: impl FnMut(&str) -> Result<String>,
+) -> Result<String> {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        let reference = &rest[start..start + 3 + len];
+        result.push_str(&rest[..start]);
+        match rest[start + 2..start + 2 + len].strip_prefix(SECRET_PREFIX) {
+            Some(name) => result.push_str(&resolve(name)?),
+            None => result.push_str(reference),
+        }
+        rest = &rest[start + 3 + len..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Secrets kept in one directory, usually ~/.x402dev/secrets
+pub struct SecretStore {
+    dir: PathBuf,
+    passphrase: Box<dyn PassphraseProvider>,
+}
+
+impl fmt::Debug for SecretStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretStore")
+            .field("dir", &self.dir)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SecretStore {
+    /// Store in `dir`; `passphrase` is asked when a file is encrypted or decrypted
+    pub fn new(dir: impl Into<PathBuf>, passphrase: impl PassphraseProvider + 'static) -> Self {
+        Self {
+            dir: dir.into(),
+            passphrase: Box::new(passphrase),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, name: &str, backend: SecretBackend) -> PathBuf {
+        self.dir.join(format!("{}.{}", name, backend.extension()))
+    }
+
+    /// Store `value` under `name`, replacing a previous value in either backend
+    pub fn set(&self, name: &str, value: &str, backend: SecretBackend) -> Result<()> {
+        validate_secret_name(name)?;
+        create_private_dir(&self.dir)?;
+
+        match backend {
+            SecretBackend::File => {
+                let passphrase = self.checked_passphrase()?;
+                let encrypted = encrypt(&passphrase, value)?;
+                write_private(
+                    &self.entry_path(name, backend),
+                    &serde_json::to_string_pretty(&encrypted)?,
+                )?;
+            }
+            SecretBackend::Keyring => {
+                keyring_set(name, value)?;
+                write_private(&self.entry_path(name, backend), "")?;
+            }
+        }
+
+        let other = match backend {
+            SecretBackend::File => SecretBackend::Keyring,
+            SecretBackend::Keyring => SecretBackend::File,
+        };
+        self.remove_entry(name, other)?;
+        Ok(())
+    }
+
+    /// Value of `name`; an error names the secret if it is not stored
+    pub fn get(&self, name: &str) -> Result<String> {
+        validate_secret_name(name)?;
+        match self.backend_of(name) {
+            Some(SecretBackend::File) => {
+                let path = self.entry_path(name, SecretBackend::File);
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read secret file: {:?}", path))?;
+                let encrypted: EncryptedSecret = serde_json::from_str(&content)
+                    .with_context(|| format!("Secret file {:?} is corrupted", path))?;
+                decrypt(&self.passphrase()?, &encrypted).with_context(|| {
+                    format!(
+                        "Could not decrypt secret '{}'\nFix: Check {} or the passphrase you entered",
+                        name, PASSPHRASE_ENV
+                    )
+                })
+            }
+            Some(SecretBackend::Keyring) => keyring_get(name),
+            None => bail!(
+                "Secret '{}' not found in {}\nFix: Run x402-dev secret set {}",
+                name,
+                self.dir.display(),
+                name
+            ),
+        }
+    }
+
+    /// Backend holding `name`, if it is stored
+    pub fn backend_of(&self, name: &str) -> Option<SecretBackend> {
+        [SecretBackend::File, SecretBackend::Keyring]
+            .into_iter()
+            .find(|backend| self.entry_path(name, *backend).is_file())
+    }
+
+    /// Stored secret names with their backend, sorted by name
+    pub fn list(&self) -> Result<Vec<(String, SecretBackend)>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut secrets = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let (Some(name), Some(extension)) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|s| s.to_str()),
+            ) else {
+                continue;
+            };
+            let backend = match extension {
+                "enc" => SecretBackend::File,
+                "keyring" => SecretBackend::Keyring,
+                _ => continue,
+            };
+            if validate_secret_name(name).is_ok() {
+                secrets.push((name.to_string(), backend));
+            }
+        }
+        secrets.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(secrets)
+    }
+
+    /// Delete `name`; false if it was not stored
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        validate_secret_name(name)?;
+        let Some(backend) = self.backend_of(name) else {
+            return Ok(false);
+        };
+        self.remove_entry(name, backend)?;
+        Ok(true)
+    }
+
+    fn remove_entry(&self, name: &str, backend: SecretBackend) -> Result<()> {
+        let path = self.entry_path(name, backend);
+        if !path.exists() {
+            return Ok(());
+        }
+        if backend == SecretBackend::Keyring {
+            keyring_delete(name)?;
+        }
+        fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))
+    }
+
+    fn passphrase(&self) -> Result<String> {
+        let passphrase = self.passphrase.passphrase()?;
+        if passphrase.is_empty() {
+            bail!(
+                "The secrets passphrase is empty\nFix: Set {} or enter a passphrase",
+                PASSPHRASE_ENV
+            );
+        }
+        Ok(passphrase)
+    }
+
+    /// Passphrase for a new file, checked against an existing one so all
+    /// files share it
+    fn checked_passphrase(&self) -> Result<String> {
+        let passphrase = self.passphrase()?;
+        let existing = self
+            .list()?
+            .into_iter()
+            .find(|(_, backend)| *backend == SecretBackend::File);
+        if let Some((name, _)) = existing {
+            let path = self.entry_path(&name, SecretBackend::File);
+            let encrypted: Option<EncryptedSecret> = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok());
+            if let Some(encrypted) = encrypted {
+                if decrypt(&passphrase, &encrypted).is_err() {
+                    bail!(
+                        "The passphrase does not match the stored secrets\nFix: Use the passphrase of the existing secrets, or remove them with x402-dev secret rm"
+                    );
+                }
+            }
+        }
+        Ok(passphrase)
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(passphrase: &str, value: &str) -> Result<EncryptedSecret> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, value.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+    Ok(EncryptedSecret {
+        version: FILE_VERSION,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn decrypt(passphrase: &str, encrypted: &EncryptedSecret) -> Result<String> {
+    if encrypted.version != FILE_VERSION {
+        bail!("Unsupported secret file version {}", encrypted.version);
+    }
+    let salt = BASE64.decode(&encrypted.salt)?;
+    let nonce: [u8; 12] = BASE64
+        .decode(&encrypted.nonce)?
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Invalid nonce length"))?;
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let plaintext = cipher
+        .decrypt(
+            &Nonce::from(nonce),
+            BASE64.decode(&encrypted.ciphertext)?.as_slice(),
+        )
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase or modified secret file"))?;
+    Ok(String::from_utf8(plaintext)?)
+}
+
+/// Create `dir` readable by the owner only
+fn create_private_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(())
+}
+
+/// Write `content` to a file readable by the owner only
+fn write_private(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_entry(name: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, name)
+        .with_context(|| format!("Failed to open keyring entry for secret '{}'", name))
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_set(name: &str, value: &str) -> Result<()> {
+    keyring_entry(name)?
+        .set_password(value)
+        .with_context(|| format!("Failed to store secret '{}' in the OS keyring\nFix: Store it as an encrypted file with x402-dev secret set --file {}", name, name))
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_get(name: &str) -> Result<String> {
+    keyring_entry(name)?.get_password().with_context(|| {
+        format!(
+            "Secret '{}' is missing from the OS keyring\nFix: Run x402-dev secret set {}",
+            name, name
+        )
+    })
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_delete(name: &str) -> Result<()> {
+    match keyring_entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e)
+            .with_context(|| format!("Failed to remove secret '{}' from the OS keyring", name)),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn no_keyring(name: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Secret '{}' needs the OS keyring, which this build does not support\nFix: Rebuild with --features keyring, or use x402-dev secret set --file {}",
+        name,
+        name
+    )
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_set(name: &str, _value: &str) -> Result<()> {
+    Err(no_keyring(name))
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_get(name: &str) -> Result<String> {
+    Err(no_keyring(name))
+}
+
+/// Without keyring support only the marker file can be removed
+#[cfg(not(feature = "keyring"))]
+fn keyring_delete(_name: &str) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_name_validation() {
+        for name in ["facilitator_key", "helius.devnet", "A-1"] {
+            assert!(validate_secret_name(name).is_ok(), "{}", name);
+        }
+        for name in [
+            "",
+            ".hidden",
+            "../key",
+            "has space",
+            "ключ",
+            &"k".repeat(65),
+        ] {
+            assert!(validate_secret_name(name).is_err(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_interpolate_secrets() {
+        let text = "Bearer ${secret:api_key} for ${agent} at ${secret:rpc}";
+        assert_eq!(
+            secret_references(text),
+            BTreeSet::from(["api_key".to_string(), "rpc".to_string()])
+        );
+        let resolved = interpolate_secrets(text, |name| Ok(name.to_uppercase())).unwrap();
+        assert_eq!(resolved, "Bearer API_KEY for ${agent} at RPC");
+
+        let err = interpolate_secrets(text, |name| bail!("Secret '{}' not found", name));
+        assert!(err.unwrap_err().to_string().contains("'api_key'"));
+    }
+}
@@ -0,0 +1,237 @@
+// Ed25519 invoice signatures (`signing` feature)
+//
+// A signed invoice carries two extra WWW-Authenticate fields:
+// - signer: base58 Ed25519 public key
+// - signature: base58 signature over InvoiceHeader::canonical_string()
+//
+// Payment receipts (crate::receipt) are signed the same way.
+//
+// Keys load from Solana CLI keypair files (JSON array of 64 bytes, secret
+// then public) or are generated per process for ephemeral use.
+
+use crate::testing::InvoiceHeader;
+use anyhow::{bail, Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use std::path::Path;
+
+/// Signs canonical invoice strings with one Ed25519 key
+#[derive(Clone)]
+pub struct InvoiceSigner {
+    key: SigningKey,
+}
+
+impl std::fmt::Debug for InvoiceSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the secret key
+        f.debug_struct("InvoiceSigner")
+            .field("signer", &self.public_key())
+            .finish()
+    }
+}
+
+impl InvoiceSigner {
+    /// Generate a fresh key (lost when the process exits)
+    pub fn generate() -> Self {
+        Self {
+            key: SigningKey::generate(&mut rand_core::OsRng),
+        }
+    }
+
+    /// Load a Solana CLI keypair file (`solana-keygen new -o key.json`)
+    pub fn from_keypair_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read keypair file: {}", path.display()))?;
+        let bytes: Vec<u8> = serde_json::from_str(&content).with_context(|| {
+            format!(
+                "Invalid keypair file: {}\nFix: Use a Solana CLI keypair (JSON array of 64 bytes)",
+                path.display()
+            )
+        })?;
+        Self::from_keypair_bytes(&bytes)
+            .with_context(|| format!("Invalid keypair file: {}", path.display()))
+    }
+
+    /// Key from 64 keypair bytes (secret then public) or a 32-byte secret
+    ///
+    /// For 64 bytes the public half must match the secret half.
+    pub fn from_keypair_bytes(bytes: &[u8]) -> Result<Self> {
+        let key = match bytes.len() {
+            32 => SigningKey::from_bytes(bytes.try_into().expect("length checked")),
+            64 => SigningKey::from_keypair_bytes(bytes.try_into().expect("length checked"))
+                .context("Keypair public key does not match its secret key")?,
+            n => bail!("Keypair must be 64 bytes (or a 32-byte secret), got {}", n),
+        };
+        Ok(Self { key })
+    }
+
+    /// Base58 public key, the `signer` field value
+    pub fn public_key(&self) -> String {
+        bs58::encode(self.key.verifying_key().as_bytes()).into_string()
+    }
+
+    /// Base58 signature over `message`, the `signature` field value
+    pub fn sign(&self, message: &str) -> String {
+        bs58::encode(self.key.sign(message.as_bytes()).to_bytes()).into_string()
+    }
+}
+
+/// Verify the `signature` field of a parsed invoice header
+///
+/// The signature must cover the header's canonical string under the key in
+/// its `signer` field. With `expected_signer`, that key must also match.
+/// Returns the signer on success.
+pub fn verify_invoice_signature(
+    header: &InvoiceHeader,
+    expected_signer: Option<&str>,
+) -> Result<String> {
+    let Some(signer) = header.get("signer") else {
+        bail!("Invoice has no signer field");
+    };
+    let Some(signature) = header.get("signature") else {
+        bail!("Invoice has no signature field");
+    };
+    if let Some(expected) = expected_signer {
+        if signer != expected {
+            bail!("Invoice signed by {}, expected {}", signer, expected);
+        }
+    }
+
+    if !verify_message(signer, signature, &header.canonical_string())? {
+        bail!("Signature does not match the invoice fields");
+    }
+    Ok(signer.to_string())
+}
+
+/// True if base58 `signature` is `signer`'s signature over `message`
+///
+/// Errors only when the signer or signature is malformed.
+pub(crate) fn verify_message(signer: &str, signature: &str, message: &str) -> Result<bool> {
+    let key_bytes: [u8; 32] = decode_base58(signer, "signer")?;
+    let key = VerifyingKey::from_bytes(&key_bytes).context("signer is not a valid Ed25519 key")?;
+    let signature = Signature::from_bytes(&decode_base58(signature, "signature")?);
+    Ok(key.verify(message.as_bytes(), &signature).is_ok())
+}
+
+fn decode_base58<const N: usize>(value: &str, field: &str) -> Result<[u8; N]> {
+    let bytes = bs58::decode(value)
+        .into_vec()
+        .with_context(|| format!("{} is not valid base58", field))?;
+    let len = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("{} must decode to {} bytes, got {}", field, N, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UNSIGNED: &str = "x402-solana recipient=GXk8vTest1111111111111111111111111111qPz9 amount=0.01 currency=USDC memo=req-123 network=devnet";
+
+    fn signed_header(signer: &InvoiceSigner, header: &str) -> String {
+        let canonical = InvoiceHeader::parse(header).unwrap().canonical_string();
+        format!(
+            "{} signer={} signature={}",
+            header,
+            signer.public_key(),
+            signer.sign(&canonical)
+        )
+    }
+
+    fn verify(header: &str, expected: Option<&str>) -> Result<String> {
+        verify_invoice_signature(&InvoiceHeader::parse(header).unwrap(), expected)
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = InvoiceSigner::generate();
+        let header = signed_header(&signer, UNSIGNED);
+
+        assert_eq!(verify(&header, None).unwrap(), signer.public_key());
+        assert!(verify(&header, Some(&signer.public_key())).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_amount_fails() {
+        let signer = InvoiceSigner::generate();
+        let header = signed_header(&signer, UNSIGNED).replace("amount=0.01", "amount=0.001");
+
+        let err = verify(&header, None).unwrap_err();
+        assert!(err.to_string().contains("does not match"));
+    }
+
+    #[test]
+    fn test_wrong_signer_fails() {
+        let header = signed_header(&InvoiceSigner::generate(), UNSIGNED);
+        let other = InvoiceSigner::generate().public_key();
+
+        let err = verify(&header, Some(&other)).unwrap_err();
+        assert!(err.to_string().contains("expected"));
+
+        // Substituting the signer field breaks the signature
+        let signer = InvoiceHeader::parse(&header)
+            .unwrap()
+            .get("signer")
+            .unwrap()
+            .to_string();
+        assert!(verify(&header.replace(&signer, &other), None).is_err());
+    }
+
+    #[test]
+    fn test_reordered_header_still_verifies() {
+        let signer = InvoiceSigner::generate();
+        let signed = signed_header(&signer, UNSIGNED);
+        let header = InvoiceHeader::parse(&signed).unwrap();
+
+        let mut fields: Vec<String> = header
+            .fields
+            .iter()
+            .map(|f| format!("{}={}", f.name, f.value))
+            .collect();
+        fields.reverse();
+        let reordered = format!("x402-solana {}", fields.join(" "));
+
+        assert!(verify(&reordered, None).is_ok());
+    }
+
+    #[test]
+    fn test_unsigned_and_malformed() {
+        assert!(verify(UNSIGNED, None)
+            .unwrap_err()
+            .to_string()
+            .contains("no signer"));
+        let malformed = format!("{} signer=0OIl signature=abc", UNSIGNED);
+        assert!(verify(&malformed, None)
+            .unwrap_err()
+            .to_string()
+            .contains("not valid base58"));
+    }
+
+    #[test]
+    fn test_keypair_loading() {
+        let signer = InvoiceSigner::generate();
+        let dir = tempfile::tempdir().unwrap();
+
+        // Solana CLI format: 64 bytes, secret then public
+        let keypair = signer.key.to_keypair_bytes();
+        let path = dir.path().join("key.json");
+        std::fs::write(&path, serde_json::to_string(&keypair.to_vec()).unwrap()).unwrap();
+        let loaded = InvoiceSigner::from_keypair_file(&path).unwrap();
+        assert_eq!(loaded.public_key(), signer.public_key());
+
+        // A bare 32-byte secret derives the same key
+        let secret = InvoiceSigner::from_keypair_bytes(&keypair[..32]).unwrap();
+        assert_eq!(secret.public_key(), signer.public_key());
+
+        // Mismatched halves, wrong lengths and non-JSON files are rejected
+        let mut mismatched = keypair;
+        mismatched[63] ^= 1;
+        assert!(InvoiceSigner::from_keypair_bytes(&mismatched).is_err());
+        assert!(InvoiceSigner::from_keypair_bytes(&[0; 48]).is_err());
+        std::fs::write(&path, "not json").unwrap();
+        assert!(InvoiceSigner::from_keypair_file(&path).is_err());
+
+        // The secret never shows up in debug output
+        assert!(format!("{:?}", signer).contains(&signer.public_key()));
+    }
+}
@@ -0,0 +1,341 @@
+// Solana JSON-RPC client for payment verification
+//
+// Just the calls needed to check that a transaction paid an invoice:
+// getSignatureStatuses, getTransaction (jsonParsed), getBalance and
+// requestAirdrop. Works against a real cluster or the mock server's fake
+// RPC (`rpc:` in the config, served on /rpc), which is how its tests run.
+//
+// A transfer pays an invoice when it is confirmed or finalized, carries the
+// invoice memo, and moves exactly the invoiced amount (in base units) to the
+// invoice recipient.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Timeout of one RPC call
+pub const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Commitment of a signature as reported by `getSignatureStatuses`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmationStatus {
+    Processed,
+    Confirmed,
+    Finalized,
+}
+
+/// Status of a signature the cluster knows about
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureStatus {
+    pub slot: u64,
+    /// Blocks since the transaction, `None` once finalized
+    pub confirmations: Option<u64>,
+    /// Transaction error, `None` on success
+    pub err: Option<Value>,
+    pub confirmation_status: Option<ConfirmationStatus>,
+}
+
+impl SignatureStatus {
+    /// True when confirmed or finalized without error
+    pub fn is_settled(&self) -> bool {
+        self.err.is_none()
+            && matches!(
+                self.confirmation_status,
+                Some(ConfirmationStatus::Confirmed | ConfirmationStatus::Finalized)
+            )
+    }
+}
+
+/// Token transfer and memo found in a `jsonParsed` transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedTransfer {
+    pub slot: u64,
+    /// Destination account of the `transfer`/`transferChecked` instruction
+    pub destination: String,
+    /// Amount in base units (USDC: millionths)
+    pub amount: u64,
+    /// Text of the SPL Memo instruction, if any
+    pub memo: Option<String>,
+}
+
+/// What a payment for an invoice must look like
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedTransfer {
+    pub memo: String,
+    pub recipient: String,
+    /// Amount in base units
+    pub amount: u64,
+}
+
+/// Outcome of checking a signature against an invoice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferVerdict {
+    /// Settled transfer matching the invoice
+    Verified,
+    /// The cluster does not know the signature
+    NotFound,
+    /// Known but not yet confirmed; retry later
+    Unconfirmed,
+    /// Settled, but the transaction failed
+    Failed(String),
+    /// Settled transfer that does not match the invoice
+    Mismatch(Vec<String>),
+}
+
+impl fmt::Display for TransferVerdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Verified => write!(f, "verified"),
+            Self::NotFound => write!(f, "transaction not found"),
+            Self::Unconfirmed => write!(f, "transaction not yet confirmed"),
+            Self::Failed(err) => write!(f, "transaction failed: {}", err),
+            Self::Mismatch(reasons) => write!(f, "{}", reasons.join("; ")),
+        }
+    }
+}
+
+/// JSON-RPC client for one endpoint (`solana_rpc` in the config)
+#[derive(Debug)]
+pub struct SolanaRpcClient {
+    url: String,
+    http: reqwest::Client,
+    next_id: AtomicU64,
+}
+
+impl SolanaRpcClient {
+    pub fn new(url: impl Into<String>) -> Result<Self> {
+        let url = url.into();
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            bail!(
+                "Invalid Solana RPC URL: {}. URL must start with http:// or https://.\nFix: Use a valid URL, e.g., http://127.0.0.1:3402/rpc for the mock server",
+                crate::redact::redact_url(&url)
+            );
+        }
+        let http = reqwest::Client::builder()
+            .timeout(RPC_TIMEOUT)
+            .build()
+            .context("Failed to build the RPC client")?;
+        Ok(Self {
+            url,
+            http,
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Call `method` and return its `result`; JSON-RPC errors become errors
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let url = crate::redact::redact_url(&self.url);
+        let response: Value = self
+            .http
+            .post(&self.url)
+            .json(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .send()
+            .await
+            .with_context(|| format!("{} request to {} failed", method, url))?
+            .error_for_status()
+            .with_context(|| format!("{} request to {} failed", method, url))?
+            .json()
+            .await
+            .with_context(|| format!("{} response from {} is not JSON", method, url))?;
+
+        if let Some(error) = response.get("error") {
+            bail!(
+                "{} failed: {} (code {})",
+                method,
+                error["message"].as_str().unwrap_or("unknown error"),
+                error["code"]
+            );
+        }
+        response
+            .get("result")
+            .cloned()
+            .with_context(|| format!("{} response from {} has no result", method, url))
+    }
+
+    /// Status of `signature`, `None` when the cluster does not know it
+    pub async fn get_signature_status(&self, signature: &str) -> Result<Option<SignatureStatus>> {
+        let result = self
+            .call(
+                "getSignatureStatuses",
+                json!([[signature], { "searchTransactionHistory": true }]),
+            )
+            .await?;
+        let status = result["value"].get(0).cloned().unwrap_or(Value::Null);
+        serde_json::from_value(status).context("getSignatureStatuses returned an unexpected status")
+    }
+
+    /// Token transfer of a confirmed transaction, `None` when there is none yet
+    pub async fn get_transfer(&self, signature: &str) -> Result<Option<ParsedTransfer>> {
+        let result = self
+            .call(
+                "getTransaction",
+                json!([signature, {
+                    "encoding": "jsonParsed",
+                    "commitment": "confirmed",
+                    "maxSupportedTransactionVersion": 0,
+                }]),
+            )
+            .await?;
+        if result.is_null() {
+            return Ok(None);
+        }
+        parse_transfer(&result).map(Some)
+    }
+
+    /// Balance of `address` in lamports
+    pub async fn get_balance(&self, address: &str) -> Result<u64> {
+        let result = self.call("getBalance", json!([address])).await?;
+        result["value"]
+            .as_u64()
+            .context("getBalance returned no lamports value")
+    }
+
+    /// Request `lamports` for `address`, returning the airdrop signature
+    pub async fn request_airdrop(&self, address: &str, lamports: u64) -> Result<String> {
+        let result = self
+            .call("requestAirdrop", json!([address, lamports]))
+            .await?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .context("requestAirdrop returned no signature")
+    }
+
+    /// Check that `signature` settled a payment matching `expected`
+    pub async fn verify_transfer(
+        &self,
+        signature: &str,
+        expected: &ExpectedTransfer,
+    ) -> Result<TransferVerdict> {
+        let Some(status) = self.get_signature_status(signature).await? else {
+            return Ok(TransferVerdict::NotFound);
+        };
+        if let Some(err) = &status.err {
+            return Ok(TransferVerdict::Failed(err.to_string()));
+        }
+        if !status.is_settled() {
+            return Ok(TransferVerdict::Unconfirmed);
+        }
+        match self.get_transfer(signature).await? {
+            Some(transfer) => Ok(compare_transfer(&transfer, expected)),
+            None => Ok(TransferVerdict::Unconfirmed),
+        }
+    }
+}
+
+/// Compare a transfer with the invoice it should pay
+pub fn compare_transfer(transfer: &ParsedTransfer, expected: &ExpectedTransfer) -> TransferVerdict {
+    let mut reasons = Vec::new();
+    if transfer.memo.as_deref() != Some(expected.memo.as_str()) {
+        reasons.push(format!(
+            "memo is {}, expected {}",
+            transfer.memo.as_deref().unwrap_or("missing"),
+            expected.memo
+        ));
+    }
+    if transfer.destination != expected.recipient {
+        reasons.push(format!(
+            "paid to {}, expected {}",
+            transfer.destination, expected.recipient
+        ));
+    }
+    if transfer.amount != expected.amount {
+        reasons.push(format!(
+            "amount is {} base units, expected {}",
+            transfer.amount, expected.amount
+        ));
+    }
+    if reasons.is_empty() {
+        TransferVerdict::Verified
+    } else {
+        TransferVerdict::Mismatch(reasons)
+    }
+}
+
+/// Find the token transfer and memo in a `jsonParsed` transaction
+pub fn parse_transfer(transaction: &Value) -> Result<ParsedTransfer> {
+    let instructions = transaction["transaction"]["message"]["instructions"]
+        .as_array()
+        .context("Transaction has no parsed instructions (request encoding jsonParsed)")?;
+
+    let transfer = instructions
+        .iter()
+        .map(|ix| &ix["parsed"])
+        .find(|parsed| {
+            matches!(
+                parsed["type"].as_str(),
+                Some("transfer" | "transferChecked")
+            )
+        })
+        .context("Transaction has no token transfer instruction")?;
+    let info = &transfer["info"];
+    let destination = info["destination"]
+        .as_str()
+        .context("Token transfer has no destination")?
+        .to_string();
+    let amount = info["tokenAmount"]["amount"]
+        .as_str()
+        .or_else(|| info["amount"].as_str())
+        .and_then(|amount| amount.parse().ok())
+        .context("Token transfer has no amount")?;
+
+    let memo = instructions
+        .iter()
+        .find(|ix| ix["program"] == "spl-memo")
+        .and_then(|ix| ix["parsed"].as_str())
+        .map(str::to_string);
+
+    Ok(ParsedTransfer {
+        slot: transaction["slot"].as_u64().unwrap_or_default(),
+        destination,
+        amount,
+        memo,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expected() -> ExpectedTransfer {
+        ExpectedTransfer {
+            memo: "req-1".to_string(),
+            recipient: "GXk8vTest1111111111111111111111111111qPz9".to_string(),
+            amount: 10_000,
+        }
+    }
+
+    #[test]
+    fn test_compare_transfer_reports_each_difference() {
+        let mut transfer = ParsedTransfer {
+            slot: 1,
+            destination: expected().recipient,
+            amount: 10_000,
+            memo: Some("req-1".to_string()),
+        };
+        assert_eq!(
+            compare_transfer(&transfer, &expected()),
+            TransferVerdict::Verified
+        );
+
+        transfer.amount = 5_000;
+        transfer.memo = None;
+        let verdict = compare_transfer(&transfer, &expected());
+        assert_eq!(
+            verdict.to_string(),
+            "memo is missing, expected req-1; amount is 5000 base units, expected 10000"
+        );
+    }
+
+    #[test]
+    fn test_parse_transfer_without_instructions() {
+        let err = parse_transfer(&json!({ "slot": 1 })).unwrap_err();
+        assert!(err.to_string().contains("jsonParsed"));
+    }
+}
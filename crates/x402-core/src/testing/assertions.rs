@@ -1,5 +1,8 @@
 // Assertion framework (FR-2.2)
 
+use super::encoding::{sha256_hex, DecodedBody};
+use super::header_diff::FailureDetail;
+use super::invoice_header::InvoiceHeader;
 use super::parser::Expectations;
 use anyhow::Result;
 use regex::Regex;
@@ -13,13 +16,19 @@ pub struct AssertionResult {
     pub description: String,
     pub expected: String,
     pub actual: String,
+    /// Invoice field checked by an `invoice:` assertion (value is parsed)
+    pub invoice_field: Option<String>,
+    /// Structured diff for failed header value/contains assertions
+    pub failure_detail: Option<FailureDetail>,
 }
 
 /// Assertion types
 #[derive(Debug)]
 pub enum Assertion {
     StatusCode(u16),
+    StatusIn(Vec<u16>),
     HeaderExists(String),
+    HeaderAbsent(String),
     HeaderValue { name: String, value: String },
     HeaderContains { name: String, substring: String },
     HeaderRegex { name: String, pattern: String },
@@ -42,6 +51,37 @@ impl Assertion {
                     description: format!("Status code is {}", expected),
                     expected: expected.to_string(),
                     actual: actual.to_string(),
+                    invoice_field: None,
+                    failure_detail: None,
+                })
+            }
+
+            Assertion::StatusIn(expected) => {
+                let actual = response.status().as_u16();
+                let codes: Vec<String> = expected.iter().map(u16::to_string).collect();
+                Ok(AssertionResult {
+                    passed: expected.contains(&actual),
+                    description: format!("Status code is one of {}", codes.join(", ")),
+                    expected: format!("one of [{}]", codes.join(", ")),
+                    actual: actual.to_string(),
+                    invoice_field: None,
+                    failure_detail: None,
+                })
+            }
+
+            Assertion::HeaderAbsent(name) => {
+                let exists = response.headers().contains_key(name);
+                Ok(AssertionResult {
+                    passed: !exists,
+                    description: format!("Header '{}' is absent", name),
+                    expected: "header missing".to_string(),
+                    actual: if exists {
+                        "header present".to_string()
+                    } else {
+                        "header missing".to_string()
+                    },
+                    invoice_field: None,
+                    failure_detail: None,
                 })
             }
 
@@ -56,30 +96,31 @@ impl Assertion {
                     } else {
                         "header missing".to_string()
                     },
+                    invoice_field: None,
+                    failure_detail: None,
                 })
             }
 
             Assertion::HeaderValue { name, value } => {
-                let actual_value = response
-                    .headers()
-                    .get(name)
-                    .and_then(|v| v.to_str().ok())
-                    .unwrap_or("");
+                let header = response.headers().get(name).and_then(|v| v.to_str().ok());
+                let actual_value = header.unwrap_or("");
+                let passed = actual_value == value;
 
                 Ok(AssertionResult {
-                    passed: actual_value == value,
+                    passed,
                     description: format!("Header '{}' equals '{}'", name, value),
                     expected: value.clone(),
                     actual: actual_value.to_string(),
+                    invoice_field: None,
+                    failure_detail: header
+                        .filter(|_| !passed)
+                        .map(|actual| FailureDetail::for_equals(name, value, actual)),
                 })
             }
 
             Assertion::HeaderContains { name, substring } => {
-                let actual_value = response
-                    .headers()
-                    .get(name)
-                    .and_then(|v| v.to_str().ok())
-                    .unwrap_or("");
+                let header = response.headers().get(name).and_then(|v| v.to_str().ok());
+                let actual_value = header.unwrap_or("");
 
                 let contains = actual_value.contains(substring);
 
@@ -88,6 +129,10 @@ impl Assertion {
                     description: format!("Header '{}' contains '{}'", name, substring),
                     expected: format!("contains '{}'", substring),
                     actual: actual_value.to_string(),
+                    invoice_field: None,
+                    failure_detail: header
+                        .filter(|_| !contains)
+                        .map(|actual| FailureDetail::for_contains(name, substring, actual)),
                 })
             }
 
@@ -106,6 +151,8 @@ impl Assertion {
                     description: format!("Header '{}' matches regex '{}'", name, pattern),
                     expected: format!("matches /{}/", pattern),
                     actual: actual_value.to_string(),
+                    invoice_field: None,
+                    failure_detail: None,
                 })
             }
 
@@ -132,6 +179,8 @@ impl Assertion {
                     actual: actual_amount
                         .map(|a| a.to_string())
                         .unwrap_or_else(|| "not found".to_string()),
+                    invoice_field: None,
+                    failure_detail: None,
                 })
             }
 
@@ -142,6 +191,8 @@ impl Assertion {
                     description: format!("Response time <= {}ms", max_duration.as_millis()),
                     expected: format!("<={}ms", max_duration.as_millis()),
                     actual: format!("{}ms", response_time.as_millis()),
+                    invoice_field: None,
+                    failure_detail: None,
                 })
             }
         }
@@ -151,12 +202,11 @@ impl Assertion {
 /// Parse amount from WWW-Authenticate header (x402-solana format)
 fn parse_invoice_amount(header: &str) -> Option<f64> {
     // Example: "x402-solana recipient=... amount=0.01 currency=USDC ..."
-    for part in header.split_whitespace() {
-        if let Some(amount_str) = part.strip_prefix("amount=") {
-            return amount_str.parse::<f64>().ok();
-        }
-    }
-    None
+    InvoiceHeader::parse(header)
+        .ok()?
+        .get("amount")?
+        .parse::<f64>()
+        .ok()
 }
 
 /// Build assertions from expectations
@@ -167,12 +217,17 @@ pub fn build_assertions(expect: &Expectations) -> Vec<Assertion> {
     if let Some(status) = expect.status {
         assertions.push(Assertion::StatusCode(status));
     }
+    if let Some(codes) = &expect.status_in {
+        assertions.push(Assertion::StatusIn(codes.clone()));
+    }
 
     // Header assertions
     if let Some(headers) = &expect.headers {
         for header in headers {
-            if let Some(true) = header.exists {
-                assertions.push(Assertion::HeaderExists(header.name.clone()));
+            match header.exists {
+                Some(true) => assertions.push(Assertion::HeaderExists(header.name.clone())),
+                Some(false) => assertions.push(Assertion::HeaderAbsent(header.name.clone())),
+                None => {}
             }
 
             if let Some(value) = &header.value {
@@ -204,13 +259,79 @@ pub fn build_assertions(expect: &Expectations) -> Vec<Assertion> {
     }
 
     // Response time assertion
-    if let Some(ms) = expect.response_time_ms {
-        assertions.push(Assertion::ResponseTime(Duration::from_millis(ms)));
+    if let Some(limit) = expect.response_time_ms {
+        assertions.push(Assertion::ResponseTime(limit.as_duration()));
     }
 
     assertions
 }
 
+/// Longest body excerpt shown in a failed `body_contains` result
+const BODY_EXCERPT_CHARS: usize = 200;
+
+/// Check a response body contains `expected` (`body_contains:`)
+///
+/// Checked by the executor once the body is read, after the header
+/// assertions, since reading consumes the response.
+pub(crate) fn body_contains(expected: &str, body: &str) -> AssertionResult {
+    let mut excerpt: String = body.chars().take(BODY_EXCERPT_CHARS).collect();
+    if excerpt.len() < body.len() {
+        excerpt.push_str("...");
+    }
+
+    AssertionResult {
+        passed: body.contains(expected),
+        description: format!("Body contains '{}'", expected),
+        expected: format!("contains '{}'", expected),
+        actual: excerpt,
+        invoice_field: None,
+        failure_detail: None,
+    }
+}
+
+/// Check the decoded body hashes to `expected` (`body_sha256:`)
+pub(crate) fn body_sha256(expected: &str, body: &[u8]) -> AssertionResult {
+    let actual = sha256_hex(body);
+    AssertionResult {
+        passed: actual.eq_ignore_ascii_case(expected.trim()),
+        description: "Body SHA-256 matches".to_string(),
+        expected: expected.trim().to_ascii_lowercase(),
+        actual,
+        invoice_field: None,
+        failure_detail: None,
+    }
+}
+
+/// Check the decoded body is `expected` bytes long (`body_size:`)
+pub(crate) fn body_size(expected: u64, actual: usize) -> AssertionResult {
+    AssertionResult {
+        passed: actual as u64 == expected,
+        description: format!("Body is {} bytes", expected),
+        expected: format!("{} bytes", expected),
+        actual: format!("{} bytes", actual),
+        invoice_field: None,
+        failure_detail: None,
+    }
+}
+
+/// Check the body as received is at most `max` bytes (`encoded_size_max:`)
+pub(crate) fn encoded_size_max(max: u64, body: &DecodedBody) -> AssertionResult {
+    let encoding = body.encoding.as_deref().unwrap_or("identity");
+    AssertionResult {
+        passed: body.encoded_size as u64 <= max,
+        description: format!("Encoded body <= {} bytes", max),
+        expected: format!("<={} bytes", max),
+        actual: format!(
+            "{} bytes {} ({} decoded)",
+            body.encoded_size,
+            encoding,
+            body.bytes.len()
+        ),
+        invoice_field: None,
+        failure_detail: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
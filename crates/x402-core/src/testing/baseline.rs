@@ -0,0 +1,271 @@
+// Baseline snapshots for `check --save-baseline` / `check --baseline`
+//
+// A baseline records the shape of a 402 response (status, invoice fields and
+// headers) minus volatile values, so later runs can report drift even when
+// every compliance check still passes.
+
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, WWW_AUTHENTICATE};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+/// Fields and headers that change on every response and are never compared
+pub const DEFAULT_VOLATILE_FIELDS: &[&str] = &[
+    "memo",
+    "timestamp",
+    "expires",
+    "expires_at",
+    "ts",
+    "date",
+    "content-length",
+    "etag",
+    "last-modified",
+    "set-cookie",
+    "x-request-id",
+];
+
+/// Snapshot of a 402 response with volatile fields removed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckBaseline {
+    pub url: String,
+    pub status: u16,
+    /// Parsed WWW-Authenticate invoice fields
+    pub invoice: BTreeMap<String, String>,
+    /// Response headers by lowercase name (WWW-Authenticate is in `invoice`)
+    pub headers: BTreeMap<String, String>,
+    /// Names excluded when this baseline was captured
+    #[serde(default)]
+    pub volatile_fields: Vec<String>,
+}
+
+/// How a field differs from the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// One difference between a baseline and the current response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineDiff {
+    /// `status`, `invoice.<field>` or `header.<name>`
+    pub field: String,
+    pub change: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+}
+
+impl CheckBaseline {
+    /// Capture a snapshot, dropping any field or header named in `volatile_fields`
+    pub fn capture(
+        url: &str,
+        status: u16,
+        headers: &HeaderMap,
+        invoice: &HashMap<String, String>,
+        volatile_fields: &[String],
+    ) -> Self {
+        let is_volatile = |name: &str| volatile_fields.iter().any(|v| v.eq_ignore_ascii_case(name));
+
+        let invoice = invoice
+            .iter()
+            .filter(|(k, _)| !is_volatile(k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+
+        let mut header_map = BTreeMap::new();
+        for name in headers.keys() {
+            if *name == WWW_AUTHENTICATE || is_volatile(name.as_str()) {
+                continue;
+            }
+            let value = headers
+                .get_all(name)
+                .iter()
+                .map(|v| String::from_utf8_lossy(v.as_bytes()).into_owned())
+                .collect::<Vec<_>>()
+                .join(", ");
+            header_map.insert(name.as_str().to_string(), value);
+        }
+
+        Self {
+            url: url.to_string(),
+            status,
+            invoice,
+            headers: header_map,
+            volatile_fields: volatile_fields.to_vec(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| {
+            format!(
+                "Cannot read baseline: {}\nFix: Create one first with --save-baseline {}",
+                path.display(),
+                path.display()
+            )
+        })?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid baseline file: {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write baseline: {}", path.display()))
+    }
+
+    /// Compare `current` against this baseline
+    ///
+    /// Fields volatile in either snapshot are skipped, so a baseline saved
+    /// with extra exclusions keeps honoring them.
+    pub fn diff(&self, current: &CheckBaseline) -> Vec<BaselineDiff> {
+        let ignored: BTreeSet<String> = self
+            .volatile_fields
+            .iter()
+            .chain(current.volatile_fields.iter())
+            .map(|f| f.to_ascii_lowercase())
+            .collect();
+
+        let mut diffs = Vec::new();
+
+        if self.status != current.status {
+            diffs.push(BaselineDiff {
+                field: "status".to_string(),
+                change: ChangeKind::Changed,
+                baseline: Some(self.status.to_string()),
+                current: Some(current.status.to_string()),
+            });
+        }
+
+        diff_maps(
+            "invoice",
+            &self.invoice,
+            &current.invoice,
+            &ignored,
+            &mut diffs,
+        );
+        diff_maps(
+            "header",
+            &self.headers,
+            &current.headers,
+            &ignored,
+            &mut diffs,
+        );
+
+        diffs
+    }
+}
+
+fn diff_maps(
+    prefix: &str,
+    baseline: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+    ignored: &BTreeSet<String>,
+    diffs: &mut Vec<BaselineDiff>,
+) {
+    let names: BTreeSet<&String> = baseline.keys().chain(current.keys()).collect();
+
+    for name in names {
+        if ignored.contains(&name.to_ascii_lowercase()) {
+            continue;
+        }
+        let (before, after) = (baseline.get(name), current.get(name));
+        let change = match (before, after) {
+            (Some(b), Some(a)) if b == a => continue,
+            (Some(_), Some(_)) => ChangeKind::Changed,
+            (None, Some(_)) => ChangeKind::Added,
+            (Some(_), None) => ChangeKind::Removed,
+            (None, None) => continue,
+        };
+        diffs.push(BaselineDiff {
+            field: format!("{}.{}", prefix, name),
+            change,
+            baseline: before.cloned(),
+            current: after.cloned(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn defaults() -> Vec<String> {
+        DEFAULT_VOLATILE_FIELDS
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn snapshot(amount: &str, memo: &str, extra_header: Option<&'static str>) -> CheckBaseline {
+        let mut headers = HeaderMap::new();
+        headers.insert("cache-control", HeaderValue::from_static("no-store"));
+        headers.insert(
+            "date",
+            HeaderValue::from_static("Thu, 01 Jan 2026 00:00:00 GMT"),
+        );
+        headers.insert("www-authenticate", HeaderValue::from_static("x402-solana"));
+        if let Some(value) = extra_header {
+            headers.insert("x-price-tier", HeaderValue::from_static(value));
+        }
+
+        let invoice = HashMap::from([
+            ("amount".to_string(), amount.to_string()),
+            ("memo".to_string(), memo.to_string()),
+            ("currency".to_string(), "USDC".to_string()),
+        ]);
+
+        CheckBaseline::capture("http://localhost/api", 402, &headers, &invoice, &defaults())
+    }
+
+    #[test]
+    fn test_capture_excludes_volatile_fields() {
+        let baseline = snapshot("0.01", "req-1", None);
+        assert!(!baseline.invoice.contains_key("memo"));
+        assert!(!baseline.headers.contains_key("date"));
+        assert!(!baseline.headers.contains_key("www-authenticate"));
+        assert_eq!(baseline.headers["cache-control"], "no-store");
+    }
+
+    #[test]
+    fn test_identical_shape_has_no_diff() {
+        let baseline = snapshot("0.01", "req-1", None);
+        assert!(baseline.diff(&snapshot("0.01", "req-2", None)).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_changed_and_added() {
+        let baseline = snapshot("0.01", "req-1", None);
+        let diffs = baseline.diff(&snapshot("0.05", "req-2", Some("gold")));
+
+        assert_eq!(
+            diffs,
+            vec![
+                BaselineDiff {
+                    field: "invoice.amount".to_string(),
+                    change: ChangeKind::Changed,
+                    baseline: Some("0.01".to_string()),
+                    current: Some("0.05".to_string()),
+                },
+                BaselineDiff {
+                    field: "header.x-price-tier".to_string(),
+                    change: ChangeKind::Added,
+                    baseline: None,
+                    current: Some("gold".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_baseline_exclusions_apply_to_later_runs() {
+        let mut baseline = snapshot("0.01", "req-1", None);
+        baseline.volatile_fields.push("amount".to_string());
+        assert!(baseline.diff(&snapshot("0.05", "req-2", None)).is_empty());
+    }
+}
@@ -0,0 +1,431 @@
+// Conformance battery of `x402-dev check --self-test` and `--battery`
+//
+// Each fixture is a canned 402-style response (status, headers, body) plus
+// the verdict `check` must reach on it: the checks that fail, with every
+// other recorded check passing. `check --self-test` serves the fixtures from
+// an in-process server and runs the full check against each, so a mismatch
+// is a bug in the toolkit. Facilitator implementers can serve the same
+// responses at `<base-url>/battery/<name>` and run `check --battery
+// <base-url>` to compare.
+
+use super::compliance::ComplianceReport;
+use chrono::{Duration, Utc};
+use std::fmt;
+
+/// Path prefix the fixtures are served under
+pub const BATTERY_PATH: &str = "/battery/";
+
+/// Invoice every fixture starts from: valid and in canonical order
+pub const CANONICAL_INVOICE: [(&str, &str); 5] = [
+    ("recipient", "7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU"),
+    ("amount", "0.01"),
+    ("currency", "USDC"),
+    ("memo", "req-550e8400-e29b-41d4-a716-446655440000"),
+    ("network", "devnet"),
+];
+
+/// Protocol identifier of the fixtures' invoices
+pub const BATTERY_PROTOCOL: &str = "x402-solana";
+
+/// Memo prefix of the fixtures' invoices
+pub const BATTERY_MEMO_PREFIX: &str = "req-";
+
+/// One canned response and the verdict `check` must reach on it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatteryFixture {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+    /// Checks that must fail; every other recorded check must pass
+    pub expected_failures: Vec<&'static str>,
+}
+
+impl BatteryFixture {
+    /// Path the fixture is served at, e.g. `/battery/valid`
+    pub fn path(&self) -> String {
+        format!("{}{}", BATTERY_PATH, self.name)
+    }
+
+    /// Differences between the expected verdict and `report`
+    pub fn compare(&self, report: &ComplianceReport) -> Vec<Mismatch> {
+        if report.checks.is_empty() {
+            return vec![Mismatch::NoChecks];
+        }
+        let mut mismatches = Vec::new();
+        for name in &self.expected_failures {
+            match report.checks.iter().find(|c| c.name == *name) {
+                None => mismatches.push(Mismatch::Missing {
+                    check: name.to_string(),
+                }),
+                Some(check) if check.passed => mismatches.push(Mismatch::UnexpectedPass {
+                    check: check.name.clone(),
+                    evidence: check.evidence.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for check in &report.checks {
+            if !check.passed && !self.expected_failures.contains(&check.name.as_str()) {
+                mismatches.push(Mismatch::UnexpectedFailure {
+                    check: check.name.clone(),
+                    evidence: check.evidence.clone(),
+                });
+            }
+        }
+        mismatches
+    }
+}
+
+/// How a check verdict differs from a fixture's expectation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mismatch {
+    /// A check that should have failed passed
+    UnexpectedPass { check: String, evidence: String },
+    /// A check that should have passed failed
+    UnexpectedFailure { check: String, evidence: String },
+    /// A check that should have failed was not run
+    Missing { check: String },
+    /// The report has no checks at all
+    NoChecks,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::UnexpectedPass { check, evidence } => {
+                write!(f, "'{}' passed ({}) but should fail", check, evidence)
+            }
+            Mismatch::UnexpectedFailure { check, evidence } => {
+                write!(f, "'{}' failed ({}) but should pass", check, evidence)
+            }
+            Mismatch::Missing { check } => write!(f, "'{}' was not checked", check),
+            Mismatch::NoChecks => write!(f, "no check was recorded"),
+        }
+    }
+}
+
+/// The canonical invoice with fields replaced (`Some`) or removed (`None`)
+///
+/// Fields not in the canonical invoice are appended in order.
+pub fn invoice_with(changes: &[(&str, Option<&str>)]) -> String {
+    let mut fields: Vec<(&str, &str)> = CANONICAL_INVOICE.to_vec();
+    for (name, value) in changes {
+        match (fields.iter().position(|(n, _)| n == name), value) {
+            (Some(i), Some(value)) => fields[i].1 = value,
+            (Some(i), None) => {
+                fields.remove(i);
+            }
+            (None, Some(value)) => fields.push((name, value)),
+            (None, None) => {}
+        }
+    }
+    let mut header = BATTERY_PROTOCOL.to_string();
+    for (name, value) in fields {
+        header.push_str(&format!(" {}={}", name, value));
+    }
+    header
+}
+
+/// Every fixture, the valid ones first
+///
+/// Fixtures with an expiry are built relative to the current time.
+pub fn fixtures() -> Vec<BatteryFixture> {
+    let in_an_hour = (Utc::now() + Duration::hours(1))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let no_amount_body = r#"{"error":"Payment Required"}"#;
+    let invoice_fixture = |name, description, changes: &[(&str, Option<&str>)], failures| {
+        fixture(
+            name,
+            description,
+            402,
+            Some(invoice_with(changes)),
+            "no-store",
+            no_amount_body,
+            failures,
+        )
+    };
+
+    vec![
+        fixture(
+            "valid",
+            "Canonical invoice, not cacheable, body amount matching the header",
+            402,
+            Some(invoice_with(&[])),
+            "no-store",
+            r#"{"error":"Payment Required","invoice":{"amount":"0.01"}}"#,
+            vec![],
+        ),
+        invoice_fixture(
+            "valid_with_expiry",
+            "Canonical invoice expiring in an hour",
+            &[("expires", Some(&in_an_hour))],
+            vec![],
+        ),
+        fixture(
+            "private_cache",
+            "Cache-Control: private keeps shared caches from storing the invoice",
+            402,
+            Some(invoice_with(&[])),
+            "private, max-age=60",
+            no_amount_body,
+            vec![],
+        ),
+        fixture(
+            "missing_header",
+            "402 without a WWW-Authenticate invoice",
+            402,
+            None,
+            "no-store",
+            no_amount_body,
+            vec!["WWW-Authenticate header"],
+        ),
+        fixture(
+            "wrong_status",
+            "Valid invoice on a 200 response",
+            200,
+            Some(invoice_with(&[])),
+            "no-store",
+            no_amount_body,
+            vec!["HTTP 402 status code"],
+        ),
+        fixture(
+            "cacheable",
+            "402 that shared caches may store for five minutes",
+            402,
+            Some(invoice_with(&[])),
+            "public, max-age=300",
+            no_amount_body,
+            vec!["Not cacheable"],
+        ),
+        fixture(
+            "unparseable_header",
+            "WWW-Authenticate with another scheme",
+            402,
+            Some("Bearer realm=\"api\"".to_string()),
+            "no-store",
+            no_amount_body,
+            vec!["Invoice header"],
+        ),
+        invoice_fixture(
+            "missing_recipient",
+            "Invoice without a recipient",
+            &[("recipient", None)],
+            vec!["Field 'recipient'"],
+        ),
+        invoice_fixture(
+            "missing_amount",
+            "Invoice without an amount",
+            &[("amount", None)],
+            vec!["Field 'amount'"],
+        ),
+        invoice_fixture(
+            "missing_memo",
+            "Invoice without a memo",
+            &[("memo", None)],
+            vec!["Field 'memo'"],
+        ),
+        invoice_fixture(
+            "invalid_recipient",
+            "Recipient with characters outside Base58",
+            &[("recipient", Some("0OIl0OIl0OIl0OIl0OIl0OIl0OIl0OIl"))],
+            vec!["Recipient address"],
+        ),
+        invoice_fixture(
+            "negative_amount",
+            "Negative amount",
+            &[("amount", Some("-0.01"))],
+            vec!["Amount"],
+        ),
+        invoice_fixture(
+            "excess_precision",
+            "Amount with more decimals than USDC has",
+            &[("amount", Some("0.0000001"))],
+            vec!["Amount"],
+        ),
+        invoice_fixture(
+            "wrong_currency",
+            "Currency other than USDC",
+            &[("currency", Some("USDT"))],
+            vec!["Currency"],
+        ),
+        invoice_fixture(
+            "wrong_memo_prefix",
+            "Memo without the req- prefix",
+            &[("memo", Some("order-550e8400-e29b-41d4-a716-446655440000"))],
+            vec!["Memo"],
+        ),
+        invoice_fixture(
+            "unknown_network",
+            "Network that is not a Solana cluster",
+            &[("network", Some("ethereum"))],
+            vec!["Network"],
+        ),
+        invoice_fixture(
+            "expired",
+            "Invoice that expired in 2020",
+            &[("expires", Some("2020-01-01T00:00:00Z"))],
+            vec!["Expiry"],
+        ),
+        invoice_fixture(
+            "invalid_expiry",
+            "Expiry that is not an RFC 3339 timestamp",
+            &[("expires", Some("tomorrow"))],
+            vec!["Expiry"],
+        ),
+        fixture(
+            "body_amount_mismatch",
+            "JSON body invoicing a different amount than the header",
+            402,
+            Some(invoice_with(&[])),
+            "no-store",
+            r#"{"error":"Payment Required","invoice":{"amount":"0.02"}}"#,
+            vec!["Body amount"],
+        ),
+    ]
+}
+
+fn fixture(
+    name: &'static str,
+    description: &'static str,
+    status: u16,
+    invoice: Option<String>,
+    cache_control: &str,
+    body: &str,
+    expected_failures: Vec<&'static str>,
+) -> BatteryFixture {
+    let mut headers = Vec::new();
+    if let Some(invoice) = invoice {
+        headers.push(("WWW-Authenticate", invoice));
+    }
+    headers.push(("Cache-Control", cache_control.to_string()));
+    headers.push(("Content-Type", "application/json".to_string()));
+    BatteryFixture {
+        name,
+        description,
+        status,
+        headers,
+        body: body.to_string(),
+        expected_failures,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{InvoiceHeader, PROTOCOL_SECTION};
+
+    fn fixture_named(name: &str) -> BatteryFixture {
+        fixtures().into_iter().find(|f| f.name == name).unwrap()
+    }
+
+    /// Report of a correct validator on the `cacheable` fixture
+    fn cacheable_report() -> ComplianceReport {
+        let mut report = ComplianceReport::new("http://127.0.0.1/battery/cacheable");
+        report.record(PROTOCOL_SECTION, "HTTP 402 status code", true, "402");
+        report.record(
+            PROTOCOL_SECTION,
+            "Not cacheable",
+            false,
+            "public, max-age=300",
+        );
+        report.record(PROTOCOL_SECTION, "WWW-Authenticate header", true, "present");
+        report.record("Invoice Structure", "Amount", true, "0.01 USDC");
+        report
+    }
+
+    #[test]
+    fn test_fixture_names_are_unique_paths() {
+        let fixtures = fixtures();
+        let mut names: Vec<_> = fixtures.iter().map(|f| f.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), fixtures.len());
+        assert_eq!(fixtures[0].path(), "/battery/valid");
+    }
+
+    #[test]
+    fn test_invoices_parse_except_where_meant_not_to() {
+        for fixture in fixtures() {
+            let Some((_, invoice)) = fixture
+                .headers
+                .iter()
+                .find(|(n, _)| *n == "WWW-Authenticate")
+            else {
+                continue;
+            };
+            let parsed = InvoiceHeader::parse_with_protocols(invoice, &[BATTERY_PROTOCOL]);
+            assert_eq!(
+                parsed.is_ok(),
+                fixture.name != "unparseable_header",
+                "{}: {}",
+                fixture.name,
+                invoice
+            );
+        }
+    }
+
+    #[test]
+    fn test_invoice_with_replaces_removes_and_appends() {
+        let invoice = invoice_with(&[
+            ("amount", Some("1.5")),
+            ("memo", None),
+            ("expires", Some("2030-01-01T00:00:00Z")),
+        ]);
+        assert_eq!(
+            invoice,
+            "x402-solana recipient=7xKXtg2CW87d97TXJSDpbD5jBkheTqA83TZRuJosgAsU amount=1.5 \
+             currency=USDC network=devnet expires=2030-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn test_matching_verdict_has_no_mismatches() {
+        assert!(fixture_named("cacheable")
+            .compare(&cacheable_report())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_broken_validator_is_detected() {
+        // A cache check that accepts everything
+        let mut report = cacheable_report();
+        report.checks[1].passed = true;
+        assert_eq!(
+            fixture_named("cacheable").compare(&report),
+            [Mismatch::UnexpectedPass {
+                check: "Not cacheable".to_string(),
+                evidence: "public, max-age=300".to_string(),
+            }]
+        );
+
+        // An amount check that rejects a valid amount
+        let mut report = cacheable_report();
+        report.checks[3].passed = false;
+        let mismatches = fixture_named("cacheable").compare(&report);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0].to_string(),
+            "'Amount' failed (0.01 USDC) but should pass"
+        );
+
+        // A validator that skips the cache check entirely
+        let mut report = cacheable_report();
+        report.checks.remove(1);
+        assert_eq!(
+            fixture_named("cacheable").compare(&report),
+            [Mismatch::Missing {
+                check: "Not cacheable".to_string()
+            }]
+        );
+
+        // A validator that records nothing passes nothing
+        let report = ComplianceReport::new("http://127.0.0.1/battery/valid");
+        assert_eq!(
+            fixture_named("valid").compare(&report),
+            [Mismatch::NoChecks]
+        );
+    }
+}
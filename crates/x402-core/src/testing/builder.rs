@@ -0,0 +1,455 @@
+// Programmatic test suite construction
+//
+// Builds the same TestSuite/Test/Expectations values the YAML parser
+// produces, so suites can be assembled and run from Rust without writing
+// YAML. `build` runs the parser's validation.
+
+use super::parser::{
+    AmountCondition, Budget, Expectations, HeaderAssertion, InvoiceExpectations, LatencyBudget,
+    RequestBody, Test, TestSuite,
+};
+use anyhow::Result;
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use x402_domain::DurationField;
+
+/// Builder for a [`TestSuite`]
+///
+/// ```no_run
+/// use x402_core::testing::{execute_test_suite, TestSuite};
+///
+/// # async fn run() -> anyhow::Result<()> {
+/// let suite = TestSuite::builder()
+///     .test("invoice for /api/data")
+///     .get("http://localhost:3402/api/data")
+///     .expect_status(402)
+///     .expect_invoice(|i| i.amount_eq("0.01").currency("USDC"))
+///     .test("health")
+///     .get("http://localhost:3402/health")
+///     .expect_status(200)
+///     .build()?;
+///
+/// let result = execute_test_suite(&suite).await?;
+/// assert_eq!(result.failed, 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct TestSuiteBuilder {
+    suite: TestSuite,
+    errors: Vec<String>,
+}
+
+impl TestSuite {
+    /// Start building a suite in Rust instead of YAML
+    pub fn builder() -> TestSuiteBuilder {
+        TestSuiteBuilder::default()
+    }
+}
+
+impl TestSuiteBuilder {
+    /// Value substituted for `${name}` in URLs, headers and bodies
+    pub fn variable(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.suite.variables.insert(name.into(), value.into());
+        self
+    }
+
+    /// Default per-request timeout
+    pub fn timeout_ms(mut self, ms: u64) -> Self {
+        self.suite.timeout_ms = Some(DurationField::from_millis(ms));
+        self
+    }
+
+    /// Time budget for the whole run; tests not started in time are skipped
+    pub fn total_timeout_ms(mut self, ms: u64) -> Self {
+        self.suite.total_timeout_ms = Some(DurationField::from_millis(ms));
+        self
+    }
+
+    /// Cap on the invoiced total, e.g. `budget("0.05", "USDC")`
+    pub fn budget(mut self, max_total: &str, currency: impl Into<String>) -> Self {
+        match parse_decimal("budget max_total", max_total) {
+            Ok(max_total) => {
+                self.suite.budget = Some(Budget {
+                    max_total,
+                    currency: currency.into(),
+                })
+            }
+            Err(e) => self.errors.push(e),
+        }
+        self
+    }
+
+    /// Default latency budget; a test's own bounds override it
+    pub fn latency(mut self, budget: LatencyBudget) -> Self {
+        self.suite.latency = Some(budget);
+        self
+    }
+
+    /// Start a test; it defaults to `GET` with no URL until one is set
+    pub fn test(self, name: impl Into<String>) -> TestBuilder {
+        TestBuilder {
+            suite: self,
+            test: Test {
+                name: name.into(),
+                url: String::new(),
+                method: "GET".to_string(),
+                tags: vec![],
+                headers: BTreeMap::new(),
+                body: None,
+                repeat: None,
+                timeout_ms: None,
+                expect: Expectations::default(),
+                extract: BTreeMap::new(),
+                then: None,
+                extends: None,
+            },
+        }
+    }
+
+    /// Validate and return the suite
+    ///
+    /// Fails on the first invalid value passed to a builder method, then on
+    /// anything [`TestSuite::validate`] rejects: an empty suite, duplicate
+    /// test names or malformed URLs.
+    pub fn build(self) -> Result<TestSuite> {
+        if let Some(error) = self.errors.into_iter().next() {
+            anyhow::bail!(error);
+        }
+        self.suite.validate()?;
+        Ok(self.suite)
+    }
+}
+
+/// Builder for one [`Test`], returned by [`TestSuiteBuilder::test`]
+///
+/// Calling [`test`](Self::test) or [`build`](Self::build) adds the test to
+/// the suite.
+#[derive(Debug)]
+pub struct TestBuilder {
+    suite: TestSuiteBuilder,
+    test: Test,
+}
+
+impl TestBuilder {
+    /// Send `method` to `url`
+    pub fn request(mut self, method: impl Into<String>, url: impl Into<String>) -> Self {
+        self.test.method = method.into().to_uppercase();
+        self.test.url = url.into();
+        self
+    }
+
+    pub fn get(self, url: impl Into<String>) -> Self {
+        self.request("GET", url)
+    }
+
+    pub fn head(self, url: impl Into<String>) -> Self {
+        self.request("HEAD", url)
+    }
+
+    pub fn post(self, url: impl Into<String>) -> Self {
+        self.request("POST", url)
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.test.tags.push(tag.into());
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.test.headers.insert(name.into(), value.into());
+        self
+    }
+
+    /// Text request body, sent as-is
+    pub fn body(mut self, text: impl Into<String>) -> Self {
+        self.test.body = Some(RequestBody::Text(text.into()));
+        self
+    }
+
+    /// JSON request body
+    pub fn json(mut self, value: serde_json::Value) -> Self {
+        self.test.body = Some(RequestBody::Json(value));
+        self
+    }
+
+    /// Send the request `times` times; assertions apply to the last response
+    pub fn repeat(mut self, times: u32) -> Self {
+        self.test.repeat = Some(times);
+        self
+    }
+
+    /// Per-request timeout, overriding the suite's
+    pub fn timeout_ms(mut self, ms: u64) -> Self {
+        self.test.timeout_ms = Some(DurationField::from_millis(ms));
+        self
+    }
+
+    pub fn expect_status(mut self, status: u16) -> Self {
+        self.test.expect.status = Some(status);
+        self
+    }
+
+    /// Status must be one of `statuses`
+    pub fn expect_status_in(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.test.expect.status_in = Some(statuses.into_iter().collect());
+        self
+    }
+
+    /// Header `name` must be present
+    pub fn expect_header(self, name: impl Into<String>) -> Self {
+        self.header_assertion(HeaderAssertion {
+            name: name.into(),
+            exists: Some(true),
+            value: None,
+            contains: None,
+            regex: None,
+        })
+    }
+
+    /// Header `name` must equal `value`
+    pub fn expect_header_value(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.header_assertion(HeaderAssertion {
+            name: name.into(),
+            exists: None,
+            value: Some(value.into()),
+            contains: None,
+            regex: None,
+        })
+    }
+
+    /// Any header assertion, as in the YAML `headers:` list
+    pub fn header_assertion(mut self, assertion: HeaderAssertion) -> Self {
+        self.test
+            .expect
+            .headers
+            .get_or_insert_with(Vec::new)
+            .push(assertion);
+        self
+    }
+
+    /// Response must arrive within `ms` milliseconds
+    pub fn expect_response_time_ms(mut self, ms: u64) -> Self {
+        self.test.expect.response_time_ms = Some(DurationField::from_millis(ms));
+        self
+    }
+
+    /// The exchange must take at most `ms` milliseconds, checked last
+    pub fn expect_max_duration_ms(mut self, ms: u64) -> Self {
+        self.test.expect.max_duration_ms = Some(DurationField::from_millis(ms));
+        self
+    }
+
+    /// The exchange must take at least `ms` milliseconds
+    pub fn expect_min_duration_ms(mut self, ms: u64) -> Self {
+        self.test.expect.min_duration_ms = Some(DurationField::from_millis(ms));
+        self
+    }
+
+    /// Response body must contain `text`
+    pub fn expect_body_contains(mut self, text: impl Into<String>) -> Self {
+        self.test.expect.body_contains = Some(text.into());
+        self
+    }
+
+    /// Typed assertions on the invoice, e.g. `|i| i.amount_eq("0.01")`
+    pub fn expect_invoice(
+        mut self,
+        configure: impl FnOnce(InvoiceExpectationsBuilder) -> InvoiceExpectationsBuilder,
+    ) -> Self {
+        let start = InvoiceExpectationsBuilder {
+            invoice: self.test.expect.invoice.take().unwrap_or_default(),
+            errors: vec![],
+        };
+        let built = configure(start);
+        self.suite.errors.extend(
+            built
+                .errors
+                .into_iter()
+                .map(|e| format!("Test '{}': {}", self.test.name, e)),
+        );
+        self.test.expect.invoice = Some(built.invoice);
+        self
+    }
+
+    /// Replace the expectations wholesale
+    pub fn expectations(mut self, expect: Expectations) -> Self {
+        self.test.expect = expect;
+        self
+    }
+
+    /// Add this test and start the next one
+    pub fn test(self, name: impl Into<String>) -> TestBuilder {
+        self.done().test(name)
+    }
+
+    /// Add this test and return to the suite builder
+    pub fn done(mut self) -> TestSuiteBuilder {
+        self.suite.suite.tests.push(self.test);
+        self.suite
+    }
+
+    /// Add this test and build the suite
+    pub fn build(self) -> Result<TestSuite> {
+        self.done().build()
+    }
+}
+
+/// Builder for [`InvoiceExpectations`], used by [`TestBuilder::expect_invoice`]
+///
+/// Amounts are decimal strings so they compare exactly; an unparseable
+/// amount fails [`TestSuiteBuilder::build`].
+#[derive(Debug)]
+pub struct InvoiceExpectationsBuilder {
+    invoice: InvoiceExpectations,
+    errors: Vec<String>,
+}
+
+impl InvoiceExpectationsBuilder {
+    /// Amount must equal `amount` exactly
+    pub fn amount_eq(self, amount: &str) -> Self {
+        self.amount("amount_eq", amount, |condition, value| {
+            condition.equals = Some(value)
+        })
+    }
+
+    /// Amount must be less than `amount`
+    pub fn amount_lt(self, amount: &str) -> Self {
+        self.amount("amount_lt", amount, |condition, value| {
+            condition.less_than = Some(value)
+        })
+    }
+
+    /// Amount must be greater than `amount`
+    pub fn amount_gt(self, amount: &str) -> Self {
+        self.amount("amount_gt", amount, |condition, value| {
+            condition.greater_than = Some(value)
+        })
+    }
+
+    fn amount(
+        mut self,
+        what: &str,
+        amount: &str,
+        set: impl FnOnce(&mut AmountCondition, Decimal),
+    ) -> Self {
+        match parse_decimal(what, amount) {
+            Ok(value) => set(
+                self.invoice.amount.get_or_insert_with(Default::default),
+                value,
+            ),
+            Err(e) => self.errors.push(e),
+        }
+        self
+    }
+
+    pub fn currency(mut self, currency: impl Into<String>) -> Self {
+        self.invoice.currency = Some(currency.into());
+        self
+    }
+
+    /// Recipient must be one of `recipients`
+    pub fn recipient_in<S: Into<String>>(
+        mut self,
+        recipients: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.invoice.recipient_in = Some(recipients.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn network(mut self, network: impl Into<String>) -> Self {
+        self.invoice.network = Some(network.into());
+        self
+    }
+
+    /// Memo must start with `prefix`
+    pub fn memo_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.invoice.memo_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Invoice must expire no later than `seconds` from now
+    pub fn expires_within_seconds(mut self, seconds: u64) -> Self {
+        self.invoice.expires_within_seconds = Some(DurationField::from_secs(seconds));
+        self
+    }
+}
+
+fn parse_decimal(what: &str, text: &str) -> Result<Decimal, String> {
+    Decimal::from_str(text.trim())
+        .map_err(|_| format!("{}: invalid decimal amount '{}'", what, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_matches_yaml() {
+        let built = TestSuite::builder()
+            .variable("base", "http://localhost:3402")
+            .test("paid")
+            .get("${base}/api/data")
+            .tag("fast")
+            .expect_status(402)
+            .expect_header("WWW-Authenticate")
+            .expect_invoice(|i| i.amount_eq("0.01").currency("USDC"))
+            .build()
+            .unwrap();
+        let parsed = <TestSuite as FromStr>::from_str(
+            r#"
+variables:
+  base: http://localhost:3402
+tests:
+  - name: paid
+    url: ${base}/api/data
+    tags: [fast]
+    expect:
+      status: 402
+      headers:
+        - name: WWW-Authenticate
+          exists: true
+      invoice:
+        amount:
+          equals: "0.01"
+        currency: USDC
+"#,
+        )
+        .unwrap();
+        assert_eq!(built.to_yaml().unwrap(), parsed.to_yaml().unwrap());
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_suites() {
+        let err = TestSuite::builder().build().unwrap_err();
+        assert!(err.to_string().contains("at least one test"));
+
+        let err = TestSuite::builder()
+            .test("a")
+            .get("http://localhost:3402/")
+            .test("a")
+            .get("http://localhost:3402/other")
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("Duplicate test name 'a'"));
+
+        let err = TestSuite::builder()
+            .test("no url")
+            .expect_status(200)
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid URL"));
+
+        let err = TestSuite::builder()
+            .test("bad amount")
+            .get("http://localhost:3402/")
+            .expect_invoice(|i| i.amount_eq("ten cents"))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Test 'bad amount': amount_eq: invalid decimal amount 'ten cents'"
+        );
+    }
+}
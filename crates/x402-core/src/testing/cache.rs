@@ -0,0 +1,126 @@
+// Cacheability check for 402 responses
+//
+// A 402 response carries a single-use invoice (memo + expiry). If shared
+// caches are allowed to store it, clients can be handed stale invoices.
+
+use reqwest::header::{HeaderMap, CACHE_CONTROL, EXPIRES, VARY};
+
+/// Outcome of inspecting the caching headers of a 402 response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheCheck {
+    /// True if the response opts out of shared caching
+    pub passed: bool,
+    /// What was found in the headers
+    pub detail: String,
+    /// How to fix the response (only set on failure)
+    pub suggestion: Option<String>,
+}
+
+const NO_STORE_SUGGESTION: &str =
+    "Send 'Cache-Control: no-store' on 402 responses so intermediaries never serve stale invoices";
+
+/// Inspect Cache-Control, Expires and Vary on a 402 response
+///
+/// Passes only when Cache-Control contains `no-store` or `private`.
+pub fn check_cache_headers(headers: &HeaderMap) -> CacheCheck {
+    let directives: Vec<String> = headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|d| d.trim().to_ascii_lowercase())
+        .filter(|d| !d.is_empty())
+        .collect();
+
+    let has = |name: &str| {
+        directives
+            .iter()
+            .any(|d| d == name || d.starts_with(&format!("{}=", name)))
+    };
+
+    if has("no-store") {
+        return CacheCheck {
+            passed: true,
+            detail: "no-store".to_string(),
+            suggestion: None,
+        };
+    }
+
+    if has("private") {
+        return CacheCheck {
+            passed: true,
+            detail: "private (not stored by shared caches)".to_string(),
+            suggestion: None,
+        };
+    }
+
+    let mut reasons = Vec::new();
+    if directives.is_empty() {
+        reasons.push("missing Cache-Control".to_string());
+    } else {
+        reasons.push(format!("Cache-Control: {}", directives.join(", ")));
+        if has("public") || has("s-maxage") || has("max-age") {
+            reasons.push("cacheable by shared caches".to_string());
+        }
+    }
+    if headers.contains_key(EXPIRES) {
+        reasons.push("Expires header allows caching".to_string());
+    }
+    if let Some(vary) = headers.get(VARY).and_then(|v| v.to_str().ok()) {
+        reasons.push(format!("Vary: {} does not prevent shared caching", vary));
+    }
+
+    CacheCheck {
+        passed: false,
+        detail: reasons.join("; "),
+        suggestion: Some(NO_STORE_SUGGESTION.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    #[test]
+    fn test_no_store_passes() {
+        let check = check_cache_headers(&headers(&[("cache-control", "no-store")]));
+        assert!(check.passed);
+        assert!(check.suggestion.is_none());
+    }
+
+    #[test]
+    fn test_private_passes() {
+        let check = check_cache_headers(&headers(&[("cache-control", "private, max-age=60")]));
+        assert!(check.passed);
+    }
+
+    #[test]
+    fn test_cacheable_upstream_fails() {
+        let check = check_cache_headers(&headers(&[
+            ("cache-control", "public, max-age=300"),
+            ("expires", "Thu, 01 Dec 2099 16:00:00 GMT"),
+            ("vary", "Accept"),
+        ]));
+        assert!(!check.passed);
+        assert!(check.detail.contains("cacheable by shared caches"));
+        assert!(check.detail.contains("Expires"));
+        assert!(check.detail.contains("Vary: Accept"));
+        assert!(check.suggestion.unwrap().contains("no-store"));
+    }
+
+    #[test]
+    fn test_missing_cache_control_fails() {
+        let check = check_cache_headers(&HeaderMap::new());
+        assert!(!check.passed);
+        assert_eq!(check.detail, "missing Cache-Control");
+    }
+}
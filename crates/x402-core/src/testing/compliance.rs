@@ -0,0 +1,389 @@
+// Compliance report of `x402-dev check` and the artifacts derived from it
+//
+// Every named check of a run is recorded in a `ComplianceReport` with its
+// section, result and the evidence shown for it. The report grades the
+// endpoint, and renders an SVG badge (`--badge`), a markdown summary
+// (`--summary`) and canonical JSON (`--report`). All depend only on the
+// report, never on the time or the network, so identical results give
+// byte-identical files to commit.
+
+use crate::canonical::to_canonical_string;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Section of the checks every compliant endpoint must pass first
+pub const PROTOCOL_SECTION: &str = "Protocol Validation";
+
+/// Section of the checks `check --strict` adds
+pub const STRICT_SECTION: &str = "Strict Protocol";
+
+/// Longest evidence snippet in the summary, in characters
+const MAX_EVIDENCE_CHARS: usize = 80;
+
+/// One named check and what it found
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComplianceCheck {
+    pub section: String,
+    pub name: String,
+    pub passed: bool,
+    /// What the check saw, e.g. `0.01 USDC` or `got 200`
+    pub evidence: String,
+}
+
+/// Overall result of a compliance run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplianceGrade {
+    /// Every check passed, including the strict protocol checks
+    Strict,
+    /// Every check passed
+    Compliant,
+    /// The protocol basics passed, other checks failed
+    Partial,
+    /// A protocol basic (402 status, invoice header, ...) failed
+    Failing,
+}
+
+impl ComplianceGrade {
+    pub const ALL: [ComplianceGrade; 4] = [
+        ComplianceGrade::Strict,
+        ComplianceGrade::Compliant,
+        ComplianceGrade::Partial,
+        ComplianceGrade::Failing,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ComplianceGrade::Strict => "strict",
+            ComplianceGrade::Compliant => "compliant",
+            ComplianceGrade::Partial => "partial",
+            ComplianceGrade::Failing => "failing",
+        }
+    }
+
+    /// Badge color (shields.io palette)
+    pub fn color(&self) -> &'static str {
+        match self {
+            ComplianceGrade::Strict => "#4c1",
+            ComplianceGrade::Compliant => "#97ca00",
+            ComplianceGrade::Partial => "#dfb317",
+            ComplianceGrade::Failing => "#e05d44",
+        }
+    }
+}
+
+impl fmt::Display for ComplianceGrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Checks of one `x402-dev check` run against `url`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComplianceReport {
+    pub url: String,
+    pub checks: Vec<ComplianceCheck>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl ComplianceReport {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            checks: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Record a check; returns `passed`
+    pub fn record(
+        &mut self,
+        section: &str,
+        name: impl Into<String>,
+        passed: bool,
+        evidence: impl Into<String>,
+    ) -> bool {
+        self.checks.push(ComplianceCheck {
+            section: section.to_string(),
+            name: name.into(),
+            passed,
+            evidence: evidence.into(),
+        });
+        passed
+    }
+
+    pub fn passed(&self) -> usize {
+        self.checks.iter().filter(|c| c.passed).count()
+    }
+
+    pub fn total(&self) -> usize {
+        self.checks.len()
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.checks.iter().all(|c| c.passed)
+    }
+
+    /// Strict when everything passed with strict checks run, compliant
+    /// when everything passed, partial when only non-protocol checks failed
+    pub fn grade(&self) -> ComplianceGrade {
+        let protocol_ok = self
+            .checks
+            .iter()
+            .filter(|c| c.section == PROTOCOL_SECTION)
+            .all(|c| c.passed);
+        if self.checks.is_empty() || !protocol_ok {
+            ComplianceGrade::Failing
+        } else if !self.all_passed() {
+            ComplianceGrade::Partial
+        } else if self.checks.iter().any(|c| c.section == STRICT_SECTION) {
+            ComplianceGrade::Strict
+        } else {
+            ComplianceGrade::Compliant
+        }
+    }
+
+    /// Badge message: the grade, plus the count when something failed
+    pub fn badge_message(&self) -> String {
+        if self.all_passed() && !self.checks.is_empty() {
+            self.grade().to_string()
+        } else {
+            format!("{} {}/{}", self.grade(), self.passed(), self.total())
+        }
+    }
+
+    /// Flat shields-style SVG badge reading `x402 | <grade>`
+    pub fn render_badge(&self) -> String {
+        render_badge("x402", &self.badge_message(), self.grade().color())
+    }
+
+    /// Canonical JSON of the grade, the counts and every check, with a
+    /// `content_hash`
+    pub fn render_json(&self) -> String {
+        let report = serde_json::json!({
+            "url": self.url,
+            "status": if self.all_passed() { "pass" } else { "fail" },
+            "grade": self.grade(),
+            "checks_passed": self.passed(),
+            "checks_total": self.total(),
+            "checks": self.checks,
+            "warnings": self.warnings,
+        });
+        to_canonical_string(&report).unwrap_or_default() + "\n"
+    }
+
+    /// Markdown summary: the result, then one table row per check
+    pub fn render_summary(&self) -> String {
+        let mut out = String::from("# x402 Compliance\n\n");
+        out.push_str(&format!("- **Endpoint:** `{}`\n", code_span(&self.url)));
+        out.push_str(&format!(
+            "- **Grade:** {} ({}/{} checks passed)\n\n",
+            self.grade(),
+            self.passed(),
+            self.total()
+        ));
+        out.push_str("| Section | Check | Result | Evidence |\n");
+        out.push_str("|---------|-------|--------|----------|\n");
+        for check in &self.checks {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                table_cell(&check.section),
+                table_cell(&check.name),
+                if check.passed { "✅ pass" } else { "❌ fail" },
+                evidence_cell(&check.evidence)
+            ));
+        }
+        if !self.warnings.is_empty() {
+            out.push_str("\n## Warnings\n\n");
+            for warning in &self.warnings {
+                out.push_str(&format!("- {}\n", one_line(warning)));
+            }
+        }
+        out
+    }
+}
+
+/// Badge with `label` on grey and `message` on `color`
+///
+/// Text widths come from a fixed per-character estimate of 11px Verdana,
+/// so the output depends on the strings alone.
+fn render_badge(label: &str, message: &str, color: &str) -> String {
+    let label_width = text_width(label) + 10;
+    let message_width = text_width(message) + 10;
+    let width = label_width + message_width;
+    let (label, message) = (xml_escape(label), xml_escape(message));
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">"#,
+            "\n  <title>{label}: {message}</title>",
+            "\n  <linearGradient id=\"s\" x2=\"0\" y2=\"100%\"><stop offset=\"0\" stop-color=\"#bbb\" stop-opacity=\".1\"/><stop offset=\"1\" stop-opacity=\".1\"/></linearGradient>",
+            "\n  <clipPath id=\"r\"><rect width=\"{width}\" height=\"20\" rx=\"3\" fill=\"#fff\"/></clipPath>",
+            "\n  <g clip-path=\"url(#r)\">",
+            "\n    <rect width=\"{label_width}\" height=\"20\" fill=\"#555\"/>",
+            "\n    <rect x=\"{label_width}\" width=\"{message_width}\" height=\"20\" fill=\"{color}\"/>",
+            "\n    <rect width=\"{width}\" height=\"20\" fill=\"url(#s)\"/>",
+            "\n  </g>",
+            "\n  <g fill=\"#fff\" text-anchor=\"middle\" font-family=\"Verdana,Geneva,DejaVu Sans,sans-serif\" font-size=\"11\">",
+            "\n    <text x=\"{label_x}\" y=\"14\">{label}</text>",
+            "\n    <text x=\"{message_x}\" y=\"14\">{message}</text>",
+            "\n  </g>",
+            "\n</svg>\n"
+        ),
+        width = width,
+        label = label,
+        message = message,
+        label_width = label_width,
+        message_width = message_width,
+        color = color,
+        label_x = label_width / 2,
+        message_x = label_width + message_width / 2,
+    )
+}
+
+/// Approximate width of `text` in 11px Verdana
+fn text_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c {
+            'i' | 'l' | 'j' | '.' | ',' | ':' | '\'' | '|' | '!' => 4,
+            'f' | 't' | 'r' | ' ' | '/' | '(' | ')' | '-' => 5,
+            'm' | 'w' | 'M' | 'W' => 11,
+            c if c.is_ascii_uppercase() || c.is_ascii_digit() => 8,
+            _ => 7,
+        })
+        .sum()
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `text` on one line
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `text` on one line with table pipes escaped
+fn table_cell(text: &str) -> String {
+    one_line(text).replace('|', "\\|")
+}
+
+/// Backticks can't be escaped inside a code span; show them as quotes
+fn code_span(text: &str) -> String {
+    text.replace('`', "'")
+}
+
+/// Evidence as a code span, cut to [`MAX_EVIDENCE_CHARS`]
+fn evidence_cell(evidence: &str) -> String {
+    let evidence = table_cell(evidence);
+    if evidence.is_empty() {
+        return String::new();
+    }
+    let snippet: String = if evidence.chars().count() > MAX_EVIDENCE_CHARS {
+        let cut: String = evidence.chars().take(MAX_EVIDENCE_CHARS - 1).collect();
+        format!("{}…", cut.trim_end())
+    } else {
+        evidence
+    };
+    format!("`{}`", code_span(&snippet))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Report graded `grade`
+    fn report(grade: ComplianceGrade) -> ComplianceReport {
+        let mut report = ComplianceReport::new("http://localhost:3402/api/data");
+        let protocol_ok = grade != ComplianceGrade::Failing;
+        report.record(PROTOCOL_SECTION, "HTTP 402 status code", protocol_ok, "402");
+        let rest_ok = matches!(grade, ComplianceGrade::Strict | ComplianceGrade::Compliant);
+        report.record("Invoice Structure", "Amount", rest_ok, "0.01 USDC");
+        if grade == ComplianceGrade::Strict {
+            report.record(STRICT_SECTION, "Memo format", true, "req-<uuid>");
+        }
+        report
+    }
+
+    #[test]
+    fn test_grades() {
+        for grade in ComplianceGrade::ALL {
+            assert_eq!(report(grade).grade(), grade);
+        }
+        let empty = ComplianceReport::new("http://x");
+        assert_eq!(empty.grade(), ComplianceGrade::Failing);
+        assert_eq!(empty.badge_message(), "failing 0/0");
+    }
+
+    #[test]
+    fn test_json_report_is_reproducible_and_hashed() {
+        let json = report(ComplianceGrade::Partial).render_json();
+        assert_eq!(json, report(ComplianceGrade::Partial).render_json());
+        assert!(crate::canonical::verify_report(&json).unwrap().matches());
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["grade"], "partial");
+        assert_eq!(value["checks_passed"], 1);
+    }
+
+    #[test]
+    fn test_badge_for_each_grade_is_valid_svg() {
+        for grade in ComplianceGrade::ALL {
+            let report = report(grade);
+            let svg = report.render_badge();
+
+            // Well-formed: one root element, every tag closed
+            assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+            assert!(svg.ends_with("</svg>\n"));
+            for tag in ["svg", "title", "g", "text", "clipPath", "linearGradient"] {
+                let opened = svg.matches(&format!("<{} ", tag)).count()
+                    + svg.matches(&format!("<{}>", tag)).count();
+                let closed = svg.matches(&format!("</{}>", tag)).count();
+                assert!(opened > 0 && opened == closed, "<{}> in {}", tag, svg);
+            }
+            let empty = svg.matches("<rect ").count() + svg.matches("<stop ").count();
+            assert_eq!(empty, svg.matches("/>").count());
+
+            // Grade text and color
+            let message = report.badge_message();
+            assert!(message.starts_with(grade.as_str()));
+            assert!(svg.contains(&format!(">{}</text>", message)), "{}", svg);
+            assert!(svg.contains(&format!("fill=\"{}\"", grade.color())));
+            assert!(svg.contains(&format!("aria-label=\"x402: {}\"", message)));
+
+            // Same report, same bytes
+            assert_eq!(svg, report.clone().render_badge());
+        }
+        assert_eq!(
+            report(ComplianceGrade::Partial).badge_message(),
+            "partial 1/2"
+        );
+    }
+
+    #[test]
+    fn test_badge_width_follows_message() {
+        let width = |svg: &str| -> usize {
+            let start = svg.find("width=\"").unwrap() + 7;
+            svg[start..start + svg[start..].find('"').unwrap()]
+                .parse()
+                .unwrap()
+        };
+        let short = render_badge("x402", "strict", "#4c1");
+        let long = render_badge("x402", "partial 12/14", "#dfb317");
+        assert!(width(&long) > width(&short));
+        assert!(render_badge("a<b", "\"&\"", "#4c1").contains("a&lt;b: &quot;&amp;&quot;"));
+    }
+
+    #[test]
+    fn test_evidence_cells() {
+        assert_eq!(evidence_cell(""), "");
+        assert_eq!(evidence_cell("a | b\nc"), "`a \\| b c`");
+        assert_eq!(evidence_cell("uses `x`"), "`uses 'x'`");
+        let long = evidence_cell(&"x".repeat(200));
+        assert_eq!(long.chars().count(), MAX_EVIDENCE_CHARS + 2);
+        assert!(long.ends_with("…`"));
+    }
+}
@@ -0,0 +1,505 @@
+// Suite composition: `import:`, `fragments:` and `extends:`
+//
+// A suite (or any file it imports) may define named fragments: partial
+// tests such as a shared `expect:` block. A test with `extends: <name>` is
+// the fragment deep-merged with the test's own keys, and fragments may
+// extend other fragments. `import:` pulls the fragments (only) of other
+// files, resolved relative to the importing file.
+//
+// Merging is deterministic:
+// - mappings merge key by key, recursively
+// - the test's scalars replace the fragment's (`null` clears a value)
+// - lists append: the fragment's items, then the test's
+// - a list or mapping tagged `!replace` replaces the fragment's instead
+//
+// Composition runs on the parsed value before it becomes a TestSuite, so
+// suites without these keys are parsed exactly as before.
+
+use crate::format::DetectedFormat;
+use anyhow::{bail, Context, Result};
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Tag that makes a test value replace the fragment's instead of merging
+const REPLACE_TAG: &str = "replace";
+
+/// A named fragment and the file that defined it
+struct Fragment {
+    value: Mapping,
+    file: Source,
+}
+
+/// Text and display name of a suite file, for error locations
+#[derive(Clone)]
+struct Source {
+    name: String,
+    text: String,
+}
+
+/// True if `doc` uses `import:`, `fragments:` or a test's `extends:`
+pub(crate) fn uses_composition(doc: &Value) -> bool {
+    doc.get("import").is_some()
+        || doc.get("fragments").is_some()
+        || doc
+            .get("tests")
+            .and_then(Value::as_sequence)
+            .is_some_and(|tests| tests.iter().any(|test| test.get("extends").is_some()))
+}
+
+/// Resolve imports and `extends:` in the suite `doc` parsed from `text`
+///
+/// `path` is the suite file; without one, imports are resolved from the
+/// working directory. The result has no `import:` or `fragments:` keys,
+/// and each extended test keeps `extends:` naming its fragment.
+pub(crate) fn compose(mut doc: Value, text: &str, path: Option<&Path>) -> Result<Value> {
+    let source = Source {
+        name: path.map_or_else(|| "<suite>".to_string(), |p| p.display().to_string()),
+        text: text.to_string(),
+    };
+    let mut fragments = BTreeMap::new();
+    let mut loader = Loader {
+        stack: path
+            .and_then(|p| p.canonicalize().ok())
+            .into_iter()
+            .collect(),
+        loaded: BTreeSet::new(),
+    };
+    let dir = path
+        .and_then(Path::parent)
+        .map_or_else(PathBuf::new, Path::to_path_buf);
+    loader.collect(&doc, &source, &dir, &mut fragments)?;
+
+    let Some(root) = doc.as_mapping_mut() else {
+        return Ok(doc);
+    };
+    root.remove("import");
+    root.remove("fragments");
+    let Some(tests) = root.get_mut("tests").and_then(Value::as_sequence_mut) else {
+        return Ok(doc);
+    };
+    for test in tests {
+        let Some(name) = test.get("extends").cloned() else {
+            continue;
+        };
+        let Some(name) = name.as_str() else {
+            bail!(
+                "extends must be a fragment name{}",
+                location(&source, "extends:")
+            );
+        };
+        let base = resolve(name, &fragments, &source, &mut Vec::new())?;
+        let Value::Mapping(local) = std::mem::take(test) else {
+            bail!("Tests must be mappings{}", location(&source, name));
+        };
+        let mut merged = merge(Value::Mapping(base), Value::Mapping(local));
+        if let Value::Mapping(map) = &mut merged {
+            map.insert("extends".into(), name.into());
+        }
+        *test = merged;
+    }
+    Ok(doc)
+}
+
+/// Follows imports depth-first, detecting cycles
+struct Loader {
+    /// Files being imported, outermost first
+    stack: Vec<PathBuf>,
+    /// Files whose fragments were already collected (diamond imports)
+    loaded: BTreeSet<PathBuf>,
+}
+
+impl Loader {
+    /// Add the fragments of `doc`'s imports, then its own
+    fn collect(
+        &mut self,
+        doc: &Value,
+        source: &Source,
+        dir: &Path,
+        fragments: &mut BTreeMap<String, Fragment>,
+    ) -> Result<()> {
+        for import in imports(doc, source)? {
+            let path = dir.join(&import);
+            let canonical = path.canonicalize().with_context(|| {
+                format!(
+                    "Cannot import {}{}",
+                    path.display(),
+                    location(source, &import)
+                )
+            })?;
+            if self.stack.contains(&canonical) {
+                let cycle: Vec<String> = self
+                    .stack
+                    .iter()
+                    .skip_while(|p| **p != canonical)
+                    .chain([&canonical])
+                    .map(|p| file_name(p))
+                    .collect();
+                bail!(
+                    "Import cycle: {}{}\nFix: Move the shared fragments into a file neither imports",
+                    cycle.join(" -> "),
+                    location(source, &import)
+                );
+            }
+            if !self.loaded.insert(canonical.clone()) {
+                continue;
+            }
+
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let imported: Value = DetectedFormat::detect(Some(&path), &text)
+                .parse(&text)
+                .with_context(|| format!("Invalid import {}", path.display()))?;
+            let imported_source = Source {
+                name: path.display().to_string(),
+                text,
+            };
+            self.stack.push(canonical);
+            let imported_dir = path.parent().unwrap_or(dir).to_path_buf();
+            self.collect(&imported, &imported_source, &imported_dir, fragments)?;
+            self.stack.pop();
+        }
+
+        let Some(defined) = doc.get("fragments") else {
+            return Ok(());
+        };
+        let Some(defined) = defined.as_mapping() else {
+            bail!(
+                "fragments must map names to partial tests{}",
+                location(source, "fragments:")
+            );
+        };
+        for (name, value) in defined {
+            let Some(name) = name.as_str() else {
+                bail!(
+                    "Fragment names must be strings{}",
+                    location(source, "fragments:")
+                );
+            };
+            let Some(value) = value.as_mapping() else {
+                bail!(
+                    "Fragment '{}' must be a mapping of test keys{}",
+                    name,
+                    location(source, &format!("{}:", name))
+                );
+            };
+            if let Some(existing) = fragments.get(name) {
+                bail!(
+                    "Fragment '{}' is defined in both {} and {}\nFix: Rename one of them",
+                    name,
+                    existing.file.name,
+                    source.name
+                );
+            }
+            fragments.insert(
+                name.to_string(),
+                Fragment {
+                    value: value.clone(),
+                    file: source.clone(),
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+/// `import:` entries of `doc` (one path or a list)
+fn imports(doc: &Value, source: &Source) -> Result<Vec<String>> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "import must be a file path or a list of them{}",
+            location(source, "import:")
+        )
+    };
+    match doc.get("import") {
+        None => Ok(vec![]),
+        Some(Value::String(path)) => Ok(vec![path.clone()]),
+        Some(Value::Sequence(paths)) => paths
+            .iter()
+            .map(|p| p.as_str().map(str::to_string).ok_or_else(invalid))
+            .collect(),
+        Some(_) => Err(invalid()),
+    }
+}
+
+/// Fragment `name` with its own `extends:` chain merged in
+fn resolve(
+    name: &str,
+    fragments: &BTreeMap<String, Fragment>,
+    referrer: &Source,
+    chain: &mut Vec<String>,
+) -> Result<Mapping> {
+    let Some(fragment) = fragments.get(name) else {
+        let known: Vec<&str> = fragments.keys().map(String::as_str).collect();
+        bail!(
+            "Unknown fragment '{}'{}\nFix: Define it under fragments: or import the file that does (known: {})",
+            name,
+            location(referrer, &format!("extends: {}", name)),
+            if known.is_empty() { "none".to_string() } else { known.join(", ") }
+        );
+    };
+    if chain.iter().any(|n| n == name) {
+        chain.push(name.to_string());
+        bail!("Fragment cycle: {}", chain.join(" -> "));
+    }
+    chain.push(name.to_string());
+
+    let mut value = fragment.value.clone();
+    let Some(parent) = value.remove("extends") else {
+        return Ok(value);
+    };
+    let Some(parent) = parent.as_str() else {
+        bail!(
+            "extends must be a fragment name{}",
+            location(&fragment.file, &format!("{}:", name))
+        );
+    };
+    let base = resolve(parent, fragments, &fragment.file, chain)?;
+    match merge(Value::Mapping(base), Value::Mapping(value)) {
+        Value::Mapping(merged) => Ok(merged),
+        _ => unreachable!("merging two mappings gives a mapping"),
+    }
+}
+
+/// Deep-merge `local` over `base` (see the module comment)
+fn merge(base: Value, local: Value) -> Value {
+    match (base, local) {
+        (_, Value::Tagged(tagged)) if tagged.tag == REPLACE_TAG => untag(tagged.value),
+        (Value::Mapping(mut base), Value::Mapping(local)) => {
+            for (key, value) in local {
+                let merged = match base.get_mut(&key) {
+                    Some(existing) => merge(std::mem::take(existing), value),
+                    None => untag(value),
+                };
+                base.insert(key, merged);
+            }
+            Value::Mapping(base)
+        }
+        (Value::Sequence(mut base), Value::Sequence(local)) => {
+            base.extend(local.into_iter().map(untag));
+            Value::Sequence(base)
+        }
+        (_, local) => untag(local),
+    }
+}
+
+/// Drop `!replace` tags that had nothing to replace
+fn untag(value: Value) -> Value {
+    match value {
+        Value::Tagged(tagged) if tagged.tag == REPLACE_TAG => untag(tagged.value),
+        Value::Mapping(map) => {
+            Value::Mapping(map.into_iter().map(|(k, v)| (k, untag(v))).collect())
+        }
+        Value::Sequence(items) => Value::Sequence(items.into_iter().map(untag).collect()),
+        other => other,
+    }
+}
+
+/// ` (file:line)` of the first line containing `needle`, ignoring quotes
+fn location(source: &Source, needle: &str) -> String {
+    let line = source
+        .text
+        .lines()
+        .position(|line| line.replace(['"', '\''], "").contains(needle));
+    match line {
+        Some(index) => format!(" ({}:{})", source.name, index + 1),
+        None => format!(" ({})", source.name),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(
+        || path.display().to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::TestSuite;
+    use std::str::FromStr;
+
+    const FRAGMENTS: &str = r#"
+fragments:
+  valid-402:
+    tags: [x402]
+    expect:
+      status: 402
+      headers:
+        - name: WWW-Authenticate
+          exists: true
+      body_contains: invoice
+"#;
+
+    #[test]
+    fn test_override_precedence() {
+        let suite = <TestSuite as FromStr>::from_str(&format!(
+            r#"{}
+tests:
+  - name: "Premium"
+    url: "http://localhost:3402/api/premium"
+    extends: valid-402
+    method: POST
+    expect:
+      body_contains: premium
+      response_time_ms: 500
+  - name: "No body check"
+    url: "http://localhost:3402/api/data"
+    extends: valid-402
+    expect:
+      body_contains: null
+"#,
+            FRAGMENTS
+        ))
+        .unwrap();
+
+        let premium = &suite.tests[0];
+        assert_eq!(premium.extends.as_deref(), Some("valid-402"));
+        assert_eq!(premium.method, "POST");
+        assert_eq!(premium.expect.status, Some(402));
+        assert_eq!(premium.expect.body_contains.as_deref(), Some("premium"));
+        assert_eq!(
+            premium.expect.response_time_ms.map(|t| t.as_millis()),
+            Some(500)
+        );
+        assert_eq!(suite.tests[1].expect.body_contains, None);
+        assert_eq!(suite.tests[1].expect.status, Some(402));
+
+        // The composed suite serializes without fragments and re-parses
+        let reparsed = <TestSuite as FromStr>::from_str(&suite.to_yaml().unwrap()).unwrap();
+        assert_eq!(reparsed.tests[0].expect.status, Some(402));
+        assert_eq!(reparsed.tests[0].extends, None);
+    }
+
+    #[test]
+    fn test_lists_append_unless_replaced() {
+        let suite = <TestSuite as FromStr>::from_str(&format!(
+            r#"{}
+  paid:
+    extends: valid-402
+    tags: [paid]
+tests:
+  - name: "Appends"
+    url: "http://localhost:3402/a"
+    extends: paid
+    tags: [smoke]
+    expect:
+      headers:
+        - name: Cache-Control
+          value: no-store
+  - name: "Replaces"
+    url: "http://localhost:3402/b"
+    extends: paid
+    tags: !replace [only]
+    expect:
+      headers: !replace
+        - name: X-Custom
+          exists: true
+"#,
+            FRAGMENTS
+        ))
+        .unwrap();
+
+        let appended = &suite.tests[0];
+        assert_eq!(appended.tags, ["x402", "paid", "smoke"]);
+        let headers: Vec<&str> = appended
+            .expect
+            .headers
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|h| h.name.as_str())
+            .collect();
+        assert_eq!(headers, ["WWW-Authenticate", "Cache-Control"]);
+        assert_eq!(appended.extends.as_deref(), Some("paid"));
+
+        let replaced = &suite.tests[1];
+        assert_eq!(replaced.tags, ["only"]);
+        let headers = replaced.expect.headers.as_ref().unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].name, "X-Custom");
+    }
+
+    #[test]
+    fn test_imports_resolve_relative_to_the_importing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("shared")).unwrap();
+        std::fs::write(dir.path().join("shared/common.yaml"), FRAGMENTS).unwrap();
+        std::fs::write(
+            dir.path().join("shared/paid.yaml"),
+            "import: [common.yaml]\nfragments:\n  paid:\n    extends: valid-402\n    headers:\n      X-Payment-Proof: proof\n",
+        )
+        .unwrap();
+        let suite = dir.path().join("suite.yaml");
+        std::fs::write(
+            &suite,
+            "import:\n  - shared/paid.yaml\n  - shared/common.yaml\ntests:\n  - name: t\n    url: http://localhost:3402/\n    extends: paid\n",
+        )
+        .unwrap();
+
+        let suite = TestSuite::from_file(&suite).unwrap();
+        assert_eq!(suite.tests[0].headers["X-Payment-Proof"], "proof");
+        assert_eq!(suite.tests[0].expect.status, Some(402));
+    }
+
+    #[test]
+    fn test_import_cycle_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.yaml"), "import: b.yaml\n").unwrap();
+        std::fs::write(dir.path().join("b.yaml"), "import: a.yaml\n").unwrap();
+        let suite = dir.path().join("suite.yaml");
+        std::fs::write(
+            &suite,
+            "import: a.yaml\ntests:\n  - name: t\n    url: http://localhost:3402/\n    expect:\n      status: 402\n",
+        )
+        .unwrap();
+
+        let err = TestSuite::from_file(&suite).unwrap_err().to_string();
+        assert!(
+            err.starts_with("Import cycle: a.yaml -> b.yaml -> a.yaml"),
+            "{}",
+            err
+        );
+        assert!(err.contains("b.yaml:1)"), "{}", err);
+
+        // A suite importing itself is a cycle too
+        std::fs::write(&suite, "import: suite.yaml\ntests: []\n").unwrap();
+        let err = TestSuite::from_file(&suite).unwrap_err().to_string();
+        assert!(
+            err.starts_with("Import cycle: suite.yaml -> suite.yaml"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_unknown_fragment_reports_file_and_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let suite = dir.path().join("suite.yaml");
+        std::fs::write(
+            &suite,
+            format!(
+                "{}tests:\n  - name: t\n    url: http://localhost:3402/\n    extends: \"valid-420\"\n",
+                FRAGMENTS
+            ),
+        )
+        .unwrap();
+
+        let err = TestSuite::from_file(&suite).unwrap_err().to_string();
+        assert!(
+            err.starts_with(&format!(
+                "Unknown fragment 'valid-420' ({}:14)",
+                suite.display()
+            )),
+            "{}",
+            err
+        );
+        assert!(err.contains("(known: valid-402)"), "{}", err);
+
+        let err = <TestSuite as FromStr>::from_str(
+            "fragments:\n  a:\n    extends: b\n  b:\n    extends: a\ntests:\n  - name: t\n    url: http://localhost:3402/\n    extends: a\n",
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Fragment cycle: a -> b -> a");
+    }
+}
@@ -0,0 +1,117 @@
+// Content-Encoding handling for test and check responses
+//
+// reqwest is built without its decompression features, so bodies arrive
+// exactly as sent. Decoding them here keeps the encoded size around for
+// `encoded_size_max` assertions and results.
+
+use anyhow::{bail, Context, Result};
+use flate2::read::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+
+/// Response body after undoing its `Content-Encoding`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedBody {
+    pub bytes: Vec<u8>,
+    /// Bytes received on the wire, before decoding
+    pub encoded_size: usize,
+    /// `Content-Encoding` that was removed, if any
+    pub encoding: Option<String>,
+}
+
+impl DecodedBody {
+    /// Decoded body as text (invalid UTF-8 is replaced)
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.bytes).into_owned()
+    }
+}
+
+/// Undo `content_encoding` (e.g. `gzip`, `deflate` or `gzip, deflate`) on `raw`
+///
+/// Codings are removed last-applied first; `identity` is a no-op and
+/// anything else is an error.
+pub fn decode_body(content_encoding: Option<&str>, raw: Vec<u8>) -> Result<DecodedBody> {
+    let encoded_size = raw.len();
+    let encoding = content_encoding
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(str::to_string);
+
+    let mut bytes = raw;
+    if let Some(encoding) = &encoding {
+        for coding in encoding.rsplit(',').map(|c| c.trim().to_ascii_lowercase()) {
+            bytes = match coding.as_str() {
+                "identity" => bytes,
+                "gzip" | "x-gzip" => read_all(MultiGzDecoder::new(bytes.as_slice()), &coding)?,
+                // RFC 9110 deflate is zlib-wrapped, but some servers send raw deflate
+                "deflate" => read_all(ZlibDecoder::new(bytes.as_slice()), &coding)
+                    .or_else(|_| read_all(DeflateDecoder::new(bytes.as_slice()), &coding))?,
+                other => bail!(
+                    "Unsupported Content-Encoding '{}'\nFix: Serve gzip, deflate or identity bodies",
+                    other
+                ),
+            };
+        }
+    }
+
+    Ok(DecodedBody {
+        bytes,
+        encoded_size,
+        encoding,
+    })
+}
+
+fn read_all(mut reader: impl Read, coding: &str) -> Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    reader
+        .read_to_end(&mut decoded)
+        .with_context(|| format!("Invalid {} response body", coding))?;
+    Ok(decoded)
+}
+
+/// Lowercase hex SHA-256 of `bytes`
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::{DeflateEncoder, GzEncoder};
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn test_decode_gzip_and_raw_deflate() {
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(b"{\"ok\":true}").unwrap();
+        let gzip = gzip.finish().unwrap();
+        let size = gzip.len();
+
+        let decoded = decode_body(Some("gzip"), gzip).unwrap();
+        assert_eq!(decoded.text(), "{\"ok\":true}");
+        assert_eq!(decoded.encoded_size, size);
+        assert_eq!(decoded.encoding.as_deref(), Some("gzip"));
+
+        let mut deflate = DeflateEncoder::new(Vec::new(), Compression::default());
+        deflate.write_all(b"raw").unwrap();
+        let decoded = decode_body(Some("deflate"), deflate.finish().unwrap()).unwrap();
+        assert_eq!(decoded.bytes, b"raw");
+
+        let plain = decode_body(None, b"plain".to_vec()).unwrap();
+        assert_eq!((plain.encoded_size, plain.encoding), (5, None));
+        assert!(decode_body(Some("br"), vec![1, 2]).is_err());
+        assert!(decode_body(Some("gzip"), b"not gzip".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -1,10 +1,94 @@
 // Test execution engine (FR-2.3)
 
-use super::assertions::{build_assertions, AssertionResult};
-use super::parser::{Test, TestSuite};
+use super::assertions::{
+    body_contains, body_sha256, body_size, build_assertions, encoded_size_max, AssertionResult,
+};
+use super::encoding::{decode_body, DecodedBody};
+use super::extract::Extraction;
+use super::faults::{classify_fault, FaultKind};
+use super::filter::TestFilter;
+use super::invoice_assertions;
+use super::invoice_header::InvoiceHeader;
+use super::latency::{LatencyMeasurement, LatencySummary};
+use super::parser::{
+    Budget, ErrorExpectation, Expectations, HttpOptions, HttpVersion, RequestBody, Step, Test,
+    TestSuite,
+};
+use super::preflight::{run_preflight, PreflightReport};
+use super::resolve::apply_resolve_overrides;
+use crate::redact::{contains_any, mask_values};
 use anyhow::Result;
-use reqwest::Client;
+use reqwest::header::CONTENT_ENCODING;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
 use std::time::{Duration, Instant};
+use x402_domain::Amount;
+
+/// Why a test was not executed
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// Excluded by tag or name selection
+    Filtered(String),
+    /// Not started before the suite's `total_timeout_ms` ran out
+    TotalTimeout(u64),
+    /// Not started because the invoiced total reached the suite's budget
+    BudgetExceeded { spent: Decimal, budget: Budget },
+    /// Not started because a pre-flight check failed
+    PreflightFailed,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Filtered(reason) => write!(f, "filtered: {}", reason),
+            SkipReason::TotalTimeout(ms) => {
+                write!(f, "cancelled: suite total_timeout_ms of {}ms exceeded", ms)
+            }
+            SkipReason::BudgetExceeded { spent, budget } => write!(
+                f,
+                "budget exceeded: spent {} of {} {}",
+                spent, budget.max_total, budget.currency
+            ),
+            SkipReason::PreflightFailed => write!(f, "pre-flight checks failed"),
+        }
+    }
+}
+
+/// Why a test produced no response to assert on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// No response within the test, suite or client timeout
+    Timeout,
+    /// Connection could not be established
+    Connection,
+    /// Any other request failure (e.g. an undefined variable)
+    Request,
+}
+
+impl ErrorCategory {
+    fn from_reqwest(error: &reqwest::Error) -> Self {
+        if error.is_timeout() {
+            ErrorCategory::Timeout
+        } else if error.is_connect() {
+            ErrorCategory::Connection
+        } else {
+            ErrorCategory::Request
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ErrorCategory::Timeout => "timeout",
+            ErrorCategory::Connection => "connection",
+            ErrorCategory::Request => "request",
+        })
+    }
+}
 
 /// Result of a single test execution
 #[derive(Debug, Clone)]
@@ -16,6 +100,40 @@ pub struct TestResult {
     pub duration: Duration,
     pub assertions: Vec<AssertionResult>,
     pub error: Option<String>,
+    /// Kind of failure behind `error`
+    pub error_category: Option<ErrorCategory>,
+    /// Set when the test was not executed
+    pub skipped: Option<SkipReason>,
+    /// Protocol the response arrived over, e.g. "HTTP/2.0" (None if no response)
+    pub http_version: Option<String>,
+    /// Bytes of the last response body as received, before Content-Encoding
+    /// is undone (from Content-Length when the body was not read)
+    pub encoded_size: Option<u64>,
+    /// Fragment the test extended (`extends:`)
+    pub extends: Option<String>,
+    /// Timing of each exchange that had a latency budget
+    pub latency: Vec<LatencyMeasurement>,
+}
+
+impl TestResult {
+    /// Placeholder result for a test that was not executed
+    fn skipped(test: &Test, reason: SkipReason) -> Self {
+        Self {
+            name: test.name.clone(),
+            url: test.url.clone(),
+            method: test.method.clone(),
+            passed: false,
+            duration: Duration::ZERO,
+            assertions: vec![],
+            error: None,
+            error_category: None,
+            skipped: Some(reason),
+            http_version: None,
+            encoded_size: None,
+            extends: test.extends.clone(),
+            latency: Vec::new(),
+        }
+    }
 }
 
 /// Result of entire test suite execution
@@ -25,31 +143,165 @@ pub struct SuiteResult {
     pub total: usize,
     pub passed: usize,
     pub failed: usize,
+    /// Tests that were not executed (counted in `total`)
+    pub skipped: usize,
     pub duration: Duration,
+    /// Invoiced total per currency over every 402 response received
+    pub spent: BTreeMap<String, Decimal>,
+    /// The suite's budget, if it had one
+    pub budget: Option<Budget>,
+    /// Pre-flight checks, when the suite ran them
+    pub preflight: Option<PreflightReport>,
+}
+
+/// Overall outcome of a suite run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SuiteStatus {
+    /// No executed test failed
+    Passed,
+    /// At least one test failed
+    Failed,
+    /// A pre-flight check failed and no test was run
+    PreflightFailed,
+}
+
+impl std::fmt::Display for SuiteStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SuiteStatus::Passed => "passed",
+            SuiteStatus::Failed => "failed",
+            SuiteStatus::PreflightFailed => "preflight_failed",
+        })
+    }
 }
 
 impl SuiteResult {
-    pub fn exit_code(&self) -> i32 {
-        if self.failed > 0 {
-            1 // FR-2.4: Exit code 1 if any test fails
+    /// Overall outcome of the run
+    pub fn status(&self) -> SuiteStatus {
+        if self.preflight.as_ref().is_some_and(|p| !p.passed()) {
+            SuiteStatus::PreflightFailed
+        } else if self.failed > 0 {
+            SuiteStatus::Failed
         } else {
-            0 // FR-2.4: Exit code 0 if all pass
+            SuiteStatus::Passed
+        }
+    }
+
+    /// Latency budget violations over every test
+    pub fn latency_summary(&self) -> LatencySummary {
+        self.tests.iter().flat_map(|test| &test.latency).collect()
+    }
+
+    /// Invoiced total in `currency`
+    pub fn spent_in(&self, currency: &str) -> Decimal {
+        self.spent.get(currency).copied().unwrap_or_default()
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self.status() {
+            SuiteStatus::Passed => 0,          // FR-2.4: Exit code 0 if all pass
+            SuiteStatus::Failed => 1,          // FR-2.4: Exit code 1 if any test fails
+            SuiteStatus::PreflightFailed => 3, // Target unreachable, as other network errors
         }
     }
 }
 
 /// Execute a complete test suite
 pub async fn execute_test_suite(suite: &TestSuite) -> Result<SuiteResult> {
+    execute_test_suite_filtered(suite, &TestFilter::default()).await
+}
+
+/// Execute the tests of a suite selected by `filter`
+///
+/// Excluded tests are reported as skipped with the reason they were filtered.
+/// Once `total_timeout_ms` runs out the request in flight fails with a
+/// timeout and the remaining tests are skipped. Likewise, once the invoiced
+/// total reaches the `budget`, the remaining tests are skipped; the test
+/// that reached it still runs to the end.
+///
+/// With `preflight:` set, the selected tests run only once every pre-flight
+/// check passed; otherwise they are all skipped and the suite reports
+/// [`SuiteStatus::PreflightFailed`].
+pub async fn execute_test_suite_filtered(
+    suite: &TestSuite,
+    filter: &TestFilter,
+) -> Result<SuiteResult> {
     let start = Instant::now();
-    let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+    let client = suite.http.build_client()?;
+
+    let preflight = match &suite.preflight {
+        Some(options) => {
+            let selected: Vec<&Test> = suite
+                .tests
+                .iter()
+                .filter(|test| filter.exclusion_reason(test).is_none())
+                .collect();
+            Some(run_preflight(suite, &selected, options, &client).await)
+        }
+        None => None,
+    };
+    if let Some(report) = preflight.as_ref().filter(|report| !report.passed()) {
+        let tests = suite
+            .tests
+            .iter()
+            .map(|test| {
+                let reason = filter
+                    .exclusion_reason(test)
+                    .map_or(SkipReason::PreflightFailed, SkipReason::Filtered);
+                TestResult::skipped(test, reason)
+            })
+            .collect();
+        return Ok(SuiteResult {
+            tests,
+            total: suite.tests.len(),
+            passed: 0,
+            failed: 0,
+            skipped: suite.tests.len(),
+            duration: start.elapsed(),
+            spent: BTreeMap::new(),
+            budget: suite.budget.clone(),
+            preflight: Some(report.clone()),
+        });
+    }
+    let deadline = suite
+        .total_timeout_ms
+        .map(|limit| (limit.as_millis(), start + limit.as_duration()));
 
     let mut test_results = Vec::new();
     let mut passed_count = 0;
     let mut failed_count = 0;
+    let mut skipped_count = 0;
+    let mut spent = BTreeMap::new();
 
     // FR-2.3: Execute tests sequentially
     for test in &suite.tests {
-        let result = execute_single_test(&client, test).await;
+        if let Some(reason) = filter.exclusion_reason(test) {
+            skipped_count += 1;
+            test_results.push(TestResult::skipped(test, SkipReason::Filtered(reason)));
+            continue;
+        }
+        if let Some((ms, deadline)) = deadline {
+            if Instant::now() >= deadline {
+                skipped_count += 1;
+                test_results.push(TestResult::skipped(test, SkipReason::TotalTimeout(ms)));
+                continue;
+            }
+        }
+        if let Some(budget) = &suite.budget {
+            let total = spent.get(&budget.currency).copied().unwrap_or_default();
+            if total >= budget.max_total {
+                skipped_count += 1;
+                let reason = SkipReason::BudgetExceeded {
+                    spent: total,
+                    budget: budget.clone(),
+                };
+                test_results.push(TestResult::skipped(test, reason));
+                continue;
+            }
+        }
+
+        let result = execute_single_test(&client, suite, test, deadline, &mut spent).await;
 
         if result.passed {
             passed_count += 1;
@@ -61,6 +313,12 @@ pub async fn execute_test_suite(suite: &TestSuite) -> Result<SuiteResult> {
         // FR-2.3: Continue execution on test failure (fail-soft)
     }
 
+    if !suite.secrets.is_empty() {
+        for result in &mut test_results {
+            mask_secrets(result, suite);
+        }
+    }
+
     let total_duration = start.elapsed();
 
     Ok(SuiteResult {
@@ -68,79 +326,1287 @@ pub async fn execute_test_suite(suite: &TestSuite) -> Result<SuiteResult> {
         total: suite.tests.len(),
         passed: passed_count,
         failed: failed_count,
+        skipped: skipped_count,
         duration: total_duration,
+        spent,
+        budget: suite.budget.clone(),
+        preflight,
     })
 }
 
-/// Execute a single test
-async fn execute_single_test(client: &Client, test: &Test) -> TestResult {
-    let start = Instant::now();
+/// Mask resolved `${secret:name}` values in a result before any report sees it
+///
+/// Structured header diffs mentioning a secret are dropped: their character
+/// offsets would not survive masking.
+fn mask_secrets(result: &mut TestResult, suite: &TestSuite) {
+    let secrets = || suite.secrets.values().map(String::as_str);
+    result.url = mask_values(&result.url, secrets());
+    if let Some(error) = &mut result.error {
+        *error = mask_values(error, secrets());
+    }
+    for assertion in &mut result.assertions {
+        let leaks = contains_any(&assertion.expected, secrets())
+            || contains_any(&assertion.actual, secrets());
+        assertion.description = mask_values(&assertion.description, secrets());
+        assertion.expected = mask_values(&assertion.expected, secrets());
+        assertion.actual = mask_values(&assertion.actual, secrets());
+        if leaks {
+            assertion.failure_detail = None;
+        }
+    }
+}
+
+impl HttpOptions {
+    /// Build the HTTP client shared by all tests of a suite
+    ///
+    /// Also used by `x402-dev curl`, so one-off requests behave like suites.
+    pub fn build_client(&self) -> Result<Client> {
+        let mut builder = Client::builder().timeout(Duration::from_secs(30));
+
+        builder = match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::H1 => builder.http1_only(),
+            HttpVersion::H2 => builder.http2_prior_knowledge(),
+        };
+
+        if !self.reuse_connections {
+            // No idle pool: every request opens (and closes) its own connection
+            builder = builder.pool_max_idle_per_host(0);
+        } else if let Some(max) = self.max_idle_connections {
+            builder = builder.pool_max_idle_per_host(max);
+        }
 
-    // Build HTTP request
-    let request = match test.method.to_uppercase().as_str() {
-        "GET" => client.get(&test.url),
-        "POST" => client.post(&test.url),
-        "PUT" => client.put(&test.url),
-        "DELETE" => client.delete(&test.url),
-        "PATCH" => client.patch(&test.url),
-        "HEAD" => client.head(&test.url),
-        _ => client.get(&test.url), // Default to GET
+        builder = apply_resolve_overrides(builder, &self.resolve);
+
+        Ok(builder.build()?)
+    }
+}
+
+/// Request and checks of one step: the test itself or one of its `then:` steps
+struct StepSpec<'a> {
+    /// Prefix for assertion descriptions; None for single-step tests
+    label: Option<String>,
+    url: &'a str,
+    method: &'a str,
+    headers: &'a BTreeMap<String, String>,
+    body: Option<&'a RequestBody>,
+    repeat: u32,
+    expect: &'a Expectations,
+    extract: &'a BTreeMap<String, Extraction>,
+    then: Option<&'a Step>,
+}
+
+impl<'a> StepSpec<'a> {
+    fn first(test: &'a Test) -> Self {
+        Self {
+            label: test.then.is_some().then(|| "step 1".to_string()),
+            url: &test.url,
+            method: &test.method,
+            headers: &test.headers,
+            body: test.body.as_ref(),
+            repeat: test.repeat.unwrap_or(1),
+            expect: &test.expect,
+            extract: &test.extract,
+            then: test.then.as_deref(),
+        }
+    }
+
+    /// The `then:` step following this one, as step `number`
+    fn next(&self, number: usize) -> Option<Self> {
+        let step = self.then?;
+        Some(Self {
+            label: Some(
+                step.name
+                    .clone()
+                    .unwrap_or_else(|| format!("step {}", number)),
+            ),
+            url: step.url.as_deref().unwrap_or(self.url),
+            method: &step.method,
+            headers: &step.headers,
+            body: step.body.as_ref(),
+            repeat: 1,
+            expect: &step.expect,
+            extract: &step.extract,
+            then: step.then.as_deref(),
+        })
+    }
+
+    fn labelled(&self, text: String) -> String {
+        match &self.label {
+            Some(label) => format!("{}: {}", label, text),
+            None => text,
+        }
+    }
+}
+
+/// Build the HTTP request for a step, resolving extracted and suite variables
+fn build_request(
+    client: &Client,
+    suite: &TestSuite,
+    step: &StepSpec<'_>,
+    extracted: &BTreeMap<String, String>,
+) -> Result<RequestBuilder> {
+    let url = suite.interpolate_with(step.url, extracted)?;
+
+    let mut request = match step.method.to_uppercase().as_str() {
+        "GET" => client.get(&url),
+        "POST" => client.post(&url),
+        "PUT" => client.put(&url),
+        "DELETE" => client.delete(&url),
+        "PATCH" => client.patch(&url),
+        "HEAD" => client.head(&url),
+        _ => client.get(&url), // Default to GET
     };
 
-    // Execute request
-    let response_result = request.send().await;
-    let request_duration = start.elapsed();
+    for (name, value) in step.headers {
+        request = request.header(name, suite.interpolate_with(value, extracted)?);
+    }
 
-    match response_result {
-        Ok(response) => {
-            // Build assertions from expectations
-            let assertions_to_check = build_assertions(&test.expect);
-            let mut assertion_results = Vec::new();
-            let mut all_passed = true;
-
-            // Check each assertion
-            for assertion in assertions_to_check {
-                match assertion.check(&response, request_duration).await {
-                    Ok(result) => {
-                        if !result.passed {
-                            all_passed = false;
-                        }
-                        assertion_results.push(result);
-                    }
-                    Err(e) => {
-                        // Assertion error (e.g., regex compilation failed)
-                        all_passed = false;
-                        assertion_results.push(AssertionResult {
-                            passed: false,
-                            description: format!("Assertion error: {}", e),
-                            expected: "valid assertion".to_string(),
-                            actual: format!("error: {}", e),
-                        });
-                    }
+    // JSON bodies get `Content-Type: application/json` unless a header sets it
+    match step.body {
+        Some(RequestBody::Text(text)) => {
+            request = request.body(suite.interpolate_with(text, extracted)?);
+        }
+        Some(RequestBody::Json(value)) => {
+            request = request.json(&interpolate_json(suite, value, extracted)?);
+        }
+        None => {}
+    }
+
+    Ok(request)
+}
+
+/// Substitute variables in every string of a JSON body
+fn interpolate_json(
+    suite: &TestSuite,
+    value: &serde_json::Value,
+    extracted: &BTreeMap<String, String>,
+) -> Result<serde_json::Value> {
+    use serde_json::Value;
+
+    Ok(match value {
+        Value::String(s) => Value::String(suite.interpolate_with(s, extracted)?),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| interpolate_json(suite, item, extracted))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, item)| Ok((key.clone(), interpolate_json(suite, item, extracted)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// Why a request produced no response
+struct RequestFailure {
+    category: ErrorCategory,
+    message: String,
+    /// Connection-level fault, matched against `expect_error:`
+    fault: Option<FaultKind>,
+}
+
+impl RequestFailure {
+    fn request(message: String) -> Self {
+        Self {
+            category: ErrorCategory::Request,
+            message,
+            fault: None,
+        }
+    }
+}
+
+/// Send one request, bounded by the test timeout and the suite deadline
+async fn send_request(
+    request: RequestBuilder,
+    timeout_ms: Option<u64>,
+    deadline: Option<(u64, Instant)>,
+) -> Result<Response, RequestFailure> {
+    let test_limit = timeout_ms.map(|ms| {
+        (
+            Duration::from_millis(ms),
+            format!("Request timed out after {}ms", ms),
+            Some(FaultKind::Timeout),
+        )
+    });
+    // Running out of suite time says nothing about the endpoint: no fault
+    let suite_limit = deadline.map(|(ms, deadline)| {
+        (
+            deadline.saturating_duration_since(Instant::now()),
+            format!(
+                "Request cancelled: suite total_timeout_ms of {}ms exceeded",
+                ms
+            ),
+            None,
+        )
+    });
+    let limit = [test_limit, suite_limit]
+        .into_iter()
+        .flatten()
+        .min_by_key(|(duration, _, _)| *duration);
+
+    let result = match limit {
+        Some((duration, message, fault)) => tokio::time::timeout(duration, request.send())
+            .await
+            .map_err(|_| RequestFailure {
+                category: ErrorCategory::Timeout,
+                message,
+                fault,
+            })?,
+        None => request.send().await,
+    };
+    result.map_err(|e| RequestFailure {
+        category: ErrorCategory::from_reqwest(&e),
+        message: format!("HTTP request failed: {}", e),
+        fault: classify_fault(&e),
+    })
+}
+
+/// Currency and amount of the invoice on a 402 response
+fn invoice_charge(response: &Response) -> Option<(String, Amount)> {
+    if response.status() != StatusCode::PAYMENT_REQUIRED {
+        return None;
+    }
+    let header = response.headers().get("www-authenticate")?.to_str().ok()?;
+    let invoice = InvoiceHeader::parse(header).ok()?;
+    let amount = Amount::from_decimal_str(invoice.get("amount")?).ok()?;
+    Some((invoice.get("currency")?.to_string(), amount))
+}
+
+/// Add `amount` to the total spent in `currency`
+///
+/// A total that would overflow stays as it is; it is far past any budget.
+fn add_charge(spent: &mut BTreeMap<String, Decimal>, currency: String, amount: Amount) {
+    let total = spent.entry(currency).or_default();
+    if let Ok(sum) = Amount::new(*total).and_then(|spent| Amount::sum([spent, amount])) {
+        *total = sum.as_decimal();
+    }
+}
+
+/// Execute a single test, adding the invoices it receives to `spent`
+///
+/// The `then:` steps run in order while every earlier step passes; their
+/// assertions are reported on the test, prefixed with the step label.
+async fn execute_single_test(
+    client: &Client,
+    suite: &TestSuite,
+    test: &Test,
+    deadline: Option<(u64, Instant)>,
+    spent: &mut BTreeMap<String, Decimal>,
+) -> TestResult {
+    let expect_error = test.expect.expect_error.as_ref();
+    // An expected timeout without its own limit times out at max_duration_ms
+    let timeout_ms = test
+        .timeout_ms
+        .or(suite.timeout_ms)
+        .or_else(|| {
+            expect_error
+                .filter(|e| e.category == FaultKind::Timeout)
+                .and_then(|e| e.max_duration_ms)
+        })
+        .map(|limit| limit.as_millis());
+
+    let mut step = StepSpec::first(test);
+    let mut extracted = BTreeMap::new();
+    let (response_result, attempts) = send_step(
+        client, suite, &step, &extracted, timeout_ms, deadline, spent,
+    )
+    .await;
+
+    if let Some(expected) = expect_error {
+        return expected_error_result(
+            test,
+            expected,
+            response_result,
+            last_attempt(&attempts),
+            timeout_ms,
+        );
+    }
+
+    let mut result = TestResult {
+        name: test.name.clone(),
+        url: test.url.clone(),
+        method: test.method.clone(),
+        passed: true,
+        duration: Duration::ZERO,
+        assertions: vec![],
+        error: None,
+        error_category: None,
+        skipped: None,
+        http_version: None,
+        encoded_size: None,
+        extends: test.extends.clone(),
+        latency: Vec::new(),
+    };
+    let mut outcome = (response_result, attempts);
+    let mut number = 1;
+    loop {
+        let (response_result, attempts) = outcome;
+        result.duration += last_attempt(&attempts);
+
+        let response = match response_result {
+            Ok(response) => response,
+            Err(failure) => {
+                // HTTP request failed or timed out
+                result.passed = false;
+                result.error = Some(step.labelled(failure.message));
+                result.error_category = Some(failure.category);
+                break;
+            }
+        };
+        result
+            .http_version
+            .get_or_insert_with(|| format!("{:?}", response.version()));
+
+        let step_passed = check_step(
+            suite,
+            &step,
+            response,
+            &attempts,
+            &mut extracted,
+            &mut result,
+        )
+        .await;
+        result.passed &= step_passed;
+
+        number += 1;
+        match step.next(number) {
+            Some(next) if step_passed => step = next,
+            _ => break,
+        }
+        outcome = send_step(
+            client, suite, &step, &extracted, timeout_ms, deadline, spent,
+        )
+        .await;
+    }
+
+    result
+}
+
+/// Send a step's request `repeat` times, adding invoices to `spent`
+///
+/// Earlier repetitions only build up server-side state (rate limits,
+/// spending); the last outcome is returned with the duration of every
+/// attempt, in order.
+async fn send_step(
+    client: &Client,
+    suite: &TestSuite,
+    step: &StepSpec<'_>,
+    extracted: &BTreeMap<String, String>,
+    timeout_ms: Option<u64>,
+    deadline: Option<(u64, Instant)>,
+    spent: &mut BTreeMap<String, Decimal>,
+) -> (Result<Response, RequestFailure>, Vec<Duration>) {
+    let mut response_result = Err(RequestFailure::request("test has repeat: 0".to_string()));
+    let mut attempts = Vec::new();
+    for _ in 0..step.repeat {
+        let request_start = Instant::now();
+        response_result = match build_request(client, suite, step, extracted) {
+            Ok(request) => send_request(request, timeout_ms, deadline).await,
+            Err(e) => Err(RequestFailure::request(e.to_string())),
+        };
+        attempts.push(request_start.elapsed());
+        match &response_result {
+            Ok(response) => {
+                if let Some((currency, amount)) = invoice_charge(response) {
+                    add_charge(spent, currency, amount);
                 }
             }
+            Err(_) => break,
+        }
+    }
+    (response_result, attempts)
+}
+
+/// Duration of the exchange a step's assertions check
+fn last_attempt(attempts: &[Duration]) -> Duration {
+    attempts.last().copied().unwrap_or_default()
+}
+
+/// Run a step's assertions and extractions against its response
+///
+/// Assertions, the body's size as received and latency measurements are
+/// added to `result`, extracted values to `extracted`. Returns true if every
+/// assertion passed and every variable was extracted.
+async fn check_step(
+    suite: &TestSuite,
+    step: &StepSpec<'_>,
+    response: Response,
+    attempts: &[Duration],
+    extracted: &mut BTreeMap<String, String>,
+    result: &mut TestResult,
+) -> bool {
+    let duration = last_attempt(attempts);
+    let mut results = Vec::new();
 
-            TestResult {
-                name: test.name.clone(),
-                url: test.url.clone(),
-                method: test.method.clone(),
-                passed: all_passed,
-                duration: request_duration,
-                assertions: assertion_results,
-                error: None,
+    // Check each assertion
+    for assertion in build_assertions(step.expect) {
+        match assertion.check(&response, duration).await {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                // Assertion error (e.g., regex compilation failed)
+                results.push(assertion_error(e.to_string()));
             }
         }
-        Err(e) => {
-            // HTTP request failed
-            TestResult {
-                name: test.name.clone(),
-                url: test.url.clone(),
-                method: test.method.clone(),
-                passed: false,
-                duration: request_duration,
-                assertions: vec![],
-                error: Some(format!("HTTP request failed: {}", e)),
+    }
+
+    // The body is read once, after the header checks, and only if needed;
+    // Content-Encoding is undone here since reqwest leaves bodies as sent
+    let headers = response.headers().clone();
+    let content_length = response.content_length();
+    let needs_body = step.expect.reads_body() || step.extract.values().any(Extraction::reads_body);
+    let decoded = if needs_body {
+        let raw = response.bytes().await.map(Vec::from).unwrap_or_default();
+        let content_encoding = headers.get(CONTENT_ENCODING).and_then(|v| v.to_str().ok());
+        match decode_body(content_encoding, raw) {
+            Ok(body) => Some(body),
+            Err(e) => {
+                results.push(assertion_error(format!("{:#}", e)));
+                None
             }
         }
+    } else {
+        None
+    };
+    result.encoded_size = decoded
+        .as_ref()
+        .map(|body| body.encoded_size as u64)
+        .or(content_length);
+    let body = decoded.as_ref().map(DecodedBody::text).unwrap_or_default();
+    let json: Option<serde_json::Value> = serde_json::from_str(&body).ok();
+
+    if let Some(expected) = &step.expect.body_contains {
+        results.push(match suite.interpolate_with(expected, extracted) {
+            Ok(expected) => body_contains(&expected, &body),
+            Err(e) => assertion_error(e.to_string()),
+        });
+    }
+
+    if let Some(body) = &decoded {
+        if let Some(expected) = &step.expect.body_sha256 {
+            results.push(body_sha256(expected, &body.bytes));
+        }
+        if let Some(expected) = step.expect.body_size {
+            results.push(body_size(expected, body.bytes.len()));
+        }
+        if let Some(max) = step.expect.encoded_size_max {
+            results.push(encoded_size_max(max, body));
+        }
+    }
+
+    if let Some(invoice) = &step.expect.invoice {
+        results.extend(invoice_assertions::check_response(
+            invoice,
+            &headers,
+            json.as_ref(),
+        ));
+    }
+
+    for (name, extraction) in step.extract {
+        let outcome = extraction.extract(&headers, json.as_ref());
+        results.push(AssertionResult {
+            passed: outcome.is_ok(),
+            description: format!("Extract '{}' from {}", name, extraction.source()),
+            expected: "a value".to_string(),
+            actual: outcome.clone().unwrap_or_else(|e| e),
+            invoice_field: None,
+            failure_detail: None,
+        });
+        if let Ok(value) = outcome {
+            extracted.insert(name.clone(), value);
+        }
+    }
+
+    // Last, so a functional failure is reported before a slow response
+    let budget = step.expect.latency_budget(suite.latency.as_ref());
+    if !budget.is_empty() {
+        let measurement = LatencyMeasurement::new(step.label.clone(), attempts, &budget);
+        results.extend(measurement.assertions());
+        result.latency.push(measurement);
+    }
+
+    let passed = results.iter().all(|r| r.passed);
+    for mut assertion in results {
+        assertion.description = step.labelled(assertion.description);
+        result.assertions.push(assertion);
+    }
+    passed
+}
+
+fn assertion_error(error: String) -> AssertionResult {
+    AssertionResult {
+        passed: false,
+        description: format!("Assertion error: {}", error),
+        expected: "valid assertion".to_string(),
+        actual: format!("error: {}", error),
+        invoice_field: None,
+        failure_detail: None,
+    }
+}
+
+/// Outcome of an `expect_error:` test
+///
+/// Passes when the request failed with the expected fault (within
+/// `max_duration_ms` if set) and fails on any response. A timeout counts as
+/// taking its configured limit, so `max_duration_ms` bounds that limit.
+fn expected_error_result(
+    test: &Test,
+    expected: &ErrorExpectation,
+    outcome: Result<Response, RequestFailure>,
+    duration: Duration,
+    timeout_ms: Option<u64>,
+) -> TestResult {
+    let mut result = TestResult {
+        name: test.name.clone(),
+        url: test.url.clone(),
+        method: test.method.clone(),
+        passed: false,
+        duration,
+        assertions: vec![],
+        error: None,
+        error_category: None,
+        skipped: None,
+        http_version: None,
+        encoded_size: None,
+        extends: test.extends.clone(),
+        latency: Vec::new(),
+    };
+
+    let (fault, actual) = match outcome {
+        Ok(response) => {
+            result.http_version = Some(format!("{:?}", response.version()));
+            (
+                None,
+                format!("HTTP {} response", response.status().as_u16()),
+            )
+        }
+        Err(failure) => {
+            let actual = failure
+                .fault
+                .map_or_else(|| format!("{} error", failure.category), |f| f.to_string());
+            if failure.fault != Some(expected.category) {
+                result.error = Some(failure.message);
+                result.error_category = Some(failure.category);
+            }
+            (failure.fault, actual)
+        }
+    };
+    result.assertions.push(AssertionResult {
+        passed: fault == Some(expected.category),
+        description: format!("Request fails with {}", expected.category),
+        expected: expected.category.to_string(),
+        actual,
+        invoice_field: None,
+        failure_detail: None,
+    });
+
+    let max_duration_ms = expected.max_duration_ms.map(|max| max.as_millis());
+    if let (Some(max_ms), Some(fault)) = (max_duration_ms, fault) {
+        let took_ms = match (fault, timeout_ms) {
+            (FaultKind::Timeout, Some(limit)) => limit as u128,
+            _ => duration.as_millis(),
+        };
+        result.assertions.push(AssertionResult {
+            passed: took_ms <= max_ms as u128,
+            description: format!("Fails within {}ms", max_ms),
+            expected: format!("<= {}ms", max_ms),
+            actual: format!("{}ms", took_ms),
+            invoice_field: None,
+            failure_detail: None,
+        });
+    }
+
+    result.passed = result.assertions.iter().all(|a| a.passed);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{FailureDetail, FieldDifference};
+    use std::str::FromStr;
+    use wiremock::matchers::{any, body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const INVOICE: &str = "x402-solana recipient=GXk8vTest1111111111111111111111111111qPz9 amount=0.30 currency=USDC memo=req-123 network=devnet";
+
+    /// Run one test with an `invoice:` block against a mocked 402 response
+    async fn run_invoice_test(
+        header: Option<&str>,
+        body: serde_json::Value,
+        invoice_yaml: &str,
+    ) -> TestResult {
+        let server = MockServer::start().await;
+        let mut response = ResponseTemplate::new(402).set_body_json(body);
+        if let Some(header) = header {
+            response = response.insert_header("WWW-Authenticate", header);
+        }
+        Mock::given(any())
+            .respond_with(response)
+            .mount(&server)
+            .await;
+
+        let invoice: String = invoice_yaml
+            .trim()
+            .lines()
+            .map(|line| format!("        {}\n", line))
+            .collect();
+        let yaml = format!(
+            "tests:\n  - name: invoice\n    url: \"{}/api/data\"\n    expect:\n      invoice:\n{}",
+            server.uri(),
+            invoice
+        );
+        let suite = <TestSuite as FromStr>::from_str(&yaml).unwrap();
+        execute_test_suite(&suite).await.unwrap().tests.remove(0)
+    }
+
+    fn outcomes(result: &TestResult) -> Vec<(bool, &str, &str)> {
+        result
+            .assertions
+            .iter()
+            .map(|a| (a.passed, a.expected.as_str(), a.actual.as_str()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_filtered_tests_are_not_executed() {
+        // Unroutable URL: the test would fail if it were actually executed
+        let suite = <TestSuite as FromStr>::from_str(
+            r#"
+tests:
+  - name: "Slow e2e"
+    url: "http://127.0.0.1:1/unreachable"
+    tags: [e2e]
+    expect:
+      status: 402
+"#,
+        )
+        .unwrap();
+        let filter = TestFilter {
+            skip_tags: vec!["e2e".to_string()],
+            ..TestFilter::default()
+        };
+
+        let result = execute_test_suite_filtered(&suite, &filter).await.unwrap();
+
+        assert_eq!(result.total, 1);
+        assert_eq!(result.skipped, 1);
+        assert_eq!(result.failed, 0);
+        assert_eq!(result.exit_code(), 0);
+        assert_eq!(
+            result.tests[0].skipped,
+            Some(SkipReason::Filtered("has skipped tag 'e2e'".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout_fails_test_and_run_continues() {
+        // Given: One endpoint that answers after 5s and one that answers at once
+        let server = MockServer::start().await;
+        Mock::given(path("/slow"))
+            .respond_with(ResponseTemplate::new(402).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+        Mock::given(path("/fast"))
+            .respond_with(ResponseTemplate::new(402))
+            .mount(&server)
+            .await;
+
+        let suite = <TestSuite as FromStr>::from_str(&format!(
+            r#"
+timeout_ms: 10000
+tests:
+  - name: "Hung endpoint"
+    url: "{uri}/slow"
+    timeout_ms: 100
+    expect:
+      status: 402
+  - name: "Fast endpoint"
+    url: "{uri}/fast"
+    expect:
+      status: 402
+  - name: "Refused connection"
+    url: "http://127.0.0.1:1/api"
+    expect:
+      status: 402
+"#,
+            uri = server.uri()
+        ))
+        .unwrap();
+
+        // When: The suite runs
+        let start = Instant::now();
+        let result = execute_test_suite(&suite).await.unwrap();
+
+        // Then: The hung test fails as a timeout without stalling the run
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!((result.passed, result.failed), (1, 2));
+        let hung = &result.tests[0];
+        assert_eq!(hung.error_category, Some(ErrorCategory::Timeout));
+        assert_eq!(hung.error.as_deref(), Some("Request timed out after 100ms"));
+        assert!(result.tests[1].passed);
+
+        // And: Connection errors are reported separately
+        assert_eq!(
+            result.tests[2].error_category,
+            Some(ErrorCategory::Connection)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_total_timeout_skips_remaining_tests() {
+        // Given: A 300ms budget and a hung endpoint in the middle of the suite
+        let server = MockServer::start().await;
+        Mock::given(path("/slow"))
+            .respond_with(ResponseTemplate::new(402).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(402))
+            .mount(&server)
+            .await;
+
+        let suite = <TestSuite as FromStr>::from_str(&format!(
+            r#"
+total_timeout_ms: 300
+tests:
+  - name: "First"
+    url: "{uri}/fast"
+    expect:
+      status: 402
+  - name: "Hung"
+    url: "{uri}/slow"
+    expect:
+      status: 402
+  - name: "Never started"
+    url: "{uri}/fast"
+    expect:
+      status: 402
+"#,
+            uri = server.uri()
+        ))
+        .unwrap();
+
+        // When: The suite runs
+        let result = execute_test_suite(&suite).await.unwrap();
+
+        // Then: The request in flight is cancelled at the deadline
+        assert!(result.duration < Duration::from_secs(5));
+        assert_eq!((result.passed, result.failed, result.skipped), (1, 1, 1));
+        assert_eq!(result.tests[1].error_category, Some(ErrorCategory::Timeout));
+        assert_eq!(
+            result.tests[1].error.as_deref(),
+            Some("Request cancelled: suite total_timeout_ms of 300ms exceeded")
+        );
+
+        // And: Tests after it are skipped with the reason
+        assert_eq!(result.tests[2].skipped, Some(SkipReason::TotalTimeout(300)));
+        assert_eq!(result.exit_code(), 1);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_budget_skips_tests_after_cap() {
+        // Given: Three endpoints invoicing 0.20, 0.30 and 0.10 USDC and a
+        // budget that covers only the first two
+        let server = MockServer::start().await;
+        for (route, amount) in [("/a", "0.20"), ("/b", "0.30"), ("/c", "0.10")] {
+            Mock::given(path(route))
+                .respond_with(
+                    ResponseTemplate::new(402)
+                        .insert_header("WWW-Authenticate", INVOICE.replace("0.30", amount)),
+                )
+                .mount(&server)
+                .await;
+        }
+        let suite = <TestSuite as FromStr>::from_str(&format!(
+            r#"
+budget:
+  max_total: "0.50"
+  currency: USDC
+tests:
+  - name: "First"
+    url: "{uri}/a"
+    expect:
+      status: 402
+  - name: "Second"
+    url: "{uri}/b"
+    expect:
+      status: 402
+  - name: "Third"
+    url: "{uri}/c"
+    expect:
+      status: 402
+"#,
+            uri = server.uri()
+        ))
+        .unwrap();
+
+        // When: The suite runs
+        let result = execute_test_suite(&suite).await.unwrap();
+
+        // Then: The third test is skipped with the budget reason
+        assert_eq!((result.passed, result.failed, result.skipped), (2, 0, 1));
+        let reason = result.tests[2].skipped.as_ref().unwrap();
+        assert!(matches!(reason, SkipReason::BudgetExceeded { .. }));
+        assert_eq!(
+            reason.to_string(),
+            "budget exceeded: spent 0.50 of 0.50 USDC"
+        );
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+        // And: The total spend is reported exactly
+        assert_eq!(result.spent_in("USDC"), Decimal::from_str("0.50").unwrap());
+        assert_eq!(result.budget, suite.budget);
+        assert_eq!(result.exit_code(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_expect_error_matches_connection_faults() {
+        // Given: A hung endpoint, a fast one and a closed port, each expected
+        // to fail in a specific way
+        let server = MockServer::start().await;
+        Mock::given(path("/slow"))
+            .respond_with(ResponseTemplate::new(402).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+        Mock::given(path("/fast"))
+            .respond_with(ResponseTemplate::new(402))
+            .mount(&server)
+            .await;
+
+        let suite = <TestSuite as FromStr>::from_str(&format!(
+            r#"
+tests:
+  - name: "Hung endpoint times out"
+    url: "{uri}/slow"
+    timeout_ms: 100
+    expect:
+      expect_error:
+        category: timeout
+        max_duration_ms: 1000
+  - name: "Closed port refuses"
+    url: "http://127.0.0.1:1/api"
+    expect:
+      expect_error:
+        category: connection_refused
+        max_duration_ms: 5000
+  - name: "Endpoint unexpectedly answers"
+    url: "{uri}/fast"
+    expect:
+      expect_error:
+        category: timeout
+        max_duration_ms: 1000
+  - name: "Wrong fault"
+    url: "http://127.0.0.1:1/api"
+    expect:
+      expect_error:
+        category: timeout
+"#,
+            uri = server.uri()
+        ))
+        .unwrap();
+
+        // When: The suite runs
+        let result = execute_test_suite(&suite).await.unwrap();
+
+        // Then: Matching faults pass, without an error
+        assert_eq!((result.passed, result.failed), (2, 2));
+        let timed_out = &result.tests[0];
+        assert!(timed_out.passed, "{:?}", timed_out);
+        assert!(timed_out.error.is_none());
+        assert!(timed_out.duration < Duration::from_secs(2));
+        assert_eq!(timed_out.assertions[1].actual, "100ms");
+        assert!(result.tests[1].passed, "{:?}", result.tests[1]);
+
+        // And: A response or a different fault fails the test
+        let answered = &result.tests[2];
+        assert!(!answered.passed);
+        assert_eq!(answered.assertions.len(), 1);
+        assert_eq!(answered.assertions[0].actual, "HTTP 402 response");
+        let wrong = &result.tests[3];
+        assert!(!wrong.passed);
+        assert_eq!(wrong.assertions[0].expected, "timeout");
+        assert_eq!(wrong.assertions[0].actual, "connection_refused");
+        assert_eq!(wrong.error_category, Some(ErrorCategory::Connection));
+    }
+
+    #[tokio::test]
+    async fn test_header_mismatch_carries_failure_detail() {
+        // Given: A server whose invoice amount differs from the expected header
+        let server = MockServer::start().await;
+        Mock::given(any())
+            .respond_with(
+                ResponseTemplate::new(402)
+                    .insert_header("WWW-Authenticate", INVOICE.replace("0.30", "0.35"))
+                    .insert_header("Cache-Control", "no-cache"),
+            )
+            .mount(&server)
+            .await;
+        let suite = <TestSuite as FromStr>::from_str(&format!(
+            r#"
+tests:
+  - name: "headers"
+    url: "{}/api/data"
+    expect:
+      headers:
+        - name: WWW-Authenticate
+          value: "{}"
+        - name: Cache-Control
+          contains: no-store
+"#,
+            server.uri(),
+            INVOICE
+        ))
+        .unwrap();
+
+        // When: The suite runs
+        let result = execute_test_suite(&suite).await.unwrap();
+
+        // Then: Each failed header assertion explains the difference
+        let assertions = &result.tests[0].assertions;
+        assert_eq!(
+            assertions[0].failure_detail,
+            Some(FailureDetail::InvoiceFields {
+                differences: vec![FieldDifference {
+                    field: "amount".to_string(),
+                    expected: Some("0.30".to_string()),
+                    actual: Some("0.35".to_string()),
+                }]
+            })
+        );
+        assert!(matches!(
+            assertions[1].failure_detail,
+            Some(FailureDetail::Text {
+                first_divergence: 3,
+                offset: 0,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invoice_amount_operators() {
+        let yaml = r#"
+amount:
+  equals: 0.3
+  less_than: "0.31"
+  greater_than: 0.3
+"#;
+        let result = run_invoice_test(Some(INVOICE), serde_json::json!({}), yaml).await;
+
+        assert!(!result.passed);
+        assert_eq!(
+            outcomes(&result),
+            vec![
+                (true, "== 0.3", "0.30"),
+                (true, "< 0.31", "0.30"),
+                (false, "> 0.3", "0.30"),
+            ]
+        );
+        assert_eq!(
+            result.assertions[0].invoice_field.as_deref(),
+            Some("amount")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invoice_amount_compares_as_decimal() {
+        // As f64 both sides round to 0.1 and the comparison would fail
+        let header = INVOICE.replace("amount=0.30", "amount=0.1000000000000000001");
+        let yaml = r#"
+amount:
+  greater_than: 0.1
+  less_than: "0.1000000000000000002"
+"#;
+        let result = run_invoice_test(Some(&header), serde_json::json!({}), yaml).await;
+
+        assert!(result.passed, "{:?}", result.assertions);
+        assert_eq!(result.assertions[0].actual, "0.1000000000000000001");
+    }
+
+    #[tokio::test]
+    async fn test_invoice_field_operators() {
+        let yaml = r#"
+currency: USDC
+network: mainnet-beta
+recipient_in: [Other1111, GXk8vTest1111111111111111111111111111qPz9]
+memo_prefix: req-
+"#;
+        let result = run_invoice_test(Some(INVOICE), serde_json::json!({}), yaml).await;
+
+        assert!(!result.passed);
+        assert_eq!(
+            outcomes(&result),
+            vec![
+                (true, "USDC", "USDC"),
+                (false, "mainnet-beta", "devnet"),
+                (
+                    true,
+                    "one of [Other1111, GXk8vTest1111111111111111111111111111qPz9]",
+                    "GXk8vTest1111111111111111111111111111qPz9"
+                ),
+                (true, "starts with 'req-'", "req-123"),
+            ]
+        );
+
+        let yaml = "recipient_in: [Other1111]\nmemo_prefix: inv-";
+        let result = run_invoice_test(Some(INVOICE), serde_json::json!({}), yaml).await;
+        assert!(result.assertions.iter().all(|a| !a.passed));
+        assert_eq!(result.assertions[1].actual, "req-123");
+    }
+
+    #[tokio::test]
+    async fn test_invoice_expires_within_seconds() {
+        let soon = (chrono::Utc::now() + chrono::Duration::seconds(120)).to_rfc3339();
+        let body = serde_json::json!({ "invoice": { "expires_at": soon } });
+
+        let result =
+            run_invoice_test(Some(INVOICE), body.clone(), "expires_within_seconds: 300").await;
+        assert!(result.passed, "{:?}", result.assertions);
+        assert!(result.assertions[0].actual.starts_with("expires in 1"));
+
+        let result = run_invoice_test(Some(INVOICE), body, "expires_within_seconds: 60").await;
+        assert!(!result.passed);
+
+        // A header field takes precedence over the body
+        let past = (chrono::Utc::now() - chrono::Duration::seconds(10)).to_rfc3339();
+        let header = format!("{} expires=\"{}\"", INVOICE, past);
+        let result = run_invoice_test(
+            Some(&header),
+            serde_json::json!({}),
+            "expires_within_seconds: 300",
+        )
+        .await;
+        assert!(!result.passed);
+        assert!(result.assertions[0].actual.starts_with("expired"));
+
+        let result = run_invoice_test(
+            Some(INVOICE),
+            serde_json::json!({}),
+            "expires_within_seconds: 300",
+        )
+        .await;
+        assert_eq!(result.assertions[0].actual, "no expiry in header or body");
+    }
+
+    #[tokio::test]
+    async fn test_invoice_assertions_skipped_without_header() {
+        let yaml = "currency: USDC\nnetwork: devnet";
+        let result = run_invoice_test(None, serde_json::json!({}), yaml).await;
+
+        assert!(!result.passed);
+        assert_eq!(result.assertions.len(), 1);
+        assert_eq!(
+            result.assertions[0].description,
+            "Invoice assertions skipped: response has no WWW-Authenticate header"
+        );
+
+        let result = run_invoice_test(Some("Bearer realm=x"), serde_json::json!({}), yaml).await;
+        assert!(result.assertions[0]
+            .description
+            .contains("WWW-Authenticate header is invalid"));
+    }
+
+    #[tokio::test]
+    async fn test_request_headers_repeat_and_variables() {
+        // Given: A server that rate limits agent-a after two requests
+        let server = MockServer::start().await;
+        Mock::given(header("X-Agent-Id", "agent-a"))
+            .respond_with(ResponseTemplate::new(402))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(429))
+            .mount(&server)
+            .await;
+
+        let mut suite = <TestSuite as FromStr>::from_str(
+            r#"
+variables:
+  base_url: "http://127.0.0.1:1"
+  agent: agent-a
+tests:
+  - name: "Third request is limited"
+    url: "${base_url}/api/data"
+    headers:
+      X-Agent-Id: "${agent}"
+    repeat: 3
+    expect:
+      status: 429
+      headers:
+        - name: WWW-Authenticate
+          exists: false
+"#,
+        )
+        .unwrap();
+        // --var overrides the suite default
+        suite.variables.insert("base_url".to_string(), server.uri());
+
+        let result = execute_test_suite(&suite).await.unwrap();
+        assert_eq!(result.passed, 1, "{:?}", result.tests[0]);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+
+        // Undefined variables fail the test instead of being sent literally
+        suite.tests[0].url = "${missing}/api".to_string();
+        let result = execute_test_suite(&suite).await.unwrap();
+        assert!(result.tests[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("Undefined variable '${missing}'"));
+    }
+
+    #[tokio::test]
+    async fn test_then_step_sends_extracted_values() {
+        // Given: A server that wants the memo from its invoice in a JSON body
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/data"))
+            .and(body_json(
+                serde_json::json!({ "memo": "req-123", "attempt": 2 }),
+            ))
+            .respond_with(ResponseTemplate::new(200).set_body_string("paid req-123"))
+            .mount(&server)
+            .await;
+        Mock::given(path("/api/data"))
+            .respond_with(ResponseTemplate::new(402).insert_header("WWW-Authenticate", INVOICE))
+            .mount(&server)
+            .await;
+
+        let yaml = format!(
+            r#"
+tests:
+  - name: "Pay with the memo"
+    url: "{}/api/data"
+    expect:
+      status: 402
+    extract:
+      memo:
+        header: WWW-Authenticate
+        regex: 'memo=(\S+)'
+    then:
+      method: POST
+      body:
+        memo: "${{memo}}"
+        attempt: 2
+      expect:
+        status: 200
+        body_contains: "paid ${{memo}}"
+"#,
+            server.uri()
+        );
+        let mut suite = <TestSuite as FromStr>::from_str(&yaml).unwrap();
+
+        // When/Then: Both steps pass, labelled in the report
+        let result = execute_test_suite(&suite).await.unwrap();
+        let test = &result.tests[0];
+        assert!(test.passed, "{:?}", test);
+        let descriptions: Vec<&str> = test
+            .assertions
+            .iter()
+            .map(|a| a.description.as_str())
+            .collect();
+        assert_eq!(
+            descriptions,
+            [
+                "step 1: Status code is 402",
+                "step 1: Extract 'memo' from header 'WWW-Authenticate'",
+                "step 2: Status code is 200",
+                "step 2: Body contains 'paid req-123'",
+            ]
+        );
+
+        // A failed extraction stops the chain before the second request
+        suite.tests[0].extract.get_mut("memo").unwrap().regex = Some("signer=(\\S+)".to_string());
+        let before = server.received_requests().await.unwrap().len();
+        let result = execute_test_suite(&suite).await.unwrap();
+        assert!(!result.tests[0].passed);
+        assert_eq!(result.tests[0].assertions.len(), 2);
+        assert_eq!(server.received_requests().await.unwrap().len(), before + 1);
+    }
+
+    #[tokio::test]
+    async fn test_latency_budget_violations_report_measured_ms() {
+        // Given: A slow endpoint and an instant one
+        let server = MockServer::start().await;
+        Mock::given(path("/slow"))
+            .respond_with(ResponseTemplate::new(402).set_delay(Duration::from_millis(300)))
+            .mount(&server)
+            .await;
+        Mock::given(path("/cached"))
+            .respond_with(ResponseTemplate::new(402))
+            .mount(&server)
+            .await;
+
+        // A suite-wide budget, loosened by one test
+        let yaml = format!(
+            r#"
+latency:
+  max_duration_ms: 100
+tests:
+  - name: "Slow invoice"
+    url: "{uri}/slow"
+    expect:
+      status: 200
+  - name: "Cached invoice"
+    url: "{uri}/cached"
+    repeat: 2
+    expect:
+      status: 402
+      min_duration_ms: 200
+      max_duration_ms: 1s
+  - name: "Slow but allowed"
+    url: "{uri}/slow"
+    expect:
+      status: 402
+      max_duration_ms: 5s
+"#,
+            uri = server.uri()
+        );
+        let suite = <TestSuite as FromStr>::from_str(&yaml).unwrap();
+
+        // When: Running the suite
+        let result = execute_test_suite(&suite).await.unwrap();
+
+        // Then: The functional failure comes first, the latency one after it
+        let slow = &result.tests[0];
+        assert!(!slow.passed);
+        assert_eq!(slow.assertions[0].description, "Status code is 200");
+        let latency = &slow.assertions[1];
+        assert_eq!(latency.description, "Responds within 100ms");
+        assert!(!latency.passed);
+        let measured: u64 = latency.actual.trim_end_matches("ms").parse().unwrap();
+        assert!(measured >= 300, "{}", latency.actual);
+        assert_eq!(slow.latency[0].measured_ms, measured);
+
+        // The cached response is too fast; every attempt is recorded
+        let cached = &result.tests[1];
+        assert!(!cached.passed, "{:?}", cached);
+        assert_eq!(cached.latency[0].attempts_ms.len(), 2);
+        let minimum = &cached.assertions[2];
+        assert_eq!(minimum.description, "Takes at least 200ms");
+        assert_eq!(minimum.expected, ">= 200ms");
+        assert!(minimum.actual.ends_with("ms"), "{}", minimum.actual);
+
+        assert!(result.tests[2].passed, "{:?}", result.tests[2]);
+
+        let summary = result.latency_summary();
+        assert_eq!(
+            (summary.measured, summary.over_max, summary.under_min),
+            (3, 1, 1)
+        );
+        let report =
+            crate::testing::format_summary(&result, true, &x402_domain::AmountFormatter::default());
+        assert!(
+            report.contains("2 of 3 outside budget (1 over max, 1 under min)"),
+            "{}",
+            report
+        );
     }
 }
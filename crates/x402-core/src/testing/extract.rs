@@ -0,0 +1,241 @@
+// Values captured from a response for later steps (`extract:` entries)
+//
+// A multi-step test pulls values such as the invoice memo out of one
+// response and sends them in the next request as `${name}`. Sources are a
+// response header or a path into the JSON body, optionally narrowed by a
+// regex (its first capture group, or the whole match without groups).
+
+use regex::Regex;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+/// Where to read one extracted variable from
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Extraction {
+    /// Response header to read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    /// Path into the JSON body, e.g. `$.invoice.memo` or `items[0].id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_path: Option<String>,
+    /// Keep the first capture group (or the whole match) of this regex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<String>,
+}
+
+impl Extraction {
+    /// Check the entry names exactly one source and a valid regex
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.header, &self.json_path) {
+            (Some(_), None) | (None, Some(_)) => {}
+            _ => return Err("needs exactly one of header or json_path".to_string()),
+        }
+        if let Some(path) = &self.json_path {
+            parse_json_path(path)?;
+        }
+        if let Some(pattern) = &self.regex {
+            Regex::new(pattern).map_err(|e| format!("invalid regex: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Human-readable source, e.g. `header 'WWW-Authenticate'`
+    pub fn source(&self) -> String {
+        match (&self.header, &self.json_path) {
+            (Some(header), _) => format!("header '{}'", header),
+            (_, Some(path)) => format!("JSON path '{}'", path),
+            _ => "nothing".to_string(),
+        }
+    }
+
+    /// True if the value comes from the body rather than a header
+    pub fn reads_body(&self) -> bool {
+        self.json_path.is_some()
+    }
+
+    /// Read the value from a response's headers and (parsed JSON) body
+    pub fn extract(
+        &self,
+        headers: &HeaderMap,
+        body: Option<&serde_json::Value>,
+    ) -> Result<String, String> {
+        let raw = if let Some(name) = &self.header {
+            headers
+                .get(name)
+                .ok_or_else(|| "header missing".to_string())?
+                .to_str()
+                .map_err(|_| "header is not valid text".to_string())?
+                .to_string()
+        } else if let Some(path) = &self.json_path {
+            let body = body.ok_or_else(|| "body is not JSON".to_string())?;
+            match lookup(body, &parse_json_path(path)?) {
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(serde_json::Value::Null) | None => return Err("no value at path".to_string()),
+                Some(value) => value.to_string(),
+            }
+        } else {
+            return Err("needs exactly one of header or json_path".to_string());
+        };
+
+        let Some(pattern) = &self.regex else {
+            return Ok(raw);
+        };
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid regex: {}", e))?;
+        let captures = regex
+            .captures(&raw)
+            .ok_or_else(|| format!("'{}' does not match /{}/", raw, pattern))?;
+        let matched = captures.get(1).or_else(|| captures.get(0));
+        Ok(matched.map_or_else(String::new, |m| m.as_str().to_string()))
+    }
+}
+
+/// One step of a JSON path
+#[derive(Debug, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Split `$.invoice.items[0].id` into keys and indexes
+fn parse_json_path(path: &str) -> Result<Vec<Segment>, String> {
+    let invalid = |reason: &str| format!("invalid JSON path '{}': {}", path, reason);
+    let rest = path.strip_prefix('$').unwrap_or(path);
+    let rest = rest.strip_prefix('.').unwrap_or(rest);
+    if rest.is_empty() {
+        return Err(invalid("path is empty"));
+    }
+
+    let mut segments = Vec::new();
+    for part in rest.split('.') {
+        let (key, mut indexes) = match part.find('[') {
+            Some(at) => (&part[..at], &part[at..]),
+            None => (part, ""),
+        };
+        if key.is_empty() && indexes.is_empty() {
+            return Err(invalid("empty segment"));
+        }
+        if !key.is_empty() {
+            segments.push(Segment::Key(key.to_string()));
+        }
+        while !indexes.is_empty() {
+            let Some(close) = indexes.find(']') else {
+                return Err(invalid("missing ']'"));
+            };
+            let index = indexes[1..close]
+                .parse()
+                .map_err(|_| invalid("index must be a number"))?;
+            segments.push(Segment::Index(index));
+            indexes = &indexes[close + 1..];
+            if !indexes.is_empty() && !indexes.starts_with('[') {
+                return Err(invalid("unexpected text after ']'"));
+            }
+        }
+    }
+    Ok(segments)
+}
+
+fn lookup<'a>(value: &'a serde_json::Value, path: &[Segment]) -> Option<&'a serde_json::Value> {
+    path.iter().try_fold(value, |value, segment| match segment {
+        Segment::Key(key) => value.get(key),
+        Segment::Index(index) => value.get(*index),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn from_header(name: &str, regex: Option<&str>) -> Extraction {
+        Extraction {
+            header: Some(name.to_string()),
+            json_path: None,
+            regex: regex.map(str::to_string),
+        }
+    }
+
+    fn from_json(path: &str) -> Extraction {
+        Extraction {
+            header: None,
+            json_path: Some(path.to_string()),
+            regex: None,
+        }
+    }
+
+    #[test]
+    fn test_header_with_regex() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "www-authenticate",
+            HeaderValue::from_static("x402-solana amount=0.01 memo=req-123 network=devnet"),
+        );
+
+        let memo = from_header("WWW-Authenticate", Some(r"memo=(\S+)"));
+        assert_eq!(memo.extract(&headers, None).unwrap(), "req-123");
+
+        let whole = from_header("WWW-Authenticate", Some(r"req-\d+"));
+        assert_eq!(whole.extract(&headers, None).unwrap(), "req-123");
+
+        let missing = from_header("X-Memo", None).extract(&headers, None);
+        assert_eq!(missing.unwrap_err(), "header missing");
+
+        let no_match = from_header("WWW-Authenticate", Some(r"signer=(\S+)"));
+        assert!(no_match
+            .extract(&headers, None)
+            .unwrap_err()
+            .contains("does not match"));
+    }
+
+    #[test]
+    fn test_json_path() {
+        let body = serde_json::json!({
+            "invoice": { "memo": "req-123", "amount": 0.01 },
+            "items": [{ "id": "a" }, { "id": "b" }],
+        });
+        let headers = HeaderMap::new();
+
+        for (path, expected) in [
+            ("$.invoice.memo", "req-123"),
+            ("invoice.amount", "0.01"),
+            ("$.items[1].id", "b"),
+        ] {
+            assert_eq!(
+                from_json(path).extract(&headers, Some(&body)).unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            from_json("$.invoice.signer")
+                .extract(&headers, Some(&body))
+                .unwrap_err(),
+            "no value at path"
+        );
+        assert_eq!(
+            from_json("$.invoice.memo")
+                .extract(&headers, None)
+                .unwrap_err(),
+            "body is not JSON"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(from_header("X-Memo", Some("(.*)")).validate().is_ok());
+        assert!(from_json("$.items[0]").validate().is_ok());
+
+        let both = Extraction {
+            header: Some("X-Memo".to_string()),
+            ..from_json("$.memo")
+        };
+        assert!(both.validate().unwrap_err().contains("exactly one"));
+        assert!(from_json("$.items[x]")
+            .validate()
+            .unwrap_err()
+            .contains("index must be a number"));
+        assert!(from_header("X-Memo", Some("("))
+            .validate()
+            .unwrap_err()
+            .contains("invalid regex"));
+    }
+}
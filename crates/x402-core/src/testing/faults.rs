@@ -0,0 +1,182 @@
+// Connection-level faults for `expect_error:` tests
+//
+// Negative tests assert how a request fails rather than what it returns.
+// Failures are classified here and nowhere else: typed checks first (reqwest
+// flags, io::ErrorKind, hyper's "dns error"), then the OS messages that carry
+// no usable kind (e.g. Windows' "actively refused" text, TLS library errors).
+
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// How a request failed before producing a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum FaultKind {
+    /// No response within the test timeout
+    Timeout,
+    /// Nothing listening on the port (ECONNREFUSED / WSAECONNREFUSED)
+    ConnectionRefused,
+    /// The host name did not resolve
+    DnsFailure,
+    /// The TLS handshake failed (untrusted certificate, protocol mismatch)
+    TlsError,
+}
+
+impl fmt::Display for FaultKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FaultKind::Timeout => "timeout",
+            FaultKind::ConnectionRefused => "connection_refused",
+            FaultKind::DnsFailure => "dns_failure",
+            FaultKind::TlsError => "tls_error",
+        })
+    }
+}
+
+/// Message fragments of TLS failures from native-tls (OpenSSL, Secure
+/// Transport, SChannel) and rustls
+const TLS_MARKERS: [&str; 5] = ["certificate", "tls", "ssl", "handshake", "x509"];
+
+/// Classify a request failure, walking the whole source chain
+///
+/// Returns None for failures that are none of the known faults (e.g. a
+/// connection reset mid-response or an invalid URL).
+pub fn classify_fault(error: &(dyn Error + 'static)) -> Option<FaultKind> {
+    let chain = || std::iter::successors(Some(error), |e| (*e).source());
+
+    if chain().any(|e| {
+        e.downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_timeout)
+            || e.downcast_ref::<io::Error>()
+                .is_some_and(|io| io.kind() == io::ErrorKind::TimedOut)
+    }) {
+        return Some(FaultKind::Timeout);
+    }
+    // hyper reports resolver failures as "dns error" above the OS message
+    if chain().any(|e| e.to_string() == "dns error") {
+        return Some(FaultKind::DnsFailure);
+    }
+    if chain().any(|e| {
+        e.downcast_ref::<io::Error>()
+            .is_some_and(|io| io.kind() == io::ErrorKind::ConnectionRefused)
+    }) {
+        return Some(FaultKind::ConnectionRefused);
+    }
+
+    let messages: Vec<String> = chain().map(|e| e.to_string().to_lowercase()).collect();
+    let mentions = |needle: &str| messages.iter().any(|m| m.contains(needle));
+    if mentions("connection refused") || mentions("actively refused") {
+        Some(FaultKind::ConnectionRefused)
+    } else if mentions("failed to lookup address") || mentions("no such host") {
+        Some(FaultKind::DnsFailure)
+    } else if TLS_MARKERS.iter().any(|marker| mentions(marker)) {
+        Some(FaultKind::TlsError)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Error with a message and an optional cause, like hyper's ConnectError
+    #[derive(Debug)]
+    struct Wrapped {
+        message: &'static str,
+        source: Option<Box<dyn Error + 'static>>,
+    }
+
+    impl fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl Error for Wrapped {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.source.as_deref()
+        }
+    }
+
+    fn wrap(message: &'static str, source: impl Error + 'static) -> Wrapped {
+        Wrapped {
+            message,
+            source: Some(Box::new(source)),
+        }
+    }
+
+    fn leaf(message: &'static str) -> Wrapped {
+        Wrapped {
+            message,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_typed_causes() {
+        let refused = wrap(
+            "tcp connect error",
+            io::Error::from(io::ErrorKind::ConnectionRefused),
+        );
+        assert_eq!(
+            classify_fault(&wrap("error sending request", refused)),
+            Some(FaultKind::ConnectionRefused)
+        );
+
+        let timed_out = wrap(
+            "tcp connect error",
+            io::Error::from(io::ErrorKind::TimedOut),
+        );
+        assert_eq!(classify_fault(&timed_out), Some(FaultKind::Timeout));
+
+        let dns = wrap(
+            "dns error",
+            io::Error::other("failed to lookup address information"),
+        );
+        assert_eq!(
+            classify_fault(&wrap("client error (Connect)", dns)),
+            Some(FaultKind::DnsFailure)
+        );
+    }
+
+    #[test]
+    fn test_os_specific_messages() {
+        for (message, expected) in [
+            // Linux / macOS
+            ("Connection refused (os error 111)", FaultKind::ConnectionRefused),
+            ("Connection refused (os error 61)", FaultKind::ConnectionRefused),
+            // Windows, when the kind is lost behind another error type
+            (
+                "No connection could be made because the target machine actively refused it. (os error 10061)",
+                FaultKind::ConnectionRefused,
+            ),
+            (
+                "failed to lookup address information: Name or service not known",
+                FaultKind::DnsFailure,
+            ),
+            ("No such host is known. (os error 11001)", FaultKind::DnsFailure),
+            (
+                "error:0A000086:SSL routines:tls_post_process_server_certificate:certificate verify failed",
+                FaultKind::TlsError,
+            ),
+            ("invalid peer certificate: UnknownIssuer", FaultKind::TlsError),
+        ] {
+            let error = wrap("client error (Connect)", leaf(message));
+            assert_eq!(classify_fault(&error), Some(expected), "{}", message);
+        }
+    }
+
+    #[test]
+    fn test_unknown_failures_unclassified() {
+        let reset = wrap(
+            "connection error",
+            io::Error::from(io::ErrorKind::ConnectionReset),
+        );
+        assert_eq!(classify_fault(&reset), None);
+        assert_eq!(classify_fault(&leaf("builder error")), None);
+    }
+}
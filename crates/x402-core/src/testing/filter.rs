@@ -0,0 +1,134 @@
+// Test selection by tags and name (--tag, --skip-tag, --only)
+
+use super::parser::Test;
+
+/// Selection criteria applied to a test suite before execution
+///
+/// All criteria combine with AND semantics: a test runs only if it carries
+/// every requested tag, none of the skipped tags, and its name contains the
+/// `only` substring.
+#[derive(Debug, Clone, Default)]
+pub struct TestFilter {
+    /// Tags a test must carry (all of them)
+    pub tags: Vec<String>,
+    /// Tags that exclude a test (any of them)
+    pub skip_tags: Vec<String>,
+    /// Substring the test name must contain
+    pub only: Option<String>,
+}
+
+impl TestFilter {
+    /// True when no criteria are set and every test is selected
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.skip_tags.is_empty() && self.only.is_none()
+    }
+
+    /// Whether the test passes all selection criteria
+    pub fn matches(&self, test: &Test) -> bool {
+        self.exclusion_reason(test).is_none()
+    }
+
+    /// Explain why a test is excluded, or `None` if it is selected
+    pub fn exclusion_reason(&self, test: &Test) -> Option<String> {
+        if let Some(missing) = self.tags.iter().find(|t| !test.tags.contains(t)) {
+            return Some(format!("missing tag '{}'", missing));
+        }
+
+        if let Some(skipped) = self.skip_tags.iter().find(|t| test.tags.contains(t)) {
+            return Some(format!("has skipped tag '{}'", skipped));
+        }
+
+        if let Some(only) = &self.only {
+            if !test.name.contains(only.as_str()) {
+                return Some(format!("name does not contain '{}'", only));
+            }
+        }
+
+        None
+    }
+
+    /// Count tests selected by this filter
+    pub fn selected_count(&self, tests: &[Test]) -> usize {
+        tests.iter().filter(|t| self.matches(t)).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::parser::TestSuite;
+    use std::str::FromStr;
+
+    fn suite() -> TestSuite {
+        <TestSuite as FromStr>::from_str(
+            r#"
+tests:
+  - name: "Fast header check"
+    url: "http://localhost:3402/a"
+    tags: [fast, headers]
+    expect:
+      status: 402
+  - name: "Full payment flow"
+    url: "http://localhost:3402/b"
+    tags: [e2e]
+    expect:
+      status: 402
+  - name: "Fast e2e smoke"
+    url: "http://localhost:3402/c"
+    tags: [fast, e2e]
+    expect:
+      status: 402
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_empty_filter_selects_all() {
+        let filter = TestFilter::default();
+        assert!(filter.is_empty());
+        assert_eq!(filter.selected_count(&suite().tests), 3);
+    }
+
+    #[test]
+    fn test_tag_and_skip_tag_combine() {
+        let filter = TestFilter {
+            tags: vec!["fast".to_string()],
+            skip_tags: vec!["e2e".to_string()],
+            only: None,
+        };
+        let tests = suite().tests;
+
+        assert!(filter.matches(&tests[0]));
+        assert_eq!(
+            filter.exclusion_reason(&tests[1]).unwrap(),
+            "missing tag 'fast'"
+        );
+        assert_eq!(
+            filter.exclusion_reason(&tests[2]).unwrap(),
+            "has skipped tag 'e2e'"
+        );
+    }
+
+    #[test]
+    fn test_only_combines_with_tags() {
+        let filter = TestFilter {
+            tags: vec!["e2e".to_string()],
+            skip_tags: vec![],
+            only: Some("smoke".to_string()),
+        };
+        let tests = suite().tests;
+
+        assert_eq!(filter.selected_count(&tests), 1);
+        assert!(filter.matches(&tests[2]));
+    }
+
+    #[test]
+    fn test_no_tests_selected() {
+        let filter = TestFilter {
+            tags: vec!["nonexistent".to_string()],
+            ..TestFilter::default()
+        };
+        assert_eq!(filter.selected_count(&suite().tests), 0);
+    }
+}
@@ -0,0 +1,208 @@
+// GitHub Actions workflow commands (--format github)
+//
+// Failures are written as `::error` / `::warning` commands, which the
+// runner turns into inline annotations on the run and the PR, and a
+// suite's log is folded into a `::group::`. Values are escaped the way the
+// runner unescapes them: `%`, CR and LF everywhere, plus `:` and `,` inside
+// properties such as `title`.
+
+use super::executor::{SuiteResult, TestResult};
+use std::fmt;
+
+/// Closes the group opened by [`group`]
+pub const END_GROUP: &str = "::endgroup::";
+
+/// Severity of an annotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationLevel {
+    Error,
+    Warning,
+}
+
+/// One `::error` / `::warning` workflow command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub level: AnnotationLevel,
+    pub title: String,
+    pub message: String,
+    /// File the annotation points at, relative to the repository root
+    pub file: Option<String>,
+}
+
+impl Annotation {
+    pub fn error(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            level: AnnotationLevel::Error,
+            title: title.into(),
+            message: message.into(),
+            file: None,
+        }
+    }
+
+    pub fn warning(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            level: AnnotationLevel::Warning,
+            ..Self::error(title, message)
+        }
+    }
+
+    /// Attach the annotation to `file`
+    pub fn in_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let command = match self.level {
+            AnnotationLevel::Error => "error",
+            AnnotationLevel::Warning => "warning",
+        };
+        let mut properties = Vec::new();
+        if let Some(file) = &self.file {
+            properties.push(format!("file={}", escape_property(file)));
+        }
+        properties.push(format!("title={}", escape_property(&self.title)));
+        write!(
+            f,
+            "::{} {}::{}",
+            command,
+            properties.join(","),
+            escape_data(&self.message)
+        )
+    }
+}
+
+/// `::group::` line starting a collapsible section named `name`
+pub fn group(name: &str) -> String {
+    format!("::group::{}", escape_data(name))
+}
+
+/// Escape a command message
+pub fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a command property value
+pub fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// One error annotation per failed pre-flight check and per failed test
+/// (skipped tests are not annotated)
+///
+/// Suites carry no source locations yet, so annotations are titled with the
+/// test name rather than pointing at a line of the suite file.
+pub fn test_annotations(result: &SuiteResult) -> Vec<Annotation> {
+    let preflight = result.preflight.iter().flat_map(|report| {
+        report.failures().map(|check| {
+            Annotation::error(
+                format!("Pre-flight {} check", check.kind),
+                check.error.clone().unwrap_or_default(),
+            )
+        })
+    });
+    preflight
+        .chain(
+            result
+                .tests
+                .iter()
+                .filter(|test| !test.passed && test.skipped.is_none())
+                .map(|test| Annotation::error(&test.name, failure_message(test))),
+        )
+        .collect()
+}
+
+fn failure_message(test: &TestResult) -> String {
+    let mut lines = vec![format!("{} {}", test.method, test.url)];
+    if let Some(error) = &test.error {
+        match test.error_category {
+            Some(category) => lines.push(format!("Error ({}): {}", category, error)),
+            None => lines.push(format!("Error: {}", error)),
+        }
+    }
+    for assertion in test.assertions.iter().filter(|a| !a.passed) {
+        match &assertion.failure_detail {
+            Some(detail) => lines.push(format!(
+                "{}\n{}",
+                assertion.description,
+                detail.render().trim_end()
+            )),
+            None => lines.push(format!(
+                "{}: expected {}, got {}",
+                assertion.description, assertion.expected, assertion.actual
+            )),
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::AssertionResult;
+    use std::time::Duration;
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!(escape_data("50%\r\ndone"), "50%25%0D%0Adone");
+        assert_eq!(escape_property("a::b, c%"), "a%3A%3Ab%2C c%25");
+
+        let annotation = Annotation::warning("Baseline: drift", "amount changed\n0.01 → 0.02")
+            .in_file("checks/api.yaml");
+        assert_eq!(
+            annotation.to_string(),
+            "::warning file=checks/api.yaml,title=Baseline%3A drift::amount changed%0A0.01 → 0.02"
+        );
+    }
+
+    #[test]
+    fn test_failed_tests_annotated() {
+        let test = |name: &str, passed: bool| TestResult {
+            name: name.to_string(),
+            url: "http://localhost:3402/api".to_string(),
+            method: "GET".to_string(),
+            passed,
+            duration: Duration::ZERO,
+            assertions: vec![AssertionResult {
+                passed,
+                description: "Status code is 402".to_string(),
+                expected: "402".to_string(),
+                actual: "200".to_string(),
+                invoice_field: None,
+                failure_detail: None,
+            }],
+            error: None,
+            error_category: None,
+            skipped: None,
+            http_version: None,
+            encoded_size: None,
+            extends: None,
+            latency: Vec::new(),
+        };
+        let result = SuiteResult {
+            tests: vec![test("ok", true), test("api::data", false)],
+            total: 2,
+            passed: 1,
+            failed: 1,
+            skipped: 0,
+            duration: Duration::ZERO,
+            spent: Default::default(),
+            budget: None,
+            preflight: None,
+        };
+
+        let annotations: Vec<String> = test_annotations(&result)
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+
+        assert_eq!(
+            annotations,
+            ["::error title=api%3A%3Adata::GET http://localhost:3402/api%0AStatus code is 402: expected 402, got 200"]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+// HEAD parity check for 402 responses
+//
+// Clients probe endpoints with HEAD before paying, so HEAD must answer like
+// GET without a body: same status and an invoice with the same terms.
+// Per-request fields (memo, expiry, rotated recipient, signature) may differ.
+
+use super::invoice_header::InvoiceHeader;
+
+/// Invoice fields that must agree between the GET and HEAD invoices
+pub const HEAD_PARITY_FIELDS: [&str; 3] = ["amount", "currency", "network"];
+
+/// Status, invoice and body length of one response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProbeResponse<'a> {
+    pub status: u16,
+    /// WWW-Authenticate value, if any
+    pub invoice: Option<&'a str>,
+    pub body_len: usize,
+}
+
+/// Outcome of comparing a HEAD response with the GET response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadParityCheck {
+    /// True if HEAD answered like GET without a body
+    pub passed: bool,
+    /// What was found
+    pub detail: String,
+    /// How to fix the server (only set on failure)
+    pub suggestion: Option<String>,
+}
+
+const HEAD_SUGGESTION: &str =
+    "Answer HEAD like GET (same status and WWW-Authenticate invoice) and send no body";
+
+/// Compare the HEAD response for a URL with its GET response
+pub fn check_head_parity(get: ProbeResponse<'_>, head: ProbeResponse<'_>) -> HeadParityCheck {
+    let mut reasons = Vec::new();
+
+    if head.status != get.status {
+        reasons.push(format!("HEAD returned {}, GET {}", head.status, get.status));
+    }
+
+    match (get.invoice, head.invoice) {
+        (Some(_), None) => reasons.push("HEAD response has no WWW-Authenticate".to_string()),
+        (Some(get_value), Some(head_value)) => {
+            // HEAD must use the same protocol identifier as GET, whichever it is
+            let protocol = get_value.split_whitespace().next().unwrap_or_default();
+            match (
+                InvoiceHeader::parse_with_protocols(get_value, &[protocol]),
+                InvoiceHeader::parse_with_protocols(head_value, &[protocol]),
+            ) {
+                (Ok(get_invoice), Ok(head_invoice)) => {
+                    let names = |invoice: &InvoiceHeader| {
+                        let mut names: Vec<String> =
+                            invoice.fields.iter().map(|f| f.name.clone()).collect();
+                        names.sort();
+                        names
+                    };
+                    if names(&get_invoice) != names(&head_invoice) {
+                        reasons.push("invoice fields differ from GET".to_string());
+                    }
+                    for field in HEAD_PARITY_FIELDS {
+                        let (expected, actual) = (get_invoice.get(field), head_invoice.get(field));
+                        if expected != actual {
+                            reasons.push(format!(
+                                "{} {} (GET: {})",
+                                field,
+                                actual.unwrap_or("missing"),
+                                expected.unwrap_or("missing")
+                            ));
+                        }
+                    }
+                }
+                (Ok(_), Err(e)) => reasons.push(format!("HEAD invoice is invalid: {}", e)),
+                // GET's own invoice errors are reported by the structure checks
+                (Err(_), _) => {}
+            }
+        }
+        (None, _) => {}
+    }
+
+    if head.body_len > 0 {
+        reasons.push(format!("HEAD response has a {}-byte body", head.body_len));
+    }
+
+    if reasons.is_empty() {
+        HeadParityCheck {
+            passed: true,
+            detail: format!("{} with matching invoice, no body", head.status),
+            suggestion: None,
+        }
+    } else {
+        HeadParityCheck {
+            passed: false,
+            detail: reasons.join("; "),
+            suggestion: Some(HEAD_SUGGESTION.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GET_INVOICE: &str =
+        "x402-solana recipient=GXk8v amount=0.01 currency=USDC memo=req-1 network=devnet";
+
+    fn probe(status: u16, invoice: Option<&str>, body_len: usize) -> ProbeResponse<'_> {
+        ProbeResponse {
+            status,
+            invoice,
+            body_len,
+        }
+    }
+
+    #[test]
+    fn test_matching_terms_pass_despite_new_memo() {
+        let head_invoice =
+            "x402-solana recipient=HYl9w amount=0.01 currency=USDC memo=req-2 network=devnet";
+
+        let check = check_head_parity(
+            probe(402, Some(GET_INVOICE), 120),
+            probe(402, Some(head_invoice), 0),
+        );
+
+        assert!(check.passed, "{}", check.detail);
+        assert!(check.suggestion.is_none());
+    }
+
+    #[test]
+    fn test_status_amount_and_body_mismatches() {
+        let head_invoice =
+            "x402-solana recipient=GXk8v amount=0.02 currency=USDC memo=req-2 network=devnet";
+
+        let check = check_head_parity(
+            probe(402, Some(GET_INVOICE), 120),
+            probe(402, Some(head_invoice), 64),
+        );
+        assert!(!check.passed);
+        assert_eq!(
+            check.detail,
+            "amount 0.02 (GET: 0.01); HEAD response has a 64-byte body"
+        );
+
+        let check = check_head_parity(probe(402, Some(GET_INVOICE), 120), probe(405, None, 0));
+        assert_eq!(
+            check.detail,
+            "HEAD returned 405, GET 402; HEAD response has no WWW-Authenticate"
+        );
+    }
+
+    #[test]
+    fn test_extra_field_on_head_fails() {
+        let head_invoice = format!("{} expires=2030-01-01T00:00:00Z", GET_INVOICE);
+
+        let check = check_head_parity(
+            probe(402, Some(GET_INVOICE), 0),
+            probe(402, Some(&head_invoice), 0),
+        );
+
+        assert_eq!(check.detail, "invoice fields differ from GET");
+    }
+}
@@ -0,0 +1,280 @@
+// Structured diffs for failed header assertions
+//
+// WWW-Authenticate values are compared field by field through the shared
+// invoice header parser, so a changed amount is reported as one field
+// rather than as two long strings. Other headers get a character-level
+// comparison pointing at the first divergent index.
+
+use super::invoice_header::{InvoiceHeader, SCHEME};
+use serde::Serialize;
+
+/// Header holding the x402 invoice
+const INVOICE_HEADER: &str = "WWW-Authenticate";
+
+/// Why a header assertion failed, in a form reporters can render
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FailureDetail {
+    /// Invoice fields whose values differ
+    InvoiceFields { differences: Vec<FieldDifference> },
+    /// Character-level comparison of the two values
+    Text {
+        expected: String,
+        actual: String,
+        /// Character index into `expected` of the first mismatch
+        first_divergence: usize,
+        /// Character index into `actual` the comparison starts at
+        /// (0 for equality, the best match for `contains`)
+        offset: usize,
+    },
+}
+
+/// One invoice field that differs (None = field missing on that side)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldDifference {
+    pub field: String,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+impl FailureDetail {
+    /// Detail for a failed `value:` assertion
+    pub fn for_equals(header: &str, expected: &str, actual: &str) -> Self {
+        if header.eq_ignore_ascii_case(INVOICE_HEADER) {
+            if let (Ok(expected), Ok(actual)) =
+                (InvoiceHeader::parse(expected), InvoiceHeader::parse(actual))
+            {
+                let mut names: Vec<&str> = Vec::new();
+                for field in expected.fields.iter().chain(&actual.fields) {
+                    if !names.contains(&field.name.as_str()) {
+                        names.push(&field.name);
+                    }
+                }
+                let differences = field_differences(&names, &expected, &actual);
+                // Same fields written differently (order, quoting): fall back to text
+                if !differences.is_empty() {
+                    return FailureDetail::InvoiceFields { differences };
+                }
+            }
+        }
+
+        FailureDetail::Text {
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+            first_divergence: common_prefix_len(expected, actual),
+            offset: 0,
+        }
+    }
+
+    /// Detail for a failed `contains:` assertion
+    pub fn for_contains(header: &str, substring: &str, actual: &str) -> Self {
+        if header.eq_ignore_ascii_case(INVOICE_HEADER) {
+            // `amount=0.01 currency=USDC` parses as the fields of an invoice
+            let fragment = substring.strip_prefix(SCHEME).unwrap_or(substring);
+            if let (Ok(expected), Ok(actual)) = (
+                InvoiceHeader::parse(&format!("{} {}", SCHEME, fragment)),
+                InvoiceHeader::parse(actual),
+            ) {
+                let names: Vec<&str> = expected.fields.iter().map(|f| f.name.as_str()).collect();
+                let differences = field_differences(&names, &expected, &actual);
+                if !differences.is_empty() {
+                    return FailureDetail::InvoiceFields { differences };
+                }
+            }
+        }
+
+        // Align on the longest prefix of the substring that does occur
+        let chars: Vec<char> = substring.chars().collect();
+        let (matched, offset) = (1..=chars.len())
+            .rev()
+            .find_map(|len| {
+                let prefix: String = chars[..len].iter().collect();
+                actual
+                    .find(&prefix)
+                    .map(|byte| (len, actual[..byte].chars().count()))
+            })
+            .unwrap_or((0, 0));
+
+        FailureDetail::Text {
+            expected: substring.to_string(),
+            actual: actual.to_string(),
+            first_divergence: matched,
+            offset,
+        }
+    }
+
+    /// Multi-line plain-text rendering shared by the reporters
+    pub fn render(&self) -> String {
+        match self {
+            FailureDetail::InvoiceFields { differences } => {
+                let mut out = String::from("Invoice fields differ:\n");
+                for diff in differences {
+                    out.push_str(&format!(
+                        "  {}: expected {}, got {}\n",
+                        diff.field,
+                        diff.expected.as_deref().unwrap_or("(missing)"),
+                        diff.actual.as_deref().unwrap_or("(missing)")
+                    ));
+                }
+                out
+            }
+            FailureDetail::Text {
+                expected,
+                actual,
+                first_divergence,
+                offset,
+            } => {
+                let (marker, shown) = if *offset > 0 {
+                    ("…", actual.chars().skip(*offset).collect::<String>())
+                } else {
+                    ("", actual.clone())
+                };
+                // Pad the expected line so both values start in the same column
+                let pad = " ".repeat(marker.chars().count());
+                format!(
+                    "expected: {}{}\nactual:   {}{}\n          {}{}^ first difference at index {}\n",
+                    pad,
+                    expected,
+                    marker,
+                    shown,
+                    pad,
+                    " ".repeat(*first_divergence),
+                    first_divergence
+                )
+            }
+        }
+    }
+}
+
+fn field_differences(
+    names: &[&str],
+    expected: &InvoiceHeader,
+    actual: &InvoiceHeader,
+) -> Vec<FieldDifference> {
+    names
+        .iter()
+        .filter_map(|name| {
+            let (expected, actual) = (expected.get(name), actual.get(name));
+            (expected != actual).then(|| FieldDifference {
+                field: name.to_string(),
+                expected: expected.map(str::to_string),
+                actual: actual.map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// Number of leading characters the two strings share
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.chars().zip(b.chars()).take_while(|(x, y)| x == y).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INVOICE: &str =
+        "x402-solana recipient=GXk8v amount=0.01 currency=USDC memo=req-1 network=devnet";
+
+    #[test]
+    fn test_invoice_equals_reports_changed_amount() {
+        let actual = INVOICE.replace("amount=0.01", "amount=0.02");
+
+        let detail = FailureDetail::for_equals("www-authenticate", INVOICE, &actual);
+
+        assert_eq!(
+            detail,
+            FailureDetail::InvoiceFields {
+                differences: vec![FieldDifference {
+                    field: "amount".to_string(),
+                    expected: Some("0.01".to_string()),
+                    actual: Some("0.02".to_string()),
+                }]
+            }
+        );
+        assert_eq!(
+            detail.render(),
+            "Invoice fields differ:\n  amount: expected 0.01, got 0.02\n"
+        );
+    }
+
+    #[test]
+    fn test_invoice_contains_compares_listed_fields() {
+        let detail =
+            FailureDetail::for_contains("WWW-Authenticate", "amount=0.05 network=devnet", INVOICE);
+
+        assert_eq!(
+            detail,
+            FailureDetail::InvoiceFields {
+                differences: vec![FieldDifference {
+                    field: "amount".to_string(),
+                    expected: Some("0.05".to_string()),
+                    actual: Some("0.01".to_string()),
+                }]
+            }
+        );
+    }
+
+    #[test]
+    fn test_generic_header_first_divergence() {
+        let detail = FailureDetail::for_equals("Cache-Control", "no-store", "no-cache");
+
+        assert_eq!(
+            detail,
+            FailureDetail::Text {
+                expected: "no-store".to_string(),
+                actual: "no-cache".to_string(),
+                first_divergence: 3,
+                offset: 0,
+            }
+        );
+        assert_eq!(
+            detail.render(),
+            "expected: no-store\nactual:   no-cache\n             ^ first difference at index 3\n"
+        );
+
+        // A value that is a prefix of the other diverges where it ends
+        match FailureDetail::for_equals("X-Price", "0.01", "0.010") {
+            FailureDetail::Text {
+                first_divergence, ..
+            } => assert_eq!(first_divergence, 4),
+            other => panic!("unexpected detail {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_contains_aligns_on_longest_match() {
+        let detail =
+            FailureDetail::for_contains("Cache-Control", "max-age=60", "public, max-age=30");
+
+        assert_eq!(
+            detail,
+            FailureDetail::Text {
+                expected: "max-age=60".to_string(),
+                actual: "public, max-age=30".to_string(),
+                first_divergence: 8,
+                offset: 8,
+            }
+        );
+        assert_eq!(
+            detail.render(),
+            "expected:  max-age=60\nactual:   …max-age=30\n                   ^ first difference at index 8\n"
+        );
+    }
+
+    #[test]
+    fn test_invoice_with_same_fields_falls_back_to_text() {
+        let reordered =
+            "x402-solana amount=0.01 recipient=GXk8v currency=USDC memo=req-1 network=devnet";
+
+        let detail = FailureDetail::for_equals("WWW-Authenticate", INVOICE, reordered);
+
+        assert!(matches!(
+            detail,
+            FailureDetail::Text {
+                first_divergence: 12,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,461 @@
+// Results history for `test --history-dir` and `test trends`
+//
+// Each run appends one JSON line to <dir>/history.jsonl: per-test status,
+// duration and attempt count, plus the time and git commit of the run. The
+// line is written with a single append-mode write, so CI jobs sharing a
+// directory interleave whole lines. Reading skips lines that do not parse
+// and reports them instead of failing, since one truncated write must not
+// hide the rest of the history.
+
+use super::executor::SuiteResult;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// File inside the history directory
+pub const HISTORY_FILE: &str = "history.jsonl";
+
+/// Runs `test trends` shows by default
+pub const DEFAULT_TREND_RUNS: usize = 20;
+
+/// Runs at the end of the window checked for new flakiness
+const RECENT_RUNS: usize = 5;
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Outcome of one test in one run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// One test of a recorded run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestRecord {
+    pub name: String,
+    pub status: RunStatus,
+    pub duration_ms: u64,
+    /// Times the test was run before its outcome was final
+    pub attempts: u32,
+}
+
+/// One line of the history file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+    /// Suite file as given on the command line
+    pub suite: String,
+    pub tests: Vec<TestRecord>,
+}
+
+impl RunRecord {
+    /// Summarize a suite run
+    pub fn from_result(
+        result: &SuiteResult,
+        suite: &str,
+        timestamp: DateTime<Utc>,
+        git_sha: Option<String>,
+    ) -> Self {
+        let tests = result
+            .tests
+            .iter()
+            .map(|test| TestRecord {
+                name: test.name.clone(),
+                status: if test.skipped.is_some() {
+                    RunStatus::Skipped
+                } else if test.passed {
+                    RunStatus::Passed
+                } else {
+                    RunStatus::Failed
+                },
+                duration_ms: u64::try_from(test.duration.as_millis()).unwrap_or(u64::MAX),
+                // The runner does not retry; every executed test ran once
+                attempts: u32::from(test.skipped.is_none()),
+            })
+            .collect();
+        Self {
+            timestamp,
+            git_sha,
+            suite: suite.to_string(),
+            tests,
+        }
+    }
+}
+
+/// A line of the history file that could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorruptLine {
+    /// 1-based line number
+    pub line: usize,
+    pub error: String,
+}
+
+/// Every readable run of a history file, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    pub runs: Vec<RunRecord>,
+    pub corrupt: Vec<CorruptLine>,
+}
+
+impl History {
+    /// Parse history file contents, skipping blank and corrupt lines
+    pub fn parse(contents: &str) -> Self {
+        let mut history = History::default();
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(run) => history.runs.push(run),
+                Err(e) => history.corrupt.push(CorruptLine {
+                    line: i + 1,
+                    error: e.to_string(),
+                }),
+            }
+        }
+        history
+    }
+
+    /// Read `<dir>/history.jsonl`; a missing file is an empty history
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(HISTORY_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Ok(Self::parse(&contents)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+}
+
+/// Append `run` as one line to `<dir>/history.jsonl`, creating both
+pub fn append_run(dir: &Path, run: &RunRecord) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(HISTORY_FILE);
+    let mut line = serde_json::to_string(run)?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    // One write per line: O_APPEND places it at the end as a whole
+    file.write_all(line.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// One test over the runs of a window, oldest first
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestTrend {
+    pub name: String,
+    /// Status per run of the window; None where the test was not recorded
+    pub statuses: Vec<Option<RunStatus>>,
+    /// Duration per run in which the test executed
+    pub durations_ms: Vec<Option<u64>>,
+    /// Passed runs over executed runs (0.0 if it never executed)
+    pub pass_rate: f64,
+    pub executed: usize,
+}
+
+impl TestTrend {
+    /// Statuses as text: ✓ passed, ✗ failed, - skipped, space when absent
+    pub fn status_line(&self) -> String {
+        self.statuses
+            .iter()
+            .map(|status| match status {
+                Some(RunStatus::Passed) => '✓',
+                Some(RunStatus::Failed) => '✗',
+                Some(RunStatus::Skipped) => '-',
+                None => ' ',
+            })
+            .collect()
+    }
+
+    /// Durations as a sparkline between the window's fastest and slowest run
+    pub fn duration_sparkline(&self) -> String {
+        sparkline(&self.durations_ms)
+    }
+}
+
+/// Trends of the last runs of a history
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trends {
+    /// Runs in the window
+    pub runs: usize,
+    /// Tests by name
+    pub tests: Vec<TestTrend>,
+    /// Tests that were reliable earlier in the window and are not recently
+    pub newly_flaky: Vec<String>,
+}
+
+/// Compute trends over the last `last` runs
+///
+/// A test is newly flaky when it passed on its first attempt in every run
+/// of the window before the recent ones (the last five, or the last half of
+/// a shorter window), and in the recent runs it needed more than one
+/// attempt, or both passed and failed.
+pub fn compute_trends(runs: &[RunRecord], last: usize) -> Trends {
+    let window = &runs[runs.len().saturating_sub(last)..];
+    let recent_start = window.len() - RECENT_RUNS.min(window.len() / 2);
+
+    let mut by_name: BTreeMap<&str, Vec<Option<&TestRecord>>> = BTreeMap::new();
+    for (i, run) in window.iter().enumerate() {
+        for test in &run.tests {
+            by_name
+                .entry(&test.name)
+                .or_insert_with(|| vec![None; window.len()])[i] = Some(test);
+        }
+    }
+
+    let mut tests = Vec::new();
+    let mut newly_flaky = Vec::new();
+    for (name, records) in by_name {
+        let executed: Vec<&TestRecord> = records
+            .iter()
+            .flatten()
+            .filter(|t| t.status != RunStatus::Skipped)
+            .copied()
+            .collect();
+        let passed = executed
+            .iter()
+            .filter(|t| t.status == RunStatus::Passed)
+            .count();
+        let (earlier, recent) = records.split_at(recent_start);
+        if is_newly_flaky(earlier, recent) {
+            newly_flaky.push(name.to_string());
+        }
+        tests.push(TestTrend {
+            name: name.to_string(),
+            statuses: records.iter().map(|t| t.map(|t| t.status)).collect(),
+            durations_ms: records
+                .iter()
+                .map(|t| {
+                    t.filter(|t| t.status != RunStatus::Skipped)
+                        .map(|t| t.duration_ms)
+                })
+                .collect(),
+            pass_rate: if executed.is_empty() {
+                0.0
+            } else {
+                passed as f64 / executed.len() as f64
+            },
+            executed: executed.len(),
+        });
+    }
+
+    Trends {
+        runs: window.len(),
+        tests,
+        newly_flaky,
+    }
+}
+
+fn is_newly_flaky(earlier: &[Option<&TestRecord>], recent: &[Option<&TestRecord>]) -> bool {
+    let executed = |records: &[Option<&TestRecord>]| -> Vec<TestRecord> {
+        records
+            .iter()
+            .flatten()
+            .filter(|t| t.status != RunStatus::Skipped)
+            .map(|t| (*t).clone())
+            .collect()
+    };
+    let earlier = executed(earlier);
+    let recent = executed(recent);
+    let reliable = !earlier.is_empty()
+        && earlier
+            .iter()
+            .all(|t| t.status == RunStatus::Passed && t.attempts <= 1);
+    let retried = recent
+        .iter()
+        .any(|t| t.status == RunStatus::Passed && t.attempts > 1);
+    let intermittent = recent.iter().any(|t| t.status == RunStatus::Passed)
+        && recent.iter().any(|t| t.status == RunStatus::Failed);
+    reliable && (retried || intermittent)
+}
+
+/// Render values as a sparkline from the lowest to the highest; None is a space
+pub fn sparkline(values: &[Option<u64>]) -> String {
+    let min = values.iter().flatten().copied().min().unwrap_or(0);
+    let max = values.iter().flatten().copied().max().unwrap_or(0);
+    let range = u128::from(max - min);
+    let top = SPARK_LEVELS.len() as u128 - 1;
+    values
+        .iter()
+        .map(|value| match value {
+            None => ' ',
+            Some(_) if range == 0 => SPARK_LEVELS[0],
+            Some(v) => SPARK_LEVELS[((u128::from(v - min) * top + range / 2) / range) as usize],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, status: RunStatus, duration_ms: u64, attempts: u32) -> TestRecord {
+        TestRecord {
+            name: name.to_string(),
+            status,
+            duration_ms,
+            attempts,
+        }
+    }
+
+    fn run(minute: u32, tests: Vec<TestRecord>) -> RunRecord {
+        RunRecord {
+            timestamp: format!("2026-03-01T10:{:02}:00Z", minute).parse().unwrap(),
+            git_sha: Some(format!("sha{}", minute)),
+            suite: "suite.yaml".to_string(),
+            tests,
+        }
+    }
+
+    /// Ten runs: `stable` always passes and slows down, `flaky` starts
+    /// failing now and then in the last runs, `retried` needs a second
+    /// attempt once, `broken` always fails
+    fn fixture() -> Vec<RunRecord> {
+        (0..10)
+            .map(|i| {
+                let flaky = if i == 7 || i == 9 {
+                    RunStatus::Failed
+                } else {
+                    RunStatus::Passed
+                };
+                run(
+                    i,
+                    vec![
+                        record("stable", RunStatus::Passed, 100 + 10 * i as u64, 1),
+                        record("flaky", flaky, 50, 1),
+                        record("retried", RunStatus::Passed, 20, if i == 8 { 2 } else { 1 }),
+                        record("broken", RunStatus::Failed, 5, 1),
+                    ],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trend_computation() {
+        let trends = compute_trends(&fixture(), DEFAULT_TREND_RUNS);
+        assert_eq!(trends.runs, 10);
+        let names: Vec<_> = trends.tests.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["broken", "flaky", "retried", "stable"]);
+
+        let flaky = &trends.tests[1];
+        assert_eq!(flaky.executed, 10);
+        assert!((flaky.pass_rate - 0.8).abs() < 1e-9);
+        assert_eq!(flaky.status_line(), "✓✓✓✓✓✓✓✗✓✗");
+        assert_eq!(trends.tests[0].pass_rate, 0.0);
+
+        let stable = &trends.tests[3];
+        assert_eq!(stable.pass_rate, 1.0);
+        assert_eq!(stable.durations_ms[9], Some(190));
+        assert_eq!(stable.duration_sparkline(), "▁▂▃▃▄▅▆▆▇█");
+    }
+
+    #[test]
+    fn test_window_takes_the_last_runs() {
+        let trends = compute_trends(&fixture(), 3);
+        assert_eq!(trends.runs, 3);
+        assert_eq!(trends.tests[1].status_line(), "✗✓✗");
+        assert_eq!(
+            trends.tests[3].durations_ms,
+            [Some(170), Some(180), Some(190)]
+        );
+    }
+
+    #[test]
+    fn test_newly_flaky_detection() {
+        let trends = compute_trends(&fixture(), DEFAULT_TREND_RUNS);
+        // broken never passed and stable never wavered
+        assert_eq!(trends.newly_flaky, ["flaky", "retried"]);
+
+        // Flaky from the start is not new
+        let trends = compute_trends(&fixture()[7..], DEFAULT_TREND_RUNS);
+        assert!(trends.newly_flaky.is_empty());
+    }
+
+    #[test]
+    fn test_tests_missing_from_runs() {
+        let mut runs = fixture();
+        runs[9].tests.retain(|t| t.name != "stable");
+        runs[9].tests.push(record("new", RunStatus::Skipped, 0, 0));
+        let trends = compute_trends(&runs, 2);
+        let stable = trends.tests.iter().find(|t| t.name == "stable").unwrap();
+        assert_eq!(stable.status_line(), "✓ ");
+        assert_eq!(stable.executed, 1);
+        let new = trends.tests.iter().find(|t| t.name == "new").unwrap();
+        assert_eq!(new.status_line(), " -");
+        assert_eq!(new.duration_sparkline(), "  ");
+        assert_eq!(new.pass_rate, 0.0);
+    }
+
+    #[test]
+    fn test_corrupt_lines_are_skipped() {
+        let runs = fixture();
+        let mut contents = String::new();
+        for run in &runs[..3] {
+            contents += &serde_json::to_string(run).unwrap();
+            contents += "\n";
+        }
+        // A write cut short, a blank line and something else entirely
+        contents += "{\"timestamp\":\"2026-03-01T10:03:00Z\",\"tes\n\nnot json\n";
+        contents += &serde_json::to_string(&runs[3]).unwrap();
+
+        let history = History::parse(&contents);
+        assert_eq!(history.runs, runs[..4]);
+        let lines: Vec<_> = history.corrupt.iter().map(|c| c.line).collect();
+        assert_eq!(lines, [4, 6]);
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let history_dir = dir.path().join(".x402-history");
+        assert_eq!(History::load(&history_dir).unwrap(), History::default());
+
+        let runs = fixture();
+        for run in &runs[..2] {
+            append_run(&history_dir, run).unwrap();
+        }
+        let history = History::load(&history_dir).unwrap();
+        assert_eq!(history.runs, runs[..2]);
+        assert!(history.corrupt.is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_appends_keep_lines_whole() {
+        let dir = tempfile::tempdir().unwrap();
+        let runs = fixture();
+        std::thread::scope(|scope| {
+            for run in &runs {
+                let dir = dir.path();
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        append_run(dir, run).unwrap();
+                    }
+                });
+            }
+        });
+        let history = History::load(dir.path()).unwrap();
+        assert!(history.corrupt.is_empty());
+        assert_eq!(history.runs.len(), 200);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[Some(10), Some(17), None, Some(24)]), "▁▅ █");
+        assert_eq!(sparkline(&[Some(5), Some(5)]), "▁▁");
+        assert_eq!(sparkline(&[]), "");
+    }
+}
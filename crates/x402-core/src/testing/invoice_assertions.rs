@@ -0,0 +1,196 @@
+// Typed invoice assertions (`invoice:` block)
+//
+// The WWW-Authenticate header goes through the shared InvoiceHeader parser
+// and every condition is checked against the parsed field, so failures show
+// parsed-vs-expected values rather than the raw header. Amounts compare as
+// rust_decimal values; f64 would round `0.1000000000000000001` to `0.1`.
+
+use super::assertions::AssertionResult;
+use super::invoice_header::InvoiceHeader;
+use super::parser::InvoiceExpectations;
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+/// Run the invoice assertions against a response's headers and JSON body
+///
+/// Without a parseable WWW-Authenticate header the assertions are skipped
+/// and a single failed result explains why. The deadline is the header's
+/// `expires` field when present, otherwise the JSON body's
+/// `invoice.expires_at`.
+pub(crate) fn check_response(
+    expect: &InvoiceExpectations,
+    headers: &HeaderMap,
+    body: Option<&serde_json::Value>,
+) -> Vec<AssertionResult> {
+    let Some(value) = headers.get("WWW-Authenticate") else {
+        return vec![skipped("response has no WWW-Authenticate header")];
+    };
+    let header = match value
+        .to_str()
+        .map_err(anyhow::Error::from)
+        .and_then(InvoiceHeader::parse)
+    {
+        Ok(header) => header,
+        Err(e) => {
+            return vec![skipped(&format!(
+                "WWW-Authenticate header is invalid: {}",
+                e
+            ))]
+        }
+    };
+
+    let expires_at = match (expect.expires_within_seconds, header.expires()) {
+        (None, _) => None,
+        (Some(_), Ok(Some(expires))) => Some(expires),
+        (Some(_), Err(_)) => None,
+        (Some(_), Ok(None)) => body.and_then(body_expires_at),
+    };
+
+    check_invoice(expect, &header, expires_at, Utc::now())
+}
+
+/// Check every configured condition against a parsed invoice
+pub(crate) fn check_invoice(
+    expect: &InvoiceExpectations,
+    header: &InvoiceHeader,
+    expires_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Vec<AssertionResult> {
+    let mut results = Vec::new();
+
+    if let Some(amount) = &expect.amount {
+        let parsed = header.get("amount").map(|a| (a, Decimal::from_str(a).ok()));
+        let conditions = [
+            ("equals", "==", amount.equals),
+            ("less than", "<", amount.less_than),
+            ("greater than", ">", amount.greater_than),
+        ];
+        for (label, op, bound) in conditions {
+            let Some(bound) = bound else { continue };
+            let (passed, actual) = match parsed {
+                Some((_, Some(value))) => {
+                    let passed = match op {
+                        "==" => value == bound,
+                        "<" => value < bound,
+                        _ => value > bound,
+                    };
+                    (passed, value.to_string())
+                }
+                Some((raw, None)) => (false, format!("not a decimal: '{}'", raw)),
+                None => (false, "field missing".to_string()),
+            };
+            results.push(result(
+                "amount",
+                passed,
+                format!("Invoice amount {} {}", label, bound),
+                format!("{} {}", op, bound),
+                actual,
+            ));
+        }
+    }
+
+    if let Some(currency) = &expect.currency {
+        results.push(field_equals(header, "currency", currency));
+    }
+
+    if let Some(network) = &expect.network {
+        results.push(field_equals(header, "network", network));
+    }
+
+    if let Some(recipients) = &expect.recipient_in {
+        let actual = header.get("recipient");
+        results.push(result(
+            "recipient",
+            actual.is_some_and(|r| recipients.iter().any(|e| e == r)),
+            "Invoice recipient is allowed".to_string(),
+            format!("one of [{}]", recipients.join(", ")),
+            display_field(actual),
+        ));
+    }
+
+    if let Some(prefix) = &expect.memo_prefix {
+        let actual = header.get("memo");
+        results.push(result(
+            "memo",
+            actual.is_some_and(|m| m.starts_with(prefix.as_str())),
+            format!("Invoice memo starts with '{}'", prefix),
+            format!("starts with '{}'", prefix),
+            display_field(actual),
+        ));
+    }
+
+    if let Some(seconds) = expect.expires_within_seconds.map(|within| within.as_secs()) {
+        let (passed, actual) = match expires_at {
+            Some(expires_at) => {
+                let remaining = (expires_at - now).num_seconds();
+                let actual = if remaining > 0 {
+                    format!("expires in {}s ({})", remaining, expires_at.to_rfc3339())
+                } else {
+                    format!("expired {}s ago ({})", -remaining, expires_at.to_rfc3339())
+                };
+                (remaining > 0 && remaining as u64 <= seconds, actual)
+            }
+            None => (false, "no expiry in header or body".to_string()),
+        };
+        results.push(result(
+            "expires_at",
+            passed,
+            format!("Invoice expires within {}s", seconds),
+            format!("expires in 1..={}s", seconds),
+            actual,
+        ));
+    }
+
+    results
+}
+
+fn field_equals(header: &InvoiceHeader, field: &str, expected: &str) -> AssertionResult {
+    let actual = header.get(field);
+    result(
+        field,
+        actual == Some(expected),
+        format!("Invoice {} is {}", field, expected),
+        expected.to_string(),
+        display_field(actual),
+    )
+}
+
+fn display_field(value: Option<&str>) -> String {
+    value.map_or_else(|| "field missing".to_string(), str::to_string)
+}
+
+fn result(
+    field: &str,
+    passed: bool,
+    description: String,
+    expected: String,
+    actual: String,
+) -> AssertionResult {
+    AssertionResult {
+        passed,
+        description,
+        expected,
+        actual,
+        invoice_field: Some(field.to_string()),
+        failure_detail: None,
+    }
+}
+
+fn skipped(reason: &str) -> AssertionResult {
+    AssertionResult {
+        passed: false,
+        description: format!("Invoice assertions skipped: {}", reason),
+        expected: "x402-solana WWW-Authenticate header".to_string(),
+        actual: reason.to_string(),
+        invoice_field: None,
+        failure_detail: None,
+    }
+}
+
+fn body_expires_at(body: &serde_json::Value) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(body["invoice"]["expires_at"].as_str()?)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
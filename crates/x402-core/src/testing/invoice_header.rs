@@ -0,0 +1,788 @@
+// WWW-Authenticate invoice header parsing (x402-solana)
+//
+// Shared by the check command, test assertions and the MCP compliance tool.
+// Forks of the scheme (e.g. x402-svm) parse with `parse_with_protocols`.
+// Parsing is lenient; `strict_checks` reports every deviation from the
+// canonical form as its own named check for spec-conformance testing.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Authentication scheme of x402 invoices on Solana (the default protocol
+/// identifier)
+pub const SCHEME: &str = "x402-solana";
+
+/// Header carrying the payment proof on the retried request
+pub const PAYMENT_PROOF_HEADER: &str = "X-Payment-Proof";
+
+/// Invoice fields in the order the canonical header lists them
+pub const CANONICAL_FIELDS: [&str; 7] = [
+    "recipient",
+    "amount",
+    "currency",
+    "memo",
+    "network",
+    "expires",
+    "ts",
+];
+
+/// Canonical fields every invoice must carry
+///
+/// `expires` is left out so headers from servers that predate it still
+/// validate; when present it is checked and signed like the others.
+pub const REQUIRED_FIELDS: [&str; 5] = ["recipient", "amount", "currency", "memo", "network"];
+
+/// Optional fields of signed invoices, listed after the canonical ones
+pub const SIGNATURE_FIELDS: [&str; 2] = ["signer", "signature"];
+
+/// Longest header value the parsers accept, in bytes
+///
+/// Invoices are a few hundred bytes; anything far longer is rejected with
+/// [`HeaderTooLong`] before it is scanned.
+pub const DEFAULT_MAX_HEADER_BYTES: usize = 8 * 1024;
+
+/// A header value over the parser's length limit
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{header} header is {len} bytes, over the limit of {max}")]
+pub struct HeaderTooLong {
+    pub header: &'static str,
+    pub len: usize,
+    pub max: usize,
+}
+
+/// Seconds local and server clocks may differ before skew is reported
+pub const DEFAULT_SKEW_TOLERANCE_SECS: u64 = 30;
+
+/// Most decimal places an amount may carry (USDC has 6)
+pub const MAX_AMOUNT_DECIMALS: usize = 6;
+
+/// Characters that must not appear in an unquoted value
+const RESERVED_CHARS: &[char] = &[
+    ',', ';', '=', '"', '\\', '(', ')', '<', '>', '@', ':', '/', '[', ']', '?', '{', '}',
+];
+
+/// One `name=value` parameter as it appeared in the header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvoiceField {
+    pub name: String,
+    /// Value with quotes and escapes removed
+    pub value: String,
+    /// True if the value was written as a quoted string
+    pub quoted: bool,
+}
+
+/// A parsed `WWW-Authenticate: x402-solana ...` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvoiceHeader {
+    /// Protocol identifier (auth scheme) the header starts with
+    pub protocol: String,
+    /// Fields in header order, duplicates included
+    pub fields: Vec<InvoiceField>,
+}
+
+/// Outcome of one strict-mode rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrictCheck {
+    /// Check name shown in reports, e.g. "Canonical field order"
+    pub name: &'static str,
+    pub passed: bool,
+    /// What was found (offending fields on failure)
+    pub detail: String,
+}
+
+/// Time left on an invoice and the clock skew its `ts` field reveals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpiryStatus {
+    pub expires: DateTime<Utc>,
+    /// Seconds until `expires`; negative once it has passed
+    pub remaining: i64,
+    /// Local time minus the server's `ts`, in seconds (positive: local clock ahead)
+    pub skew: Option<i64>,
+    /// True if `remaining` was measured from `ts` rather than the local clock
+    pub server_time: bool,
+}
+
+impl InvoiceHeader {
+    /// Parse a header value, accepting quoted values and unknown fields
+    ///
+    /// Bare tokens without `=` after the scheme are ignored.
+    pub fn parse(header: &str) -> Result<Self> {
+        Self::parse_with_protocols(header, &[SCHEME])
+    }
+
+    /// Parse a header whose scheme is one of `protocols`
+    ///
+    /// The matched identifier is kept in [`protocol`](Self::protocol).
+    pub fn parse_with_protocols<P: AsRef<str>>(header: &str, protocols: &[P]) -> Result<Self> {
+        Self::parse_limited(header, protocols, DEFAULT_MAX_HEADER_BYTES)
+    }
+
+    /// [`parse_with_protocols`](Self::parse_with_protocols) with a length
+    /// limit other than [`DEFAULT_MAX_HEADER_BYTES`]
+    pub fn parse_limited<P: AsRef<str>>(
+        header: &str,
+        protocols: &[P],
+        max_bytes: usize,
+    ) -> Result<Self> {
+        if header.len() > max_bytes {
+            return Err(HeaderTooLong {
+                header: "WWW-Authenticate",
+                len: header.len(),
+                max: max_bytes,
+            }
+            .into());
+        }
+        let header = header.trim();
+        let (scheme, rest) = header
+            .split_once(char::is_whitespace)
+            .unwrap_or((header, ""));
+        if !protocols.iter().any(|p| p.as_ref() == scheme) {
+            let expected: Vec<String> = protocols
+                .iter()
+                .map(|p| format!("'{}'", p.as_ref()))
+                .collect();
+            bail!(
+                "Invalid protocol identifier '{}', expected {}",
+                scheme,
+                expected.join(" or ")
+            );
+        }
+
+        let mut fields = Vec::new();
+        let mut chars = rest.chars().peekable();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                break;
+            }
+
+            let mut name = String::new();
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+                name.push(c);
+            }
+            if chars.next_if_eq(&'=').is_none() {
+                continue;
+            }
+
+            let mut value = String::new();
+            let quoted = chars.next_if_eq(&'"').is_some();
+            if quoted {
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next()),
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        c => value.push(c),
+                    }
+                }
+                if !closed {
+                    bail!("Unterminated quoted value for field '{}'", name);
+                }
+            } else {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    value.push(c);
+                }
+            }
+
+            fields.push(InvoiceField {
+                name,
+                value,
+                quoted,
+            });
+        }
+
+        Ok(Self {
+            protocol: scheme.to_string(),
+            fields,
+        })
+    }
+
+    /// Value of the first field called `name`
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|f| f.name == name)
+            .map(|f| f.value.as_str())
+    }
+
+    /// Proof to pay this invoice with on the mock: the invoice memo
+    ///
+    /// The mock accepts any proof; the memo ties the payment to this invoice
+    /// in receipts and replay protection.
+    pub fn payment_proof(&self) -> Option<&str> {
+        self.get("memo")
+    }
+
+    /// The `expires` deadline, if the header has one
+    ///
+    /// Errors when the value is not an RFC 3339 timestamp.
+    pub fn expires(&self) -> Result<Option<DateTime<Utc>>> {
+        self.timestamp("expires")
+    }
+
+    /// The `ts` time the server issued the invoice at, if the header has one
+    pub fn issued_at(&self) -> Result<Option<DateTime<Utc>>> {
+        self.timestamp("ts")
+    }
+
+    fn timestamp(&self, name: &str) -> Result<Option<DateTime<Utc>>> {
+        self.get(name)
+            .map(|value| {
+                DateTime::parse_from_rfc3339(value)
+                    .map(|t| t.with_timezone(&Utc))
+                    .with_context(|| format!("{} '{}' is not an RFC 3339 timestamp", name, value))
+            })
+            .transpose()
+    }
+
+    /// Time left before `expires` at local time `now`, if the header has a deadline
+    ///
+    /// With `skew_tolerance` set and local time more than that many seconds
+    /// away from `ts`, the remaining time is `expires - ts` instead: the
+    /// invoice was just issued, so only the server's own clock is compared
+    /// and a skewed local clock cannot expire it.
+    pub fn expiry_status(
+        &self,
+        now: DateTime<Utc>,
+        skew_tolerance: Option<u64>,
+    ) -> Result<Option<ExpiryStatus>> {
+        let Some(expires) = self.expires()? else {
+            return Ok(None);
+        };
+        let issued_at = self.issued_at()?;
+        let skew = issued_at.map(|ts| (now - ts).num_seconds());
+
+        let server_time = match (skew, skew_tolerance) {
+            (Some(skew), Some(tolerance)) => skew.unsigned_abs() > tolerance,
+            _ => false,
+        };
+        let reference = match issued_at {
+            Some(ts) if server_time => ts,
+            _ => now,
+        };
+        Ok(Some(ExpiryStatus {
+            expires,
+            remaining: (expires - reference).num_seconds(),
+            skew,
+            server_time,
+        }))
+    }
+
+    /// Field values by name (first occurrence wins)
+    pub fn to_map(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        for field in &self.fields {
+            map.entry(field.name.clone())
+                .or_insert_with(|| field.value.clone());
+        }
+        map
+    }
+
+    /// The header written back out: every field in order, duplicates and
+    /// unknown fields included, quoted only where needed
+    ///
+    /// Parsing the result gives the same names and values.
+    pub fn to_header_string(&self) -> String {
+        let mut header = self.protocol.clone();
+        for field in &self.fields {
+            header.push(' ');
+            header.push_str(&field.name);
+            header.push('=');
+            push_value(&mut header, &field.value);
+        }
+        header
+    }
+
+    /// The string an invoice signature covers
+    ///
+    /// The protocol identifier followed by the canonical fields present, in canonical
+    /// order, with values quoted only where they contain whitespace or
+    /// reserved characters. Signature fields, unknown fields and
+    /// duplicates are left out, so field order and quoting in the
+    /// received header do not affect verification.
+    pub fn canonical_string(&self) -> String {
+        let mut canonical = self.protocol.clone();
+        for name in CANONICAL_FIELDS {
+            if let Some(value) = self.get(name) {
+                canonical.push(' ');
+                canonical.push_str(name);
+                canonical.push('=');
+                push_value(&mut canonical, value);
+            }
+        }
+        canonical
+    }
+
+    /// Strict-mode rules with the protocol identifier checked against
+    /// `protocol` first
+    ///
+    /// Lenient parsing may accept several identifiers; strict mode expects
+    /// the header to use the preferred one.
+    pub fn strict_checks_for(&self, protocol: &str) -> Vec<StrictCheck> {
+        let passed = self.protocol == protocol;
+        let mut checks = vec![StrictCheck {
+            name: "Protocol identifier",
+            passed,
+            detail: if passed {
+                self.protocol.clone()
+            } else {
+                format!("got {}, expected {}", self.protocol, protocol)
+            },
+        }];
+        checks.extend(self.strict_checks());
+        checks
+    }
+
+    /// Run every strict-mode rule; each is reported separately
+    pub fn strict_checks(&self) -> Vec<StrictCheck> {
+        vec![
+            self.check_unknown_fields(),
+            self.check_duplicate_fields(),
+            self.check_field_order(),
+            self.check_reserved_chars(),
+            self.check_amount_precision(),
+            self.check_expiry_format(),
+            self.check_issued_at(),
+        ]
+    }
+
+    fn check_unknown_fields(&self) -> StrictCheck {
+        let unknown: Vec<&str> = self
+            .fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .filter(|name| !CANONICAL_FIELDS.contains(name) && !SIGNATURE_FIELDS.contains(name))
+            .collect();
+        check("No unknown fields", &unknown, "unknown")
+    }
+
+    fn check_duplicate_fields(&self) -> StrictCheck {
+        let mut duplicates = Vec::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            let repeated = self.fields[..i].iter().any(|f| f.name == field.name);
+            if repeated && !duplicates.contains(&field.name.as_str()) {
+                duplicates.push(field.name.as_str());
+            }
+        }
+        check("No duplicate fields", &duplicates, "repeated")
+    }
+
+    fn check_field_order(&self) -> StrictCheck {
+        // Order of first occurrences of the known fields
+        let mut order: Vec<&str> = Vec::new();
+        for field in &self.fields {
+            let name = field.name.as_str();
+            if CANONICAL_FIELDS.contains(&name) && !order.contains(&name) {
+                order.push(name);
+            }
+        }
+        let rank = |name: &str| CANONICAL_FIELDS.iter().position(|f| *f == name);
+        let canonical = order.windows(2).all(|pair| rank(pair[0]) < rank(pair[1]));
+
+        StrictCheck {
+            name: "Canonical field order",
+            passed: canonical,
+            detail: if canonical {
+                CANONICAL_FIELDS.join(", ")
+            } else {
+                format!(
+                    "got {}, expected {}",
+                    order.join(", "),
+                    CANONICAL_FIELDS.join(", ")
+                )
+            },
+        }
+    }
+
+    fn check_reserved_chars(&self) -> StrictCheck {
+        let unquoted: Vec<&str> = self
+            .fields
+            .iter()
+            .filter(|f| !f.quoted && f.value.contains(RESERVED_CHARS))
+            .map(|f| f.name.as_str())
+            .collect();
+        check(
+            "Reserved characters quoted",
+            &unquoted,
+            "unquoted reserved characters in",
+        )
+    }
+
+    fn check_amount_precision(&self) -> StrictCheck {
+        let decimals = self
+            .get("amount")
+            .and_then(|a| a.split_once('.'))
+            .map_or(0, |(_, fraction)| fraction.len());
+        let passed = decimals <= MAX_AMOUNT_DECIMALS;
+
+        StrictCheck {
+            name: "Amount precision",
+            passed,
+            detail: if passed {
+                format!("{} decimal places", decimals)
+            } else {
+                format!("{} decimal places (max {})", decimals, MAX_AMOUNT_DECIMALS)
+            },
+        }
+    }
+
+    fn check_expiry_format(&self) -> StrictCheck {
+        let (passed, detail) = match self.expires() {
+            Ok(Some(expires)) => (true, expires.to_rfc3339()),
+            Ok(None) => (true, "not present".to_string()),
+            Err(e) => (false, e.to_string()),
+        };
+        StrictCheck {
+            name: "Expiry timestamp",
+            passed,
+            detail,
+        }
+    }
+
+    fn check_issued_at(&self) -> StrictCheck {
+        let (passed, detail) = match (self.issued_at(), self.expires()) {
+            (Ok(Some(ts)), Ok(Some(expires))) if ts > expires => (
+                false,
+                format!(
+                    "{} is after expires {}",
+                    ts.to_rfc3339(),
+                    expires.to_rfc3339()
+                ),
+            ),
+            (Ok(Some(ts)), _) => (true, ts.to_rfc3339()),
+            (Ok(None), _) => (true, "not present".to_string()),
+            (Err(e), _) => (false, e.to_string()),
+        };
+        StrictCheck {
+            name: "Issued-at timestamp",
+            passed,
+            detail,
+        }
+    }
+}
+
+/// Append `value`, quoted and escaped if it is empty or contains whitespace
+/// or reserved characters
+fn push_value(header: &mut String, value: &str) {
+    if value.is_empty() || value.contains(char::is_whitespace) || value.contains(RESERVED_CHARS) {
+        header.push('"');
+        for c in value.chars() {
+            if c == '"' || c == '\\' {
+                header.push('\\');
+            }
+            header.push(c);
+        }
+        header.push('"');
+    } else {
+        header.push_str(value);
+    }
+}
+
+/// Check an `X-Payment-Proof` value: not empty, not over
+/// [`DEFAULT_MAX_HEADER_BYTES`] and free of control characters
+///
+/// The proof itself is opaque (the mock accepts any memo), so it is
+/// returned unchanged.
+pub fn parse_payment_proof(value: &str) -> Result<&str> {
+    if value.len() > DEFAULT_MAX_HEADER_BYTES {
+        return Err(HeaderTooLong {
+            header: PAYMENT_PROOF_HEADER,
+            len: value.len(),
+            max: DEFAULT_MAX_HEADER_BYTES,
+        }
+        .into());
+    }
+    if value.trim().is_empty() {
+        bail!("{} is empty", PAYMENT_PROOF_HEADER);
+    }
+    if value.contains(char::is_control) {
+        bail!("{} contains control characters", PAYMENT_PROOF_HEADER);
+    }
+    Ok(value)
+}
+
+/// Check that passes when `offenders` is empty
+fn check(name: &'static str, offenders: &[&str], label: &str) -> StrictCheck {
+    StrictCheck {
+        name,
+        passed: offenders.is_empty(),
+        detail: if offenders.is_empty() {
+            "none".to_string()
+        } else {
+            format!("{}: {}", label, offenders.join(", "))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANONICAL: &str = "x402-solana recipient=GXk8vTest1111111111111111111111111111qPz9 amount=0.01 currency=USDC memo=req-123 network=devnet";
+
+    fn failed(header: &str) -> Vec<&'static str> {
+        InvoiceHeader::parse(header)
+            .unwrap()
+            .strict_checks()
+            .into_iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        let header = InvoiceHeader::parse(CANONICAL).unwrap();
+        assert_eq!(header.get("amount"), Some("0.01"));
+        assert_eq!(header.get("network"), Some("devnet"));
+        assert_eq!(header.to_map().len(), 5);
+
+        assert!(InvoiceHeader::parse("Bearer realm=x").is_err());
+    }
+
+    #[test]
+    fn test_parse_quoted_values() {
+        let header = InvoiceHeader::parse(r#"x402-solana memo="req 1, \"a\"" amount=1"#).unwrap();
+        assert_eq!(header.get("memo"), Some(r#"req 1, "a""#));
+        assert!(header.fields[0].quoted);
+        assert!(!header.fields[1].quoted);
+
+        assert!(InvoiceHeader::parse(r#"x402-solana memo="open"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_with_protocols() {
+        let variant = CANONICAL.replace("x402-solana", "x402-svm");
+        assert!(InvoiceHeader::parse(&variant).is_err());
+
+        let header =
+            InvoiceHeader::parse_with_protocols(&variant, &["x402-solana", "x402-svm"]).unwrap();
+        assert_eq!(header.protocol, "x402-svm");
+        assert_eq!(header.get("memo"), Some("req-123"));
+        assert_eq!(header.canonical_string(), variant);
+
+        let err = InvoiceHeader::parse_with_protocols(&variant, &["x402-solana"]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid protocol identifier 'x402-svm', expected 'x402-solana'"
+        );
+        assert_eq!(InvoiceHeader::parse(CANONICAL).unwrap().protocol, SCHEME);
+    }
+
+    #[test]
+    fn test_strict_checks_for_protocol() {
+        let header =
+            InvoiceHeader::parse_with_protocols(CANONICAL, &["x402-svm", "x402-solana"]).unwrap();
+        let checks = header.strict_checks_for("x402-svm");
+        assert_eq!(checks[0].name, "Protocol identifier");
+        assert!(!checks[0].passed);
+        assert_eq!(checks[0].detail, "got x402-solana, expected x402-svm");
+        assert!(checks[1..].iter().all(|c| c.passed));
+
+        assert!(header.strict_checks_for(SCHEME).iter().all(|c| c.passed));
+    }
+
+    #[test]
+    fn test_canonical_header_passes_strict() {
+        assert!(failed(CANONICAL).is_empty());
+    }
+
+    #[test]
+    fn test_shuffled_order_fails_only_ordering() {
+        let header = "x402-solana amount=0.01 recipient=GXk8vTest1111111111111111111111111111qPz9 network=devnet currency=USDC memo=req-123";
+        assert_eq!(failed(header), vec!["Canonical field order"]);
+
+        let check = InvoiceHeader::parse(header).unwrap().strict_checks()[2].clone();
+        assert!(check
+            .detail
+            .starts_with("got amount, recipient, network, currency, memo"));
+    }
+
+    #[test]
+    fn test_seven_decimal_amount_fails_precision() {
+        let header = CANONICAL.replace("amount=0.01", "amount=0.0000001");
+        assert_eq!(failed(&header), vec!["Amount precision"]);
+
+        let header = CANONICAL.replace("amount=0.01", "amount=0.000001");
+        assert!(failed(&header).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_duplicate_and_reserved() {
+        assert_eq!(
+            failed(&format!("{} tier=gold", CANONICAL)),
+            vec!["No unknown fields"]
+        );
+        assert_eq!(
+            failed(&format!("{} network=testnet", CANONICAL)),
+            vec!["No duplicate fields"]
+        );
+        assert_eq!(
+            failed(&CANONICAL.replace("memo=req-123", "memo=req;123")),
+            vec!["Reserved characters quoted"]
+        );
+        assert!(failed(&CANONICAL.replace("memo=req-123", r#"memo="req;123""#)).is_empty());
+    }
+
+    #[test]
+    fn test_signature_fields_are_known() {
+        let signed = format!("{} signer=Abc123 signature=5xYz", CANONICAL);
+        let header = InvoiceHeader::parse(&signed).unwrap();
+        assert_eq!(header.get("signer"), Some("Abc123"));
+        assert!(failed(&signed).is_empty());
+    }
+
+    #[test]
+    fn test_expires_field() {
+        let header = format!(r#"{} expires="2026-01-01T00:05:00Z""#, CANONICAL);
+        let parsed = InvoiceHeader::parse(&header).unwrap();
+        assert_eq!(
+            parsed.expires().unwrap().unwrap().to_rfc3339(),
+            "2026-01-01T00:05:00+00:00"
+        );
+        assert!(failed(&header).is_empty());
+        assert_eq!(parsed.canonical_string(), header);
+        assert_eq!(
+            InvoiceHeader::parse(CANONICAL).unwrap().expires().unwrap(),
+            None
+        );
+
+        // Unquoted, the colons are reserved characters
+        let unquoted = format!("{} expires=2026-01-01T00:05:00Z", CANONICAL);
+        assert_eq!(failed(&unquoted), vec!["Reserved characters quoted"]);
+
+        // Before network is out of canonical order
+        let early = CANONICAL.replace(
+            " network=devnet",
+            r#" expires="2026-01-01T00:05:00Z" network=devnet"#,
+        );
+        assert_eq!(failed(&early), vec!["Canonical field order"]);
+
+        let invalid = format!(r#"{} expires="tomorrow""#, CANONICAL);
+        assert_eq!(failed(&invalid), vec!["Expiry timestamp"]);
+    }
+
+    #[test]
+    fn test_issued_at_field() {
+        let header = format!(
+            r#"{} expires="2026-01-01T00:05:00Z" ts="2026-01-01T00:00:00Z""#,
+            CANONICAL
+        );
+        assert!(failed(&header).is_empty());
+        assert_eq!(
+            InvoiceHeader::parse(&header).unwrap().canonical_string(),
+            header
+        );
+
+        // Issued after its own deadline, or not a timestamp
+        let late = header.replace("ts=\"2026-01-01T00:00:00Z", "ts=\"2026-01-01T00:10:00Z");
+        assert_eq!(failed(&late), vec!["Issued-at timestamp"]);
+        let invalid = format!(r#"{} ts="now""#, CANONICAL);
+        assert_eq!(failed(&invalid), vec!["Issued-at timestamp"]);
+    }
+
+    #[test]
+    fn test_expiry_status_with_skew() {
+        // Issued 300s before expiry by a server an hour behind the local clock
+        let header = InvoiceHeader::parse(&format!(
+            r#"{} expires="2026-01-01T00:05:00Z" ts="2026-01-01T00:00:00Z""#,
+            CANONICAL
+        ))
+        .unwrap();
+        let now = DateTime::parse_from_rfc3339("2026-01-01T01:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // Local time: expired, and the skew is reported
+        let status = header.expiry_status(now, None).unwrap().unwrap();
+        assert_eq!(status.remaining, -3300);
+        assert_eq!(status.skew, Some(3600));
+        assert!(!status.server_time);
+
+        // Tolerant: measured from ts
+        let status = header.expiry_status(now, Some(30)).unwrap().unwrap();
+        assert_eq!(status.remaining, 300);
+        assert!(status.server_time);
+
+        // Within the tolerance the local clock is used
+        let close = now - chrono::Duration::seconds(3590);
+        let status = header.expiry_status(close, Some(30)).unwrap().unwrap();
+        assert_eq!((status.remaining, status.skew), (290, Some(10)));
+        assert!(!status.server_time);
+
+        // Without ts there is no skew to measure
+        let status =
+            InvoiceHeader::parse(&format!(r#"{} expires="2026-01-01T00:05:00Z""#, CANONICAL))
+                .unwrap()
+                .expiry_status(now, Some(30))
+                .unwrap()
+                .unwrap();
+        assert_eq!((status.remaining, status.skew), (-3300, None));
+    }
+
+    #[test]
+    fn test_header_too_long() {
+        let long = format!(
+            "{} memo={}",
+            CANONICAL,
+            "a".repeat(DEFAULT_MAX_HEADER_BYTES)
+        );
+        let err = InvoiceHeader::parse(&long).unwrap_err();
+        let too_long = err.downcast_ref::<HeaderTooLong>().unwrap();
+        assert_eq!(too_long.max, DEFAULT_MAX_HEADER_BYTES);
+        assert_eq!(too_long.len, long.len());
+
+        // The limit is configurable
+        assert!(InvoiceHeader::parse_limited(CANONICAL, &[SCHEME], 64).is_err());
+        assert!(InvoiceHeader::parse_limited(&long, &[SCHEME], long.len()).is_ok());
+    }
+
+    #[test]
+    fn test_to_header_string_round_trip() {
+        let header = InvoiceHeader::parse(
+            r#"x402-solana  memo="plain" tier="gold, silver" empty="" network=devnet network=testnet"#,
+        )
+        .unwrap();
+        let written = header.to_header_string();
+        assert_eq!(
+            written,
+            r#"x402-solana memo=plain tier="gold, silver" empty="" network=devnet network=testnet"#
+        );
+        assert_eq!(
+            InvoiceHeader::parse(&written).unwrap().to_header_string(),
+            written
+        );
+    }
+
+    #[test]
+    fn test_parse_payment_proof() {
+        assert_eq!(parse_payment_proof("req-123").unwrap(), "req-123");
+        assert!(parse_payment_proof("").is_err());
+        assert!(parse_payment_proof("req\n123").is_err());
+        let err = parse_payment_proof(&"a".repeat(DEFAULT_MAX_HEADER_BYTES + 1)).unwrap_err();
+        assert!(err.downcast_ref::<HeaderTooLong>().is_some());
+    }
+
+    #[test]
+    fn test_canonical_string() {
+        // Order, signature fields and unknown fields do not change it
+        let header = InvoiceHeader::parse(
+            r#"x402-solana signature=sig tier=gold memo="req 1" network=devnet amount=0.01 currency=USDC recipient=Abc"#,
+        )
+        .unwrap();
+        assert_eq!(
+            header.canonical_string(),
+            r#"x402-solana recipient=Abc amount=0.01 currency=USDC memo="req 1" network=devnet"#
+        );
+
+        // A canonical header is its own canonical string
+        assert_eq!(
+            InvoiceHeader::parse(CANONICAL).unwrap().canonical_string(),
+            CANONICAL
+        );
+    }
+}
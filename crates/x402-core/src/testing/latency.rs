@@ -0,0 +1,165 @@
+// Latency budget checks (`max_duration_ms` / `min_duration_ms`)
+//
+// The executor times every request it sends with a monotonic clock, from
+// building the request to receiving the response headers. With `repeat`,
+// each attempt is recorded but only the last one (the exchange the other
+// assertions check) is held to the budget. Latency assertions are added
+// after every other assertion of a step, so a functional failure is the
+// first one reported.
+
+use super::assertions::AssertionResult;
+use super::parser::LatencyBudget;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Measured latency of one exchange against its budget
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LatencyMeasurement {
+    /// Step the exchange belongs to; None for single-step tests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    /// Every request sent, in order
+    pub attempts_ms: Vec<u64>,
+    /// The checked exchange (the last attempt)
+    pub measured_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_ms: Option<u64>,
+}
+
+/// Which side of its budget an exchange fell on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyViolation {
+    OverMax,
+    UnderMin,
+}
+
+/// Latency violations over a whole run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LatencySummary {
+    /// Exchanges that had a budget
+    pub measured: usize,
+    pub over_max: usize,
+    pub under_min: usize,
+}
+
+impl LatencyMeasurement {
+    pub(crate) fn new(step: Option<String>, attempts: &[Duration], budget: &LatencyBudget) -> Self {
+        let attempts_ms: Vec<u64> = attempts.iter().map(|d| millis(*d)).collect();
+        Self {
+            step,
+            measured_ms: attempts_ms.last().copied().unwrap_or_default(),
+            attempts_ms,
+            max_ms: budget.max_duration_ms.map(|max| max.as_millis()),
+            min_ms: budget.min_duration_ms.map(|min| min.as_millis()),
+        }
+    }
+
+    pub fn violation(&self) -> Option<LatencyViolation> {
+        if self.max_ms.is_some_and(|max| self.measured_ms > max) {
+            Some(LatencyViolation::OverMax)
+        } else if self.min_ms.is_some_and(|min| self.measured_ms < min) {
+            Some(LatencyViolation::UnderMin)
+        } else {
+            None
+        }
+    }
+
+    /// One assertion per bound
+    pub(crate) fn assertions(&self) -> Vec<AssertionResult> {
+        let actual = format!("{}ms", self.measured_ms);
+        let max = self.max_ms.map(|max| AssertionResult {
+            passed: self.measured_ms <= max,
+            description: format!("Responds within {}ms", max),
+            expected: format!("<= {}ms", max),
+            actual: actual.clone(),
+            invoice_field: None,
+            failure_detail: None,
+        });
+        let min = self.min_ms.map(|min| AssertionResult {
+            passed: self.measured_ms >= min,
+            description: format!("Takes at least {}ms", min),
+            expected: format!(">= {}ms", min),
+            actual: actual.clone(),
+            invoice_field: None,
+            failure_detail: None,
+        });
+        max.into_iter().chain(min).collect()
+    }
+}
+
+impl LatencySummary {
+    pub fn violations(&self) -> usize {
+        self.over_max + self.under_min
+    }
+}
+
+impl<'a> FromIterator<&'a LatencyMeasurement> for LatencySummary {
+    fn from_iter<I: IntoIterator<Item = &'a LatencyMeasurement>>(iter: I) -> Self {
+        let mut summary = Self::default();
+        for measurement in iter {
+            summary.measured += 1;
+            match measurement.violation() {
+                Some(LatencyViolation::OverMax) => summary.over_max += 1,
+                Some(LatencyViolation::UnderMin) => summary.under_min += 1,
+                None => {}
+            }
+        }
+        summary
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x402_domain::DurationField;
+
+    fn budget(max: Option<u64>, min: Option<u64>) -> LatencyBudget {
+        LatencyBudget {
+            max_duration_ms: max.map(DurationField::from_millis),
+            min_duration_ms: min.map(DurationField::from_millis),
+        }
+    }
+
+    #[test]
+    fn test_last_attempt_is_checked() {
+        let attempts = [Duration::from_millis(400), Duration::from_millis(90)];
+        let measurement = LatencyMeasurement::new(None, &attempts, &budget(Some(150), None));
+
+        assert_eq!(measurement.attempts_ms, [400, 90]);
+        assert_eq!(measurement.measured_ms, 90);
+        assert_eq!(measurement.violation(), None);
+        let assertions = measurement.assertions();
+        assert_eq!(assertions.len(), 1);
+        assert!(assertions[0].passed);
+        assert_eq!(assertions[0].expected, "<= 150ms");
+        assert_eq!(assertions[0].actual, "90ms");
+    }
+
+    #[test]
+    fn test_summary_counts_violations() {
+        let both = budget(Some(150), Some(20));
+        let measurements = [
+            LatencyMeasurement::new(None, &[Duration::from_millis(212)], &both),
+            LatencyMeasurement::new(None, &[Duration::from_millis(3)], &both),
+            LatencyMeasurement::new(None, &[Duration::from_millis(60)], &both),
+        ];
+        let summary: LatencySummary = measurements.iter().collect();
+
+        assert_eq!(
+            summary,
+            LatencySummary {
+                measured: 3,
+                over_max: 1,
+                under_min: 1,
+            }
+        );
+        assert_eq!(summary.violations(), 2);
+    }
+}
@@ -3,11 +3,65 @@
 // Implements YAML-based test suites for x402 payment flows
 
 mod assertions;
+mod baseline;
+pub mod battery;
+mod builder;
+mod cache;
+mod compliance;
+mod compose;
+mod encoding;
 mod executor;
+mod extract;
+mod faults;
+mod filter;
+pub mod github;
+mod head_parity;
+mod header_diff;
+mod history;
+mod invoice_assertions;
+mod invoice_header;
+mod latency;
 mod parser;
+mod preflight;
 mod reporter;
+mod resolve;
 
 pub use assertions::{Assertion, AssertionResult};
-pub use executor::{execute_test_suite, SuiteResult, TestResult};
-pub use parser::{Expectations, HeaderAssertion, Test, TestSuite};
-pub use reporter::{format_json, format_summary, generate_junit_xml};
+pub use baseline::{BaselineDiff, ChangeKind, CheckBaseline, DEFAULT_VOLATILE_FIELDS};
+pub use builder::{InvoiceExpectationsBuilder, TestBuilder, TestSuiteBuilder};
+pub use cache::{check_cache_headers, CacheCheck};
+pub use compliance::{
+    ComplianceCheck, ComplianceGrade, ComplianceReport, PROTOCOL_SECTION, STRICT_SECTION,
+};
+pub use encoding::{decode_body, sha256_hex, DecodedBody};
+pub use executor::{
+    execute_test_suite, execute_test_suite_filtered, ErrorCategory, SkipReason, SuiteResult,
+    SuiteStatus, TestResult,
+};
+pub use extract::Extraction;
+pub use faults::{classify_fault, FaultKind};
+pub use filter::TestFilter;
+pub use github::{Annotation, AnnotationLevel};
+pub use head_parity::{check_head_parity, HeadParityCheck, ProbeResponse, HEAD_PARITY_FIELDS};
+pub use header_diff::{FailureDetail, FieldDifference};
+pub use history::{
+    append_run, compute_trends, sparkline, CorruptLine, History, RunRecord, RunStatus, TestRecord,
+    TestTrend, Trends, DEFAULT_TREND_RUNS, HISTORY_FILE,
+};
+pub use invoice_header::{
+    parse_payment_proof, ExpiryStatus, HeaderTooLong, InvoiceField, InvoiceHeader, StrictCheck,
+    CANONICAL_FIELDS, DEFAULT_MAX_HEADER_BYTES, DEFAULT_SKEW_TOLERANCE_SECS, MAX_AMOUNT_DECIMALS,
+    PAYMENT_PROOF_HEADER, REQUIRED_FIELDS, SIGNATURE_FIELDS,
+};
+pub use latency::{LatencyMeasurement, LatencySummary, LatencyViolation};
+pub use parser::{
+    AmountCondition, Budget, ErrorExpectation, Expectations, HeaderAssertion, HttpOptions,
+    HttpVersion, InvoiceExpectations, LatencyBudget, RequestBody, Step, Test, TestSuite,
+};
+pub use preflight::{
+    run_preflight, Preflight, PreflightCheck, PreflightCheckKind, PreflightReport,
+};
+pub use reporter::{
+    format_github, format_json, format_summary, generate_html_report, generate_junit_xml,
+};
+pub use resolve::{apply_resolve_overrides, is_dns_error, override_for_url, ResolveOverride};
@@ -1,85 +1,631 @@
 // YAML test suite parser (FR-2.1)
 
+use super::compose::{compose, uses_composition};
+use super::extract::Extraction;
+use super::faults::FaultKind;
+use super::preflight::Preflight;
+use super::resolve::ResolveOverride;
+use crate::format::{DetectedFormat, FileFormat};
+use crate::secrets::{secret_references, SECRET_PREFIX};
 use anyhow::Result;
-use serde::Deserialize;
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
 use std::str::FromStr;
+use x402_domain::{DurationField, Millis};
 
-/// A complete test suite from YAML file
-#[derive(Debug, Deserialize)]
+/// A complete test suite, from a YAML file or [`TestSuite::builder`]
+#[derive(Debug, Default, Deserialize, Serialize)]
 pub struct TestSuite {
+    /// Client settings shared by every test in the suite
+    #[serde(default, skip_serializing_if = "HttpOptions::is_default")]
+    pub http: HttpOptions,
+    /// Values substituted for `${name}` in test URLs, request headers and bodies
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Values for `${secret:name}` references, loaded by the caller with
+    /// [`TestSuite::load_secrets`]; never serialized, masked in results
+    #[serde(skip)]
+    pub secrets: BTreeMap<String, String>,
+    /// Default per-request timeout for tests without their own `timeout_ms`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<DurationField<Millis>>,
+    /// Budget for the whole run; tests not started in time are skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_timeout_ms: Option<DurationField<Millis>>,
+    /// Cap on the invoiced total; tests after it is reached are skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<Budget>,
+    /// Check variables, DNS and reachability before the first test
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preflight: Option<Preflight>,
+    /// Default latency budget for tests that set neither bound themselves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencyBudget>,
     pub tests: Vec<Test>,
 }
 
+/// Spending cap for a suite (`budget:` section)
+///
+/// The executor never pays, so the amounts counted are those of the 402
+/// invoices the run received: what a paying client would have spent.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    /// Invoiced total at which the remaining tests are skipped
+    #[serde(deserialize_with = "deserialize_decimal")]
+    pub max_total: Decimal,
+    /// Only invoices in this currency count toward the cap
+    #[serde(default = "default_budget_currency")]
+    pub currency: String,
+}
+
+/// Bounds on how long one HTTP exchange may take (`latency:` section)
+///
+/// A test's own `max_duration_ms`/`min_duration_ms` override the suite's
+/// bound by bound.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LatencyBudget {
+    /// Slowest acceptable exchange
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_ms: Option<DurationField<Millis>>,
+    /// Fastest plausible exchange; quicker ones suggest a cached response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_duration_ms: Option<DurationField<Millis>>,
+}
+
+impl LatencyBudget {
+    pub fn is_empty(&self) -> bool {
+        self.max_duration_ms.is_none() && self.min_duration_ms.is_none()
+    }
+
+    /// Reject a minimum above the maximum
+    fn validate(&self, context: &str) -> Result<()> {
+        if let (Some(min), Some(max)) = (self.min_duration_ms, self.max_duration_ms) {
+            if min > max {
+                anyhow::bail!(
+                    "{}: min_duration_ms ({}) is above max_duration_ms ({})\nFix: Lower min_duration_ms or raise max_duration_ms",
+                    context,
+                    min,
+                    max
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+fn default_budget_currency() -> String {
+    "USDC".to_string()
+}
+
+/// Suite-level HTTP client options (`http:` section)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct HttpOptions {
+    /// Protocol to speak (`auto`, `h1` or `h2`)
+    pub http_version: HttpVersion,
+    /// Keep connections open between tests; `false` opens one per request
+    pub reuse_connections: bool,
+    /// Idle connections kept per host while reuse is on (client default if unset)
+    pub max_idle_connections: Option<usize>,
+    /// `HOST:PORT:ADDR` host name overrides, like curl's --resolve
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resolve: Vec<ResolveOverride>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            http_version: HttpVersion::Auto,
+            reuse_connections: true,
+            max_idle_connections: None,
+            resolve: vec![],
+        }
+    }
+}
+
+impl HttpOptions {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// HTTP protocol selection for the test client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpVersion {
+    /// HTTP/1.1 for http://, ALPN negotiation (h2 or HTTP/1.1) for https://
+    #[default]
+    Auto,
+    /// HTTP/1.1 only
+    H1,
+    /// HTTP/2 only; cleartext URLs use prior knowledge (h2c)
+    H2,
+}
+
 /// Individual test case
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Test {
     pub name: String,
     pub url: String,
     #[serde(default = "default_method")]
     pub method: String,
+    /// Free-form labels used for selection (e.g. `fast`, `e2e`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Request headers sent with the test request
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Request body; `${name}` references are substituted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<RequestBody>,
+    /// Send the request this many times; assertions apply to the last response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repeat: Option<u32>,
+    /// Per-request timeout, overriding the suite's `timeout_ms`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<DurationField<Millis>>,
     pub expect: Expectations,
+    /// Variables captured from the response for the `then:` steps
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extract: BTreeMap<String, Extraction>,
+    /// Follow-up request sent after this one passes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub then: Option<Box<Step>>,
+    /// Fragment this test was merged with (`extends:`), for reports; not
+    /// serialized, since the merged test already contains it
+    #[serde(default, skip_serializing)]
+    pub extends: Option<String>,
 }
 
 fn default_method() -> String {
     "GET".to_string()
 }
 
+/// Follow-up request of a multi-step test (`then:` block)
+///
+/// Runs after the previous step passed, with its extracted variables in
+/// scope. Each request uses the test's `timeout_ms`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Step {
+    /// Label in reports (default `step N`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Defaults to the previous step's URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default = "default_method")]
+    pub method: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<RequestBody>,
+    pub expect: Expectations,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extract: BTreeMap<String, Extraction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub then: Option<Box<Step>>,
+}
+
+/// Request body: a string is sent as-is, anything else as JSON
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum RequestBody {
+    Text(String),
+    Json(serde_json::Value),
+}
+
 /// Expected outcomes for a test
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
 pub struct Expectations {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<u16>,
+    /// Status must be one of these codes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_in: Option<Vec<u16>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub headers: Option<Vec<HeaderAssertion>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub invoice_amount: Option<f64>,
-    pub response_time_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_time_ms: Option<DurationField<Millis>>,
+    /// Typed assertions on the parsed WWW-Authenticate invoice
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice: Option<InvoiceExpectations>,
+    /// The request must fail this way instead of getting a response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expect_error: Option<ErrorExpectation>,
+    /// Response body must contain this text (`${name}` is substituted)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_contains: Option<String>,
+    /// Hex SHA-256 the decoded body must have (byte-exact binary checks)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_sha256: Option<String>,
+    /// Decoded body must be exactly this many bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_size: Option<u64>,
+    /// Body as received, before Content-Encoding is undone, must be at most
+    /// this many bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoded_size_max: Option<u64>,
+    /// The exchange must take at most this long; checked after every other
+    /// assertion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_duration_ms: Option<DurationField<Millis>>,
+    /// The exchange must take at least this long
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_duration_ms: Option<DurationField<Millis>>,
+}
+
+impl Expectations {
+    /// True if any assertion on a response is set
+    fn expects_response(&self) -> bool {
+        self.status.is_some()
+            || self.status_in.is_some()
+            || self.headers.is_some()
+            || self.invoice_amount.is_some()
+            || self.response_time_ms.is_some()
+            || self.max_duration_ms.is_some()
+            || self.min_duration_ms.is_some()
+            || self.reads_body()
+    }
+
+    /// Latency bounds for this exchange, each falling back to `default`
+    pub fn latency_budget(&self, default: Option<&LatencyBudget>) -> LatencyBudget {
+        LatencyBudget {
+            max_duration_ms: self
+                .max_duration_ms
+                .or(default.and_then(|d| d.max_duration_ms)),
+            min_duration_ms: self
+                .min_duration_ms
+                .or(default.and_then(|d| d.min_duration_ms)),
+        }
+    }
+
+    /// True if an assertion needs the decoded body or its encoded size
+    pub(crate) fn reads_body(&self) -> bool {
+        self.invoice.is_some()
+            || self.body_contains.is_some()
+            || self.body_sha256.is_some()
+            || self.body_size.is_some()
+            || self.encoded_size_max.is_some()
+    }
+}
+
+/// Connection-level failure a negative test expects (`expect_error:` block)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ErrorExpectation {
+    /// `timeout`, `connection_refused`, `dns_failure` or `tls_error`
+    pub category: FaultKind,
+    /// The failure must happen within this many milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_ms: Option<DurationField<Millis>>,
+}
+
+/// Invoice assertions (`invoice:` block)
+///
+/// Each condition that is set becomes its own assertion, checked against
+/// the header as parsed by [`InvoiceHeader`](super::InvoiceHeader).
+#[derive(Debug, Default, Deserialize, Serialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct InvoiceExpectations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<AmountCondition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// Recipient must be one of these addresses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_in: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+    /// Memo must start with this prefix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo_prefix: Option<String>,
+    /// Invoice must expire no later than this many seconds from now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_within_seconds: Option<DurationField>,
+}
+
+/// Exact decimal comparisons on the invoice amount
+#[derive(Debug, Default, Deserialize, Serialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct AmountCondition {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_opt_decimal",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub equals: Option<Decimal>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_opt_decimal",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub less_than: Option<Decimal>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_opt_decimal",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub greater_than: Option<Decimal>,
+}
+
+/// Accept `0.01` or `"0.01"`; strings keep every digit as written
+fn deserialize_opt_decimal<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Decimal>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrString {
+        Number(f64),
+        String(String),
+    }
+
+    let text = match Option::<NumberOrString>::deserialize(d)? {
+        None => return Ok(None),
+        // Shortest round-trip form, so YAML `0.3` becomes exactly 0.3
+        Some(NumberOrString::Number(n)) => n.to_string(),
+        Some(NumberOrString::String(s)) => s,
+    };
+    Decimal::from_str(text.trim())
+        .map(Some)
+        .map_err(|_| de::Error::custom(format!("invalid decimal amount '{}'", text)))
+}
+
+fn deserialize_decimal<'de, D: Deserializer<'de>>(d: D) -> Result<Decimal, D::Error> {
+    deserialize_opt_decimal(d)?.ok_or_else(|| de::Error::custom("expected a decimal amount"))
 }
 
 /// Header assertion types
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct HeaderAssertion {
     pub name: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub exists: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub contains: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub regex: Option<String>,
 }
 
 impl TestSuite {
-    /// Parse YAML test suite from file
+    /// Parse a YAML or JSON test suite from file
+    ///
+    /// `import:` paths are resolved relative to the file.
     pub fn from_file(path: &Path) -> Result<Self> {
         let contents = std::fs::read_to_string(path)?;
-        let suite: TestSuite = serde_yaml::from_str(&contents)?;
+        Self::parse_at(
+            &contents,
+            DetectedFormat::detect(Some(path), &contents),
+            Some(path),
+        )
+    }
 
-        // Validate suite has at least one test
-        if suite.tests.is_empty() {
-            anyhow::bail!("Test suite must contain at least one test");
-        }
+    /// Parse a suite embedded in the file at `path` (e.g. a scenario's
+    /// `suite:` section), resolving `import:` paths relative to that file
+    pub fn from_yaml_at(yaml: &str, path: &Path) -> Result<Self> {
+        Self::parse_at(yaml, DetectedFormat::given(FileFormat::Yaml), Some(path))
+    }
 
+    /// Like [`TestSuite::from_yaml_at`] for a suite in either format
+    pub fn from_str_at(content: &str, format: DetectedFormat, path: &Path) -> Result<Self> {
+        Self::parse_at(content, format, Some(path))
+    }
+
+    /// Parse `content`, composing fragments when it uses them
+    fn parse_at(content: &str, format: DetectedFormat, path: Option<&Path>) -> Result<Self> {
+        let doc: serde_yaml::Value = format.parse(content)?;
+        let suite: TestSuite = if uses_composition(&doc) {
+            serde_yaml::from_value(compose(doc, content, path)?)?
+        } else {
+            // Straight from the text, so errors keep their line numbers
+            format.parse(content)?
+        };
+        suite.validate()?;
         Ok(suite)
     }
 
+    /// Replace the budget's `max_total`, keeping its currency (e.g. `--budget`)
+    pub fn override_budget(&mut self, max_total: Decimal) {
+        let currency = self
+            .budget
+            .take()
+            .map_or_else(default_budget_currency, |budget| budget.currency);
+        self.budget = Some(Budget {
+            max_total,
+            currency,
+        });
+    }
+
+    /// Serialize the suite in the YAML schema `from_file` reads
+    pub fn to_yaml(&self) -> Result<String> {
+        self.render(FileFormat::Yaml)
+    }
+
+    /// Serialize the suite as YAML or JSON
+    pub fn render(&self, format: FileFormat) -> Result<String> {
+        format.render(self)
+    }
+
+    /// Names referenced as `${secret:name}` anywhere in the suite
+    pub fn secret_names(&self) -> Result<BTreeSet<String>> {
+        Ok(secret_references(&self.to_yaml()?))
+    }
+
+    /// Resolve every `${secret:name}` reference with `resolve` before running
+    pub fn load_secrets(&mut self, mut resolve: impl FnMut(&str) -> Result<String>) -> Result<()> {
+        for name in self.secret_names()? {
+            let value = resolve(&name)?;
+            self.secrets.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// Substitute `${name}` references using the suite's variables
+    ///
+    /// Unknown variables are an error rather than being sent literally.
+    pub fn interpolate(&self, text: &str) -> Result<String> {
+        self.interpolate_with(text, &BTreeMap::new())
+    }
+
+    /// Substitute `${name}` references, looking in `extracted` before the
+    /// suite's variables
+    pub fn interpolate_with(
+        &self,
+        text: &str,
+        extracted: &BTreeMap<String, String>,
+    ) -> Result<String> {
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            let Some(len) = rest[start + 2..].find('}') else {
+                break;
+            };
+            let name = &rest[start + 2..start + 2 + len];
+            let value = match name.strip_prefix(SECRET_PREFIX) {
+                Some(secret) => self.secrets.get(secret).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Secret '{}' was not loaded\nFix: Run x402-dev secret set {}",
+                        secret,
+                        secret
+                    )
+                })?,
+                None => extracted
+                    .get(name)
+                    .or_else(|| self.variables.get(name))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Undefined variable '${{{}}}'\nFix: Add it under `variables:` or pass --var {}=<value>",
+                            name,
+                            name
+                        )
+                    })?,
+            };
+            result.push_str(&rest[..start]);
+            result.push_str(value);
+            rest = &rest[start + 3 + len..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
     /// Parse YAML test suite from string
+    #[deprecated(note = "use `str::parse` or `FromStr::from_str` instead")]
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(yaml: &str) -> Result<Self> {
         yaml.parse()
     }
 }
 
 /// Implement FromStr trait for standard string parsing
+///
+/// The format is detected from the content. `import:` paths are resolved
+/// relative to the working directory.
 impl FromStr for TestSuite {
     type Err = anyhow::Error;
 
-    fn from_str(yaml: &str) -> Result<Self, Self::Err> {
-        let suite: TestSuite = serde_yaml::from_str(yaml)?;
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        Self::parse_at(content, DetectedFormat::detect(None, content), None)
+    }
+}
 
-        if suite.tests.is_empty() {
+impl TestSuite {
+    /// Check rules the YAML schema cannot express
+    /// Check the suite is runnable; YAML parsing and
+    /// [`TestSuiteBuilder::build`](super::TestSuiteBuilder::build) both call it
+    ///
+    /// URLs containing `${name}` are checked once interpolated, at run time.
+    pub fn validate(&self) -> Result<()> {
+        if self.tests.is_empty() {
             anyhow::bail!("Test suite must contain at least one test");
         }
+        let mut names = BTreeSet::new();
+        for test in &self.tests {
+            if !names.insert(test.name.as_str()) {
+                anyhow::bail!(
+                    "Duplicate test name '{}'\nFix: Give each test a unique name",
+                    test.name
+                );
+            }
+            validate_url(&test.name, &test.url)?;
+            if test.expect.expect_error.is_some() && test.expect.expects_response() {
+                anyhow::bail!(
+                    "Test '{}': expect_error cannot be combined with response expectations",
+                    test.name
+                );
+            }
+            if test.expect.expect_error.is_some() && test.then.is_some() {
+                anyhow::bail!(
+                    "Test '{}': expect_error cannot be combined with then: steps",
+                    test.name
+                );
+            }
+            validate_extractions(&test.name, &test.extract)?;
+            let context = format!("Test '{}'", test.name);
+            test.expect
+                .latency_budget(self.latency.as_ref())
+                .validate(&context)?;
 
-        Ok(suite)
+            let mut step = test.then.as_deref();
+            while let Some(next) = step {
+                if next.expect.expect_error.is_some() {
+                    anyhow::bail!(
+                        "Test '{}': expect_error is not supported in then: steps",
+                        test.name
+                    );
+                }
+                if let Some(url) = &next.url {
+                    validate_url(&test.name, url)?;
+                }
+                validate_extractions(&test.name, &next.extract)?;
+                next.expect
+                    .latency_budget(self.latency.as_ref())
+                    .validate(&context)?;
+                step = next.then.as_deref();
+            }
+        }
+        if let Some(budget) = &self.budget {
+            if budget.max_total <= Decimal::ZERO {
+                anyhow::bail!(
+                    "budget max_total must be positive, got {}",
+                    budget.max_total
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reject URLs that are not absolute http(s) URLs, unless they still
+/// contain `${name}` references
+fn validate_url(test: &str, url: &str) -> Result<()> {
+    if url.contains("${") {
+        return Ok(());
+    }
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+        _ => anyhow::bail!(
+            "Test '{}': invalid URL '{}'\nFix: Use an absolute http:// or https:// URL",
+            test,
+            crate::redact::redact_url(url)
+        ),
     }
 }
 
+fn validate_extractions(test: &str, extract: &BTreeMap<String, Extraction>) -> Result<()> {
+    for (name, extraction) in extract {
+        extraction
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Test '{}': extract '{}' {}", test, name, e))?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -98,7 +644,7 @@ tests:
           exists: true
 "#;
 
-        let suite = TestSuite::from_str(yaml).unwrap();
+        let suite = <TestSuite as FromStr>::from_str(yaml).unwrap();
         assert_eq!(suite.tests.len(), 1);
         assert_eq!(suite.tests[0].name, "Test 402 response");
         assert_eq!(suite.tests[0].method, "GET");
@@ -114,7 +660,281 @@ tests:
       status: 200
 "#;
 
-        let suite = TestSuite::from_str(yaml).unwrap();
+        let suite = <TestSuite as FromStr>::from_str(yaml).unwrap();
         assert_eq!(suite.tests[0].method, "GET");
+        assert!(suite.tests[0].tags.is_empty());
+    }
+
+    #[test]
+    fn test_http_options() {
+        let default = <TestSuite as FromStr>::from_str(
+            r#"
+tests:
+  - name: "Test"
+    url: "http://localhost:3402/"
+    expect:
+      status: 402
+"#,
+        )
+        .unwrap();
+        assert_eq!(default.http, HttpOptions::default());
+        assert!(default.http.reuse_connections);
+
+        let suite = <TestSuite as FromStr>::from_str(
+            r#"
+http:
+  http_version: h2
+  reuse_connections: false
+  max_idle_connections: 4
+  resolve:
+    - "api.internal:8402:127.0.0.1"
+tests:
+  - name: "Test"
+    url: "http://localhost:3402/"
+    expect:
+      status: 402
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            suite.http,
+            HttpOptions {
+                http_version: HttpVersion::H2,
+                reuse_connections: false,
+                max_idle_connections: Some(4),
+                resolve: vec!["api.internal:8402:127.0.0.1".parse().unwrap()],
+            }
+        );
+
+        let err = <TestSuite as FromStr>::from_str(
+            "http:\n  resolve: [\"api.internal:127.0.0.1\"]\ntests: []\n",
+        )
+        .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("expected HOST:PORT:ADDR"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_budget_section_and_override() {
+        let tests = "tests:\n  - name: T\n    url: \"http://localhost:3402/\"\n    expect:\n      status: 402\n";
+        let mut suite =
+            <TestSuite as FromStr>::from_str(&format!("budget:\n  max_total: 0.5\n{}", tests))
+                .unwrap();
+        assert_eq!(
+            suite.budget,
+            Some(Budget {
+                max_total: Decimal::from_str("0.5").unwrap(),
+                currency: "USDC".to_string(),
+            })
+        );
+
+        // --budget replaces the cap and keeps the suite's currency
+        suite.budget.as_mut().unwrap().currency = "SOL".to_string();
+        suite.override_budget(Decimal::from_str("0.25").unwrap());
+        let budget = suite.budget.unwrap();
+        assert_eq!(
+            (budget.max_total.to_string(), budget.currency.as_str()),
+            ("0.25".to_string(), "SOL")
+        );
+
+        for (budget, expected) in [
+            ("max_total: \"0\"", "must be positive"),
+            ("max_total: abc", "invalid decimal amount 'abc'"),
+            ("currency: USDC", "missing field `max_total`"),
+        ] {
+            let yaml = format!("budget:\n  {}\n{}", budget, tests);
+            let err = <TestSuite as FromStr>::from_str(&yaml).unwrap_err();
+            assert!(err.to_string().contains(expected), "{}: {}", budget, err);
+        }
+    }
+
+    #[test]
+    fn test_latency_section_and_override() {
+        let suite = <TestSuite as FromStr>::from_str(
+            "latency:\n  max_duration_ms: 150ms\n  min_duration_ms: 2\ntests:\n  - name: T\n    url: \"http://localhost:3402/\"\n    expect:\n      max_duration_ms: 1s\n",
+        )
+        .unwrap();
+        let budget = suite.tests[0].expect.latency_budget(suite.latency.as_ref());
+        // The test's maximum wins; the suite's minimum still applies
+        assert_eq!(budget.max_duration_ms.unwrap().as_millis(), 1000);
+        assert_eq!(budget.min_duration_ms.unwrap().as_millis(), 2);
+
+        let err = <TestSuite as FromStr>::from_str(
+            "latency:\n  max_duration_ms: 100\ntests:\n  - name: T\n    url: \"http://localhost:3402/\"\n    expect:\n      min_duration_ms: 200\n",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Test 'T': min_duration_ms (200ms) is above max_duration_ms (100ms)"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_timeouts_with_units() {
+        let suite = <TestSuite as FromStr>::from_str(
+            "timeout_ms: 2s\ntotal_timeout_ms: 1m\ntests:\n  - name: T\n    url: \"http://localhost:3402/\"\n    timeout_ms: 500\n    expect:\n      response_time_ms: 250ms\n      invoice:\n        expires_within_seconds: 5m\n",
+        )
+        .unwrap();
+        assert_eq!(suite.timeout_ms.unwrap().as_millis(), 2000);
+        assert_eq!(suite.total_timeout_ms.unwrap().as_millis(), 60_000);
+        let test = &suite.tests[0];
+        assert_eq!(test.timeout_ms.unwrap().as_millis(), 500);
+        assert_eq!(test.expect.response_time_ms.unwrap().as_millis(), 250);
+        let invoice = test.expect.invoice.as_ref().unwrap();
+        assert_eq!(invoice.expires_within_seconds.unwrap().as_secs(), 300);
+    }
+
+    #[test]
+    fn test_expect_error_block() {
+        let suite = <TestSuite as FromStr>::from_str(
+            "tests:\n  - name: T\n    url: \"http://127.0.0.1:1/\"\n    expect:\n      expect_error:\n        category: connection_refused\n        max_duration_ms: 500\n",
+        )
+        .unwrap();
+        assert_eq!(
+            suite.tests[0].expect.expect_error,
+            Some(ErrorExpectation {
+                category: FaultKind::ConnectionRefused,
+                max_duration_ms: Some(DurationField::from_millis(500)),
+            })
+        );
+
+        for (expect, expected) in [
+            (
+                "expect_error:\n        category: timeout\n      status: 402",
+                "cannot be combined with response expectations",
+            ),
+            (
+                "expect_error:\n        category: reset",
+                "unknown variant `reset`",
+            ),
+        ] {
+            let yaml = format!(
+                "tests:\n  - name: T\n    url: \"http://localhost:3402/\"\n    expect:\n      {}\n",
+                expect
+            );
+            let err = <TestSuite as FromStr>::from_str(&yaml).unwrap_err();
+            assert!(format!("{:#}", err).contains(expected), "{:#}", err);
+        }
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        let yaml = r#"
+tests:
+  - name: "Tagged"
+    url: "http://localhost:3402/"
+    tags: [fast, headers]
+    expect:
+      status: 402
+"#;
+
+        let suite = <TestSuite as FromStr>::from_str(yaml).unwrap();
+        assert_eq!(suite.tests[0].tags, vec!["fast", "headers"]);
+    }
+
+    #[test]
+    fn test_parse_invoice_block() {
+        let yaml = r#"
+tests:
+  - name: "Invoice"
+    url: "http://localhost:3402/"
+    expect:
+      invoice:
+        amount:
+          equals: 0.3
+          less_than: "0.300000001"
+        currency: USDC
+        recipient_in: [abc, def]
+        memo_prefix: req-
+        expires_within_seconds: 300
+"#;
+
+        let suite = <TestSuite as FromStr>::from_str(yaml).unwrap();
+        let invoice = suite.tests[0].expect.invoice.as_ref().unwrap();
+        let amount = invoice.amount.as_ref().unwrap();
+        assert_eq!(amount.equals, Some(Decimal::from_str("0.3").unwrap()));
+        assert_eq!(
+            amount.less_than,
+            Some(Decimal::from_str("0.300000001").unwrap())
+        );
+        assert_eq!(amount.greater_than, None);
+        assert_eq!(invoice.recipient_in.as_ref().unwrap().len(), 2);
+        assert_eq!(invoice.network, None);
+
+        let typo = yaml.replace("memo_prefix", "memo_start");
+        assert!(<TestSuite as FromStr>::from_str(&typo).is_err());
+        let bad_amount = yaml.replace("equals: 0.3", "equals: \"0,3\"");
+        assert!(<TestSuite as FromStr>::from_str(&bad_amount).is_err());
+    }
+
+    #[test]
+    fn test_parse_then_steps() {
+        let yaml = r#"
+tests:
+  - name: "Pay"
+    url: "http://localhost:3402/api/data"
+    method: POST
+    body: "ping"
+    expect:
+      status: 402
+    extract:
+      memo:
+        json_path: $.invoice.memo
+    then:
+      name: retry
+      headers:
+        X-Payment-Proof: "${memo}"
+      body:
+        memo: "${memo}"
+      expect:
+        status: 200
+        body_contains: "${memo}"
+"#;
+
+        let suite = <TestSuite as FromStr>::from_str(yaml).unwrap();
+        let test = &suite.tests[0];
+        assert_eq!(test.body, Some(RequestBody::Text("ping".to_string())));
+        assert!(test.extract["memo"].reads_body());
+        let step = test.then.as_deref().unwrap();
+        assert_eq!(step.name.as_deref(), Some("retry"));
+        assert_eq!(step.url, None);
+        assert_eq!(step.method, "GET");
+        assert_eq!(
+            step.body,
+            Some(RequestBody::Json(serde_json::json!({ "memo": "${memo}" })))
+        );
+
+        // Extracted values shadow suite variables
+        let extracted = BTreeMap::from([("memo".to_string(), "req-1".to_string())]);
+        assert_eq!(
+            suite.interpolate_with("memo=${memo}", &extracted).unwrap(),
+            "memo=req-1"
+        );
+
+        for (from, to, expected) in [
+            (
+                "json_path: $.invoice.memo",
+                "json_path: $.invoice.memo\n        header: X-Memo",
+                "extract 'memo' needs exactly one of header or json_path",
+            ),
+            (
+                "json_path: $.invoice.memo",
+                "json_path: $.items[x]",
+                "index must be a number",
+            ),
+            (
+                "status: 402",
+                "expect_error:\n        category: timeout",
+                "expect_error cannot be combined with then: steps",
+            ),
+        ] {
+            let err = <TestSuite as FromStr>::from_str(&yaml.replacen(from, to, 1)).unwrap_err();
+            assert!(format!("{:#}", err).contains(expected), "{:#}", err);
+        }
     }
 }
@@ -0,0 +1,445 @@
+// Pre-flight checks run before the first test of a suite
+//
+// A target that is not up yet makes every test fail with the same
+// connection error. The pre-flight phase checks once that the suite can run
+// at all: every `${name}` resolves, every host resolves, every target
+// accepts TCP connections and, optionally, answers a health path with 2xx.
+
+use super::parser::{Step, Test, TestSuite};
+use super::resolve::override_for_url;
+use crate::secrets::SECRET_PREFIX;
+use reqwest::{Client, Url};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use x402_domain::{DurationField, Millis};
+
+/// Suite-level pre-flight settings (`preflight:` section)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preflight {
+    /// Path requested on every target once it accepts connections; any
+    /// 2xx passes (e.g. `/health`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<String>,
+    /// Limit for each lookup, connection and health request
+    pub timeout_ms: DurationField<Millis>,
+}
+
+impl Default for Preflight {
+    fn default() -> Self {
+        Self {
+            health: None,
+            timeout_ms: DurationField::from_millis(2000),
+        }
+    }
+}
+
+/// What a pre-flight check verified
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum PreflightCheckKind {
+    /// `${name}` and `${secret:name}` references resolve
+    Variables,
+    /// A host name resolves to an address
+    Dns,
+    /// A target accepts TCP connections
+    Tcp,
+    /// A target answers the health path with 2xx
+    Health,
+}
+
+impl std::fmt::Display for PreflightCheckKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PreflightCheckKind::Variables => "variables",
+            PreflightCheckKind::Dns => "dns",
+            PreflightCheckKind::Tcp => "tcp",
+            PreflightCheckKind::Health => "health",
+        })
+    }
+}
+
+/// One pre-flight check; `error` says what failed and how to fix it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreflightCheck {
+    pub kind: PreflightCheckKind,
+    /// Reference, host, `host:port` or health URL checked
+    pub target: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PreflightCheck {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Outcome of the pre-flight phase
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+    #[serde(serialize_with = "serialize_millis", rename = "duration_ms")]
+    pub duration: Duration,
+}
+
+fn serialize_millis<S: serde::Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u64(d.as_millis() as u64)
+}
+
+impl PreflightReport {
+    /// True if every check passed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(PreflightCheck::passed)
+    }
+
+    /// Checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &PreflightCheck> {
+        self.checks.iter().filter(|check| !check.passed())
+    }
+}
+
+/// Run the pre-flight checks of `suite` for `tests`
+///
+/// Hosts of URLs that depend on values extracted at run time are not known
+/// yet and are not checked. Targets whose host did not resolve are not
+/// connected to, and targets refusing connections get no health request.
+pub async fn run_preflight(
+    suite: &TestSuite,
+    tests: &[&Test],
+    options: &Preflight,
+    client: &Client,
+) -> PreflightReport {
+    let start = Instant::now();
+    let timeout = options.timeout_ms.as_duration();
+    let mut checks = check_references(suite, tests);
+
+    let mut targets: Vec<Url> = Vec::new();
+    for url in tests.iter().flat_map(|test| urls(test)) {
+        let Ok(url) = suite.interpolate(url) else {
+            continue;
+        };
+        let Ok(url) = Url::parse(&url) else {
+            continue;
+        };
+        let Some(port) = url.port_or_known_default() else {
+            continue;
+        };
+        let seen = targets.iter().any(|target| {
+            target.scheme() == url.scheme()
+                && target.host_str() == url.host_str()
+                && target.port_or_known_default() == Some(port)
+        });
+        if !seen {
+            targets.push(url);
+        }
+    }
+
+    let mut resolved: Vec<(String, Result<Vec<IpAddr>, String>)> = Vec::new();
+    for url in &targets {
+        let host = host_of(url);
+        if resolved.iter().any(|(seen, _)| *seen == host) {
+            continue;
+        }
+        let addrs = match override_for_url(url.as_str(), &suite.http.resolve) {
+            Some(entry) => Ok(vec![entry.addr]),
+            None => match host.parse::<IpAddr>() {
+                Ok(ip) => Ok(vec![ip]),
+                Err(_) => {
+                    let addrs = lookup(&host, timeout).await;
+                    checks.push(PreflightCheck {
+                        kind: PreflightCheckKind::Dns,
+                        target: host.clone(),
+                        error: addrs.as_ref().err().cloned(),
+                    });
+                    addrs
+                }
+            },
+        };
+        resolved.push((host, addrs));
+    }
+
+    for url in &targets {
+        let host = host_of(url);
+        let Some(Ok(addrs)) = resolved
+            .iter()
+            .find(|(seen, _)| *seen == host)
+            .map(|(_, addrs)| addrs)
+        else {
+            continue;
+        };
+        let port = url.port_or_known_default().unwrap_or_default();
+        let target = format!("{}:{}", host, port);
+        let error = connect(addrs, port, timeout).await.err().map(|e| {
+            format!(
+                "{} is not accepting connections: {}\nFix: Start the server (e.g. `x402-dev mock --port {}`) or point the suite at a running one with --var",
+                target, e, port
+            )
+        });
+        let reachable = error.is_none();
+        checks.push(PreflightCheck {
+            kind: PreflightCheckKind::Tcp,
+            target,
+            error,
+        });
+
+        if let (true, Some(path)) = (reachable, &options.health) {
+            let mut health = url.clone();
+            health.set_path(path);
+            health.set_query(None);
+            checks.push(check_health(client, &health, timeout).await);
+        }
+    }
+
+    PreflightReport {
+        checks,
+        duration: start.elapsed(),
+    }
+}
+
+/// Host as it appears in the URL, without IPv6 brackets
+fn host_of(url: &Url) -> String {
+    url.host_str()
+        .unwrap_or_default()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string()
+}
+
+async fn lookup(host: &str, timeout: Duration) -> Result<Vec<IpAddr>, String> {
+    let fix =
+        "Fix: Check the host name in the suite or --var, or map it with --resolve HOST:PORT:ADDR";
+    match tokio::time::timeout(timeout, tokio::net::lookup_host((host, 0))).await {
+        Ok(Ok(addrs)) => {
+            let addrs: Vec<IpAddr> = addrs.map(|addr| addr.ip()).collect();
+            if addrs.is_empty() {
+                Err(format!("{} has no addresses\n{}", host, fix))
+            } else {
+                Ok(addrs)
+            }
+        }
+        Ok(Err(e)) => Err(format!("{} did not resolve: {}\n{}", host, e, fix)),
+        Err(_) => Err(format!(
+            "{} did not resolve within {}ms\n{}",
+            host,
+            timeout.as_millis(),
+            fix
+        )),
+    }
+}
+
+/// Connect to the first address that accepts
+async fn connect(addrs: &[IpAddr], port: u16, timeout: Duration) -> Result<(), String> {
+    let mut last = String::from("no address");
+    for addr in addrs {
+        let connecting = tokio::net::TcpStream::connect((*addr, port));
+        match tokio::time::timeout(timeout, connecting).await {
+            Ok(Ok(_)) => return Ok(()),
+            Ok(Err(e)) => last = e.to_string(),
+            Err(_) => last = format!("no answer within {}ms", timeout.as_millis()),
+        }
+    }
+    Err(last)
+}
+
+async fn check_health(client: &Client, url: &Url, timeout: Duration) -> PreflightCheck {
+    let target = crate::redact::redact_url(url.as_str());
+    let error = match client.get(url.clone()).timeout(timeout).send().await {
+        Ok(resp) if resp.status().is_success() => None,
+        Ok(resp) => Some(format!("{} answered {}", target, resp.status())),
+        Err(e) => Some(format!("{} failed: {}", target, e)),
+    };
+    PreflightCheck {
+        kind: PreflightCheckKind::Health,
+        target: target.clone(),
+        error: error.map(|e| {
+            format!(
+                "{}\nFix: Wait for the server to become healthy, or change `preflight.health`",
+                e
+            )
+        }),
+    }
+}
+
+/// URLs requested by a test and its `then:` steps
+fn urls(test: &Test) -> Vec<&str> {
+    let mut urls = vec![test.url.as_str()];
+    let mut step = test.then.as_deref();
+    while let Some(next) = step {
+        urls.extend(next.url.as_deref());
+        step = next.then.as_deref();
+    }
+    urls
+}
+
+/// Variables extracted by a test and its steps, usable by later steps
+fn extracted_names(test: &Test) -> BTreeSet<&str> {
+    let mut names: BTreeSet<&str> = test.extract.keys().map(String::as_str).collect();
+    let mut step: Option<&Step> = test.then.as_deref();
+    while let Some(next) = step {
+        names.extend(next.extract.keys().map(String::as_str));
+        step = next.then.as_deref();
+    }
+    names
+}
+
+/// One failed check per reference that cannot resolve, or one passing
+/// check when all do
+fn check_references(suite: &TestSuite, tests: &[&Test]) -> Vec<PreflightCheck> {
+    let mut checks = Vec::new();
+    let mut missing = BTreeSet::new();
+    for test in tests {
+        let extracted = extracted_names(test);
+        let Ok(yaml) = serde_yaml::to_string(test) else {
+            continue;
+        };
+        for name in references(&yaml) {
+            let error = match name.strip_prefix(SECRET_PREFIX) {
+                Some(secret) if !suite.secrets.contains_key(secret) => format!(
+                    "Secret '{}' used by test '{}' was not loaded\nFix: Run x402-dev secret set {}",
+                    secret, test.name, secret
+                ),
+                Some(_) => continue,
+                None if suite.variables.contains_key(name) || extracted.contains(name) => {
+                    continue
+                }
+                None => format!(
+                    "Undefined variable '${{{}}}' used by test '{}'\nFix: Add it under `variables:` or pass --var {}=<value>",
+                    name, test.name, name
+                ),
+            };
+            if missing.insert(name.to_string()) {
+                checks.push(PreflightCheck {
+                    kind: PreflightCheckKind::Variables,
+                    target: format!("${{{}}}", name),
+                    error: Some(error),
+                });
+            }
+        }
+    }
+    if checks.is_empty() {
+        checks.push(PreflightCheck {
+            kind: PreflightCheckKind::Variables,
+            target: "all references".to_string(),
+            error: None,
+        });
+    }
+    checks
+}
+
+/// Names inside `${...}` in `text`, in order of appearance
+fn references(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        names.push(&rest[start + 2..start + 2 + len]);
+        rest = &rest[start + 3 + len..];
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suite(yaml: &str) -> TestSuite {
+        yaml.parse().unwrap()
+    }
+
+    async fn preflight(suite: &TestSuite) -> PreflightReport {
+        let tests: Vec<&Test> = suite.tests.iter().collect();
+        run_preflight(suite, &tests, &Preflight::default(), &Client::new()).await
+    }
+
+    #[tokio::test]
+    async fn test_undefined_variable_and_secret_fail() {
+        let suite = suite(
+            r#"
+tests:
+  - name: uses vars
+    url: "http://127.0.0.1:9/${path}"
+    expect: { status: 402 }
+    headers:
+      Authorization: "Bearer ${secret:token}"
+    extract:
+      memo: { header: X-Memo }
+    then:
+      url: "http://127.0.0.1:9/pay?memo=${memo}"
+      expect: { status: 200 }
+"#,
+        );
+        let report = preflight(&suite).await;
+        let targets: Vec<&str> = report
+            .failures()
+            .filter(|c| c.kind == PreflightCheckKind::Variables)
+            .map(|c| c.target.as_str())
+            .collect();
+        assert_eq!(targets, ["${path}", "${secret:token}"]);
+    }
+
+    #[tokio::test]
+    async fn test_closed_port_fails_tcp_once() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let suite = suite(&format!(
+            r#"
+tests:
+  - name: one
+    url: "http://127.0.0.1:{port}/a"
+    expect: {{ status: 402 }}
+  - name: two
+    url: "http://127.0.0.1:{port}/b"
+    expect: {{ status: 402 }}
+"#
+        ));
+
+        let report = preflight(&suite).await;
+        assert!(!report.passed());
+        let failures: Vec<&PreflightCheck> = report.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].kind, PreflightCheckKind::Tcp);
+        assert_eq!(failures[0].target, format!("127.0.0.1:{}", port));
+    }
+
+    #[tokio::test]
+    async fn test_unresolvable_host_fails_dns() {
+        let suite = suite(
+            r#"
+tests:
+  - name: nowhere
+    url: "http://does-not-exist.invalid/api"
+    expect: { status: 402 }
+"#,
+        );
+        let report = preflight(&suite).await;
+        let failures: Vec<&PreflightCheck> = report.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].kind, PreflightCheckKind::Dns);
+        assert_eq!(failures[0].target, "does-not-exist.invalid");
+    }
+
+    #[test]
+    fn test_preflight_section_parses() {
+        let suite = suite(
+            r#"
+preflight:
+  health: /health
+  timeout_ms: 500ms
+tests:
+  - name: one
+    url: "http://127.0.0.1:3402/a"
+    expect: { status: 402 }
+"#,
+        );
+        let preflight = suite.preflight.unwrap();
+        assert_eq!(preflight.health.as_deref(), Some("/health"));
+        assert_eq!(preflight.timeout_ms.as_millis(), 500);
+    }
+}
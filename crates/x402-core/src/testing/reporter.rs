@@ -1,10 +1,15 @@
 // Test reporting (FR-2.5)
 
-use super::executor::{SuiteResult, TestResult};
+use super::executor::{ErrorCategory, SkipReason, SuiteResult, SuiteStatus, TestResult};
+use super::github::{self, Annotation};
+use crate::canonical::to_canonical_string;
 use colored::Colorize;
 use serde_json::json;
+use x402_domain::AmountFormatter;
 
 /// Format results as JSON (FR-2.4: --json flag)
+///
+/// The JSON is canonical, with a `content_hash` over the report.
 pub fn format_json(result: &SuiteResult) -> String {
     let tests_json: Vec<_> = result
         .tests
@@ -22,31 +27,47 @@ pub fn format_json(result: &SuiteResult) -> String {
                         "passed": a.passed,
                         "expected": a.expected,
                         "actual": a.actual,
+                        "invoice_field": a.invoice_field,
+                        "failure_detail": a.failure_detail,
                     })
                 }).collect::<Vec<_>>(),
                 "error": test.error,
+                "error_category": test.error_category.map(|c| c.to_string()),
+                "skipped": test.skipped.as_ref().map(|r| r.to_string()),
+                "http_version": test.http_version,
+                "encoded_size": test.encoded_size,
+                "extends": test.extends,
+                "latency": test.latency,
             })
         })
         .collect();
 
+    let latency = result.latency_summary();
     let summary = json!({
         "total": result.total,
         "passed": result.passed,
         "failed": result.failed,
+        "skipped": result.skipped,
         "duration_ms": result.duration.as_millis(),
         "exit_code": result.exit_code(),
+        "status": result.status().to_string(),
+        "preflight": result.preflight,
+        "spent": result.spent,
+        "budget": result.budget,
+        "latency": (latency.measured > 0).then_some(latency),
         "tests": tests_json,
     });
 
-    serde_json::to_string_pretty(&summary).unwrap_or_else(|_| "{}".to_string())
+    to_canonical_string(&summary).unwrap_or_else(|_| "{}".to_string())
 }
 
-/// Format results as human-readable summary (FR-2.5)
-pub fn format_summary(result: &SuiteResult, quiet: bool) -> String {
+/// Format results as human-readable summary (FR-2.5), amounts shown with
+/// `amounts`
+pub fn format_summary(result: &SuiteResult, quiet: bool, amounts: &AmountFormatter) -> String {
     let mut output = String::new();
 
     // Test results (unless quiet mode)
-    if !quiet {
+    if !quiet && result.status() != SuiteStatus::PreflightFailed {
         for test in &result.tests {
             format_test_result(test, &mut output);
         }
@@ -54,6 +75,17 @@ pub fn format_summary(result: &SuiteResult, quiet: bool) -> String {
         output.push('\n');
     }
 
+    if let Some(report) = result.preflight.as_ref().filter(|r| !r.passed()) {
+        output.push_str(&format!("{}\n", "Pre-flight checks failed".red().bold()));
+        for check in report.failures() {
+            let error = check.error.as_deref().unwrap_or_default();
+            output.push_str(&format!("  ✗ {} {}\n", check.kind, check.target.bold()));
+            for line in error.lines() {
+                output.push_str(&format!("    {}\n", line.yellow()));
+            }
+        }
+    }
+
     // Summary (FR-2.5: total, passed, failed, duration)
     output.push_str(&format!("\n{}\n", "Test Suite Summary".bold().cyan()));
     output.push_str(&format!(
@@ -68,27 +100,131 @@ pub fn format_summary(result: &SuiteResult, quiet: bool) -> String {
         "  Failed:   {}\n",
         result.failed.to_string().red().bold()
     ));
+    if result.skipped > 0 {
+        output.push_str(&format!(
+            "  Skipped:  {}\n",
+            result.skipped.to_string().yellow().bold()
+        ));
+    }
     output.push_str(&format!(
         "  Duration: {}ms\n",
         result.duration.as_millis().to_string().bold()
     ));
+    if let Some(spend) = describe_spend(result, amounts) {
+        output.push_str(&format!("  Spent:    {}\n", spend.bold()));
+    }
+    if let Some(latency) = describe_latency(result) {
+        output.push_str(&format!("  Latency:  {}\n", latency.bold()));
+    }
 
     // Overall status
     output.push('\n');
-    if result.failed == 0 {
-        output.push_str(&format!("{}\n", "✓ All tests passed!".green().bold()));
-    } else {
-        output.push_str(&format!(
+    match result.status() {
+        SuiteStatus::Passed => {
+            output.push_str(&format!("{}\n", "✓ All tests passed!".green().bold()))
+        }
+        SuiteStatus::Failed => output.push_str(&format!(
             "{}\n",
             format!("✗ {} test(s) failed", result.failed).red().bold()
-        ));
+        )),
+        SuiteStatus::PreflightFailed => output.push_str(&format!(
+            "{}\n",
+            "✗ Pre-flight failed; no tests were run".red().bold()
+        )),
+    }
+
+    output
+}
+
+/// Format results for GitHub Actions (--format github)
+///
+/// The per-test log is folded into a group named after the suite, each
+/// failed test becomes an `::error` annotation, and the summary stays
+/// visible below them.
+pub fn format_github(result: &SuiteResult, suite_name: &str) -> String {
+    let mut output = github::group(suite_name);
+    output.push('\n');
+    for test in &result.tests {
+        format_test_result(test, &mut output);
+    }
+    output.push_str(github::END_GROUP);
+    output.push('\n');
+
+    for annotation in github::test_annotations(result) {
+        output.push_str(&format!("{}\n", annotation));
+    }
+    let budget_skip = result.tests.iter().find_map(|test| match &test.skipped {
+        Some(reason @ SkipReason::BudgetExceeded { .. }) => Some(reason),
+        _ => None,
+    });
+    if let Some(reason) = budget_skip {
+        let annotation = Annotation::warning(
+            "Budget exceeded",
+            format!("{}; remaining tests were skipped", reason),
+        );
+        output.push_str(&format!("{}\n", annotation));
     }
 
+    output.push_str(&format_summary(result, true, &AmountFormatter::default()));
     output
 }
 
+/// Invoiced totals, e.g. `0.50 USDC (budget 0.50 USDC)`; None if nothing
+/// was invoiced and the suite has no budget
+fn describe_spend(result: &SuiteResult, amounts: &AmountFormatter) -> Option<String> {
+    let mut spent = result.spent.clone();
+    if let Some(budget) = &result.budget {
+        spent.entry(budget.currency.clone()).or_default();
+    }
+    if spent.is_empty() {
+        return None;
+    }
+    let mut line = spent
+        .iter()
+        .map(|(currency, amount)| amounts.format_currency(*amount, currency))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if let Some(budget) = &result.budget {
+        line.push_str(&format!(
+            " (budget {})",
+            amounts.format_currency(budget.max_total, &budget.currency)
+        ));
+    }
+    Some(line)
+}
+
+/// Latency budget violations, e.g. `2 of 5 outside budget (1 over max,
+/// 1 under min)`; None if no test had a budget
+fn describe_latency(result: &SuiteResult) -> Option<String> {
+    let latency = result.latency_summary();
+    if latency.measured == 0 {
+        return None;
+    }
+    if latency.violations() == 0 {
+        return Some(format!("{} within budget", latency.measured));
+    }
+    Some(format!(
+        "{} of {} outside budget ({} over max, {} under min)",
+        latency.violations(),
+        latency.measured,
+        latency.over_max,
+        latency.under_min
+    ))
+}
+
 /// Format individual test result
 fn format_test_result(test: &TestResult, output: &mut String) {
+    if let Some(reason) = &test.skipped {
+        output.push_str(&format!(
+            "{} {} {} ({})\n",
+            "○".yellow(),
+            "SKIP".yellow().bold(),
+            test.name.bold(),
+            reason
+        ));
+        return;
+    }
+
     let status_icon = if test.passed {
         "✓".green()
     } else {
@@ -111,16 +247,32 @@ fn format_test_result(test: &TestResult, output: &mut String) {
 
     // Show error if present
     if let Some(error) = &test.error {
-        output.push_str(&format!("  Error: {}\n", error.red()));
+        match test.error_category {
+            Some(category) => {
+                output.push_str(&format!("  Error ({}): {}\n", category, error.red()))
+            }
+            None => output.push_str(&format!("  Error: {}\n", error.red())),
+        }
     }
 
-    // Show failed assertions
+    // Show failed assertions (invoice assertions report the parsed value)
     for assertion in &test.assertions {
-        if !assertion.passed {
+        if let (false, Some(detail)) = (assertion.passed, &assertion.failure_detail) {
+            output.push_str(&format!("  ✗ {}\n", assertion.description));
+            for line in detail.render().lines() {
+                output.push_str(&format!("    {}\n", line.yellow()));
+            }
+        } else if !assertion.passed {
+            let label = if assertion.invoice_field.is_some() {
+                "Parsed:  "
+            } else {
+                "Actual:  "
+            };
             output.push_str(&format!(
-                "  ✗ {}\n    Expected: {}\n    Actual:   {}\n",
+                "  ✗ {}\n    Expected: {}\n    {} {}\n",
                 assertion.description,
                 assertion.expected,
+                label,
                 assertion.actual.yellow()
             ));
         }
@@ -134,13 +286,57 @@ pub fn generate_junit_xml(result: &SuiteResult) -> String {
     xml.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
     xml.push('\n');
     xml.push_str(&format!(
-        r#"<testsuite name="x402-dev Test Suite" tests="{}" failures="{}" time="{:.3}">"#,
+        r#"<testsuite name="x402-dev Test Suite" tests="{}" failures="{}" skipped="{}" time="{:.3}">"#,
         result.total,
         result.failed,
+        result.skipped,
         result.duration.as_secs_f64()
     ));
     xml.push('\n');
 
+    if let Some(report) = result.preflight.as_ref().filter(|r| !r.passed()) {
+        xml.push_str(&format!(
+            "  <testcase name=\"pre-flight\" classname=\"preflight\" time=\"{:.3}\">\n",
+            report.duration.as_secs_f64()
+        ));
+        for check in report.failures() {
+            xml.push_str(&format!(
+                "    <failure message=\"{} check failed: {}\" type=\"preflight.{}\">{}</failure>\n",
+                check.kind,
+                escape_xml(&check.target),
+                check.kind,
+                escape_xml(check.error.as_deref().unwrap_or_default())
+            ));
+        }
+        xml.push_str("  </testcase>\n");
+    }
+
+    let latency = result.latency_summary();
+    if !result.spent.is_empty() || result.budget.is_some() || latency.measured > 0 {
+        xml.push_str("  <properties>\n");
+        for (currency, amount) in &result.spent {
+            xml.push_str(&format!(
+                "    <property name=\"spent.{}\" value=\"{}\"/>\n",
+                escape_xml(currency),
+                amount
+            ));
+        }
+        if let Some(budget) = &result.budget {
+            xml.push_str(&format!(
+                "    <property name=\"budget\" value=\"{} {}\"/>\n",
+                budget.max_total,
+                escape_xml(&budget.currency)
+            ));
+        }
+        if latency.measured > 0 {
+            xml.push_str(&format!(
+                "    <property name=\"latency.over_max\" value=\"{}\"/>\n    <property name=\"latency.under_min\" value=\"{}\"/>\n",
+                latency.over_max, latency.under_min
+            ));
+        }
+        xml.push_str("  </properties>\n");
+    }
+
     for test in &result.tests {
         xml.push_str(&format!(
             r#"  <testcase name="{}" classname="{}" time="{:.3}">"#,
@@ -150,11 +346,42 @@ pub fn generate_junit_xml(result: &SuiteResult) -> String {
         ));
         xml.push('\n');
 
-        if !test.passed {
+        let properties: Vec<(&str, &String)> = [
+            ("http_version", test.http_version.as_ref()),
+            ("extends", test.extends.as_ref()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .collect();
+        if !properties.is_empty() {
+            xml.push_str("    <properties>\n");
+            for (name, value) in properties {
+                xml.push_str(&format!(
+                    "      <property name=\"{}\" value=\"{}\"/>\n",
+                    name,
+                    escape_xml(value)
+                ));
+            }
+            xml.push_str("    </properties>\n");
+        }
+
+        if let Some(reason) = &test.skipped {
+            xml.push_str(&format!(
+                r#"    <skipped message="{}"/>"#,
+                escape_xml(&reason.to_string())
+            ));
+            xml.push('\n');
+        } else if !test.passed {
             if let Some(error) = &test.error {
+                let (message, category) = match test.error_category {
+                    Some(ErrorCategory::Timeout) => ("Request timed out", "timeout"),
+                    Some(ErrorCategory::Connection) => ("Request failed", "connection"),
+                    Some(ErrorCategory::Request) | None => ("Request failed", "request"),
+                };
                 xml.push_str(&format!(
-                    r#"    <failure message="{}">{}</failure>"#,
-                    escape_xml("Request failed"),
+                    r#"    <failure message="{}" type="{}">{}</failure>"#,
+                    escape_xml(message),
+                    category,
                     escape_xml(error)
                 ));
                 xml.push('\n');
@@ -164,12 +391,26 @@ pub fn generate_junit_xml(result: &SuiteResult) -> String {
                     test.assertions.iter().filter(|a| !a.passed).collect();
 
                 for assertion in failed_assertions {
-                    xml.push_str(&format!(
-                        r#"    <failure message="{}">Expected: {} / Actual: {}</failure>"#,
-                        escape_xml(&assertion.description),
-                        escape_xml(&assertion.expected),
-                        escape_xml(&assertion.actual)
-                    ));
+                    match &assertion.invoice_field {
+                        Some(field) => xml.push_str(&format!(
+                            r#"    <failure message="{}" type="invoice.{}">Expected: {} / Parsed: {}</failure>"#,
+                            escape_xml(&assertion.description),
+                            escape_xml(field),
+                            escape_xml(&assertion.expected),
+                            escape_xml(&assertion.actual)
+                        )),
+                        None => xml.push_str(&format!(
+                            r#"    <failure message="{}">Expected: {} / Actual: {}{}</failure>"#,
+                            escape_xml(&assertion.description),
+                            escape_xml(&assertion.expected),
+                            escape_xml(&assertion.actual),
+                            assertion
+                                .failure_detail
+                                .as_ref()
+                                .map(|detail| format!("\n{}", cdata(&detail.render())))
+                                .unwrap_or_default()
+                        )),
+                    }
                     xml.push('\n');
                 }
             }
@@ -182,6 +423,95 @@ pub fn generate_junit_xml(result: &SuiteResult) -> String {
     xml
 }
 
+/// Wrap text in a CDATA section, splitting any `]]>` it contains
+fn cdata(s: &str) -> String {
+    format!("<![CDATA[{}]]>", s.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+/// Generate a standalone HTML report
+pub fn generate_html_report(result: &SuiteResult) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>x402-dev Test Report</title>\n<style>\n\
+         body { font-family: sans-serif; margin: 2em; }\n\
+         .pass { color: #1a7f37; } .fail { color: #cf222e; } .skip { color: #9a6700; }\n\
+         pre { background: #f6f8fa; padding: 0.5em; overflow-x: auto; }\n\
+         </style>\n</head>\n<body>\n<h1>x402-dev Test Report</h1>\n",
+    );
+    html.push_str(&format!(
+        "<p>Total: {} &middot; <span class=\"pass\">Passed: {}</span> &middot; \
+         <span class=\"fail\">Failed: {}</span> &middot; <span class=\"skip\">Skipped: {}</span> \
+         &middot; Duration: {}ms</p>\n",
+        result.total,
+        result.passed,
+        result.failed,
+        result.skipped,
+        result.duration.as_millis()
+    ));
+    if let Some(spend) = describe_spend(result, &AmountFormatter::default()) {
+        html.push_str(&format!("<p>Spent: {}</p>\n", escape_xml(&spend)));
+    }
+    if let Some(latency) = describe_latency(result) {
+        html.push_str(&format!("<p>Latency: {}</p>\n", escape_xml(&latency)));
+    }
+
+    for test in &result.tests {
+        let (class, status) = match (&test.skipped, test.passed) {
+            (Some(_), _) => ("skip", "SKIP"),
+            (None, true) => ("pass", "PASS"),
+            (None, false) => ("fail", "FAIL"),
+        };
+        html.push_str(&format!(
+            "<h2 class=\"{}\">{} {}</h2>\n<p>{} {} ({}ms)</p>\n",
+            class,
+            status,
+            escape_xml(&test.name),
+            escape_xml(&test.method),
+            escape_xml(&test.url),
+            test.duration.as_millis()
+        ));
+
+        if let Some(reason) = &test.skipped {
+            html.push_str(&format!("<p>{}</p>\n", escape_xml(&reason.to_string())));
+        }
+        if let Some(error) = &test.error {
+            let category = test
+                .error_category
+                .map(|c| format!(" ({})", c))
+                .unwrap_or_default();
+            html.push_str(&format!(
+                "<p class=\"fail\">Error{}: {}</p>\n",
+                category,
+                escape_xml(error)
+            ));
+        }
+
+        let failed: Vec<_> = test.assertions.iter().filter(|a| !a.passed).collect();
+        if failed.is_empty() {
+            continue;
+        }
+        html.push_str("<ul>\n");
+        for assertion in failed {
+            html.push_str(&format!("<li>{}", escape_xml(&assertion.description)));
+            match &assertion.failure_detail {
+                Some(detail) => {
+                    html.push_str(&format!("<pre>{}</pre>", escape_xml(&detail.render())))
+                }
+                None => html.push_str(&format!(
+                    "<pre>Expected: {}\nActual:   {}</pre>",
+                    escape_xml(&assertion.expected),
+                    escape_xml(&assertion.actual)
+                )),
+            }
+            html.push_str("</li>\n");
+        }
+        html.push_str("</ul>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
 /// Escape XML special characters
 fn escape_xml(s: &str) -> String {
     s.replace('&', "&amp;")
@@ -194,10 +524,269 @@ fn escape_xml(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{AssertionResult, Budget, FailureDetail};
+    use rust_decimal::Decimal;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
 
     #[test]
     fn test_escape_xml() {
         assert_eq!(escape_xml("foo & bar"), "foo &amp; bar");
         assert_eq!(escape_xml("<test>"), "&lt;test&gt;");
     }
+
+    #[test]
+    fn test_junit_marks_filtered_tests_skipped() {
+        let result = SuiteResult {
+            tests: vec![TestResult {
+                name: "e2e flow".to_string(),
+                url: "http://localhost:3402/api".to_string(),
+                method: "GET".to_string(),
+                passed: false,
+                duration: Duration::ZERO,
+                assertions: vec![],
+                error: None,
+                error_category: None,
+                skipped: Some(SkipReason::Filtered("missing tag 'fast'".to_string())),
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
+            }],
+            total: 1,
+            passed: 0,
+            failed: 0,
+            skipped: 1,
+            duration: Duration::ZERO,
+            spent: Default::default(),
+            budget: None,
+            preflight: None,
+        };
+
+        let xml = generate_junit_xml(&result);
+        assert!(xml.contains(r#"skipped="1""#));
+        assert!(xml.contains(r#"<skipped message="filtered: missing tag &apos;fast&apos;"/>"#));
+        assert!(!xml.contains("<failure"));
+    }
+
+    #[test]
+    fn test_invoice_failures_show_parsed_value() {
+        let result = SuiteResult {
+            tests: vec![TestResult {
+                name: "invoice".to_string(),
+                url: "http://localhost:3402/api".to_string(),
+                method: "GET".to_string(),
+                passed: false,
+                duration: Duration::ZERO,
+                assertions: vec![AssertionResult {
+                    passed: false,
+                    description: "Invoice currency is USDC".to_string(),
+                    expected: "USDC".to_string(),
+                    actual: "SOL".to_string(),
+                    invoice_field: Some("currency".to_string()),
+                    failure_detail: None,
+                }],
+                error: None,
+                error_category: None,
+                skipped: None,
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
+            }],
+            total: 1,
+            passed: 0,
+            failed: 1,
+            skipped: 0,
+            duration: Duration::ZERO,
+            spent: Default::default(),
+            budget: None,
+            preflight: None,
+        };
+
+        let summary = format_summary(&result, false, &AmountFormatter::default());
+        assert!(summary.contains("Parsed:") && summary.contains("SOL"));
+        assert!(!summary.contains("Actual:"));
+        assert!(generate_junit_xml(&result)
+            .contains(r#"type="invoice.currency">Expected: USDC / Parsed: SOL</failure>"#));
+        assert!(format_json(&result).contains(r#""invoice_field": "currency""#));
+    }
+
+    #[test]
+    fn test_timeout_category_rendered() {
+        let result = SuiteResult {
+            tests: vec![TestResult {
+                name: "hung".to_string(),
+                url: "http://localhost:3402/api".to_string(),
+                method: "GET".to_string(),
+                passed: false,
+                duration: Duration::from_millis(100),
+                assertions: vec![],
+                error: Some("Request timed out after 100ms".to_string()),
+                error_category: Some(ErrorCategory::Timeout),
+                skipped: None,
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
+            }],
+            total: 1,
+            passed: 0,
+            failed: 1,
+            skipped: 0,
+            duration: Duration::from_millis(100),
+            spent: Default::default(),
+            budget: None,
+            preflight: None,
+        };
+
+        assert!(format_summary(&result, false, &AmountFormatter::default())
+            .contains("Error (timeout):"));
+        assert!(generate_junit_xml(&result).contains(
+            r#"<failure message="Request timed out" type="timeout">Request timed out after 100ms</failure>"#
+        ));
+        assert!(format_json(&result).contains(r#""error_category": "timeout""#));
+    }
+
+    #[test]
+    fn test_failure_detail_rendered_in_reports() {
+        let result = SuiteResult {
+            tests: vec![TestResult {
+                name: "cache".to_string(),
+                url: "http://localhost:3402/api".to_string(),
+                method: "GET".to_string(),
+                passed: false,
+                duration: Duration::ZERO,
+                assertions: vec![AssertionResult {
+                    passed: false,
+                    description: "Header 'Cache-Control' equals 'no-store'".to_string(),
+                    expected: "no-store".to_string(),
+                    actual: "no-cache".to_string(),
+                    invoice_field: None,
+                    failure_detail: Some(FailureDetail::for_equals(
+                        "Cache-Control",
+                        "no-store",
+                        "no-cache",
+                    )),
+                }],
+                error: None,
+                error_category: None,
+                skipped: None,
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
+            }],
+            total: 1,
+            passed: 0,
+            failed: 1,
+            skipped: 0,
+            duration: Duration::ZERO,
+            spent: Default::default(),
+            budget: None,
+            preflight: None,
+        };
+
+        assert!(format_summary(&result, false, &AmountFormatter::default())
+            .contains("first difference at index 3"));
+        assert!(generate_junit_xml(&result).contains(
+            "Expected: no-store / Actual: no-cache\n<![CDATA[expected: no-store\nactual:   no-cache\n"
+        ));
+        assert!(format_json(&result).contains(r#""first_divergence": 3"#));
+        let html = generate_html_report(&result);
+        assert!(html.contains("<h2 class=\"fail\">FAIL cache</h2>"));
+        assert!(html.contains("<pre>expected: no-store\nactual:   no-cache\n"));
+    }
+
+    #[test]
+    fn test_budget_spend_in_all_reports() {
+        let budget = Budget {
+            max_total: Decimal::new(50, 2),
+            currency: "USDC".to_string(),
+        };
+        let result = SuiteResult {
+            tests: vec![TestResult {
+                name: "third".to_string(),
+                url: "http://localhost:3402/api".to_string(),
+                method: "GET".to_string(),
+                passed: false,
+                duration: Duration::ZERO,
+                assertions: vec![],
+                error: None,
+                error_category: None,
+                skipped: Some(SkipReason::BudgetExceeded {
+                    spent: Decimal::new(50, 2),
+                    budget: budget.clone(),
+                }),
+                http_version: None,
+                encoded_size: None,
+                extends: Some("paid-402".to_string()),
+                latency: Vec::new(),
+            }],
+            total: 3,
+            passed: 2,
+            failed: 0,
+            skipped: 1,
+            duration: Duration::ZERO,
+            spent: BTreeMap::from([("USDC".to_string(), Decimal::new(50, 2))]),
+            budget: Some(budget),
+            preflight: None,
+        };
+
+        let summary = format_summary(&result, false, &AmountFormatter::default());
+        assert!(summary.contains("SKIP"));
+        assert!(summary.contains("(budget exceeded: spent 0.50 of 0.50 USDC)"));
+        assert!(summary.contains("0.50 USDC (budget 0.50 USDC)"));
+
+        let json: serde_json::Value = serde_json::from_str(&format_json(&result)).unwrap();
+        assert_eq!(json["spent"], serde_json::json!({ "USDC": "0.50" }));
+        assert_eq!(json["budget"]["max_total"], "0.50");
+        assert_eq!(json["tests"][0]["extends"], "paid-402");
+
+        let xml = generate_junit_xml(&result);
+        assert!(xml.contains(r#"<property name="spent.USDC" value="0.50"/>"#));
+        assert!(xml.contains(r#"<property name="budget" value="0.50 USDC"/>"#));
+        assert!(xml.contains(r#"<property name="extends" value="paid-402"/>"#));
+        assert!(
+            generate_html_report(&result).contains("<p>Spent: 0.50 USDC (budget 0.50 USDC)</p>")
+        );
+        assert!(format_github(&result, "suite.yaml").contains(
+            "::warning title=Budget exceeded::budget exceeded: spent 0.50 of 0.50 USDC; remaining tests were skipped"
+        ));
+    }
+
+    #[test]
+    fn test_spend_display_format_leaves_json_canonical() {
+        let result = SuiteResult {
+            tests: vec![],
+            total: 0,
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+            duration: Duration::ZERO,
+            spent: BTreeMap::from([("USDC".to_string(), Decimal::new(12345675, 1))]),
+            budget: Some(Budget {
+                max_total: Decimal::new(2_000_000, 0),
+                currency: "USDC".to_string(),
+            }),
+            preflight: None,
+        };
+
+        let summary = format_summary(&result, true, &AmountFormatter::default());
+        assert!(summary.contains("1,234,567.50 USDC (budget 2,000,000.00 USDC)"));
+        let ungrouped = AmountFormatter {
+            grouping: false,
+            ..AmountFormatter::default()
+        };
+        assert!(format_summary(&result, true, &ungrouped).contains("1234567.50 USDC"));
+
+        let json: serde_json::Value = serde_json::from_str(&format_json(&result)).unwrap();
+        assert_eq!(json["spent"]["USDC"], "1234567.5");
+        assert_eq!(json["budget"]["max_total"], "2000000");
+    }
+
+    #[test]
+    fn test_cdata_splits_terminator() {
+        assert_eq!(cdata("a]]>b"), "<![CDATA[a]]]]><![CDATA[>b]]>");
+    }
 }
@@ -0,0 +1,183 @@
+// Host name overrides for the check and test clients (--resolve)
+//
+// `HOST:PORT:ADDR` entries pin a host name to an address without touching
+// DNS or /etc/hosts, the way curl's --resolve does. IPv6 addresses may be
+// written in brackets (`api.local:443:[::1]`). reqwest keys overrides by host
+// only: the override applies to every port of the host, and URLs without a
+// port connect to PORT.
+
+use super::faults::{classify_fault, FaultKind};
+use reqwest::ClientBuilder;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+/// One `HOST:PORT:ADDR` entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ResolveOverride {
+    /// Host name, lowercased
+    pub host: String,
+    pub port: u16,
+    pub addr: IpAddr,
+}
+
+impl ResolveOverride {
+    /// Address requests to the host connect to
+    pub fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.addr, self.port)
+    }
+
+    /// True if this entry applies to `url`
+    pub fn matches(&self, url: &reqwest::Url) -> bool {
+        url.host_str()
+            .is_some_and(|host| host.eq_ignore_ascii_case(&self.host))
+    }
+}
+
+impl FromStr for ResolveOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = |reason: &str| format!("invalid resolve entry '{}': {}", s, reason);
+
+        let mut parts = s.splitn(3, ':');
+        let (Some(host), Some(port), Some(addr)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(malformed("expected HOST:PORT:ADDR"));
+        };
+
+        if host.is_empty() {
+            return Err(malformed("host is empty"));
+        }
+        if !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_')
+        {
+            return Err(malformed("host must be a plain host name"));
+        }
+
+        let port = match port.parse::<u16>() {
+            Ok(port) if port > 0 => port,
+            _ => return Err(malformed("port must be 1-65535")),
+        };
+
+        let addr = match addr.strip_prefix('[') {
+            Some(rest) => match rest.strip_suffix(']') {
+                Some(inner) => match inner.parse::<IpAddr>() {
+                    Ok(IpAddr::V6(v6)) => IpAddr::V6(v6),
+                    _ => return Err(malformed("brackets must hold an IPv6 address")),
+                },
+                None => return Err(malformed("missing closing ']' after IPv6 address")),
+            },
+            None => addr
+                .parse::<IpAddr>()
+                .map_err(|_| malformed("address must be an IPv4 or IPv6 address"))?,
+        };
+
+        Ok(Self {
+            host: host.to_ascii_lowercase(),
+            port,
+            addr,
+        })
+    }
+}
+
+impl TryFrom<String> for ResolveOverride {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<ResolveOverride> for String {
+    fn from(entry: ResolveOverride) -> Self {
+        entry.to_string()
+    }
+}
+
+impl fmt::Display for ResolveOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.addr {
+            IpAddr::V4(v4) => write!(f, "{}:{}:{}", self.host, self.port, v4),
+            IpAddr::V6(v6) => write!(f, "{}:{}:[{}]", self.host, self.port, v6),
+        }
+    }
+}
+
+/// Add `overrides` to a client; later entries for the same host win
+pub fn apply_resolve_overrides(
+    mut builder: ClientBuilder,
+    overrides: &[ResolveOverride],
+) -> ClientBuilder {
+    for entry in overrides {
+        builder = builder.resolve(&entry.host, entry.socket_addr());
+    }
+    builder
+}
+
+/// The override that applies to `url`, if any (the last matching entry)
+pub fn override_for_url<'a>(
+    url: &str,
+    overrides: &'a [ResolveOverride],
+) -> Option<&'a ResolveOverride> {
+    let url = reqwest::Url::parse(url).ok()?;
+    overrides.iter().rev().find(|entry| entry.matches(&url))
+}
+
+/// True if a request failed while resolving the host name
+pub fn is_dns_error(error: &reqwest::Error) -> bool {
+    classify_fault(error) == Some(FaultKind::DnsFailure)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ipv4_and_bracketed_ipv6() {
+        let entry: ResolveOverride = "API.Example:8402:127.0.0.1".parse().unwrap();
+        assert_eq!(entry.host, "api.example");
+        assert_eq!(entry.socket_addr(), "127.0.0.1:8402".parse().unwrap());
+        assert_eq!(entry.to_string(), "api.example:8402:127.0.0.1");
+
+        let entry: ResolveOverride = "api.example:443:[::1]".parse().unwrap();
+        assert_eq!(entry.socket_addr(), "[::1]:443".parse().unwrap());
+        assert_eq!(entry.to_string(), "api.example:443:[::1]");
+
+        // Unbracketed IPv6 is unambiguous once host and port are split off
+        let bare: ResolveOverride = "api.example:443:::1".parse().unwrap();
+        assert_eq!(bare, entry);
+    }
+
+    #[test]
+    fn test_malformed_entries_rejected() {
+        for (entry, expected) in [
+            ("api.example:8402", "expected HOST:PORT:ADDR"),
+            (":8402:127.0.0.1", "host is empty"),
+            ("api/v1:80:127.0.0.1", "plain host name"),
+            ("api.example:0:127.0.0.1", "port must be 1-65535"),
+            ("api.example:http:127.0.0.1", "port must be 1-65535"),
+            ("api.example:80:localhost", "IPv4 or IPv6"),
+            ("api.example:80:[::1", "missing closing ']'"),
+            ("api.example:80:[127.0.0.1]", "IPv6 address"),
+        ] {
+            let err = entry.parse::<ResolveOverride>().unwrap_err();
+            assert!(err.contains(expected), "{}: {}", entry, err);
+        }
+    }
+
+    #[test]
+    fn test_override_for_url() {
+        let overrides: Vec<ResolveOverride> = ["a.example:80:10.0.0.1", "a.example:80:10.0.0.2"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+
+        let entry = override_for_url("http://A.example:8080/api", &overrides).unwrap();
+        assert_eq!(entry.addr.to_string(), "10.0.0.2");
+        assert!(override_for_url("http://b.example/api", &overrides).is_none());
+    }
+}
@@ -0,0 +1,260 @@
+// Payment verification behind one interface
+//
+// A `PaymentVerifier` decides whether a payment proof pays an invoice. The
+// mock server's payment flow, `x402-dev verify` and the tests all go
+// through it, whichever backend `verifier:` selects: the simulated one
+// (simulation modes, declared amounts) or the RPC one (a transaction looked
+// up on a real cluster or the mock's fake `/rpc`). Both classify what they
+// find the same way, so a receipt, an event or a stats counter means the
+// same thing for either. The implementations live with the invoice store
+// in x402-server.
+//
+// The trait returns a boxed future rather than using `async fn` so that the
+// backend can be picked at runtime as a `&dyn PaymentVerifier`.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use x402_domain::{Amount, Decimal};
+
+/// Error `code` of a payment below the invoice amount and its tolerance
+pub const UNDERPAYMENT: &str = "underpayment";
+
+/// Error `code` of a payment above the invoice amount and its tolerance
+pub const OVERPAYMENT: &str = "overpayment";
+
+/// Verification backend (`verifier:` in the config)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifierKind {
+    /// Simulation modes decide; the proof is the invoice memo
+    #[default]
+    Simulated,
+    /// The proof is a transaction signature checked over Solana JSON-RPC
+    Rpc,
+}
+
+impl VerifierKind {
+    pub fn is_simulated(&self) -> bool {
+        *self == Self::Simulated
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Simulated => "simulated",
+            Self::Rpc => "rpc",
+        }
+    }
+}
+
+impl fmt::Display for VerifierKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for VerifierKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "simulated" => Ok(Self::Simulated),
+            "rpc" => Ok(Self::Rpc),
+            _ => anyhow::bail!("Unknown verifier '{}'\nFix: Use simulated or rpc", s),
+        }
+    }
+}
+
+/// A payment proof as the payer sent it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentProof {
+    /// `X-Payment-Proof`: the invoice memo, or a transaction signature
+    pub proof: String,
+    /// Amount the payer declared (`X-Payment-Amount`); simulated payments
+    /// without one paid the invoice exactly
+    pub declared_amount: Option<Amount>,
+    /// When the proof arrived; invoice deadlines are checked against it
+    pub received_at: DateTime<Utc>,
+}
+
+/// What the proof must pay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvoiceTerms {
+    /// Memo the payment must carry; `None` to take it from the payment and
+    /// look the invoice up by it
+    pub memo: Option<String>,
+    /// Address the payment must go to; `None` to take it from the invoice
+    pub recipient: Option<String>,
+    pub amount: Amount,
+    /// Path the proof was sent to
+    pub resource: String,
+}
+
+/// A payment amount outside the tolerance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountMismatch {
+    pub invoiced: Amount,
+    pub paid: Amount,
+    /// Largest difference that would have been accepted
+    pub allowance: Decimal,
+}
+
+impl AmountMismatch {
+    /// [`UNDERPAYMENT`] or [`OVERPAYMENT`]
+    pub fn code(&self) -> &'static str {
+        if self.paid < self.invoiced {
+            UNDERPAYMENT
+        } else {
+            OVERPAYMENT
+        }
+    }
+}
+
+impl fmt::Display for AmountMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Paid {} USDC for an invoice of {} USDC (tolerance: {} USDC)",
+            self.paid.to_canonical_string(),
+            self.invoiced.to_canonical_string(),
+            self.allowance.normalize()
+        )
+    }
+}
+
+/// Why a proof did not pay its invoice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FailureReason {
+    /// Refused: a simulated failure, or no settled transfer matching the
+    /// invoice (detail when the backend knows more)
+    Rejected(Option<String>),
+    /// Not settled in time: a simulated timeout or an unconfirmed
+    /// transaction; the payer may try again
+    Pending(String),
+    /// The proof was already used
+    Replayed,
+    /// The invoice expired before it was paid
+    Expired { expires_at: DateTime<Utc> },
+    /// Paid, but not the invoiced amount
+    AmountMismatch(AmountMismatch),
+}
+
+/// Result of verifying a proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationOutcome {
+    Verified {
+        /// Memo of the paid invoice
+        memo: String,
+        /// `paid - invoiced`, within the tolerance
+        amount_delta: Decimal,
+        /// The proof was used before and replays are allowed
+        replayed: bool,
+    },
+    Failed(FailureReason),
+}
+
+impl VerificationOutcome {
+    pub fn is_verified(&self) -> bool {
+        matches!(self, Self::Verified { .. })
+    }
+
+    /// Classification shared by all backends: `verified`, `rejected`,
+    /// `pending`, `replayed`, `expired`, `underpayment` or `overpayment`
+    pub fn class(&self) -> &'static str {
+        match self {
+            Self::Verified { .. } => "verified",
+            Self::Failed(FailureReason::Rejected(_)) => "rejected",
+            Self::Failed(FailureReason::Pending(_)) => "pending",
+            Self::Failed(FailureReason::Replayed) => "replayed",
+            Self::Failed(FailureReason::Expired { .. }) => "expired",
+            Self::Failed(FailureReason::AmountMismatch(mismatch)) => mismatch.code(),
+        }
+    }
+}
+
+impl fmt::Display for VerificationOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Verified { replayed: true, .. } => write!(f, "verified (replayed proof)"),
+            Self::Verified { .. } => write!(f, "verified"),
+            Self::Failed(FailureReason::Rejected(Some(detail))) => {
+                write!(f, "rejected: {}", detail)
+            }
+            Self::Failed(FailureReason::Rejected(None)) => write!(f, "rejected"),
+            Self::Failed(FailureReason::Pending(detail)) => write!(f, "pending: {}", detail),
+            Self::Failed(FailureReason::Replayed) => write!(f, "proof already used"),
+            Self::Failed(FailureReason::Expired { expires_at }) => {
+                write!(f, "invoice expired at {}", expires_at.to_rfc3339())
+            }
+            Self::Failed(FailureReason::AmountMismatch(mismatch)) => write!(f, "{}", mismatch),
+        }
+    }
+}
+
+/// Future returned by [`PaymentVerifier::verify`]
+pub type VerifyFuture<'a> = Pin<Box<dyn Future<Output = Result<VerificationOutcome>> + Send + 'a>>;
+
+/// Decides whether a payment proof pays an invoice
+///
+/// `Err` is reserved for a backend that could not answer (an unreachable
+/// RPC endpoint); a proof that does not pay is an `Ok` with
+/// [`VerificationOutcome::Failed`].
+pub trait PaymentVerifier: Send + Sync {
+    fn kind(&self) -> VerifierKind;
+
+    fn verify<'a>(&'a self, proof: &'a PaymentProof, invoice: &'a InvoiceTerms)
+        -> VerifyFuture<'a>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn amount(s: &str) -> Amount {
+        Amount::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_classes() {
+        let mismatch = |paid| {
+            VerificationOutcome::Failed(FailureReason::AmountMismatch(AmountMismatch {
+                invoiced: amount("0.05"),
+                paid: amount(paid),
+                allowance: Decimal::ZERO,
+            }))
+        };
+        let verified = VerificationOutcome::Verified {
+            memo: "req-1".to_string(),
+            amount_delta: Decimal::ZERO,
+            replayed: false,
+        };
+        assert_eq!(verified.class(), "verified");
+        assert_eq!(mismatch("0.04").class(), UNDERPAYMENT);
+        assert_eq!(mismatch("0.06").class(), OVERPAYMENT);
+        assert_eq!(
+            VerificationOutcome::Failed(FailureReason::Pending("unconfirmed".into())).class(),
+            "pending"
+        );
+        assert_eq!(
+            mismatch("0.048").to_string(),
+            "Paid 0.048 USDC for an invoice of 0.05 USDC (tolerance: 0 USDC)"
+        );
+    }
+
+    #[test]
+    fn test_verifier_kind_config_values() {
+        assert_eq!(VerifierKind::default(), VerifierKind::Simulated);
+        let kind: VerifierKind = serde_yaml::from_str("rpc").unwrap();
+        assert_eq!(kind, VerifierKind::Rpc);
+        assert_eq!(
+            VerifierKind::from_str("simulated").unwrap().as_str(),
+            "simulated"
+        );
+        let err = VerifierKind::from_str("chain").unwrap_err().to_string();
+        assert!(err.contains("Fix: Use simulated or rpc"), "{}", err);
+    }
+}
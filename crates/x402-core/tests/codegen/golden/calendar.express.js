@@ -0,0 +1,217 @@
+// Generated by: x402-dev policy generate calendar.yaml
+// DO NOT EDIT THIS FILE MANUALLY - Regenerate from policy file
+
+
+// Spending tracking (in-memory, use database for production)
+const spendingStore = new Map();
+
+/**
+ * Check if spending cap is exceeded for an agent
+ * @param {string} agentId - The agent identifier
+ * @param {number} amount - Current request amount
+ * @param {number} maxAmount - Maximum spending allowed
+ * @param {object} window - { type: 'rolling', seconds } or
+ *   { type: 'calendar_day' | 'calendar_month', timeZone }
+ * @returns {boolean} true if spending cap exceeded
+ */
+function spendingCapExceeded(agentId, amount, maxAmount, window) {
+  const now = Date.now();
+  const key = `spending:${agentId}`;
+
+  if (window.type === 'rolling') {
+    // Keep only payments inside the sliding window
+    const payments = (spendingStore.get(key) || [])
+      .filter(entry => now - entry.timestamp <= window.seconds * 1000);
+    spendingStore.set(key, payments);
+
+    const total = payments.reduce((sum, entry) => sum + entry.amount, 0);
+    if (total + amount > maxAmount) {
+      return true;
+    }
+    payments.push({ timestamp: now, amount });
+    return false;
+  }
+
+  // Calendar windows keep one running total, reset when the bucket changes
+  const bucket = calendarBucket(window, now);
+  const spending = spendingStore.get(key);
+  const total = spending && spending.bucket === bucket ? spending.total : 0;
+
+  // Check if adding this amount would exceed cap
+  if (total + amount > maxAmount) {
+    return true;
+  }
+
+  // Add to spending
+  spendingStore.set(key, { bucket, total: total + amount });
+
+  return false;
+}
+
+/**
+ * Calendar day or month a timestamp falls in, in the window's timezone
+ * @param {object} window - { type: 'calendar_day' | 'calendar_month', timeZone }
+ * @param {number} timestamp - Milliseconds since the epoch
+ * @returns {string} '2025-03-14' for days, '2025-03' for months
+ */
+function calendarBucket(window, timestamp) {
+  const day = new Intl.DateTimeFormat('en-CA', {
+    timeZone: window.timeZone,
+    year: 'numeric',
+    month: '2-digit',
+    day: '2-digit'
+  }).format(timestamp);
+  return window.type === 'calendar_month' ? day.slice(0, 7) : day;
+}
+
+/**
+ * Amount left under the agent's spending cap in the current window
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxAmount - Maximum spending allowed
+ * @param {object} window - Same window object as the spending cap check
+ * @returns {number} Amount remaining
+ */
+function spendingRemaining(agentId, maxAmount, window) {
+  const now = Date.now();
+  const spending = spendingStore.get(`spending:${agentId}`);
+  let total = 0;
+  if (window.type === 'rolling') {
+    total = (spending || [])
+      .filter(entry => now - entry.timestamp <= window.seconds * 1000)
+      .reduce((sum, entry) => sum + entry.amount, 0);
+  } else if (spending && spending.bucket === calendarBucket(window, now)) {
+    total = spending.total;
+  }
+  return Math.max(0, maxAmount - total);
+}
+
+/**
+ * Decimal string with at most 6 places, e.g. 0.09
+ * @param {number} amount - Amount to format
+ * @returns {string}
+ */
+function formatAmount(amount) {
+  return String(Number(amount.toFixed(6)));
+}
+
+// In-flight request tracking (per process)
+const inFlightStore = new Map();
+
+/**
+ * Take an in-flight slot for an agent
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxInFlight - Maximum simultaneous requests
+ * @returns {boolean} true if a slot was taken
+ */
+function acquireSlot(agentId, maxInFlight) {
+  const count = inFlightStore.get(agentId) || 0;
+  if (count >= maxInFlight) {
+    return false;
+  }
+  inFlightStore.set(agentId, count + 1);
+  return true;
+}
+
+/**
+ * Release the agent's slot once the response finishes or the client disconnects
+ * @param {object} res - Node.js response object
+ * @param {string} agentId - The agent identifier
+ */
+function releaseSlotOnFinish(res, agentId) {
+  let released = false;
+  const release = () => {
+    if (released) return;
+    released = true;
+    const count = inFlightStore.get(agentId) || 0;
+    if (count <= 1) {
+      inFlightStore.delete(agentId);
+    } else {
+      inFlightStore.set(agentId, count - 1);
+    }
+  };
+  res.on('finish', release);
+  res.on('close', release);
+}
+
+/**
+ * Generate x402 payment invoice
+ * @param {string} agentId - The agent identifier
+ * @param {string} resource - The requested resource
+ * @returns {string} WWW-Authenticate header value
+ */
+function generateInvoice(agentId, resource) {
+  const amount = 0.05;
+  const currency = 'USDC';
+  const memoPrefix = 'shop';
+  const memo = memoPrefix ? `${memoPrefix}_req_${Date.now()}` : `req_${Date.now()}`;
+
+  // Simplified invoice format (replace with actual x402 protocol format)
+  const invoice = `x402 amount="${amount}" currency="${currency}" memo="${memo}" agent="${agentId}" resource="${resource}"`;
+
+  return invoice;
+}
+
+
+/**
+ * x402 Payment middleware with policy enforcement
+ * @param {object} req - Express request object
+ * @param {object} res - Express response object
+ * @param {function} next - Express next middleware function
+ */
+const x402Middleware = (req, res, next) => {
+  // Extract agent ID from header
+  const agentId = req.headers['x-agent-id'] || req.headers['x-forwarded-for'] || 'unknown';
+  const resource = req.path;
+
+  const requestAmount = 0.01; // Amount for this request
+
+  // Denylist policy check
+  const deniedAgents_0 = ['9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin'];
+  if (deniedAgents_0.includes(agentId)) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'denylist_check', 'denied');
+    }
+    return res.status(403).json({ error: 'Agent denied' });
+  }
+
+  // Spending cap policy check
+  if (spendingCapExceeded(agentId, requestAmount, 25.5, { type: 'calendar_day', timeZone: 'America/New_York' })) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'spending_cap_check', 'exceeded');
+    }
+    return res.status(402).json({
+      error: 'Spending cap exceeded',
+      max_amount: 25.5,
+      window: { type: 'calendar_day', timeZone: 'America/New_York' },
+      currency: 'USDC'
+    });
+  }
+
+  // Concurrency limit policy check
+  if (!acquireSlot(agentId, 4)) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'concurrency_limit_check', 'exceeded');
+    }
+    return res.status(429).json({ error: 'Too many concurrent requests', max_in_flight: 4 });
+  }
+  releaseSlotOnFinish(res, agentId);
+
+  // Remaining quota, as the x402-dev mock server reports it
+  res.set('X-Spend-Cap', formatAmount(25.5));
+  res.set('X-Spend-Remaining', formatAmount(spendingRemaining(agentId, 25.5, { type: 'calendar_day', timeZone: 'America/New_York' })));
+
+  // Generate 402 Payment Required response with invoice
+  const invoice = generateInvoice(agentId, resource);
+
+  // Return 402 Payment Required with invoice
+  res.status(402)
+     .set('WWW-Authenticate', invoice)
+     .json({
+       error: 'Payment Required',
+       invoice: invoice,
+       amount: 0.05,
+       currency: 'USDC'
+     });
+};
+
+module.exports = x402Middleware;
@@ -0,0 +1,349 @@
+// Generated by: x402-dev policy generate edge_cases.yaml
+// DO NOT EDIT THIS FILE MANUALLY - Regenerate from policy file
+
+
+// Rate limit tracking (in-memory, use Redis for production)
+const rateLimitStore = new Map();
+
+/**
+ * Check if rate limit is exceeded for an agent
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxRequests - Maximum requests allowed
+ * @param {number} windowSeconds - Time window in seconds
+ * @returns {boolean} true if rate limit exceeded
+ */
+function rateLimitExceeded(agentId, maxRequests, windowSeconds) {
+  const now = Date.now();
+  const key = `ratelimit:${agentId}`;
+
+  if (!rateLimitStore.has(key)) {
+    rateLimitStore.set(key, []);
+  }
+
+  const timestamps = rateLimitStore.get(key);
+  const windowStart = now - (windowSeconds * 1000);
+
+  // Remove expired timestamps
+  const validTimestamps = timestamps.filter(ts => ts >= windowStart);
+  rateLimitStore.set(key, validTimestamps);
+
+  // Check if limit exceeded
+  if (validTimestamps.length >= maxRequests) {
+    return true;
+  }
+
+  // Add current request timestamp
+  validTimestamps.push(now);
+  rateLimitStore.set(key, validTimestamps);
+
+  return false;
+}
+
+/**
+ * Requests left in the agent's current rate limit window
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxRequests - Maximum requests allowed
+ * @param {number} windowSeconds - Time window in seconds
+ * @returns {number} Requests remaining
+ */
+function rateLimitRemaining(agentId, maxRequests, windowSeconds) {
+  const windowStart = Date.now() - (windowSeconds * 1000);
+  const timestamps = rateLimitStore.get(`ratelimit:${agentId}`) || [];
+  return Math.max(0, maxRequests - timestamps.filter(ts => ts >= windowStart).length);
+}
+
+// Spending tracking (in-memory, use database for production)
+const spendingStore = new Map();
+
+/**
+ * Check if spending cap is exceeded for an agent
+ * @param {string} agentId - The agent identifier
+ * @param {number} amount - Current request amount
+ * @param {number} maxAmount - Maximum spending allowed
+ * @param {object} window - { type: 'rolling', seconds } or
+ *   { type: 'calendar_day' | 'calendar_month', timeZone }
+ * @returns {boolean} true if spending cap exceeded
+ */
+function spendingCapExceeded(agentId, amount, maxAmount, window) {
+  const now = Date.now();
+  const key = `spending:${agentId}`;
+
+  if (window.type === 'rolling') {
+    // Keep only payments inside the sliding window
+    const payments = (spendingStore.get(key) || [])
+      .filter(entry => now - entry.timestamp <= window.seconds * 1000);
+    spendingStore.set(key, payments);
+
+    const total = payments.reduce((sum, entry) => sum + entry.amount, 0);
+    if (total + amount > maxAmount) {
+      return true;
+    }
+    payments.push({ timestamp: now, amount });
+    return false;
+  }
+
+  // Calendar windows keep one running total, reset when the bucket changes
+  const bucket = calendarBucket(window, now);
+  const spending = spendingStore.get(key);
+  const total = spending && spending.bucket === bucket ? spending.total : 0;
+
+  // Check if adding this amount would exceed cap
+  if (total + amount > maxAmount) {
+    return true;
+  }
+
+  // Add to spending
+  spendingStore.set(key, { bucket, total: total + amount });
+
+  return false;
+}
+
+/**
+ * Calendar day or month a timestamp falls in, in the window's timezone
+ * @param {object} window - { type: 'calendar_day' | 'calendar_month', timeZone }
+ * @param {number} timestamp - Milliseconds since the epoch
+ * @returns {string} '2025-03-14' for days, '2025-03' for months
+ */
+function calendarBucket(window, timestamp) {
+  const day = new Intl.DateTimeFormat('en-CA', {
+    timeZone: window.timeZone,
+    year: 'numeric',
+    month: '2-digit',
+    day: '2-digit'
+  }).format(timestamp);
+  return window.type === 'calendar_month' ? day.slice(0, 7) : day;
+}
+
+/**
+ * Amount left under the agent's spending cap in the current window
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxAmount - Maximum spending allowed
+ * @param {object} window - Same window object as the spending cap check
+ * @returns {number} Amount remaining
+ */
+function spendingRemaining(agentId, maxAmount, window) {
+  const now = Date.now();
+  const spending = spendingStore.get(`spending:${agentId}`);
+  let total = 0;
+  if (window.type === 'rolling') {
+    total = (spending || [])
+      .filter(entry => now - entry.timestamp <= window.seconds * 1000)
+      .reduce((sum, entry) => sum + entry.amount, 0);
+  } else if (spending && spending.bucket === calendarBucket(window, now)) {
+    total = spending.total;
+  }
+  return Math.max(0, maxAmount - total);
+}
+
+/**
+ * Decimal string with at most 6 places, e.g. 0.09
+ * @param {number} amount - Amount to format
+ * @returns {string}
+ */
+function formatAmount(amount) {
+  return String(Number(amount.toFixed(6)));
+}
+
+// In-flight request tracking (per process)
+const inFlightStore = new Map();
+
+/**
+ * Take an in-flight slot for an agent
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxInFlight - Maximum simultaneous requests
+ * @returns {boolean} true if a slot was taken
+ */
+function acquireSlot(agentId, maxInFlight) {
+  const count = inFlightStore.get(agentId) || 0;
+  if (count >= maxInFlight) {
+    return false;
+  }
+  inFlightStore.set(agentId, count + 1);
+  return true;
+}
+
+/**
+ * Release the agent's slot once the response finishes or the client disconnects
+ * @param {object} res - Node.js response object
+ * @param {string} agentId - The agent identifier
+ */
+function releaseSlotOnFinish(res, agentId) {
+  let released = false;
+  const release = () => {
+    if (released) return;
+    released = true;
+    const count = inFlightStore.get(agentId) || 0;
+    if (count <= 1) {
+      inFlightStore.delete(agentId);
+    } else {
+      inFlightStore.set(agentId, count - 1);
+    }
+  };
+  res.on('finish', release);
+  res.on('close', release);
+}
+
+/**
+ * Generate x402 payment invoice
+ * @param {string} agentId - The agent identifier
+ * @param {string} resource - The requested resource
+ * @returns {string} WWW-Authenticate header value
+ */
+function generateInvoice(agentId, resource) {
+  const amount = 0.000001;
+  const currency = 'US\'DC';
+  const memoPrefix = 'it\'s\\memo';
+  const memo = memoPrefix ? `${memoPrefix}_req_${Date.now()}` : `req_${Date.now()}`;
+
+  // Simplified invoice format (replace with actual x402 protocol format)
+  const invoice = `x402 amount="${amount}" currency="${currency}" memo="${memo}" agent="${agentId}" resource="${resource}"`;
+
+  return invoice;
+}
+
+/**
+ * Log payment attempt for audit trail
+ * @param {string} agentId - The agent identifier
+ * @param {string} resource - The requested resource
+ * @param {string} action - The action taken ('payment_required', 'rate_limited', 'denied', etc.)
+ * @param {string} result - The result ('success', 'failure', etc.)
+ */
+function logPaymentAttempt(agentId, resource, action, result) {
+  const timestamp = new Date().toISOString();
+  const logEntry = {
+    timestamp,
+    agent_id: agentId,
+    resource,
+    action,
+    result
+  };
+
+  const format = 'csv';
+  const destination = '/var/log/x402 \'audit\'.log';
+
+  let logLine;
+  if (format === 'csv') {
+    // CSV format: timestamp,agent_id,resource,action,result
+    logLine = `${timestamp},${agentId},${resource},${action},${result}`;
+  } else {
+    // JSON format
+    logLine = JSON.stringify(logEntry);
+  }
+
+  // Output to destination
+  if (destination === 'stdout') {
+    console.log(logLine);
+  } else {
+    // In production, write to file or send to logging service
+    // For now, just log to console
+    console.log(`[AUDIT:${destination}] ${logLine}`);
+  }
+}
+
+/**
+ * x402 Payment middleware with policy enforcement
+ * @param {object} req - Express request object
+ * @param {object} res - Express response object
+ * @param {function} next - Express next middleware function
+ */
+const x402Middleware = (req, res, next) => {
+  // Extract agent ID from header
+  const agentId = req.headers['x-agent-id'] || req.headers['x-forwarded-for'] || 'unknown';
+  const resource = req.path;
+
+  const requestAmount = 0.01; // Amount for this request
+
+  // Allowlist policy check
+  const allowedAgents_0 = ['агент-1', 'エージェント', '🤖-bot', 'o\'brien', 'back\\slash', 'line\nbreak', 'para\u2028sep', ''];
+  if (!allowedAgents_0.includes(agentId)) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'allowlist_check', 'denied');
+    }
+    return res.status(403).json({ error: 'Agent not allowed' });
+  }
+
+  // Denylist policy check
+  const deniedAgents_1 = ['\'; process.exit(1); \'', '</script>', '${injected}'];
+  if (deniedAgents_1.includes(agentId)) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'denylist_check', 'denied');
+    }
+    return res.status(403).json({ error: 'Agent denied' });
+  }
+
+  // Rate limit policy check
+  if (rateLimitExceeded(agentId, 1, 1)) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'rate_limit_check', 'exceeded');
+    }
+    return res.status(429).json({ error: 'Rate limit exceeded', retry_after: 1 });
+  }
+
+  // Rate limit policy check
+  if (rateLimitExceeded(agentId, 4294967295, 86400)) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'rate_limit_check', 'exceeded');
+    }
+    return res.status(429).json({ error: 'Rate limit exceeded', retry_after: 86400 });
+  }
+
+  // Spending cap policy check
+  if (spendingCapExceeded(agentId, requestAmount, 0.000001, { type: 'rolling', seconds: 60 })) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'spending_cap_check', 'exceeded');
+    }
+    return res.status(402).json({
+      error: 'Spending cap exceeded',
+      max_amount: 0.000001,
+      window: { type: 'rolling', seconds: 60 },
+      currency: 'US\'DC'
+    });
+  }
+
+  // Spending cap policy check
+  if (spendingCapExceeded(agentId, requestAmount, 1000000, { type: 'calendar_month', timeZone: 'Asia/Kathmandu' })) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'spending_cap_check', 'exceeded');
+    }
+    return res.status(402).json({
+      error: 'Spending cap exceeded',
+      max_amount: 1000000,
+      window: { type: 'calendar_month', timeZone: 'Asia/Kathmandu' },
+      currency: 'USDC'
+    });
+  }
+
+  // Concurrency limit policy check
+  if (!acquireSlot(agentId, 1)) {
+    if (typeof logPaymentAttempt === 'function') {
+      logPaymentAttempt(agentId, resource, 'concurrency_limit_check', 'exceeded');
+    }
+    return res.status(429).json({ error: 'Too many concurrent requests', max_in_flight: 1 });
+  }
+  releaseSlotOnFinish(res, agentId);
+
+  // Remaining quota, as the x402-dev mock server reports it
+  res.set('X-RateLimit-Limit', '1');
+  res.set('X-RateLimit-Remaining', String(rateLimitRemaining(agentId, 1, 1)));
+  res.set('X-RateLimit-Reset', '1');
+  res.set('X-Spend-Cap', formatAmount(0.000001));
+  res.set('X-Spend-Remaining', formatAmount(spendingRemaining(agentId, 0.000001, { type: 'rolling', seconds: 60 })));
+
+  // Generate 402 Payment Required response with invoice
+  const invoice = generateInvoice(agentId, resource);
+
+  // Log payment attempt
+  logPaymentAttempt(agentId, resource, 'payment_required', 'pending');
+
+  // Return 402 Payment Required with invoice
+  res.status(402)
+     .set('WWW-Authenticate', invoice)
+     .json({
+       error: 'Payment Required',
+       invoice: invoice,
+       amount: 0.000001,
+       currency: 'US\'DC'
+     });
+};
+
+module.exports = x402Middleware;
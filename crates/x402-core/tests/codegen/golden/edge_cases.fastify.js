@@ -0,0 +1,374 @@
+// Generated by: x402-dev policy generate
+// Policy file: edge_cases.yaml
+// Framework: Fastify.js
+//
+// This is a production-ready Fastify plugin implementing x402 protocol
+// payment enforcement with the policies defined in your YAML file.
+//
+// Installation:
+//   npm install fastify fastify-plugin
+//
+// Usage:
+//   const fastify = require('fastify')();
+//   const x402Plugin = require('./x402-policy-plugin');
+//
+//   fastify.register(x402Plugin);
+//   fastify.listen({ port: 3000 });
+
+'use strict';
+
+const fp = require('fastify-plugin');
+
+// JSON Schema for request validation
+const requestSchema = {
+  type: 'object',
+  properties: {
+    headers: {
+      type: 'object',
+      properties: {
+        'x-agent-id': { type: 'string' },
+        'x-wallet-address': { type: 'string' },
+        'x-request-id': { type: 'string' }
+      },
+      required: ['x-agent-id']
+    }
+  }
+};
+
+// Rate limiting state (in-memory, use Redis in production)
+const rateLimitStore = new Map();
+
+function checkRateLimit(agentId, maxRequests, windowSeconds) {
+  const now = Date.now();
+  const windowMs = windowSeconds * 1000;
+  const key = `rate:${agentId}`;
+
+  if (!rateLimitStore.has(key)) {
+    rateLimitStore.set(key, []);
+  }
+
+  const requests = rateLimitStore.get(key);
+
+  // Remove expired entries
+  const validRequests = requests.filter(timestamp => now - timestamp <= windowMs);
+
+  if (validRequests.length >= maxRequests) {
+    return false; // Rate limit exceeded
+  }
+
+  // Add current request
+  validRequests.push(now);
+  rateLimitStore.set(key, validRequests);
+
+  return true; // Within rate limit
+}
+
+/**
+ * Requests left in the agent's current rate limit window
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxRequests - Maximum requests allowed
+ * @param {number} windowSeconds - Time window in seconds
+ * @returns {number} Requests remaining
+ */
+function rateLimitRemaining(agentId, maxRequests, windowSeconds) {
+  const windowStart = Date.now() - (windowSeconds * 1000);
+  const timestamps = rateLimitStore.get(`rate:${agentId}`) || [];
+  return Math.max(0, maxRequests - timestamps.filter(ts => ts >= windowStart).length);
+}
+
+// Spending tracking state (in-memory, use database in production)
+const spendingStore = new Map();
+
+// window: { type: 'rolling', seconds } or
+//         { type: 'calendar_day' | 'calendar_month', timeZone }
+function checkSpendingCap(agentId, amount, maxAmount, window) {
+  const now = Date.now();
+  const key = `spending:${agentId}`;
+
+  if (window.type !== 'rolling') {
+    // Calendar windows keep one running total, reset when the bucket changes
+    const bucket = calendarBucket(window, now);
+    const spending = spendingStore.get(key);
+    const total = spending && spending.bucket === bucket ? spending.total : 0;
+
+    if (total + amount > maxAmount) {
+      return false; // Spending cap exceeded
+    }
+
+    spendingStore.set(key, { bucket, total: total + amount });
+    return true; // Within spending cap
+  }
+
+  const windowMs = window.seconds * 1000;
+
+  if (!spendingStore.has(key)) {
+    spendingStore.set(key, []);
+  }
+
+  const payments = spendingStore.get(key);
+
+  // Remove expired entries
+  const validPayments = payments.filter(entry => now - entry.timestamp <= windowMs);
+
+  // Calculate total spending
+  const totalSpent = validPayments.reduce((sum, entry) => sum + entry.amount, 0);
+
+  if (totalSpent + amount > maxAmount) {
+    return false; // Spending cap exceeded
+  }
+
+  // Add current payment
+  validPayments.push({ timestamp: now, amount });
+  spendingStore.set(key, validPayments);
+
+  return true; // Within spending cap
+}
+
+/**
+ * Calendar day or month a timestamp falls in, in the window's timezone
+ * @param {object} window - { type: 'calendar_day' | 'calendar_month', timeZone }
+ * @param {number} timestamp - Milliseconds since the epoch
+ * @returns {string} '2025-03-14' for days, '2025-03' for months
+ */
+function calendarBucket(window, timestamp) {
+  const day = new Intl.DateTimeFormat('en-CA', {
+    timeZone: window.timeZone,
+    year: 'numeric',
+    month: '2-digit',
+    day: '2-digit'
+  }).format(timestamp);
+  return window.type === 'calendar_month' ? day.slice(0, 7) : day;
+}
+
+/**
+ * Amount left under the agent's spending cap in the current window
+ * @param {string} agentId - The agent identifier
+ * @param {number} maxAmount - Maximum spending allowed
+ * @param {object} window - Same window object as the spending cap check
+ * @returns {number} Amount remaining
+ */
+function spendingRemaining(agentId, maxAmount, window) {
+  const now = Date.now();
+  const spending = spendingStore.get(`spending:${agentId}`);
+  let total = 0;
+  if (window.type === 'rolling') {
+    total = (spending || [])
+      .filter(entry => now - entry.timestamp <= window.seconds * 1000)
+      .reduce((sum, entry) => sum + entry.amount, 0);
+  } else if (spending && spending.bucket === calendarBucket(window, now)) {
+    total = spending.total;
+  }
+  return Math.max(0, maxAmount - total);
+}
+
+/**
+ * Decimal string with at most 6 places, e.g. 0.09
+ * @param {number} amount - Amount to format
+ * @returns {string}
+ */
+function formatAmount(amount) {
+  return String(Number(amount.toFixed(6)));
+}
+
+// In-flight request tracking (per process)
+const inFlightStore = new Map();
+
+function acquireSlot(agentId, maxInFlight) {
+  const count = inFlightStore.get(agentId) || 0;
+  if (count >= maxInFlight) {
+    return false; // Too many concurrent requests
+  }
+  inFlightStore.set(agentId, count + 1);
+  return true;
+}
+
+// Release once the raw response finishes or the client disconnects
+function releaseSlotOnFinish(raw, agentId) {
+  let released = false;
+  const release = () => {
+    if (released) return;
+    released = true;
+    const count = inFlightStore.get(agentId) || 0;
+    if (count <= 1) {
+      inFlightStore.delete(agentId);
+    } else {
+      inFlightStore.set(agentId, count - 1);
+    }
+  };
+  raw.on('finish', release);
+  raw.on('close', release);
+}
+
+// Generate x402 invoice for payment
+function generateInvoice(amount, currency, memo, network = 'devnet') {
+  const recipient = process.env.X402_RECIPIENT || 'Dev123456789';
+
+  return `x402-solana recipient=${recipient} amount=${amount} currency=${currency} memo=${memo} network=${network}`;
+}
+
+// Audit logging
+function logAuditEvent(event) {
+  const logEntry = {
+    timestamp: new Date().toISOString(),
+    ...event
+  };
+
+  // Log to console (use proper logging in production: pino, winston, etc.)
+  console.log(JSON.stringify(logEntry));
+}
+
+// Main Fastify plugin function
+async function x402PolicyPlugin(fastify, options) {
+  // Add preHandler hook for policy enforcement
+  fastify.addHook('preHandler', async (request, reply) => {
+    const agentId = request.headers['x-agent-id'];
+    const walletAddress = request.headers['x-wallet-address'];
+    const requestId = request.headers['x-request-id'] || `req_${Date.now()}`;
+
+    logAuditEvent({
+      event: 'payment_request',
+      agent_id: agentId,
+      wallet_address: walletAddress,
+      request_id: requestId,
+      url: request.url,
+      method: request.method
+    });
+
+    // Policy 1: Allowlist { field: "agent_id", values: ["агент-1", "エージェント", "🤖-bot", "o'brien", "back\\slash", "line\nbreak", "para\u{2028}sep", ""] }
+    const allowedValues_0 = ['агент-1', 'エージェント', '🤖-bot', 'o\'brien', 'back\\slash', 'line\nbreak', 'para\u2028sep', ''];
+    if (!allowedValues_0.includes(agentId)) {
+      logAuditEvent({
+        event: 'policy_denied',
+        policy: 'allowlist',
+        field: 'agent_id',
+        value: agentId,
+        reason: 'not in allowlist'
+      });
+      return reply.code(403).send({ error: 'Agent not allowed' });
+    }
+
+    // Policy 2: Denylist { field: "agent_id", values: ["'; process.exit(1); '", "</script>", "${injected}"] }
+    const deniedValues_1 = ['\'; process.exit(1); \'', '</script>', '${injected}'];
+    if (deniedValues_1.includes(agentId)) {
+      logAuditEvent({
+        event: 'policy_denied',
+        policy: 'denylist',
+        field: 'agent_id',
+        value: agentId,
+        reason: 'in denylist'
+      });
+      return reply.code(403).send({ error: 'Agent blocked' });
+    }
+
+    // Policy 3: RateLimit { max_requests: 1, window_seconds: 1s }
+    if (!checkRateLimit(agentId, 1, 1)) {
+      logAuditEvent({
+        event: 'policy_denied',
+        policy: 'rate_limit',
+        agent_id: agentId,
+        reason: 'rate limit exceeded'
+      });
+      return reply.code(429).send({ error: 'Rate limit exceeded' });
+    }
+
+    // Policy 4: RateLimit { max_requests: 4294967295, window_seconds: 1d }
+    if (!checkRateLimit(agentId, 4294967295, 86400)) {
+      logAuditEvent({
+        event: 'policy_denied',
+        policy: 'rate_limit',
+        agent_id: agentId,
+        reason: 'rate limit exceeded'
+      });
+      return reply.code(429).send({ error: 'Rate limit exceeded' });
+    }
+
+    // Policy 5: SpendingCap { max_amount: 1e-6, currency: "US'DC", window_seconds: Some(1m), window_type: Rolling, timezone: None }
+    const paymentAmount_4 = parseFloat(request.headers['x-payment-amount'] || '0.01');
+    if (!checkSpendingCap(agentId, paymentAmount_4, 0.000001, { type: 'rolling', seconds: 60 })) {
+      logAuditEvent({
+        event: 'policy_denied',
+        policy: 'spending_cap',
+        agent_id: agentId,
+        amount: paymentAmount_4,
+        max_amount: 0.000001,
+        currency: 'US\'DC',
+        reason: 'spending cap exceeded'
+      });
+      return reply.code(403).send({ error: 'Spending cap exceeded' });
+    }
+
+    // Policy 6: SpendingCap { max_amount: 1000000.0, currency: "USDC", window_seconds: None, window_type: CalendarMonth, timezone: Some(Asia/Kathmandu) }
+    const paymentAmount_5 = parseFloat(request.headers['x-payment-amount'] || '0.01');
+    if (!checkSpendingCap(agentId, paymentAmount_5, 1000000, { type: 'calendar_month', timeZone: 'Asia/Kathmandu' })) {
+      logAuditEvent({
+        event: 'policy_denied',
+        policy: 'spending_cap',
+        agent_id: agentId,
+        amount: paymentAmount_5,
+        max_amount: 1000000,
+        currency: 'USDC',
+        reason: 'spending cap exceeded'
+      });
+      return reply.code(403).send({ error: 'Spending cap exceeded' });
+    }
+
+    // Policy 7: ConcurrencyLimit { max_in_flight: 1 }
+    if (!acquireSlot(agentId, 1)) {
+      logAuditEvent({
+        event: 'policy_denied',
+        policy: 'concurrency_limit',
+        agent_id: agentId,
+        max_in_flight: 1,
+        reason: 'too many concurrent requests'
+      });
+      return reply.code(429).send({ error: 'Too many concurrent requests' });
+    }
+    releaseSlotOnFinish(reply.raw, agentId);
+
+    // Remaining quota, as the x402-dev mock server reports it
+    reply.header('X-RateLimit-Limit', '1');
+    reply.header('X-RateLimit-Remaining', String(rateLimitRemaining(agentId, 1, 1)));
+    reply.header('X-RateLimit-Reset', '1');
+    reply.header('X-Spend-Cap', formatAmount(0.000001));
+    reply.header('X-Spend-Remaining', formatAmount(spendingRemaining(agentId, 0.000001, { type: 'rolling', seconds: 60 })));
+
+    // All policies passed, generate 402 Payment Required response
+    const amount = parseFloat(request.headers['x-payment-amount'] || '0.01');
+    const currency = request.headers['x-payment-currency'] || 'USDC';
+    const network = request.headers['x-network'] || 'devnet';
+    const memo = requestId;
+
+    const invoice = generateInvoice(amount, currency, memo, network);
+
+    logAuditEvent({
+      event: 'payment_required',
+      agent_id: agentId,
+      request_id: requestId,
+      amount,
+      currency,
+      network,
+      invoice
+    });
+
+    return reply
+      .code(402)
+      .header('WWW-Authenticate', invoice)
+      .send({ error: 'Payment Required', invoice });
+  });
+
+  // Optional: Add a test route
+  fastify.get('/api/test', async (request, reply) => {
+    return { message: 'This route is protected by x402 policies' };
+  });
+}
+
+
+// Export plugin with metadata
+module.exports = fp(x402PolicyPlugin, {
+  fastify: '4.x',
+  name: 'x402-policy-plugin',
+  decorators: {
+    request: [],
+    reply: []
+  }
+});
@@ -0,0 +1,130 @@
+// Codegen Golden Tests
+//
+// Renders every fixture policy in tests/codegen/fixtures/ with each
+// generator and compares the output byte for byte with the checked-in file
+// in tests/codegen/golden/. After an intended change to generated code, run
+// `cargo xtask bless-codegen`, which reruns this test with
+// X402_BLESS_CODEGEN=1 to rewrite the golden files.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use x402_core::policy::{
+    generate_express_middleware, generate_fastify_plugin, generate_test_suite, PolicyFile,
+    QuotaHeaders,
+};
+
+/// Set to rewrite the golden files instead of comparing with them
+const BLESS_ENV: &str = "X402_BLESS_CODEGEN";
+
+/// Generator name, golden file extension and renderer
+type Generator = (&'static str, &'static str, fn(&PolicyFile, &str) -> String);
+
+const GENERATORS: &[Generator] = &[
+    ("express", "js", |policy, name| {
+        generate_express_middleware(policy, name, &QuotaHeaders::default())
+    }),
+    ("fastify", "js", |policy, name| {
+        generate_fastify_plugin(&policy.policies, Some(name), &QuotaHeaders::default())
+    }),
+    ("tests", "yaml", |policy, _| {
+        generate_test_suite(policy).to_yaml().unwrap()
+    }),
+];
+
+fn codegen_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/codegen")
+}
+
+/// Fixture files, sorted by name
+fn fixtures() -> Vec<PathBuf> {
+    let mut fixtures: Vec<PathBuf> = std::fs::read_dir(codegen_dir().join("fixtures"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml"))
+        .collect();
+    fixtures.sort();
+    fixtures
+}
+
+/// First line where `golden` and `generated` differ
+fn first_difference(golden: &str, generated: &str) -> String {
+    let mut golden_lines = golden.lines();
+    let mut generated_lines = generated.lines();
+    for line in 1.. {
+        match (golden_lines.next(), generated_lines.next()) {
+            (Some(a), Some(b)) if a == b => continue,
+            (None, None) => break,
+            (a, b) => {
+                return format!(
+                    "line {}: golden {:?}, generated {:?}",
+                    line,
+                    a.unwrap_or("<end of file>"),
+                    b.unwrap_or("<end of file>")
+                )
+            }
+        }
+    }
+    "line endings differ".to_string()
+}
+
+/// Test generated code matches the golden files for every fixture × generator
+#[test]
+fn test_codegen_matches_golden_files() {
+    let bless = std::env::var_os(BLESS_ENV).is_some();
+    let golden_dir = codegen_dir().join("golden");
+    let mut rendered = BTreeSet::new();
+    let mut mismatches = Vec::new();
+
+    for fixture in fixtures() {
+        let name = fixture.file_name().unwrap().to_str().unwrap();
+        let stem = fixture.file_stem().unwrap().to_str().unwrap();
+        let policy: PolicyFile = serde_yaml::from_str(&std::fs::read_to_string(&fixture).unwrap())
+            .unwrap_or_else(|e| panic!("Invalid fixture {}: {}", name, e));
+
+        for (generator, extension, render) in GENERATORS {
+            let file = format!("{}.{}.{}", stem, generator, extension);
+            let generated = render(&policy, name);
+            assert_eq!(
+                generated,
+                render(&policy, name),
+                "{} output is not deterministic",
+                file
+            );
+
+            let path = golden_dir.join(&file);
+            if bless {
+                std::fs::write(&path, &generated).unwrap();
+            } else {
+                match std::fs::read_to_string(&path) {
+                    Ok(golden) if golden == generated => {}
+                    Ok(golden) => mismatches.push(format!(
+                        "{}: {}",
+                        file,
+                        first_difference(&golden, &generated)
+                    )),
+                    Err(_) => mismatches.push(format!("{}: missing", file)),
+                }
+            }
+            rendered.insert(file);
+        }
+    }
+
+    // Golden files of removed fixtures or generators
+    for entry in std::fs::read_dir(&golden_dir).unwrap() {
+        let path = entry.unwrap().path();
+        let file = path.file_name().unwrap().to_str().unwrap().to_string();
+        if !rendered.contains(&file) {
+            if bless {
+                std::fs::remove_file(&path).unwrap();
+            } else {
+                mismatches.push(format!("{}: no fixture renders it", file));
+            }
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "Generated code differs from the golden files:\n  {}\n\nIf the change is intended, run `cargo xtask bless-codegen` and commit the golden files",
+        mismatches.join("\n  ")
+    );
+}
@@ -0,0 +1,63 @@
+// Compliance Artifact Snapshot Tests
+//
+// Renders the markdown summary and the SVG badge of each fixture report in
+// tests/compliance/ and compares them byte for byte with the checked-in
+// `.md` and `.svg` files next to it. After an intended change to the
+// output, rerun with X402_BLESS_COMPLIANCE=1 to rewrite them.
+
+use std::path::{Path, PathBuf};
+use x402_core::testing::{ComplianceGrade, ComplianceReport};
+
+/// Set to rewrite the snapshots instead of comparing with them
+const BLESS_ENV: &str = "X402_BLESS_COMPLIANCE";
+
+fn fixture(name: &str) -> (ComplianceReport, PathBuf) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/compliance")
+        .join(name);
+    let json = std::fs::read_to_string(path.with_extension("json")).unwrap();
+    (serde_json::from_str(&json).unwrap(), path)
+}
+
+fn assert_snapshot(path: &Path, rendered: &str) {
+    if std::env::var_os(BLESS_ENV).is_some() {
+        std::fs::write(path, rendered).unwrap();
+        return;
+    }
+    let snapshot = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!(
+            "{}: {}\nFix: Rerun with {}=1 to create it",
+            path.display(),
+            e,
+            BLESS_ENV
+        )
+    });
+    assert_eq!(
+        snapshot,
+        rendered,
+        "{} is out of date\nFix: Rerun with {}=1 if the change is intended",
+        path.display(),
+        BLESS_ENV
+    );
+}
+
+/// Test: summary of a report with passes, failures and a warning
+#[test]
+fn test_mixed_report_summary_snapshot() {
+    // Given: A report whose protocol checks pass and invoice checks fail
+    let (report, path) = fixture("mixed");
+    assert_eq!(report.grade(), ComplianceGrade::Partial);
+
+    // When: Rendering the summary
+    let summary = report.render_summary();
+
+    // Then: It matches the checked-in markdown
+    assert_snapshot(&path.with_extension("md"), &summary);
+}
+
+/// Test: badge of the same report
+#[test]
+fn test_mixed_report_badge_snapshot() {
+    let (report, path) = fixture("mixed");
+    assert_snapshot(&path.with_extension("svg"), &report.render_badge());
+}
@@ -1,5 +1,5 @@
 // Concurrency and Thread Safety Tests
-// Phase 2.3: Testing PolicyState under concurrent load
+// Phase 2.3: Testing MemoryStateStore under concurrent load
 //
 // These tests ensure that the policy engine works correctly when
 // handling multiple concurrent requests from a multi-threaded server
@@ -8,12 +8,12 @@ use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, SystemTime};
 use x402_core::policy::runtime_types::{RateLimitConfig, SpendingCapConfig};
-use x402_core::policy::state::PolicyState;
+use x402_core::policy::state::MemoryStateStore;
 
 /// Test: Concurrent rate limit state access
 #[test]
 fn test_concurrent_rate_limit_access() {
-    let state = Arc::new(PolicyState::new());
+    let state = Arc::new(MemoryStateStore::new());
     let num_threads = 10;
     let requests_per_thread = 100;
     let mut handles = vec![];
@@ -47,7 +47,7 @@ fn test_concurrent_rate_limit_access() {
 /// Test: Concurrent spending state access
 #[test]
 fn test_concurrent_spending_state_access() {
-    let state = Arc::new(PolicyState::new());
+    let state = Arc::new(MemoryStateStore::new());
     let num_threads = 8;
     let payments_per_thread = 50;
     let mut handles = vec![];
@@ -81,7 +81,7 @@ fn test_concurrent_spending_state_access() {
 /// Test: Mixed concurrent operations (rate limit + spending)
 #[test]
 fn test_mixed_concurrent_operations() {
-    let state = Arc::new(PolicyState::new());
+    let state = Arc::new(MemoryStateStore::new());
     let num_threads = 12;
     let mut handles = vec![];
 
@@ -128,7 +128,7 @@ fn test_concurrent_rate_limit_checking() {
         window: Duration::from_secs(60),
     };
 
-    let state = Arc::new(PolicyState::new());
+    let state = Arc::new(MemoryStateStore::new());
     let key = "shared-agent";
     let num_threads = 8;
     let mut handles = vec![];
@@ -172,7 +172,7 @@ fn test_concurrent_rate_limit_checking() {
 /// Test: High-frequency concurrent updates (stress test)
 #[test]
 fn test_high_frequency_concurrent_updates() {
-    let state = Arc::new(PolicyState::new());
+    let state = Arc::new(MemoryStateStore::new());
     let num_threads = 16;
     let operations_per_thread = 1000;
     let mut handles = vec![];
@@ -213,7 +213,7 @@ fn test_high_frequency_concurrent_updates() {
 /// Test: Concurrent cleanup of expired entries
 #[test]
 fn test_concurrent_cleanup() {
-    let state = Arc::new(PolicyState::new());
+    let state = Arc::new(MemoryStateStore::new());
     let num_threads = 6;
     let mut handles = vec![];
 
@@ -253,7 +253,7 @@ fn test_concurrent_cleanup() {
 /// Test: Thread-local state isolation
 #[test]
 fn test_thread_local_isolation() {
-    let state = Arc::new(PolicyState::new());
+    let state = Arc::new(MemoryStateStore::new());
     let mut handles = vec![];
 
     // Each thread works with its own agent
@@ -296,10 +296,12 @@ fn test_concurrent_spending_cap_checking() {
     let config = SpendingCapConfig {
         max_amount: 1000.0,
         currency: "USDC".to_string(),
-        window: Duration::from_secs(86400),
+        window: Some(Duration::from_secs(86400)),
+        window_type: Default::default(),
+        timezone: None,
     };
 
-    let state = Arc::new(PolicyState::new());
+    let state = Arc::new(MemoryStateStore::new());
     let key = "spending-agent";
     let num_threads = 10;
     let mut handles = vec![];
@@ -315,8 +317,12 @@ fn test_concurrent_spending_cap_checking() {
                 // Check if cap exceeded
                 let check_state = state_clone.get_spending_state(key);
                 let max_in_cents = (config.max_amount * 100.0) as u64;
-                let _exceeds =
-                    !check_state.check_cap(config.window, max_in_cents, 10, SystemTime::now());
+                let _exceeds = !check_state.check_cap(
+                    config.window.unwrap(),
+                    max_in_cents,
+                    10,
+                    SystemTime::now(),
+                );
             }
         });
         handles.push(handle);
@@ -328,7 +334,7 @@ fn test_concurrent_spending_cap_checking() {
 
     // Verify final spending
     let final_state = state.get_spending_state(key);
-    let total = final_state.total_in_window(config.window, SystemTime::now());
+    let total = final_state.total_in_window(config.window.unwrap(), SystemTime::now());
 
     // Should have accumulated from all threads
     assert!(total > 0);
@@ -338,7 +344,7 @@ fn test_concurrent_spending_cap_checking() {
 /// This test ensures thread-safety through runtime verification
 #[test]
 fn test_no_data_races() {
-    let state = Arc::new(PolicyState::new());
+    let state = Arc::new(MemoryStateStore::new());
     let num_threads = 4;
     let mut handles = vec![];
 
@@ -373,3 +379,61 @@ fn test_no_data_races() {
         handle.join().expect("No data races should occur");
     }
 }
+
+/// Test: Policy hit counters under concurrent evaluation
+#[test]
+fn test_concurrent_match_stats() {
+    use x402_core::policy::{PolicyAction, PolicyEngine, Request, RuntimePolicy};
+
+    let policy = RuntimePolicy {
+        id: "allow-agents".to_string(),
+        description: String::new(),
+        priority: 0,
+        agent_patterns: vec!["agent-*".to_string()],
+        endpoint_patterns: vec![],
+        action: PolicyAction::Allow,
+        rate_limit: None,
+        spending_cap: None,
+        concurrency_limit: None,
+    };
+    let engine = Arc::new(PolicyEngine::new(vec![policy]).with_match_stats());
+    let num_threads = 8;
+    let requests_per_thread = 250;
+
+    let handles: Vec<_> = (0..num_threads)
+        .map(|thread_id| {
+            let engine = Arc::clone(&engine);
+            thread::spawn(move || {
+                // Odd threads hit the default deny
+                let agent_id = if thread_id % 2 == 0 {
+                    "agent-1"
+                } else {
+                    "bot-1"
+                };
+                for _ in 0..requests_per_thread {
+                    let request = Request {
+                        agent_id: agent_id.to_string(),
+                        wallet_address: None,
+                        ip_address: None,
+                        endpoint: "/api/data".to_string(),
+                        amount: 0.01,
+                        timestamp: SystemTime::now(),
+                        memo_id: None,
+                        request_id: None,
+                    };
+                    engine.evaluate(&request).unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().expect("Thread should not panic");
+    }
+
+    // Every evaluation is counted exactly once
+    let hits = engine.match_stats().unwrap().snapshot();
+    assert_eq!(hits[0].hits, (num_threads / 2 * requests_per_thread) as u64);
+    assert_eq!(hits[1].rule, "default");
+    assert_eq!(hits[1].hits, (num_threads / 2 * requests_per_thread) as u64);
+    assert!(hits.iter().all(|rule| rule.last_matched.is_some()));
+}
@@ -258,7 +258,9 @@ mod spending_cap_properties {
             let config = SpendingCapConfig {
                 max_amount: max_amount as f64,
                 currency: "USDC".to_string(),
-                window: Duration::from_secs(86400),
+                window: Some(Duration::from_secs(86400)),
+                window_type: Default::default(),
+                timezone: None,
             };
 
             let mut state = SpendingState::new();
@@ -274,7 +276,7 @@ mod spending_cap_properties {
             }
 
             // Total in state should not exceed what we tracked
-            let state_total = state.total_in_window(config.window, now);
+            let state_total = state.total_in_window(config.window.unwrap(), now);
             assert!(state_total <= max_amount);
         }
 
@@ -287,7 +289,9 @@ mod spending_cap_properties {
             let config = SpendingCapConfig {
                 max_amount: amount as f64,
                 currency: "USDC".to_string(),
-                window: Duration::from_secs(window_secs),
+                window: Some(Duration::from_secs(window_secs)),
+                window_type: Default::default(),
+                timezone: None,
             };
 
             let mut state = SpendingState::new();
@@ -297,13 +301,13 @@ mod spending_cap_properties {
             state.add_spending(now, amount);
 
             // Should be at capacity
-            assert_eq!(state.total_in_window(config.window, now), amount);
+            assert_eq!(state.total_in_window(config.window.unwrap(), now), amount);
 
             // After window expires
             let future = now + Duration::from_secs(window_secs + 1);
 
             // Should be reset
-            assert_eq!(state.total_in_window(config.window, future), 0);
+            assert_eq!(state.total_in_window(config.window.unwrap(), future), 0);
         }
 
         /// Property: Spending accumulates correctly
@@ -349,7 +353,10 @@ mod policy_evaluation_properties {
                 },
             ];
 
-            let config = PolicyConfig { policies };
+            let config = PolicyConfig {
+                policies,
+                allow_custom_currencies: false,
+            };
 
             // When: Creating engine and evaluating
             // Then: Denylist should win (tested via validator conflict detection)
@@ -362,7 +369,10 @@ mod policy_evaluation_properties {
         /// Property: Empty policy list should have deterministic behavior
         #[test]
         fn empty_policies_deterministic(agent_id in "[a-z]{5,10}") {
-            let _config = PolicyConfig { policies: vec![] };
+            let _config = PolicyConfig {
+                policies: vec![],
+                allow_custom_currencies: false,
+            };
 
             // Empty policies should behave consistently
             // (Implementation-dependent: might allow all or deny all)
@@ -455,9 +465,9 @@ mod concurrency_properties {
             num_threads in 2usize..8,
             requests_per_thread in 1usize..10
         ) {
-            use x402_core::policy::state::PolicyState;
+            use x402_core::policy::state::MemoryStateStore;
 
-            let state = Arc::new(PolicyState::new());
+            let state = Arc::new(MemoryStateStore::new());
             let mut handles = vec![];
 
             for _ in 0..num_threads {
@@ -0,0 +1,206 @@
+// Secret Store Tests
+//
+// Uses the encrypted-file backend with an injected passphrase: set, get,
+// list and rm, passphrase checks, and `${secret:name}` interpolated into a
+// request header, checked by wiremock and masked in the results.
+
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+use x402_core::secrets::{SecretBackend, SecretStore};
+use x402_core::testing::{execute_test_suite, TestSuite};
+
+const API_KEY: &str = "fk_live_7Qm2xR9pL4vT";
+
+/// Store in a temp dir whose passphrase can be changed between calls
+fn store(dir: &TempDir) -> (SecretStore, Arc<Mutex<String>>) {
+    let passphrase = Arc::new(Mutex::new("correct horse".to_string()));
+    let provided = Arc::clone(&passphrase);
+    let store = SecretStore::new(dir.path().join("secrets"), move || {
+        Ok(provided.lock().unwrap().clone())
+    });
+    (store, passphrase)
+}
+
+/// Test set, get, list and rm on the encrypted-file backend
+#[test]
+fn test_set_get_list_rm() {
+    let dir = TempDir::new().unwrap();
+    let (store, _) = store(&dir);
+
+    // Given: Two secrets
+    store
+        .set("facilitator_key", API_KEY, SecretBackend::File)
+        .unwrap();
+    store
+        .set("helius.devnet", "3f9a1c22", SecretBackend::File)
+        .unwrap();
+
+    // Then: Values round-trip and the files hold no plaintext
+    assert_eq!(store.get("facilitator_key").unwrap(), API_KEY);
+    let file = std::fs::read_to_string(dir.path().join("secrets/facilitator_key.enc")).unwrap();
+    assert!(!file.contains(API_KEY), "{}", file);
+
+    // And: list shows names only, sorted
+    assert_eq!(
+        store.list().unwrap(),
+        [
+            ("facilitator_key".to_string(), SecretBackend::File),
+            ("helius.devnet".to_string(), SecretBackend::File),
+        ]
+    );
+
+    // And: Setting again replaces the value
+    store
+        .set("facilitator_key", "fk_rotated_0001", SecretBackend::File)
+        .unwrap();
+    assert_eq!(store.get("facilitator_key").unwrap(), "fk_rotated_0001");
+
+    // When: One is removed
+    assert!(store.remove("helius.devnet").unwrap());
+    assert!(!store.remove("helius.devnet").unwrap());
+
+    // Then: Reading it fails with an error naming the secret
+    let err = store.get("helius.devnet").unwrap_err().to_string();
+    assert!(err.contains("Secret 'helius.devnet' not found"), "{}", err);
+    assert!(err.contains("x402-dev secret set helius.devnet"), "{}", err);
+    assert_eq!(store.list().unwrap().len(), 1);
+}
+
+/// Test a wrong passphrase can neither read nor add secrets
+#[test]
+fn test_wrong_passphrase() {
+    let dir = TempDir::new().unwrap();
+    let (store, passphrase) = store(&dir);
+    store
+        .set("facilitator_key", API_KEY, SecretBackend::File)
+        .unwrap();
+
+    *passphrase.lock().unwrap() = "wrong".to_string();
+    let err = store.get("facilitator_key").unwrap_err().to_string();
+    assert!(
+        err.contains("Could not decrypt secret 'facilitator_key'"),
+        "{}",
+        err
+    );
+    let err = store
+        .set("other", "value", SecretBackend::File)
+        .unwrap_err();
+    assert!(err.to_string().contains("does not match"), "{}", err);
+
+    *passphrase.lock().unwrap() = String::new();
+    assert!(store.get("facilitator_key").is_err());
+
+    // Names are still listed without the passphrase
+    assert_eq!(store.list().unwrap().len(), 1);
+}
+
+/// Test invalid names are rejected before touching the directory
+#[test]
+fn test_invalid_name() {
+    let dir = TempDir::new().unwrap();
+    let (store, _) = store(&dir);
+
+    let err = store
+        .set("../escape", "x", SecretBackend::File)
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid secret name '../escape'"));
+    assert!(!dir.path().join("secrets").exists());
+}
+
+/// Test `${secret:name}` reaches the request header and is masked in results
+#[tokio::test]
+async fn test_secret_in_request_header() {
+    // Given: A server that only answers 402 to the right bearer token
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/data"))
+        .and(header(
+            "authorization",
+            format!("Bearer {}", API_KEY).as_str(),
+        ))
+        .respond_with(ResponseTemplate::new(402).insert_header("x-echo", API_KEY))
+        .mount(&server)
+        .await;
+
+    let dir = TempDir::new().unwrap();
+    let (store, _) = store(&dir);
+    store
+        .set("facilitator_key", API_KEY, SecretBackend::File)
+        .unwrap();
+
+    let yaml = format!(
+        r#"
+tests:
+  - name: "authorized"
+    url: "{uri}/api/data"
+    headers:
+      Authorization: "Bearer ${{secret:facilitator_key}}"
+    expect:
+      status: 402
+  - name: "echo mismatch"
+    url: "{uri}/api/data"
+    headers:
+      Authorization: "Bearer ${{secret:facilitator_key}}"
+    expect:
+      status: 402
+      headers:
+        - name: x-echo
+          value: "expected-value"
+  - name: "unreachable"
+    url: "http://127.0.0.1:1/api?key=${{secret:facilitator_key}}"
+    expect:
+      status: 402
+"#,
+        uri = server.uri()
+    );
+    let mut suite: TestSuite = yaml.parse().unwrap();
+
+    // When: Secrets are loaded from the store and the suite runs
+    assert_eq!(
+        suite
+            .secret_names()
+            .unwrap()
+            .into_iter()
+            .collect::<Vec<_>>(),
+        ["facilitator_key"]
+    );
+    suite.load_secrets(|name| store.get(name)).unwrap();
+    let result = execute_test_suite(&suite).await.unwrap();
+
+    // Then: The header matched (any other request would get 404)
+    assert!(result.tests[0].passed, "{:?}", result.tests[0]);
+
+    // And: No result carries the value
+    assert!(!result.tests[1].passed);
+    let debug = format!("{:?}", result);
+    assert!(!debug.contains(API_KEY), "{}", debug);
+    assert_eq!(result.tests[1].assertions[1].actual, "••••");
+    assert!(debug.contains("key=••••"), "{}", debug);
+    assert!(result.tests[2].error.is_some());
+}
+
+/// Test a suite whose secrets were not loaded fails naming the secret
+#[tokio::test]
+async fn test_unloaded_secret_fails() {
+    let suite: TestSuite = r#"
+tests:
+  - name: "needs key"
+    url: "http://127.0.0.1:1/api"
+    headers:
+      Authorization: "Bearer ${secret:facilitator_key}"
+    expect:
+      status: 402
+"#
+    .parse()
+    .unwrap();
+
+    let result = execute_test_suite(&suite).await.unwrap();
+    let error = result.tests[0].error.as_deref().unwrap();
+    assert!(
+        error.contains("Secret 'facilitator_key' was not loaded"),
+        "{}",
+        error
+    );
+}
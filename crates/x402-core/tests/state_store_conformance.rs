@@ -0,0 +1,449 @@
+// Policy State Store Conformance Tests
+//
+// Every PolicyStateStore must behave the same: each test runs against the
+// in-memory store and a SQLite store in a temp dir. The SQLite-only tests
+// reopen the file (restart) and share it between connections (processes).
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tempfile::TempDir;
+use x402_core::policy::{
+    Admission, MemoryStateStore, PolicyStateStore, RateCounter, SpendCounter, SpendingWindow,
+    SqliteStateStore, StateBackend, StoreInstance,
+};
+
+/// Every backend under test; the temp dir holds the SQLite file
+fn backends() -> Vec<(Arc<dyn PolicyStateStore>, Option<TempDir>)> {
+    let dir = TempDir::new().unwrap();
+    let sqlite = SqliteStateStore::open(dir.path().join("state.db")).unwrap();
+    vec![
+        (Arc::new(MemoryStateStore::new()), None),
+        (Arc::new(sqlite), Some(dir)),
+    ]
+}
+
+fn backend(store: &dyn PolicyStateStore) -> StateBackend {
+    store.stats().unwrap().backend
+}
+
+fn at(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1_700_000_000 + seconds)
+}
+
+fn rate(max_requests: u32) -> RateCounter<'static> {
+    RateCounter {
+        key: "rate:metered:agent-1",
+        window: Duration::from_secs(60),
+        max_requests,
+    }
+}
+
+fn spend(window: &SpendingWindow, amount: u64) -> SpendCounter<'_> {
+    SpendCounter {
+        key: "spend:capped:agent-1",
+        window,
+        max_amount: 500,
+        amount,
+        memo_id: None,
+    }
+}
+
+#[test]
+fn test_rate_limit_sliding_window() {
+    for (store, _dir) in backends() {
+        let name = backend(store.as_ref());
+
+        // Given: Three requests admitted under a limit of 3 per minute
+        for second in 0..3 {
+            let admission = store.try_admit(Some(rate(3)), None, at(second)).unwrap();
+            assert_eq!(admission, Admission::Admitted, "{}", name);
+        }
+
+        // Then: The fourth is limited and not recorded
+        let admission = store.try_admit(Some(rate(3)), None, at(10)).unwrap();
+        assert_eq!(admission, Admission::RateLimited, "{}", name);
+        let count = store
+            .rate_count(rate(3).key, rate(3).window, at(10))
+            .unwrap();
+        assert_eq!(count, 3, "{}", name);
+
+        // And: Requests leave the window one by one
+        let count = store
+            .rate_count(rate(3).key, rate(3).window, at(62))
+            .unwrap();
+        assert_eq!(count, 1, "{}", name);
+        let admission = store.try_admit(Some(rate(3)), None, at(62)).unwrap();
+        assert_eq!(admission, Admission::Admitted, "{}", name);
+    }
+}
+
+#[test]
+fn test_rolling_spending_cap() {
+    for (store, _dir) in backends() {
+        let name = backend(store.as_ref());
+        let window = SpendingWindow::Rolling(Duration::from_secs(600));
+
+        let mut first = spend(&window, 300);
+        first.memo_id = Some("550e8400-e29b-41d4-a716-446655440000");
+        assert_eq!(
+            store.try_admit(None, Some(first), at(0)).unwrap(),
+            Admission::Admitted,
+            "{}",
+            name
+        );
+        assert_eq!(
+            store
+                .try_admit(None, Some(spend(&window, 300)), at(1))
+                .unwrap(),
+            Admission::SpendingCapped,
+            "{}",
+            name
+        );
+        assert_eq!(
+            store
+                .try_admit(None, Some(spend(&window, 200)), at(2))
+                .unwrap(),
+            Admission::Admitted,
+            "{}",
+            name
+        );
+        assert_eq!(
+            store.spent(first.key, &window, at(2)).unwrap(),
+            500,
+            "{}",
+            name
+        );
+
+        // Records keep their order, amounts and memo IDs
+        let records = store.spending_records(first.key).unwrap();
+        let recorded: Vec<(SystemTime, u64, Option<&str>)> = records
+            .iter()
+            .map(|r| (r.timestamp, r.amount, r.memo_id.as_deref()))
+            .collect();
+        assert_eq!(
+            recorded,
+            [(at(0), 300, first.memo_id), (at(2), 200, None)],
+            "{}",
+            name
+        );
+
+        // The first spend leaves the window after 10 minutes
+        assert_eq!(
+            store.spent(first.key, &window, at(601)).unwrap(),
+            200,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_denied_admission_records_nothing() {
+    for (store, _dir) in backends() {
+        let name = backend(store.as_ref());
+        let window = SpendingWindow::Rolling(Duration::from_secs(600));
+
+        // A spend over the cap does not use rate limit quota...
+        let admission = store
+            .try_admit(Some(rate(5)), Some(spend(&window, 600)), at(0))
+            .unwrap();
+        assert_eq!(admission, Admission::SpendingCapped, "{}", name);
+        assert_eq!(
+            store
+                .rate_count(rate(5).key, rate(5).window, at(0))
+                .unwrap(),
+            0,
+            "{}",
+            name
+        );
+
+        // ...and a rate limited request is not charged
+        store.try_admit(Some(rate(1)), None, at(0)).unwrap();
+        let admission = store
+            .try_admit(Some(rate(1)), Some(spend(&window, 100)), at(1))
+            .unwrap();
+        assert_eq!(admission, Admission::RateLimited, "{}", name);
+        assert_eq!(
+            store.spent(spend(&window, 0).key, &window, at(1)).unwrap(),
+            0,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_calendar_day_buckets() {
+    let utc = |s: &str| -> SystemTime { chrono::DateTime::parse_from_rfc3339(s).unwrap().into() };
+    for (store, _dir) in backends() {
+        let name = backend(store.as_ref());
+        let window = SpendingWindow::CalendarDay(chrono_tz::Europe::Berlin);
+
+        // 22:30 UTC is 23:30 in Berlin; 23:30 UTC is already the next day
+        let late = utc("2025-03-10T22:30:00Z");
+        let next_day = utc("2025-03-10T23:30:00Z");
+        store
+            .try_admit(None, Some(spend(&window, 400)), late)
+            .unwrap();
+        assert_eq!(
+            store
+                .try_admit(None, Some(spend(&window, 400)), next_day)
+                .unwrap(),
+            Admission::Admitted,
+            "{}",
+            name
+        );
+        assert_eq!(
+            store
+                .try_admit(None, Some(spend(&window, 200)), next_day)
+                .unwrap(),
+            Admission::SpendingCapped,
+            "{}",
+            name
+        );
+
+        // Cleanup keeps the current bucket only
+        store.cleanup(next_day).unwrap();
+        let key = spend(&window, 0).key;
+        assert_eq!(store.spent(key, &window, late).unwrap(), 0, "{}", name);
+        assert_eq!(
+            store.spent(key, &window, next_day).unwrap(),
+            400,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_cleanup_and_stats() {
+    for (store, _dir) in backends() {
+        let name = backend(store.as_ref());
+        let window = SpendingWindow::Rolling(Duration::from_secs(60));
+
+        store.try_admit(Some(rate(10)), None, at(0)).unwrap();
+        store
+            .try_admit(Some(rate(10)), Some(spend(&window, 100)), at(3000))
+            .unwrap();
+        store
+            .try_admit(Some(rate(10)), Some(spend(&window, 100)), at(5000))
+            .unwrap();
+        let stats = store.stats().unwrap();
+        assert_eq!((stats.rate_limit_keys, stats.requests), (1, 3), "{}", name);
+        assert_eq!(
+            (stats.spending_keys, stats.spending_records),
+            (1, 2),
+            "{}",
+            name
+        );
+
+        // An hour is kept; anything older or in the future is dropped
+        store.cleanup(at(3601)).unwrap();
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.requests, 1, "{}", name);
+        assert_eq!(stats.spending_records, 1, "{}", name);
+
+        store.cleanup(at(7300)).unwrap();
+        let stats = store.stats().unwrap();
+        assert_eq!(
+            (
+                stats.rate_limit_keys,
+                stats.requests,
+                stats.spending_records
+            ),
+            (0, 0, 0),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_concurrent_admissions_never_overshoot() {
+    for (store, _dir) in backends() {
+        let name = backend(store.as_ref());
+        let now = SystemTime::now();
+
+        // Given: 8 threads racing for 25 requests and 3000 cents of quota
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let store = Arc::clone(&store);
+                thread::spawn(move || {
+                    let window = SpendingWindow::Rolling(Duration::from_secs(60));
+                    let mut admitted = 0;
+                    for _ in 0..10 {
+                        let rate = RateCounter {
+                            max_requests: 25,
+                            ..rate(0)
+                        };
+                        let spend = SpendCounter {
+                            max_amount: 3000,
+                            ..spend(&window, 100)
+                        };
+                        if store.try_admit(Some(rate), Some(spend), now).unwrap()
+                            == Admission::Admitted
+                        {
+                            admitted += 1;
+                        }
+                    }
+                    admitted
+                })
+            })
+            .collect();
+        let admitted: u32 = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+        // Then: Exactly the rate limit is admitted and charged
+        assert_eq!(admitted, 25, "{}", name);
+        let window = SpendingWindow::Rolling(Duration::from_secs(60));
+        assert_eq!(
+            store.spent(spend(&window, 0).key, &window, now).unwrap(),
+            2500,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_sqlite_state_survives_reopen() {
+    // Given: A SQLite store with a request and a calendar spend recorded
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("nested").join("state.db");
+    let window = SpendingWindow::CalendarMonth(chrono_tz::UTC);
+    {
+        let store = SqliteStateStore::open(&path).unwrap();
+        store
+            .try_admit(Some(rate(2)), Some(spend(&window, 450)), at(0))
+            .unwrap();
+    }
+
+    // When: The file is opened again, as after a restart
+    let store = SqliteStateStore::open(&path).unwrap();
+
+    // Then: Both counters carry over
+    assert_eq!(store.path(), path);
+    assert_eq!(
+        store
+            .rate_count(rate(2).key, rate(2).window, at(1))
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        store
+            .try_admit(None, Some(spend(&window, 100)), at(1))
+            .unwrap(),
+        Admission::SpendingCapped
+    );
+    assert_eq!(
+        store.spent(spend(&window, 0).key, &window, at(1)).unwrap(),
+        450
+    );
+}
+
+#[test]
+fn test_sqlite_connections_share_quota() {
+    // Given: Four connections to one file, like four sidecar processes
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("state.db");
+    let now = SystemTime::now();
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let store = SqliteStateStore::open(&path).unwrap();
+            thread::spawn(move || {
+                (0..10)
+                    .filter(|_| {
+                        store.try_admit(Some(rate(15)), None, now).unwrap() == Admission::Admitted
+                    })
+                    .count()
+            })
+        })
+        .collect();
+    let admitted: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+
+    // Then: The limit holds across connections
+    assert_eq!(admitted, 15);
+}
+
+#[test]
+fn test_sqlite_instances_tag_their_requests() {
+    // Given: Two servers registered with one file
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("state.db");
+    let register = |id: &str, port: u16| {
+        let store = SqliteStateStore::open(&path).unwrap();
+        let instance = StoreInstance {
+            id: id.to_string(),
+            pid: 4242,
+            port: Some(port),
+            started: Some(1_700_000_000),
+        };
+        store.register_instance(&instance).unwrap();
+        store
+    };
+    let first = register("4242-3402", 3402);
+    let second = register("4242-3403", 3403);
+
+    // When: Each admits requests against the same limit
+    first.try_admit(Some(rate(3)), None, at(0)).unwrap();
+    second.try_admit(Some(rate(3)), None, at(1)).unwrap();
+    second.try_admit(Some(rate(3)), None, at(2)).unwrap();
+
+    // Then: Both see both instances, oldest first
+    let ids: Vec<String> = second
+        .instances()
+        .unwrap()
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+    assert_eq!(ids, ["4242-3402", "4242-3403"]);
+    assert_eq!(first.instances().unwrap()[1].port, Some(3403));
+
+    // And: Every request is tagged with the instance that admitted it
+    assert_eq!(
+        first.requests_by_instance().unwrap(),
+        [
+            (Some("4242-3402".to_string()), 1),
+            (Some("4242-3403".to_string()), 2)
+        ]
+    );
+
+    // And: The memory store keeps no registry
+    let memory = MemoryStateStore::new();
+    memory
+        .register_instance(&first.instances().unwrap()[0])
+        .unwrap();
+    assert!(memory.instances().unwrap().is_empty());
+}
+
+#[test]
+fn test_sqlite_file_without_instance_column_is_upgraded() {
+    // Given: A state file written before instance tagging
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("state.db");
+    let old = rusqlite::Connection::open(&path).unwrap();
+    old.execute_batch(
+        "CREATE TABLE rate_requests (key TEXT NOT NULL, timestamp INTEGER NOT NULL);
+         CREATE TABLE spending_records (
+             key TEXT NOT NULL, timestamp INTEGER NOT NULL, amount INTEGER NOT NULL, memo_id TEXT
+         );
+         INSERT INTO rate_requests VALUES ('rate:metered:agent-1', 1700000000000000000);",
+    )
+    .unwrap();
+    drop(old);
+
+    // When: Opening it
+    let store = SqliteStateStore::open(&path).unwrap();
+
+    // Then: Old rows still count, untagged, and new ones can be recorded
+    assert_eq!(store.requests_by_instance().unwrap(), [(None, 1)]);
+    let admission = store.try_admit(Some(rate(3)), None, at(1)).unwrap();
+    assert_eq!(admission, Admission::Admitted);
+    assert_eq!(
+        store
+            .rate_count(rate(3).key, rate(3).window, at(1))
+            .unwrap(),
+        2
+    );
+}
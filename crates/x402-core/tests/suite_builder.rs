@@ -0,0 +1,52 @@
+// Programmatic Test Suite Tests
+//
+// Builds a suite with TestSuite::builder and runs it against wiremock: no
+// YAML and no files, just the values the builder produced.
+
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+use x402_core::testing::{execute_test_suite, TestSuite};
+
+const INVOICE: &str = "x402-solana recipient=GXk8vTest1111111111111111111111111111qPz9 amount=0.01 currency=USDC memo=req-123 network=devnet";
+
+/// Test a two-test suite built in Rust runs and reports each outcome
+#[tokio::test]
+async fn test_built_suite_runs_against_server() {
+    // Given: A paid endpoint and a free one
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api/data"))
+        .respond_with(ResponseTemplate::new(402).insert_header("WWW-Authenticate", INVOICE))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .mount(&server)
+        .await;
+
+    // When: Running a suite whose second test expects the wrong status
+    let suite = TestSuite::builder()
+        .variable("base", server.uri())
+        .test("invoice")
+        .get("${base}/api/data")
+        .expect_status(402)
+        .expect_invoice(|i| i.amount_eq("0.01").currency("USDC").memo_prefix("req-"))
+        .test("health")
+        .get(format!("{}/health", server.uri()))
+        .expect_status(204)
+        .build()
+        .unwrap();
+    let result = execute_test_suite(&suite).await.unwrap();
+
+    // Then: The invoice test passes and the health test fails on its status
+    assert_eq!((result.total, result.passed, result.failed), (2, 1, 1));
+    assert_eq!(result.tests[0].name, "invoice");
+    assert!(result.tests[0].passed);
+    let health = &result.tests[1];
+    assert!(!health.passed);
+    assert!(health
+        .assertions
+        .iter()
+        .any(|a| !a.passed && a.actual == "200"));
+}
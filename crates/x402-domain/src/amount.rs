@@ -1,11 +1,14 @@
 use crate::error::{DomainError, DomainResult};
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display};
 use std::ops::{Add, Sub};
 use std::str::FromStr;
 
+/// Decimal places a USDC amount carries on the wire
+pub const USDC_DECIMALS: u32 = 6;
+
 /// Type-safe amount using Decimal (NOT f64!) to prevent floating-point errors
 ///
 /// # Example
@@ -49,6 +52,171 @@ impl Amount {
         Self::new(decimal)
     }
 
+    /// Parses an amount typed by a user (CLI flag, config file, tool param)
+    ///
+    /// Plain decimals (`0.01`) and unambiguous grouping (`1_000`, `1,000.50`)
+    /// are accepted. Locale formats that put a comma in the decimal position
+    /// (`0,01`, `1.000,50`) are rejected with a corrected suggestion rather
+    /// than silently guessing.
+    ///
+    /// # Example
+    /// ```
+    /// use x402_domain::Amount;
+    ///
+    /// assert_eq!(Amount::parse_user_input("1,000.50").unwrap().to_string(), "1000.50");
+    /// assert!(Amount::parse_user_input("0,01").is_err());
+    /// ```
+    pub fn parse_user_input(input: &str) -> DomainResult<Self> {
+        let s = input.trim();
+        let format_error = |reason: &str, suggestion: String| DomainError::AmountFormat {
+            input: input.to_string(),
+            reason: reason.to_string(),
+            suggestion,
+        };
+
+        // `1_000` → digit grouping with underscores, in the integer part only
+        let s = if s.contains('_') {
+            let (int_part, frac_part) = s.split_at(s.find('.').unwrap_or(s.len()));
+            if !is_thousands_grouped(int_part, '_') || frac_part.contains('_') {
+                return Err(DomainError::InvalidAmount(format!(
+                    "invalid digit grouping in '{}'",
+                    input
+                )));
+            }
+            s.replace('_', "")
+        } else {
+            s.to_string()
+        };
+
+        if !s.contains(',') {
+            return Self::from_decimal_str(&s);
+        }
+
+        let comma = s.rfind(',').unwrap_or(0);
+        match s.rfind('.') {
+            // `1,000.50` → commas group thousands before a decimal point
+            Some(dot) if dot > comma => {
+                let (int_part, frac_part) = s.split_at(dot);
+                if is_thousands_grouped(int_part, ',') {
+                    Self::from_decimal_str(&format!("{}{}", int_part.replace(',', ""), frac_part))
+                } else {
+                    Err(format_error("misplaced ',' separator", s.replace(',', "")))
+                }
+            }
+            // `1.000,50` → European grouping with comma decimal
+            Some(_) => Err(format_error(
+                "',' used as decimal separator and '.' as thousands separator",
+                s.replace('.', "").replace(',', "."),
+            )),
+            // `1,000` → comma is either decimal or grouping; refuse to guess
+            None if s.matches(',').count() == 1 && is_thousands_grouped(&s, ',') => {
+                Err(format_error(
+                    "',' may group thousands or separate decimals",
+                    format!("{} or {}", s.replace(',', ""), s.replace(',', ".")),
+                ))
+            }
+            // `1,000,000` → grouping only
+            None if is_thousands_grouped(&s, ',') => Err(format_error(
+                "',' is not a valid grouping separator",
+                s.replace(',', ""),
+            )),
+            // `0,01` → comma used as decimal separator
+            None => Err(format_error(
+                "',' is not a valid decimal separator",
+                s.replace(',', "."),
+            )),
+        }
+    }
+
+    /// Parses an amount as written in an invoice (`amount=0.01`)
+    ///
+    /// Stricter than [`Amount::from_decimal_str`]: scientific notation, a
+    /// leading `+`, zero, more than [`USDC_DECIMALS`] decimal places and
+    /// values above `max` are each rejected with their own message.
+    /// Redundant zeros (`00.01`, `.5`) are accepted; use
+    /// [`Amount::to_canonical_string`] to report the normalized form.
+    ///
+    /// # Example
+    /// ```
+    /// use x402_domain::Amount;
+    ///
+    /// let amount = Amount::parse_invoice_amount("00.010", None).unwrap();
+    /// assert_eq!(amount.to_canonical_string(), "0.01");
+    /// assert!(Amount::parse_invoice_amount("1e-3", None).is_err());
+    /// ```
+    pub fn parse_invoice_amount(input: &str, max: Option<Amount>) -> DomainResult<Self> {
+        let invalid = |reason: String| Err(DomainError::InvalidAmount(reason));
+        let s = input.trim();
+
+        if s.is_empty() {
+            return invalid("empty value".into());
+        }
+        if let Some(unsigned) = s.strip_prefix('+') {
+            return invalid(format!("leading '+' is not allowed (write {})", unsigned));
+        }
+        if s.starts_with('-') {
+            return invalid("cannot be negative".into());
+        }
+        if s.contains(['e', 'E']) {
+            return match Decimal::from_scientific(s) {
+                Ok(value) => invalid(format!(
+                    "scientific notation is not allowed (write {})",
+                    value.normalize()
+                )),
+                Err(_) => invalid(format!("'{}' is not a decimal number", s)),
+            };
+        }
+
+        let (int_part, frac_part) = s.split_once('.').unwrap_or((s, ""));
+        let digits_only = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if !digits_only(int_part)
+            || !digits_only(frac_part)
+            || (int_part.is_empty() && frac_part.is_empty())
+        {
+            return invalid(format!("'{}' is not a decimal number", s));
+        }
+        if frac_part.len() > USDC_DECIMALS as usize {
+            return invalid(format!(
+                "{} decimal places, USDC allows at most {}",
+                frac_part.len(),
+                USDC_DECIMALS
+            ));
+        }
+
+        let amount = Self::from_decimal_str(s)?;
+        if amount.is_zero() {
+            return invalid("must be greater than zero".into());
+        }
+        if let Some(max) = max.filter(|max| amount > *max) {
+            return invalid(format!(
+                "{} exceeds the maximum of {}",
+                amount.to_canonical_string(),
+                max.to_canonical_string()
+            ));
+        }
+        Ok(amount)
+    }
+
+    /// Rounds an f64 price to [`USDC_DECIMALS`] places
+    ///
+    /// Float artifacts like `0.30000000000000004` become `0.3`.
+    pub fn from_f64_rounded(value: f64) -> DomainResult<Self> {
+        let decimal = Decimal::from_f64(value).ok_or_else(|| {
+            DomainError::InvalidAmount(format!("{} is not a finite number", value))
+        })?;
+        Self::new(decimal.round_dp(USDC_DECIMALS))
+    }
+
+    /// Normalized decimal without leading or trailing zeros (`0.01`, `1.5`, `2`)
+    pub fn to_canonical_string(&self) -> String {
+        self.0.normalize().to_string()
+    }
+
+    /// Returns the amount as f64 for legacy f64-based configs
+    pub fn to_f64(&self) -> f64 {
+        self.0.to_f64().unwrap_or(0.0)
+    }
+
     /// Returns the inner Decimal value
     pub fn as_decimal(&self) -> Decimal {
         self.0
@@ -109,6 +277,237 @@ impl Amount {
             .map(Amount)
             .ok_or_else(|| DomainError::ArithmeticOverflow("division overflow".into()))
     }
+
+    /// Sum of `amounts`, failing on overflow instead of panicking
+    ///
+    /// # Example
+    /// ```
+    /// use x402_domain::Amount;
+    ///
+    /// let charges = [10_000, 10_000, 5_000].map(|l| Amount::from_usdc_lamports(l).unwrap());
+    /// let total = Amount::sum(charges).unwrap();
+    /// assert_eq!(total.to_usdc_lamports(), 25_000);
+    /// assert_eq!(Amount::sum([]).unwrap(), Amount::zero());
+    /// ```
+    pub fn sum(amounts: impl IntoIterator<Item = Amount>) -> DomainResult<Amount> {
+        amounts
+            .into_iter()
+            .try_fold(Amount::zero(), |total, amount| total.checked_add(&amount))
+    }
+
+    /// Smallest of `amounts`, or `None` when empty
+    ///
+    /// For two amounts, `a.min(b)` from [`Ord`] does the same.
+    pub fn min_of(amounts: impl IntoIterator<Item = Amount>) -> Option<Amount> {
+        amounts.into_iter().min()
+    }
+
+    /// Largest of `amounts`, or `None` when empty
+    pub fn max_of(amounts: impl IntoIterator<Item = Amount>) -> Option<Amount> {
+        amounts.into_iter().max()
+    }
+
+    /// Splits the amount in proportion to `percents`, losing nothing to rounding
+    ///
+    /// Parts are whole lamports (or whole units of the amount's own scale
+    /// when it is finer). Each part is rounded down, then the lamports left
+    /// over go one each to the parts with the largest remainders (earlier
+    /// parts win ties), so the parts always add up to the original and no
+    /// part is more than one lamport from its exact share. Only the ratios
+    /// of `percents` matter; they usually add up to 100.
+    ///
+    /// # Example
+    /// ```
+    /// use rust_decimal::Decimal;
+    /// use x402_domain::Amount;
+    ///
+    /// // 97.5% to the merchant, 2.5% to the facilitator
+    /// let price = Amount::from_usdc_lamports(10_001).unwrap();
+    /// let parts = price
+    ///     .split_percent(&[Decimal::new(975, 1), Decimal::new(25, 1)])
+    ///     .unwrap();
+    /// let lamports: Vec<u64> = parts.iter().map(Amount::to_usdc_lamports).collect();
+    /// assert_eq!(lamports, [9_751, 250]); // exact shares 9750.975 and 250.025
+    ///
+    /// // Thirds: the leftover lamport goes to the first part
+    /// let parts = Amount::from_usdc_lamports(100)
+    ///     .unwrap()
+    ///     .split_percent(&[Decimal::ONE; 3])
+    ///     .unwrap();
+    /// let lamports: Vec<u64> = parts.iter().map(Amount::to_usdc_lamports).collect();
+    /// assert_eq!(lamports, [34, 33, 33]);
+    /// ```
+    pub fn split_percent(&self, percents: &[Decimal]) -> DomainResult<Vec<Amount>> {
+        if percents.is_empty() {
+            return Err(DomainError::InvalidAmount(
+                "split needs at least one percentage".into(),
+            ));
+        }
+        if percents.iter().any(Decimal::is_sign_negative) {
+            return Err(DomainError::InvalidAmount(
+                "split percentages cannot be negative".into(),
+            ));
+        }
+
+        // Integers throughout: the amount in its smallest unit, the weights
+        // at their common scale
+        let overflow = || DomainError::ArithmeticOverflow("split overflow".into());
+        let scale = self.0.scale().max(USDC_DECIMALS);
+        let units = rescaled_mantissa(self.0, scale).ok_or_else(overflow)?;
+        let weight_scale = percents.iter().map(Decimal::scale).max().unwrap_or(0);
+        let weights = percents
+            .iter()
+            .map(|percent| rescaled_mantissa(*percent, weight_scale))
+            .collect::<Option<Vec<u128>>>()
+            .ok_or_else(overflow)?;
+        let total_weight = weights
+            .iter()
+            .try_fold(0u128, |total, weight| total.checked_add(*weight))
+            .ok_or_else(overflow)?;
+        if total_weight == 0 {
+            return Err(DomainError::InvalidAmount(
+                "split percentages add up to 0".into(),
+            ));
+        }
+
+        let mut parts = Vec::with_capacity(weights.len());
+        let mut remainders = Vec::with_capacity(weights.len());
+        for weight in &weights {
+            let exact = units.checked_mul(*weight).ok_or_else(overflow)?;
+            parts.push(exact / total_weight);
+            remainders.push(exact % total_weight);
+        }
+
+        // Fewer leftover units than parts, since each part lost less than one
+        let leftover = units - parts.iter().sum::<u128>();
+        let mut order: Vec<usize> = (0..parts.len()).collect();
+        order.sort_by(|a, b| remainders[*b].cmp(&remainders[*a]).then(a.cmp(b)));
+        for index in order.into_iter().take(leftover as usize) {
+            parts[index] += 1;
+        }
+
+        parts
+            .into_iter()
+            .map(|part| {
+                let mantissa = i128::try_from(part).map_err(|_| overflow())?;
+                Decimal::try_from_i128_with_scale(mantissa, scale)
+                    .map(Amount)
+                    .map_err(|_| overflow())
+            })
+            .collect()
+    }
+}
+
+/// Integer mantissa of non-negative `value` at `scale` decimal places
+///
+/// `scale` must be at least the value's own scale.
+fn rescaled_mantissa(value: Decimal, scale: u32) -> Option<u128> {
+    let mantissa = u128::try_from(value.mantissa()).ok()?;
+    10u128
+        .checked_pow(scale - value.scale())
+        .and_then(|factor| mantissa.checked_mul(factor))
+}
+
+/// True if `s` is digits grouped in threes by `sep` (e.g. `1,000,000`)
+fn is_thousands_grouped(s: &str, sep: char) -> bool {
+    let mut groups = s.split(sep);
+    let first_ok = groups
+        .next()
+        .map(|g| (1..=3).contains(&g.len()) && g.chars().all(|c| c.is_ascii_digit()))
+        .unwrap_or(false);
+    first_ok && groups.all(|g| g.len() == 3 && g.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Serde helpers that accept amounts as numbers or user-formatted strings
+///
+/// Strings go through [`Amount::parse_user_input`], so a YAML value like
+/// `"0,01"` fails with a targeted message instead of a generic type error.
+pub mod user_input {
+    use super::Amount;
+    use serde::{de, Deserialize, Deserializer};
+    use std::collections::HashMap;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    pub(super) enum NumberOrString {
+        Number(f64),
+        String(String),
+    }
+
+    impl NumberOrString {
+        fn into_f64<E: de::Error>(self) -> Result<f64, E> {
+            match self {
+                NumberOrString::Number(n) => Ok(n),
+                NumberOrString::String(s) => Amount::parse_user_input(&s)
+                    .map(|a| a.to_f64())
+                    .map_err(E::custom),
+            }
+        }
+
+        pub(super) fn into_amount<E: de::Error>(self) -> Result<Amount, E> {
+            match self {
+                NumberOrString::Number(n) => Amount::from_f64_rounded(n).map_err(E::custom),
+                NumberOrString::String(s) => Amount::parse_user_input(&s).map_err(E::custom),
+            }
+        }
+    }
+
+    /// Deserialize an `f64` amount
+    pub fn deserialize_f64<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+        NumberOrString::deserialize(d)?.into_f64()
+    }
+
+    /// Deserialize an optional `f64` amount
+    pub fn deserialize_opt_f64<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+        Option::<NumberOrString>::deserialize(d)?
+            .map(NumberOrString::into_f64)
+            .transpose()
+    }
+
+    /// Deserialize a map of `f64` amounts (e.g. per-resource pricing)
+    pub fn deserialize_f64_map<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<HashMap<String, f64>, D::Error> {
+        HashMap::<String, NumberOrString>::deserialize(d)?
+            .into_iter()
+            .map(|(k, v)| v.into_f64().map(|v| (k, v)))
+            .collect()
+    }
+}
+
+/// Serde `with` module writing an [`Amount`] as a JSON/YAML number
+///
+/// Keeps wire formats that carried `f64` amounts (`"amount": 0.01`) while
+/// the field itself is an `Amount`. Numbers are rounded to
+/// [`USDC_DECIMALS`] places; strings go through [`Amount::parse_user_input`].
+///
+/// # Example
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use x402_domain::Amount;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Price {
+///     #[serde(with = "x402_domain::amount::as_number")]
+///     amount: Amount,
+/// }
+///
+/// let price: Price = serde_json::from_str(r#"{"amount": 0.01}"#).unwrap();
+/// assert_eq!(price.amount.to_canonical_string(), "0.01");
+/// assert_eq!(serde_json::to_string(&price).unwrap(), r#"{"amount":0.01}"#);
+/// ```
+pub mod as_number {
+    use super::user_input::NumberOrString;
+    use super::Amount;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(amount: &Amount, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_f64(amount.to_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Amount, D::Error> {
+        NumberOrString::deserialize(d)?.into_amount()
+    }
 }
 
 impl Display for Amount {
@@ -252,6 +651,56 @@ mod tests {
         assert!(amount.checked_div(Decimal::ZERO).is_err());
     }
 
+    #[test]
+    fn test_sum_min_max() {
+        let amounts = [3, 1, 2].map(|l| Amount::from_usdc_lamports(l).unwrap());
+        assert_eq!(Amount::sum(amounts).unwrap().to_usdc_lamports(), 6);
+        assert_eq!(Amount::min_of(amounts).unwrap().to_usdc_lamports(), 1);
+        assert_eq!(Amount::max_of(amounts).unwrap().to_usdc_lamports(), 3);
+        assert_eq!(Amount::min_of([]), None);
+
+        let max = Amount::new(Decimal::MAX).unwrap();
+        assert!(matches!(
+            Amount::sum([max, max]),
+            Err(DomainError::ArithmeticOverflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_split_percent() {
+        let amount = Amount::from_decimal_str("1").unwrap();
+        let parts = amount
+            .split_percent(&[Decimal::from(70), Decimal::from(20), Decimal::from(10)])
+            .unwrap();
+        let lamports: Vec<u64> = parts.iter().map(Amount::to_usdc_lamports).collect();
+        assert_eq!(lamports, [700_000, 200_000, 100_000]);
+
+        // Finer than a lamport: split in the amount's own unit
+        let amount = Amount::from_decimal_str("0.0000001").unwrap();
+        let parts = amount.split_percent(&[Decimal::ONE, Decimal::ONE]).unwrap();
+        assert_eq!(Amount::sum(parts.clone()).unwrap(), amount);
+        assert_eq!(parts[0].to_canonical_string(), "0.0000001");
+        assert!(parts[1].is_zero());
+
+        // A zero weight gets nothing
+        let parts = Amount::from_usdc_lamports(5)
+            .unwrap()
+            .split_percent(&[Decimal::ZERO, Decimal::ONE])
+            .unwrap();
+        assert!(parts[0].is_zero());
+        assert_eq!(parts[1].to_usdc_lamports(), 5);
+    }
+
+    #[test]
+    fn test_split_percent_rejections() {
+        let amount = Amount::from_usdc_lamports(100).unwrap();
+        assert!(amount.split_percent(&[]).is_err());
+        assert!(amount.split_percent(&[Decimal::ZERO]).is_err());
+        assert!(amount
+            .split_percent(&[Decimal::from(110), Decimal::from(-10)])
+            .is_err());
+    }
+
     #[test]
     fn test_amount_ordering() {
         let small = Amount::new(Decimal::from(10)).unwrap();
@@ -291,4 +740,161 @@ mod tests {
         let sum = a.checked_add(&b).unwrap();
         assert_eq!(sum, expected);
     }
+
+    #[test]
+    fn test_parse_user_input_plain() {
+        assert_eq!(
+            Amount::parse_user_input("0.01").unwrap(),
+            Amount::from_decimal_str("0.01").unwrap()
+        );
+        assert_eq!(
+            Amount::parse_user_input(" 5 ").unwrap(),
+            Amount::from_decimal_str("5").unwrap()
+        );
+        assert!(Amount::parse_user_input("abc").is_err());
+        assert!(Amount::parse_user_input("-1").is_err());
+    }
+
+    #[test]
+    fn test_parse_user_input_normalizes_unambiguous_grouping() {
+        assert_eq!(
+            Amount::parse_user_input("1_000").unwrap(),
+            Amount::from_decimal_str("1000").unwrap()
+        );
+        assert_eq!(
+            Amount::parse_user_input("1,000.50").unwrap(),
+            Amount::from_decimal_str("1000.50").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_user_input_rejects_comma_decimal() {
+        let err = Amount::parse_user_input("0,01").unwrap_err();
+        assert!(matches!(
+            &err,
+            DomainError::AmountFormat { suggestion, .. } if suggestion == "0.01"
+        ));
+        assert!(err.to_string().contains("did you mean 0.01?"));
+
+        let err = Amount::parse_user_input("1.000,50").unwrap_err();
+        assert!(err.to_string().contains("did you mean 1000.50?"));
+
+        // Grouping or decimal: both readings are offered
+        let err = Amount::parse_user_input("1,000").unwrap_err();
+        assert!(
+            err.to_string().contains("did you mean 1000 or 1.000?"),
+            "{}",
+            err
+        );
+        let err = Amount::parse_user_input("1,000,000").unwrap_err();
+        assert!(err.to_string().contains("did you mean 1000000?"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_user_input_underscores_group_thousands() {
+        assert_eq!(
+            Amount::parse_user_input("12_345_678.5").unwrap(),
+            Amount::from_decimal_str("12345678.5").unwrap()
+        );
+        for input in ["1_0.5_00", "1_0", "1_0000", "_100", "1__000", "1_000.000_1"] {
+            assert!(Amount::parse_user_input(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_invoice_amount_rejections() {
+        let reason = |input: &str, max: Option<Amount>| {
+            Amount::parse_invoice_amount(input, max)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            reason("1e2", None),
+            "Invalid amount: scientific notation is not allowed (write 100)"
+        );
+        assert_eq!(
+            reason("0.0000001", None),
+            "Invalid amount: 7 decimal places, USDC allows at most 6"
+        );
+        assert_eq!(
+            reason("+0.01", None),
+            "Invalid amount: leading '+' is not allowed (write 0.01)"
+        );
+        assert_eq!(
+            reason("0", None),
+            "Invalid amount: must be greater than zero"
+        );
+        assert_eq!(
+            reason("NaN", None),
+            "Invalid amount: 'NaN' is not a decimal number"
+        );
+        assert_eq!(
+            reason(".", None),
+            "Invalid amount: '.' is not a decimal number"
+        );
+        assert_eq!(
+            reason("150", Some(Amount::from_decimal_str("100").unwrap())),
+            "Invalid amount: 150 exceeds the maximum of 100"
+        );
+    }
+
+    #[test]
+    fn test_parse_invoice_amount_normalizes() {
+        let canonical = |input: &str| {
+            Amount::parse_invoice_amount(input, None)
+                .unwrap()
+                .to_canonical_string()
+        };
+
+        assert_eq!(canonical("00.01"), "0.01");
+        assert_eq!(canonical(".5"), "0.5");
+        assert_eq!(canonical("1.500000"), "1.5");
+        assert_eq!(canonical("0.000001"), "0.000001");
+        assert_eq!(
+            Amount::from_f64_rounded(0.1 + 0.2)
+                .unwrap()
+                .to_canonical_string(),
+            "0.3"
+        );
+    }
+
+    #[test]
+    fn test_user_input_deserialize() {
+        #[derive(Debug, Deserialize)]
+        struct Pricing {
+            #[serde(deserialize_with = "user_input::deserialize_f64")]
+            default: f64,
+        }
+
+        let p: Pricing = serde_yaml::from_str("default: 0.02").unwrap();
+        assert_eq!(p.default, 0.02);
+        let p: Pricing = serde_yaml::from_str("default: \"1_000\"").unwrap();
+        assert_eq!(p.default, 1000.0);
+
+        let err = serde_yaml::from_str::<Pricing>("default: \"0,01\"").unwrap_err();
+        assert!(err.to_string().contains("did you mean 0.01?"));
+    }
+
+    #[test]
+    fn test_as_number_round_trip() {
+        #[derive(Debug, Serialize, Deserialize)]
+        struct Invoice {
+            #[serde(with = "as_number")]
+            amount: Amount,
+        }
+
+        let invoice: Invoice = serde_json::from_str(r#"{"amount":0.30000000000000004}"#).unwrap();
+        assert_eq!(invoice.amount, Amount::from_decimal_str("0.3").unwrap());
+        assert_eq!(
+            serde_json::to_string(&invoice).unwrap(),
+            r#"{"amount":0.3}"#
+        );
+
+        let invoice: Invoice = serde_yaml::from_str("amount: \"1,000.50\"").unwrap();
+        assert_eq!(invoice.amount.to_canonical_string(), "1000.5");
+        assert_eq!(serde_yaml::to_string(&invoice).unwrap(), "amount: 1000.5\n");
+
+        assert!(serde_json::from_str::<Invoice>(r#"{"amount":-1}"#).is_err());
+    }
 }
@@ -0,0 +1,130 @@
+//! Registry of the currency symbols policies and prices may name
+//!
+//! Spending caps and invoices are compared by currency symbol, so `usdc`
+//! and `USDC` must be the same currency and a typo such as `USDDC` must not
+//! pass silently. Known symbols are the [`Currency`] variants; they match
+//! case-insensitively and normalize to their canonical spelling. Anything
+//! else is rejected with the closest known symbol as a suggestion, unless
+//! the caller admits custom currencies.
+
+use crate::amount::Currency;
+use crate::error::{DomainError, DomainResult};
+
+/// Largest edit distance at which an unknown symbol gets a suggestion
+pub(crate) const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+impl Currency {
+    /// Every known currency, in the order suggestions prefer them
+    pub const ALL: [Currency; 2] = [Currency::USDC, Currency::SOL];
+}
+
+/// Canonical symbols of the known currencies
+pub fn known_currencies() -> impl Iterator<Item = String> {
+    Currency::ALL.iter().map(Currency::to_string)
+}
+
+/// Canonical spelling of a known `symbol`, or `symbol` itself (trimmed) if it
+/// is not a known currency
+pub fn normalize_currency(symbol: &str) -> String {
+    let symbol = symbol.trim();
+    match symbol.parse::<Currency>() {
+        Ok(currency) => currency.to_string(),
+        Err(_) => symbol.to_string(),
+    }
+}
+
+/// Closest known currency to an unknown `symbol`, if any is close enough
+pub fn suggest_currency(symbol: &str) -> Option<Currency> {
+    let symbol = symbol.trim().to_ascii_uppercase();
+    Currency::ALL
+        .iter()
+        .map(|currency| (edit_distance(&symbol, &currency.to_string()), *currency))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, currency)| currency)
+}
+
+/// Canonical symbol of `symbol`, checked against the registry
+///
+/// Known symbols are normalized to canonical case. Unknown symbols are an
+/// error, or kept as written when `allow_custom` is set.
+pub fn canonical_currency(symbol: &str, allow_custom: bool) -> DomainResult<String> {
+    let trimmed = symbol.trim();
+    if trimmed.is_empty() {
+        return Err(DomainError::UnknownCurrency {
+            symbol: symbol.to_string(),
+            suggestion: None,
+        });
+    }
+    match trimmed.parse::<Currency>() {
+        Ok(currency) => Ok(currency.to_string()),
+        Err(_) if allow_custom => Ok(trimmed.to_string()),
+        Err(_) => Err(DomainError::UnknownCurrency {
+            symbol: symbol.to_string(),
+            suggestion: suggest_currency(trimmed).map(|currency| currency.to_string()),
+        }),
+    }
+}
+
+/// Levenshtein distance between two ASCII-ish strings, by `char`
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_currencies_normalize() {
+        assert_eq!(canonical_currency("usdc", false).unwrap(), "USDC");
+        assert_eq!(canonical_currency(" Sol ", false).unwrap(), "SOL");
+        assert_eq!(normalize_currency("usdc"), "USDC");
+        assert_eq!(normalize_currency("credits"), "credits");
+        assert_eq!(known_currencies().collect::<Vec<_>>(), ["USDC", "SOL"]);
+    }
+
+    #[test]
+    fn test_unknown_currency_suggests_closest() {
+        let err = canonical_currency("USD", false).unwrap_err();
+        assert_eq!(
+            err,
+            DomainError::UnknownCurrency {
+                symbol: "USD".to_string(),
+                suggestion: Some("USDC".to_string()),
+            }
+        );
+        assert!(err.to_string().contains("did you mean USDC?"), "{}", err);
+
+        assert_eq!(suggest_currency("USDDC"), Some(Currency::USDC));
+        assert_eq!(suggest_currency("sool"), Some(Currency::SOL));
+        assert_eq!(suggest_currency("CREDITS"), None);
+        assert!(canonical_currency("", true).is_err());
+    }
+
+    #[test]
+    fn test_custom_currencies_kept_as_written() {
+        assert!(canonical_currency("CREDITS", false).is_err());
+        assert_eq!(canonical_currency("CREDITS", true).unwrap(), "CREDITS");
+        // Known symbols are still normalized
+        assert_eq!(canonical_currency("usdc", true).unwrap(), "USDC");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("USD", "USDC"), 1);
+        assert_eq!(edit_distance("USDDC", "USDC"), 1);
+        assert_eq!(edit_distance("SOL", "USDC"), 3);
+        assert_eq!(edit_distance("", "SOL"), 3);
+    }
+}
@@ -0,0 +1,180 @@
+//! Human-readable amounts
+//!
+//! [`AmountFormatter`] renders amounts for people: trailing zeros trimmed
+//! down to `min_fraction_digits`, rounded past `max_fraction_digits`, with
+//! thousands grouping and a currency suffix (`1,000,000.00 USDC`). It is the
+//! `display:` section of the config. Only the rendered text is rounded; the
+//! value passed in is never changed, and machine output (JSON, reports)
+//! keeps [`Amount::to_canonical_string`](crate::Amount::to_canonical_string).
+
+use crate::amount::USDC_DECIMALS;
+use crate::error::{DomainError, DomainResult};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+
+/// Most fraction digits a `Decimal` can hold
+const MAX_SCALE: u32 = 28;
+
+/// How amounts are shown in human output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AmountFormatter {
+    /// Separate thousands with `,`
+    pub grouping: bool,
+    /// Fraction digits kept even when they are zeros
+    pub min_fraction_digits: u32,
+    /// Fraction digits shown at most; the rest is rounded half away from zero
+    pub max_fraction_digits: u32,
+}
+
+impl Default for AmountFormatter {
+    fn default() -> Self {
+        AmountFormatter {
+            grouping: true,
+            min_fraction_digits: 2,
+            max_fraction_digits: USDC_DECIMALS,
+        }
+    }
+}
+
+impl AmountFormatter {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// At most 28 fraction digits, and no more kept than shown
+    pub fn validate(&self) -> DomainResult<()> {
+        if self.max_fraction_digits > MAX_SCALE {
+            return Err(DomainError::InvalidDisplayFormat(format!(
+                "max_fraction_digits is {}, above the limit of {}",
+                self.max_fraction_digits, MAX_SCALE
+            )));
+        }
+        if self.min_fraction_digits > self.max_fraction_digits {
+            return Err(DomainError::InvalidDisplayFormat(format!(
+                "min_fraction_digits ({}) is above max_fraction_digits ({})",
+                self.min_fraction_digits, self.max_fraction_digits
+            )));
+        }
+        Ok(())
+    }
+
+    /// `value` without a currency, e.g. `1,234,567.89`; negative values
+    /// (deltas) keep their sign
+    pub fn format(&self, value: Decimal) -> String {
+        let mut shown = value
+            .round_dp_with_strategy(
+                self.max_fraction_digits,
+                RoundingStrategy::MidpointAwayFromZero,
+            )
+            .normalize();
+        if shown.scale() < self.min_fraction_digits {
+            shown.rescale(self.min_fraction_digits);
+        }
+        let digits = shown.abs().to_string();
+        let (whole, fraction) = match digits.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (digits.as_str(), None),
+        };
+
+        let mut out = String::with_capacity(digits.len() + whole.len() / 3 + 1);
+        if shown.is_sign_negative() && !shown.is_zero() {
+            out.push('-');
+        }
+        for (i, digit) in whole.chars().enumerate() {
+            if self.grouping && i > 0 && (whole.len() - i) % 3 == 0 {
+                out.push(',');
+            }
+            out.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            out.push('.');
+            out.push_str(fraction);
+        }
+        out
+    }
+
+    /// `value` followed by its currency, e.g. `0.05 USDC`
+    pub fn format_currency(&self, value: Decimal, currency: &str) -> String {
+        format!("{} {}", self.format(value), currency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_default_format() {
+        let f = AmountFormatter::default();
+        assert_eq!(f.format_currency(dec("0.05"), "USDC"), "0.05 USDC");
+        assert_eq!(f.format_currency(dec("0.0500000"), "USDC"), "0.05 USDC");
+        assert_eq!(f.format(dec("1000000")), "1,000,000.00");
+        assert_eq!(f.format(dec("1234567.89")), "1,234,567.89");
+        assert_eq!(f.format(dec("0.000001")), "0.000001");
+        assert_eq!(f.format(dec("0")), "0.00");
+        assert_eq!(f.format(dec("999")), "999.00");
+    }
+
+    #[test]
+    fn test_negative_deltas() {
+        let f = AmountFormatter::default();
+        assert_eq!(f.format_currency(dec("-0.0015"), "USDC"), "-0.0015 USDC");
+        assert_eq!(f.format(dec("-1234.5")), "-1,234.50");
+        // A delta rounded to nothing is shown without a sign
+        assert_eq!(f.format(dec("-0.0000001")), "0.00");
+    }
+
+    #[test]
+    fn test_options() {
+        let f = AmountFormatter {
+            grouping: false,
+            min_fraction_digits: 0,
+            max_fraction_digits: 2,
+        };
+        assert_eq!(f.format(dec("1000000")), "1000000");
+        assert_eq!(f.format(dec("0.005")), "0.01");
+        assert_eq!(f.format(dec("-0.005")), "-0.01");
+        assert_eq!(f.format(dec("0.000001")), "0");
+    }
+
+    #[test]
+    fn test_value_is_not_rounded() {
+        let f = AmountFormatter {
+            max_fraction_digits: 2,
+            ..AmountFormatter::default()
+        };
+        let value = dec("0.123456");
+        assert_eq!(f.format(value), "0.12");
+        assert_eq!(value.to_string(), "0.123456");
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(AmountFormatter::default().validate().is_ok());
+        let inverted = AmountFormatter {
+            min_fraction_digits: 4,
+            max_fraction_digits: 2,
+            ..AmountFormatter::default()
+        };
+        assert!(inverted.validate().is_err());
+        let too_precise = AmountFormatter {
+            max_fraction_digits: 29,
+            ..AmountFormatter::default()
+        };
+        assert!(too_precise.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_section() {
+        let f: AmountFormatter = serde_yaml::from_str("grouping: false").unwrap();
+        assert!(!f.grouping);
+        assert_eq!(f.min_fraction_digits, 2);
+        assert!(serde_yaml::from_str::<AmountFormatter>("groupin: false").is_err());
+    }
+}
@@ -12,20 +12,67 @@ pub enum DomainError {
     #[error("Invalid invoice memo: {0}")]
     InvalidInvoiceMemo(String),
 
+    #[error("Invalid memo prefix: {0}")]
+    InvalidMemoPrefix(String),
+
+    #[error("Invalid ID format: {0}")]
+    InvalidIdFormat(String),
+
+    #[error("Invalid protocol identifier: {0}")]
+    InvalidProtocolId(String),
+
     #[error("Invalid Solana address: {0}")]
     InvalidSolanaAddress(String),
 
     #[error("Invalid amount: {0}")]
     InvalidAmount(String),
 
+    #[error("Invalid amount '{input}': {reason}. Use '.' as the decimal separator without grouping (did you mean {suggestion}?)")]
+    AmountFormat {
+        input: String,
+        reason: String,
+        suggestion: String,
+    },
+
+    #[error("Invalid display format: {0}")]
+    InvalidDisplayFormat(String),
+
     #[error("Invalid port: {0}")]
     InvalidPort(String),
 
+    #[error("Invalid port {port}: ports below 1024 are privileged and need root. Use 1024-65535 (did you mean {suggestion}?)")]
+    PrivilegedPort { port: u16, suggestion: u16 },
+
     #[error("Invalid resource path: {0}")]
     InvalidResourcePath(String),
 
+    #[error("Invalid duration {0}")]
+    InvalidDuration(String),
+
+    #[error("Invalid size {0}")]
+    InvalidByteSize(String),
+
     #[error("Arithmetic overflow: {0}")]
     ArithmeticOverflow(String),
+
+    #[error("Unknown currency '{symbol}'{}", did_you_mean(.suggestion))]
+    UnknownCurrency {
+        symbol: String,
+        suggestion: Option<String>,
+    },
+
+    #[error("Unknown Solana network '{name}'{}. Use devnet, testnet or mainnet-beta", did_you_mean(.suggestion))]
+    UnknownNetwork {
+        name: String,
+        suggestion: Option<String>,
+    },
+}
+
+fn did_you_mean(suggestion: &Option<String>) -> String {
+    match suggestion {
+        Some(suggestion) => format!(" (did you mean {}?)", suggestion),
+        None => String::new(),
+    }
 }
 
 pub type DomainResult<T> = Result<T, DomainError>;
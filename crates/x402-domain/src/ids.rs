@@ -0,0 +1,360 @@
+//! Identifier formats for invoice memos and request IDs (`id_format`)
+//!
+//! `uuidv4` IDs are random; `uuidv7` and `ulid` IDs start with their
+//! creation time in milliseconds, so they sort chronologically as text.
+//! Random bits come from the OS CSPRNG. A seeded [`IdGenerator`] derives
+//! them from its seed instead, which makes IDs reproducible in tests and
+//! unfit for production.
+
+use crate::error::{DomainError, DomainResult};
+use crate::validation::{validate_ulid_format, validate_uuid_format, CROCKFORD_ALPHABET};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Length of a hyphenated UUID
+pub const UUID_LEN: usize = 36;
+
+/// Length of a ULID
+pub const ULID_LEN: usize = 26;
+
+/// How generated IDs are written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum IdFormat {
+    /// Random UUID (RFC 9562 version 4)
+    #[default]
+    Uuidv4,
+    /// Time-ordered UUID (RFC 9562 version 7)
+    Uuidv7,
+    /// Time-ordered ULID in Crockford base32
+    Ulid,
+}
+
+impl IdFormat {
+    pub const ALL: [IdFormat; 3] = [IdFormat::Uuidv4, IdFormat::Uuidv7, IdFormat::Ulid];
+
+    /// Name as written in configs (`uuidv7`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IdFormat::Uuidv4 => "uuidv4",
+            IdFormat::Uuidv7 => "uuidv7",
+            IdFormat::Ulid => "ulid",
+        }
+    }
+
+    /// True for the default `uuidv4`
+    pub fn is_default(&self) -> bool {
+        *self == IdFormat::default()
+    }
+
+    /// True for formats that start with their creation time
+    pub fn is_time_ordered(&self) -> bool {
+        !matches!(self, IdFormat::Uuidv4)
+    }
+
+    /// Characters in an ID of this format
+    pub fn id_len(&self) -> usize {
+        match self {
+            IdFormat::Uuidv4 | IdFormat::Uuidv7 => UUID_LEN,
+            IdFormat::Ulid => ULID_LEN,
+        }
+    }
+
+    /// Format of `id`; UUIDs of versions other than 4 and 7 have none
+    pub fn detect(id: &str) -> Option<Self> {
+        if validate_uuid_format(id).is_ok() {
+            // The version is the first digit of the third group
+            match id.as_bytes()[14] {
+                b'4' => Some(IdFormat::Uuidv4),
+                b'7' => Some(IdFormat::Uuidv7),
+                _ => None,
+            }
+        } else {
+            validate_ulid_format(id).is_ok().then_some(IdFormat::Ulid)
+        }
+    }
+
+    /// Creation time of a time-ordered `id`, in milliseconds since the
+    /// Unix epoch
+    pub fn timestamp_ms(id: &str) -> Option<u64> {
+        match Self::detect(id)? {
+            IdFormat::Uuidv4 => None,
+            IdFormat::Uuidv7 => {
+                let hex = format!("{}{}", &id[..8], &id[9..13]);
+                u64::from_str_radix(&hex, 16).ok()
+            }
+            IdFormat::Ulid => id[..10].bytes().try_fold(0u64, |ms, c| {
+                let digit = CROCKFORD_ALPHABET
+                    .iter()
+                    .position(|a| *a == c.to_ascii_uppercase())?;
+                Some(ms << 5 | digit as u64)
+            }),
+        }
+    }
+}
+
+impl Display for IdFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for IdFormat {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str() == name)
+            .ok_or_else(|| {
+                DomainError::InvalidIdFormat(format!("'{}' is not one of uuidv4, uuidv7, ulid", s))
+            })
+    }
+}
+
+impl TryFrom<String> for IdFormat {
+    type Error = DomainError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<IdFormat> for String {
+    fn from(value: IdFormat) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+/// Generates IDs in one [`IdFormat`]
+///
+/// Time-ordered IDs from one generator strictly increase, also within a
+/// millisecond or when the given time goes backwards.
+pub struct IdGenerator {
+    format: IdFormat,
+    state: Mutex<State>,
+}
+
+struct State {
+    /// None for the OS CSPRNG
+    seeded: Option<SplitMix64>,
+    /// Time and random bits of the last time-ordered ID
+    last: Option<(u64, u128)>,
+}
+
+impl IdGenerator {
+    /// Generator drawing random bits from the OS
+    pub fn new(format: IdFormat) -> Self {
+        Self {
+            format,
+            state: Mutex::new(State {
+                seeded: None,
+                last: None,
+            }),
+        }
+    }
+
+    /// Reproducible IDs from `seed`, for tests only
+    ///
+    /// Available for `uuidv4` and `ulid`; `uuidv7` IDs always use OS
+    /// randomness.
+    pub fn seeded(format: IdFormat, seed: u64) -> DomainResult<Self> {
+        if format == IdFormat::Uuidv7 {
+            return Err(DomainError::InvalidIdFormat(
+                "uuidv7 IDs cannot be seeded; use uuidv4 or ulid for reproducible IDs".into(),
+            ));
+        }
+        Ok(Self {
+            format,
+            state: Mutex::new(State {
+                seeded: Some(SplitMix64(seed)),
+                last: None,
+            }),
+        })
+    }
+
+    pub fn format(&self) -> IdFormat {
+        self.format
+    }
+
+    /// True when IDs come from a seed rather than the OS
+    pub fn is_seeded(&self) -> bool {
+        self.state.lock().unwrap().seeded.is_some()
+    }
+
+    /// Next ID; time-ordered formats record `unix_ms` as its creation time
+    pub fn generate(&self, unix_ms: u64) -> String {
+        let mut state = self.state.lock().unwrap();
+        match self.format {
+            IdFormat::Uuidv4 => {
+                let mut bytes = [0u8; 16];
+                state.fill(&mut bytes);
+                uuid::Builder::from_random_bytes(bytes)
+                    .into_uuid()
+                    .to_string()
+            }
+            IdFormat::Uuidv7 => {
+                let (ms, random) = state.next_ordered(unix_ms, 74);
+                let mut bytes = [0u8; 16];
+                bytes[..6].copy_from_slice(&ms.to_be_bytes()[2..]);
+                // 12 random bits after the version, 62 after the variant
+                let (rand_a, rand_b) = ((random >> 62) as u16, random as u64 & (u64::MAX >> 2));
+                bytes[6] = 0x70 | (rand_a >> 8) as u8;
+                bytes[7] = rand_a as u8;
+                bytes[8..].copy_from_slice(&(rand_b | 1 << 63).to_be_bytes());
+                uuid::Uuid::from_bytes(bytes).to_string()
+            }
+            IdFormat::Ulid => {
+                let (ms, random) = state.next_ordered(unix_ms, 80);
+                let value = u128::from(ms) << 80 | random;
+                (0..ULID_LEN)
+                    .map(|i| {
+                        let digit = (value >> (125 - 5 * i)) & 31;
+                        CROCKFORD_ALPHABET[digit as usize] as char
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl fmt::Debug for IdGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IdGenerator")
+            .field("format", &self.format)
+            .field("seeded", &self.is_seeded())
+            .finish()
+    }
+}
+
+impl State {
+    fn fill(&mut self, bytes: &mut [u8]) {
+        match &mut self.seeded {
+            Some(rng) => {
+                for chunk in bytes.chunks_mut(8) {
+                    chunk.copy_from_slice(&rng.next().to_le_bytes()[..chunk.len()]);
+                }
+            }
+            None => getrandom::fill(bytes).expect("OS random number generator unavailable"),
+        }
+    }
+
+    fn random_bits(&mut self, bits: u32) -> u128 {
+        let mut bytes = [0u8; 16];
+        self.fill(&mut bytes);
+        u128::from_le_bytes(bytes) >> (128 - bits)
+    }
+
+    /// Time and `bits` random bits of the next time-ordered ID: fresh bits
+    /// for a later millisecond, else the last ones plus one
+    fn next_ordered(&mut self, unix_ms: u64, bits: u32) -> (u64, u128) {
+        // Times are 48 bits in both formats
+        let unix_ms = unix_ms.min((1 << 48) - 1);
+        let next = match self.last {
+            Some((ms, random)) if unix_ms <= ms => {
+                if random + 1 < 1 << bits {
+                    (ms, random + 1)
+                } else {
+                    (ms + 1, self.random_bits(bits))
+                }
+            }
+            _ => (unix_ms, self.random_bits(bits)),
+        };
+        self.last = Some(next);
+        next
+    }
+}
+
+/// Seeded generator for reproducible IDs (not cryptographically secure)
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_760_000_000_000;
+
+    #[test]
+    fn test_generated_ids_match_their_format() {
+        for format in IdFormat::ALL {
+            let ids = IdGenerator::new(format);
+            let id = ids.generate(MS);
+            assert_eq!(id.len(), format.id_len(), "{}", id);
+            assert_eq!(IdFormat::detect(&id), Some(format), "{}", id);
+            let expected = format.is_time_ordered().then_some(MS);
+            assert_eq!(IdFormat::timestamp_ms(&id), expected, "{}", id);
+        }
+        let v7 = IdGenerator::new(IdFormat::Uuidv7).generate(MS);
+        assert_eq!(uuid::Uuid::parse_str(&v7).unwrap().get_version_num(), 7);
+        let v4 = IdGenerator::new(IdFormat::Uuidv4).generate(MS);
+        assert_eq!(uuid::Uuid::parse_str(&v4).unwrap().get_version_num(), 4);
+    }
+
+    #[test]
+    fn test_time_ordered_ids_increase() {
+        for format in [IdFormat::Uuidv7, IdFormat::Ulid] {
+            let ids = IdGenerator::new(format);
+            // Same millisecond, then a clock going backwards
+            let generated: Vec<String> = [MS, MS, MS, MS - 5, MS + 1]
+                .into_iter()
+                .map(|ms| ids.generate(ms))
+                .collect();
+            let mut sorted = generated.clone();
+            sorted.sort();
+            assert_eq!(generated, sorted, "{}", format);
+            sorted.dedup();
+            assert_eq!(sorted.len(), generated.len(), "{}", format);
+        }
+    }
+
+    #[test]
+    fn test_seeded_ids_repeat() {
+        for format in [IdFormat::Uuidv4, IdFormat::Ulid] {
+            let first = IdGenerator::seeded(format, 7).unwrap();
+            let second = IdGenerator::seeded(format, 7).unwrap();
+            assert!(first.is_seeded());
+            assert_eq!(first.generate(MS), second.generate(MS));
+            assert_ne!(first.generate(MS), IdGenerator::new(format).generate(MS));
+        }
+        assert!(IdGenerator::seeded(IdFormat::Uuidv7, 7).is_err());
+    }
+
+    #[test]
+    fn test_detect_and_parse() {
+        assert_eq!(
+            IdFormat::detect("550e8400-e29b-41d4-a716-446655440000"),
+            Some(IdFormat::Uuidv4)
+        );
+        // Version 1 UUIDs are valid memos but no generated format
+        assert_eq!(
+            IdFormat::detect("550e8400-e29b-11d4-a716-446655440000"),
+            None
+        );
+        assert_eq!(
+            IdFormat::detect("01arz3ndektsv4rrffq69g5fav"),
+            Some(IdFormat::Ulid)
+        );
+        assert_eq!(
+            IdFormat::timestamp_ms("01ARZ3NDEKTSV4RRFFQ69G5FAV"),
+            Some(1_469_922_850_259)
+        );
+        assert_eq!(IdFormat::detect("81ARZ3NDEKTSV4RRFFQ69G5FAV"), None);
+
+        assert_eq!("ULID".parse::<IdFormat>().unwrap(), IdFormat::Ulid);
+        let err = "uuid".parse::<IdFormat>().unwrap_err();
+        assert!(err.to_string().contains("uuidv4, uuidv7, ulid"));
+    }
+}
@@ -5,10 +5,13 @@
 //! ## Overview
 //!
 //! This crate provides validated newtypes for:
-//! - **Identifiers**: `AgentId`, `PolicyId`, `InvoiceMemo`
-//! - **Addresses**: `SolanaAddress` (Base58 validated)
+//! - **Identifiers**: `AgentId`, `PolicyId`, `InvoiceMemo`, `MemoPrefix`,
+//!   `ProtocolId`, `IdFormat` (`uuidv4`, `uuidv7`, `ulid`)
+//! - **Addresses**: `SolanaAddress` (Base58 validated), `SolanaNetwork`
 //! - **Resources**: `ResourcePath`, `Port`
 //! - **Financial**: `Amount` (uses Decimal, NOT f64!), `Currency`
+//! - **Display**: `AmountFormatter` (`1,000,000.00 USDC` for human output)
+//! - **Units**: `DurationField` (`"30s"`, `"1h"`), `ByteSizeField` (`"10MB"`)
 //!
 //! ## Why This Crate?
 //!
@@ -71,19 +74,43 @@
 //! let amount = Amount::from_usdc_lamports(1_000_000).unwrap();
 //! assert_eq!(amount.to_usdc_lamports(), 1_000_000);
 //! ```
+//!
+//! ### Fee Splits
+//! ```rust
+//! use x402_domain::Amount;
+//! use rust_decimal::Decimal;
+//!
+//! // Parts add up to the price exactly; no lamport is lost to rounding
+//! let price = Amount::from_usdc_lamports(333).unwrap();
+//! let parts = price.split_percent(&[Decimal::new(975, 1), Decimal::new(25, 1)]).unwrap();
+//! assert_eq!(Amount::sum(parts).unwrap(), price);
+//! ```
 
 pub mod amount;
 pub mod conversions;
+pub mod currency;
+pub mod display;
 pub mod error;
+pub mod ids;
+pub mod network;
 pub mod pricing;
 pub mod types;
+pub mod units;
 pub mod validation;
 
 // Re-export main types for convenience
-pub use amount::{Amount, Currency};
+pub use amount::{Amount, Currency, USDC_DECIMALS};
+pub use currency::{canonical_currency, known_currencies, normalize_currency, suggest_currency};
+pub use display::AmountFormatter;
 pub use error::{DomainError, DomainResult};
+pub use ids::{IdFormat, IdGenerator};
+pub use network::SolanaNetwork;
 pub use pricing::PricingConfig;
-pub use types::{AgentId, InvoiceMemo, PolicyId, Port, ResourcePath, SolanaAddress};
+pub use types::{
+    AgentId, InvoiceMemo, MemoPrefix, PolicyId, Port, ProtocolId, ResourcePath, SolanaAddress,
+    DEFAULT_MEMO_PREFIX, DEFAULT_PROTOCOL_ID,
+};
+pub use units::{ByteSizeField, DurationField, LegacyUnit, Millis, Secs};
 
 // Re-export rust_decimal for users
 pub use rust_decimal::Decimal;
@@ -0,0 +1,121 @@
+//! Solana clusters an invoice may name
+//!
+//! Invoices carry `network=devnet` and configs pick an RPC endpoint by
+//! cluster. Names match case-insensitively, `mainnet` is accepted for
+//! `mainnet-beta`, and a typo such as `devnett` is rejected with the closest
+//! cluster as a suggestion.
+
+use crate::currency::{edit_distance, MAX_SUGGESTION_DISTANCE};
+use crate::error::DomainError;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// A Solana cluster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum SolanaNetwork {
+    Devnet,
+    Testnet,
+    MainnetBeta,
+}
+
+impl SolanaNetwork {
+    /// Every cluster, in the order suggestions and prompts list them
+    pub const ALL: [SolanaNetwork; 3] = [
+        SolanaNetwork::Devnet,
+        SolanaNetwork::Testnet,
+        SolanaNetwork::MainnetBeta,
+    ];
+
+    /// Cluster name as written in invoices (`mainnet-beta`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SolanaNetwork::Devnet => "devnet",
+            SolanaNetwork::Testnet => "testnet",
+            SolanaNetwork::MainnetBeta => "mainnet-beta",
+        }
+    }
+
+    /// Public RPC endpoint of the cluster
+    pub fn rpc_url(&self) -> &'static str {
+        match self {
+            SolanaNetwork::Devnet => "https://api.devnet.solana.com",
+            SolanaNetwork::Testnet => "https://api.testnet.solana.com",
+            SolanaNetwork::MainnetBeta => "https://api.mainnet-beta.solana.com",
+        }
+    }
+}
+
+impl Display for SolanaNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SolanaNetwork {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        if name == "mainnet" {
+            return Ok(SolanaNetwork::MainnetBeta);
+        }
+        if let Some(network) = Self::ALL.into_iter().find(|n| n.as_str() == name) {
+            return Ok(network);
+        }
+        Err(DomainError::UnknownNetwork {
+            name: s.to_string(),
+            suggestion: Self::ALL
+                .iter()
+                .map(|n| (edit_distance(&name, n.as_str()), n))
+                .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(_, n)| n.to_string()),
+        })
+    }
+}
+
+impl TryFrom<String> for SolanaNetwork {
+    type Error = DomainError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SolanaNetwork> for String {
+    fn from(value: SolanaNetwork) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_networks_parse_case_insensitively() {
+        assert_eq!("devnet".parse(), Ok(SolanaNetwork::Devnet));
+        assert_eq!(" Testnet ".parse(), Ok(SolanaNetwork::Testnet));
+        assert_eq!("mainnet".parse(), Ok(SolanaNetwork::MainnetBeta));
+        assert_eq!(SolanaNetwork::MainnetBeta.to_string(), "mainnet-beta");
+        for network in SolanaNetwork::ALL {
+            assert_eq!(network.to_string().parse(), Ok(network));
+        }
+    }
+
+    #[test]
+    fn test_unknown_network_suggests_closest() {
+        let err = "devnett".parse::<SolanaNetwork>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown Solana network 'devnett' (did you mean devnet?). Use devnet, testnet or mainnet-beta"
+        );
+        let err = "localhost".parse::<SolanaNetwork>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown Solana network 'localhost'. Use devnet, testnet or mainnet-beta"
+        );
+    }
+}
@@ -318,8 +318,10 @@ mod tests {
 
     #[test]
     fn test_validation_empty_currency() {
-        let mut config = PricingConfig::default();
-        config.currency = String::new();
+        let config = PricingConfig {
+            currency: String::new(),
+            ..PricingConfig::default()
+        };
         assert!(config.validate().is_err());
     }
 
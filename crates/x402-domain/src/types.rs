@@ -1,4 +1,5 @@
 use crate::error::{DomainError, DomainResult};
+use crate::ids::{IdFormat, IdGenerator, ULID_LEN, UUID_LEN};
 use crate::validation::*;
 use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display};
@@ -6,6 +7,7 @@ use std::str::FromStr;
 
 /// Type-safe agent identifier
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct AgentId(String);
 
 impl AgentId {
@@ -34,8 +36,23 @@ impl FromStr for AgentId {
     }
 }
 
+impl TryFrom<String> for AgentId {
+    type Error = DomainError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(s)
+    }
+}
+
+impl From<AgentId> for String {
+    fn from(value: AgentId) -> Self {
+        value.0
+    }
+}
+
 /// Type-safe policy identifier
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct PolicyId(String);
 
 impl PolicyId {
@@ -66,20 +83,237 @@ impl FromStr for PolicyId {
     }
 }
 
+impl TryFrom<String> for PolicyId {
+    type Error = DomainError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(s)
+    }
+}
+
+impl From<PolicyId> for String {
+    fn from(value: PolicyId) -> Self {
+        value.0
+    }
+}
+
+/// Memo prefix used when none is configured
+pub const DEFAULT_MEMO_PREFIX: &str = "req-";
+
+/// Prefix put before the UUID in issued invoice memos
+///
+/// 2-16 characters, alphanumeric or '-', ending in '-' (e.g. `staging-`),
+/// so invoices from different environments can be told apart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MemoPrefix(String);
+
+impl MemoPrefix {
+    pub fn new(s: impl Into<String>) -> DomainResult<Self> {
+        let s = s.into();
+        validate_memo_prefix(&s)?;
+        Ok(Self(s))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// True for the default `req-` prefix
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_MEMO_PREFIX
+    }
+}
+
+impl Default for MemoPrefix {
+    fn default() -> Self {
+        Self(DEFAULT_MEMO_PREFIX.to_string())
+    }
+}
+
+impl Display for MemoPrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for MemoPrefix {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.to_string())
+    }
+}
+
+impl TryFrom<String> for MemoPrefix {
+    type Error = DomainError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(s)
+    }
+}
+
+impl From<MemoPrefix> for String {
+    fn from(value: MemoPrefix) -> Self {
+        value.0
+    }
+}
+
+/// Auth scheme of issued invoices when none is configured
+pub const DEFAULT_PROTOCOL_ID: &str = "x402-solana";
+
+/// Auth scheme that starts `WWW-Authenticate` invoice headers
+///
+/// An RFC 7235 token (e.g. `x402-svm`), so forks of the x402 scheme can be
+/// tested against the same toolkit.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ProtocolId(String);
+
+impl ProtocolId {
+    pub fn new(s: impl Into<String>) -> DomainResult<Self> {
+        let s = s.into();
+        validate_protocol_id(&s)?;
+        Ok(Self(s))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// True for the default `x402-solana` scheme
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_PROTOCOL_ID
+    }
+}
+
+impl Default for ProtocolId {
+    fn default() -> Self {
+        Self(DEFAULT_PROTOCOL_ID.to_string())
+    }
+}
+
+impl Display for ProtocolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for ProtocolId {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s.to_string())
+    }
+}
+
+impl TryFrom<String> for ProtocolId {
+    type Error = DomainError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(s)
+    }
+}
+
+impl From<ProtocolId> for String {
+    fn from(value: ProtocolId) -> Self {
+        value.0
+    }
+}
+
+impl AsRef<str> for ProtocolId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
 /// Type-safe invoice memo with UUID validation
+///
+/// Accepts a bare UUID or ULID, or `<prefix><id>` for any valid
+/// [`MemoPrefix`], including the default `req-`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct InvoiceMemo(String);
 
 impl InvoiceMemo {
     pub fn new(s: impl Into<String>) -> DomainResult<Self> {
         let s = s.into();
-        validate_uuid_format(&s)?;
+        if validate_memo_id(&s).is_err() {
+            // `<prefix><id>`: the ID is always the last 36 (UUID) or 26
+            // (ULID) characters
+            let split_at = |len: usize| {
+                let split = s.len().saturating_sub(len);
+                if s.is_char_boundary(split) {
+                    split
+                } else {
+                    0
+                }
+            };
+            let ulid = validate_uuid_format(&s[split_at(UUID_LEN)..]).is_err()
+                && validate_ulid_format(&s[split_at(ULID_LEN)..]).is_ok();
+            let (prefix, id) = s.split_at(split_at(if ulid { ULID_LEN } else { UUID_LEN }));
+            validate_memo_id(id)?;
+            validate_memo_prefix(prefix)
+                .map_err(|e| DomainError::InvalidInvoiceMemo(e.to_string()))?;
+        }
         Ok(Self(s))
     }
 
+    /// New memo with a random UUID after `prefix`
+    pub fn generate(prefix: &MemoPrefix) -> Self {
+        Self(format!("{}{}", prefix, uuid::Uuid::new_v4()))
+    }
+
+    /// New memo with the next ID of `ids` after `prefix`, created at
+    /// `unix_ms` for time-ordered formats
+    pub fn generate_with(prefix: &MemoPrefix, ids: &IdGenerator, unix_ms: u64) -> Self {
+        Self(format!("{}{}", prefix, ids.generate(unix_ms)))
+    }
+
     pub fn as_str(&self) -> &str {
         &self.0
     }
+
+    /// Prefix before the ID (None for a bare ID)
+    pub fn prefix(&self) -> Option<&str> {
+        let split = self.0.len() - self.id_len();
+        (split > 0).then(|| &self.0[..split])
+    }
+
+    /// The UUID (or ULID) with any prefix stripped, for correlating across
+    /// systems
+    pub fn uuid(&self) -> &str {
+        &self.0[self.0.len() - self.id_len()..]
+    }
+
+    /// Format of the ID, if it is one the server generates
+    pub fn format(&self) -> Option<IdFormat> {
+        IdFormat::detect(self.uuid())
+    }
+
+    /// Creation time of a time-ordered ID, in milliseconds since the Unix
+    /// epoch
+    pub fn timestamp_ms(&self) -> Option<u64> {
+        IdFormat::timestamp_ms(self.uuid())
+    }
+
+    fn id_len(&self) -> usize {
+        let uuid = self
+            .0
+            .len()
+            .checked_sub(UUID_LEN)
+            .is_some_and(|split| validate_uuid_format(&self.0[split..]).is_ok());
+        if uuid {
+            UUID_LEN
+        } else {
+            ULID_LEN
+        }
+    }
+}
+
+/// A bare UUID or ULID, with the UUID error for neither
+fn validate_memo_id(s: &str) -> DomainResult<()> {
+    validate_uuid_format(s).or_else(|e| validate_ulid_format(s).map_err(|_| e))
 }
 
 impl Display for InvoiceMemo {
@@ -96,8 +330,23 @@ impl FromStr for InvoiceMemo {
     }
 }
 
+impl TryFrom<String> for InvoiceMemo {
+    type Error = DomainError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(s)
+    }
+}
+
+impl From<InvoiceMemo> for String {
+    fn from(value: InvoiceMemo) -> Self {
+        value.0
+    }
+}
+
 /// Type-safe Solana address with Base58 validation
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct SolanaAddress(String);
 
 impl SolanaAddress {
@@ -126,8 +375,23 @@ impl FromStr for SolanaAddress {
     }
 }
 
+impl TryFrom<String> for SolanaAddress {
+    type Error = DomainError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(s)
+    }
+}
+
+impl From<SolanaAddress> for String {
+    fn from(value: SolanaAddress) -> Self {
+        value.0
+    }
+}
+
 /// Type-safe HTTP resource path
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub struct ResourcePath(String);
 
 impl ResourcePath {
@@ -156,8 +420,23 @@ impl FromStr for ResourcePath {
     }
 }
 
+impl TryFrom<String> for ResourcePath {
+    type Error = DomainError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(s)
+    }
+}
+
+impl From<ResourcePath> for String {
+    fn from(value: ResourcePath) -> Self {
+        value.0
+    }
+}
+
 /// Type-safe port number with range validation (1024-65535)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
 pub struct Port(u16);
 
 impl Port {
@@ -181,13 +460,33 @@ impl FromStr for Port {
     type Err = DomainError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let port = s
-            .parse::<u16>()
-            .map_err(|_| DomainError::InvalidPort("must be a valid u16".into()))?;
+        let port = s.trim().parse::<u16>().map_err(|_| {
+            DomainError::InvalidPort(format!("'{}' is not a number from 1024 to 65535", s))
+        })?;
         Self::new(port)
     }
 }
 
+impl TryFrom<u16> for Port {
+    type Error = DomainError;
+
+    fn try_from(port: u16) -> Result<Self, Self::Error> {
+        Self::new(port)
+    }
+}
+
+impl From<Port> for u16 {
+    fn from(port: Port) -> Self {
+        port.0
+    }
+}
+
+impl PartialEq<u16> for Port {
+    fn eq(&self, other: &u16) -> bool {
+        self.0 == *other
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,11 +520,83 @@ mod tests {
         let memo = InvoiceMemo::new("550e8400-e29b-41d4-a716-446655440000").unwrap();
         assert_eq!(memo.as_str(), "550e8400-e29b-41d4-a716-446655440000");
 
+        // Mock server memo
+        let memo = InvoiceMemo::new("req-550e8400-e29b-41d4-a716-446655440000").unwrap();
+        assert_eq!(memo.as_str(), "req-550e8400-e29b-41d4-a716-446655440000");
+
+        assert_eq!(memo.prefix(), Some("req-"));
+        assert_eq!(memo.uuid(), "550e8400-e29b-41d4-a716-446655440000");
+
+        // Custom prefix
+        let memo = InvoiceMemo::new("staging-550e8400-e29b-41d4-a716-446655440000").unwrap();
+        assert_eq!(memo.prefix(), Some("staging-"));
+        assert_eq!(memo.uuid(), "550e8400-e29b-41d4-a716-446655440000");
+
+        // Invalid prefix
+        let err = InvoiceMemo::new("bad_prefix-550e8400-e29b-41d4-a716-446655440000").unwrap_err();
+        assert!(err.to_string().contains("Invalid memo prefix"));
+
+        // ULIDs, bare or prefixed
+        let memo = InvoiceMemo::new("01ARZ3NDEKTSV4RRFFQ69G5FAV").unwrap();
+        assert_eq!(memo.prefix(), None);
+        assert_eq!(memo.format(), Some(IdFormat::Ulid));
+        let memo = InvoiceMemo::new("staging-01ARZ3NDEKTSV4RRFFQ69G5FAV").unwrap();
+        assert_eq!(memo.prefix(), Some("staging-"));
+        assert_eq!(memo.uuid(), "01ARZ3NDEKTSV4RRFFQ69G5FAV");
+        assert_eq!(memo.timestamp_ms(), Some(1_469_922_850_259));
+
+        // Every generated format parses back
+        for format in IdFormat::ALL {
+            let ids = IdGenerator::new(format);
+            let memo = InvoiceMemo::generate_with(&MemoPrefix::default(), &ids, 1_000);
+            assert_eq!(InvoiceMemo::new(memo.to_string()).unwrap(), memo);
+            assert_eq!(memo.prefix(), Some("req-"));
+            assert_eq!(memo.format(), Some(format));
+        }
+
         // Invalid UUID
         assert!(InvoiceMemo::new("not-a-uuid").is_err());
+        assert!(InvoiceMemo::new("req-01ARZ3NDEKTSV4RRFFQ69G5FA").is_err());
+        assert!(InvoiceMemo::new("req-").is_err());
         assert!(InvoiceMemo::new("").is_err());
     }
 
+    #[test]
+    fn test_memo_prefix() {
+        assert_eq!(MemoPrefix::default().as_str(), "req-");
+        assert!(MemoPrefix::default().is_default());
+
+        let prefix: MemoPrefix = "perf-".parse().unwrap();
+        let memo = InvoiceMemo::generate(&prefix);
+        assert!(memo.as_str().starts_with("perf-"));
+        assert_eq!(InvoiceMemo::new(memo.to_string()).unwrap(), memo);
+        assert_eq!(memo.prefix(), Some("perf-"));
+
+        for invalid in ["x", "perf", "perf_", "a-very-long-prefix-"] {
+            let err = MemoPrefix::new(invalid).unwrap_err();
+            assert!(
+                matches!(err, DomainError::InvalidMemoPrefix(_)),
+                "{}",
+                invalid
+            );
+        }
+        assert!(serde_json::from_str::<MemoPrefix>(r#""perf""#).is_err());
+    }
+
+    #[test]
+    fn test_protocol_id() {
+        assert_eq!(ProtocolId::default().as_str(), "x402-solana");
+        assert!(ProtocolId::default().is_default());
+
+        let variant: ProtocolId = "x402-svm".parse().unwrap();
+        assert!(!variant.is_default());
+        assert_eq!(variant.to_string(), "x402-svm");
+
+        let err = ProtocolId::new("x402 svm").unwrap_err();
+        assert!(matches!(err, DomainError::InvalidProtocolId(_)));
+        assert!(serde_json::from_str::<ProtocolId>(r#""x402 svm""#).is_err());
+    }
+
     #[test]
     fn test_solana_address() {
         // Valid address
@@ -247,11 +618,18 @@ mod tests {
         let path = ResourcePath::new("/api/v1/agents").unwrap();
         assert_eq!(path.as_str(), "/api/v1/agents");
 
+        // RFC 3986 path characters
+        assert!(ResourcePath::new("/api/items;v=2/caf%C3%A9@edge:1").is_ok());
+
         // Invalid: no leading slash
         assert!(ResourcePath::new("no-slash").is_err());
 
         // Invalid: empty
         assert!(ResourcePath::new("").is_err());
+
+        // Invalid: whitespace and query strings
+        assert!(ResourcePath::new("/api/my data").is_err());
+        assert!(ResourcePath::new("/api/data?x=1").is_err());
     }
 
     #[test]
@@ -262,6 +640,11 @@ mod tests {
         // Invalid: privileged port
         assert!(Port::new(80).is_err());
         assert!(Port::new(1023).is_err());
+        assert_eq!(
+            "443".parse::<Port>().unwrap_err().to_string(),
+            "Invalid port 443: ports below 1024 are privileged and need root. Use 1024-65535 (did you mean 8443?)"
+        );
+        assert!("http".parse::<Port>().is_err());
 
         // Valid: edge cases
         assert!(Port::new(1024).is_ok());
@@ -276,5 +659,20 @@ mod tests {
 
         let deserialized: AgentId = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized, id);
+
+        let port: Port = serde_json::from_str("8080").unwrap();
+        assert_eq!(port, 8080);
+        assert_eq!(serde_json::to_string(&port).unwrap(), "8080");
+    }
+
+    #[test]
+    fn test_deserialization_validates() {
+        let err = serde_json::from_str::<SolanaAddress>(r#""not-base58!""#).unwrap_err();
+        assert!(err.to_string().contains("Invalid Solana address"));
+
+        assert!(serde_json::from_str::<Port>("80").is_err());
+        assert!(serde_json::from_str::<ResourcePath>(r#""no-slash""#).is_err());
+        assert!(serde_json::from_str::<InvoiceMemo>(r#""req-1""#).is_err());
+        assert!(serde_json::from_str::<AgentId>(r#""""#).is_err());
     }
 }
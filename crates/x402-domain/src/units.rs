@@ -0,0 +1,477 @@
+//! Human-friendly durations and byte sizes for config fields
+//!
+//! [`DurationField`] accepts `"1h"`, `"30m"`, `"500ms"` or a bare integer in
+//! the field's legacy unit, so `timeout_delay_ms: 5000` and
+//! `timeout_delay_ms: 5s` mean the same thing. [`ByteSizeField`] accepts
+//! `"10MB"`, `"512KiB"` or a bare number of bytes. Both render back in the
+//! largest unit that represents the value exactly.
+
+use crate::error::{DomainError, DomainResult};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Unit a bare integer is read in, from before fields took unit suffixes
+pub trait LegacyUnit {
+    /// Length of one unit in milliseconds
+    const MILLIS: u64;
+    /// Plural name used in error messages ("seconds")
+    const NAME: &'static str;
+    /// Example bare value used in error messages
+    const EXAMPLE: u64;
+}
+
+/// Bare integers are seconds (`window_seconds: 3600`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Secs;
+
+impl LegacyUnit for Secs {
+    const MILLIS: u64 = 1000;
+    const NAME: &'static str = "seconds";
+    const EXAMPLE: u64 = 3600;
+}
+
+/// Bare integers are milliseconds (`timeout_delay_ms: 5000`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Millis;
+
+impl LegacyUnit for Millis {
+    const MILLIS: u64 = 1;
+    const NAME: &'static str = "milliseconds";
+    const EXAMPLE: u64 = 5000;
+}
+
+/// Duration written as `"1h"`/`"500ms"` or as a bare number of `U`
+///
+/// Serializes to the friendliest exact unit (`5400` seconds is `"90m"`);
+/// Debug prints the same, so rules echoed into generated code stay readable.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DurationField<U = Secs> {
+    duration: Duration,
+    unit: PhantomData<U>,
+}
+
+impl<U: LegacyUnit> DurationField<U> {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            unit: PhantomData,
+        }
+    }
+
+    pub fn from_secs(secs: u64) -> Self {
+        Self::new(Duration::from_secs(secs))
+    }
+
+    pub fn from_millis(millis: u64) -> Self {
+        Self::new(Duration::from_millis(millis))
+    }
+
+    /// Value given as a bare integer in the legacy unit
+    pub fn from_legacy(value: u64) -> Self {
+        Self::from_millis(value.saturating_mul(U::MILLIS))
+    }
+
+    pub fn as_duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn as_secs(&self) -> u64 {
+        self.duration.as_secs()
+    }
+
+    pub fn as_millis(&self) -> u64 {
+        u64::try_from(self.duration.as_millis()).unwrap_or(u64::MAX)
+    }
+
+    /// Value in the legacy unit, rounded down
+    pub fn legacy_value(&self) -> u64 {
+        self.as_millis() / U::MILLIS
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.duration.is_zero()
+    }
+
+    fn invalid(input: &str) -> DomainError {
+        DomainError::InvalidDuration(format!(
+            "'{}'. Use a number of {} (e.g. {}) or a duration with a unit (e.g. \"500ms\", \"30s\", \"5m\", \"1h\", \"1d\")",
+            input,
+            U::NAME,
+            U::EXAMPLE
+        ))
+    }
+}
+
+impl<U: LegacyUnit> From<Duration> for DurationField<U> {
+    fn from(duration: Duration) -> Self {
+        Self::new(duration)
+    }
+}
+
+impl<U> From<DurationField<U>> for Duration {
+    fn from(field: DurationField<U>) -> Self {
+        field.duration
+    }
+}
+
+impl<U: LegacyUnit> FromStr for DurationField<U> {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if !trimmed.is_empty() && trimmed.bytes().all(|b| b.is_ascii_digit()) {
+            return trimmed
+                .parse::<u64>()
+                .map(Self::from_legacy)
+                .map_err(|_| Self::invalid(s));
+        }
+        humantime::parse_duration(trimmed)
+            .map(Self::new)
+            .map_err(|_| Self::invalid(s))
+    }
+}
+
+impl<U> Display for DurationField<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [(u128, &str); 5] = [
+            (86_400_000, "d"),
+            (3_600_000, "h"),
+            (60_000, "m"),
+            (1000, "s"),
+            (1, "ms"),
+        ];
+        if self.duration.is_zero() {
+            return f.write_str("0s");
+        }
+        if self.duration.subsec_nanos().is_multiple_of(1_000_000) {
+            let millis = self.duration.as_millis();
+            if let Some((size, suffix)) =
+                UNITS.iter().find(|(size, _)| millis.is_multiple_of(*size))
+            {
+                return write!(f, "{}{}", millis / size, suffix);
+            }
+        }
+        write!(f, "{}", humantime::format_duration(self.duration))
+    }
+}
+
+impl<U> fmt::Debug for DurationField<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl<U> Serialize for DurationField<U> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, U: LegacyUnit> Deserialize<'de> for DurationField<U> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldVisitor<U>(PhantomData<U>);
+
+        impl<U: LegacyUnit> Visitor<'_> for FieldVisitor<U> {
+            type Value = DurationField<U>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "a number of {} (e.g. {}) or a duration with a unit (e.g. \"30s\", \"1h\")",
+                    U::NAME,
+                    U::EXAMPLE
+                )
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(DurationField::from_legacy(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                u64::try_from(value)
+                    .map(DurationField::from_legacy)
+                    .map_err(|_| E::custom(DurationField::<U>::invalid(&value.to_string())))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(FieldVisitor(PhantomData))
+    }
+}
+
+/// Byte count written as `"10MB"`/`"512KiB"` or as a bare number of bytes
+///
+/// `KB`/`MB`/`GB` are powers of 1000 and `KiB`/`MiB`/`GiB` powers of 1024;
+/// unit letters are case-insensitive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ByteSizeField(u64);
+
+/// Largest first so Display picks the friendliest exact unit
+const BYTE_UNITS: [(u64, &str); 7] = [
+    (1 << 30, "GiB"),
+    (1_000_000_000, "GB"),
+    (1 << 20, "MiB"),
+    (1_000_000, "MB"),
+    (1 << 10, "KiB"),
+    (1000, "KB"),
+    (1, "B"),
+];
+
+impl ByteSizeField {
+    pub fn new(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.0
+    }
+
+    fn invalid(input: &str) -> DomainError {
+        DomainError::InvalidByteSize(format!(
+            "'{}'. Use a number of bytes (e.g. 1048576) or a size with a unit (e.g. \"512KB\", \"10MB\", \"1GiB\")",
+            input
+        ))
+    }
+}
+
+impl From<u64> for ByteSizeField {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<ByteSizeField> for u64 {
+    fn from(size: ByteSizeField) -> Self {
+        size.0
+    }
+}
+
+impl FromStr for ByteSizeField {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> DomainResult<Self> {
+        let trimmed = s.trim();
+        let split = trimmed
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(trimmed.len());
+        let (number, unit) = trimmed.split_at(split);
+        let unit = unit.trim();
+        let multiplier = if unit.is_empty() {
+            1
+        } else {
+            BYTE_UNITS
+                .iter()
+                .find(|(_, name)| name.eq_ignore_ascii_case(unit))
+                .map(|(size, _)| *size)
+                .ok_or_else(|| Self::invalid(s))?
+        };
+        if let Ok(whole) = number.parse::<u64>() {
+            return whole
+                .checked_mul(multiplier)
+                .map(Self)
+                .ok_or_else(|| Self::invalid(s));
+        }
+        // Fractions ("1.5MB") must come out to a whole number of bytes
+        let value: f64 = number.parse().map_err(|_| Self::invalid(s))?;
+        let bytes = value * multiplier as f64;
+        if unit.is_empty() || bytes.fract() != 0.0 || bytes > u64::MAX as f64 {
+            return Err(Self::invalid(s));
+        }
+        Ok(Self(bytes as u64))
+    }
+}
+
+impl Display for ByteSizeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            return f.write_str("0B");
+        }
+        let (size, name) = BYTE_UNITS
+            .iter()
+            .find(|(size, _)| self.0.is_multiple_of(*size))
+            .expect("every count is a whole number of bytes");
+        write!(f, "{}{}", self.0 / size, name)
+    }
+}
+
+impl Serialize for ByteSizeField {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSizeField {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SizeVisitor;
+
+        impl Visitor<'_> for SizeVisitor {
+            type Value = ByteSizeField;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a number of bytes or a size with a unit (e.g. \"10MB\")")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(ByteSizeField(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                u64::try_from(value)
+                    .map(ByteSizeField)
+                    .map_err(|_| E::custom(ByteSizeField::invalid(&value.to_string())))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(SizeVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_parsing_matrix() {
+        let cases: [(&str, u64); 10] = [
+            ("500ms", 500),
+            ("30s", 30_000),
+            ("30m", 1_800_000),
+            ("1h", 3_600_000),
+            ("24h", 86_400_000),
+            ("1d", 86_400_000),
+            ("1h 30m", 5_400_000),
+            ("1m30s", 90_000),
+            ("1.5h", 5_400_000),
+            (" 2min ", 120_000),
+        ];
+        for (input, millis) in cases {
+            let secs: DurationField = input.parse().unwrap();
+            let ms: DurationField<Millis> = input.parse().unwrap();
+            assert_eq!(secs.as_millis(), millis, "{}", input);
+            assert_eq!(ms.as_millis(), millis, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_bare_integers_keep_the_legacy_unit() {
+        let window: DurationField = serde_yaml::from_str("3600").unwrap();
+        assert_eq!(window.as_secs(), 3600);
+        let timeout: DurationField<Millis> = serde_yaml::from_str("5000").unwrap();
+        assert_eq!(timeout.as_millis(), 5000);
+        assert_eq!(timeout.legacy_value(), 5000);
+        // A quoted number is still a bare number
+        let quoted: DurationField = serde_yaml::from_str("'90'").unwrap();
+        assert_eq!(quoted.as_secs(), 90);
+        assert_eq!(
+            "250".parse::<DurationField<Millis>>().unwrap().as_millis(),
+            250
+        );
+    }
+
+    #[test]
+    fn test_invalid_duration_shows_both_forms() {
+        for input in ["", "soon", "-5", "5 parsecs"] {
+            let err = input.parse::<DurationField>().unwrap_err().to_string();
+            assert!(err.contains("number of seconds (e.g. 3600)"), "{}", err);
+            assert!(err.contains("\"1h\""), "{}", err);
+        }
+        let err = serde_yaml::from_str::<DurationField<Millis>>("-1")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("number of milliseconds (e.g. 5000)"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_duration_displays_friendliest_unit() {
+        let cases: [(u64, &str); 7] = [
+            (0, "0s"),
+            (500, "500ms"),
+            (1500, "1500ms"),
+            (30_000, "30s"),
+            (5_400_000, "90m"),
+            (86_400_000, "1d"),
+            (172_800_000 + 3_600_000, "49h"),
+        ];
+        for (millis, shown) in cases {
+            assert_eq!(
+                DurationField::<Secs>::from_millis(millis).to_string(),
+                shown
+            );
+        }
+    }
+
+    #[test]
+    fn test_duration_round_trips_through_yaml() {
+        for millis in [1, 999, 60_000, 3_600_000, 5_400_000, 86_400_000] {
+            let field = DurationField::<Millis>::from_millis(millis);
+            let yaml = serde_yaml::to_string(&field).unwrap();
+            let back: DurationField<Millis> = serde_yaml::from_str(&yaml).unwrap();
+            assert_eq!(back, field, "{}", yaml);
+        }
+        let json = serde_json::to_string(&DurationField::<Secs>::from_secs(86_400)).unwrap();
+        assert_eq!(json, "\"1d\"");
+    }
+
+    #[test]
+    fn test_byte_size_parsing_matrix() {
+        let cases: [(&str, u64); 9] = [
+            ("0", 0),
+            ("1024", 1024),
+            ("512B", 512),
+            ("10KB", 10_000),
+            ("10MB", 10_000_000),
+            ("10mb", 10_000_000),
+            ("512KiB", 524_288),
+            ("1 GiB", 1 << 30),
+            ("1.5MB", 1_500_000),
+        ];
+        for (input, bytes) in cases {
+            assert_eq!(
+                input.parse::<ByteSizeField>().unwrap().bytes(),
+                bytes,
+                "{}",
+                input
+            );
+        }
+        for input in ["", "MB", "10XB", "1.5", "0.3B", "-1KB"] {
+            let err = input.parse::<ByteSizeField>().unwrap_err().to_string();
+            assert!(err.contains("number of bytes"), "{}", err);
+            assert!(err.contains("\"10MB\""), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_byte_size_round_trips_through_yaml() {
+        let cases: [(u64, &str); 5] = [
+            (0, "0B"),
+            (1000, "1KB"),
+            (10_000_000, "10MB"),
+            (1 << 20, "1MiB"),
+            (1_500, "1500B"),
+        ];
+        for (bytes, shown) in cases {
+            let size = ByteSizeField::new(bytes);
+            assert_eq!(size.to_string(), shown);
+            let back: ByteSizeField =
+                serde_yaml::from_str(&serde_yaml::to_string(&size).unwrap()).unwrap();
+            assert_eq!(back, size);
+        }
+        let legacy: ByteSizeField = serde_yaml::from_str("1048576").unwrap();
+        assert_eq!(legacy.to_string(), "1MiB");
+    }
+}
@@ -49,6 +49,85 @@ pub fn validate_uuid_format(s: &str) -> DomainResult<()> {
     Ok(())
 }
 
+/// Digits of Crockford base32, as used by ULIDs
+pub(crate) const CROCKFORD_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Validates ULID format: 26 Crockford base32 characters (either case),
+/// the first at most `7`
+pub fn validate_ulid_format(s: &str) -> DomainResult<()> {
+    if s.len() != 26 {
+        return Err(DomainError::InvalidInvoiceMemo(
+            "ULID must be 26 characters".into(),
+        ));
+    }
+
+    if !s
+        .bytes()
+        .all(|c| CROCKFORD_ALPHABET.contains(&c.to_ascii_uppercase()))
+    {
+        return Err(DomainError::InvalidInvoiceMemo(
+            "ULID must contain only Crockford base32 characters".into(),
+        ));
+    }
+
+    // 26 characters hold 130 bits; the top two must be zero
+    if s.as_bytes()[0] > b'7' {
+        return Err(DomainError::InvalidInvoiceMemo(
+            "ULID timestamp is out of range".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Validates an invoice memo prefix (2-16 characters, alphanumeric or '-', ending in '-')
+pub fn validate_memo_prefix(s: &str) -> DomainResult<()> {
+    if s.len() < 2 || s.len() > 16 {
+        return Err(DomainError::InvalidMemoPrefix(format!(
+            "'{}' must be between 2 and 16 characters",
+            s
+        )));
+    }
+
+    if !s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(DomainError::InvalidMemoPrefix(format!(
+            "'{}' may only contain letters, digits and '-'",
+            s
+        )));
+    }
+
+    if !s.ends_with('-') {
+        return Err(DomainError::InvalidMemoPrefix(format!(
+            "'{}' must end with '-'",
+            s
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validates an auth-scheme protocol identifier: an RFC 7235 token
+/// (letters, digits and ``!#$%&'*+-.^_`|~``)
+pub fn validate_protocol_id(s: &str) -> DomainResult<()> {
+    if s.is_empty() {
+        return Err(DomainError::InvalidProtocolId(
+            "identifier cannot be empty".into(),
+        ));
+    }
+
+    if let Some(c) = s
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !"!#$%&'*+-.^_`|~".contains(*c))
+    {
+        return Err(DomainError::InvalidProtocolId(format!(
+            "'{}' contains {:?}; auth schemes are tokens of letters, digits and !#$%&'*+-.^_`|~",
+            s, c
+        )));
+    }
+
+    Ok(())
+}
+
 /// Validates Base58 Solana address (32-44 characters)
 pub fn validate_solana_address(s: &str) -> DomainResult<()> {
     if s.len() < 32 || s.len() > 44 {
@@ -80,10 +159,12 @@ pub fn validate_resource_path(s: &str) -> DomainResult<()> {
         ));
     }
 
-    // Check for valid URL path characters (including * for wildcards)
-    if !s.chars().all(|c| {
-        c.is_alphanumeric() || c == '/' || c == '-' || c == '_' || c == '.' || c == '~' || c == '*'
-    }) {
+    // Check for valid URL path characters (RFC 3986 pchar, including * for wildcards)
+    const PATH_PUNCTUATION: &str = "/-_.~*!$&'()+,;=:@%";
+    if !s
+        .chars()
+        .all(|c| c.is_alphanumeric() || PATH_PUNCTUATION.contains(c))
+    {
         return Err(DomainError::InvalidResourcePath(
             "contains invalid path characters".into(),
         ));
@@ -95,9 +176,11 @@ pub fn validate_resource_path(s: &str) -> DomainResult<()> {
 /// Validates port range (1024-65535 for non-privileged ports)
 pub fn validate_port(port: u16) -> DomainResult<()> {
     if port < 1024 {
-        Err(DomainError::InvalidPort(
-            "must be >= 1024 (non-privileged port)".into(),
-        ))
+        // 80 → 8080, 443 → 8443
+        Err(DomainError::PrivilegedPort {
+            port,
+            suggestion: port + 8000,
+        })
     } else {
         Ok(())
     }
@@ -113,6 +196,30 @@ mod tests {
         assert!(validate_non_empty("", "field").is_err());
     }
 
+    #[test]
+    fn test_validate_memo_prefix() {
+        assert!(validate_memo_prefix("req-").is_ok());
+        assert!(validate_memo_prefix("a-").is_ok());
+        assert!(validate_memo_prefix("staging-eu-west-").is_ok());
+
+        assert!(validate_memo_prefix("-").is_err()); // Too short
+        assert!(validate_memo_prefix("staging-eu-west-1-").is_err()); // Too long
+        assert!(validate_memo_prefix("req_").is_err());
+        assert!(validate_memo_prefix("prod").is_err());
+    }
+
+    #[test]
+    fn test_validate_protocol_id() {
+        assert!(validate_protocol_id("x402-solana").is_ok());
+        assert!(validate_protocol_id("x402-svm").is_ok());
+        assert!(validate_protocol_id("X402_v2.beta").is_ok());
+
+        assert!(validate_protocol_id("").is_err());
+        assert!(validate_protocol_id("x402 svm").is_err());
+        assert!(validate_protocol_id("x402=svm").is_err());
+        assert!(validate_protocol_id("x402/svm").is_err());
+    }
+
     #[test]
     fn test_validate_uuid_format() {
         // Valid UUID
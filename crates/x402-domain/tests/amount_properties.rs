@@ -0,0 +1,62 @@
+// Property-based tests for Amount allocation
+//
+// For random amounts and weight vectors, `split_percent` must hand out the
+// whole amount: parts are non-negative, add up to the input exactly, and
+// stay within one lamport of their exact share.
+
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+use x402_domain::Amount;
+
+const LAMPORTS_PER_USDC: u64 = 1_000_000;
+
+/// Weights with up to two decimal places, e.g. 97.5 or 2.25
+fn weights() -> impl Strategy<Value = Vec<Decimal>> {
+    prop::collection::vec(0u32..=10_000, 1..8)
+        .prop_filter("weights must not all be zero", |w| w.iter().any(|w| *w > 0))
+        .prop_map(|w| w.into_iter().map(|w| Decimal::new(w as i64, 2)).collect())
+}
+
+proptest! {
+    /// Property: parts are non-negative and add up to the input exactly
+    #[test]
+    fn split_parts_sum_to_input(lamports in 0u64..=u64::MAX / 1_000, weights in weights()) {
+        let amount = Amount::from_usdc_lamports(lamports).unwrap();
+        let parts = amount.split_percent(&weights).unwrap();
+
+        prop_assert_eq!(parts.len(), weights.len());
+        prop_assert!(parts.iter().all(|part| !part.as_decimal().is_sign_negative()));
+        prop_assert_eq!(Amount::sum(parts).unwrap(), amount);
+    }
+
+    /// Property: no part is a lamport or more away from its exact share
+    #[test]
+    fn split_parts_within_one_lamport(lamports in 0u64..=u64::MAX / 1_000, weights in weights()) {
+        let amount = Amount::from_usdc_lamports(lamports).unwrap();
+        let parts = amount.split_percent(&weights).unwrap();
+        let total: Decimal = weights.iter().sum();
+        let lamport = Decimal::new(1, 6);
+
+        for (part, weight) in parts.iter().zip(&weights) {
+            // Exact share in lamports, compared without dividing the amount
+            let exact = Decimal::from(lamports) * weight;
+            let part_lamports = Decimal::from(part.to_usdc_lamports()) * total;
+            prop_assert!(
+                (part_lamports - exact).abs() < total,
+                "part {} for weight {} of {} in {}",
+                part, weight, total, amount
+            );
+            prop_assert_eq!(part.as_decimal() % lamport, Decimal::ZERO);
+        }
+    }
+
+    /// Property: splitting whole USDC by equal weights differs by at most one lamport
+    #[test]
+    fn equal_split_is_fair(usdc in 0u64..1_000_000, n in 1usize..10) {
+        let amount = Amount::from_usdc_lamports(usdc * LAMPORTS_PER_USDC).unwrap();
+        let parts = amount.split_percent(&vec![Decimal::ONE; n]).unwrap();
+        let min = Amount::min_of(parts.iter().copied()).unwrap().to_usdc_lamports();
+        let max = Amount::max_of(parts.iter().copied()).unwrap().to_usdc_lamports();
+        prop_assert!(max - min <= 1);
+    }
+}
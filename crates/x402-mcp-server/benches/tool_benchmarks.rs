@@ -6,7 +6,9 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use serde_json::json;
 use std::time::Duration;
-use x402_core::policy::{IssueType, ResolutionSuggestion, ValidationIssue, ValidationReport};
+use x402_core::policy::{
+    IssueType, ResolutionSuggestion, RuleCode, ValidationIssue, ValidationReport,
+};
 use x402_core::testing::{SuiteResult, TestResult};
 use x402_mcp_server::{
     convert_suite_result, convert_validation_report, MockStartParams, MockStartResponse,
@@ -94,8 +96,10 @@ fn bench_response_serialization(c: &mut Criterion) {
         total: 10,
         passed: 10,
         failed: 0,
+        skipped: 0,
         duration_ms: 1000,
         tests: vec![],
+        spent: Default::default(),
         summary: "All tests passed".to_string(),
     };
     group.bench_function("test_suite_response", |b| {
@@ -116,6 +120,7 @@ fn bench_conversions(c: &mut Criterion) {
         issues: vec![],
         has_errors: false,
         has_warnings: false,
+        skipped: vec![],
     };
     group.bench_function("convert_validation_empty", |b| {
         b.iter(|| {
@@ -127,17 +132,22 @@ fn bench_conversions(c: &mut Criterion) {
     let report_with_issues = ValidationReport {
         issues: vec![
             ValidationIssue {
+                code: RuleCode::InvalidPolicy,
                 issue_type: IssueType::Error,
+                suppressed: false,
                 message: "Error 1".to_string(),
                 details: Some("Details".to_string()),
                 suggestions: vec![ResolutionSuggestion {
                     description: "Fix".to_string(),
                     action: "Do this".to_string(),
+                    catalog: None,
                 }],
                 policy_indices: vec![0],
             },
             ValidationIssue {
+                code: RuleCode::MultipleRateLimits,
                 issue_type: IssueType::Warning,
+                suppressed: false,
                 message: "Warning 1".to_string(),
                 details: None,
                 suggestions: vec![],
@@ -146,6 +156,7 @@ fn bench_conversions(c: &mut Criterion) {
         ],
         has_errors: true,
         has_warnings: true,
+        skipped: vec![],
     };
     group.bench_function("convert_validation_with_issues", |b| {
         b.iter(|| {
@@ -160,7 +171,11 @@ fn bench_conversions(c: &mut Criterion) {
                 total: 5,
                 passed: 5,
                 failed: 0,
+                skipped: 0,
                 duration: Duration::from_millis(500),
+                spent: Default::default(),
+                budget: None,
+                preflight: None,
                 tests: (0..5)
                     .map(|i| TestResult {
                         name: format!("test{}", i),
@@ -170,6 +185,12 @@ fn bench_conversions(c: &mut Criterion) {
                         duration: Duration::from_millis(100),
                         assertions: vec![],
                         error: None,
+                        error_category: None,
+                        skipped: None,
+                        http_version: None,
+                        encoded_size: None,
+                        extends: None,
+                        latency: Vec::new(),
                     })
                     .collect(),
             };
@@ -184,7 +205,11 @@ fn bench_conversions(c: &mut Criterion) {
                 total: 100,
                 passed: 95,
                 failed: 5,
+                skipped: 0,
                 duration: Duration::from_millis(10000),
+                spent: Default::default(),
+                budget: None,
+                preflight: None,
                 tests: (0..100)
                     .map(|i| TestResult {
                         name: format!("test{}", i),
@@ -198,6 +223,12 @@ fn bench_conversions(c: &mut Criterion) {
                         } else {
                             None
                         },
+                        error_category: None,
+                        skipped: None,
+                        http_version: None,
+                        encoded_size: None,
+                        extends: None,
+                        latency: Vec::new(),
                     })
                     .collect(),
             };
@@ -244,6 +275,7 @@ fn bench_end_to_end(c: &mut Criterion) {
                 issues: vec![],
                 has_errors: false,
                 has_warnings: false,
+                skipped: vec![],
             };
             let response = convert_validation_report(report);
 
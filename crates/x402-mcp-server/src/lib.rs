@@ -3,12 +3,15 @@
 // Epic 8: MCP Server Integration for x402-dev
 // Exposes server and tools for testing
 
+pub mod prompts;
+pub mod resources;
 pub mod server;
 pub mod tools;
 pub mod types;
 pub mod utils;
 
 // Re-export main server type for testing
+pub use resources::WorkspaceResources;
 pub use server::X402McpServer;
 
 // Re-export tool types for testing
@@ -18,6 +21,9 @@ pub use tools::{
         convert_validation_report, PolicyGenerateParams, PolicyGenerateResponse, PolicyIssue,
         PolicyValidateParams, PolicyValidateResponse,
     },
+    scenario::{
+        convert_scenario_report, ScenarioIssueItem, ScenarioRunParams, ScenarioRunResponse,
+    },
     testing::{
         convert_suite_result, CheckComplianceParams, ComplianceCheckResponse, TestResultItem,
         TestSuiteParams, TestSuiteResponse,
@@ -12,6 +12,8 @@ use anyhow::Result;
 use rmcp::{transport::stdio, ServiceExt};
 use tracing::info;
 
+mod prompts;
+mod resources;
 mod server;
 mod tools;
 mod types;
@@ -0,0 +1,208 @@
+// MCP prompts for x402-mcp-server
+//
+// Guided workflows that chain the server's tools:
+// - x402_setup_local_testing: start mock, validate policy, basic compliance
+// - x402_diagnose_endpoint: strict check, decode the challenge, suggest fixes
+// - x402_generate_middleware: validate, generate, drift-check
+//
+// Prompt bodies are markdown files next to this module; `{{name}}`
+// placeholders are replaced with the prompt's arguments.
+
+use rmcp::model::{
+    GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole,
+};
+use rmcp::ErrorData as McpError;
+
+/// Prefix shared by every tool name the prompts may reference
+#[allow(dead_code)]
+pub const TOOL_PREFIX: &str = "x402__";
+
+/// Kind of value an argument accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArgKind {
+    Text,
+    /// TCP port, substituted unquoted into JSON templates
+    Port,
+}
+
+/// One argument of a prompt
+struct ArgSpec {
+    name: &'static str,
+    description: &'static str,
+    /// Value used when the client omits the argument; `None` makes it required
+    default: Option<&'static str>,
+    kind: ArgKind,
+}
+
+/// A prompt and its markdown template
+struct PromptSpec {
+    name: &'static str,
+    title: &'static str,
+    description: &'static str,
+    arguments: &'static [ArgSpec],
+    template: &'static str,
+}
+
+const PORT_ARG: ArgSpec = ArgSpec {
+    name: "port",
+    description: "Mock server port (1024-65535, default 3402)",
+    default: Some("3402"),
+    kind: ArgKind::Port,
+};
+
+const PROMPTS: &[PromptSpec] = &[
+    PromptSpec {
+        name: "x402_setup_local_testing",
+        title: "Set up local x402 testing",
+        description: "Start the mock server, validate a policy and run a basic compliance check",
+        arguments: &[
+            PORT_ARG,
+            ArgSpec {
+                name: "policy_file",
+                description: "Policy YAML to validate (default policy.yaml)",
+                default: Some("policy.yaml"),
+                kind: ArgKind::Text,
+            },
+        ],
+        template: include_str!("setup_local_testing.md"),
+    },
+    PromptSpec {
+        name: "x402_diagnose_endpoint",
+        title: "Diagnose a failing 402 endpoint",
+        description:
+            "Check an endpoint in strict mode, decode its payment challenge and suggest fixes",
+        arguments: &[ArgSpec {
+            name: "url",
+            description: "URL of the endpoint that should answer 402 Payment Required",
+            default: None,
+            kind: ArgKind::Text,
+        }],
+        template: include_str!("diagnose_endpoint.md"),
+    },
+    PromptSpec {
+        name: "x402_generate_middleware",
+        title: "Generate and verify middleware",
+        description:
+            "Validate a policy, generate Express middleware and check the output for drift",
+        arguments: &[
+            ArgSpec {
+                name: "policy_file",
+                description: "Policy YAML to generate from",
+                default: None,
+                kind: ArgKind::Text,
+            },
+            ArgSpec {
+                name: "output",
+                description: "Path of the generated middleware (default middleware.js)",
+                default: Some("middleware.js"),
+                kind: ArgKind::Text,
+            },
+        ],
+        template: include_str!("generate_middleware.md"),
+    },
+];
+
+/// All prompts, as announced by `prompts/list`
+pub fn list() -> Vec<Prompt> {
+    PROMPTS
+        .iter()
+        .map(|spec| {
+            let arguments = spec
+                .arguments
+                .iter()
+                .map(|arg| PromptArgument {
+                    name: arg.name.to_string(),
+                    title: None,
+                    description: Some(arg.description.to_string()),
+                    required: Some(arg.default.is_none()),
+                })
+                .collect();
+            Prompt {
+                title: Some(spec.title.to_string()),
+                ..Prompt::new(spec.name, Some(spec.description), Some(arguments))
+            }
+        })
+        .collect()
+}
+
+/// Render prompt `name` with `arguments`, as answered to `prompts/get`
+pub fn get(name: &str, arguments: Option<&JsonObject>) -> Result<GetPromptResult, McpError> {
+    let spec = PROMPTS
+        .iter()
+        .find(|spec| spec.name == name)
+        .ok_or_else(|| McpError::invalid_params(format!("Unknown prompt: {}", name), None))?;
+
+    let mut text = spec.template.to_string();
+    for arg in spec.arguments {
+        let value = argument_value(arg, arguments)?;
+        text = text.replace(&format!("{{{{{}}}}}", arg.name), &value);
+    }
+
+    Ok(GetPromptResult {
+        description: Some(spec.description.to_string()),
+        messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+    })
+}
+
+/// Tool names (`x402__...`) referenced by the body of prompt `name`
+///
+/// Library API for tests checking prompts against the tool router
+#[allow(dead_code)]
+pub fn referenced_tools(name: &str) -> Vec<String> {
+    let Some(spec) = PROMPTS.iter().find(|spec| spec.name == name) else {
+        return Vec::new();
+    };
+    let mut tools: Vec<String> = spec
+        .template
+        .match_indices(TOOL_PREFIX)
+        .map(|(start, _)| {
+            spec.template[start..]
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect()
+        })
+        .collect();
+    tools.sort();
+    tools.dedup();
+    tools
+}
+
+/// Value of `arg`, escaped for use inside the JSON argument templates
+fn argument_value(arg: &ArgSpec, arguments: Option<&JsonObject>) -> Result<String, McpError> {
+    let value = match arguments.and_then(|args| args.get(arg.name)) {
+        Some(serde_json::Value::String(s)) if !s.trim().is_empty() => s.trim().to_string(),
+        Some(serde_json::Value::Number(n)) => n.to_string(),
+        Some(serde_json::Value::Null) | Some(serde_json::Value::String(_)) | None => {
+            match arg.default {
+                Some(default) => default.to_string(),
+                None => {
+                    return Err(McpError::invalid_params(
+                        format!("Missing required argument: {}", arg.name),
+                        None,
+                    ))
+                }
+            }
+        }
+        Some(other) => {
+            return Err(McpError::invalid_params(
+                format!("Argument {} must be a string, got {}", arg.name, other),
+                None,
+            ))
+        }
+    };
+
+    if arg.kind == ArgKind::Port && !matches!(value.parse::<u16>(), Ok(port) if port >= 1024) {
+        return Err(McpError::invalid_params(
+            format!(
+                "Argument {} must be a port between 1024 and 65535, got {}",
+                arg.name, value
+            ),
+            None,
+        ));
+    }
+
+    // Serialize as a JSON string and drop the quotes, so paths with quotes or
+    // backslashes keep the argument templates valid JSON
+    let escaped = serde_json::Value::String(value).to_string();
+    Ok(escaped[1..escaped.len() - 1].to_string())
+}
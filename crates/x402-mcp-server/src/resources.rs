@@ -0,0 +1,290 @@
+// MCP resources for x402-mcp-server
+//
+// Read-only workspace views, so agents can inspect state without tool calls:
+// - x402://config: merged mock server config (YAML, annotated with sources)
+// - x402://policy/<path>: policy files under the workspace
+// - x402://reports/last-test: SuiteResult JSON from the last testing_run_suite
+
+use rmcp::model::{AnnotateAble, RawResource, Resource, ResourceContents};
+use rmcp::ErrorData as McpError;
+use serde_yaml::{Mapping, Value};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use x402_core::format::DetectedFormat;
+use x402_core::policy::detect_policy_schema;
+
+pub const CONFIG_URI: &str = "x402://config";
+pub const POLICY_URI_PREFIX: &str = "x402://policy/";
+pub const LAST_TEST_URI: &str = "x402://reports/last-test";
+
+const YAML_MIME: &str = "application/yaml";
+
+/// Project config file names, preferred first (as in the CLI)
+const PROJECT_CONFIG_FILES: [&str; 3] = [".x402dev.yaml", ".x402dev.yml", ".x402dev.json"];
+const JSON_MIME: &str = "application/json";
+
+/// Directories never scanned for policy files
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+/// How deep below the workspace root policy files are discovered
+const MAX_SCAN_DEPTH: usize = 4;
+
+/// Environment overrides applied on top of the config files
+const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("port", "X402_DEV_PORT"),
+    ("solana_rpc", "X402_DEV_SOLANA_RPC"),
+    ("log_level", "X402_DEV_LOG_LEVEL"),
+];
+
+/// Resources served for one workspace
+///
+/// Clones share the last test report, so the copy held by the tool router
+/// and the one answering resource reads stay in sync.
+#[derive(Debug, Clone)]
+pub struct WorkspaceResources {
+    root: PathBuf,
+    global_config: Option<PathBuf>,
+    last_test: Arc<RwLock<Option<String>>>,
+}
+
+impl WorkspaceResources {
+    /// Serve `root`, merging `global_config` (if any) below its `.x402dev.yaml`
+    /// (or `.yml`, `.json`)
+    pub fn new(root: impl Into<PathBuf>, global_config: Option<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            global_config,
+            last_test: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Serve `root` with the user's `~/.x402dev/config.yaml`
+    pub fn for_workspace(root: impl Into<PathBuf>) -> Self {
+        let global = directories::BaseDirs::new()
+            .map(|dirs| dirs.home_dir().join(".x402dev").join("config.yaml"));
+        Self::new(root, global)
+    }
+
+    /// Keep `report` (SuiteResult JSON) as the `x402://reports/last-test` content
+    pub fn record_test_report(&self, report: String) {
+        *self.last_test.write().unwrap_or_else(|e| e.into_inner()) = Some(report);
+    }
+
+    /// All resources currently available
+    pub fn list(&self) -> Vec<Resource> {
+        let mut resources = vec![resource(
+            CONFIG_URI,
+            "config",
+            "Merged x402-dev mock server configuration with value sources",
+            YAML_MIME,
+        )];
+
+        for path in self.policy_files() {
+            let uri = format!("{}{}", POLICY_URI_PREFIX, path);
+            resources.push(resource(&uri, &path, "x402 policy file", YAML_MIME));
+        }
+
+        if self.last_test_report().is_some() {
+            resources.push(resource(
+                LAST_TEST_URI,
+                "last-test",
+                "Result of the most recent x402__testing_run_suite or x402__scenario_run call",
+                JSON_MIME,
+            ));
+        }
+
+        resources
+    }
+
+    /// Contents of the resource at `uri`
+    pub fn read(&self, uri: &str) -> Result<ResourceContents, McpError> {
+        let (text, mime_type) = if uri == CONFIG_URI {
+            (self.render_config()?, YAML_MIME)
+        } else if uri == LAST_TEST_URI {
+            let report = self.last_test_report().ok_or_else(|| {
+                McpError::resource_not_found(
+                    "No test report yet: run x402__testing_run_suite first",
+                    None,
+                )
+            })?;
+            (report, JSON_MIME)
+        } else if let Some(path) = uri.strip_prefix(POLICY_URI_PREFIX) {
+            (self.read_policy(path)?, YAML_MIME)
+        } else {
+            return Err(McpError::resource_not_found(
+                format!("Unknown resource: {}", uri),
+                None,
+            ));
+        };
+
+        Ok(ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type.to_string()),
+            text,
+            meta: None,
+        })
+    }
+
+    fn last_test_report(&self) -> Option<String> {
+        self.last_test
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Workspace-relative paths ('/'-separated) of YAML files holding policies
+    fn policy_files(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        collect_policy_files(&self.root, &self.root, 0, &mut found);
+        found.sort();
+        found
+    }
+
+    /// Read a policy file, refusing paths that leave the workspace
+    fn read_policy(&self, relative: &str) -> Result<String, McpError> {
+        let not_found =
+            || McpError::resource_not_found(format!("No policy file at {}", relative), None);
+
+        let relative = Path::new(relative);
+        if !relative
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(not_found());
+        }
+
+        // Symlinks must not lead outside the workspace either
+        let path = self.root.join(relative);
+        let inside = match (path.canonicalize(), self.root.canonicalize()) {
+            (Ok(path), Ok(root)) => path.starts_with(root),
+            _ => false,
+        };
+        if !inside {
+            return Err(not_found());
+        }
+
+        let content = std::fs::read_to_string(path).map_err(|_| not_found())?;
+        if detect_policy_schema(&content).is_err() {
+            return Err(not_found());
+        }
+        Ok(content)
+    }
+
+    /// Merge defaults < global < project < environment, one source per key
+    fn render_config(&self) -> Result<String, McpError> {
+        let defaults = serde_yaml::to_value(x402_server::Config::default())
+            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+        let mut merged: Vec<(String, Value, String)> = match defaults {
+            Value::Mapping(map) => map
+                .into_iter()
+                .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v, "default".to_string())))
+                .collect(),
+            _ => vec![],
+        };
+
+        let mut layers = vec![];
+        if let Some(global) = &self.global_config {
+            layers.push((
+                global.clone(),
+                "global (~/.x402dev/config.yaml)".to_string(),
+            ));
+        }
+        // Same preference as the CLI when a directory has more than one
+        if let Some(name) = PROJECT_CONFIG_FILES
+            .iter()
+            .find(|name| self.root.join(name).is_file())
+        {
+            layers.push((self.root.join(name), format!("project ({})", name)));
+        }
+
+        for (path, source) in layers {
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let layer: Mapping = DetectedFormat::detect(Some(&path), &content)
+                .parse(&content)
+                .map_err(|e| {
+                    McpError::internal_error(
+                        format!("Failed to parse config file {}: {:#}", path.display(), e),
+                        None,
+                    )
+                })?;
+            for (key, value) in layer {
+                if let Some(key) = key.as_str() {
+                    set_value(&mut merged, key, value, source.clone());
+                }
+            }
+        }
+
+        for (key, var) in ENV_OVERRIDES {
+            if let Ok(raw) = std::env::var(var) {
+                let value = serde_yaml::from_str(&raw).unwrap_or(Value::String(raw));
+                set_value(&mut merged, key, value, format!("environment ({})", var));
+            }
+        }
+
+        let mut yaml = String::from(
+            "# x402-dev merged configuration\n\
+             # Priority: environment > project > global > defaults\n",
+        );
+        for (key, value, source) in merged {
+            let mut entry = Mapping::new();
+            entry.insert(Value::String(key), value);
+            let rendered = serde_yaml::to_string(&entry)
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            yaml.push_str(&format!("# source: {}\n{}", source, rendered));
+        }
+        Ok(yaml)
+    }
+}
+
+fn resource(uri: &str, name: &str, description: &str, mime_type: &str) -> Resource {
+    let mut raw = RawResource::new(uri, name);
+    raw.description = Some(description.to_string());
+    raw.mime_type = Some(mime_type.to_string());
+    raw.no_annotation()
+}
+
+fn set_value(merged: &mut Vec<(String, Value, String)>, key: &str, value: Value, source: String) {
+    match merged.iter_mut().find(|(k, _, _)| k == key) {
+        Some(entry) => {
+            entry.1 = value;
+            entry.2 = source;
+        }
+        None => merged.push((key.to_string(), value, source)),
+    }
+}
+
+fn collect_policy_files(root: &Path, dir: &Path, depth: usize, found: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+
+        if path.is_dir() {
+            if depth < MAX_SCAN_DEPTH && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_policy_files(root, &path, depth + 1, found);
+            }
+            continue;
+        }
+
+        let is_yaml = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yaml" | "yml")
+        );
+        let is_policy = is_yaml
+            && std::fs::read_to_string(&path).is_ok_and(|c| detect_policy_schema(&c).is_ok());
+        if let (true, Ok(relative)) = (is_policy, path.strip_prefix(root)) {
+            let parts: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            found.push(parts.join("/"));
+        }
+    }
+}
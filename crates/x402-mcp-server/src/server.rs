@@ -8,20 +8,35 @@
 use rmcp::{
     handler::server::{tool::ToolRouter, wrapper::Parameters, ServerHandler},
     model::*,
-    tool, tool_handler, tool_router, ErrorData as McpError, Json,
+    service::RequestContext,
+    tool, tool_handler, tool_router, ErrorData as McpError, Json, Peer, RoleServer,
 };
 
+use crate::prompts;
+use crate::resources::WorkspaceResources;
 use crate::tools::mock_server::{MockStartParams, MockStartResponse, MockStatusResponse};
 use crate::tools::policy::{
     convert_validation_report, PolicyGenerateParams, PolicyGenerateResponse, PolicyValidateParams,
     PolicyValidateResponse,
 };
+use crate::tools::scenario::{
+    convert_scenario_report, ScenarioIssueItem, ScenarioRunParams, ScenarioRunResponse,
+};
 use crate::tools::testing::{
     convert_suite_result, CheckComplianceParams, ComplianceCheckResponse, TestSuiteParams,
     TestSuiteResponse,
 };
-use x402_core::policy::{generate_express_middleware, validate_policies, PolicyConfig, PolicyFile};
-use x402_core::testing::{execute_test_suite, TestSuite};
+use x402_core::format::DetectedFormat;
+use x402_core::policy::{
+    generate_express_middleware, generate_test_suite, validate_policies, PolicyConfig, PolicyFile,
+    QuotaHeaders, Suppressions,
+};
+use x402_core::testing::{
+    check_cache_headers, check_head_parity, execute_test_suite_filtered, format_json,
+    HeadParityCheck, InvoiceHeader, ProbeResponse, TestSuite,
+};
+use x402_domain::Amount;
+use x402_server::{validate_scenario, Config, MockServerConfig, ProcessManager, SimulationMode};
 
 /// x402 MCP Server
 ///
@@ -31,6 +46,8 @@ use x402_core::testing::{execute_test_suite, TestSuite};
 pub struct X402McpServer {
     /// Tool router for dispatching tool calls
     tool_router: ToolRouter<Self>,
+    /// Config, policy and report resources for the workspace
+    resources: WorkspaceResources,
 }
 
 /// Implement Default trait for convenient instantiation
@@ -42,13 +59,26 @@ impl Default for X402McpServer {
 
 #[tool_router]
 impl X402McpServer {
-    /// Create a new MCP server instance
+    /// Create a new MCP server instance serving the current directory
     pub fn new() -> Self {
+        let root = std::env::current_dir().unwrap_or_else(|_| ".".into());
+        Self::with_resources(WorkspaceResources::for_workspace(root))
+    }
+
+    /// Create a server exposing `resources` (e.g. a different workspace root)
+    pub fn with_resources(resources: WorkspaceResources) -> Self {
         Self {
             tool_router: Self::tool_router(),
+            resources,
         }
     }
 
+    /// Tools registered in the router, as announced by `tools/list`
+    #[allow(dead_code)]
+    pub fn tools(&self) -> Vec<Tool> {
+        self.tool_router.list_all()
+    }
+
     /// Start x402 mock facilitator server
     ///
     /// Starts a local HTTP server that responds with 402 Payment Required.
@@ -63,15 +93,7 @@ impl X402McpServer {
     ) -> Result<Json<MockStartResponse>, McpError> {
         let params = params.0;
 
-        // Validate port range
-        if params.port < 1024 {
-            return Err(McpError::invalid_params(
-                format!("Port must be >= 1024, got {}", params.port),
-                None,
-            ));
-        }
-
-        // Validate pricing
+        // Validate pricing (the server accepts 0, but a free mock is a mistake here)
         if params.pricing <= 0.0 {
             return Err(McpError::invalid_params(
                 format!("Pricing must be positive, got {}", params.pricing),
@@ -79,15 +101,29 @@ impl X402McpServer {
             ));
         }
 
+        let simulation_mode = params
+            .simulation_mode
+            .parse::<SimulationMode>()
+            .map_err(|e| McpError::invalid_params(e, None))?;
+
+        // Port range and pricing bounds are checked by Config::validate
+        let config = Config::builder()
+            .port(params.port)
+            .default_price(params.pricing)
+            .simulation_mode(simulation_mode)
+            .build()
+            .and_then(MockServerConfig::new)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+
         // TODO: Phase 1 implementation
         // For now, return a simulated response
         // Full implementation will use x402_server::start_server() in background task
 
         tracing::info!(
-            "Mock server start requested: port={}, pricing={}, mode={}",
-            params.port,
-            params.pricing,
-            params.simulation_mode
+            "Mock server start requested: port={}, pricing={}, mode={:?}",
+            config.port,
+            config.config.pricing.default,
+            config.config.simulation_mode
         );
 
         Ok(Json(MockStartResponse {
@@ -103,21 +139,28 @@ impl X402McpServer {
 
     /// Check x402 mock server status
     ///
-    /// Returns the current status of the mock payment server.
+    /// Reads the mock server's PID file; the port is the one the server
+    /// actually bound, which differs from --port after a port fallback.
     #[tool(
         name = "x402__server_mock_status",
         description = "Check if x402 mock server is running"
     )]
     async fn mock_status(&self) -> Result<Json<MockStatusResponse>, McpError> {
-        // TODO: Phase 1 implementation
-        // Full implementation will check actual server process status
-
         tracing::info!("Mock server status check");
 
+        let manager = ProcessManager::new();
+        if !manager.is_running() {
+            return Ok(Json(MockStatusResponse {
+                status: "stopped".to_string(),
+                pid: None,
+                port: None,
+            }));
+        }
+
         Ok(Json(MockStatusResponse {
-            status: "stopped".to_string(),
-            pid: None,
-            port: None,
+            status: "running".to_string(),
+            pid: manager.get_pid(),
+            port: manager.get_port(),
         }))
     }
 
@@ -150,19 +193,24 @@ impl X402McpServer {
             McpError::invalid_params(format!("Failed to read policy file: {}", e), None)
         })?;
 
-        // Parse YAML into PolicyConfig
-        let policy_config: PolicyConfig = serde_yaml::from_str(&yaml_content).map_err(|e| {
+        // Parse YAML or JSON into PolicyConfig
+        let format = DetectedFormat::detect(Some(policy_path), &yaml_content);
+        let policy_config: PolicyConfig = format.parse(&yaml_content).map_err(|e| {
             McpError::invalid_params(
-                format!("Invalid YAML format: {}", e),
+                format!("Invalid {} format: {:#}", format.format, e),
                 Some(serde_json::json!({
-                    "hint": "Check YAML syntax and policy structure",
-                    "error": e.to_string()
+                    "hint": "Check the file syntax and policy structure",
+                    "error": format!("{:#}", e)
                 })),
             )
         })?;
 
+        let suppressions = Suppressions::parse(&yaml_content, format)
+            .map_err(|e| McpError::invalid_params(format!("{:#}", e), None))?;
+
         // Validate policies using x402-core
-        let report = validate_policies(&policy_config);
+        let mut report = validate_policies(&policy_config);
+        report.apply_suppressions(&suppressions);
 
         // Convert to MCP response format
         let response = convert_validation_report(report);
@@ -187,9 +235,20 @@ impl X402McpServer {
     async fn testing_run_suite(
         &self,
         params: Parameters<TestSuiteParams>,
+        peer: Peer<RoleServer>,
     ) -> Result<Json<TestSuiteResponse>, McpError> {
-        let params = params.0;
+        let response = self.run_suite(params.0).await?;
 
+        // x402://reports/last-test now has (new) content
+        if let Err(e) = peer.notify_resource_list_changed().await {
+            tracing::warn!("Failed to send resource list_changed: {}", e);
+        }
+
+        Ok(Json(response))
+    }
+
+    /// Run a test suite and keep its report as the last-test resource
+    pub async fn run_suite(&self, params: TestSuiteParams) -> Result<TestSuiteResponse, McpError> {
         tracing::info!("Test suite execution requested: {}", params.suite);
 
         // Validate suite file exists
@@ -202,7 +261,7 @@ impl X402McpServer {
         }
 
         // Parse test suite from YAML
-        let suite = TestSuite::from_file(suite_path).map_err(|e| {
+        let mut suite = TestSuite::from_file(suite_path).map_err(|e| {
             McpError::invalid_params(
                 format!("Failed to parse test suite: {}", e),
                 Some(serde_json::json!({
@@ -212,13 +271,56 @@ impl X402McpServer {
             )
         })?;
 
+        if let Some(budget) = &params.budget {
+            let max_total = Amount::parse_user_input(budget)
+                .ok()
+                .filter(|amount| !amount.is_zero())
+                .ok_or_else(|| {
+                    McpError::invalid_params(
+                        format!("Invalid budget '{}': expected a positive amount", budget),
+                        None,
+                    )
+                })?;
+            suite.override_budget(max_total.as_decimal());
+        }
+        if params.preflight {
+            suite.preflight.get_or_insert_with(Default::default);
+        }
+
         tracing::info!("Executing {} tests from suite", suite.tests.len());
 
         // Execute test suite using refactored x402-core function (Day 0 work!)
-        let result = execute_test_suite(&suite)
+        let filter = params.filter();
+        if filter.selected_count(&suite.tests) == 0 {
+            tracing::warn!("No tests matched the given tag/name filters");
+        }
+
+        let result = execute_test_suite_filtered(&suite, &filter)
             .await
             .map_err(|e| McpError::invalid_params(format!("Test execution failed: {}", e), None))?;
 
+        self.resources.record_test_report(format_json(&result));
+
+        // An unreachable target is one error, not a suite of failed tests
+        if let Some(report) = result.preflight.as_ref().filter(|r| !r.passed()) {
+            let first = report
+                .failures()
+                .next()
+                .and_then(|check| check.error.as_deref())
+                .unwrap_or_default();
+            return Err(McpError::internal_error(
+                format!(
+                    "Pre-flight checks failed, no tests were run: {}",
+                    first.lines().next().unwrap_or_default()
+                ),
+                Some(serde_json::json!({
+                    "status": result.status().to_string(),
+                    "preflight": report,
+                    "hint": "Start the target server or fix the failed checks, then run the suite again"
+                })),
+            ));
+        }
+
         // Convert to MCP response format
         let response = convert_suite_result(result);
 
@@ -228,9 +330,78 @@ impl X402McpServer {
             response.failed
         );
 
+        Ok(response)
+    }
+
+    /// Run a scenario bundle (config + policy + test suite)
+    ///
+    /// Serves the scenario's config and policies from an in-process mock
+    /// server on a free loopback port, runs its suite and stops the server.
+    #[tool(
+        name = "x402__scenario_run",
+        description = "Run an x402-scenario.yaml: start an in-process mock server with its config and policies, run its test suite, report results and server stats"
+    )]
+    async fn scenario_run(
+        &self,
+        params: Parameters<ScenarioRunParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<Json<ScenarioRunResponse>, McpError> {
+        let response = self.run_scenario(params.0).await?;
+
+        // The suite report also becomes x402://reports/last-test
+        if let Err(e) = peer.notify_resource_list_changed().await {
+            tracing::warn!("Failed to send resource list_changed: {}", e);
+        }
+
         Ok(Json(response))
     }
 
+    /// Validate and run a scenario, keeping its suite report as the
+    /// last-test resource
+    pub async fn run_scenario(
+        &self,
+        params: ScenarioRunParams,
+    ) -> Result<ScenarioRunResponse, McpError> {
+        tracing::info!("Scenario run requested: {}", params.scenario);
+
+        let path = std::path::Path::new(&params.scenario);
+        if !path.exists() {
+            return Err(McpError::invalid_params(
+                format!("Scenario file not found: {}", params.scenario),
+                None,
+            ));
+        }
+
+        let validation = validate_scenario(path)
+            .map_err(|e| McpError::invalid_params(format!("{:#}", e), None))?;
+        let issues: Vec<ScenarioIssueItem> = validation.issues.iter().map(Into::into).collect();
+        let Some(scenario) = validation.scenario else {
+            return Err(McpError::invalid_params(
+                format!("Invalid scenario: {}", params.scenario),
+                Some(serde_json::json!({
+                    "hint": "Fix the listed sections; locations are <file>#<section>",
+                    "issues": issues,
+                })),
+            ));
+        };
+
+        let report = scenario
+            .run(&params.filter())
+            .await
+            .map_err(|e| McpError::internal_error(format!("Scenario run failed: {:#}", e), None))?;
+        self.resources
+            .record_test_report(format_json(&report.suite));
+
+        let response = convert_scenario_report(report, issues);
+        tracing::info!(
+            "Scenario {} complete: {} passed, {} failed",
+            response.scenario,
+            response.suite.passed,
+            response.suite.failed
+        );
+        Ok(response)
+    }
+
     /// Check x402 endpoint compliance
     ///
     /// Validates that an HTTP endpoint properly implements the 402 payment protocol.
@@ -265,16 +436,84 @@ impl X402McpServer {
             })?;
 
         let status_code = response.status().as_u16();
-        let has_www_authenticate = response.headers().contains_key("www-authenticate");
+        let www_authenticate = response
+            .headers()
+            .get("www-authenticate")
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let has_www_authenticate = www_authenticate.is_some();
 
         // Analyze compliance
         let mut issues = Vec::new();
         let status = if status_code == 402 {
             if !has_www_authenticate {
                 issues.push("Missing WWW-Authenticate header".to_string());
-                "non_compliant"
-            } else {
+            }
+
+            let cache_check = check_cache_headers(response.headers());
+            if !cache_check.passed {
+                let mut issue = format!("402 response is cacheable ({})", cache_check.detail);
+                if let Some(suggestion) = cache_check.suggestion {
+                    issue.push_str(&format!(". {}", suggestion));
+                }
+                issues.push(issue);
+            }
+
+            // HEAD must answer like GET without a body
+            let get = ProbeResponse {
+                status: status_code,
+                invoice: www_authenticate.as_deref(),
+                body_len: 0,
+            };
+            let head_check = match client.head(url.clone()).send().await {
+                Ok(head) => {
+                    let status = head.status().as_u16();
+                    let invoice = head
+                        .headers()
+                        .get("www-authenticate")
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string);
+                    let body_len = head.bytes().await.map_or(0, |b| b.len());
+                    check_head_parity(
+                        get,
+                        ProbeResponse {
+                            status,
+                            invoice: invoice.as_deref(),
+                            body_len,
+                        },
+                    )
+                }
+                Err(e) => HeadParityCheck {
+                    passed: false,
+                    detail: format!("HEAD request failed: {}", e),
+                    suggestion: None,
+                },
+            };
+            if !head_check.passed {
+                let mut issue = format!("HEAD parity ({})", head_check.detail);
+                if let Some(suggestion) = head_check.suggestion {
+                    issue.push_str(&format!(". {}", suggestion));
+                }
+                issues.push(issue);
+            }
+
+            if let (true, Some(header)) = (params.strict, &www_authenticate) {
+                match InvoiceHeader::parse(header) {
+                    Ok(invoice) => issues.extend(
+                        invoice
+                            .strict_checks()
+                            .into_iter()
+                            .filter(|check| !check.passed)
+                            .map(|check| format!("Strict: {} ({})", check.name, check.detail)),
+                    ),
+                    Err(e) => issues.push(format!("Strict: {}", e)),
+                }
+            }
+
+            if issues.is_empty() {
                 "compliant"
+            } else {
+                "non_compliant"
             }
         } else {
             issues.push(format!("Expected 402 status code, got {}", status_code));
@@ -337,8 +576,9 @@ impl X402McpServer {
             McpError::invalid_params(format!("Failed to read policy file: {}", e), None)
         })?;
 
-        let policy_file: PolicyFile = serde_yaml::from_str(&yaml_content)
-            .map_err(|e| McpError::invalid_params(format!("Invalid policy YAML: {}", e), None))?;
+        let policy_file: PolicyFile = DetectedFormat::detect(Some(policy_path), &yaml_content)
+            .parse(&yaml_content)
+            .map_err(|e| McpError::invalid_params(format!("Invalid policy file: {:#}", e), None))?;
 
         // Extract filename from path
         let policy_file_name = policy_path
@@ -348,7 +588,7 @@ impl X402McpServer {
 
         // Generate code based on framework
         let generated_code = if params.framework == "express" {
-            generate_express_middleware(&policy_file, policy_file_name)
+            generate_express_middleware(&policy_file, policy_file_name, &QuotaHeaders::default())
         } else {
             return Err(McpError::invalid_params(
                 format!(
@@ -361,6 +601,15 @@ impl X402McpServer {
 
         let policy_count = policy_file.policies.len();
 
+        let test_suite = if params.with_tests {
+            let suite = generate_test_suite(&policy_file);
+            Some(suite.to_yaml().map_err(|e| {
+                McpError::internal_error(format!("Failed to serialize test suite: {}", e), None)
+            })?)
+        } else {
+            None
+        };
+
         // Write to file or return as string
         let (output_file, code) = if let Some(output_path) = params.output {
             std::fs::write(&output_path, &generated_code).map_err(|e| {
@@ -382,6 +631,7 @@ impl X402McpServer {
             status: "success".to_string(),
             code,
             output_file,
+            test_suite,
             policy_count,
             summary,
         }))
@@ -418,14 +668,55 @@ impl ServerHandler for X402McpServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_prompts()
+                .enable_resources()
+                .enable_resources_list_changed()
+                .build(),
             server_info: Implementation::from_build_env(),
             instructions: Some(
                 "x402-dev MCP Server - Payment protocol testing toolkit. \
                 Provides tools for: mock server management, policy validation, \
-                test execution, and compliance checking."
+                test execution, and compliance checking. Resources: x402://config, \
+                x402://policy/<path>, x402://reports/last-test. Prompts: \
+                x402_setup_local_testing, x402_diagnose_endpoint, x402_generate_middleware."
                     .to_string(),
             ),
         }
     }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        Ok(ListResourcesResult::with_all_items(self.resources.list()))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        Ok(ReadResourceResult {
+            contents: vec![self.resources.read(&request.uri)?],
+        })
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, McpError> {
+        Ok(ListPromptsResult::with_all_items(prompts::list()))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        prompts::get(&request.name, request.arguments.as_ref())
+    }
 }
@@ -7,6 +7,7 @@
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use x402_domain::amount::user_input;
 
 /// Parameters for starting the mock server
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -16,7 +17,10 @@ pub struct MockStartParams {
     pub port: u16,
 
     /// Default pricing in SOL/USDC
-    #[serde(default = "default_pricing")]
+    #[serde(
+        default = "default_pricing",
+        deserialize_with = "user_input::deserialize_f64"
+    )]
     pub pricing: f64,
 
     /// Simulation mode: "success", "failure", or "timeout"
@@ -12,7 +12,11 @@
 // - x402__testing_check_compliance (Epic 3 integration)
 // - x402__policy_generate_express (Epic 5 integration)
 // - x402__server_mock_stop (Epic 2 integration)
+//
+// Scenarios:
+// - x402__scenario_run (config + policy + suite bundles)
 
 pub mod mock_server;
 pub mod policy;
+pub mod scenario;
 pub mod testing;
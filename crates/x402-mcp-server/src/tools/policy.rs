@@ -17,9 +17,17 @@ pub struct PolicyValidateParams {
 /// Single validation issue
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PolicyIssue {
+    /// Stable rule code, e.g. "X402-P010"
+    #[serde(default)]
+    pub code: String,
+
     /// Issue type: "error", "warning", or "info"
     pub issue_type: String,
 
+    /// Acknowledged by a `suppress:` list in the policy file; not counted
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suppressed: bool,
+
     /// Issue message
     pub message: String,
 
@@ -57,6 +65,7 @@ fn issue_type_to_string(issue_type: &IssueType) -> String {
         IssueType::Error => "error".to_string(),
         IssueType::Warning => "warning".to_string(),
         IssueType::Info => "info".to_string(),
+        _ => "unknown".to_string(),
     }
 }
 
@@ -66,7 +75,9 @@ pub fn convert_validation_report(report: ValidationReport) -> PolicyValidateResp
         .issues
         .iter()
         .map(|issue| PolicyIssue {
+            code: issue.code.to_string(),
             issue_type: issue_type_to_string(&issue.issue_type),
+            suppressed: issue.suppressed,
             message: issue.message.clone(),
             details: issue.details.clone(),
             suggestions: issue
@@ -77,8 +88,14 @@ pub fn convert_validation_report(report: ValidationReport) -> PolicyValidateResp
         })
         .collect();
 
-    let error_count = issues.iter().filter(|i| i.issue_type == "error").count();
-    let warning_count = issues.iter().filter(|i| i.issue_type == "warning").count();
+    let counted = |issue_type: &str| {
+        issues
+            .iter()
+            .filter(|i| i.issue_type == issue_type && !i.suppressed)
+            .count()
+    };
+    let error_count = counted("error");
+    let warning_count = counted("warning");
 
     let status = if error_count > 0 {
         "invalid"
@@ -120,6 +137,10 @@ pub struct PolicyGenerateParams {
     /// Optional output file path (if not provided, returns code as string)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output: Option<String>,
+
+    /// Also return a test suite (YAML) that exercises the middleware
+    #[serde(default)]
+    pub with_tests: bool,
 }
 
 /// Response from policy code generation
@@ -136,6 +157,10 @@ pub struct PolicyGenerateResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_file: Option<String>,
 
+    /// Test suite YAML for `x402-dev test` (if `with_tests` was set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_suite: Option<String>,
+
     /// Number of policies processed
     pub policy_count: usize,
 
@@ -0,0 +1,109 @@
+// Scenario tools for x402-mcp-server
+//
+// - x402__scenario_run: Run an x402-scenario.yaml (config + policy + suite)
+//   against an in-process mock server
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use x402_core::policy::IssueType;
+use x402_core::testing::TestFilter;
+use x402_server::{ScenarioIssue, ScenarioReport};
+
+use super::testing::{convert_suite_result, TestSuiteResponse};
+
+/// Parameters for running a scenario
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioRunParams {
+    /// Path to the scenario file (e.g. x402-scenario.yaml)
+    pub scenario: String,
+
+    /// Only run tests carrying all of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Skip tests carrying any of these tags
+    #[serde(default)]
+    pub skip_tags: Vec<String>,
+
+    /// Only run tests whose name contains this substring
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub only: Option<String>,
+}
+
+impl ScenarioRunParams {
+    /// Build the test selection filter from the tag/name parameters
+    pub fn filter(&self) -> TestFilter {
+        TestFilter {
+            tags: self.tags.clone(),
+            skip_tags: self.skip_tags.clone(),
+            only: self.only.clone(),
+        }
+    }
+}
+
+/// Section problem found while validating a scenario
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioIssueItem {
+    /// "config", "policy" or "suite"
+    pub section: String,
+
+    /// Scenario file and section, e.g. "x402-scenario.yaml#policy.policies[1]"
+    pub location: String,
+
+    /// "error" or "warning"
+    pub severity: String,
+
+    pub message: String,
+}
+
+impl From<&ScenarioIssue> for ScenarioIssueItem {
+    fn from(issue: &ScenarioIssue) -> Self {
+        Self {
+            section: issue.section.to_string(),
+            location: issue.location.clone(),
+            severity: match issue.issue_type {
+                IssueType::Error => "error",
+                IssueType::Warning => "warning",
+                _ => "info",
+            }
+            .to_string(),
+            message: issue.message.clone(),
+        }
+    }
+}
+
+/// Scenario run response: suite results plus the scenario server's stats
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScenarioRunResponse {
+    /// Scenario name (file stem when unnamed)
+    pub scenario: String,
+
+    /// "passed" or "failed"
+    pub status: String,
+
+    /// Suite results, as returned by x402__testing_run_suite
+    pub suite: TestSuiteResponse,
+
+    /// `/__admin/stats` of the scenario server after the suite ran
+    pub stats: serde_json::Value,
+
+    /// Validation warnings (errors fail the call instead)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<ScenarioIssueItem>,
+}
+
+/// Convert a ScenarioReport to ScenarioRunResponse
+pub fn convert_scenario_report(
+    report: ScenarioReport,
+    warnings: Vec<ScenarioIssueItem>,
+) -> ScenarioRunResponse {
+    let stats = serde_json::to_value(&report.stats).unwrap_or_default();
+    let suite = convert_suite_result(report.suite);
+    ScenarioRunResponse {
+        scenario: report.name,
+        status: suite.status.clone(),
+        suite,
+        stats,
+        warnings,
+    }
+}
@@ -6,7 +6,9 @@
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use x402_core::testing::SuiteResult;
+use std::collections::BTreeMap;
+use x402_core::testing::{SuiteResult, TestFilter};
+use x402_domain::amount::user_input;
 
 /// Parameters for running a test suite
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -25,6 +27,39 @@ pub struct TestSuiteParams {
     /// Generate JUnit XML report (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub junit: Option<String>,
+
+    /// Only run tests carrying all of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Skip tests carrying any of these tags
+    #[serde(default)]
+    pub skip_tags: Vec<String>,
+
+    /// Only run tests whose name contains this substring
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub only: Option<String>,
+
+    /// Invoiced total at which remaining tests are skipped, e.g. "0.50"
+    /// (overrides the suite's `budget.max_total`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<String>,
+
+    /// Check variables, DNS and target reachability before running any
+    /// test, even if the suite has no `preflight:` section
+    #[serde(default)]
+    pub preflight: bool,
+}
+
+impl TestSuiteParams {
+    /// Build the test selection filter from the tag/name parameters
+    pub fn filter(&self) -> TestFilter {
+        TestFilter {
+            tags: self.tags.clone(),
+            skip_tags: self.skip_tags.clone(),
+            only: self.only.clone(),
+        }
+    }
 }
 
 /// Single test result
@@ -48,6 +83,14 @@ pub struct TestResultItem {
     /// Error message (if failed)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+
+    /// Kind of failure behind `error`: "timeout", "connection" or "request"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_category: Option<String>,
+
+    /// Reason the test was not executed (if skipped)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
 }
 
 /// Test suite execution response
@@ -65,12 +108,20 @@ pub struct TestSuiteResponse {
     /// Number of tests failed
     pub failed: usize,
 
+    /// Number of tests not executed (e.g. filtered out)
+    #[serde(default)]
+    pub skipped: usize,
+
     /// Total duration in milliseconds
     pub duration_ms: u64,
 
     /// Individual test results
     pub tests: Vec<TestResultItem>,
 
+    /// Invoiced total per currency, e.g. {"USDC": "0.50"}
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub spent: BTreeMap<String, String>,
+
     /// Human-readable summary
     pub summary: String,
 }
@@ -86,8 +137,17 @@ pub struct CheckComplianceParams {
     pub timeout: u64,
 
     /// Expected pricing (optional validation)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "user_input::deserialize_opt_f64"
+    )]
     pub expected_pricing: Option<f64>,
+
+    /// Also report strict protocol deviations in the WWW-Authenticate header
+    /// (unknown/duplicate fields, field order, reserved characters, precision)
+    #[serde(default)]
+    pub strict: bool,
 }
 
 fn default_timeout() -> u64 {
@@ -129,6 +189,8 @@ pub fn convert_suite_result(result: SuiteResult) -> TestSuiteResponse {
             passed: test.passed,
             duration_ms: test.duration.as_millis() as u64,
             error: test.error.clone(),
+            error_category: test.error_category.map(|c| c.to_string()),
+            skipped: test.skipped.as_ref().map(|r| r.to_string()),
         })
         .collect();
 
@@ -138,20 +200,37 @@ pub fn convert_suite_result(result: SuiteResult) -> TestSuiteResponse {
         "failed"
     };
 
-    let summary = format!(
+    let mut summary = format!(
         "{} of {} tests passed in {}ms",
         result.passed,
-        result.total,
+        result.total - result.skipped,
         result.duration.as_millis()
     );
+    if result.skipped > 0 {
+        summary.push_str(&format!(" ({} skipped)", result.skipped));
+    }
+    if let Some(budget) = &result.budget {
+        summary.push_str(&format!(
+            ", spent {} of {} {}",
+            result.spent_in(&budget.currency),
+            budget.max_total,
+            budget.currency
+        ));
+    }
 
     TestSuiteResponse {
         status: status.to_string(),
         total: result.total,
         passed: result.passed,
         failed: result.failed,
+        skipped: result.skipped,
         duration_ms: result.duration.as_millis() as u64,
         tests,
+        spent: result
+            .spent
+            .iter()
+            .map(|(currency, amount)| (currency.clone(), amount.to_string()))
+            .collect(),
         summary,
     }
 }
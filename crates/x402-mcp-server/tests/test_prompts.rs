@@ -0,0 +1,153 @@
+// Integration tests for MCP prompts
+//
+// Each prompt must render with its arguments substituted and reference
+// only tools registered in the server's tool router.
+
+use rmcp::model::{JsonObject, PromptMessageContent, PromptMessageRole};
+use serde_json::json;
+use x402_mcp_server::prompts;
+use x402_mcp_server::X402McpServer;
+
+fn args(value: serde_json::Value) -> JsonObject {
+    value.as_object().cloned().unwrap()
+}
+
+fn render(name: &str, arguments: Option<&JsonObject>) -> String {
+    let result = prompts::get(name, arguments).unwrap();
+    assert_eq!(result.messages.len(), 1);
+    assert_eq!(result.messages[0].role, PromptMessageRole::User);
+    match &result.messages[0].content {
+        PromptMessageContent::Text { text } => text.clone(),
+        other => panic!("expected text content, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_list_prompts() {
+    let names: Vec<String> = prompts::list().into_iter().map(|p| p.name).collect();
+    assert_eq!(
+        names,
+        [
+            "x402_setup_local_testing",
+            "x402_diagnose_endpoint",
+            "x402_generate_middleware"
+        ]
+    );
+
+    let diagnose = prompts::list()
+        .into_iter()
+        .find(|p| p.name == "x402_diagnose_endpoint")
+        .unwrap();
+    let url = &diagnose.arguments.unwrap()[0];
+    assert_eq!(url.name, "url");
+    assert_eq!(url.required, Some(true));
+}
+
+#[test]
+fn test_setup_prompt_substitutes_arguments() {
+    let text = render(
+        "x402_setup_local_testing",
+        Some(&args(
+            json!({"port": "8402", "policy_file": "policies/api.yaml"}),
+        )),
+    );
+    assert!(text.contains(r#"{"port": 8402, "pricing": 0.01"#));
+    assert!(text.contains(r#"{"policy_file": "policies/api.yaml"}"#));
+    assert!(text.contains(r#"{"url": "http://localhost:8402/api/data"}"#));
+    assert!(
+        !text.contains("{{"),
+        "unsubstituted placeholder in:\n{}",
+        text
+    );
+}
+
+#[test]
+fn test_setup_prompt_uses_defaults() {
+    let text = render("x402_setup_local_testing", None);
+    assert!(text.contains(r#"{"port": 3402"#));
+    assert!(text.contains(r#"{"policy_file": "policy.yaml"}"#));
+
+    // Numeric ports are accepted as well as strings
+    let text = render(
+        "x402_setup_local_testing",
+        Some(&args(json!({"port": 9000}))),
+    );
+    assert!(text.contains(r#"{"port": 9000"#));
+}
+
+#[test]
+fn test_diagnose_prompt_substitutes_arguments() {
+    let text = render(
+        "x402_diagnose_endpoint",
+        Some(&args(json!({"url": "https://api.example.com/paid"}))),
+    );
+    assert!(text.contains(r#"{"url": "https://api.example.com/paid", "strict": true}"#));
+    assert!(!text.contains("{{"));
+}
+
+#[test]
+fn test_generate_prompt_substitutes_arguments() {
+    let text = render(
+        "x402_generate_middleware",
+        Some(&args(
+            json!({"policy_file": "policy.yaml", "output": "src/x402.js"}),
+        )),
+    );
+    assert!(text.contains(
+        r#"{"policy_file": "policy.yaml", "framework": "express", "output": "src/x402.js", "with_tests": true}"#
+    ));
+    assert!(text.contains(r#"{"policy_file": "policy.yaml", "framework": "express"}"#));
+    assert!(!text.contains("{{"));
+}
+
+#[test]
+fn test_arguments_are_json_escaped() {
+    let text = render(
+        "x402_generate_middleware",
+        Some(&args(json!({"policy_file": r#"C:\policies\"a".yaml"#}))),
+    );
+    assert!(text.contains(r#"{"policy_file": "C:\\policies\\\"a\".yaml"}"#));
+}
+
+#[test]
+fn test_invalid_arguments_are_rejected() {
+    let missing = prompts::get("x402_diagnose_endpoint", None).unwrap_err();
+    assert!(missing.message.contains("Missing required argument: url"));
+
+    let port = prompts::get(
+        "x402_setup_local_testing",
+        Some(&args(json!({"port": "80"}))),
+    )
+    .unwrap_err();
+    assert!(port.message.contains("between 1024 and 65535"));
+
+    let unknown = prompts::get("x402_unknown", None).unwrap_err();
+    assert!(unknown.message.contains("Unknown prompt"));
+}
+
+#[test]
+fn test_prompts_reference_only_registered_tools() {
+    let registered: Vec<String> = X402McpServer::new()
+        .tools()
+        .into_iter()
+        .map(|tool| tool.name.to_string())
+        .collect();
+
+    for prompt in prompts::list() {
+        let referenced = prompts::referenced_tools(&prompt.name);
+        assert!(
+            !referenced.is_empty(),
+            "{} references no tools",
+            prompt.name
+        );
+        for tool in referenced {
+            assert!(
+                registered.contains(&tool),
+                "{} references unknown tool {} (registered: {:?})",
+                prompt.name,
+                tool,
+                registered
+            );
+        }
+    }
+}
@@ -0,0 +1,162 @@
+// Unit tests for MCP resources
+//
+// Tests: x402://config, x402://policy/<path>, x402://reports/last-test
+// (listing, MIME types, content) and the last-test report after a suite run
+
+use rmcp::handler::server::ServerHandler;
+use rmcp::model::ResourceContents;
+use std::fs;
+use std::path::Path;
+use x402_mcp_server::resources::{CONFIG_URI, LAST_TEST_URI, POLICY_URI_PREFIX};
+use x402_mcp_server::{TestSuiteParams, WorkspaceResources, X402McpServer};
+
+const POLICY_YAML: &str = r#"policies:
+  - type: allowlist
+    field: agent_id
+    values: ["agent-1"]
+"#;
+
+/// Workspace with a project config, a nested policy file and a test suite
+fn workspace() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join(".x402dev.yaml"),
+        "port: 4100\npricing:\n  default: 0.02\n  per_resource: {}\n",
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("policies")).unwrap();
+    fs::write(dir.path().join("policies/agents.yaml"), POLICY_YAML).unwrap();
+    fs::write(
+        dir.path().join("suite.yaml"),
+        "tests:\n  - name: unreachable\n    url: http://127.0.0.1:1/api\n    expect:\n      status: 402\n",
+    )
+    .unwrap();
+    dir
+}
+
+fn read_text(resources: &WorkspaceResources, uri: &str) -> (String, Option<String>) {
+    match resources.read(uri).unwrap() {
+        ResourceContents::TextResourceContents {
+            text, mime_type, ..
+        } => (text, mime_type),
+        other => panic!("expected text contents, got {:?}", other),
+    }
+}
+
+fn uris(resources: &WorkspaceResources) -> Vec<String> {
+    resources.list().into_iter().map(|r| r.raw.uri).collect()
+}
+
+#[test]
+fn test_list_resources() {
+    let dir = workspace();
+    let resources = WorkspaceResources::new(dir.path(), None);
+
+    let listed = resources.list();
+    let uris: Vec<_> = listed.iter().map(|r| r.raw.uri.as_str()).collect();
+
+    // The suite file is YAML but not a policy, and no report exists yet
+    assert_eq!(uris, vec![CONFIG_URI, "x402://policy/policies/agents.yaml"]);
+    for resource in &listed {
+        assert_eq!(resource.raw.mime_type.as_deref(), Some("application/yaml"));
+    }
+}
+
+#[test]
+fn test_read_config_with_sources() {
+    let dir = workspace();
+    let resources = WorkspaceResources::new(dir.path(), None);
+
+    let (text, mime_type) = read_text(&resources, CONFIG_URI);
+
+    assert_eq!(mime_type.as_deref(), Some("application/yaml"));
+    assert!(text.contains("# source: project (.x402dev.yaml)\nport: 4100\n"));
+    assert!(text.contains("# source: project (.x402dev.yaml)\npricing:\n  default: 0.02"));
+    assert!(text.contains("# source: default\nsimulation_mode: success\n"));
+
+    // Still valid YAML once the annotations are ignored
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&text).unwrap();
+    assert_eq!(parsed["port"], 4100);
+}
+
+#[test]
+fn test_global_config_is_overridden_by_project() {
+    let dir = workspace();
+    let global = dir.path().join("global.yaml");
+    fs::write(&global, "port: 5000\nsimulation_mode: failure\n").unwrap();
+    let resources = WorkspaceResources::new(dir.path(), Some(global));
+
+    let (text, _) = read_text(&resources, CONFIG_URI);
+
+    assert!(text.contains("# source: project (.x402dev.yaml)\nport: 4100\n"));
+    assert!(text.contains("# source: global (~/.x402dev/config.yaml)\nsimulation_mode: failure\n"));
+}
+
+#[test]
+fn test_read_policy_file() {
+    let dir = workspace();
+    let resources = WorkspaceResources::new(dir.path(), None);
+
+    let uri = format!("{}policies/agents.yaml", POLICY_URI_PREFIX);
+    let (text, mime_type) = read_text(&resources, &uri);
+    assert_eq!(text, POLICY_YAML);
+    assert_eq!(mime_type.as_deref(), Some("application/yaml"));
+
+    // Non-policy YAML, missing files and paths leaving the workspace are not served
+    for path in [
+        "suite.yaml",
+        "missing.yaml",
+        "../outside.yaml",
+        "/etc/passwd",
+    ] {
+        let err = resources
+            .read(&format!("{}{}", POLICY_URI_PREFIX, path))
+            .unwrap_err();
+        assert!(err.message.contains("No policy file"), "{}", path);
+    }
+    assert!(resources.read("x402://unknown").is_err());
+}
+
+#[test]
+fn test_last_test_missing_before_any_run() {
+    let dir = workspace();
+    let resources = WorkspaceResources::new(dir.path(), None);
+
+    let err = resources.read(LAST_TEST_URI).unwrap_err();
+    assert!(err.message.contains("No test report yet"));
+}
+
+#[test]
+fn test_server_advertises_resources() {
+    let info = X402McpServer::new().get_info();
+    let resources = info.capabilities.resources.unwrap();
+    assert_eq!(resources.list_changed, Some(true));
+}
+
+#[tokio::test]
+async fn test_last_test_resource_after_running_suite() {
+    // Given: A server for the workspace (sharing the resources' report slot)
+    let dir = workspace();
+    let resources = WorkspaceResources::new(dir.path(), None);
+    let server = X402McpServer::with_resources(resources.clone());
+    assert!(!uris(&resources).contains(&LAST_TEST_URI.to_string()));
+
+    // When: A suite runs (its one test fails to connect)
+    let suite = dir.path().join("suite.yaml");
+    let params: TestSuiteParams =
+        serde_json::from_value(serde_json::json!({ "suite": path_str(&suite) })).unwrap();
+    let response = server.run_suite(params).await.unwrap();
+    assert_eq!(response.failed, 1);
+
+    // Then: The report is listed and readable as SuiteResult JSON
+    assert!(uris(&resources).contains(&LAST_TEST_URI.to_string()));
+    let (text, mime_type) = read_text(&resources, LAST_TEST_URI);
+    assert_eq!(mime_type.as_deref(), Some("application/json"));
+    let report: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(report["failed"], 1);
+    assert_eq!(report["tests"][0]["name"], "unreachable");
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
@@ -24,6 +24,7 @@ fn test_server_capabilities() {
 
     // Verify that tools capability is enabled
     assert!(info.capabilities.tools.is_some());
+    assert!(info.capabilities.prompts.is_some());
 }
 
 #[test]
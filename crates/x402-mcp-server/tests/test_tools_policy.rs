@@ -4,7 +4,9 @@
 // Tests: x402__policy_validate, x402__policy_generate_express
 
 use serde_json::json;
-use x402_core::policy::{IssueType, ResolutionSuggestion, ValidationIssue, ValidationReport};
+use x402_core::policy::{
+    IssueType, ResolutionSuggestion, RuleCode, ValidationIssue, ValidationReport,
+};
 use x402_mcp_server::{
     convert_validation_report, PolicyGenerateParams, PolicyGenerateResponse, PolicyIssue,
     PolicyValidateParams, PolicyValidateResponse,
@@ -23,7 +25,9 @@ fn test_policy_validate_params_deserialization() {
 #[test]
 fn test_policy_issue_serialization() {
     let issue = PolicyIssue {
+        code: "X402-P002".to_string(),
         issue_type: "error".to_string(),
+        suppressed: false,
         message: "Test error message".to_string(),
         details: Some("Detailed information".to_string()),
         suggestions: vec!["Fix this".to_string(), "Or fix that".to_string()],
@@ -55,7 +59,9 @@ fn test_policy_validate_response_with_errors() {
     let response = PolicyValidateResponse {
         status: "invalid".to_string(),
         issues: vec![PolicyIssue {
+            code: "X402-P002".to_string(),
             issue_type: "error".to_string(),
+            suppressed: false,
             message: "Missing required field".to_string(),
             details: None,
             suggestions: vec![],
@@ -76,6 +82,7 @@ fn test_convert_validation_report_no_issues() {
         issues: vec![],
         has_errors: false,
         has_warnings: false,
+        skipped: vec![],
     };
 
     let response = convert_validation_report(report);
@@ -90,14 +97,18 @@ fn test_convert_validation_report_with_errors() {
     let report = ValidationReport {
         issues: vec![
             ValidationIssue {
+                code: RuleCode::InvalidPolicy,
                 issue_type: IssueType::Error,
+                suppressed: false,
                 message: "Error 1".to_string(),
                 details: None,
                 suggestions: vec![],
                 policy_indices: vec![],
             },
             ValidationIssue {
+                code: RuleCode::MultipleRateLimits,
                 issue_type: IssueType::Warning,
+                suppressed: false,
                 message: "Warning 1".to_string(),
                 details: None,
                 suggestions: vec![],
@@ -106,6 +117,7 @@ fn test_convert_validation_report_with_errors() {
         ],
         has_errors: true,
         has_warnings: true,
+        skipped: vec![],
     };
 
     let response = convert_validation_report(report);
@@ -120,14 +132,18 @@ fn test_convert_validation_report_warnings_only() {
     let report = ValidationReport {
         issues: vec![
             ValidationIssue {
+                code: RuleCode::MultipleRateLimits,
                 issue_type: IssueType::Warning,
+                suppressed: false,
                 message: "Warning 1".to_string(),
                 details: None,
                 suggestions: vec![],
                 policy_indices: vec![],
             },
             ValidationIssue {
+                code: RuleCode::MultipleRateLimits,
                 issue_type: IssueType::Warning,
+                suppressed: false,
                 message: "Warning 2".to_string(),
                 details: None,
                 suggestions: vec![],
@@ -136,6 +152,7 @@ fn test_convert_validation_report_warnings_only() {
         ],
         has_errors: false,
         has_warnings: true,
+        skipped: vec![],
     };
 
     let response = convert_validation_report(report);
@@ -149,17 +166,21 @@ fn test_convert_validation_report_warnings_only() {
 fn test_convert_validation_report_with_suggestions() {
     let report = ValidationReport {
         issues: vec![ValidationIssue {
+            code: RuleCode::InvalidPolicy,
             issue_type: IssueType::Error,
+            suppressed: false,
             message: "Invalid pricing".to_string(),
             details: Some("Pricing must be positive".to_string()),
             suggestions: vec![ResolutionSuggestion {
                 description: "Use positive value".to_string(),
                 action: "Set pricing: 0.001".to_string(),
+                catalog: None,
             }],
             policy_indices: vec![],
         }],
         has_errors: true,
         has_warnings: false,
+        skipped: vec![],
     };
 
     let response = convert_validation_report(report);
@@ -178,6 +199,7 @@ fn test_policy_generate_params_deserialization() {
     assert_eq!(params.policy_file, "/path/to/policy.yaml");
     assert_eq!(params.framework, "express");
     assert_eq!(params.output, None);
+    assert!(!params.with_tests);
 }
 
 #[test]
@@ -198,6 +220,7 @@ fn test_policy_generate_response_with_code() {
         status: "success".to_string(),
         code: Some("const middleware = () => {};".to_string()),
         output_file: None,
+        test_suite: None,
         policy_count: 3,
         summary: "Generated Express middleware for 3 policies".to_string(),
     };
@@ -213,6 +236,7 @@ fn test_policy_generate_response_with_file() {
         status: "success".to_string(),
         code: None,
         output_file: Some("/path/to/output.js".to_string()),
+        test_suite: None,
         policy_count: 5,
         summary: "Generated Express middleware for 5 policies".to_string(),
     };
@@ -222,3 +246,28 @@ fn test_policy_generate_response_with_file() {
     assert_eq!(json["output_file"], "/path/to/output.js");
     assert_eq!(json["policy_count"], 5);
 }
+
+#[test]
+fn test_convert_validation_report_codes_and_suppressed() {
+    let report = ValidationReport {
+        issues: vec![ValidationIssue {
+            code: RuleCode::MultipleRateLimits,
+            issue_type: IssueType::Warning,
+            suppressed: true,
+            message: "Multiple rate limits defined".to_string(),
+            details: None,
+            suggestions: vec![],
+            policy_indices: vec![0, 1],
+        }],
+        has_errors: false,
+        has_warnings: false,
+        skipped: vec![],
+    };
+
+    let response = convert_validation_report(report);
+    assert_eq!(response.status, "valid");
+    assert_eq!(response.warning_count, 0);
+    let json = serde_json::to_value(&response.issues[0]).unwrap();
+    assert_eq!(json["code"], "X402-P010");
+    assert_eq!(json["suppressed"], true);
+}
@@ -0,0 +1,67 @@
+// Unit tests for scenario tools
+//
+// Tests: x402__scenario_run with the bundled fixture (in-process server,
+// no externally started mock)
+
+use serde_json::json;
+use x402_mcp_server::{ScenarioRunParams, X402McpServer};
+
+const FIXTURE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../tests/fixtures/scenarios/x402-scenario.yaml"
+);
+
+fn params(value: serde_json::Value) -> ScenarioRunParams {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn test_scenario_params_deserialization() {
+    let params = params(json!({ "scenario": "x402-scenario.yaml", "tags": ["smoke"] }));
+    assert_eq!(params.scenario, "x402-scenario.yaml");
+    assert_eq!(params.filter().tags, vec!["smoke".to_string()]);
+    assert!(params.only.is_none());
+}
+
+#[tokio::test]
+async fn test_scenario_run_fixture() {
+    // Given: The bundled allowlist + pricing scenario
+    let server = X402McpServer::new();
+
+    // When: Running it through the tool
+    let response = server
+        .run_scenario(params(json!({ "scenario": FIXTURE })))
+        .await
+        .unwrap();
+
+    // Then: The pricing assertions and the allowlist denial all pass
+    assert_eq!(response.scenario, "premium-allowlist");
+    assert_eq!(response.status, "passed");
+    assert_eq!((response.suite.passed, response.suite.failed), (3, 0));
+    assert_eq!(response.stats["totals"]["policy_denied"], 1);
+    assert_eq!(response.stats["totals"]["invoices_issued"], 2);
+    assert!(response.warnings.is_empty());
+}
+
+#[tokio::test]
+async fn test_scenario_run_rejects_invalid_sections() {
+    // Given: A scenario whose policy file is missing
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("x402-scenario.yaml");
+    std::fs::write(
+        &path,
+        "policy: missing.yaml\nsuite:\n  tests:\n    - name: t\n      url: ${base_url}/x\n      expect: { status: 402 }\n",
+    )
+    .unwrap();
+
+    // When: Running it
+    let err = X402McpServer::new()
+        .run_scenario(params(json!({ "scenario": path.to_str().unwrap() })))
+        .await
+        .unwrap_err();
+
+    // Then: The policy section is named in the error data
+    let issues = &err.data.unwrap()["issues"];
+    assert_eq!(issues[0]["section"], "policy");
+    assert_eq!(issues[0]["severity"], "error");
+}
@@ -5,10 +5,10 @@
 
 use serde_json::json;
 use std::time::Duration;
-use x402_core::testing::{SuiteResult, TestResult};
+use x402_core::testing::{ErrorCategory, SkipReason, SuiteResult, TestResult};
 use x402_mcp_server::{
     convert_suite_result, CheckComplianceParams, ComplianceCheckResponse, TestResultItem,
-    TestSuiteParams, TestSuiteResponse,
+    TestSuiteParams, TestSuiteResponse, X402McpServer,
 };
 
 #[test]
@@ -28,8 +28,10 @@ fn test_suite_response_serialization() {
         total: 10,
         passed: 10,
         failed: 0,
+        skipped: 0,
         duration_ms: 1500,
         tests: vec![],
+        spent: Default::default(),
         summary: "10 of 10 tests passed in 1500ms".to_string(),
     };
 
@@ -47,8 +49,10 @@ fn test_suite_response_with_failures() {
         total: 10,
         passed: 7,
         failed: 3,
+        skipped: 0,
         duration_ms: 2000,
         tests: vec![],
+        spent: Default::default(),
         summary: "7 of 10 tests passed in 2000ms".to_string(),
     };
 
@@ -65,6 +69,8 @@ fn test_result_item_serialization() {
         passed: true,
         duration_ms: 100,
         error: None,
+        error_category: None,
+        skipped: None,
     };
 
     let json = serde_json::to_value(&item).unwrap();
@@ -81,12 +87,15 @@ fn test_result_item_with_error() {
         method: "POST".to_string(),
         passed: false,
         duration_ms: 200,
-        error: Some("Connection timeout".to_string()),
+        error: Some("Request timed out after 500ms".to_string()),
+        error_category: Some("timeout".to_string()),
+        skipped: None,
     };
 
     let json = serde_json::to_value(&item).unwrap();
     assert_eq!(json["passed"], false);
-    assert_eq!(json["error"], "Connection timeout");
+    assert_eq!(json["error"], "Request timed out after 500ms");
+    assert_eq!(json["error_category"], "timeout");
 }
 
 #[test]
@@ -95,7 +104,11 @@ fn test_convert_suite_result_all_passed() {
         total: 3,
         passed: 3,
         failed: 0,
+        skipped: 0,
         duration: Duration::from_millis(500),
+        spent: Default::default(),
+        budget: None,
+        preflight: None,
         tests: vec![
             TestResult {
                 name: "test1".to_string(),
@@ -105,6 +118,12 @@ fn test_convert_suite_result_all_passed() {
                 duration: Duration::from_millis(100),
                 assertions: vec![],
                 error: None,
+                error_category: None,
+                skipped: None,
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
             },
             TestResult {
                 name: "test2".to_string(),
@@ -114,6 +133,12 @@ fn test_convert_suite_result_all_passed() {
                 duration: Duration::from_millis(150),
                 assertions: vec![],
                 error: None,
+                error_category: None,
+                skipped: None,
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
             },
             TestResult {
                 name: "test3".to_string(),
@@ -123,6 +148,12 @@ fn test_convert_suite_result_all_passed() {
                 duration: Duration::from_millis(250),
                 assertions: vec![],
                 error: None,
+                error_category: None,
+                skipped: None,
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
             },
         ],
     };
@@ -142,7 +173,11 @@ fn test_convert_suite_result_with_failures() {
         total: 2,
         passed: 1,
         failed: 1,
+        skipped: 0,
         duration: Duration::from_millis(300),
+        spent: Default::default(),
+        budget: None,
+        preflight: None,
         tests: vec![
             TestResult {
                 name: "test1".to_string(),
@@ -152,6 +187,12 @@ fn test_convert_suite_result_with_failures() {
                 duration: Duration::from_millis(100),
                 assertions: vec![],
                 error: None,
+                error_category: None,
+                skipped: None,
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
             },
             TestResult {
                 name: "test2".to_string(),
@@ -160,7 +201,13 @@ fn test_convert_suite_result_with_failures() {
                 passed: false,
                 duration: Duration::from_millis(200),
                 assertions: vec![],
-                error: Some("Failed assertion".to_string()),
+                error: Some("Request timed out after 150ms".to_string()),
+                error_category: Some(ErrorCategory::Timeout),
+                skipped: None,
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
             },
         ],
     };
@@ -170,8 +217,10 @@ fn test_convert_suite_result_with_failures() {
     assert_eq!(response.failed, 1);
     assert_eq!(
         response.tests[1].error,
-        Some("Failed assertion".to_string())
+        Some("Request timed out after 150ms".to_string())
     );
+    assert_eq!(response.tests[1].error_category.as_deref(), Some("timeout"));
+    assert_eq!(response.tests[0].error_category, None);
 }
 
 #[test]
@@ -258,11 +307,161 @@ fn test_suite_response_summary_format() {
         total: 5,
         passed: 5,
         failed: 0,
+        skipped: 0,
         duration_ms: 1234,
         tests: vec![],
+        spent: Default::default(),
         summary: "5 of 5 tests passed in 1234ms".to_string(),
     };
 
     assert!(response.summary.contains("5 of 5"));
     assert!(response.summary.contains("1234ms"));
 }
+
+#[test]
+fn test_suite_params_filters() {
+    let json = json!({
+        "suite": "/path/to/suite.yaml",
+        "tags": ["fast"],
+        "skip_tags": ["e2e"],
+        "only": "invoice"
+    });
+
+    let params: TestSuiteParams = serde_json::from_value(json).unwrap();
+    let filter = params.filter();
+    assert_eq!(filter.tags, vec!["fast"]);
+    assert_eq!(filter.skip_tags, vec!["e2e"]);
+    assert_eq!(filter.only.as_deref(), Some("invoice"));
+}
+
+#[test]
+fn test_convert_suite_result_with_filtered() {
+    let suite_result = SuiteResult {
+        total: 2,
+        passed: 1,
+        failed: 0,
+        skipped: 1,
+        duration: Duration::from_millis(100),
+        spent: Default::default(),
+        budget: None,
+        preflight: None,
+        tests: vec![
+            TestResult {
+                name: "fast".to_string(),
+                url: "http://example.com/1".to_string(),
+                method: "GET".to_string(),
+                passed: true,
+                duration: Duration::from_millis(100),
+                assertions: vec![],
+                error: None,
+                error_category: None,
+                skipped: None,
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
+            },
+            TestResult {
+                name: "slow".to_string(),
+                url: "http://example.com/2".to_string(),
+                method: "GET".to_string(),
+                passed: false,
+                duration: Duration::ZERO,
+                assertions: vec![],
+                error: None,
+                error_category: None,
+                skipped: Some(SkipReason::Filtered("missing tag 'fast'".to_string())),
+                http_version: None,
+                encoded_size: None,
+                extends: None,
+                latency: Vec::new(),
+            },
+        ],
+    };
+
+    let response = convert_suite_result(suite_result);
+    assert_eq!(response.status, "passed");
+    assert_eq!(response.skipped, 1);
+    assert_eq!(
+        response.tests[1].skipped.as_deref(),
+        Some("filtered: missing tag 'fast'")
+    );
+    assert_eq!(response.summary, "1 of 1 tests passed in 100ms (1 skipped)");
+}
+
+#[test]
+fn test_compliance_params_locale_amount() {
+    let json = json!({
+        "url": "http://localhost:3000/api/test",
+        "expected_pricing": "0,01"
+    });
+
+    let err = serde_json::from_value::<CheckComplianceParams>(json).unwrap_err();
+    assert!(err.to_string().contains("did you mean 0.01?"));
+}
+
+#[tokio::test]
+async fn test_run_suite_budget_param_overrides_suite() {
+    // Given: A suite with a 5.00 USDC budget whose one test fails to connect
+    let dir = tempfile::tempdir().unwrap();
+    let suite = dir.path().join("suite.yaml");
+    std::fs::write(
+        &suite,
+        "budget:\n  max_total: \"5.00\"\ntests:\n  - name: unreachable\n    url: http://127.0.0.1:1/api\n    expect:\n      status: 402\n",
+    )
+    .unwrap();
+    let params = |budget: &str| -> TestSuiteParams {
+        serde_json::from_value(json!({ "suite": suite.to_str().unwrap(), "budget": budget }))
+            .unwrap()
+    };
+    let server = X402McpServer::new();
+
+    // When/Then: The budget param replaces the suite's cap
+    let response = server.run_suite(params("0.25")).await.unwrap();
+    assert_eq!(
+        response.summary,
+        format!(
+            "0 of 1 tests passed in {}ms, spent 0 of 0.25 USDC",
+            response.duration_ms
+        )
+    );
+
+    // And: A budget that is not a positive amount is rejected
+    for budget in ["0", "abc"] {
+        let err = server.run_suite(params(budget)).await.unwrap_err();
+        assert!(
+            err.message.contains("expected a positive amount"),
+            "{}",
+            budget
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_run_suite_preflight_failure_is_an_error() {
+    // Given: A suite whose only test targets a closed port
+    let dir = tempfile::tempdir().unwrap();
+    let suite = dir.path().join("suite.yaml");
+    std::fs::write(
+        &suite,
+        "tests:\n  - name: unreachable\n    url: http://127.0.0.1:1/api\n    expect:\n      status: 402\n",
+    )
+    .unwrap();
+    let params: TestSuiteParams =
+        serde_json::from_value(json!({ "suite": suite.to_str().unwrap(), "preflight": true }))
+            .unwrap();
+
+    // When: The suite runs with pre-flight checks
+    let err = X402McpServer::new().run_suite(params).await.unwrap_err();
+
+    // Then: One structured error names the failed check instead of a failed test
+    assert!(
+        err.message.starts_with("Pre-flight checks failed"),
+        "{}",
+        err.message
+    );
+    let data = err.data.unwrap();
+    assert_eq!(data["status"], "preflight_failed");
+    assert_eq!(data["preflight"]["checks"][1]["kind"], "tcp");
+    assert_eq!(data["preflight"]["checks"][1]["target"], "127.0.0.1:1");
+}
@@ -0,0 +1,116 @@
+// Invoice store benchmarks
+//
+// Target: lookups under a microsecond with 100k invoices stored, and
+// eviction sweeps whose cost depends on what they drop, not on the store's
+// size (compare the 10k and 100k runs).
+
+use chrono::{DateTime, Duration, Utc};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use x402_domain::{Amount, InvoiceMemo, MemoPrefix, ResourcePath, SolanaAddress};
+use x402_server::server::TEST_ADDRESSES;
+use x402_server::{Invoice, InvoiceStore};
+
+const SIZES: [usize; 2] = [10_000, 100_000];
+
+/// Invoices dropped by one sweep
+const SWEPT: usize = 100;
+
+fn invoice(i: usize, expires_at: DateTime<Utc>) -> Invoice {
+    let mut invoice = Invoice::new(
+        Amount::from_f64_rounded(0.01).unwrap(),
+        ResourcePath::new(format!("/api/{}", i % 50)).unwrap(),
+        SolanaAddress::new(TEST_ADDRESSES[i % TEST_ADDRESSES.len()]).unwrap(),
+        InvoiceMemo::generate(&MemoPrefix::default()),
+        300,
+    );
+    invoice.expires_at = expires_at;
+    invoice
+}
+
+/// Store of `size` unexpired invoices with room for `spare` more, and their memos
+fn filled(size: usize, spare: usize, now: DateTime<Utc>) -> (InvoiceStore, Vec<String>) {
+    let store = InvoiceStore::new(size + spare);
+    let memos = (0..size)
+        .map(|i| {
+            let invoice = invoice(i, now + Duration::days(1));
+            let memo = invoice.memo.to_string();
+            store.insert(invoice, now);
+            memo
+        })
+        .collect();
+    (store, memos)
+}
+
+fn bench_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("invoice_lookup");
+    let now = Utc::now();
+    for size in SIZES {
+        let (store, memos) = filled(size, 0, now);
+        for memo in memos.iter().step_by(2) {
+            store.record_verification(memo, "/api/0", now);
+        }
+
+        let mut i = 0;
+        group.bench_with_input(BenchmarkId::new("status", size), &memos, |b, memos| {
+            b.iter(|| {
+                i = (i + 7919) % memos.len();
+                black_box(store.status(&memos[i]))
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("get", size), &memos, |b, memos| {
+            b.iter(|| {
+                i = (i + 7919) % memos.len();
+                black_box(store.get(&memos[i]))
+            });
+        });
+        // Verifying a proof that was already used: the replay check
+        group.bench_with_input(
+            BenchmarkId::new("replay_check", size),
+            &memos,
+            |b, memos| {
+                b.iter(|| {
+                    i = (i + 2) % memos.len();
+                    black_box(store.record_verification(&memos[i & !1], "/api/0", now))
+                });
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("miss", size), &(), |b, _| {
+            b.iter(|| black_box(store.status("req-00000000-0000-4000-8000-000000000000")));
+        });
+    }
+    group.finish();
+}
+
+fn bench_eviction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("invoice_eviction");
+    let now = Utc::now();
+    for size in SIZES {
+        // Sweep SWEPT expired invoices out of `size` live ones
+        let (store, _) = filled(size, SWEPT, now);
+        group.bench_function(BenchmarkId::new("sweep_100_expired", size), |b| {
+            b.iter_batched(
+                || {
+                    for i in 0..SWEPT {
+                        store.insert(invoice(i, now + Duration::seconds(5)), now);
+                    }
+                },
+                |()| black_box(store.sweep_expired(now + Duration::minutes(1))),
+                BatchSize::PerIteration,
+            );
+        });
+
+        // Insert into a full store: the oldest entry is dropped
+        let (full, _) = filled(size, 0, now);
+        group.bench_function(BenchmarkId::new("insert_at_capacity", size), |b| {
+            b.iter_batched(
+                || invoice(0, now + Duration::days(1)),
+                |invoice| full.insert(invoice, now),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_lookup, bench_eviction);
+criterion_main!(benches);
@@ -0,0 +1,153 @@
+//! Loopback-only access to admin endpoints
+//!
+//! With `bind_address` set to a LAN interface or a wildcard, teammates and
+//! containers can reach the payment flow, but `/__admin/*` can reset stats
+//! and switch the simulation mode. Admin handlers take [`AdminPeer`], which
+//! answers 403 to non-loopback peers while `admin_loopback_only` is on.
+
+use std::future::{ready, Ready};
+use std::net::{IpAddr, Ipv4Addr};
+
+use actix_web::dev::Payload;
+use actix_web::error::InternalError;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse};
+
+use crate::server::Config;
+
+/// Address the mock binds unless configured otherwise
+pub const DEFAULT_BIND_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+/// True for loopback addresses, including IPv4-mapped ones such as
+/// `::ffff:127.0.0.1` seen on dual-stack `::` listeners
+pub fn is_loopback(ip: IpAddr) -> bool {
+    ip.to_canonical().is_loopback()
+}
+
+/// True when a peer at `peer` may call admin endpoints of a listener on `local`
+///
+/// A peer with the listener's own address is on the same machine (a client
+/// connecting to a specific interface IP). Requests without a peer address
+/// come from in-process test services and are allowed.
+pub fn admin_allowed(loopback_only: bool, peer: Option<IpAddr>, local: IpAddr) -> bool {
+    !loopback_only
+        || peer.is_none_or(|ip| is_loopback(ip) || ip.to_canonical() == local.to_canonical())
+}
+
+/// Extractor guarding admin handlers (403 for remote peers)
+///
+/// Reads `admin_loopback_only` from the app's [`Config`]; apps without one
+/// use the default, which restricts admin endpoints to loopback.
+#[derive(Debug, Clone, Copy)]
+pub struct AdminPeer;
+
+impl FromRequest for AdminPeer {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let loopback_only = req
+            .app_data::<web::Data<Config>>()
+            .is_none_or(|config| config.admin_loopback_only);
+        let peer = req.peer_addr().map(|addr| addr.ip());
+        let local = req.app_config().local_addr().ip();
+        if admin_allowed(loopback_only, peer, local) {
+            return ready(Ok(AdminPeer));
+        }
+
+        let peer = peer.map(|ip| ip.to_string()).unwrap_or_default();
+        println!(
+            "🚫 {} {} refused: admin endpoints are loopback-only (peer {})",
+            req.method(),
+            req.path(),
+            peer
+        );
+        let response = HttpResponse::Forbidden()
+            .insert_header(("Cache-Control", "no-store"))
+            .json(serde_json::json!({
+                "error": "Admin endpoint restricted",
+                "message": format!("{} only answers loopback clients, not {}", req.path(), peer),
+                "fix": "Call it from the machine running the mock, or set admin_loopback_only: false",
+            }));
+        ready(Err(InternalError::from_response(
+            "admin endpoint restricted",
+            response,
+        )
+        .into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use std::net::SocketAddr;
+
+    fn peer(addr: &str) -> SocketAddr {
+        addr.parse().unwrap()
+    }
+
+    #[test]
+    fn test_loopback_detection() {
+        assert!(is_loopback("127.0.0.1".parse().unwrap()));
+        assert!(is_loopback("::1".parse().unwrap()));
+        assert!(is_loopback("::ffff:127.0.0.1".parse().unwrap()));
+        assert!(!is_loopback("0.0.0.0".parse().unwrap()));
+        assert!(!is_loopback("192.168.1.20".parse().unwrap()));
+        assert!(!is_loopback("::ffff:10.0.0.5".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_same_host_peer_allowed_on_interface_bind() {
+        let lan: IpAddr = "192.168.1.10".parse().unwrap();
+        assert!(admin_allowed(true, Some(lan), lan));
+        assert!(!admin_allowed(
+            true,
+            Some("192.168.1.20".parse().unwrap()),
+            lan
+        ));
+        assert!(admin_allowed(true, None, lan));
+        assert!(admin_allowed(
+            false,
+            Some("192.168.1.20".parse().unwrap()),
+            lan
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_extractor_rejects_remote_peer() {
+        let config = web::Data::new(Config::builder().build().unwrap());
+        let (req, mut payload) = TestRequest::get()
+            .uri("/__admin/stats")
+            .app_data(config)
+            .peer_addr(peer("192.168.1.20:51000"))
+            .to_http_parts();
+
+        let err = AdminPeer::from_request(&req, &mut payload)
+            .await
+            .unwrap_err();
+        assert_eq!(err.error_response().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn test_extractor_allows_loopback_and_unrestricted() {
+        let restricted = web::Data::new(Config::builder().build().unwrap());
+        let (req, mut payload) = TestRequest::get()
+            .app_data(restricted)
+            .peer_addr(peer("[::ffff:127.0.0.1]:51000"))
+            .to_http_parts();
+        assert!(AdminPeer::from_request(&req, &mut payload).await.is_ok());
+
+        let open = web::Data::new(
+            Config::builder()
+                .admin_loopback_only(false)
+                .build()
+                .unwrap(),
+        );
+        let (req, mut payload) = TestRequest::get()
+            .app_data(open)
+            .peer_addr(peer("192.168.1.20:51000"))
+            .to_http_parts();
+        assert!(AdminPeer::from_request(&req, &mut payload).await.is_ok());
+    }
+}
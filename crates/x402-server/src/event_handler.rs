@@ -0,0 +1,499 @@
+//! Typed callbacks for what the payment handler does
+//!
+//! Register an [`EventHandler`] on a [`MockServerConfig`] to observe
+//! issued invoices, verification outcomes, policy decisions and error
+//! responses as Rust values, without polling `/__admin/events` or running a
+//! webhook receiver. Webhooks and the event log are fed by an
+//! [`EventHandler`] too, which runs before the registered ones.
+//!
+//! Handlers are called inline on the worker answering the request, in
+//! registration order, so they must return quickly and never block. Slow
+//! consumers should register a [`ChannelHandler`] and read owned
+//! [`ServerEvent`]s from its channel instead.
+//!
+//! ```rust,no_run
+//! use std::sync::{Arc, Mutex};
+//! use x402_server::event_handler::{EventContext, EventHandler};
+//! use x402_server::{Config, Invoice, MockServerConfig, TestServer};
+//!
+//! #[derive(Default)]
+//! struct Invoices(Mutex<Vec<Invoice>>);
+//!
+//! impl EventHandler for Invoices {
+//!     fn on_invoice_issued(&self, _context: &EventContext, invoice: &Invoice) {
+//!         self.0
+//!             .lock()
+//!             .expect("CRITICAL: Invoices lock poisoned - thread panic detected")
+//!             .push(invoice.clone());
+//!     }
+//! }
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let issued = Arc::new(Invoices::default());
+//! let server_config = MockServerConfig::builder()
+//!     .config(Config::default())
+//!     .event_handler(issued.clone())
+//!     .build()?;
+//! let server = TestServer::start(server_config).await?;
+//!
+//! reqwest::get(format!("{}/api/data", server.base_url())).await?;
+//! let invoices = issued
+//!     .0
+//!     .lock()
+//!     .expect("CRITICAL: Invoices lock poisoned - thread panic detected");
+//! assert_eq!(invoices[0].resource_path.as_str(), "/api/data");
+//! server.stop().await
+//! # }
+//! ```
+//!
+//! [`MockServerConfig`]: crate::MockServerConfig
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use x402_core::policy::{PolicyDecision, Request};
+use x402_core::verifier::{FailureReason, VerificationOutcome};
+
+use crate::events::EventLog;
+use crate::handlers::{EXPIRED_INVOICE, REPLAYED_PAYMENT};
+use crate::maintenance::{DEFAULT_MAINTENANCE_MESSAGE, MAINTENANCE};
+use crate::server::Invoice;
+use crate::webhooks::{EventType, WebhookDispatcher, WebhookEvent};
+
+/// The request an event happened on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventContext {
+    /// Generated per request; the same for every event of one request
+    pub request_id: String,
+    pub method: String,
+    pub path: String,
+    /// Price of the path in USDC (0 before it is known)
+    pub amount: f64,
+    /// `X-Payment-Proof`, once the request's proof was read
+    pub proof: Option<String>,
+}
+
+/// Error response the payment handler answered with
+///
+/// Mirrors the `error`, `message` and `code` fields of the JSON body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorEnvelope {
+    /// HTTP status code
+    pub status: u16,
+    /// Short error title, e.g. `Invalid payment amount`
+    pub error: String,
+    pub message: String,
+    /// Machine-readable code, for errors that have one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
+impl ErrorEnvelope {
+    pub fn new(status: u16, error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            error: error.into(),
+            message: message.into(),
+            code: None,
+        }
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+}
+
+/// Callbacks for the payment handler's events
+///
+/// Every method defaults to doing nothing. Implementations run inline on
+/// the request and must be fast; see the [module docs](self).
+pub trait EventHandler: Send + Sync {
+    /// A 402 with `invoice` was answered
+    fn on_invoice_issued(&self, _context: &EventContext, _invoice: &Invoice) {}
+
+    /// A payment proof was verified, successfully or not
+    fn on_verification(&self, _context: &EventContext, _outcome: &VerificationOutcome) {}
+
+    /// The runtime policies decided on `request`
+    ///
+    /// Evaluation errors arrive as a denial by policy `error`.
+    fn on_policy_decision(
+        &self,
+        _context: &EventContext,
+        _request: &Request,
+        _decision: &PolicyDecision,
+    ) {
+    }
+
+    /// The request was answered with an error, including 503 maintenance
+    fn on_error(&self, _context: &EventContext, _error: &ErrorEnvelope) {}
+}
+
+impl<T: EventHandler + ?Sized> EventHandler for Arc<T> {
+    fn on_invoice_issued(&self, context: &EventContext, invoice: &Invoice) {
+        (**self).on_invoice_issued(context, invoice);
+    }
+
+    fn on_verification(&self, context: &EventContext, outcome: &VerificationOutcome) {
+        (**self).on_verification(context, outcome);
+    }
+
+    fn on_policy_decision(
+        &self,
+        context: &EventContext,
+        request: &Request,
+        decision: &PolicyDecision,
+    ) {
+        (**self).on_policy_decision(context, request, decision);
+    }
+
+    fn on_error(&self, context: &EventContext, error: &ErrorEnvelope) {
+        (**self).on_error(context, error);
+    }
+}
+
+/// Handlers registered on a server, called in order
+#[derive(Default, Clone)]
+pub struct EventHandlers(Vec<Arc<dyn EventHandler>>);
+
+impl EventHandlers {
+    pub fn push(&mut self, handler: impl EventHandler + 'static) {
+        self.0.push(Arc::new(handler));
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &dyn EventHandler> {
+        self.0.iter().map(|handler| handler.as_ref())
+    }
+}
+
+// ============================================================================
+// Async variant
+// ============================================================================
+
+/// An event as an owned value, as sent by a [`ChannelHandler`]
+#[derive(Debug, Clone)]
+pub enum ServerEvent {
+    InvoiceIssued {
+        context: EventContext,
+        invoice: Invoice,
+    },
+    Verification {
+        context: EventContext,
+        outcome: VerificationOutcome,
+    },
+    /// An `Allow` decision is sent without its concurrency slot
+    PolicyDecision {
+        context: EventContext,
+        request: Request,
+        decision: PolicyDecision,
+    },
+    Error {
+        context: EventContext,
+        error: ErrorEnvelope,
+    },
+}
+
+/// Queues events on a bounded channel for a consumer on another task
+///
+/// Sending never waits: events are dropped (and counted) while the
+/// channel is full or after the receiver is gone.
+pub struct ChannelHandler {
+    sender: mpsc::Sender<ServerEvent>,
+    dropped: AtomicU64,
+}
+
+impl ChannelHandler {
+    /// Handler and the receiving end of its channel of `capacity` events
+    pub fn new(capacity: usize) -> (Self, mpsc::Receiver<ServerEvent>) {
+        let (sender, receiver) = mpsc::channel(capacity.max(1));
+        let handler = Self {
+            sender,
+            dropped: AtomicU64::new(0),
+        };
+        (handler, receiver)
+    }
+
+    /// Events discarded because the channel was full or closed
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn send(&self, event: ServerEvent) {
+        if self.sender.try_send(event).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl EventHandler for ChannelHandler {
+    fn on_invoice_issued(&self, context: &EventContext, invoice: &Invoice) {
+        self.send(ServerEvent::InvoiceIssued {
+            context: context.clone(),
+            invoice: invoice.clone(),
+        });
+    }
+
+    fn on_verification(&self, context: &EventContext, outcome: &VerificationOutcome) {
+        self.send(ServerEvent::Verification {
+            context: context.clone(),
+            outcome: outcome.clone(),
+        });
+    }
+
+    fn on_policy_decision(
+        &self,
+        context: &EventContext,
+        request: &Request,
+        decision: &PolicyDecision,
+    ) {
+        // A queued guard would hold the slot until the consumer caught up
+        let decision = match decision {
+            PolicyDecision::Allow { policy_id, .. } => PolicyDecision::Allow {
+                policy_id: policy_id.clone(),
+                guard: None,
+            },
+            other => other.clone(),
+        };
+        self.send(ServerEvent::PolicyDecision {
+            context: context.clone(),
+            request: request.clone(),
+            decision,
+        });
+    }
+
+    fn on_error(&self, context: &EventContext, error: &ErrorEnvelope) {
+        self.send(ServerEvent::Error {
+            context: context.clone(),
+            error: error.clone(),
+        });
+    }
+}
+
+// ============================================================================
+// Webhooks and event log
+// ============================================================================
+
+/// Turns events into [`WebhookEvent`]s for webhooks and, when registered,
+/// the `/__admin/events` log, which both get the same event
+pub(crate) struct EventFeed<'a> {
+    pub webhooks: &'a WebhookDispatcher,
+    pub log: Option<&'a EventLog>,
+}
+
+impl EventFeed<'_> {
+    fn emit(&self, event: WebhookEvent) {
+        if let Some(log) = self.log {
+            log.record(event.clone());
+        }
+        self.webhooks.emit(event);
+    }
+}
+
+impl EventHandler for EventFeed<'_> {
+    fn on_invoice_issued(&self, context: &EventContext, invoice: &Invoice) {
+        self.emit(WebhookEvent::new(
+            EventType::InvoiceIssued,
+            &context.request_id,
+            Some(invoice.memo.to_string()),
+            &context.path,
+            context.amount,
+            "payment_required",
+        ));
+    }
+
+    fn on_verification(&self, context: &EventContext, outcome: &VerificationOutcome) {
+        // The event carries the paid invoice's memo, so dashboards can match it up
+        let memo = match outcome {
+            VerificationOutcome::Verified { memo, .. } => Some(memo.clone()),
+            VerificationOutcome::Failed(_) => context.proof.clone(),
+        };
+        let event = |event_type, outcome| {
+            WebhookEvent::new(
+                event_type,
+                &context.request_id,
+                memo.clone(),
+                &context.path,
+                context.amount,
+                outcome,
+            )
+        };
+
+        let event = match outcome {
+            VerificationOutcome::Verified {
+                amount_delta,
+                replayed,
+                ..
+            } => {
+                let verified =
+                    event(EventType::PaymentVerified, "success").with_amount_delta(*amount_delta);
+                if *replayed {
+                    verified.with_reason(REPLAYED_PAYMENT)
+                } else {
+                    verified
+                }
+            }
+            VerificationOutcome::Failed(FailureReason::Rejected(detail)) => {
+                let failed = event(EventType::PaymentFailed, "failure");
+                match detail {
+                    Some(detail) => failed.with_reason(detail.as_str()),
+                    None => failed,
+                }
+            }
+            VerificationOutcome::Failed(FailureReason::Pending(_)) => {
+                event(EventType::PaymentFailed, "timeout")
+            }
+            VerificationOutcome::Failed(FailureReason::Replayed) => {
+                event(EventType::PaymentFailed, "replayed").with_reason(REPLAYED_PAYMENT)
+            }
+            VerificationOutcome::Failed(FailureReason::Expired { .. }) => {
+                event(EventType::PaymentFailed, "expired").with_reason(EXPIRED_INVOICE)
+            }
+            VerificationOutcome::Failed(FailureReason::AmountMismatch(mismatch)) => {
+                event(EventType::PaymentFailed, mismatch.code()).with_reason(mismatch.to_string())
+            }
+        };
+        self.emit(event);
+    }
+
+    fn on_policy_decision(
+        &self,
+        context: &EventContext,
+        _request: &Request,
+        decision: &PolicyDecision,
+    ) {
+        if let PolicyDecision::Deny { reason, .. } = decision {
+            self.emit(
+                WebhookEvent::new(
+                    EventType::PolicyDenied,
+                    &context.request_id,
+                    None,
+                    &context.path,
+                    context.amount,
+                    "denied",
+                )
+                .with_reason(reason.as_str()),
+            );
+        }
+    }
+
+    fn on_error(&self, context: &EventContext, error: &ErrorEnvelope) {
+        if error.code.as_deref() == Some(MAINTENANCE) {
+            let event = WebhookEvent::new(
+                EventType::Maintenance,
+                &context.request_id,
+                None,
+                &context.path,
+                context.amount,
+                MAINTENANCE,
+            );
+            // Only a rule's own message is a reason
+            self.emit(if error.message == DEFAULT_MAINTENANCE_MESSAGE {
+                event
+            } else {
+                event.with_reason(error.message.as_str())
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn context() -> EventContext {
+        EventContext {
+            request_id: "req-1".to_string(),
+            method: "GET".to_string(),
+            path: "/api/data".to_string(),
+            amount: 0.01,
+            proof: Some("proof-1".to_string()),
+        }
+    }
+
+    #[derive(Default)]
+    struct Errors(Mutex<Vec<u16>>);
+
+    impl EventHandler for Errors {
+        fn on_error(&self, _context: &EventContext, error: &ErrorEnvelope) {
+            self.0
+                .lock()
+                .expect("CRITICAL: Errors lock poisoned - thread panic detected")
+                .push(error.status);
+        }
+    }
+
+    #[test]
+    fn test_registered_handlers_share_state_through_arc() {
+        let errors = Arc::new(Errors::default());
+        let mut handlers = EventHandlers::default();
+        handlers.push(errors.clone());
+        handlers.push(Errors::default());
+
+        for handler in handlers.iter() {
+            handler.on_error(&context(), &ErrorEnvelope::new(400, "Bad", "bad"));
+            // Defaults do nothing
+            handler.on_verification(
+                &context(),
+                &VerificationOutcome::Failed(FailureReason::Replayed),
+            );
+        }
+        assert_eq!(handlers.len(), 2);
+        assert_eq!(
+            *errors
+                .0
+                .lock()
+                .expect("CRITICAL: Errors lock poisoned - thread panic detected"),
+            vec![400]
+        );
+    }
+
+    #[test]
+    fn test_channel_handler_drops_when_full() {
+        let (handler, mut receiver) = ChannelHandler::new(1);
+        let error = ErrorEnvelope::new(502, "Unavailable", "rpc down");
+        handler.on_error(&context(), &error);
+        handler.on_error(&context(), &error);
+
+        assert_eq!(handler.dropped(), 1);
+        match receiver.try_recv().unwrap() {
+            ServerEvent::Error { context, error } => {
+                assert_eq!(context.request_id, "req-1");
+                assert_eq!(error.status, 502);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_feed_logs_failures_under_the_proof() {
+        let webhooks = WebhookDispatcher::disabled();
+        let log = EventLog::new();
+        let feed = EventFeed {
+            webhooks: &webhooks,
+            log: Some(&log),
+        };
+        feed.on_verification(
+            &context(),
+            &VerificationOutcome::Failed(FailureReason::Replayed),
+        );
+        feed.on_error(&context(), &ErrorEnvelope::new(400, "Bad", "bad"));
+
+        let page = log.after(0);
+        assert_eq!(page.events.len(), 1);
+        let event = &page.events[0].event;
+        assert_eq!(event.memo.as_deref(), Some("proof-1"));
+        assert_eq!(event.outcome, "replayed");
+        assert_eq!(event.reason.as_deref(), Some(REPLAYED_PAYMENT));
+    }
+}
@@ -0,0 +1,107 @@
+//! Recent payment events served on `/__admin/events`
+//!
+//! Every event the payment handler emits for webhooks is also kept in a
+//! bounded in-memory log with a sequence number, so dashboards can poll for
+//! what happened since their last request. When the log is full the oldest
+//! event is dropped; a poll that asks for dropped events is told how many
+//! it missed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::webhooks::WebhookEvent;
+
+/// Polling endpoint (GET, `?after=<seq>`)
+pub const ADMIN_EVENTS_PATH: &str = "/__admin/events";
+
+/// Events kept for polling
+pub const EVENT_LOG_CAPACITY: usize = 512;
+
+/// An event with its position in the log (the first is 1)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: WebhookEvent,
+}
+
+/// `/__admin/events` response body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventsPage {
+    /// Events after the requested sequence number, oldest first
+    pub events: Vec<LoggedEvent>,
+    /// Cursor for the next poll (`after=`): the last sequence number issued
+    pub last_seq: u64,
+    /// Events after the cursor that were dropped before this poll
+    pub missed: u64,
+}
+
+/// Bounded log of recent events
+pub struct EventLog {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    events: VecDeque<LoggedEvent>,
+    last_seq: u64,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        Self::with_capacity(EVENT_LOG_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(Inner {
+                events: VecDeque::with_capacity(capacity),
+                last_seq: 0,
+            }),
+        }
+    }
+
+    /// Append an event, dropping the oldest when full
+    pub fn record(&self, event: WebhookEvent) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.last_seq += 1;
+        let seq = inner.last_seq;
+        if inner.events.len() == self.capacity {
+            inner.events.pop_front();
+        }
+        inner.events.push_back(LoggedEvent { seq, event });
+    }
+
+    /// Append events from a snapshot, oldest first
+    ///
+    /// They get new sequence numbers after the log's last one.
+    pub fn restore(&self, events: impl IntoIterator<Item = LoggedEvent>) {
+        for logged in events {
+            self.record(logged.event);
+        }
+    }
+
+    /// Events with a sequence number above `after`
+    pub fn after(&self, after: u64) -> EventsPage {
+        let inner = self.inner.lock().unwrap();
+        let oldest = inner.events.front().map_or(inner.last_seq + 1, |e| e.seq);
+        EventsPage {
+            events: inner
+                .events
+                .iter()
+                .filter(|e| e.seq > after)
+                .cloned()
+                .collect(),
+            last_seq: inner.last_seq,
+            missed: oldest.saturating_sub(after + 1),
+        }
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,9 +1,59 @@
-use actix_web::{web, HttpRequest, HttpResponse};
-use std::time::Duration;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::{Method, StatusCode};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+use x402_core::clock::VirtualClock;
+use x402_core::policy::{
+    ConcurrencyGuard, MatchStats, PolicyDecision, PolicyEngine, QuotaHeaders, QuotaStatus, Request,
+};
+use x402_core::receipt::{PaymentReceipt, RECEIPT_HEADER};
+use x402_core::redact::HeaderRedactor;
+use x402_core::solana_rpc::SolanaRpcClient;
+use x402_core::testing::parse_payment_proof;
+use x402_core::verifier::{
+    FailureReason, InvoiceTerms, PaymentProof, PaymentVerifier, VerificationOutcome,
+};
+use x402_domain::{Amount, DomainError, InvoiceMemo, ResourcePath};
 
 // Import configuration types from x402-core
 // Note: These types need to be available from x402-core or passed as app data
-use crate::server::{Config, InvoiceGenerator, PricingMatcher, SimulationMode};
+use crate::access::AdminPeer;
+use crate::event_handler::{ErrorEnvelope, EventContext, EventFeed, EventHandler, EventHandlers};
+use crate::events::EventLog;
+use crate::idle::IdleShutdown;
+use crate::invoices::InvoiceQuery;
+use crate::maintenance::{
+    MaintenanceRule, MaintenanceSwitch, DEFAULT_MAINTENANCE_MESSAGE, MAINTENANCE,
+};
+use crate::readiness::Readiness;
+use crate::responses::ResponseSpec;
+use crate::rpc::RpcSimulator;
+use crate::server::{
+    Config, InvoiceGenerator, PriceOutOfBounds, PricingMatcher, SimulationMode, SimulationSwitch,
+    ZeroPriceBehavior, ADMIN_CAPABILITIES,
+};
+use crate::snapshot::{ServerSnapshot, SnapshotSources, DEFAULT_SNAPSHOT_INVOICES};
+use crate::stats::{Outcome, RuleHitsSnapshot, StatsRegistry, StatsSnapshot, DEFAULT_PATTERN};
+use crate::tolerance::{AmountMismatch, PAYMENT_AMOUNT_HEADER};
+use crate::verifier::{RpcVerifier, Settlement, SimulatedVerifier};
+use crate::webhooks::WebhookDispatcher;
+
+/// `println!` a request line unless the server's `console_output` is off
+macro_rules! console {
+    ($req:expr, $($arg:tt)*) => {
+        if console_enabled(&$req) {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// False for servers configured without console output (e.g. a
+/// [`TestServer`](crate::TestServer)); apps without a [`Config`] print
+fn console_enabled(req: &HttpRequest) -> bool {
+    req.app_data::<web::Data<Config>>()
+        .is_none_or(|config| config.console_output)
+}
 
 // ============================================================================
 // Payment Proof Detection (Two-Phase Flow)
@@ -19,17 +69,70 @@ fn has_payment_proof(headers: &actix_web::http::header::HeaderMap) -> bool {
 }
 
 /// Extract payment proof from header
+///
+/// `None` when the value is not visible ASCII or fails
+/// [`parse_payment_proof`], e.g. is over its length limit.
 fn extract_payment_proof(headers: &actix_web::http::header::HeaderMap) -> Option<String> {
     headers
         .get("X-Payment-Proof")
         .and_then(|v| v.to_str().ok())
+        .and_then(|s| parse_payment_proof(s).ok())
         .map(|s| s.to_string())
 }
 
-/// Get simulation mode from header override or global config
+/// Amount declared in `X-Payment-Amount`, if sent
+///
+/// Without the header a simulated payment counts as paying the invoice
+/// exactly.
+fn declared_amount(
+    headers: &actix_web::http::header::HeaderMap,
+) -> Result<Option<Amount>, DomainError> {
+    let Some(value) = headers.get(PAYMENT_AMOUNT_HEADER) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .map_err(|_| DomainError::InvalidAmount("not a decimal number".into()))
+        .and_then(|value| Amount::parse_invoice_amount(value, None))
+        .map(Some)
+}
+
+/// 400 for an `X-Payment-Amount` that is not a USDC amount
+fn invalid_payment_amount(
+    req: &HttpRequest,
+    err: &DomainError,
+    events: &EventSink<'_>,
+    context: &EventContext,
+) -> HttpResponse {
+    console!(
+        req,
+        "⚠️  {} {} -> Invalid {} header ({})",
+        req.method(),
+        req.path(),
+        PAYMENT_AMOUNT_HEADER,
+        err
+    );
+
+    let error = ErrorEnvelope::new(
+        400,
+        "Invalid payment amount",
+        format!("{}: {}", PAYMENT_AMOUNT_HEADER, err),
+    );
+    events.each(|handler| handler.on_error(context, &error));
+
+    HttpResponse::BadRequest()
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "error": error.error,
+            "message": error.message,
+        }))
+}
+
+/// Get simulation mode from header override or the server's current mode
 fn get_simulation_mode(
     headers: &actix_web::http::header::HeaderMap,
-    config: &Config,
+    current: SimulationMode,
 ) -> SimulationMode {
     headers
         .get("X-Simulation-Mode")
@@ -40,67 +143,594 @@ fn get_simulation_mode(
             "timeout" => Some(SimulationMode::Timeout),
             _ => None,
         })
-        .unwrap_or(config.simulation_mode)
+        .unwrap_or(current)
+}
+
+/// Passes handler events to webhooks and the event log, then to the
+/// [`EventHandler`]s registered on the server
+struct EventSink<'a> {
+    feed: EventFeed<'a>,
+    registered: Option<&'a EventHandlers>,
+}
+
+impl EventSink<'_> {
+    fn each(&self, call: impl Fn(&dyn EventHandler)) {
+        call(&self.feed);
+        for handler in self.registered.into_iter().flat_map(EventHandlers::iter) {
+            call(handler);
+        }
+    }
+}
+
+/// Log the request headers (debug and trace), masking sensitive values
+fn log_request_headers(req: &HttpRequest, redactor: &HeaderRedactor) {
+    let headers = req
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.to_str().unwrap_or("<binary>")));
+    tracing::debug!(
+        "🔎 {} {} headers: {}",
+        req.method(),
+        req.path(),
+        redactor.format(headers)
+    );
+}
+
+// ============================================================================
+// Policy Enforcement
+// ============================================================================
+
+/// Evaluate the request against the runtime policies
+///
+/// `memo` is the invoice about to be issued, if any; its UUID is recorded
+/// with the agent's spending. A `paid` request retries with a payment
+/// proof and was counted when its invoice was issued, so it only takes a
+/// concurrency slot. Returns the in-flight slot to hold for the rest of
+/// the request and the quota left, or the denial when a policy denies it.
+fn enforce_policies(
+    engine: &PolicyEngine,
+    req: &HttpRequest,
+    amount: f64,
+    memo: Option<&InvoiceMemo>,
+    paid: bool,
+    events: &EventSink<'_>,
+    context: &EventContext,
+) -> Result<PolicyAllowance, PolicyDenial> {
+    let agent_id = req
+        .headers()
+        .get("X-Agent-Id")
+        .and_then(|v| v.to_str().ok())
+        .filter(|s| !s.is_empty())
+        .unwrap_or("anonymous")
+        .to_string();
+
+    let request = Request {
+        agent_id,
+        wallet_address: req
+            .headers()
+            .get("X-Wallet-Address")
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string()),
+        ip_address: req.peer_addr().map(|addr| addr.ip().to_string()),
+        endpoint: req.path().to_string(),
+        amount,
+        timestamp: engine.now(),
+        memo_id: memo.map(|m| m.uuid().to_string()),
+        request_id: Some(context.request_id.clone()),
+    };
+
+    let decision = if paid {
+        engine.evaluate_paid(&request)
+    } else {
+        engine.evaluate(&request)
+    };
+    let decision = decision.unwrap_or_else(|e| PolicyDecision::Deny {
+        reason: format!("Policy evaluation failed: {}", e),
+        policy_id: "error".to_string(),
+    });
+    events.each(|handler| handler.on_policy_decision(context, &request, &decision));
+
+    let (reason, policy_id) = match decision {
+        PolicyDecision::Allow { policy_id, guard } => {
+            let quota = engine.quota(&policy_id, &request.agent_id, request.timestamp);
+            return Ok(PolicyAllowance { guard, quota });
+        }
+        PolicyDecision::Deny { reason, policy_id } => (reason, policy_id),
+        // Fail closed on decisions this server doesn't know
+        _ => (
+            "Unsupported policy decision".to_string(),
+            "error".to_string(),
+        ),
+    };
+
+    console!(
+        req,
+        "🚫 {} {} -> 403 Policy denied (agent: {}, policy: {}, reason: {})",
+        req.method(),
+        req.path(),
+        request.agent_id,
+        policy_id,
+        reason
+    );
+
+    Err(PolicyDenial {
+        reason,
+        policy_id,
+        agent_id: request.agent_id,
+    })
+}
+
+/// A request the policies allowed
+struct PolicyAllowance {
+    guard: Option<ConcurrencyGuard>,
+    /// Allowance left under the allowing policy, this request included
+    quota: QuotaStatus,
+}
+
+/// Report the remaining quota on the response of an allowed request
+fn insert_quota_headers(response: &mut HttpResponse, names: &QuotaHeaders, quota: &QuotaStatus) {
+    for (name, value) in names.values(quota) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::try_from(name),
+            HeaderValue::try_from(value.as_str()),
+        ) {
+            response.headers_mut().insert(name, value);
+        }
+    }
+}
+
+/// Why a request was denied, answered with 403
+struct PolicyDenial {
+    reason: String,
+    policy_id: String,
+    agent_id: String,
+}
+
+impl PolicyDenial {
+    fn response(&self) -> HttpResponse {
+        HttpResponse::Forbidden()
+            .insert_header(("Content-Type", "application/json"))
+            .insert_header(("Cache-Control", "no-store"))
+            .json(serde_json::json!({
+                "error": "Policy denied",
+                "reason": self.reason,
+                "policy_id": self.policy_id,
+                "agent_id": self.agent_id,
+            }))
+    }
+}
+
+// ============================================================================
+// Pricing Bounds
+// ============================================================================
+
+/// 500 response for a price outside the invoice bounds (`out_of_bounds: error`)
+///
+/// The server refuses to issue an invoice a facilitator would reject and
+/// explains the misconfiguration instead.
+fn pricing_misconfigured(
+    req: &HttpRequest,
+    err: &PriceOutOfBounds,
+    events: &EventSink<'_>,
+    context: &EventContext,
+) -> HttpResponse {
+    console!(
+        req,
+        "⚠️  {} {} -> 500 Pricing misconfigured ({})",
+        req.method(),
+        req.path(),
+        err
+    );
+
+    let error = ErrorEnvelope::new(500, "Pricing misconfigured", err.to_string());
+    events.each(|handler| handler.on_error(context, &error));
+
+    HttpResponse::InternalServerError()
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "error": error.error,
+            "message": error.message,
+            "path": err.path,
+            "amount": err.amount,
+            "min_amount": err.min_amount,
+            "max_amount": err.max_amount,
+            "fix": "Adjust the price for this path or pricing.min_amount/max_amount, or set pricing.out_of_bounds: clamp",
+        }))
+}
+
+/// Response for a request whose path or price cannot go on an invoice
+///
+/// A path outside RFC 3986 is the client's mistake (400); a price that is
+/// not a valid amount is the config's (500).
+fn invoice_field_rejected(
+    req: &HttpRequest,
+    status: StatusCode,
+    err: &DomainError,
+    events: &EventSink<'_>,
+    context: &EventContext,
+) -> HttpResponse {
+    console!(
+        req,
+        "⚠️  {} {} -> {} Invoice not issued ({})",
+        req.method(),
+        req.path(),
+        status.as_u16(),
+        err
+    );
+
+    let error = ErrorEnvelope::new(status.as_u16(), "Invoice not issued", err.to_string());
+    events.each(|handler| handler.on_error(context, &error));
+
+    HttpResponse::build(status)
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "error": error.error,
+            "message": error.message,
+            "path": req.path(),
+        }))
+}
+
+// ============================================================================
+// Maintenance
+// ============================================================================
+
+/// 503 with `Retry-After` for a path in maintenance, instead of an invoice
+fn under_maintenance(
+    req: &HttpRequest,
+    rule: &MaintenanceRule,
+    pricing: &PricingMatcher,
+    generator: &InvoiceGenerator,
+    events: &EventSink<'_>,
+) -> HttpResponse {
+    let path = req.path();
+    console!(
+        req,
+        "🚧 {} {} -> 503 Maintenance (rule: {}, retry after {}s)",
+        req.method(),
+        path,
+        rule.pattern,
+        rule.retry_after_secs
+    );
+
+    let mut context = event_context(req, generator);
+    context.amount = pricing
+        .invoice_amount_for_request(req.method().as_str(), path)
+        .unwrap_or(0.0);
+    let error = ErrorEnvelope::new(
+        503,
+        "Service unavailable",
+        rule.message
+            .as_deref()
+            .unwrap_or(DEFAULT_MAINTENANCE_MESSAGE),
+    )
+    .with_code(MAINTENANCE);
+    events.each(|handler| handler.on_error(&context, &error));
+
+    HttpResponse::ServiceUnavailable()
+        .insert_header(("Retry-After", rule.retry_after_secs.to_string()))
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "error": error.error,
+            "code": MAINTENANCE,
+            "message": error.message,
+            "path": path,
+            "pattern": rule.pattern,
+            "retry_after_secs": rule.retry_after_secs,
+        }))
+}
+
+// ============================================================================
+// Method Handling
+// ============================================================================
+
+/// 204 answer to a plain OPTIONS request (CORS preflights never get here)
+fn options_response(allowed: &[&str]) -> HttpResponse {
+    HttpResponse::NoContent()
+        .insert_header(("Allow", allowed.join(", ")))
+        .finish()
+}
+
+/// 405 response listing the methods `req.path()` accepts
+fn method_not_allowed(req: &HttpRequest, allowed: &[&str]) -> HttpResponse {
+    console!(
+        req,
+        "🚫 {} {} -> 405 Method Not Allowed (allowed: {})",
+        req.method(),
+        req.path(),
+        allowed.join(", ")
+    );
+
+    HttpResponse::MethodNotAllowed()
+        .insert_header(("Allow", allowed.join(", ")))
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "error": "Method not allowed",
+            "message": format!("{} is not supported on {}", req.method(), req.path()),
+            "allowed": allowed,
+        }))
 }
 
 // ============================================================================
 // Payment Verification Handlers
 // ============================================================================
 
+/// Error `code` of a payment proof that was already verified
+///
+/// Servers answer replays with 409 unless [`Config::allow_replay`] is set;
+/// events for replayed proofs carry it as their `reason`.
+pub const REPLAYED_PAYMENT: &str = "replayed_payment";
+
+/// Error `code` of a payment for an invoice past its `expires_at`
+pub const EXPIRED_INVOICE: &str = "expired_invoice";
+
 /// Verify payment with success simulation
-async fn verify_payment_success(payment_proof: String, resource_path: String) -> HttpResponse {
-    println!(
+///
+/// Serves the path's `responses:` body when one is configured, else a JSON
+/// acknowledgement that also carries the receipt. Either way the receipt is
+/// sent in `X-Payment-Receipt`.
+async fn verify_payment_success(
+    req: &HttpRequest,
+    payment_proof: String,
+    resource_path: String,
+    response: Option<&ResponseSpec>,
+    receipt: PaymentReceipt,
+) -> HttpResponse {
+    console!(
+        req,
         "✅ Payment verification SUCCESS for proof: {} (resource: {})",
-        payment_proof, resource_path
+        payment_proof,
+        resource_path
     );
 
+    if let Some(spec) = response {
+        return configured_response(req, spec, &resource_path, Some(&receipt));
+    }
+
     HttpResponse::Ok()
+        .insert_header((RECEIPT_HEADER, receipt.encode()))
         .insert_header(("Content-Type", "application/json"))
         .json(serde_json::json!({
             "status": "success",
             "payment_proof": payment_proof,
             "message": "Payment accepted",
-            "resource": format!("Content for {}", resource_path)
+            "resource": format!("Content for {}", resource_path),
+            "receipt": receipt
+        }))
+}
+
+/// 200 with a path's `responses:` body, or 500 when it cannot be rendered
+fn configured_response(
+    req: &HttpRequest,
+    spec: &ResponseSpec,
+    resource_path: &str,
+    receipt: Option<&PaymentReceipt>,
+) -> HttpResponse {
+    let body = match spec.render() {
+        Ok(body) => body,
+        Err(e) => {
+            console!(req, "⚠️  Response for {} failed: {:#}", resource_path, e);
+            return HttpResponse::InternalServerError()
+                .insert_header(("Cache-Control", "no-store"))
+                .json(serde_json::json!({
+                    "error": "Response unavailable",
+                    "message": format!("{:#}", e)
+                }));
+        }
+    };
+    let mut builder = HttpResponse::Ok();
+    if let Some(receipt) = receipt {
+        builder.insert_header((RECEIPT_HEADER, receipt.encode()));
+    }
+    builder.insert_header(("Content-Type", spec.resolved_content_type()));
+    if let Some(encoding) = spec.encoding {
+        builder.insert_header(("Content-Encoding", encoding.as_str()));
+    }
+    builder.body(body)
+}
+
+/// Serve a path priced at 0 without an invoice or receipt
+///
+/// Sends the path's `responses:` body when one is configured, else a JSON
+/// acknowledgement. A payment proof sent anyway is ignored.
+fn serve_free_resource(req: &HttpRequest, config: &Config) -> HttpResponse {
+    let path = req.path();
+    console!(
+        req,
+        "🆓 {} {} -> 200 Free resource (price: 0)",
+        req.method(),
+        path
+    );
+
+    if let Some(spec) = config.response_for_path(path) {
+        return configured_response(req, spec, path, None);
+    }
+    HttpResponse::Ok()
+        .insert_header(("Content-Type", "application/json"))
+        .json(serde_json::json!({
+            "status": "free",
+            "message": "No payment required",
+            "resource": format!("Content for {}", path),
         }))
 }
 
-/// Verify payment with failure simulation
-async fn verify_payment_failure(payment_proof: String) -> HttpResponse {
-    println!(
-        "❌ Payment verification FAILURE for proof: {}",
+/// Reject a payment proof the verifier did not accept
+///
+/// `reason` is the verifier's detail, e.g. why a transaction did not pay.
+async fn verify_payment_failure(
+    req: &HttpRequest,
+    payment_proof: String,
+    reason: Option<&str>,
+) -> HttpResponse {
+    console!(
+        req,
+        "❌ Payment verification FAILURE for proof: {}{}",
+        payment_proof,
+        reason.map(|r| format!(" ({})", r)).unwrap_or_default()
+    );
+
+    let mut body = serde_json::json!({
+        "status": "failure",
+        "payment_proof": payment_proof,
+        "error": "Payment rejected",
+        "message": "Payment verification failed - invalid or expired proof"
+    });
+    if let Some(reason) = reason {
+        body["reason"] = serde_json::json!(reason);
+    }
+    HttpResponse::PaymentRequired()
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(body)
+}
+
+/// Reject a payment proof that was already verified
+async fn verify_payment_replayed(req: &HttpRequest, payment_proof: String) -> HttpResponse {
+    console!(
+        req,
+        "🔁 Payment verification REPLAY for proof: {}",
         payment_proof
     );
 
+    HttpResponse::Conflict()
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "status": "failure",
+            "payment_proof": payment_proof,
+            "error": "Payment already used",
+            "code": REPLAYED_PAYMENT,
+            "message": "This payment proof was already verified"
+        }))
+}
+
+/// Reject a payment for an invoice that expired before it was paid
+async fn verify_payment_expired(
+    req: &HttpRequest,
+    payment_proof: String,
+    expires_at: DateTime<Utc>,
+) -> HttpResponse {
+    console!(
+        req,
+        "⌛ Payment verification EXPIRED for proof: {} (invoice expired {})",
+        payment_proof,
+        expires_at.to_rfc3339()
+    );
+
     HttpResponse::PaymentRequired()
         .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
         .json(serde_json::json!({
             "status": "failure",
             "payment_proof": payment_proof,
-            "error": "Payment rejected",
-            "message": "Payment verification failed - invalid or expired proof"
+            "error": "Invoice expired",
+            "code": EXPIRED_INVOICE,
+            "expires_at": expires_at.to_rfc3339(),
+            "message": "The invoice for this payment proof expired; request a new invoice"
         }))
 }
 
-/// Verify payment with timeout simulation
-async fn verify_payment_timeout(payment_proof: String, delay_ms: u64) -> HttpResponse {
-    println!(
-        "⏱️  Payment verification TIMEOUT for proof: {} (delay: {}ms)",
-        payment_proof, delay_ms
+/// Reject a payment whose declared amount is outside the tolerance
+async fn verify_payment_mismatch(
+    req: &HttpRequest,
+    payment_proof: String,
+    mismatch: &AmountMismatch,
+) -> HttpResponse {
+    console!(
+        req,
+        "❌ Payment verification {} for proof: {} ({})",
+        mismatch.code().to_uppercase(),
+        payment_proof,
+        mismatch
     );
 
-    // Simulate timeout delay using tokio
-    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    HttpResponse::PaymentRequired()
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "status": "failure",
+            "payment_proof": payment_proof,
+            "error": "Payment amount does not match the invoice",
+            "code": mismatch.code(),
+            "invoice_amount": mismatch.invoiced.to_canonical_string(),
+            "paid_amount": mismatch.paid.to_canonical_string(),
+            "tolerance": mismatch.allowance.normalize().to_string(),
+            "message": mismatch.to_string()
+        }))
+}
+
+/// Answer a payment the verifier could not settle in time
+///
+/// The simulated verifier has already waited out `timeout_delay_ms`.
+async fn verify_payment_timeout(
+    req: &HttpRequest,
+    payment_proof: String,
+    detail: &str,
+) -> HttpResponse {
+    console!(
+        req,
+        "⏱️  Payment verification TIMEOUT for proof: {} ({})",
+        payment_proof,
+        detail
+    );
 
     HttpResponse::RequestTimeout()
         .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
         .json(serde_json::json!({
             "status": "timeout",
             "payment_proof": payment_proof,
             "error": "Request timeout",
-            "message": "Payment verification timed out"
+            "message": "Payment verification timed out",
+            "reason": detail
         }))
 }
 
+/// 502 when the verifier could not answer, e.g. an unreachable RPC endpoint
+fn verification_unavailable(
+    req: &HttpRequest,
+    err: &anyhow::Error,
+    events: &EventSink<'_>,
+    context: &EventContext,
+) -> HttpResponse {
+    console!(
+        req,
+        "⚠️  {} {} -> Payment verification unavailable ({:#})",
+        req.method(),
+        req.path(),
+        err
+    );
+
+    let error = ErrorEnvelope::new(
+        502,
+        "Payment verification unavailable",
+        format!("{:#}", err),
+    );
+    events.each(|handler| handler.on_error(context, &error));
+
+    HttpResponse::BadGateway()
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "error": error.error,
+            "message": error.message,
+        }))
+}
+
+/// Context for the events of `req`, under a new request ID
+fn event_context(req: &HttpRequest, generator: &InvoiceGenerator) -> EventContext {
+    EventContext {
+        request_id: generator.next_request_id(),
+        method: req.method().to_string(),
+        path: req.path().to_string(),
+        amount: 0.0,
+        proof: None,
+    }
+}
+
 // ============================================================================
 // Main Request Handler (Two-Phase x402 Flow)
 // ============================================================================
@@ -109,47 +739,221 @@ async fn verify_payment_timeout(payment_proof: String, delay_ms: u64) -> HttpRes
 ///
 /// PHASE 1: Request without X-Payment-Proof → 402 with invoice
 /// PHASE 2: Request with X-Payment-Proof → Simulate verification
+///
+/// Paths priced at exactly 0 get 200 without an invoice after policy
+/// enforcement, unless `zero_price_behavior` is `invoice`.
+///
+/// HEAD runs the same flow and drops the body. Plain OPTIONS gets 204 and
+/// methods the path does not accept get 405, both with an `Allow` header.
+///
+/// When a [`StatsRegistry`] is registered, each request that reaches the
+/// payment flow is counted against the pricing rule that matched it. A
+/// registered [`EventLog`] receives the same events as webhooks, and a
+/// [`SimulationSwitch`] replaces the configured simulation mode. Paths a
+/// registered [`MaintenanceSwitch`] covers get 503 before policies run.
 pub async fn payment_required_handler(
     req: HttpRequest,
     pricing: web::Data<PricingMatcher>,
     generator: web::Data<InvoiceGenerator>,
     config: web::Data<Config>,
+    webhooks: web::Data<WebhookDispatcher>,
+    policies: Option<web::Data<PolicyEngine>>,
+    stats: Option<web::Data<StatsRegistry>>,
+) -> HttpResponse {
+    let allowed = config.allowed_methods_for_path(req.path());
+    if req.method() == Method::OPTIONS {
+        return options_response(&allowed);
+    }
+    if !allowed.contains(&req.method().as_str()) {
+        return method_not_allowed(&req, &allowed);
+    }
+
+    if config.logs_request_headers() {
+        log_request_headers(&req, &config.header_redactor());
+    }
+
+    let start = Instant::now();
+    let events = EventSink {
+        feed: EventFeed {
+            webhooks: &webhooks,
+            log: req
+                .app_data::<web::Data<EventLog>>()
+                .map(|log| log.get_ref()),
+        },
+        registered: req
+            .app_data::<web::Data<EventHandlers>>()
+            .map(|handlers| handlers.get_ref()),
+    };
+    let mode = req
+        .app_data::<web::Data<SimulationSwitch>>()
+        .map_or(config.simulation_mode, |switch| switch.get());
+    let agent = req
+        .headers()
+        .get("X-Agent-Id")
+        .and_then(|v| v.to_str().ok())
+        .filter(|s| !s.is_empty());
+    let maintenance = req
+        .app_data::<web::Data<MaintenanceSwitch>>()
+        .and_then(|switch| switch.rule_for(req.path(), agent));
+    let mut response = match maintenance {
+        Some(rule) => under_maintenance(&req, &rule, &pricing, &generator, &events),
+        None => {
+            payment_flow(
+                &req,
+                &pricing,
+                &generator,
+                &config,
+                &events,
+                policies.as_ref().map(|p| p.get_ref()),
+                mode,
+            )
+            .await
+        }
+    };
+
+    if let Some(stats) = stats {
+        let pattern = pricing
+            .rule_for_request(req.method().as_str(), req.path())
+            .unwrap_or(DEFAULT_PATTERN);
+        stats.record(pattern, Outcome::from_response(&response), start.elapsed());
+    }
+    tracing::info!(
+        method = %req.method(),
+        path = req.path(),
+        status = response.status().as_u16(),
+        duration_ms = start.elapsed().as_millis() as u64,
+        "request"
+    );
+
+    // Allowed requests carry their remaining quota whatever the outcome
+    if let Some(quota) = req.extensions().get::<QuotaStatus>() {
+        insert_quota_headers(&mut response, &config.quota_headers, quota);
+    }
+
+    // Same status and headers as GET, no body
+    if req.method() == Method::HEAD {
+        return response.drop_body().map_into_boxed_body();
+    }
+    response
+}
+
+async fn payment_flow(
+    req: &HttpRequest,
+    pricing: &PricingMatcher,
+    generator: &InvoiceGenerator,
+    config: &Config,
+    events: &EventSink<'_>,
+    policies: Option<&PolicyEngine>,
+    mode: SimulationMode,
 ) -> HttpResponse {
     let path = req.path();
     let method = req.method();
     let headers = req.headers();
+    let mut context = event_context(req, generator);
+    let amount = match pricing.invoice_amount_for_request(method.as_str(), path) {
+        Ok(amount) => amount,
+        Err(err) => return pricing_misconfigured(req, &err, events, &context),
+    };
+    context.amount = amount;
+
+    // Free paths skip both phases unless zero-amount invoices are wanted
+    let free = amount == 0.0 && config.zero_price_behavior == ZeroPriceBehavior::Serve;
+
+    // Requests without proof are invoiced: take the memo now so policies
+    // record the same UUID the invoice carries
+    let memo = (!free && !has_payment_proof(headers)).then(|| generator.next_memo());
+
+    // ============================================================================
+    // Policy enforcement (when policies are configured)
+    // ============================================================================
+    // The slot is held until this handler returns, so concurrency limits
+    // cover the whole request including simulated verification delays
+    let paid = !free && memo.is_none();
+    let enforced =
+        policies.map(|e| enforce_policies(e, req, amount, memo.as_ref(), paid, events, &context));
+    let _slot = match enforced {
+        Some(Ok(allowance)) => {
+            req.extensions_mut().insert(allowance.quota);
+            allowance.guard
+        }
+        Some(Err(denial)) => return denial.response(),
+        None => None,
+    };
+
+    if free {
+        return serve_free_resource(req, config);
+    }
 
     // ============================================================================
     // PHASE 1: Check for payment proof
     // ============================================================================
-    if !has_payment_proof(headers) {
+    if let Some(memo) = memo {
         // No payment proof → Return 402 with invoice (Story 2.4)
-        let amount = pricing.get_price_for_path(path);
-        let invoice = generator.generate(amount, path);
+        let resource_path = match ResourcePath::new(path) {
+            Ok(resource_path) => resource_path,
+            Err(err) => {
+                return invoice_field_rejected(req, StatusCode::BAD_REQUEST, &err, events, &context)
+            }
+        };
+        let invoice_amount = match Amount::from_f64_rounded(amount) {
+            Ok(amount) => amount,
+            Err(err) => {
+                let status = StatusCode::INTERNAL_SERVER_ERROR;
+                return invoice_field_rejected(req, status, &err, events, &context);
+            }
+        };
+        let invoice = generator.generate_with_memo(
+            invoice_amount,
+            resource_path,
+            memo,
+            config.invoice_ttl_for_path(path),
+        );
         let invoice_header = invoice.format_www_authenticate();
 
-        println!(
-            "📨 {} {} -> 402 Payment Required (amount: {} SOL/USDC, recipient: {}, memo: {})",
-            method, path, amount, invoice.recipient, invoice.memo
+        console!(req,
+            "📨 {} {} -> 402 Payment Required (amount: {} SOL/USDC, recipient: {}{}, memo: {}, expires: {})",
+            method,
+            path,
+            amount,
+            invoice.recipient,
+            invoice
+                .route
+                .as_ref()
+                .map_or_else(String::new, |route| format!(" via route {}", route)),
+            invoice.memo,
+            invoice.expires_at.to_rfc3339()
         );
 
+        events.each(|handler| handler.on_invoice_issued(&context, &invoice));
+
+        let mut invoice_json = serde_json::json!({
+            "recipient": invoice.recipient,
+            "amount": invoice.amount.to_f64(),
+            "currency": invoice.currency,
+            "memo": invoice.memo,
+            "network": invoice.network,
+            "timestamp": invoice.timestamp.to_rfc3339(),
+            "expires_at": invoice.expires_at.to_rfc3339(),
+            "resource_path": invoice.resource_path,
+        });
+        if let (Some(signer), Some(signature)) = (&invoice.signer, &invoice.signature) {
+            invoice_json["signer"] = serde_json::json!(signer);
+            invoice_json["signature"] = serde_json::json!(signature);
+        }
+        if let Some(route) = &invoice.route {
+            invoice_json["route"] = serde_json::json!(route);
+        }
+
+        // no-store: intermediaries must never replay an invoice with an expired memo
         return HttpResponse::PaymentRequired()
             .insert_header(("WWW-Authenticate", invoice_header))
             .insert_header(("Content-Type", "application/json"))
+            .insert_header(("Cache-Control", "no-store"))
             .json(serde_json::json!({
                 "error": "Payment Required",
                 "message": "Please complete payment to access this resource",
-                "protocol": "x402-solana",
-                "invoice": {
-                    "recipient": invoice.recipient,
-                    "amount": invoice.amount,
-                    "currency": invoice.currency,
-                    "memo": invoice.memo,
-                    "network": invoice.network,
-                    "timestamp": invoice.timestamp.to_rfc3339(),
-                    "expires_at": invoice.expires_at.to_rfc3339(),
-                    "resource_path": invoice.resource_path,
-                },
+                "protocol": invoice.protocol.as_str(),
+                "invoice": invoice_json,
                 "path": path
             }));
     }
@@ -160,30 +964,566 @@ pub async fn payment_required_handler(
     let payment_proof = match extract_payment_proof(headers) {
         Some(proof) => proof,
         None => {
-            println!("⚠️  {} {} -> Invalid payment proof header", method, path);
+            console!(
+                req,
+                "⚠️  {} {} -> Invalid payment proof header",
+                method,
+                path
+            );
+            let error = ErrorEnvelope::new(
+                400,
+                "Invalid payment proof",
+                "X-Payment-Proof header is malformed",
+            );
+            events.each(|handler| handler.on_error(&context, &error));
             return HttpResponse::BadRequest()
                 .insert_header(("Content-Type", "application/json"))
+                .insert_header(("Cache-Control", "no-store"))
                 .json(serde_json::json!({
-                    "error": "Invalid payment proof",
-                    "message": "X-Payment-Proof header is malformed"
+                    "error": error.error,
+                    "message": error.message,
                 }));
         }
     };
+    context.proof = Some(payment_proof.clone());
+
+    // Get simulation mode (header override or the server's current mode)
+    let mode = get_simulation_mode(headers, mode);
+    let rpc = req.app_data::<web::Data<SolanaRpcClient>>();
+
+    console!(
+        req,
+        "🔍 {} {} -> Verifying payment (verifier: {}, mode: {:?}, proof: {})",
+        method,
+        path,
+        config.verifier,
+        mode,
+        payment_proof
+    );
+
+    let invoiced = match Amount::from_f64_rounded(amount) {
+        Ok(amount) => amount,
+        Err(err) => {
+            let status = StatusCode::INTERNAL_SERVER_ERROR;
+            return invoice_field_rejected(req, status, &err, events, &context);
+        }
+    };
+    let declared_amount = match declared_amount(headers) {
+        Ok(declared) => declared,
+        Err(err) => return invalid_payment_amount(req, &err, events, &context),
+    };
+
+    let now = generator.now();
+    let proof = PaymentProof {
+        proof: payment_proof.clone(),
+        declared_amount,
+        received_at: now,
+    };
+    let terms = InvoiceTerms {
+        memo: None,
+        recipient: None,
+        amount: invoiced,
+        resource: path.to_string(),
+    };
+    let settlement = Settlement::new(config.amount_tolerance)
+        .with_invoices(generator.invoices(), config.allow_replay);
+    let simulated;
+    let rpc_verifier;
+    let verifier: &dyn PaymentVerifier = match rpc {
+        Some(client) => {
+            rpc_verifier = RpcVerifier::new(client, settlement);
+            &rpc_verifier
+        }
+        None => {
+            let timeout = Duration::from_millis(config.timeout_delay_ms);
+            simulated = SimulatedVerifier::new(mode, timeout, settlement);
+            &simulated
+        }
+    };
+    let outcome = match verifier.verify(&proof, &terms).await {
+        Ok(outcome) => outcome,
+        Err(err) => return verification_unavailable(req, &err, events, &context),
+    };
+    events.each(|handler| handler.on_verification(&context, &outcome));
+
+    match outcome {
+        VerificationOutcome::Verified {
+            memo, amount_delta, ..
+        } => {
+            let mut receipt = PaymentReceipt::new(
+                memo.as_str(),
+                invoiced.to_canonical_string(),
+                path,
+                "success",
+                now,
+            )
+            .with_amount_delta(amount_delta);
+            if let Some(recipient) = generator.receipt_recipient(path) {
+                receipt = receipt.with_recipient(recipient.to_string());
+            }
+            generator.sign_receipt(&mut receipt);
+            verify_payment_success(
+                req,
+                payment_proof,
+                path.to_string(),
+                config.response_for_path(path),
+                receipt,
+            )
+            .await
+        }
+        VerificationOutcome::Failed(FailureReason::Rejected(detail)) => {
+            verify_payment_failure(req, payment_proof, detail.as_deref()).await
+        }
+        VerificationOutcome::Failed(FailureReason::Pending(detail)) => {
+            verify_payment_timeout(req, payment_proof, &detail).await
+        }
+        VerificationOutcome::Failed(FailureReason::Replayed) => {
+            verify_payment_replayed(req, payment_proof).await
+        }
+        VerificationOutcome::Failed(FailureReason::Expired { expires_at }) => {
+            verify_payment_expired(req, payment_proof, expires_at).await
+        }
+        VerificationOutcome::Failed(FailureReason::AmountMismatch(mismatch)) => {
+            verify_payment_mismatch(req, payment_proof, &mismatch).await
+        }
+    }
+}
+
+// ============================================================================
+// Admin Endpoints
+// ============================================================================
+
+/// GET /health/live: the process is up and answering
+///
+/// Always 200, even while startup hooks run or after one failed.
+pub async fn liveness_handler() -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "status": "ok",
+            "pid": std::process::id(),
+        }))
+}
+
+/// GET /health/ready (and its alias /health): readiness with the address
+/// and port actually bound, the PID and the admin capabilities this server
+/// supports
+///
+/// Open to every peer; the `/__admin/*` handlers below take [`AdminPeer`].
+/// Answers 503 with `status: not_ready` and the `reasons` until every
+/// readiness check passed.
+///
+/// `idle_shutdown_in_secs` and `max_lifetime_in_secs` count down to the
+/// automatic shutdowns (null when disabled); health pings do not reset the
+/// idle clock, any other request does.
+pub async fn health_handler(
+    config: web::Data<Config>,
+    idle: Option<web::Data<IdleShutdown>>,
+    readiness: Option<web::Data<Readiness>>,
+) -> HttpResponse {
+    let now = Instant::now();
+    let idle = idle.as_deref();
+    let readiness = readiness.as_deref();
+    let ready = readiness.is_none_or(|r| r.is_ready());
+    let mut response = if ready {
+        HttpResponse::Ok()
+    } else {
+        HttpResponse::ServiceUnavailable()
+    };
+    response
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "status": if ready { "ok" } else { "not_ready" },
+            "ready": ready,
+            "reasons": readiness.map(|r| r.reasons()).unwrap_or_default(),
+            "checks": readiness.map(|r| r.checks()).unwrap_or_default(),
+            "port": config.port,
+            "bind_address": config.bind_address,
+            "pid": std::process::id(),
+            "instance_id": config.instance_id(),
+            "capabilities": ADMIN_CAPABILITIES,
+            "idle_shutdown_in_secs": idle.and_then(|i| i.secs_until_idle_shutdown(now)),
+            "max_lifetime_in_secs": idle.and_then(|i| i.secs_until_max_lifetime(now)),
+        }))
+}
+
+/// GET /__admin/stats: per-pattern counters since start or last reset
+///
+/// Rule hit counters are included when the pricing matcher or policy
+/// engine has match stats enabled.
+pub async fn stats_handler(
+    req: HttpRequest,
+    _: AdminPeer,
+    stats: web::Data<StatsRegistry>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(stats_snapshot(&req, &stats))
+}
+
+/// POST /__admin/stats/reset: zero all counters without restarting
+pub async fn reset_stats_handler(
+    req: HttpRequest,
+    _: AdminPeer,
+    stats: web::Data<StatsRegistry>,
+) -> HttpResponse {
+    stats.reset();
+    for match_stats in rule_match_stats(&req).into_iter().flatten() {
+        match_stats.reset();
+    }
+    console!(req, "📊 Stats reset");
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(stats_snapshot(&req, &stats))
+}
+
+/// Match stats of the pricing matcher and the policy engine, if registered
+fn rule_match_stats(req: &HttpRequest) -> [Option<&MatchStats>; 2] {
+    [
+        req.app_data::<web::Data<PricingMatcher>>()
+            .and_then(|pricing| pricing.match_stats()),
+        req.app_data::<web::Data<PolicyEngine>>()
+            .and_then(|engine| engine.match_stats()),
+    ]
+}
+
+fn stats_snapshot(req: &HttpRequest, stats: &StatsRegistry) -> StatsSnapshot {
+    let mut snapshot = stats.snapshot();
+    let [pricing, policies] = rule_match_stats(req);
+    if pricing.is_some() || policies.is_some() {
+        snapshot.rule_hits = Some(RuleHitsSnapshot {
+            pricing: pricing.map(MatchStats::snapshot).unwrap_or_default(),
+            policies: policies.map(MatchStats::snapshot).unwrap_or_default(),
+        });
+    }
+    snapshot
+}
+
+/// Query of `/__admin/events`
+#[derive(Debug, serde::Deserialize)]
+pub struct EventsQuery {
+    /// Return events after this sequence number (default: all retained)
+    #[serde(default)]
+    pub after: u64,
+}
+
+/// GET /__admin/events?after=<seq>: events recorded since a previous poll
+pub async fn events_handler(
+    _: AdminPeer,
+    events: web::Data<EventLog>,
+    query: web::Query<EventsQuery>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(events.after(query.after))
+}
+
+/// GET /__admin/invoices?path=&status=&limit=: invoices and verified
+/// proofs the server remembers, oldest first
+pub async fn invoices_handler(
+    _: AdminPeer,
+    generator: web::Data<InvoiceGenerator>,
+    query: web::Query<InvoiceQuery>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(generator.invoices().page(&query))
+}
+
+/// GET /__admin/invoices/{memo}: one invoice or verified proof
+pub async fn invoice_handler(
+    _: AdminPeer,
+    generator: web::Data<InvoiceGenerator>,
+    memo: web::Path<String>,
+) -> HttpResponse {
+    match generator.invoices().get(&memo) {
+        Some(record) => HttpResponse::Ok()
+            .insert_header(("Cache-Control", "no-store"))
+            .json(&*record),
+        None => HttpResponse::NotFound()
+            .insert_header(("Cache-Control", "no-store"))
+            .json(serde_json::json!({
+                "error": "Invoice not found",
+                "memo": memo.as_str(),
+                "message": "No invoice or verified payment with this memo (never issued, expired or evicted)",
+            })),
+    }
+}
+
+/// Query of `GET /__admin/snapshot`
+#[derive(Debug, serde::Deserialize)]
+pub struct SnapshotQuery {
+    /// Keep at most this many of the newest invoices
+    #[serde(default = "default_snapshot_invoices")]
+    pub max_invoices: usize,
+}
+
+fn default_snapshot_invoices() -> usize {
+    DEFAULT_SNAPSHOT_INVOICES
+}
+
+/// GET /__admin/snapshot?max_invoices=: the whole server state, secrets
+/// masked (see [`crate::snapshot`])
+pub async fn snapshot_handler(
+    req: HttpRequest,
+    _: AdminPeer,
+    config: web::Data<Config>,
+    generator: web::Data<InvoiceGenerator>,
+    stats: web::Data<StatsRegistry>,
+    events: web::Data<EventLog>,
+    query: web::Query<SnapshotQuery>,
+) -> HttpResponse {
+    let engine = req.app_data::<web::Data<PolicyEngine>>();
+    let snapshot = ServerSnapshot::capture(
+        SnapshotSources {
+            config: &config,
+            invoices: generator.invoices(),
+            stats: stats_snapshot(&req, &stats),
+            events: &events,
+            policy: engine.map(|engine| engine.get_ref()),
+            now: generator.now(),
+        },
+        query.max_invoices,
+    );
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(snapshot)
+}
+
+/// POST /__admin/snapshot: add a snapshot's invoices, counters, events and
+/// policy state to this server
+pub async fn restore_snapshot_handler(
+    req: HttpRequest,
+    _: AdminPeer,
+    generator: web::Data<InvoiceGenerator>,
+    stats: web::Data<StatsRegistry>,
+    events: web::Data<EventLog>,
+    body: web::Bytes,
+) -> HttpResponse {
+    let snapshot: ServerSnapshot = match serde_json::from_slice(&body) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .insert_header(("Cache-Control", "no-store"))
+                .json(serde_json::json!({
+                    "error": "Invalid snapshot",
+                    "message": e.to_string(),
+                }))
+        }
+    };
+    let engine = req.app_data::<web::Data<PolicyEngine>>();
+    let summary = snapshot.restore_into(
+        generator.invoices(),
+        &stats,
+        &events,
+        engine.map(|engine| engine.get_ref()),
+        generator.now(),
+    );
+    console!(
+        req,
+        "📦 Snapshot restored: {} invoices, {} events",
+        summary.invoices,
+        summary.events
+    );
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(summary)
+}
+
+/// POST /rpc: fake Solana JSON-RPC (see [`crate::rpc`])
+///
+/// Errors are JSON-RPC error objects with status 200, as Solana nodes answer.
+pub async fn rpc_handler(rpc: web::Data<RpcSimulator>, body: web::Bytes) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(rpc.handle(&body))
+}
+
+/// Body of `POST /__admin/simulation-mode`
+#[derive(Debug, serde::Deserialize)]
+pub struct SimulationModeUpdate {
+    pub mode: String,
+}
 
-    // Get simulation mode (header override or global config)
-    let mode = get_simulation_mode(headers, &config);
+/// GET /__admin/simulation-mode: the mode used for requests without an
+/// `X-Simulation-Mode` header
+pub async fn simulation_mode_handler(
+    _: AdminPeer,
+    simulation: web::Data<SimulationSwitch>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({ "mode": simulation.get().as_str() }))
+}
 
-    println!(
-        "🔍 {} {} -> Verifying payment (mode: {:?}, proof: {})",
-        method, path, mode, payment_proof
+/// POST /__admin/simulation-mode: switch the mode without restarting
+pub async fn set_simulation_mode_handler(
+    req: HttpRequest,
+    _: AdminPeer,
+    simulation: web::Data<SimulationSwitch>,
+    update: web::Json<SimulationModeUpdate>,
+) -> HttpResponse {
+    let mode = match update.mode.parse::<SimulationMode>() {
+        Ok(mode) => mode,
+        Err(message) => {
+            return HttpResponse::BadRequest()
+                .insert_header(("Cache-Control", "no-store"))
+                .json(serde_json::json!({
+                    "error": "Invalid simulation mode",
+                    "message": message,
+                }))
+        }
+    };
+    let previous = simulation.set(mode);
+    console!(
+        req,
+        "🎛️  Simulation mode: {} -> {}",
+        previous.as_str(),
+        mode.as_str()
     );
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "mode": mode.as_str(),
+            "previous": previous.as_str(),
+        }))
+}
+
+/// Query of `DELETE /__admin/maintenance`
+#[derive(Debug, serde::Deserialize)]
+pub struct MaintenanceQuery {
+    /// Clear only the rules for this pattern (default: all)
+    pub pattern: Option<String>,
+}
+
+fn maintenance_rules(maintenance: &MaintenanceSwitch) -> serde_json::Value {
+    serde_json::json!({ "rules": maintenance.rules() })
+}
+
+/// GET /__admin/maintenance: the rules in effect
+pub async fn maintenance_handler(
+    _: AdminPeer,
+    maintenance: web::Data<MaintenanceSwitch>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(maintenance_rules(&maintenance))
+}
 
-    // Route to appropriate verification handler
-    match mode {
-        SimulationMode::Success => verify_payment_success(payment_proof, path.to_string()).await,
-        SimulationMode::Failure => verify_payment_failure(payment_proof).await,
-        SimulationMode::Timeout => {
-            verify_payment_timeout(payment_proof, config.timeout_delay_ms).await
+/// PUT /__admin/maintenance: put a path pattern into maintenance, replacing
+/// the rule with the same pattern and agent
+pub async fn set_maintenance_handler(
+    req: HttpRequest,
+    _: AdminPeer,
+    maintenance: web::Data<MaintenanceSwitch>,
+    body: web::Bytes,
+) -> HttpResponse {
+    let rule = serde_json::from_slice::<MaintenanceRule>(&body)
+        .map_err(anyhow::Error::from)
+        .and_then(|rule| rule.validate().map(|()| rule));
+    let rule = match rule {
+        Ok(rule) => rule,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .insert_header(("Cache-Control", "no-store"))
+                .json(serde_json::json!({
+                    "error": "Invalid maintenance rule",
+                    "message": e.to_string(),
+                }))
         }
+    };
+    console!(
+        req,
+        "🚧 Maintenance on: {}{} (retry after {}s)",
+        rule.pattern,
+        rule.agent
+            .as_deref()
+            .map_or_else(String::new, |agent| format!(" for agent {}", agent)),
+        rule.retry_after_secs
+    );
+    maintenance.set(rule);
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(maintenance_rules(&maintenance))
+}
+
+/// DELETE /__admin/maintenance?pattern=: clear one pattern's rules, or all
+///
+/// 404 when no rule has the pattern.
+pub async fn clear_maintenance_handler(
+    req: HttpRequest,
+    _: AdminPeer,
+    maintenance: web::Data<MaintenanceSwitch>,
+    query: web::Query<MaintenanceQuery>,
+) -> HttpResponse {
+    let removed = maintenance.clear(query.pattern.as_deref());
+    if let (Some(pattern), true) = (&query.pattern, removed.is_empty()) {
+        return HttpResponse::NotFound()
+            .insert_header(("Cache-Control", "no-store"))
+            .json(serde_json::json!({
+                "error": "Maintenance rule not found",
+                "pattern": pattern,
+                "message": "No path is in maintenance under this pattern",
+            }));
     }
+    console!(req, "✅ Maintenance off: {} rule(s) cleared", removed.len());
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(maintenance_rules(&maintenance))
+}
+
+/// Body of `POST /__admin/clock/advance`
+#[derive(Debug, serde::Deserialize)]
+pub struct ClockAdvance {
+    pub seconds: u64,
+}
+
+/// GET /__admin/clock: the server's current time and how far it was
+/// moved forward (`offset_seconds` is 0 without time travel)
+pub async fn clock_handler(
+    _: AdminPeer,
+    generator: web::Data<InvoiceGenerator>,
+    time_travel: Option<web::Data<VirtualClock>>,
+) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "now": generator.now().to_rfc3339(),
+            "time_travel": time_travel.is_some(),
+            "offset_seconds": time_travel.map_or(0.0, |clock| clock.offset_ms() as f64 / 1000.0),
+        }))
+}
+
+/// POST /__admin/clock/advance: move the virtual clock forward
+///
+/// Only servers started with `allow_time_travel` have one; others answer
+/// 403. Rate limit windows, spending caps and invoice deadlines all follow
+/// the moved clock.
+pub async fn advance_clock_handler(
+    req: HttpRequest,
+    _: AdminPeer,
+    time_travel: Option<web::Data<VirtualClock>>,
+    update: web::Json<ClockAdvance>,
+) -> HttpResponse {
+    let Some(clock) = time_travel else {
+        return HttpResponse::Forbidden()
+            .insert_header(("Cache-Control", "no-store"))
+            .json(serde_json::json!({
+                "error": "Time travel disabled",
+                "message": "Start the server with --allow-time-travel to move its clock",
+            }));
+    };
+    let now: DateTime<Utc> = clock.advance(Duration::from_secs(update.seconds)).into();
+    console!(
+        req,
+        "⏩ Clock advanced {}s to {}",
+        update.seconds,
+        now.to_rfc3339()
+    );
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "now": now.to_rfc3339(),
+            "offset_seconds": clock.offset_ms() as f64 / 1000.0,
+        }))
 }
@@ -0,0 +1,166 @@
+//! Automatic shutdown after inactivity or a fixed lifetime
+//!
+//! CI jobs that crash between `mock` and `mock stop` would otherwise leave
+//! the server running until the runner is recycled. Every request except
+//! `/health`, `/health/live`, `/health/ready` and `/__admin/*` counts as
+//! activity; a watchdog stops the
+//! server through the same graceful path as Ctrl+C once `idle_timeout_secs`
+//! pass without any, or once `max_lifetime_secs` pass since start.
+
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::readiness::{HEALTH_LIVE_PATH, HEALTH_READY_PATH};
+use crate::server::HEALTH_PATH;
+
+/// Prefix of admin endpoints, which do not count as activity
+pub const ADMIN_PATH_PREFIX: &str = "/__admin/";
+
+/// How often the watchdog checks the deadlines
+pub const WATCHDOG_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Why the server shut itself down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// No activity for the idle timeout
+    Idle(Duration),
+    /// The maximum lifetime elapsed
+    MaxLifetime(Duration),
+}
+
+impl fmt::Display for ShutdownReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Idle(timeout) => write!(f, "idle for {}s", timeout.as_secs()),
+            Self::MaxLifetime(lifetime) => {
+                write!(f, "maximum lifetime of {}s reached", lifetime.as_secs())
+            }
+        }
+    }
+}
+
+/// Activity clock and deadlines of one server
+#[derive(Debug)]
+pub struct IdleShutdown {
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    started: Instant,
+    last_activity: Mutex<Instant>,
+}
+
+impl IdleShutdown {
+    /// Deadlines in seconds; 0 disables either
+    pub fn new(idle_timeout_secs: u64, max_lifetime_secs: u64) -> Self {
+        let now = Instant::now();
+        let enabled = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+        Self {
+            idle_timeout: enabled(idle_timeout_secs),
+            max_lifetime: enabled(max_lifetime_secs),
+            started: now,
+            last_activity: Mutex::new(now),
+        }
+    }
+
+    /// True when neither deadline is set
+    pub fn is_disabled(&self) -> bool {
+        self.idle_timeout.is_none() && self.max_lifetime.is_none()
+    }
+
+    /// True when a request for `path` resets the idle clock
+    pub fn counts_as_activity(path: &str) -> bool {
+        ![HEALTH_PATH, HEALTH_LIVE_PATH, HEALTH_READY_PATH].contains(&path)
+            && !path.starts_with(ADMIN_PATH_PREFIX)
+    }
+
+    /// Record activity now
+    pub fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Seconds until the idle timeout fires at `now` (None when disabled)
+    pub fn secs_until_idle_shutdown(&self, now: Instant) -> Option<u64> {
+        let last = *self.last_activity.lock().unwrap();
+        self.idle_timeout
+            .map(|timeout| timeout.saturating_sub(now.saturating_duration_since(last)))
+            .map(|left| left.as_secs())
+    }
+
+    /// Seconds until the maximum lifetime is reached at `now` (None when disabled)
+    pub fn secs_until_max_lifetime(&self, now: Instant) -> Option<u64> {
+        self.max_lifetime
+            .map(|lifetime| lifetime.saturating_sub(now.saturating_duration_since(self.started)))
+            .map(|left| left.as_secs())
+    }
+
+    /// Deadline that has passed at `now`, if any
+    pub fn due(&self, now: Instant) -> Option<ShutdownReason> {
+        if let Some(lifetime) = self.max_lifetime {
+            if now.saturating_duration_since(self.started) >= lifetime {
+                return Some(ShutdownReason::MaxLifetime(lifetime));
+            }
+        }
+        let last = *self.last_activity.lock().unwrap();
+        match self.idle_timeout {
+            Some(timeout) if now.saturating_duration_since(last) >= timeout => {
+                Some(ShutdownReason::Idle(timeout))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_by_default_values() {
+        let idle = IdleShutdown::new(0, 0);
+        let later = Instant::now() + Duration::from_secs(3600);
+        assert!(idle.is_disabled());
+        assert_eq!(idle.due(later), None);
+        assert_eq!(idle.secs_until_idle_shutdown(later), None);
+        assert_eq!(idle.secs_until_max_lifetime(later), None);
+    }
+
+    #[test]
+    fn test_idle_timeout_and_touch() {
+        let start = Instant::now();
+        let idle = IdleShutdown::new(10, 0);
+        assert_eq!(idle.secs_until_idle_shutdown(start), Some(10));
+        assert_eq!(idle.due(start + Duration::from_secs(5)), None);
+        assert_eq!(
+            idle.due(start + Duration::from_secs(11)),
+            Some(ShutdownReason::Idle(Duration::from_secs(10)))
+        );
+
+        idle.touch();
+        assert_eq!(idle.due(Instant::now() + Duration::from_secs(5)), None);
+    }
+
+    #[test]
+    fn test_max_lifetime_ignores_activity() {
+        let idle = IdleShutdown::new(0, 60);
+        idle.touch();
+        let later = Instant::now() + Duration::from_secs(61);
+        assert_eq!(
+            idle.due(later),
+            Some(ShutdownReason::MaxLifetime(Duration::from_secs(60)))
+        );
+        assert_eq!(idle.secs_until_max_lifetime(later), Some(0));
+        assert_eq!(
+            ShutdownReason::MaxLifetime(Duration::from_secs(60)).to_string(),
+            "maximum lifetime of 60s reached"
+        );
+    }
+
+    #[test]
+    fn test_admin_paths_are_not_activity() {
+        assert!(IdleShutdown::counts_as_activity("/api/data"));
+        assert!(IdleShutdown::counts_as_activity("/healthz"));
+        assert!(!IdleShutdown::counts_as_activity("/health"));
+        assert!(!IdleShutdown::counts_as_activity("/health/ready"));
+        assert!(!IdleShutdown::counts_as_activity("/__admin/stats"));
+    }
+}
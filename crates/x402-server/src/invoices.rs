@@ -0,0 +1,672 @@
+//! Issued invoices and verified payment proofs, looked up by memo
+//!
+//! Every invoice the mock issues and every proof it verifies goes through
+//! one [`InvoiceStore`]: payment verification and replay protection, the
+//! fake RPC's `getTransaction` and `/__admin/invoices` all read from it.
+//!
+//! The store is a sharded hash map keyed by memo, so a lookup is one hash
+//! plus a read lock on one shard. Each shard also indexes its entries by
+//! path, by status and, for unpaid invoices, by expiry bucket. The store is
+//! bounded: an insert first drops unpaid invoices whose bucket has passed,
+//! and if the shard is still full it drops its oldest entry. Both cost the
+//! number of entries dropped, not the size of the store. A proof dropped
+//! this way is no longer recognized as a replay.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::server::Invoice;
+use x402_domain::{IdFormat, InvoiceMemo};
+
+/// Lookup endpoint (GET, `?path=&status=&limit=&sort=`, or `/<memo>`)
+pub const ADMIN_INVOICES_PATH: &str = "/__admin/invoices";
+
+/// Invoices and proofs kept by default
+pub const DEFAULT_MAX_INVOICES: usize = 100_000;
+
+/// Entries returned by a listing without `limit`
+pub const DEFAULT_LIST_LIMIT: usize = 100;
+
+/// Width of an expiry bucket; unpaid invoices outlive their expiry by less
+const EXPIRY_BUCKET_SECS: i64 = 10;
+
+/// Shards of a large store
+const SHARDS: usize = 16;
+
+/// Smallest per-shard capacity worth sharding for; smaller stores use one
+/// shard so eviction is exactly oldest-first
+const MIN_SHARD_CAPACITY: usize = 1024;
+
+/// Where a memo is in the payment flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvoiceStatus {
+    /// Invoiced, no verified payment yet
+    Issued,
+    /// A payment proof with this memo was verified
+    Verified,
+}
+
+/// What the store knows about one memo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceRecord {
+    pub memo: String,
+    /// Invoiced path, or the request path of a proof never invoiced here
+    pub path: String,
+    pub status: InvoiceStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<DateTime<Utc>>,
+    /// The issued invoice; `None` for a proof this server never invoiced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub invoice: Option<Invoice>,
+}
+
+/// Filter of [`InvoiceStore::find`], also the `/__admin/invoices` query
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InvoiceQuery {
+    pub path: Option<String>,
+    pub status: Option<InvoiceStatus>,
+    /// At most this many records (default [`DEFAULT_LIST_LIMIT`])
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub sort: InvoiceSort,
+}
+
+/// Order of [`InvoiceStore::find`] results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvoiceSort {
+    /// Oldest entry first
+    #[default]
+    Issued,
+    /// By memo; time-ordered memos (uuidv7, ulid) sort by their timestamp
+    Memo,
+}
+
+/// `/__admin/invoices` response body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoicesPage {
+    /// Matching records, oldest first
+    pub invoices: Vec<InvoiceRecord>,
+    /// Entries in the store
+    pub len: usize,
+    pub capacity: usize,
+    /// Entries dropped since start to stay within capacity
+    pub evicted: u64,
+}
+
+/// Bounded, concurrent store of invoices by memo
+pub struct InvoiceStore {
+    shards: Box<[RwLock<Shard>]>,
+    hasher: RandomState,
+    capacity: usize,
+    shard_capacity: usize,
+    next_seq: AtomicU64,
+    evicted: AtomicU64,
+}
+
+struct Entry {
+    /// Shared so lookups hand out a reference count, not a deep copy
+    record: Arc<InvoiceRecord>,
+    /// Insertion order, for oldest-first eviction
+    seq: u64,
+    /// Expiry bucket while the invoice is unpaid
+    bucket: Option<i64>,
+}
+
+#[derive(Default)]
+struct Shard {
+    entries: HashMap<Arc<str>, Entry>,
+    by_path: HashMap<String, HashSet<Arc<str>>>,
+    by_status: HashMap<InvoiceStatus, HashSet<Arc<str>>>,
+    by_expiry: BTreeMap<i64, HashSet<Arc<str>>>,
+    by_seq: BTreeMap<u64, Arc<str>>,
+}
+
+impl InvoiceStore {
+    /// Store holding at most `capacity` entries (at least one)
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let shards = if capacity >= SHARDS * MIN_SHARD_CAPACITY {
+            SHARDS
+        } else {
+            1
+        };
+        Self {
+            shards: (0..shards).map(|_| RwLock::default()).collect(),
+            hasher: RandomState::new(),
+            capacity,
+            shard_capacity: capacity.div_ceil(shards),
+            next_seq: AtomicU64::new(0),
+            evicted: AtomicU64::new(0),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| {
+                shard
+                    .read()
+                    .expect("CRITICAL: Invoice shard lock poisoned - thread panic detected")
+                    .entries
+                    .len()
+            })
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Entries dropped since start to stay within capacity
+    pub fn evicted(&self) -> u64 {
+        self.evicted.load(Ordering::Relaxed)
+    }
+
+    fn shard(&self, memo: &str) -> &RwLock<Shard> {
+        let index = self.hasher.hash_one(memo) as usize % self.shards.len();
+        &self.shards[index]
+    }
+
+    /// Remember an issued invoice as unpaid
+    ///
+    /// A memo the store already knows keeps its status, so an invoice can
+    /// never turn a verified proof back into an unpaid one.
+    pub fn insert(&self, invoice: Invoice, now: DateTime<Utc>) {
+        let mut shard = self
+            .shard(invoice.memo.as_str())
+            .write()
+            .expect("CRITICAL: Invoice shard lock poisoned - thread panic detected");
+        if let Some(entry) = shard.entries.get_mut(invoice.memo.as_str()) {
+            if entry.record.invoice.is_none() {
+                Arc::make_mut(&mut entry.record).invoice = Some(invoice);
+            }
+            return;
+        }
+        let memo: Arc<str> = invoice.memo.as_str().into();
+        let bucket = expiry_bucket(&invoice.expires_at);
+        let record = InvoiceRecord {
+            memo: memo.to_string(),
+            path: invoice.resource_path.as_str().to_string(),
+            status: InvoiceStatus::Issued,
+            verified_at: None,
+            invoice: Some(invoice),
+        };
+        self.make_room(&mut shard, now);
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        shard.insert(memo, record, seq, Some(bucket));
+    }
+
+    /// Record a successful verification of the payment proof `memo` for `path`
+    ///
+    /// Returns `false` when the memo was already verified, i.e. the payment
+    /// is a replay. A proof for a memo never invoiced here is remembered
+    /// too, so its replays are caught as well.
+    pub fn record_verification(&self, memo: &str, path: &str, now: DateTime<Utc>) -> bool {
+        let mut shard = self
+            .shard(memo)
+            .write()
+            .expect("CRITICAL: Invoice shard lock poisoned - thread panic detected");
+        if let Some(entry) = shard.entries.get(memo) {
+            if entry.record.status == InvoiceStatus::Verified {
+                return false;
+            }
+            shard.mark_verified(memo, now);
+            return true;
+        }
+        self.make_room(&mut shard, now);
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let record = InvoiceRecord {
+            memo: memo.to_string(),
+            path: path.to_string(),
+            status: InvoiceStatus::Verified,
+            verified_at: Some(now),
+            invoice: None,
+        };
+        shard.insert(memo.into(), record, seq, None);
+        true
+    }
+
+    /// Add a record from a snapshot as it was, keeping its status
+    ///
+    /// Returns `false` if the store already knows the memo.
+    pub fn restore(&self, record: InvoiceRecord, now: DateTime<Utc>) -> bool {
+        let mut shard = self
+            .shard(&record.memo)
+            .write()
+            .expect("CRITICAL: Invoice shard lock poisoned - thread panic detected");
+        if shard.entries.contains_key(record.memo.as_str()) {
+            return false;
+        }
+        let bucket = match (record.status, &record.invoice) {
+            (InvoiceStatus::Issued, Some(invoice)) => Some(expiry_bucket(&invoice.expires_at)),
+            _ => None,
+        };
+        self.make_room(&mut shard, now);
+        let seq = self.next_seq.fetch_add(1, Ordering::Relaxed);
+        let memo: Arc<str> = record.memo.as_str().into();
+        shard.insert(memo, record, seq, bucket);
+        true
+    }
+
+    /// Status of `memo`, without copying its record
+    pub fn status(&self, memo: &str) -> Option<InvoiceStatus> {
+        let shard = self
+            .shard(memo)
+            .read()
+            .expect("CRITICAL: Invoice shard lock poisoned - thread panic detected");
+        shard.entries.get(memo).map(|entry| entry.record.status)
+    }
+
+    /// Record for `memo`
+    pub fn get(&self, memo: &str) -> Option<Arc<InvoiceRecord>> {
+        let shard = self
+            .shard(memo)
+            .read()
+            .expect("CRITICAL: Invoice shard lock poisoned - thread panic detected");
+        shard
+            .entries
+            .get(memo)
+            .map(|entry| Arc::clone(&entry.record))
+    }
+
+    /// Records matching `query`, oldest first unless sorted by memo
+    ///
+    /// Uses the path index when a path is given, else the status index.
+    pub fn find(&self, query: &InvoiceQuery) -> Vec<InvoiceRecord> {
+        let mut found: Vec<(u64, InvoiceRecord)> = Vec::new();
+        for shard in self.shards.iter() {
+            let shard = shard
+                .read()
+                .expect("CRITICAL: Invoice shard lock poisoned - thread panic detected");
+            let memos: Box<dyn Iterator<Item = &Arc<str>>> = match (&query.path, query.status) {
+                (Some(path), _) => Box::new(shard.by_path.get(path).into_iter().flatten()),
+                (None, Some(status)) => {
+                    Box::new(shard.by_status.get(&status).into_iter().flatten())
+                }
+                (None, None) => Box::new(shard.entries.keys()),
+            };
+            found.extend(
+                memos
+                    .filter_map(|memo| shard.entries.get(memo))
+                    .filter(|entry| query.status.is_none_or(|s| entry.record.status == s))
+                    .map(|entry| (entry.seq, InvoiceRecord::clone(&entry.record))),
+            );
+        }
+        match query.sort {
+            InvoiceSort::Issued => found.sort_unstable_by_key(|(seq, _)| *seq),
+            InvoiceSort::Memo => found.sort_by_cached_key(|(_, record)| memo_order(&record.memo)),
+        }
+        found
+            .into_iter()
+            .take(query.limit.unwrap_or(DEFAULT_LIST_LIMIT))
+            .map(|(_, record)| record)
+            .collect()
+    }
+
+    /// Drop unpaid invoices whose expiry bucket ended by `now`
+    ///
+    /// Returns how many were dropped.
+    pub fn sweep_expired(&self, now: DateTime<Utc>) -> usize {
+        let swept: usize = self
+            .shards
+            .iter()
+            .map(|shard| {
+                shard
+                    .write()
+                    .expect("CRITICAL: Invoice shard lock poisoned - thread panic detected")
+                    .sweep(now)
+            })
+            .sum();
+        self.evicted.fetch_add(swept as u64, Ordering::Relaxed);
+        swept
+    }
+
+    /// Matching records plus the store's size, for `/__admin/invoices`
+    pub fn page(&self, query: &InvoiceQuery) -> InvoicesPage {
+        InvoicesPage {
+            invoices: self.find(query),
+            len: self.len(),
+            capacity: self.capacity,
+            evicted: self.evicted(),
+        }
+    }
+
+    /// Free a slot in `shard`: expired invoices first, else the oldest entry
+    fn make_room(&self, shard: &mut Shard, now: DateTime<Utc>) {
+        let mut dropped = shard.sweep(now);
+        while shard.entries.len() >= self.shard_capacity {
+            let Some((_, oldest)) = shard.by_seq.pop_first() else {
+                break;
+            };
+            shard.remove(&oldest);
+            dropped += 1;
+        }
+        if dropped > 0 {
+            self.evicted.fetch_add(dropped as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Default for InvoiceStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_INVOICES)
+    }
+}
+
+impl Shard {
+    fn insert(&mut self, memo: Arc<str>, record: InvoiceRecord, seq: u64, bucket: Option<i64>) {
+        self.by_path
+            .entry(record.path.clone())
+            .or_default()
+            .insert(memo.clone());
+        self.by_status
+            .entry(record.status)
+            .or_default()
+            .insert(memo.clone());
+        if let Some(bucket) = bucket {
+            self.by_expiry
+                .entry(bucket)
+                .or_default()
+                .insert(memo.clone());
+        }
+        self.by_seq.insert(seq, memo.clone());
+        self.entries.insert(
+            memo,
+            Entry {
+                record: Arc::new(record),
+                seq,
+                bucket,
+            },
+        );
+    }
+
+    /// Remove `memo` from the map and every index; false if absent
+    fn remove(&mut self, memo: &str) -> bool {
+        let Some(entry) = self.entries.remove(memo) else {
+            return false;
+        };
+        unindex(&mut self.by_path, &entry.record.path, memo);
+        unindex(&mut self.by_status, &entry.record.status, memo);
+        if let Some(bucket) = entry.bucket {
+            unindex_bucket(&mut self.by_expiry, bucket, memo);
+        }
+        self.by_seq.remove(&entry.seq);
+        true
+    }
+
+    fn mark_verified(&mut self, memo: &str, now: DateTime<Utc>) {
+        let Some((key, entry)) = self.entries.get_key_value(memo) else {
+            return;
+        };
+        let (key, previous, bucket) = (key.clone(), entry.record.status, entry.bucket);
+        if let Some(entry) = self.entries.get_mut(memo) {
+            let record = Arc::make_mut(&mut entry.record);
+            record.status = InvoiceStatus::Verified;
+            record.verified_at = Some(now);
+            entry.bucket = None;
+        }
+        unindex(&mut self.by_status, &previous, memo);
+        if let Some(bucket) = bucket {
+            unindex_bucket(&mut self.by_expiry, bucket, memo);
+        }
+        self.by_status
+            .entry(InvoiceStatus::Verified)
+            .or_default()
+            .insert(key);
+    }
+
+    /// Remove unpaid invoices in buckets that ended by `now`
+    fn sweep(&mut self, now: DateTime<Utc>) -> usize {
+        let mut swept = 0;
+        while let Some(entry) = self.by_expiry.first_entry() {
+            if *entry.key() * EXPIRY_BUCKET_SECS > now.timestamp() {
+                break;
+            }
+            for memo in entry.remove() {
+                swept += usize::from(self.remove(&memo));
+            }
+        }
+        swept
+    }
+}
+
+/// Bucket whose end is the first bucket boundary at or after `expires_at`
+fn expiry_bucket(expires_at: &DateTime<Utc>) -> i64 {
+    let secs = expires_at.timestamp();
+    secs.div_euclid(EXPIRY_BUCKET_SECS) + i64::from(secs.rem_euclid(EXPIRY_BUCKET_SECS) != 0)
+}
+
+/// Remove `memo` from the set under `key`, dropping the set once empty
+fn unindex<K: Hash + Eq>(index: &mut HashMap<K, HashSet<Arc<str>>>, key: &K, memo: &str) {
+    if let Some(memos) = index.get_mut(key) {
+        memos.remove(memo);
+        if memos.is_empty() {
+            index.remove(key);
+        }
+    }
+}
+
+/// [`unindex`] for the expiry buckets
+fn unindex_bucket(index: &mut BTreeMap<i64, HashSet<Arc<str>>>, bucket: i64, memo: &str) {
+    if let Some(memos) = index.get_mut(&bucket) {
+        memos.remove(memo);
+        if memos.is_empty() {
+            index.remove(&bucket);
+        }
+    }
+}
+
+/// Sort key of a memo: its embedded timestamp, if any, then the memo
+///
+/// Memos without a timestamp (uuidv4, proofs never invoiced here) sort
+/// first, by text.
+fn memo_order(memo: &str) -> (Option<u64>, String) {
+    let timestamp = InvoiceMemo::new(memo)
+        .ok()
+        .and_then(|parsed| parsed.timestamp_ms())
+        .or_else(|| IdFormat::timestamp_ms(memo));
+    (timestamp, memo.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::TEST_ADDRESSES;
+    use x402_domain::{Amount, InvoiceMemo, ResourcePath, SolanaAddress};
+
+    const UUID: &str = "00000000-0000-4000-8000-000000000000";
+
+    /// Memo `<name>-<UUID>`, so tests can name their invoices
+    fn m(name: &str) -> String {
+        format!("{}-{}", name, UUID)
+    }
+
+    fn invoice(name: &str, path: &str, expires_at: DateTime<Utc>) -> Invoice {
+        let mut invoice = Invoice::new(
+            Amount::from_f64_rounded(0.01).unwrap(),
+            ResourcePath::new(path).unwrap(),
+            SolanaAddress::new(TEST_ADDRESSES[0]).unwrap(),
+            InvoiceMemo::new(m(name)).unwrap(),
+            300,
+        );
+        invoice.expires_at = expires_at;
+        invoice
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_verification_and_replay() {
+        let store = InvoiceStore::new(10);
+        store.insert(invoice("req-a", "/api/a", at(300)), at(0));
+        assert_eq!(store.status(&m("req-a")), Some(InvoiceStatus::Issued));
+
+        assert!(store.record_verification(&m("req-a"), "/api/a", at(5)));
+        assert!(!store.record_verification(&m("req-a"), "/api/a", at(6)));
+        let record = store.get(&m("req-a")).unwrap();
+        assert_eq!(record.status, InvoiceStatus::Verified);
+        assert_eq!(record.verified_at, Some(at(5)));
+        assert!(record.invoice.is_some());
+
+        // Proofs never invoiced here are remembered for replay detection
+        assert!(store.record_verification("external-1", "/api/b", at(7)));
+        assert!(!store.record_verification("external-1", "/api/b", at(8)));
+        assert_eq!(store.get("external-1").unwrap().path, "/api/b");
+        assert!(store.get("external-1").unwrap().invoice.is_none());
+
+        // Issuing an invoice for a verified memo does not reopen it
+        store.record_verification(&m("req-b"), "/api/b", at(9));
+        store.insert(invoice("req-b", "/api/b", at(300)), at(10));
+        let record = store.get(&m("req-b")).unwrap();
+        assert_eq!(record.status, InvoiceStatus::Verified);
+        assert!(record.invoice.is_some());
+        assert!(!store.record_verification(&m("req-b"), "/api/b", at(11)));
+    }
+
+    #[test]
+    fn test_find_uses_path_and_status() {
+        let store = InvoiceStore::new(10);
+        store.insert(invoice("req-1", "/api/a", at(300)), at(0));
+        store.insert(invoice("req-2", "/api/b", at(300)), at(1));
+        store.insert(invoice("req-3", "/api/a", at(300)), at(2));
+        store.record_verification(&m("req-3"), "/api/a", at(3));
+
+        let memos = |query: InvoiceQuery| -> Vec<String> {
+            let suffix = format!("-{}", UUID);
+            store
+                .find(&query)
+                .into_iter()
+                .map(|r| r.memo.strip_suffix(&suffix).unwrap_or(&r.memo).to_string())
+                .collect()
+        };
+        assert_eq!(
+            memos(InvoiceQuery {
+                path: Some("/api/a".to_string()),
+                ..Default::default()
+            }),
+            ["req-1", "req-3"]
+        );
+        assert_eq!(
+            memos(InvoiceQuery {
+                status: Some(InvoiceStatus::Issued),
+                ..Default::default()
+            }),
+            ["req-1", "req-2"]
+        );
+        assert_eq!(
+            memos(InvoiceQuery {
+                path: Some("/api/a".to_string()),
+                status: Some(InvoiceStatus::Verified),
+                limit: Some(5),
+                sort: InvoiceSort::Issued,
+            }),
+            ["req-3"]
+        );
+        assert_eq!(
+            memos(InvoiceQuery {
+                limit: Some(2),
+                ..Default::default()
+            }),
+            ["req-1", "req-2"]
+        );
+    }
+
+    #[test]
+    fn test_find_sorts_time_ordered_memos_by_timestamp() {
+        let store = InvoiceStore::new(10);
+        // Fresh generators, as one never goes back in time
+        let id = |format, ms| x402_domain::IdGenerator::new(format).generate(ms);
+        let (late, early) = (id(IdFormat::Uuidv7, 2_000), id(IdFormat::Uuidv7, 1_000));
+        let ulid = id(IdFormat::Ulid, 1_500);
+        for id in [late, early, ulid, UUID.to_string()] {
+            let mut invoice = invoice("req", "/api/a", at(300));
+            invoice.memo = InvoiceMemo::new(format!("req-{}", id)).unwrap();
+            store.insert(invoice, at(0));
+        }
+
+        let memos = |sort| -> Vec<String> {
+            store
+                .find(&InvoiceQuery {
+                    sort,
+                    ..Default::default()
+                })
+                .into_iter()
+                .map(|r| r.memo)
+                .collect()
+        };
+        let issued = memos(InvoiceSort::Issued);
+        // No timestamp first, then by timestamp across formats
+        let sorted = memos(InvoiceSort::Memo);
+        assert_eq!(
+            sorted,
+            [&issued[3], &issued[1], &issued[2], &issued[0]].map(String::clone)
+        );
+    }
+
+    #[test]
+    fn test_sweep_drops_only_expired_unpaid_invoices() {
+        let store = InvoiceStore::new(10);
+        store.insert(invoice("req-old", "/a", at(10)), at(0));
+        store.insert(invoice("req-paid", "/a", at(10)), at(0));
+        store.insert(invoice("req-new", "/a", at(100)), at(0));
+        store.record_verification(&m("req-paid"), "/a", at(1));
+
+        // The bucket holding expiry 10 ends at 10
+        assert_eq!(store.sweep_expired(at(9)), 0);
+        assert_eq!(store.sweep_expired(at(10)), 1);
+        assert_eq!(store.get(&m("req-old")).map(|r| r.memo.clone()), None);
+        assert!(store.get(&m("req-paid")).is_some());
+        assert!(store.get(&m("req-new")).is_some());
+        assert_eq!(store.evicted(), 1);
+    }
+
+    #[test]
+    fn test_capacity_evicts_expired_then_oldest() {
+        let store = InvoiceStore::new(3);
+        store.insert(invoice("req-1", "/a", at(1000)), at(0));
+        store.insert(invoice("req-2", "/a", at(20)), at(0));
+        store.insert(invoice("req-3", "/a", at(1000)), at(0));
+
+        // Full: the expired invoice goes first, though req-1 is older
+        store.insert(invoice("req-4", "/a", at(1000)), at(30));
+        assert!(store.get(&m("req-2")).is_none());
+        assert!(store.get(&m("req-1")).is_some());
+
+        // Nothing expired: the oldest entry goes
+        assert!(store.record_verification("external", "/a", at(31)));
+        assert!(store.get(&m("req-1")).is_none());
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.evicted(), 2);
+
+        // The indices follow evictions
+        let all = store.find(&InvoiceQuery {
+            path: Some("/a".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(all.len(), 3);
+    }
+
+    #[test]
+    fn test_large_store_is_sharded_within_capacity() {
+        let store = InvoiceStore::new(SHARDS * MIN_SHARD_CAPACITY);
+        assert_eq!(store.shards.len(), SHARDS);
+        for i in 0..SHARDS * MIN_SHARD_CAPACITY * 2 {
+            store.record_verification(&format!("p-{}", i), "/a", at(0));
+        }
+        assert_eq!(store.len(), store.capacity());
+        assert_eq!(store.evicted() as usize, SHARDS * MIN_SHARD_CAPACITY);
+    }
+}
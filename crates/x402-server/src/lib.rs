@@ -8,51 +8,115 @@
 //! ## Architecture
 //!
 //! - `server`: HTTP server setup and configuration
+//! - `access`: Loopback-only guard for `/__admin/*`
+//! - `event_handler`: Typed callbacks for invoices, verifications, policy decisions and errors
+//! - `events`: Recent payment events for `/__admin/events`
 //! - `handlers`: Request handlers implementing x402 protocol
+//! - `idle`: Shutdown after inactivity or a maximum lifetime
+//! - `invoices`: Bounded store of issued invoices and verified proofs by memo
+//! - `maintenance`: Paths answering 503 with `Retry-After`, switched at runtime
 //! - `process`: PID management and process lifecycle
+//! - `readiness`: Liveness and readiness checks, startup hooks
+//! - `responses`: Post-payment bodies per path (inline or file, gzip/deflate)
+//! - `routing`: Per-path invoice recipients with weighted splits
+//! - `rpc`: Fake Solana JSON-RPC on `/rpc` for payment verification tests
+//! - `scenario`: Config, policy and test suite bundles run against a `TestServer`
 //! - `lifecycle`: Start/stop/restart/status commands
+//! - `logging`: Request log lines (`log_level`, `log_format: pretty|json`)
+//! - `policy_server`: Policy engine sidecar for `x402-dev policy serve`
+//! - `signing`: Optional Ed25519 invoice signatures (`signing` feature)
+//! - `snapshot`: Whole-server state archives for bug reports (`/__admin/snapshot`)
+//! - `state_dir`: PID and cache directory (~/.x402dev, `X402_DEV_STATE_DIR` or temp)
+//! - `stats`: Per-path request statistics for `/__admin/stats`
+//! - `test_server`: In-process server on an ephemeral loopback port
+//! - `tls`: Optional https:// serving (PEM files or self-signed)
+//! - `tolerance`: Accepted differences between declared and invoiced amounts
+//! - `webhooks`: Event notifications POSTed to subscribed URLs
 //!
 //! ## Example Usage
 //!
 //! ```rust,no_run
-//! use x402_server::{start_server, MockServerConfig, PricingMatcher, InvoiceGenerator};
-//! use x402_server::{Config, PricingConfig, SimulationMode};
-//! use std::collections::HashMap;
+//! use x402_server::{start_server, Config, MockServerConfig, SimulationMode};
 //!
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
-//!     let config = Config {
-//!         port: 3402,
-//!         solana_rpc: "https://api.devnet.solana.com".to_string(),
-//!         log_level: "info".to_string(),
-//!         pricing: PricingConfig {
-//!             default: 0.01,
-//!             per_resource: HashMap::new(),
-//!         },
-//!         simulation_mode: SimulationMode::Success,
-//!         timeout_delay_ms: 5000,
-//!     };
-//!
-//!     let server_config = MockServerConfig {
-//!         port: 3402,
-//!         pricing_matcher: PricingMatcher::new(config.pricing.clone()),
-//!         invoice_generator: InvoiceGenerator::new(),
-//!         config: config.clone(),
-//!     };
+//!     let config = Config::builder()
+//!         .port(3402)
+//!         .default_price(0.01)
+//!         .resource_price("/api/premium/*", 0.05)
+//!         .simulation_mode(SimulationMode::Success)
+//!         .build()?;
+//!
+//!     let server_config = MockServerConfig::builder().config(config).build()?;
 //!
 //!     start_server(server_config).await
 //! }
 //! ```
 
+pub mod access;
+pub mod event_handler;
+pub mod events;
 pub mod handlers;
+pub mod idle;
+pub mod invoices;
 pub mod lifecycle;
+pub mod logging;
+pub mod maintenance;
+pub mod policy_server;
 pub mod process;
+pub mod readiness;
+pub mod responses;
+pub mod routing;
+pub mod rpc;
+pub mod scenario;
 pub mod server;
+pub mod signing;
+pub mod snapshot;
+pub mod state_dir;
+pub mod stats;
+pub mod test_server;
+pub mod tls;
+pub mod tolerance;
+pub mod verifier;
+pub mod webhooks;
 
 // Re-export main types for convenience
-pub use lifecycle::{restart_server, server_status, start_server, stop_server};
-pub use process::ProcessManager;
+pub use access::{AdminPeer, DEFAULT_BIND_ADDRESS};
+pub use event_handler::{
+    ChannelHandler, ErrorEnvelope, EventContext, EventHandler, EventHandlers, ServerEvent,
+};
+pub use events::{EventLog, EventsPage, LoggedEvent};
+pub use idle::{IdleShutdown, ShutdownReason};
+pub use invoices::{
+    InvoiceQuery, InvoiceRecord, InvoiceSort, InvoiceStatus, InvoiceStore, InvoicesPage,
+    DEFAULT_MAX_INVOICES,
+};
+pub use lifecycle::{
+    restart_server, server_status, start_policy_server, start_server, stop_server,
+};
+pub use logging::LogFormat;
+pub use maintenance::{MaintenanceRule, MaintenanceSwitch};
+pub use policy_server::{PolicySidecar, DEFAULT_POLICY_SERVER_PORT};
+pub use process::{PidMetadata, ProcessManager};
+pub use readiness::{Readiness, ReadinessCheck, StartupHook};
+pub use responses::{ResponseEncoding, ResponseSpec, MAX_RESPONSE_FILE_BYTES};
+pub use routing::{RecipientRoute, RecipientRouter, RouteMatch, WeightedRecipient};
+pub use rpc::{RpcBehavior, RpcConfig, RpcSimulator, RPC_PATH};
+pub use scenario::{
+    validate_scenario, Scenario, ScenarioIssue, ScenarioReport, ScenarioSection, ScenarioValidation,
+};
 pub use server::{
-    Config, Invoice, InvoiceGenerator, MockServer, MockServerConfig, PricingConfig, PricingMatcher,
-    SimulationMode,
+    Config, ConfigBuilder, Invoice, InvoiceGenerator, MatchExplanation, MatchKind, MockServer,
+    MockServerConfig, MockServerConfigBuilder, OutOfBounds, PortFallback, PriceCandidate,
+    PriceOutOfBounds, PricingConfig, PricingMatcher, SimulationMode, SimulationSwitch,
+    ZeroPriceBehavior, DEFAULT_INVOICE_TTL_SECONDS, DEFAULT_PORT_FALLBACK_ATTEMPTS,
+    SUPPORTED_METHODS,
 };
+pub use signing::SigningConfig;
+pub use snapshot::{RestoreSummary, ServerSnapshot, SnapshotManifest};
+pub use stats::{Outcome, PathStatsSnapshot, RuleHitsSnapshot, StatsRegistry, StatsSnapshot};
+pub use test_server::TestServer;
+pub use tls::TlsConfig;
+pub use tolerance::{AmountMismatch, AmountTolerance};
+pub use verifier::{RpcVerifier, Settlement, SimulatedVerifier};
+pub use webhooks::{EventType, WebhookConfig, WebhookDispatcher, WebhookEvent};
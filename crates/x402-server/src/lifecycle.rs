@@ -1,8 +1,18 @@
+use crate::maintenance::{MaintenanceRule, ADMIN_MAINTENANCE_PATH};
+use crate::policy_server::{run_policy_server, PolicySidecar};
 use crate::process::{
-    delete_pid_file, is_server_running, read_pid_file, stop_server_process, write_pid_file,
+    delete_pid_file, is_server_running, read_pid_metadata, remove_pid_file_of, stop_server_process,
+    try_claim_pid_file, write_pid_metadata, PidMetadata, MOCK_SERVER_INSTANCE,
+    POLICY_SERVER_INSTANCE,
 };
-use crate::server::{MockServer, MockServerConfig};
-use anyhow::{anyhow, Result};
+use crate::readiness::HEALTH_READY_PATH;
+use crate::server::{bind_listener, listen, write_env_file, MockServerConfig};
+use anyhow::{anyhow, bail, Context, Result};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use x402_core::policy::{AuditLog, AuditLogConfig, StateStoreConfig};
 
 // ============================================================================
 // Command Handlers
@@ -10,31 +20,50 @@ use anyhow::{anyhow, Result};
 
 /// Handle stop command
 pub async fn stop_server() -> Result<()> {
-    let pid =
-        read_pid_file().ok_or_else(|| anyhow!("No PID file found. Server is not running."))?;
+    let metadata = read_pid_metadata(MOCK_SERVER_INSTANCE)
+        .ok_or_else(|| anyhow!("No PID file found. Server is not running."))?;
 
-    if !is_server_running(pid) {
-        delete_pid_file()?;
+    // A PID reused by another process counts as stale, so it is never signalled
+    if !is_server_running(&metadata) {
+        remove_pid_file_of(MOCK_SERVER_INSTANCE, &metadata)?;
         println!("Server is not running (stale PID file removed)");
         std::process::exit(2); // Exit code 2: not running
     }
 
-    println!("Stopping server (PID: {})...", pid);
-    stop_server_process(pid)?;
-    delete_pid_file()?;
+    println!("Stopping server (PID: {})...", metadata.pid);
+    stop_server_process(&metadata)?;
+    remove_pid_file_of(MOCK_SERVER_INSTANCE, &metadata)?;
     println!("Server stopped successfully");
     Ok(())
 }
 
 /// Handle status command
+///
+/// A running server's maintenance rules are listed too; servers that do not
+/// answer `/__admin/maintenance` list nothing.
 pub async fn server_status() -> Result<()> {
-    match read_pid_file() {
-        Some(pid) => {
-            if is_server_running(pid) {
-                println!("Server is running (PID: {})", pid);
+    match read_pid_metadata(MOCK_SERVER_INSTANCE) {
+        Some(metadata) => {
+            let PidMetadata {
+                pid, port, bind, ..
+            } = metadata;
+            if is_server_running(&metadata) {
+                match (port, bind) {
+                    (Some(port), Some(bind)) => println!(
+                        "Server is running (PID: {}, port: {}, bind: {})",
+                        pid, port, bind
+                    ),
+                    (Some(port), None) => {
+                        println!("Server is running (PID: {}, port: {})", pid, port)
+                    }
+                    (None, _) => println!("Server is running (PID: {})", pid),
+                }
+                if let Some(port) = port {
+                    print_maintenance(bind, port).await;
+                }
                 std::process::exit(0);
             } else {
-                delete_pid_file()?;
+                remove_pid_file_of(MOCK_SERVER_INSTANCE, &metadata)?;
                 println!("Server is not running (stale PID removed)");
                 std::process::exit(2);
             }
@@ -46,14 +75,64 @@ pub async fn server_status() -> Result<()> {
     }
 }
 
+/// Print the maintenance rules of the server on `port`, if it lists them
+async fn print_maintenance(bind: Option<IpAddr>, port: u16) {
+    let url = local_url("http", bind, port, ADMIN_MAINTENANCE_PATH);
+    let Some(rules) = fetch_maintenance(&url).await else {
+        return;
+    };
+    if rules.is_empty() {
+        println!("Maintenance: none");
+        return;
+    }
+    println!("Maintenance: {} rule(s)", rules.len());
+    for rule in rules {
+        println!(
+            "  {}{}  503, Retry-After {}s{}",
+            rule.pattern,
+            rule.agent
+                .map_or_else(String::new, |agent| format!(" (agent {})", agent)),
+            rule.retry_after_secs,
+            rule.message
+                .map_or_else(String::new, |message| format!(": {}", message))
+        );
+    }
+}
+
+/// URL of `path` on a server bound to `bind`, via loopback for wildcard binds
+fn local_url(scheme: &str, bind: Option<IpAddr>, port: u16, path: &str) -> String {
+    let host = bind
+        .filter(|ip| !ip.is_unspecified())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    format!("{}://{}{}", scheme, SocketAddr::new(host, port), path)
+}
+
+async fn fetch_maintenance(url: &str) -> Option<Vec<MaintenanceRule>> {
+    #[derive(serde::Deserialize)]
+    struct Rules {
+        rules: Vec<MaintenanceRule>,
+    }
+    let response = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(2))
+        .build()
+        .ok()?
+        .get(url)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
+    response.json::<Rules>().await.ok().map(|r| r.rules)
+}
+
 /// Handle restart command
 pub async fn restart_server(config: MockServerConfig) -> Result<()> {
     // Stop if running
-    if let Some(pid) = read_pid_file() {
-        if is_server_running(pid) {
-            println!("Stopping server (PID: {})...", pid);
-            stop_server_process(pid)?;
-            delete_pid_file()?;
+    if let Some(metadata) = read_pid_metadata(MOCK_SERVER_INSTANCE) {
+        if is_server_running(&metadata) {
+            println!("Stopping server (PID: {})...", metadata.pid);
+            stop_server_process(&metadata)?;
+            remove_pid_file_of(MOCK_SERVER_INSTANCE, &metadata)?;
             println!("Server stopped");
         }
     }
@@ -68,25 +147,36 @@ pub async fn restart_server(config: MockServerConfig) -> Result<()> {
 // ============================================================================
 
 /// Start the mock facilitator server
+///
+/// The port is bound before the banner, so the PID file, the env file and
+/// the output all report the port actually in use after a fallback.
 pub async fn start_server(server_config: MockServerConfig) -> Result<()> {
-    let port = server_config.port;
-
-    // Check if already running
-    if let Some(pid) = read_pid_file() {
-        if is_server_running(pid) {
-            eprintln!("Server already running (PID: {})", pid);
-            std::process::exit(3); // Exit code 3: already running
-        } else {
-            // Clean up stale PID file
-            delete_pid_file()?;
+    let (instance, current) = claim_mock_pid_file(&server_config)?;
+
+    let listener = match bind_listener(&server_config.config) {
+        Ok(listener) => listener,
+        Err(e) => {
+            delete_pid_file(&instance)?;
+            return Err(e);
         }
+    };
+    let local_addr = listener
+        .local_addr()
+        .context("Failed to read the bound address")?;
+    let port = local_addr.port();
+    write_pid_metadata(
+        &instance,
+        &PidMetadata {
+            port: Some(port),
+            bind: Some(local_addr.ip()),
+            ..current
+        },
+    )?;
+    if let Some(path) = &server_config.env_file {
+        write_env_file(path, port)?;
     }
 
-    // Write PID file
-    let current_pid = std::process::id();
-    write_pid_file(current_pid)?;
-
-    println!("🚀 Starting x402 mock facilitator server on port {}", port);
+    println!("🚀 Starting x402 mock facilitator server on {}", local_addr);
     println!("📋 Server will respond with 402 Payment Required to all requests");
     println!(
         "💰 Default pricing: {} SOL/USDC",
@@ -110,18 +200,171 @@ pub async fn start_server(server_config: MockServerConfig) -> Result<()> {
         }
     }
 
+    if !server_config.config.webhooks.is_empty() {
+        println!(
+            "📡 Webhooks: {} subscription(s)",
+            server_config.config.webhooks.len()
+        );
+    }
+
+    if !server_config.config.policies.is_empty() {
+        println!(
+            "🛡️  Policy enforcement: {} policies (agent from X-Agent-Id)",
+            server_config.config.policies.len()
+        );
+    }
+
     println!("🌐 CORS enabled for frontend testing");
-    println!("🔢 PID: {}", current_pid);
+    println!("🔢 PID: {}", current.pid);
     println!();
     println!("Press Ctrl+C to stop the server");
     println!();
 
-    // Start HTTP server
-    let server = MockServer::new(server_config);
-    let result = server.run().await;
+    // Start HTTP server, then wait until it reports ready
+    let scheme = if server_config.config.tls.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    let ready_url = local_url(scheme, Some(local_addr.ip()), port, HEALTH_READY_PATH);
+    let ready_timeout = Duration::from_secs(server_config.config.ready_timeout_secs);
+    let result = match listen(server_config, listener, true) {
+        Ok(listening) => {
+            let handle = listening.server.handle();
+            let running = tokio::spawn(listening.run());
+            match wait_until_ready(&ready_url, ready_timeout).await {
+                Ok(()) => {
+                    println!("✅ Ready ({})", HEALTH_READY_PATH);
+                    running
+                        .await
+                        .context("HTTP server task failed")?
+                        .context("HTTP server error")
+                }
+                Err(e) => {
+                    handle.stop(true).await;
+                    let _ = running.await;
+                    Err(e)
+                }
+            }
+        }
+        Err(e) => Err(e),
+    };
 
     // Clean up PID file on shutdown
-    delete_pid_file()?;
+    delete_pid_file(&instance)?;
+
+    result
+}
+
+/// Poll `url` (a `/health/ready` endpoint) until it answers 200
+///
+/// Fails after `timeout` with the reasons from the last 503, so a failed
+/// startup hook is named in the error.
+pub async fn wait_until_ready(url: &str, timeout: Duration) -> Result<()> {
+    #[derive(serde::Deserialize)]
+    struct NotReady {
+        #[serde(default)]
+        reasons: Vec<String>,
+    }
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .danger_accept_invalid_certs(true)
+        .build()
+        .context("Failed to create the readiness client")?;
+    let deadline = Instant::now() + timeout;
+    let mut reasons = vec![format!("no answer from {}", url)];
+    loop {
+        if let Ok(response) = client.get(url).send().await {
+            if response.status().is_success() {
+                return Ok(());
+            }
+            if let Ok(body) = response.json::<NotReady>().await {
+                reasons = body.reasons;
+            }
+        }
+        if Instant::now() >= deadline {
+            bail!(
+                "Server did not become ready within {}s: {}\nFix: Check startup_hooks in your config, or raise ready_timeout_secs",
+                timeout.as_secs(),
+                reasons.join("; ")
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+/// Start the policy engine sidecar
+///
+/// The policy file is loaded before the PID file is written, so an invalid
+/// file fails fast without leaving a stale instance behind.
+pub async fn start_policy_server(
+    policy_file: PathBuf,
+    port: u16,
+    state: StateStoreConfig,
+    audit_log: Option<AuditLogConfig>,
+) -> Result<()> {
+    let mut sidecar = PolicySidecar::with_state(policy_file, &state)?;
+    if let Some(audit_log) = &audit_log {
+        sidecar = sidecar.with_decision_hook(Arc::new(AuditLog::open(audit_log)?));
+    }
+    let current = claim_pid_file(POLICY_SERVER_INSTANCE)?;
+
+    println!("🛡️  Starting x402 policy server on port {}", port);
+    println!(
+        "📋 Policy file: {} ({} policies)",
+        sidecar.path().display(),
+        sidecar.engine().policies().len()
+    );
+    println!("💾 Policy state: {}", state);
+    if let Some(audit_log) = &audit_log {
+        println!("📝 Policy audit log: {}", audit_log.path.display());
+    }
+    println!("🔌 POST /evaluate, GET /policies, POST /reload");
+    println!("🔢 PID: {}", current.pid);
+    println!();
+    println!("Press Ctrl+C to stop the server");
+    println!();
+
+    let result = run_policy_server(sidecar, port).await;
+
+    delete_pid_file(POLICY_SERVER_INSTANCE)?;
 
     result
 }
+
+/// Claim the mock's PID file, returning its instance name
+///
+/// Mocks sharing a SQLite policy state file (`--shared-state`) are meant to
+/// run side by side: when another mock holds the usual file, this one takes
+/// `mock-server-<port>` instead. `mock stop` and `mock status` manage the
+/// first; `mock list` shows them all.
+fn claim_mock_pid_file(server_config: &MockServerConfig) -> Result<(String, PidMetadata)> {
+    let config = &server_config.config;
+    let shares_state = !config.policies.is_empty() && !config.policy_state.backend.is_memory();
+    if !shares_state {
+        return Ok((
+            MOCK_SERVER_INSTANCE.to_string(),
+            claim_pid_file(MOCK_SERVER_INSTANCE)?,
+        ));
+    }
+    let current = PidMetadata::for_process(std::process::id());
+    if try_claim_pid_file(MOCK_SERVER_INSTANCE, &current)?.is_none() {
+        return Ok((MOCK_SERVER_INSTANCE.to_string(), current));
+    }
+    let instance = format!("{}-{}", MOCK_SERVER_INSTANCE, config.port);
+    let current = claim_pid_file(&instance)?;
+    Ok((instance, current))
+}
+
+/// Write the current PID for `instance`, exiting if it is already running
+///
+/// A PID file left by a crashed run, or naming a process that only reuses
+/// the old PID, is replaced.
+fn claim_pid_file(instance: &str) -> Result<PidMetadata> {
+    let current = PidMetadata::for_process(std::process::id());
+    if let Some(running) = try_claim_pid_file(instance, &current)? {
+        eprintln!("Server already running (PID: {})", running.pid);
+        std::process::exit(3); // Exit code 3: already running
+    }
+    Ok(current)
+}
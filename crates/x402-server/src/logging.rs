@@ -0,0 +1,274 @@
+//! Request log output (`log_level`, `log_format`)
+//!
+//! Request logs go through `tracing`. [`init`] installs a subscriber on
+//! stdout filtered at the configured `log_level`, writing human-readable
+//! lines (`pretty`) or one JSON object per line (`json`). Both start with
+//! an RFC3339 UTC timestamp with millisecond precision, so lines sort and
+//! parse the same way whatever the machine's locale or timezone. The
+//! startup banner is printed as before and is not part of the log.
+
+use anyhow::{bail, Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::io::IsTerminal;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::FormatTime;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+use crate::server::Config;
+
+/// Shape of log lines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// `<timestamp> <LEVEL> <target>: <message> <fields>`
+    #[default]
+    Pretty,
+    /// `{"timestamp", "level", "target", "message", ...fields}` per line
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!(
+                "Invalid log format: '{}'. Valid values: pretty, json",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogFormat::Pretty => write!(f, "pretty"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Parse a `log_level` value (`error`, `warn`, `info`, `debug`, `trace`)
+pub fn parse_level(level: &str) -> Result<Level> {
+    match level.parse() {
+        Ok(level) => Ok(level),
+        Err(_) => bail!(
+            "Invalid log level: '{}'\nFix: Use one of error, warn, info, debug, trace",
+            level
+        ),
+    }
+}
+
+/// Current time as RFC3339 UTC with milliseconds, e.g. `2026-03-01T12:00:00.123Z`
+pub fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// [`timestamp`] as the timer of human-readable lines
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UtcMillis;
+
+impl FormatTime for UtcMillis {
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        write!(w, "{}", timestamp())
+    }
+}
+
+/// One JSON object per event; event fields sit next to `message`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonLines;
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut line = Map::new();
+        line.insert("timestamp".to_string(), Value::from(timestamp()));
+        line.insert("level".to_string(), Value::from(metadata.level().as_str()));
+        line.insert("target".to_string(), Value::from(metadata.target()));
+        let mut fields = JsonFields(line);
+        event.record(&mut fields);
+        writeln!(writer, "{}", Value::Object(fields.0))
+    }
+}
+
+/// Collects event fields, keeping numbers and booleans typed
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(
+            field.name().to_string(),
+            Value::from(format!("{:?}", value)),
+        );
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+}
+
+/// Subscriber writing `format` lines at `level` and above to `writer`
+pub fn subscriber<W>(
+    level: Level,
+    format: LogFormat,
+    writer: W,
+    ansi: bool,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let filter = EnvFilter::default().add_directive(LevelFilter::from_level(level).into());
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_timer(UtcMillis)
+        .with_ansi(ansi);
+    match format {
+        LogFormat::Pretty => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(builder.event_format(JsonLines).finish()),
+    }
+}
+
+/// Install the process-wide subscriber for `config` on stdout
+///
+/// Call once before starting the server; the log level and format cannot
+/// change afterwards.
+pub fn init(config: &Config) -> Result<()> {
+    let ansi = config.log_format == LogFormat::Pretty && std::io::stdout().is_terminal();
+    let subscriber = subscriber(
+        parse_level(&config.log_level)?,
+        config.log_format,
+        std::io::stdout,
+        ansi,
+    );
+    tracing::subscriber::set_global_default(subscriber)
+        .context("A global log subscriber is already installed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer collecting log output in memory
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Buffer {
+        type Writer = Buffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn capture(level: Level, format: LogFormat) -> String {
+        let buffer = Buffer::default();
+        let subscriber = subscriber(level, format, buffer.clone(), false);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(path = "/api/data", "headers logged");
+            tracing::info!(status = 402_u64, paid = false, "request");
+        });
+        let output = buffer.0.lock().unwrap().clone();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_json_lines_have_rfc3339_utc_timestamps() {
+        let output = capture(Level::DEBUG, LogFormat::Json);
+        let lines: Vec<Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2, "{}", output);
+
+        for line in &lines {
+            let stamp = line["timestamp"].as_str().unwrap();
+            let parsed = chrono::DateTime::parse_from_rfc3339(stamp).unwrap();
+            assert_eq!(parsed.offset().local_minus_utc(), 0);
+            // Milliseconds, then `Z`: 2026-03-01T12:00:00.123Z
+            assert_eq!(stamp.len(), 24, "{}", stamp);
+            assert!(stamp.ends_with('Z'), "{}", stamp);
+        }
+        assert_eq!(lines[0]["level"], "DEBUG");
+        assert_eq!(lines[0]["path"], "/api/data");
+        assert_eq!(lines[1]["message"], "request");
+        assert_eq!(lines[1]["status"], 402);
+        assert_eq!(lines[1]["paid"], false);
+        assert_eq!(lines[1]["target"], module_path!());
+    }
+
+    #[test]
+    fn test_info_level_suppresses_debug_lines() {
+        for format in [LogFormat::Pretty, LogFormat::Json] {
+            let output = capture(Level::INFO, format);
+            assert!(!output.contains("headers logged"), "{}", output);
+            assert_eq!(output.lines().count(), 1, "{}", output);
+        }
+
+        let pretty = capture(Level::DEBUG, LogFormat::Pretty);
+        let first = pretty.lines().next().unwrap();
+        let stamp = first.split_whitespace().next().unwrap();
+        assert!(
+            chrono::DateTime::parse_from_rfc3339(stamp).is_ok(),
+            "{}",
+            first
+        );
+        assert!(first.contains("DEBUG"), "{}", first);
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("debug").unwrap(), Level::DEBUG);
+        assert_eq!(parse_level("WARN").unwrap(), Level::WARN);
+        let err = parse_level("verbose").unwrap_err();
+        assert!(err.to_string().contains("Invalid log level"));
+    }
+}
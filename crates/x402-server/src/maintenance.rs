@@ -0,0 +1,253 @@
+//! Maintenance mode (`maintenance:` and `/__admin/maintenance`)
+//!
+//! Requests to a path matching a rule get 503 Service Unavailable with
+//! `Retry-After` instead of an invoice, e.g. while fixtures are reloaded.
+//! Rules match an exact path or a `/prefix/*` pattern (`/*` for every
+//! path), optionally only for one tenant's `X-Agent-Id`. They come from the
+//! config and can be added or cleared at runtime without a restart.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// Active rules (GET), add or replace one (PUT), clear (DELETE)
+pub const ADMIN_MAINTENANCE_PATH: &str = "/__admin/maintenance";
+
+/// Error `code` of a response from a path in maintenance
+pub const MAINTENANCE: &str = "maintenance";
+
+/// Response message when a rule sets none
+pub const DEFAULT_MAINTENANCE_MESSAGE: &str = "This resource is temporarily under maintenance";
+
+/// `Retry-After` when a rule sets none
+pub const DEFAULT_RETRY_AFTER_SECS: u64 = 60;
+
+/// Longest `Retry-After` a rule may announce (1 day)
+pub const MAX_RETRY_AFTER_SECS: u64 = 86_400;
+
+/// A path pattern answering 503 for the time being
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MaintenanceRule {
+    /// Exact path or `/prefix/*` pattern
+    pub pattern: String,
+    /// Seconds clients are told to wait (`Retry-After`)
+    #[serde(default = "default_retry_after_secs")]
+    pub retry_after_secs: u64,
+    /// Shown in the error body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Only requests with this `X-Agent-Id` (every agent when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+}
+
+fn default_retry_after_secs() -> u64 {
+    DEFAULT_RETRY_AFTER_SECS
+}
+
+impl MaintenanceRule {
+    /// Rule for every agent with the default `Retry-After`
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            retry_after_secs: DEFAULT_RETRY_AFTER_SECS,
+            message: None,
+            agent: None,
+        }
+    }
+
+    pub fn retry_after_secs(mut self, secs: u64) -> Self {
+        self.retry_after_secs = secs;
+        self
+    }
+
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn agent(mut self, agent: impl Into<String>) -> Self {
+        self.agent = Some(agent.into());
+        self
+    }
+
+    /// Check the pattern and `Retry-After`
+    pub fn validate(&self) -> Result<()> {
+        let prefix = self.pattern.strip_suffix("/*").unwrap_or(&self.pattern);
+        if !self.pattern.starts_with('/')
+            || prefix.contains('*')
+            || prefix.contains(char::is_whitespace)
+        {
+            bail!(
+                "Invalid maintenance pattern: {}\nFix: Use an exact path like /api/data, a prefix pattern like /api/premium/*, or /* for every path",
+                self.pattern
+            );
+        }
+        if !(1..=MAX_RETRY_AFTER_SECS).contains(&self.retry_after_secs) {
+            bail!(
+                "Invalid retry_after_secs for maintenance of {}: {}. Must be between 1 and {} (1 day).\nFix: Set retry_after_secs to a value in range, e.g., {}",
+                self.pattern,
+                self.retry_after_secs,
+                MAX_RETRY_AFTER_SECS,
+                DEFAULT_RETRY_AFTER_SECS
+            );
+        }
+        Ok(())
+    }
+
+    /// True when the rule covers `path` for `agent`
+    pub fn matches(&self, path: &str, agent: Option<&str>) -> bool {
+        let path_matches = match self.pattern.strip_suffix("/*") {
+            Some(prefix) => path.starts_with(prefix),
+            None => self.pattern == path,
+        };
+        path_matches && self.agent.as_deref().is_none_or(|a| agent == Some(a))
+    }
+
+    /// Exact paths beat prefixes and longer prefixes shorter ones; a
+    /// tenant's own rule beats one for every agent
+    fn specificity(&self) -> (bool, bool, usize) {
+        (
+            self.agent.is_some(),
+            !self.pattern.ends_with("/*"),
+            self.pattern.len(),
+        )
+    }
+}
+
+/// Check every rule
+pub fn validate_maintenance(rules: &[MaintenanceRule]) -> Result<()> {
+    rules.iter().try_for_each(MaintenanceRule::validate)
+}
+
+/// Maintenance rules in effect, switched at runtime through
+/// `/__admin/maintenance`
+#[derive(Debug, Default)]
+pub struct MaintenanceSwitch {
+    rules: RwLock<Vec<MaintenanceRule>>,
+}
+
+impl MaintenanceSwitch {
+    /// Switch starting with the configured (validated) rules
+    pub fn new(rules: Vec<MaintenanceRule>) -> Self {
+        Self {
+            rules: RwLock::new(rules),
+        }
+    }
+
+    /// Active rules in the order they were added
+    pub fn rules(&self) -> Vec<MaintenanceRule> {
+        self.rules
+            .read()
+            .expect("CRITICAL: Maintenance rules lock poisoned - thread panic detected")
+            .clone()
+    }
+
+    /// Add `rule`, replacing the one with the same pattern and agent;
+    /// returns the replaced rule
+    pub fn set(&self, rule: MaintenanceRule) -> Option<MaintenanceRule> {
+        let mut rules = self
+            .rules
+            .write()
+            .expect("CRITICAL: Maintenance rules lock poisoned - thread panic detected");
+        match rules
+            .iter_mut()
+            .find(|r| r.pattern == rule.pattern && r.agent == rule.agent)
+        {
+            Some(existing) => Some(std::mem::replace(existing, rule)),
+            None => {
+                rules.push(rule);
+                None
+            }
+        }
+    }
+
+    /// Remove the rules for `pattern` (every rule when `None`), returning
+    /// them
+    pub fn clear(&self, pattern: Option<&str>) -> Vec<MaintenanceRule> {
+        let mut rules = self
+            .rules
+            .write()
+            .expect("CRITICAL: Maintenance rules lock poisoned - thread panic detected");
+        let (removed, kept) = std::mem::take(&mut *rules)
+            .into_iter()
+            .partition(|r| pattern.is_none_or(|p| r.pattern == p));
+        *rules = kept;
+        removed
+    }
+
+    /// Most specific rule covering `path` for `agent`
+    pub fn rule_for(&self, path: &str, agent: Option<&str>) -> Option<MaintenanceRule> {
+        self.rules
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|r| r.matches(path, agent))
+            .max_by_key(|r| r.specificity())
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_specific_rule_wins() {
+        let switch = MaintenanceSwitch::new(vec![
+            MaintenanceRule::new("/*").retry_after_secs(300),
+            MaintenanceRule::new("/api/*").retry_after_secs(30),
+            MaintenanceRule::new("/api/data").retry_after_secs(10),
+            MaintenanceRule::new("/api/*")
+                .agent("tenant-a")
+                .retry_after_secs(5),
+        ]);
+        let retry = |path, agent| switch.rule_for(path, agent).map(|r| r.retry_after_secs);
+
+        assert_eq!(retry("/api/data", None), Some(10));
+        assert_eq!(retry("/api/other", None), Some(30));
+        assert_eq!(retry("/api/other", Some("tenant-a")), Some(5));
+        assert_eq!(retry("/api/other", Some("tenant-b")), Some(30));
+        assert_eq!(retry("/health-check", None), Some(300));
+    }
+
+    #[test]
+    fn test_agent_rules_only_match_their_agent() {
+        let rule = MaintenanceRule::new("/api/*").agent("tenant-a");
+        assert!(rule.matches("/api/data", Some("tenant-a")));
+        assert!(!rule.matches("/api/data", Some("tenant-b")));
+        assert!(!rule.matches("/api/data", None));
+        assert!(!rule.matches("/other", Some("tenant-a")));
+    }
+
+    #[test]
+    fn test_set_replaces_and_clear_removes() {
+        let switch = MaintenanceSwitch::default();
+        assert!(switch.set(MaintenanceRule::new("/api/*")).is_none());
+        let replaced = switch.set(MaintenanceRule::new("/api/*").message("reloading"));
+        assert_eq!(replaced, Some(MaintenanceRule::new("/api/*")));
+        switch.set(MaintenanceRule::new("/api/*").agent("tenant-a"));
+        switch.set(MaintenanceRule::new("/other"));
+        assert_eq!(switch.rules().len(), 3);
+
+        assert_eq!(switch.clear(Some("/api/*")).len(), 2);
+        assert_eq!(switch.rules(), vec![MaintenanceRule::new("/other")]);
+        assert!(switch.clear(Some("/api/*")).is_empty());
+        assert_eq!(switch.clear(None).len(), 1);
+        assert!(switch.rule_for("/other", None).is_none());
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        for rule in [
+            MaintenanceRule::new("api/*"),
+            MaintenanceRule::new("/api/*/data"),
+            MaintenanceRule::new("/api").retry_after_secs(0),
+            MaintenanceRule::new("/api").retry_after_secs(MAX_RETRY_AFTER_SECS + 1),
+        ] {
+            assert!(rule.validate().is_err(), "{:?} was accepted", rule);
+        }
+        assert!(validate_maintenance(&[MaintenanceRule::new("/*")]).is_ok());
+    }
+}
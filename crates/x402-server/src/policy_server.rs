@@ -0,0 +1,379 @@
+//! Policy engine sidecar for `x402-dev policy serve`
+//!
+//! Services that don't run the generated Node middleware can enforce the same
+//! policies by asking a local process:
+//!
+//! - `POST /evaluate`: decision for one request plus the remaining quota
+//! - `GET /policies`: runtime policies currently loaded and state store stats
+//! - `POST /reload`: re-read the policy file; an invalid file, or one marked
+//!   `lenient: true` (which may be missing rules), is rejected and the
+//!   loaded policies stay in place
+//!
+//! Denials are a normal answer (200 with `"decision": "deny"`); error
+//! statuses are reserved for bad requests and failed reloads. Counters live in
+//! the state store (memory or SQLite), which a reload keeps: policies whose
+//! IDs remain keep their rate limit and spending windows. Decision hooks
+//! (such as an audit log) stay attached across reloads.
+
+use actix_web::error::{InternalError, JsonPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use x402_core::policy::{
+    load_policy_file, validate_policies, DecisionHook, IssueType, LoadedPolicyFile, PolicyDecision,
+    PolicyEngine, PolicyStateStore, QuotaStatus, Request, RuntimePolicy, StateStoreConfig,
+};
+
+/// Default port of the sidecar
+pub const DEFAULT_POLICY_SERVER_PORT: u16 = 9402;
+
+/// Evaluate endpoint (POST)
+pub const EVALUATE_PATH: &str = "/evaluate";
+
+/// Loaded policies endpoint (GET)
+pub const POLICIES_PATH: &str = "/policies";
+
+/// Reload endpoint (POST)
+pub const RELOAD_PATH: &str = "/reload";
+
+// ============================================================================
+// Policy Loading
+// ============================================================================
+
+/// Load and validate a policy file (simple or runtime schema)
+///
+/// Simple files must pass the same conflict detection as
+/// `x402-dev policy validate`; runtime files must have unique policy IDs.
+pub fn load_policies(path: &Path) -> Result<Vec<RuntimePolicy>> {
+    let runtime = match load_policy_file(path)? {
+        LoadedPolicyFile::Simple(policy_file) => {
+            let report = validate_policies(&policy_file.to_config());
+            if report.has_errors {
+                let errors: Vec<&str> = report
+                    .issues
+                    .iter()
+                    .filter(|issue| issue.issue_type == IssueType::Error)
+                    .map(|issue| issue.message.as_str())
+                    .collect();
+                bail!(
+                    "Policy file {} has conflicts: {}\nFix: Run `x402-dev policy validate {}` and resolve the errors",
+                    path.display(),
+                    errors.join("; "),
+                    path.display()
+                );
+            }
+            policy_file.to_runtime()?
+        }
+        LoadedPolicyFile::Runtime(runtime) => runtime,
+    };
+
+    let mut ids: Vec<&str> = runtime.policies.iter().map(|p| p.id.as_str()).collect();
+    ids.sort_unstable();
+    if let Some(pair) = ids.windows(2).find(|pair| pair[0] == pair[1]) {
+        bail!(
+            "Policy file {} defines policy '{}' more than once\nFix: Give every policy a unique id",
+            path.display(),
+            pair[0]
+        );
+    }
+
+    Ok(runtime.policies)
+}
+
+// ============================================================================
+// Sidecar State
+// ============================================================================
+
+/// Policy file and the engine built from it, swappable at runtime
+pub struct PolicySidecar {
+    path: PathBuf,
+    store: Arc<dyn PolicyStateStore>,
+    /// Attached to every engine, so reloads keep logging decisions
+    hooks: Vec<Arc<dyn DecisionHook>>,
+    loaded: RwLock<LoadedEngine>,
+}
+
+struct LoadedEngine {
+    engine: Arc<PolicyEngine>,
+    loaded_at: DateTime<Utc>,
+}
+
+impl LoadedEngine {
+    fn new(
+        policies: Vec<RuntimePolicy>,
+        store: Arc<dyn PolicyStateStore>,
+        hooks: &[Arc<dyn DecisionHook>],
+    ) -> Self {
+        let engine = hooks
+            .iter()
+            .fold(PolicyEngine::with_store(policies, store), |engine, hook| {
+                engine.with_decision_hook(hook.clone())
+            });
+        Self {
+            engine: Arc::new(engine),
+            loaded_at: Utc::now(),
+        }
+    }
+}
+
+impl PolicySidecar {
+    /// Load the policy file with in-memory state; fails if it is missing or invalid
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        Self::with_state(path, &StateStoreConfig::default())
+    }
+
+    /// Load the policy file and open the configured state store
+    pub fn with_state(path: impl Into<PathBuf>, state: &StateStoreConfig) -> Result<Self> {
+        let path = path.into();
+        let policies = load_policies(&path)?;
+        let store = state.open()?;
+        Ok(Self {
+            path,
+            loaded: RwLock::new(LoadedEngine::new(policies, store.clone(), &[])),
+            store,
+            hooks: Vec::new(),
+        })
+    }
+
+    /// Hand every decision to `hook`, including after reloads
+    pub fn with_decision_hook(mut self, hook: Arc<dyn DecisionHook>) -> Self {
+        self.hooks.push(hook);
+        let loaded = self.loaded.get_mut().unwrap();
+        let policies = loaded.engine.policies().to_vec();
+        *loaded = LoadedEngine::new(policies, self.store.clone(), &self.hooks);
+        self
+    }
+
+    /// Policy file the sidecar was started with
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Engine serving requests right now
+    ///
+    /// Evaluations in flight during a reload finish on the engine they started with.
+    pub fn engine(&self) -> Arc<PolicyEngine> {
+        self.loaded.read().unwrap().engine.clone()
+    }
+
+    /// When the current engine was loaded
+    pub fn loaded_at(&self) -> DateTime<Utc> {
+        self.loaded.read().unwrap().loaded_at
+    }
+
+    /// Re-read the policy file and swap engines if it is valid
+    ///
+    /// Returns the number of policies now loaded. On error the previous
+    /// engine stays in place; either way the counters are kept.
+    pub fn reload(&self) -> Result<usize> {
+        let policies = load_policies(&self.path)
+            .with_context(|| format!("Reload of {} rejected", self.path.display()))?;
+        let count = policies.len();
+        *self.loaded.write().unwrap() =
+            LoadedEngine::new(policies, self.store.clone(), &self.hooks);
+        Ok(count)
+    }
+}
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+/// `POST /evaluate` request body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluateRequest {
+    pub agent_id: String,
+    pub endpoint: String,
+    pub amount: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet_address: Option<String>,
+    /// Caller's request ID, copied into audit log entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+/// Outcome of an evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    Allow,
+    Deny,
+}
+
+/// `POST /evaluate` response body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvaluateResponse {
+    pub decision: Decision,
+    /// Matching policy, or `default` when no allow policy matched
+    pub policy_id: String,
+    /// Why the request was denied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub agent_id: String,
+    /// Allowance left under `policy_id` after this request
+    pub quota: QuotaStatus,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// JSON error envelope shared by every sidecar failure
+fn error_response(status: StatusCode, error: &str, reason: String) -> HttpResponse {
+    HttpResponse::build(status)
+        .insert_header(("Content-Type", "application/json"))
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "error": error,
+            "reason": reason,
+        }))
+}
+
+/// Malformed `/evaluate` bodies get the envelope instead of actix's plain text
+fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let response = error_response(
+        StatusCode::BAD_REQUEST,
+        "Invalid evaluate request",
+        err.to_string(),
+    );
+    InternalError::from_response(err, response).into()
+}
+
+/// POST /evaluate
+pub async fn evaluate_handler(
+    sidecar: web::Data<PolicySidecar>,
+    body: web::Json<EvaluateRequest>,
+) -> HttpResponse {
+    let body = body.into_inner();
+    if !body.amount.is_finite() || body.amount < 0.0 {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "Invalid evaluate request",
+            format!("amount must be a non-negative number, got {}", body.amount),
+        );
+    }
+
+    let engine = sidecar.engine();
+    let request = Request {
+        agent_id: body.agent_id,
+        wallet_address: body.wallet_address,
+        ip_address: None,
+        endpoint: body.endpoint,
+        amount: body.amount,
+        timestamp: engine.now(),
+        memo_id: None,
+        request_id: body.request_id,
+    };
+
+    // Concurrency slots are released when the decision drops at the end of
+    // this call; callers can't hold them across requests
+    let (decision, policy_id, reason) = match engine.evaluate(&request) {
+        Ok(PolicyDecision::Allow { policy_id, .. }) => (Decision::Allow, policy_id, None),
+        Ok(PolicyDecision::Deny { reason, policy_id }) => (Decision::Deny, policy_id, Some(reason)),
+        Ok(_) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Policy evaluation failed",
+                "Unsupported policy decision".to_string(),
+            );
+        }
+        Err(e) => {
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Policy evaluation failed",
+                e.to_string(),
+            );
+        }
+    };
+
+    println!(
+        "🛡️  {} {} (agent: {}, amount: {}) -> {:?} by {}",
+        EVALUATE_PATH, request.endpoint, request.agent_id, request.amount, decision, policy_id
+    );
+
+    let quota = engine.quota(&policy_id, &request.agent_id, request.timestamp);
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(EvaluateResponse {
+            decision,
+            policy_id,
+            reason,
+            agent_id: request.agent_id,
+            quota,
+        })
+}
+
+/// GET /policies
+pub async fn policies_handler(sidecar: web::Data<PolicySidecar>) -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(serde_json::json!({
+            "policy_file": sidecar.path().display().to_string(),
+            "loaded_at": sidecar.loaded_at(),
+            "policies": sidecar.engine().policies(),
+            "state": sidecar.store.stats().ok(),
+        }))
+}
+
+/// POST /reload
+pub async fn reload_handler(sidecar: web::Data<PolicySidecar>) -> HttpResponse {
+    match sidecar.reload() {
+        Ok(count) => {
+            println!(
+                "🔄 Reloaded {} ({} policies)",
+                sidecar.path().display(),
+                count
+            );
+            HttpResponse::Ok()
+                .insert_header(("Cache-Control", "no-store"))
+                .json(serde_json::json!({
+                    "status": "reloaded",
+                    "policy_file": sidecar.path().display().to_string(),
+                    "policies": count,
+                }))
+        }
+        Err(e) => {
+            println!("⚠️  {:#}", e);
+            error_response(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Reload rejected",
+                format!("{:#}", e),
+            )
+        }
+    }
+}
+
+/// Register the sidecar routes (405 on other methods)
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(web::JsonConfig::default().error_handler(json_error_handler))
+        .service(web::resource(EVALUATE_PATH).post(evaluate_handler))
+        .service(web::resource(POLICIES_PATH).get(policies_handler))
+        .service(web::resource(RELOAD_PATH).post(reload_handler));
+}
+
+// ============================================================================
+// Server Setup
+// ============================================================================
+
+/// Serve `sidecar` on 127.0.0.1:`port` until shutdown
+pub async fn run_policy_server(sidecar: PolicySidecar, port: u16) -> Result<()> {
+    let sidecar = web::Data::new(sidecar);
+
+    HttpServer::new(move || App::new().app_data(sidecar.clone()).configure(configure))
+        .bind(("127.0.0.1", port))
+        .inspect_err(|e| {
+            if e.kind() == std::io::ErrorKind::AddrInUse {
+                eprintln!("❌ Error: Port {} is already in use", port);
+                eprintln!("💡 Fix: Stop the process using this port or choose a different port");
+                std::process::exit(2); // Exit code 2: port in use
+            }
+        })
+        .with_context(|| format!("Failed to bind to port {}", port))?
+        .run()
+        .await
+        .context("HTTP server error")
+}
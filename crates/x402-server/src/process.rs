@@ -1,10 +1,12 @@
-use anyhow::{anyhow, Context, Result};
-use directories::BaseDirs;
+use anyhow::{anyhow, bail, Context, Result};
 use fs2::FileExt;
-use std::fs::{self, File};
-use std::path::PathBuf;
-use std::time::Duration;
-use sysinfo::{Pid, System};
+use std::fs::{self, File, OpenOptions};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessStatus, System};
+
+use crate::state_dir;
 
 // ============================================================================
 // Constants
@@ -13,122 +15,374 @@ use sysinfo::{Pid, System};
 /// Maximum wait time for graceful shutdown (seconds)
 pub const SHUTDOWN_TIMEOUT_SECS: u64 = 5;
 
+/// Maximum wait time after a forced kill (seconds)
+pub const KILL_TIMEOUT_SECS: u64 = 2;
+
 /// Poll interval for checking process shutdown (milliseconds)
 pub const SHUTDOWN_POLL_INTERVAL_MS: u64 = 100;
 
+/// Start times further apart than this belong to different processes
+const START_TIME_TOLERANCE_SECS: u64 = 1;
+
+/// Instance name of the mock facilitator server (`x402-dev mock`)
+pub const MOCK_SERVER_INSTANCE: &str = "mock-server";
+
+/// Instance name of the policy sidecar (`x402-dev policy serve`)
+pub const POLICY_SERVER_INSTANCE: &str = "policy-server";
+
 // ============================================================================
 // PID File Management
 // ============================================================================
 
-/// Get path to PID file using platform-specific home directory
-pub fn get_pid_file_path() -> Result<PathBuf> {
-    let base_dirs = BaseDirs::new().ok_or_else(|| anyhow!("Cannot determine home directory"))?;
+/// Get path to an instance's PID file in the state directory
+///
+/// Each instance (see [`MOCK_SERVER_INSTANCE`]) has its own file, so the
+/// mock server and the policy sidecar can run side by side. The directory
+/// is ~/.x402dev unless overridden or not writable (see [`state_dir`]).
+///
+/// [`state_dir`]: crate::state_dir
+pub fn get_pid_file_path(instance: &str) -> Result<PathBuf> {
+    Ok(state_dir::resolve()?.path.join(format!("{}.pid", instance)))
+}
 
-    Ok(base_dirs
-        .home_dir()
-        .join(".x402dev")
-        .join("mock-server.pid"))
+/// Contents of an instance's PID file
+///
+/// The PID is on the first line; once the server is listening, `port=` and
+/// `bind=` lines record the port and address it actually bound. `started=`
+/// is the process start time, which tells the server apart from an
+/// unrelated process that was given the same PID after it exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PidMetadata {
+    pub pid: u32,
+    pub port: Option<u16>,
+    pub bind: Option<IpAddr>,
+    /// Process start time in seconds since the Unix epoch (absent in PID
+    /// files written by older versions)
+    pub started: Option<u64>,
 }
 
-/// Write PID file with exclusive locking to prevent race conditions
-pub fn write_pid_file(pid: u32) -> Result<()> {
-    let pid_path = get_pid_file_path()?;
+impl PidMetadata {
+    /// Metadata for a live process, with its start time
+    pub fn for_process(pid: u32) -> Self {
+        Self {
+            pid,
+            port: None,
+            bind: None,
+            started: process_start_time(pid),
+        }
+    }
+
+    /// Parse PID file contents; unknown lines are ignored
+    pub fn parse(contents: &str) -> Option<Self> {
+        let mut lines = contents.lines();
+        let pid = lines.next()?.trim().parse().ok()?;
+        let mut port = None;
+        let mut bind = None;
+        let mut started = None;
+        for line in lines.map(str::trim) {
+            if let Some(value) = line.strip_prefix("port=") {
+                port = port.or(value.parse().ok());
+            } else if let Some(value) = line.strip_prefix("bind=") {
+                bind = bind.or(value.parse().ok());
+            } else if let Some(value) = line.strip_prefix("started=") {
+                started = started.or(value.parse().ok());
+            }
+        }
+        Some(Self {
+            pid,
+            port,
+            bind,
+            started,
+        })
+    }
+}
 
+impl std::fmt::Display for PidMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.pid)?;
+        if let Some(port) = self.port {
+            write!(f, "\nport={}", port)?;
+        }
+        if let Some(bind) = self.bind {
+            write!(f, "\nbind={}", bind)?;
+        }
+        if let Some(started) = self.started {
+            write!(f, "\nstarted={}", started)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write the PID file for `pid`, recording its start time
+pub fn write_pid_file(instance: &str, pid: u32) -> Result<()> {
+    write_pid_metadata(instance, &PidMetadata::for_process(pid))
+}
+
+/// Write PID file contents including the bound port
+///
+/// The file is replaced atomically, so readers never see it half written.
+pub fn write_pid_metadata(instance: &str, metadata: &PidMetadata) -> Result<()> {
+    write_pid_path(&get_pid_file_path(instance)?, metadata)
+}
+
+fn write_pid_path(pid_path: &Path, metadata: &PidMetadata) -> Result<()> {
     // Create parent directory if it doesn't exist
     if let Some(parent) = pid_path.parent() {
-        fs::create_dir_all(parent).context("Failed to create .x402dev directory")?;
+        state_dir::ensure_writable(parent)?;
     }
 
-    // Open file and acquire exclusive lock (prevents TOCTOU race condition)
-    let file = File::create(&pid_path).context("Failed to create PID file")?;
+    // Write next to the PID file and rename over it
+    let tmp_path = pid_path.with_extension("pid.tmp");
+    fs::write(&tmp_path, metadata.to_string()).context("Failed to write PID file")?;
+    fs::rename(&tmp_path, pid_path).context("Failed to replace PID file")?;
+    Ok(())
+}
 
-    file.try_lock_exclusive()
-        .context("Server already running (cannot acquire PID file lock)")?;
+/// Exclusive lock held while a PID file is checked and replaced
+///
+/// The lock is taken on `<instance>.pid.lock`, since the PID file itself is
+/// replaced by rename. The OS releases it when its holder exits, so a lock
+/// file left behind by a crashed run never blocks the next one.
+fn lock_pid_path(pid_path: &Path) -> Result<File> {
+    if let Some(parent) = pid_path.parent() {
+        state_dir::ensure_writable(parent)?;
+    }
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(pid_path.with_extension("pid.lock"))
+        .context("Failed to open PID lock file")?;
+    lock.lock_exclusive().context("Failed to lock PID file")?;
+    Ok(lock)
+}
 
-    fs::write(&pid_path, pid.to_string()).context("Failed to write PID file")?;
+/// Write `metadata` as `instance`'s PID file unless a live server holds it
+///
+/// Returns the running server's metadata instead of writing. A stale file
+/// (dead process, reused PID or unreadable contents) is replaced. Checking
+/// and replacing happen under the instance lock, so of two servers starting
+/// at once exactly one claims the file.
+pub fn try_claim_pid_file(instance: &str, metadata: &PidMetadata) -> Result<Option<PidMetadata>> {
+    try_claim_pid_path(&get_pid_file_path(instance)?, metadata, is_server_running)
+}
 
-    // Lock is automatically released when file handle is dropped
-    Ok(())
+fn try_claim_pid_path(
+    pid_path: &Path,
+    metadata: &PidMetadata,
+    is_running: fn(&PidMetadata) -> bool,
+) -> Result<Option<PidMetadata>> {
+    let _lock = lock_pid_path(pid_path)?;
+    if let Some(existing) = read_pid_path(pid_path).filter(is_running) {
+        return Ok(Some(existing));
+    }
+    write_pid_path(pid_path, metadata)?;
+    Ok(None)
 }
 
 /// Read PID file
-pub fn read_pid_file() -> Option<u32> {
-    let pid_path = get_pid_file_path().ok()?;
-    fs::read_to_string(&pid_path).ok()?.trim().parse().ok()
+pub fn read_pid_file(instance: &str) -> Option<u32> {
+    read_pid_metadata(instance).map(|metadata| metadata.pid)
+}
+
+/// Read PID file contents including the bound port
+pub fn read_pid_metadata(instance: &str) -> Option<PidMetadata> {
+    read_pid_path(&get_pid_file_path(instance).ok()?)
+}
+
+fn read_pid_path(pid_path: &Path) -> Option<PidMetadata> {
+    PidMetadata::parse(&fs::read_to_string(pid_path).ok()?)
 }
 
 /// Delete PID file
-pub fn delete_pid_file() -> Result<()> {
-    let pid_path = get_pid_file_path()?;
+pub fn delete_pid_file(instance: &str) -> Result<()> {
+    let pid_path = get_pid_file_path(instance)?;
     if pid_path.exists() {
         fs::remove_file(&pid_path).context("Failed to remove PID file")?;
     }
     Ok(())
 }
 
+/// Delete `instance`'s PID file if it still describes `metadata`'s process
+///
+/// A server that claimed the file since `metadata` was read keeps it.
+pub fn remove_pid_file_of(instance: &str, metadata: &PidMetadata) -> Result<()> {
+    remove_pid_path_of(&get_pid_file_path(instance)?, metadata)
+}
+
+fn remove_pid_path_of(pid_path: &Path, metadata: &PidMetadata) -> Result<()> {
+    let _lock = lock_pid_path(pid_path)?;
+    let same_process =
+        |current: &PidMetadata| (current.pid, current.started) == (metadata.pid, metadata.started);
+    if read_pid_path(pid_path).is_some_and(|current| same_process(&current)) {
+        fs::remove_file(pid_path).context("Failed to remove PID file")?;
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Process Management
 // ============================================================================
 
-/// Check if server is running by PID
-pub fn is_server_running(pid: u32) -> bool {
-    let mut sys = System::new_all();
-    sys.refresh_processes();
+/// The live (not zombie) process `pid`, refreshed into `sys`
+fn live_process(sys: &mut System, pid: u32) -> Option<&Process> {
+    let pid = Pid::from_u32(pid);
+    if !sys.refresh_process(pid) {
+        return None;
+    }
+    sys.process(pid)
+        .filter(|process| process.status() != ProcessStatus::Zombie)
+}
+
+/// Start time of a live process in seconds since the Unix epoch
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    live_process(&mut System::new(), pid).map(Process::start_time)
+}
+
+/// True if `process` started at the time recorded in `metadata`, if any
+fn started_as_recorded(process: &Process, metadata: &PidMetadata) -> bool {
+    metadata
+        .started
+        .is_none_or(|started| started.abs_diff(process.start_time()) <= START_TIME_TOLERANCE_SECS)
+}
 
-    if let Some(process) = sys.process(Pid::from_u32(pid)) {
+/// True if `metadata.pid` is alive and, when recorded, started at
+/// `metadata.started`
+///
+/// Windows and Unix both recycle PIDs; the start time tells a new process
+/// with an old PID apart from the one that wrote the PID file.
+pub fn is_same_process(metadata: &PidMetadata) -> bool {
+    live_process(&mut System::new(), metadata.pid)
+        .is_some_and(|process| started_as_recorded(process, metadata))
+}
+
+/// Check if the server described by a PID file is running
+///
+/// The process must be the one that wrote the file (see
+/// [`is_same_process`]) and look like an x402-dev server.
+pub fn is_server_running(metadata: &PidMetadata) -> bool {
+    live_process(&mut System::new(), metadata.pid).is_some_and(|process| {
         // Check if it's actually our x402-dev process
         let name = process.name();
-        name.contains("x402-dev") || name.contains("mock")
-    } else {
-        false
+        started_as_recorded(process, metadata)
+            && (name.contains("x402-dev") || name.contains("mock"))
+    })
+}
+
+/// Stop the server described by a PID file
+///
+/// Nothing is signalled unless [`is_server_running`] confirms the PID
+/// still belongs to the server. On Unix the server gets SIGTERM and, after
+/// [`SHUTDOWN_TIMEOUT_SECS`], SIGKILL; Windows has no graceful signal for
+/// console processes, so the server is terminated right away.
+pub fn stop_server_process(metadata: &PidMetadata) -> Result<()> {
+    if !is_server_running(metadata) {
+        bail!(
+            "PID {} is not a running x402-dev server; nothing was stopped",
+            metadata.pid
+        );
+    }
+    terminate(metadata, Duration::from_secs(SHUTDOWN_TIMEOUT_SECS))
+}
+
+/// Wait up to `timeout` for the process to exit
+fn wait_for_exit(metadata: &PidMetadata, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !is_same_process(metadata) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(SHUTDOWN_POLL_INTERVAL_MS));
     }
 }
 
-/// Stop server process gracefully
-pub fn stop_server_process(pid: u32) -> Result<()> {
+/// SIGTERM, then SIGKILL once `grace` has passed
+#[cfg(unix)]
+fn terminate(metadata: &PidMetadata, grace: Duration) -> Result<()> {
+    use nix::errno::Errno;
     use nix::sys::signal::{kill, Signal};
     use nix::unistd::Pid as NixPid;
 
-    // Send SIGTERM for graceful shutdown
-    kill(NixPid::from_raw(pid as i32), Signal::SIGTERM).context("Failed to send SIGTERM")?;
+    let pid = NixPid::from_raw(metadata.pid as i32);
+    let signal = |signal: Signal| match kill(pid, signal) {
+        // Exited in the meantime
+        Ok(()) | Err(Errno::ESRCH) => Ok(()),
+        Err(e) => Err(anyhow!("Failed to send {}: {}", signal, e)),
+    };
 
-    // Wait up to SHUTDOWN_TIMEOUT_SECS for graceful shutdown
-    let max_attempts = (SHUTDOWN_TIMEOUT_SECS * 1000) / SHUTDOWN_POLL_INTERVAL_MS;
-    for _ in 0..max_attempts {
-        if !is_server_running(pid) {
-            return Ok(());
-        }
-        std::thread::sleep(Duration::from_millis(SHUTDOWN_POLL_INTERVAL_MS));
+    signal(Signal::SIGTERM)?;
+    if wait_for_exit(metadata, grace) {
+        return Ok(());
     }
 
-    // If still running after timeout, it's an error
+    // Only kill the process we sent SIGTERM to, not a successor with its PID
+    if is_same_process(metadata) {
+        println!(
+            "Server did not shut down within {} seconds, sending SIGKILL",
+            grace.as_secs()
+        );
+        signal(Signal::SIGKILL)?;
+    }
+    if wait_for_exit(metadata, Duration::from_secs(KILL_TIMEOUT_SECS)) {
+        return Ok(());
+    }
     Err(anyhow!(
-        "Server did not shut down gracefully within {} seconds",
-        SHUTDOWN_TIMEOUT_SECS
+        "Server (PID {}) is still running after SIGKILL",
+        metadata.pid
+    ))
+}
+
+/// TerminateProcess through sysinfo; there is no grace period to wait out
+#[cfg(not(unix))]
+fn terminate(metadata: &PidMetadata, _grace: Duration) -> Result<()> {
+    let mut sys = System::new();
+    if let Some(process) = live_process(&mut sys, metadata.pid) {
+        if !process.kill() {
+            bail!("Failed to terminate process {}", metadata.pid);
+        }
+    }
+    if wait_for_exit(metadata, Duration::from_secs(KILL_TIMEOUT_SECS)) {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "Server (PID {}) is still running after being terminated",
+        metadata.pid
     ))
 }
 
 /// Process manager for coordinating server lifecycle
-pub struct ProcessManager;
+pub struct ProcessManager {
+    instance: &'static str,
+}
 
 impl ProcessManager {
-    /// Create a new process manager
+    /// Create a process manager for the mock server
     pub fn new() -> Self {
-        Self
+        Self::for_instance(MOCK_SERVER_INSTANCE)
+    }
+
+    /// Create a process manager for a named instance
+    pub fn for_instance(instance: &'static str) -> Self {
+        Self { instance }
     }
 
     /// Check if server is currently running
     pub fn is_running(&self) -> bool {
-        if let Some(pid) = read_pid_file() {
-            is_server_running(pid)
-        } else {
-            false
-        }
+        read_pid_metadata(self.instance).is_some_and(|metadata| is_server_running(&metadata))
     }
 
     /// Get the current server PID if running
     pub fn get_pid(&self) -> Option<u32> {
-        read_pid_file()
+        read_pid_file(self.instance)
+    }
+
+    /// Port the server bound, which differs from the configured one after
+    /// a port fallback
+    pub fn get_port(&self) -> Option<u16> {
+        read_pid_metadata(self.instance)?.port
     }
 }
 
@@ -137,3 +391,153 @@ impl Default for ProcessManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Child, Command, Stdio};
+
+    /// An unrelated long-running process
+    fn spawn_sleeper() -> Child {
+        #[cfg(unix)]
+        let mut command = Command::new("sleep");
+        #[cfg(unix)]
+        command.arg("30");
+        #[cfg(not(unix))]
+        let mut command = Command::new("ping");
+        #[cfg(not(unix))]
+        command.args(["-n", "30", "127.0.0.1"]);
+        command
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pid_metadata_round_trip() {
+        let metadata = PidMetadata {
+            pid: 4242,
+            port: Some(3403),
+            bind: Some("0.0.0.0".parse().unwrap()),
+            started: Some(1_767_225_600),
+        };
+        assert_eq!(
+            metadata.to_string(),
+            "4242\nport=3403\nbind=0.0.0.0\nstarted=1767225600"
+        );
+        assert_eq!(PidMetadata::parse(&metadata.to_string()), Some(metadata));
+
+        // PID files written before the port line existed still parse
+        assert_eq!(
+            PidMetadata::parse("4242\n"),
+            Some(PidMetadata {
+                pid: 4242,
+                port: None,
+                bind: None,
+                started: None,
+            })
+        );
+        assert_eq!(PidMetadata::parse("not-a-pid"), None);
+    }
+
+    #[test]
+    fn test_unrelated_live_process_is_not_our_server() {
+        let mut sleeper = spawn_sleeper();
+        let sleeper_own = PidMetadata::for_process(sleeper.id());
+        let started = sleeper_own.started.unwrap();
+
+        // A server that wrote the PID file a minute ago, whose PID now
+        // names the sleeper
+        let reused = PidMetadata {
+            started: Some(started - 60),
+            ..sleeper_own
+        };
+        // Without a start time (older PID files) only the name check is left
+        let legacy = PidMetadata {
+            started: None,
+            ..sleeper_own
+        };
+
+        let same = [reused, sleeper_own].map(|m| is_same_process(&m));
+        let running = [reused, sleeper_own, legacy].map(|m| is_server_running(&m));
+        let stopped = stop_server_process(&sleeper_own);
+        let alive = is_same_process(&sleeper_own);
+        sleeper.kill().unwrap();
+        sleeper.wait().unwrap();
+
+        assert_eq!(same, [false, true]);
+        assert_eq!(running, [false, false, false]);
+        assert!(stopped.is_err());
+        assert!(alive, "stop must not signal an unrelated process");
+        assert!(!is_same_process(&sleeper_own));
+    }
+
+    #[test]
+    fn test_stale_pid_file_replaced_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_path = dir.path().join("mock-server.pid");
+        let current = PidMetadata::for_process(std::process::id());
+
+        // A crashed run left garbage and its lock file behind
+        fs::write(&pid_path, "not-a-pid").unwrap();
+        fs::write(pid_path.with_extension("pid.lock"), "").unwrap();
+        assert_eq!(
+            try_claim_pid_path(&pid_path, &current, is_server_running).unwrap(),
+            None
+        );
+        assert_eq!(read_pid_path(&pid_path), Some(current));
+        assert!(!pid_path.with_extension("pid.tmp").exists());
+
+        // A live holder keeps the file
+        let held = try_claim_pid_path(&pid_path, &PidMetadata::for_process(1), |_| true).unwrap();
+        assert_eq!(held, Some(current));
+        assert_eq!(read_pid_path(&pid_path), Some(current));
+
+        // Cleanup only removes the file of the process it was read for
+        let other = PidMetadata {
+            started: current.started.map(|s| s + 60),
+            ..current
+        };
+        remove_pid_path_of(&pid_path, &other).unwrap();
+        assert!(pid_path.exists());
+        remove_pid_path_of(&pid_path, &current).unwrap();
+        assert!(!pid_path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sigkill_after_grace_period() {
+        // A process that ignores SIGTERM
+        let mut child = Command::new("sh")
+            .args(["-c", "trap '' TERM; exec sleep 30"])
+            .spawn()
+            .unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        let metadata = PidMetadata::for_process(child.id());
+
+        let start = Instant::now();
+        terminate(&metadata, Duration::from_millis(300)).unwrap();
+
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(!is_same_process(&metadata));
+        let status = child.wait().unwrap();
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(
+            status.signal(),
+            Some(nix::sys::signal::Signal::SIGKILL as i32)
+        );
+    }
+
+    #[cfg(not(unix))]
+    #[test]
+    fn test_terminate_without_grace_period() {
+        let mut child = spawn_sleeper();
+        let metadata = PidMetadata::for_process(child.id());
+
+        terminate(&metadata, Duration::from_secs(SHUTDOWN_TIMEOUT_SECS)).unwrap();
+
+        assert!(!is_same_process(&metadata));
+        assert!(!child.wait().unwrap().success());
+    }
+}
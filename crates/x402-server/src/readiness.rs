@@ -0,0 +1,309 @@
+//! Liveness, readiness and startup hooks (`/health/live`, `/health/ready`)
+//!
+//! Liveness only says the process answers. Readiness also needs the config
+//! loaded, policies compiled, the policy state opened, the listener
+//! attached and every `startup_hooks` entry run in order. Until then
+//! `/health/ready` (and `/health`, its alias) answers 503 with the reasons.
+//! A failed hook keeps the server not ready for good; `mock` gives up
+//! waiting after `ready_timeout_secs`.
+
+use crate::server::PricingConfig;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Process up and answering (GET)
+pub const HEALTH_LIVE_PATH: &str = "/health/live";
+
+/// Ready for traffic, or 503 with the reasons (GET)
+pub const HEALTH_READY_PATH: &str = "/health/ready";
+
+/// How long `mock` waits for readiness when no timeout is configured
+pub const DEFAULT_READY_TIMEOUT_SECS: u64 = 30;
+
+/// Longest a `self_check` request may take
+pub const SELF_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Work run after the listener is attached, before the server is ready
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum StartupHook {
+    /// Resolve the default price and every per-resource rule once; fails
+    /// when one is outside the bounds with `out_of_bounds: error`
+    WarmPricing,
+    /// GET `path` on the server itself and expect `expect_status`
+    ///
+    /// The request shows up in stats and events like any other.
+    SelfCheck {
+        path: String,
+        #[serde(default = "default_expect_status")]
+        expect_status: u16,
+    },
+}
+
+fn default_expect_status() -> u16 {
+    402
+}
+
+impl StartupHook {
+    /// Name of the hook's readiness check, e.g. `self_check GET /api/data`
+    pub fn name(&self) -> String {
+        match self {
+            StartupHook::WarmPricing => "warm_pricing".to_string(),
+            StartupHook::SelfCheck { path, .. } => format!("self_check GET {}", path),
+        }
+    }
+
+    /// Check the self-check path and status
+    pub fn validate(&self) -> Result<()> {
+        if let StartupHook::SelfCheck {
+            path,
+            expect_status,
+        } = self
+        {
+            if !path.starts_with('/') || path.contains(char::is_whitespace) {
+                bail!(
+                    "Invalid self_check path in startup_hooks: {}\nFix: Use a path starting with /, e.g., /api/data",
+                    path
+                );
+            }
+            if !(100..=599).contains(expect_status) {
+                bail!(
+                    "Invalid expect_status for self_check {}: {}. Must be an HTTP status from 100 to 599.\nFix: Set expect_status to the status the path answers, e.g., 402",
+                    path,
+                    expect_status
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the hook against the server at `base_url`; the error is the
+    /// reason shown in `/health/ready`
+    pub async fn run(&self, pricing: &PricingConfig, base_url: &str) -> Result<(), String> {
+        match self {
+            StartupHook::WarmPricing => {
+                let rules = pricing.per_resource.iter().map(|(rule, &amount)| {
+                    // Method-qualified rules ("POST /api/*") price their path part
+                    let pattern = rule.rsplit(' ').next().unwrap_or(rule);
+                    (pattern.strip_suffix("/*").unwrap_or(pattern), amount)
+                });
+                std::iter::once(("/", pricing.default))
+                    .chain(rules)
+                    .try_for_each(|(path, amount)| {
+                        pricing
+                            .apply_bounds(path, amount)
+                            .map(drop)
+                            .map_err(|e| e.to_string())
+                    })
+            }
+            StartupHook::SelfCheck {
+                path,
+                expect_status,
+            } => {
+                let client = reqwest::Client::builder()
+                    .timeout(SELF_CHECK_TIMEOUT)
+                    // The server's own (possibly self-signed) certificate
+                    .danger_accept_invalid_certs(true)
+                    .build()
+                    .map_err(|e| e.to_string())?;
+                let status = client
+                    .get(format!("{}{}", base_url, path))
+                    .send()
+                    .await
+                    .map_err(|e| format!("request failed: {}", e))?
+                    .status()
+                    .as_u16();
+                if status == *expect_status {
+                    Ok(())
+                } else {
+                    Err(format!("answered {}, expected {}", status, expect_status))
+                }
+            }
+        }
+    }
+}
+
+/// Check every hook
+pub fn validate_startup_hooks(hooks: &[StartupHook]) -> Result<()> {
+    hooks.iter().try_for_each(StartupHook::validate)
+}
+
+/// State of one readiness check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Pending,
+    Failed,
+}
+
+/// One condition of readiness, as listed in `/health/ready`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReadinessCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Why the check has not passed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Readiness checks of one server, in the order they were registered
+#[derive(Debug, Default)]
+pub struct Readiness {
+    checks: RwLock<Vec<ReadinessCheck>>,
+}
+
+impl Readiness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark `name` passed
+    pub fn pass(&self, name: impl Into<String>) {
+        self.set(name.into(), CheckStatus::Passed, None);
+    }
+
+    /// Mark `name` not passed yet
+    pub fn pending(&self, name: impl Into<String>, reason: impl Into<String>) {
+        self.set(name.into(), CheckStatus::Pending, Some(reason.into()));
+    }
+
+    /// Mark `name` failed for good
+    pub fn fail(&self, name: impl Into<String>, reason: impl Into<String>) {
+        self.set(name.into(), CheckStatus::Failed, Some(reason.into()));
+    }
+
+    fn set(&self, name: String, status: CheckStatus, reason: Option<String>) {
+        let mut checks = self.checks.write().unwrap();
+        let check = ReadinessCheck {
+            name,
+            status,
+            reason,
+        };
+        match checks.iter_mut().find(|c| c.name == check.name) {
+            Some(existing) => *existing = check,
+            None => checks.push(check),
+        }
+    }
+
+    pub fn checks(&self) -> Vec<ReadinessCheck> {
+        self.checks.read().unwrap().clone()
+    }
+
+    /// True when every check passed
+    pub fn is_ready(&self) -> bool {
+        self.checks
+            .read()
+            .unwrap()
+            .iter()
+            .all(|c| c.status == CheckStatus::Passed)
+    }
+
+    /// `<check>: <reason>` for every check that has not passed
+    pub fn reasons(&self) -> Vec<String> {
+        self.checks
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|c| c.status != CheckStatus::Passed)
+            .map(|c| {
+                format!(
+                    "{}: {}",
+                    c.name,
+                    c.reason.as_deref().unwrap_or("not passed")
+                )
+            })
+            .collect()
+    }
+
+    /// Register `hooks` as pending checks
+    pub fn expect_hooks(&self, hooks: &[StartupHook]) {
+        for hook in hooks {
+            self.pending(hook.name(), "not run yet");
+        }
+    }
+
+    /// Run `hooks` in order against `base_url`, stopping at the first
+    /// failure
+    pub async fn run_hooks(&self, hooks: &[StartupHook], pricing: &PricingConfig, base_url: &str) {
+        for (i, hook) in hooks.iter().enumerate() {
+            match hook.run(pricing, base_url).await {
+                Ok(()) => self.pass(hook.name()),
+                Err(reason) => {
+                    self.fail(hook.name(), reason);
+                    for later in &hooks[i + 1..] {
+                        self.pending(later.name(), "not run: an earlier startup hook failed");
+                    }
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ready_once_every_check_passed() {
+        let readiness = Readiness::new();
+        readiness.pass("config");
+        readiness.expect_hooks(&[StartupHook::WarmPricing]);
+        assert!(!readiness.is_ready());
+        assert_eq!(readiness.reasons(), vec!["warm_pricing: not run yet"]);
+
+        readiness.pass("warm_pricing");
+        assert!(readiness.is_ready());
+        assert_eq!(readiness.checks().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_hook_leaves_later_hooks_pending() {
+        let pricing = PricingConfig {
+            max_amount: Some(0.001),
+            out_of_bounds: crate::server::OutOfBounds::Error,
+            ..PricingConfig::default()
+        };
+        let hooks = [
+            StartupHook::WarmPricing,
+            StartupHook::SelfCheck {
+                path: "/api/data".to_string(),
+                expect_status: 402,
+            },
+        ];
+        let readiness = Readiness::new();
+        readiness.expect_hooks(&hooks);
+        readiness
+            .run_hooks(&hooks, &pricing, "http://127.0.0.1:1")
+            .await;
+
+        let checks = readiness.checks();
+        assert_eq!(checks[0].status, CheckStatus::Failed);
+        assert_eq!(checks[1].status, CheckStatus::Pending);
+        assert!(readiness.reasons()[1].contains("earlier startup hook failed"));
+    }
+
+    #[test]
+    fn test_hooks_deserialize_by_type() {
+        let hooks: Vec<StartupHook> =
+            serde_yaml::from_str("- type: warm_pricing\n- type: self_check\n  path: /api\n")
+                .unwrap();
+        assert_eq!(hooks[1].name(), "self_check GET /api");
+        assert_eq!(
+            hooks[1],
+            StartupHook::SelfCheck {
+                path: "/api".to_string(),
+                expect_status: 402
+            }
+        );
+        assert!(StartupHook::SelfCheck {
+            path: "api".to_string(),
+            expect_status: 402
+        }
+        .validate()
+        .is_err());
+    }
+}
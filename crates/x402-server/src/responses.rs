@@ -0,0 +1,279 @@
+//! Post-payment response bodies for mock paths (`responses:`)
+//!
+//! Each exact path or `/prefix/*` pattern can answer a verified payment with
+//! an inline body or a file, with its own content type and optional gzip or
+//! deflate encoding, so clients exercise the decoding they need against real
+//! paid APIs. Paths without a spec get the default JSON acknowledgement.
+//! Files are checked when the config is validated and read per request, so
+//! edits show up without a restart.
+
+use anyhow::{bail, Context, Result};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Largest `file:` body served (10 MiB)
+pub const MAX_RESPONSE_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// `Content-Encoding` applied to a response body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseEncoding {
+    Gzip,
+    /// zlib-wrapped deflate, as HTTP's `deflate` coding specifies
+    Deflate,
+}
+
+impl ResponseEncoding {
+    /// `Content-Encoding` header value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResponseEncoding::Gzip => "gzip",
+            ResponseEncoding::Deflate => "deflate",
+        }
+    }
+
+    /// Compress `body` with this coding
+    pub fn encode(&self, body: &[u8]) -> Result<Vec<u8>> {
+        let encoded = match self {
+            ResponseEncoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()?
+            }
+            ResponseEncoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()?
+            }
+        };
+        Ok(encoded)
+    }
+}
+
+/// Body served for a path once its payment is verified
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResponseSpec {
+    /// Inline body, sent as-is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// File whose bytes are the body (relative to the working directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    /// Defaults to JSON for JSON bodies and `.json` files, text for other
+    /// inline bodies, and `application/octet-stream` for other files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// Compress the body and set `Content-Encoding`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<ResponseEncoding>,
+}
+
+impl ResponseSpec {
+    /// Serve `body` inline
+    pub fn from_body(body: impl Into<String>) -> Self {
+        Self {
+            body: Some(body.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Serve the bytes of `path`
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            file: Some(path.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+        self
+    }
+
+    pub fn encoding(mut self, encoding: ResponseEncoding) -> Self {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Explicit content type, else one guessed from the body or file name
+    pub fn resolved_content_type(&self) -> String {
+        if let Some(content_type) = &self.content_type {
+            return content_type.clone();
+        }
+        let json = match (&self.body, &self.file) {
+            (Some(body), _) => serde_json::from_str::<serde_json::Value>(body).is_ok(),
+            (None, Some(file)) => file.extension().is_some_and(|ext| ext == "json"),
+            (None, None) => false,
+        };
+        if json {
+            "application/json".to_string()
+        } else if self.body.is_some() {
+            "text/plain; charset=utf-8".to_string()
+        } else {
+            "application/octet-stream".to_string()
+        }
+    }
+
+    /// Body bytes before encoding; files over [`MAX_RESPONSE_FILE_BYTES`]
+    /// are refused
+    pub fn load(&self) -> Result<Vec<u8>> {
+        match (&self.body, &self.file) {
+            (Some(body), _) => Ok(body.clone().into_bytes()),
+            (None, Some(path)) => {
+                check_file_size(path)?;
+                fs::read(path).with_context(|| format!("Failed to read response file {:?}", path))
+            }
+            (None, None) => Ok(Vec::new()),
+        }
+    }
+
+    /// Body as sent: loaded, then encoded if `encoding` is set
+    pub fn render(&self) -> Result<Vec<u8>> {
+        let body = self.load()?;
+        match self.encoding {
+            Some(encoding) => encoding.encode(&body),
+            None => Ok(body),
+        }
+    }
+}
+
+/// Error if `path` is missing or over [`MAX_RESPONSE_FILE_BYTES`]
+fn check_file_size(path: &Path) -> Result<()> {
+    let size = fs::metadata(path)
+        .with_context(|| format!("Response file {:?} not found", path))?
+        .len();
+    if size > MAX_RESPONSE_FILE_BYTES {
+        bail!(
+            "Response file {:?} is {} bytes, over the {}-byte limit\nFix: Serve a smaller file",
+            path,
+            size,
+            MAX_RESPONSE_FILE_BYTES
+        );
+    }
+    Ok(())
+}
+
+/// Check patterns, that each spec has one body source, and that files
+/// exist within the size limit
+pub fn validate_responses(responses: &HashMap<String, ResponseSpec>) -> Result<()> {
+    let mut patterns: Vec<&String> = responses.keys().collect();
+    patterns.sort();
+    for pattern in patterns {
+        let prefix = pattern.strip_suffix("/*").unwrap_or(pattern);
+        if !pattern.starts_with('/') || prefix.contains('*') || prefix.contains(char::is_whitespace)
+        {
+            bail!(
+                "Invalid responses path: {}\nFix: Use an exact path like /api/data or a prefix pattern like /api/files/*",
+                pattern
+            );
+        }
+
+        let spec = &responses[pattern];
+        if spec.body.is_some() == spec.file.is_some() {
+            bail!(
+                "responses for {} needs exactly one of body or file\nFix: Set body: for an inline body or file: for a file's contents",
+                pattern
+            );
+        }
+        if let Some(path) = &spec.file {
+            if !path.is_file() {
+                bail!(
+                    "Response file for {} not found: {}\nFix: Check the path; relative paths are resolved from the working directory",
+                    pattern,
+                    path.display()
+                );
+            }
+            check_file_size(path)
+                .with_context(|| format!("Invalid responses entry for {}", pattern))?;
+        }
+        if let Some(content_type) = &spec.content_type {
+            if actix_web::http::header::HeaderValue::from_str(content_type).is_err() {
+                bail!(
+                    "Invalid content_type for {} in responses: '{}'\nFix: Use a media type such as application/json",
+                    pattern,
+                    content_type
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_gzip_round_trip_and_content_type() {
+        let spec =
+            ResponseSpec::from_body(r#"{"data": [1, 2, 3]}"#).encoding(ResponseEncoding::Gzip);
+        assert_eq!(spec.resolved_content_type(), "application/json");
+
+        let mut decoded = String::new();
+        GzDecoder::new(spec.render().unwrap().as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, r#"{"data": [1, 2, 3]}"#);
+
+        assert_eq!(
+            ResponseSpec::from_body("plain").resolved_content_type(),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(
+            ResponseSpec::from_file("blob.bin").resolved_content_type(),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_validate_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("blob.bin");
+        fs::write(&file, [0u8, 1, 2]).unwrap();
+
+        let valid = HashMap::from([
+            ("/api/data".to_string(), ResponseSpec::from_body("{}")),
+            ("/files/*".to_string(), ResponseSpec::from_file(&file)),
+        ]);
+        assert!(validate_responses(&valid).is_ok());
+
+        let missing = HashMap::from([(
+            "/files/*".to_string(),
+            ResponseSpec::from_file(dir.path().join("gone.bin")),
+        )]);
+        let err = validate_responses(&missing).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Response file for /files/* not found"));
+
+        let both = HashMap::from([(
+            "/api/data".to_string(),
+            ResponseSpec {
+                file: Some(file),
+                ..ResponseSpec::from_body("{}")
+            },
+        )]);
+        let err = validate_responses(&both).unwrap_err();
+        assert!(err.to_string().contains("exactly one of body or file"));
+
+        let big = dir.path().join("big.bin");
+        fs::File::create(&big)
+            .unwrap()
+            .set_len(MAX_RESPONSE_FILE_BYTES + 1)
+            .unwrap();
+        let oversized = HashMap::from([("/big".to_string(), ResponseSpec::from_file(&big))]);
+        let err = validate_responses(&oversized).unwrap_err();
+        assert!(format!("{:#}", err).contains("over the 10485760-byte limit"));
+
+        let pattern = HashMap::from([("api/*".to_string(), ResponseSpec::from_body("{}"))]);
+        assert!(validate_responses(&pattern).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Per-path recipient routing for mock invoices (`recipient_routing:`)
+//!
+//! Each exact path or `/prefix/*` pattern pays to one address or splits
+//! invoices between several by weight (e.g. 80/20). Paths without a route
+//! fall back to `recipient` or the rotating test addresses. Weighted picks
+//! come from a SplitMix64 sequence, so a fixed `recipient_routing_seed`
+//! replays the same recipients in the same order.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use x402_domain::SolanaAddress;
+
+use crate::server::match_resource;
+
+/// Weights of a split must add up to this
+pub const TOTAL_WEIGHT: u32 = 100;
+
+/// SplitMix64 increment
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Where invoices for one path pattern are paid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RecipientRoute {
+    /// Every invoice pays to this address
+    Address(SolanaAddress),
+    /// Invoices are split between addresses by weight
+    Weighted(Vec<WeightedRecipient>),
+}
+
+/// One address of a weighted split
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeightedRecipient {
+    pub address: SolanaAddress,
+    /// Share of invoices in percent
+    pub weight: u32,
+}
+
+/// Recipient picked for an invoice and the route pattern that chose it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteMatch {
+    pub pattern: String,
+    pub recipient: SolanaAddress,
+}
+
+/// Check route patterns and split weights
+pub fn validate_recipient_routing(routes: &HashMap<String, RecipientRoute>) -> Result<()> {
+    let mut patterns: Vec<&String> = routes.keys().collect();
+    patterns.sort();
+    for pattern in patterns {
+        let prefix = pattern.strip_suffix("/*").unwrap_or(pattern);
+        if !pattern.starts_with('/') || prefix.contains('*') || prefix.contains(char::is_whitespace)
+        {
+            bail!(
+                "Invalid recipient_routing path: {}\nFix: Use an exact path like /api/data or a prefix pattern like /api/premium/*",
+                pattern
+            );
+        }
+
+        let RecipientRoute::Weighted(split) = &routes[pattern] else {
+            continue;
+        };
+        if split.is_empty() || split.iter().any(|r| r.weight == 0) {
+            bail!(
+                "recipient_routing for {} needs at least one address with a weight above 0\nFix: Give each address a weight, e.g., 80 and 20",
+                pattern
+            );
+        }
+        let total: u32 = split.iter().map(|r| r.weight).sum();
+        if total != TOTAL_WEIGHT {
+            bail!(
+                "recipient_routing weights for {} add up to {}, not {}\nFix: Adjust the weights so they sum to {}, e.g., 80 and 20",
+                pattern,
+                total,
+                TOTAL_WEIGHT,
+                TOTAL_WEIGHT
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Picks recipients for paths with a route
+#[derive(Debug)]
+pub struct RecipientRouter {
+    routes: HashMap<String, RecipientRoute>,
+    /// SplitMix64 state, advanced once per weighted pick
+    state: AtomicU64,
+}
+
+impl RecipientRouter {
+    /// Router over validated `routes`; seeded from the clock without `seed`
+    pub fn new(routes: HashMap<String, RecipientRoute>, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos() as u64)
+                ^ u64::from(std::process::id())
+        });
+        Self {
+            routes,
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Route configured for `path`, without picking a recipient
+    pub fn route_for(&self, path: &str) -> Option<&RecipientRoute> {
+        match_resource(&self.routes, path).map(|(_, route)| route)
+    }
+
+    /// Recipient for `path`, or `None` when no route matches
+    pub fn route(&self, path: &str) -> Option<RouteMatch> {
+        let (pattern, route) = match_resource(&self.routes, path)?;
+        let recipient = match route {
+            RecipientRoute::Address(address) => address.clone(),
+            RecipientRoute::Weighted(split) => self.pick(split).clone(),
+        };
+        Some(RouteMatch {
+            pattern: pattern.to_string(),
+            recipient,
+        })
+    }
+
+    fn pick<'a>(&self, split: &'a [WeightedRecipient]) -> &'a SolanaAddress {
+        let total: u32 = split.iter().map(|r| r.weight).sum();
+        let mut draw = (self.next_u64() % u64::from(total.max(1))) as u32;
+        for recipient in split {
+            if draw < recipient.weight {
+                return &recipient.address;
+            }
+            draw -= recipient.weight;
+        }
+        &split[split.len() - 1].address
+    }
+
+    fn next_u64(&self) -> u64 {
+        let mut z = self
+            .state
+            .fetch_add(GOLDEN_GAMMA, Ordering::Relaxed)
+            .wrapping_add(GOLDEN_GAMMA);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl Default for RecipientRouter {
+    fn default() -> Self {
+        Self::new(HashMap::new(), Some(0))
+    }
+}
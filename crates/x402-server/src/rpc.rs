@@ -0,0 +1,417 @@
+//! Fake Solana JSON-RPC for testing payment verification (`rpc:`)
+//!
+//! With `rpc:` configured, `POST /rpc` answers just enough of the Solana
+//! RPC surface to verify a payment: `getTransaction`,
+//! `getSignatureStatuses`, `getBalance` and `requestAirdrop`. Point
+//! `solana_rpc` at `http://127.0.0.1:<port>/rpc` to use it.
+//!
+//! A transaction signature is the memo of the invoice it pays, the same
+//! convention as `X-Payment-Proof`. Signatures starting with a configured
+//! prefix get that prefix's behavior (not found, not yet confirmed, wrong
+//! amount) and the rest of the signature is the memo, so tests can trigger
+//! each verification path deterministically. Transactions are synthesized
+//! from the invoices in the server's [`InvoiceStore`], so invoices dropped
+//! from it (see `max_invoices`) are not found; the fake has no token
+//! accounts, so a transfer's `destination` is the invoice recipient itself.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use x402_domain::{Amount, SolanaAddress, USDC_DECIMALS};
+
+use crate::invoices::InvoiceStore;
+use crate::server::TEST_ADDRESSES;
+
+/// Path of the JSON-RPC endpoint
+pub const RPC_PATH: &str = "/rpc";
+
+/// SPL Token program that synthetic transfers go through
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+/// SPL Memo program carrying the invoice memo
+pub const MEMO_PROGRAM_ID: &str = "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr";
+
+/// Devnet USDC mint of synthetic transfers
+pub const USDC_MINT: &str = "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU";
+
+/// Fee in lamports reported for synthetic transactions
+pub const TRANSACTION_FEE_LAMPORTS: u64 = 5000;
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Slot of the first answer; each call advances it by one
+const FIRST_SLOT: u64 = 300_000_000;
+
+/// How the fake RPC treats a signature
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcBehavior {
+    /// Finalized transfer matching the invoice
+    #[default]
+    Confirmed,
+    /// Unknown to the cluster: no status, no transaction
+    NotFound,
+    /// Processed but not yet confirmed: status without a transaction
+    Unconfirmed,
+    /// Finalized transfer of half the invoice amount
+    WrongAmount,
+}
+
+impl RpcBehavior {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Confirmed => "confirmed",
+            Self::NotFound => "not_found",
+            Self::Unconfirmed => "unconfirmed",
+            Self::WrongAmount => "wrong_amount",
+        }
+    }
+}
+
+/// Settings of the fake RPC; its presence in [`Config`](crate::Config)
+/// enables `/rpc`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RpcConfig {
+    /// Behaviors for signatures starting with a prefix (longest prefix wins)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub behaviors: HashMap<String, RpcBehavior>,
+}
+
+impl RpcConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.behaviors.contains_key("") {
+            bail!(
+                "Empty signature prefix in rpc.behaviors\nFix: Use a distinct prefix per behavior, e.g., \"notfound-\": not_found"
+            );
+        }
+        Ok(())
+    }
+
+    /// Behavior for `signature` and the invoice memo it refers to
+    pub fn resolve<'a>(&self, signature: &'a str) -> (RpcBehavior, &'a str) {
+        self.behaviors
+            .iter()
+            .filter(|(prefix, _)| signature.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or((RpcBehavior::Confirmed, signature), |(prefix, behavior)| {
+                (*behavior, &signature[prefix.len()..])
+            })
+    }
+}
+
+/// What a synthetic transfer pays, taken from an issued invoice
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IssuedTransfer {
+    recipient: String,
+    amount: Amount,
+    block_time: i64,
+}
+
+/// Airdrops and balances; transfers come from the invoice store
+#[derive(Debug, Default)]
+struct Ledger {
+    airdrops: HashSet<String>,
+    balances: HashMap<String, u64>,
+}
+
+/// State behind `/rpc`: the server's invoices and airdropped balances
+pub struct RpcSimulator {
+    config: RpcConfig,
+    invoices: Arc<InvoiceStore>,
+    ledger: Mutex<Ledger>,
+    slot: AtomicU64,
+}
+
+impl RpcSimulator {
+    /// Simulator answering for the invoices in `invoices`
+    pub fn new(config: RpcConfig, invoices: Arc<InvoiceStore>) -> Self {
+        Self {
+            config,
+            invoices,
+            ledger: Mutex::new(Ledger::default()),
+            slot: AtomicU64::new(FIRST_SLOT),
+        }
+    }
+
+    /// Answer a JSON-RPC request body (single call or batch)
+    pub fn handle(&self, body: &[u8]) -> Value {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(Value::Array(calls)) if !calls.is_empty() => {
+                Value::Array(calls.iter().map(|call| self.handle_call(call)).collect())
+            }
+            Ok(call @ Value::Object(_)) => self.handle_call(&call),
+            Ok(_) => error_response(Value::Null, INVALID_REQUEST, "Invalid request"),
+            Err(_) => error_response(Value::Null, PARSE_ERROR, "Parse error"),
+        }
+    }
+
+    fn handle_call(&self, call: &Value) -> Value {
+        let id = call.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = call.get("method").and_then(Value::as_str) else {
+            return error_response(id, INVALID_REQUEST, "Invalid request");
+        };
+        let params = call.get("params").cloned().unwrap_or(json!([]));
+        let slot = self.slot.fetch_add(1, Ordering::Relaxed);
+
+        let result = match method {
+            "getTransaction" => self.get_transaction(&params, slot),
+            "getSignatureStatuses" => self.get_signature_statuses(&params, slot),
+            "getBalance" => self.get_balance(&params, slot),
+            "requestAirdrop" => self.request_airdrop(&params),
+            _ => {
+                return error_response(
+                    id,
+                    METHOD_NOT_FOUND,
+                    &format!("Method not found: {}", method),
+                )
+            }
+        };
+        match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+            Err(message) => error_response(id, INVALID_PARAMS, &message),
+        }
+    }
+
+    /// Behavior, memo and issued invoice of `signature`
+    fn transfer_for<'a>(
+        &self,
+        signature: &'a str,
+    ) -> (RpcBehavior, &'a str, Option<IssuedTransfer>) {
+        let (behavior, memo) = self.config.resolve(signature);
+        let transfer = self.invoices.get(memo).and_then(|record| {
+            record.invoice.as_ref().map(|invoice| IssuedTransfer {
+                recipient: invoice.recipient.to_string(),
+                amount: invoice.amount,
+                block_time: invoice.timestamp.timestamp(),
+            })
+        });
+        (behavior, memo, transfer)
+    }
+
+    fn get_transaction(&self, params: &Value, slot: u64) -> Result<Value, String> {
+        let signature = string_param(params, 0, "signature")?;
+        let (behavior, memo, transfer) = self.transfer_for(signature);
+        let Some(transfer) = transfer else {
+            return Ok(Value::Null);
+        };
+        let paid = match behavior {
+            RpcBehavior::NotFound | RpcBehavior::Unconfirmed => return Ok(Value::Null),
+            RpcBehavior::Confirmed => transfer.amount.to_usdc_lamports(),
+            RpcBehavior::WrongAmount => transfer.amount.to_usdc_lamports() / 2,
+        };
+        Ok(synthetic_transaction(
+            signature, memo, &transfer, paid, slot,
+        ))
+    }
+
+    fn get_signature_statuses(&self, params: &Value, slot: u64) -> Result<Value, String> {
+        let signatures = params
+            .get(0)
+            .and_then(Value::as_array)
+            .ok_or("Invalid params: expected an array of signatures")?;
+        let statuses = signatures
+            .iter()
+            .map(|signature| {
+                let signature = signature
+                    .as_str()
+                    .ok_or("Invalid params: signatures must be strings")?;
+                Ok(self.signature_status(signature, slot))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(json!({ "context": { "slot": slot }, "value": statuses }))
+    }
+
+    fn signature_status(&self, signature: &str, slot: u64) -> Value {
+        let finalized = json!({
+            "slot": slot.saturating_sub(32),
+            "confirmations": null,
+            "err": null,
+            "status": { "Ok": null },
+            "confirmationStatus": "finalized",
+        });
+        if self.ledger.lock().unwrap().airdrops.contains(signature) {
+            return finalized;
+        }
+        match self.transfer_for(signature) {
+            (_, _, None) | (RpcBehavior::NotFound, _, _) => Value::Null,
+            (RpcBehavior::Unconfirmed, _, Some(_)) => json!({
+                "slot": slot,
+                "confirmations": 0,
+                "err": null,
+                "status": { "Ok": null },
+                "confirmationStatus": "processed",
+            }),
+            (RpcBehavior::Confirmed | RpcBehavior::WrongAmount, _, Some(_)) => finalized,
+        }
+    }
+
+    fn get_balance(&self, params: &Value, slot: u64) -> Result<Value, String> {
+        let address = address_param(params)?;
+        let lamports = self
+            .ledger
+            .lock()
+            .unwrap()
+            .balances
+            .get(&address)
+            .copied()
+            .unwrap_or(0);
+        Ok(json!({ "context": { "slot": slot }, "value": lamports }))
+    }
+
+    fn request_airdrop(&self, params: &Value) -> Result<Value, String> {
+        let address = address_param(params)?;
+        let lamports = params
+            .get(1)
+            .and_then(Value::as_u64)
+            .filter(|lamports| *lamports > 0)
+            .ok_or("Invalid params: lamports must be a positive integer")?;
+        let signature = format!("airdrop-{}", uuid::Uuid::new_v4().simple());
+        let mut ledger = self.ledger.lock().unwrap();
+        let balance = ledger.balances.entry(address).or_insert(0);
+        *balance = balance.saturating_add(lamports);
+        ledger.airdrops.insert(signature.clone());
+        Ok(json!(signature))
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "error": { "code": code, "message": message },
+        "id": id,
+    })
+}
+
+fn string_param<'a>(params: &'a Value, index: usize, name: &str) -> Result<&'a str, String> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Invalid params: missing {}", name))
+}
+
+fn address_param(params: &Value) -> Result<String, String> {
+    let address = string_param(params, 0, "pubkey")?;
+    SolanaAddress::new(address)
+        .map(|address| address.to_string())
+        .map_err(|_| "Invalid param: Invalid".to_string())
+}
+
+/// `jsonParsed`-shaped transaction paying `paid` base units to the
+/// invoice recipient with the invoice memo
+fn synthetic_transaction(
+    signature: &str,
+    memo: &str,
+    transfer: &IssuedTransfer,
+    paid: u64,
+    slot: u64,
+) -> Value {
+    let ui_amount = Amount::from_usdc_lamports(paid)
+        .map(|amount| amount.to_canonical_string())
+        .unwrap_or_else(|_| paid.to_string());
+    let payer = TEST_ADDRESSES[TEST_ADDRESSES.len() - 1];
+    json!({
+        "slot": slot.saturating_sub(32),
+        "blockTime": transfer.block_time,
+        "meta": {
+            "err": null,
+            "fee": TRANSACTION_FEE_LAMPORTS,
+            "status": { "Ok": null },
+        },
+        "transaction": {
+            "signatures": [signature],
+            "message": {
+                "accountKeys": [
+                    { "pubkey": payer, "signer": true, "writable": true },
+                    { "pubkey": transfer.recipient, "signer": false, "writable": true },
+                    { "pubkey": USDC_MINT, "signer": false, "writable": false },
+                ],
+                "instructions": [
+                    {
+                        "program": "spl-token",
+                        "programId": TOKEN_PROGRAM_ID,
+                        "parsed": {
+                            "type": "transferChecked",
+                            "info": {
+                                "authority": payer,
+                                "source": payer,
+                                "destination": transfer.recipient,
+                                "mint": USDC_MINT,
+                                "tokenAmount": {
+                                    "amount": paid.to_string(),
+                                    "decimals": USDC_DECIMALS,
+                                    "uiAmountString": ui_amount,
+                                },
+                            },
+                        },
+                    },
+                    {
+                        "program": "spl-memo",
+                        "programId": MEMO_PROGRAM_ID,
+                        "parsed": memo,
+                    },
+                ],
+            },
+        },
+        "version": "legacy",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(behaviors: &[(&str, RpcBehavior)]) -> RpcConfig {
+        RpcConfig {
+            behaviors: behaviors
+                .iter()
+                .map(|(prefix, behavior)| (prefix.to_string(), *behavior))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_longest_prefix_selects_behavior() {
+        let config = config(&[
+            ("slow-", RpcBehavior::Unconfirmed),
+            ("slow-short-", RpcBehavior::WrongAmount),
+        ]);
+        assert_eq!(config.resolve("req-1"), (RpcBehavior::Confirmed, "req-1"));
+        assert_eq!(
+            config.resolve("slow-req-1"),
+            (RpcBehavior::Unconfirmed, "req-1")
+        );
+        assert_eq!(
+            config.resolve("slow-short-req-1"),
+            (RpcBehavior::WrongAmount, "req-1")
+        );
+    }
+
+    #[test]
+    fn test_empty_prefix_rejected() {
+        let err = config(&[("", RpcBehavior::NotFound)])
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("Empty signature prefix"));
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        let rpc = RpcSimulator::new(RpcConfig::default(), Arc::default());
+        assert_eq!(rpc.handle(b"{not json")["error"]["code"], PARSE_ERROR);
+        assert_eq!(rpc.handle(b"42")["error"]["code"], INVALID_REQUEST);
+        let unknown = rpc.handle(br#"{"jsonrpc":"2.0","id":7,"method":"getSlot"}"#);
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(unknown["id"], 7);
+        let bad =
+            rpc.handle(br#"{"jsonrpc":"2.0","id":1,"method":"getBalance","params":["0OIl"]}"#);
+        assert_eq!(bad["error"]["code"], INVALID_PARAMS);
+    }
+}
@@ -0,0 +1,603 @@
+// Scenario bundles: config, policy and test suite in one file
+//
+// An `x402-scenario.yaml` holds the three files a facilitator setup needs
+// to stay consistent. Each section is either embedded or a path relative to
+// the scenario file. The scenario and the files it references may each be
+// YAML or JSON:
+//
+// ```yaml
+// name: premium-allowlist
+// config:                  # mock server settings (as in `Config`)
+//   pricing:
+//     default: 0.01
+//     per_resource:
+//       /api/premium/*: 0.05
+// policy: policies/agents.yaml
+// suite:
+//   tests:
+//     - name: premium price
+//       url: ${base_url}/api/premium/report
+//       expect:
+//         status: 402
+// ```
+//
+// `run` serves the config and policies from a `TestServer`, runs the suite
+// with `${base_url}` pointing at it, and stops the server again.
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::server::{Config, ConfigBuilder, MockServerConfig};
+use crate::stats::StatsSnapshot;
+use crate::test_server::TestServer;
+use x402_core::canonical::CONTENT_HASH_FIELD;
+use x402_core::format::{DetectedFormat, FileFormat};
+use x402_core::policy::{
+    parse_policy_document, validate_policies, IssueType, LoadedPolicyFile, RuntimePolicy,
+    Suppressions,
+};
+use x402_core::testing::{
+    execute_test_suite_filtered, format_json, SuiteResult, TestFilter, TestSuite,
+};
+
+/// Suite variable holding the scenario server's URL
+pub const BASE_URL_VARIABLE: &str = "base_url";
+
+/// Conventional scenario file name
+pub const DEFAULT_SCENARIO_FILE: &str = "x402-scenario.yaml";
+
+/// Top-level layout of a scenario file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioDocument {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    config: Option<SectionSource>,
+    #[serde(default)]
+    policy: Option<SectionSource>,
+    suite: SectionSource,
+}
+
+/// A section written inline or as a path to its own file
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SectionSource {
+    File(PathBuf),
+    Inline(serde_yaml::Value),
+}
+
+/// Part of a scenario file an issue belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScenarioSection {
+    Config,
+    Policy,
+    Suite,
+}
+
+impl fmt::Display for ScenarioSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScenarioSection::Config => "config",
+            ScenarioSection::Policy => "policy",
+            ScenarioSection::Suite => "suite",
+        })
+    }
+}
+
+/// Problem found by a section's validator
+#[derive(Debug, Clone)]
+pub struct ScenarioIssue {
+    pub section: ScenarioSection,
+    /// `<scenario>#<section>[.policies[N]]`, followed by the referenced
+    /// file in parentheses when the section is a path
+    pub location: String,
+    pub issue_type: IssueType,
+    pub message: String,
+}
+
+impl fmt::Display for ScenarioIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Outcome of validating every section of a scenario file
+#[derive(Debug)]
+pub struct ScenarioValidation {
+    /// The runnable scenario, unless a section has errors
+    pub scenario: Option<Scenario>,
+    /// Errors and warnings in file order (config, policy, suite)
+    pub issues: Vec<ScenarioIssue>,
+}
+
+impl ScenarioValidation {
+    pub fn has_errors(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| issue.issue_type == IssueType::Error)
+    }
+
+    fn push(
+        &mut self,
+        section: &SectionContext,
+        suffix: &str,
+        issue_type: IssueType,
+        message: String,
+    ) {
+        self.issues.push(ScenarioIssue {
+            section: section.section,
+            location: section.location(suffix),
+            issue_type,
+            message,
+        });
+    }
+}
+
+/// Where a section's text came from, for issue locations
+struct SectionContext {
+    section: ScenarioSection,
+    scenario: String,
+    file: Option<PathBuf>,
+}
+
+impl SectionContext {
+    fn location(&self, suffix: &str) -> String {
+        let location = format!("{}#{}{}", self.scenario, self.section, suffix);
+        match &self.file {
+            Some(file) => format!("{} ({})", location, file.display()),
+            None => location,
+        }
+    }
+}
+
+/// A validated scenario, ready to [`run`](Self::run)
+#[derive(Debug)]
+pub struct Scenario {
+    pub name: String,
+    pub description: Option<String>,
+    /// Server settings with the `policy:` section's runtime policies appended
+    pub config: Config,
+    pub suite: TestSuite,
+}
+
+impl Scenario {
+    /// Load and validate a scenario file, failing on any section error
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let validation = validate_scenario(path)?;
+        match validation.scenario {
+            Some(scenario) => Ok(scenario),
+            None => {
+                let errors: Vec<String> = validation
+                    .issues
+                    .iter()
+                    .filter(|issue| issue.issue_type == IssueType::Error)
+                    .map(ToString::to_string)
+                    .collect();
+                bail!(
+                    "Invalid scenario {}:\n{}\nFix: Correct the sections listed above, then run x402-dev scenario validate {}",
+                    path.display(),
+                    errors.join("\n"),
+                    path.display()
+                )
+            }
+        }
+    }
+
+    /// Serve the scenario's config from a [`TestServer`], run the suite
+    /// against it and stop the server
+    ///
+    /// `${base_url}` in the suite resolves to the server's URL, replacing
+    /// any `base_url` variable the suite defines.
+    pub async fn run(mut self, filter: &TestFilter) -> Result<ScenarioReport> {
+        let server = TestServer::start(MockServerConfig::new(self.config)?).await?;
+        self.suite
+            .variables
+            .insert(BASE_URL_VARIABLE.to_string(), server.base_url().to_string());
+
+        let result = execute_test_suite_filtered(&self.suite, filter).await;
+        let stats = server.stats().await;
+        server.stop().await?;
+
+        Ok(ScenarioReport {
+            name: self.name,
+            suite: result?,
+            stats: stats?,
+        })
+    }
+}
+
+/// Suite results and server stats of one scenario run
+#[derive(Debug)]
+pub struct ScenarioReport {
+    pub name: String,
+    pub suite: SuiteResult,
+    /// `/__admin/stats` of the scenario server after the suite finished
+    pub stats: StatsSnapshot,
+}
+
+impl ScenarioReport {
+    /// 0 when every test passed, 1 otherwise
+    pub fn exit_code(&self) -> i32 {
+        self.suite.exit_code()
+    }
+
+    /// `{"scenario", "passed", "suite", "stats"}`, with `suite` in the
+    /// `x402-dev test --json` format
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut suite: serde_json::Value =
+            serde_json::from_str(&format_json(&self.suite)).unwrap_or_default();
+        // The combined report is hashed as a whole when written
+        if let Some(suite) = suite.as_object_mut() {
+            suite.remove(CONTENT_HASH_FIELD);
+        }
+        serde_json::json!({
+            "scenario": self.name,
+            "passed": self.suite.failed == 0,
+            "suite": suite,
+            "stats": self.stats,
+        })
+    }
+}
+
+/// Validate the config, policy and suite sections of a scenario file
+///
+/// Every section is checked even when an earlier one fails. Only a missing
+/// or malformed scenario file is an `Err`; section problems are reported as
+/// [`ScenarioIssue`]s.
+pub fn validate_scenario(path: &Path) -> Result<ScenarioValidation> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read scenario file: {}", path.display()))?;
+    let document: ScenarioDocument = DetectedFormat::detect(Some(path), &content)
+        .parse(&content)
+        .map_err(|e| {
+            anyhow!(
+                "Invalid scenario file: {}: {:#}\nFix: Use the top-level keys name, description, config, policy and suite (suite is required)",
+                path.display(),
+                e
+            )
+        })?;
+
+    let scenario_name = path.display().to_string();
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut validation = ScenarioValidation {
+        scenario: None,
+        issues: Vec::new(),
+    };
+
+    let config = match &document.config {
+        Some(source) => load_config(source, dir, &scenario_name, &mut validation),
+        None => Some(Config::default()),
+    };
+    let policies = match &document.policy {
+        Some(source) => load_policies(source, dir, &scenario_name, &mut validation),
+        None => Some(Vec::new()),
+    };
+    let suite = load_suite(&document.suite, path, &scenario_name, &mut validation);
+
+    if let (Some(mut config), Some(policies), Some(suite)) = (config, policies, suite) {
+        if !validation.has_errors() {
+            config.policies.extend(policies);
+            validation.scenario = Some(Scenario {
+                name: document.name.unwrap_or_else(|| {
+                    path.file_stem().map_or_else(
+                        || scenario_name.clone(),
+                        |s| s.to_string_lossy().into_owned(),
+                    )
+                }),
+                description: document.description,
+                config,
+                suite,
+            });
+        }
+    }
+    Ok(validation)
+}
+
+/// Text of a section, its format and where it came from
+///
+/// Inline sections are re-serialized as YAML; referenced files keep their
+/// own format.
+fn read_section(
+    source: &SectionSource,
+    section: ScenarioSection,
+    dir: &Path,
+    scenario: &str,
+) -> (SectionContext, Result<(String, DetectedFormat)>) {
+    match source {
+        SectionSource::Inline(value) => (
+            SectionContext {
+                section,
+                scenario: scenario.to_string(),
+                file: None,
+            },
+            serde_yaml::to_string(value)
+                .map(|text| (text, DetectedFormat::given(FileFormat::Yaml)))
+                .map_err(Into::into),
+        ),
+        SectionSource::File(file) => {
+            let file = dir.join(file);
+            let text = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {} file", section))
+                .map(|text| {
+                    let format = DetectedFormat::detect(Some(&file), &text);
+                    (text, format)
+                });
+            (
+                SectionContext {
+                    section,
+                    scenario: scenario.to_string(),
+                    file: Some(file),
+                },
+                text,
+            )
+        }
+    }
+}
+
+fn load_config(
+    source: &SectionSource,
+    dir: &Path,
+    scenario: &str,
+    validation: &mut ScenarioValidation,
+) -> Option<Config> {
+    let (context, text) = read_section(source, ScenarioSection::Config, dir, scenario);
+    let config = text.and_then(|(text, format)| {
+        let config: Config = format.parse(&text)?;
+        ConfigBuilder::from(config).build()
+    });
+    match config {
+        Ok(config) => Some(config),
+        Err(e) => {
+            validation.push(&context, "", IssueType::Error, format!("{:#}", e));
+            None
+        }
+    }
+}
+
+/// Runtime policies of the section; simple rules go through conflict
+/// detection first, honoring the section's `suppress:` lists
+fn load_policies(
+    source: &SectionSource,
+    dir: &Path,
+    scenario: &str,
+    validation: &mut ScenarioValidation,
+) -> Option<Vec<RuntimePolicy>> {
+    let (context, text) = read_section(source, ScenarioSection::Policy, dir, scenario);
+    let loaded =
+        text.and_then(|(text, format)| Ok((parse_policy_document(&text, format)?, text, format)));
+    let file = match loaded {
+        Ok((LoadedPolicyFile::Runtime(file), ..)) => return Some(file.policies),
+        Ok((LoadedPolicyFile::Simple(file), text, format)) => {
+            match Suppressions::parse(&text, format) {
+                Ok(suppressions) => {
+                    let mut report = validate_policies(&file.to_config());
+                    report.apply_suppressions(&suppressions);
+                    for issue in report.issues.iter().filter(|i| !i.suppressed) {
+                        if issue.issue_type == IssueType::Info {
+                            continue;
+                        }
+                        let suffix = match issue.policy_indices.first() {
+                            Some(index) => format!(".policies[{}]", index),
+                            None => String::new(),
+                        };
+                        validation.push(
+                            &context,
+                            &suffix,
+                            issue.issue_type.clone(),
+                            format!("{} [{}]", issue.message, issue.code),
+                        );
+                    }
+                    if report.has_errors {
+                        return None;
+                    }
+                }
+                Err(e) => {
+                    validation.push(&context, "", IssueType::Error, format!("{:#}", e));
+                    return None;
+                }
+            }
+            file
+        }
+        Err(e) => {
+            validation.push(&context, "", IssueType::Error, format!("{:#}", e));
+            return None;
+        }
+    };
+
+    match file.to_runtime() {
+        Ok(runtime) => Some(runtime.policies),
+        Err(e) => {
+            validation.push(&context, "", IssueType::Error, format!("{:#}", e));
+            None
+        }
+    }
+}
+
+fn load_suite(
+    source: &SectionSource,
+    scenario_path: &Path,
+    scenario: &str,
+    validation: &mut ScenarioValidation,
+) -> Option<TestSuite> {
+    let dir = scenario_path.parent().unwrap_or_else(|| Path::new(""));
+    let (context, text) = read_section(source, ScenarioSection::Suite, dir, scenario);
+    // `import:` paths resolve against the file the suite is written in
+    let origin = context
+        .file
+        .clone()
+        .unwrap_or_else(|| scenario_path.to_path_buf());
+    let suite = match text.and_then(|(text, format)| TestSuite::from_str_at(&text, format, &origin))
+    {
+        Ok(suite) => suite,
+        Err(e) => {
+            validation.push(&context, "", IssueType::Error, format!("{:#}", e));
+            return None;
+        }
+    };
+
+    let reference = format!("${{{}}}", BASE_URL_VARIABLE);
+    for test in suite.tests.iter().filter(|t| !t.url.contains(&reference)) {
+        validation.push(
+            &context,
+            "",
+            IssueType::Warning,
+            format!(
+                "Test '{}' does not use {} and will not reach the scenario server\nFix: Start its url with {}",
+                test.name, reference, reference
+            ),
+        );
+    }
+    Some(suite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const SUITE: &str = r#"
+suite:
+  tests:
+    - name: priced
+      url: ${base_url}/api/data
+      expect:
+        status: 402
+"#;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_inline_sections_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            DEFAULT_SCENARIO_FILE,
+            &format!(
+                "name: inline\nconfig:\n  pricing:\n    default: 0.02\npolicy:\n  policies:\n    - type: denylist\n      field: agent_id\n      values: [agent-bad]\n{}",
+                SUITE
+            ),
+        );
+
+        let scenario = Scenario::from_file(&path).unwrap();
+        assert_eq!(scenario.name, "inline");
+        assert_eq!(scenario.config.pricing.default, 0.02);
+        assert!(scenario.config.console_output);
+        assert!(!scenario.config.policies.is_empty());
+        assert_eq!(scenario.suite.tests.len(), 1);
+    }
+
+    #[test]
+    fn test_referenced_sections_resolve_against_scenario_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "server.yaml", "pricing:\n  default: 0.03\n");
+        write(
+            dir.path(),
+            "suite.yaml",
+            "tests:\n  - name: t\n    url: ${base_url}/x\n    expect:\n      status: 402\n",
+        );
+        let path = write(
+            dir.path(),
+            "bundle.yaml",
+            "config: server.yaml\nsuite: suite.yaml\n",
+        );
+
+        let scenario = Scenario::from_file(&path).unwrap();
+        assert_eq!(scenario.name, "bundle");
+        assert_eq!(scenario.config.pricing.default, 0.03);
+    }
+
+    #[test]
+    fn test_every_section_reports_its_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            DEFAULT_SCENARIO_FILE,
+            r#"
+config:
+  port: 80
+policy: missing.yaml
+suite:
+  tests:
+    - name: dup
+      url: ${base_url}/a
+      expect: { status: 402 }
+    - name: dup
+      url: ${base_url}/b
+      expect: { status: 402 }
+"#,
+        );
+
+        let validation = validate_scenario(&path).unwrap();
+        assert!(validation.scenario.is_none());
+        let sections: Vec<_> = validation.issues.iter().map(|i| i.section).collect();
+        assert_eq!(
+            sections,
+            [
+                ScenarioSection::Config,
+                ScenarioSection::Policy,
+                ScenarioSection::Suite
+            ]
+        );
+        let scenario = path.display().to_string();
+        assert_eq!(
+            validation.issues[0].location,
+            format!("{}#config", scenario)
+        );
+        assert!(validation.issues[1].location.ends_with("missing.yaml)"));
+        assert!(validation.issues[2].message.contains("Duplicate test name"));
+    }
+
+    #[test]
+    fn test_policy_conflicts_point_at_the_rule() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            DEFAULT_SCENARIO_FILE,
+            &format!(
+                "policy:\n  policies:\n    - type: allowlist\n      field: agent_id\n      values: [a]\n    - type: denylist\n      field: agent_id\n      values: [a]\n{}",
+                SUITE
+            ),
+        );
+
+        let validation = validate_scenario(&path).unwrap();
+        let issue = &validation.issues[0];
+        assert_eq!(issue.section, ScenarioSection::Policy);
+        assert_eq!(issue.issue_type, IssueType::Error);
+        assert!(issue.location.contains("#policy.policies["), "{}", issue);
+    }
+
+    #[test]
+    fn test_urls_without_base_url_warn() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            DEFAULT_SCENARIO_FILE,
+            "suite:\n  tests:\n    - name: external\n      url: http://example.com/\n      expect: { status: 200 }\n",
+        );
+
+        let validation = validate_scenario(&path).unwrap();
+        assert!(validation.scenario.is_some());
+        assert_eq!(validation.issues.len(), 1);
+        assert_eq!(validation.issues[0].issue_type, IssueType::Warning);
+    }
+
+    #[test]
+    fn test_unknown_top_level_key_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write(
+            dir.path(),
+            DEFAULT_SCENARIO_FILE,
+            &format!("polcy: x\n{}", SUITE),
+        );
+        assert!(validate_scenario(&path).is_err());
+    }
+}
@@ -1,9 +1,61 @@
 use actix_cors::Cors;
+use actix_web::dev::{Server, ServerHandle, Service};
+use actix_web::http::header::HeaderName;
 use actix_web::{web, App, HttpServer};
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::net::{IpAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Instant, SystemTime};
+use tracing::Level;
+
+use crate::access::{is_loopback, DEFAULT_BIND_ADDRESS};
+use crate::event_handler::{EventHandler, EventHandlers};
+use crate::events::{EventLog, ADMIN_EVENTS_PATH};
+use crate::idle::{IdleShutdown, WATCHDOG_INTERVAL};
+use crate::invoices::{InvoiceStore, ADMIN_INVOICES_PATH, DEFAULT_MAX_INVOICES};
+use crate::logging::{parse_level, LogFormat};
+use crate::maintenance::{
+    validate_maintenance, MaintenanceRule, MaintenanceSwitch, ADMIN_MAINTENANCE_PATH,
+};
+use crate::process::PidMetadata;
+use crate::readiness::{
+    validate_startup_hooks, Readiness, StartupHook, DEFAULT_READY_TIMEOUT_SECS, HEALTH_LIVE_PATH,
+    HEALTH_READY_PATH,
+};
+use crate::responses::{validate_responses, ResponseSpec};
+use crate::routing::{validate_recipient_routing, RecipientRoute, RecipientRouter};
+use crate::rpc::{RpcConfig, RpcSimulator, RPC_PATH};
+use crate::signing::SigningConfig;
+use crate::snapshot::{ADMIN_SNAPSHOT_PATH, MAX_SNAPSHOT_BYTES};
+use crate::stats::{StatsRegistry, ADMIN_STATS_PATH, ADMIN_STATS_RESET_PATH, DEFAULT_PATTERN};
+use crate::tls::TlsConfig;
+use crate::tolerance::AmountTolerance;
+use crate::webhooks::{WebhookConfig, WebhookDispatcher};
+use x402_core::clock::{Clock, SystemClock, VirtualClock};
+use x402_core::policy::{
+    AuditLog, AuditLogConfig, MatchStats, PolicyEngine, QuotaHeaders, RuntimePolicy,
+    StateStoreConfig, StoreInstance,
+};
+use x402_core::receipt::PaymentReceipt;
+use x402_core::redact::{redact_url, HeaderRedactor};
+use x402_core::solana_rpc::SolanaRpcClient;
+use x402_core::verifier::VerifierKind;
+use x402_domain::{
+    Amount, IdFormat, IdGenerator, InvoiceMemo, MemoPrefix, Port, ProtocolId, ResourcePath,
+    SolanaAddress,
+};
 
 // Re-export types needed by handlers and lifecycle
-pub use crate::handlers::payment_required_handler;
+pub use crate::handlers::{
+    advance_clock_handler, clear_maintenance_handler, clock_handler, events_handler,
+    health_handler, invoice_handler, invoices_handler, liveness_handler, maintenance_handler,
+    payment_required_handler, reset_stats_handler, restore_snapshot_handler, rpc_handler,
+    set_maintenance_handler, set_simulation_mode_handler, simulation_mode_handler,
+    snapshot_handler, stats_handler,
+};
 
 // Import from CLI crate (temporary - will move to x402-core later)
 // For now, we need to access these from the calling code
@@ -22,98 +74,1426 @@ pub enum SimulationMode {
     Timeout,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl std::str::FromStr for SimulationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "success" => Ok(SimulationMode::Success),
+            "fail" | "failure" => Ok(SimulationMode::Failure),
+            "timeout" => Ok(SimulationMode::Timeout),
+            _ => Err(format!(
+                "Invalid simulation mode: '{}'. Valid values: success, failure, timeout",
+                s
+            )),
+        }
+    }
+}
+
+impl SimulationMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SimulationMode::Success => "success",
+            SimulationMode::Failure => "failure",
+            SimulationMode::Timeout => "timeout",
+        }
+    }
+}
+
+/// Simulation mode of a running server, switchable without a restart
+///
+/// Starts at the configured mode and is changed through
+/// `/__admin/simulation-mode`. A request's `X-Simulation-Mode` header still
+/// takes precedence.
+#[derive(Debug)]
+pub struct SimulationSwitch {
+    mode: RwLock<SimulationMode>,
+}
+
+impl SimulationSwitch {
+    pub fn new(mode: SimulationMode) -> Self {
+        Self {
+            mode: RwLock::new(mode),
+        }
+    }
+
+    pub fn get(&self) -> SimulationMode {
+        *self.mode.read().unwrap()
+    }
+
+    /// Switch to `mode`, returning the previous mode
+    pub fn set(&self, mode: SimulationMode) -> SimulationMode {
+        std::mem::replace(&mut *self.mode.write().unwrap(), mode)
+    }
+}
+
+/// What the server does when its configured port is taken
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PortFallback {
+    /// Exit with code 2
+    #[default]
+    Fail,
+    /// Try the next `port_fallback_attempts` ports in order
+    Auto,
+}
+
+impl std::str::FromStr for PortFallback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fail" => Ok(PortFallback::Fail),
+            "auto" => Ok(PortFallback::Auto),
+            _ => Err(format!(
+                "Invalid port fallback: '{}'. Valid values: fail, auto",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for PortFallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortFallback::Fail => write!(f, "fail"),
+            PortFallback::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// What the server does for requests priced at exactly 0
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ZeroPriceBehavior {
+    /// 200 without an invoice; counted as a free hit in stats
+    #[default]
+    Serve,
+    /// 402 with an amount=0 invoice, as for any other price
+    Invoice,
+}
+
+/// Mock server settings
+///
+/// Construct with [`Config::builder`] or [`Config::default`]; the struct is
+/// `#[non_exhaustive]` so new settings are not breaking changes. Fields
+/// missing from serialized configs take their default values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
 pub struct Config {
-    pub port: u16,
+    pub port: Port,
+    /// Interface to listen on: loopback (default), a LAN address, or
+    /// `0.0.0.0` / `::` for all interfaces
+    #[serde(skip_serializing_if = "is_default_bind_address")]
+    pub bind_address: IpAddr,
+    /// Answer `/__admin/*` for loopback peers only, whatever `bind_address` is
+    pub admin_loopback_only: bool,
+    /// Behaviour when `port` is already in use
+    pub port_fallback: PortFallback,
+    /// Ports after `port` tried with [`PortFallback::Auto`]
+    pub port_fallback_attempts: u16,
     pub solana_rpc: String,
+    /// Lowest level of request log lines (`error` to `trace`)
     pub log_level: String,
+    /// Request log lines as human-readable text or JSON
+    #[serde(skip_serializing_if = "is_default_log_format")]
+    pub log_format: LogFormat,
     pub pricing: PricingConfig,
+    /// Free paths (price 0) are served directly unless set to `invoice`
+    #[serde(skip_serializing_if = "is_default_zero_price_behavior")]
+    pub zero_price_behavior: ZeroPriceBehavior,
     pub simulation_mode: SimulationMode,
     pub timeout_delay_ms: u64,
+    /// Serve over https:// when set
+    pub tls: Option<TlsConfig>,
+    /// Accept cleartext HTTP/2 (h2c prior knowledge) alongside HTTP/1.1;
+    /// TLS listeners always negotiate h2 via ALPN
+    pub http2: bool,
+    /// Event webhook subscriptions
+    pub webhooks: Vec<WebhookConfig>,
+    /// Runtime policies enforced before invoicing (disabled when empty)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<RuntimePolicy>,
+    /// Where policy rate limit and spending counters are kept
+    pub policy_state: StateStoreConfig,
+    /// Write policy decisions to a JSONL file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<AuditLogConfig>,
+    /// Sign invoices with Ed25519 (needs the `signing` feature)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
+    /// Seconds an invoice stays payable (`expires` header field)
+    pub invoice_ttl_seconds: u64,
+    /// TTL overrides for exact paths or `/prefix/*` patterns
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub invoice_ttl_per_resource: HashMap<String, u64>,
+    /// Methods accepted on exact paths or `/prefix/*` patterns; others get
+    /// 405 (paths without an entry accept all [`SUPPORTED_METHODS`])
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub methods: HashMap<String, Vec<String>>,
+    /// Address every invoice pays to (default: rotate through [`TEST_ADDRESSES`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<SolanaAddress>,
+    /// Recipients for exact paths or `/prefix/*` patterns, before `recipient`
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub recipient_routing: HashMap<String, RecipientRoute>,
+    /// Seed for weighted recipient picks (clock-seeded when unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_routing_seed: Option<u64>,
+    /// Paths answering 503 with `Retry-After` from the start; more can be
+    /// switched on at `/__admin/maintenance`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub maintenance: Vec<MaintenanceRule>,
+    /// Bodies served after a verified payment, for exact paths or
+    /// `/prefix/*` patterns (others get a JSON acknowledgement)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub responses: HashMap<String, ResponseSpec>,
+    /// Prefix before the UUID in invoice memos (default `req-`)
+    #[serde(skip_serializing_if = "MemoPrefix::is_default")]
+    pub memo_prefix: MemoPrefix,
+    /// Auth scheme starting invoice headers (default `x402-solana`)
+    #[serde(skip_serializing_if = "ProtocolId::is_default")]
+    pub protocol_id: ProtocolId,
+    /// Format of memo and request IDs: `uuidv4` (default), or the
+    /// time-ordered `uuidv7` or `ulid`
+    #[serde(skip_serializing_if = "IdFormat::is_default")]
+    pub id_format: IdFormat,
+    /// Seed for reproducible `uuidv4` or `ulid` IDs; for tests only, never
+    /// in production (OS randomness when unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_seed: Option<u64>,
+    /// Request headers masked in logs on top of
+    /// [`x402_core::redact::DEFAULT_SENSITIVE_HEADERS`]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub log_redaction: Vec<String>,
+    /// Accept a payment proof again after it was verified (replays are
+    /// rejected with `replayed_payment` otherwise)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub allow_replay: bool,
+    /// Issued invoices and verified proofs remembered for verification,
+    /// replay protection and `/__admin/invoices`; beyond this expired
+    /// invoices, then the oldest entries, are dropped
+    #[serde(skip_serializing_if = "is_default_max_invoices")]
+    pub max_invoices: usize,
+    /// Accepted difference between an `X-Payment-Amount` and the invoice
+    /// amount (exact by default)
+    #[serde(skip_serializing_if = "AmountTolerance::is_exact")]
+    pub amount_tolerance: AmountTolerance,
+    /// Header names for the remaining quota on requests `policies` allow
+    #[serde(skip_serializing_if = "QuotaHeaders::is_default")]
+    pub quota_headers: QuotaHeaders,
+    /// Fake Solana JSON-RPC on `/rpc` for verifying payments (disabled
+    /// when unset)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc: Option<RpcConfig>,
+    /// How payment proofs are verified: by simulation mode (default) or as
+    /// transaction signatures looked up on `solana_rpc`
+    #[serde(skip_serializing_if = "VerifierKind::is_simulated")]
+    pub verifier: VerifierKind,
+    /// Shut down after this many seconds without a non-admin request
+    /// (0 disables)
+    #[serde(skip_serializing_if = "is_zero")]
+    pub idle_timeout_secs: u64,
+    /// Shut down this many seconds after start regardless of activity
+    /// (0 disables)
+    #[serde(skip_serializing_if = "is_zero")]
+    pub max_lifetime_secs: u64,
+    /// Run in order once the listener is attached; the server is not
+    /// ready until all of them succeeded
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub startup_hooks: Vec<StartupHook>,
+    /// Seconds `mock` waits for `/health/ready` before giving up
+    #[serde(skip_serializing_if = "is_default_ready_timeout")]
+    pub ready_timeout_secs: u64,
+    /// Print a line per request on stdout; off for servers embedded in
+    /// other output, such as a [`TestServer`](crate::TestServer)
+    #[serde(skip)]
+    pub console_output: bool,
+    /// Count hits per pricing rule and policy, reported under `rule_hits`
+    /// on `/__admin/stats`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub match_stats: bool,
+    /// Run invoices and policies on a virtual clock that
+    /// `POST /__admin/clock/advance` moves forward
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub allow_time_travel: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_default_ready_timeout(value: &u64) -> bool {
+    *value == DEFAULT_READY_TIMEOUT_SECS
+}
+
+fn is_default_max_invoices(value: &usize) -> bool {
+    *value == DEFAULT_MAX_INVOICES
+}
+
+fn is_default_log_format(format: &LogFormat) -> bool {
+    *format == LogFormat::default()
+}
+
+fn is_default_zero_price_behavior(behavior: &ZeroPriceBehavior) -> bool {
+    *behavior == ZeroPriceBehavior::default()
+}
+
+fn is_default_bind_address(address: &IpAddr) -> bool {
+    *address == DEFAULT_BIND_ADDRESS
+}
+
+/// Ports tried after the configured one when `port_fallback` is auto
+pub const DEFAULT_PORT_FALLBACK_ATTEMPTS: u16 = 10;
+
+/// Most ports `port_fallback` may try
+pub const MAX_PORT_FALLBACK_ATTEMPTS: u16 = 100;
+
+/// Invoice lifetime when no TTL is configured
+pub const DEFAULT_INVOICE_TTL_SECONDS: u64 = 300;
+
+/// Longest configurable invoice lifetime (1 day)
+pub const MAX_INVOICE_TTL_SECONDS: u64 = 86_400;
+
+/// Methods the payment flow answers, in `Allow` header order
+pub const SUPPORTED_METHODS: [&str; 7] =
+    ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"];
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            port: Port::new(3402).expect("3402 is a non-privileged port"),
+            bind_address: DEFAULT_BIND_ADDRESS,
+            admin_loopback_only: true,
+            port_fallback: PortFallback::default(),
+            port_fallback_attempts: DEFAULT_PORT_FALLBACK_ATTEMPTS,
+            solana_rpc: "https://api.devnet.solana.com".to_string(),
+            log_level: "info".to_string(),
+            log_format: LogFormat::default(),
+            pricing: PricingConfig::default(),
+            zero_price_behavior: ZeroPriceBehavior::default(),
+            simulation_mode: SimulationMode::default(),
+            timeout_delay_ms: 5000,
+            tls: None,
+            http2: false,
+            webhooks: vec![],
+            policies: vec![],
+            policy_state: StateStoreConfig::default(),
+            audit_log: None,
+            signing: None,
+            invoice_ttl_seconds: DEFAULT_INVOICE_TTL_SECONDS,
+            invoice_ttl_per_resource: HashMap::new(),
+            methods: HashMap::new(),
+            recipient: None,
+            recipient_routing: HashMap::new(),
+            recipient_routing_seed: None,
+            maintenance: vec![],
+            responses: HashMap::new(),
+            memo_prefix: MemoPrefix::default(),
+            protocol_id: ProtocolId::default(),
+            id_format: IdFormat::default(),
+            id_seed: None,
+            log_redaction: vec![],
+            allow_replay: false,
+            max_invoices: DEFAULT_MAX_INVOICES,
+            amount_tolerance: AmountTolerance::default(),
+            quota_headers: QuotaHeaders::default(),
+            rpc: None,
+            verifier: VerifierKind::default(),
+            idle_timeout_secs: 0,
+            max_lifetime_secs: 0,
+            startup_hooks: vec![],
+            ready_timeout_secs: DEFAULT_READY_TIMEOUT_SECS,
+            console_output: true,
+            match_stats: false,
+            allow_time_travel: false,
+        }
+    }
+}
+
+impl Config {
+    /// Start building a config from the defaults
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+
+    /// `<pid>-<port>`: tells servers sharing a policy state file apart
+    pub fn instance_id(&self) -> String {
+        format!("{}-{}", std::process::id(), self.port)
+    }
+
+    /// Validate configuration values
+    ///
+    /// The port and recipient are checked when the config is deserialized
+    /// or built.
+    pub fn validate(&self) -> Result<()> {
+        if !self.solana_rpc.starts_with("http://") && !self.solana_rpc.starts_with("https://") {
+            bail!(
+                "Invalid Solana RPC URL: {}. URL must start with http:// or https://.\nFix: Use a valid URL, e.g., https://api.devnet.solana.com",
+                redact_url(&self.solana_rpc)
+            );
+        }
+
+        parse_level(&self.log_level)?;
+        self.pricing.validate()?;
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.validate()?;
+        }
+
+        if !(1..=MAX_PORT_FALLBACK_ATTEMPTS).contains(&self.port_fallback_attempts) {
+            bail!(
+                "Invalid port_fallback_attempts: {}. Must be between 1 and {}.\nFix: Set port_fallback_attempts to a small number, e.g., {}",
+                self.port_fallback_attempts,
+                MAX_PORT_FALLBACK_ATTEMPTS,
+                DEFAULT_PORT_FALLBACK_ATTEMPTS
+            );
+        }
+
+        if self.timeout_delay_ms < 100 || self.timeout_delay_ms > 60000 {
+            bail!(
+                "Invalid timeout delay: {} ms. Must be between 100ms and 60000ms (1 minute).\n\
+                Fix: Set timeout_delay_ms to a reasonable value between 100 and 60000",
+                self.timeout_delay_ms
+            );
+        }
+
+        if let Some(tls) = &self.tls {
+            if !tls.self_signed && (tls.cert_path.is_none() || tls.key_path.is_none()) {
+                bail!(
+                    "TLS requires both cert_path and key_path\nFix: Set tls.cert_path and tls.key_path in your config, or use --tls-self-signed"
+                );
+            }
+        }
+
+        if let Some(signing) = &self.signing {
+            signing.validate()?;
+        }
+
+        if self.id_seed.is_some() && self.id_format == IdFormat::Uuidv7 {
+            bail!(
+                "id_seed cannot be used with id_format: uuidv7; uuidv7 IDs always use OS randomness\nFix: Remove id_seed, or use id_format: uuidv4 or ulid for reproducible test IDs"
+            );
+        }
+
+        let overrides = self
+            .invoice_ttl_per_resource
+            .iter()
+            .map(|(path, ttl)| (path.as_str(), *ttl));
+        for (path, ttl) in
+            std::iter::once(("invoice_ttl_seconds", self.invoice_ttl_seconds)).chain(overrides)
+        {
+            if !(1..=MAX_INVOICE_TTL_SECONDS).contains(&ttl) {
+                bail!(
+                    "Invalid invoice TTL for {}: {} seconds. Must be between 1 and {} (1 day).\nFix: Set the TTL to a value in range, e.g., {}",
+                    path,
+                    ttl,
+                    MAX_INVOICE_TTL_SECONDS,
+                    DEFAULT_INVOICE_TTL_SECONDS
+                );
+            }
+        }
+
+        if self.max_invoices == 0 {
+            bail!(
+                "Invalid max_invoices: 0. The mock must remember at least one invoice.\nFix: Set max_invoices to a positive number, e.g., {}",
+                DEFAULT_MAX_INVOICES
+            );
+        }
+
+        validate_recipient_routing(&self.recipient_routing)?;
+        validate_maintenance(&self.maintenance)?;
+        validate_responses(&self.responses)?;
+        validate_startup_hooks(&self.startup_hooks)?;
+        self.amount_tolerance.validate()?;
+        self.quota_headers.validate()?;
+        if let Some(rpc) = &self.rpc {
+            rpc.validate()?;
+        }
+
+        if self.ready_timeout_secs == 0 {
+            bail!(
+                "Invalid ready_timeout_secs: 0. The mock needs time to become ready.\nFix: Set ready_timeout_secs to a positive number, e.g., {}",
+                DEFAULT_READY_TIMEOUT_SECS
+            );
+        }
+
+        for (path, methods) in &self.methods {
+            if methods.is_empty() {
+                bail!(
+                    "No methods listed for {} in methods\nFix: List at least one method, e.g., [GET]",
+                    path
+                );
+            }
+            if let Some(method) = methods
+                .iter()
+                .find(|m| !SUPPORTED_METHODS.contains(&m.as_str()))
+            {
+                bail!(
+                    "Unsupported method {} for {} in methods\nFix: Use uppercase methods from: {}",
+                    method,
+                    path,
+                    SUPPORTED_METHODS.join(", ")
+                );
+            }
+        }
+
+        for webhook in &self.webhooks {
+            if !webhook.url.starts_with("http://") && !webhook.url.starts_with("https://") {
+                bail!(
+                    "Invalid webhook URL: {}. URL must start with http:// or https://.\nFix: Use a full receiver URL, e.g., http://localhost:9000/hooks",
+                    redact_url(&webhook.url)
+                );
+            }
+            if webhook.events.is_empty() {
+                bail!(
+                    "Webhook {} subscribes to no events\nFix: List at least one of invoice.issued, payment.verified, payment.failed, policy.denied",
+                    redact_url(&webhook.url)
+                );
+            }
+        }
+
+        if let Some(name) = self
+            .log_redaction
+            .iter()
+            .find(|name| HeaderName::from_bytes(name.as_bytes()).is_err())
+        {
+            bail!(
+                "Invalid header name in log_redaction: '{}'\nFix: List header names such as X-Session-Token",
+                name
+            );
+        }
+
+        Ok(())
+    }
+
+    /// `log_level` as a tracing level (`info` if it does not parse)
+    pub fn tracing_level(&self) -> Level {
+        parse_level(&self.log_level).unwrap_or(Level::INFO)
+    }
+
+    /// True at `debug` and `trace` log levels, which log request headers
+    pub fn logs_request_headers(&self) -> bool {
+        self.tracing_level() >= Level::DEBUG
+    }
+
+    /// Masks the default sensitive headers and `log_redaction`
+    pub fn header_redactor(&self) -> HeaderRedactor {
+        HeaderRedactor::new(&self.log_redaction)
+    }
+
+    /// Seconds an invoice for `path` stays payable: the most specific
+    /// per-resource override, else `invoice_ttl_seconds`
+    pub fn invoice_ttl_for_path(&self, path: &str) -> u64 {
+        match_resource(&self.invoice_ttl_per_resource, path)
+            .map_or(self.invoice_ttl_seconds, |(_, ttl)| *ttl)
+    }
+
+    /// Body to serve for `path` after a verified payment, if configured
+    pub fn response_for_path(&self, path: &str) -> Option<&ResponseSpec> {
+        match_resource(&self.responses, path).map(|(_, spec)| spec)
+    }
+
+    /// Methods `path` accepts, in [`SUPPORTED_METHODS`] order
+    ///
+    /// HEAD is implied by GET and OPTIONS is always answered.
+    pub fn allowed_methods_for_path(&self, path: &str) -> Vec<&'static str> {
+        let Some((_, listed)) = match_resource(&self.methods, path) else {
+            return SUPPORTED_METHODS.to_vec();
+        };
+        let listed = |method: &str| listed.iter().any(|m| m == method);
+        SUPPORTED_METHODS
+            .into_iter()
+            .filter(|method| match *method {
+                "OPTIONS" => true,
+                "HEAD" => listed("HEAD") || listed("GET"),
+                other => listed(other),
+            })
+            .collect()
+    }
+}
+
+/// Fluent builder for [`Config`], validated on [`build`](Self::build)
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+    /// Port passed to [`port`](Self::port) that [`Port`] rejected
+    invalid_port: Option<u16>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        match Port::new(port) {
+            Ok(port) => {
+                self.config.port = port;
+                self.invalid_port = None;
+            }
+            Err(_) => self.invalid_port = Some(port),
+        }
+        self
+    }
+
+    /// Interface to listen on (default 127.0.0.1)
+    pub fn bind_address(mut self, address: IpAddr) -> Self {
+        self.config.bind_address = address;
+        self
+    }
+
+    /// Restrict `/__admin/*` to loopback peers (default: true)
+    pub fn admin_loopback_only(mut self, enabled: bool) -> Self {
+        self.config.admin_loopback_only = enabled;
+        self
+    }
+
+    /// What to do when the port is taken (default: fail)
+    pub fn port_fallback(mut self, fallback: PortFallback) -> Self {
+        self.config.port_fallback = fallback;
+        self
+    }
+
+    /// Ports to try after the configured one with [`PortFallback::Auto`]
+    pub fn port_fallback_attempts(mut self, attempts: u16) -> Self {
+        self.config.port_fallback_attempts = attempts;
+        self
+    }
+
+    /// Address every invoice pays to instead of the rotating test addresses
+    pub fn recipient(mut self, recipient: SolanaAddress) -> Self {
+        self.config.recipient = Some(recipient);
+        self
+    }
+
+    /// Route invoices for an exact path or a `/prefix/*` pattern
+    pub fn recipient_route(mut self, path: impl Into<String>, route: RecipientRoute) -> Self {
+        self.config.recipient_routing.insert(path.into(), route);
+        self
+    }
+
+    /// Replay the same weighted recipient picks on every run
+    pub fn recipient_routing_seed(mut self, seed: u64) -> Self {
+        self.config.recipient_routing_seed = Some(seed);
+        self
+    }
+
+    /// Answer 503 with `Retry-After` on the paths `rule` matches
+    pub fn maintenance(mut self, rule: MaintenanceRule) -> Self {
+        self.config.maintenance.push(rule);
+        self
+    }
+
+    /// Serve `spec` after a verified payment on an exact path or a
+    /// `/prefix/*` pattern
+    pub fn response(mut self, path: impl Into<String>, spec: ResponseSpec) -> Self {
+        self.config.responses.insert(path.into(), spec);
+        self
+    }
+
+    /// Prefix invoice memos with `prefix` instead of `req-`
+    pub fn memo_prefix(mut self, prefix: MemoPrefix) -> Self {
+        self.config.memo_prefix = prefix;
+        self
+    }
+
+    /// Start invoice headers with `protocol` instead of `x402-solana`
+    pub fn protocol_id(mut self, protocol: ProtocolId) -> Self {
+        self.config.protocol_id = protocol;
+        self
+    }
+
+    /// Write memo and request IDs as `format` instead of `uuidv4`
+    pub fn id_format(mut self, format: IdFormat) -> Self {
+        self.config.id_format = format;
+        self
+    }
+
+    /// Derive memo and request IDs from `seed` (tests only)
+    pub fn id_seed(mut self, seed: u64) -> Self {
+        self.config.id_seed = Some(seed);
+        self
+    }
+
+    pub fn solana_rpc(mut self, url: impl Into<String>) -> Self {
+        self.config.solana_rpc = url.into();
+        self
+    }
+
+    pub fn log_level(mut self, level: impl Into<String>) -> Self {
+        self.config.log_level = level.into();
+        self
+    }
+
+    /// Write request log lines as `pretty` text or `json`
+    pub fn log_format(mut self, format: LogFormat) -> Self {
+        self.config.log_format = format;
+        self
+    }
+
+    /// Also mask these request headers in logs
+    pub fn log_redaction(mut self, headers: Vec<String>) -> Self {
+        self.config.log_redaction = headers;
+        self
+    }
+
+    pub fn pricing(mut self, pricing: PricingConfig) -> Self {
+        self.config.pricing = pricing;
+        self
+    }
+
+    /// Price charged for paths without a per-resource rule
+    pub fn default_price(mut self, amount: f64) -> Self {
+        self.config.pricing.default = amount;
+        self
+    }
+
+    /// Price for an exact path or a `/prefix/*` pattern
+    pub fn resource_price(mut self, path: impl Into<String>, amount: f64) -> Self {
+        self.config.pricing.per_resource.insert(path.into(), amount);
+        self
+    }
+
+    /// Smallest and largest amount a single invoice may carry
+    pub fn amount_bounds(mut self, min: Option<f64>, max: Option<f64>) -> Self {
+        self.config.pricing.min_amount = min;
+        self.config.pricing.max_amount = max;
+        self
+    }
+
+    /// Clamp or reject prices outside the amount bounds
+    pub fn out_of_bounds(mut self, policy: OutOfBounds) -> Self {
+        self.config.pricing.out_of_bounds = policy;
+        self
+    }
+
+    pub fn simulation_mode(mut self, mode: SimulationMode) -> Self {
+        self.config.simulation_mode = mode;
+        self
+    }
+
+    pub fn timeout_delay_ms(mut self, delay_ms: u64) -> Self {
+        self.config.timeout_delay_ms = delay_ms;
+        self
+    }
+
+    /// Serve price-0 paths directly (default) or invoice them for 0
+    pub fn zero_price_behavior(mut self, behavior: ZeroPriceBehavior) -> Self {
+        self.config.zero_price_behavior = behavior;
+        self
+    }
+
+    pub fn allow_replay(mut self, allow: bool) -> Self {
+        self.config.allow_replay = allow;
+        self
+    }
+
+    /// Invoices and proofs remembered at most (default [`DEFAULT_MAX_INVOICES`])
+    pub fn max_invoices(mut self, max: usize) -> Self {
+        self.config.max_invoices = max;
+        self
+    }
+
+    pub fn amount_tolerance(mut self, tolerance: AmountTolerance) -> Self {
+        self.config.amount_tolerance = tolerance;
+        self
+    }
+
+    pub fn quota_headers(mut self, headers: QuotaHeaders) -> Self {
+        self.config.quota_headers = headers;
+        self
+    }
+
+    /// Serve a fake Solana JSON-RPC on `/rpc`
+    pub fn rpc(mut self, rpc: RpcConfig) -> Self {
+        self.config.rpc = Some(rpc);
+        self
+    }
+
+    /// Verify payment proofs with `verifier` (simulated by default)
+    pub fn verifier(mut self, verifier: VerifierKind) -> Self {
+        self.config.verifier = verifier;
+        self
+    }
+
+    /// Shut down after `secs` without a non-admin request (0 disables)
+    pub fn idle_timeout_secs(mut self, secs: u64) -> Self {
+        self.config.idle_timeout_secs = secs;
+        self
+    }
+
+    /// Shut down `secs` after start regardless of activity (0 disables)
+    pub fn max_lifetime_secs(mut self, secs: u64) -> Self {
+        self.config.max_lifetime_secs = secs;
+        self
+    }
+
+    /// Run `hook` before the server reports ready
+    pub fn startup_hook(mut self, hook: StartupHook) -> Self {
+        self.config.startup_hooks.push(hook);
+        self
+    }
+
+    /// Seconds `mock` waits for readiness (default 30)
+    pub fn ready_timeout_secs(mut self, secs: u64) -> Self {
+        self.config.ready_timeout_secs = secs;
+        self
+    }
+
+    /// Print request lines on stdout (default true)
+    pub fn console_output(mut self, enabled: bool) -> Self {
+        self.config.console_output = enabled;
+        self
+    }
+
+    /// Count hits per pricing rule and policy (default false)
+    pub fn match_stats(mut self, enabled: bool) -> Self {
+        self.config.match_stats = enabled;
+        self
+    }
+
+    /// Enable `POST /__admin/clock/advance` (default false)
+    pub fn allow_time_travel(mut self, enabled: bool) -> Self {
+        self.config.allow_time_travel = enabled;
+        self
+    }
+
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.config.tls = Some(tls);
+        self
+    }
+
+    pub fn webhook(mut self, webhook: WebhookConfig) -> Self {
+        self.config.webhooks.push(webhook);
+        self
+    }
+
+    /// Accept cleartext HTTP/2 in addition to HTTP/1.1
+    pub fn http2(mut self, enabled: bool) -> Self {
+        self.config.http2 = enabled;
+        self
+    }
+
+    pub fn webhooks(mut self, webhooks: Vec<WebhookConfig>) -> Self {
+        self.config.webhooks = webhooks;
+        self
+    }
+
+    /// Enforce runtime policies on every request
+    pub fn policies(mut self, policies: Vec<RuntimePolicy>) -> Self {
+        self.config.policies = policies;
+        self
+    }
+
+    /// Keep policy counters in memory (default) or a SQLite file
+    pub fn policy_state(mut self, state: StateStoreConfig) -> Self {
+        self.config.policy_state = state;
+        self
+    }
+
+    /// Log policy decisions (denials always, allows sampled)
+    pub fn audit_log(mut self, audit_log: AuditLogConfig) -> Self {
+        self.config.audit_log = Some(audit_log);
+        self
+    }
+
+    /// Sign invoices (keypair file or ephemeral key)
+    pub fn signing(mut self, signing: SigningConfig) -> Self {
+        self.config.signing = Some(signing);
+        self
+    }
+
+    /// Seconds an invoice stays payable unless a path overrides it
+    pub fn invoice_ttl_seconds(mut self, seconds: u64) -> Self {
+        self.config.invoice_ttl_seconds = seconds;
+        self
+    }
+
+    /// Invoice TTL for an exact path or a `/prefix/*` pattern
+    pub fn invoice_ttl(mut self, path: impl Into<String>, seconds: u64) -> Self {
+        self.config
+            .invoice_ttl_per_resource
+            .insert(path.into(), seconds);
+        self
+    }
+
+    /// Methods accepted on an exact path or a `/prefix/*` pattern
+    pub fn allowed_methods<I, S>(mut self, path: impl Into<String>, methods: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config
+            .methods
+            .insert(path.into(), methods.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Validate and return the config (same errors as [`Config::validate`])
+    pub fn build(self) -> Result<Config> {
+        if let Some(port) = self.invalid_port {
+            bail!(
+                "Invalid port: {}. Port must be between 1024 and 65535.\nFix: Set port to a value in the valid range, e.g., 3402",
+                port
+            );
+        }
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+impl From<Config> for ConfigBuilder {
+    fn from(config: Config) -> Self {
+        Self {
+            config,
+            invalid_port: None,
+        }
+    }
+}
+
+/// What to do with a price outside `min_amount..=max_amount`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutOfBounds {
+    /// Invoice the nearest bound instead
+    #[default]
+    Clamp,
+    /// Refuse to invoice and answer 500
+    Error,
+}
+
+impl std::fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutOfBounds::Clamp => write!(f, "clamp"),
+            OutOfBounds::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PricingConfig {
     pub default: f64,
+    #[serde(default)]
     pub per_resource: HashMap<String, f64>,
+    /// Smallest amount an invoice may carry (facilitators refuse dust)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_amount: Option<f64>,
+    /// Largest amount a single invoice may carry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_amount: Option<f64>,
+    /// Applied when a resolved price falls outside the bounds
+    #[serde(default)]
+    pub out_of_bounds: OutOfBounds,
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            default: 0.01,
+            per_resource: HashMap::new(),
+            min_amount: None,
+            max_amount: None,
+            out_of_bounds: OutOfBounds::default(),
+        }
+    }
+}
+
+/// A resolved price the config forbids invoicing (`out_of_bounds: error`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceOutOfBounds {
+    pub path: String,
+    pub amount: f64,
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+}
+
+impl std::fmt::Display for PriceOutOfBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.min_amount, self.max_amount) {
+            (Some(min), _) if self.amount < min => write!(
+                f,
+                "Price {} for {} is below min_amount {}",
+                self.amount, self.path, min
+            ),
+            (_, Some(max)) => write!(
+                f,
+                "Price {} for {} is above max_amount {}",
+                self.amount, self.path, max
+            ),
+            _ => write!(
+                f,
+                "Price {} for {} is out of bounds",
+                self.amount, self.path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PriceOutOfBounds {}
+
+impl PricingConfig {
+    /// Validate pricing configuration values
+    pub fn validate(&self) -> Result<()> {
+        if self.default < 0.0 {
+            bail!(
+                "Default pricing must be non-negative. Got: {}\nFix: Set default pricing to a non-negative value, e.g., 0.01",
+                self.default
+            );
+        }
+        if self.default > 100.0 {
+            bail!(
+                "Default pricing must be <= 100 SOL. Got: {}\nFix: Set default pricing to a reasonable value, e.g., 0.01",
+                self.default
+            );
+        }
+
+        for (path, amount) in &self.per_resource {
+            if let Some((method, _)) = path.split_once(' ') {
+                if !SUPPORTED_METHODS.contains(&method) {
+                    bail!(
+                        "Unsupported method in pricing rule '{}'\nFix: Prefix the path with an uppercase method, e.g., \"HEAD /api/data\"",
+                        path
+                    );
+                }
+            }
+            if *amount < 0.0 {
+                bail!(
+                    "Pricing for {} must be non-negative. Got: {}\nFix: Set pricing to a non-negative value",
+                    path,
+                    amount
+                );
+            }
+            if *amount > 100.0 {
+                bail!(
+                    "Pricing for {} must be <= 100 SOL. Got: {}\nFix: Set pricing to a reasonable value",
+                    path,
+                    amount
+                );
+            }
+        }
+
+        for (name, bound) in [
+            ("min_amount", self.min_amount),
+            ("max_amount", self.max_amount),
+        ] {
+            if let Some(amount) = bound.filter(|b| *b < 0.0) {
+                bail!(
+                    "Pricing {} must be non-negative. Got: {}\nFix: Set {} to a non-negative value or remove it",
+                    name,
+                    amount,
+                    name
+                );
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_amount, self.max_amount) {
+            if min > max {
+                bail!(
+                    "Pricing bounds are inverted: min_amount {} > max_amount {}\nFix: Swap the values so min_amount <= max_amount",
+                    min,
+                    max
+                );
+            }
+        }
+        if let Some(min) = self.min_amount.filter(|min| self.default < *min) {
+            bail!(
+                "Default pricing {} is below min_amount {}\nFix: Raise default pricing or lower min_amount",
+                self.default,
+                min
+            );
+        }
+        if let Some(max) = self.max_amount.filter(|max| self.default > *max) {
+            bail!(
+                "Default pricing {} is above max_amount {}\nFix: Lower default pricing or raise max_amount",
+                self.default,
+                max
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Apply `min_amount`/`max_amount` to a resolved price for `path`
+    ///
+    /// Clamps to the nearest bound, or returns [`PriceOutOfBounds`] when
+    /// `out_of_bounds` is `error`.
+    pub fn apply_bounds(&self, path: &str, amount: f64) -> Result<f64, PriceOutOfBounds> {
+        let below = self.min_amount.is_some_and(|min| amount < min);
+        let above = self.max_amount.is_some_and(|max| amount > max);
+        if !below && !above {
+            return Ok(amount);
+        }
+
+        match self.out_of_bounds {
+            OutOfBounds::Clamp if below => Ok(self.min_amount.unwrap_or(amount)),
+            OutOfBounds::Clamp => Ok(self.max_amount.unwrap_or(amount)),
+            OutOfBounds::Error => Err(PriceOutOfBounds {
+                path: path.to_string(),
+                amount,
+                min_amount: self.min_amount,
+                max_amount: self.max_amount,
+            }),
+        }
+    }
 }
 
 pub struct PricingMatcher {
     config: PricingConfig,
+    /// Hits per pattern plus `*` for the default price, when enabled
+    match_stats: Option<MatchStats>,
 }
 
 impl PricingMatcher {
     pub fn new(config: PricingConfig) -> Self {
-        PricingMatcher { config }
+        PricingMatcher {
+            config,
+            match_stats: None,
+        }
+    }
+
+    /// Count the rule behind every invoice amount (see [`Self::match_stats`])
+    pub fn with_match_stats(mut self) -> Self {
+        let mut patterns: Vec<&str> = self
+            .config
+            .per_resource
+            .keys()
+            .map(String::as_str)
+            .collect();
+        patterns.sort_unstable();
+        self.match_stats = Some(MatchStats::new(
+            patterns.into_iter().chain([DEFAULT_PATTERN]),
+        ));
+        self
+    }
+
+    /// Hits per pattern (sorted), then `*` for the default price; `None`
+    /// unless enabled with [`Self::with_match_stats`]
+    pub fn match_stats(&self) -> Option<&MatchStats> {
+        self.match_stats.as_ref()
     }
 
     pub fn get_price_for_path(&self, path: &str) -> f64 {
-        // Priority 1: Exact match
-        if let Some(&amount) = self.config.per_resource.get(path) {
-            return amount;
-        }
-
-        // Priority 2: Prefix match (wildcard patterns)
-        let mut matches: Vec<(&str, f64)> = Vec::new();
-        for (pattern, &amount) in &self.config.per_resource {
-            if pattern.ends_with("/*") {
-                let prefix = &pattern[..pattern.len() - 2];
-                if path.starts_with(prefix) {
-                    matches.push((prefix, amount));
-                }
-            }
+        match match_resource(&self.config.per_resource, path) {
+            Some((_, &amount)) => amount,
+            // Priority 3: Default pricing
+            None => self.config.default,
         }
+    }
+
+    /// Per-resource rule that prices `path`, or `None` for the default price
+    pub fn rule_for_path(&self, path: &str) -> Option<&str> {
+        match_resource(&self.config.per_resource, path).map(|(pattern, _)| pattern)
+    }
+
+    /// Amount to invoice for `path`: the matched price with bounds applied
+    ///
+    /// Counts a hit for the matched rule when match stats are enabled.
+    pub fn invoice_amount_for_path(&self, path: &str) -> Result<f64, PriceOutOfBounds> {
+        let matched = match_resource(&self.config.per_resource, path);
+        self.record_hit(matched.map(|(pattern, _)| pattern));
+        let amount = matched.map_or(self.config.default, |(_, &amount)| amount);
+        self.config.apply_bounds(path, amount)
+    }
+
+    /// Per-resource rule that prices a `method` request for `path`
+    ///
+    /// Method-qualified rules (`"HEAD /api/data"`, `"POST /api/*"`) win
+    /// over path rules; HEAD is priced like GET unless it has its own rule.
+    pub fn rule_for_request(&self, method: &str, path: &str) -> Option<&str> {
+        self.match_request(method, path).map(|(pattern, _)| pattern)
+    }
+
+    /// Price for a `method` request, before bounds
+    pub fn get_price_for_request(&self, method: &str, path: &str) -> f64 {
+        self.match_request(method, path)
+            .map_or(self.config.default, |(_, &amount)| amount)
+    }
+
+    /// Amount to invoice for a `method` request with bounds applied
+    ///
+    /// Counts a hit for the matched rule when match stats are enabled.
+    pub fn invoice_amount_for_request(
+        &self,
+        method: &str,
+        path: &str,
+    ) -> Result<f64, PriceOutOfBounds> {
+        let matched = self.match_request(method, path);
+        self.record_hit(matched.map(|(pattern, _)| pattern));
+        let amount = matched.map_or(self.config.default, |(_, &amount)| amount);
+        self.config.apply_bounds(path, amount)
+    }
+
+    /// Rules that apply to `path`, in priority order, ending with the
+    /// default price; the first one is the winner
+    pub fn explain(&self, path: &str) -> MatchExplanation {
+        let mut candidates = Vec::new();
+        self.push_candidates(path, &mut candidates);
+        self.explanation(None, path, candidates)
+    }
 
-        // If multiple wildcards match, use the longest (most specific) prefix
-        if !matches.is_empty() {
-            matches.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
-            return matches[0].1;
+    /// Like [`Self::explain`] for a `method` request: method-qualified rules
+    /// first, then GET rules for HEAD, then path rules
+    pub fn explain_request(&self, method: &str, path: &str) -> MatchExplanation {
+        let mut candidates = Vec::new();
+        self.push_candidates(&format!("{} {}", method, path), &mut candidates);
+        if method == "HEAD" {
+            self.push_candidates(&format!("GET {}", path), &mut candidates);
         }
+        self.push_candidates(path, &mut candidates);
+        self.explanation(Some(method), path, candidates)
+    }
+
+    /// Rules matching `key` in [`match_resource`] order: exact, then
+    /// prefixes from longest to shortest
+    fn push_candidates(&self, key: &str, candidates: &mut Vec<PriceCandidate>) {
+        let rules = &self.config.per_resource;
+        if let Some(&price) = rules.get(key) {
+            candidates.push(PriceCandidate {
+                pattern: key.to_string(),
+                kind: MatchKind::Exact,
+                price,
+                winner: false,
+            });
+        }
+        let mut prefixes: Vec<(&String, &f64)> = rules
+            .iter()
+            .filter(|(pattern, _)| pattern.as_str() != key && prefix_matches(pattern, key))
+            .collect();
+        prefixes.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(b.0)));
+        candidates.extend(
+            prefixes
+                .into_iter()
+                .map(|(pattern, &price)| PriceCandidate {
+                    pattern: pattern.clone(),
+                    kind: MatchKind::Prefix,
+                    price,
+                    winner: false,
+                }),
+        );
+    }
+
+    fn explanation(
+        &self,
+        method: Option<&str>,
+        path: &str,
+        mut candidates: Vec<PriceCandidate>,
+    ) -> MatchExplanation {
+        candidates.push(PriceCandidate {
+            pattern: DEFAULT_PATTERN.to_string(),
+            kind: MatchKind::Default,
+            price: self.config.default,
+            winner: false,
+        });
+        candidates[0].winner = true;
+        let price = candidates[0].price;
+        MatchExplanation {
+            method: method.map(str::to_string),
+            path: path.to_string(),
+            candidates,
+            price,
+            invoice_amount: self.config.apply_bounds(path, price).ok(),
+        }
+    }
+
+    fn record_hit(&self, pattern: Option<&str>) {
+        if let Some(stats) = &self.match_stats {
+            stats.record(pattern.unwrap_or(DEFAULT_PATTERN), SystemTime::now());
+        }
+    }
+
+    fn match_request(&self, method: &str, path: &str) -> Option<(&str, &f64)> {
+        let rules = &self.config.per_resource;
+        match_resource(rules, &format!("{} {}", method, path))
+            .or_else(|| {
+                (method == "HEAD")
+                    .then(|| match_resource(rules, &format!("GET {}", path)))
+                    .flatten()
+            })
+            .or_else(|| match_resource(rules, path))
+    }
+}
+
+/// Pricing rules considered for a path, from [`PricingMatcher::explain`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchExplanation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    pub path: String,
+    /// Matching rules in priority order, ending with the default price
+    pub candidates: Vec<PriceCandidate>,
+    /// Price of the winning rule
+    pub price: f64,
+    /// `price` with `min_amount`/`max_amount` applied; `None` when
+    /// `out_of_bounds: error` rejects it
+    pub invoice_amount: Option<f64>,
+}
 
-        // Priority 3: Default pricing
-        self.config.default
+/// One rule in a [`MatchExplanation`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceCandidate {
+    /// Rule key, or `*` for the default price
+    pub pattern: String,
+    pub kind: MatchKind,
+    pub price: f64,
+    pub winner: bool,
+}
+
+/// How a pricing rule applies to a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// Rule key equals the path
+    Exact,
+    /// `/prefix/*` rule
+    Prefix,
+    /// No rule matched
+    Default,
+}
+
+impl std::fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MatchKind::Exact => "exact",
+            MatchKind::Prefix => "prefix",
+            MatchKind::Default => "default",
+        })
     }
 }
 
+/// Entry of `rules` (exact paths and `/prefix/*` patterns) that applies to `path`
+pub(crate) fn match_resource<'a, T>(
+    rules: &'a HashMap<String, T>,
+    path: &str,
+) -> Option<(&'a str, &'a T)> {
+    // Priority 1: Exact match
+    if let Some((pattern, value)) = rules.get_key_value(path) {
+        return Some((pattern, value));
+    }
+
+    // Priority 2: Prefix match (wildcard patterns)
+    // If multiple wildcards match, use the longest (most specific) prefix
+    rules
+        .iter()
+        .filter(|(pattern, _)| prefix_matches(pattern, path))
+        .max_by_key(|(pattern, _)| pattern.len())
+        .map(|(pattern, value)| (pattern.as_str(), value))
+}
+
+/// True for a `/prefix/*` pattern whose prefix starts `path`
+fn prefix_matches(pattern: &str, path: &str) -> bool {
+    pattern
+        .strip_suffix("/*")
+        .is_some_and(|prefix| path.starts_with(prefix))
+}
+
 // Re-export Invoice types (these will move to x402-core in future)
-pub use chrono::{DateTime, Duration as ChronoDuration, Utc};
+pub use chrono::{DateTime, Duration as ChronoDuration, SecondsFormat, Utc};
 pub use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Invoice {
-    pub recipient: String,
-    pub amount: f64,
+    pub recipient: SolanaAddress,
+    /// Written as a number (`"amount": 0.01`)
+    #[serde(with = "x402_domain::amount::as_number")]
+    pub amount: Amount,
     pub currency: String,
-    pub memo: String,
+    pub memo: InvoiceMemo,
     pub network: String,
     pub timestamp: DateTime<Utc>,
-    pub resource_path: String,
+    pub resource_path: ResourcePath,
     pub expires_at: DateTime<Utc>,
+    /// Auth scheme of the `WWW-Authenticate` header
+    #[serde(default, skip_serializing_if = "ProtocolId::is_default")]
+    pub protocol: ProtocolId,
+    /// Base58 Ed25519 public key of a signed invoice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    /// Base58 signature over [`Invoice::canonical_www_authenticate`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// `recipient_routing` pattern that chose the recipient
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
 }
 
 impl Invoice {
-    pub fn new(amount: f64, resource_path: &str, recipient: String) -> Self {
-        let now = Utc::now();
-        let expires_at = now + ChronoDuration::minutes(5);
+    /// New invoice payable for `ttl_seconds` from now
+    pub fn new(
+        amount: Amount,
+        resource_path: ResourcePath,
+        recipient: SolanaAddress,
+        memo: InvoiceMemo,
+        ttl_seconds: u64,
+    ) -> Self {
+        Self::issued_at(
+            amount,
+            resource_path,
+            recipient,
+            memo,
+            ttl_seconds,
+            Utc::now(),
+        )
+    }
+
+    /// New invoice issued at `now`, payable for `ttl_seconds`
+    pub fn issued_at(
+        amount: Amount,
+        resource_path: ResourcePath,
+        recipient: SolanaAddress,
+        memo: InvoiceMemo,
+        ttl_seconds: u64,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let expires_at = now + ChronoDuration::seconds(ttl_seconds as i64);
 
         Self {
             recipient,
             amount,
             currency: "USDC".to_string(),
-            memo: format!("req-{}", Uuid::new_v4()),
+            memo,
             network: "devnet".to_string(),
             timestamp: now,
-            resource_path: resource_path.to_string(),
+            resource_path,
             expires_at,
+            protocol: ProtocolId::default(),
+            signer: None,
+            signature: None,
+            route: None,
         }
     }
 
-    pub fn format_www_authenticate(&self) -> String {
+    /// Canonical `WWW-Authenticate` value, accepted by `check --strict`
+    ///
+    /// The header starts with [`protocol`](Self::protocol). Fields are always in canonical order (recipient, amount, currency,
+    /// memo, network, expires, ts) and the amount is rounded to USDC's 6
+    /// decimals, so float artifacts like `0.30000000000000004` never reach
+    /// the header. `expires` and the issue time `ts` are RFC 3339 in whole
+    /// seconds, quoted because they contain `:`. This is also the string
+    /// invoice signatures cover.
+    pub fn canonical_www_authenticate(&self) -> String {
         format!(
-            "x402-solana recipient={} amount={} currency={} memo={} network={}",
-            self.recipient, self.amount, self.currency, self.memo, self.network
+            "{} recipient={} amount={} currency={} memo={} network={} expires=\"{}\" ts=\"{}\"",
+            self.protocol,
+            self.recipient,
+            self.amount.to_canonical_string(),
+            self.currency,
+            self.memo,
+            self.network,
+            self.expires_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            self.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true)
         )
     }
+
+    /// `WWW-Authenticate` value: the canonical fields, then `signer` and
+    /// `signature` when the invoice is signed
+    pub fn format_www_authenticate(&self) -> String {
+        let mut header = self.canonical_www_authenticate();
+        if let (Some(signer), Some(signature)) = (&self.signer, &self.signature) {
+            header.push_str(&format!(" signer={} signature={}", signer, signature));
+        }
+        header
+    }
 }
 
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -143,19 +1523,232 @@ pub const TEST_ADDRESSES: &[&str] = &[
 
 pub struct InvoiceGenerator {
     address_index: AtomicUsize,
+    /// Fixed recipient from [`Config::recipient`]
+    recipient: Option<SolanaAddress>,
+    /// Per-path recipients from [`Config::recipient_routing`]
+    router: RecipientRouter,
+    /// Prefix for generated memos from [`Config::memo_prefix`]
+    memo_prefix: MemoPrefix,
+    /// Auth scheme of generated invoices from [`Config::protocol_id`]
+    protocol_id: ProtocolId,
+    /// Memo and request IDs from [`Config::id_format`]
+    ids: IdGenerator,
+    /// Issued invoices and verified proofs
+    invoices: Arc<InvoiceStore>,
+    /// Issue, expiry and verification times
+    clock: Arc<dyn Clock>,
+    #[cfg(feature = "signing")]
+    signer: Option<crate::signing::InvoiceSigner>,
 }
 
 impl InvoiceGenerator {
     pub fn new() -> Self {
         Self {
             address_index: AtomicUsize::new(0),
+            recipient: None,
+            router: RecipientRouter::default(),
+            memo_prefix: MemoPrefix::default(),
+            protocol_id: ProtocolId::default(),
+            ids: IdGenerator::new(IdFormat::default()),
+            invoices: Arc::new(InvoiceStore::default()),
+            clock: Arc::new(SystemClock),
+            #[cfg(feature = "signing")]
+            signer: None,
+        }
+    }
+
+    /// Generator for `config`, using its recipient routing, memo prefix,
+    /// protocol identifier, ID format and signing key if any
+    ///
+    /// Without the `signing` feature, [`Config::validate`] has already
+    /// refused a `signing` section.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut generator = Self::new()
+            .with_invoice_store(InvoiceStore::new(config.max_invoices))
+            .with_memo_prefix(config.memo_prefix.clone())
+            .with_protocol_id(config.protocol_id.clone())
+            .with_ids(match config.id_seed {
+                Some(seed) => IdGenerator::seeded(config.id_format, seed)?,
+                None => IdGenerator::new(config.id_format),
+            })
+            .with_routing(RecipientRouter::new(
+                config.recipient_routing.clone(),
+                config.recipient_routing_seed,
+            ));
+        if let Some(recipient) = &config.recipient {
+            generator = generator.with_recipient(recipient.clone());
+        }
+        #[cfg(feature = "signing")]
+        if let Some(signing) = &config.signing {
+            generator = generator.with_signer(signing.load()?);
+        }
+        Ok(generator)
+    }
+
+    /// Pay every invoice to `recipient` instead of rotating test addresses
+    pub fn with_recipient(mut self, recipient: SolanaAddress) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+
+    /// Pick recipients per path with `router`, falling back to the
+    /// fixed recipient or the test addresses
+    pub fn with_routing(mut self, router: RecipientRouter) -> Self {
+        self.router = router;
+        self
+    }
+
+    /// Remember invoices and proofs in `store` instead of a default-sized one
+    pub fn with_invoice_store(mut self, store: InvoiceStore) -> Self {
+        self.invoices = Arc::new(store);
+        self
+    }
+
+    /// Invoices generated so far and payment proofs verified
+    pub fn invoices(&self) -> &Arc<InvoiceStore> {
+        &self.invoices
+    }
+
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Clock invoices are issued, expired and verified by
+    pub fn clock(&self) -> &Arc<dyn Clock> {
+        &self.clock
+    }
+
+    /// Current time of [`clock`](Self::clock)
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now_utc()
+    }
+
+    /// Start every memo with `prefix` instead of `req-`
+    pub fn with_memo_prefix(mut self, prefix: MemoPrefix) -> Self {
+        self.memo_prefix = prefix;
+        self
+    }
+
+    /// Start every invoice header with `protocol` instead of `x402-solana`
+    pub fn with_protocol_id(mut self, protocol: ProtocolId) -> Self {
+        self.protocol_id = protocol;
+        self
+    }
+
+    /// Generate memo and request IDs with `ids` instead of random UUIDs
+    pub fn with_ids(mut self, ids: IdGenerator) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Fresh memo for the next invoice
+    ///
+    /// The handler takes the memo before invoicing so policy evaluation can
+    /// record its UUID; pass it on with [`generate_with_memo`](Self::generate_with_memo).
+    /// Time-ordered formats take the creation time from [`clock`](Self::clock).
+    pub fn next_memo(&self) -> InvoiceMemo {
+        InvoiceMemo::generate_with(&self.memo_prefix, &self.ids, self.unix_ms())
+    }
+
+    /// Fresh ID for a request, in the same format as memos
+    pub fn next_request_id(&self) -> String {
+        self.ids.generate(self.unix_ms())
+    }
+
+    fn unix_ms(&self) -> u64 {
+        self.now().timestamp_millis().max(0) as u64
+    }
+
+    /// Sign every generated invoice with `signer`
+    #[cfg(feature = "signing")]
+    pub fn with_signer(mut self, signer: crate::signing::InvoiceSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Recipient every invoice for `path` pays, for receipts
+    ///
+    /// `None` when the recipient is picked per invoice (weighted routes or
+    /// rotating test addresses), so a later payment cannot be matched to it.
+    pub fn receipt_recipient(&self, path: &str) -> Option<SolanaAddress> {
+        match self.router.route_for(path) {
+            Some(RecipientRoute::Address(address)) => Some(address.clone()),
+            Some(RecipientRoute::Weighted(_)) => None,
+            None => self.recipient.clone(),
+        }
+    }
+
+    /// Sign `receipt` with the invoice key, if signing is enabled
+    pub fn sign_receipt(&self, receipt: &mut PaymentReceipt) {
+        #[cfg(feature = "signing")]
+        if let Some(signer) = &self.signer {
+            receipt.sign(signer);
         }
+        #[cfg(not(feature = "signing"))]
+        let _ = receipt;
     }
 
-    pub fn generate(&self, amount: f64, resource_path: &str) -> Invoice {
-        let idx = self.address_index.fetch_add(1, Ordering::SeqCst);
-        let recipient = TEST_ADDRESSES[idx % TEST_ADDRESSES.len()].to_string();
-        Invoice::new(amount, resource_path, recipient)
+    /// Base58 public key invoices are signed with, if signing is enabled
+    pub fn signer_public_key(&self) -> Option<String> {
+        #[cfg(feature = "signing")]
+        if let Some(signer) = &self.signer {
+            return Some(signer.public_key());
+        }
+        None
+    }
+
+    /// Next invoice for `resource_path`, payable for `ttl_seconds`
+    ///
+    /// The recipient comes from the route matching the path, else the
+    /// fixed recipient, else the next test address.
+    pub fn generate(
+        &self,
+        amount: Amount,
+        resource_path: ResourcePath,
+        ttl_seconds: u64,
+    ) -> Invoice {
+        self.generate_with_memo(amount, resource_path, self.next_memo(), ttl_seconds)
+    }
+
+    /// Next invoice with a memo from [`next_memo`](Self::next_memo)
+    ///
+    /// The invoice is added to [`invoices`](Self::invoices) as unpaid.
+    pub fn generate_with_memo(
+        &self,
+        amount: Amount,
+        resource_path: ResourcePath,
+        memo: InvoiceMemo,
+        ttl_seconds: u64,
+    ) -> Invoice {
+        let route = self.router.route(resource_path.as_str());
+        let recipient = match &route {
+            Some(route) => route.recipient.clone(),
+            None => self.recipient.clone().unwrap_or_else(|| {
+                let idx = self.address_index.fetch_add(1, Ordering::SeqCst);
+                SolanaAddress::new(TEST_ADDRESSES[idx % TEST_ADDRESSES.len()])
+                    .expect("test addresses are valid Base58")
+            }),
+        };
+        let mut invoice = Invoice::issued_at(
+            amount,
+            resource_path,
+            recipient,
+            memo,
+            ttl_seconds,
+            self.now(),
+        );
+        invoice.route = route.map(|route| route.pattern);
+        invoice.protocol = self.protocol_id.clone();
+
+        #[cfg(feature = "signing")]
+        if let Some(signer) = &self.signer {
+            invoice.signature = Some(signer.sign(&invoice.canonical_www_authenticate()));
+            invoice.signer = Some(signer.public_key());
+        }
+        self.invoices.insert(invoice.clone(), invoice.timestamp);
+        invoice
     }
 }
 
@@ -170,31 +1763,405 @@ impl Default for InvoiceGenerator {
 // ============================================================================
 
 /// Configuration for the mock server
+///
+/// Construct with [`MockServerConfig::builder`] or [`MockServerConfig::new`].
+#[non_exhaustive]
 pub struct MockServerConfig {
-    pub port: u16,
+    pub port: Port,
     pub pricing_matcher: PricingMatcher,
     pub invoice_generator: InvoiceGenerator,
     pub config: Config,
+    /// File that gets `X402_DEV_ACTUAL_PORT=<port>` appended once bound
+    pub env_file: Option<PathBuf>,
+    /// Called inline on every request, after webhooks and the event log
+    pub event_handlers: EventHandlers,
+}
+
+impl MockServerConfig {
+    /// Validate `config` and derive the pricing matcher and port from it
+    pub fn new(config: Config) -> Result<Self> {
+        Self::builder().config(config).build()
+    }
+
+    /// Register `handler` for the server's events
+    ///
+    /// Handlers run on the request and must return quickly; see
+    /// [`event_handler`](crate::event_handler).
+    pub fn with_event_handler(mut self, handler: impl EventHandler + 'static) -> Self {
+        self.event_handlers.push(handler);
+        self
+    }
+
+    pub fn builder() -> MockServerConfigBuilder {
+        MockServerConfigBuilder::default()
+    }
+}
+
+/// Fluent builder for [`MockServerConfig`]
+#[derive(Default)]
+pub struct MockServerConfigBuilder {
+    config: ConfigBuilder,
+    invoice_generator: Option<InvoiceGenerator>,
+    env_file: Option<PathBuf>,
+    event_handlers: EventHandlers,
+}
+
+impl MockServerConfigBuilder {
+    /// Replace the server settings (defaults to [`Config::default`])
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = ConfigBuilder::from(config);
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.config = self.config.port(port);
+        self
+    }
+
+    pub fn invoice_generator(mut self, generator: InvoiceGenerator) -> Self {
+        self.invoice_generator = Some(generator);
+        self
+    }
+
+    /// Append `X402_DEV_ACTUAL_PORT=<port>` to `path` once the server is
+    /// listening (works with `$GITHUB_ENV`)
+    pub fn env_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.env_file = Some(path.into());
+        self
+    }
+
+    /// See [`MockServerConfig::with_event_handler`]
+    pub fn event_handler(mut self, handler: impl EventHandler + 'static) -> Self {
+        self.event_handlers.push(handler);
+        self
+    }
+
+    /// Validate the config and assemble the server state
+    pub fn build(self) -> Result<MockServerConfig> {
+        let config = self.config.build()?;
+        Ok(MockServerConfig {
+            port: config.port,
+            pricing_matcher: if config.match_stats {
+                PricingMatcher::new(config.pricing.clone()).with_match_stats()
+            } else {
+                PricingMatcher::new(config.pricing.clone())
+            },
+            invoice_generator: match self.invoice_generator {
+                Some(generator) => generator,
+                None => InvoiceGenerator::from_config(&config)?,
+            },
+            config,
+            env_file: self.env_file,
+            event_handlers: self.event_handlers,
+        })
+    }
 }
 
 /// CORS max age for preflight requests (seconds)
 const CORS_MAX_AGE_SECS: usize = 3600;
 
+/// Alias of [`HEALTH_READY_PATH`], kept for older clients (GET)
+pub const HEALTH_PATH: &str = "/health";
+
+/// Current simulation mode (GET) and runtime switch (POST)
+pub const ADMIN_SIMULATION_MODE_PATH: &str = "/__admin/simulation-mode";
+
+/// Virtual time of a server with `allow_time_travel` (GET)
+pub const ADMIN_CLOCK_PATH: &str = "/__admin/clock";
+
+/// Move the virtual clock forward by `{"seconds": N}` (POST)
+pub const ADMIN_CLOCK_ADVANCE_PATH: &str = "/__admin/clock/advance";
+
+/// Admin features listed under `capabilities` in `/health`, so clients can
+/// tell what an older server lacks
+pub const ADMIN_CAPABILITIES: &[&str] = &[
+    "stats",
+    "events",
+    "simulation_mode",
+    "invoices",
+    "clock",
+    "snapshot",
+    "maintenance",
+    "readiness",
+];
+
+/// Variable the env file exports the bound port as
+pub const ACTUAL_PORT_ENV_VAR: &str = "X402_DEV_ACTUAL_PORT";
+
 // ============================================================================
 // Server Setup
 // ============================================================================
 
+/// Bind `bind_address` on the configured port
+///
+/// With [`PortFallback::Auto`], a port in use moves on to the next one, up
+/// to `port_fallback_attempts` times. Exits with code 2 when no port is free.
+pub fn bind_listener(config: &Config) -> Result<TcpListener> {
+    let first = config.port.get();
+    let attempts = match config.port_fallback {
+        PortFallback::Fail => 0,
+        PortFallback::Auto => config.port_fallback_attempts,
+    };
+    let last = first.saturating_add(attempts);
+
+    for port in first..=last {
+        match TcpListener::bind((config.bind_address, port)) {
+            Ok(listener) => {
+                if port != first {
+                    println!("⚠️  Port {} is in use, using port {} instead", first, port);
+                }
+                return Ok(listener);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => continue,
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!(
+                        "Failed to bind to {}:{}\nFix: Use an address of this machine's interfaces, or 0.0.0.0 / :: for all",
+                        config.bind_address, port
+                    )
+                })
+            }
+        }
+    }
+
+    // Exit code 2: port in use
+    if first == last {
+        eprintln!("❌ Error: Port {} is already in use", first);
+        eprintln!("💡 Fix: Stop the process using this port, choose a different port, or pass --port-fallback auto");
+    } else {
+        eprintln!("❌ Error: Ports {}-{} are all in use", first, last);
+        eprintln!("💡 Fix: Stop the processes using these ports or raise --port-fallback-attempts");
+    }
+    std::process::exit(2);
+}
+
+/// Append `X402_DEV_ACTUAL_PORT=<port>` to `path`
+pub fn write_env_file(path: &Path, port: u16) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open env file {}", path.display()))?;
+    writeln!(file, "{}={}", ACTUAL_PORT_ENV_VAR, port)
+        .with_context(|| format!("Failed to write env file {}", path.display()))
+}
+
 /// Start the HTTP server with the given configuration
 pub async fn start_http_server(server_config: MockServerConfig) -> Result<()> {
-    let port = server_config.port;
+    let listener = bind_listener(&server_config.config)?;
+    serve_listener(server_config, listener).await
+}
+
+/// Serve on an already bound listener (see [`bind_listener`])
+///
+/// The config's port and bind address are replaced with the listener's, so
+/// `/health` reports the address actually in use.
+pub async fn serve_listener(server_config: MockServerConfig, listener: TcpListener) -> Result<()> {
+    listen(server_config, listener, true)?
+        .run()
+        .await
+        .context("HTTP server error")
+}
+
+/// Server set up by [`listen`], not yet running
+pub(crate) struct Listening {
+    pub server: Server,
+    audit_log: Option<Arc<AuditLog>>,
+}
+
+impl Listening {
+    /// Serve until stopped, then write out the queued audit entries
+    ///
+    /// Workers drop their copy of the audit log on their own threads, which
+    /// can be after the server has stopped.
+    pub async fn run(self) -> std::io::Result<()> {
+        let result = self.server.await;
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.flush();
+        }
+        result
+    }
+}
+
+/// Set up the server on `listener` without running it
+///
+/// Startup lines (TLS fingerprint, policy state, ...) are printed when
+/// `announce` is set; in-process servers embedded in other output, such as
+/// [`TestServer`](crate::TestServer), leave it off.
+pub(crate) fn listen(
+    mut server_config: MockServerConfig,
+    listener: TcpListener,
+    announce: bool,
+) -> Result<Listening> {
+    let local_addr = listener
+        .local_addr()
+        .context("Failed to read the bound address")?;
+    let port = local_addr.port();
+    server_config.port = Port::new(port)?;
+    server_config.config.port = server_config.port;
+    server_config.config.bind_address = local_addr.ip();
+    if announce {
+        warn_if_exposed(&server_config.config);
+    }
+    let readiness = web::Data::new(Readiness::new());
+    readiness.pass("config");
 
     let pricing_data = web::Data::new(server_config.pricing_matcher);
-    let invoice_generator = web::Data::new(server_config.invoice_generator);
+    if let Some(signer) = server_config
+        .invoice_generator
+        .signer_public_key()
+        .filter(|_| announce)
+    {
+        println!("✍️  Invoice signing enabled, signer: {}", signer);
+    }
+    // Invoices and policies share one clock; with time travel it is a
+    // virtual one /__admin/clock/advance moves forward
+    let time_travel = server_config
+        .config
+        .allow_time_travel
+        .then(|| Arc::new(VirtualClock::new()));
+    let mut generator = server_config.invoice_generator;
+    if let Some(clock) = &time_travel {
+        generator = generator.with_clock(clock.clone());
+        if announce {
+            println!(
+                "⏩ Time travel enabled: POST {} moves the clock forward",
+                ADMIN_CLOCK_ADVANCE_PATH
+            );
+        }
+    }
+    let clock = generator.clock().clone();
+    let time_travel = time_travel.map(web::Data::from);
+    let invoice_generator = web::Data::new(generator);
+
+    // Load certificates before binding so file errors surface immediately
+    let tls_config = server_config.config.tls.clone();
+    let tls = tls_config.as_ref().map(TlsConfig::load).transpose()?;
+    let http2 = server_config.config.http2;
+    let webhooks = web::Data::new(WebhookDispatcher::start(
+        server_config.config.webhooks.clone(),
+    ));
+    let mut audit_log = None;
+    let policy_engine = if server_config.config.policies.is_empty() {
+        None
+    } else {
+        let store = server_config.config.policy_state.open()?;
+        store.register_instance(&StoreInstance {
+            id: server_config.config.instance_id(),
+            pid: std::process::id(),
+            port: Some(port),
+            started: PidMetadata::for_process(std::process::id()).started,
+        })?;
+        readiness.pass("policy_state");
+        if announce {
+            println!("💾 Policy state: {}", server_config.config.policy_state);
+            let others = store.instances()?.len().saturating_sub(1);
+            if others > 0 {
+                println!(
+                    "🔗 Shared with {} other instance(s) (this one: {})",
+                    others,
+                    server_config.config.instance_id()
+                );
+            }
+        }
+        let mut engine = PolicyEngine::with_store(server_config.config.policies.clone(), store)
+            .with_clock(clock);
+        if let Some(config) = &server_config.config.audit_log {
+            let log = Arc::new(AuditLog::open(config)?);
+            engine = engine.with_decision_hook(log.clone());
+            audit_log = Some(log);
+            if announce {
+                println!("📝 Policy audit log: {}", config.path.display());
+            }
+        }
+        readiness.pass("policies");
+        Some(web::Data::new(if server_config.config.match_stats {
+            engine.with_match_stats()
+        } else {
+            engine
+        }))
+    };
+    let simulation = web::Data::new(SimulationSwitch::new(server_config.config.simulation_mode));
+    let maintenance = web::Data::new(MaintenanceSwitch::new(
+        server_config.config.maintenance.clone(),
+    ));
+    let rpc = server_config.config.rpc.clone().map(|rpc| {
+        if announce {
+            let scheme = if tls.is_some() { "https" } else { "http" };
+            println!(
+                "🛰️  Fake Solana RPC: {}://{}{}",
+                scheme, local_addr, RPC_PATH
+            );
+        }
+        web::Data::new(RpcSimulator::new(rpc, invoice_generator.invoices().clone()))
+    });
+    // Proofs are looked up over JSON-RPC only with `verifier: rpc`
+    let rpc_client = match server_config.config.verifier {
+        VerifierKind::Rpc => {
+            if announce {
+                println!(
+                    "🔎 Verifying payments over RPC: {}",
+                    redact_url(&server_config.config.solana_rpc)
+                );
+            }
+            Some(web::Data::new(SolanaRpcClient::new(
+                server_config.config.solana_rpc.as_str(),
+            )?))
+        }
+        VerifierKind::Simulated => None,
+    };
+    let idle = web::Data::new(IdleShutdown::new(
+        server_config.config.idle_timeout_secs,
+        server_config.config.max_lifetime_secs,
+    ));
+    if announce && server_config.config.idle_timeout_secs > 0 {
+        println!(
+            "💤 Idle shutdown after {}s without requests",
+            server_config.config.idle_timeout_secs
+        );
+    }
+    if announce && server_config.config.max_lifetime_secs > 0 {
+        println!(
+            "⏳ Shutdown {}s after start",
+            server_config.config.max_lifetime_secs
+        );
+    }
+    // Hooks run against the server itself once it listens
+    readiness.pending("listener", "not attached yet");
+    let startup_hooks = server_config.config.startup_hooks.clone();
+    readiness.expect_hooks(&startup_hooks);
+    let hook_pricing = server_config.config.pricing.clone();
+    let self_url = format!(
+        "{}://{}",
+        if tls.is_some() { "https" } else { "http" },
+        std::net::SocketAddr::new(
+            if local_addr.ip().is_unspecified() {
+                IpAddr::V4(std::net::Ipv4Addr::LOCALHOST)
+            } else {
+                local_addr.ip()
+            },
+            port
+        )
+    );
     let config_data = web::Data::new(server_config.config);
+    let stats = web::Data::new(StatsRegistry::new());
+    let events = web::Data::new(EventLog::new());
+    let event_handlers = web::Data::new(server_config.event_handlers);
 
-    HttpServer::new(move || {
+    let idle_tracker = idle.clone();
+    let readiness_data = readiness.clone();
+    let server = HttpServer::new(move || {
+        let activity = idle_tracker.clone();
         App::new()
-            // CORS middleware - allow all origins, methods, and headers for testing
+            // Requests outside /health and /__admin/* reset the idle clock
+            .wrap_fn(move |req, srv| {
+                if IdleShutdown::counts_as_activity(req.path()) {
+                    activity.touch();
+                }
+                srv.call(req)
+            })
+            // CORS middleware - allow all origins, methods, and headers for testing.
+            // Preflights are answered here; plain OPTIONS reaches the handler
             .wrap(
                 Cors::default()
                     .allow_any_origin()
@@ -202,26 +2169,148 @@ pub async fn start_http_server(server_config: MockServerConfig) -> Result<()> {
                     .allow_any_header()
                     .max_age(CORS_MAX_AGE_SECS),
             )
-            // Share pricing matcher, invoice generator, and config across all handlers
+            // Share pricing matcher, invoice generator, config and webhooks across all handlers
             .app_data(pricing_data.clone())
             .app_data(invoice_generator.clone())
             .app_data(config_data.clone())
+            .app_data(webhooks.clone())
+            .app_data(stats.clone())
+            .app_data(events.clone())
+            .app_data(event_handlers.clone())
+            .app_data(simulation.clone())
+            .app_data(maintenance.clone())
+            .app_data(idle_tracker.clone())
+            .app_data(readiness_data.clone())
+            // Policy enforcement is optional; handlers skip it when absent
+            .configure(|cfg| {
+                if let Some(engine) = &policy_engine {
+                    cfg.app_data(engine.clone());
+                }
+            })
+            // Only servers started with allow_time_travel can move their clock
+            .configure(|cfg| {
+                if let Some(clock) = &time_travel {
+                    cfg.app_data(clock.clone());
+                }
+            })
+            // The fake RPC is optional too; issued invoices are recorded for it
+            .configure(|cfg| {
+                if let Some(rpc) = &rpc {
+                    cfg.app_data(rpc.clone());
+                    cfg.service(web::resource(RPC_PATH).post(rpc_handler));
+                }
+            })
+            // Handlers verify with the simulation mode when no client is set
+            .configure(|cfg| {
+                if let Some(client) = &rpc_client {
+                    cfg.app_data(client.clone());
+                }
+            })
+            // Admin endpoints take precedence over the payment flow (405 on other methods)
+            .service(web::resource(HEALTH_PATH).get(health_handler))
+            .service(web::resource(HEALTH_READY_PATH).get(health_handler))
+            .service(web::resource(HEALTH_LIVE_PATH).get(liveness_handler))
+            .service(web::resource(ADMIN_STATS_PATH).get(stats_handler))
+            .service(web::resource(ADMIN_STATS_RESET_PATH).post(reset_stats_handler))
+            .service(web::resource(ADMIN_EVENTS_PATH).get(events_handler))
+            .service(web::resource(ADMIN_INVOICES_PATH).get(invoices_handler))
+            .service(
+                web::resource(format!("{}/{{memo}}", ADMIN_INVOICES_PATH)).get(invoice_handler),
+            )
+            .service(
+                web::resource(ADMIN_SNAPSHOT_PATH)
+                    .app_data(web::PayloadConfig::new(MAX_SNAPSHOT_BYTES))
+                    .get(snapshot_handler)
+                    .post(restore_snapshot_handler),
+            )
+            .service(web::resource(ADMIN_CLOCK_PATH).get(clock_handler))
+            .service(web::resource(ADMIN_CLOCK_ADVANCE_PATH).post(advance_clock_handler))
+            .service(
+                web::resource(ADMIN_SIMULATION_MODE_PATH)
+                    .get(simulation_mode_handler)
+                    .post(set_simulation_mode_handler),
+            )
+            .service(
+                web::resource(ADMIN_MAINTENANCE_PATH)
+                    .get(maintenance_handler)
+                    .put(set_maintenance_handler)
+                    .delete(clear_maintenance_handler),
+            )
             // Wildcard route handler - matches all paths and methods
             .default_service(web::route().to(payment_required_handler))
-    })
-    .bind(("127.0.0.1", port))
-    .inspect_err(|e| {
-        // Check if port is already in use (exit code 2 requirement)
-        if e.kind() == std::io::ErrorKind::AddrInUse {
-            eprintln!("❌ Error: Port {} is already in use", port);
-            eprintln!("💡 Fix: Stop the process using this port or choose a different port");
-            std::process::exit(2); // Exit code 2: port in use
-        }
-    })
-    .with_context(|| format!("Failed to bind to port {}", port))?
-    .run()
-    .await
-    .context("HTTP server error")
+    });
+
+    let server = match tls {
+        Some(loaded) => {
+            if announce {
+                println!("🔒 TLS enabled: https://{}", local_addr);
+                println!("🔑 Certificate SHA-256: {}", loaded.fingerprint);
+                if tls_config.is_some_and(|t| t.self_signed) {
+                    println!("💡 Self-signed certificate: pin the fingerprint above or pass --insecure to clients");
+                }
+            }
+            server.listen_rustls_0_23(listener, loaded.server_config)
+        }
+        None if http2 => {
+            if announce {
+                println!("⚡ HTTP/2 enabled (h2c prior knowledge + HTTP/1.1)");
+            }
+            server.listen_auto_h2c(listener)
+        }
+        None => server.listen(listener),
+    };
+
+    let server = server
+        .with_context(|| format!("Failed to listen on port {}", port))?
+        .run();
+    readiness.pass("listener");
+    if !startup_hooks.is_empty() {
+        tokio::spawn(async move {
+            readiness
+                .run_hooks(&startup_hooks, &hook_pricing, &self_url)
+                .await
+        });
+    }
+    if !idle.is_disabled() {
+        spawn_shutdown_watchdog(idle.into_inner(), server.handle());
+    }
+    Ok(Listening { server, audit_log })
+}
+
+/// Announce a listener reachable from other machines
+///
+/// Loud when admin endpoints are reachable too, since anyone on the network
+/// could reset stats or switch the simulation mode.
+fn warn_if_exposed(config: &Config) {
+    if is_loopback(config.bind_address) {
+        return;
+    }
+    if config.admin_loopback_only {
+        println!(
+            "🌐 Listening on {}, reachable from other machines; /__admin/* answers loopback clients only",
+            config.bind_address
+        );
+    } else {
+        eprintln!(
+            "⚠️  WARNING: listening on {} with admin_loopback_only disabled: anyone on the network can call /__admin/*",
+            config.bind_address
+        );
+        eprintln!("💡 Fix: Remove admin_loopback_only: false, or bind 127.0.0.1");
+    }
+}
+
+/// Stop the server gracefully once `idle` has a deadline due
+fn spawn_shutdown_watchdog(idle: Arc<IdleShutdown>, handle: ServerHandle) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(WATCHDOG_INTERVAL).await;
+            if let Some(reason) = idle.due(Instant::now()) {
+                println!("💤 Shutting down: {}", reason);
+                handle.stop(true).await;
+                break;
+            }
+        }
+    });
 }
 
 /// Mock server instance
@@ -239,4 +2328,9 @@ impl MockServer {
     pub async fn run(self) -> Result<()> {
         start_http_server(self.config).await
     }
+
+    /// Start the server on a listener from [`bind_listener`] (blocking)
+    pub async fn run_on(self, listener: TcpListener) -> Result<()> {
+        serve_listener(self.config, listener).await
+    }
 }
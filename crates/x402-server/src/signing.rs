@@ -0,0 +1,64 @@
+// Invoice signing settings for the mock server
+//
+// Signing itself needs the `signing` feature (ed25519-dalek); the settings
+// always parse so a config file works with either build.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[cfg(feature = "signing")]
+pub use x402_core::signing::InvoiceSigner;
+
+/// Ed25519 invoice signing for the mock server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningConfig {
+    /// Solana CLI keypair file (JSON array of 64 bytes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keypair_path: Option<PathBuf>,
+
+    /// Generate an ephemeral key at startup
+    #[serde(default)]
+    pub ephemeral: bool,
+}
+
+impl SigningConfig {
+    /// Config that signs with a key generated at startup
+    pub fn ephemeral() -> Self {
+        Self {
+            ephemeral: true,
+            ..Self::default()
+        }
+    }
+
+    /// Config that signs with the key in a Solana CLI keypair file
+    pub fn keypair(path: impl Into<PathBuf>) -> Self {
+        Self {
+            keypair_path: Some(path.into()),
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<()> {
+        if cfg!(not(feature = "signing")) {
+            bail!(
+                "Invoice signing is not available in this build\nFix: Rebuild x402-dev with `--features signing`, or remove the signing section"
+            );
+        }
+        if !self.ephemeral && self.keypair_path.is_none() {
+            bail!(
+                "Invoice signing requires a key\nFix: Set signing.keypair_path to a Solana keypair file, or set signing.ephemeral: true"
+            );
+        }
+        Ok(())
+    }
+
+    /// Load the keypair file, or generate a key when ephemeral
+    #[cfg(feature = "signing")]
+    pub fn load(&self) -> Result<InvoiceSigner> {
+        match &self.keypair_path {
+            Some(path) => InvoiceSigner::from_keypair_file(path),
+            None => Ok(InvoiceSigner::generate()),
+        }
+    }
+}
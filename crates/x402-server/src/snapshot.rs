@@ -0,0 +1,396 @@
+//! Whole-server state for bug reports (`/__admin/snapshot`, `mock snapshot`)
+//!
+//! A snapshot holds the effective config with its secrets masked, the
+//! issued invoices and verified proofs, the policy rate limit and spending
+//! state, the recent payment events and the request statistics, plus
+//! version metadata. `GET /__admin/snapshot` takes one and
+//! `POST /__admin/snapshot` adds one to a running server, so a session can
+//! be replayed on a fresh instance. On disk a snapshot is a tar.gz archive
+//! with one entry per part; entries are written one at a time, and read
+//! back with a size limit each.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use x402_core::policy::{PolicyEngine, StateBackend, StateDump};
+use x402_core::redact::redact_json;
+
+use crate::events::{EventLog, LoggedEvent};
+use crate::invoices::{InvoiceQuery, InvoiceRecord, InvoiceStore};
+use crate::server::Config;
+use crate::stats::{StatsRegistry, StatsSnapshot};
+
+/// Snapshot endpoint (GET takes one, POST restores one)
+pub const ADMIN_SNAPSHOT_PATH: &str = "/__admin/snapshot";
+
+/// Version of the snapshot layout
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Invoices a snapshot keeps by default, the most recent ones
+pub const DEFAULT_SNAPSHOT_INVOICES: usize = 10_000;
+
+/// Largest snapshot body `POST /__admin/snapshot` accepts, and largest
+/// archive entry read back
+pub const MAX_SNAPSHOT_BYTES: usize = 64 * 1024 * 1024;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const CONFIG_ENTRY: &str = "config.json";
+const STATS_ENTRY: &str = "stats.json";
+const POLICY_STATE_ENTRY: &str = "policy_state.json";
+const INVOICES_ENTRY: &str = "invoices.jsonl";
+const EVENTS_ENTRY: &str = "events.jsonl";
+
+/// Everything a server knew when the snapshot was taken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerSnapshot {
+    pub manifest: SnapshotManifest,
+    /// Effective config with secrets, credentials in URLs and sensitive
+    /// headers masked
+    pub config: serde_json::Value,
+    pub stats: StatsSnapshot,
+    /// Policy counters; None without policies or with a shared store
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_state: Option<StateDump>,
+    /// Most recent invoices and verified proofs, oldest first
+    pub invoices: Vec<InvoiceRecord>,
+    /// Payment events still in the log, oldest first
+    pub events: Vec<LoggedEvent>,
+}
+
+/// Version metadata and counts of a snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub format: u32,
+    /// Version of the server that took the snapshot
+    pub version: String,
+    pub created_at: DateTime<Utc>,
+    /// The server's clock, which differs from `created_at` after time travel
+    pub server_time: DateTime<Utc>,
+    /// Policy state backend, when the server enforced policies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_backend: Option<StateBackend>,
+    /// Why `policy_state` is missing although the server had policies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_state_note: Option<String>,
+    /// Entries in the invoice store, of which `invoices` holds the newest
+    pub invoices_total: usize,
+    pub invoices_included: usize,
+}
+
+/// What `POST /__admin/snapshot` added to the server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestoreSummary {
+    pub invoices: usize,
+    /// Invoices whose memo the server already knew
+    pub invoices_skipped: usize,
+    pub events: usize,
+    /// Patterns whose counters were added
+    pub stats_paths: usize,
+    pub policy_state: bool,
+    /// Parts of the snapshot that were not restored, and why
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// The server parts a snapshot is taken from
+pub struct SnapshotSources<'a> {
+    pub config: &'a Config,
+    pub invoices: &'a InvoiceStore,
+    pub stats: StatsSnapshot,
+    pub events: &'a EventLog,
+    pub policy: Option<&'a PolicyEngine>,
+    pub now: DateTime<Utc>,
+}
+
+impl ServerSnapshot {
+    /// Take a snapshot keeping at most `max_invoices` of the newest invoices
+    pub fn capture(sources: SnapshotSources<'_>, max_invoices: usize) -> Self {
+        let mut config = serde_json::to_value(sources.config).unwrap_or_default();
+        redact_json(&mut config, &sources.config.header_redactor());
+
+        let invoices_total = sources.invoices.len();
+        let mut invoices = sources.invoices.find(&InvoiceQuery {
+            limit: Some(usize::MAX),
+            ..InvoiceQuery::default()
+        });
+        invoices.drain(..invoices.len().saturating_sub(max_invoices));
+
+        let (policy_backend, policy_state, policy_state_note) = match sources.policy {
+            Some(engine) => {
+                let store = engine.store();
+                let backend = store.stats().ok().map(|stats| stats.backend);
+                match store.dump() {
+                    Ok(dump) => (backend, Some(dump), None),
+                    Err(e) => (backend, None, Some(format!("{:#}", e))),
+                }
+            }
+            None => (None, None, None),
+        };
+
+        Self {
+            manifest: SnapshotManifest {
+                format: SNAPSHOT_FORMAT,
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                created_at: Utc::now(),
+                server_time: sources.now,
+                policy_backend,
+                policy_state_note,
+                invoices_total,
+                invoices_included: invoices.len(),
+            },
+            config,
+            stats: sources.stats,
+            policy_state,
+            invoices,
+            events: sources.events.after(0).events,
+        }
+    }
+
+    /// Add this snapshot's state to a server
+    ///
+    /// The config is informational and never applied: the receiving server
+    /// keeps its own.
+    pub fn restore_into(
+        self,
+        invoices: &InvoiceStore,
+        stats: &StatsRegistry,
+        events: &EventLog,
+        policy: Option<&PolicyEngine>,
+        now: DateTime<Utc>,
+    ) -> RestoreSummary {
+        let mut summary = RestoreSummary {
+            stats_paths: self.stats.paths.len(),
+            events: self.events.len(),
+            ..RestoreSummary::default()
+        };
+        for record in self.invoices {
+            if invoices.restore(record, now) {
+                summary.invoices += 1;
+            } else {
+                summary.invoices_skipped += 1;
+            }
+        }
+        stats.restore(&self.stats);
+        events.restore(self.events);
+
+        match (self.policy_state, policy) {
+            (Some(dump), Some(engine)) => match engine.store().restore(&dump) {
+                Ok(()) => summary.policy_state = true,
+                Err(e) => summary
+                    .warnings
+                    .push(format!("policy state not restored: {:#}", e)),
+            },
+            (Some(dump), None) if !dump.is_empty() => summary
+                .warnings
+                .push("policy state not restored: this server enforces no policies".to_string()),
+            _ => {}
+        }
+        summary
+    }
+
+    /// Write the snapshot as a tar.gz archive, one entry per part
+    pub fn write_archive<W: Write>(&self, out: W) -> Result<W> {
+        let mut archive = tar::Builder::new(GzEncoder::new(out, Compression::default()));
+        let mtime = self.manifest.created_at.timestamp().max(0) as u64;
+
+        append(
+            &mut archive,
+            MANIFEST_ENTRY,
+            mtime,
+            &pretty(&self.manifest)?,
+        )?;
+        append(&mut archive, CONFIG_ENTRY, mtime, &pretty(&self.config)?)?;
+        append(&mut archive, STATS_ENTRY, mtime, &pretty(&self.stats)?)?;
+        if let Some(state) = &self.policy_state {
+            append(&mut archive, POLICY_STATE_ENTRY, mtime, &pretty(state)?)?;
+        }
+        append(
+            &mut archive,
+            INVOICES_ENTRY,
+            mtime,
+            &json_lines(&self.invoices)?,
+        )?;
+        append(
+            &mut archive,
+            EVENTS_ENTRY,
+            mtime,
+            &json_lines(&self.events)?,
+        )?;
+
+        let out = archive
+            .into_inner()
+            .context("Failed to finish the snapshot archive")?
+            .finish()
+            .context("Failed to finish the snapshot archive")?;
+        Ok(out)
+    }
+
+    /// Read a snapshot written by [`write_archive`](Self::write_archive)
+    ///
+    /// Entries over [`MAX_SNAPSHOT_BYTES`] are rejected; unknown entries
+    /// are ignored.
+    pub fn read_archive<R: Read>(input: R) -> Result<Self> {
+        let mut archive = tar::Archive::new(GzDecoder::new(input));
+        let mut manifest = None;
+        let mut config = None;
+        let mut stats = None;
+        let mut policy_state = None;
+        let mut invoices = None;
+        let mut events = None;
+
+        for entry in archive
+            .entries()
+            .context("Snapshot is not a tar.gz archive")?
+        {
+            let entry = entry.context("Snapshot archive is corrupt")?;
+            let name = entry.path()?.to_string_lossy().into_owned();
+            let size = entry.header().size()?;
+            if size > MAX_SNAPSHOT_BYTES as u64 {
+                bail!(
+                    "Snapshot entry {} is {} bytes, over the {} byte limit\nFix: Export again with a lower --max-invoices",
+                    name,
+                    size,
+                    MAX_SNAPSHOT_BYTES
+                );
+            }
+            let mut bytes = Vec::new();
+            entry
+                .take(MAX_SNAPSHOT_BYTES as u64)
+                .read_to_end(&mut bytes)
+                .with_context(|| format!("Failed to read snapshot entry {}", name))?;
+            let context = || format!("Invalid snapshot entry {}", name);
+            match name.as_str() {
+                MANIFEST_ENTRY => {
+                    manifest = Some(serde_json::from_slice(&bytes).with_context(context)?)
+                }
+                CONFIG_ENTRY => {
+                    config = Some(serde_json::from_slice(&bytes).with_context(context)?)
+                }
+                STATS_ENTRY => stats = Some(serde_json::from_slice(&bytes).with_context(context)?),
+                POLICY_STATE_ENTRY => {
+                    policy_state = Some(serde_json::from_slice(&bytes).with_context(context)?)
+                }
+                INVOICES_ENTRY => invoices = Some(parse_json_lines(&bytes).with_context(context)?),
+                EVENTS_ENTRY => events = Some(parse_json_lines(&bytes).with_context(context)?),
+                _ => {}
+            }
+        }
+
+        let manifest: SnapshotManifest = manifest.context(
+            "Snapshot archive has no manifest.json\nFix: Create it with `x402-dev mock snapshot export`",
+        )?;
+        if manifest.format > SNAPSHOT_FORMAT {
+            bail!(
+                "Snapshot format {} is newer than this x402-dev supports ({})\nFix: Upgrade x402-dev to {} or later",
+                manifest.format,
+                SNAPSHOT_FORMAT,
+                manifest.version
+            );
+        }
+        Ok(Self {
+            manifest,
+            config: config.unwrap_or_default(),
+            stats: stats.context("Snapshot archive has no stats.json")?,
+            policy_state,
+            invoices: invoices.unwrap_or_default(),
+            events: events.unwrap_or_default(),
+        })
+    }
+}
+
+fn append<W: Write>(
+    archive: &mut tar::Builder<W>,
+    name: &str,
+    mtime: u64,
+    data: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    archive
+        .append_data(&mut header, name, data)
+        .with_context(|| format!("Failed to write snapshot entry {}", name))
+}
+
+fn pretty<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let mut bytes = serde_json::to_vec_pretty(value)?;
+    bytes.push(b'\n');
+    Ok(bytes)
+}
+
+/// One compact JSON document per line, so large entries stay greppable
+fn json_lines<T: Serialize>(items: &[T]) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    for item in items {
+        serde_json::to_writer(&mut bytes, item)?;
+        bytes.push(b'\n');
+    }
+    Ok(bytes)
+}
+
+fn parse_json_lines<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<Vec<T>> {
+    bytes
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .enumerate()
+        .map(|(i, line)| serde_json::from_slice(line).with_context(|| format!("line {}", i + 1)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trip_masks_secrets() {
+        let config = Config {
+            webhooks: vec![crate::webhooks::WebhookConfig {
+                url: "https://hooks.internal/x402?token=abcdefghij".to_string(),
+                events: vec![],
+                secret: Some("whsec_1234567890".to_string()),
+                max_retries: 0,
+            }],
+            ..Config::default()
+        };
+        let invoices = InvoiceStore::new(10);
+        let events = EventLog::new();
+        let snapshot = ServerSnapshot::capture(
+            SnapshotSources {
+                config: &config,
+                invoices: &invoices,
+                stats: StatsRegistry::new().snapshot(),
+                events: &events,
+                policy: None,
+                now: Utc::now(),
+            },
+            DEFAULT_SNAPSHOT_INVOICES,
+        );
+        let text = snapshot.config.to_string();
+        assert!(!text.contains("whsec_1234567890"), "{}", text);
+        assert!(!text.contains("abcdefghij"), "{}", text);
+
+        let bytes = snapshot.write_archive(Vec::new()).unwrap();
+        let read = ServerSnapshot::read_archive(bytes.as_slice()).unwrap();
+        assert_eq!(read.manifest, snapshot.manifest);
+        assert_eq!(read.config, snapshot.config);
+        assert_eq!(read.stats, snapshot.stats);
+    }
+
+    #[test]
+    fn test_rejects_other_archives() {
+        let err = ServerSnapshot::read_archive(&b"not gzip"[..]).unwrap_err();
+        assert!(format!("{:#}", err).contains("Snapshot"), "{:#}", err);
+
+        // A tar.gz without a manifest
+        let mut archive = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        append(&mut archive, "notes.txt", 0, b"hello").unwrap();
+        let bytes = archive.into_inner().unwrap().finish().unwrap();
+        let err = ServerSnapshot::read_archive(bytes.as_slice()).unwrap_err();
+        assert!(err.to_string().contains("no manifest.json"), "{}", err);
+    }
+}
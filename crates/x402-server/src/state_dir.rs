@@ -0,0 +1,160 @@
+//! Directory for runtime state: PID files and the update check cache
+//!
+//! `X402_DEV_STATE_DIR` wins when set. Otherwise ~/.x402dev is used if it can
+//! be written, else a per-user directory in the system temp dir, so servers
+//! still start in containers with a read-only home.
+
+use anyhow::Result;
+use directories::BaseDirs;
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use x402_core::i18n::{CatalogError, Message};
+
+/// Environment variable that overrides the state directory
+pub const STATE_DIR_ENV: &str = "X402_DEV_STATE_DIR";
+
+/// Where the state directory came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateDirSource {
+    /// `X402_DEV_STATE_DIR`
+    Override,
+    /// ~/.x402dev
+    Home,
+    /// The system temp dir, because ~/.x402dev is not writable
+    Temp,
+}
+
+/// A resolved, writable state directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateDir {
+    pub path: PathBuf,
+    pub source: StateDirSource,
+}
+
+/// ~/.x402dev, where global config, secrets and (by default) state live
+pub fn home_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.home_dir().join(".x402dev"))
+}
+
+/// Per-user fallback in the system temp dir
+///
+/// The name is stable, so `mock stop` finds the PID file `mock start` wrote.
+pub fn temp_dir() -> PathBuf {
+    let user = env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| "user".to_string());
+    env::temp_dir().join(format!("x402dev-{}", user))
+}
+
+/// Resolve the state directory from the process environment
+pub fn resolve() -> Result<StateDir> {
+    let override_dir = env::var_os(STATE_DIR_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+    resolve_from(override_dir, home_dir(), temp_dir())
+}
+
+/// [`resolve`] over explicit candidates
+///
+/// An override that cannot be written is an error rather than a reason to
+/// fall back: the user asked for that directory.
+pub fn resolve_from(
+    override_dir: Option<PathBuf>,
+    home: Option<PathBuf>,
+    temp: PathBuf,
+) -> Result<StateDir> {
+    if let Some(path) = override_dir {
+        ensure_writable(&path)?;
+        return Ok(StateDir {
+            path,
+            source: StateDirSource::Override,
+        });
+    }
+    if let Some(path) = home {
+        match probe_writable(&path) {
+            Ok(()) => {
+                return Ok(StateDir {
+                    path,
+                    source: StateDirSource::Home,
+                })
+            }
+            Err(e) => tracing::debug!(
+                "{} is not writable ({}), using {} for state",
+                path.display(),
+                e,
+                temp.display()
+            ),
+        }
+    }
+    ensure_writable(&temp)?;
+    Ok(StateDir {
+        path: temp,
+        source: StateDirSource::Temp,
+    })
+}
+
+/// Create `dir` if needed and check that a file can be written in it
+pub fn probe_writable(dir: &Path) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".write-probe-{}", std::process::id()));
+    File::create(&probe)?;
+    fs::remove_file(&probe)
+}
+
+/// [`probe_writable`], failing with `config.state_dir_unwritable`
+pub fn ensure_writable(dir: &Path) -> Result<()> {
+    probe_writable(dir).map_err(|e| unwritable(dir, &e))
+}
+
+/// `config.state_dir_unwritable` for `path`, naming `X402_DEV_STATE_DIR`
+pub fn unwritable(path: &Path, error: &io::Error) -> anyhow::Error {
+    CatalogError::new(
+        Message::new("config.state_dir_unwritable")
+            .arg("path", path.display())
+            .arg("error", error)
+            .arg("var", STATE_DIR_ENV),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_home_used_when_writable() {
+        let dir = TempDir::new().unwrap();
+        let home = dir.path().join(".x402dev");
+        let state = resolve_from(None, Some(home.clone()), dir.path().join("tmp")).unwrap();
+        assert_eq!(state.path, home);
+        assert_eq!(state.source, StateDirSource::Home);
+        assert_eq!(fs::read_dir(&home).unwrap().count(), 0, "probe left behind");
+    }
+
+    #[test]
+    fn test_unwritable_home_falls_back_to_temp() {
+        // A file where the directory should be blocks it even for root
+        let dir = TempDir::new().unwrap();
+        let home = dir.path().join(".x402dev");
+        fs::write(&home, "").unwrap();
+        let state = resolve_from(None, Some(home), dir.path().join("tmp")).unwrap();
+        assert_eq!(state.path, dir.path().join("tmp"));
+        assert_eq!(state.source, StateDirSource::Temp);
+    }
+
+    #[test]
+    fn test_unwritable_override_names_path_and_variable() {
+        let dir = TempDir::new().unwrap();
+        let blocked = dir.path().join("state");
+        fs::write(&blocked, "").unwrap();
+        let err = resolve_from(Some(blocked.clone()), None, dir.path().join("tmp")).unwrap_err();
+        let catalog = err.downcast_ref::<CatalogError>().unwrap();
+        assert_eq!(catalog.code(), "config.state_dir_unwritable");
+        let text = err.to_string();
+        assert!(text.contains(&blocked.display().to_string()), "{}", text);
+        assert!(text.contains(STATE_DIR_ENV), "{}", text);
+    }
+}
@@ -0,0 +1,433 @@
+//! Per-path request statistics served on `/__admin/stats`
+//!
+//! Requests are grouped by the pricing rule that matched them. Counters and
+//! latency histograms are atomics; the path map only takes its write lock
+//! the first time a pattern is seen, so recording a request costs a read
+//! lock and a few relaxed increments. Reset swaps in an empty map.
+//!
+//! With `match_stats` enabled the snapshot also carries hit counters of the
+//! pricing rules and policies (`rule_hits`), filled in by the handler.
+
+use actix_web::HttpResponse;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use x402_core::policy::RuleHits;
+use x402_core::receipt::RECEIPT_HEADER;
+
+/// Snapshot endpoint (GET)
+pub const ADMIN_STATS_PATH: &str = "/__admin/stats";
+
+/// Reset endpoint (POST)
+pub const ADMIN_STATS_RESET_PATH: &str = "/__admin/stats/reset";
+
+/// Pattern recorded for paths billed at the default price
+pub const DEFAULT_PATTERN: &str = "*";
+
+/// Pattern of the totals row in a snapshot
+pub const TOTAL_PATTERN: &str = "total";
+
+/// Upper bounds of the latency histogram buckets, in microseconds
+///
+/// Slower requests land in a final overflow bucket.
+const LATENCY_BUCKETS_US: [u64; 16] = [
+    100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000,
+    1_000_000, 2_500_000, 5_000_000, 10_000_000,
+];
+
+const BUCKET_COUNT: usize = LATENCY_BUCKETS_US.len() + 1;
+
+/// How the payment handler answered a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// 402 with an invoice
+    InvoiceIssued,
+    /// 200 after simulated verification
+    Verified,
+    /// 200 for a path priced at 0, served without an invoice
+    Free,
+    /// 402 without an invoice, or 409 for a replay: the payment proof was
+    /// rejected
+    VerificationFailed,
+    /// 408 after the simulated verification delay
+    VerificationTimeout,
+    /// 403 from policy enforcement
+    PolicyDenied,
+    /// 503 with `Retry-After` from a path in maintenance
+    Maintenance,
+    /// Any other response (malformed proof, pricing misconfiguration)
+    Error,
+}
+
+impl Outcome {
+    /// Classify a response produced by the payment handler
+    pub fn from_response(response: &HttpResponse) -> Self {
+        match response.status().as_u16() {
+            402 if response.headers().contains_key("WWW-Authenticate") => Outcome::InvoiceIssued,
+            402 | 409 => Outcome::VerificationFailed,
+            // Verified payments always carry a receipt; free hits never do
+            200 if response.headers().contains_key(RECEIPT_HEADER) => Outcome::Verified,
+            200 => Outcome::Free,
+            408 => Outcome::VerificationTimeout,
+            403 => Outcome::PolicyDenied,
+            503 if response.headers().contains_key("Retry-After") => Outcome::Maintenance,
+            _ => Outcome::Error,
+        }
+    }
+}
+
+/// Live counters for one pricing pattern
+#[derive(Default)]
+struct PathStats {
+    requests: AtomicU64,
+    invoices_issued: AtomicU64,
+    verified: AtomicU64,
+    free: AtomicU64,
+    verification_failed: AtomicU64,
+    verification_timeout: AtomicU64,
+    policy_denied: AtomicU64,
+    maintenance: AtomicU64,
+    errors: AtomicU64,
+    latency: LatencyHistogram,
+}
+
+impl PathStats {
+    fn record(&self, outcome: Outcome, elapsed: Duration) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        let counter = match outcome {
+            Outcome::InvoiceIssued => &self.invoices_issued,
+            Outcome::Verified => &self.verified,
+            Outcome::Free => &self.free,
+            Outcome::VerificationFailed => &self.verification_failed,
+            Outcome::VerificationTimeout => &self.verification_timeout,
+            Outcome::PolicyDenied => &self.policy_denied,
+            Outcome::Maintenance => &self.maintenance,
+            Outcome::Error => &self.errors,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.latency.record(elapsed);
+    }
+
+    /// Add the counters of a snapshot row
+    fn add_snapshot(&self, row: &PathStatsSnapshot) {
+        for (counter, count) in [
+            (&self.requests, row.requests),
+            (&self.invoices_issued, row.invoices_issued),
+            (&self.verified, row.verified),
+            (&self.free, row.free),
+            (&self.verification_failed, row.verification_failed),
+            (&self.verification_timeout, row.verification_timeout),
+            (&self.policy_denied, row.policy_denied),
+            (&self.maintenance, row.maintenance),
+            (&self.errors, row.errors),
+        ] {
+            counter.fetch_add(count, Ordering::Relaxed);
+        }
+        self.latency
+            .add_summary(row.requests, row.latency_mean_ms, row.latency_p95_ms);
+    }
+
+    fn counts(&self) -> Counts {
+        Counts {
+            requests: self.requests.load(Ordering::Relaxed),
+            invoices_issued: self.invoices_issued.load(Ordering::Relaxed),
+            verified: self.verified.load(Ordering::Relaxed),
+            free: self.free.load(Ordering::Relaxed),
+            verification_failed: self.verification_failed.load(Ordering::Relaxed),
+            verification_timeout: self.verification_timeout.load(Ordering::Relaxed),
+            policy_denied: self.policy_denied.load(Ordering::Relaxed),
+            maintenance: self.maintenance.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            latency: self.latency.load(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKET_COUNT],
+    sum_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+impl LatencyHistogram {
+    fn record(&self, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let bucket = LATENCY_BUCKETS_US
+            .iter()
+            .position(|bound| micros <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_us.fetch_add(micros, Ordering::Relaxed);
+        self.max_us.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    /// Count `requests` requests with the given mean and p95, all in the
+    /// p95 bucket: both figures come back out, the distribution does not
+    fn add_summary(&self, requests: u64, mean_ms: f64, p95_ms: f64) {
+        if requests == 0 {
+            return;
+        }
+        let p95_us = (p95_ms * 1000.0).round() as u64;
+        let bucket = LATENCY_BUCKETS_US
+            .iter()
+            .position(|bound| p95_us <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+        self.buckets[bucket].fetch_add(requests, Ordering::Relaxed);
+        let sum_us = (mean_ms * 1000.0 * requests as f64).round() as u64;
+        self.sum_us.fetch_add(sum_us, Ordering::Relaxed);
+        self.max_us.fetch_max(p95_us, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> Latency {
+        Latency {
+            buckets: std::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed)),
+            sum_us: self.sum_us.load(Ordering::Relaxed),
+            max_us: self.max_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Plain copy of a pattern's counters, summed for the totals row
+#[derive(Default)]
+struct Counts {
+    requests: u64,
+    invoices_issued: u64,
+    verified: u64,
+    free: u64,
+    verification_failed: u64,
+    verification_timeout: u64,
+    policy_denied: u64,
+    maintenance: u64,
+    errors: u64,
+    latency: Latency,
+}
+
+impl Counts {
+    fn add(&mut self, other: &Counts) {
+        self.requests += other.requests;
+        self.invoices_issued += other.invoices_issued;
+        self.verified += other.verified;
+        self.free += other.free;
+        self.verification_failed += other.verification_failed;
+        self.verification_timeout += other.verification_timeout;
+        self.policy_denied += other.policy_denied;
+        self.maintenance += other.maintenance;
+        self.errors += other.errors;
+        for (total, count) in self.latency.buckets.iter_mut().zip(other.latency.buckets) {
+            *total += count;
+        }
+        self.latency.sum_us += other.latency.sum_us;
+        self.latency.max_us = self.latency.max_us.max(other.latency.max_us);
+    }
+
+    fn snapshot(&self, pattern: &str) -> PathStatsSnapshot {
+        PathStatsSnapshot {
+            pattern: pattern.to_string(),
+            requests: self.requests,
+            invoices_issued: self.invoices_issued,
+            verified: self.verified,
+            free: self.free,
+            verification_failed: self.verification_failed,
+            verification_timeout: self.verification_timeout,
+            policy_denied: self.policy_denied,
+            maintenance: self.maintenance,
+            errors: self.errors,
+            latency_mean_ms: self.latency.mean_ms(),
+            latency_p95_ms: self.latency.percentile_ms(0.95),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Latency {
+    buckets: [u64; BUCKET_COUNT],
+    sum_us: u64,
+    max_us: u64,
+}
+
+impl Latency {
+    fn count(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    fn mean_ms(&self) -> f64 {
+        match self.count() {
+            0 => 0.0,
+            count => self.sum_us as f64 / count as f64 / 1000.0,
+        }
+    }
+
+    /// Upper bound of the bucket holding the percentile, capped at the
+    /// slowest request seen
+    fn percentile_ms(&self, percentile: f64) -> f64 {
+        let count = self.count();
+        if count == 0 {
+            return 0.0;
+        }
+        let rank = (count as f64 * percentile).ceil() as u64;
+        let mut seen = 0;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket;
+            if seen >= rank {
+                let bound = LATENCY_BUCKETS_US.get(i).copied().unwrap_or(u64::MAX);
+                return bound.min(self.max_us) as f64 / 1000.0;
+            }
+        }
+        self.max_us as f64 / 1000.0
+    }
+}
+
+/// Request statistics since server start or the last reset
+pub struct StatsRegistry {
+    since: RwLock<DateTime<Utc>>,
+    paths: RwLock<HashMap<String, Arc<PathStats>>>,
+}
+
+impl StatsRegistry {
+    pub fn new() -> Self {
+        Self {
+            since: RwLock::new(Utc::now()),
+            paths: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Count one handled request against `pattern`
+    pub fn record(&self, pattern: &str, outcome: Outcome, elapsed: Duration) {
+        let existing = self
+            .paths
+            .read()
+            .expect("CRITICAL: Path stats lock poisoned - thread panic detected")
+            .get(pattern)
+            .cloned();
+        let stats = existing.unwrap_or_else(|| {
+            self.paths
+                .write()
+                .unwrap()
+                .entry(pattern.to_string())
+                .or_default()
+                .clone()
+        });
+        stats.record(outcome, elapsed);
+    }
+
+    /// Zero every counter and restart the measurement window
+    pub fn reset(&self) {
+        *self
+            .paths
+            .write()
+            .expect("CRITICAL: Path stats lock poisoned - thread panic detected") = HashMap::new();
+        *self
+            .since
+            .write()
+            .expect("CRITICAL: Stats window lock poisoned - thread panic detected") = Utc::now();
+    }
+
+    /// Add the counters of `snapshot`, e.g. one taken by another server
+    ///
+    /// Counts add up exactly; latency keeps each pattern's mean and p95
+    /// but not its histogram. The window starts at the earlier `since`.
+    pub fn restore(&self, snapshot: &StatsSnapshot) {
+        let mut paths = self
+            .paths
+            .write()
+            .expect("CRITICAL: Path stats lock poisoned - thread panic detected");
+        for row in &snapshot.paths {
+            paths
+                .entry(row.pattern.clone())
+                .or_default()
+                .add_snapshot(row);
+        }
+        let mut since = self
+            .since
+            .write()
+            .expect("CRITICAL: Stats window lock poisoned - thread panic detected");
+        *since = (*since).min(snapshot.since);
+    }
+
+    /// Current counters per pattern (sorted) plus totals
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let mut totals = Counts::default();
+        let mut paths: Vec<PathStatsSnapshot> = self
+            .paths
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(pattern, stats)| {
+                let counts = stats.counts();
+                totals.add(&counts);
+                counts.snapshot(pattern)
+            })
+            .collect();
+        paths.sort_by(|a, b| a.pattern.cmp(&b.pattern));
+
+        StatsSnapshot {
+            since: *self
+                .since
+                .read()
+                .expect("CRITICAL: Stats window lock poisoned - thread panic detected"),
+            paths,
+            totals: totals.snapshot(TOTAL_PATTERN),
+            rule_hits: None,
+        }
+    }
+}
+
+impl Default for StatsRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `/__admin/stats` response body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    /// Server start or last reset
+    pub since: DateTime<Utc>,
+    /// One entry per pricing pattern that has seen traffic
+    pub paths: Vec<PathStatsSnapshot>,
+    /// Sum over all patterns (pattern `total`)
+    pub totals: PathStatsSnapshot,
+    /// Hits per pricing rule and policy, when `match_stats` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_hits: Option<RuleHitsSnapshot>,
+}
+
+/// Rule hit counters of `/__admin/stats`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleHitsSnapshot {
+    /// Pricing patterns (sorted), then `*` for the default price
+    pub pricing: Vec<RuleHits>,
+    /// Policies in evaluation order, then `default` (empty without policies)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<RuleHits>,
+}
+
+/// Counters for one pricing pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathStatsSnapshot {
+    /// Exact path, `/prefix/*` rule, or `*` for the default price
+    pub pattern: String,
+    pub requests: u64,
+    /// 402 responses carrying an invoice
+    pub invoices_issued: u64,
+    pub verified: u64,
+    /// 200 responses for paths priced at 0 (no invoice)
+    #[serde(default)]
+    pub free: u64,
+    pub verification_failed: u64,
+    pub verification_timeout: u64,
+    /// 403 responses from policy enforcement
+    pub policy_denied: u64,
+    /// 503 responses from paths in maintenance
+    #[serde(default)]
+    pub maintenance: u64,
+    /// Malformed proofs and pricing misconfigurations
+    pub errors: u64,
+    pub latency_mean_ms: f64,
+    /// Histogram bucket bound, so an upper estimate
+    pub latency_p95_ms: f64,
+}
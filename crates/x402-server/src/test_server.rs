@@ -0,0 +1,236 @@
+// In-process mock server for tests and scenario runs
+//
+// Binds an ephemeral loopback port, runs in the background of the current
+// runtime and is stopped explicitly, so callers can drive it and read its
+// stats without a separate `x402-dev mock` process. The same wrapper serves
+// the conformance battery of `check --self-test`.
+
+use actix_web::dev::ServerHandle;
+use actix_web::http::{Method, StatusCode};
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use anyhow::{Context, Result};
+use std::net::{Ipv4Addr, TcpListener};
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use x402_core::testing::battery::{self, BatteryFixture};
+
+use crate::server::{listen, MockServerConfig};
+use crate::snapshot::{RestoreSummary, ServerSnapshot, ADMIN_SNAPSHOT_PATH};
+use crate::stats::{StatsSnapshot, ADMIN_STATS_PATH};
+
+/// Mock server running inside the current process on `127.0.0.1:<free port>`
+///
+/// The config's `port`, `port_fallback` and `bind_address` are ignored.
+/// Neither startup nor request lines are printed, so the server can run
+/// behind JSON output or a stdio protocol. Call [`stop`](Self::stop) when
+/// done.
+pub struct TestServer {
+    base_url: String,
+    port: u16,
+    handle: ServerHandle,
+    task: JoinHandle<std::io::Result<()>>,
+}
+
+impl TestServer {
+    /// Start serving `server_config` in the background
+    pub async fn start(mut server_config: MockServerConfig) -> Result<Self> {
+        server_config.config.console_output = false;
+        let (listener, port) = bind_loopback()?;
+        let scheme = if server_config.config.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+
+        let listening = listen(server_config, listener, false)?;
+        let handle = listening.server.handle();
+        let task = tokio::spawn(listening.run());
+
+        Ok(Self {
+            base_url: format!("{}://127.0.0.1:{}", scheme, port),
+            port,
+            handle,
+            task,
+        })
+    }
+
+    /// Serve the conformance battery: each fixture's canned response at
+    /// `/battery/<name>`, HEAD without the body, 404 elsewhere
+    pub async fn start_battery() -> Result<Self> {
+        let (listener, port) = bind_loopback()?;
+        let fixtures = Arc::new(battery::fixtures());
+        let server = HttpServer::new(move || {
+            let fixtures = Arc::clone(&fixtures);
+            App::new().default_service(web::route().to(move |req: HttpRequest| {
+                let fixtures = Arc::clone(&fixtures);
+                async move { fixture_response(&fixtures, &req) }
+            }))
+        })
+        .workers(1)
+        .disable_signals()
+        .listen(listener)
+        .context("Failed to listen for the battery server")?
+        .run();
+        let handle = server.handle();
+        let task = tokio::spawn(server);
+
+        Ok(Self {
+            base_url: format!("http://127.0.0.1:{}", port),
+            port,
+            handle,
+            task,
+        })
+    }
+
+    /// `http://127.0.0.1:<port>` (https with TLS), without a trailing slash
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Current `/__admin/stats` snapshot
+    pub async fn stats(&self) -> Result<StatsSnapshot> {
+        self.client()?
+            .get(format!("{}{}", self.base_url, ADMIN_STATS_PATH))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context("Failed to fetch test server stats")?
+            .json()
+            .await
+            .context("Invalid test server stats response")
+    }
+
+    /// Whole server state from `/__admin/snapshot`
+    pub async fn snapshot(&self) -> Result<ServerSnapshot> {
+        self.client()?
+            .get(format!("{}{}", self.base_url, ADMIN_SNAPSHOT_PATH))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context("Failed to fetch test server snapshot")?
+            .json()
+            .await
+            .context("Invalid test server snapshot response")
+    }
+
+    /// Add `snapshot`'s state to this server
+    pub async fn restore(&self, snapshot: &ServerSnapshot) -> Result<RestoreSummary> {
+        self.client()?
+            .post(format!("{}{}", self.base_url, ADMIN_SNAPSHOT_PATH))
+            .json(snapshot)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .context("Failed to restore the test server snapshot")?
+            .json()
+            .await
+            .context("Invalid test server restore response")
+    }
+
+    /// Client for the admin API, trusting a self-signed certificate
+    fn client(&self) -> Result<reqwest::Client> {
+        Ok(reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .build()?)
+    }
+
+    /// Stop gracefully and wait for in-flight requests to finish
+    pub async fn stop(self) -> Result<()> {
+        self.handle.stop(true).await;
+        self.task
+            .await
+            .context("Test server task panicked")?
+            .context("HTTP server error")
+    }
+}
+
+/// Bind an ephemeral port on 127.0.0.1
+fn bind_loopback() -> Result<(TcpListener, u16)> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .context("Failed to bind a loopback port for the test server")?;
+    let port = listener
+        .local_addr()
+        .context("Failed to read the bound address")?
+        .port();
+    Ok((listener, port))
+}
+
+/// The canned response of the fixture at the request's path
+fn fixture_response(fixtures: &[BatteryFixture], req: &HttpRequest) -> HttpResponse {
+    let Some(fixture) = fixtures.iter().find(|f| f.path() == req.path()) else {
+        return HttpResponse::NotFound().body("No battery fixture at this path");
+    };
+    let status = StatusCode::from_u16(fixture.status).unwrap_or(StatusCode::PAYMENT_REQUIRED);
+    let mut response = HttpResponse::build(status);
+    for (name, value) in &fixture.headers {
+        response.insert_header((*name, value.as_str()));
+    }
+    if req.method() == Method::HEAD {
+        response.finish()
+    } else {
+        response.body(fixture.body.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::Config;
+
+    #[tokio::test]
+    async fn test_serves_on_ephemeral_port_until_stopped() {
+        let config = Config::builder().default_price(0.02).build().unwrap();
+        let server = TestServer::start(MockServerConfig::new(config).unwrap())
+            .await
+            .unwrap();
+        assert!(server.base_url().starts_with("http://127.0.0.1:"));
+
+        let resp = reqwest::get(format!("{}/api/data", server.base_url()))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 402);
+        let stats = server.stats().await.unwrap();
+        assert_eq!(stats.totals.invoices_issued, 1);
+
+        let url = server.base_url().to_string();
+        server.stop().await.unwrap();
+        assert!(reqwest::get(url).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_battery_serves_fixtures() {
+        let server = TestServer::start_battery().await.unwrap();
+        let client = reqwest::Client::new();
+
+        let resp = client
+            .get(format!("{}/battery/cacheable", server.base_url()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 402);
+        assert_eq!(resp.headers()["cache-control"], "public, max-age=300");
+        assert!(resp.headers()["www-authenticate"]
+            .to_str()
+            .unwrap()
+            .starts_with("x402-solana recipient="));
+        assert!(!resp.text().await.unwrap().is_empty());
+
+        let head = client
+            .head(format!("{}/battery/wrong_status", server.base_url()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(head.status(), 200);
+        assert!(head.bytes().await.unwrap().is_empty());
+
+        let missing = reqwest::get(format!("{}/battery/nope", server.base_url()))
+            .await
+            .unwrap();
+        assert_eq!(missing.status(), 404);
+        server.stop().await.unwrap();
+    }
+}
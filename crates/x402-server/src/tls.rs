@@ -0,0 +1,128 @@
+//! TLS support for serving the mock facilitator over https://
+//!
+//! Certificates come either from PEM files on disk or from an ephemeral
+//! self-signed certificate generated at startup.
+
+use anyhow::{anyhow, Context, Result};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// TLS settings for the mock server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM certificate chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert_path: Option<PathBuf>,
+
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<PathBuf>,
+
+    /// Generate an ephemeral self-signed certificate at startup
+    #[serde(default)]
+    pub self_signed: bool,
+}
+
+/// A rustls server configuration plus the leaf certificate fingerprint
+pub struct LoadedTls {
+    pub server_config: rustls::ServerConfig,
+    /// SHA-256 of the leaf certificate, colon-separated hex
+    pub fingerprint: String,
+}
+
+impl TlsConfig {
+    /// Config that generates a self-signed certificate at startup
+    pub fn self_signed() -> Self {
+        Self {
+            self_signed: true,
+            ..Self::default()
+        }
+    }
+
+    /// Load or generate the certificate and build the rustls config
+    pub fn load(&self) -> Result<LoadedTls> {
+        let (certs, key) = if self.self_signed {
+            generate_self_signed()?
+        } else {
+            match (&self.cert_path, &self.key_path) {
+                (Some(cert), Some(key)) => (read_certs(cert)?, read_key(key)?),
+                _ => {
+                    return Err(anyhow!(
+                        "TLS requires both cert_path and key_path\nFix: Set tls.cert_path and tls.key_path in your config, or use --tls-self-signed"
+                    ))
+                }
+            }
+        };
+
+        let fingerprint = fingerprint(&certs[0]);
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let server_config = rustls::ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .context("Failed to configure TLS protocol versions")?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .context("TLS certificate and private key do not match")?;
+
+        Ok(LoadedTls {
+            server_config,
+            fingerprint,
+        })
+    }
+}
+
+/// Generate a self-signed certificate valid for localhost and 127.0.0.1
+fn generate_self_signed() -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+    let certified =
+        rcgen::generate_simple_self_signed(vec!["localhost".to_string(), "127.0.0.1".to_string()])
+            .context("Failed to generate self-signed certificate")?;
+
+    let key = PrivatePkcs8KeyDer::from(certified.key_pair.serialize_der());
+    Ok((vec![certified.cert.der().clone()], key.into()))
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    std::fs::metadata(path).with_context(|| {
+        format!(
+            "Cannot read TLS certificate: {}\nFix: Check that tls.cert_path points to a readable PEM file",
+            path.display()
+        )
+    })?;
+
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| anyhow!("Invalid TLS certificate {}: {}", path.display(), e))?;
+
+    if certs.is_empty() {
+        return Err(anyhow!(
+            "No certificates found in {}\nFix: The file must contain at least one '-----BEGIN CERTIFICATE-----' block",
+            path.display()
+        ));
+    }
+    Ok(certs)
+}
+
+fn read_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
+    std::fs::metadata(path).with_context(|| {
+        format!(
+            "Cannot read TLS private key: {}\nFix: Check that tls.key_path points to a readable PEM file",
+            path.display()
+        )
+    })?;
+
+    PrivateKeyDer::from_pem_file(path)
+        .map_err(|e| anyhow!("Invalid TLS private key {}: {}", path.display(), e))
+}
+
+/// SHA-256 fingerprint in the `AB:CD:...` form shown by browsers and openssl
+fn fingerprint(cert: &CertificateDer<'_>) -> String {
+    ring::digest::digest(&ring::digest::SHA256, cert.as_ref())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
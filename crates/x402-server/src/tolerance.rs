@@ -0,0 +1,192 @@
+//! Amount tolerance for payment verification (`amount_tolerance:`)
+//!
+//! A simulated payment may declare what it paid in `X-Payment-Amount`, and
+//! an RPC-verified one paid its transfer amount; either is compared with the
+//! invoice amount instead of assuming an exact match.
+//! Facilitators accept small differences (aggregator fees, lamport
+//! rounding), so the tolerance allows up to an absolute USDC amount, a
+//! percentage of the invoice, or the larger of the two when both are set.
+//! All arithmetic is on decimals.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use x402_domain::{Amount, Decimal, USDC_DECIMALS};
+
+pub use x402_core::verifier::{AmountMismatch, OVERPAYMENT, UNDERPAYMENT};
+
+/// Request header declaring the amount a payment paid
+pub const PAYMENT_AMOUNT_HEADER: &str = "X-Payment-Amount";
+
+/// Accepted difference between a declared payment and its invoice
+///
+/// The default accepts exact payments only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AmountTolerance {
+    /// Largest accepted difference in USDC
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub absolute: Option<Amount>,
+    /// Largest accepted difference in percent of the invoice amount
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<Decimal>,
+}
+
+impl AmountTolerance {
+    /// True when only exact payments are accepted
+    pub fn is_exact(&self) -> bool {
+        self.absolute.is_none() && self.percent.is_none()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(absolute) = self.absolute {
+            let absolute = absolute.as_decimal();
+            if absolute.is_sign_negative() || absolute.scale() > USDC_DECIMALS {
+                bail!(
+                    "Invalid amount_tolerance.absolute: {}. Must be a non-negative USDC amount with at most {} decimal places.\nFix: Set it to e.g. 0.000001",
+                    absolute,
+                    USDC_DECIMALS
+                );
+            }
+        }
+        if let Some(percent) = self.percent {
+            if percent.is_sign_negative() || percent > Decimal::ONE_HUNDRED {
+                bail!(
+                    "Invalid amount_tolerance.percent: {}. Must be between 0 and 100.\nFix: Set it to e.g. 0.5 for half a percent",
+                    percent
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Largest difference from `invoiced` that is accepted
+    pub fn allowance(&self, invoiced: Amount) -> Decimal {
+        let absolute = self.absolute.map_or(Decimal::ZERO, |a| a.as_decimal());
+        let relative = self.percent.map_or(Decimal::ZERO, |percent| {
+            invoiced.as_decimal() * percent / Decimal::ONE_HUNDRED
+        });
+        absolute.max(relative)
+    }
+
+    /// Compare a declared payment with its invoice amount
+    ///
+    /// Returns the accepted delta, `paid - invoiced` (zero for an exact
+    /// match). A difference equal to the allowance is accepted.
+    pub fn check(&self, invoiced: Amount, paid: Amount) -> Result<Decimal, AmountMismatch> {
+        let delta = paid.as_decimal() - invoiced.as_decimal();
+        let allowance = self.allowance(invoiced);
+        if delta.abs() <= allowance {
+            Ok(delta)
+        } else {
+            Err(AmountMismatch {
+                invoiced,
+                paid,
+                allowance,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn amount(s: &str) -> Amount {
+        Amount::from_str(s).unwrap()
+    }
+
+    fn tolerance(absolute: Option<&str>, percent: Option<&str>) -> AmountTolerance {
+        AmountTolerance {
+            absolute: absolute.map(amount),
+            percent: percent.map(|p| Decimal::from_str(p).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_error_codes_are_explained() {
+        for code in [
+            UNDERPAYMENT,
+            OVERPAYMENT,
+            crate::handlers::REPLAYED_PAYMENT,
+            crate::handlers::EXPIRED_INVOICE,
+        ] {
+            assert!(
+                x402_core::explain::lookup(code).is_some(),
+                "add {} to x402-core's explain catalog",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_exact_match_only_by_default() {
+        let exact = AmountTolerance::default();
+        assert_eq!(
+            exact.check(amount("0.01"), amount("0.010")),
+            Ok(Decimal::ZERO)
+        );
+        let err = exact.check(amount("0.01"), amount("0.009999")).unwrap_err();
+        assert_eq!(err.code(), UNDERPAYMENT);
+    }
+
+    #[test]
+    fn test_within_absolute_and_percent() {
+        let absolute = tolerance(Some("0.0001"), None);
+        assert_eq!(
+            absolute.check(amount("1"), amount("0.99995")),
+            Ok(Decimal::from_str("-0.00005").unwrap())
+        );
+
+        // 0.5% of 2 USDC is 0.01
+        let percent = tolerance(None, Some("0.5"));
+        assert_eq!(
+            percent.check(amount("2"), amount("2.008")),
+            Ok(Decimal::from_str("0.008").unwrap())
+        );
+
+        // Both set: the larger allowance applies
+        let both = tolerance(Some("0.0001"), Some("0.5"));
+        assert!(both.check(amount("2"), amount("1.995")).is_ok());
+        assert!(both.check(amount("0.001"), amount("0.0011")).is_ok());
+    }
+
+    #[test]
+    fn test_outside_tolerance() {
+        let tolerance = tolerance(Some("0.001"), Some("1"));
+        let err = tolerance
+            .check(amount("0.05"), amount("0.048"))
+            .unwrap_err();
+        assert_eq!(err.code(), UNDERPAYMENT);
+        assert_eq!(
+            err.to_string(),
+            "Paid 0.048 USDC for an invoice of 0.05 USDC (tolerance: 0.001 USDC)"
+        );
+        let err = tolerance
+            .check(amount("0.05"), amount("0.052"))
+            .unwrap_err();
+        assert_eq!(err.code(), OVERPAYMENT);
+    }
+
+    #[test]
+    fn test_boundary_is_accepted() {
+        let absolute = tolerance(Some("0.000001"), None);
+        assert!(absolute.check(amount("0.01"), amount("0.009999")).is_ok());
+        assert!(absolute.check(amount("0.01"), amount("0.010001")).is_ok());
+        assert!(absolute.check(amount("0.01"), amount("0.009998")).is_err());
+
+        // 1% of 0.3 is exactly 0.003, with no float rounding to fall short
+        let percent = tolerance(None, Some("1"));
+        assert!(percent.check(amount("0.3"), amount("0.297")).is_ok());
+        assert!(percent.check(amount("0.3"), amount("0.296999")).is_err());
+    }
+
+    #[test]
+    fn test_validate_ranges() {
+        assert!(tolerance(Some("0.000001"), Some("100")).validate().is_ok());
+        assert!(tolerance(None, Some("100.1")).validate().is_err());
+        assert!(tolerance(None, Some("-1")).validate().is_err());
+        let err = tolerance(Some("0.0000001"), None).validate().unwrap_err();
+        assert!(err.to_string().contains("amount_tolerance.absolute"));
+    }
+}
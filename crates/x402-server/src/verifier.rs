@@ -0,0 +1,319 @@
+//! Payment verifiers of the mock server (`verifier:`)
+//!
+//! [`SimulatedVerifier`] answers from the simulation mode: `success` takes
+//! the proof as the paid invoice's memo and `X-Payment-Amount` (or the
+//! invoice amount) as what was paid, `failure` rejects and `timeout` waits
+//! `timeout_delay_ms` before giving up. [`RpcVerifier`] takes the proof as
+//! a transaction signature and looks it up over Solana JSON-RPC, on a real
+//! cluster or the mock's fake `/rpc`; the transfer's memo names the
+//! invoice and its amount is what was paid.
+//!
+//! Both then settle the payment the same way through [`Settlement`]: the
+//! amount against `amount_tolerance`, then the invoice deadline, then
+//! replays, against the server's [`InvoiceStore`]. Without a store, as in
+//! `x402-dev verify`, only the amount is checked.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+use x402_core::solana_rpc::SolanaRpcClient;
+use x402_core::verifier::{
+    FailureReason, InvoiceTerms, PaymentProof, PaymentVerifier, VerificationOutcome, VerifierKind,
+    VerifyFuture,
+};
+use x402_domain::Amount;
+
+use crate::invoices::{InvoiceStatus, InvoiceStore};
+use crate::server::SimulationMode;
+use crate::tolerance::AmountTolerance;
+
+/// Checks every verifier applies once it knows the memo and amount paid
+#[derive(Clone, Copy)]
+pub struct Settlement<'a> {
+    invoices: Option<&'a InvoiceStore>,
+    tolerance: AmountTolerance,
+    allow_replay: bool,
+}
+
+impl<'a> Settlement<'a> {
+    pub fn new(tolerance: AmountTolerance) -> Self {
+        Self {
+            invoices: None,
+            tolerance,
+            allow_replay: false,
+        }
+    }
+
+    /// Check deadlines and replays against `invoices`, recording
+    /// verifications in it
+    pub fn with_invoices(mut self, invoices: &'a InvoiceStore, allow_replay: bool) -> Self {
+        self.invoices = Some(invoices);
+        self.allow_replay = allow_replay;
+        self
+    }
+
+    /// Settle a payment of `paid` for the invoice `memo`
+    ///
+    /// A payment that would go through is checked before it uses up its
+    /// proof: first the amount, then the invoice deadline, then replays.
+    pub fn settle(
+        &self,
+        memo: &str,
+        paid: Amount,
+        invoice: &InvoiceTerms,
+        at: DateTime<Utc>,
+    ) -> VerificationOutcome {
+        let amount_delta = match self.tolerance.check(invoice.amount, paid) {
+            Ok(delta) => delta,
+            Err(mismatch) => {
+                return VerificationOutcome::Failed(FailureReason::AmountMismatch(mismatch))
+            }
+        };
+        let mut replayed = false;
+        if let Some(invoices) = self.invoices {
+            if let Some(expires_at) = expired_invoice(invoices, memo, at) {
+                return VerificationOutcome::Failed(FailureReason::Expired { expires_at });
+            }
+            replayed = !invoices.record_verification(memo, &invoice.resource, at);
+            if replayed && !self.allow_replay {
+                return VerificationOutcome::Failed(FailureReason::Replayed);
+            }
+        }
+        VerificationOutcome::Verified {
+            memo: memo.to_string(),
+            amount_delta,
+            replayed,
+        }
+    }
+
+    /// Recipient of the invoice `memo` in the store, if it is there
+    fn invoice_recipient(&self, memo: &str) -> Option<String> {
+        let record = self.invoices?.get(memo)?;
+        record
+            .invoice
+            .as_ref()
+            .map(|invoice| invoice.recipient.to_string())
+    }
+}
+
+/// Deadline of the unpaid invoice `memo`, if it passed by `now`
+///
+/// Proofs the store never saw invoiced (or has forgotten) carry no deadline.
+fn expired_invoice(
+    invoices: &InvoiceStore,
+    memo: &str,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    let record = invoices.get(memo)?;
+    let invoice = record.invoice.as_ref()?;
+    (record.status == InvoiceStatus::Issued && invoice.expires_at <= now)
+        .then_some(invoice.expires_at)
+}
+
+/// Verification by simulation mode; the proof is the invoice memo
+#[derive(Clone, Copy)]
+pub struct SimulatedVerifier<'a> {
+    mode: SimulationMode,
+    timeout: Duration,
+    settlement: Settlement<'a>,
+}
+
+impl<'a> SimulatedVerifier<'a> {
+    /// `timeout` is how long the `timeout` mode waits before giving up
+    pub fn new(mode: SimulationMode, timeout: Duration, settlement: Settlement<'a>) -> Self {
+        Self {
+            mode,
+            timeout,
+            settlement,
+        }
+    }
+}
+
+impl PaymentVerifier for SimulatedVerifier<'_> {
+    fn kind(&self) -> VerifierKind {
+        VerifierKind::Simulated
+    }
+
+    fn verify<'a>(
+        &'a self,
+        proof: &'a PaymentProof,
+        invoice: &'a InvoiceTerms,
+    ) -> VerifyFuture<'a> {
+        Box::pin(async move {
+            Ok(match self.mode {
+                SimulationMode::Success => {
+                    let memo = invoice.memo.as_deref().unwrap_or(&proof.proof);
+                    let paid = proof.declared_amount.unwrap_or(invoice.amount);
+                    self.settlement
+                        .settle(memo, paid, invoice, proof.received_at)
+                }
+                SimulationMode::Failure => {
+                    VerificationOutcome::Failed(FailureReason::Rejected(None))
+                }
+                SimulationMode::Timeout => {
+                    tokio::time::sleep(self.timeout).await;
+                    VerificationOutcome::Failed(FailureReason::Pending(format!(
+                        "verification timed out after {}ms",
+                        self.timeout.as_millis()
+                    )))
+                }
+            })
+        })
+    }
+}
+
+/// Verification of a transaction signature over Solana JSON-RPC
+#[derive(Clone, Copy)]
+pub struct RpcVerifier<'a> {
+    client: &'a SolanaRpcClient,
+    settlement: Settlement<'a>,
+}
+
+impl<'a> RpcVerifier<'a> {
+    pub fn new(client: &'a SolanaRpcClient, settlement: Settlement<'a>) -> Self {
+        Self { client, settlement }
+    }
+
+    async fn check(
+        &self,
+        proof: &PaymentProof,
+        invoice: &InvoiceTerms,
+    ) -> Result<VerificationOutcome> {
+        let rejected =
+            |detail: String| VerificationOutcome::Failed(FailureReason::Rejected(Some(detail)));
+        let pending = || {
+            VerificationOutcome::Failed(FailureReason::Pending(
+                "transaction not yet confirmed".to_string(),
+            ))
+        };
+
+        let signature = &proof.proof;
+        let Some(status) = self.client.get_signature_status(signature).await? else {
+            return Ok(rejected("transaction not found".to_string()));
+        };
+        if let Some(err) = &status.err {
+            return Ok(rejected(format!("transaction failed: {}", err)));
+        }
+        if !status.is_settled() {
+            return Ok(pending());
+        }
+        let Some(transfer) = self.client.get_transfer(signature).await? else {
+            return Ok(pending());
+        };
+
+        let Some(memo) = transfer.memo else {
+            return Ok(rejected("transaction carries no memo".to_string()));
+        };
+        if let Some(expected) = invoice.memo.as_deref().filter(|expected| *expected != memo) {
+            return Ok(rejected(format!("memo is {}, expected {}", memo, expected)));
+        }
+        let recipient = invoice
+            .recipient
+            .clone()
+            .or_else(|| self.settlement.invoice_recipient(&memo));
+        if let Some(expected) = recipient.filter(|expected| *expected != transfer.destination) {
+            return Ok(rejected(format!(
+                "paid to {}, expected {}",
+                transfer.destination, expected
+            )));
+        }
+        let paid = Amount::from_usdc_lamports(transfer.amount)?;
+        Ok(self
+            .settlement
+            .settle(&memo, paid, invoice, proof.received_at))
+    }
+}
+
+impl PaymentVerifier for RpcVerifier<'_> {
+    fn kind(&self) -> VerifierKind {
+        VerifierKind::Rpc
+    }
+
+    fn verify<'a>(
+        &'a self,
+        proof: &'a PaymentProof,
+        invoice: &'a InvoiceTerms,
+    ) -> VerifyFuture<'a> {
+        Box::pin(self.check(proof, invoice))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use x402_domain::Decimal;
+
+    fn amount(s: &str) -> Amount {
+        Amount::from_str(s).unwrap()
+    }
+
+    fn terms() -> InvoiceTerms {
+        InvoiceTerms {
+            memo: None,
+            recipient: None,
+            amount: amount("0.05"),
+            resource: "/api/data".to_string(),
+        }
+    }
+
+    fn proof(declared: Option<&str>) -> PaymentProof {
+        PaymentProof {
+            proof: "req-1".to_string(),
+            declared_amount: declared.map(amount),
+            received_at: Utc::now(),
+        }
+    }
+
+    async fn simulate(
+        mode: SimulationMode,
+        settlement: Settlement<'_>,
+        proof: &PaymentProof,
+    ) -> VerificationOutcome {
+        SimulatedVerifier::new(mode, Duration::from_millis(1), settlement)
+            .verify(proof, &terms())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_simulated_modes() {
+        let settlement = Settlement::new(AmountTolerance::default());
+
+        let outcome = simulate(SimulationMode::Success, settlement, &proof(None)).await;
+        assert_eq!(
+            outcome,
+            VerificationOutcome::Verified {
+                memo: "req-1".to_string(),
+                amount_delta: Decimal::ZERO,
+                replayed: false,
+            }
+        );
+        let failure = simulate(SimulationMode::Failure, settlement, &proof(None)).await;
+        assert_eq!(failure.class(), "rejected");
+        let timeout = simulate(SimulationMode::Timeout, settlement, &proof(None)).await;
+        assert_eq!(timeout.class(), "pending");
+        let short = simulate(SimulationMode::Success, settlement, &proof(Some("0.04"))).await;
+        assert_eq!(short.class(), "underpayment");
+    }
+
+    #[tokio::test]
+    async fn test_store_rejects_replays_unless_allowed() {
+        let invoices = InvoiceStore::default();
+        let strict = Settlement::new(AmountTolerance::default()).with_invoices(&invoices, false);
+        let proof = proof(None);
+
+        assert!(simulate(SimulationMode::Success, strict, &proof)
+            .await
+            .is_verified());
+        let replay = simulate(SimulationMode::Success, strict, &proof).await;
+        assert_eq!(replay.class(), "replayed");
+
+        let lenient = Settlement::new(AmountTolerance::default()).with_invoices(&invoices, true);
+        let allowed = simulate(SimulationMode::Success, lenient, &proof).await;
+        assert!(matches!(
+            allowed,
+            VerificationOutcome::Verified { replayed: true, .. }
+        ));
+    }
+}
@@ -0,0 +1,361 @@
+//! Event webhooks for the mock server
+//!
+//! Handlers push events into a bounded queue; a background worker POSTs
+//! them to subscribed URLs with retries. At most [`WEBHOOK_QUEUE_CAPACITY`]
+//! events are queued or being delivered at once, retries included. Request
+//! handling never waits on delivery: when the limit is reached the event is
+//! dropped and counted.
+//! Logged URLs have their password and query values redacted.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+use x402_core::redact::redact_url;
+use x402_domain::Decimal;
+
+/// Maximum number of undelivered events, queued or in flight
+pub const WEBHOOK_QUEUE_CAPACITY: usize = 256;
+
+/// Delay before the first retry; doubles on each subsequent attempt
+const INITIAL_BACKOFF_MS: u64 = 100;
+
+/// Per-attempt HTTP timeout
+const DELIVERY_TIMEOUT_SECS: u64 = 5;
+
+/// Header carrying the HMAC-SHA256 signature of the request body
+pub const SIGNATURE_HEADER: &str = "X-X402-Signature";
+
+/// Header carrying the event type
+pub const EVENT_HEADER: &str = "X-X402-Event";
+
+/// Event types a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EventType {
+    #[serde(rename = "invoice.issued")]
+    InvoiceIssued,
+    #[serde(rename = "payment.verified")]
+    PaymentVerified,
+    #[serde(rename = "payment.failed")]
+    PaymentFailed,
+    #[serde(rename = "policy.denied")]
+    PolicyDenied,
+    /// 503 from a path in maintenance
+    #[serde(rename = "request.maintenance")]
+    Maintenance,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::InvoiceIssued => "invoice.issued",
+            EventType::PaymentVerified => "payment.verified",
+            EventType::PaymentFailed => "payment.failed",
+            EventType::PolicyDenied => "policy.denied",
+            EventType::Maintenance => "request.maintenance",
+        }
+    }
+}
+
+/// A webhook subscription from the `webhooks:` config section
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Receiver URL
+    pub url: String,
+
+    /// Event types delivered to this URL
+    pub events: Vec<EventType>,
+
+    /// Shared secret for the signature header (unsigned if absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+
+    /// Retries after the first attempt on 5xx or connection errors
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+/// JSON envelope shared by all event types
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookEvent {
+    /// Unique event ID
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub request_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    pub path: String,
+    pub amount: f64,
+    /// e.g. "payment_required", "success", "failure", "timeout", "replayed",
+    /// "denied"
+    pub outcome: String,
+    /// Why a policy denied the request, or `replayed_payment` for a
+    /// payment proof that was already verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Accepted difference between the declared and invoiced amount
+    /// (`payment.verified` within `amount_tolerance` only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_delta: Option<String>,
+    pub timestamp: String,
+}
+
+impl WebhookEvent {
+    pub fn new(
+        event_type: EventType,
+        request_id: &str,
+        memo: Option<String>,
+        path: &str,
+        amount: f64,
+        outcome: &str,
+    ) -> Self {
+        Self {
+            id: format!("evt-{}", Uuid::new_v4()),
+            event_type,
+            request_id: request_id.to_string(),
+            memo,
+            path: path.to_string(),
+            amount,
+            outcome: outcome.to_string(),
+            reason: None,
+            amount_delta: None,
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Record a non-zero accepted amount difference
+    pub fn with_amount_delta(mut self, delta: Decimal) -> Self {
+        self.amount_delta = (!delta.is_zero()).then(|| delta.normalize().to_string());
+        self
+    }
+}
+
+/// Delivery counters, readable while the server runs
+#[derive(Debug, Default)]
+pub struct WebhookStats {
+    /// HTTP attempts made, including retries
+    pub attempts: AtomicU64,
+    /// Deliveries acknowledged with a 2xx
+    pub delivered: AtomicU64,
+    /// Deliveries abandoned after exhausting retries or on 4xx
+    pub failed: AtomicU64,
+    /// Events discarded because too many were undelivered
+    pub dropped: AtomicU64,
+}
+
+/// Fire-and-forget event dispatcher shared by request handlers
+pub struct WebhookDispatcher {
+    sender: Option<mpsc::Sender<(WebhookEvent, OwnedSemaphorePermit)>>,
+    /// One permit per undelivered event, held until all its deliveries end
+    permits: Arc<Semaphore>,
+    stats: Arc<WebhookStats>,
+}
+
+impl WebhookDispatcher {
+    /// Dispatcher that discards every event
+    pub fn disabled() -> Self {
+        Self {
+            sender: None,
+            permits: Arc::new(Semaphore::new(0)),
+            stats: Arc::new(WebhookStats::default()),
+        }
+    }
+
+    /// Start the background delivery worker on the current tokio runtime
+    pub fn start(webhooks: Vec<WebhookConfig>) -> Self {
+        Self::with_capacity(webhooks, WEBHOOK_QUEUE_CAPACITY)
+    }
+
+    /// Like [`start`](Self::start) with an explicit limit on undelivered
+    /// events
+    pub fn with_capacity(webhooks: Vec<WebhookConfig>, capacity: usize) -> Self {
+        if webhooks.is_empty() {
+            return Self::disabled();
+        }
+
+        let stats = Arc::new(WebhookStats::default());
+        let (sender, receiver) = mpsc::channel(capacity);
+        tokio::spawn(run_worker(receiver, webhooks, stats.clone()));
+
+        Self {
+            sender: Some(sender),
+            permits: Arc::new(Semaphore::new(capacity)),
+            stats,
+        }
+    }
+
+    /// Queue an event without waiting; drops it if too many are undelivered
+    pub fn emit(&self, event: WebhookEvent) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+
+        let queued = match self.permits.clone().try_acquire_owned() {
+            Ok(permit) => sender.try_send((event, permit)).map_err(|err| match err {
+                mpsc::error::TrySendError::Full((e, _))
+                | mpsc::error::TrySendError::Closed((e, _)) => e,
+            }),
+            Err(_) => Err(event),
+        };
+        if let Err(event) = queued {
+            let dropped = self.stats.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            eprintln!(
+                "⚠️  webhook event={} id={} dropped (queue full, total dropped: {})",
+                event.event_type.as_str(),
+                event.id,
+                dropped
+            );
+        }
+    }
+
+    pub fn stats(&self) -> &WebhookStats {
+        &self.stats
+    }
+}
+
+async fn run_worker(
+    mut receiver: mpsc::Receiver<(WebhookEvent, OwnedSemaphorePermit)>,
+    webhooks: Vec<WebhookConfig>,
+    stats: Arc<WebhookStats>,
+) {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!(
+                "❌ webhook worker disabled: failed to create HTTP client: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    while let Some((event, permit)) = receiver.recv().await {
+        let permit = Arc::new(permit);
+        let body = match serde_json::to_vec(&event) {
+            Ok(body) => Arc::new(body),
+            Err(e) => {
+                eprintln!("❌ webhook event={} not serializable: {}", event.id, e);
+                continue;
+            }
+        };
+
+        for webhook in webhooks.iter() {
+            if !webhook.events.contains(&event.event_type) {
+                continue;
+            }
+            // One task per delivery so a slow receiver doesn't hold up the others
+            tokio::spawn(deliver(
+                client.clone(),
+                webhook.clone(),
+                event.event_type,
+                event.id.clone(),
+                body.clone(),
+                stats.clone(),
+                permit.clone(),
+            ));
+        }
+    }
+}
+
+async fn deliver(
+    client: reqwest::Client,
+    webhook: WebhookConfig,
+    event_type: EventType,
+    event_id: String,
+    body: Arc<Vec<u8>>,
+    stats: Arc<WebhookStats>,
+    _permit: Arc<OwnedSemaphorePermit>,
+) {
+    let max_attempts = webhook.max_retries + 1;
+    let mut backoff = Duration::from_millis(INITIAL_BACKOFF_MS);
+
+    for attempt in 1..=max_attempts {
+        stats.attempts.fetch_add(1, Ordering::Relaxed);
+
+        let mut request = client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header(EVENT_HEADER, event_type.as_str())
+            .body(body.as_ref().clone());
+        if let Some(secret) = &webhook.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+        }
+
+        let retryable = match request.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                stats.delivered.fetch_add(1, Ordering::Relaxed);
+                println!(
+                    "📡 webhook event={} id={} url={} status={} attempt={} delivered",
+                    event_type.as_str(),
+                    event_id,
+                    redact_url(&webhook.url),
+                    resp.status().as_u16(),
+                    attempt
+                );
+                return;
+            }
+            Ok(resp) => {
+                eprintln!(
+                    "⚠️  webhook event={} id={} url={} status={} attempt={}/{} failed",
+                    event_type.as_str(),
+                    event_id,
+                    redact_url(&webhook.url),
+                    resp.status().as_u16(),
+                    attempt,
+                    max_attempts
+                );
+                resp.status().is_server_error()
+            }
+            Err(e) => {
+                eprintln!(
+                    "⚠️  webhook event={} id={} url={} attempt={}/{} error: {}",
+                    event_type.as_str(),
+                    event_id,
+                    redact_url(&webhook.url),
+                    attempt,
+                    max_attempts,
+                    e.without_url()
+                );
+                true
+            }
+        };
+
+        if !retryable || attempt == max_attempts {
+            break;
+        }
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+    }
+
+    stats.failed.fetch_add(1, Ordering::Relaxed);
+    eprintln!(
+        "❌ webhook event={} id={} url={} gave up",
+        event_type.as_str(),
+        event_id,
+        redact_url(&webhook.url)
+    );
+}
+
+/// `sha256=<hex>` HMAC of the body using the webhook secret
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = ring::hmac::sign(&key, body);
+    let hex: String = tag.as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
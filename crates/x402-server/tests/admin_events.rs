@@ -0,0 +1,240 @@
+// Admin Events and Simulation Mode Tests
+//
+// Drives the payment handler and checks what /__admin/events reports, that
+// /__admin/simulation-mode switches verification outcomes at runtime, and
+// that /health lists both as capabilities.
+
+use actix_web::{http::StatusCode, test, web, App};
+use x402_core::policy::{PolicyAction, PolicyEngine, RuntimePolicy};
+use x402_server::events::ADMIN_EVENTS_PATH;
+use x402_server::handlers::{
+    events_handler, health_handler, payment_required_handler, set_simulation_mode_handler,
+    simulation_mode_handler,
+};
+use x402_server::server::{ADMIN_SIMULATION_MODE_PATH, HEALTH_PATH};
+use x402_server::{
+    Config, EventLog, EventType, EventsPage, InvoiceGenerator, PricingMatcher, SimulationSwitch,
+    WebhookDispatcher, WebhookEvent,
+};
+
+fn policy(id: &str, agents: &[&str], action: PolicyAction, priority: u32) -> RuntimePolicy {
+    RuntimePolicy {
+        id: id.to_string(),
+        description: String::new(),
+        priority,
+        agent_patterns: agents.iter().map(|a| a.to_string()).collect(),
+        endpoint_patterns: vec![],
+        action,
+        rate_limit: None,
+        spending_cap: None,
+        concurrency_limit: None,
+    }
+}
+
+macro_rules! admin_app {
+    () => {{
+        let config = Config::builder()
+            .policies(vec![
+                policy(
+                    "block-bad",
+                    &["agent-bad"],
+                    PolicyAction::Deny("Blocked agent".to_string()),
+                    20,
+                ),
+                policy("allow-all", &[], PolicyAction::Allow, 10),
+            ])
+            .build()
+            .unwrap();
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(InvoiceGenerator::new()))
+                .app_data(web::Data::new(PolicyEngine::new(config.policies.clone())))
+                .app_data(web::Data::new(SimulationSwitch::new(
+                    config.simulation_mode,
+                )))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .app_data(web::Data::new(EventLog::new()))
+                .service(web::resource(HEALTH_PATH).get(health_handler))
+                .service(web::resource(ADMIN_EVENTS_PATH).get(events_handler))
+                .service(
+                    web::resource(ADMIN_SIMULATION_MODE_PATH)
+                        .get(simulation_mode_handler)
+                        .post(set_simulation_mode_handler),
+                )
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+macro_rules! get {
+    ($app:expr, $uri:expr $(, $header:expr)*) => {{
+        let req = test::TestRequest::get()
+            .uri($uri)
+            $(.insert_header($header))*
+            .to_request();
+        test::call_service(&$app, req).await.status()
+    }};
+}
+
+macro_rules! events {
+    ($app:expr, $after:expr) => {{
+        let req = test::TestRequest::get()
+            .uri(&format!("{}?after={}", ADMIN_EVENTS_PATH, $after))
+            .to_request();
+        let page: EventsPage = test::call_and_read_body_json(&$app, req).await;
+        page
+    }};
+}
+
+macro_rules! mode {
+    ($app:expr) => {{
+        let req = test::TestRequest::get()
+            .uri(ADMIN_SIMULATION_MODE_PATH)
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        body["mode"].as_str().unwrap().to_string()
+    }};
+}
+
+macro_rules! set_mode {
+    ($app:expr, $mode:expr) => {{
+        let req = test::TestRequest::post()
+            .uri(ADMIN_SIMULATION_MODE_PATH)
+            .set_json(serde_json::json!({ "mode": $mode }))
+            .to_request();
+        test::call_service(&$app, req).await
+    }};
+}
+
+/// Test the event log records invoices, payments and denials in order
+#[actix_web::test]
+async fn test_events_record_invoice_payment_and_denial() {
+    // Given: A server with a deny policy and an event log
+    let app = admin_app!();
+
+    // When: Invoicing, paying and being denied
+    assert_eq!(get!(app, "/api/data"), StatusCode::PAYMENT_REQUIRED);
+    let memo = events!(app, 0).events[0].event.memo.clone().unwrap();
+    assert_eq!(
+        get!(app, "/api/data", ("X-Payment-Proof", memo.as_str())),
+        StatusCode::OK
+    );
+    assert_eq!(
+        get!(app, "/api/data", ("X-Agent-Id", "agent-bad")),
+        StatusCode::FORBIDDEN
+    );
+
+    // Then: The events arrive in order, payments carry the proof as memo
+    let page = events!(app, 0);
+    let types: Vec<EventType> = page.events.iter().map(|e| e.event.event_type).collect();
+    assert_eq!(
+        types,
+        [
+            EventType::InvoiceIssued,
+            EventType::PaymentVerified,
+            EventType::PolicyDenied
+        ]
+    );
+    assert_eq!(page.events[1].event.memo.as_deref(), Some(memo.as_str()));
+    assert_eq!(
+        page.events[2].event.reason.as_deref(),
+        Some("Blocked agent")
+    );
+    assert_eq!((page.last_seq, page.missed), (3, 0));
+
+    // And: Polling after a cursor returns only newer events
+    let page = events!(app, 2);
+    assert_eq!(page.events.len(), 1);
+    assert_eq!(page.events[0].seq, 3);
+    assert!(events!(app, 3).events.is_empty());
+}
+
+/// Test a full log drops the oldest events and reports them as missed
+#[actix_web::test]
+async fn test_event_log_counts_dropped_events() {
+    let log = EventLog::with_capacity(3);
+    for i in 1..=5 {
+        log.record(WebhookEvent::new(
+            EventType::InvoiceIssued,
+            "req",
+            None,
+            &format!("/api/{}", i),
+            0.01,
+            "payment_required",
+        ));
+    }
+
+    let page = log.after(1);
+    let seqs: Vec<u64> = page.events.iter().map(|e| e.seq).collect();
+    assert_eq!(seqs, [3, 4, 5]);
+    assert_eq!(page.events[0].event.path, "/api/3");
+    assert_eq!((page.last_seq, page.missed), (5, 1));
+    assert_eq!(log.after(4).missed, 0);
+}
+
+/// Test switching the simulation mode changes verification outcomes
+#[actix_web::test]
+async fn test_simulation_mode_switch() {
+    // Given: A server verifying in success mode
+    let app = admin_app!();
+    assert_eq!(mode!(app), "success");
+
+    // When: Switching to failure
+    let resp = set_mode!(app, "failure");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body,
+        serde_json::json!({"mode": "failure", "previous": "success"})
+    );
+
+    // Then: Payments fail, unless a request overrides the mode
+    assert_eq!(
+        get!(app, "/api/data", ("X-Payment-Proof", "proof-1")),
+        StatusCode::PAYMENT_REQUIRED
+    );
+    assert_eq!(
+        get!(
+            app,
+            "/api/data",
+            ("X-Payment-Proof", "proof-2"),
+            ("X-Simulation-Mode", "success")
+        ),
+        StatusCode::OK
+    );
+
+    // And: Unknown modes are rejected without changing the mode
+    assert_eq!(
+        set_mode!(app, "sometimes").status(),
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(mode!(app), "failure");
+
+    // And: "fail" is accepted as an alias, like --simulation-mode
+    assert_eq!(set_mode!(app, "fail").status(), StatusCode::OK);
+    assert_eq!(mode!(app), "failure");
+}
+
+/// Test /health advertises the admin capabilities
+#[actix_web::test]
+async fn test_health_lists_capabilities() {
+    let app = admin_app!();
+    let req = test::TestRequest::get().uri(HEALTH_PATH).to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        body["capabilities"],
+        serde_json::json!([
+            "stats",
+            "events",
+            "simulation_mode",
+            "invoices",
+            "clock",
+            "snapshot",
+            "maintenance",
+            "readiness"
+        ])
+    );
+}
@@ -0,0 +1,185 @@
+// Admin Stats Tests
+//
+// Drives a known mix of requests through the payment handler and checks
+// the per-pattern counters on /__admin/stats, and that a reset zeroes them
+// while the app keeps serving.
+
+use actix_web::{http::StatusCode, test, web, App};
+use std::time::Duration;
+use x402_core::policy::{PolicyAction, PolicyEngine, RuntimePolicy};
+use x402_server::handlers::{payment_required_handler, reset_stats_handler, stats_handler};
+use x402_server::stats::{ADMIN_STATS_PATH, ADMIN_STATS_RESET_PATH};
+use x402_server::{
+    Config, InvoiceGenerator, Outcome, PricingMatcher, StatsRegistry, StatsSnapshot,
+    WebhookDispatcher,
+};
+
+fn policy(id: &str, agents: &[&str], action: PolicyAction, priority: u32) -> RuntimePolicy {
+    RuntimePolicy {
+        id: id.to_string(),
+        description: String::new(),
+        priority,
+        agent_patterns: agents.iter().map(|a| a.to_string()).collect(),
+        endpoint_patterns: vec![],
+        action,
+        rate_limit: None,
+        spending_cap: None,
+        concurrency_limit: None,
+    }
+}
+
+macro_rules! stats_app {
+    () => {{
+        let config = Config::builder()
+            .resource_price("/api/premium/*", 0.05)
+            .policies(vec![
+                policy(
+                    "block-bad",
+                    &["agent-bad"],
+                    PolicyAction::Deny("Blocked agent".to_string()),
+                    20,
+                ),
+                policy("allow-all", &[], PolicyAction::Allow, 10),
+            ])
+            .build()
+            .unwrap();
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(InvoiceGenerator::new()))
+                .app_data(web::Data::new(PolicyEngine::new(config.policies.clone())))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .app_data(web::Data::new(StatsRegistry::new()))
+                .service(web::resource(ADMIN_STATS_PATH).get(stats_handler))
+                .service(web::resource(ADMIN_STATS_RESET_PATH).post(reset_stats_handler))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+macro_rules! get {
+    ($app:expr, $uri:expr $(, $header:expr)*) => {{
+        let req = test::TestRequest::get()
+            .uri($uri)
+            $(.insert_header($header))*
+            .to_request();
+        test::call_service(&$app, req).await.status()
+    }};
+}
+
+macro_rules! snapshot {
+    ($app:expr) => {{
+        let req = test::TestRequest::get().uri(ADMIN_STATS_PATH).to_request();
+        let snapshot: StatsSnapshot = test::call_and_read_body_json(&$app, req).await;
+        snapshot
+    }};
+}
+
+/// Test counters per pricing pattern for a known request mix
+#[actix_web::test]
+async fn test_stats_count_request_mix() {
+    // Given: A server with a premium rule and a deny policy
+    let app = stats_app!();
+
+    // When: Driving invoices, verifications and a denial
+    assert_eq!(get!(app, "/api/data"), StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(
+        get!(app, "/api/data", ("X-Payment-Proof", "proof-1")),
+        StatusCode::OK
+    );
+    assert_eq!(get!(app, "/api/premium/a"), StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(get!(app, "/api/premium/b"), StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(
+        get!(
+            app,
+            "/api/premium/a",
+            ("X-Payment-Proof", "proof-2"),
+            ("X-Simulation-Mode", "failure")
+        ),
+        StatusCode::PAYMENT_REQUIRED
+    );
+    assert_eq!(
+        get!(app, "/api/data", ("X-Agent-Id", "agent-bad")),
+        StatusCode::FORBIDDEN
+    );
+
+    // Then: Each pattern has its own row
+    let snapshot = snapshot!(app);
+    let patterns: Vec<&str> = snapshot.paths.iter().map(|p| p.pattern.as_str()).collect();
+    assert_eq!(patterns, vec!["*", "/api/premium/*"]);
+
+    let default = &snapshot.paths[0];
+    assert_eq!(default.requests, 3);
+    assert_eq!(default.invoices_issued, 1);
+    assert_eq!(default.verified, 1);
+    assert_eq!(default.policy_denied, 1);
+
+    let premium = &snapshot.paths[1];
+    assert_eq!(premium.requests, 3);
+    assert_eq!(premium.invoices_issued, 2);
+    assert_eq!(premium.verification_failed, 1);
+    assert_eq!(premium.verified, 0);
+
+    // And: Totals sum the rows; admin requests are not counted
+    assert_eq!(snapshot.totals.requests, 6);
+    assert_eq!(snapshot.totals.invoices_issued, 3);
+    assert!(snapshot.totals.latency_p95_ms >= snapshot.totals.latency_mean_ms);
+    assert_eq!(snapshot!(app).totals.requests, 6);
+}
+
+/// Test reset zeroes all counters without restarting the server
+#[actix_web::test]
+async fn test_stats_reset() {
+    // Given: Some recorded traffic
+    let app = stats_app!();
+    get!(app, "/api/data");
+    get!(app, "/api/premium/a");
+    let before = snapshot!(app);
+    assert_eq!(before.totals.requests, 2);
+
+    // When: Resetting
+    let req = test::TestRequest::post()
+        .uri(ADMIN_STATS_RESET_PATH)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Then: Everything is zero and the window restarted
+    let after = snapshot!(app);
+    assert!(after.paths.is_empty());
+    assert_eq!(after.totals.requests, 0);
+    assert_eq!(after.totals.invoices_issued, 0);
+    assert_eq!(after.totals.latency_mean_ms, 0.0);
+    assert!(after.since >= before.since);
+
+    // And: The same app keeps counting from zero
+    get!(app, "/api/data");
+    assert_eq!(snapshot!(app).totals.requests, 1);
+
+    // And: Reset only accepts POST
+    let req = test::TestRequest::get()
+        .uri(ADMIN_STATS_RESET_PATH)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+/// Test the p95 is a histogram bucket bound capped at the slowest request
+#[actix_web::test]
+async fn test_p95_uses_bucket_bound_capped_at_max() {
+    let stats = StatsRegistry::new();
+    for _ in 0..19 {
+        stats.record("*", Outcome::InvoiceIssued, Duration::from_micros(200));
+    }
+    stats.record("*", Outcome::InvoiceIssued, Duration::from_millis(30));
+
+    // 19 of 20 requests fall in the 250us bucket
+    assert_eq!(stats.snapshot().totals.latency_p95_ms, 0.25);
+    assert_eq!(stats.snapshot().totals.latency_mean_ms, 1.69);
+
+    // Now the 95th percentile lands in the 50ms bucket, capped at 30ms
+    stats.record("*", Outcome::InvoiceIssued, Duration::from_millis(30));
+    assert_eq!(stats.snapshot().totals.latency_p95_ms, 30.0);
+}
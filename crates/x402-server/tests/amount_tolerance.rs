@@ -0,0 +1,167 @@
+// Amount Tolerance Tests
+//
+// Pays with an X-Payment-Amount that differs from the invoice: differences
+// within `amount_tolerance` verify with the delta in the receipt and the
+// event log, others fail with an underpayment or overpayment error code.
+
+use actix_web::{http::StatusCode, test, web, App};
+use std::str::FromStr;
+use x402_core::receipt::{PaymentReceipt, RECEIPT_HEADER};
+use x402_domain::{Amount, Decimal};
+use x402_server::events::ADMIN_EVENTS_PATH;
+use x402_server::handlers::{events_handler, payment_required_handler};
+use x402_server::tolerance::{OVERPAYMENT, PAYMENT_AMOUNT_HEADER, UNDERPAYMENT};
+use x402_server::{
+    AmountTolerance, Config, EventLog, EventType, EventsPage, InvoiceGenerator, PricingMatcher,
+    WebhookDispatcher,
+};
+
+fn tolerance(absolute: Option<&str>, percent: Option<&str>) -> AmountTolerance {
+    AmountTolerance {
+        absolute: absolute.map(|a| Amount::from_str(a).unwrap()),
+        percent: percent.map(|p| Decimal::from_str(p).unwrap()),
+    }
+}
+
+macro_rules! tolerance_app {
+    ($tolerance:expr) => {{
+        let config = Config::builder()
+            .default_price(0.05)
+            .amount_tolerance($tolerance)
+            .build()
+            .unwrap();
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(InvoiceGenerator::new()))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .app_data(web::Data::new(EventLog::new()))
+                .service(web::resource(ADMIN_EVENTS_PATH).get(events_handler))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+/// Pay with a fresh proof declaring `$paid`
+macro_rules! pay {
+    ($app:expr, $paid:expr) => {{
+        let req = test::TestRequest::get()
+            .uri("/api/data")
+            .insert_header(("X-Payment-Proof", uuid::Uuid::new_v4().to_string()))
+            .insert_header((PAYMENT_AMOUNT_HEADER, $paid))
+            .to_request();
+        test::call_service(&$app, req).await
+    }};
+}
+
+macro_rules! last_event {
+    ($app:expr) => {{
+        let req = test::TestRequest::get().uri(ADMIN_EVENTS_PATH).to_request();
+        let page: EventsPage = test::call_and_read_body_json(&$app, req).await;
+        page.events.last().unwrap().event.clone()
+    }};
+}
+
+fn receipt(resp: &actix_web::dev::ServiceResponse) -> PaymentReceipt {
+    PaymentReceipt::decode(
+        resp.headers()
+            .get(RECEIPT_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+/// Test an exact declared amount verifies without a delta
+#[actix_web::test]
+async fn test_exact_match() {
+    let app = tolerance_app!(AmountTolerance::default());
+
+    let resp = pay!(app, "0.050");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(receipt(&resp).amount_delta, None);
+    assert_eq!(last_event!(app).amount_delta, None);
+}
+
+/// Test a difference within the absolute tolerance is recorded
+#[actix_web::test]
+async fn test_within_absolute_tolerance() {
+    let app = tolerance_app!(tolerance(Some("0.0001"), None));
+
+    // When: Paying 0.00005 short
+    let resp = pay!(app, "0.04995");
+
+    // Then: Verified, with the delta in the receipt and the event
+    assert_eq!(resp.status(), StatusCode::OK);
+    let receipt = receipt(&resp);
+    assert_eq!(receipt.amount, "0.05");
+    assert_eq!(receipt.amount_delta.as_deref(), Some("-0.00005"));
+    let event = last_event!(app);
+    assert_eq!(event.event_type, EventType::PaymentVerified);
+    assert_eq!(event.amount_delta.as_deref(), Some("-0.00005"));
+}
+
+/// Test a difference within the percentage tolerance verifies
+#[actix_web::test]
+async fn test_within_percent_tolerance() {
+    // 2% of 0.05 is 0.001
+    let app = tolerance_app!(tolerance(None, Some("2")));
+
+    let resp = pay!(app, "0.0505");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(receipt(&resp).amount_delta.as_deref(), Some("0.0005"));
+}
+
+/// Test differences outside the tolerance fail with both amounts
+#[actix_web::test]
+async fn test_outside_tolerance() {
+    let app = tolerance_app!(tolerance(Some("0.001"), None));
+
+    // Then: Underpayment and overpayment get their own codes
+    let resp = pay!(app, "0.048");
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], UNDERPAYMENT);
+    assert_eq!(body["invoice_amount"], "0.05");
+    assert_eq!(body["paid_amount"], "0.048");
+    assert_eq!(body["tolerance"], "0.001");
+
+    let event = last_event!(app);
+    assert_eq!(event.event_type, EventType::PaymentFailed);
+    assert_eq!(event.outcome, UNDERPAYMENT);
+
+    let resp = pay!(app, "0.06");
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], OVERPAYMENT);
+    assert_eq!(body["paid_amount"], "0.06");
+
+    // And: Declared amounts must be USDC amounts
+    let resp = pay!(app, "0,05");
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Test a difference equal to the tolerance is accepted
+#[actix_web::test]
+async fn test_boundary_equality() {
+    let app = tolerance_app!(tolerance(Some("0.001"), Some("1")));
+
+    // The larger allowance, 0.001, is the boundary on both sides
+    assert_eq!(pay!(app, "0.049").status(), StatusCode::OK);
+    assert_eq!(pay!(app, "0.051").status(), StatusCode::OK);
+    assert_eq!(pay!(app, "0.048999").status(), StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(pay!(app, "0.051001").status(), StatusCode::PAYMENT_REQUIRED);
+}
+
+/// Test out-of-range tolerances are refused at startup
+#[actix_web::test]
+async fn test_invalid_tolerance_rejected() {
+    let err = Config::builder()
+        .amount_tolerance(tolerance(None, Some("150")))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("amount_tolerance.percent"));
+}
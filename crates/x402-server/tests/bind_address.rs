@@ -0,0 +1,149 @@
+// Bind Address Tests
+//
+// Serves the mock on 0.0.0.0 and checks the 402 flow and /health, and that
+// admin endpoints refuse non-loopback peers unless admin_loopback_only is
+// turned off (remote peers are simulated with TestRequest::peer_addr).
+
+mod common;
+
+use actix_web::{http::StatusCode, test, web, App};
+use common::free_port;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+use x402_server::handlers::{payment_required_handler, stats_handler};
+use x402_server::server::bind_listener;
+use x402_server::stats::ADMIN_STATS_PATH;
+use x402_server::{
+    Config, InvoiceGenerator, MockServer, MockServerConfig, PricingMatcher, StatsRegistry,
+    WebhookDispatcher,
+};
+
+const REMOTE_PEER: &str = "192.168.1.20:51000";
+
+macro_rules! admin_app {
+    ($config:expr) => {{
+        let config: Config = $config;
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(InvoiceGenerator::new()))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .app_data(web::Data::new(StatsRegistry::new()))
+                .service(web::resource(ADMIN_STATS_PATH).get(stats_handler))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+/// Test a wildcard listener serves the 402 flow and reports its address
+#[actix_web::test]
+async fn test_wildcard_bind_serves_payment_flow() {
+    // Given: The mock bound to all interfaces on a free port
+    let port = free_port();
+    let config = Config::builder()
+        .port(port)
+        .bind_address("0.0.0.0".parse().unwrap())
+        .build()
+        .unwrap();
+    let listener = bind_listener(&config).unwrap();
+    assert_eq!(
+        listener.local_addr().unwrap().ip(),
+        "0.0.0.0".parse::<IpAddr>().unwrap()
+    );
+    let server = MockServer::new(MockServerConfig::new(config).unwrap());
+    actix_web::rt::spawn(server.run_on(listener));
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let base = format!("http://127.0.0.1:{}", port);
+
+    // When: Requesting a resource without payment
+    let response = client
+        .get(format!("{}/api/data", base))
+        .send()
+        .await
+        .unwrap();
+
+    // Then: 402 as on loopback, and /health shows the wildcard address
+    assert_eq!(response.status().as_u16(), 402);
+    let health: serde_json::Value = client
+        .get(format!("{}/health", base))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(health["bind_address"], "0.0.0.0");
+    assert_eq!(health["port"], port);
+
+    // And: Admin endpoints still answer the loopback client
+    let stats = client
+        .get(format!("{}{}", base, ADMIN_STATS_PATH))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(stats.status().as_u16(), 200);
+}
+
+/// Test admin endpoints refuse remote peers while the payment flow serves them
+#[actix_web::test]
+async fn test_admin_rejects_remote_peer() {
+    // Given: A server config with the default admin_loopback_only
+    let app = admin_app!(Config::builder()
+        .bind_address("0.0.0.0".parse().unwrap())
+        .build()
+        .unwrap());
+    let remote: SocketAddr = REMOTE_PEER.parse().unwrap();
+
+    // When: A LAN peer calls the admin endpoint
+    let req = test::TestRequest::get()
+        .uri(ADMIN_STATS_PATH)
+        .peer_addr(remote)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: 403 with a fix hint
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Admin endpoint restricted");
+    assert!(body["fix"]
+        .as_str()
+        .unwrap()
+        .contains("admin_loopback_only"));
+
+    // And: The same peer gets invoices
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .peer_addr(remote)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+
+    // And: Loopback peers keep admin access
+    let req = test::TestRequest::get()
+        .uri(ADMIN_STATS_PATH)
+        .peer_addr("127.0.0.1:51000".parse().unwrap())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
+
+/// Test admin_loopback_only: false opens admin endpoints to the network
+#[actix_web::test]
+async fn test_admin_open_when_restriction_disabled() {
+    let app = admin_app!(Config::builder()
+        .bind_address("0.0.0.0".parse().unwrap())
+        .admin_loopback_only(false)
+        .build()
+        .unwrap());
+
+    let req = test::TestRequest::get()
+        .uri(ADMIN_STATS_PATH)
+        .peer_addr(REMOTE_PEER.parse().unwrap())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+}
@@ -0,0 +1,42 @@
+// Helpers shared by the integration tests
+//
+// Each test file is its own crate and compiles this module with `mod
+// common;`, so helpers a file does not call are dead code there.
+
+#![allow(dead_code)]
+
+use std::time::Duration;
+use x402_server::server::start_http_server;
+use x402_server::{Config, MockServerConfig};
+
+/// A port free on every interface, for servers bound to 0.0.0.0 too
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("0.0.0.0:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// Wait until the server on `port` accepts connections on localhost
+pub async fn wait_until_listening(port: u16) {
+    for _ in 0..50 {
+        if tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .is_ok()
+        {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("server on port {} did not start", port);
+}
+
+/// Start a mock server for `config` on the actix runtime and return its
+/// port once it listens
+pub async fn serve(config: Config) -> u16 {
+    let port = config.port.get();
+    actix_web::rt::spawn(start_http_server(MockServerConfig::new(config).unwrap()));
+    wait_until_listening(port).await;
+    port
+}
@@ -0,0 +1,234 @@
+// Config Builder Tests
+//
+// ConfigBuilder / MockServerConfigBuilder defaults, validation on build(),
+// and tolerance of config fields this version does not know about.
+
+use x402_server::{
+    Config, EventType, MockServerConfig, PortFallback, PricingConfig, SigningConfig,
+    SimulationMode, TlsConfig, WebhookConfig,
+};
+
+/// Test the builder starts from Config::default()
+#[test]
+fn test_builder_defaults_equal_config_default() {
+    let built = Config::builder().build().unwrap();
+    assert_eq!(built, Config::default());
+
+    assert_eq!(built.port, 3402);
+    assert_eq!(built.pricing, PricingConfig::default());
+    assert_eq!(built.simulation_mode, SimulationMode::Success);
+    assert!(built.tls.is_none());
+    assert!(built.webhooks.is_empty());
+}
+
+/// Test fluent setters land in the built config
+#[test]
+fn test_builder_setters() {
+    let config = Config::builder()
+        .port(4000)
+        .default_price(0.02)
+        .resource_price("/api/premium/*", 0.5)
+        .simulation_mode(SimulationMode::Timeout)
+        .timeout_delay_ms(250)
+        .tls(TlsConfig::self_signed())
+        .port_fallback(PortFallback::Auto)
+        .port_fallback_attempts(3)
+        .build()
+        .unwrap();
+
+    assert_eq!(config.port, 4000);
+    assert_eq!(config.pricing.default, 0.02);
+    assert_eq!(config.pricing.per_resource["/api/premium/*"], 0.5);
+    assert_eq!(config.simulation_mode, SimulationMode::Timeout);
+    assert_eq!(config.timeout_delay_ms, 250);
+    assert!(config.tls.unwrap().self_signed);
+    assert_eq!(
+        (config.port_fallback, config.port_fallback_attempts),
+        (PortFallback::Auto, 3)
+    );
+}
+
+/// Test invalid values fail at build() with the Config::validate messages
+#[test]
+fn test_builder_rejects_invalid_values() {
+    let cases = [
+        (Config::builder().port(80), "Invalid port: 80"),
+        (
+            Config::builder().solana_rpc("api.devnet.solana.com"),
+            "Invalid Solana RPC URL",
+        ),
+        (
+            Config::builder().default_price(-1.0),
+            "Default pricing must be non-negative",
+        ),
+        (
+            Config::builder().resource_price("/api/x", 500.0),
+            "Pricing for /api/x must be <= 100 SOL",
+        ),
+        (
+            Config::builder().timeout_delay_ms(10),
+            "Invalid timeout delay: 10 ms",
+        ),
+        (
+            Config::builder().port_fallback_attempts(0),
+            "Invalid port_fallback_attempts: 0",
+        ),
+    ];
+
+    for (builder, expected) in cases {
+        let err = builder.build().unwrap_err().to_string();
+        assert!(err.contains(expected), "{} not in {}", expected, err);
+        assert!(err.contains("Fix:"));
+    }
+
+    // A config file with the same port is rejected when it is loaded
+    let err = serde_yaml::from_str::<Config>("port: 80").unwrap_err();
+    assert!(err.to_string().contains("Invalid port"), "{}", err);
+
+    // A later valid port replaces a rejected one
+    assert!(Config::builder().port(80).port(4000).build().is_ok());
+}
+
+/// Test invalid combinations of otherwise valid settings
+#[test]
+fn test_builder_rejects_invalid_combinations() {
+    let tls_without_key = TlsConfig {
+        cert_path: Some("cert.pem".into()),
+        ..TlsConfig::default()
+    };
+    let err = Config::builder()
+        .tls(tls_without_key)
+        .build()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("TLS requires both cert_path and key_path"));
+
+    let err = Config::builder()
+        .webhook(WebhookConfig {
+            url: "localhost:9000/hooks".to_string(),
+            events: vec![EventType::InvoiceIssued],
+            secret: None,
+            max_retries: 3,
+        })
+        .build()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Invalid webhook URL"));
+
+    let err = MockServerConfig::builder()
+        .port(1)
+        .build()
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("Invalid port: 1"));
+}
+
+/// Test bounds must satisfy min_amount <= default <= max_amount
+#[test]
+fn test_bounds_validation() {
+    let cases = [
+        (
+            Config::builder().amount_bounds(Some(1.0), Some(0.001)),
+            "Pricing bounds are inverted: min_amount 1 > max_amount 0.001",
+        ),
+        (
+            Config::builder().amount_bounds(Some(0.05), None),
+            "Default pricing 0.01 is below min_amount 0.05",
+        ),
+        (
+            Config::builder().amount_bounds(None, Some(0.005)),
+            "Default pricing 0.01 is above max_amount 0.005",
+        ),
+        (
+            Config::builder().amount_bounds(Some(-1.0), None),
+            "Pricing min_amount must be non-negative",
+        ),
+    ];
+
+    for (builder, expected) in cases {
+        let err = builder.build().unwrap_err().to_string();
+        assert!(err.contains(expected), "{} not in {}", expected, err);
+        assert!(err.contains("Fix:"));
+    }
+
+    // Per-resource prices may fall outside; they are handled per request
+    assert!(Config::builder()
+        .resource_price("/api/dust", 0.0001)
+        .amount_bounds(Some(0.001), Some(1.0))
+        .build()
+        .is_ok());
+}
+
+/// Test MockServerConfig derives its port and pricing from the config
+#[test]
+fn test_mock_server_config_builder() {
+    let config = Config::builder()
+        .port(4100)
+        .resource_price("/api/data", 0.07)
+        .build()
+        .unwrap();
+    let server = MockServerConfig::new(config).unwrap();
+
+    assert_eq!(server.port, 4100);
+    assert_eq!(server.pricing_matcher.get_price_for_path("/api/data"), 0.07);
+    assert_eq!(server.pricing_matcher.get_price_for_path("/other"), 0.01);
+
+    let server = MockServerConfig::builder().port(4200).build().unwrap();
+    assert_eq!(server.port, 4200);
+    assert_eq!(server.config.port, 4200);
+}
+
+/// Test a config written by a newer version still loads and builds
+#[test]
+fn test_unknown_future_fields_are_ignored() {
+    let json = serde_json::json!({
+        "port": 4300,
+        "pricing": { "default": 0.03, "per_resource": {} },
+        "rate_limits": { "per_minute": 60 },
+        "future_flag": true
+    });
+
+    let config: Config = serde_json::from_value(json).unwrap();
+    let rebuilt = x402_server::ConfigBuilder::from(config.clone())
+        .build()
+        .unwrap();
+
+    assert_eq!(rebuilt, config);
+    assert_eq!(
+        rebuilt,
+        Config::builder()
+            .port(4300)
+            .default_price(0.03)
+            .build()
+            .unwrap()
+    );
+}
+
+/// Test signing settings parse in every build but need a key and the feature
+#[test]
+fn test_signing_validation() {
+    let config: Config =
+        serde_json::from_value(serde_json::json!({ "signing": { "ephemeral": true } })).unwrap();
+    assert_eq!(config.signing, Some(SigningConfig::ephemeral()));
+
+    let keyless = Config::builder()
+        .signing(SigningConfig::default())
+        .build()
+        .unwrap_err()
+        .to_string();
+    let ephemeral = Config::builder()
+        .signing(SigningConfig::ephemeral())
+        .build();
+
+    if cfg!(feature = "signing") {
+        assert!(keyless.contains("Invoice signing requires a key"));
+        assert!(ephemeral.is_ok());
+    } else {
+        assert!(keyless.contains("not available in this build"));
+        assert!(ephemeral
+            .unwrap_err()
+            .to_string()
+            .contains("--features signing"));
+    }
+}
@@ -0,0 +1,74 @@
+// Duration Unit Tests
+//
+// Policy windows may be written with a unit ("24h") instead of a bare
+// number of seconds. A simple-schema spending cap with `window_seconds: 24h`
+// is loaded and enforced by a time-travelling server.
+
+use x402_core::policy::runtime_policies_from_yaml;
+use x402_server::server::ADMIN_CLOCK_ADVANCE_PATH;
+use x402_server::{Config, MockServerConfig, TestServer};
+
+/// 0.02 USDC a day per agent; each invoice is 0.01
+const DAILY_CAP: &str = r#"
+policies:
+  - type: spending_cap
+    max_amount: 0.02
+    currency: USDC
+    window_seconds: 24h
+"#;
+
+async fn advance(server: &TestServer, seconds: u64) {
+    let resp = reqwest::Client::new()
+        .post(format!("{}{}", server.base_url(), ADMIN_CLOCK_ADVANCE_PATH))
+        .json(&serde_json::json!({ "seconds": seconds }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+async fn get_status(server: &TestServer) -> u16 {
+    reqwest::Client::new()
+        .get(format!("{}/api/data", server.base_url()))
+        .header("X-Agent-Id", "agent-1")
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+/// Test: a "24h" spending cap window is loaded and enforced
+#[tokio::test]
+async fn test_spending_cap_window_in_hours() {
+    // Given: A policy file capping spend over a "24h" window
+    let file = runtime_policies_from_yaml(DAILY_CAP).unwrap();
+    let window = file.policies[0].spending_cap.as_ref().unwrap().window;
+    assert_eq!(window, Some(std::time::Duration::from_secs(86_400)));
+
+    let server = TestServer::start(
+        MockServerConfig::new(
+            Config::builder()
+                .allow_time_travel(true)
+                .policies(file.policies)
+                .build()
+                .unwrap(),
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    // When: The agent is invoiced up to the cap
+    assert_eq!(get_status(&server).await, 402);
+    assert_eq!(get_status(&server).await, 402);
+
+    // Then: Further requests are denied
+    assert_eq!(get_status(&server).await, 403);
+
+    // And: Still denied well after a bare "24" seconds would have reset
+    advance(&server, 30 * 60).await;
+    assert_eq!(get_status(&server).await, 403);
+
+    server.stop().await.unwrap();
+}
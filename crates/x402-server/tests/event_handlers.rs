@@ -0,0 +1,227 @@
+// Event Handler Tests
+//
+// Handlers registered on a MockServerConfig are called inline for every
+// policy decision, issued invoice, verification outcome and error response
+// of the payment flow, in order and with the request they belong to. A
+// ChannelHandler delivers the same events as owned values on a channel.
+
+use std::sync::{Arc, Mutex};
+use x402_core::policy::{runtime_policies_from_yaml, PolicyDecision, Request};
+use x402_core::verifier::VerificationOutcome;
+use x402_server::{
+    ChannelHandler, Config, ErrorEnvelope, EventContext, EventHandler, Invoice, MaintenanceRule,
+    MockServerConfig, ServerEvent, TestServer,
+};
+
+/// Everyone is allowed; agent `blocked` is denied
+const POLICIES: &str = r#"
+policies:
+  - id: blocklist
+    agent_patterns: ["blocked"]
+    action: deny
+  - id: open
+    agent_patterns: ["*"]
+    action: allow
+"#;
+
+/// Records each call as `(request_id, summary)`
+#[derive(Default)]
+struct Recorder(Mutex<Vec<(String, String)>>);
+
+impl Recorder {
+    fn push(&self, context: &EventContext, summary: String) {
+        self.0
+            .lock()
+            .unwrap()
+            .push((context.request_id.clone(), summary));
+    }
+
+    fn take(&self) -> Vec<(String, String)> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl EventHandler for Recorder {
+    fn on_invoice_issued(&self, context: &EventContext, invoice: &Invoice) {
+        self.push(
+            context,
+            format!(
+                "invoice {} {} {}",
+                invoice.memo, invoice.amount, invoice.resource_path
+            ),
+        );
+    }
+
+    fn on_verification(&self, context: &EventContext, outcome: &VerificationOutcome) {
+        let summary = match outcome {
+            VerificationOutcome::Verified { memo, .. } => format!("verified {}", memo),
+            VerificationOutcome::Failed(_) => format!("failed {}", outcome.class()),
+        };
+        self.push(context, summary);
+    }
+
+    fn on_policy_decision(
+        &self,
+        context: &EventContext,
+        request: &Request,
+        decision: &PolicyDecision,
+    ) {
+        let summary = match decision {
+            PolicyDecision::Allow { policy_id, .. } => format!("allow {}", policy_id),
+            PolicyDecision::Deny { policy_id, .. } => format!("deny {}", policy_id),
+            _ => "other".to_string(),
+        };
+        self.push(context, format!("{} {}", request.agent_id, summary));
+    }
+
+    fn on_error(&self, context: &EventContext, error: &ErrorEnvelope) {
+        self.push(
+            context,
+            format!(
+                "error {} {}",
+                error.status,
+                error.code.as_deref().unwrap_or("-")
+            ),
+        );
+    }
+}
+
+async fn start(config: Config, handler: impl EventHandler + 'static) -> TestServer {
+    let server_config = MockServerConfig::builder()
+        .config(config)
+        .event_handler(handler)
+        .build()
+        .unwrap();
+    TestServer::start(server_config).await.unwrap()
+}
+
+async fn get(server: &TestServer, headers: &[(&str, &str)]) -> reqwest::Response {
+    let mut request = reqwest::Client::new().get(format!("{}/api/data", server.base_url()));
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    request.send().await.unwrap()
+}
+
+async fn invoice_memo(response: reqwest::Response) -> String {
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["invoice"]["memo"].as_str().unwrap().to_string()
+}
+
+fn summaries(events: &[(String, String)]) -> Vec<&str> {
+    events.iter().map(|(_, summary)| summary.as_str()).collect()
+}
+
+/// Test: the two-phase flow calls handlers in order with matching payloads
+#[tokio::test]
+async fn test_two_phase_flow_event_sequence() {
+    // Given: A server with policies and a recording handler
+    let recorder = Arc::new(Recorder::default());
+    let config = Config::builder()
+        .default_price(0.01)
+        .policies(runtime_policies_from_yaml(POLICIES).unwrap().policies)
+        .build()
+        .unwrap();
+    let server = start(config, recorder.clone()).await;
+
+    // When: An agent is invoiced, then pays the invoice
+    let agent = ("X-Agent-Id", "agent-1");
+    let memo = invoice_memo(get(&server, &[agent]).await).await;
+    let paid = get(&server, &[agent, ("X-Payment-Proof", &memo)]).await;
+    assert_eq!(paid.status(), 200);
+
+    // Then: Each request's policy decision comes before its invoice or verification
+    let events = recorder.take();
+    assert_eq!(
+        summaries(&events),
+        vec![
+            "agent-1 allow open".to_string(),
+            format!("invoice {} 0.01 /api/data", memo),
+            "agent-1 allow open".to_string(),
+            format!("verified {}", memo),
+        ]
+    );
+    // And: Events of one request share its ID, and the two requests differ
+    assert_eq!(events[0].0, events[1].0);
+    assert_eq!(events[2].0, events[3].0);
+    assert_ne!(events[0].0, events[2].0);
+
+    server.stop().await.unwrap();
+}
+
+/// Test: denials, failed payments and error responses reach the handler
+#[tokio::test]
+async fn test_denials_failures_and_errors() {
+    // Given: A server with policies, one path in maintenance and a recording handler
+    let recorder = Arc::new(Recorder::default());
+    let config = Config::builder()
+        .policies(runtime_policies_from_yaml(POLICIES).unwrap().policies)
+        .maintenance(MaintenanceRule::new("/api/down"))
+        .build()
+        .unwrap();
+    let server = start(config, recorder.clone()).await;
+
+    // When: A blocked agent calls, a payment is rejected, an amount is
+    // malformed and a path in maintenance is requested
+    let blocked = get(&server, &[("X-Agent-Id", "blocked")]).await;
+    assert_eq!(blocked.status(), 403);
+    let rejected = get(
+        &server,
+        &[
+            ("X-Payment-Proof", "proof-1"),
+            ("X-Simulation-Mode", "failure"),
+        ],
+    )
+    .await;
+    assert_eq!(rejected.status(), 402);
+    let malformed = get(
+        &server,
+        &[("X-Payment-Proof", "proof-2"), ("X-Payment-Amount", "lots")],
+    )
+    .await;
+    assert_eq!(malformed.status(), 400);
+    let down = reqwest::get(format!("{}/api/down", server.base_url()))
+        .await
+        .unwrap();
+    assert_eq!(down.status(), 503);
+
+    // Then: Each is reported; maintenance skips the policies
+    let events = recorder.take();
+    assert_eq!(
+        summaries(&events),
+        vec![
+            "blocked deny blocklist",
+            "anonymous allow open",
+            "failed rejected",
+            "anonymous allow open",
+            "error 400 -",
+            "error 503 maintenance",
+        ]
+    );
+
+    server.stop().await.unwrap();
+}
+
+/// Test: a ChannelHandler delivers owned events to another task
+#[tokio::test]
+async fn test_channel_handler_delivers_owned_events() {
+    // Given: A server with a channel handler
+    let (handler, mut receiver) = ChannelHandler::new(16);
+    let server = start(Config::default(), handler).await;
+
+    // When: A request is invoiced
+    let memo = invoice_memo(get(&server, &[]).await).await;
+
+    // Then: The invoice arrives with its request
+    match receiver.recv().await.unwrap() {
+        ServerEvent::InvoiceIssued { context, invoice } => {
+            assert_eq!(invoice.memo.to_string(), memo);
+            assert_eq!(context.method, "GET");
+            assert_eq!(context.path, "/api/data");
+            assert_eq!(context.proof, None);
+        }
+        other => panic!("unexpected event {:?}", other),
+    }
+
+    server.stop().await.unwrap();
+}
@@ -0,0 +1,165 @@
+// Free Resource Tests
+//
+// Paths priced at exactly 0 are served with 200 and no invoice, counted as
+// free hits in stats and still subject to policies. With
+// `zero_price_behavior: invoice` they get an amount=0 invoice instead.
+
+use actix_web::{http::StatusCode, test, web, App};
+use x402_core::policy::{PolicyAction, PolicyEngine, RuntimePolicy};
+use x402_core::receipt::RECEIPT_HEADER;
+use x402_server::handlers::{payment_required_handler, stats_handler};
+use x402_server::stats::ADMIN_STATS_PATH;
+use x402_server::{
+    Config, InvoiceGenerator, PricingMatcher, StatsRegistry, StatsSnapshot, WebhookDispatcher,
+    ZeroPriceBehavior,
+};
+
+fn policy(id: &str, agents: &[&str], action: PolicyAction, priority: u32) -> RuntimePolicy {
+    RuntimePolicy {
+        id: id.to_string(),
+        description: String::new(),
+        priority,
+        agent_patterns: agents.iter().map(|a| a.to_string()).collect(),
+        endpoint_patterns: vec![],
+        action,
+        rate_limit: None,
+        spending_cap: None,
+        concurrency_limit: None,
+    }
+}
+
+macro_rules! free_app {
+    ($builder:expr) => {{
+        let config = $builder.resource_price("/api/free/*", 0.0).build().unwrap();
+        let mut app = App::new()
+            .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+            .app_data(web::Data::new(InvoiceGenerator::new()))
+            .app_data(web::Data::new(WebhookDispatcher::disabled()))
+            .app_data(web::Data::new(StatsRegistry::new()));
+        if !config.policies.is_empty() {
+            app = app.app_data(web::Data::new(PolicyEngine::new(config.policies.clone())));
+        }
+        test::init_service(
+            app.app_data(web::Data::new(config))
+                .service(web::resource(ADMIN_STATS_PATH).get(stats_handler))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+/// Test a price of 0 is served directly and counted as a free hit
+#[actix_web::test]
+async fn test_free_path_served_without_invoice() {
+    // Given: A server with a free prefix and the default zero_price_behavior
+    let app = free_app!(Config::builder());
+
+    // When: Requesting a free path, with and without a payment proof
+    let req = test::TestRequest::get().uri("/api/free/news").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: 200 without invoice or receipt
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers().get("www-authenticate").is_none());
+    assert!(resp.headers().get(RECEIPT_HEADER).is_none());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "free");
+    assert_eq!(body["resource"], "Content for /api/free/news");
+
+    let req = test::TestRequest::get()
+        .uri("/api/free/news")
+        .insert_header(("X-Payment-Proof", "proof-1"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    // And: Priced paths are still invoiced
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+
+    // And: Stats count the free hits apart from verified payments
+    let req = test::TestRequest::get().uri(ADMIN_STATS_PATH).to_request();
+    let snapshot: StatsSnapshot = test::call_and_read_body_json(&app, req).await;
+    let free = snapshot
+        .paths
+        .iter()
+        .find(|p| p.pattern == "/api/free/*")
+        .unwrap();
+    assert_eq!((free.requests, free.free, free.verified), (2, 2, 0));
+    assert_eq!(free.invoices_issued, 0);
+    assert_eq!(snapshot.totals.free, 2);
+    assert_eq!(snapshot.totals.invoices_issued, 1);
+}
+
+/// Test zero_price_behavior: invoice keeps issuing amount=0 invoices
+#[actix_web::test]
+async fn test_zero_price_invoiced_when_configured() {
+    // Given: A free prefix with zero-amount invoices requested
+    let app = free_app!(Config::builder().zero_price_behavior(ZeroPriceBehavior::Invoice));
+
+    // When: Requesting a free path without payment
+    let req = test::TestRequest::get().uri("/api/free/news").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: 402 with an invoice for 0
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let header = resp
+        .headers()
+        .get("www-authenticate")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(header.contains("amount=0"), "{}", header);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["invoice"]["amount"], 0.0);
+
+    // And: Stats record an invoice, not a free hit
+    let req = test::TestRequest::get().uri(ADMIN_STATS_PATH).to_request();
+    let snapshot: StatsSnapshot = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        (snapshot.totals.invoices_issued, snapshot.totals.free),
+        (1, 0)
+    );
+}
+
+/// Test a deny policy still blocks a free path
+#[actix_web::test]
+async fn test_policy_deny_blocks_free_path() {
+    // Given: agent-bad denied, everyone else allowed
+    let app = free_app!(Config::builder().policies(vec![
+        policy(
+            "block-bad",
+            &["agent-bad"],
+            PolicyAction::Deny("Blocked agent".to_string()),
+            20
+        ),
+        policy("allow-all", &[], PolicyAction::Allow, 10),
+    ]));
+
+    // When: The denied agent requests a free path
+    let req = test::TestRequest::get()
+        .uri("/api/free/news")
+        .insert_header(("X-Agent-Id", "agent-bad"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: 403 from the policy, not a free 200
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["policy_id"], "block-bad");
+
+    // And: Other agents get the free resource
+    let req = test::TestRequest::get()
+        .uri("/api/free/news")
+        .insert_header(("X-Agent-Id", "agent-good"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get().uri(ADMIN_STATS_PATH).to_request();
+    let snapshot: StatsSnapshot = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(
+        (snapshot.totals.policy_denied, snapshot.totals.free),
+        (1, 1)
+    );
+}
@@ -0,0 +1,102 @@
+// HTTP/2 Integration Tests
+//
+// Runs test suites with the `http:` client options against the mock server
+// (h2c enabled) and against a bare server that counts accepted connections.
+
+mod common;
+
+use actix_web::{web, App, HttpResponse, HttpServer};
+use common::{free_port, serve};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use x402_core::testing::{execute_test_suite, format_json, generate_junit_xml, TestSuite};
+use x402_server::Config;
+
+fn suite(base_url: &str, http: &str, count: usize) -> TestSuite {
+    let tests: String = (0..count)
+        .map(|i| {
+            format!(
+                "  - name: \"request {i}\"\n    url: \"{base_url}/api/{i}\"\n    expect:\n      status: 402\n"
+            )
+        })
+        .collect();
+    <TestSuite as FromStr>::from_str(&format!("http:\n{}\ntests:\n{}", http, tests)).unwrap()
+}
+
+/// Test the negotiated protocol is recorded per test and in reports
+#[actix_web::test]
+async fn test_records_negotiated_protocol_against_h2c_mock() {
+    // Given: The mock server accepting cleartext HTTP/2
+    let port = free_port();
+    let config = Config::builder().port(port).http2(true).build().unwrap();
+    serve(config).await;
+    let base_url = format!("http://127.0.0.1:{}", port);
+
+    // When: The suite asks for h2
+    let result = execute_test_suite(&suite(&base_url, "  http_version: h2", 2))
+        .await
+        .unwrap();
+
+    // Then: Every test ran over HTTP/2 and the reports say so
+    assert_eq!(result.passed, 2, "{:?}", result.tests);
+    for test in &result.tests {
+        assert_eq!(test.http_version.as_deref(), Some("HTTP/2.0"));
+    }
+    let json: serde_json::Value = serde_json::from_str(&format_json(&result)).unwrap();
+    assert_eq!(json["tests"][0]["http_version"], "HTTP/2.0");
+    assert!(
+        generate_junit_xml(&result).contains(r#"<property name="http_version" value="HTTP/2.0"/>"#)
+    );
+
+    // And: auto on a cleartext URL stays on HTTP/1.1
+    let result = execute_test_suite(&suite(&base_url, "  http_version: auto", 1))
+        .await
+        .unwrap();
+    assert_eq!(result.tests[0].http_version.as_deref(), Some("HTTP/1.1"));
+}
+
+/// Test reuse_connections=false opens one connection per request
+#[actix_web::test]
+async fn test_connection_reuse_option() {
+    // Given: A server counting accepted connections
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = connections.clone();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let server = HttpServer::new(|| {
+        App::new().default_service(web::to(|| async {
+            HttpResponse::PaymentRequired().finish()
+        }))
+    })
+    .on_connect(move |_, _| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    })
+    .workers(1)
+    .listen(listener)
+    .unwrap()
+    .run();
+    actix_web::rt::spawn(server);
+    let base_url = format!("http://127.0.0.1:{}", port);
+
+    // When: Three requests run with reuse disabled
+    let result = execute_test_suite(&suite(
+        &base_url,
+        "  http_version: h1\n  reuse_connections: false",
+        3,
+    ))
+    .await
+    .unwrap();
+
+    // Then: Each request used its own connection
+    assert_eq!(result.passed, 3);
+    assert_eq!(connections.load(Ordering::SeqCst), 3);
+
+    // And: With reuse (the default) one connection serves all three
+    connections.store(0, Ordering::SeqCst);
+    let result = execute_test_suite(&suite(&base_url, "  max_idle_connections: 1", 3))
+        .await
+        .unwrap();
+    assert_eq!(result.passed, 3);
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,232 @@
+// HTTP Method Tests
+//
+// Runs the payment handler behind the CORS layer and checks HEAD answers
+// like GET without a body, plain OPTIONS and unsupported methods get an
+// Allow header, and method-qualified pricing rules override path rules.
+
+use actix_cors::Cors;
+use actix_web::dev::ServiceResponse;
+use actix_web::http::{Method, StatusCode};
+use actix_web::{test, web, App};
+use x402_core::testing::InvoiceHeader;
+use x402_server::handlers::payment_required_handler;
+use x402_server::{Config, InvoiceGenerator, PricingMatcher, WebhookDispatcher};
+
+macro_rules! methods_app {
+    () => {{
+        let config = Config::builder()
+            .default_price(0.01)
+            .resource_price("/api/data", 0.02)
+            .resource_price("POST /api/data", 0.05)
+            .resource_price("HEAD /api/free", 0.0)
+            .resource_price("/api/free", 0.03)
+            .allowed_methods("/api/data", ["GET", "POST"])
+            .allowed_methods("/api/admin/*", ["DELETE"])
+            .build()
+            .unwrap();
+        test::init_service(
+            App::new()
+                .wrap(
+                    Cors::default()
+                        .allow_any_origin()
+                        .allow_any_method()
+                        .allow_any_header(),
+                )
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(InvoiceGenerator::new()))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+macro_rules! call {
+    ($app:expr, $method:expr, $uri:expr) => {{
+        let req = test::TestRequest::default()
+            .method($method)
+            .uri($uri)
+            .to_request();
+        test::call_service(&$app, req).await
+    }};
+}
+
+fn invoice<B>(resp: &ServiceResponse<B>) -> InvoiceHeader {
+    let header = resp.headers().get("www-authenticate").unwrap();
+    InvoiceHeader::parse(header.to_str().unwrap()).unwrap()
+}
+
+/// Test HEAD gets the GET status and invoice header with an empty body
+#[actix_web::test]
+async fn test_head_matches_get_without_body() {
+    // Given: The payment app
+    let app = methods_app!();
+
+    // When: The same resource is requested with GET and HEAD
+    let get = call!(app, Method::GET, "/api/data");
+    let head = call!(app, Method::HEAD, "/api/data");
+
+    // Then: Both are 402 with the same invoice terms and header layout
+    assert_eq!(get.status(), StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(head.status(), StatusCode::PAYMENT_REQUIRED);
+    let (get_invoice, head_invoice) = (invoice(&get), invoice(&head));
+    let names = |h: &InvoiceHeader| h.fields.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(&get_invoice), names(&head_invoice));
+    for field in ["amount", "currency", "network"] {
+        assert_eq!(get_invoice.get(field), head_invoice.get(field), "{}", field);
+    }
+    assert_eq!(head_invoice.get("amount"), Some("0.02"));
+    assert_eq!(
+        head.headers().get("cache-control").unwrap(),
+        get.headers().get("cache-control").unwrap()
+    );
+
+    // And: Only GET carries a body
+    assert!(!test::read_body(get).await.is_empty());
+    assert!(test::read_body(head).await.is_empty());
+}
+
+/// Test unsupported methods get 405 with the path's Allow list
+#[actix_web::test]
+async fn test_unsupported_method_gets_405_with_allow() {
+    // Given: /api/data accepts GET and POST, /api/admin/* only DELETE
+    let app = methods_app!();
+
+    // When: PUT is sent to /api/data
+    let resp = call!(app, Method::PUT, "/api/data");
+
+    // Then: 405 listing GET (with implied HEAD), POST and OPTIONS
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(
+        resp.headers().get("allow").unwrap(),
+        "GET, HEAD, POST, OPTIONS"
+    );
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Method not allowed");
+    assert_eq!(body["message"], "PUT is not supported on /api/data");
+
+    // And: Prefix rules apply, and HEAD is not implied without GET
+    let resp = call!(app, Method::HEAD, "/api/admin/users");
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(resp.headers().get("allow").unwrap(), "DELETE, OPTIONS");
+    let resp = call!(app, Method::DELETE, "/api/admin/users");
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+
+    // And: Paths without a methods entry reject only unknown methods
+    let resp = call!(app, Method::PATCH, "/api/other");
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let resp = call!(app, Method::TRACE, "/api/other");
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(
+        resp.headers().get("allow").unwrap(),
+        "GET, HEAD, POST, PUT, PATCH, DELETE, OPTIONS"
+    );
+}
+
+/// Test plain OPTIONS answers with Allow and preflights go to the CORS layer
+#[actix_web::test]
+async fn test_options_and_cors_preflight() {
+    // Given: The payment app behind the CORS layer
+    let app = methods_app!();
+
+    // When: A plain OPTIONS request is sent
+    let resp = call!(app, Method::OPTIONS, "/api/data");
+
+    // Then: 204 with the Allow list and no invoice
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        resp.headers().get("allow").unwrap(),
+        "GET, HEAD, POST, OPTIONS"
+    );
+    assert!(resp.headers().get("www-authenticate").is_none());
+
+    // And: A CORS preflight is answered by the CORS layer
+    let req = test::TestRequest::default()
+        .method(Method::OPTIONS)
+        .uri("/api/data")
+        .insert_header(("Origin", "http://localhost:3000"))
+        .insert_header(("Access-Control-Request-Method", "POST"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers().contains_key("access-control-allow-methods"));
+    assert!(resp.headers().get("allow").is_none());
+}
+
+/// Test method-qualified pricing rules and HEAD falling back to GET pricing
+#[actix_web::test]
+async fn test_method_pricing_overrides() {
+    // Given: /api/data costs 0.02, POST /api/data 0.05, HEAD /api/free 0
+    let app = methods_app!();
+
+    for (method, uri, expected) in [
+        (Method::GET, "/api/data", "0.02"),
+        (Method::HEAD, "/api/data", "0.02"),
+        (Method::POST, "/api/data", "0.05"),
+        (Method::GET, "/api/free", "0.03"),
+    ] {
+        // When: Requesting without payment
+        let resp = call!(app, method.clone(), uri);
+
+        // Then: The invoice carries the method's price
+        assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+        assert_eq!(
+            invoice(&resp).get("amount"),
+            Some(expected),
+            "{} {}",
+            method,
+            uri
+        );
+    }
+
+    // And: The free HEAD probe is served without an invoice
+    let resp = call!(app, Method::HEAD, "/api/free");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers().get("www-authenticate").is_none());
+
+    // And: The matcher reports the rule that priced each request
+    let matcher = PricingMatcher::new(
+        Config::builder()
+            .resource_price("GET /api/*", 0.02)
+            .resource_price("/api/*", 0.04)
+            .build()
+            .unwrap()
+            .pricing,
+    );
+    assert_eq!(
+        matcher.rule_for_request("HEAD", "/api/items"),
+        Some("GET /api/*")
+    );
+    assert_eq!(
+        matcher.rule_for_request("PUT", "/api/items"),
+        Some("/api/*")
+    );
+    assert_eq!(matcher.get_price_for_request("PUT", "/api/items"), 0.04);
+}
+
+/// Test invalid method names are rejected in methods and pricing rules
+#[actix_web::test]
+async fn test_invalid_methods_rejected() {
+    let err = Config::builder()
+        .allowed_methods("/api/data", ["get"])
+        .build()
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Unsupported method get for /api/data"));
+
+    let err = Config::builder()
+        .allowed_methods("/api/data", Vec::<String>::new())
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("No methods listed for /api/data"));
+
+    let err = Config::builder()
+        .resource_price("FETCH /api/data", 0.01)
+        .build()
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Unsupported method in pricing rule 'FETCH /api/data'"));
+}
@@ -0,0 +1,178 @@
+// ID Format Tests
+//
+// `id_format` picks how the mock generates invoice memos and request IDs:
+// random uuidv4 (default), or time-ordered uuidv7 and ulid. Time-ordered
+// memos sort chronologically in `/__admin/invoices?sort=memo`, and
+// `id_seed` makes uuidv4 and ulid IDs repeatable.
+
+use std::sync::{Arc, Mutex};
+use x402_domain::validation::{validate_ulid_format, validate_uuid_format};
+use x402_domain::{IdFormat, InvoiceMemo};
+use x402_server::invoices::ADMIN_INVOICES_PATH;
+use x402_server::server::ADMIN_CLOCK_ADVANCE_PATH;
+use x402_server::{
+    Config, EventContext, EventHandler, Invoice, InvoicesPage, MockServerConfig, TestServer,
+};
+
+/// Records the request ID of every issued invoice
+#[derive(Default)]
+struct RequestIds(Mutex<Vec<String>>);
+
+impl EventHandler for RequestIds {
+    fn on_invoice_issued(&self, context: &EventContext, _invoice: &Invoice) {
+        self.0.lock().unwrap().push(context.request_id.clone());
+    }
+}
+
+async fn start(config: Config, handler: Arc<RequestIds>) -> TestServer {
+    let server_config = MockServerConfig::builder()
+        .config(config)
+        .event_handler(handler)
+        .build()
+        .unwrap();
+    TestServer::start(server_config).await.unwrap()
+}
+
+async fn issue(server: &TestServer, path: &str) -> String {
+    let response = reqwest::get(format!("{}{}", server.base_url(), path))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 402);
+    let body: serde_json::Value = response.json().await.unwrap();
+    body["invoice"]["memo"].as_str().unwrap().to_string()
+}
+
+fn assert_format(id: &str, format: IdFormat) {
+    match format {
+        IdFormat::Ulid => validate_ulid_format(id).unwrap(),
+        IdFormat::Uuidv4 | IdFormat::Uuidv7 => validate_uuid_format(id).unwrap(),
+    }
+    assert_eq!(IdFormat::detect(id), Some(format), "{}", id);
+}
+
+/// Test: memos and request IDs follow the configured format
+#[tokio::test]
+async fn test_generated_ids_follow_id_format() {
+    for format in IdFormat::ALL {
+        // Given: A server generating IDs in this format
+        let ids = Arc::new(RequestIds::default());
+        let config = Config::builder().id_format(format).build().unwrap();
+        let server = start(config, ids.clone()).await;
+
+        // When: Two invoices are issued
+        let memos = [
+            issue(&server, "/api/a").await,
+            issue(&server, "/api/b").await,
+        ];
+
+        // Then: Memos and request IDs are well-formed and distinct
+        for memo in &memos {
+            let parsed = InvoiceMemo::new(memo.as_str()).unwrap();
+            assert_eq!(parsed.prefix(), Some("req-"));
+            assert_eq!(parsed.format(), Some(format));
+            assert_format(parsed.uuid(), format);
+            // And: Only time-ordered formats carry a timestamp
+            assert_eq!(parsed.timestamp_ms().is_some(), format.is_time_ordered());
+        }
+        assert_ne!(memos[0], memos[1]);
+        let request_ids = ids.0.lock().unwrap().clone();
+        assert_eq!(request_ids.len(), 2);
+        for id in &request_ids {
+            assert_format(id, format);
+        }
+        assert_ne!(request_ids[0], request_ids[1]);
+
+        server.stop().await.unwrap();
+    }
+}
+
+/// Test: the invoice store sorts uuidv7 memos chronologically
+#[tokio::test]
+async fn test_uuidv7_memos_sort_chronologically() {
+    // Given: A time-travelling server issuing uuidv7 memos
+    let config = Config::builder()
+        .id_format(IdFormat::Uuidv7)
+        .allow_time_travel(true)
+        .build()
+        .unwrap();
+    let server = start(config, Arc::default()).await;
+    let client = reqwest::Client::new();
+
+    // When: Pairs of invoices are issued a minute apart
+    let mut issued = Vec::new();
+    for path in ["/api/c", "/api/a", "/api/b"] {
+        issued.push(issue(&server, path).await);
+        issued.push(issue(&server, path).await);
+        let advanced = client
+            .post(format!("{}{}", server.base_url(), ADMIN_CLOCK_ADVANCE_PATH))
+            .json(&serde_json::json!({ "seconds": 60 }))
+            .send()
+            .await
+            .unwrap();
+        assert!(advanced.status().is_success());
+    }
+
+    // Then: Sorted by memo they come back in issue order
+    let page: InvoicesPage = client
+        .get(format!(
+            "{}{}?sort=memo",
+            server.base_url(),
+            ADMIN_INVOICES_PATH
+        ))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let sorted: Vec<&str> = page.invoices.iter().map(|r| r.memo.as_str()).collect();
+    assert_eq!(sorted, issued);
+    // And: Their timestamps follow the virtual clock
+    let timestamps: Vec<u64> = issued
+        .iter()
+        .map(|memo| {
+            InvoiceMemo::new(memo.as_str())
+                .unwrap()
+                .timestamp_ms()
+                .unwrap()
+        })
+        .collect();
+    assert!(timestamps[2] - timestamps[0] >= 60_000);
+    assert!(timestamps[4] - timestamps[2] >= 60_000);
+
+    server.stop().await.unwrap();
+}
+
+/// Test: a seed makes IDs repeatable, except for uuidv7
+#[tokio::test]
+async fn test_seeded_ids_repeat() {
+    // Given: Two servers with the same seed
+    let seeded = || {
+        Config::builder()
+            .id_format(IdFormat::Uuidv4)
+            .id_seed(7)
+            .build()
+            .unwrap()
+    };
+    let first = start(seeded(), Arc::default()).await;
+    let second = start(seeded(), Arc::default()).await;
+
+    // When: Each issues an invoice
+    let memos = [
+        issue(&first, "/api/a").await,
+        issue(&second, "/api/a").await,
+    ];
+
+    // Then: The memos are equal
+    assert_eq!(memos[0], memos[1]);
+    // And: A seed is rejected for uuidv7
+    let err = Config::builder()
+        .id_format(IdFormat::Uuidv7)
+        .id_seed(7)
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("Fix:"));
+
+    first.stop().await.unwrap();
+    second.stop().await.unwrap();
+}
@@ -0,0 +1,127 @@
+// Invoice Expiry Tests
+//
+// Runs the payment handler with a global invoice TTL and per-path overrides
+// and checks the `expires` header field carries the matching deadline and
+// `ts` the issue time.
+
+use actix_web::{http::StatusCode, test, web, App};
+use chrono::{DateTime, Utc};
+use x402_core::testing::InvoiceHeader;
+use x402_server::handlers::payment_required_handler;
+use x402_server::{
+    Config, InvoiceGenerator, PricingMatcher, WebhookDispatcher, DEFAULT_INVOICE_TTL_SECONDS,
+};
+
+macro_rules! expiring_app {
+    ($config:expr) => {{
+        let config = $config;
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(InvoiceGenerator::new()))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+/// Request `uri` and return the header's `expires` deadline and the body's `expires_at`
+macro_rules! deadlines {
+    ($app:expr, $uri:expr) => {{
+        let req = test::TestRequest::get().uri($uri).to_request();
+        let resp = test::call_service(&$app, req).await;
+        assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+        let header = InvoiceHeader::parse(
+            resp.headers()
+                .get("www-authenticate")
+                .unwrap()
+                .to_str()
+                .unwrap(),
+        )
+        .unwrap();
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        let body_expires_at: DateTime<Utc> = body["invoice"]["expires_at"]
+            .as_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let expires = header.expires().unwrap().unwrap();
+        let issued_at = header.issued_at().unwrap().unwrap();
+        assert!(issued_at <= expires);
+        assert!((Utc::now() - issued_at).num_seconds() <= 1);
+        assert!(header.strict_checks().iter().all(|c| c.passed));
+        (expires, body_expires_at)
+    }};
+}
+
+/// Test a short per-path TTL is exposed in the header; other paths keep the default
+#[actix_web::test]
+async fn test_per_path_ttl_in_expires_field() {
+    // Given: A 2-second TTL for /api/fast/*
+    let app = expiring_app!(Config::builder()
+        .invoice_ttl("/api/fast/*", 2)
+        .build()
+        .unwrap());
+
+    // When: Requesting the short-lived path
+    let (expires, body_expires_at) = deadlines!(app, "/api/fast/quote");
+
+    // Then: The header deadline is ~2s away (truncated to whole seconds)
+    let remaining = (expires - Utc::now()).num_milliseconds();
+    assert!((0..=2000).contains(&remaining), "{}ms", remaining);
+    assert_eq!(expires.timestamp(), body_expires_at.timestamp());
+
+    // And: The default path keeps the 300s TTL
+    let (expires, _) = deadlines!(app, "/api/data");
+    let remaining = (expires - Utc::now()).num_seconds();
+    assert!((298..=300).contains(&remaining), "{}s", remaining);
+    assert_eq!(DEFAULT_INVOICE_TTL_SECONDS, 300);
+}
+
+/// Test the global TTL and the most specific override win
+#[actix_web::test]
+async fn test_global_ttl_and_override_priority() {
+    // Given: A 60s default, 3600s for /api/*, 10s for /api/admin/login
+    let app = expiring_app!(Config::builder()
+        .invoice_ttl_seconds(60)
+        .invoice_ttl("/api/*", 3600)
+        .invoice_ttl("/api/admin/login", 10)
+        .build()
+        .unwrap());
+
+    for (path, ttl) in [
+        ("/other", 60),
+        ("/api/report", 3600),
+        ("/api/admin/login", 10),
+    ] {
+        let (expires, _) = deadlines!(app, path);
+        let remaining = (expires - Utc::now()).num_seconds();
+        assert!(
+            (ttl - 2..=ttl).contains(&remaining),
+            "{}: {}s",
+            path,
+            remaining
+        );
+    }
+}
+
+/// Test out-of-range TTLs are rejected with a fix suggestion
+#[actix_web::test]
+async fn test_invalid_ttl_rejected() {
+    let err = Config::builder()
+        .invoice_ttl_seconds(0)
+        .build()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Invalid invoice TTL for invoice_ttl_seconds: 0 seconds"));
+    assert!(err.contains("Fix:"));
+
+    let err = Config::builder()
+        .invoice_ttl("/api/slow", 100_000)
+        .build()
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("for /api/slow: 100000 seconds"));
+}
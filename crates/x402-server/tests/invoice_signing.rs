@@ -0,0 +1,116 @@
+// Invoice Signing Tests (`signing` feature)
+//
+// Runs the payment handler with a signing key and verifies the signed
+// WWW-Authenticate header and JSON body with the shared verifier.
+
+#![cfg(feature = "signing")]
+
+use actix_web::{http::StatusCode, test, web, App};
+use x402_core::signing::{verify_invoice_signature, InvoiceSigner};
+use x402_core::testing::InvoiceHeader;
+use x402_domain::{Amount, ResourcePath};
+use x402_server::handlers::payment_required_handler;
+use x402_server::{Config, InvoiceGenerator, PricingMatcher, SigningConfig, WebhookDispatcher};
+
+macro_rules! signing_app {
+    ($signing:expr) => {{
+        let config = Config::builder()
+            .default_price(0.01)
+            .signing($signing)
+            .build()
+            .unwrap();
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(
+                    InvoiceGenerator::from_config(&config).unwrap(),
+                ))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+/// Test an ephemeral key signs both the header and the JSON invoice
+#[actix_web::test]
+async fn test_signed_invoice_verifies() {
+    // Given: A mock signing with an ephemeral key
+    let app = signing_app!(SigningConfig::ephemeral());
+
+    // When: Requesting a resource without payment
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: The header verifies and still passes strict mode
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let header = resp
+        .headers()
+        .get("www-authenticate")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let invoice = InvoiceHeader::parse(&header).unwrap();
+    let signer = verify_invoice_signature(&invoice, None).unwrap();
+    assert!(invoice.strict_checks().iter().all(|c| c.passed));
+
+    // And: The JSON body carries the same signer and signature
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["invoice"]["signer"], signer);
+    assert_eq!(
+        body["invoice"]["signature"],
+        invoice.get("signature").unwrap()
+    );
+}
+
+/// Test a keypair file fixes the signer, and tampering breaks verification
+#[actix_web::test]
+async fn test_keypair_file_and_tampering() {
+    // Given: A Solana CLI keypair file
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("signer.json");
+    let bytes: Vec<u8> = (1..=32).collect();
+    std::fs::write(&path, serde_json::to_string(&bytes).unwrap()).unwrap();
+    let expected = InvoiceSigner::from_keypair_bytes(&bytes)
+        .unwrap()
+        .public_key();
+    let app = signing_app!(SigningConfig::keypair(&path));
+
+    // When: Requesting a resource without payment
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+    let header = resp
+        .headers()
+        .get("www-authenticate")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    // Then: It is signed by the file's key
+    let invoice = InvoiceHeader::parse(&header).unwrap();
+    assert!(verify_invoice_signature(&invoice, Some(&expected)).is_ok());
+
+    // And: Changing the amount invalidates the signature
+    let tampered = InvoiceHeader::parse(&header.replace("amount=0.01", "amount=0.0001")).unwrap();
+    assert!(verify_invoice_signature(&tampered, Some(&expected)).is_err());
+}
+
+/// Test unsigned mocks keep emitting the plain canonical header
+#[actix_web::test]
+async fn test_unsigned_by_default() {
+    let generator = InvoiceGenerator::new();
+    assert!(generator.signer_public_key().is_none());
+
+    let invoice = generator.generate(
+        Amount::from_decimal_str("0.01").unwrap(),
+        ResourcePath::new("/api/data").unwrap(),
+        300,
+    );
+    assert_eq!(
+        invoice.format_www_authenticate(),
+        invoice.canonical_www_authenticate()
+    );
+}
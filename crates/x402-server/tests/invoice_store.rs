@@ -0,0 +1,291 @@
+// Invoice Store Tests
+//
+// Racing inserts, verifications and eviction sweeps against one
+// InvoiceStore, and the /__admin/invoices lookups of a running server as an
+// invoice is issued, paid and replayed.
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use x402_domain::{Amount, InvoiceMemo, MemoPrefix, ResourcePath, SolanaAddress};
+use x402_server::server::{ADMIN_CAPABILITIES, HEALTH_PATH, TEST_ADDRESSES};
+use x402_server::{
+    Config, Invoice, InvoiceQuery, InvoiceRecord, InvoiceStatus, InvoiceStore, InvoicesPage,
+    MockServerConfig, TestServer,
+};
+
+const THREADS: usize = 4;
+
+fn invoice(path: &str, expires_at: DateTime<Utc>) -> Invoice {
+    let mut invoice = Invoice::new(
+        Amount::from_f64_rounded(0.01).unwrap(),
+        ResourcePath::new(path).unwrap(),
+        SolanaAddress::new(TEST_ADDRESSES[0]).unwrap(),
+        InvoiceMemo::generate(&MemoPrefix::default()),
+        300,
+    );
+    invoice.expires_at = expires_at;
+    invoice
+}
+
+/// Test: each memo verifies exactly once however many threads race for it
+#[test]
+fn test_concurrent_verifications_pay_once() {
+    // Given: 4000 invoices inserted by four threads while four others verify
+    let store = Arc::new(InvoiceStore::default());
+    let now = Utc::now();
+    let invoices: Vec<Invoice> = (0..4000)
+        .map(|i| invoice(&format!("/api/{}", i % 7), now + Duration::seconds(300)))
+        .collect();
+    let memos: Arc<Vec<String>> = Arc::new(invoices.iter().map(|i| i.memo.to_string()).collect());
+    let paid = Arc::new(AtomicUsize::new(0));
+    let barrier = Arc::new(Barrier::new(THREADS * 2));
+
+    let mut handles = Vec::new();
+    for chunk in invoices.chunks(1000) {
+        let (store, barrier, chunk) = (store.clone(), barrier.clone(), chunk.to_vec());
+        handles.push(thread::spawn(move || {
+            barrier.wait();
+            for invoice in chunk {
+                store.insert(invoice, now);
+            }
+        }));
+    }
+    for _ in 0..THREADS {
+        let (store, barrier, memos, paid) =
+            (store.clone(), barrier.clone(), memos.clone(), paid.clone());
+        handles.push(thread::spawn(move || {
+            barrier.wait();
+            for memo in memos.iter() {
+                if store.record_verification(memo, "/api/x", now) {
+                    paid.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }));
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    // Then: One successful verification per memo, all of them verified
+    assert_eq!(paid.load(Ordering::Relaxed), memos.len());
+    assert_eq!(store.len(), memos.len());
+    let verified = store.find(&InvoiceQuery {
+        status: Some(InvoiceStatus::Verified),
+        limit: Some(usize::MAX),
+        ..Default::default()
+    });
+    assert_eq!(verified.len(), memos.len());
+    // And: Issuing after a racing verification kept the invoice
+    assert!(memos
+        .iter()
+        .all(|memo| store.get(memo).unwrap().invoice.is_some()));
+}
+
+/// Test: capacity holds while inserts, verifications and sweeps interleave
+#[test]
+fn test_concurrent_eviction_keeps_indices_consistent() {
+    // Given: A small store and threads issuing half-expired invoices
+    let store = Arc::new(InvoiceStore::new(500));
+    let now = Utc::now();
+    let barrier = Arc::new(Barrier::new(THREADS + 1));
+    let mut handles = Vec::new();
+    for t in 0..THREADS {
+        let (store, barrier) = (store.clone(), barrier.clone());
+        handles.push(thread::spawn(move || {
+            barrier.wait();
+            for i in 0..2000 {
+                let expires = if i % 2 == 0 {
+                    now - Duration::seconds(60)
+                } else {
+                    now + Duration::seconds(300)
+                };
+                let path = format!("/t{}", t);
+                let invoice = invoice(&path, expires);
+                let memo = invoice.memo.to_string();
+                store.insert(invoice, now);
+                if i % 3 == 0 {
+                    store.record_verification(&memo, &path, now);
+                }
+            }
+        }));
+    }
+    // And: A sweeper running alongside
+    let sweeper = {
+        let (store, barrier) = (store.clone(), barrier.clone());
+        thread::spawn(move || {
+            barrier.wait();
+            let mut swept = 0;
+            for _ in 0..200 {
+                swept += store.sweep_expired(now);
+                thread::yield_now();
+            }
+            swept
+        })
+    };
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    sweeper.join().unwrap();
+
+    // Then: The store is within capacity and every index agrees with it
+    let len = store.len();
+    assert!(len <= store.capacity(), "{} entries", len);
+    let all = |query: InvoiceQuery| {
+        store
+            .find(&InvoiceQuery {
+                limit: Some(usize::MAX),
+                ..query
+            })
+            .len()
+    };
+    assert_eq!(all(InvoiceQuery::default()), len);
+    let by_status = all(InvoiceQuery {
+        status: Some(InvoiceStatus::Issued),
+        ..Default::default()
+    }) + all(InvoiceQuery {
+        status: Some(InvoiceStatus::Verified),
+        ..Default::default()
+    });
+    assert_eq!(by_status, len);
+    let by_path: usize = (0..THREADS)
+        .map(|t| {
+            all(InvoiceQuery {
+                path: Some(format!("/t{}", t)),
+                ..Default::default()
+            })
+        })
+        .sum();
+    assert_eq!(by_path, len);
+    // And: Every insert is still stored or counted as evicted (a proof
+    // verified after its invoice was evicted adds an entry of its own)
+    assert!(len as u64 + store.evicted() >= (THREADS * 2000) as u64);
+}
+
+/// Test: /__admin/invoices follows an invoice from issued to verified
+#[tokio::test]
+async fn test_admin_lookup_through_payment() {
+    // Given: A running server
+    let server = TestServer::start(MockServerConfig::new(Config::default()).unwrap())
+        .await
+        .unwrap();
+    let client = reqwest::Client::new();
+    let url = |path: &str| format!("{}{}", server.base_url(), path);
+    let body: serde_json::Value = client
+        .get(url("/api/data"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let memo = body["invoice"]["memo"].as_str().unwrap().to_string();
+
+    // When: Looking the memo up before paying
+    let record: InvoiceRecord = client
+        .get(url(&format!("/__admin/invoices/{}", memo)))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Then: It is an issued invoice for the requested path
+    assert_eq!(record.status, InvoiceStatus::Issued);
+    assert_eq!(record.path, "/api/data");
+    assert_eq!(record.invoice.unwrap().memo.as_str(), memo);
+
+    // When: Paying, then replaying the proof
+    for expected in [200, 409] {
+        let status = client
+            .get(url("/api/data"))
+            .header("X-Payment-Proof", &memo)
+            .send()
+            .await
+            .unwrap()
+            .status();
+        assert_eq!(status.as_u16(), expected);
+    }
+
+    // Then: The memo is verified and listed under its status
+    let page: InvoicesPage = client
+        .get(url("/__admin/invoices?status=verified"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(page.invoices.len(), 1);
+    assert_eq!(page.invoices[0].memo, memo);
+    assert!(page.invoices[0].verified_at.is_some());
+    assert_eq!((page.len, page.evicted), (1, 0));
+
+    // And: Unknown memos are 404
+    let missing = client
+        .get(url("/__admin/invoices/req-unknown"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status().as_u16(), 404);
+
+    // And: /health advertises the endpoint
+    let health: serde_json::Value = client
+        .get(url(HEALTH_PATH))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(ADMIN_CAPABILITIES.contains(&"invoices"));
+    assert!(health["capabilities"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("invoices")));
+
+    server.stop().await.unwrap();
+}
+
+/// Test: max_invoices bounds what the server remembers
+#[tokio::test]
+async fn test_max_invoices_evicts_oldest() {
+    let config = Config::builder().max_invoices(2).build().unwrap();
+    let server = TestServer::start(MockServerConfig::new(config).unwrap())
+        .await
+        .unwrap();
+    let client = reqwest::Client::new();
+
+    let mut memos = Vec::new();
+    for _ in 0..3 {
+        let body: serde_json::Value = client
+            .get(format!("{}/api/data", server.base_url()))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        memos.push(body["invoice"]["memo"].as_str().unwrap().to_string());
+    }
+
+    let page: InvoicesPage = client
+        .get(format!("{}/__admin/invoices", server.base_url()))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let listed: Vec<&str> = page.invoices.iter().map(|r| r.memo.as_str()).collect();
+    assert_eq!(listed, [memos[1].as_str(), memos[2].as_str()]);
+    assert_eq!((page.capacity, page.evicted), (2, 1));
+
+    // max_invoices: 0 is refused up front
+    let err = Config::builder().max_invoices(0).build().unwrap_err();
+    assert!(err.to_string().contains("max_invoices"));
+
+    server.stop().await.unwrap();
+}
@@ -0,0 +1,187 @@
+// Maintenance Mode Tests
+//
+// Paths matching a maintenance rule answer 503 with Retry-After and the
+// `maintenance` error envelope instead of an invoice. Rules come from the
+// config or PUT /__admin/maintenance and are cleared with DELETE, all
+// without a restart. Stats and the event log tag the 503s separately.
+
+use x402_server::maintenance::ADMIN_MAINTENANCE_PATH;
+use x402_server::{Config, MaintenanceRule, MockServerConfig, TestServer};
+
+async fn start(config: Config) -> TestServer {
+    TestServer::start(MockServerConfig::new(config).unwrap())
+        .await
+        .unwrap()
+}
+
+fn admin_url(server: &TestServer) -> String {
+    format!("{}{}", server.base_url(), ADMIN_MAINTENANCE_PATH)
+}
+
+async fn get(server: &TestServer, path: &str, agent: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new().get(format!("{}{}", server.base_url(), path));
+    if let Some(agent) = agent {
+        request = request.header("X-Agent-Id", agent);
+    }
+    request.send().await.unwrap()
+}
+
+async fn status(server: &TestServer, path: &str) -> u16 {
+    get(server, path, None).await.status().as_u16()
+}
+
+/// Test: a path switched into maintenance answers 503 until cleared
+#[tokio::test]
+async fn test_admin_switches_maintenance_on_and_off() {
+    // Given: A running server
+    let server = start(Config::default()).await;
+    let client = reqwest::Client::new();
+
+    // When: /api/data/* goes into maintenance at runtime
+    let response = client
+        .put(admin_url(&server))
+        .json(&serde_json::json!({
+            "pattern": "/api/data/*",
+            "retry_after_secs": 120,
+            "message": "Reloading fixtures",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["rules"][0]["pattern"], "/api/data/*");
+
+    // Then: Matching paths get 503 with Retry-After and the error envelope
+    let response = get(&server, "/api/data/report", None).await;
+    assert_eq!(response.status(), 503);
+    assert_eq!(response.headers()["retry-after"], "120");
+    assert!(response.headers().get("www-authenticate").is_none());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "maintenance");
+    assert_eq!(body["message"], "Reloading fixtures");
+    assert_eq!(body["retry_after_secs"], 120);
+
+    // And: Other paths are still invoiced
+    assert_eq!(status(&server, "/api/other").await, 402);
+
+    // And: Stats and events tag the 503 as maintenance
+    let stats: serde_json::Value = reqwest::get(format!("{}/__admin/stats", server.base_url()))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(stats["totals"]["maintenance"], 1);
+    assert_eq!(stats["totals"]["invoices_issued"], 1);
+    let events: serde_json::Value = reqwest::get(format!("{}/__admin/events", server.base_url()))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let maintenance: Vec<&serde_json::Value> = events["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["type"] == "request.maintenance")
+        .collect();
+    assert_eq!(maintenance.len(), 1);
+    assert_eq!(maintenance[0]["path"], "/api/data/report");
+    assert_eq!(maintenance[0]["reason"], "Reloading fixtures");
+
+    // When: The rule is cleared
+    let response = client
+        .delete(format!("{}?pattern=/api/data/*", admin_url(&server)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+
+    // Then: The path recovers and clearing again finds nothing
+    assert_eq!(status(&server, "/api/data/report").await, 402);
+    let response = client
+        .delete(format!("{}?pattern=/api/data/*", admin_url(&server)))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+    server.stop().await.unwrap();
+}
+
+/// Test: configured rules apply from the start, per tenant when set
+#[tokio::test]
+async fn test_configured_rules_and_tenant_rules() {
+    // Given: Every path in maintenance for one tenant
+    let server = start(
+        Config::builder()
+            .maintenance(MaintenanceRule::new("/*").agent("tenant-a"))
+            .build()
+            .unwrap(),
+    )
+    .await;
+
+    // Then: Only that tenant gets 503, with the default Retry-After
+    let response = get(&server, "/api/data", Some("tenant-a")).await;
+    assert_eq!(response.status(), 503);
+    assert_eq!(response.headers()["retry-after"], "60");
+    assert_eq!(
+        get(&server, "/api/data", Some("tenant-b")).await.status(),
+        402
+    );
+    assert_eq!(status(&server, "/api/data").await, 402);
+
+    // And: A rule without a message logs its event without a reason
+    let events: serde_json::Value = reqwest::get(format!("{}/__admin/events", server.base_url()))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let maintenance = events["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["type"] == "request.maintenance")
+        .unwrap();
+    assert!(maintenance.get("reason").is_none());
+
+    // And: The rule is listed, and DELETE without a pattern clears it
+    let rules: serde_json::Value = reqwest::get(admin_url(&server))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(rules["rules"][0]["agent"], "tenant-a");
+    reqwest::Client::new()
+        .delete(admin_url(&server))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        get(&server, "/api/data", Some("tenant-a")).await.status(),
+        402
+    );
+    server.stop().await.unwrap();
+}
+
+/// Test: invalid rules are refused
+#[tokio::test]
+async fn test_invalid_rule_is_rejected() {
+    let server = start(Config::default()).await;
+    let response = reqwest::Client::new()
+        .put(admin_url(&server))
+        .json(&serde_json::json!({ "pattern": "api/*", "retry_after_secs": 10 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("Invalid maintenance pattern"));
+    assert_eq!(status(&server, "/api/data").await, 402);
+    server.stop().await.unwrap();
+}
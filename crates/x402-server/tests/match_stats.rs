@@ -0,0 +1,188 @@
+// Match Stats Tests
+//
+// Checks PricingMatcher::explain against the documented priority (method
+// rules, exact path, longest prefix, default) and that rule hit counters on
+// /__admin/stats add up when requests arrive concurrently.
+
+use std::collections::HashMap;
+use x402_core::policy::{PolicyAction, RuntimePolicy};
+use x402_server::{Config, MatchKind, MockServerConfig, PricingMatcher, TestServer};
+
+fn matcher() -> PricingMatcher {
+    PricingMatcher::new(
+        Config::builder()
+            .default_price(0.01)
+            .resource_price("/api/*", 0.02)
+            .resource_price("/api/premium/*", 0.05)
+            .resource_price("/api/premium/report", 0.10)
+            .resource_price("GET /api/premium/*", 0.07)
+            .amount_bounds(None, Some(0.08))
+            .build()
+            .unwrap()
+            .pricing,
+    )
+}
+
+/// Test: explain lists matching rules from highest to lowest priority
+#[test]
+fn test_explain_orders_candidates_by_priority() {
+    // Given: Overlapping exact, prefix and method rules
+    let matcher = matcher();
+
+    // When: Explaining a path matched by three path rules
+    let explanation = matcher.explain("/api/premium/report");
+
+    // Then: Exact beats the longer prefix, which beats the shorter one
+    let order: Vec<(&str, MatchKind, bool)> = explanation
+        .candidates
+        .iter()
+        .map(|c| (c.pattern.as_str(), c.kind, c.winner))
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            ("/api/premium/report", MatchKind::Exact, true),
+            ("/api/premium/*", MatchKind::Prefix, false),
+            ("/api/*", MatchKind::Prefix, false),
+            ("*", MatchKind::Default, false),
+        ]
+    );
+    assert_eq!(explanation.price, 0.10);
+    // And: max_amount clamps the invoice amount
+    assert_eq!(explanation.invoice_amount, Some(0.08));
+
+    // And: Unmatched paths fall back to the default price alone
+    let explanation = matcher.explain("/other");
+    assert_eq!(explanation.candidates.len(), 1);
+    assert!(explanation.candidates[0].winner);
+    assert_eq!(explanation.price, 0.01);
+}
+
+/// Test: method rules come first and HEAD falls back to GET rules
+#[test]
+fn test_explain_request_puts_method_rules_first() {
+    let matcher = matcher();
+
+    let explanation = matcher.explain_request("HEAD", "/api/premium/report");
+    let patterns: Vec<&str> = explanation
+        .candidates
+        .iter()
+        .map(|c| c.pattern.as_str())
+        .collect();
+    assert_eq!(
+        patterns,
+        vec![
+            "GET /api/premium/*",
+            "/api/premium/report",
+            "/api/premium/*",
+            "/api/*",
+            "*",
+        ]
+    );
+
+    // The winner is the rule the request is priced with
+    for (method, path) in [
+        ("GET", "/api/premium/x"),
+        ("POST", "/api/premium/report"),
+        ("HEAD", "/api/data"),
+        ("GET", "/nothing"),
+    ] {
+        let explanation = matcher.explain_request(method, path);
+        let winner = explanation.candidates.iter().find(|c| c.winner).unwrap();
+        assert_eq!(
+            winner.pattern,
+            matcher.rule_for_request(method, path).unwrap_or("*"),
+            "{} {}",
+            method,
+            path
+        );
+        assert_eq!(
+            explanation.price,
+            matcher.get_price_for_request(method, path)
+        );
+    }
+}
+
+/// Test: hit counters stay exact under concurrent requests
+#[tokio::test]
+async fn test_rule_hits_under_concurrent_requests() {
+    // Given: A server with match stats, a premium rule and an allowlist
+    let config = Config::builder()
+        .resource_price("/api/premium/*", 0.05)
+        .policies(vec![RuntimePolicy {
+            id: "allow-agents".to_string(),
+            description: String::new(),
+            priority: 1,
+            agent_patterns: vec!["agent-*".to_string()],
+            endpoint_patterns: vec![],
+            action: PolicyAction::Allow,
+            rate_limit: None,
+            spending_cap: None,
+            concurrency_limit: None,
+        }])
+        .match_stats(true)
+        .build()
+        .unwrap();
+    let server = TestServer::start(MockServerConfig::new(config).unwrap())
+        .await
+        .unwrap();
+
+    // When: 60 requests run at once, split over two paths and two agents
+    let client = reqwest::Client::new();
+    let tasks: Vec<_> = (0..60)
+        .map(|i| {
+            let path = if i % 3 == 0 {
+                "/api/premium/a"
+            } else {
+                "/api/data"
+            };
+            let agent = if i % 2 == 0 { "agent-1" } else { "bot-1" };
+            let request = client
+                .get(format!("{}{}", server.base_url(), path))
+                .header("X-Agent-Id", agent);
+            tokio::spawn(request.send())
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    // Then: Every request is counted once per rule set
+    let stats = server.stats().await.unwrap();
+    let rule_hits = stats.rule_hits.expect("match stats enabled");
+    let hits = |rules: &[x402_core::policy::RuleHits]| -> HashMap<String, u64> {
+        rules.iter().map(|r| (r.rule.clone(), r.hits)).collect()
+    };
+    assert_eq!(
+        hits(&rule_hits.pricing),
+        HashMap::from([("/api/premium/*".to_string(), 20), ("*".to_string(), 40)])
+    );
+    assert_eq!(
+        hits(&rule_hits.policies),
+        HashMap::from([
+            ("allow-agents".to_string(), 30),
+            ("default".to_string(), 30)
+        ])
+    );
+    assert!(rule_hits.pricing.iter().all(|r| r.last_matched.is_some()));
+
+    server.stop().await.unwrap();
+}
+
+/// Test: without match stats the snapshot has no rule_hits
+#[tokio::test]
+async fn test_rule_hits_absent_when_disabled() {
+    let server = TestServer::start(MockServerConfig::new(Config::default()).unwrap())
+        .await
+        .unwrap();
+    reqwest::get(format!("{}/api/data", server.base_url()))
+        .await
+        .unwrap();
+
+    let stats = server.stats().await.unwrap();
+    assert!(stats.rule_hits.is_none());
+    assert!(PricingMatcher::new(Config::default().pricing)
+        .match_stats()
+        .is_none());
+    server.stop().await.unwrap();
+}
@@ -308,3 +308,86 @@ async fn test_error_response_format() {
     assert!(json.get("message").is_some());
     assert!(json.get("help").is_some());
 }
+
+/// Test the real 402 handler passes its own cacheability check
+#[actix_web::test]
+async fn test_payment_required_is_not_cacheable() {
+    use x402_server::handlers::payment_required_handler;
+    use x402_server::{Config, InvoiceGenerator, PricingConfig, PricingMatcher, SimulationMode};
+
+    // Given: The mock server's payment handler with default pricing
+    let pricing = PricingConfig {
+        default: 0.01,
+        per_resource: std::collections::HashMap::new(),
+        ..PricingConfig::default()
+    };
+    let config = Config::builder()
+        .pricing(pricing.clone())
+        .simulation_mode(SimulationMode::Success)
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(PricingMatcher::new(pricing)))
+            .app_data(web::Data::new(InvoiceGenerator::new()))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(x402_server::WebhookDispatcher::disabled()))
+            .default_service(web::to(payment_required_handler)),
+    )
+    .await;
+
+    // When: Requesting a resource without payment proof
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: The 402 carries Cache-Control: no-store and passes the check
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let cache_control = resp
+        .headers()
+        .get("cache-control")
+        .expect("Cache-Control header missing")
+        .to_str()
+        .unwrap();
+    assert_eq!(cache_control, "no-store");
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("cache-control", cache_control.parse().unwrap());
+    assert!(x402_core::testing::check_cache_headers(&headers).passed);
+}
+
+/// Test the real 402 handler's WWW-Authenticate header passes strict mode
+#[actix_web::test]
+async fn test_payment_required_header_is_strict() {
+    use x402_core::testing::InvoiceHeader;
+    use x402_server::handlers::payment_required_handler;
+    use x402_server::{Config, InvoiceGenerator, PricingMatcher};
+
+    // Given: A price that is not representable exactly as f64
+    let config = Config::builder().default_price(0.1 + 0.2).build().unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+            .app_data(web::Data::new(InvoiceGenerator::new()))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(x402_server::WebhookDispatcher::disabled()))
+            .default_service(web::to(payment_required_handler)),
+    )
+    .await;
+
+    // When: Requesting a resource without payment proof
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: The header is canonical, with the amount rounded to 6 decimals
+    let header = resp
+        .headers()
+        .get("www-authenticate")
+        .expect("WWW-Authenticate header missing")
+        .to_str()
+        .unwrap();
+    let invoice = InvoiceHeader::parse(header).unwrap();
+    assert_eq!(invoice.get("amount"), Some("0.3"));
+    for check in invoice.strict_checks() {
+        assert!(check.passed, "{}: {}", check.name, check.detail);
+    }
+}
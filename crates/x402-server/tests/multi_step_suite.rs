@@ -0,0 +1,70 @@
+// Multi-step Test Suite Integration Tests
+//
+// Runs a `then:` test against the real mock server: the first request gets
+// the 402 invoice, the memo is extracted, and the follow-up request pays
+// with it as the payment proof.
+
+mod common;
+
+use common::{free_port, serve};
+use std::str::FromStr;
+use x402_core::testing::{execute_test_suite, TestSuite};
+use x402_server::{Config, SimulationMode};
+
+/// Test the 402 → pay → 200 flow with the memo carried between steps
+#[actix_web::test]
+async fn test_pays_with_extracted_memo() {
+    // Given: The mock server accepting every payment proof
+    let port = free_port();
+    let config = Config::builder()
+        .port(port)
+        .simulation_mode(SimulationMode::Success)
+        .build()
+        .unwrap();
+    serve(config).await;
+
+    // When: A suite extracts the memo from the header and the JSON body
+    let suite = <TestSuite as FromStr>::from_str(&format!(
+        r#"
+variables:
+  base_url: "http://127.0.0.1:{}"
+tests:
+  - name: "Pay for /api/data"
+    url: "${{base_url}}/api/data"
+    method: POST
+    body:
+      query: premium
+    expect:
+      status: 402
+    extract:
+      memo:
+        header: WWW-Authenticate
+        regex: 'memo=(\S+)'
+      body_memo:
+        json_path: $.invoice.memo
+    then:
+      name: pay
+      method: POST
+      headers:
+        X-Payment-Proof: "${{memo}}"
+      body: "proof for ${{body_memo}}"
+      expect:
+        status: 200
+        body_contains: "\"payment_proof\":\"${{memo}}\""
+"#,
+        port
+    ))
+    .unwrap();
+    let result = execute_test_suite(&suite).await.unwrap();
+
+    // Then: Both steps pass and the second response echoes the memo
+    let test = &result.tests[0];
+    assert!(test.passed, "{:#?}", test);
+    let memo = &test.assertions[1].actual;
+    assert_eq!(memo, &test.assertions[2].actual);
+    assert!(!memo.is_empty());
+    assert!(test
+        .assertions
+        .iter()
+        .any(|a| a.description == format!("pay: Body contains '\"payment_proof\":\"{}\"'", memo)));
+}
@@ -0,0 +1,144 @@
+// Paid Response Integration Tests
+//
+// Checks `responses:` bodies served after a verified payment: a gzipped JSON
+// body is decoded by the test executor before body assertions, and a binary
+// file arrives byte-identical (checked by SHA-256).
+
+mod common;
+
+use common::{free_port, serve};
+use std::str::FromStr;
+use x402_core::testing::{execute_test_suite, format_json, sha256_hex, TestSuite};
+use x402_server::{Config, ResponseEncoding, ResponseSpec};
+
+/// Test a gzipped JSON response is decoded before the body assertion
+#[actix_web::test]
+async fn test_gzipped_json_response() {
+    // Given: A large JSON body served gzip-encoded after payment
+    let items: Vec<String> = (0..200).map(|i| format!("{{\"id\":{}}}", i)).collect();
+    let body = format!("{{\"items\":[{}],\"complete\":true}}", items.join(","));
+    let port = serve(
+        Config::builder()
+            .port(free_port())
+            .response(
+                "/api/data",
+                ResponseSpec::from_body(body.clone()).encoding(ResponseEncoding::Gzip),
+            )
+            .build()
+            .unwrap(),
+    )
+    .await;
+
+    // When: A suite pays and asserts on the decoded body and the encoded size
+    let suite = <TestSuite as FromStr>::from_str(&format!(
+        r#"
+tests:
+  - name: "Paid gzip JSON"
+    url: "http://127.0.0.1:{}/api/data"
+    headers:
+      X-Payment-Proof: proof-1
+    expect:
+      status: 200
+      headers:
+        - name: Content-Encoding
+          value: gzip
+        - name: Content-Type
+          value: application/json
+      body_contains: '"complete":true'
+      body_size: {}
+      encoded_size_max: {}
+"#,
+        port,
+        body.len(),
+        body.len() / 2
+    ))
+    .unwrap();
+    let result = execute_test_suite(&suite).await.unwrap();
+
+    // Then: Every assertion passes and the wire size is reported
+    let test = &result.tests[0];
+    assert!(test.passed, "{:#?}", test.assertions);
+    let encoded = test.encoded_size.unwrap();
+    assert!(encoded < body.len() as u64 / 2, "{} bytes", encoded);
+    let json: serde_json::Value = serde_json::from_str(&format_json(&result)).unwrap();
+    assert_eq!(json["tests"][0]["encoded_size"], encoded);
+}
+
+/// Test a binary file response round-trips byte-identically
+#[actix_web::test]
+async fn test_binary_file_round_trip() {
+    // Given: Every byte value, served from a file with deflate
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("blob.bin");
+    let bytes: Vec<u8> = (0..4096).map(|i| (i * 7 % 256) as u8).collect();
+    std::fs::write(&file, &bytes).unwrap();
+    let port = serve(
+        Config::builder()
+            .port(free_port())
+            .response(
+                "/files/*",
+                ResponseSpec::from_file(&file).encoding(ResponseEncoding::Deflate),
+            )
+            .build()
+            .unwrap(),
+    )
+    .await;
+
+    // Each run pays with its own proof: a used proof is refused as a replay
+    let suite = |proof: &str, sha256: &str| {
+        <TestSuite as FromStr>::from_str(&format!(
+            r#"
+tests:
+  - name: "Paid binary"
+    url: "http://127.0.0.1:{}/files/blob.bin"
+    headers:
+      X-Payment-Proof: {}
+    expect:
+      status: 200
+      headers:
+        - name: Content-Type
+          value: application/octet-stream
+      body_sha256: "{}"
+      body_size: {}
+"#,
+            port,
+            proof,
+            sha256,
+            bytes.len()
+        ))
+        .unwrap()
+    };
+
+    // Then: The decoded body hashes to the file's SHA-256
+    let result = execute_test_suite(&suite("proof-1", &sha256_hex(&bytes)))
+        .await
+        .unwrap();
+    assert!(result.tests[0].passed, "{:#?}", result.tests[0].assertions);
+
+    // And: A different hash fails with the actual one reported
+    let result = execute_test_suite(&suite("proof-2", &sha256_hex(b"other")))
+        .await
+        .unwrap();
+    let failed = result.tests[0]
+        .assertions
+        .iter()
+        .find(|a| !a.passed)
+        .unwrap();
+    assert_eq!(failed.description, "Body SHA-256 matches");
+    assert_eq!(failed.actual, sha256_hex(&bytes));
+}
+
+/// Test startup validation rejects missing files
+#[test]
+fn test_missing_response_file_is_rejected() {
+    let err = Config::builder()
+        .response("/files/*", ResponseSpec::from_file("does/not/exist.bin"))
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Response file for /files/* not found: does/not/exist.bin"),
+        "{}",
+        err
+    );
+}
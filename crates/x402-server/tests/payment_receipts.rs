@@ -0,0 +1,151 @@
+// Payment Receipt Tests
+//
+// Pays through the handler with the invoice memo as proof and checks the
+// X-Payment-Receipt header and the receipt in the JSON acknowledgement;
+// with the `signing` feature, that receipts verify and tampering fails.
+
+use actix_web::{http::StatusCode, test, web, App};
+use x402_core::receipt::{PaymentReceipt, RECEIPT_HEADER};
+use x402_domain::SolanaAddress;
+use x402_server::handlers::payment_required_handler;
+use x402_server::{Config, InvoiceGenerator, PricingMatcher, ResponseSpec, WebhookDispatcher};
+
+macro_rules! receipt_app {
+    ($config:expr) => {{
+        let config = $config;
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(
+                    InvoiceGenerator::from_config(&config).unwrap(),
+                ))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+fn receipt_header(resp: &actix_web::dev::ServiceResponse) -> PaymentReceipt {
+    let value = resp
+        .headers()
+        .get(RECEIPT_HEADER)
+        .expect("receipt header")
+        .to_str()
+        .unwrap();
+    PaymentReceipt::decode(value).unwrap()
+}
+
+/// Test a verified payment returns a receipt matching the invoice
+#[actix_web::test]
+async fn test_success_returns_receipt() {
+    // Given: A mock with a fixed recipient
+    let recipient = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+    let app = receipt_app!(Config::builder()
+        .default_price(0.25)
+        .recipient(SolanaAddress::new(recipient).unwrap())
+        .build()
+        .unwrap());
+
+    // When: Taking the invoice memo and paying with it as proof
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let invoice: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let memo = invoice["invoice"]["memo"].as_str().unwrap();
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Payment-Proof", memo))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: The header receipt records the payment
+    assert_eq!(resp.status(), StatusCode::OK);
+    let receipt = receipt_header(&resp);
+    assert_eq!(receipt.memo, memo);
+    assert_eq!(receipt.amount, "0.25");
+    assert_eq!(receipt.recipient.as_deref(), Some(recipient));
+    assert_eq!(receipt.resource, "/api/data");
+    assert_eq!(receipt.simulation_mode, "success");
+    assert!(!receipt.is_signed());
+
+    // And: The JSON acknowledgement carries the same receipt
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["receipt"], serde_json::to_value(&receipt).unwrap());
+}
+
+/// Test receipts omit a recipient the mock cannot know, and ride along
+/// with configured response bodies without changing them
+#[actix_web::test]
+async fn test_receipt_with_rotating_recipient_and_custom_body() {
+    let app = receipt_app!(Config::builder()
+        .response("/api/data", ResponseSpec::from_body("paid content"))
+        .build()
+        .unwrap());
+
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Payment-Proof", "req-proof"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(receipt_header(&resp).recipient, None);
+    assert_eq!(test::read_body(resp).await, "paid content");
+}
+
+/// Test failed and timed-out verifications issue no receipt
+#[actix_web::test]
+async fn test_no_receipt_without_success() {
+    let app = receipt_app!(Config::builder().timeout_delay_ms(100).build().unwrap());
+
+    for mode in ["failure", "timeout"] {
+        let req = test::TestRequest::get()
+            .uri("/api/data")
+            .insert_header(("X-Payment-Proof", "req-proof"))
+            .insert_header(("X-Simulation-Mode", mode))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.headers().get(RECEIPT_HEADER).is_none(), "{}", mode);
+    }
+}
+
+#[cfg(feature = "signing")]
+mod signing {
+    use super::*;
+    use x402_core::testing::InvoiceHeader;
+    use x402_server::SigningConfig;
+
+    /// Test signed mocks sign receipts with the invoice key
+    #[actix_web::test]
+    async fn test_signed_receipt_verifies_and_tampering_fails() {
+        // Given: A mock signing with an ephemeral key
+        let app = receipt_app!(Config::builder()
+            .signing(SigningConfig::ephemeral())
+            .build()
+            .unwrap());
+        let req = test::TestRequest::get().uri("/api/data").to_request();
+        let resp = test::call_service(&app, req).await;
+        let header = resp.headers().get("www-authenticate").unwrap();
+        let invoice = InvoiceHeader::parse(header.to_str().unwrap()).unwrap();
+        let signer = invoice.get("signer").unwrap().to_string();
+
+        // When: Paying
+        let req = test::TestRequest::get()
+            .uri("/api/data")
+            .insert_header(("X-Payment-Proof", invoice.get("memo").unwrap()))
+            .to_request();
+        let receipt = receipt_header(&test::call_service(&app, req).await);
+
+        // Then: The receipt verifies under the invoice signer
+        assert_eq!(receipt.verify_signature(Some(&signer)).unwrap(), signer);
+
+        // And: A changed amount survives decoding but not verification
+        let mut tampered = receipt;
+        tampered.amount = "0.0001".to_string();
+        let decoded = PaymentReceipt::decode(&tampered.encode()).unwrap();
+        assert!(decoded
+            .verify_signature(None)
+            .unwrap_err()
+            .to_string()
+            .contains("does not match the receipt fields"));
+    }
+}
@@ -0,0 +1,121 @@
+// Policy Audit Log Tests
+//
+// The mock server and the policy sidecar attach the same audit log hook to
+// their engines. Decisions are written as JSONL: denials always, allows at
+// the configured sample rate, and the sidecar keeps logging after a reload.
+
+use actix_web::{test, web, App};
+use serde_json::json;
+use std::io::Write;
+use std::sync::Arc;
+use tempfile::NamedTempFile;
+use x402_core::policy::{
+    read_audit_entries, runtime_policies_from_yaml, AuditDecision, AuditLog, AuditLogConfig,
+};
+use x402_server::policy_server::configure;
+use x402_server::{Config, MockServerConfig, PolicySidecar, TestServer};
+
+/// One invoice per agent, then denials
+const ONE_REQUEST: &str = r#"
+policies:
+  - id: metered
+    agent_patterns: ["agent-*"]
+    action: allow
+    rate_limit:
+      max_requests: 1
+      window: 1m
+"#;
+
+async fn get_status(server: &TestServer) -> u16 {
+    reqwest::Client::new()
+        .get(format!("{}/api/data", server.base_url()))
+        .header("X-Agent-Id", "agent-1")
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+/// Test: with a sample rate of 0 the server logs only denials
+#[tokio::test]
+async fn test_server_logs_denials_with_quota() {
+    // Given: A server logging decisions with allows sampled out
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let server = TestServer::start(
+        MockServerConfig::new(
+            Config::builder()
+                .policies(runtime_policies_from_yaml(ONE_REQUEST).unwrap().policies)
+                .audit_log(AuditLogConfig::new(&path).sample_rate(0.0))
+                .build()
+                .unwrap(),
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    // When: The agent is allowed once, then denied twice
+    assert_eq!(get_status(&server).await, 402);
+    assert_eq!(get_status(&server).await, 403);
+    assert_eq!(get_status(&server).await, 403);
+    server.stop().await.unwrap();
+
+    // Then: Only the denials are in the log, with request IDs and quota
+    let entries = read_audit_entries(&path).unwrap();
+    assert_eq!(entries.len(), 2);
+    for entry in &entries {
+        assert_eq!(entry.decision, AuditDecision::Deny);
+        assert_eq!(entry.agent, "agent-1");
+        assert_eq!(entry.endpoint, "/api/data");
+        assert_eq!(entry.policy_id, "metered");
+        assert!(entry.request_id.is_some());
+        assert!(entry
+            .reason
+            .as_deref()
+            .unwrap()
+            .starts_with("Rate limit exceeded"));
+        assert_eq!(entry.quota.rate_limit.as_ref().unwrap().remaining, 0);
+    }
+    assert_ne!(entries[0].request_id, entries[1].request_id);
+}
+
+/// Test: the sidecar's hook stays attached across a reload
+#[actix_web::test]
+async fn test_sidecar_logs_after_reload() {
+    // Given: A sidecar logging every decision
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(ONE_REQUEST.as_bytes()).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let log = Arc::new(AuditLog::open(&AuditLogConfig::new(&path)).unwrap());
+    let sidecar = PolicySidecar::load(file.path())
+        .unwrap()
+        .with_decision_hook(log.clone());
+    let sidecar = web::Data::new(sidecar);
+    let app = test::init_service(App::new().app_data(sidecar.clone()).configure(configure)).await;
+    let evaluate = |agent: &str| {
+        test::TestRequest::post()
+            .uri("/evaluate")
+            .set_json(json!({
+                "agent_id": agent,
+                "endpoint": "/api/data",
+                "amount": 0.01,
+                "request_id": format!("req-{}", agent)
+            }))
+            .to_request()
+    };
+
+    // When: One agent is evaluated before a reload and another after
+    test::call_service(&app, evaluate("agent-1")).await;
+    sidecar.reload().unwrap();
+    test::call_service(&app, evaluate("agent-2")).await;
+
+    // Then: Both allows are logged with the caller's request IDs
+    log.flush();
+    let entries = read_audit_entries(&path).unwrap();
+    let ids: Vec<_> = entries.iter().map(|e| e.request_id.as_deref()).collect();
+    assert_eq!(ids, [Some("req-agent-1"), Some("req-agent-2")]);
+    assert!(entries.iter().all(|e| e.decision == AuditDecision::Allow));
+}
@@ -0,0 +1,363 @@
+// Policy Enforcement Tests
+//
+// Runs the payment handler with a PolicyEngine registered and checks that
+// denials short-circuit with 403, concurrency slots span the request,
+// spending is recorded against the issued invoice memo and allowed responses
+// report the remaining quota.
+
+use actix_web::{http::StatusCode, test, web, App};
+use std::time::Duration;
+use x402_core::policy::{
+    ConcurrencyLimitConfig, PolicyAction, PolicyEngine, QuotaHeaders, RateLimitConfig,
+    RuntimePolicy, SpendingCapConfig,
+};
+use x402_core::testing::InvoiceHeader;
+use x402_domain::{InvoiceMemo, MemoPrefix};
+use x402_server::handlers::payment_required_handler;
+use x402_server::{
+    Config, InvoiceGenerator, MockServerConfig, PricingMatcher, TestServer, WebhookDispatcher,
+};
+
+fn policy(id: &str, agents: &[&str], action: PolicyAction, priority: u32) -> RuntimePolicy {
+    RuntimePolicy {
+        id: id.to_string(),
+        description: String::new(),
+        priority,
+        agent_patterns: agents.iter().map(|a| a.to_string()).collect(),
+        endpoint_patterns: vec![],
+        action,
+        rate_limit: None,
+        spending_cap: None,
+        concurrency_limit: None,
+    }
+}
+
+macro_rules! enforcing_app {
+    ($policies:expr) => {{
+        let config = Config::builder()
+            .timeout_delay_ms(300)
+            .policies($policies)
+            .build()
+            .unwrap();
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(InvoiceGenerator::new()))
+                .app_data(web::Data::new(PolicyEngine::new(config.policies.clone())))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+/// Test a deny policy returns 403 before any invoice is issued
+#[actix_web::test]
+async fn test_denied_agent_gets_403() {
+    // Given: agent-bad denied, everyone else allowed
+    let app = enforcing_app!(vec![
+        policy(
+            "block-bad",
+            &["agent-bad"],
+            PolicyAction::Deny("Blocked agent".to_string()),
+            20
+        ),
+        policy("allow-all", &[], PolicyAction::Allow, 10),
+    ]);
+
+    // When: The denied agent requests a resource
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Agent-Id", "agent-bad"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: 403 with the policy reason and no invoice
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert!(resp.headers().get("www-authenticate").is_none());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["policy_id"], "block-bad");
+    assert_eq!(body["reason"], "Blocked agent");
+
+    // And: Other agents still get invoiced
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Agent-Id", "agent-good"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+}
+
+/// Test the concurrency slot is held for the whole (slow) request
+#[actix_web::test]
+async fn test_concurrency_slot_held_for_request_duration() {
+    // Given: One in-flight request per agent
+    let mut limited = policy("one-at-a-time", &[], PolicyAction::Allow, 10);
+    limited.concurrency_limit = Some(ConcurrencyLimitConfig { max_in_flight: 1 });
+    let app = enforcing_app!(vec![limited]);
+
+    let slow = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Agent-Id", "agent-1"))
+        .insert_header(("X-Payment-Proof", "proof-123"))
+        .insert_header(("X-Simulation-Mode", "timeout"))
+        .to_request();
+    let fast = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Agent-Id", "agent-1"))
+        .to_request();
+
+    // When: A second request arrives while the slow verification is running
+    let (slow_resp, fast_resp) = tokio::join!(test::call_service(&app, slow), async {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        test::call_service(&app, fast).await
+    });
+
+    // Then: The slow request completes and the concurrent one is rejected
+    assert_eq!(slow_resp.status(), StatusCode::REQUEST_TIMEOUT);
+    assert_eq!(fast_resp.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(fast_resp).await;
+    assert!(body["reason"]
+        .as_str()
+        .unwrap()
+        .contains("Concurrency limit exceeded"));
+
+    // And: The slot is free once the slow request finished
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Agent-Id", "agent-1"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+}
+
+/// Test custom memo prefixes reach the invoice and the policy spending record
+#[actix_web::test]
+async fn test_custom_memo_prefix_recorded_with_spending() {
+    // Given: A staging memo prefix and a spending cap for every agent
+    let mut capped = policy("spend-cap", &[], PolicyAction::Allow, 10);
+    capped.spending_cap = Some(SpendingCapConfig {
+        max_amount: 1.0,
+        currency: "USDC".to_string(),
+        window: Some(Duration::from_secs(3600)),
+        window_type: Default::default(),
+        timezone: None,
+    });
+    let config = Config::builder()
+        .memo_prefix(MemoPrefix::new("staging-").unwrap())
+        .policies(vec![capped])
+        .build()
+        .unwrap();
+    let engine = web::Data::new(PolicyEngine::new(config.policies.clone()));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+            .app_data(web::Data::new(
+                InvoiceGenerator::from_config(&config).unwrap(),
+            ))
+            .app_data(engine.clone())
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(WebhookDispatcher::disabled()))
+            .default_service(web::to(payment_required_handler)),
+    )
+    .await;
+
+    // When: An agent is invoiced
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Agent-Id", "agent-1"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: The header and body carry the same staging-<uuid> memo
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let header = resp.headers().get("www-authenticate").unwrap();
+    let invoice = InvoiceHeader::parse(header.to_str().unwrap()).unwrap();
+    let memo = InvoiceMemo::new(invoice.get("memo").unwrap()).unwrap();
+    assert_eq!(memo.prefix(), Some("staging-"));
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["invoice"]["memo"], memo.as_str());
+
+    // And: The spend is recorded under the prefix-stripped UUID
+    let records = engine
+        .store()
+        .spending_records("spend:spend-cap:agent-1")
+        .unwrap();
+    let memo_ids: Vec<Option<&str>> = records.iter().map(|r| r.memo_id.as_deref()).collect();
+    assert_eq!(memo_ids, [Some(memo.uuid())]);
+}
+
+/// Policy allowing everyone with a rate limit and a spending cap
+fn quota_policy() -> RuntimePolicy {
+    let mut policy = policy("metered", &[], PolicyAction::Allow, 10);
+    policy.rate_limit = Some(RateLimitConfig {
+        max_requests: 3,
+        window: Duration::from_secs(60),
+    });
+    policy.spending_cap = Some(SpendingCapConfig {
+        max_amount: 0.1,
+        currency: "USDC".to_string(),
+        window: Some(Duration::from_secs(3600)),
+        window_type: Default::default(),
+        timezone: None,
+    });
+    policy
+}
+
+fn header<'a>(resp: &'a actix_web::dev::ServiceResponse, name: &str) -> Option<&'a str> {
+    resp.headers().get(name).map(|v| v.to_str().unwrap())
+}
+
+/// Test allowed 402 and 200 responses carry the remaining quota
+#[actix_web::test]
+async fn test_allowed_responses_report_remaining_quota() {
+    // Given: 3 requests a minute and 0.1 USDC an hour at 0.01 per request
+    let app = enforcing_app!(vec![quota_policy()]);
+
+    // When: The agent is invoiced twice
+    let mut remaining = Vec::new();
+    for _ in 0..2 {
+        let req = test::TestRequest::get()
+            .uri("/api/data")
+            .insert_header(("X-Agent-Id", "agent-1"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+        assert_eq!(header(&resp, "X-RateLimit-Limit"), Some("3"));
+        assert_eq!(header(&resp, "X-RateLimit-Reset"), Some("60"));
+        assert_eq!(header(&resp, "X-Spend-Cap"), Some("0.1"));
+        remaining.push((
+            header(&resp, "X-RateLimit-Remaining").unwrap().to_string(),
+            header(&resp, "X-Spend-Remaining").unwrap().to_string(),
+        ));
+    }
+
+    // Then: Each response counts itself
+    assert_eq!(
+        remaining,
+        [
+            ("2".to_string(), "0.09".to_string()),
+            ("1".to_string(), "0.08".to_string())
+        ]
+    );
+
+    // And: The paid response reports the quota without counting itself
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Agent-Id", "agent-1"))
+        .insert_header(("X-Payment-Proof", "proof-1"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(header(&resp, "X-RateLimit-Remaining"), Some("1"));
+    assert_eq!(header(&resp, "X-Spend-Remaining"), Some("0.08"));
+
+    // And: The third invoice takes the last of the rate limit
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Agent-Id", "agent-1"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(header(&resp, "X-RateLimit-Remaining"), Some("0"));
+
+    // And: Denials don't report quota
+    let req = test::TestRequest::get()
+        .uri("/api/data")
+        .insert_header(("X-Agent-Id", "agent-1"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert_eq!(header(&resp, "X-RateLimit-Remaining"), None);
+}
+
+/// Test quota header names follow `quota_headers`
+#[actix_web::test]
+async fn test_quota_header_names_are_configurable() {
+    let config = Config::builder()
+        .policies(vec![quota_policy()])
+        .quota_headers(QuotaHeaders {
+            rate_limit_remaining: "RateLimit-Remaining".to_string(),
+            spend_remaining: "X-Budget-Left".to_string(),
+            ..QuotaHeaders::default()
+        })
+        .build()
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+            .app_data(web::Data::new(InvoiceGenerator::new()))
+            .app_data(web::Data::new(PolicyEngine::new(config.policies.clone())))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(WebhookDispatcher::disabled()))
+            .default_service(web::to(payment_required_handler)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(header(&resp, "RateLimit-Remaining"), Some("2"));
+    assert_eq!(header(&resp, "X-Budget-Left"), Some("0.09"));
+    assert_eq!(header(&resp, "X-RateLimit-Remaining"), None);
+
+    // Names must be valid header names
+    let err = Config::builder()
+        .quota_headers(QuotaHeaders {
+            spend_cap: "X Spend Cap".to_string(),
+            ..QuotaHeaders::default()
+        })
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("Invalid quota_headers name"));
+}
+
+/// Test full invoice → proof flows spend the price once each
+#[tokio::test]
+async fn test_paid_flows_count_once_against_spending_cap() {
+    // Given: A server capping spend at 4 invoices of 0.01
+    let mut capped = policy("capped", &[], PolicyAction::Allow, 10);
+    capped.spending_cap = Some(SpendingCapConfig {
+        max_amount: 0.04,
+        currency: "USDC".to_string(),
+        window: Some(Duration::from_secs(3600)),
+        window_type: Default::default(),
+        timezone: None,
+    });
+    let config = Config::builder()
+        .default_price(0.01)
+        .policies(vec![capped])
+        .build()
+        .unwrap();
+    let server = TestServer::start(MockServerConfig::new(config).unwrap())
+        .await
+        .unwrap();
+    let url = format!("{}/api/data", server.base_url());
+    let client = reqwest::Client::new();
+
+    // When: The agent pays for 4 invoices with their memos
+    for _ in 0..4 {
+        let invoice = client.get(&url).send().await.unwrap();
+        assert_eq!(invoice.status(), 402);
+        let body: serde_json::Value = invoice.json().await.unwrap();
+        let memo = body["invoice"]["memo"].as_str().unwrap().to_string();
+        let paid = client
+            .get(&url)
+            .header("X-Payment-Proof", memo)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(paid.status(), 200);
+    }
+
+    // Then: The cap is spent exactly, so a fifth invoice is refused
+    let denied = client.get(&url).send().await.unwrap();
+    assert_eq!(denied.status(), 403);
+    let body: serde_json::Value = denied.json().await.unwrap();
+    assert!(body["reason"]
+        .as_str()
+        .unwrap()
+        .starts_with("Spending cap exceeded"));
+
+    server.stop().await.unwrap();
+}
@@ -0,0 +1,262 @@
+// Policy Sidecar Tests
+//
+// Runs the `x402-dev policy serve` routes against a policy file on disk and
+// checks decisions, quota accumulation across calls, that a rejected
+// reload keeps the previous policies, and that SQLite state outlives the
+// sidecar.
+
+use actix_web::{http::StatusCode, test, web, App};
+use serde_json::json;
+use std::io::Write;
+use tempfile::{NamedTempFile, TempDir};
+use x402_core::policy::{StateBackend, StateStoreConfig};
+use x402_server::policy_server::{configure, Decision, EvaluateResponse};
+use x402_server::PolicySidecar;
+
+const RATE_LIMITED: &str = r#"
+policies:
+  - id: block-bad
+    priority: 20
+    agent_patterns: ["agent-bad"]
+    action:
+      deny: Blocked agent
+  - id: metered
+    priority: 10
+    agent_patterns: ["agent-*"]
+    action: allow
+    rate_limit:
+      max_requests: 3
+      window: 1m
+"#;
+
+fn policy_file(yaml: &str) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(yaml.as_bytes()).unwrap();
+    file
+}
+
+macro_rules! sidecar_app {
+    ($file:expr) => {
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PolicySidecar::load($file.path()).unwrap()))
+                .configure(configure),
+        )
+        .await
+    };
+}
+
+macro_rules! evaluate {
+    ($app:expr, $agent:expr) => {{
+        let req = test::TestRequest::post()
+            .uri("/evaluate")
+            .set_json(json!({"agent_id": $agent, "endpoint": "/api/data", "amount": 0.01}))
+            .to_request();
+        let resp = test::call_service(&$app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: EvaluateResponse = test::read_body_json(resp).await;
+        body
+    }};
+}
+
+/// Test allow and deny decisions with the matching policy and reason
+#[actix_web::test]
+async fn test_evaluate_returns_decision() {
+    // Given: A sidecar with a deny rule for agent-bad
+    let file = policy_file(RATE_LIMITED);
+    let app = sidecar_app!(file);
+
+    // When: A denied agent is evaluated
+    let req = test::TestRequest::post()
+        .uri("/evaluate")
+        .set_json(json!({
+            "agent_id": "agent-bad",
+            "endpoint": "/api/data",
+            "amount": 0.05,
+            "wallet_address": "So1anaWa11et"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: 200 with the deny decision in the envelope fields
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["decision"], "deny");
+    assert_eq!(body["policy_id"], "block-bad");
+    assert_eq!(body["reason"], "Blocked agent");
+    assert_eq!(body["agent_id"], "agent-bad");
+    assert_eq!(body["quota"], json!({}));
+
+    // And: Unmatched agents fall through to the default deny
+    let body = evaluate!(app, "other-agent");
+    assert_eq!(body.policy_id, "default");
+    assert_eq!(body.reason.as_deref(), Some("No matching allow policy"));
+
+    // And: Malformed requests get the error envelope
+    let req = test::TestRequest::post()
+        .uri("/evaluate")
+        .set_json(json!({"agent_id": "agent-1"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Invalid evaluate request");
+    assert!(body["reason"].as_str().unwrap().contains("endpoint"));
+}
+
+/// Test rate limit quota accumulates across calls until the limit denies
+#[actix_web::test]
+async fn test_rate_limit_accumulates_across_calls() {
+    // Given: 3 requests per minute for agent-* agents
+    let file = policy_file(RATE_LIMITED);
+    let app = sidecar_app!(file);
+
+    // When: The same agent is evaluated three times
+    for remaining in [2, 1, 0] {
+        let body = evaluate!(app, "agent-1");
+
+        // Then: Each call is allowed and uses up one request
+        assert_eq!(body.decision, Decision::Allow);
+        assert_eq!(body.policy_id, "metered");
+        let rate = body.quota.rate_limit.unwrap();
+        assert_eq!(rate.max_requests, 3);
+        assert_eq!(rate.remaining, remaining);
+        assert_eq!(rate.window_seconds, 60);
+    }
+
+    // And: The fourth call is denied by the rate limit
+    let body = evaluate!(app, "agent-1");
+    assert_eq!(body.decision, Decision::Deny);
+    assert_eq!(body.policy_id, "metered");
+    assert_eq!(
+        body.reason.as_deref(),
+        Some("Rate limit exceeded: 3 requests per 60s")
+    );
+    assert_eq!(body.quota.rate_limit.unwrap().remaining, 0);
+
+    // And: Other agents have their own window
+    let body = evaluate!(app, "agent-2");
+    assert_eq!(body.quota.rate_limit.unwrap().remaining, 2);
+}
+
+/// Test an invalid file is rejected on reload and the old policies stay loaded
+#[actix_web::test]
+async fn test_reload_rejects_invalid_file() {
+    // Given: A running sidecar that has counted one request
+    let file = policy_file(RATE_LIMITED);
+    let app = sidecar_app!(file);
+    evaluate!(app, "agent-1");
+
+    // When: The file is broken and a reload is requested
+    std::fs::write(file.path(), "policies:\n  - id: [unterminated\n").unwrap();
+    let req = test::TestRequest::post().uri("/reload").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: 422 with the error envelope
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Reload rejected");
+    assert!(body["reason"]
+        .as_str()
+        .unwrap()
+        .contains("Invalid policy file"));
+
+    // And: The old policies and their counters are still in use
+    let req = test::TestRequest::get().uri("/policies").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<&str> = body["policies"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["block-bad", "metered"]);
+    let body = evaluate!(app, "agent-1");
+    assert_eq!(body.quota.rate_limit.unwrap().remaining, 1);
+}
+
+/// Test a valid file is swapped in on reload
+#[actix_web::test]
+async fn test_reload_swaps_policies() {
+    // Given: A running sidecar
+    let file = policy_file(RATE_LIMITED);
+    let app = sidecar_app!(file);
+
+    // When: The file now allows everyone and a reload is requested
+    std::fs::write(
+        file.path(),
+        "policies:\n  - id: allow-all\n    action: allow\n",
+    )
+    .unwrap();
+    let req = test::TestRequest::post().uri("/reload").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+    // Then: The new policy answers evaluations
+    assert_eq!(body["status"], "reloaded");
+    assert_eq!(body["policies"], 1);
+    let body = evaluate!(app, "agent-bad");
+    assert_eq!(body.policy_id, "allow-all");
+    assert!(body.reason.is_none());
+
+    // And: Other methods on the endpoints are rejected
+    let req = test::TestRequest::get().uri("/reload").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+/// Test duplicate policy IDs fail validation
+#[actix_web::test]
+async fn test_duplicate_policy_ids_rejected() {
+    let file =
+        policy_file("policies:\n  - id: same\n    action: allow\n  - id: same\n    action: deny\n");
+
+    let err = PolicySidecar::load(file.path()).err().unwrap();
+
+    assert!(err
+        .to_string()
+        .contains("defines policy 'same' more than once"));
+}
+
+/// Test SQLite state carries quota over to a restarted sidecar
+#[actix_web::test]
+async fn test_sqlite_state_survives_restart() {
+    // Given: A sidecar keeping state in a SQLite file
+    let file = policy_file(RATE_LIMITED);
+    let dir = TempDir::new().unwrap();
+    let state = StateStoreConfig {
+        backend: StateBackend::Sqlite,
+        path: Some(dir.path().join("policy-state.db")),
+    };
+    {
+        let sidecar = PolicySidecar::with_state(file.path(), &state).unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(sidecar))
+                .configure(configure),
+        )
+        .await;
+        evaluate!(app, "agent-1");
+        evaluate!(app, "agent-1");
+    }
+
+    // When: A new sidecar opens the same file
+    let sidecar = PolicySidecar::with_state(file.path(), &state).unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(sidecar))
+            .configure(configure),
+    )
+    .await;
+
+    // Then: The earlier requests still count
+    let body = evaluate!(app, "agent-1");
+    assert_eq!(body.quota.rate_limit.unwrap().remaining, 0);
+    let body = evaluate!(app, "agent-1");
+    assert_eq!(body.decision, Decision::Deny);
+
+    // And: /policies reports the backend and its entries
+    let req = test::TestRequest::get().uri("/policies").to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["state"]["backend"], "sqlite");
+    assert_eq!(body["state"]["requests"], 3);
+}
@@ -0,0 +1,82 @@
+// Pricing Bounds Tests
+//
+// Runs the payment handler with min_amount/max_amount configured and checks
+// out-of-range prices are clamped or refused with a 500 envelope.
+
+use actix_web::{http::StatusCode, test, web, App};
+use x402_server::handlers::payment_required_handler;
+use x402_server::{Config, InvoiceGenerator, OutOfBounds, PricingMatcher, WebhookDispatcher};
+
+macro_rules! bounded_app {
+    ($out_of_bounds:expr) => {{
+        let config = Config::builder()
+            .default_price(0.01)
+            .resource_price("/api/dust", 0.0001)
+            .resource_price("/api/premium", 5.0)
+            .amount_bounds(Some(0.001), Some(1.0))
+            .out_of_bounds($out_of_bounds)
+            .build()
+            .unwrap();
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(InvoiceGenerator::new()))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+/// Test out-of-range prices are invoiced at the nearest bound
+#[actix_web::test]
+async fn test_clamp_invoices_nearest_bound() {
+    // Given: Bounds 0.001..=1 with clamping
+    let app = bounded_app!(OutOfBounds::Clamp);
+
+    for (path, expected) in [
+        ("/api/dust", 0.001),
+        ("/api/premium", 1.0),
+        ("/api/other", 0.01),
+    ] {
+        // When: Requesting each resource without payment
+        let req = test::TestRequest::get().uri(path).to_request();
+        let resp = test::call_service(&app, req).await;
+
+        // Then: The invoice carries the clamped amount
+        assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["invoice"]["amount"], expected, "{}", path);
+    }
+}
+
+/// Test out_of_bounds: error answers 500 instead of issuing a bad invoice
+#[actix_web::test]
+async fn test_error_refuses_to_invoice() {
+    // Given: Bounds 0.001..=1 with errors
+    let app = bounded_app!(OutOfBounds::Error);
+
+    // When: Requesting a resource priced below min_amount
+    let req = test::TestRequest::get().uri("/api/dust").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: 500 with an envelope explaining the misconfiguration
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(resp.headers().get("www-authenticate").is_none());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Pricing misconfigured");
+    assert_eq!(body["path"], "/api/dust");
+    assert_eq!(body["amount"], 0.0001);
+    assert_eq!(body["min_amount"], 0.001);
+    assert_eq!(body["max_amount"], 1.0);
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("below min_amount 0.001"));
+
+    // And: In-range prices are still invoiced
+    let req = test::TestRequest::get().uri("/api/other").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+}
@@ -0,0 +1,84 @@
+// Protocol Identifier Tests
+//
+// A server configured with a fork of the auth scheme (`protocol_id`) must
+// issue headers the shared parser accepts for that identifier only.
+
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use x402_core::testing::InvoiceHeader;
+use x402_domain::ProtocolId;
+use x402_server::handlers::payment_required_handler;
+use x402_server::{Config, InvoiceGenerator, PricingMatcher, WebhookDispatcher};
+
+macro_rules! app {
+    ($config:expr) => {{
+        let config: Config = $config;
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(
+                    InvoiceGenerator::from_config(&config).unwrap(),
+                ))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+/// Test a variant protocol identifier starts the header and the JSON body
+#[actix_web::test]
+async fn test_variant_protocol_issues_parseable_headers() {
+    // Given: A server configured for x402-svm
+    let config = Config::builder()
+        .protocol_id(ProtocolId::new("x402-svm").unwrap())
+        .build()
+        .unwrap();
+    let app = app!(config);
+
+    // When: A resource is requested without payment
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let header = resp
+        .headers()
+        .get("www-authenticate")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body: serde_json::Value = test::read_body_json(resp).await;
+
+    // Then: The header parses for x402-svm, canonically, and not for the default
+    assert!(header.starts_with("x402-svm recipient="));
+    assert_eq!(body["protocol"], "x402-svm");
+
+    let invoice = InvoiceHeader::parse_with_protocols(&header, &["x402-svm"]).unwrap();
+    assert_eq!(invoice.protocol, "x402-svm");
+    assert!(invoice.get("memo").is_some());
+    assert!(invoice
+        .strict_checks_for("x402-svm")
+        .iter()
+        .all(|c| c.passed));
+    assert!(InvoiceHeader::parse(&header).is_err());
+}
+
+/// Test the default configuration keeps x402-solana
+#[actix_web::test]
+async fn test_default_protocol_is_x402_solana() {
+    let app = app!(Config::default());
+
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+    let header = resp.headers().get("www-authenticate").unwrap();
+    let invoice = InvoiceHeader::parse(header.to_str().unwrap()).unwrap();
+
+    assert_eq!(invoice.protocol, "x402-solana");
+    assert!(invoice
+        .strict_checks_for("x402-solana")
+        .iter()
+        .all(|c| c.passed));
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["protocol"], "x402-solana");
+}
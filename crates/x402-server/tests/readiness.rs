@@ -0,0 +1,120 @@
+// Liveness and Readiness Tests
+//
+// /health/live answers as soon as the server listens. /health/ready, and
+// /health as its alias, answers 503 with the reasons until the startup
+// hooks ran; a failed hook keeps the server not ready while liveness stays
+// OK.
+
+use std::time::Duration;
+use x402_server::readiness::{HEALTH_LIVE_PATH, HEALTH_READY_PATH};
+use x402_server::server::HEALTH_PATH;
+use x402_server::{Config, MockServerConfig, StartupHook, TestServer};
+
+async fn start(config: Config) -> TestServer {
+    TestServer::start(MockServerConfig::new(config).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn get(server: &TestServer, path: &str) -> (u16, serde_json::Value) {
+    let resp = reqwest::get(format!("{}{}", server.base_url(), path))
+        .await
+        .unwrap();
+    let status = resp.status().as_u16();
+    (status, resp.json().await.unwrap())
+}
+
+/// Poll /health/ready until no check is pending
+async fn settled_readiness(server: &TestServer) -> (u16, serde_json::Value) {
+    for _ in 0..50 {
+        let (status, body) = get(server, HEALTH_READY_PATH).await;
+        let pending = body["checks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|c| c["status"] == "pending" && c["reason"] == "not run yet");
+        if !pending {
+            return (status, body);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("startup hooks did not finish");
+}
+
+/// Test: a server without hooks is ready right away
+#[tokio::test]
+async fn test_ready_without_hooks() {
+    // Given: A default server
+    let server = start(Config::default()).await;
+
+    // When: Readiness and its /health alias are requested
+    let (status, body) = get(&server, HEALTH_READY_PATH).await;
+    let (alias_status, alias) = get(&server, HEALTH_PATH).await;
+
+    // Then: Both answer 200 with every check passed
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["reasons"], serde_json::json!([]));
+    assert!(body["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|c| c["name"] == "listener" && c["status"] == "passed"));
+    assert_eq!(alias_status, 200);
+    assert_eq!(alias["ready"], true);
+}
+
+/// Test: passing hooks flip readiness to true
+#[tokio::test]
+async fn test_passing_hooks_make_server_ready() {
+    // Given: Pricing warm-up and a self-check expecting the 402
+    let config = Config::builder()
+        .startup_hook(StartupHook::WarmPricing)
+        .startup_hook(StartupHook::SelfCheck {
+            path: "/api/data".to_string(),
+            expect_status: 402,
+        })
+        .build()
+        .unwrap();
+    let server = start(config).await;
+
+    // When: The hooks have run
+    let (status, body) = settled_readiness(&server).await;
+
+    // Then: The server is ready
+    assert_eq!(status, 200, "{}", body);
+    assert_eq!(body["ready"], true);
+}
+
+/// Test: a failing hook keeps the server not ready, with the reason
+#[tokio::test]
+async fn test_failing_hook_keeps_server_not_ready() {
+    // Given: A self-check expecting 200 from a path that answers 402
+    let config = Config::builder()
+        .startup_hook(StartupHook::SelfCheck {
+            path: "/api/data".to_string(),
+            expect_status: 200,
+        })
+        .build()
+        .unwrap();
+    let server = start(config).await;
+
+    // When: The hook has run
+    let (status, body) = settled_readiness(&server).await;
+
+    // Then: Readiness and /health answer 503 with the reason
+    assert_eq!(status, 503);
+    assert_eq!(body["status"], "not_ready");
+    assert_eq!(
+        body["reasons"],
+        serde_json::json!(["self_check GET /api/data: answered 402, expected 200"])
+    );
+    let (alias_status, _) = get(&server, HEALTH_PATH).await;
+    assert_eq!(alias_status, 503);
+
+    // And: Liveness is OK
+    let (live_status, live) = get(&server, HEALTH_LIVE_PATH).await;
+    assert_eq!(live_status, 200);
+    assert_eq!(live["status"], "ok");
+}
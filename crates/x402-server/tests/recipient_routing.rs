@@ -0,0 +1,228 @@
+// Recipient Routing Tests
+//
+// Checks `recipient_routing`: exact and prefix routes, seeded weighted
+// splits, fallback to the fixed recipient or test addresses, validation,
+// and the matched route in the 402 response.
+
+use actix_web::{http::StatusCode, test as actix_test, web, App};
+use x402_core::testing::InvoiceHeader;
+use x402_domain::{Amount, ResourcePath, SolanaAddress};
+use x402_server::handlers::payment_required_handler;
+use x402_server::server::TEST_ADDRESSES;
+use x402_server::{
+    Config, InvoiceGenerator, PricingMatcher, RecipientRoute, WebhookDispatcher, WeightedRecipient,
+};
+
+const TREASURY: &str = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+const PREMIUM: &str = "7EqQdEULxWcraVx3mXKFjc84LhCkMGZCkRuDpvcMwJeK";
+const FALLBACK: &str = "So11111111111111111111111111111111111111112";
+
+fn address(s: &str) -> SolanaAddress {
+    SolanaAddress::new(s).unwrap()
+}
+
+fn split(weights: &[(&str, u32)]) -> RecipientRoute {
+    RecipientRoute::Weighted(
+        weights
+            .iter()
+            .map(|(a, weight)| WeightedRecipient {
+                address: address(a),
+                weight: *weight,
+            })
+            .collect(),
+    )
+}
+
+/// Recipient and matched route of the next invoice for `path`
+fn invoice_for(generator: &InvoiceGenerator, path: &str) -> (String, Option<String>) {
+    let invoice = generator.generate(
+        Amount::from_decimal_str("0.01").unwrap(),
+        ResourcePath::new(path).unwrap(),
+        300,
+    );
+    (invoice.recipient.to_string(), invoice.route)
+}
+
+/// Test exact and prefix routes pick their address; other paths fall back
+#[test]
+fn test_exact_and_prefix_routes() {
+    // Given: A treasury for /api/data and another for /api/premium/*
+    let config = Config::builder()
+        .recipient_route("/api/data", RecipientRoute::Address(address(TREASURY)))
+        .recipient_route("/api/premium/*", RecipientRoute::Address(address(PREMIUM)))
+        .recipient(address(FALLBACK))
+        .build()
+        .unwrap();
+    let generator = InvoiceGenerator::from_config(&config).unwrap();
+
+    // Then: Each path pays to its route's address
+    assert_eq!(
+        invoice_for(&generator, "/api/data"),
+        (TREASURY.to_string(), Some("/api/data".to_string()))
+    );
+    assert_eq!(
+        invoice_for(&generator, "/api/premium/report"),
+        (PREMIUM.to_string(), Some("/api/premium/*".to_string()))
+    );
+
+    // And: Unrouted paths use the fixed recipient
+    assert_eq!(
+        invoice_for(&generator, "/api/data/extra"),
+        (FALLBACK.to_string(), None)
+    );
+}
+
+/// Test unrouted paths rotate through the test addresses without a recipient
+#[test]
+fn test_fallback_to_test_address_pool() {
+    let config = Config::builder()
+        .recipient_route("/api/data", RecipientRoute::Address(address(TREASURY)))
+        .build()
+        .unwrap();
+    let generator = InvoiceGenerator::from_config(&config).unwrap();
+
+    let pool: Vec<String> = (0..3)
+        .map(|_| invoice_for(&generator, "/api/other").0)
+        .collect();
+    assert_eq!(pool, &TEST_ADDRESSES[..3]);
+
+    // Routed invoices don't advance the rotation
+    assert_eq!(invoice_for(&generator, "/api/data").0, TREASURY);
+    assert_eq!(invoice_for(&generator, "/api/other").0, TEST_ADDRESSES[3]);
+}
+
+/// Test an 80/20 split over 1000 seeded draws, repeatable by seed
+#[test]
+fn test_weighted_split_with_seed() {
+    let config = Config::builder()
+        .recipient_route("/api/*", split(&[(TREASURY, 80), (PREMIUM, 20)]))
+        .recipient_routing_seed(42)
+        .build()
+        .unwrap();
+    let draws = |config: &Config| -> Vec<String> {
+        let generator = InvoiceGenerator::from_config(config).unwrap();
+        (0..1000)
+            .map(|_| invoice_for(&generator, "/api/data").0)
+            .collect()
+    };
+
+    // When: 1000 invoices are generated
+    let first = draws(&config);
+
+    // Then: About 80% go to the treasury
+    let treasury = first.iter().filter(|r| *r == TREASURY).count();
+    assert!((750..=850).contains(&treasury), "{} of 1000", treasury);
+    assert_eq!(
+        first.iter().filter(|r| *r == PREMIUM).count(),
+        1000 - treasury
+    );
+
+    // And: The same seed replays the same sequence, another seed does not
+    assert_eq!(draws(&config), first);
+    let mut reseeded = config.clone();
+    reseeded.recipient_routing_seed = Some(7);
+    assert_ne!(draws(&reseeded), first);
+}
+
+/// Test weights and path syntax are validated
+#[test]
+fn test_routing_validation() {
+    let err = Config::builder()
+        .recipient_route("/api/data", split(&[(TREASURY, 70), (PREMIUM, 20)]))
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("recipient_routing weights for /api/data add up to 90, not 100"),
+        "{}",
+        err
+    );
+
+    let err = Config::builder()
+        .recipient_route("/api/data", split(&[(TREASURY, 100), (PREMIUM, 0)]))
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("weight above 0"), "{}", err);
+
+    for pattern in ["api/data", "/api/*/reports", "/api/*.json", "/api/ data"] {
+        let err = Config::builder()
+            .recipient_route(pattern, RecipientRoute::Address(address(TREASURY)))
+            .build()
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&format!("Invalid recipient_routing path: {}", pattern)),
+            "{}",
+            err
+        );
+    }
+}
+
+/// Test both route forms parse from YAML and addresses are checked
+#[test]
+fn test_routing_yaml() {
+    let yaml = format!(
+        "recipient_routing:\n  /api/data: {}\n  /api/premium/*:\n    - address: {}\n      weight: 80\n    - address: {}\n      weight: 20\nrecipient_routing_seed: 9\n",
+        TREASURY, TREASURY, PREMIUM
+    );
+    let config: Config = serde_yaml::from_str(&yaml).unwrap();
+
+    assert_eq!(
+        config.recipient_routing["/api/data"],
+        RecipientRoute::Address(address(TREASURY))
+    );
+    assert_eq!(
+        config.recipient_routing["/api/premium/*"],
+        split(&[(TREASURY, 80), (PREMIUM, 20)])
+    );
+    assert_eq!(config.recipient_routing_seed, Some(9));
+    assert!(config.validate().is_ok());
+
+    // An invalid address matches neither form
+    assert!(
+        serde_yaml::from_str::<Config>("recipient_routing:\n  /api/data: not-base58!\n").is_err()
+    );
+}
+
+/// Test the 402 response pays the routed address and names the route
+#[actix_web::test]
+async fn test_route_in_payment_required_response() {
+    let config = Config::builder()
+        .recipient_route("/api/premium/*", RecipientRoute::Address(address(PREMIUM)))
+        .build()
+        .unwrap();
+    let app = actix_test::init_service(
+        App::new()
+            .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+            .app_data(web::Data::new(
+                InvoiceGenerator::from_config(&config).unwrap(),
+            ))
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(WebhookDispatcher::disabled()))
+            .default_service(web::to(payment_required_handler)),
+    )
+    .await;
+
+    let req = actix_test::TestRequest::get()
+        .uri("/api/premium/report")
+        .to_request();
+    let resp = actix_test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let header = InvoiceHeader::parse(
+        resp.headers()
+            .get("www-authenticate")
+            .unwrap()
+            .to_str()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(header.get("recipient"), Some(PREMIUM));
+    let body: serde_json::Value = actix_test::read_body_json(resp).await;
+    assert_eq!(body["invoice"]["recipient"], PREMIUM);
+    assert_eq!(body["invoice"]["route"], "/api/premium/*");
+
+    // Unrouted paths have no route field
+    let req = actix_test::TestRequest::get().uri("/api/data").to_request();
+    let body: serde_json::Value = actix_test::call_and_read_body_json(&app, req).await;
+    assert!(body["invoice"].get("route").is_none());
+}
@@ -0,0 +1,142 @@
+// Replay Protection Tests
+//
+// Pays twice with the same invoice memo: the second verification is refused
+// with the `replayed_payment` error code unless `allow_replay` is set, and
+// the event log marks replays either way.
+
+use actix_web::{http::StatusCode, test, web, App};
+use x402_server::events::ADMIN_EVENTS_PATH;
+use x402_server::handlers::{events_handler, payment_required_handler, REPLAYED_PAYMENT};
+use x402_server::{
+    Config, EventLog, EventType, EventsPage, InvoiceGenerator, PricingMatcher, WebhookDispatcher,
+};
+
+macro_rules! replay_app {
+    ($config:expr) => {{
+        let config = $config;
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+                .app_data(web::Data::new(InvoiceGenerator::new()))
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(WebhookDispatcher::disabled()))
+                .app_data(web::Data::new(EventLog::new()))
+                .service(web::resource(ADMIN_EVENTS_PATH).get(events_handler))
+                .default_service(web::to(payment_required_handler)),
+        )
+        .await
+    }};
+}
+
+/// Take an invoice and return its memo
+macro_rules! invoice_memo {
+    ($app:expr) => {{
+        let req = test::TestRequest::get().uri("/api/data").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&$app, req).await;
+        body["invoice"]["memo"].as_str().unwrap().to_string()
+    }};
+}
+
+macro_rules! pay {
+    ($app:expr, $proof:expr $(, $header:expr)*) => {{
+        let req = test::TestRequest::get()
+            .uri("/api/data")
+            .insert_header(("X-Payment-Proof", $proof))
+            $(.insert_header($header))*
+            .to_request();
+        test::call_service(&$app, req).await
+    }};
+}
+
+macro_rules! events {
+    ($app:expr) => {{
+        let req = test::TestRequest::get().uri(ADMIN_EVENTS_PATH).to_request();
+        let page: EventsPage = test::call_and_read_body_json(&$app, req).await;
+        page
+    }};
+}
+
+/// Test each invoice memo pays once
+#[actix_web::test]
+async fn test_verify_once_succeeds() {
+    let app = replay_app!(Config::default());
+
+    let first = invoice_memo!(app);
+    let second = invoice_memo!(app);
+    assert_eq!(pay!(app, first.as_str()).status(), StatusCode::OK);
+    assert_eq!(pay!(app, second.as_str()).status(), StatusCode::OK);
+}
+
+/// Test a second verification of the same memo is refused as a replay
+#[actix_web::test]
+async fn test_verify_twice_is_rejected() {
+    // Given: A memo that already paid
+    let app = replay_app!(Config::default());
+    let memo = invoice_memo!(app);
+    assert_eq!(pay!(app, memo.as_str()).status(), StatusCode::OK);
+
+    // When: Paying with it again
+    let resp = pay!(app, memo.as_str());
+
+    // Then: 409 with the replayed_payment error envelope
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], REPLAYED_PAYMENT);
+    assert_eq!(body["error"], "Payment already used");
+    assert_eq!(body["payment_proof"], memo.as_str());
+
+    // And: The event log marks the replay as a failed payment
+    let page = events!(app);
+    let replay = &page.events.last().unwrap().event;
+    assert_eq!(replay.event_type, EventType::PaymentFailed);
+    assert_eq!(replay.outcome, "replayed");
+    assert_eq!(replay.reason.as_deref(), Some(REPLAYED_PAYMENT));
+    assert_eq!(replay.memo.as_deref(), Some(memo.as_str()));
+}
+
+/// Test failed verifications do not use up the proof
+#[actix_web::test]
+async fn test_failed_verification_does_not_use_proof() {
+    let app = replay_app!(Config::default());
+    let memo = invoice_memo!(app);
+
+    let failed = pay!(app, memo.as_str(), ("X-Simulation-Mode", "failure"));
+    assert_eq!(failed.status(), StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(pay!(app, memo.as_str()).status(), StatusCode::OK);
+    assert_eq!(pay!(app, memo.as_str()).status(), StatusCode::CONFLICT);
+}
+
+/// Test `allow_replay` passes replays through, still marked in the log
+#[actix_web::test]
+async fn test_allow_replay_passes_through() {
+    // Given: A lenient server
+    let app = replay_app!(Config::builder().allow_replay(true).build().unwrap());
+    let memo = invoice_memo!(app);
+
+    // When: Paying twice with the same memo
+    assert_eq!(pay!(app, memo.as_str()).status(), StatusCode::OK);
+    assert_eq!(pay!(app, memo.as_str()).status(), StatusCode::OK);
+
+    // Then: Both verify, and only the second is marked as a replay
+    let page = events!(app);
+    let payments: Vec<_> = page
+        .events
+        .iter()
+        .map(|e| &e.event)
+        .filter(|e| e.event_type == EventType::PaymentVerified)
+        .map(|e| e.reason.as_deref())
+        .collect();
+    assert_eq!(payments, [None, Some(REPLAYED_PAYMENT)]);
+}
+
+/// Test a proof over the header length limit is refused before lookup
+#[actix_web::test]
+async fn test_oversized_proof_is_rejected() {
+    let app = replay_app!(Config::default());
+    let proof = "a".repeat(x402_core::testing::DEFAULT_MAX_HEADER_BYTES + 1);
+
+    let resp = pay!(app, proof.as_str());
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Invalid payment proof");
+}
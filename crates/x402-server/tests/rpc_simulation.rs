@@ -0,0 +1,249 @@
+// Fake Solana RPC Tests
+//
+// Runs the mock with `rpc:` behaviors, issues an invoice, and verifies its
+// payment through x402_core::solana_rpc against /rpc: confirmed, not found,
+// not yet confirmed and wrong amount, plus airdrops and balances. Raw
+// responses are compared with the shapes in tests/fixtures/rpc after
+// replacing per-run values with placeholders.
+
+mod common;
+
+use common::{free_port, serve};
+use serde_json::{json, Value};
+use x402_core::solana_rpc::{ExpectedTransfer, SolanaRpcClient, TransferVerdict};
+use x402_server::server::TEST_ADDRESSES;
+use x402_server::{Config, RpcBehavior, RpcConfig, RPC_PATH};
+
+const AIRDROP_ADDRESS: &str = "HYn9xTest2222222222222222222222222222rAb3";
+
+/// Start the mock with prefix behaviors and return its base URL
+async fn start_mock() -> String {
+    let rpc = RpcConfig {
+        behaviors: [
+            ("notfound-", RpcBehavior::NotFound),
+            ("pending-", RpcBehavior::Unconfirmed),
+            ("short-", RpcBehavior::WrongAmount),
+        ]
+        .into_iter()
+        .map(|(prefix, behavior)| (prefix.to_string(), behavior))
+        .collect(),
+    };
+    let config = Config::builder()
+        .port(free_port())
+        .default_price(0.01)
+        .rpc(rpc)
+        .build()
+        .unwrap();
+    let port = serve(config).await;
+    format!("http://127.0.0.1:{}", port)
+}
+
+/// Request a resource and return the invoice the mock issued
+async fn issue_invoice(base: &str) -> ExpectedTransfer {
+    let body: Value = reqwest::get(format!("{}/api/data", base))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let invoice = &body["invoice"];
+    ExpectedTransfer {
+        memo: invoice["memo"].as_str().unwrap().to_string(),
+        recipient: invoice["recipient"].as_str().unwrap().to_string(),
+        amount: 10_000,
+    }
+}
+
+async fn raw_call(base: &str, method: &str, params: Value) -> Value {
+    reqwest::Client::new()
+        .post(format!("{}{}", base, RPC_PATH))
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+/// Replace per-run values with the placeholders used in the fixtures
+fn normalize(value: Value, replacements: &[(&str, &str)]) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let value = match (key.as_str(), &value) {
+                        ("slot", Value::Number(_)) => json!("<slot>"),
+                        ("blockTime", Value::Number(_)) => json!("<block_time>"),
+                        _ => normalize(value, replacements),
+                    };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| normalize(item, replacements))
+                .collect(),
+        ),
+        Value::String(s) if s.starts_with("airdrop-") => json!("<airdrop_signature>"),
+        Value::String(s) => replacements
+            .iter()
+            .find(|(actual, _)| *actual == s)
+            .map_or(Value::String(s.clone()), |(_, placeholder)| {
+                json!(placeholder)
+            }),
+        other => other,
+    }
+}
+
+fn fixture(name: &str) -> Value {
+    let path = format!(
+        "{}/tests/fixtures/rpc/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap()
+}
+
+/// Test each configured behavior through the RPC client's verification
+#[actix_web::test]
+async fn test_verify_transfer_behaviors() {
+    // Given: An invoice issued by the mock
+    let base = start_mock().await;
+    let expected = issue_invoice(&base).await;
+    let client = SolanaRpcClient::new(format!("{}{}", base, RPC_PATH)).unwrap();
+    let memo = &expected.memo;
+
+    // Then: The plain memo is a finalized transfer matching the invoice
+    assert_eq!(
+        client.verify_transfer(memo, &expected).await.unwrap(),
+        TransferVerdict::Verified
+    );
+    let transfer = client.get_transfer(memo).await.unwrap().unwrap();
+    assert_eq!(transfer.memo.as_deref(), Some(memo.as_str()));
+    assert_eq!(transfer.destination, expected.recipient);
+
+    // And: Each prefix triggers its verification path
+    assert_eq!(
+        client
+            .verify_transfer(&format!("notfound-{}", memo), &expected)
+            .await
+            .unwrap(),
+        TransferVerdict::NotFound
+    );
+    assert_eq!(
+        client
+            .verify_transfer(&format!("pending-{}", memo), &expected)
+            .await
+            .unwrap(),
+        TransferVerdict::Unconfirmed
+    );
+    let short = client
+        .verify_transfer(&format!("short-{}", memo), &expected)
+        .await
+        .unwrap();
+    assert_eq!(
+        short,
+        TransferVerdict::Mismatch(vec!["amount is 5000 base units, expected 10000".to_string()])
+    );
+
+    // And: Signatures for invoices never issued are unknown
+    assert_eq!(
+        client
+            .verify_transfer("req-00000000-0000-0000-0000-000000000000", &expected)
+            .await
+            .unwrap(),
+        TransferVerdict::NotFound
+    );
+}
+
+/// Test airdrops credit balances and settle their signatures
+#[actix_web::test]
+async fn test_airdrop_and_balance() {
+    let base = start_mock().await;
+    let client = SolanaRpcClient::new(format!("{}{}", base, RPC_PATH)).unwrap();
+
+    assert_eq!(client.get_balance(AIRDROP_ADDRESS).await.unwrap(), 0);
+    let signature = client
+        .request_airdrop(AIRDROP_ADDRESS, 1_000_000_000)
+        .await
+        .unwrap();
+    assert_eq!(
+        client.get_balance(AIRDROP_ADDRESS).await.unwrap(),
+        1_000_000_000
+    );
+    let status = client
+        .get_signature_status(&signature)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(status.is_settled());
+
+    // Invalid addresses get the JSON-RPC invalid params error
+    let err = client.get_balance("not-base58!").await.unwrap_err();
+    assert!(err.to_string().contains("code -32602"), "{}", err);
+}
+
+/// Test raw responses match the fixture shapes
+#[actix_web::test]
+async fn test_response_shapes_match_fixtures() {
+    let base = start_mock().await;
+    let expected = issue_invoice(&base).await;
+    let memo = expected.memo.as_str();
+    let payer = TEST_ADDRESSES[TEST_ADDRESSES.len() - 1];
+    let replacements = [
+        (memo, "<memo>"),
+        (expected.recipient.as_str(), "<recipient>"),
+        (payer, "<payer>"),
+    ];
+
+    let transaction = raw_call(
+        &base,
+        "getTransaction",
+        json!([memo, { "encoding": "jsonParsed" }]),
+    )
+    .await;
+    let normalized = normalize(transaction, &replacements);
+    // The signature and memo are the same string for unprefixed signatures
+    let mut want = fixture("get_transaction");
+    want["result"]["transaction"]["signatures"] = json!(["<memo>"]);
+    assert_eq!(normalized, want);
+
+    let missing = raw_call(
+        &base,
+        "getTransaction",
+        json!([format!("notfound-{}", memo)]),
+    )
+    .await;
+    assert_eq!(missing, fixture("get_transaction_not_found"));
+
+    let statuses = raw_call(
+        &base,
+        "getSignatureStatuses",
+        json!([[
+            memo,
+            format!("pending-{}", memo),
+            format!("notfound-{}", memo)
+        ]]),
+    )
+    .await;
+    assert_eq!(
+        normalize(statuses, &replacements),
+        fixture("get_signature_statuses")
+    );
+
+    let airdrop = raw_call(
+        &base,
+        "requestAirdrop",
+        json!([AIRDROP_ADDRESS, 1_000_000_000]),
+    )
+    .await;
+    assert_eq!(normalize(airdrop, &[]), fixture("request_airdrop"));
+    let balance = raw_call(&base, "getBalance", json!([AIRDROP_ADDRESS])).await;
+    assert_eq!(normalize(balance, &[]), fixture("get_balance"));
+
+    let unknown = raw_call(&base, "getSlot", json!([])).await;
+    assert_eq!(unknown, fixture("method_not_found"));
+}
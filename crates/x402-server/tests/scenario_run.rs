@@ -0,0 +1,91 @@
+// Scenario Run Tests
+//
+// Runs the bundled x402-scenario.yaml fixture: its config and allowlist
+// policy are served by an in-process TestServer and its suite runs against
+// it, so no externally started mock server is needed.
+
+use std::path::PathBuf;
+use x402_core::testing::TestFilter;
+use x402_server::{validate_scenario, Scenario};
+
+fn fixture() -> PathBuf {
+    PathBuf::from(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../tests/fixtures/scenarios/x402-scenario.yaml"
+    ))
+}
+
+/// Test the fixture passes validation without warnings
+#[test]
+fn test_fixture_validates_cleanly() {
+    // Given/When: Validating the bundled scenario
+    let validation = validate_scenario(&fixture()).unwrap();
+
+    // Then: Runnable, with the referenced allowlist converted to policies
+    assert!(validation.issues.is_empty(), "{:?}", validation.issues);
+    let scenario = validation.scenario.unwrap();
+    assert_eq!(scenario.name, "premium-allowlist");
+    assert!(!scenario.config.policies.is_empty());
+}
+
+/// Test a scenario run enforces the allowlist and the configured pricing
+#[tokio::test]
+async fn test_scenario_run_end_to_end() {
+    // Given: The bundled scenario
+    let scenario = Scenario::from_file(&fixture()).unwrap();
+
+    // When: Running it
+    let report = scenario.run(&TestFilter::default()).await.unwrap();
+
+    // Then: Every test passes, including the premium price and the denial
+    assert_eq!(report.exit_code(), 0, "{:#?}", report.suite.tests);
+    assert_eq!((report.suite.passed, report.suite.failed), (3, 0));
+
+    // And: The server stats cover the same requests
+    assert_eq!(report.stats.totals.invoices_issued, 2);
+    assert_eq!(report.stats.totals.policy_denied, 1);
+
+    let json = report.to_json();
+    assert_eq!(json["scenario"], "premium-allowlist");
+    assert_eq!(json["passed"], true);
+    assert_eq!(json["stats"]["totals"]["policy_denied"], 1);
+}
+
+/// Test failing assertions are reported, not turned into errors
+#[tokio::test]
+async fn test_scenario_run_reports_failures() {
+    // Given: A scenario expecting the wrong premium price
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("x402-scenario.yaml");
+    std::fs::write(
+        &path,
+        r#"
+config:
+  pricing:
+    default: 0.01
+    per_resource:
+      /api/premium/*: 0.05
+suite:
+  tests:
+    - name: wrong price
+      url: ${base_url}/api/premium/report
+      expect:
+        invoice:
+          amount:
+            equals: "0.02"
+"#,
+    )
+    .unwrap();
+
+    // When: Running it
+    let report = Scenario::from_file(&path)
+        .unwrap()
+        .run(&TestFilter::default())
+        .await
+        .unwrap();
+
+    // Then: The failure shows in the report and the exit code
+    assert_eq!(report.suite.failed, 1);
+    assert_eq!(report.exit_code(), 1);
+    assert_eq!(report.stats.totals.invoices_issued, 1);
+}
@@ -0,0 +1,119 @@
+// Shared Policy State Tests
+//
+// Two mock servers behind a simulated load balancer share one SQLite
+// policy state file, so an agent cannot double its limit by spreading
+// requests over both.
+
+use std::time::Duration;
+use x402_core::policy::{
+    PolicyAction, PolicyStateStore, RateLimitConfig, RuntimePolicy, SqliteStateStore, StateBackend,
+    StateStoreConfig,
+};
+use x402_server::{Config, MockServerConfig, TestServer};
+
+/// Every agent may make four requests a minute
+fn four_per_minute() -> RuntimePolicy {
+    RuntimePolicy {
+        id: "four-per-minute".to_string(),
+        description: String::new(),
+        priority: 10,
+        agent_patterns: vec![],
+        endpoint_patterns: vec![],
+        action: PolicyAction::Allow,
+        rate_limit: Some(RateLimitConfig {
+            max_requests: 4,
+            window: Duration::from_secs(60),
+        }),
+        spending_cap: None,
+        concurrency_limit: None,
+    }
+}
+
+async fn start(state: &StateStoreConfig) -> TestServer {
+    let config = Config::builder()
+        .policies(vec![four_per_minute()])
+        .policy_state(state.clone())
+        .build()
+        .unwrap();
+    TestServer::start(MockServerConfig::new(config).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn get_status(server: &TestServer) -> u16 {
+    reqwest::Client::new()
+        .get(format!("{}/api/data", server.base_url()))
+        .header("X-Agent-Id", "agent-1")
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+async fn instance_id(server: &TestServer) -> String {
+    let health: serde_json::Value = reqwest::get(format!("{}/health", server.base_url()))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    health["instance_id"].as_str().unwrap().to_string()
+}
+
+/// Test: the limit holds across two instances sharing a state file
+#[tokio::test]
+async fn test_instances_share_rate_limit() {
+    // Given: Two servers on different ports sharing one state file
+    let dir = tempfile::tempdir().unwrap();
+    let state = StateStoreConfig {
+        backend: StateBackend::Sqlite,
+        path: Some(dir.path().join("shared.db")),
+    };
+    let first = start(&state).await;
+    let second = start(&state).await;
+
+    // When: The four allowed requests are split across both
+    for server in [&first, &second, &first, &second] {
+        assert_eq!(get_status(server).await, 402);
+    }
+
+    // Then: The next request is denied by either instance
+    assert_eq!(get_status(&first).await, 403);
+    assert_eq!(get_status(&second).await, 403);
+
+    // And: Both registered in the store and tagged their requests
+    let store = SqliteStateStore::open(state.sqlite_path()).unwrap();
+    let ids = [instance_id(&first).await, instance_id(&second).await];
+    let registered: Vec<String> = store
+        .instances()
+        .unwrap()
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+    assert_eq!(registered, ids);
+    let mut expected: Vec<(Option<String>, u64)> =
+        ids.iter().map(|id| (Some(id.clone()), 2)).collect();
+    expected.sort();
+    assert_eq!(store.requests_by_instance().unwrap(), expected);
+
+    first.stop().await.unwrap();
+    second.stop().await.unwrap();
+}
+
+/// Test: without a shared file each instance counts on its own
+#[tokio::test]
+async fn test_memory_state_is_per_instance() {
+    let state = StateStoreConfig::default();
+    let first = start(&state).await;
+    let second = start(&state).await;
+
+    for _ in 0..4 {
+        assert_eq!(get_status(&first).await, 402);
+    }
+    assert_eq!(get_status(&first).await, 403);
+    assert_eq!(get_status(&second).await, 402);
+
+    first.stop().await.unwrap();
+    second.stop().await.unwrap();
+}
@@ -0,0 +1,118 @@
+// Snapshot Tests
+//
+// GET /__admin/snapshot exports a server's invoices, stats, events and policy
+// counters; POST /__admin/snapshot adds them to another server. The archive
+// written by `mock snapshot export` must round-trip through the same types.
+
+use std::time::Duration;
+use x402_core::policy::{PolicyAction, RateLimitConfig, RuntimePolicy};
+use x402_server::{
+    Config, InvoiceStatus, MockServerConfig, ServerSnapshot, SnapshotManifest, TestServer,
+};
+
+/// Every agent may make two requests a minute
+fn two_per_minute() -> RuntimePolicy {
+    RuntimePolicy {
+        id: "two-per-minute".to_string(),
+        description: String::new(),
+        priority: 10,
+        agent_patterns: vec![],
+        endpoint_patterns: vec![],
+        action: PolicyAction::Allow,
+        rate_limit: Some(RateLimitConfig {
+            max_requests: 2,
+            window: Duration::from_secs(60),
+        }),
+        spending_cap: None,
+        concurrency_limit: None,
+    }
+}
+
+async fn start() -> TestServer {
+    let config = Config::builder()
+        .policies(vec![two_per_minute()])
+        .build()
+        .unwrap();
+    TestServer::start(MockServerConfig::new(config).unwrap())
+        .await
+        .unwrap()
+}
+
+/// Test: a restored server continues where the exported one stopped
+#[tokio::test]
+async fn test_snapshot_round_trip() {
+    // Given: A server that issued an invoice and verified its payment
+    let original = start().await;
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/data", original.base_url());
+    let body: serde_json::Value = client.get(&url).send().await.unwrap().json().await.unwrap();
+    let memo = body["invoice"]["memo"].as_str().unwrap().to_string();
+    let paid = client
+        .get(&url)
+        .header("X-Payment-Proof", &memo)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(paid.status().as_u16(), 200);
+
+    // When: Exporting it through an archive
+    let snapshot = original.snapshot().await.unwrap();
+    let archive = snapshot.write_archive(Vec::new()).unwrap();
+    let read = ServerSnapshot::read_archive(archive.as_slice()).unwrap();
+    original.stop().await.unwrap();
+
+    // Then: The archive holds the same state
+    assert_eq!(read.manifest, snapshot.manifest);
+    assert_eq!(read.manifest.invoices_total, 1);
+    assert_eq!(read.invoices.len(), 1);
+    assert_eq!(read.invoices[0].status, InvoiceStatus::Verified);
+    assert!(read.policy_state.is_some());
+
+    // When: Restoring it into a fresh server
+    let restored = start().await;
+    let summary = restored.restore(&read).await.unwrap();
+
+    // Then: Invoices, counters and events are back
+    assert_eq!(summary.invoices, 1);
+    assert_eq!(summary.invoices_skipped, 0);
+    assert_eq!(summary.events, read.events.len());
+    assert!(summary.policy_state);
+    assert!(summary.warnings.is_empty(), "{:?}", summary.warnings);
+    let stats = restored.stats().await.unwrap();
+    assert_eq!(stats.totals.requests, 2);
+    assert_eq!(stats.totals.invoices_issued, 1);
+    assert_eq!(stats.totals.verified, 1);
+    let again = restored.snapshot().await.unwrap();
+    assert_eq!(again.invoices[0].memo, memo);
+    assert_eq!(again.invoices[0].status, InvoiceStatus::Verified);
+    assert_eq!(again.events.len(), read.events.len());
+
+    // And: The rate limit remembers the paid flow, which counted once
+    let url = format!("{}/api/data", restored.base_url());
+    let last = client.get(&url).send().await.unwrap();
+    assert_eq!(last.status().as_u16(), 402);
+    let denied = client.get(&url).send().await.unwrap();
+    assert_eq!(denied.status().as_u16(), 403);
+
+    restored.stop().await.unwrap();
+}
+
+/// Test: archives of an unknown format are refused
+#[tokio::test]
+async fn test_newer_format_is_refused() {
+    // Given: A snapshot claiming a future format
+    let server = start().await;
+    let mut snapshot = server.snapshot().await.unwrap();
+    server.stop().await.unwrap();
+    snapshot.manifest = SnapshotManifest {
+        format: snapshot.manifest.format + 1,
+        ..snapshot.manifest
+    };
+    let archive = snapshot.write_archive(Vec::new()).unwrap();
+
+    // When: Reading it back
+    let err = ServerSnapshot::read_archive(archive.as_slice()).unwrap_err();
+
+    // Then: The error names the format
+    assert!(err.to_string().contains("format"), "{}", err);
+}
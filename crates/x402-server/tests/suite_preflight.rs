@@ -0,0 +1,89 @@
+// Suite Pre-flight Tests
+//
+// A suite with `preflight:` checks its targets before the first test. A
+// closed port is reported once, as a pre-flight failure, with every test
+// skipped; a healthy mock server passes the checks and the suite runs.
+
+use std::str::FromStr;
+use x402_core::testing::{
+    execute_test_suite, PreflightCheckKind, SkipReason, SuiteStatus, TestSuite,
+};
+use x402_server::{Config, MockServerConfig, TestServer};
+
+fn suite(base_url: &str) -> TestSuite {
+    <TestSuite as FromStr>::from_str(&format!(
+        r#"
+variables:
+  base_url: "{}"
+preflight:
+  health: /health
+  timeout_ms: 1s
+tests:
+  - name: "Invoice for /api/data"
+    url: "${{base_url}}/api/data"
+    expect:
+      status: 402
+  - name: "Invoice for /api/premium"
+    url: "${{base_url}}/api/premium"
+    expect:
+      status: 402
+  - name: "Invoice for /api/other"
+    url: "${{base_url}}/api/other"
+    expect:
+      status: 402
+"#,
+        base_url
+    ))
+    .unwrap()
+}
+
+/// Test: a closed port fails pre-flight once and runs no test
+#[tokio::test]
+async fn test_closed_port_fails_preflight() {
+    // Given: A suite pointed at a port nobody listens on
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+    let suite = suite(&format!("http://127.0.0.1:{}", port));
+
+    // When: The suite runs
+    let result = execute_test_suite(&suite).await.unwrap();
+
+    // Then: One TCP failure is reported and no test was executed
+    assert_eq!(result.status(), SuiteStatus::PreflightFailed);
+    assert_eq!(result.exit_code(), 3);
+    let report = result.preflight.as_ref().unwrap();
+    let failures: Vec<_> = report.failures().collect();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].kind, PreflightCheckKind::Tcp);
+    assert!(failures[0].error.as_deref().unwrap().contains("Fix:"));
+    assert_eq!((result.passed, result.failed, result.skipped), (0, 0, 3));
+    assert!(result
+        .tests
+        .iter()
+        .all(|test| test.skipped == Some(SkipReason::PreflightFailed)));
+}
+
+/// Test: a healthy target passes pre-flight and the suite runs
+#[tokio::test]
+async fn test_healthy_target_runs_suite() {
+    // Given: A running mock server
+    let server = TestServer::start(MockServerConfig::new(Config::default()).unwrap())
+        .await
+        .unwrap();
+    let suite = suite(server.base_url());
+
+    // When: The suite runs
+    let result = execute_test_suite(&suite).await.unwrap();
+    server.stop().await.unwrap();
+
+    // Then: Every check passed, including the health request, and all tests ran
+    let report = result.preflight.as_ref().unwrap();
+    assert!(report.passed(), "{:?}", report);
+    assert!(report
+        .checks
+        .iter()
+        .any(|check| check.kind == PreflightCheckKind::Health));
+    assert_eq!(result.status(), SuiteStatus::Passed);
+    assert_eq!((result.passed, result.skipped), (3, 0));
+}
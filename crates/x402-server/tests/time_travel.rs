@@ -0,0 +1,167 @@
+// Time Travel Tests
+//
+// Servers started with `allow_time_travel` run invoices and policies on a
+// virtual clock. POST /__admin/clock/advance moves it past rate limit
+// windows and invoice deadlines without sleeping.
+
+use std::time::Duration;
+use x402_core::policy::{PolicyAction, RateLimitConfig, RuntimePolicy};
+use x402_server::server::{ADMIN_CLOCK_ADVANCE_PATH, ADMIN_CLOCK_PATH};
+use x402_server::{Config, InvoiceRecord, InvoiceStatus, MockServerConfig, TestServer};
+
+/// Every agent may make two requests a minute
+fn two_per_minute() -> RuntimePolicy {
+    RuntimePolicy {
+        id: "two-per-minute".to_string(),
+        description: String::new(),
+        priority: 10,
+        agent_patterns: vec![],
+        endpoint_patterns: vec![],
+        action: PolicyAction::Allow,
+        rate_limit: Some(RateLimitConfig {
+            max_requests: 2,
+            window: Duration::from_secs(60),
+        }),
+        spending_cap: None,
+        concurrency_limit: None,
+    }
+}
+
+async fn start(config: Config) -> TestServer {
+    TestServer::start(MockServerConfig::new(config).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn advance(server: &TestServer, seconds: u64) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}{}", server.base_url(), ADMIN_CLOCK_ADVANCE_PATH))
+        .json(&serde_json::json!({ "seconds": seconds }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_status(server: &TestServer, path: &str) -> u16 {
+    reqwest::get(format!("{}{}", server.base_url(), path))
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+/// Test: advancing past the window resets a rate limit
+#[tokio::test]
+async fn test_advance_resets_rate_limit() {
+    // Given: A time-travelling server limiting agents to 2 requests a minute
+    let server = start(
+        Config::builder()
+            .allow_time_travel(true)
+            .policies(vec![two_per_minute()])
+            .build()
+            .unwrap(),
+    )
+    .await;
+
+    // When: The limit is used up
+    assert_eq!(get_status(&server, "/api/data").await, 402);
+    assert_eq!(get_status(&server, "/api/data").await, 402);
+
+    // Then: The next request is denied
+    assert_eq!(get_status(&server, "/api/data").await, 403);
+
+    // When: Advancing the clock past the window
+    let moved = advance(&server, 61).await;
+    assert_eq!(moved.status().as_u16(), 200);
+    let body: serde_json::Value = moved.json().await.unwrap();
+    assert_eq!(body["offset_seconds"], 61.0);
+
+    // Then: The limit has reset
+    assert_eq!(get_status(&server, "/api/data").await, 402);
+
+    // And: /__admin/clock reports the moved time
+    let clock: serde_json::Value =
+        reqwest::get(format!("{}{}", server.base_url(), ADMIN_CLOCK_PATH))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+    assert_eq!(clock["time_travel"], true);
+    assert_eq!(clock["offset_seconds"], 61.0);
+
+    server.stop().await.unwrap();
+}
+
+/// Test: a payment after the clock passed the invoice deadline is refused
+#[tokio::test]
+async fn test_advance_expires_invoice() {
+    // Given: An invoice valid for 60 seconds
+    let server = start(
+        Config::builder()
+            .allow_time_travel(true)
+            .invoice_ttl_seconds(60)
+            .build()
+            .unwrap(),
+    )
+    .await;
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/data", server.base_url());
+    let body: serde_json::Value = client.get(&url).send().await.unwrap().json().await.unwrap();
+    let memo = body["invoice"]["memo"].as_str().unwrap().to_string();
+
+    // When: Paying after moving the clock past the deadline
+    assert_eq!(advance(&server, 120).await.status().as_u16(), 200);
+    let paid = client
+        .get(&url)
+        .header("X-Payment-Proof", &memo)
+        .send()
+        .await
+        .unwrap();
+
+    // Then: The payment is refused as expired
+    assert_eq!(paid.status().as_u16(), 402);
+    let body: serde_json::Value = paid.json().await.unwrap();
+    assert_eq!(body["code"], "expired_invoice");
+    assert_eq!(body["payment_proof"], memo.as_str());
+
+    // And: The invoice was not used up
+    let record: InvoiceRecord = client
+        .get(format!("{}/__admin/invoices/{}", server.base_url(), memo))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(record.status, InvoiceStatus::Issued);
+
+    // And: A fresh invoice, issued at the moved time, still pays
+    let body: serde_json::Value = client.get(&url).send().await.unwrap().json().await.unwrap();
+    let fresh = body["invoice"]["memo"].as_str().unwrap();
+    let paid = client
+        .get(&url)
+        .header("X-Payment-Proof", fresh)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(paid.status().as_u16(), 200);
+
+    server.stop().await.unwrap();
+}
+
+/// Test: without allow_time_travel the clock cannot be moved
+#[tokio::test]
+async fn test_advance_requires_time_travel() {
+    let server = start(Config::default()).await;
+
+    let refused = advance(&server, 60).await;
+    assert_eq!(refused.status().as_u16(), 403);
+    let body: serde_json::Value = refused.json().await.unwrap();
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("--allow-time-travel"));
+
+    server.stop().await.unwrap();
+}
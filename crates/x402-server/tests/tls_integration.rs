@@ -0,0 +1,120 @@
+// TLS Integration Tests
+//
+// Runs the real mock server over https:// with a self-signed certificate and
+// exercises the two-phase 402 flow with a client that accepts the cert.
+
+mod common;
+
+use common::{free_port, wait_until_listening};
+use std::path::PathBuf;
+use std::time::Duration;
+use x402_server::server::start_http_server;
+use x402_server::{Config, MockServerConfig, TlsConfig};
+
+fn server_config(port: u16, tls: TlsConfig) -> MockServerConfig {
+    let config = Config::builder()
+        .port(port)
+        .timeout_delay_ms(100)
+        .tls(tls)
+        .build()
+        .unwrap();
+    MockServerConfig::new(config).unwrap()
+}
+
+/// Test the 402 flow over https with a self-signed certificate
+#[actix_web::test]
+async fn test_self_signed_tls_payment_flow() {
+    // Given: The mock server running with an ephemeral self-signed cert
+    let port = free_port();
+    actix_web::rt::spawn(start_http_server(server_config(
+        port,
+        TlsConfig::self_signed(),
+    )));
+
+    let client = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let url = format!("https://127.0.0.1:{}/api/data", port);
+
+    // When: Requesting without payment proof (retry while the server binds)
+    let mut response = None;
+    for _ in 0..50 {
+        if let Ok(resp) = client.get(&url).send().await {
+            response = Some(resp);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    let response = response.expect("https server did not start");
+
+    // Then: 402 with an x402 invoice
+    assert_eq!(response.status().as_u16(), 402);
+    let www_auth = response.headers()["www-authenticate"].to_str().unwrap();
+    assert!(www_auth.starts_with("x402-solana "));
+
+    // And: Retrying with a payment proof succeeds
+    let response = client
+        .get(&url)
+        .header("X-Payment-Proof", "tx_test_proof")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+/// Test a plain http client is rejected by the https listener
+#[actix_web::test]
+async fn test_strict_client_rejects_self_signed() {
+    let port = free_port();
+    actix_web::rt::spawn(start_http_server(server_config(
+        port,
+        TlsConfig::self_signed(),
+    )));
+    wait_until_listening(port).await;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let result = client
+        .get(format!("https://127.0.0.1:{}/api/data", port))
+        .send()
+        .await;
+
+    assert!(
+        result.is_err(),
+        "self-signed cert must not be trusted by default"
+    );
+}
+
+/// Test unreadable certificate files produce a clear error
+#[actix_web::test]
+async fn test_unreadable_cert_error() {
+    let tls = TlsConfig {
+        cert_path: Some(PathBuf::from("/nonexistent/cert.pem")),
+        key_path: Some(PathBuf::from("/nonexistent/key.pem")),
+        self_signed: false,
+    };
+
+    let err = start_http_server(server_config(free_port(), tls))
+        .await
+        .unwrap_err();
+
+    assert!(err
+        .to_string()
+        .contains("Cannot read TLS certificate: /nonexistent/cert.pem"));
+}
+
+/// Test missing key path is reported with a fix
+#[test]
+fn test_missing_key_path_error() {
+    let tls = TlsConfig {
+        cert_path: Some(PathBuf::from("cert.pem")),
+        ..TlsConfig::default()
+    };
+
+    let err = tls.load().err().unwrap();
+    assert!(err.to_string().contains("--tls-self-signed"));
+}
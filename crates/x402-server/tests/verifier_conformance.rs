@@ -0,0 +1,189 @@
+// Verifier Conformance Tests
+//
+// Runs one mock with `verifier: simulated` and one with `verifier: rpc`
+// (verifying against its own fake /rpc) and sends each the same matrix of
+// payments: paid, rejected, not settled in time, underpaid and replayed.
+// Both backends must answer every case with the same status, and the
+// events they log must classify it the same way.
+
+mod common;
+
+use common::{free_port, serve};
+use reqwest::StatusCode;
+use serde_json::Value;
+use x402_core::verifier::VerifierKind;
+use x402_server::{Config, RpcBehavior, RpcConfig};
+
+/// Start a mock verifying with `verifier` and return its base URL
+async fn start_mock(verifier: VerifierKind) -> String {
+    let port = free_port();
+    let base = format!("http://127.0.0.1:{}", port);
+    let rpc = RpcConfig {
+        behaviors: [
+            ("notfound-", RpcBehavior::NotFound),
+            ("pending-", RpcBehavior::Unconfirmed),
+            ("short-", RpcBehavior::WrongAmount),
+        ]
+        .into_iter()
+        .map(|(prefix, behavior)| (prefix.to_string(), behavior))
+        .collect(),
+    };
+    let config = Config::builder()
+        .port(port)
+        .default_price(0.01)
+        .timeout_delay_ms(100)
+        .solana_rpc(format!("{}/rpc", base))
+        .rpc(rpc)
+        .verifier(verifier)
+        .build()
+        .unwrap();
+    serve(config).await;
+    base
+}
+
+/// A payment the matrix sends
+#[derive(Debug, Clone, Copy)]
+enum Case {
+    Paid,
+    Rejected,
+    Pending,
+    Underpaid,
+}
+
+/// Issue an invoice, pay it as `case` describes for `verifier`, and return
+/// the memo and the status of the payment
+async fn pay(base: &str, verifier: VerifierKind, case: Case) -> (String, StatusCode) {
+    let client = reqwest::Client::new();
+    let invoice: Value = client
+        .get(format!("{}/api/data", base))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let memo = invoice["invoice"]["memo"].as_str().unwrap().to_string();
+
+    // The simulated verifier takes the memo and simulation headers; the RPC
+    // verifier takes a signature whose prefix selects the fake RPC behavior
+    let mut request = client.get(format!("{}/api/data", base));
+    let proof = match (verifier, case) {
+        (VerifierKind::Simulated, Case::Rejected) => {
+            request = request.header("X-Simulation-Mode", "failure");
+            memo.clone()
+        }
+        (VerifierKind::Simulated, Case::Pending) => {
+            request = request.header("X-Simulation-Mode", "timeout");
+            memo.clone()
+        }
+        (VerifierKind::Simulated, Case::Underpaid) => {
+            request = request.header("X-Payment-Amount", "0.005");
+            memo.clone()
+        }
+        (VerifierKind::Simulated, Case::Paid) => memo.clone(),
+        (VerifierKind::Rpc, Case::Rejected) => format!("notfound-{}", memo),
+        (VerifierKind::Rpc, Case::Pending) => format!("pending-{}", memo),
+        (VerifierKind::Rpc, Case::Underpaid) => format!("short-{}", memo),
+        (VerifierKind::Rpc, Case::Paid) => memo.clone(),
+    };
+    let status = request
+        .header("X-Payment-Proof", proof)
+        .send()
+        .await
+        .unwrap()
+        .status();
+    (memo, status)
+}
+
+/// Outcome of the last logged payment event
+async fn last_outcome(base: &str) -> String {
+    let events: Value = reqwest::get(format!("{}/__admin/events", base))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let events = events["events"].as_array().unwrap();
+    events.last().unwrap()["outcome"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[actix_web::test]
+async fn test_verifiers_classify_the_same_way() {
+    // Given: one mock per verifier
+    let simulated = start_mock(VerifierKind::Simulated).await;
+    let rpc = start_mock(VerifierKind::Rpc).await;
+
+    let matrix = [
+        (Case::Paid, StatusCode::OK, "success"),
+        (Case::Rejected, StatusCode::PAYMENT_REQUIRED, "failure"),
+        (Case::Pending, StatusCode::REQUEST_TIMEOUT, "timeout"),
+        (
+            Case::Underpaid,
+            StatusCode::PAYMENT_REQUIRED,
+            "underpayment",
+        ),
+    ];
+    for (case, status, outcome) in matrix {
+        for (base, verifier) in [
+            (&simulated, VerifierKind::Simulated),
+            (&rpc, VerifierKind::Rpc),
+        ] {
+            // When: the same payment is sent to each
+            let (_, actual) = pay(base, verifier, case).await;
+
+            // Then: both answer and classify it alike
+            assert_eq!(actual, status, "{:?} with the {} verifier", case, verifier);
+            assert_eq!(
+                last_outcome(base).await,
+                outcome,
+                "{:?} with the {} verifier",
+                case,
+                verifier
+            );
+        }
+    }
+}
+
+#[actix_web::test]
+async fn test_verifiers_reject_replays_alike() {
+    for verifier in [VerifierKind::Simulated, VerifierKind::Rpc] {
+        // Given: a paid invoice
+        let base = start_mock(verifier).await;
+        let (memo, status) = pay(&base, verifier, Case::Paid).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // When: its proof is sent again
+        let replay = reqwest::Client::new()
+            .get(format!("{}/api/data", base))
+            .header("X-Payment-Proof", memo)
+            .send()
+            .await
+            .unwrap();
+
+        // Then: the replay is refused the same way
+        assert_eq!(replay.status(), StatusCode::CONFLICT, "{}", verifier);
+        assert_eq!(last_outcome(&base).await, "replayed", "{}", verifier);
+    }
+}
+
+#[actix_web::test]
+async fn test_rpc_verifier_ignores_simulation_mode() {
+    // Given: a mock verifying over RPC
+    let base = start_mock(VerifierKind::Rpc).await;
+
+    // When: the server's simulation mode is switched to failure
+    let switched = reqwest::Client::new()
+        .post(format!("{}/__admin/simulation-mode", base))
+        .json(&serde_json::json!({ "mode": "failure" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(switched.status().is_success());
+
+    // Then: settled transactions still verify
+    let (_, status) = pay(&base, VerifierKind::Rpc, Case::Paid).await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,243 @@
+// Webhook Integration Tests
+//
+// Drives the real payment handler and asserts that events reach a wiremock
+// receiver, signed, without delaying the 402 response.
+
+use actix_web::{http::StatusCode, test, web, App};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+use x402_server::handlers::payment_required_handler;
+use x402_server::webhooks::{EVENT_HEADER, SIGNATURE_HEADER};
+use x402_server::{
+    Config, EventType, InvoiceGenerator, PricingMatcher, WebhookConfig, WebhookDispatcher,
+};
+
+fn config() -> Config {
+    Config::builder().timeout_delay_ms(100).build().unwrap()
+}
+
+/// Poll until the receiver has seen `count` requests
+async fn wait_for_requests(receiver: &MockServer, count: usize) -> Vec<wiremock::Request> {
+    for _ in 0..100 {
+        let requests = receiver.received_requests().await.unwrap_or_default();
+        if requests.len() >= count {
+            return requests;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    receiver.received_requests().await.unwrap_or_default()
+}
+
+/// Test invoice.issued is delivered with the shared envelope and a valid signature
+#[actix_web::test]
+async fn test_invoice_issued_webhook_payload_and_signature() {
+    // Given: A receiver subscribed to invoice.issued with a secret
+    let receiver = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hooks"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&receiver)
+        .await;
+
+    let dispatcher = web::Data::new(WebhookDispatcher::start(vec![WebhookConfig {
+        url: format!("{}/hooks", receiver.uri()),
+        events: vec![EventType::InvoiceIssued],
+        secret: Some("s3cret".to_string()),
+        max_retries: 0,
+    }]));
+    let config = config();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+            .app_data(web::Data::new(InvoiceGenerator::new()))
+            .app_data(web::Data::new(config))
+            .app_data(dispatcher.clone())
+            .default_service(web::to(payment_required_handler)),
+    )
+    .await;
+
+    // When: A client requests a resource without payment
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    let www_auth = resp
+        .headers()
+        .get("www-authenticate")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    // Then: The receiver gets one signed invoice.issued event
+    let requests = wait_for_requests(&receiver, 1).await;
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+
+    let event: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(event["type"], "invoice.issued");
+    assert_eq!(event["path"], "/api/data");
+    assert_eq!(event["amount"], 0.01);
+    assert_eq!(event["outcome"], "payment_required");
+    assert!(event["request_id"].is_string());
+    let memo = event["memo"].as_str().unwrap();
+    assert!(www_auth.contains(&format!("memo={}", memo)));
+
+    assert_eq!(request.headers[EVENT_HEADER], "invoice.issued");
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, b"s3cret");
+    let expected: String = ring::hmac::sign(&key, &request.body)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    assert_eq!(
+        request.headers[SIGNATURE_HEADER].to_str().unwrap(),
+        format!("sha256={}", expected)
+    );
+    assert_eq!(dispatcher.stats().delivered.load(Ordering::Relaxed), 1);
+}
+
+/// Test a failing receiver is retried with backoff without slowing the 402
+#[actix_web::test]
+async fn test_failing_webhook_retries_without_blocking() {
+    // Given: A receiver that always returns 503
+    let receiver = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .mount(&receiver)
+        .await;
+
+    let dispatcher = web::Data::new(WebhookDispatcher::start(vec![WebhookConfig {
+        url: receiver.uri(),
+        events: vec![EventType::InvoiceIssued],
+        secret: None,
+        max_retries: 2,
+    }]));
+    let config = config();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(PricingMatcher::new(config.pricing.clone())))
+            .app_data(web::Data::new(InvoiceGenerator::new()))
+            .app_data(web::Data::new(config))
+            .app_data(dispatcher.clone())
+            .default_service(web::to(payment_required_handler)),
+    )
+    .await;
+
+    // When: A client requests a resource
+    let start = Instant::now();
+    let req = test::TestRequest::get().uri("/api/data").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    // Then: The 402 returns before any retry backoff elapses
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    assert!(start.elapsed() < Duration::from_millis(100));
+
+    // And: The receiver sees the first attempt plus two retries
+    let requests = wait_for_requests(&receiver, 3).await;
+    assert_eq!(requests.len(), 3);
+    for _ in 0..50 {
+        if dispatcher.stats().failed.load(Ordering::Relaxed) == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(dispatcher.stats().attempts.load(Ordering::Relaxed), 3);
+    assert_eq!(dispatcher.stats().failed.load(Ordering::Relaxed), 1);
+}
+
+/// Test a full queue drops and counts events instead of blocking
+#[actix_web::test]
+async fn test_full_queue_drops_events() {
+    let dispatcher = WebhookDispatcher::with_capacity(
+        vec![WebhookConfig {
+            url: "http://127.0.0.1:1/unreachable".to_string(),
+            events: vec![EventType::InvoiceIssued],
+            secret: None,
+            max_retries: 0,
+        }],
+        1,
+    );
+
+    // The worker hasn't run yet on this single-threaded runtime, so the
+    // second event finds the queue full
+    for _ in 0..3 {
+        dispatcher.emit(x402_server::WebhookEvent::new(
+            EventType::InvoiceIssued,
+            "req",
+            None,
+            "/api",
+            0.01,
+            "payment_required",
+        ));
+    }
+
+    assert_eq!(dispatcher.stats().dropped.load(Ordering::Relaxed), 2);
+}
+
+/// Test a slow receiver limits deliveries in flight and drops the excess
+#[actix_web::test]
+async fn test_slow_receiver_drops_events_beyond_capacity() {
+    // Given: A receiver that takes a second to answer, and room for two events
+    let receiver = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(1)))
+        .mount(&receiver)
+        .await;
+    let dispatcher = WebhookDispatcher::with_capacity(
+        vec![WebhookConfig {
+            url: receiver.uri(),
+            events: vec![EventType::InvoiceIssued],
+            secret: None,
+            max_retries: 0,
+        }],
+        2,
+    );
+    let emit = || {
+        dispatcher.emit(x402_server::WebhookEvent::new(
+            EventType::InvoiceIssued,
+            "req",
+            None,
+            "/api",
+            0.01,
+            "payment_required",
+        ))
+    };
+
+    // When: Two events are being delivered and three more arrive
+    emit();
+    emit();
+    wait_for_requests(&receiver, 2).await;
+    for _ in 0..3 {
+        emit();
+    }
+
+    // Then: The three are dropped instead of piling up behind the receiver
+    assert_eq!(dispatcher.stats().dropped.load(Ordering::Relaxed), 3);
+    // And: Once the deliveries finish there is room again
+    for _ in 0..100 {
+        if dispatcher.stats().delivered.load(Ordering::Relaxed) == 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    emit();
+    assert_eq!(dispatcher.stats().dropped.load(Ordering::Relaxed), 3);
+}
+
+/// Test webhook config parses with dotted event names
+#[actix_web::test]
+async fn test_webhook_config_event_names() {
+    let config: WebhookConfig = serde_json::from_value(serde_json::json!({
+        "url": "http://localhost:9000/hooks",
+        "events": ["invoice.issued", "payment.failed"],
+        "secret": "abc"
+    }))
+    .unwrap();
+    assert_eq!(
+        config.events,
+        vec![EventType::InvoiceIssued, EventType::PaymentFailed]
+    );
+    assert_eq!(config.max_retries, 3);
+}
@@ -0,0 +1,576 @@
+// The local quality gate: `cargo xtask ci`
+//
+// Runs the checks CI runs, as stages of child cargo processes: the build,
+// clippy, unit tests, integration tests grouped by crate, the codegen golden
+// files, the JSON Schemas, the public API snapshot, policy conformance and
+// the fuzz smoke run. Like CI, the build, clippy and test stages enable all
+// features, so `signing` and `keyring` code is checked too. A stage starts after the stages it depends on, and is
+// skipped when one of them failed. Output is captured and printed only for
+// failed stages (everything streams with --verbose); a table of results and
+// timings ends the run.
+//
+// --only and --skip select stages, --offline skips the integration groups
+// that start servers and talk HTTP to them and passes --offline to cargo,
+// and --dry-run prints the plan without running anything.
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// A step of the gate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stage {
+    pub name: &'static str,
+    /// Arguments of the `cargo` child process, separated by spaces
+    pub cargo: &'static str,
+    /// Stages that must pass first when they run too
+    pub after: &'static [&'static str],
+    /// Starts servers and connects to them, so --offline skips it
+    pub network: bool,
+}
+
+const fn stage(name: &'static str, cargo: &'static str, after: &'static [&'static str]) -> Stage {
+    Stage {
+        name,
+        cargo,
+        after,
+        network: false,
+    }
+}
+
+const fn network(mut stage: Stage) -> Stage {
+    stage.network = true;
+    stage
+}
+
+/// Every stage, in the order they run when nothing else decides
+pub const STAGES: &[Stage] = &[
+    stage(
+        "build",
+        "build --workspace --all-targets --all-features",
+        &[],
+    ),
+    stage(
+        "clippy",
+        "clippy --workspace --all-targets --all-features -- -D warnings",
+        &["build"],
+    ),
+    stage(
+        "unit",
+        "test --workspace --lib --bins --all-features",
+        &["build"],
+    ),
+    stage(
+        "integration-core",
+        "test -p x402-core -p x402-domain --test * --all-features",
+        &["unit"],
+    ),
+    network(stage(
+        "integration-server",
+        "test -p x402-server --test * --all-features",
+        &["unit"],
+    )),
+    network(stage(
+        "integration-cli",
+        "test -p x402-cli --test * --all-features",
+        &["unit"],
+    )),
+    network(stage(
+        "integration-mcp",
+        "test -p x402-mcp-server --test * --all-features",
+        &["unit"],
+    )),
+    stage(
+        "codegen",
+        "test -p x402-core --test codegen_golden",
+        &["build"],
+    ),
+    stage("schema", "run --quiet -p xtask -- schema", &["build"]),
+    stage(
+        "public-api",
+        "run --quiet -p xtask -- public-api",
+        &["build"],
+    ),
+    stage(
+        "policy-conformance",
+        "run --quiet -p xtask -- policy-conformance",
+        &["codegen"],
+    ),
+    stage(
+        "fuzz",
+        "run --quiet -p xtask -- fuzz-smoke --seconds 2",
+        &["build"],
+    ),
+];
+
+/// Command line of `cargo xtask ci`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    pub only: Vec<String>,
+    pub skip: Vec<String>,
+    pub offline: bool,
+    pub verbose: bool,
+    pub dry_run: bool,
+}
+
+impl Options {
+    /// `--only` and `--skip` take a stage or a comma-separated list and
+    /// may be repeated
+    pub fn parse(args: &[&str]) -> Result<Self> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match *arg {
+                "--only" | "--skip" => {
+                    let value = args
+                        .next()
+                        .with_context(|| format!("{} needs a stage name", arg))?;
+                    let names = value.split(',').map(str::trim).map(String::from);
+                    if *arg == "--only" {
+                        options.only.extend(names);
+                    } else {
+                        options.skip.extend(names);
+                    }
+                }
+                "--offline" => options.offline = true,
+                "--verbose" | "-v" => options.verbose = true,
+                "--dry-run" => options.dry_run = true,
+                other => bail!("Unknown ci option: {}", other),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// What the plan does with a stage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Planned {
+    Run,
+    /// Left out by --skip or --offline
+    Skip(&'static str),
+}
+
+/// Order `stages` so each runs after the stages it depends on, and decide
+/// which of them run
+///
+/// Stages left out by --only are not part of the plan at all. Dependencies
+/// are not pulled in: cargo builds what a stage needs anyway, so they only
+/// order the stages and hold back dependents of a failure.
+pub fn plan<'a>(stages: &'a [Stage], options: &Options) -> Result<Vec<(&'a Stage, Planned)>> {
+    for name in options.only.iter().chain(&options.skip) {
+        if !stages.iter().any(|stage| stage.name == name) {
+            let names: Vec<_> = stages.iter().map(|stage| stage.name).collect();
+            bail!(
+                "Unknown stage: {}\nFix: Use one of {}",
+                name,
+                names.join(", ")
+            );
+        }
+    }
+
+    let mut planned = Vec::with_capacity(stages.len());
+    for stage in ordered(stages)? {
+        if !options.only.is_empty() && !options.only.iter().any(|name| name == stage.name) {
+            continue;
+        }
+        let decision = if options.skip.iter().any(|name| name == stage.name) {
+            Planned::Skip("--skip")
+        } else if options.offline && stage.network {
+            Planned::Skip("--offline")
+        } else {
+            Planned::Run
+        };
+        planned.push((stage, decision));
+    }
+    Ok(planned)
+}
+
+/// Dependencies first, otherwise in registry order
+fn ordered(stages: &[Stage]) -> Result<Vec<&Stage>> {
+    for stage in stages {
+        for dependency in stage.after {
+            if !stages.iter().any(|other| other.name == *dependency) {
+                bail!(
+                    "Stage {} runs after unknown stage {}",
+                    stage.name,
+                    dependency
+                );
+            }
+        }
+    }
+    let mut order: Vec<&Stage> = Vec::with_capacity(stages.len());
+    while order.len() < stages.len() {
+        let ready = stages.iter().find(|stage| {
+            !order.iter().any(|done| done.name == stage.name)
+                && stage
+                    .after
+                    .iter()
+                    .all(|dependency| order.iter().any(|done| done.name == *dependency))
+        });
+        match ready {
+            Some(stage) => order.push(stage),
+            None => {
+                let waiting: Vec<_> = stages
+                    .iter()
+                    .filter(|stage| !order.iter().any(|done| done.name == stage.name))
+                    .map(|stage| stage.name)
+                    .collect();
+                bail!("Stage dependencies form a cycle: {}", waiting.join(", "));
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// How a stage ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    Skipped(String),
+}
+
+/// A stage's outcome and how long it ran
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageResult {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    pub elapsed: Duration,
+}
+
+/// Run the planned stages in order with `run_stage`, which reports whether
+/// a stage passed
+///
+/// A stage after a failed or held-back stage is skipped.
+pub fn execute(
+    planned: &[(&Stage, Planned)],
+    mut run_stage: impl FnMut(&Stage) -> Result<bool>,
+) -> Result<Vec<StageResult>> {
+    let mut results = Vec::with_capacity(planned.len());
+    // Stages that failed or were held back themselves
+    let mut held: Vec<&str> = Vec::new();
+    for (stage, decision) in planned {
+        let blocked_by = stage
+            .after
+            .iter()
+            .find(|dependency| held.contains(dependency));
+        let (outcome, elapsed) = match (decision, blocked_by) {
+            (Planned::Skip(reason), _) => (Outcome::Skipped(reason.to_string()), Duration::ZERO),
+            (Planned::Run, Some(dependency)) => {
+                held.push(stage.name);
+                (
+                    Outcome::Skipped(format!("after {} failed", dependency)),
+                    Duration::ZERO,
+                )
+            }
+            (Planned::Run, None) => {
+                let started = Instant::now();
+                let passed = run_stage(stage)?;
+                let outcome = if passed {
+                    Outcome::Passed
+                } else {
+                    held.push(stage.name);
+                    Outcome::Failed
+                };
+                (outcome, started.elapsed())
+            }
+        };
+        results.push(StageResult {
+            name: stage.name,
+            outcome,
+            elapsed,
+        });
+    }
+    Ok(results)
+}
+
+/// Run `cargo xtask ci`
+pub fn run(root: &Path, args: &[&str]) -> Result<bool> {
+    let options = Options::parse(args)?;
+    let planned = plan(STAGES, &options)?;
+
+    if options.dry_run {
+        print_plan(&planned, options.offline);
+        return Ok(true);
+    }
+
+    let started = Instant::now();
+    let results = execute(&planned, |stage| {
+        run_cargo(root, stage, options.offline, options.verbose)
+    })?;
+    print_summary(&results, started.elapsed());
+    Ok(!results
+        .iter()
+        .any(|result| result.outcome == Outcome::Failed))
+}
+
+/// Arguments of a stage's cargo process
+fn cargo_args(stage: &Stage, offline: bool) -> Vec<&'static str> {
+    let mut args: Vec<_> = stage.cargo.split_whitespace().collect();
+    if offline {
+        // After the subcommand, before any `--`
+        args.insert(1, "--offline");
+    }
+    args
+}
+
+/// Run one stage, printing its output when it fails (or as it runs with
+/// `verbose`)
+fn run_cargo(root: &Path, stage: &Stage, offline: bool, verbose: bool) -> Result<bool> {
+    let args = cargo_args(stage, offline);
+    println!("▶ {:<20} cargo {}", stage.name, args.join(" "));
+    let mut command = Command::new("cargo");
+    command.current_dir(root).args(&args);
+
+    if verbose {
+        let status = command.status().context("Failed to run cargo")?;
+        return Ok(status.success());
+    }
+    let output = command.output().context("Failed to run cargo")?;
+    if !output.status.success() {
+        println!("\n── {} output ──", stage.name);
+        print!("{}", String::from_utf8_lossy(&output.stdout));
+        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        println!("── end of {} output ──\n", stage.name);
+    }
+    Ok(output.status.success())
+}
+
+fn print_plan(planned: &[(&Stage, Planned)], offline: bool) {
+    println!("Plan ({} stages):", planned.len());
+    for (stage, decision) in planned {
+        match decision {
+            Planned::Run => {
+                let after: Vec<_> = stage
+                    .after
+                    .iter()
+                    .filter(|dependency| {
+                        planned.iter().any(|(other, _)| other.name == **dependency)
+                    })
+                    .copied()
+                    .collect();
+                print!(
+                    "  {:<20} cargo {}",
+                    stage.name,
+                    cargo_args(stage, offline).join(" ")
+                );
+                if !after.is_empty() {
+                    print!("  (after {})", after.join(", "));
+                }
+                println!();
+            }
+            Planned::Skip(reason) => println!("  {:<20} skipped ({})", stage.name, reason),
+        }
+    }
+}
+
+fn print_summary(results: &[StageResult], total: Duration) {
+    println!("\n{:<20}  {:<7}  Time", "Stage", "Result");
+    let (mut passed, mut failed, mut skipped) = (0, 0, 0);
+    for result in results {
+        let (label, detail) = match &result.outcome {
+            Outcome::Passed => {
+                passed += 1;
+                ("pass", format_duration(result.elapsed))
+            }
+            Outcome::Failed => {
+                failed += 1;
+                ("FAIL", format_duration(result.elapsed))
+            }
+            Outcome::Skipped(reason) => {
+                skipped += 1;
+                ("skip", reason.clone())
+            }
+        };
+        println!("{:<20}  {:<7}  {}", result.name, label, detail);
+    }
+    println!(
+        "\n{} passed, {} failed, {} skipped in {}",
+        passed,
+        failed,
+        skipped,
+        format_duration(total)
+    );
+}
+
+/// `4.2s` under a minute, `3m 07s` above
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(planned: &[(&Stage, Planned)]) -> Vec<&'static str> {
+        planned.iter().map(|(stage, _)| stage.name).collect()
+    }
+
+    fn options(args: &[&str]) -> Options {
+        Options::parse(args).unwrap()
+    }
+
+    #[test]
+    fn test_registry_plans_every_stage_after_its_dependencies() {
+        let planned = plan(STAGES, &Options::default()).unwrap();
+        assert_eq!(planned.len(), STAGES.len());
+        assert_eq!(planned[0].0.name, "build");
+        for (i, (stage, decision)) in planned.iter().enumerate() {
+            assert_eq!(*decision, Planned::Run);
+            for dependency in stage.after {
+                let position = names(&planned).iter().position(|n| n == dependency);
+                assert!(
+                    position.unwrap() < i,
+                    "{} before {}",
+                    dependency,
+                    stage.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_dependencies_reorder_stages() {
+        let stages = [
+            stage("test", "test", &["build"]),
+            stage("lint", "clippy", &[]),
+            stage("build", "build", &[]),
+        ];
+        let planned = plan(&stages, &Options::default()).unwrap();
+        assert_eq!(names(&planned), ["lint", "build", "test"]);
+    }
+
+    #[test]
+    fn test_cycles_and_unknown_dependencies_are_rejected() {
+        let cycle = [stage("a", "", &["b"]), stage("b", "", &["a"])];
+        let err = plan(&cycle, &Options::default()).unwrap_err();
+        assert!(err.to_string().contains("cycle: a, b"), "{}", err);
+
+        let dangling = [stage("a", "", &["missing"])];
+        assert!(plan(&dangling, &Options::default()).is_err());
+    }
+
+    #[test]
+    fn test_only_and_skip_select_stages() {
+        let planned = plan(
+            STAGES,
+            &options(&["--only", "unit,codegen", "--only", "build"]),
+        )
+        .unwrap();
+        assert_eq!(names(&planned), ["build", "unit", "codegen"]);
+
+        let planned = plan(STAGES, &options(&["--skip", "fuzz", "--skip", "clippy"])).unwrap();
+        assert_eq!(planned.len(), STAGES.len());
+        for (stage, decision) in &planned {
+            let expected = match stage.name {
+                "fuzz" | "clippy" => Planned::Skip("--skip"),
+                _ => Planned::Run,
+            };
+            assert_eq!(*decision, expected, "{}", stage.name);
+        }
+
+        // --skip wins over --only
+        let planned = plan(STAGES, &options(&["--only", "unit", "--skip", "unit"])).unwrap();
+        assert_eq!(planned, [(&STAGES[2], Planned::Skip("--skip"))]);
+    }
+
+    #[test]
+    fn test_unknown_stage_is_rejected() {
+        let err = plan(STAGES, &options(&["--only", "units"])).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Unknown stage: units"), "{}", message);
+        assert!(
+            message.contains("Fix: Use one of build, clippy, unit"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_offline_skips_network_stages() {
+        let planned = plan(STAGES, &options(&["--offline"])).unwrap();
+        let skipped: Vec<_> = planned
+            .iter()
+            .filter(|(_, decision)| *decision == Planned::Skip("--offline"))
+            .map(|(stage, _)| stage.name)
+            .collect();
+        assert_eq!(
+            skipped,
+            ["integration-server", "integration-cli", "integration-mcp"]
+        );
+        assert_eq!(
+            cargo_args(&STAGES[1], true),
+            [
+                "clippy",
+                "--offline",
+                "--workspace",
+                "--all-targets",
+                "--all-features",
+                "--",
+                "-D",
+                "warnings"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failure_holds_back_dependents() {
+        let stages = [
+            stage("build", "", &[]),
+            stage("unit", "", &["build"]),
+            stage("integration", "", &["unit"]),
+            stage("schema", "", &["build"]),
+            stage("lint", "", &[]),
+        ];
+        let planned = plan(&stages, &options(&["--skip", "schema"])).unwrap();
+        let mut ran = Vec::new();
+        let results = execute(&planned, |stage| {
+            ran.push(stage.name);
+            Ok(stage.name != "unit")
+        })
+        .unwrap();
+
+        assert_eq!(ran, ["build", "unit", "lint"]);
+        let outcomes: Vec<_> = results
+            .iter()
+            .map(|r| (r.name, r.outcome.clone()))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("build", Outcome::Passed),
+                ("unit", Outcome::Failed),
+                ("integration", Outcome::Skipped("after unit failed".into())),
+                ("schema", Outcome::Skipped("--skip".into())),
+                ("lint", Outcome::Passed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        assert_eq!(
+            options(&["--offline", "-v", "--dry-run"]),
+            Options {
+                offline: true,
+                verbose: true,
+                dry_run: true,
+                ..Options::default()
+            }
+        );
+        assert!(Options::parse(&["--only"]).is_err());
+        assert!(Options::parse(&["--fast"]).is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(4200)), "4.2s");
+        assert_eq!(format_duration(Duration::from_secs(187)), "3m 07s");
+    }
+}
@@ -0,0 +1,101 @@
+// Golden files of the policy code generators
+//
+// x402-core's codegen_golden test renders the fixture policies in
+// crates/x402-core/tests/codegen/fixtures/ with each generator and compares
+// the output with crates/x402-core/tests/codegen/golden/. `cargo xtask
+// bless-codegen` reruns that test with X402_BLESS_CODEGEN=1, which makes it
+// rewrite the golden files, and prints what changed for review.
+
+use anyhow::{bail, Context, Result};
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::process::Command;
+
+const GOLDEN_DIR: &str = "crates/x402-core/tests/codegen/golden";
+
+/// Read by the golden test: rewrite the files instead of comparing
+const BLESS_VAR: &str = "X402_BLESS_CODEGEN";
+
+/// Rewrite the golden files and print a summary of the changes
+pub fn bless(root: &Path) -> Result<bool> {
+    let dir = root.join(GOLDEN_DIR);
+    let before = read_golden(&dir)?;
+
+    let status = Command::new("cargo")
+        .current_dir(root)
+        .args(["test", "--package", "x402-core", "--test", "codegen_golden"])
+        .env(BLESS_VAR, "1")
+        .status()
+        .context("Failed to run cargo test")?;
+    if !status.success() {
+        bail!("The codegen golden test failed while blessing");
+    }
+
+    let after = read_golden(&dir)?;
+    let files: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut unchanged = 0;
+    println!();
+    for file in files {
+        match (before.get(file), after.get(file)) {
+            (Some(old), Some(new)) if old == new => unchanged += 1,
+            (Some(old), Some(new)) => {
+                let (added, removed) = line_changes(old, new);
+                println!("  updated  {} (+{} -{})", file, added, removed);
+            }
+            (None, Some(new)) => println!("  new      {} ({} lines)", file, new.lines().count()),
+            (Some(_), None) => println!("  removed  {}", file),
+            (None, None) => unreachable!(),
+        }
+    }
+    println!("  {} unchanged", unchanged);
+    println!(
+        "\nReview the changes with `git diff {}` and commit them",
+        GOLDEN_DIR
+    );
+    Ok(true)
+}
+
+/// Golden file contents by file name
+fn read_golden(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for entry in
+        std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?
+    {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        files.insert(name, content);
+    }
+    Ok(files)
+}
+
+/// Lines added and removed between two versions of a file
+fn line_changes(old: &str, new: &str) -> (usize, usize) {
+    let diff = TextDiff::from_lines(old, new);
+    let mut changes = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => changes.0 += 1,
+            ChangeTag::Delete => changes.1 += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_changes_counts_edits() {
+        assert_eq!(line_changes("a\nb\nc\n", "a\nb\nc\n"), (0, 0));
+        assert_eq!(line_changes("a\nb\nc\n", "a\nB\nc\nd\n"), (2, 1));
+    }
+}
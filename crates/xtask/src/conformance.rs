@@ -0,0 +1,607 @@
+// Conformance of the generated policy middleware with PolicyEngine
+//
+// Each fixture in crates/xtask/conformance/ is a simple-schema policy file
+// plus a request sequence. The policy is rendered with the Express and
+// Fastify generators, and conformance_driver.js feeds the sequence to each
+// artifact in Node on a fake clock. The same sequence goes to a
+// PolicyEngine built from `PolicyFile::to_runtime`, the policies `mock
+// --policy` enforces. Responses are reduced to outcomes (allowed, denied,
+// rate_limited, ...) and compared request by request. On a divergence the
+// sequence is shrunk by dropping requests while it still diverges, and the
+// minimal sequence is printed and written out for replay.
+//
+// Without `node` on PATH the task prints a warning and succeeds.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, UNIX_EPOCH};
+use x402_core::policy::{
+    generate_express_middleware, generate_fastify_plugin, parse_policy_yaml, LoadedPolicyFile,
+    PolicyDecision, PolicyEngine, PolicyFile, QuotaHeaders, Request, RuntimePolicy,
+    RATE_LIMIT_EXCEEDED, SPENDING_CAP_EXCEEDED,
+};
+
+const FIXTURE_DIR: &str = "crates/xtask/conformance";
+
+/// Artifacts, driver and minimal sequences are kept here for inspection
+const OUT_DIR: &str = "target/policy-conformance";
+
+const DRIVER: &str = include_str!("conformance_driver.js");
+
+/// Amount the generated Express middleware charges per request
+const REQUEST_AMOUNT: f64 = 0.01;
+
+/// Clock of a fixture's first request by default: 2026-01-01T00:00:00Z
+const DEFAULT_START_MS: u64 = 1_767_225_600_000;
+
+const DEFAULT_ENDPOINT: &str = "/api/data";
+
+/// Fixture file: a policy and the requests sent to it
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Fixture {
+    /// Simple-schema policy file, as `policy generate` reads it
+    policy: serde_yaml::Value,
+    /// Clock of `at_ms: 0` in milliseconds since the epoch
+    #[serde(default = "default_start_ms")]
+    start_ms: u64,
+    requests: Vec<RequestSpec>,
+}
+
+fn default_start_ms() -> u64 {
+    DEFAULT_START_MS
+}
+
+/// `count` requests of one agent, `every_ms` apart
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RequestSpec {
+    agent: String,
+    #[serde(default = "default_endpoint")]
+    endpoint: String,
+    /// Milliseconds after the fixture's start
+    at_ms: u64,
+    #[serde(default = "default_count")]
+    count: u32,
+    #[serde(default)]
+    every_ms: u64,
+}
+
+fn default_endpoint() -> String {
+    DEFAULT_ENDPOINT.to_string()
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// One request, as the driver reads it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct DriverRequest {
+    /// Milliseconds since the epoch
+    at_ms: u64,
+    agent: String,
+    endpoint: String,
+    amount: f64,
+}
+
+/// One response, as the driver writes it
+#[derive(Debug, Deserialize)]
+struct DriverResponse {
+    #[serde(default)]
+    status: Option<u16>,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    thrown: Option<String>,
+    #[serde(default)]
+    next: bool,
+}
+
+/// What a request got, comparable across implementations
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Allowed,
+    Denied,
+    RateLimited,
+    SpendingCapped,
+    ConcurrencyLimited,
+    /// Errors and responses no policy produces
+    Other(String),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Allowed => write!(f, "allowed"),
+            Outcome::Denied => write!(f, "denied"),
+            Outcome::RateLimited => write!(f, "rate_limited"),
+            Outcome::SpendingCapped => write!(f, "spending_capped"),
+            Outcome::ConcurrencyLimited => write!(f, "concurrency_limited"),
+            Outcome::Other(what) => write!(f, "{}", what),
+        }
+    }
+}
+
+impl From<DriverResponse> for Outcome {
+    fn from(response: DriverResponse) -> Self {
+        if let Some(thrown) = response.thrown {
+            return Outcome::Other(format!("threw: {}", thrown));
+        }
+        if response.next {
+            return Outcome::Other("passed to next handler".to_string());
+        }
+        match (response.status, response.error.as_deref()) {
+            (Some(402), Some("Payment Required")) => Outcome::Allowed,
+            (_, Some("Rate limit exceeded")) => Outcome::RateLimited,
+            (_, Some("Spending cap exceeded")) => Outcome::SpendingCapped,
+            (_, Some("Too many concurrent requests")) => Outcome::ConcurrencyLimited,
+            (Some(403), _) => Outcome::Denied,
+            (status, error) => Outcome::Other(format!(
+                "{} {}",
+                status.map_or("-".to_string(), |s| s.to_string()),
+                error.unwrap_or("-")
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Framework {
+    Express,
+    Fastify,
+}
+
+impl Framework {
+    const ALL: [Framework; 2] = [Framework::Express, Framework::Fastify];
+
+    fn name(self) -> &'static str {
+        match self {
+            Framework::Express => "express",
+            Framework::Fastify => "fastify",
+        }
+    }
+
+    fn generate(self, policy: &PolicyFile, file_name: &str) -> String {
+        let headers = QuotaHeaders::default();
+        match self {
+            Framework::Express => generate_express_middleware(policy, file_name, &headers),
+            Framework::Fastify => {
+                generate_fastify_plugin(&policy.policies, Some(file_name), &headers)
+            }
+        }
+    }
+}
+
+/// A fixture ready to run
+struct Case {
+    name: String,
+    start_ms: u64,
+    policy: PolicyFile,
+    runtime: Vec<RuntimePolicy>,
+    requests: Vec<DriverRequest>,
+}
+
+/// Runs artifacts of one case in Node
+struct Runner<'a> {
+    node: &'a str,
+    out_dir: &'a Path,
+}
+
+/// Run every fixture (or those named in `only`) and report divergences
+pub fn run(root: &Path, only: &[&str]) -> Result<bool> {
+    let node = "node";
+    let version = match Command::new(node).arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        _ => {
+            eprintln!("warning: `node` not found on PATH; skipping policy conformance");
+            return Ok(true);
+        }
+    };
+
+    let cases = load_cases(&root.join(FIXTURE_DIR), only)?;
+    let out_dir = root.join(OUT_DIR);
+    std::fs::create_dir_all(&out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    std::fs::write(out_dir.join("driver.js"), DRIVER).context("Failed to write driver.js")?;
+    let runner = Runner {
+        node,
+        out_dir: &out_dir,
+    };
+
+    println!(
+        "Policy conformance: {} fixtures, node {}\n",
+        cases.len(),
+        version
+    );
+    let width = cases.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    let mut reports = Vec::new();
+    for case in &cases {
+        let engine = engine_outcomes(&case.runtime, &case.requests);
+        for framework in Framework::ALL {
+            let artifact = runner.write_artifact(case, framework)?;
+            let generated = runner.outcomes(&artifact, framework, &case.requests)?;
+            match first_divergence(&engine, &generated) {
+                None => println!(
+                    "  {:<width$}  {:<7}  ok         {} requests",
+                    case.name,
+                    framework.name(),
+                    case.requests.len(),
+                    width = width
+                ),
+                Some(index) => {
+                    println!(
+                        "  {:<width$}  {:<7}  DIVERGES   at request {} of {}",
+                        case.name,
+                        framework.name(),
+                        index + 1,
+                        case.requests.len(),
+                        width = width
+                    );
+                    let minimal = runner.shrink(case, &artifact, framework, index)?;
+                    reports.push(runner.report(case, &artifact, framework, &minimal)?);
+                }
+            }
+        }
+    }
+
+    if reports.is_empty() {
+        println!("\nGenerated middleware matches PolicyEngine on every fixture");
+        return Ok(true);
+    }
+    for report in &reports {
+        println!("\n{}", report);
+    }
+    println!(
+        "{} of {} runs diverge from PolicyEngine",
+        reports.len(),
+        cases.len() * Framework::ALL.len()
+    );
+    Ok(false)
+}
+
+fn load_cases(dir: &Path, only: &[&str]) -> Result<Vec<Case>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    paths.retain(|path| path.extension().is_some_and(|ext| ext == "yaml"));
+    paths.sort();
+
+    let mut cases = Vec::new();
+    for path in paths {
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        if !only.is_empty() && !only.contains(&name.as_str()) {
+            continue;
+        }
+        cases.push(load_case(&path, name).with_context(|| format!("Fixture {}", path.display()))?);
+    }
+    if let Some(missing) = only
+        .iter()
+        .find(|name| !cases.iter().any(|case| case.name == **name))
+    {
+        bail!("No fixture {}/{}.yaml", FIXTURE_DIR, missing);
+    }
+    Ok(cases)
+}
+
+fn load_case(path: &Path, name: String) -> Result<Case> {
+    let content = std::fs::read_to_string(path)?;
+    let fixture: Fixture = serde_yaml::from_str(&content)?;
+    let policy = match parse_policy_yaml(&serde_yaml::to_string(&fixture.policy)?)? {
+        LoadedPolicyFile::Simple(policy) => policy,
+        LoadedPolicyFile::Runtime(_) => {
+            bail!("`policy` must use the simple schema, the one the generators read")
+        }
+    };
+    let runtime = policy.to_runtime()?.policies;
+
+    let mut requests: Vec<DriverRequest> = fixture
+        .requests
+        .iter()
+        .flat_map(|spec| {
+            (0..u64::from(spec.count)).map(move |i| DriverRequest {
+                at_ms: fixture.start_ms + spec.at_ms + i * spec.every_ms,
+                agent: spec.agent.clone(),
+                endpoint: spec.endpoint.clone(),
+                amount: REQUEST_AMOUNT,
+            })
+        })
+        .collect();
+    // Stable, so requests at the same time keep the fixture's order
+    requests.sort_by_key(|request| request.at_ms);
+
+    Ok(Case {
+        name,
+        start_ms: fixture.start_ms,
+        policy,
+        runtime,
+        requests,
+    })
+}
+
+/// Decisions of a fresh PolicyEngine for `requests`
+fn engine_outcomes(policies: &[RuntimePolicy], requests: &[DriverRequest]) -> Vec<Outcome> {
+    let engine = PolicyEngine::new(policies.to_vec());
+    requests
+        .iter()
+        .map(|request| {
+            let request = Request {
+                agent_id: request.agent.clone(),
+                wallet_address: None,
+                ip_address: None,
+                endpoint: request.endpoint.clone(),
+                amount: request.amount,
+                timestamp: UNIX_EPOCH + Duration::from_millis(request.at_ms),
+                memo_id: None,
+                request_id: None,
+            };
+            // The in-flight slot is released when the decision drops, as
+            // the server does once the response is sent
+            match engine.evaluate(&request) {
+                Ok(PolicyDecision::Allow { .. }) => Outcome::Allowed,
+                Ok(PolicyDecision::Deny { reason, .. }) => {
+                    if reason.starts_with(RATE_LIMIT_EXCEEDED) {
+                        Outcome::RateLimited
+                    } else if reason.starts_with(SPENDING_CAP_EXCEEDED) {
+                        Outcome::SpendingCapped
+                    } else if reason.starts_with("Concurrency limit exceeded") {
+                        Outcome::ConcurrencyLimited
+                    } else {
+                        Outcome::Denied
+                    }
+                }
+                Ok(decision) => Outcome::Other(format!("{:?}", decision)),
+                Err(e) => Outcome::Other(format!("error: {:#}", e)),
+            }
+        })
+        .collect()
+}
+
+fn first_divergence(engine: &[Outcome], generated: &[Outcome]) -> Option<usize> {
+    engine
+        .iter()
+        .zip(generated)
+        .position(|(a, b)| a != b)
+        .or_else(|| (engine.len() != generated.len()).then(|| engine.len().min(generated.len())))
+}
+
+impl Runner<'_> {
+    fn write_artifact(&self, case: &Case, framework: Framework) -> Result<PathBuf> {
+        let path = self
+            .out_dir
+            .join(format!("{}.{}.js", case.name, framework.name()));
+        let code = framework.generate(&case.policy, &format!("{}.yaml", case.name));
+        std::fs::write(&path, code)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Outcomes of `requests` sent to a fresh instance of the artifact
+    fn outcomes(
+        &self,
+        artifact: &Path,
+        framework: Framework,
+        requests: &[DriverRequest],
+    ) -> Result<Vec<Outcome>> {
+        let mut child = Command::new(self.node)
+            .arg(self.out_dir.join("driver.js"))
+            .arg(framework.name())
+            .arg(artifact)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to start node")?;
+        let input = json_lines(requests)?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        // Written from a thread so a full stdout pipe cannot block us
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+        let output = child.wait_with_output().context("Failed to run node")?;
+        writer
+            .join()
+            .expect("stdin writer panicked")
+            .context("Failed to send requests to node")?;
+        if !output.status.success() {
+            bail!(
+                "node {} {} failed:\n{}",
+                framework.name(),
+                artifact.display(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<DriverResponse>(line)
+                    .map(Outcome::from)
+                    .with_context(|| format!("Invalid driver response: {}", line))
+            })
+            .collect()
+    }
+
+    fn divergence(
+        &self,
+        case: &Case,
+        artifact: &Path,
+        framework: Framework,
+        requests: &[DriverRequest],
+    ) -> Result<Option<usize>> {
+        let engine = engine_outcomes(&case.runtime, requests);
+        let generated = self.outcomes(artifact, framework, requests)?;
+        Ok(first_divergence(&engine, &generated))
+    }
+
+    /// Shortest subsequence found that still diverges
+    ///
+    /// Starts from the requests up to the first divergence and drops one
+    /// request at a time, latest first, keeping every drop after which the
+    /// sequence still diverges, until no single drop does.
+    fn shrink(
+        &self,
+        case: &Case,
+        artifact: &Path,
+        framework: Framework,
+        first: usize,
+    ) -> Result<Vec<DriverRequest>> {
+        let mut current = case.requests[..=first].to_vec();
+        loop {
+            let mut shrunk = false;
+            for i in (0..current.len().saturating_sub(1)).rev() {
+                if i + 1 >= current.len() {
+                    continue;
+                }
+                let mut candidate = current.clone();
+                candidate.remove(i);
+                if let Some(index) = self.divergence(case, artifact, framework, &candidate)? {
+                    candidate.truncate(index + 1);
+                    current = candidate;
+                    shrunk = true;
+                }
+            }
+            if !shrunk {
+                return Ok(current);
+            }
+        }
+    }
+
+    /// Side-by-side table of the minimal sequence, with replay instructions
+    fn report(
+        &self,
+        case: &Case,
+        artifact: &Path,
+        framework: Framework,
+        requests: &[DriverRequest],
+    ) -> Result<String> {
+        let engine = engine_outcomes(&case.runtime, requests);
+        let generated = self.outcomes(artifact, framework, requests)?;
+        let replay = self
+            .out_dir
+            .join(format!("{}.{}.min.jsonl", case.name, framework.name()));
+        std::fs::write(&replay, json_lines(requests)?)
+            .with_context(|| format!("Failed to write {}", replay.display()))?;
+
+        let mut out = format!(
+            "{} / {}: minimal diverging sequence of {} requests (from {})\n\n",
+            case.name,
+            framework.name(),
+            requests.len(),
+            case.requests.len()
+        );
+        let agent_width = requests
+            .iter()
+            .map(|r| r.agent.len())
+            .max()
+            .unwrap_or(0)
+            .max(5);
+        let endpoint_width = requests
+            .iter()
+            .map(|r| r.endpoint.len())
+            .max()
+            .unwrap_or(0)
+            .max(8);
+        out.push_str(&format!(
+            "  {:>3}  {:>9}  {:<aw$}  {:<ew$}  {:<20}  {}\n",
+            "#",
+            "AT ms",
+            "AGENT",
+            "ENDPOINT",
+            "POLICY ENGINE",
+            framework.name().to_uppercase(),
+            aw = agent_width,
+            ew = endpoint_width
+        ));
+        for (i, request) in requests.iter().enumerate() {
+            let ours = engine.get(i).map_or("-".to_string(), Outcome::to_string);
+            let theirs = generated.get(i).map_or("-".to_string(), Outcome::to_string);
+            let marker = if ours != theirs { "  <-- diverges" } else { "" };
+            out.push_str(&format!(
+                "  {:>3}  {:>9}  {:<aw$}  {:<ew$}  {:<20}  {}{}\n",
+                i + 1,
+                request.at_ms.saturating_sub(case.start_ms),
+                request.agent,
+                request.endpoint,
+                ours,
+                theirs,
+                marker,
+                aw = agent_width,
+                ew = endpoint_width
+            ));
+        }
+        out.push_str(&format!(
+            "\n  Replay: node {} {} {} < {}\n",
+            self.out_dir.join("driver.js").display(),
+            framework.name(),
+            artifact.display(),
+            replay.display()
+        ));
+        Ok(out)
+    }
+}
+
+fn json_lines(requests: &[DriverRequest]) -> Result<String> {
+    let mut out = String::new();
+    for request in requests {
+        out.push_str(&serde_json::to_string(request)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(json: &str) -> Outcome {
+        serde_json::from_str::<DriverResponse>(json).unwrap().into()
+    }
+
+    #[test]
+    fn test_driver_responses_map_to_outcomes() {
+        assert_eq!(
+            response(r#"{"status":402,"error":"Payment Required"}"#),
+            Outcome::Allowed
+        );
+        assert_eq!(
+            response(r#"{"status":403,"error":"Agent denied"}"#),
+            Outcome::Denied
+        );
+        assert_eq!(
+            response(r#"{"status":403,"error":"Spending cap exceeded"}"#),
+            Outcome::SpendingCapped
+        );
+        assert_eq!(
+            response(r#"{"status":429,"error":"Rate limit exceeded"}"#),
+            Outcome::RateLimited
+        );
+        assert_eq!(
+            response(r#"{"thrown":"x is not defined"}"#),
+            Outcome::Other("threw: x is not defined".to_string())
+        );
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        use Outcome::*;
+        assert_eq!(
+            first_divergence(&[Allowed, Denied], &[Allowed, Denied]),
+            None
+        );
+        assert_eq!(
+            first_divergence(&[Allowed, RateLimited], &[Allowed, Allowed]),
+            Some(1)
+        );
+        assert_eq!(first_divergence(&[Allowed, Allowed], &[Allowed]), Some(1));
+    }
+}
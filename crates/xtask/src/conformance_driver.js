@@ -0,0 +1,142 @@
+// Policy conformance driver, written next to the generated artifacts by
+// `cargo xtask policy-conformance`
+//
+// Usage: node driver.js <express|fastify> <artifact.js>
+//
+// Reads one request per line on stdin:
+//   {"at_ms":1767225600000,"agent":"agent-1","endpoint":"/api/data","amount":0.01}
+// and answers each with one line on stdout:
+//   {"status":402,"error":"Payment Required"}, {"thrown":"..."} or {"next":true}
+//
+// Date.now() returns the request's `at_ms`, so windows follow the fixture's
+// clock. No npm packages are needed: `fastify-plugin` is stubbed and the
+// request and response objects are fakes with the members the generated
+// code uses.
+
+'use strict';
+
+const Module = require('module');
+const path = require('path');
+const readline = require('readline');
+
+const [framework, artifact] = process.argv.slice(2);
+if (framework !== 'express' && framework !== 'fastify') {
+  process.stderr.write('Usage: node driver.js <express|fastify> <artifact.js>\n');
+  process.exit(2);
+}
+
+let now = 0;
+Date.now = () => now;
+
+// Audit logs go to stderr; stdout carries one response per request
+const stdout = process.stdout;
+console.log = (...args) => process.stderr.write(args.join(' ') + '\n');
+
+const load = Module._load;
+Module._load = function (request, parent, isMain) {
+  if (request === 'fastify-plugin') {
+    return plugin => plugin;
+  }
+  return load.call(this, request, parent, isMain);
+};
+const exported = require(path.resolve(artifact));
+
+function fakeResponse(done) {
+  const listeners = {};
+  const res = {
+    statusCode: 200,
+    headers: {},
+    status(code) {
+      this.statusCode = code;
+      return this;
+    },
+    code(code) {
+      this.statusCode = code;
+      return this;
+    },
+    set(name, value) {
+      this.headers[String(name).toLowerCase()] = value;
+      return this;
+    },
+    header(name, value) {
+      return this.set(name, value);
+    },
+    json(body) {
+      return this.send(body);
+    },
+    send(body) {
+      done(this.statusCode, body);
+      this.emit('finish');
+      return this;
+    },
+    on(event, listener) {
+      (listeners[event] = listeners[event] || []).push(listener);
+      return this;
+    },
+    emit(event) {
+      (listeners[event] || []).forEach(listener => listener());
+    }
+  };
+  res.raw = res;
+  return res;
+}
+
+async function fastifyHook() {
+  let hook = null;
+  const fastify = {
+    addHook(name, handler) {
+      if (name === 'preHandler') {
+        hook = handler;
+      }
+    },
+    get() {}
+  };
+  await exported(fastify, {});
+  if (!hook) {
+    throw new Error('The plugin registered no preHandler hook');
+  }
+  return hook;
+}
+
+async function handle(hook, request) {
+  now = request.at_ms;
+  const headers = {
+    'x-agent-id': request.agent,
+    'x-payment-amount': String(request.amount)
+  };
+  const req = { headers, path: request.endpoint, url: request.endpoint, method: 'GET' };
+  let result = null;
+  const res = fakeResponse((status, body) => {
+    result = { status, error: body && body.error ? String(body.error) : null };
+  });
+  try {
+    if (framework === 'express') {
+      exported(req, res, () => {
+        result = { next: true };
+      });
+    } else {
+      await hook(req, res);
+    }
+  } catch (err) {
+    result = { thrown: String((err && err.message) || err) };
+  }
+  res.emit('close');
+  return result || { next: true };
+}
+
+async function main() {
+  const hook = framework === 'fastify' ? await fastifyHook() : null;
+  const lines = readline.createInterface({ input: process.stdin, crlfDelay: Infinity });
+  for await (const line of lines) {
+    if (line.trim() === '') {
+      continue;
+    }
+    const result = await handle(hook, JSON.parse(line));
+    stdout.write(JSON.stringify(result) + '\n');
+  }
+}
+
+main().catch(err => {
+  process.stderr.write(`driver: ${(err && err.stack) || err}\n`);
+  process.exit(1);
+});
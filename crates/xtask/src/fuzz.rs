@@ -0,0 +1,319 @@
+// Stable-toolchain smoke run of the fuzz targets in fuzz/
+//
+// `cargo fuzz` needs nightly and libFuzzer, so CI runs the same targets with
+// a small seeded mutator instead: each iteration takes an entry of the
+// checked-in corpus, applies a few byte-level and dictionary mutations, and
+// calls the target under `catch_unwind`. Inputs that panic or take longer
+// than SLOW are written to target/fuzz-smoke/<target>/ and printed with the
+// seed, so `--seed` replays the run. This finds shallow bugs only; run
+// `cargo +nightly fuzz run <target> --features libfuzzer` for coverage.
+
+use anyhow::{bail, Context, Result};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const CORPUS_DIR: &str = "fuzz/corpus";
+
+/// Crashing and slow inputs are kept here for replay
+const OUT_DIR: &str = "target/fuzz-smoke";
+
+const DEFAULT_SECONDS: u64 = 5;
+
+/// An input slower than this is reported like a crash
+const SLOW: Duration = Duration::from_secs(1);
+
+/// Tokens of the header grammar spliced into inputs
+const DICTIONARY: &[&[u8]] = &[
+    b"=",
+    b"\"",
+    b"\\",
+    b" ",
+    b",",
+    b";",
+    b"x402-solana ",
+    b"x402-svm ",
+    b"memo=",
+    b"amount=",
+    b"expires=",
+    b"sig=",
+    b"\"\"",
+    b"\\\"",
+    b"\xff",
+    b"\x00",
+    b"\n",
+];
+
+/// Deterministic xorshift64* generator, so a seed replays a run
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform-enough value in `0..n`; `n` must not be zero
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Apply one to four random mutations to `input`
+fn mutate(rng: &mut Rng, input: &mut Vec<u8>) {
+    for _ in 0..1 + rng.below(4) {
+        let len = input.len();
+        match rng.below(7) {
+            0 if len > 0 => {
+                let i = rng.below(len);
+                input[i] ^= 1 << rng.below(8);
+            }
+            1 => {
+                let i = rng.below(len + 1);
+                input.insert(i, rng.next() as u8);
+            }
+            2 if len > 0 => {
+                let start = rng.below(len);
+                let end = start + rng.below(len - start + 1);
+                input.drain(start..end);
+            }
+            3 if len > 0 => {
+                let start = rng.below(len);
+                let end = start + rng.below((len - start).min(32) + 1);
+                let slice = input[start..end].to_vec();
+                let at = rng.below(len + 1);
+                input.splice(at..at, slice);
+            }
+            4 => {
+                let token = DICTIONARY[rng.below(DICTIONARY.len())];
+                let at = rng.below(len + 1);
+                input.splice(at..at, token.iter().copied());
+            }
+            5 if len > 0 => {
+                // Splice a run of one byte, the shape of padding attacks
+                let at = rng.below(len + 1);
+                let byte = input[rng.below(len)];
+                input.splice(at..at, std::iter::repeat_n(byte, rng.below(64)));
+            }
+            _ => {
+                let i = rng.below(len + 1);
+                input.truncate(i);
+            }
+        }
+    }
+    // Now and then blow the input up past any header limit
+    if rng.below(64) == 0 {
+        let copy = input.clone();
+        while !copy.is_empty() && input.len() < 16 * 1024 {
+            input.extend_from_slice(&copy);
+        }
+    }
+}
+
+/// A failing input of one target
+struct Finding {
+    kind: &'static str,
+    detail: String,
+    input: Vec<u8>,
+}
+
+/// Fuzz `target` for `budget`, returning the iteration count and findings
+fn fuzz_target(
+    target: fn(&[u8]),
+    corpus: &[Vec<u8>],
+    rng: &mut Rng,
+    budget: Duration,
+) -> (u64, Vec<Finding>) {
+    let deadline = Instant::now() + budget;
+    let mut iterations = 0;
+    let mut findings = Vec::new();
+    // The whole corpus runs unchanged first, however short the budget
+    let mut queue: Vec<Vec<u8>> = corpus.to_vec();
+    while (!queue.is_empty() || Instant::now() < deadline) && findings.len() < 10 {
+        let input = queue.pop().unwrap_or_else(|| {
+            let mut input = corpus[rng.below(corpus.len())].clone();
+            mutate(rng, &mut input);
+            input
+        });
+        let started = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| target(&input)));
+        let elapsed = started.elapsed();
+        iterations += 1;
+        if let Err(payload) = result {
+            let detail = payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_else(|| "panic".to_string());
+            findings.push(Finding {
+                kind: "crash",
+                detail,
+                input,
+            });
+        } else if elapsed > SLOW {
+            findings.push(Finding {
+                kind: "slow",
+                detail: format!("took {:.2?}", elapsed),
+                input,
+            });
+        }
+    }
+    (iterations, findings)
+}
+
+/// Load the corpus of one target, sorted by file name
+fn load_corpus(dir: &Path) -> Result<Vec<Vec<u8>>> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    paths.sort();
+    let corpus = paths
+        .iter()
+        .map(std::fs::read)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    if corpus.is_empty() {
+        bail!(
+            "{} is empty\nFix: Add at least one seed input",
+            dir.display()
+        );
+    }
+    Ok(corpus)
+}
+
+/// Run `cargo xtask fuzz-smoke [--seconds N] [--seed S] [TARGET...]`
+pub fn run(root: &Path, args: &[&str]) -> Result<bool> {
+    let mut seconds = DEFAULT_SECONDS;
+    let mut seed = None;
+    let mut only = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--seconds" | "--seed" => {
+                let value = args
+                    .next()
+                    .with_context(|| format!("{} needs a value", arg))?;
+                let value: u64 = value
+                    .parse()
+                    .with_context(|| format!("{} must be a number, got {:?}", arg, value))?;
+                if *arg == "--seconds" {
+                    seconds = value;
+                } else {
+                    seed = Some(value);
+                }
+            }
+            name => only.push(name),
+        }
+    }
+    for name in &only {
+        if !x402_fuzz::TARGETS.iter().any(|(target, _)| target == name) {
+            let names: Vec<_> = x402_fuzz::TARGETS.iter().map(|(n, _)| *n).collect();
+            bail!(
+                "Unknown fuzz target: {}\nFix: Use one of {}",
+                name,
+                names.join(", ")
+            );
+        }
+    }
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |d| d.as_nanos() as u64)
+    });
+
+    println!("Fuzz smoke: seed {}, {}s per target\n", seed, seconds);
+    let mut rng = Rng::new(seed);
+    let mut failed = false;
+    // Panics are expected and reported; keep their messages off stderr
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let mut results = Vec::new();
+    for (name, target) in x402_fuzz::TARGETS {
+        if !only.is_empty() && !only.contains(name) {
+            continue;
+        }
+        let corpus = match load_corpus(&root.join(CORPUS_DIR).join(name)) {
+            Ok(corpus) => corpus,
+            Err(e) => {
+                panic::set_hook(hook);
+                return Err(e);
+            }
+        };
+        let budget = Duration::from_secs(seconds);
+        results.push((*name, fuzz_target(*target, &corpus, &mut rng, budget)));
+    }
+    panic::set_hook(hook);
+
+    for (name, (iterations, findings)) in results {
+        if findings.is_empty() {
+            println!("  {:<16}  ok      {} inputs", name, iterations);
+            continue;
+        }
+        failed = true;
+        println!(
+            "  {:<16}  FAILED  {} of {} inputs",
+            name,
+            findings.len(),
+            iterations
+        );
+        let out_dir = root.join(OUT_DIR).join(name);
+        std::fs::create_dir_all(&out_dir)
+            .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+        for (i, finding) in findings.iter().enumerate() {
+            let path = out_dir.join(format!("{}-{}-{}", finding.kind, seed, i));
+            std::fs::write(&path, &finding.input)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("    {}: {}", finding.kind, finding.detail);
+            println!("      input: {}", path.display());
+        }
+    }
+    if failed {
+        println!(
+            "\nReplay with `cargo xtask fuzz-smoke --seed {}`, or feed an input to \
+             `cargo +nightly fuzz run <target> --features libfuzzer <input>`",
+            seed
+        );
+    }
+    Ok(!failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_mutations() {
+        let mutated = |seed| {
+            let mut rng = Rng::new(seed);
+            let mut input = b"x402-solana memo=req-1 amount=0.01".to_vec();
+            for _ in 0..100 {
+                mutate(&mut rng, &mut input);
+            }
+            input
+        };
+        assert_eq!(mutated(7), mutated(7));
+        assert_ne!(mutated(7), mutated(8));
+    }
+
+    #[test]
+    fn test_panicking_inputs_are_findings() {
+        fn target(data: &[u8]) {
+            assert!(!data.contains(&b'!'), "bang");
+        }
+        let corpus = vec![b"ok".to_vec(), b"bang!".to_vec()];
+        let (iterations, findings) =
+            fuzz_target(target, &corpus, &mut Rng::new(1), Duration::from_millis(50));
+
+        assert!(iterations >= 2);
+        assert!(!findings.is_empty());
+        assert_eq!(findings[0].kind, "crash");
+        assert_eq!(findings[0].detail, "bang");
+        assert!(findings.iter().all(|f| f.input.contains(&b'!')));
+    }
+}
@@ -1,14 +1,81 @@
 // xtask: Build automation for x402-dev
 //
-// This provides custom build tasks like:
-// - CI/CD automation
-// - Release preparation
-// - Cross-compilation
-// - Testing workflows
-//
-// Future stories will add actual build tasks
+// Run with `cargo xtask <task>` (alias in .cargo/config.toml). Tasks:
+// - public-api: check x402-core's public API against its checked-in snapshot
+// - schema: check the JSON Schemas in docs/schemas/ against the Rust types
+// - bless-codegen: rewrite the golden files of the policy code generators
+// - policy-conformance: compare generated middleware with PolicyEngine in Node
+// - fuzz-smoke: run the fuzz targets in fuzz/ briefly on stable
+// - ci: run all of the above with the build, clippy and tests as one gate
+
+mod ci;
+mod codegen;
+mod conformance;
+mod fuzz;
+mod public_api;
+mod schema;
+
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: cargo xtask <task>
+
+Tasks:
+  public-api [--bless]   Check x402-core's public API against public-api.txt
+                         (--bless rewrites the snapshot)
+  schema [--bless]       Check docs/schemas/*.schema.json against the Rust
+                         types (--bless regenerates them)
+  bless-codegen          Rewrite x402-core's codegen golden files and
+                         summarize the changes
+  policy-conformance [FIXTURE...]
+                         Run the generated Express and Fastify middleware in
+                         Node and compare their decisions with PolicyEngine
+                         (skipped without node)
+  fuzz-smoke [--seconds N] [--seed S] [TARGET...]
+                         Mutate the fuzz/ corpus and run each target for N
+                         seconds (default 5) on the stable toolchain
+  ci [--only STAGE,...] [--skip STAGE,...] [--offline] [--verbose] [--dry-run]
+                         Run the local quality gate: build, clippy, unit and
+                         integration tests, codegen, schema, public API,
+                         policy conformance and fuzz stages, with a summary
+                         (--offline skips the integration groups that start
+                         servers; --dry-run prints the plan)";
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<bool> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["public-api"] => public_api::check(false),
+        ["public-api", "--bless"] => public_api::check(true),
+        ["schema"] => schema::check(&workspace_root(), false),
+        ["schema", "--bless"] => schema::check(&workspace_root(), true),
+        ["bless-codegen"] => codegen::bless(&workspace_root()),
+        ["policy-conformance", fixtures @ ..] => conformance::run(&workspace_root(), fixtures),
+        ["fuzz-smoke", args @ ..] => fuzz::run(&workspace_root(), args),
+        ["ci", args @ ..] => ci::run(&workspace_root(), args),
+        [] | ["help"] | ["--help"] | ["-h"] => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+        _ => bail!("Unknown task: {}\n\n{}", args.join(" "), USAGE),
+    }
+}
 
-fn main() {
-    println!("xtask: Build automation for x402-dev");
-    println!("Usage: cargo xtask <task>");
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .ancestors()
+        .nth(2)
+        .expect("xtask lives in crates/xtask")
+        .to_path_buf()
 }
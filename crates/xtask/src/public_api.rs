@@ -0,0 +1,674 @@
+// Public API snapshot of x402-core
+//
+// Builds rustdoc JSON for x402-core (needs a nightly toolchain, rustdoc's
+// JSON output is unstable), walks every item reachable from the crate root
+// through public modules and re-exports, and renders one sorted line per
+// item, field, variant, method and trait impl. The result is compared with
+// crates/x402-core/public-api.txt so API changes show up in review; run
+// with --bless after an intended change to update the snapshot.
+//
+// `#[doc(hidden)]` items are left out by rustdoc and so are not part of the
+// snapshot.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const CRATE: &str = "x402-core";
+const SNAPSHOT: &str = "crates/x402-core/public-api.txt";
+
+/// Toolchain used for rustdoc JSON, overridable for pinned nightlies
+const TOOLCHAIN_VAR: &str = "X402_PUBLIC_API_TOOLCHAIN";
+
+/// Compare the current API with the snapshot (or rewrite it when `bless`)
+///
+/// Returns false when the API differs from the snapshot.
+pub fn check(bless: bool) -> Result<bool> {
+    let root = crate::workspace_root();
+    let json = build_rustdoc_json(&root)?;
+    let krate: Value = serde_json::from_str(
+        &std::fs::read_to_string(&json)
+            .with_context(|| format!("Failed to read {}", json.display()))?,
+    )
+    .context("Invalid rustdoc JSON")?;
+    let current = render_api(&krate)?;
+
+    let snapshot = root.join(SNAPSHOT);
+    if bless {
+        std::fs::write(&snapshot, current.join("\n") + "\n")
+            .with_context(|| format!("Failed to write {}", snapshot.display()))?;
+        println!("Wrote {} ({} lines)", SNAPSHOT, current.len());
+        return Ok(true);
+    }
+
+    let expected = std::fs::read_to_string(&snapshot).with_context(|| {
+        format!(
+            "Failed to read {}\nFix: Run `cargo xtask public-api --bless`",
+            SNAPSHOT
+        )
+    })?;
+    let expected: BTreeSet<&str> = expected.lines().filter(|l| !l.is_empty()).collect();
+    let current: BTreeSet<&str> = current.iter().map(String::as_str).collect();
+    if expected == current {
+        println!("Public API of {} matches {}", CRATE, SNAPSHOT);
+        return Ok(true);
+    }
+
+    println!("Public API of {} differs from {}:\n", CRATE, SNAPSHOT);
+    for line in expected.difference(&current) {
+        println!("- {}", line);
+    }
+    for line in current.difference(&expected) {
+        println!("+ {}", line);
+    }
+    println!(
+        "\nIf the change is intended, run `cargo xtask public-api --bless` and commit {}",
+        SNAPSHOT
+    );
+    Ok(false)
+}
+
+fn build_rustdoc_json(root: &Path) -> Result<PathBuf> {
+    let toolchain = std::env::var(TOOLCHAIN_VAR).unwrap_or_else(|_| "nightly".to_string());
+    let target_dir = root.join("target").join("public-api");
+    let status = Command::new("cargo")
+        .current_dir(root)
+        .arg(format!("+{}", toolchain))
+        .args(["rustdoc", "--package", CRATE, "--lib", "--all-features"])
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .args(["--", "-Z", "unstable-options", "--output-format", "json"])
+        .status()
+        .context("Failed to run cargo rustdoc")?;
+    if !status.success() {
+        bail!(
+            "cargo +{} rustdoc failed\nFix: Install the toolchain with `rustup toolchain install {}` (or set {})",
+            toolchain,
+            toolchain,
+            TOOLCHAIN_VAR
+        );
+    }
+    Ok(target_dir
+        .join("doc")
+        .join(format!("{}.json", CRATE.replace('-', "_"))))
+}
+
+/// Sorted API lines of a rustdoc JSON crate
+fn render_api(krate: &Value) -> Result<Vec<String>> {
+    let root_id = id_key(&krate["root"]);
+    let root = &krate["index"][&root_id];
+    let name = root["name"].as_str().context("Crate root has no name")?;
+
+    let mut api = Api {
+        krate,
+        lines: BTreeSet::new(),
+        walking: HashSet::new(),
+    };
+    api.module(name, root);
+    Ok(api.lines.into_iter().collect())
+}
+
+struct Api<'a> {
+    krate: &'a Value,
+    lines: BTreeSet<String>,
+    /// Modules on the current walk, so re-export cycles terminate
+    walking: HashSet<String>,
+}
+
+impl<'a> Api<'a> {
+    fn item(&self, id: &Value) -> Option<&'a Value> {
+        self.krate["index"].get(id_key(id))
+    }
+
+    fn module(&mut self, path: &str, module: &'a Value) {
+        let id = id_key(&module["id"]);
+        if !self.walking.insert(id.clone()) {
+            return;
+        }
+        for child in array(&module["inner"]["module"]["items"]) {
+            if let Some(child) = self.item(child) {
+                self.reexport_or_item(path, child);
+            }
+        }
+        self.walking.remove(&id);
+    }
+
+    fn reexport_or_item(&mut self, parent: &str, item: &'a Value) {
+        if item["visibility"] != "public" {
+            return;
+        }
+        let Some(import) = item["inner"].get("use") else {
+            if let Some(name) = item["name"].as_str() {
+                self.render(&format!("{}::{}", parent, name), item);
+            }
+            return;
+        };
+
+        let name = import["name"].as_str().unwrap_or_default();
+        let source = import["source"].as_str().unwrap_or_default();
+        match self.item(&import["id"]) {
+            Some(target) if import["is_glob"] == true => {
+                if target["inner"].get("module").is_some() {
+                    self.module(parent, target);
+                } else {
+                    self.lines
+                        .insert(format!("pub use {}::{}::*", parent, source));
+                }
+            }
+            Some(target) => self.render(&format!("{}::{}", parent, name), target),
+            // Re-exports of other crates' items
+            None => {
+                self.lines
+                    .insert(format!("pub use {}::{} = {}", parent, name, source));
+            }
+        }
+    }
+
+    fn render(&mut self, path: &str, item: &'a Value) {
+        let inner = &item["inner"];
+        let Some((kind, body)) = inner.as_object().and_then(|o| o.iter().next()) else {
+            return;
+        };
+        let non_exhaustive = if has_attr(item, "non_exhaustive") {
+            "#[non_exhaustive] "
+        } else {
+            ""
+        };
+
+        match kind.as_str() {
+            "module" => {
+                self.lines.insert(format!("pub mod {}", path));
+                self.module(path, item);
+            }
+            "function" => {
+                self.lines.insert(format!("pub {}", function(path, body)));
+            }
+            "constant" => {
+                self.lines
+                    .insert(format!("pub const {}: {}", path, ty(&body["type"])));
+            }
+            "static" => {
+                self.lines
+                    .insert(format!("pub static {}: {}", path, ty(&body["type"])));
+            }
+            "type_alias" => {
+                self.lines.insert(format!(
+                    "pub type {}{} = {}",
+                    path,
+                    generics(&body["generics"]),
+                    ty(&body["type"])
+                ));
+            }
+            "macro" | "proc_macro" => {
+                self.lines.insert(format!("macro {}", path));
+            }
+            "struct" => {
+                self.lines.insert(format!(
+                    "{}pub struct {}{}",
+                    non_exhaustive,
+                    path,
+                    generics(&body["generics"])
+                ));
+                let kind = &body["kind"];
+                if let Some(fields) = kind.get("plain") {
+                    self.fields(path, &fields["fields"]);
+                } else if let Some(fields) = kind.get("tuple") {
+                    self.lines
+                        .insert(format!("pub struct {}({})", path, self.tuple(fields)));
+                }
+                self.impls(path, &body["impls"]);
+            }
+            "enum" => {
+                self.lines.insert(format!(
+                    "{}pub enum {}{}",
+                    non_exhaustive,
+                    path,
+                    generics(&body["generics"])
+                ));
+                for variant in array(&body["variants"]) {
+                    if let Some(variant) = self.item(variant) {
+                        self.variant(path, variant);
+                    }
+                }
+                self.impls(path, &body["impls"]);
+            }
+            "trait" => {
+                let bounds = if array(&body["bounds"]).is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", bounds(&body["bounds"]))
+                };
+                self.lines.insert(format!(
+                    "pub trait {}{}{}",
+                    path,
+                    generics(&body["generics"]),
+                    bounds
+                ));
+                for member in array(&body["items"]) {
+                    if let Some(member) = self.item(member) {
+                        self.member(path, member);
+                    }
+                }
+            }
+            _ => {
+                self.lines.insert(format!("pub {} {}", kind, path));
+            }
+        }
+    }
+
+    fn fields(&mut self, path: &str, fields: &Value) {
+        for field in array(fields) {
+            if let Some(field) = self.item(field) {
+                if field["visibility"] == "public" {
+                    self.lines.insert(format!(
+                        "pub {}::{}: {}",
+                        path,
+                        field["name"].as_str().unwrap_or_default(),
+                        ty(&field["inner"]["struct_field"])
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Tuple fields; private ones show as `_`
+    fn tuple(&self, fields: &Value) -> String {
+        array(fields)
+            .iter()
+            .map(|id| match self.item(id) {
+                Some(field) => ty(&field["inner"]["struct_field"]),
+                None => "_".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn variant(&mut self, path: &str, variant: &Value) {
+        let name = variant["name"].as_str().unwrap_or_default();
+        let kind = &variant["inner"]["variant"]["kind"];
+        let line = if let Some(fields) = kind.get("tuple") {
+            format!("pub {}::{}({})", path, name, self.tuple(fields))
+        } else if let Some(fields) = kind.get("struct") {
+            let fields = array(&fields["fields"])
+                .iter()
+                .filter_map(|id| self.item(id))
+                .map(|f| {
+                    format!(
+                        "{}: {}",
+                        f["name"].as_str().unwrap_or_default(),
+                        ty(&f["inner"]["struct_field"])
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("pub {}::{} {{ {} }}", path, name, fields)
+        } else {
+            format!("pub {}::{}", path, name)
+        };
+        self.lines.insert(line);
+    }
+
+    /// Inherent methods and constants, and explicit trait impls (auto
+    /// traits and blanket impls are left out)
+    fn impls(&mut self, path: &str, impls: &Value) {
+        for id in array(impls) {
+            let Some(imp) = self.item(id) else { continue };
+            let body = &imp["inner"]["impl"];
+            if body["is_synthetic"] == true || !body["blanket_impl"].is_null() {
+                continue;
+            }
+            match body.get("trait").filter(|t| !t.is_null()) {
+                Some(tr) => {
+                    let negative = if body["is_negative"] == true { "!" } else { "" };
+                    self.lines.insert(format!(
+                        "impl{} {}{} for {}",
+                        generics(&body["generics"]),
+                        negative,
+                        resolved_path(tr),
+                        path
+                    ));
+                }
+                None => {
+                    for member in array(&body["items"]) {
+                        if let Some(member) = self.item(member) {
+                            if member["visibility"] == "public" {
+                                self.member(path, member);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// A method, associated constant or associated type of `path`
+    fn member(&mut self, path: &str, member: &Value) {
+        let name = member["name"].as_str().unwrap_or_default();
+        let path = format!("{}::{}", path, name);
+        let inner = &member["inner"];
+        let line = if let Some(f) = inner.get("function") {
+            format!("pub {}", function(&path, f))
+        } else if let Some(c) = inner.get("assoc_const") {
+            format!("pub const {}: {}", path, ty(&c["type"]))
+        } else if let Some(t) = inner.get("assoc_type") {
+            match array(&t["bounds"]).is_empty() {
+                true => format!("pub type {}", path),
+                false => format!("pub type {}: {}", path, bounds(&t["bounds"])),
+            }
+        } else {
+            return;
+        };
+        self.lines.insert(line);
+    }
+}
+
+fn id_key(id: &Value) -> String {
+    match id {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn array(value: &Value) -> &[Value] {
+    value.as_array().map(Vec::as_slice).unwrap_or_default()
+}
+
+fn has_attr(item: &Value, attr: &str) -> bool {
+    array(&item["attrs"]).iter().any(|a| {
+        a.as_str() == Some(attr)
+            || a.as_str()
+                .is_some_and(|s| s.contains(&format!("#[{}]", attr)))
+    })
+}
+
+fn function(path: &str, f: &Value) -> String {
+    let header = &f["header"];
+    let mut qualifiers = String::new();
+    for (flag, word) in [
+        ("is_const", "const "),
+        ("is_async", "async "),
+        ("is_unsafe", "unsafe "),
+    ] {
+        if header[flag] == true {
+            qualifiers.push_str(word);
+        }
+    }
+
+    let sig = &f["sig"];
+    let inputs = array(&sig["inputs"])
+        .iter()
+        .map(|input| {
+            let name = input[0].as_str().unwrap_or("_");
+            let t = &input[1];
+            if name != "self" {
+                return format!("{}: {}", name, ty(t));
+            }
+            if t["generic"] == "Self" {
+                return "self".to_string();
+            }
+            match t.get("borrowed_ref") {
+                Some(r) if r["type"]["generic"] == "Self" => {
+                    format!("{}self", reference(r))
+                }
+                _ => format!("self: {}", ty(t)),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let output = match &sig["output"] {
+        Value::Null => String::new(),
+        t => format!(" -> {}", ty(t)),
+    };
+    format!(
+        "{}fn {}{}({}){}{}",
+        qualifiers,
+        path,
+        generics(&f["generics"]),
+        inputs,
+        output,
+        where_clause(&f["generics"])
+    )
+}
+
+/// `<...>` of declared generic parameters (not `impl Trait` arguments)
+fn generics(g: &Value) -> String {
+    let params: Vec<String> = array(&g["params"])
+        .iter()
+        .filter_map(|p| {
+            let name = p["name"].as_str().unwrap_or_default();
+            let kind = &p["kind"];
+            if let Some(t) = kind.get("type") {
+                if t["is_synthetic"] == true {
+                    return None;
+                }
+                let mut param = name.to_string();
+                if !array(&t["bounds"]).is_empty() {
+                    param.push_str(&format!(": {}", bounds(&t["bounds"])));
+                }
+                if !t["default"].is_null() {
+                    param.push_str(&format!(" = {}", ty(&t["default"])));
+                }
+                Some(param)
+            } else if let Some(c) = kind.get("const") {
+                Some(format!("const {}: {}", name, ty(&c["type"])))
+            } else {
+                let outlives: Vec<&str> = array(&kind["lifetime"]["outlives"])
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect();
+                match outlives.is_empty() {
+                    true => Some(name.to_string()),
+                    false => Some(format!("{}: {}", name, outlives.join(" + "))),
+                }
+            }
+        })
+        .collect();
+    match params.is_empty() {
+        true => String::new(),
+        false => format!("<{}>", params.join(", ")),
+    }
+}
+
+fn where_clause(g: &Value) -> String {
+    let predicates: Vec<String> = array(&g["where_predicates"])
+        .iter()
+        .filter_map(|p| {
+            let bound = p.get("bound_predicate")?;
+            Some(format!(
+                "{}: {}",
+                ty(&bound["type"]),
+                bounds(&bound["bounds"])
+            ))
+        })
+        .collect();
+    match predicates.is_empty() {
+        true => String::new(),
+        false => format!(" where {}", predicates.join(", ")),
+    }
+}
+
+fn bounds(bounds: &Value) -> String {
+    array(bounds)
+        .iter()
+        .map(|b| {
+            if let Some(t) = b.get("trait_bound") {
+                let maybe = if t["modifier"] == "maybe" { "?" } else { "" };
+                format!("{}{}", maybe, resolved_path(&t["trait"]))
+            } else if let Some(l) = b.get("outlives") {
+                l.as_str().unwrap_or_default().to_string()
+            } else {
+                "_".to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+fn reference(r: &Value) -> String {
+    let lifetime = r["lifetime"]
+        .as_str()
+        .map(|l| format!("{} ", l))
+        .unwrap_or_default();
+    let mutable = if r["is_mutable"] == true { "mut " } else { "" };
+    format!("&{}{}", lifetime, mutable)
+}
+
+/// Name of a path type with its generic arguments
+///
+/// Only the last path segment is kept: how a type is imported is not part
+/// of the API, and moving an item already changes its own lines.
+fn resolved_path(p: &Value) -> String {
+    let path = p["path"].as_str().unwrap_or_default();
+    let name = path.rsplit("::").next().unwrap_or(path);
+    format!("{}{}", name, args(&p["args"]))
+}
+
+fn args(a: &Value) -> String {
+    if let Some(angle) = a.get("angle_bracketed") {
+        let mut parts: Vec<String> = array(&angle["args"])
+            .iter()
+            .map(|arg| {
+                if let Some(t) = arg.get("type") {
+                    ty(t)
+                } else if let Some(l) = arg.get("lifetime") {
+                    l.as_str().unwrap_or_default().to_string()
+                } else if let Some(c) = arg.get("const") {
+                    c["expr"].as_str().unwrap_or("_").to_string()
+                } else {
+                    "_".to_string()
+                }
+            })
+            .collect();
+        for constraint in array(&angle["constraints"]) {
+            let name = constraint["name"].as_str().unwrap_or_default();
+            let binding = &constraint["binding"];
+            if let Some(t) = binding.get("equality").and_then(|e| e.get("type")) {
+                parts.push(format!("{} = {}", name, ty(t)));
+            } else if let Some(b) = binding.get("constraint") {
+                parts.push(format!("{}: {}", name, bounds(b)));
+            }
+        }
+        match parts.is_empty() {
+            true => String::new(),
+            false => format!("<{}>", parts.join(", ")),
+        }
+    } else if let Some(paren) = a.get("parenthesized") {
+        let inputs: Vec<String> = array(&paren["inputs"]).iter().map(ty).collect();
+        let output = match &paren["output"] {
+            Value::Null => String::new(),
+            t => format!(" -> {}", ty(t)),
+        };
+        format!("({}){}", inputs.join(", "), output)
+    } else {
+        String::new()
+    }
+}
+
+fn ty(t: &Value) -> String {
+    let Some((kind, v)) = t.as_object().and_then(|o| o.iter().next()) else {
+        return t.as_str().unwrap_or("_").to_string();
+    };
+    match kind.as_str() {
+        "resolved_path" => resolved_path(v),
+        "generic" | "primitive" => v.as_str().unwrap_or_default().to_string(),
+        "borrowed_ref" => format!("{}{}", reference(v), ty(&v["type"])),
+        "raw_pointer" => {
+            let kind = if v["is_mutable"] == true {
+                "mut"
+            } else {
+                "const"
+            };
+            format!("*{} {}", kind, ty(&v["type"]))
+        }
+        "slice" => format!("[{}]", ty(v)),
+        "array" => format!("[{}; {}]", ty(&v["type"]), v["len"].as_str().unwrap_or("_")),
+        "tuple" => {
+            let items: Vec<String> = array(v).iter().map(ty).collect();
+            match items.len() {
+                1 => format!("({},)", items[0]),
+                _ => format!("({})", items.join(", ")),
+            }
+        }
+        "impl_trait" => format!("impl {}", bounds(v)),
+        "dyn_trait" => {
+            let mut traits: Vec<String> = array(&v["traits"])
+                .iter()
+                .map(|t| resolved_path(&t["trait"]))
+                .collect();
+            if let Some(lifetime) = v["lifetime"].as_str() {
+                traits.push(lifetime.to_string());
+            }
+            format!("dyn {}", traits.join(" + "))
+        }
+        "function_pointer" => {
+            let inputs: Vec<String> = array(&v["sig"]["inputs"])
+                .iter()
+                .map(|i| ty(&i[1]))
+                .collect();
+            let output = match &v["sig"]["output"] {
+                Value::Null => String::new(),
+                t => format!(" -> {}", ty(t)),
+            };
+            format!("fn({}){}", inputs.join(", "), output)
+        }
+        "qualified_path" => {
+            let self_type = ty(&v["self_type"]);
+            let name = v["name"].as_str().unwrap_or_default();
+            match v.get("trait").filter(|t| !t.is_null()) {
+                Some(tr) => format!("<{} as {}>::{}", self_type, resolved_path(tr), name),
+                None => format!("{}::{}", self_type, name),
+            }
+        }
+        "infer" => "_".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_api_follows_reexports() {
+        let path = |name: &str| json!({"resolved_path": {"path": name, "id": 9, "args": null}});
+        let krate = json!({
+            "root": 0,
+            "index": {
+                "0": {"id": 0, "name": "demo", "visibility": "public", "attrs": [],
+                      "inner": {"module": {"items": [1, 2]}}},
+                "1": {"id": 1, "name": "inner", "visibility": "default", "attrs": [],
+                      "inner": {"module": {"items": [3, 4]}}},
+                "2": {"id": 2, "name": null, "visibility": "public", "attrs": [],
+                      "inner": {"use": {"source": "inner::Kind", "name": "Kind", "id": 4, "is_glob": false}}},
+                "3": {"id": 3, "name": "load", "visibility": "public", "attrs": [],
+                      "inner": {"function": {
+                          "sig": {"inputs": [["yaml", {"borrowed_ref": {"lifetime": null, "is_mutable": false, "type": {"primitive": "str"}}}]],
+                                  "output": {"resolved_path": {"path": "anyhow::Result", "id": 8,
+                                             "args": {"angle_bracketed": {"args": [{"type": path("super::Kind")}], "constraints": []}}}}},
+                          "generics": {"params": [], "where_predicates": []},
+                          "header": {"is_const": false, "is_unsafe": false, "is_async": false}}}},
+                "4": {"id": 4, "name": "Kind", "visibility": "public", "attrs": ["non_exhaustive"],
+                      "inner": {"enum": {"generics": {"params": [], "where_predicates": []},
+                                         "variants": [5], "impls": []}}},
+                "5": {"id": 5, "name": "Simple", "visibility": "default", "attrs": [],
+                      "inner": {"variant": {"kind": "plain"}}}
+            }
+        });
+
+        // The private module is skipped; its enum shows at the re-export
+        assert_eq!(
+            render_api(&krate).unwrap(),
+            [
+                "#[non_exhaustive] pub enum demo::Kind",
+                "pub demo::Kind::Simple",
+            ]
+        );
+        assert_eq!(
+            function("demo::load", &krate["index"]["3"]["inner"]["function"]),
+            "fn demo::load(yaml: &str) -> Result<Kind>"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+// JSON Schemas for x402 wire formats
+//
+// Generated from the Rust types with schemars (x402-core's `schema`
+// feature) and checked in under docs/schemas/ so clients in other languages
+// can validate what the mock server sends. `cargo xtask schema` fails when
+// a checked-in schema is stale; --bless rewrites them.
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use x402_core::receipt::PaymentReceipt;
+
+/// Checked-in schema files and their generators
+fn schemas() -> Vec<(&'static str, schemars::Schema)> {
+    vec![(
+        "docs/schemas/payment-receipt.schema.json",
+        schemars::schema_for!(PaymentReceipt),
+    )]
+}
+
+/// Pretty JSON with a trailing newline, as checked in
+fn render(schema: &schemars::Schema) -> String {
+    serde_json::to_string_pretty(schema).expect("schemas serialize") + "\n"
+}
+
+/// Compare generated schemas with the checked-in files (or rewrite them
+/// when `bless`)
+///
+/// Returns false when any file is missing or stale.
+pub fn check(root: &Path, bless: bool) -> Result<bool> {
+    let mut current = true;
+    for (file, schema) in schemas() {
+        let path = root.join(file);
+        let generated = render(&schema);
+        if bless {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("Failed to create {}", dir.display()))?;
+            }
+            std::fs::write(&path, &generated)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Wrote {}", file);
+        } else if std::fs::read_to_string(&path).ok().as_deref() == Some(generated.as_str()) {
+            println!("{} is up to date", file);
+        } else {
+            println!("{} is missing or stale", file);
+            current = false;
+        }
+    }
+    if !current {
+        println!("\nRun `cargo xtask schema --bless` and commit the result");
+    }
+    Ok(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_in_schemas_are_current() {
+        assert!(
+            check(&crate::workspace_root(), false).unwrap(),
+            "run `cargo xtask schema --bless`"
+        );
+    }
+}
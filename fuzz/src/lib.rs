@@ -0,0 +1,123 @@
+//! Fuzz targets for the header parsers servers and clients feed untrusted input
+//!
+//! Each target takes arbitrary bytes and panics only on a bug. The libFuzzer
+//! binaries in `fuzz_targets/` call them (`cargo +nightly fuzz run <target>
+//! --features libfuzzer`), and `cargo xtask fuzz-smoke` runs them on stable
+//! with a simple mutator over the checked-in corpus in `corpus/<target>/`.
+
+use x402_core::testing::{
+    parse_payment_proof, HeaderTooLong, InvoiceHeader, DEFAULT_MAX_HEADER_BYTES,
+};
+
+/// Entry point of a fuzz target
+pub type Target = fn(&[u8]);
+
+/// Every target by name, as `cargo fuzz` and the corpus directories call it
+pub const TARGETS: &[(&str, Target)] = &[
+    ("invoice_header", invoice_header),
+    ("payment_proof", payment_proof),
+];
+
+/// Protocol identifiers the invoice target accepts
+const PROTOCOLS: [&str; 2] = ["x402-solana", "x402-svm"];
+
+/// `WWW-Authenticate` invoice parsing
+///
+/// Oversized input fails with [`HeaderTooLong`]. Parsed headers survive
+/// parse → format → parse with the same names and values, and both
+/// formatters are stable on their own output.
+pub fn invoice_header(data: &[u8]) {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let header = match InvoiceHeader::parse_with_protocols(text, &PROTOCOLS) {
+        Ok(header) => header,
+        Err(e) => {
+            if text.len() > DEFAULT_MAX_HEADER_BYTES {
+                assert!(e.downcast_ref::<HeaderTooLong>().is_some(), "{}", e);
+            }
+            return;
+        }
+    };
+    assert!(text.len() <= DEFAULT_MAX_HEADER_BYTES);
+
+    // Quoting can grow the header past the limit, so reparse without one
+    let reparse = |written: &str| {
+        InvoiceHeader::parse_limited(written, &PROTOCOLS, usize::MAX)
+            .unwrap_or_else(|e| panic!("formatted header {:?} does not parse: {}", written, e))
+    };
+
+    let written = header.to_header_string();
+    let reparsed = reparse(&written);
+    assert_eq!(reparsed.protocol, header.protocol);
+    assert_eq!(names_and_values(&reparsed), names_and_values(&header));
+    assert_eq!(reparsed.to_header_string(), written);
+
+    let canonical = header.canonical_string();
+    assert_eq!(reparse(&canonical).canonical_string(), canonical);
+
+    // Accessors and strict checks handle whatever was parsed
+    let _ = header.strict_checks();
+    let _ = header.expires();
+    let _ = header.issued_at();
+    let _ = header.to_map();
+}
+
+/// `X-Payment-Proof` validation
+///
+/// Accepted proofs come back unchanged and within the length limit;
+/// oversized input fails with [`HeaderTooLong`].
+pub fn payment_proof(data: &[u8]) {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    match parse_payment_proof(text) {
+        Ok(proof) => {
+            assert_eq!(proof, text);
+            assert!(proof.len() <= DEFAULT_MAX_HEADER_BYTES);
+            assert_eq!(parse_payment_proof(proof).ok(), Some(proof));
+        }
+        Err(e) => {
+            if text.len() > DEFAULT_MAX_HEADER_BYTES {
+                assert!(e.downcast_ref::<HeaderTooLong>().is_some(), "{}", e);
+            }
+        }
+    }
+}
+
+fn names_and_values(header: &InvoiceHeader) -> Vec<(&str, &str)> {
+    header
+        .fields
+        .iter()
+        .map(|field| (field.name.as_str(), field.value.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// Every checked-in corpus entry passes its target
+    #[test]
+    fn test_corpus_passes() {
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus");
+        for (name, target) in TARGETS {
+            let entries = fs::read_dir(corpus.join(name)).unwrap();
+            let mut count = 0;
+            for entry in entries {
+                target(&fs::read(entry.unwrap().path()).unwrap());
+                count += 1;
+            }
+            assert!(count > 0, "corpus/{} is empty", name);
+        }
+    }
+
+    #[test]
+    fn test_oversized_input_is_rejected_not_scanned() {
+        let long = format!("x402-solana memo={}", "a".repeat(DEFAULT_MAX_HEADER_BYTES));
+        invoice_header(long.as_bytes());
+        payment_proof(long.as_bytes());
+    }
+}
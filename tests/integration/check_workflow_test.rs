@@ -1,24 +1,32 @@
 // Check Command Workflow Integration Tests
 // End-to-end tests for the check command with real HTTP interactions
+//
+// Mocks match any method: check probes each URL with GET and then HEAD.
 
 use assert_cmd::Command;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use predicates::prelude::*;
+use std::io::Write;
 use std::time::Duration;
 use wiremock::{
-    matchers::{method, path},
+    matchers::{any, method, path},
     Mock, MockServer, ResponseTemplate,
 };
 
+/// `<prefix><uuid>` memo accepted by `--strict`
+const UUID_MEMO: &str = "req-550e8400-e29b-41d4-a716-446655440000";
+
 #[tokio::test]
 async fn test_complete_check_workflow_success() {
     // Start a mock HTTP server with wiremock
     let mock_server = MockServer::start().await;
 
     // Configure mock to return proper 402 response with x402-solana header
-    Mock::given(method("GET"))
-        .and(path("/api/data"))
+    Mock::given(path("/api/data"))
         .respond_with(
             ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
                 .insert_header(
                     "WWW-Authenticate",
                     "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo=req-test-123 network=devnet"
@@ -66,10 +74,10 @@ async fn test_check_workflow_with_retries() {
 async fn test_check_workflow_verbose_mode() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("GET"))
-        .and(path("/api/test"))
+    Mock::given(path("/api/test"))
         .respond_with(
             ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
                 .insert_header(
                     "WWW-Authenticate",
                     "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.05 currency=USDC memo=req-verbose-test network=devnet"
@@ -97,9 +105,10 @@ async fn test_check_workflow_verbose_mode() {
 async fn test_check_workflow_json_output() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("GET"))
+    Mock::given(any())
         .respond_with(
             ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
                 .insert_header(
                     "WWW-Authenticate",
                     "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.02 currency=USDC memo=req-json-test network=devnet"
@@ -132,10 +141,10 @@ async fn test_check_workflow_multiple_urls() {
     let mock_server = MockServer::start().await;
 
     // Mock multiple endpoints
-    Mock::given(method("GET"))
-        .and(path("/api/endpoint1"))
+    Mock::given(path("/api/endpoint1"))
         .respond_with(
             ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
                 .insert_header(
                     "WWW-Authenticate",
                     "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo=req-ep1 network=devnet"
@@ -144,10 +153,10 @@ async fn test_check_workflow_multiple_urls() {
         .mount(&mock_server)
         .await;
 
-    Mock::given(method("GET"))
-        .and(path("/api/endpoint2"))
+    Mock::given(path("/api/endpoint2"))
         .respond_with(
             ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
                 .insert_header(
                     "WWW-Authenticate",
                     "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.03 currency=USDC memo=req-ep2 network=devnet"
@@ -206,7 +215,7 @@ async fn test_check_workflow_non_402_response() {
     let mock_server = MockServer::start().await;
 
     // Mock server returns 200 OK instead of 402
-    Mock::given(method("GET"))
+    Mock::given(any())
         .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
             "message": "Success"
         })))
@@ -229,9 +238,10 @@ async fn test_check_workflow_missing_www_authenticate_header() {
     let mock_server = MockServer::start().await;
 
     // Mock 402 response but missing required header
-    Mock::given(method("GET"))
+    Mock::given(any())
         .respond_with(
             ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
                 .set_body_json(serde_json::json!({"error": "Payment required"})),
         )
         .mount(&mock_server)
@@ -252,7 +262,7 @@ async fn test_check_workflow_invalid_header_format() {
     let mock_server = MockServer::start().await;
 
     // Mock 402 with malformed WWW-Authenticate header
-    Mock::given(method("GET"))
+    Mock::given(any())
         .respond_with(
             ResponseTemplate::new(402).insert_header("WWW-Authenticate", "invalid-format-here"),
         )
@@ -274,9 +284,10 @@ async fn test_check_workflow_invalid_header_format() {
 async fn test_check_workflow_custom_port() {
     let mock_server = MockServer::start().await;
 
-    Mock::given(method("GET"))
+    Mock::given(any())
         .respond_with(
             ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
                 .insert_header(
                     "WWW-Authenticate",
                     "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo=req-custom-port network=devnet"
@@ -292,3 +303,715 @@ async fn test_check_workflow_custom_port() {
 
     cmd.assert().success();
 }
+
+#[tokio::test]
+async fn test_check_workflow_cacheable_402_fails() {
+    let mock_server = MockServer::start().await;
+
+    // Valid invoice, but shared caches are allowed to store it
+    Mock::given(any())
+        .respond_with(
+            ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "public, max-age=300")
+                .insert_header(
+                    "WWW-Authenticate",
+                    "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo=req-cached network=devnet"
+                )
+        )
+        .mount(&mock_server)
+        .await;
+
+    let url = mock_server.uri();
+
+    let mut cmd = Command::cargo_bin("x402-dev").unwrap();
+    cmd.arg("check").arg(&url).timeout(Duration::from_secs(10));
+
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("cacheable by shared caches"))
+        .stdout(predicate::str::contains("Cache-Control: no-store"));
+}
+
+/// Mount a compliant 402 response with the given amount, memo and extra headers
+async fn mount_invoice(server: &MockServer, amount: &str, memo: &str, extra: &[(&str, &str)]) {
+    let mut template = ResponseTemplate::new(402)
+        .insert_header("Cache-Control", "no-store")
+        .insert_header(
+            "WWW-Authenticate",
+            format!(
+                "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount={} currency=USDC memo={} network=devnet",
+                amount, memo
+            )
+            .as_str(),
+        );
+    for (name, value) in extra {
+        template = template.insert_header(*name, *value);
+    }
+    Mock::given(any())
+        .respond_with(template)
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_check_workflow_baseline_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    let baseline_path = dir.path().join("baseline.json");
+
+    // First response: save the baseline
+    let original = MockServer::start().await;
+    mount_invoice(&original, "0.01", "req-first", &[]).await;
+
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(original.uri())
+        .arg("--save-baseline")
+        .arg(&baseline_path)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Baseline saved"));
+
+    let saved: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&baseline_path).unwrap()).unwrap();
+    assert_eq!(saved["invoice"]["amount"], "0.01");
+    assert!(saved["invoice"].get("memo").is_none());
+
+    // Second response: amount changed, header added, memo differs (volatile)
+    let changed = MockServer::start().await;
+    mount_invoice(&changed, "0.05", "req-second", &[("X-Price-Tier", "gold")]).await;
+
+    let output = Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(changed.uri())
+        .arg("--baseline")
+        .arg(&baseline_path)
+        .arg("--format")
+        .arg("json")
+        .timeout(Duration::from_secs(10))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "drift is only a warning by default"
+    );
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("invoice.amount changed: 0.01 → 0.05"));
+    let json_start = stdout.rfind("\n{").unwrap();
+    let report: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+    assert_eq!(
+        report["baseline"]["diff"],
+        serde_json::json!([
+            {"field": "invoice.amount", "change": "changed", "baseline": "0.01", "current": "0.05"},
+            {"field": "header.x-price-tier", "change": "added", "current": "gold"},
+        ])
+    );
+
+    // --strict-baseline turns drift into a failure
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(changed.uri())
+        .arg("--baseline")
+        .arg(&baseline_path)
+        .arg("--strict-baseline")
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("header.x-price-tier added: gold"));
+
+    // Excluding the drifting fields makes the strict run pass
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(changed.uri())
+        .arg("--baseline")
+        .arg(&baseline_path)
+        .arg("--strict-baseline")
+        .arg("--baseline-ignore")
+        .arg("amount")
+        .arg("--baseline-ignore")
+        .arg("x-price-tier")
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Matches baseline"));
+}
+
+#[tokio::test]
+async fn test_check_workflow_json_output_ignores_locale() {
+    let dir = tempfile::tempdir().unwrap();
+    let baseline_path = dir.path().join("baseline.json");
+
+    let original = MockServer::start().await;
+    mount_invoice(&original, "0.01", "req-first", &[]).await;
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(original.uri())
+        .arg("--save-baseline")
+        .arg(&baseline_path)
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success();
+
+    let changed = MockServer::start().await;
+    mount_invoice(&changed, "0.05", "req-second", &[]).await;
+
+    let json_report = |lang: &str| {
+        let output = Command::cargo_bin("x402-dev")
+            .unwrap()
+            .env("X402_DEV_LANG", lang)
+            .arg("check")
+            .arg(changed.uri())
+            .arg("--baseline")
+            .arg(&baseline_path)
+            .arg("--format")
+            .arg("json")
+            .timeout(Duration::from_secs(10))
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let json_start = stdout.rfind("\n{").unwrap();
+        let report: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+        report
+    };
+
+    let english = json_report("en");
+    assert_eq!(english["baseline"]["diff"][0]["change"], "changed");
+    assert_eq!(json_report("es"), english);
+    assert_eq!(json_report("ja"), english);
+}
+
+#[tokio::test]
+async fn test_check_workflow_min_amount_warning() {
+    let server = MockServer::start().await;
+    mount_invoice(&server, "0.0001", "req-dust", &[]).await;
+
+    // Below --min-amount: warned, but the check still passes
+    let output = Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(server.uri())
+        .arg("--min-amount")
+        .arg("0.001")
+        .arg("--format")
+        .arg("json")
+        .timeout(Duration::from_secs(10))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Invoice amount 0.0001 is below --min-amount 0.001"));
+    let json_start = stdout.rfind("\n{").unwrap();
+    let report: serde_json::Value = serde_json::from_str(&stdout[json_start..]).unwrap();
+    assert_eq!(
+        report["warnings"],
+        serde_json::json!(["Invoice amount 0.0001 is below --min-amount 0.001"])
+    );
+
+    // At or above the minimum: no warning
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(server.uri())
+        .arg("--min-amount")
+        .arg("0.0001")
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("below --min-amount").not());
+}
+
+#[tokio::test]
+async fn test_check_workflow_strict_protocol() {
+    // Shuffled field order: lenient check passes, strict fails only on ordering
+    let shuffled = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(
+            ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
+                .insert_header(
+                    "WWW-Authenticate",
+                    "x402-solana amount=0.01 recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d network=devnet currency=USDC memo=req-550e8400-e29b-41d4-a716-446655440000",
+                ),
+        )
+        .mount(&shuffled)
+        .await;
+
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(shuffled.uri())
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(shuffled.uri())
+        .arg("--strict")
+        .timeout(Duration::from_secs(10))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Strict Protocol:"));
+    assert_eq!(failed_checks(&stdout), 1, "{}", stdout);
+    assert!(stdout.contains("❌ Canonical field order"));
+
+    // Seven decimal places: fails the amount check and strict precision
+    let precise = MockServer::start().await;
+    mount_invoice(&precise, "0.0000001", UUID_MEMO, &[]).await;
+
+    let output = Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(precise.uri())
+        .arg("--strict")
+        .timeout(Duration::from_secs(10))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(failed_checks(&stdout), 2, "{}", stdout);
+    assert!(stdout.contains("❌ Amount: Invalid amount: 7 decimal places"));
+    assert!(stdout.contains("❌ Amount precision"));
+}
+
+#[tokio::test]
+async fn test_check_workflow_invoice_expiry() {
+    let invoice = |expires: &str| {
+        format!(
+            "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo={} network=devnet expires=\"{}\"",
+            UUID_MEMO, expires
+        )
+    };
+    let run = |url: String, strict: bool| {
+        let mut cmd = Command::cargo_bin("x402-dev").unwrap();
+        cmd.arg("check").arg(url).timeout(Duration::from_secs(10));
+        if strict {
+            cmd.arg("--strict");
+        }
+        cmd.output().unwrap()
+    };
+
+    // A future deadline passes lenient and strict checks
+    let future = (chrono::Utc::now() + chrono::Duration::seconds(300))
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let fresh = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(
+            ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
+                .insert_header("WWW-Authenticate", invoice(&future).as_str()),
+        )
+        .mount(&fresh)
+        .await;
+
+    let output = run(fresh.uri(), true);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains(&format!("✅ Expiry: {} (in", future)));
+    assert!(stdout.contains("✅ Expiry timestamp"));
+
+    // An expired invoice fails only the expiry check
+    let expired = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(
+            ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
+                .insert_header("WWW-Authenticate", invoice("2020-01-01T00:00:00Z").as_str()),
+        )
+        .mount(&expired)
+        .await;
+
+    let output = run(expired.uri(), false);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    assert_eq!(failed_checks(&stdout), 1, "{}", stdout);
+    assert!(stdout.contains("❌ Expiry: 2020-01-01T00:00:00Z (expired"));
+}
+
+#[tokio::test]
+async fn test_check_workflow_clock_skew() {
+    // A fresh 300s invoice from a server whose clock is an hour behind ours
+    let server_now = chrono::Utc::now() - chrono::Duration::seconds(3600);
+    let stamp =
+        |t: chrono::DateTime<chrono::Utc>| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let header = format!(
+        "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo={} network=devnet expires=\"{}\" ts=\"{}\"",
+        UUID_MEMO,
+        stamp(server_now + chrono::Duration::seconds(300)),
+        stamp(server_now)
+    );
+    let skewed = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(
+            ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
+                .insert_header("WWW-Authenticate", header.as_str()),
+        )
+        .mount(&skewed)
+        .await;
+    let run = |extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("x402-dev").unwrap();
+        cmd.arg("check")
+            .arg(skewed.uri())
+            .args(extra)
+            .timeout(Duration::from_secs(10));
+        cmd.output().unwrap()
+    };
+
+    // By the local clock it expired long ago; the skew is reported
+    let output = run(&[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    assert_eq!(failed_checks(&stdout), 1, "{}", stdout);
+    assert!(stdout.contains("❌ Expiry:"), "{}", stdout);
+    // 3600s, or 3601s when the seconds roll over between stamping and checking
+    assert!(stdout.contains("Local clock is 360"), "{}", stdout);
+    assert!(stdout.contains("s ahead of the server's ts (tolerance 30s)"));
+    assert!(stdout.contains("--tolerate-skew"));
+
+    // Tolerant: judged by the server's clock, still warned
+    let output = run(&["--tolerate-skew", "--strict", "--format", "json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success(), "{}", stdout);
+    assert!(stdout.contains("(in 300s, server time)"), "{}", stdout);
+    assert!(stdout.contains("✅ Issued-at timestamp"));
+    assert!(stdout.contains("\"warnings\": ["));
+    assert!(stdout.contains("s ahead of the server's ts"));
+
+    // A generous tolerance trusts the local clock and stays quiet
+    let output = run(&["--tolerate-skew", "--skew-tolerance", "7200"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    assert!(!stdout.contains("Local clock is"), "{}", stdout);
+}
+
+#[tokio::test]
+async fn test_check_workflow_amount_validation() {
+    let check = |url: String, extra: &[&str]| {
+        Command::cargo_bin("x402-dev")
+            .unwrap()
+            .arg("check")
+            .arg(url)
+            .args(extra)
+            .timeout(Duration::from_secs(10))
+            .output()
+            .unwrap()
+    };
+
+    // Each malformed amount fails with its own message; redundant zeros are normalized
+    let cases = [
+        (
+            "1e2",
+            "❌ Amount: Invalid amount: scientific notation is not allowed (write 100)",
+        ),
+        (
+            "0.0000001",
+            "❌ Amount: Invalid amount: 7 decimal places, USDC allows at most 6",
+        ),
+        ("00.01", "✅ Amount: 0.01 USDC (normalized from 00.01)"),
+        (
+            "+0.01",
+            "❌ Amount: Invalid amount: leading '+' is not allowed (write 0.01)",
+        ),
+        (".5", "✅ Amount: 0.50 USDC (normalized from .5)"),
+    ];
+    for (amount, expected) in cases {
+        let server = MockServer::start().await;
+        mount_invoice(&server, amount, "req-amount", &[]).await;
+
+        let output = check(server.uri(), &[]);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains(expected), "{}: {}", amount, stdout);
+        let passes = expected.starts_with('✅');
+        assert_eq!(output.status.success(), passes, "{}: {}", amount, stdout);
+        assert_eq!(failed_checks(&stdout), usize::from(!passes), "{}", stdout);
+    }
+
+    // --max-amount caps otherwise valid amounts
+    let server = MockServer::start().await;
+    mount_invoice(&server, "5", "req-amount", &[]).await;
+    let output = check(server.uri(), &["--max-amount", "1"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    assert!(stdout.contains("❌ Amount: Invalid amount: 5 exceeds the maximum of 1"));
+}
+
+#[tokio::test]
+async fn test_check_workflow_body_amount_mismatch() {
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(
+            ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
+                .insert_header(
+                    "WWW-Authenticate",
+                    "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo=req-body network=devnet",
+                )
+                .set_body_json(serde_json::json!({"invoice": {"amount": 0.05}})),
+        )
+        .mount(&server)
+        .await;
+
+    let output = Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(server.uri())
+        .timeout(Duration::from_secs(10))
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    assert_eq!(failed_checks(&stdout), 1, "{}", stdout);
+    assert!(stdout.contains("❌ Body amount: 0.05 does not match header 0.01"));
+}
+
+#[tokio::test]
+async fn test_check_workflow_gzip_body() {
+    // The body amount is only readable once the gzip encoding is undone
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(br#"{"invoice": {"amount": 0.05}}"#)
+        .unwrap();
+    let gzipped = encoder.finish().unwrap();
+    let encoded_size = gzipped.len();
+
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(
+            ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
+                .insert_header("Content-Encoding", "gzip")
+                .insert_header(
+                    "WWW-Authenticate",
+                    "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo=req-gzip network=devnet",
+                )
+                .set_body_raw(gzipped, "application/json"),
+        )
+        .mount(&server)
+        .await;
+
+    let output = Command::cargo_bin("x402-dev")
+        .unwrap()
+        .args(["check", &server.uri(), "--format", "json"])
+        .timeout(Duration::from_secs(10))
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains(&format!(
+        "Body: {} bytes gzip-encoded, 29 decoded",
+        encoded_size
+    )));
+    assert!(stdout.contains("❌ Body amount: 0.05 does not match header 0.01"));
+    assert!(stdout.contains(&format!("\"encoded_size\": {}", encoded_size)));
+}
+
+#[tokio::test]
+async fn test_check_workflow_head_parity() {
+    let invoice = |amount: &str| {
+        ResponseTemplate::new(402)
+            .insert_header("Cache-Control", "no-store")
+            .insert_header(
+                "WWW-Authenticate",
+                format!("x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount={} currency=USDC memo=req-head network=devnet", amount),
+            )
+    };
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/parity"))
+        .respond_with(invoice("0.01"))
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/parity"))
+        .respond_with(invoice("0.01"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/get-only"))
+        .respond_with(invoice("0.01"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repriced"))
+        .respond_with(invoice("0.01"))
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/repriced"))
+        .respond_with(invoice("0.02"))
+        .mount(&server)
+        .await;
+
+    let check = |path: &str| {
+        let output = Command::cargo_bin("x402-dev")
+            .unwrap()
+            .arg("check")
+            .arg(format!("{}{}", server.uri(), path))
+            .timeout(Duration::from_secs(10))
+            .output()
+            .unwrap();
+        (
+            output.status.success(),
+            String::from_utf8(output.stdout).unwrap(),
+        )
+    };
+
+    // HEAD answering like GET passes
+    let (success, stdout) = check("/parity");
+    assert!(success, "{}", stdout);
+    assert!(stdout.contains("✅ HEAD parity: PASS (402 with matching invoice, no body)"));
+
+    // A server that only answers GET fails the probe
+    let (success, stdout) = check("/get-only");
+    assert!(!success);
+    assert_eq!(failed_checks(&stdout), 1, "{}", stdout);
+    assert!(stdout.contains("HEAD returned 404, GET 402; HEAD response has no WWW-Authenticate"));
+    assert!(stdout.contains("Fix: Answer HEAD like GET"));
+
+    // So does a HEAD invoice with different terms
+    let (success, stdout) = check("/repriced");
+    assert!(!success);
+    assert!(stdout.contains("amount 0.02 (GET: 0.01)"), "{}", stdout);
+}
+
+#[tokio::test]
+async fn test_check_workflow_memo_prefix() {
+    let staging = MockServer::start().await;
+    mount_invoice(
+        &staging,
+        "0.01",
+        "staging-550e8400-e29b-41d4-a716-446655440000",
+        &[],
+    )
+    .await;
+    let check = |extra: &[&str]| {
+        let output = Command::cargo_bin("x402-dev")
+            .unwrap()
+            .arg("check")
+            .arg(staging.uri())
+            .args(extra)
+            .timeout(Duration::from_secs(10))
+            .output()
+            .unwrap();
+        (
+            output.status.success(),
+            String::from_utf8(output.stdout).unwrap(),
+        )
+    };
+
+    // The expected prefix passes lenient and strict checks
+    let (success, stdout) = check(&["--expected-memo-prefix", "staging-", "--strict"]);
+    assert!(success, "{}", stdout);
+    assert!(stdout.contains("✅ Memo format: staging-<id>"));
+
+    // Another environment's prefix is reported as a mismatch
+    let (success, stdout) = check(&["--expected-memo-prefix", "perf-"]);
+    assert!(!success);
+    assert_eq!(failed_checks(&stdout), 1, "{}", stdout);
+    assert!(stdout.contains(
+        "❌ Memo: staging-550e8400-e29b-41d4-a716-446655440000 does not start with 'perf-'"
+    ));
+
+    // Strict mode also rejects a memo without a UUID after the prefix
+    let short = MockServer::start().await;
+    mount_invoice(&short, "0.01", "req-short", &[]).await;
+    let output = Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(short.uri())
+        .args(["--expected-memo-prefix", "req-", "--strict"])
+        .timeout(Duration::from_secs(10))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!output.status.success());
+    assert_eq!(failed_checks(&stdout), 1, "{}", stdout);
+    assert!(stdout.contains("❌ Memo format: Invalid invoice memo"));
+
+    // Invalid prefixes are refused by the flag parser
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(short.uri())
+        .args(["--expected-memo-prefix", "perf"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must end with '-'"));
+}
+
+#[tokio::test]
+async fn test_check_workflow_resolve_override() {
+    // wiremock listens on 127.0.0.1; the check targets a host that has no DNS entry
+    let server = MockServer::start().await;
+    mount_invoice(&server, "0.01", UUID_MEMO, &[]).await;
+    let url = "http://fake.internal.example/api/data";
+    let entry = format!(
+        "fake.internal.example:{}:127.0.0.1",
+        server.address().port()
+    );
+
+    // With --resolve the host connects to the mock (port taken from the entry)
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(url)
+        .args(["--resolve", &entry])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Resolving: fake.internal.example → {} (--resolve {})",
+            server.address(),
+            entry
+        )))
+        .stdout(predicate::str::contains("ALL CHECKS PASSED"));
+
+    // Without it the lookup fails with the network exit code
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .arg("check")
+        .arg(url)
+        .timeout(Duration::from_secs(15))
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("Resolving:").not())
+        .stderr(predicate::str::contains(
+            "Could not resolve host of http://fake.internal.example/api/data",
+        ))
+        .stderr(predicate::str::contains("--resolve HOST:PORT:ADDR"));
+
+    // Malformed entries are refused by the flag parser
+    for (entry, expected) in [
+        ("fake.internal.example:127.0.0.1", "expected HOST:PORT:ADDR"),
+        ("fake.internal.example:80:[::1", "missing closing ']'"),
+    ] {
+        Command::cargo_bin("x402-dev")
+            .unwrap()
+            .arg("check")
+            .arg(url)
+            .args(["--resolve", entry])
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains(expected));
+    }
+}
+
+/// Number of failed check lines (the overall summary line excluded)
+fn failed_checks(stdout: &str) -> usize {
+    stdout
+        .lines()
+        .filter(|line| line.trim_start().starts_with("❌"))
+        .count()
+}
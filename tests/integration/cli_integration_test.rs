@@ -11,7 +11,7 @@ use std::net::TcpListener;
 use std::time::Duration;
 use tempfile::TempDir;
 use tokio::time::sleep;
-use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+use wiremock::{matchers::any, Mock, MockServer, ResponseTemplate};
 
 /// Helper function to get a free port for testing
 fn get_free_port() -> u16 {
@@ -28,9 +28,10 @@ async fn setup_mock_402_server() -> MockServer {
     let mock_server = MockServer::start().await;
 
     // Mock 402 response with valid x402-solana WWW-Authenticate header
-    Mock::given(method("GET"))
+    Mock::given(any())
         .respond_with(
             ResponseTemplate::new(402)
+                .insert_header("Cache-Control", "no-store")
                 .insert_header(
                     "WWW-Authenticate",
                     "x402-solana recipient=5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d amount=0.01 currency=USDC memo=req-test network=devnet"
@@ -355,3 +356,57 @@ fn test_config_show_command() {
         .success()
         .stdout(predicate::str::contains("Configuration").or(predicate::str::contains("port")));
 }
+
+#[tokio::test]
+async fn test_test_command_resolve_from_config() {
+    // Given: A suite against an unresolvable host and a project config
+    // mapping that host to the wiremock server
+    let mock_server = setup_mock_402_server().await;
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join(".x402dev.yaml"),
+        format!(
+            "resolve:\n  - fake.internal.example:{}:127.0.0.1\n",
+            mock_server.address().port()
+        ),
+    )
+    .unwrap();
+    let suite = r#"
+tests:
+  - name: "Resolved host"
+    url: "http://fake.internal.example/api/data"
+    expect:
+      status: 402
+"#;
+    fs::write(temp_dir.path().join("suite.yaml"), suite).unwrap();
+
+    // When/Then: The test reaches the mock and the override is reported
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["test", "suite.yaml"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Resolving: fake.internal.example → {}",
+            mock_server.address()
+        )));
+
+    // And: A malformed config entry is rejected instead of ignored
+    fs::write(
+        temp_dir.path().join(".x402dev.yaml"),
+        "resolve:\n  - fake.internal.example:127.0.0.1\n",
+    )
+    .unwrap();
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["test", "suite.yaml"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Failed to parse project config file",
+        ));
+}
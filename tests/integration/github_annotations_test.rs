@@ -0,0 +1,171 @@
+// GitHub Actions annotation tests
+//
+// `--format github` (or GITHUB_ACTIONS=true) keeps the human report and adds
+// `::error` / `::warning` workflow commands whose titles and messages are
+// escaped for the runner.
+
+use assert_cmd::Command;
+use std::fs;
+use std::time::Duration;
+use tempfile::TempDir;
+use wiremock::{matchers::any, Mock, MockServer, ResponseTemplate};
+
+fn x402_dev() -> Command {
+    let mut cmd = Command::cargo_bin("x402-dev").unwrap();
+    cmd.env_remove("GITHUB_ACTIONS")
+        .timeout(Duration::from_secs(30));
+    cmd
+}
+
+/// Lines of stdout that are workflow commands
+fn workflow_commands(stdout: &str) -> Vec<&str> {
+    stdout
+        .lines()
+        .filter(|line| line.starts_with("::"))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_failing_suite_annotations() {
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    let temp_dir = TempDir::new().unwrap();
+    let suite_path = temp_dir.path().join("suite.yaml");
+    fs::write(
+        &suite_path,
+        format!(
+            r#"
+tests:
+  - name: "api::data costs 50%"
+    url: "{uri}/api/data"
+    method: GET
+    expect:
+      status: 402
+  - name: "health"
+    url: "{uri}/health"
+    method: GET
+    expect:
+      status: 200
+"#,
+            uri = server.uri()
+        ),
+    )
+    .unwrap();
+
+    let output = x402_dev()
+        .arg("test")
+        .arg(&suite_path)
+        .args(["--format", "github"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        workflow_commands(&stdout),
+        [
+            format!("::group::{}", suite_path.display()).as_str(),
+            "::endgroup::",
+            format!(
+                "::error title=api%3A%3Adata costs 50%25::GET {}/api/data%0AStatus code is 402: expected 402, got 200",
+                server.uri()
+            )
+            .as_str(),
+        ],
+        "{}",
+        stdout
+    );
+    // The human summary is kept
+    assert!(stdout.contains("Test Suite Summary"));
+}
+
+#[tokio::test]
+async fn test_github_actions_env_enables_annotations() {
+    let server = MockServer::start().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+
+    // Detected from the environment
+    let output = x402_dev()
+        .env("GITHUB_ACTIONS", "true")
+        .arg("check")
+        .arg(server.uri())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("❌ HTTP 402 status code"));
+    assert_eq!(
+        workflow_commands(&stdout),
+        [
+            format!("::error title=HTTP 402 status code::{} returned 200", server.uri()).as_str(),
+            "::error title=Not cacheable::missing Cache-Control%0AFix: Send 'Cache-Control: no-store' on 402 responses so intermediaries never serve stale invoices",
+            format!(
+                "::error title=WWW-Authenticate header::{} sent no WWW-Authenticate invoice",
+                server.uri()
+            )
+            .as_str(),
+        ],
+        "{}",
+        stdout
+    );
+
+    // An explicit --format wins over the environment
+    let output = x402_dev()
+        .env("GITHUB_ACTIONS", "true")
+        .arg("check")
+        .arg(server.uri())
+        .args(["--format", "text"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(workflow_commands(&stdout).is_empty(), "{}", stdout);
+}
+
+#[test]
+fn test_policy_validation_error_annotation() {
+    let temp_dir = TempDir::new().unwrap();
+    let policy_path = temp_dir.path().join("conflict.yaml");
+    fs::write(
+        &policy_path,
+        r#"
+policies:
+  - type: allowlist
+    field: agent_id
+    values:
+      - "agent-test"
+  - type: denylist
+    field: agent_id
+    values:
+      - "agent-test"
+"#,
+    )
+    .unwrap();
+
+    let output = x402_dev()
+        .env("GITHUB_ACTIONS", "true")
+        .args(["policy", "validate"])
+        .arg(&policy_path)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("ERROR CONFLICT"));
+    assert_eq!(
+        workflow_commands(&stdout),
+        [format!(
+            "::error file={},title=Policy validation X402-P001::CONFLICT: agent_id in both allowlist and denylist%0AConflicting values: agent-test%0APolicy indices: #0, #1",
+            policy_path.display()
+        )
+        .as_str()],
+        "{}",
+        stdout
+    );
+}
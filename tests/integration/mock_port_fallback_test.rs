@@ -0,0 +1,128 @@
+// Mock Port Fallback Integration Tests
+//
+// Starts `x402-dev mock --port-fallback auto` with its port already taken
+// and checks every place that reports the port: the env file, /health, the
+// PID file and `mock status`. HOME points at a temp dir so the PID file is
+// isolated from a real mock server.
+
+use assert_cmd::cargo::CommandCargoExt;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::net::TcpListener;
+use std::process::{Child, Stdio};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::time::sleep;
+use x402_server::PidMetadata;
+
+/// Kills the background mock server when the test ends
+struct ServerGuard(Child);
+
+impl Drop for ServerGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// A free port whose successor is free too
+fn free_port_pair() -> u16 {
+    loop {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        if port < u16::MAX && TcpListener::bind(("127.0.0.1", port + 1)).is_ok() {
+            return port;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_mock_falls_back_to_next_port() {
+    // Given: The configured port is held by another listener
+    let home = TempDir::new().unwrap();
+    let port = free_port_pair();
+    let _occupied = TcpListener::bind(("127.0.0.1", port)).unwrap();
+    let env_file = home.path().join("mock.env");
+
+    // When: The mock starts with automatic port fallback
+    let child = std::process::Command::cargo_bin("x402-dev")
+        .unwrap()
+        .args([
+            "mock",
+            "--port",
+            &port.to_string(),
+            "--port-fallback",
+            "auto",
+        ])
+        .arg("--env-file")
+        .arg(&env_file)
+        .env("HOME", home.path())
+        .current_dir(home.path())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let server = ServerGuard(child);
+
+    let mut env = String::new();
+    for _ in 0..100 {
+        env = std::fs::read_to_string(&env_file).unwrap_or_default();
+        if !env.is_empty() {
+            break;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+
+    // Then: The env file exports the next port
+    let actual = port + 1;
+    assert_eq!(env, format!("X402_DEV_ACTUAL_PORT={}\n", actual));
+
+    // And: /health reports the same port and the server's PID
+    let health: serde_json::Value = reqwest::get(format!("http://127.0.0.1:{}/health", actual))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(health["status"], "ok");
+    assert_eq!(health["port"], actual);
+    assert_eq!(health["pid"], server.0.id());
+
+    // And: The PID file records it for `mock status` and MCP mock_status
+    let pid_file = home.path().join(".x402dev").join("mock-server.pid");
+    let metadata = PidMetadata::parse(&std::fs::read_to_string(pid_file).unwrap()).unwrap();
+    assert_eq!(metadata.pid, server.0.id());
+    assert_eq!(metadata.port, Some(actual));
+
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .args(["mock", "status"])
+        .env("HOME", home.path())
+        .current_dir(home.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("port: {}", actual)));
+}
+
+#[test]
+fn test_mock_port_in_use_fails_by_default() {
+    // Given: The configured port is held by another listener
+    let home = TempDir::new().unwrap();
+    let occupied = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = occupied.local_addr().unwrap().port();
+
+    // When/Then: The default strict mode exits with code 2
+    Command::cargo_bin("x402-dev")
+        .unwrap()
+        .args(["mock", "--port", &port.to_string()])
+        .env("HOME", home.path())
+        .current_dir(home.path())
+        .timeout(Duration::from_secs(30))
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("already in use"))
+        .stderr(predicate::str::contains("--port-fallback auto"));
+}
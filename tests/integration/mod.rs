@@ -8,7 +8,15 @@
 //! - `cli_integration_test` - General CLI command integration tests
 //! - `check_workflow_test` - Check command workflow scenarios
 //! - `doctor_workflow_test` - Doctor command workflow scenarios
+//! - `github_annotations_test` - GitHub Actions annotation output
+//! - `mock_port_fallback_test` - Mock server port fallback and port reporting
+
+// The x402-dev binary lives in the x402-cli crate, so `cargo_bin_cmd!` (which
+// relies on CARGO_BIN_EXE_*) is not available here.
+#![allow(deprecated)]
 
 mod check_workflow_test;
 mod cli_integration_test;
 mod doctor_workflow_test;
+mod github_annotations_test;
+mod mock_port_fallback_test;
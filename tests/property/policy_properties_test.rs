@@ -194,7 +194,7 @@ fn generate_code(policy: &PolicyConfig) -> String {
 fn evaluate_policy(policy: &PolicyConfig, request: &RequestContext) -> EvaluationResult {
     // Sort rules by priority (higher priority first)
     let mut sorted_rules = policy.rules.clone();
-    sorted_rules.sort_by(|a, b| b.priority.cmp(&a.priority));
+    sorted_rules.sort_by_key(|r| std::cmp::Reverse(r.priority));
 
     for rule in sorted_rules {
         if evaluate_condition(&rule.condition, request) {
@@ -0,0 +1,158 @@
+// Wire compatibility of x402-server's domain-typed config and invoices
+//
+// The shared config and invoice fixtures must load into x402-server's types
+// and serialize back to the same YAML/JSON, and values the domain types
+// reject must fail when the config or invoice is loaded.
+
+// Only the config and invoice fixtures are used here
+#[allow(dead_code, unused_imports)]
+#[path = "../fixtures/mod.rs"]
+mod fixtures;
+
+use fixtures::invoices::{
+    invoice_with_negative_amount, valid_invoice, INVALID_ADDRESSES, VALID_TEST_ADDRESSES,
+};
+use fixtures::*;
+use x402_domain::{Amount, ResourcePath};
+use x402_server::{Config, Invoice, InvoiceGenerator};
+
+const CONFIG_FIXTURES: [fn() -> &'static str; 6] = [
+    valid_config_yaml,
+    minimal_config_yaml,
+    full_config_yaml,
+    dev_environment_config,
+    test_environment_config,
+    prod_environment_config,
+];
+
+#[test]
+fn test_config_fixtures_round_trip() {
+    for fixture in CONFIG_FIXTURES {
+        let yaml = fixture();
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+
+        let written = serde_yaml::to_string(&config).unwrap();
+        let reloaded: Config = serde_yaml::from_str(&written).unwrap();
+        assert_eq!(reloaded, config, "{}", yaml);
+
+        // Port and prices stay plain numbers on the wire
+        let original: serde_yaml::Value = serde_yaml::from_str(yaml).unwrap();
+        let written: serde_yaml::Value = serde_yaml::from_str(&written).unwrap();
+        assert_eq!(written["port"], original["port"]);
+        if let Some(pricing) = original.get("pricing") {
+            assert_eq!(written["pricing"]["default"], pricing["default"]);
+        }
+    }
+
+    let config: Config = serde_yaml::from_str(full_config_yaml()).unwrap();
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.pricing.per_resource["/v1/*"], 0.03);
+}
+
+#[test]
+fn test_invalid_port_fails_at_load() {
+    for yaml in [
+        config_with_invalid_port().to_string(),
+        invalid_config_yaml("port_too_low"),
+    ] {
+        let err = serde_yaml::from_str::<Config>(&yaml).unwrap_err();
+        assert!(err.to_string().contains("Invalid port"), "{}", err);
+    }
+}
+
+#[test]
+fn test_invalid_recipient_fails_at_load() {
+    for address in INVALID_ADDRESSES {
+        let yaml = format!("{}recipient: \"{}\"\n", valid_config_yaml(), address);
+
+        let err = serde_yaml::from_str::<Config>(&yaml).unwrap_err();
+
+        assert!(
+            err.to_string().contains("Invalid Solana address"),
+            "{}: {}",
+            address,
+            err
+        );
+    }
+}
+
+#[test]
+fn test_configured_recipient_is_invoiced() {
+    let yaml = format!(
+        "{}recipient: {}\n",
+        valid_config_yaml(),
+        VALID_TEST_ADDRESSES[4]
+    );
+    let config: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert!(serde_yaml::to_string(&config)
+        .unwrap()
+        .contains(&format!("recipient: {}", VALID_TEST_ADDRESSES[4])));
+
+    let generator = InvoiceGenerator::from_config(&config).unwrap();
+    let path = ResourcePath::new("/api/data").unwrap();
+    for _ in 0..3 {
+        let invoice = generator.generate(Amount::zero(), path.clone(), 300);
+        assert_eq!(invoice.recipient.as_str(), VALID_TEST_ADDRESSES[4]);
+    }
+}
+
+#[test]
+fn test_invoice_fixtures_round_trip() {
+    for fixture in [
+        valid_invoice(),
+        devnet_invoice(0.01),
+        testnet_invoice(1.5),
+        mainnet_invoice(100.0),
+    ] {
+        let json = serde_json::to_value(&fixture).unwrap();
+
+        let invoice: Invoice = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&invoice).unwrap(), json);
+        assert_eq!(invoice.recipient.as_str(), fixture.recipient);
+        assert_eq!(invoice.amount.to_f64(), fixture.amount);
+    }
+}
+
+#[test]
+fn test_server_memo_and_path_round_trip() {
+    let generator = InvoiceGenerator::new();
+    let invoice = generator.generate(
+        Amount::from_decimal_str("0.01").unwrap(),
+        ResourcePath::new("/api/items;v=2").unwrap(),
+        300,
+    );
+
+    let json = serde_json::to_value(&invoice).unwrap();
+    assert!(json["memo"].as_str().unwrap().starts_with("req-"));
+    assert_eq!(json["amount"], serde_json::json!(0.01));
+    assert_eq!(json["resource_path"], "/api/items;v=2");
+
+    let reloaded: Invoice = serde_json::from_value(json).unwrap();
+    assert_eq!(reloaded.memo, invoice.memo);
+}
+
+#[test]
+fn test_invalid_invoice_fixtures_rejected() {
+    let cases = [
+        (
+            invoice_with_invalid_recipient("invalid_chars"),
+            "Invalid Solana address",
+        ),
+        (
+            invoice_with_malformed_memo("not_uuid"),
+            "Invalid invoice memo",
+        ),
+        (invoice_with_negative_amount(), "cannot be negative"),
+    ];
+
+    for (fixture, expected) in cases {
+        let err =
+            serde_json::from_value::<Invoice>(serde_json::to_value(&fixture).unwrap()).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+
+    let err = serde_json::from_str::<Invoice>(&invoice_missing_required_fields("no_recipient"))
+        .unwrap_err();
+    assert!(err.to_string().contains("recipient"));
+}